@@ -0,0 +1,98 @@
+use axum::extract::rejection::PathRejection;
+use axum::http::header::WWW_AUTHENTICATE;
+use axum::http::{HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use bonsaidb_core::schema::InvalidNameError;
+use serde_json::json;
+
+/// An error handling a request.
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    /// The request's credentials were rejected, or the session is not allowed
+    /// to connect.
+    #[error("unauthorized")]
+    Unauthorized,
+    /// [`BonsaiDbLayer`](crate::BonsaiDbLayer) has not been added to the
+    /// route handling this request.
+    #[error("BonsaiDbLayer has not been added to this route")]
+    MissingLayer,
+    /// The route does not contain the [`DATABASE_PARAMETER`](crate::DATABASE_PARAMETER)
+    /// parameter.
+    #[error("the route does not have a `database` parameter")]
+    MissingDatabaseParameter,
+    /// The requested resource was not found.
+    #[error("not found")]
+    NotFound,
+    /// The route's parameters could not be parsed.
+    #[error(transparent)]
+    Path(#[from] PathRejection),
+    /// A name was not valid.
+    #[error(transparent)]
+    InvalidName(#[from] InvalidNameError),
+    /// A value could not be converted to or from JSON.
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
+    /// A SQL query could not be parsed or planned.
+    #[cfg(feature = "sql")]
+    #[error(transparent)]
+    Sql(bonsaidb_core::sql::SqlError),
+    /// An error from BonsaiDb.
+    #[error(transparent)]
+    Core(#[from] bonsaidb_core::Error),
+}
+
+#[cfg(feature = "sql")]
+impl From<bonsaidb_core::sql::SqlError> for Error {
+    fn from(err: bonsaidb_core::sql::SqlError) -> Self {
+        match err {
+            bonsaidb_core::sql::SqlError::Core(err) => Self::Core(err),
+            other => Self::Sql(other),
+        }
+    }
+}
+
+impl From<pot::Error> for Error {
+    fn from(err: pot::Error) -> Self {
+        Self::Core(bonsaidb_core::Error::from(err))
+    }
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            Self::Unauthorized => {
+                let mut response = StatusCode::UNAUTHORIZED.into_response();
+                response.headers_mut().insert(
+                    WWW_AUTHENTICATE,
+                    HeaderValue::from_static("Basic realm=\"BonsaiDb\""),
+                );
+                return response;
+            }
+            Self::Path(rejection) => rejection.status(),
+            Self::MissingLayer | Self::MissingDatabaseParameter | Self::Json(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+            Self::NotFound
+            | Self::Core(
+                bonsaidb_core::Error::DatabaseNotFound(_)
+                | bonsaidb_core::Error::CollectionNotFound
+                | bonsaidb_core::Error::ViewNotFound
+                | bonsaidb_core::Error::DocumentNotFound(..),
+            ) => StatusCode::NOT_FOUND,
+            Self::InvalidName(_) | Self::Core(bonsaidb_core::Error::InvalidName(_)) => {
+                StatusCode::BAD_REQUEST
+            }
+            #[cfg(feature = "sql")]
+            Self::Sql(_) => StatusCode::BAD_REQUEST,
+            Self::Core(bonsaidb_core::Error::PermissionDenied(_)) => StatusCode::FORBIDDEN,
+            Self::Core(bonsaidb_core::Error::DocumentConflict(..)) => StatusCode::CONFLICT,
+            Self::Core(
+                bonsaidb_core::Error::RateLimited { .. }
+                | bonsaidb_core::Error::AccountLocked { .. },
+            ) => StatusCode::TOO_MANY_REQUESTS,
+            Self::Core(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, Json(json!({ "error": self.to_string() }))).into_response()
+    }
+}
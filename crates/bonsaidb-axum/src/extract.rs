@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+use std::ops::Deref;
+
+use async_trait::async_trait;
+use axum::extract::{FromRequestParts, Path};
+use axum::http::request::Parts;
+use bonsaidb_server::{Backend, CustomServer, NoBackend, ServerDatabase};
+use derive_where::derive_where;
+
+use crate::Error;
+
+/// The name of the route parameter [`Database`] uses to determine which
+/// database to open.
+pub const DATABASE_PARAMETER: &str = "database";
+
+/// An extractor providing a [`CustomServer`] limited to the permissions of the
+/// request's session. Requires [`BonsaiDbLayer`](crate::BonsaiDbLayer).
+#[derive_where(Clone, Debug)]
+pub struct Storage<B: Backend = NoBackend>(pub CustomServer<B>);
+
+impl<B: Backend> Deref for Storage<B> {
+    type Target = CustomServer<B>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[async_trait]
+impl<S, B> FromRequestParts<S> for Storage<B>
+where
+    S: Send + Sync,
+    B: Backend,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Self>()
+            .cloned()
+            .ok_or(Error::MissingLayer)
+    }
+}
+
+/// An extractor providing the database named by the route's
+/// [`DATABASE_PARAMETER`] parameter. The database is opened for each request
+/// and is limited to the permissions of the request's session. Requires
+/// [`BonsaiDbLayer`](crate::BonsaiDbLayer).
+///
+/// ```rust
+/// # use axum::routing::get;
+/// # use axum::Router;
+/// # use bonsaidb_axum::Database;
+/// # use bonsaidb_core::connection::AsyncConnection;
+/// async fn last_transaction(database: Database) -> String {
+///     format!("{:?}", database.last_transaction_id().await.unwrap())
+/// }
+///
+/// # fn app() -> Router {
+/// Router::new().route("/:database/last-transaction", get(last_transaction))
+/// # }
+/// ```
+#[derive_where(Clone, Debug)]
+pub struct Database<B: Backend = NoBackend>(pub ServerDatabase<B>);
+
+impl<B: Backend> Deref for Database<B> {
+    type Target = ServerDatabase<B>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[async_trait]
+impl<S, B> FromRequestParts<S> for Database<B>
+where
+    S: Send + Sync,
+    B: Backend,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let storage = Storage::<B>::from_request_parts(parts, state).await?;
+        let Path(parameters) =
+            Path::<HashMap<String, String>>::from_request_parts(parts, state).await?;
+        let name = parameters
+            .get(DATABASE_PARAMETER)
+            .ok_or(Error::MissingDatabaseParameter)?;
+        let database = storage.database_without_schema(name).await?;
+        Ok(Self(database))
+    }
+}
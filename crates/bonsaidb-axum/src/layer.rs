@@ -0,0 +1,144 @@
+use std::net::{IpAddr, SocketAddr};
+use std::task::{Context, Poll};
+
+use axum::extract::ConnectInfo;
+use axum::http::header::AUTHORIZATION;
+use axum::http::{HeaderValue, Request};
+use axum::response::{IntoResponse, Response};
+use bonsaidb_core::connection::Session;
+use bonsaidb_core::permissions::bonsai::{bonsaidb_resource_name, BonsaiAction, ServerAction};
+use bonsaidb_server::{Backend, CustomServer, NoBackend};
+use derive_where::derive_where;
+use futures::future::BoxFuture;
+use tower::{Layer, Service};
+
+use crate::{Error, Storage};
+
+/// A [`Layer`] that authenticates each request and makes the
+/// [`Storage`](crate::Storage) and [`Database`](crate::Database) extractors
+/// available to the handlers it wraps.
+///
+/// Requests without an `Authorization` header are given the server's default
+/// permissions. With feature `password-hashing`, requests may authenticate as
+/// a user using HTTP Basic authentication. Each request is authenticated
+/// independently, so no session is retained between requests. Requests whose
+/// session is not allowed to connect to the server are rejected.
+#[derive_where(Clone, Debug)]
+pub struct BonsaiDbLayer<B: Backend = NoBackend> {
+    server: CustomServer<B>,
+}
+
+impl<B: Backend> BonsaiDbLayer<B> {
+    /// Returns a layer that authenticates requests against `server`.
+    #[must_use]
+    pub const fn new(server: CustomServer<B>) -> Self {
+        Self { server }
+    }
+}
+
+impl<S, B: Backend> Layer<S> for BonsaiDbLayer<B> {
+    type Service = BonsaiDbService<S, B>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        BonsaiDbService {
+            inner,
+            server: self.server.clone(),
+        }
+    }
+}
+
+/// The [`Service`] created by [`BonsaiDbLayer`].
+#[derive_where(Clone; S)]
+#[derive_where(Debug; S: std::fmt::Debug)]
+pub struct BonsaiDbService<S, B: Backend = NoBackend> {
+    inner: S,
+    server: CustomServer<B>,
+}
+
+impl<S, B, ReqBody> Service<Request<ReqBody>> for BonsaiDbService<S, B>
+where
+    S: Service<Request<ReqBody>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send,
+    B: Backend,
+    ReqBody: Send + 'static,
+{
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+    type Response = Response;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<ReqBody>) -> Self::Future {
+        // The service that was polled for readiness must handle this request,
+        // so it is swapped with a fresh clone.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let server = self.server.clone();
+        let authorization = request.headers().get(AUTHORIZATION).cloned();
+        let address = request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(address)| address.ip());
+        Box::pin(async move {
+            match authenticate(&server, authorization.as_ref(), address).await {
+                Ok(storage) => {
+                    request.extensions_mut().insert(Storage(storage));
+                    inner.call(request).await
+                }
+                Err(err) => Ok(err.into_response()),
+            }
+        })
+    }
+}
+
+/// Returns `server` limited to the permissions of the session described by
+/// `authorization`.
+async fn authenticate<B: Backend>(
+    server: &CustomServer<B>,
+    authorization: Option<&HeaderValue>,
+    address: Option<IpAddr>,
+) -> Result<CustomServer<B>, Error> {
+    let session = match authorization {
+        Some(authorization) => {
+            let unauthenticated = server.assume_session(server.default_session().clone())?;
+            authenticate_with_header(&unauthenticated, authorization, address).await?
+        }
+        None => server.default_session().clone(),
+    };
+    if !session.allowed_to(
+        bonsaidb_resource_name(),
+        &BonsaiAction::Server(ServerAction::Connect),
+    ) {
+        return Err(Error::Unauthorized);
+    }
+
+    Ok(server.assume_session(session)?)
+}
+
+#[cfg(feature = "password-hashing")]
+async fn authenticate_with_header<B: Backend>(
+    server: &CustomServer<B>,
+    authorization: &HeaderValue,
+    address: Option<IpAddr>,
+) -> Result<Session, Error> {
+    server
+        .authenticate_basic_authorization(authorization.as_bytes(), address)
+        .await
+        .map_err(|err| match err {
+            bonsaidb_core::Error::RateLimited { .. }
+            | bonsaidb_core::Error::AccountLocked { .. } => Error::Core(err),
+            _ => Error::Unauthorized,
+        })
+}
+
+#[cfg(not(feature = "password-hashing"))]
+#[allow(clippy::unused_async)]
+async fn authenticate_with_header<B: Backend>(
+    _server: &CustomServer<B>,
+    _authorization: &HeaderValue,
+    _address: Option<IpAddr>,
+) -> Result<Session, Error> {
+    Err(Error::Unauthorized)
+}
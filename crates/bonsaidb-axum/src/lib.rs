@@ -0,0 +1,63 @@
+//! Integrates a BonsaiDb server with [`axum`] and other [`tower`]-based
+//! applications.
+//!
+//! [`BonsaiDbLayer`] authenticates each request and makes the [`Storage`] and
+//! [`Database`] extractors available to handlers:
+//!
+//! ```rust
+//! # use axum::routing::get;
+//! # use axum::Router;
+//! # use bonsaidb_axum::{BonsaiDbLayer, Database, Storage};
+//! # use bonsaidb_core::connection::AsyncStorageConnection;
+//! # use bonsaidb_core::keyvalue::AsyncKeyValue;
+//! # use bonsaidb_server::Server;
+//! async fn database_count(storage: Storage) -> String {
+//!     let databases = storage.list_databases().await.unwrap();
+//!     databases.len().to_string()
+//! }
+//!
+//! async fn visits(database: Database) -> String {
+//!     let visits = database.increment_key_by("visits", 1_u64).await.unwrap();
+//!     visits.to_string()
+//! }
+//!
+//! fn app(server: Server) -> Router {
+//!     Router::new()
+//!         .route("/databases", get(database_count))
+//!         .route("/:database/visits", get(visits))
+//!         .layer(BonsaiDbLayer::new(server))
+//! }
+//! ```
+//!
+//! [`RestLayer`] exposes BonsaiDb's [REST API](rest) beneath a route prefix,
+//! and [`WebSocketService`] allows BonsaiDb clients to connect through the
+//! application's router. [`RouterService`] serves a [`Router`](axum::Router)
+//! using [`CustomServer::listen_for_tcp_on()`](bonsaidb_server::CustomServer::listen_for_tcp_on).
+
+#![forbid(unsafe_code)]
+#![warn(
+    clippy::cargo,
+    missing_docs,
+    // clippy::missing_docs_in_private_items,
+    clippy::pedantic,
+    future_incompatible,
+    rust_2018_idioms,
+)]
+#![allow(
+    clippy::missing_errors_doc, // TODO clippy::missing_errors_doc
+    clippy::module_name_repetitions,
+)]
+
+mod error;
+mod extract;
+mod layer;
+pub mod rest;
+mod service;
+
+pub use self::error::Error;
+pub use self::extract::{Database, Storage, DATABASE_PARAMETER};
+pub use self::layer::{BonsaiDbLayer, BonsaiDbService};
+pub use self::rest::{RestLayer, RestService};
+pub use self::service::RouterService;
+#[cfg(feature = "websockets")]
+pub use self::service::WebSocketService;
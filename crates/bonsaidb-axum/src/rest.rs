@@ -0,0 +1,444 @@
+//! A REST API for accessing BonsaiDb over HTTP.
+//!
+//! All request and response bodies are JSON. Document contents and key-value
+//! values are converted between JSON and
+//! [`Pot`](https://github.com/khonsulabs/pot), the format used by
+//! `SerializedCollection`'s default serialization. Collections, views, and
+//! documents are identified using their encoded names and ids.
+//!
+//! | Method   | Path                                                    | Description                    |
+//! |----------|---------------------------------------------------------|--------------------------------|
+//! | `GET`    | `/databases`                                            | Lists the databases.           |
+//! | `GET`    | `/databases/:database/collections/:collection/documents` | Lists documents in id order. Accepts `after` and `limit` query parameters. |
+//! | `POST`   | `/databases/:database/collections/:collection/documents` | Inserts a document with a generated id. |
+//! | `GET`    | `/databases/:database/collections/:collection/documents/:id` | Returns a document.   |
+//! | `PUT`    | `/databases/:database/collections/:collection/documents/:id` | Inserts or overwrites a document. |
+//! | `DELETE` | `/databases/:database/collections/:collection/documents/:id` | Deletes a document.   |
+//! | `GET`    | `/databases/:database/collections/:collection/views/:view` | Lists a view's mappings. Accepts a `limit` query parameter. |
+//! | `GET`    | `/databases/:database/kv/:key`                          | Returns a key's value.         |
+//! | `PUT`    | `/databases/:database/kv/:key`                          | Sets a key's value.            |
+//! | `DELETE` | `/databases/:database/kv/:key`                          | Deletes a key.                 |
+//! | `GET`    | `/databases/:database/sql`                              | Executes the read-only SQL query in the `query` query parameter. Requires the `sql` feature. |
+//!
+//! Requests are subject to the permissions of the session established by
+//! [`BonsaiDbLayer`].
+
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use axum::body::Body;
+use axum::extract::{Path, Query};
+use axum::http::{Request, StatusCode, Uri};
+use axum::response::Response;
+use axum::routing::get;
+use axum::{Json, Router};
+use bonsaidb_core::connection::{
+    AccessPolicy, AsyncLowLevelConnection, AsyncStorageConnection, Bound, Range, Sort,
+    ViewReadOptions,
+};
+use bonsaidb_core::document::{DocumentId, Header, OwnedDocument};
+use bonsaidb_core::keyvalue::{AsyncKeyValue, KeyStatus, Numeric, Value as KeyValue};
+use bonsaidb_core::schema::{CollectionName, Name, Qualified, ViewName};
+use bonsaidb_core::transaction::{OperationResult, Transaction};
+use bonsaidb_server::{Backend, CustomServer};
+use futures::future::BoxFuture;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tower::{Layer, Service, ServiceExt};
+
+use crate::{BonsaiDbLayer, Database, Error, Storage};
+
+const DEFAULT_LIMIT: u32 = 100;
+const MAX_LIMIT: u32 = 1_000;
+
+/// Returns a [`Router`] serving the REST API. The router must be wrapped by
+/// [`BonsaiDbLayer`]. To serve the API beneath a route prefix of an existing
+/// application, use [`RestLayer`] instead.
+#[must_use]
+pub fn router<B: Backend>() -> Router {
+    let router = Router::new()
+        .route("/databases", get(list_databases::<B>))
+        .route(
+            "/databases/:database/collections/:collection/documents",
+            get(list_documents::<B>).post(insert_document::<B>),
+        )
+        .route(
+            "/databases/:database/collections/:collection/documents/:id",
+            get(get_document::<B>)
+                .put(overwrite_document::<B>)
+                .delete(delete_document::<B>),
+        )
+        .route(
+            "/databases/:database/collections/:collection/views/:view",
+            get(query_view::<B>),
+        )
+        .route(
+            "/databases/:database/kv/:key",
+            get(get_key::<B>).put(set_key::<B>).delete(delete_key::<B>),
+        );
+    #[cfg(feature = "sql")]
+    let router = router.route("/databases/:database/sql", get(query_sql::<B>));
+    router
+}
+
+/// A [`Layer`] that serves the REST API for requests whose path begins with a
+/// prefix, and forwards all other requests to the service it wraps.
+///
+/// ```rust
+/// # use axum::routing::get;
+/// # use axum::Router;
+/// # use bonsaidb_axum::RestLayer;
+/// # use bonsaidb_server::Server;
+/// fn app(server: Server) -> Router {
+///     Router::new()
+///         .route("/", get(|| async { "Hello, world!" }))
+///         .layer(RestLayer::new(server, "/bonsaidb"))
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct RestLayer {
+    prefix: Arc<str>,
+    rest: Router,
+}
+
+impl RestLayer {
+    /// Returns a layer serving the REST API for `server` beneath `prefix`.
+    /// Requests are authenticated using [`BonsaiDbLayer`].
+    #[must_use]
+    pub fn new<B: Backend>(server: CustomServer<B>, prefix: impl AsRef<str>) -> Self {
+        Self {
+            prefix: Arc::from(prefix.as_ref().trim_end_matches('/')),
+            rest: router::<B>().layer(BonsaiDbLayer::new(server)),
+        }
+    }
+}
+
+impl<S> Layer<S> for RestLayer {
+    type Service = RestService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RestService {
+            inner,
+            prefix: self.prefix.clone(),
+            rest: self.rest.clone(),
+        }
+    }
+}
+
+/// The [`Service`] created by [`RestLayer`].
+#[derive(Clone, Debug)]
+pub struct RestService<S> {
+    inner: S,
+    prefix: Arc<str>,
+    rest: Router,
+}
+
+impl<S> RestService<S> {
+    /// Removes the prefix from `request`'s path, returning false if the path
+    /// is not beneath the prefix.
+    fn strip_prefix(&self, request: &mut Request<Body>) -> bool {
+        let Some(path) = request.uri().path().strip_prefix(&*self.prefix) else {
+            return false;
+        };
+        if !path.is_empty() && !path.starts_with('/') {
+            return false;
+        }
+
+        let path = if path.is_empty() { "/" } else { path };
+        let path_and_query = match request.uri().query() {
+            Some(query) => format!("{path}?{query}"),
+            None => path.to_string(),
+        };
+        let mut parts = request.uri().clone().into_parts();
+        parts.path_and_query = path_and_query.parse().ok();
+        match Uri::from_parts(parts) {
+            Ok(uri) => {
+                *request.uri_mut() = uri;
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+impl<S> Service<Request<Body>> for RestService<S>
+where
+    S: Service<Request<Body>, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Error = Infallible;
+    type Future = BoxFuture<'static, Result<Response, Infallible>>;
+    type Response = Response;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<Body>) -> Self::Future {
+        if self.strip_prefix(&mut request) {
+            Box::pin(self.rest.clone().oneshot(request))
+        } else {
+            // The service that was polled for readiness must handle this
+            // request, so it is swapped with a fresh clone.
+            let clone = self.inner.clone();
+            let mut inner = std::mem::replace(&mut self.inner, clone);
+            Box::pin(async move { inner.call(request).await })
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct CollectionPath {
+    collection: String,
+}
+
+#[derive(Deserialize)]
+struct DocumentPath {
+    collection: String,
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct ViewPath {
+    collection: String,
+    view: String,
+}
+
+#[derive(Deserialize)]
+struct KeyPath {
+    key: String,
+}
+
+#[cfg(feature = "sql")]
+#[derive(Deserialize)]
+struct SqlOptions {
+    query: String,
+}
+
+#[derive(Deserialize)]
+struct ListOptions {
+    after: Option<String>,
+    limit: Option<u32>,
+}
+
+impl ListOptions {
+    fn limit(&self) -> u32 {
+        self.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT)
+    }
+}
+
+async fn list_databases<B: Backend>(storage: Storage<B>) -> Result<Json<Value>, Error> {
+    let databases = storage.list_databases().await?;
+    Ok(Json(Value::Array(
+        databases
+            .into_iter()
+            .map(|database| {
+                json!({
+                    "name": database.name,
+                    "schema": database.schema.to_string(),
+                })
+            })
+            .collect(),
+    )))
+}
+
+async fn list_documents<B: Backend>(
+    database: Database<B>,
+    Path(path): Path<CollectionPath>,
+    Query(options): Query<ListOptions>,
+) -> Result<Json<Value>, Error> {
+    let collection = CollectionName::parse_encoded(&path.collection)?;
+    let start = match &options.after {
+        Some(after) => Bound::Excluded(after.parse::<DocumentId>()?),
+        None => Bound::Unbounded,
+    };
+    let documents = database
+        .list_from_collection(
+            Range {
+                start,
+                end: Bound::Unbounded,
+            },
+            Sort::Ascending,
+            Some(options.limit()),
+            &collection,
+        )
+        .await?;
+    let documents = documents
+        .iter()
+        .map(document_json)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Json(Value::Array(documents)))
+}
+
+async fn insert_document<B: Backend>(
+    database: Database<B>,
+    Path(path): Path<CollectionPath>,
+    Json(contents): Json<Value>,
+) -> Result<(StatusCode, Json<Value>), Error> {
+    let collection = CollectionName::parse_encoded(&path.collection)?;
+    let results = database
+        .apply_transaction(Transaction::insert(
+            collection,
+            None,
+            pot::to_vec(&contents)?,
+        ))
+        .await?;
+    let header = updated_header(results)?;
+    Ok((StatusCode::CREATED, Json(header_json(&header))))
+}
+
+async fn get_document<B: Backend>(
+    database: Database<B>,
+    Path(path): Path<DocumentPath>,
+) -> Result<Json<Value>, Error> {
+    let collection = CollectionName::parse_encoded(&path.collection)?;
+    let document = database
+        .get_from_collection(path.id.parse()?, &collection)
+        .await?
+        .ok_or(Error::NotFound)?;
+    Ok(Json(document_json(&document)?))
+}
+
+async fn overwrite_document<B: Backend>(
+    database: Database<B>,
+    Path(path): Path<DocumentPath>,
+    Json(contents): Json<Value>,
+) -> Result<Json<Value>, Error> {
+    let collection = CollectionName::parse_encoded(&path.collection)?;
+    let results = database
+        .apply_transaction(Transaction::overwrite(
+            collection,
+            path.id.parse()?,
+            pot::to_vec(&contents)?,
+        ))
+        .await?;
+    let header = updated_header(results)?;
+    Ok(Json(header_json(&header)))
+}
+
+async fn delete_document<B: Backend>(
+    database: Database<B>,
+    Path(path): Path<DocumentPath>,
+) -> Result<StatusCode, Error> {
+    let collection = CollectionName::parse_encoded(&path.collection)?;
+    let document = database
+        .get_from_collection(path.id.parse()?, &collection)
+        .await?
+        .ok_or(Error::NotFound)?;
+    database
+        .apply_transaction(Transaction::delete(collection, document.header))
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn query_view<B: Backend>(
+    database: Database<B>,
+    Path(path): Path<ViewPath>,
+    Query(options): Query<ListOptions>,
+) -> Result<Json<Value>, Error> {
+    let view = ViewName {
+        collection: CollectionName::parse_encoded(&path.collection)?,
+        name: Name::parse_encoded(&path.view)?,
+    };
+    let mappings = database
+        .query_by_name(
+            &view,
+            None,
+            Sort::Ascending,
+            Some(options.limit()),
+            AccessPolicy::UpdateBefore,
+            ViewReadOptions::default(),
+        )
+        .await?;
+    let mappings = mappings
+        .into_iter()
+        .map(|mapping| {
+            Ok(json!({
+                "source": mapping.source.id.to_string(),
+                "key": key_json(&mapping.key),
+                "value": pot_json(&mapping.value)?,
+            }))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    Ok(Json(Value::Array(mappings)))
+}
+
+#[cfg(feature = "sql")]
+async fn query_sql<B: Backend>(
+    database: Database<B>,
+    Query(options): Query<SqlOptions>,
+) -> Result<Json<bonsaidb_core::sql::SqlResults>, Error> {
+    Ok(Json(database.query_sql(&options.query).await?))
+}
+
+async fn get_key<B: Backend>(
+    database: Database<B>,
+    Path(path): Path<KeyPath>,
+) -> Result<Json<Value>, Error> {
+    match database.get_key(path.key).await?.ok_or(Error::NotFound)? {
+        KeyValue::Bytes(bytes) => Ok(Json(pot_json(&bytes)?)),
+        KeyValue::Numeric(Numeric::Integer(value)) => Ok(Json(json!(value))),
+        KeyValue::Numeric(Numeric::UnsignedInteger(value)) => Ok(Json(json!(value))),
+        KeyValue::Numeric(Numeric::Float(value)) => Ok(Json(json!(value))),
+    }
+}
+
+async fn set_key<B: Backend>(
+    database: Database<B>,
+    Path(path): Path<KeyPath>,
+    Json(value): Json<Value>,
+) -> Result<StatusCode, Error> {
+    match database.set_key(path.key, &value).await? {
+        KeyStatus::Inserted => Ok(StatusCode::CREATED),
+        _ => Ok(StatusCode::NO_CONTENT),
+    }
+}
+
+async fn delete_key<B: Backend>(
+    database: Database<B>,
+    Path(path): Path<KeyPath>,
+) -> Result<StatusCode, Error> {
+    match database.delete_key(path.key).await? {
+        KeyStatus::Deleted => Ok(StatusCode::NO_CONTENT),
+        _ => Err(Error::NotFound),
+    }
+}
+
+fn updated_header(results: Vec<OperationResult>) -> Result<Header, Error> {
+    results
+        .into_iter()
+        .find_map(|result| match result {
+            OperationResult::DocumentUpdated { header, .. } => Some(header),
+            _ => None,
+        })
+        .ok_or(Error::NotFound)
+}
+
+fn header_json(header: &Header) -> Value {
+    json!({
+        "id": header.id.to_string(),
+        "revision": header.revision.id,
+    })
+}
+
+fn document_json(document: &OwnedDocument) -> Result<Value, Error> {
+    let mut value = header_json(&document.header);
+    value["contents"] = pot_json(&document.contents)?;
+    Ok(value)
+}
+
+/// Converts `bytes` serialized using `Pot` into JSON.
+fn pot_json(bytes: &[u8]) -> Result<Value, Error> {
+    let value = pot::from_slice::<pot::Value<'_>>(bytes)?;
+    Ok(serde_json::to_value(value)?)
+}
+
+/// Converts a view's key into JSON. Keys that are valid UTF-8 are returned as
+/// strings, and all other keys are returned as an array of bytes.
+fn key_json(bytes: &[u8]) -> Value {
+    match std::str::from_utf8(bytes) {
+        Ok(key) => Value::String(key.to_string()),
+        Err(_) => json!(bytes),
+    }
+}
@@ -0,0 +1,125 @@
+use async_trait::async_trait;
+use axum::extract::ConnectInfo;
+use axum::{Extension, Router};
+use bonsaidb_server::{HttpService, Peer};
+use hyper::server::conn::Http;
+
+/// An [`HttpService`] that serves a [`Router`] on connections accepted by
+/// [`CustomServer::listen_for_tcp_on()`](bonsaidb_server::CustomServer::listen_for_tcp_on).
+///
+/// The peer's address is provided to handlers as
+/// [`ConnectInfo<SocketAddr>`](ConnectInfo), which [`WebSocketService`]
+/// requires.
+#[derive(Clone, Debug)]
+pub struct RouterService {
+    router: Router,
+}
+
+impl RouterService {
+    /// Returns a service serving `router`.
+    #[must_use]
+    pub const fn new(router: Router) -> Self {
+        Self { router }
+    }
+}
+
+#[async_trait]
+impl HttpService for RouterService {
+    async fn handle_connection<
+        S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send + 'static,
+    >(
+        &self,
+        connection: S,
+        peer: &Peer,
+    ) -> Result<(), S> {
+        let router = self
+            .router
+            .clone()
+            .layer(Extension(ConnectInfo(peer.address)));
+        if let Err(err) = Http::new()
+            .serve_connection(connection, router)
+            .with_upgrades()
+            .await
+        {
+            log::error!("[http] error serving {}: {:?}", peer.address, err);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "websockets")]
+pub use self::websockets::WebSocketService;
+
+#[cfg(feature = "websockets")]
+mod websockets {
+    use std::convert::Infallible;
+    use std::net::SocketAddr;
+    use std::task::{Context, Poll};
+
+    use axum::extract::ConnectInfo;
+    use axum::http::{Request, StatusCode};
+    use axum::response::{IntoResponse, Response};
+    use bonsaidb_server::{Backend, CustomServer, NoBackend};
+    use derive_where::derive_where;
+    use futures::future::BoxFuture;
+    use hyper::Body;
+    use tower::Service;
+
+    /// A [`Service`] that upgrades requests to BonsaiDb's `WebSocket`
+    /// protocol, allowing BonsaiDb clients to connect through an application's
+    /// router. Requires feature `websockets`.
+    ///
+    /// The peer's address is read from [`ConnectInfo<SocketAddr>`](ConnectInfo),
+    /// which [`RouterService`](crate::RouterService) and
+    /// `Router::into_make_service_with_connect_info()` provide.
+    ///
+    /// ```rust
+    /// # use axum::Router;
+    /// # use bonsaidb_axum::WebSocketService;
+    /// # use bonsaidb_server::Server;
+    /// fn app(server: Server) -> Router {
+    ///     Router::new().route_service("/ws", WebSocketService::new(server))
+    /// }
+    /// ```
+    #[derive_where(Clone, Debug)]
+    pub struct WebSocketService<B: Backend = NoBackend> {
+        server: CustomServer<B>,
+    }
+
+    impl<B: Backend> WebSocketService<B> {
+        /// Returns a service accepting `WebSocket` connections for `server`.
+        #[must_use]
+        pub const fn new(server: CustomServer<B>) -> Self {
+            Self { server }
+        }
+    }
+
+    impl<B: Backend> Service<Request<Body>> for WebSocketService<B> {
+        type Error = Infallible;
+        type Future = BoxFuture<'static, Result<Response, Infallible>>;
+        type Response = Response;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: Request<Body>) -> Self::Future {
+            let server = self.server.clone();
+            Box::pin(async move {
+                let Some(ConnectInfo(peer_address)) = request
+                    .extensions()
+                    .get::<ConnectInfo<SocketAddr>>()
+                    .copied()
+                else {
+                    log::error!("[http] ConnectInfo<SocketAddr> is required to accept WebSockets");
+                    return Ok(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+                };
+                Ok(server
+                    .upgrade_websocket(peer_address, request)
+                    .await
+                    .into_response())
+            })
+        }
+    }
+}
@@ -0,0 +1,245 @@
+//! Tests the REST API and extractors.
+
+use axum::body::Body;
+use axum::http::{Method, Request, StatusCode};
+use axum::routing::get;
+use axum::Router;
+use bonsaidb_axum::{BonsaiDbLayer, Database, RestLayer, Storage};
+use bonsaidb_core::connection::AsyncStorageConnection;
+use bonsaidb_core::keyvalue::AsyncKeyValue;
+use bonsaidb_core::schema::{Collection, Qualified, SerializedCollection};
+use bonsaidb_core::test_util::{Basic, BasicSchema, TestDirectory};
+use bonsaidb_server::local::config::Builder;
+use bonsaidb_server::{DefaultPermissions, Server, ServerConfiguration};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+async fn send(
+    app: &Router,
+    method: Method,
+    uri: &str,
+    body: Option<Value>,
+) -> anyhow::Result<(StatusCode, Value)> {
+    let body = match body {
+        Some(body) => Body::from(serde_json::to_vec(&body)?),
+        None => Body::empty(),
+    };
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(body)?;
+    let response = app.clone().oneshot(request).await?;
+    let status = response.status();
+    let bytes = hyper::body::to_bytes(response.into_body()).await?;
+    let value = if bytes.is_empty() {
+        Value::Null
+    } else {
+        serde_json::from_slice(&bytes)?
+    };
+    Ok((status, value))
+}
+
+async fn database_count(storage: Storage) -> String {
+    storage.list_databases().await.unwrap().len().to_string()
+}
+
+async fn visits(database: Database) -> String {
+    database
+        .increment_key_by("visits", 1_u64)
+        .await
+        .unwrap()
+        .to_string()
+}
+
+#[tokio::test]
+async fn rest_api() -> anyhow::Result<()> {
+    let dir = TestDirectory::new("axum-rest-api.bonsaidb");
+    let server = Server::open(
+        ServerConfiguration::new(&dir)
+            .default_permissions(DefaultPermissions::AllowAll)
+            .with_schema::<BasicSchema>()?,
+    )
+    .await?;
+    server
+        .create_database::<BasicSchema>("tests", false)
+        .await?;
+
+    let app = Router::new()
+        .route("/", get(|| async { "hello" }))
+        .layer(RestLayer::new(server.clone(), "/bonsaidb"));
+
+    // Requests outside of the prefix reach the application's routes.
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri("/").body(Body::empty())?)
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let (status, databases) = send(&app, Method::GET, "/bonsaidb/databases", None).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert!(databases
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|database| database["name"] == "tests"));
+
+    let documents = format!(
+        "/bonsaidb/databases/tests/collections/{}/documents",
+        Basic::collection_name().encoded()
+    );
+    let (status, header) = send(
+        &app,
+        Method::POST,
+        &documents,
+        Some(json!({
+            "value": "hello",
+            "category": null,
+            "parent_id": null,
+            "tags": [],
+        })),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    let id = header["id"].as_str().unwrap().to_string();
+
+    // The document is readable using the collection's type.
+    let database = server.database::<BasicSchema>("tests").await?;
+    let stored = Basic::all_async(&database).await?;
+    assert_eq!(stored.len(), 1);
+    assert_eq!(stored[0].contents.value, "hello");
+
+    let document_path = format!("{documents}/{id}");
+    let (status, document) = send(&app, Method::GET, &document_path, None).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(document["contents"]["value"], "hello");
+
+    let (status, listed) = send(&app, Method::GET, &documents, None).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(listed.as_array().unwrap().len(), 1);
+
+    let (status, _) = send(&app, Method::DELETE, &document_path, None).await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send(&app, Method::GET, &document_path, None).await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let key = "/bonsaidb/databases/tests/kv/greeting";
+    let (status, _) = send(&app, Method::PUT, key, Some(json!("hi"))).await?;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, value) = send(&app, Method::GET, key, None).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(value, "hi");
+    let (status, _) = send(&app, Method::DELETE, key, None).await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _) = send(&app, Method::GET, "/bonsaidb/databases/missing/kv/a", None).await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[cfg(feature = "sql")]
+#[tokio::test]
+async fn sql_queries() -> anyhow::Result<()> {
+    use bonsaidb_core::test_util::BasicByParentId;
+
+    let dir = TestDirectory::new("axum-sql-queries.bonsaidb");
+    let server = Server::open(
+        ServerConfiguration::new(&dir)
+            .default_permissions(DefaultPermissions::AllowAll)
+            .sql_view("by_parent_id", &BasicByParentId)
+            .with_schema::<BasicSchema>()?,
+    )
+    .await?;
+    let database = server
+        .create_database::<BasicSchema>("tests", false)
+        .await?;
+    Basic::new("a").with_parent_id(1).push_into_async(&database).await?;
+    Basic::new("b").with_parent_id(2).push_into_async(&database).await?;
+
+    let app = Router::new().layer(RestLayer::new(server, "/bonsaidb"));
+
+    let (status, results) = send(
+        &app,
+        Method::GET,
+        "/bonsaidb/databases/tests/sql?query=SELECT%20key%2C%20value%20FROM%20by_parent_id%20WHERE%20key%20%3E%201",
+        None,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        results,
+        json!({ "columns": ["key", "value"], "rows": [[2, 1]] })
+    );
+
+    let (status, _) = send(
+        &app,
+        Method::GET,
+        "/bonsaidb/databases/tests/sql?query=SELECT%20*%20FROM%20missing",
+        None,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn extractors() -> anyhow::Result<()> {
+    let dir = TestDirectory::new("axum-extractors.bonsaidb");
+    let server = Server::open(
+        ServerConfiguration::new(&dir)
+            .default_permissions(DefaultPermissions::AllowAll)
+            .with_schema::<BasicSchema>()?,
+    )
+    .await?;
+    server
+        .create_database::<BasicSchema>("tests", false)
+        .await?;
+
+    let app = Router::new()
+        .route("/databases", get(database_count))
+        .route("/:database/visits", get(visits))
+        .layer(BonsaiDbLayer::new(server.clone()));
+
+    let (status, _) = send(&app, Method::GET, "/databases", None).await?;
+    assert_eq!(status, StatusCode::OK);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/tests/visits")
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = hyper::body::to_bytes(response.into_body()).await?;
+    assert_eq!(&body[..], b"1");
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/missing/visits")
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn default_permissions_are_enforced() -> anyhow::Result<()> {
+    let dir = TestDirectory::new("axum-default-permissions.bonsaidb");
+    let server = Server::open(ServerConfiguration::new(&dir).with_schema::<BasicSchema>()?).await?;
+    server
+        .create_database::<BasicSchema>("tests", false)
+        .await?;
+
+    let app = Router::new().layer(RestLayer::new(server, "/bonsaidb"));
+    let (status, _) = send(&app, Method::GET, "/bonsaidb/databases", None).await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    Ok(())
+}
@@ -1,17 +1,23 @@
 use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::sync::Arc;
+use std::time::Duration;
 
 use bonsaidb_core::api;
 use bonsaidb_core::api::ApiName;
-use bonsaidb_core::networking::CURRENT_PROTOCOL_VERSION;
+use bonsaidb_core::networking::{CompressionConfig, PayloadLimits, CURRENT_PROTOCOL_VERSION};
 #[cfg(not(target_arch = "wasm32"))]
 use fabruic::Certificate;
 #[cfg(not(target_arch = "wasm32"))]
 use tokio::runtime::Handle;
-use url::Url;
 
-use crate::client::{AnyApiCallback, ApiCallback};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::client::OfflineQueue;
+#[cfg(all(feature = "websockets", not(target_arch = "wasm32")))]
+use crate::client::WebSocketKeepalive;
+use crate::client::{
+    AnyApiCallback, ApiCallback, Destination, DocumentCache, RequestOptions, RetryPolicy,
+};
 #[cfg(not(target_arch = "wasm32"))]
 use crate::BlockingClient;
 use crate::{AsyncClient, Error};
@@ -23,27 +29,47 @@ pub struct Blocking;
 /// Builds a new [`Client`] with custom settings.
 #[must_use]
 pub struct Builder<AsyncMode> {
-    url: Url,
+    destination: Destination,
     protocol_version: &'static str,
     custom_apis: HashMap<ApiName, Option<Arc<dyn AnyApiCallback>>>,
+    document_cache: Option<DocumentCache>,
+    compression: Option<CompressionConfig>,
+    payload_limits: PayloadLimits,
     #[cfg(not(target_arch = "wasm32"))]
     certificate: Option<fabruic::Certificate>,
     #[cfg(not(target_arch = "wasm32"))]
+    quic_idle_timeout: Option<Duration>,
+    #[cfg(not(target_arch = "wasm32"))]
     tokio: Option<Handle>,
+    request_options: RequestOptions,
+    #[cfg(not(target_arch = "wasm32"))]
+    offline_queue: Option<OfflineQueue>,
+    #[cfg(all(feature = "websockets", not(target_arch = "wasm32")))]
+    websocket_keepalive: Option<WebSocketKeepalive>,
     mode: PhantomData<AsyncMode>,
 }
 
 impl<AsyncMode> Builder<AsyncMode> {
-    /// Creates a new builder for a client connecting to `url`.
-    pub(crate) fn new(url: Url) -> Self {
+    /// Creates a new builder for a client connecting to `destination`.
+    pub(crate) fn new(destination: Destination) -> Self {
         Self {
-            url,
+            destination,
             protocol_version: CURRENT_PROTOCOL_VERSION,
             custom_apis: HashMap::new(),
+            document_cache: None,
+            compression: None,
+            payload_limits: PayloadLimits::default(),
             #[cfg(not(target_arch = "wasm32"))]
             certificate: None,
             #[cfg(not(target_arch = "wasm32"))]
+            quic_idle_timeout: None,
+            #[cfg(not(target_arch = "wasm32"))]
             tokio: None,
+            request_options: RequestOptions::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            offline_queue: None,
+            #[cfg(all(feature = "websockets", not(target_arch = "wasm32")))]
+            websocket_keepalive: None,
             mode: PhantomData,
         }
     }
@@ -82,6 +108,89 @@ impl<AsyncMode> Builder<AsyncMode> {
         self
     }
 
+    /// Closes BonsaiDb protocol connections that have been idle for `timeout`.
+    /// By default, idle connections are kept open. Only supported with BonsaiDb
+    /// protocol-based connections.
+    ///
+    /// A connection is idle when neither side has sent anything, including
+    /// responses and [`PubSub`](bonsaidb_core::pubsub::PubSub) messages. Once
+    /// a connection is closed, the client reconnects when the next request is
+    /// made.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_quic_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.quic_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the maximum amount of time to wait for the server to respond to a
+    /// request. Requests that do not receive a response in time return
+    /// [`Error::RequestTimeout`]. By default, requests wait indefinitely.
+    ///
+    /// The timeout is sent along with each request, and the server stops
+    /// waiting on view updates, reductions, and compactions for the request
+    /// once it elapses.
+    ///
+    /// The timeout can be overridden for specific requests using
+    /// [`AsyncClient::with_request_timeout()`].
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_options.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the policy used to retry idempotent requests that time out or are
+    /// interrupted by a disconnection. By default, requests are not retried.
+    ///
+    /// The policy can be overridden for specific requests using
+    /// [`AsyncClient::with_retry_policy()`].
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.request_options.retry_policy = policy;
+        self
+    }
+
+    /// Enables queueing writes made while the client is disconnected. See
+    /// [`OfflineQueue`] for more information.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_offline_queue(mut self, queue: OfflineQueue) -> Self {
+        self.offline_queue = Some(queue);
+        self
+    }
+
+    /// Enables pinging the server to detect unhealthy WebSocket connections.
+    /// See [`WebSocketKeepalive`] for more information. By default, the
+    /// server is not pinged. This setting has no effect on connections using
+    /// the BonsaiDb protocol, whose transport has its own keepalive.
+    #[cfg(all(feature = "websockets", not(target_arch = "wasm32")))]
+    pub fn with_websocket_keepalive(mut self, keepalive: WebSocketKeepalive) -> Self {
+        self.websocket_keepalive = Some(keepalive);
+        self
+    }
+
+    /// Enables caching documents retrieved by id. See [`DocumentCache`] for
+    /// more information.
+    pub fn with_document_cache(mut self, cache: DocumentCache) -> Self {
+        self.document_cache = Some(cache);
+        self
+    }
+
+    /// Enables compressing payloads using one of the algorithms in `config`.
+    /// The algorithm is negotiated with the server each time the client
+    /// connects, preferring algorithms listed earlier in `config`. Payloads
+    /// smaller than the configured threshold are not compressed. By default,
+    /// payloads are not compressed.
+    pub fn with_compression(mut self, config: CompressionConfig) -> Self {
+        self.compression = Some(config);
+        self
+    }
+
+    /// Sets the maximum sizes of the documents and transactions this client
+    /// sends, and of the responses it accepts. Transactions exceeding these
+    /// limits are rejected before they are sent to the server. By default,
+    /// [`PayloadLimits::default()`] is used.
+    pub const fn with_payload_limits(mut self, limits: PayloadLimits) -> Self {
+        self.payload_limits = limits;
+        self
+    }
+
     /// Overrides the protocol version. Only for testing purposes.
     #[cfg(feature = "test-util")]
     #[allow(clippy::missing_const_for_fn)]
@@ -92,13 +201,23 @@ impl<AsyncMode> Builder<AsyncMode> {
 
     fn finish_internal(self) -> Result<AsyncClient, Error> {
         AsyncClient::new_from_parts(
-            self.url,
+            self.destination,
             self.protocol_version,
             self.custom_apis,
+            self.document_cache,
+            self.compression,
+            self.payload_limits,
             #[cfg(not(target_arch = "wasm32"))]
             self.certificate,
             #[cfg(not(target_arch = "wasm32"))]
+            self.quic_idle_timeout,
+            #[cfg(not(target_arch = "wasm32"))]
             self.tokio.or_else(|| Handle::try_current().ok()),
+            self.request_options,
+            #[cfg(not(target_arch = "wasm32"))]
+            self.offline_queue,
+            #[cfg(all(feature = "websockets", not(target_arch = "wasm32")))]
+            self.websocket_keepalive,
         )
     }
 }
@@ -2,25 +2,36 @@ use std::any::TypeId;
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::ops::Deref;
+#[cfg(all(unix, feature = "websockets"))]
+use std::path::{Path, PathBuf};
 #[cfg(feature = "test-util")]
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
-use bonsaidb_core::admin::{Admin, ADMIN_DATABASE_NAME};
+use bonsaidb_core::admin::{Admin, PermissionGroup, Role, User, ADMIN_DATABASE_NAME};
 use bonsaidb_core::api::{self, Api, ApiName};
 use bonsaidb_core::arc_bytes::serde::Bytes;
 use bonsaidb_core::arc_bytes::OwnedBytes;
 use bonsaidb_core::connection::{
-    AsyncStorageConnection, Database, HasSession, IdentityReference, Session,
+    AsyncStorageConnection, Database, DatabaseQuery, HasSession, IdentityReference, Session,
+    SessionId, SessionInfo,
 };
+use bonsaidb_core::document::CollectionDocument;
 use bonsaidb_core::networking::{
-    AlterUserPermissionGroupMembership, AlterUserRoleMembership, AssumeIdentity, CreateDatabase,
-    CreateUser, DeleteDatabase, DeleteUser, ListAvailableSchemas, ListDatabases, LogOutSession,
-    MessageReceived, Payload, UnregisterSubscriber, CURRENT_PROTOCOL_VERSION,
+    AlterRolePermissionGroupMembership, AlterUserPermissionGroupMembership,
+    AlterUserRoleMembership, AssumeIdentity, Capabilities, CompressionConfig, CreateDatabase,
+    CreatePermissionGroup, CreateRole, CreateUser, DeleteDatabase, DeletePermissionGroup,
+    DeleteRole, DeleteUser, DocumentsChanged, EffectivePermissions, Handshake, HandshakeResponse,
+    ListAvailableSchemas, ListDatabases, ListPermissionGroups, ListRoles, ListSessions, ListUsers,
+    LogOutSession, MessageReceived, Payload, PayloadLimits, RevokeSession, ServerEvent,
+    SetPermissionGroupStatements, SetUserDisabled, ShuttingDown, UnlockUser, UnregisterSubscriber,
+    UpdatePermissionGroupStatements, UpdateRolePermissionGroups, UserByName,
+    CURRENT_PROTOCOL_VERSION,
 };
-use bonsaidb_core::permissions::Permissions;
+use bonsaidb_core::permissions::{Permissions, Statement};
 use bonsaidb_core::schema::{Nameable, Schema, SchemaName, Schematic};
 use bonsaidb_utils::fast_async_lock;
 use flume::Sender;
@@ -31,23 +42,62 @@ use parking_lot::Mutex;
 use tokio::{runtime::Handle, task::JoinHandle};
 use url::Url;
 
+#[cfg(not(target_arch = "wasm32"))]
+pub use self::batch::BlockingBatch;
+pub use self::batch::{Batch, BatchResults, Batched, BatchedView, ViewResponse};
+pub use self::cache::DocumentCache;
+use self::cache::DocumentCacheState;
+#[cfg(feature = "client-encryption")]
+pub use self::encryption::ClientEncryptionKey;
+use self::handshake::HandshakeState;
+#[cfg(all(feature = "websockets", not(target_arch = "wasm32")))]
+pub use self::keepalive::WebSocketKeepalive;
+#[cfg(not(target_arch = "wasm32"))]
+use self::offline::OfflineQueueState;
+#[cfg(not(target_arch = "wasm32"))]
+pub use self::offline::{
+    KeyValueQueueStorage, OfflineQueue, OfflineQueueStorage, QueuedWrite, ReplayedWrite,
+    WriteOutcome,
+};
 pub use self::remote_database::{AsyncRemoteDatabase, AsyncRemoteSubscriber};
+pub(crate) use self::retry::RequestOptions;
+pub use self::retry::RetryPolicy;
 #[cfg(not(target_arch = "wasm32"))]
 pub use self::sync::{BlockingClient, BlockingRemoteDatabase, BlockingRemoteSubscriber};
 use crate::builder::Async;
 use crate::error::Error;
 use crate::{ApiError, Builder};
 
+mod batch;
+mod cache;
+#[cfg(feature = "client-encryption")]
+mod encryption;
+mod handshake;
+#[cfg(all(feature = "websockets", not(target_arch = "wasm32")))]
+mod keepalive;
+#[cfg(not(target_arch = "wasm32"))]
+mod offline;
 #[cfg(not(target_arch = "wasm32"))]
 mod quic_worker;
 mod remote_database;
+mod retry;
 #[cfg(not(target_arch = "wasm32"))]
 mod sync;
+mod timer;
+mod transaction;
 #[cfg(all(feature = "websockets", not(target_arch = "wasm32")))]
 mod tungstenite_worker;
 #[cfg(all(feature = "websockets", target_arch = "wasm32"))]
 mod wasm_websocket_worker;
 
+/// The location of the server a client connects to.
+#[derive(Debug, Clone)]
+pub(crate) enum Destination {
+    Url(Url),
+    #[cfg(all(unix, feature = "websockets"))]
+    UnixSocket(PathBuf),
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct SubscriberMap(Arc<Mutex<HashMap<u64, flume::Sender<Message>>>>);
 
@@ -66,6 +116,25 @@ impl Deref for SubscriberMap {
     }
 }
 
+/// The receivers of [`ServerEvent`]s delivered to a client.
+#[derive(Debug, Clone, Default)]
+pub struct ServerEventSubscribers(Arc<Mutex<Vec<flume::Sender<ServerEvent>>>>);
+
+impl ServerEventSubscribers {
+    fn subscribe(&self) -> flume::Receiver<ServerEvent> {
+        let (sender, receiver) = flume::unbounded();
+        self.0.lock().push(sender);
+        receiver
+    }
+
+    fn publish(&self, event: &ServerEvent) {
+        // Receivers that have been dropped are removed.
+        self.0
+            .lock()
+            .retain(|sender| sender.send(event.clone()).is_ok());
+    }
+}
+
 use bonsaidb_core::circulate::Message;
 
 #[cfg(all(feature = "websockets", not(target_arch = "wasm32")))]
@@ -158,6 +227,22 @@ pub type WebSocketError = wasm_websocket_worker::WebSocketError;
 /// # }
 /// ```
 ///
+/// ### Over a Unix Domain Socket
+///
+/// On Unix platforms, a server listening with
+/// `CustomServer::listen_on_unix_socket()` can be connected to using
+/// [`AsyncClient::build_unix_socket()`]. Access to the server is controlled by
+/// the socket's filesystem permissions.
+///
+/// ```rust
+/// # use bonsaidb_client::AsyncClient;
+/// # async fn test_fn() -> anyhow::Result<()> {
+/// # #[cfg(unix)]
+/// let client = AsyncClient::build_unix_socket("/run/bonsaidb.sock").build()?;
+/// # Ok(())
+/// # }
+/// ```
+///
 /// ## Using a `Api`
 ///
 /// Our user guide has a [section on creating and
@@ -228,6 +313,7 @@ pub type WebSocketError = wasm_websocket_worker::WebSocketError;
 pub struct AsyncClient {
     pub(crate) data: Arc<Data>,
     session: ClientSession,
+    request_options: RequestOptions,
 }
 
 impl Drop for AsyncClient {
@@ -257,6 +343,11 @@ pub struct Data {
     connection_counter: Arc<AtomicU32>,
     request_id: AtomicU32,
     subscribers: SubscriberMap,
+    server_events: ServerEventSubscribers,
+    document_cache: Option<Arc<DocumentCacheState>>,
+    handshake: Arc<HandshakeState>,
+    #[cfg(not(target_arch = "wasm32"))]
+    offline_queue: Option<Arc<OfflineQueueState>>,
     #[cfg(feature = "test-util")]
     background_task_running: Arc<AtomicBool>,
 }
@@ -264,7 +355,15 @@ pub struct Data {
 impl AsyncClient {
     /// Returns a builder for a new client connecting to `url`.
     pub fn build(url: Url) -> Builder<Async> {
-        Builder::new(url)
+        Builder::new(Destination::Url(url))
+    }
+
+    /// Returns a builder for a new client connecting to a server listening on
+    /// the Unix domain socket at `path`. The WebSocket protocol is used over
+    /// the socket.
+    #[cfg(all(unix, feature = "websockets"))]
+    pub fn build_unix_socket(path: impl AsRef<Path>) -> Builder<Async> {
+        Builder::new(Destination::UnixSocket(path.as_ref().to_path_buf()))
     }
 
     /// Initialize a client connecting to `url`. This client can be shared by
@@ -283,13 +382,23 @@ impl AsyncClient {
     /// the database is to operation.
     pub fn new(url: Url) -> Result<Self, Error> {
         Self::new_from_parts(
-            url,
+            Destination::Url(url),
             CURRENT_PROTOCOL_VERSION,
             HashMap::default(),
+            None,
+            None,
+            PayloadLimits::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            None,
             #[cfg(not(target_arch = "wasm32"))]
             None,
             #[cfg(not(target_arch = "wasm32"))]
             Handle::try_current().ok(),
+            RequestOptions::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            None,
+            #[cfg(all(feature = "websockets", not(target_arch = "wasm32")))]
+            None,
         )
     }
 
@@ -309,13 +418,23 @@ impl AsyncClient {
     /// "retry-to-recover" design, or "abort-and-fail" depending on how critical
     /// the database is to operation.
     pub(crate) fn new_from_parts(
-        url: Url,
+        destination: Destination,
         protocol_version: &'static str,
         mut custom_apis: HashMap<ApiName, Option<Arc<dyn AnyApiCallback>>>,
+        document_cache: Option<DocumentCache>,
+        compression: Option<CompressionConfig>,
+        payload_limits: PayloadLimits,
         #[cfg(not(target_arch = "wasm32"))] certificate: Option<fabruic::Certificate>,
+        #[cfg(not(target_arch = "wasm32"))] quic_idle_timeout: Option<Duration>,
         #[cfg(not(target_arch = "wasm32"))] tokio: Option<Handle>,
+        request_options: RequestOptions,
+        #[cfg(not(target_arch = "wasm32"))] offline_queue: Option<OfflineQueue>,
+        #[cfg(all(feature = "websockets", not(target_arch = "wasm32")))]
+        websocket_keepalive: Option<WebSocketKeepalive>,
     ) -> Result<Self, Error> {
         let subscribers = SubscriberMap::default();
+        #[cfg(not(target_arch = "wasm32"))]
+        let offline_queue = offline_queue.map(|queue| Arc::new(OfflineQueueState::new(queue)));
         let callback_subscribers = subscribers.clone();
         custom_apis.insert(
             MessageReceived::name(),
@@ -339,27 +458,121 @@ impl AsyncClient {
                 },
             ))),
         );
-        match url.scheme() {
-            #[cfg(not(target_arch = "wasm32"))]
-            "bonsaidb" => Ok(Self::new_bonsai_client(
-                url,
-                protocol_version,
-                certificate,
-                custom_apis,
-                tokio,
-                subscribers,
-            )),
-            #[cfg(feature = "websockets")]
-            "wss" | "ws" => Ok(Self::new_websocket_client(
-                url,
+        let server_events = ServerEventSubscribers::default();
+        custom_apis.insert(
+            ServerEvent::name(),
+            Some(Arc::new(ApiCallback::<ServerEvent>::new({
+                let server_events = server_events.clone();
+                move |event: ServerEvent| {
+                    let server_events = server_events.clone();
+                    async move {
+                        server_events.publish(&event);
+                    }
+                }
+            }))),
+        );
+        let document_cache = document_cache.map(|cache| Arc::new(DocumentCacheState::new(cache)));
+        if let Some(cache) = &document_cache {
+            let cache = cache.clone();
+            custom_apis.insert(
+                DocumentsChanged::name(),
+                Some(Arc::new(ApiCallback::<DocumentsChanged>::new(
+                    move |changed: DocumentsChanged| {
+                        let cache = cache.clone();
+                        async move {
+                            cache.invalidate_changes(&changed);
+                        }
+                    },
+                ))),
+            );
+        }
+        let handshake = Arc::new(HandshakeState::new(
+            compression.unwrap_or_else(CompressionConfig::disabled),
+            payload_limits,
+        ));
+        custom_apis.insert(
+            Handshake::name(),
+            Some(Arc::new(ApiCallback::<Handshake>::new({
+                let handshake = handshake.clone();
+                move |response: HandshakeResponse| {
+                    let handshake = handshake.clone();
+                    async move {
+                        handshake.completed(response);
+                    }
+                }
+            }))),
+        );
+        // Unless the application has registered its own callback, shutdown
+        // notifications are only logged.
+        custom_apis.entry(ShuttingDown::name()).or_insert_with(|| {
+            Some(Arc::new(ApiCallback::<ShuttingDown>::new(
+                |notice: ShuttingDown| async move {
+                    log::info!(
+                        "server is shutting down (grace period: {:?})",
+                        notice.grace_period
+                    );
+                },
+            )))
+        });
+        let client = match destination {
+            Destination::Url(url) => match url.scheme() {
+                #[cfg(not(target_arch = "wasm32"))]
+                "bonsaidb" => Ok(Self::new_bonsai_client(
+                    url,
+                    protocol_version,
+                    certificate,
+                    quic_idle_timeout,
+                    custom_apis,
+                    tokio,
+                    subscribers,
+                    server_events,
+                    document_cache,
+                    handshake,
+                    offline_queue,
+                )),
+                #[cfg(all(feature = "websockets", not(target_arch = "wasm32")))]
+                "wss" | "ws" => Ok(Self::new_websocket_client(
+                    Destination::Url(url),
+                    protocol_version,
+                    custom_apis,
+                    tokio,
+                    subscribers,
+                    server_events,
+                    document_cache,
+                    handshake,
+                    offline_queue,
+                    websocket_keepalive,
+                )),
+                #[cfg(all(feature = "websockets", target_arch = "wasm32"))]
+                "wss" | "ws" => Ok(Self::new_websocket_client(
+                    url,
+                    protocol_version,
+                    custom_apis,
+                    subscribers,
+                    server_events,
+                    document_cache,
+                    handshake,
+                )),
+                other => Err(Error::InvalidUrl(format!("unsupported scheme {other}"))),
+            },
+            #[cfg(all(unix, feature = "websockets"))]
+            destination @ Destination::UnixSocket(_) => Ok(Self::new_websocket_client(
+                destination,
                 protocol_version,
                 custom_apis,
-                #[cfg(not(target_arch = "wasm32"))]
                 tokio,
                 subscribers,
+                server_events,
+                document_cache,
+                handshake,
+                offline_queue,
+                websocket_keepalive,
             )),
-            other => Err(Error::InvalidUrl(format!("unsupported scheme {other}"))),
-        }
+        };
+        client.map(|mut client| {
+            client.request_options = request_options;
+            client
+        })
     }
 
     #[cfg(not(target_arch = "wasm32"))]
@@ -367,9 +580,14 @@ impl AsyncClient {
         url: Url,
         protocol_version: &'static str,
         certificate: Option<fabruic::Certificate>,
+        idle_timeout: Option<Duration>,
         custom_apis: HashMap<ApiName, Option<Arc<dyn AnyApiCallback>>>,
         tokio: Option<Handle>,
         subscribers: SubscriberMap,
+        server_events: ServerEventSubscribers,
+        document_cache: Option<Arc<DocumentCacheState>>,
+        handshake: Arc<HandshakeState>,
+        offline_queue: Option<Arc<OfflineQueueState>>,
     ) -> Self {
         let (request_sender, request_receiver) = flume::unbounded();
         let connection_counter = Arc::new(AtomicU32::default());
@@ -379,10 +597,12 @@ impl AsyncClient {
                 url,
                 protocol_version,
                 certificate,
+                idle_timeout,
                 request_receiver,
                 Arc::new(custom_apis),
                 subscribers.clone(),
                 connection_counter.clone(),
+                handshake.clone(),
             ),
             tokio,
         );
@@ -403,32 +623,44 @@ impl AsyncClient {
                 request_id: AtomicU32::default(),
                 effective_permissions: Mutex::default(),
                 subscribers,
+                server_events,
+                document_cache,
+                handshake,
+                offline_queue,
                 #[cfg(feature = "test-util")]
                 background_task_running,
             }),
             session: ClientSession::default(),
+            request_options: RequestOptions::default(),
         }
     }
 
     #[cfg(all(feature = "websockets", not(target_arch = "wasm32")))]
     fn new_websocket_client(
-        url: Url,
+        destination: Destination,
         protocol_version: &'static str,
         custom_apis: HashMap<ApiName, Option<Arc<dyn AnyApiCallback>>>,
         tokio: Option<Handle>,
         subscribers: SubscriberMap,
+        server_events: ServerEventSubscribers,
+        document_cache: Option<Arc<DocumentCacheState>>,
+        handshake: Arc<HandshakeState>,
+        offline_queue: Option<Arc<OfflineQueueState>>,
+        keepalive: Option<WebSocketKeepalive>,
     ) -> Self {
         let (request_sender, request_receiver) = flume::unbounded();
         let connection_counter = Arc::new(AtomicU32::default());
 
         let worker = sync::spawn_client(
             tungstenite_worker::reconnecting_client_loop(
-                url,
+                destination,
                 protocol_version,
                 request_receiver,
                 Arc::new(custom_apis),
                 subscribers.clone(),
                 connection_counter.clone(),
+                handshake.clone(),
+                keepalive,
             ),
             tokio,
         );
@@ -450,10 +682,15 @@ impl AsyncClient {
                 connection_counter,
                 effective_permissions: Mutex::default(),
                 subscribers,
+                server_events,
+                document_cache,
+                handshake,
+                offline_queue,
                 #[cfg(feature = "test-util")]
                 background_task_running,
             }),
             session: ClientSession::default(),
+            request_options: RequestOptions::default(),
         }
     }
 
@@ -463,6 +700,9 @@ impl AsyncClient {
         protocol_version: &'static str,
         custom_apis: HashMap<ApiName, Option<Arc<dyn AnyApiCallback>>>,
         subscribers: SubscriberMap,
+        server_events: ServerEventSubscribers,
+        document_cache: Option<Arc<DocumentCacheState>>,
+        handshake: Arc<HandshakeState>,
     ) -> Self {
         let (request_sender, request_receiver) = flume::unbounded();
         let connection_counter = Arc::new(AtomicU32::default());
@@ -474,6 +714,7 @@ impl AsyncClient {
             Arc::new(custom_apis),
             subscribers.clone(),
             connection_counter.clone(),
+            handshake.clone(),
             None,
         );
 
@@ -494,10 +735,14 @@ impl AsyncClient {
                 connection_counter,
                 effective_permissions: Mutex::default(),
                 subscribers,
+                server_events,
+                document_cache,
+                handshake,
                 #[cfg(feature = "test-util")]
                 background_task_running,
             }),
             session: ClientSession::default(),
+            request_options: RequestOptions::default(),
         }
     }
 
@@ -514,6 +759,8 @@ impl AsyncClient {
                 id: Some(id),
                 name,
                 value: Ok(bytes),
+                compression: None,
+                deadline: self.request_options.timeout,
             },
             responder: result_sender,
         })?;
@@ -524,23 +771,186 @@ impl AsyncClient {
     async fn send_request_async(&self, name: ApiName, bytes: Bytes) -> Result<Bytes, Error> {
         let result_receiver = self.send_request_without_confirmation(name, bytes)?;
 
+        if let Some(timeout) = self.request_options.timeout {
+            return match timer::timeout(timeout, result_receiver.recv_async()).await {
+                Some(result) => result?,
+                None => Err(Error::RequestTimeout),
+            };
+        }
+
         result_receiver.recv_async().await?
     }
 
+    async fn send_request_with_retries(
+        &self,
+        name: ApiName,
+        bytes: Bytes,
+        idempotent: bool,
+    ) -> Result<Bytes, Error> {
+        if idempotent {
+            let mut attempt = 0;
+            loop {
+                match self.send_request_async(name.clone(), bytes.clone()).await {
+                    Ok(response) => return Ok(response),
+                    Err(err) => {
+                        match self.request_options.retry_policy.retry_delay(&err, attempt) {
+                            Some(delay) => {
+                                timer::sleep(delay).await;
+                                attempt += 1;
+                            }
+                            None => return Err(err),
+                        }
+                    }
+                }
+            }
+        }
+
+        self.send_request_async(name, bytes).await
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     fn send_request(&self, name: ApiName, bytes: Bytes) -> Result<Bytes, Error> {
         let result_receiver = self.send_request_without_confirmation(name, bytes)?;
 
+        if let Some(timeout) = self.request_options.timeout {
+            return match result_receiver.recv_timeout(timeout) {
+                Ok(result) => result,
+                Err(flume::RecvTimeoutError::Timeout) => Err(Error::RequestTimeout),
+                Err(flume::RecvTimeoutError::Disconnected) => Err(Error::Disconnected),
+            };
+        }
+
         result_receiver.recv()?
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
+    fn send_request_with_retries_blocking(
+        &self,
+        name: ApiName,
+        bytes: Bytes,
+        idempotent: bool,
+    ) -> Result<Bytes, Error> {
+        if idempotent {
+            let mut attempt = 0;
+            loop {
+                match self.send_request(name.clone(), bytes.clone()) {
+                    Ok(response) => return Ok(response),
+                    Err(err) => {
+                        match self.request_options.retry_policy.retry_delay(&err, attempt) {
+                            Some(delay) => {
+                                std::thread::sleep(delay);
+                                attempt += 1;
+                            }
+                            None => return Err(err),
+                        }
+                    }
+                }
+            }
+        }
+
+        self.send_request(name, bytes)
+    }
+
+    /// Returns a clone of this client that waits at most `timeout` for the
+    /// server to respond to each request. Requests that do not receive a
+    /// response in time return [`Error::RequestTimeout`]. If `timeout` is
+    /// `None`, requests wait indefinitely.
+    ///
+    /// The timeout is sent along with each request, and the server stops
+    /// waiting on view updates, reductions, and compactions for the request
+    /// once it elapses.
+    ///
+    /// The returned client shares this client's connection and session, and
+    /// databases opened using it inherit its settings. This can be used to
+    /// override the timeout configured with
+    /// [`Builder::with_request_timeout()`] for specific requests.
+    #[must_use]
+    pub fn with_request_timeout(&self, timeout: Option<Duration>) -> Self {
+        let mut client = self.clone();
+        client.request_options.timeout = timeout;
+        client
+    }
+
+    /// Returns a clone of this client that retries requests using `policy`.
+    ///
+    /// The returned client shares this client's connection and session, and
+    /// databases opened using it inherit its settings. This can be used to
+    /// override the policy configured with [`Builder::with_retry_policy()`]
+    /// for specific requests.
+    #[must_use]
+    pub fn with_retry_policy(&self, policy: RetryPolicy) -> Self {
+        let mut client = self.clone();
+        client.request_options.retry_policy = policy;
+        client
+    }
+
+    /// Returns the optional protocol features supported by the server this
+    /// client is connected to.
+    ///
+    /// The server reports its capabilities when each connection is
+    /// established. If that response has not been received yet, the server is
+    /// asked directly. Servers that predate capability negotiation report no
+    /// capabilities.
+    pub async fn server_capabilities(&self) -> Result<Capabilities, Error> {
+        if let Some(server) = self.data.handshake.server() {
+            return Ok(server.capabilities);
+        }
+
+        let response = self.send_api_request(&self.data.handshake.request()).await;
+        self.handshake_completed(response)
+    }
+
+    /// Returns a receiver of the [`ServerEvent`]s sent by the server, such as
+    /// databases being created or deleted and the server shutting down. Only
+    /// events sent after this function is called are received.
+    ///
+    /// Events are delivered independently of `PubSub` topics and are only sent
+    /// by servers supporting [`Capabilities::SERVER_EVENTS`].
+    #[must_use]
+    pub fn server_events(&self) -> flume::Receiver<ServerEvent> {
+        self.data.server_events.subscribe()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn server_capabilities_blocking(&self) -> Result<Capabilities, Error> {
+        if let Some(server) = self.data.handshake.server() {
+            return Ok(server.capabilities);
+        }
+
+        let response = self.send_blocking_api_request(&self.data.handshake.request());
+        self.handshake_completed(response)
+    }
+
+    fn handshake_completed(
+        &self,
+        response: Result<HandshakeResponse, ApiError<bonsaidb_core::Error>>,
+    ) -> Result<Capabilities, Error> {
+        let response = match response {
+            Ok(response) => response,
+            Err(
+                ApiError::Api(bonsaidb_core::Error::ApiNotFound(_))
+                | ApiError::Client(Error::Core(bonsaidb_core::Error::ApiNotFound(_))),
+            ) => HandshakeResponse::legacy(),
+            Err(ApiError::Api(err)) => return Err(Error::Core(err)),
+            Err(ApiError::Client(err)) => return Err(err),
+        };
+        let capabilities = response.capabilities;
+        self.data.handshake.completed(response);
+        Ok(capabilities)
+    }
+
     /// Sends an api `request`.
+    ///
+    /// If the request is [idempotent](api::Api::idempotent), it is retried
+    /// according to the client's [`RetryPolicy`].
     pub async fn send_api_request<Api: api::Api>(
         &self,
         request: &Api,
     ) -> Result<Api::Response, ApiError<Api::Error>> {
         let request = Bytes::from(pot::to_vec(request).map_err(Error::from)?);
-        let response = self.send_request_async(Api::name(), request).await?;
+        let response = self
+            .send_request_with_retries(Api::name(), request, Api::idempotent())
+            .await?;
         let response =
             pot::from_slice::<Result<Api::Response, Api::Error>>(&response).map_err(Error::from)?;
         response.map_err(ApiError::Api)
@@ -552,7 +962,8 @@ impl AsyncClient {
         request: &Api,
     ) -> Result<Api::Response, ApiError<Api::Error>> {
         let request = Bytes::from(pot::to_vec(request).map_err(Error::from)?);
-        let response = self.send_request(Api::name(), request)?;
+        let response =
+            self.send_request_with_retries_blocking(Api::name(), request, Api::idempotent())?;
 
         let response =
             pot::from_slice::<Result<Api::Response, Api::Error>>(&response).map_err(Error::from)?;
@@ -580,6 +991,19 @@ impl AsyncClient {
         self.data.background_task_running.clone()
     }
 
+    /// Returns the compression algorithm negotiated for the current
+    /// connection.
+    #[cfg(feature = "test-util")]
+    #[doc(hidden)]
+    #[must_use]
+    pub fn negotiated_compression(&self) -> Option<bonsaidb_core::networking::Compression> {
+        self.data.handshake.compression()
+    }
+
+    pub(crate) fn payload_limits(&self) -> &PayloadLimits {
+        self.data.handshake.limits()
+    }
+
     pub(crate) fn register_subscriber(&self, id: u64, sender: flume::Sender<Message>) {
         let mut subscribers = self.data.subscribers.lock();
         subscribers.insert(id, sender);
@@ -677,11 +1101,15 @@ impl AsyncStorageConnection for AsyncClient {
             name: name.to_string(),
         })
         .await?;
+        self.invalidate_cached_database(name);
         Ok(())
     }
 
-    async fn list_databases(&self) -> Result<Vec<Database>, bonsaidb_core::Error> {
-        Ok(self.send_api_request(&ListDatabases).await?)
+    async fn list_databases_matching(
+        &self,
+        query: DatabaseQuery,
+    ) -> Result<Vec<Database>, bonsaidb_core::Error> {
+        Ok(self.send_api_request(&ListDatabases { query }).await?)
     }
 
     async fn list_available_schemas(&self) -> Result<Vec<SchemaName>, bonsaidb_core::Error> {
@@ -707,6 +1135,69 @@ impl AsyncStorageConnection for AsyncClient {
             .await?)
     }
 
+    async fn list_users(&self) -> Result<Vec<CollectionDocument<User>>, bonsaidb_core::Error> {
+        let users = self.send_api_request(&ListUsers).await?;
+        users.iter().map(CollectionDocument::try_from).collect()
+    }
+
+    async fn user_by_name(
+        &self,
+        username: &str,
+    ) -> Result<Option<CollectionDocument<User>>, bonsaidb_core::Error> {
+        let user = self
+            .send_api_request(&UserByName {
+                username: username.to_string(),
+            })
+            .await?;
+        user.as_ref().map(CollectionDocument::try_from).transpose()
+    }
+
+    async fn disable_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<(), bonsaidb_core::Error> {
+        Ok(self
+            .send_api_request(&SetUserDisabled {
+                user: user.name()?.into_owned(),
+                disabled: true,
+            })
+            .await?)
+    }
+
+    async fn enable_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<(), bonsaidb_core::Error> {
+        Ok(self
+            .send_api_request(&SetUserDisabled {
+                user: user.name()?.into_owned(),
+                disabled: false,
+            })
+            .await?)
+    }
+
+    async fn unlock_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<(), bonsaidb_core::Error> {
+        Ok(self
+            .send_api_request(&UnlockUser {
+                user: user.name()?.into_owned(),
+            })
+            .await?)
+    }
+
+    async fn effective_permissions<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<Permissions, bonsaidb_core::Error> {
+        Ok(self
+            .send_api_request(&EffectivePermissions {
+                user: user.name()?.into_owned(),
+            })
+            .await?)
+    }
+
     #[cfg(feature = "password-hashing")]
     async fn set_user_password<'user, U: Nameable<'user, u64> + Send + Sync>(
         &self,
@@ -735,6 +1226,7 @@ impl AsyncStorageConnection for AsyncClient {
                 session: Arc::new(session),
                 connection_id: self.data.connection_counter.load(Ordering::SeqCst),
             },
+            request_options: self.request_options,
         })
     }
 
@@ -751,6 +1243,7 @@ impl AsyncStorageConnection for AsyncClient {
                 session: Arc::new(session),
                 connection_id: self.data.connection_counter.load(Ordering::SeqCst),
             },
+            request_options: self.request_options,
         })
     }
 
@@ -829,6 +1322,157 @@ impl AsyncStorageConnection for AsyncClient {
         .await?;
         Ok(())
     }
+
+    async fn create_permission_group(
+        &self,
+        name: &str,
+        statements: Vec<Statement>,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        Ok(self
+            .send_api_request(&CreatePermissionGroup {
+                name: name.to_string(),
+                statements,
+            })
+            .await?)
+    }
+
+    async fn list_permission_groups(
+        &self,
+    ) -> Result<Vec<CollectionDocument<PermissionGroup>>, bonsaidb_core::Error> {
+        let groups = self.send_api_request(&ListPermissionGroups).await?;
+        groups.iter().map(CollectionDocument::try_from).collect()
+    }
+
+    async fn set_permission_group_statements<'group, G: Nameable<'group, u64> + Send + Sync>(
+        &self,
+        permission_group: G,
+        statements: Vec<Statement>,
+    ) -> Result<(), bonsaidb_core::Error> {
+        Ok(self
+            .send_api_request(&SetPermissionGroupStatements {
+                group: permission_group.name()?.into_owned(),
+                statements,
+            })
+            .await?)
+    }
+
+    async fn update_permission_group_statements<'group, G: Nameable<'group, u64> + Send + Sync>(
+        &self,
+        permission_group: G,
+        expected_version: u64,
+        statements: Vec<Statement>,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        Ok(self
+            .send_api_request(&UpdatePermissionGroupStatements {
+                group: permission_group.name()?.into_owned(),
+                expected_version,
+                statements,
+            })
+            .await?)
+    }
+
+    async fn delete_permission_group<'group, G: Nameable<'group, u64> + Send + Sync>(
+        &self,
+        permission_group: G,
+    ) -> Result<(), bonsaidb_core::Error> {
+        Ok(self
+            .send_api_request(&DeletePermissionGroup {
+                group: permission_group.name()?.into_owned(),
+            })
+            .await?)
+    }
+
+    async fn create_role(&self, name: &str) -> Result<u64, bonsaidb_core::Error> {
+        Ok(self
+            .send_api_request(&CreateRole {
+                name: name.to_string(),
+            })
+            .await?)
+    }
+
+    async fn list_roles(&self) -> Result<Vec<CollectionDocument<Role>>, bonsaidb_core::Error> {
+        let roles = self.send_api_request(&ListRoles).await?;
+        roles.iter().map(CollectionDocument::try_from).collect()
+    }
+
+    async fn add_permission_group_to_role<
+        'role,
+        'group,
+        R: Nameable<'role, u64> + Send + Sync,
+        G: Nameable<'group, u64> + Send + Sync,
+    >(
+        &self,
+        role: R,
+        permission_group: G,
+    ) -> Result<(), bonsaidb_core::Error> {
+        self.send_api_request(&AlterRolePermissionGroupMembership {
+            role: role.name()?.into_owned(),
+            group: permission_group.name()?.into_owned(),
+            should_be_member: true,
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn remove_permission_group_from_role<
+        'role,
+        'group,
+        R: Nameable<'role, u64> + Send + Sync,
+        G: Nameable<'group, u64> + Send + Sync,
+    >(
+        &self,
+        role: R,
+        permission_group: G,
+    ) -> Result<(), bonsaidb_core::Error> {
+        self.send_api_request(&AlterRolePermissionGroupMembership {
+            role: role.name()?.into_owned(),
+            group: permission_group.name()?.into_owned(),
+            should_be_member: false,
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn update_role_permission_groups<'role, R: Nameable<'role, u64> + Send + Sync>(
+        &self,
+        role: R,
+        expected_version: u64,
+        permission_groups: Vec<u64>,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        Ok(self
+            .send_api_request(&UpdateRolePermissionGroups {
+                role: role.name()?.into_owned(),
+                expected_version,
+                groups: permission_groups,
+            })
+            .await?)
+    }
+
+    async fn delete_role<'role, R: Nameable<'role, u64> + Send + Sync>(
+        &self,
+        role: R,
+    ) -> Result<(), bonsaidb_core::Error> {
+        Ok(self
+            .send_api_request(&DeleteRole {
+                role: role.name()?.into_owned(),
+            })
+            .await?)
+    }
+
+    async fn list_sessions<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<Vec<SessionInfo>, bonsaidb_core::Error> {
+        Ok(self
+            .send_api_request(&ListSessions {
+                user: user.name()?.into_owned(),
+            })
+            .await?)
+    }
+
+    async fn revoke_session(&self, session_id: SessionId) -> Result<(), bonsaidb_core::Error> {
+        Ok(self.send_api_request(&RevokeSession(session_id)).await?)
+    }
 }
 
 type OutstandingRequestMap = HashMap<u32, PendingRequest>;
@@ -859,10 +1503,22 @@ impl<T> Drop for CancellableHandle<T> {
 }
 
 async fn process_response_payload(
-    payload: Payload,
+    mut payload: Payload,
     outstanding_requests: &OutstandingRequestMapHandle,
     custom_apis: &HashMap<ApiName, Option<Arc<dyn AnyApiCallback>>>,
+    limits: &PayloadLimits,
 ) {
+    if let Err(err) =
+        payload
+            .decompress(limits.max_response_size)
+            .and_then(|()| match &payload.value {
+                Ok(value) => limits.check_response(value.len()),
+                Err(_) => Ok(()),
+            })
+    {
+        payload.value = Err(err);
+    }
+
     if let Some(payload_id) = payload.id {
         if let Some(outstanding_request) = {
             let mut outstanding_requests = fast_async_lock!(outstanding_requests);
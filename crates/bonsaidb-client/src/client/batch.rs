@@ -0,0 +1,519 @@
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+#[cfg(not(target_arch = "wasm32"))]
+use std::ops::{Deref, DerefMut};
+
+use bonsaidb_core::api::Api;
+use bonsaidb_core::arc_bytes::serde::Bytes;
+use bonsaidb_core::connection::{
+    AccessPolicy, GroupedReductions, QueryKey, SerializedQueryKey, Sort, ViewMappings,
+    ViewReadOptions,
+};
+use bonsaidb_core::document::{DocumentId, OwnedDocument};
+use bonsaidb_core::key::{ByteCow, Key, KeyEncoding};
+use bonsaidb_core::keyvalue::{Command, KeyOperation};
+use bonsaidb_core::networking::{
+    BatchedRequest, CountView, ExecuteBatch, ExecuteKeyOperation, Get, Query, QueryWithDocs,
+    Reduce, ReduceGrouped,
+};
+use bonsaidb_core::schema::view;
+use bonsaidb_core::schema::view::map::{MappedDocuments, MappedValue};
+use bonsaidb_core::schema::{Collection, SerializedView};
+
+use crate::{ApiError, AsyncClient, AsyncRemoteDatabase, Error};
+
+/// A set of independent requests that are sent to the server in a single
+/// round trip.
+///
+/// Each request added to the batch returns a [`Batched`] handle. After the
+/// batch is executed, the handle is used to take the request's response from
+/// the [`BatchResults`]. The server executes the requests concurrently, so
+/// requests in the same batch should not depend on each other's effects.
+///
+/// All requests are executed using the session of the client that created
+/// the batch. Documents retrieved using a batch are not read from or stored in
+/// the client's [`DocumentCache`](crate::DocumentCache).
+///
+/// Batches are useful for querying several views at once, such as when
+/// rendering a dashboard:
+///
+/// ```rust
+/// # bonsaidb_core::__doctest_prelude!();
+/// # use bonsaidb_client::AsyncClient;
+/// # use bonsaidb_core::connection::{AccessPolicy, QueryKey, Sort};
+/// # async fn test_fn(client: AsyncClient) -> anyhow::Result<()> {
+/// let db = client.database::<MySchema>("my-database").await?;
+/// let mut batch = client.batch();
+/// let top_ranks = batch.query::<ScoresByRank, _>(
+///     &db,
+///     Some(QueryKey::Range(90..=100)),
+///     Sort::Descending,
+///     Some(10),
+///     AccessPolicy::UpdateBefore,
+/// )?;
+/// let average_score =
+///     batch.reduce::<ScoresByRank, u32>(&db, None, AccessPolicy::UpdateBefore)?;
+/// let mut results = batch.execute().await?;
+///
+/// for mapping in results.take_view(top_ranks)? {
+///     println!("Rank {} scored {}", mapping.key, mapping.value);
+/// }
+/// println!("Average score: {}", results.take_view(average_score)?);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+#[must_use]
+pub struct Batch {
+    client: AsyncClient,
+    requests: Vec<BatchedRequest>,
+    idempotent: bool,
+}
+
+impl Batch {
+    pub(crate) fn new(client: AsyncClient) -> Self {
+        Self {
+            client,
+            requests: Vec::new(),
+            idempotent: true,
+        }
+    }
+
+    /// Adds an api `request` to this batch.
+    pub fn push<A: Api>(&mut self, request: &A) -> Result<Batched<A>, Error> {
+        let value = Bytes::from(pot::to_vec(request)?);
+        let index = self.requests.len();
+        self.requests.push(BatchedRequest {
+            name: A::name(),
+            value,
+        });
+        self.idempotent &= A::idempotent();
+        Ok(Batched {
+            index,
+            _api: PhantomData,
+        })
+    }
+
+    /// Adds a request to retrieve the document with `id` from collection `C`
+    /// in `database`.
+    pub fn get<C, PrimaryKey>(
+        &mut self,
+        database: &AsyncRemoteDatabase,
+        id: &PrimaryKey,
+    ) -> Result<Batched<Get>, Error>
+    where
+        C: Collection,
+        PrimaryKey: for<'k> KeyEncoding<'k, C::PrimaryKey> + ?Sized,
+    {
+        self.push(&Get {
+            database: database.name().to_string(),
+            collection: C::collection_name(),
+            id: DocumentId::new(id)?,
+        })
+    }
+
+    /// Adds a request to query view `V` in `database`. The response contains
+    /// the matching mappings.
+    pub fn query<V: SerializedView, K>(
+        &mut self,
+        database: &AsyncRemoteDatabase,
+        key: Option<QueryKey<'_, V::Key, K>>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+    ) -> Result<BatchedView<V, Query>, Error>
+    where
+        K: for<'k> KeyEncoding<'k, V::Key> + PartialEq + ?Sized,
+        V::Key: Borrow<K> + PartialEq<K>,
+    {
+        let (database_name, view, key) = view_request::<V, K>(database, key)?;
+        self.push_view(&Query {
+            database: database_name,
+            view,
+            key,
+            order,
+            limit,
+            access_policy,
+            read_options: ViewReadOptions::default(),
+        })
+    }
+
+    /// Adds a request to query view `V` in `database`. The response contains
+    /// the matching mappings and their source documents.
+    pub fn query_with_docs<V: SerializedView, K>(
+        &mut self,
+        database: &AsyncRemoteDatabase,
+        key: Option<QueryKey<'_, V::Key, K>>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+    ) -> Result<BatchedView<V, QueryWithDocs>, Error>
+    where
+        K: for<'k> KeyEncoding<'k, V::Key> + PartialEq + ?Sized,
+        V::Key: Borrow<K> + PartialEq<K>,
+    {
+        let (database_name, view, key) = view_request::<V, K>(database, key)?;
+        self.push_view(&QueryWithDocs(Query {
+            database: database_name,
+            view,
+            key,
+            order,
+            limit,
+            access_policy,
+            read_options: ViewReadOptions::default(),
+        }))
+    }
+
+    /// Adds a request to reduce view `V` in `database`. The response contains
+    /// the reduced value of all matching entries.
+    pub fn reduce<V: SerializedView, K>(
+        &mut self,
+        database: &AsyncRemoteDatabase,
+        key: Option<QueryKey<'_, V::Key, K>>,
+        access_policy: AccessPolicy,
+    ) -> Result<BatchedView<V, Reduce>, Error>
+    where
+        K: for<'k> KeyEncoding<'k, V::Key> + PartialEq + ?Sized,
+        V::Key: Borrow<K> + PartialEq<K>,
+    {
+        let (database_name, view, key) = view_request::<V, K>(database, key)?;
+        self.push_view(&Reduce {
+            database: database_name,
+            view,
+            key,
+            access_policy,
+            read_options: ViewReadOptions::default(),
+        })
+    }
+
+    /// Adds a request to reduce view `V` in `database`, grouping by key. The
+    /// response contains the reduced value of each matching key.
+    pub fn reduce_grouped<V: SerializedView, K>(
+        &mut self,
+        database: &AsyncRemoteDatabase,
+        key: Option<QueryKey<'_, V::Key, K>>,
+        access_policy: AccessPolicy,
+    ) -> Result<BatchedView<V, ReduceGrouped>, Error>
+    where
+        K: for<'k> KeyEncoding<'k, V::Key> + PartialEq + ?Sized,
+        V::Key: Borrow<K> + PartialEq<K>,
+    {
+        let (database_name, view, key) = view_request::<V, K>(database, key)?;
+        self.push_view(&ReduceGrouped(Reduce {
+            database: database_name,
+            view,
+            key,
+            access_policy,
+            read_options: ViewReadOptions::default(),
+        }))
+    }
+
+    /// Adds a request to count the entries of view `V` in `database`. The
+    /// response contains the number of matching entries.
+    pub fn count<V: SerializedView, K>(
+        &mut self,
+        database: &AsyncRemoteDatabase,
+        key: Option<QueryKey<'_, V::Key, K>>,
+        access_policy: AccessPolicy,
+    ) -> Result<BatchedView<V, CountView>, Error>
+    where
+        K: for<'k> KeyEncoding<'k, V::Key> + PartialEq + ?Sized,
+        V::Key: Borrow<K> + PartialEq<K>,
+    {
+        let (database_name, view, key) = view_request::<V, K>(database, key)?;
+        self.push_view(&CountView {
+            database: database_name,
+            view,
+            key,
+            access_policy,
+            read_options: ViewReadOptions::default(),
+        })
+    }
+
+    fn push_view<V, A: Api>(&mut self, request: &A) -> Result<BatchedView<V, A>, Error> {
+        Ok(BatchedView {
+            request: self.push(request)?,
+            _view: PhantomData,
+        })
+    }
+
+    /// Adds a request to retrieve the value stored at `key` in `database`'s
+    /// key-value store.
+    pub fn get_key(
+        &mut self,
+        database: &AsyncRemoteDatabase,
+        key: impl Into<String>,
+    ) -> Result<Batched<ExecuteKeyOperation>, Error> {
+        self.push(&ExecuteKeyOperation {
+            database: database.name().to_string(),
+            op: KeyOperation {
+                namespace: None,
+                key: key.into(),
+                command: Command::Get { delete: false },
+            },
+        })
+    }
+
+    /// Returns the number of requests in this batch.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    /// Returns true if no requests have been added to this batch.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    /// Sends all requests in this batch to the server and waits for their
+    /// responses.
+    ///
+    /// The batch is retried according to the client's
+    /// [`RetryPolicy`](crate::RetryPolicy) only if every request in it is
+    /// [idempotent](Api::idempotent).
+    pub async fn execute(self) -> Result<BatchResults, Error> {
+        if self.requests.is_empty() {
+            return Ok(BatchResults::default());
+        }
+
+        let request = Bytes::from(pot::to_vec(&ExecuteBatch {
+            requests: self.requests,
+        })?);
+        let response = self
+            .client
+            .send_request_with_retries(ExecuteBatch::name(), request, self.idempotent)
+            .await?;
+        BatchResults::from_response(&response)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn execute_blocking(self) -> Result<BatchResults, Error> {
+        if self.requests.is_empty() {
+            return Ok(BatchResults::default());
+        }
+
+        let request = Bytes::from(pot::to_vec(&ExecuteBatch {
+            requests: self.requests,
+        })?);
+        let response = self.client.send_request_with_retries_blocking(
+            ExecuteBatch::name(),
+            request,
+            self.idempotent,
+        )?;
+        BatchResults::from_response(&response)
+    }
+}
+
+/// A handle to a request added to a [`Batch`]. Pass it to
+/// [`BatchResults::take()`] to retrieve the request's response.
+#[derive(Debug)]
+#[must_use]
+pub struct Batched<A: Api> {
+    index: usize,
+    _api: PhantomData<fn() -> A>,
+}
+
+/// A handle to a view request added to a [`Batch`]. Pass it to
+/// [`BatchResults::take_view()`] to retrieve the request's deserialized
+/// response.
+#[derive(Debug)]
+#[must_use]
+pub struct BatchedView<V, A: Api> {
+    request: Batched<A>,
+    _view: PhantomData<fn() -> V>,
+}
+
+/// A view [`Api`] whose response can be deserialized using the view `V`.
+pub trait ViewResponse<V: SerializedView>: Api<Error = bonsaidb_core::Error> {
+    /// The deserialized response.
+    type Output;
+
+    /// Deserializes `response` using the view `V`.
+    fn deserialize_response(response: Self::Response)
+        -> Result<Self::Output, bonsaidb_core::Error>;
+}
+
+impl<V: SerializedView> ViewResponse<V> for Query {
+    type Output = ViewMappings<V>;
+
+    fn deserialize_response(
+        response: Self::Response,
+    ) -> Result<Self::Output, bonsaidb_core::Error> {
+        response
+            .iter()
+            .map(|mapping| {
+                mapping
+                    .deserialized::<V>()
+                    .map_err(bonsaidb_core::Error::from)
+            })
+            .collect()
+    }
+}
+
+impl<V: SerializedView> ViewResponse<V> for QueryWithDocs {
+    type Output = MappedDocuments<OwnedDocument, V>;
+
+    fn deserialize_response(
+        response: Self::Response,
+    ) -> Result<Self::Output, bonsaidb_core::Error> {
+        response.deserialized::<V>()
+    }
+}
+
+impl<V: SerializedView> ViewResponse<V> for Reduce {
+    type Output = V::Value;
+
+    fn deserialize_response(
+        response: Self::Response,
+    ) -> Result<Self::Output, bonsaidb_core::Error> {
+        V::deserialize(&response)
+    }
+}
+
+impl<V: SerializedView> ViewResponse<V> for ReduceGrouped {
+    type Output = GroupedReductions<V>;
+
+    fn deserialize_response(
+        response: Self::Response,
+    ) -> Result<Self::Output, bonsaidb_core::Error> {
+        response
+            .into_iter()
+            .map(|map| {
+                Ok(MappedValue::new(
+                    V::Key::from_ord_bytes(ByteCow::Borrowed(&map.key))
+                        .map_err(view::Error::key_serialization)?,
+                    V::deserialize(&map.value)?,
+                ))
+            })
+            .collect()
+    }
+}
+
+impl<V: SerializedView> ViewResponse<V> for CountView {
+    type Output = u64;
+
+    fn deserialize_response(
+        response: Self::Response,
+    ) -> Result<Self::Output, bonsaidb_core::Error> {
+        Ok(response)
+    }
+}
+
+fn view_request<V: SerializedView, K>(
+    database: &AsyncRemoteDatabase,
+    key: Option<QueryKey<'_, V::Key, K>>,
+) -> Result<
+    (
+        String,
+        bonsaidb_core::schema::ViewName,
+        Option<SerializedQueryKey>,
+    ),
+    Error,
+>
+where
+    K: for<'k> KeyEncoding<'k, V::Key> + PartialEq + ?Sized,
+    V::Key: Borrow<K> + PartialEq<K>,
+{
+    let view = database.schema.view::<V>()?;
+    Ok((
+        database.name().to_string(),
+        view.view_name(),
+        key.map(|key| key.serialized()).transpose()?,
+    ))
+}
+
+/// The responses to the requests in an executed [`Batch`].
+#[derive(Debug, Default)]
+pub struct BatchResults {
+    responses: Vec<Option<Result<Bytes, bonsaidb_core::Error>>>,
+}
+
+impl BatchResults {
+    fn from_response(response: &[u8]) -> Result<Self, Error> {
+        let responses = pot::from_slice::<
+            Result<Vec<Result<Bytes, bonsaidb_core::Error>>, bonsaidb_core::Error>,
+        >(response)??;
+        Ok(Self {
+            responses: responses.into_iter().map(Some).collect(),
+        })
+    }
+
+    /// Returns the response to the request identified by `request`.
+    ///
+    /// Each request's response is independent: a request failing does not
+    /// prevent the other requests in the batch from succeeding.
+    pub fn take<A: Api>(&mut self, request: Batched<A>) -> Result<A::Response, ApiError<A::Error>> {
+        let response = self
+            .responses
+            .get_mut(request.index)
+            .and_then(Option::take)
+            .ok_or_else(|| {
+                Error::Core(bonsaidb_core::Error::other(
+                    "bonsaidb-client",
+                    "batched request was not part of this batch",
+                ))
+            })?
+            .map_err(Error::Core)?;
+        let response =
+            pot::from_slice::<Result<A::Response, A::Error>>(&response).map_err(Error::from)?;
+        response.map_err(ApiError::Api)
+    }
+
+    /// Returns the deserialized response to the view request identified by
+    /// `request`.
+    pub fn take_view<V, A>(
+        &mut self,
+        request: BatchedView<V, A>,
+    ) -> Result<A::Output, bonsaidb_core::Error>
+    where
+        V: SerializedView,
+        A: ViewResponse<V>,
+    {
+        let response = self.take(request.request)?;
+        A::deserialize_response(response)
+    }
+}
+
+impl AsyncClient {
+    /// Returns a new [`Batch`] of requests that are sent to the server in a
+    /// single round trip.
+    pub fn batch(&self) -> Batch {
+        Batch::new(self.clone())
+    }
+}
+
+/// A [`Batch`] that blocks the current thread while executing.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+#[must_use]
+pub struct BlockingBatch(pub(crate) Batch);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl BlockingBatch {
+    /// Sends all requests in this batch to the server and waits for their
+    /// responses.
+    ///
+    /// The batch is retried according to the client's
+    /// [`RetryPolicy`](crate::RetryPolicy) only if every request in it is
+    /// [idempotent](Api::idempotent).
+    pub fn execute(self) -> Result<BatchResults, Error> {
+        self.0.execute_blocking()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Deref for BlockingBatch {
+    type Target = Batch;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl DerefMut for BlockingBatch {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
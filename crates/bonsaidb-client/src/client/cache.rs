@@ -0,0 +1,432 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::Ordering;
+
+use bonsaidb_core::connection::SessionId;
+use bonsaidb_core::document::{DocumentId, OwnedDocument};
+use bonsaidb_core::networking::{DocumentsChanged, Get, WatchDocumentChanges};
+use bonsaidb_core::schema::CollectionName;
+use bonsaidb_core::transaction::OperationResult;
+use parking_lot::Mutex;
+
+use crate::{ApiError, AsyncClient};
+
+/// Configures a cache of documents retrieved by a client.
+///
+/// When a client is built with a document cache, documents retrieved by id
+/// are stored locally, and requesting the same document again is answered
+/// from the cache without contacting the server. The first time a document is
+/// retrieved from a database, the client asks the server to send it a
+/// [`DocumentsChanged`] notification whenever a transaction changes documents
+/// in that database. Cached copies of changed documents are discarded when
+/// the notification is received.
+///
+/// Notifications are delivered asynchronously, which means a document changed
+/// by another client may briefly be served from the cache after the change
+/// was committed. Documents changed by transactions applied through this
+/// client are discarded as soon as the transaction's results are received.
+/// The cache is cleared each time the client reconnects, as notifications may
+/// have been missed while disconnected.
+///
+/// Documents are cached separately for each session. Watching a database
+/// requires permission to list its executed transactions. If the server
+/// denies this permission, documents from that database are not cached.
+#[derive(Debug, Clone, Copy)]
+pub struct DocumentCache {
+    capacity: usize,
+}
+
+impl DocumentCache {
+    /// Returns a cache that holds up to `capacity` documents. When the cache
+    /// is full, the oldest cached document is discarded.
+    #[must_use]
+    pub const fn new(capacity: usize) -> Self {
+        Self { capacity }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct DocumentCacheState {
+    capacity: usize,
+    contents: Mutex<CacheContents>,
+}
+
+#[derive(Debug, Default)]
+struct CacheContents {
+    connection_id: u32,
+    generation: u64,
+    watches: HashMap<String, HashMap<Option<SessionId>, Watch>>,
+    documents: HashMap<DocumentKey, HashMap<Option<SessionId>, CachedDocument>>,
+    len: usize,
+    next_sequence: u64,
+    insertion_order: VecDeque<(DocumentKey, Option<SessionId>, u64)>,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Watch {
+    Active,
+    Denied,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+struct DocumentKey {
+    database: String,
+    collection: CollectionName,
+    id: DocumentId,
+}
+
+#[derive(Debug)]
+struct CachedDocument {
+    document: OwnedDocument,
+    sequence: u64,
+}
+
+enum Lookup {
+    Hit(OwnedDocument),
+    Miss { generation: u64 },
+    Unwatched { generation: u64 },
+    Uncacheable,
+}
+
+impl DocumentCacheState {
+    pub fn new(config: DocumentCache) -> Self {
+        Self {
+            capacity: config.capacity,
+            contents: Mutex::default(),
+        }
+    }
+
+    fn lookup(&self, connection_id: u32, session: Option<SessionId>, key: &DocumentKey) -> Lookup {
+        let mut contents = self.contents.lock();
+        if contents.connection_id != connection_id {
+            // Notifications may have been missed while reconnecting.
+            contents.clear();
+            contents.connection_id = connection_id;
+        }
+
+        match contents
+            .watches
+            .get(&key.database)
+            .and_then(|sessions| sessions.get(&session))
+        {
+            Some(Watch::Active) => {
+                if let Some(cached) = contents
+                    .documents
+                    .get(key)
+                    .and_then(|sessions| sessions.get(&session))
+                {
+                    Lookup::Hit(cached.document.clone())
+                } else {
+                    Lookup::Miss {
+                        generation: contents.generation,
+                    }
+                }
+            }
+            Some(Watch::Denied) => Lookup::Uncacheable,
+            None => Lookup::Unwatched {
+                generation: contents.generation,
+            },
+        }
+    }
+
+    /// Records the result of asking the server to watch `database`. Returns
+    /// true if the database is being watched.
+    fn watched(
+        &self,
+        connection_id: u32,
+        session: Option<SessionId>,
+        database: &str,
+        result: &Result<(), ApiError<bonsaidb_core::Error>>,
+    ) -> bool {
+        let watch = match result {
+            Ok(()) => Watch::Active,
+            Err(ApiError::Api(bonsaidb_core::Error::PermissionDenied(_))) => Watch::Denied,
+            Err(_) => return false,
+        };
+
+        let mut contents = self.contents.lock();
+        if contents.connection_id != connection_id {
+            return false;
+        }
+        contents
+            .watches
+            .entry(database.to_string())
+            .or_default()
+            .insert(session, watch);
+        watch == Watch::Active
+    }
+
+    fn insert(
+        &self,
+        connection_id: u32,
+        generation: u64,
+        session: Option<SessionId>,
+        key: DocumentKey,
+        document: OwnedDocument,
+    ) {
+        let mut contents = self.contents.lock();
+        // If the connection has changed or any documents have been
+        // invalidated since the request was sent, the document may already
+        // be out of date.
+        if contents.connection_id != connection_id || contents.generation != generation {
+            return;
+        }
+
+        let sequence = contents.next_sequence;
+        contents.next_sequence += 1;
+        let replaced = contents
+            .documents
+            .entry(key.clone())
+            .or_default()
+            .insert(session, CachedDocument { document, sequence });
+        if replaced.is_none() {
+            contents.len += 1;
+        }
+        contents.insertion_order.push_back((key, session, sequence));
+
+        while contents.len > self.capacity {
+            contents.evict_oldest();
+        }
+        if contents.insertion_order.len() > self.capacity.saturating_mul(2) {
+            contents.remove_stale_insertions();
+        }
+    }
+
+    fn invalidate<'a>(
+        &self,
+        database: &str,
+        documents: impl IntoIterator<Item = (&'a CollectionName, &'a DocumentId)>,
+    ) {
+        let mut contents = self.contents.lock();
+        contents.generation += 1;
+        for (collection, id) in documents {
+            let key = DocumentKey {
+                database: database.to_string(),
+                collection: collection.clone(),
+                id: id.clone(),
+            };
+            if let Some(sessions) = contents.documents.remove(&key) {
+                contents.len -= sessions.len();
+            }
+        }
+    }
+
+    pub fn invalidate_changes(&self, changed: &DocumentsChanged) {
+        self.invalidate(
+            &changed.database,
+            changed
+                .changes
+                .iter()
+                .map(|(collection, document)| (collection, &document.id)),
+        );
+    }
+
+    pub fn invalidate_results(&self, database: &str, results: &[OperationResult]) {
+        self.invalidate(
+            database,
+            results.iter().filter_map(|result| match result {
+                OperationResult::DocumentUpdated { header, collection } => {
+                    Some((collection, &header.id))
+                }
+                OperationResult::DocumentDeleted { id, collection } => Some((collection, id)),
+                OperationResult::Success => None,
+            }),
+        );
+    }
+
+    pub fn invalidate_database(&self, database: &str) {
+        let mut contents = self.contents.lock();
+        contents.generation += 1;
+        contents.watches.remove(database);
+        let mut removed = 0;
+        contents.documents.retain(|key, sessions| {
+            if key.database == database {
+                removed += sessions.len();
+                false
+            } else {
+                true
+            }
+        });
+        contents.len -= removed;
+    }
+
+    #[cfg(feature = "test-util")]
+    pub fn document_count(&self) -> usize {
+        self.contents.lock().len
+    }
+}
+
+impl CacheContents {
+    fn clear(&mut self) {
+        self.generation += 1;
+        self.watches.clear();
+        self.documents.clear();
+        self.insertion_order.clear();
+        self.len = 0;
+    }
+
+    fn is_current(&self, key: &DocumentKey, session: Option<SessionId>, sequence: u64) -> bool {
+        self.documents
+            .get(key)
+            .and_then(|sessions| sessions.get(&session))
+            .map_or(false, |cached| cached.sequence == sequence)
+    }
+
+    fn evict_oldest(&mut self) {
+        while let Some((key, session, sequence)) = self.insertion_order.pop_front() {
+            if self.is_current(&key, session, sequence) {
+                if let Some(sessions) = self.documents.get_mut(&key) {
+                    sessions.remove(&session);
+                    if sessions.is_empty() {
+                        self.documents.remove(&key);
+                    }
+                }
+                self.len -= 1;
+                return;
+            }
+        }
+    }
+
+    /// Removes entries from `insertion_order` for documents that have been
+    /// invalidated or replaced.
+    fn remove_stale_insertions(&mut self) {
+        let mut insertion_order = std::mem::take(&mut self.insertion_order);
+        insertion_order
+            .retain(|(key, session, sequence)| self.is_current(key, *session, *sequence));
+        self.insertion_order = insertion_order;
+    }
+}
+
+impl AsyncClient {
+    fn document_cache_context(&self) -> (u32, Option<SessionId>) {
+        (
+            self.data.connection_counter.load(Ordering::SeqCst),
+            self.session.session.id,
+        )
+    }
+
+    /// Retrieves a document, using the client's [`DocumentCache`] if one was
+    /// configured.
+    pub(crate) async fn get_document(
+        &self,
+        database: &str,
+        collection: &CollectionName,
+        id: DocumentId,
+    ) -> Result<Option<OwnedDocument>, bonsaidb_core::Error> {
+        let Some(cache) = &self.data.document_cache else {
+            return Ok(self
+                .send_api_request(&Get {
+                    database: database.to_string(),
+                    collection: collection.clone(),
+                    id,
+                })
+                .await?);
+        };
+
+        let (connection_id, session) = self.document_cache_context();
+        let key = DocumentKey {
+            database: database.to_string(),
+            collection: collection.clone(),
+            id,
+        };
+        let generation = match cache.lookup(connection_id, session, &key) {
+            Lookup::Hit(document) => return Ok(Some(document)),
+            Lookup::Miss { generation } => Some(generation),
+            Lookup::Unwatched { generation } => {
+                let result = self
+                    .send_api_request(&WatchDocumentChanges {
+                        database: database.to_string(),
+                    })
+                    .await;
+                cache
+                    .watched(connection_id, session, database, &result)
+                    .then_some(generation)
+            }
+            Lookup::Uncacheable => None,
+        };
+
+        let document = self
+            .send_api_request(&Get {
+                database: key.database.clone(),
+                collection: key.collection.clone(),
+                id: key.id.clone(),
+            })
+            .await?;
+        if let (Some(generation), Some(document)) = (generation, &document) {
+            cache.insert(connection_id, generation, session, key, document.clone());
+        }
+        Ok(document)
+    }
+
+    /// Retrieves a document, using the client's [`DocumentCache`] if one was
+    /// configured.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn get_document_blocking(
+        &self,
+        database: &str,
+        collection: &CollectionName,
+        id: DocumentId,
+    ) -> Result<Option<OwnedDocument>, bonsaidb_core::Error> {
+        let Some(cache) = &self.data.document_cache else {
+            return Ok(self.send_blocking_api_request(&Get {
+                database: database.to_string(),
+                collection: collection.clone(),
+                id,
+            })?);
+        };
+
+        let (connection_id, session) = self.document_cache_context();
+        let key = DocumentKey {
+            database: database.to_string(),
+            collection: collection.clone(),
+            id,
+        };
+        let generation = match cache.lookup(connection_id, session, &key) {
+            Lookup::Hit(document) => return Ok(Some(document)),
+            Lookup::Miss { generation } => Some(generation),
+            Lookup::Unwatched { generation } => {
+                let result = self.send_blocking_api_request(&WatchDocumentChanges {
+                    database: database.to_string(),
+                });
+                cache
+                    .watched(connection_id, session, database, &result)
+                    .then_some(generation)
+            }
+            Lookup::Uncacheable => None,
+        };
+
+        let document = self.send_blocking_api_request(&Get {
+            database: key.database.clone(),
+            collection: key.collection.clone(),
+            id: key.id.clone(),
+        })?;
+        if let (Some(generation), Some(document)) = (generation, &document) {
+            cache.insert(connection_id, generation, session, key, document.clone());
+        }
+        Ok(document)
+    }
+
+    /// Discards any cached copies of the documents changed by `results`.
+    pub(crate) fn invalidate_cached_documents(&self, database: &str, results: &[OperationResult]) {
+        if let Some(cache) = &self.data.document_cache {
+            cache.invalidate_results(database, results);
+        }
+    }
+
+    /// Discards all cached documents from `database`.
+    pub(crate) fn invalidate_cached_database(&self, database: &str) {
+        if let Some(cache) = &self.data.document_cache {
+            cache.invalidate_database(database);
+        }
+    }
+
+    /// Returns the number of documents in this client's [`DocumentCache`].
+    #[cfg(feature = "test-util")]
+    #[doc(hidden)]
+    #[must_use]
+    pub fn cached_document_count(&self) -> usize {
+        self.data
+            .document_cache
+            .as_ref()
+            .map_or(0, |cache| cache.document_count())
+    }
+}
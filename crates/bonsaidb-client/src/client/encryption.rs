@@ -0,0 +1,159 @@
+use std::fmt::Debug;
+
+use bonsaidb_core::arc_bytes::serde::Bytes;
+use bonsaidb_core::sealed::{BlindValue, Sealed, SealedCollection};
+use chacha20poly1305::aead::{Aead, AeadCore, OsRng, Payload};
+use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305, XNonce};
+
+use crate::Error;
+
+const NONCE_LENGTH: usize = 24;
+
+/// Encrypts the documents of [`SealedCollection`]s before they are sent to a
+/// server.
+///
+/// Documents are encrypted using XChaCha20Poly1305. The values returned from
+/// [`SealedCollection::blind_index()`] are hashed using a keyed BLAKE3 hash,
+/// which allows the server to index them without learning the values
+/// themselves. Equal values produce equal blind keys, which means the server
+/// can tell when two documents share an indexed value.
+///
+/// The keys are derived from a 32-byte secret that the server never sees. The
+/// application is responsible for generating the secret randomly and storing
+/// it safely: documents cannot be decrypted without it.
+///
+/// ```rust
+/// # use bonsaidb_client::ClientEncryptionKey;
+/// # use bonsaidb_core::schema::{Collection, SerializedCollection};
+/// # use bonsaidb_core::sealed::{BlindIndex, BlindValue, SealedCollection};
+/// # use bonsaidb_core::connection::AsyncConnection;
+/// # use serde::{Deserialize, Serialize};
+/// #[derive(Debug, Serialize, Deserialize, Collection)]
+/// #[collection(name = "notes", sealed)]
+/// # #[collection(core = bonsaidb_core)]
+/// pub struct Note {
+///     pub tag: String,
+///     pub body: String,
+/// }
+///
+/// impl SealedCollection for Note {
+///     fn blind_index(&self) -> Vec<BlindValue> {
+///         vec![BlindValue::new("tag", self.tag.as_bytes())]
+///     }
+/// }
+///
+/// # async fn test_fn<C: AsyncConnection>(db: C, secret: [u8; 32]) -> Result<(), bonsaidb_client::Error> {
+/// let key = ClientEncryptionKey::from_secret(&secret);
+/// let note = Note {
+///     tag: String::from("work"),
+///     body: String::from("the server cannot read this"),
+/// };
+/// Note::push_async(key.seal(&note)?, &db).await?;
+///
+/// let work = db
+///     .view::<BlindIndex<Note>>()
+///     .with_key(&key.blind_key(&BlindValue::new("tag", "work")))
+///     .query_with_collection_docs()
+///     .await?;
+/// for mapping in &work {
+///     let note = key.open::<Note>(&mapping.document.contents)?;
+///     println!("{}", note.body);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ClientEncryptionKey {
+    encryption: [u8; 32],
+    blinding: [u8; 32],
+}
+
+impl ClientEncryptionKey {
+    /// Returns the keys derived from `secret`. The same secret always produces
+    /// the same keys.
+    #[must_use]
+    pub fn from_secret(secret: &[u8; 32]) -> Self {
+        Self {
+            encryption: blake3::derive_key("bonsaidb client encryption", secret),
+            blinding: blake3::derive_key("bonsaidb client blind index", secret),
+        }
+    }
+
+    /// Encrypts `contents` and computes its blind keys, returning the value to
+    /// store in the collection.
+    pub fn seal<C: SealedCollection>(&self, contents: &C) -> Result<Sealed, Error> {
+        let plaintext = pot::to_vec(contents)
+            .map_err(|err| bonsaidb_core::Error::other("serialization", err))?;
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let collection = C::collection_name().to_string();
+        let encrypted = self
+            .cipher()
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: &plaintext,
+                    aad: collection.as_bytes(),
+                },
+            )
+            .map_err(|_| Error::Encryption)?;
+        let mut ciphertext = Vec::with_capacity(NONCE_LENGTH + encrypted.len());
+        ciphertext.extend_from_slice(&nonce);
+        ciphertext.extend_from_slice(&encrypted);
+
+        Ok(Sealed {
+            ciphertext: Bytes::from(ciphertext),
+            blind_keys: contents
+                .blind_index()
+                .iter()
+                .map(|value| self.blind_key(value))
+                .collect(),
+        })
+    }
+
+    /// Decrypts the contents of `sealed`.
+    ///
+    /// Returns [`Error::Encryption`] if `sealed` was encrypted using a
+    /// different key, was stored in a different collection, or has been
+    /// tampered with.
+    pub fn open<C: SealedCollection>(&self, sealed: &Sealed) -> Result<C, Error> {
+        if sealed.ciphertext.len() < NONCE_LENGTH {
+            return Err(Error::Encryption);
+        }
+        let (nonce, encrypted) = sealed.ciphertext.split_at(NONCE_LENGTH);
+        let collection = C::collection_name().to_string();
+        let plaintext = self
+            .cipher()
+            .decrypt(
+                XNonce::from_slice(nonce),
+                Payload {
+                    msg: encrypted,
+                    aad: collection.as_bytes(),
+                },
+            )
+            .map_err(|_| Error::Encryption)?;
+        Ok(pot::from_slice(&plaintext)
+            .map_err(|err| bonsaidb_core::Error::other("serialization", err))?)
+    }
+
+    /// Returns the blind key for `value`, which can be used to query
+    /// [`BlindIndex`](bonsaidb_core::sealed::BlindIndex).
+    #[must_use]
+    pub fn blind_key(&self, value: &BlindValue) -> Bytes {
+        let mut hasher = blake3::Hasher::new_keyed(&self.blinding);
+        hasher.update(&(value.index.len() as u64).to_be_bytes());
+        hasher.update(value.index.as_bytes());
+        hasher.update(&value.value);
+        Bytes::from(hasher.finalize().as_bytes().to_vec())
+    }
+
+    fn cipher(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new(Key::from_slice(&self.encryption))
+    }
+}
+
+impl Debug for ClientEncryptionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientEncryptionKey")
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,100 @@
+use bonsaidb_core::api::Api;
+use bonsaidb_core::arc_bytes::serde::Bytes;
+#[cfg(feature = "test-util")]
+use bonsaidb_core::networking::Compression;
+use bonsaidb_core::networking::{
+    CompressionConfig, Handshake, HandshakeResponse, Payload, PayloadLimits,
+};
+use parking_lot::Mutex;
+
+use crate::Error;
+
+/// The protocol settings of a client and the information the server provided
+/// when the current connection was established.
+#[derive(Debug)]
+pub(crate) struct HandshakeState {
+    compression: CompressionConfig,
+    limits: PayloadLimits,
+    server: Mutex<Option<HandshakeResponse>>,
+}
+
+impl HandshakeState {
+    pub fn new(compression: CompressionConfig, limits: PayloadLimits) -> Self {
+        Self {
+            compression,
+            limits,
+            server: Mutex::default(),
+        }
+    }
+
+    /// Returns the limits on the size of the data exchanged with the server.
+    pub const fn limits(&self) -> &PayloadLimits {
+        &self.limits
+    }
+
+    /// Returns the handshake offered to the server.
+    pub fn request(&self) -> Handshake {
+        Handshake::new(
+            self.compression
+                .algorithms
+                .iter()
+                .copied()
+                .filter(|compression| compression.is_supported())
+                .collect(),
+        )
+    }
+
+    /// Resets the server's information for a new connection, returning the
+    /// request that must be sent before any other request on the connection.
+    pub fn connected(&self) -> Result<Payload, Error> {
+        *self.server.lock() = None;
+        Ok(Payload {
+            session_id: None,
+            // The response is delivered to the callback registered for
+            // `Handshake` rather than a pending request.
+            id: None,
+            name: Handshake::name(),
+            value: Ok(Bytes::from(pot::to_vec(&self.request())?)),
+            compression: None,
+            deadline: None,
+        })
+    }
+
+    /// Records the server's response to the handshake.
+    pub fn completed(&self, mut response: HandshakeResponse) {
+        response.compression = response.compression.filter(|compression| {
+            compression.is_supported() && self.compression.algorithms.contains(compression)
+        });
+        *self.server.lock() = Some(response);
+    }
+
+    /// Returns the server's response to the handshake for the current
+    /// connection, if it has been received.
+    pub fn server(&self) -> Option<HandshakeResponse> {
+        self.server.lock().clone()
+    }
+
+    /// Returns the algorithm negotiated for the current connection.
+    #[cfg(feature = "test-util")]
+    pub fn compression(&self) -> Option<Compression> {
+        self.server
+            .lock()
+            .as_ref()
+            .and_then(|server| server.compression)
+    }
+
+    /// Compresses `payload` if an algorithm has been negotiated and its value
+    /// is at least the configured threshold.
+    pub fn compress(&self, payload: &mut Payload) {
+        let negotiated = self
+            .server
+            .lock()
+            .as_ref()
+            .and_then(|server| server.compression);
+        if let Some(compression) = negotiated {
+            if let Err(err) = payload.compress(compression, self.compression.threshold) {
+                log::warn!("error compressing request: {err}");
+            }
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The keepalive settings of a WebSocket connection. See
+/// [`Builder::with_websocket_keepalive()`](crate::Builder::with_websocket_keepalive).
+///
+/// While connected, the client pings the server every
+/// [`ping_interval`](Self#structfield.ping_interval). If nothing is received
+/// from the server within [`pong_timeout`](Self#structfield.pong_timeout) of a
+/// ping, the connection is deemed unhealthy: the function passed to
+/// [`on_unhealthy()`](Self::on_unhealthy) is invoked, the connection is
+/// closed, and outstanding requests return
+/// [`Error::Disconnected`](crate::Error::Disconnected). The client reconnects
+/// when the next request is made.
+#[derive(Clone)]
+#[must_use]
+pub struct WebSocketKeepalive {
+    /// How often the server is pinged. Default value is 30 seconds.
+    pub ping_interval: Duration,
+    /// How long to wait for the server to respond to a ping. Default value is
+    /// 10 seconds.
+    pub pong_timeout: Duration,
+    pub(crate) on_unhealthy: Option<Arc<dyn Fn() + Send + Sync>>,
+}
+
+impl Default for WebSocketKeepalive {
+    fn default() -> Self {
+        Self {
+            ping_interval: Duration::from_secs(30),
+            pong_timeout: Duration::from_secs(10),
+            on_unhealthy: None,
+        }
+    }
+}
+
+impl WebSocketKeepalive {
+    /// Sets [`Self::ping_interval`](Self#structfield.ping_interval) to `interval` and returns self.
+    pub const fn ping_interval(mut self, interval: Duration) -> Self {
+        self.ping_interval = interval;
+        self
+    }
+
+    /// Sets [`Self::pong_timeout`](Self#structfield.pong_timeout) to `timeout` and returns self.
+    pub const fn pong_timeout(mut self, timeout: Duration) -> Self {
+        self.pong_timeout = timeout;
+        self
+    }
+
+    /// Invokes `on_unhealthy` each time a connection is deemed unhealthy,
+    /// before the connection is closed.
+    pub fn on_unhealthy<F: Fn() + Send + Sync + 'static>(mut self, on_unhealthy: F) -> Self {
+        self.on_unhealthy = Some(Arc::new(on_unhealthy));
+        self
+    }
+}
+
+impl Debug for WebSocketKeepalive {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebSocketKeepalive")
+            .field("ping_interval", &self.ping_interval)
+            .field("pong_timeout", &self.pong_timeout)
+            .field("on_unhealthy", &self.on_unhealthy.is_some())
+            .finish()
+    }
+}
@@ -0,0 +1,400 @@
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use async_lock::{Mutex, MutexGuard};
+use async_trait::async_trait;
+use bonsaidb_core::keyvalue::AsyncKeyValue;
+use bonsaidb_core::networking::ApplyTransaction;
+use bonsaidb_core::transaction::{IdempotencyKey, OperationResult, Transaction};
+use bonsaidb_utils::fast_async_lock;
+use derive_where::derive_where;
+use serde::{Deserialize, Serialize};
+
+use crate::client::{ClientSession, Data, RequestOptions};
+use crate::{ApiError, AsyncClient, AsyncRemoteDatabase, Error};
+
+const INITIAL_REPLAY_DELAY: Duration = Duration::from_millis(250);
+const MAX_REPLAY_DELAY: Duration = Duration::from_secs(30);
+
+/// Configures a queue of writes that could not be sent to the server because
+/// the client was disconnected.
+///
+/// Writes are queued using
+/// [`AsyncRemoteDatabase::apply_transaction_or_queue()`]. Once a write has been
+/// queued, the client attempts to reconnect in the background and replays the
+/// queued writes in the order they were queued. The result of each replayed
+/// write, including any conflicts, is reported to the callback provided to
+/// [`OfflineQueue::on_replayed()`].
+///
+/// Queued writes are replayed using the client's unauthenticated session, as
+/// sessions do not survive disconnections. The server's default permissions
+/// must allow the queued writes to be applied.
+///
+/// Writes that don't have an
+/// [`idempotency_key`](Transaction#structfield.idempotency_key) are assigned
+/// one before they are first sent. If the connection is lost while a write is
+/// being sent or replayed, the server may have applied the write before the
+/// disconnection. The write will be replayed again once the client reconnects,
+/// and the server will return the original results rather than applying it a
+/// second time, provided the server still remembers the key.
+#[derive_where(Debug, Clone)]
+pub struct OfflineQueue {
+    capacity: usize,
+    storage: Option<Arc<dyn OfflineQueueStorage>>,
+    #[derive_where(skip)]
+    callback: Option<Arc<dyn Fn(ReplayedWrite) + Send + Sync>>,
+}
+
+impl OfflineQueue {
+    /// Returns a new queue that holds up to `capacity` writes in memory.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            storage: None,
+            callback: None,
+        }
+    }
+
+    /// Persists the queue in `storage`, allowing queued writes to survive the
+    /// client being restarted. Writes loaded from `storage` are replayed once
+    /// the queue is first used or [`AsyncClient::resume_offline_writes()`] is
+    /// called.
+    #[must_use]
+    pub fn persisted_in<Storage: OfflineQueueStorage>(mut self, storage: Storage) -> Self {
+        self.storage = Some(Arc::new(storage));
+        self
+    }
+
+    /// Invokes `callback` with the result of each replayed write.
+    #[must_use]
+    pub fn on_replayed<F: Fn(ReplayedWrite) + Send + Sync + 'static>(
+        mut self,
+        callback: F,
+    ) -> Self {
+        self.callback = Some(Arc::new(callback));
+        self
+    }
+}
+
+/// A write that is waiting to be sent to the server.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct QueuedWrite {
+    /// The unique id of this write, returned from
+    /// [`AsyncRemoteDatabase::apply_transaction_or_queue()`].
+    pub id: u64,
+    /// The name of the database the transaction is applied to.
+    pub database: String,
+    /// The transaction to apply.
+    pub transaction: Transaction,
+}
+
+/// The result of replaying a [`QueuedWrite`].
+#[derive(Debug)]
+pub struct ReplayedWrite {
+    /// The unique id of the write.
+    pub id: u64,
+    /// The name of the database the transaction was applied to.
+    pub database: String,
+    /// The result of applying the transaction. If the transaction conflicted
+    /// with changes made while the client was disconnected, the error
+    /// describing the conflict is returned.
+    pub result: Result<Vec<OperationResult>, bonsaidb_core::Error>,
+}
+
+/// The result of [`AsyncRemoteDatabase::apply_transaction_or_queue()`].
+#[derive(Debug)]
+pub enum WriteOutcome {
+    /// The transaction was applied.
+    Applied(Vec<OperationResult>),
+    /// The client was disconnected, and the transaction was queued. The
+    /// contained value is the [`QueuedWrite::id`] of the queued write.
+    Queued(u64),
+}
+
+/// Persistent storage for an [`OfflineQueue`].
+#[async_trait]
+pub trait OfflineQueueStorage: Debug + Send + Sync + 'static {
+    /// Returns the previously saved writes.
+    async fn load(&self) -> Result<Vec<QueuedWrite>, bonsaidb_core::Error>;
+    /// Replaces the saved writes with `writes`.
+    async fn save(&self, writes: &[QueuedWrite]) -> Result<(), bonsaidb_core::Error>;
+}
+
+/// Stores an [`OfflineQueue`] in a single key of a key-value store, such as an
+/// `AsyncDatabase` from `bonsaidb-local`.
+#[derive(Debug, Clone)]
+pub struct KeyValueQueueStorage<KeyValue> {
+    database: KeyValue,
+    key: String,
+}
+
+impl<KeyValue> KeyValueQueueStorage<KeyValue> {
+    /// Returns storage that saves the queue in `key` of `database`.
+    pub fn new(database: KeyValue, key: impl Into<String>) -> Self {
+        Self {
+            database,
+            key: key.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl<KeyValue> OfflineQueueStorage for KeyValueQueueStorage<KeyValue>
+where
+    KeyValue: AsyncKeyValue + Debug + 'static,
+{
+    async fn load(&self) -> Result<Vec<QueuedWrite>, bonsaidb_core::Error> {
+        Ok(self
+            .database
+            .get_key(self.key.clone())
+            .into()
+            .await?
+            .unwrap_or_default())
+    }
+
+    async fn save(&self, writes: &[QueuedWrite]) -> Result<(), bonsaidb_core::Error> {
+        if writes.is_empty() {
+            self.database.delete_key(self.key.clone()).await?;
+        } else {
+            self.database.set_key(self.key.clone(), &writes).await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct OfflineQueueState {
+    config: OfflineQueue,
+    contents: Mutex<QueueContents>,
+}
+
+#[derive(Debug, Default)]
+struct QueueContents {
+    loaded: bool,
+    replaying: bool,
+    next_id: u64,
+    writes: VecDeque<QueuedWrite>,
+}
+
+impl OfflineQueueState {
+    pub fn new(config: OfflineQueue) -> Self {
+        Self {
+            config,
+            contents: Mutex::default(),
+        }
+    }
+
+    async fn contents(&self) -> Result<MutexGuard<'_, QueueContents>, Error> {
+        let mut contents = fast_async_lock!(self.contents);
+        if !contents.loaded {
+            if let Some(storage) = &self.config.storage {
+                let persisted = storage.load().await?;
+                contents.next_id = persisted
+                    .iter()
+                    .map(|write| write.id + 1)
+                    .max()
+                    .unwrap_or(0);
+                contents.writes = VecDeque::from(persisted);
+            }
+            contents.loaded = true;
+        }
+        Ok(contents)
+    }
+
+    async fn persist(&self, contents: &mut QueueContents) -> Result<(), Error> {
+        if let Some(storage) = &self.config.storage {
+            storage.save(contents.writes.make_contiguous()).await?;
+        }
+        Ok(())
+    }
+}
+
+impl AsyncClient {
+    /// Loads any writes persisted by this client's [`OfflineQueue`] and
+    /// begins replaying them in the background. This only needs to be called
+    /// when a client is created with a persisted queue that may contain writes
+    /// from a previous instance of the client.
+    pub async fn resume_offline_writes(&self) -> Result<(), Error> {
+        if let Some(queue) = &self.data.offline_queue {
+            let mut contents = queue.contents().await?;
+            if !contents.writes.is_empty() {
+                self.start_replaying(queue, &mut contents);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the writes waiting to be sent to the server, in the order they
+    /// will be replayed.
+    pub async fn queued_writes(&self) -> Result<Vec<QueuedWrite>, Error> {
+        if let Some(queue) = &self.data.offline_queue {
+            let contents = queue.contents().await?;
+            Ok(contents.writes.iter().cloned().collect())
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    fn start_replaying(&self, queue: &Arc<OfflineQueueState>, contents: &mut QueueContents) {
+        if !contents.replaying {
+            contents.replaying = true;
+            tokio::spawn(replay_offline_writes(
+                Arc::downgrade(&self.data),
+                queue.clone(),
+            ));
+        }
+    }
+
+    /// Sends the oldest queued write. Returns false if the queue is empty, in
+    /// which case the queue is no longer considered to be replaying.
+    async fn replay_next_write(&self, queue: &OfflineQueueState) -> Result<bool, Error> {
+        let write = {
+            let mut contents = queue.contents().await?;
+            if let Some(write) = contents.writes.front() {
+                write.clone()
+            } else {
+                contents.replaying = false;
+                return Ok(false);
+            }
+        };
+
+        let result = match self
+            .send_api_request(&ApplyTransaction {
+                database: write.database.clone(),
+                transaction: write.transaction.clone(),
+            })
+            .await
+        {
+            Err(ApiError::Client(err)) if err.is_connection_error() => return Err(err),
+            other => other.map_err(bonsaidb_core::Error::from),
+        };
+
+        {
+            let mut contents = queue.contents().await?;
+            contents.writes.pop_front();
+            if let Err(err) = queue.persist(&mut contents).await {
+                log::error!("error persisting offline write queue: {err}");
+            }
+        }
+
+        if let Some(callback) = &queue.config.callback {
+            callback(ReplayedWrite {
+                id: write.id,
+                database: write.database,
+                result,
+            });
+        }
+
+        Ok(true)
+    }
+}
+
+async fn replay_offline_writes(data: Weak<Data>, queue: Arc<OfflineQueueState>) {
+    let mut delay = INITIAL_REPLAY_DELAY;
+    loop {
+        let Some(data) = data.upgrade() else { return };
+        // Sessions don't survive reconnecting, so writes are replayed using
+        // the unauthenticated session.
+        let client = AsyncClient {
+            data,
+            session: ClientSession::default(),
+            request_options: RequestOptions::default(),
+        };
+        match client.replay_next_write(&queue).await {
+            Ok(true) => delay = INITIAL_REPLAY_DELAY,
+            Ok(false) => return,
+            Err(err) => {
+                log::debug!("unable to replay offline writes: {err}");
+                drop(client);
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(MAX_REPLAY_DELAY);
+            }
+        }
+    }
+}
+
+impl AsyncRemoteDatabase {
+    /// Applies `transaction` to this database. If the client has an
+    /// [`OfflineQueue`] and the server cannot be reached, the transaction is
+    /// queued and will be applied once the client reconnects.
+    ///
+    /// If other writes are already queued, `transaction` is queued behind them
+    /// so that writes are applied in the order they were made.
+    ///
+    /// If the client was not built with an [`OfflineQueue`], this function
+    /// behaves like
+    /// [`apply_transaction()`](bonsaidb_core::connection::AsyncLowLevelConnection::apply_transaction).
+    pub async fn apply_transaction_or_queue(
+        &self,
+        mut transaction: Transaction,
+    ) -> Result<WriteOutcome, Error> {
+        self.client
+            .payload_limits()
+            .check_transaction(&transaction)?;
+        let queue = self.client.data.offline_queue.as_ref();
+        if let Some(queue) = queue {
+            transaction
+                .idempotency_key
+                .get_or_insert_with(IdempotencyKey::random);
+            let contents = queue.contents().await?;
+            if !contents.writes.is_empty() {
+                return self.queue_write(queue, contents, transaction).await;
+            }
+        }
+
+        match self
+            .client
+            .send_api_request(&ApplyTransaction {
+                database: self.name.to_string(),
+                transaction: transaction.clone(),
+            })
+            .await
+        {
+            Ok(results) => {
+                self.client
+                    .invalidate_cached_documents(&self.name, &results);
+                Ok(WriteOutcome::Applied(results))
+            }
+            Err(ApiError::Client(err)) if err.is_connection_error() => {
+                if let Some(queue) = queue {
+                    let contents = queue.contents().await?;
+                    self.queue_write(queue, contents, transaction).await
+                } else {
+                    Err(err)
+                }
+            }
+            Err(ApiError::Client(err)) => Err(err),
+            Err(ApiError::Api(err)) => Err(Error::Core(err)),
+        }
+    }
+
+    async fn queue_write(
+        &self,
+        queue: &Arc<OfflineQueueState>,
+        mut contents: MutexGuard<'_, QueueContents>,
+        transaction: Transaction,
+    ) -> Result<WriteOutcome, Error> {
+        if contents.writes.len() >= queue.config.capacity {
+            return Err(Error::OfflineQueueFull);
+        }
+
+        let id = contents.next_id;
+        contents.next_id += 1;
+        contents.writes.push_back(QueuedWrite {
+            id,
+            database: self.name.to_string(),
+            transaction,
+        });
+        if let Err(err) = queue.persist(&mut contents).await {
+            contents.writes.pop_back();
+            return Err(err);
+        }
+
+        self.client.start_replaying(queue, &mut contents);
+
+        Ok(WriteOutcome::Queued(id))
+    }
+}
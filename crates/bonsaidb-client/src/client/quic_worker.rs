@@ -1,9 +1,10 @@
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 use bonsaidb_core::api::ApiName;
-use bonsaidb_core::networking::Payload;
+use bonsaidb_core::networking::{Payload, PayloadLimits};
 use bonsaidb_utils::fast_async_lock;
 use fabruic::{self, Certificate, Endpoint};
 use flume::Receiver;
@@ -12,7 +13,8 @@ use url::Url;
 
 use super::PendingRequest;
 use crate::client::{
-    disconnect_pending_requests, AnyApiCallback, OutstandingRequestMapHandle, SubscriberMap,
+    disconnect_pending_requests, AnyApiCallback, HandshakeState, OutstandingRequestMapHandle,
+    SubscriberMap,
 };
 use crate::Error;
 
@@ -23,10 +25,12 @@ pub async fn reconnecting_client_loop(
     mut url: Url,
     protocol_version: &'static str,
     certificate: Option<Certificate>,
+    idle_timeout: Option<Duration>,
     request_receiver: Receiver<PendingRequest>,
     custom_apis: Arc<HashMap<ApiName, Option<Arc<dyn AnyApiCallback>>>>,
     subscribers: SubscriberMap,
     connection_counter: Arc<AtomicU32>,
+    handshake: Arc<HandshakeState>,
 ) -> Result<(), Error> {
     if url.port().is_none() && url.scheme() == "bonsaidb" {
         let _ = url.set_port(Some(5645));
@@ -44,9 +48,11 @@ pub async fn reconnecting_client_loop(
             &url,
             protocol_version,
             certificate.as_ref(),
+            idle_timeout,
             request,
             &request_receiver,
             custom_apis.clone(),
+            &handshake,
         )
         .await
         {
@@ -65,12 +71,14 @@ async fn connect_and_process(
     url: &Url,
     protocol_version: &str,
     certificate: Option<&Certificate>,
-    initial_request: PendingRequest,
+    idle_timeout: Option<Duration>,
+    mut initial_request: PendingRequest,
     request_receiver: &Receiver<PendingRequest>,
     custom_apis: Arc<HashMap<ApiName, Option<Arc<dyn AnyApiCallback>>>>,
+    handshake: &HandshakeState,
 ) -> Result<(), (Option<PendingRequest>, Option<Error>)> {
     let (_connection, payload_sender, payload_receiver) =
-        match connect(url, certificate, protocol_version).await {
+        match connect(url, certificate, idle_timeout, protocol_version).await {
             Ok(result) => result,
             Err(err) => return Err((Some(initial_request), Some(err))),
         };
@@ -80,8 +88,19 @@ async fn connect_and_process(
         outstanding_requests.clone(),
         payload_receiver,
         custom_apis,
+        *handshake.limits(),
     ));
 
+    match handshake.connected() {
+        Ok(request) => {
+            if let Err(err) = payload_sender.send(&request) {
+                return Err((Some(initial_request), Some(Error::from(err))));
+            }
+        }
+        Err(err) => return Err((Some(initial_request), Some(err))),
+    }
+
+    handshake.compress(&mut initial_request.request);
     if let Err(err) = payload_sender.send(&initial_request.request) {
         return Err((Some(initial_request), Some(Error::from(err))));
     }
@@ -101,7 +120,8 @@ async fn connect_and_process(
         process_requests(
             outstanding_requests.clone(),
             request_receiver,
-            payload_sender
+            payload_sender,
+            handshake
         ),
         async { request_processor.await.map_err(|_| Error::Disconnected)? }
     ) {
@@ -118,8 +138,10 @@ async fn process_requests(
     outstanding_requests: OutstandingRequestMapHandle,
     request_receiver: &Receiver<PendingRequest>,
     payload_sender: fabruic::Sender<Payload>,
+    handshake: &HandshakeState,
 ) -> Result<(), Error> {
-    while let Ok(client_request) = request_receiver.recv_async().await {
+    while let Ok(mut client_request) = request_receiver.recv_async().await {
+        handshake.compress(&mut client_request.request);
         let mut outstanding_requests = fast_async_lock!(outstanding_requests);
         payload_sender.send(&client_request.request)?;
         outstanding_requests.insert(
@@ -138,10 +160,12 @@ pub async fn process(
     outstanding_requests: OutstandingRequestMapHandle,
     mut payload_receiver: fabruic::Receiver<Payload>,
     custom_apis: Arc<HashMap<ApiName, Option<Arc<dyn AnyApiCallback>>>>,
+    limits: PayloadLimits,
 ) -> Result<(), Error> {
     while let Some(payload) = payload_receiver.next().await {
         let payload = payload?;
-        super::process_response_payload(payload, &outstanding_requests, &custom_apis).await;
+        super::process_response_payload(payload, &outstanding_requests, &custom_apis, &limits)
+            .await;
     }
 
     Err(Error::Disconnected)
@@ -150,6 +174,7 @@ pub async fn process(
 async fn connect(
     url: &Url,
     certificate: Option<&Certificate>,
+    idle_timeout: Option<Duration>,
     protocol_version: &str,
 ) -> Result<
     (
@@ -161,7 +186,7 @@ async fn connect(
 > {
     let mut endpoint = Endpoint::builder();
     endpoint
-        .set_max_idle_timeout(None)
+        .set_max_idle_timeout(idle_timeout)
         .map_err(|err| Error::Core(bonsaidb_core::Error::other("quic", err)))?;
     endpoint.set_protocols([protocol_version.as_bytes().to_vec()]);
     let endpoint = endpoint
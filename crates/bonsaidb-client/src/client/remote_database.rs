@@ -2,19 +2,26 @@ use std::ops::Deref;
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use bonsaidb_core::api::NamedQuery;
+use bonsaidb_core::arc_bytes::serde::Bytes;
 use bonsaidb_core::connection::{
     AccessPolicy, AsyncConnection, AsyncLowLevelConnection, HasSchema, HasSession, Range,
-    SerializedQueryKey, Session, Sort,
+    SerializedQueryKey, Session, Sort, ViewReadOptions,
 };
 use bonsaidb_core::document::{DocumentId, Header, OwnedDocument};
 use bonsaidb_core::networking::{
-    ApplyTransaction, Compact, CompactCollection, CompactKeyValueStore, Count, DeleteDocs, Get,
-    GetMultiple, LastTransactionId, List, ListExecutedTransactions, ListHeaders, Query,
-    QueryWithDocs, Reduce, ReduceGrouped,
+    Aggregate, Compact, CompactCollection, CompactKeyValueStore, Count, CountView, DeleteDocs,
+    DocumentMappings, ExecuteNamedQuery, GetMultiple, LastTransactionId, List,
+    ListExecutedTransactions, ListHeaders, ListProjected, Query, QueryJoin, QueryWithDocs,
+    QueryWithProjectedDocs, Reduce, ReduceGrouped,
 };
+use bonsaidb_core::schema::view::aggregation::{
+    Aggregation, AggregationGrouping, SerializedAggregatedGroup,
+};
+use bonsaidb_core::schema::view::join::SerializedJoinedEntry;
 use bonsaidb_core::schema::view::map::MappedSerializedValue;
-use bonsaidb_core::schema::{self, CollectionName, Schematic, ViewName};
-use bonsaidb_core::transaction::{Executed, OperationResult, Transaction};
+use bonsaidb_core::schema::{self, CollectionName, Name, Schematic, ViewName};
+use bonsaidb_core::transaction::{Executed, Operation, OperationResult, Transaction};
 
 use crate::AsyncClient;
 
@@ -36,6 +43,60 @@ impl AsyncRemoteDatabase {
     pub fn name(&self) -> &str {
         self.name.as_ref()
     }
+
+    /// Executes the [`NamedQuery`] `Q` that has been registered with the
+    /// server, using `parameters`.
+    ///
+    /// # Errors
+    ///
+    /// - [`bonsaidb_core::Error::NamedQueryNotFound`]: The server has no
+    ///   query registered with the name of `Q`.
+    /// - [`bonsaidb_core::Error::PermissionDenied`]: The client is not allowed
+    ///   to execute `Q` in this database.
+    pub async fn execute_named_query<Q: NamedQuery>(
+        &self,
+        parameters: &Q::Parameters,
+    ) -> Result<Q::Output, bonsaidb_core::Error> {
+        let output = self
+            .client
+            .send_api_request(&ExecuteNamedQuery {
+                database: self.name.to_string(),
+                name: Q::name(),
+                parameters: Bytes::from(pot::to_vec(parameters)?),
+            })
+            .await?;
+        Ok(pot::from_slice(&output)?)
+    }
+
+    /// Sends `operations` to the server in chunks of at most
+    /// [`PayloadLimits::transaction_chunk_size`](bonsaidb_core::networking::PayloadLimits::transaction_chunk_size)
+    /// bytes, and applies them atomically as a single transaction once every
+    /// chunk has been sent. Because each chunk is sent as soon as it is full,
+    /// `operations` can be produced lazily without holding the entire
+    /// transaction in memory.
+    ///
+    /// Transactions applied using
+    /// [`apply_transaction()`](AsyncLowLevelConnection::apply_transaction) are
+    /// streamed automatically when they exceed the chunk size.
+    ///
+    /// # Errors
+    ///
+    /// - [`bonsaidb_core::Error::ApiNotFound`]: The server does not support
+    ///   [`Capabilities::STREAMED_TRANSACTIONS`](bonsaidb_core::networking::Capabilities::STREAMED_TRANSACTIONS).
+    /// - [`bonsaidb_core::Error::TransactionTooLarge`]: The combined size of
+    ///   the documents in `operations` exceeds the payload limits.
+    pub async fn apply_streamed_transaction<Operations>(
+        &self,
+        operations: Operations,
+    ) -> Result<Vec<OperationResult>, bonsaidb_core::Error>
+    where
+        Operations: IntoIterator<Item = Operation> + Send,
+        Operations::IntoIter: Send,
+    {
+        self.client
+            .apply_streamed_transaction(&self.name, operations)
+            .await
+    }
 }
 
 impl Deref for AsyncRemoteDatabase {
@@ -117,13 +178,7 @@ impl AsyncLowLevelConnection for AsyncRemoteDatabase {
         &self,
         transaction: Transaction,
     ) -> Result<Vec<OperationResult>, bonsaidb_core::Error> {
-        Ok(self
-            .client
-            .send_api_request(&ApplyTransaction {
-                database: self.name.to_string(),
-                transaction,
-            })
-            .await?)
+        self.client.apply_transaction(&self.name, transaction).await
     }
 
     async fn get_from_collection(
@@ -131,14 +186,7 @@ impl AsyncLowLevelConnection for AsyncRemoteDatabase {
         id: DocumentId,
         collection: &CollectionName,
     ) -> Result<Option<OwnedDocument>, bonsaidb_core::Error> {
-        Ok(self
-            .client
-            .send_api_request(&Get {
-                database: self.name.to_string(),
-                collection: collection.clone(),
-                id,
-            })
-            .await?)
+        self.client.get_document(&self.name, collection, id).await
     }
 
     async fn get_multiple_from_collection(
@@ -194,6 +242,29 @@ impl AsyncLowLevelConnection for AsyncRemoteDatabase {
             .await?)
     }
 
+    async fn list_projected_from_collection(
+        &self,
+        ids: Range<DocumentId>,
+        order: Sort,
+        limit: Option<u32>,
+        collection: &CollectionName,
+        projection: &Name,
+    ) -> Result<Vec<OwnedDocument>, bonsaidb_core::Error> {
+        Ok(self
+            .client
+            .send_api_request(&ListProjected {
+                list: List {
+                    database: self.name.to_string(),
+                    collection: collection.clone(),
+                    ids,
+                    order,
+                    limit,
+                },
+                projection: projection.clone(),
+            })
+            .await?)
+    }
+
     async fn count_from_collection(
         &self,
         ids: Range<DocumentId>,
@@ -228,6 +299,7 @@ impl AsyncLowLevelConnection for AsyncRemoteDatabase {
         order: Sort,
         limit: Option<u32>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<Vec<schema::view::map::Serialized>, bonsaidb_core::Error> {
         Ok(self
             .client
@@ -238,6 +310,7 @@ impl AsyncLowLevelConnection for AsyncRemoteDatabase {
                 order,
                 limit,
                 access_policy,
+                read_options,
             })
             .await?)
     }
@@ -249,6 +322,7 @@ impl AsyncLowLevelConnection for AsyncRemoteDatabase {
         order: Sort,
         limit: Option<u32>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<schema::view::map::MappedSerializedDocuments, bonsaidb_core::Error> {
         Ok(self
             .client
@@ -259,15 +333,44 @@ impl AsyncLowLevelConnection for AsyncRemoteDatabase {
                 order,
                 limit,
                 access_policy,
+                read_options,
             }))
             .await?)
     }
 
+    async fn query_by_name_with_projected_docs(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+        projection: &Name,
+    ) -> Result<schema::view::map::MappedSerializedDocuments, bonsaidb_core::Error> {
+        Ok(self
+            .client
+            .send_api_request(&QueryWithProjectedDocs {
+                query: Query {
+                    database: self.name.to_string(),
+                    view: view.clone(),
+                    key,
+                    order,
+                    limit,
+                    access_policy,
+                    read_options,
+                },
+                projection: projection.clone(),
+            })
+            .await?)
+    }
+
     async fn reduce_by_name(
         &self,
         view: &ViewName,
         key: Option<SerializedQueryKey>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<Vec<u8>, bonsaidb_core::Error> {
         Ok(self
             .client
@@ -276,6 +379,7 @@ impl AsyncLowLevelConnection for AsyncRemoteDatabase {
                 view: view.clone(),
                 key,
                 access_policy,
+                read_options,
             })
             .await?
             .into_vec())
@@ -286,6 +390,7 @@ impl AsyncLowLevelConnection for AsyncRemoteDatabase {
         view: &ViewName,
         key: Option<SerializedQueryKey>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<Vec<MappedSerializedValue>, bonsaidb_core::Error> {
         Ok(self
             .client
@@ -294,10 +399,93 @@ impl AsyncLowLevelConnection for AsyncRemoteDatabase {
                 view: view.clone(),
                 key,
                 access_policy,
+                read_options,
             }))
             .await?)
     }
 
+    async fn aggregate_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        aggregations: &[Aggregation],
+        grouping: AggregationGrouping,
+        access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+    ) -> Result<Vec<SerializedAggregatedGroup>, bonsaidb_core::Error> {
+        Ok(self
+            .client
+            .send_api_request(&Aggregate {
+                database: self.name.to_string(),
+                view: view.clone(),
+                key,
+                aggregations: aggregations.to_vec(),
+                grouping,
+                access_policy,
+                read_options,
+            })
+            .await?)
+    }
+
+    async fn count_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        Ok(self
+            .client
+            .send_api_request(&CountView {
+                database: self.name.to_string(),
+                view: view.clone(),
+                key,
+                access_policy,
+                read_options,
+            })
+            .await?)
+    }
+
+    async fn document_mappings_by_name(
+        &self,
+        view: &ViewName,
+        document_id: DocumentId,
+        access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+    ) -> Result<Vec<schema::view::map::Serialized>, bonsaidb_core::Error> {
+        Ok(self
+            .client
+            .send_api_request(&DocumentMappings {
+                database: self.name.to_string(),
+                view: view.clone(),
+                document_id,
+                access_policy,
+                read_options,
+            })
+            .await?)
+    }
+
+    async fn query_join_by_name(
+        &self,
+        join: &ViewName,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+    ) -> Result<Vec<SerializedJoinedEntry>, bonsaidb_core::Error> {
+        Ok(self
+            .client
+            .send_api_request(&QueryJoin {
+                database: self.name.to_string(),
+                join: join.clone(),
+                key,
+                order,
+                limit,
+                access_policy,
+            })
+            .await?)
+    }
+
     async fn delete_docs_by_name(
         &self,
         view: &ViewName,
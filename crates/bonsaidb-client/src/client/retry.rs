@@ -0,0 +1,83 @@
+use std::time::Duration;
+
+use crate::Error;
+
+/// Controls how a client retries requests that time out or are interrupted by
+/// a disconnection.
+///
+/// Only requests for [idempotent](bonsaidb_core::api::Api::idempotent) Apis
+/// are retried, as retrying other requests could cause them to be executed
+/// more than once. The delay between attempts doubles after each retry, up to
+/// `max_delay`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct RetryPolicy {
+    /// The maximum number of times a request will be retried after its first
+    /// attempt fails.
+    pub max_retries: u32,
+    /// The amount of time to wait before the first retry.
+    pub initial_delay: Duration,
+    /// The maximum amount of time to wait between retries.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+impl RetryPolicy {
+    /// Returns a policy that never retries requests. This is the default
+    /// policy.
+    #[must_use]
+    pub const fn none() -> Self {
+        Self {
+            max_retries: 0,
+            initial_delay: Duration::ZERO,
+            max_delay: Duration::ZERO,
+        }
+    }
+
+    /// Returns a policy that retries idempotent requests up to `max_retries`
+    /// times, waiting `initial_delay` before the first retry and doubling the
+    /// delay after each retry, up to a maximum of 30 seconds.
+    #[must_use]
+    pub const fn exponential(max_retries: u32, initial_delay: Duration) -> Self {
+        Self {
+            max_retries,
+            initial_delay,
+            max_delay: Duration::from_secs(30),
+        }
+    }
+
+    /// Sets the maximum amount of time to wait between retries and returns
+    /// self.
+    #[must_use]
+    pub const fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Returns the amount of time to wait before retrying a request whose
+    /// `attempt`th retry failed with `error`, or `None` if the request should
+    /// not be retried.
+    pub(crate) fn retry_delay(&self, error: &Error, attempt: u32) -> Option<Duration> {
+        if attempt >= self.max_retries
+            || !matches!(error, Error::RequestTimeout | Error::Disconnected)
+        {
+            return None;
+        }
+
+        let delay = self
+            .initial_delay
+            .saturating_mul(2_u32.saturating_pow(attempt));
+        Some(delay.min(self.max_delay))
+    }
+}
+
+/// The timeout and retry settings applied to requests made by a client.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct RequestOptions {
+    pub timeout: Option<Duration>,
+    pub retry_policy: RetryPolicy,
+}
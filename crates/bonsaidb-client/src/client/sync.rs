@@ -1,27 +1,43 @@
 use std::collections::HashMap;
+#[cfg(all(unix, feature = "websockets"))]
+use std::path::Path;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::time::Duration;
 
-use bonsaidb_core::admin::{Admin, ADMIN_DATABASE_NAME};
-use bonsaidb_core::api;
+use bonsaidb_core::admin::{Admin, PermissionGroup, Role, User, ADMIN_DATABASE_NAME};
+use bonsaidb_core::api::{self, NamedQuery};
 use bonsaidb_core::arc_bytes::serde::Bytes;
 use bonsaidb_core::connection::{
-    AccessPolicy, Connection, Database, HasSchema, HasSession, IdentityReference,
-    LowLevelConnection, Range, SerializedQueryKey, Sort, StorageConnection,
+    AccessPolicy, Connection, Database, DatabaseQuery, HasSchema, HasSession, IdentityReference,
+    LowLevelConnection, Range, SerializedQueryKey, SessionId, SessionInfo, Sort, StorageConnection,
+    ViewReadOptions,
 };
-use bonsaidb_core::document::{DocumentId, Header, OwnedDocument};
+use bonsaidb_core::document::{CollectionDocument, DocumentId, Header, OwnedDocument};
 use bonsaidb_core::keyvalue::KeyValue;
 use bonsaidb_core::networking::{
-    AlterUserPermissionGroupMembership, AlterUserRoleMembership, ApplyTransaction, AssumeIdentity,
-    Compact, CompactCollection, CompactKeyValueStore, Count, CreateDatabase, CreateSubscriber,
-    CreateUser, DeleteDatabase, DeleteDocs, DeleteUser, ExecuteKeyOperation, Get, GetMultiple,
-    LastTransactionId, List, ListAvailableSchemas, ListDatabases, ListExecutedTransactions,
-    ListHeaders, Publish, PublishToAll, Query, QueryWithDocs, Reduce, ReduceGrouped, SubscribeTo,
-    UnsubscribeFrom, CURRENT_PROTOCOL_VERSION,
+    Aggregate, AlterRolePermissionGroupMembership, AlterUserPermissionGroupMembership,
+    AlterUserRoleMembership, AssumeIdentity, Capabilities, Compact, CompactCollection,
+    CompactKeyValueStore, Count, CountView, CreateDatabase, CreatePermissionGroup, CreateRole,
+    CreateSubscriber, CreateUser, DeleteDatabase, DeleteDocs, DeletePermissionGroup, DeleteRole,
+    DeleteUser, DocumentMappings, EffectivePermissions, ExecuteKeyOperation, ExecuteNamedQuery,
+    GetMultiple, LastTransactionId, List, ListAvailableSchemas, ListDatabases,
+    ListExecutedTransactions, ListHeaders, ListPermissionGroups, ListProjected, ListRoles,
+    ListSessions, ListUsers, PayloadLimits, Publish, PublishToAll, Query, QueryJoin, QueryWithDocs,
+    QueryWithProjectedDocs, Reduce, ReduceGrouped, RevokeSession, ServerEvent,
+    SetPermissionGroupStatements, SetUserDisabled, SubscribeTo, UnlockUser, UnsubscribeFrom,
+    UpdatePermissionGroupStatements, UpdateRolePermissionGroups, UserByName,
+    CURRENT_PROTOCOL_VERSION,
 };
+use bonsaidb_core::permissions::Statement;
 use bonsaidb_core::pubsub::{AsyncSubscriber, PubSub, Receiver, Subscriber};
+use bonsaidb_core::schema::view::aggregation::{
+    Aggregation, AggregationGrouping, SerializedAggregatedGroup,
+};
+use bonsaidb_core::schema::view::join::SerializedJoinedEntry;
 use bonsaidb_core::schema::view::map;
-use bonsaidb_core::schema::{CollectionName, ViewName};
+use bonsaidb_core::schema::{CollectionName, Name, ViewName};
+use bonsaidb_core::transaction::{Operation, OperationResult};
 use futures::Future;
 use tokio::runtime::{Handle, Runtime};
 use tokio::sync::oneshot;
@@ -29,7 +45,7 @@ use tokio::task::JoinHandle;
 use url::Url;
 
 use crate::builder::Blocking;
-use crate::client::ClientSession;
+use crate::client::{BlockingBatch, ClientSession, Destination, RequestOptions, RetryPolicy};
 use crate::{ApiError, AsyncClient, AsyncRemoteDatabase, AsyncRemoteSubscriber, Builder, Error};
 
 /// A BonsaiDb client that blocks the current thread when performing requests.
@@ -39,7 +55,15 @@ pub struct BlockingClient(pub(crate) AsyncClient);
 impl BlockingClient {
     /// Returns a builder for a new client connecting to `url`.
     pub fn build(url: Url) -> Builder<Blocking> {
-        Builder::new(url)
+        Builder::new(Destination::Url(url))
+    }
+
+    /// Returns a builder for a new client connecting to a server listening on
+    /// the Unix domain socket at `path`. The WebSocket protocol is used over
+    /// the socket.
+    #[cfg(all(unix, feature = "websockets"))]
+    pub fn build_unix_socket(path: impl AsRef<Path>) -> Builder<Blocking> {
+        Builder::new(Destination::UnixSocket(path.as_ref().to_path_buf()))
     }
 
     /// Initialize a client connecting to `url`. This client can be shared by
@@ -58,17 +82,40 @@ impl BlockingClient {
     /// the database is to operation.
     pub fn new(url: Url) -> Result<Self, Error> {
         AsyncClient::new_from_parts(
-            url,
+            Destination::Url(url),
             CURRENT_PROTOCOL_VERSION,
             HashMap::default(),
+            None,
+            None,
+            PayloadLimits::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            None,
             #[cfg(not(target_arch = "wasm32"))]
             None,
             #[cfg(not(target_arch = "wasm32"))]
             Handle::try_current().ok(),
+            RequestOptions::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            None,
+            #[cfg(feature = "websockets")]
+            None,
         )
         .map(Self)
     }
 
+    /// Returns the optional protocol features supported by the server this
+    /// client is connected to. See [`AsyncClient::server_capabilities()`].
+    pub fn server_capabilities(&self) -> Result<Capabilities, Error> {
+        self.0.server_capabilities_blocking()
+    }
+
+    /// Returns a receiver of the [`ServerEvent`]s sent by the server. See
+    /// [`AsyncClient::server_events()`].
+    #[must_use]
+    pub fn server_events(&self) -> flume::Receiver<ServerEvent> {
+        self.0.server_events()
+    }
+
     /// Sends an api `request`.
     pub fn send_api_request<Api: api::Api>(
         &self,
@@ -86,6 +133,28 @@ impl BlockingClient {
             .map(|_| ())
     }
 
+    /// Returns a clone of this client that waits at most `timeout` for the
+    /// server to respond to each request. See
+    /// [`AsyncClient::with_request_timeout()`] for more information.
+    #[must_use]
+    pub fn with_request_timeout(&self, timeout: Option<Duration>) -> Self {
+        Self(self.0.with_request_timeout(timeout))
+    }
+
+    /// Returns a clone of this client that retries requests using `policy`.
+    /// See [`AsyncClient::with_retry_policy()`] for more information.
+    #[must_use]
+    pub fn with_retry_policy(&self, policy: RetryPolicy) -> Self {
+        Self(self.0.with_retry_policy(policy))
+    }
+
+    /// Returns a new [`BlockingBatch`] of requests that are sent to the server
+    /// in a single round trip. See [`Batch`](crate::Batch) for more
+    /// information.
+    pub fn batch(&self) -> BlockingBatch {
+        BlockingBatch(self.0.batch())
+    }
+
     /// Returns a reference to an async-compatible version of this client.
     #[must_use]
     pub fn as_async(&self) -> &AsyncClient {
@@ -146,13 +215,15 @@ impl StorageConnection for BlockingClient {
         self.send_api_request(&DeleteDatabase {
             name: name.to_string(),
         })?;
+        self.0.invalidate_cached_database(name);
         Ok(())
     }
 
-    fn list_databases(
+    fn list_databases_matching(
         &self,
+        query: DatabaseQuery,
     ) -> Result<Vec<bonsaidb_core::connection::Database>, bonsaidb_core::Error> {
-        Ok(self.send_api_request(&ListDatabases)?)
+        Ok(self.send_api_request(&ListDatabases { query })?)
     }
 
     fn list_available_schemas(
@@ -176,6 +247,62 @@ impl StorageConnection for BlockingClient {
         })?)
     }
 
+    fn list_users(&self) -> Result<Vec<CollectionDocument<User>>, bonsaidb_core::Error> {
+        let users = self.send_api_request(&ListUsers)?;
+        users.iter().map(CollectionDocument::try_from).collect()
+    }
+
+    fn user_by_name(
+        &self,
+        username: &str,
+    ) -> Result<Option<CollectionDocument<User>>, bonsaidb_core::Error> {
+        let user = self.send_api_request(&UserByName {
+            username: username.to_string(),
+        })?;
+        user.as_ref().map(CollectionDocument::try_from).transpose()
+    }
+
+    fn disable_user<'user, U: bonsaidb_core::schema::Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<(), bonsaidb_core::Error> {
+        Ok(self.send_api_request(&SetUserDisabled {
+            user: user.name()?.into_owned(),
+            disabled: true,
+        })?)
+    }
+
+    fn enable_user<'user, U: bonsaidb_core::schema::Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<(), bonsaidb_core::Error> {
+        Ok(self.send_api_request(&SetUserDisabled {
+            user: user.name()?.into_owned(),
+            disabled: false,
+        })?)
+    }
+
+    fn unlock_user<'user, U: bonsaidb_core::schema::Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<(), bonsaidb_core::Error> {
+        Ok(self.send_api_request(&UnlockUser {
+            user: user.name()?.into_owned(),
+        })?)
+    }
+
+    fn effective_permissions<
+        'user,
+        U: bonsaidb_core::schema::Nameable<'user, u64> + Send + Sync,
+    >(
+        &self,
+        user: U,
+    ) -> Result<bonsaidb_core::permissions::Permissions, bonsaidb_core::Error> {
+        Ok(self.send_api_request(&EffectivePermissions {
+            user: user.name()?.into_owned(),
+        })?)
+    }
+
     #[cfg(feature = "password-hashing")]
     fn set_user_password<'user, U: bonsaidb_core::schema::Nameable<'user, u64> + Send + Sync>(
         &self,
@@ -203,6 +330,7 @@ impl StorageConnection for BlockingClient {
                 session: Arc::new(session),
                 connection_id: self.0.data.connection_counter.load(Ordering::SeqCst),
             },
+            request_options: self.0.request_options,
         }))
     }
 
@@ -217,6 +345,7 @@ impl StorageConnection for BlockingClient {
                 session: Arc::new(session),
                 connection_id: self.0.data.connection_counter.load(Ordering::SeqCst),
             },
+            request_options: self.0.request_options,
         }))
     }
 
@@ -291,6 +420,151 @@ impl StorageConnection for BlockingClient {
         })?;
         Ok(())
     }
+
+    fn create_permission_group(
+        &self,
+        name: &str,
+        statements: Vec<Statement>,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        Ok(self.send_api_request(&CreatePermissionGroup {
+            name: name.to_string(),
+            statements,
+        })?)
+    }
+
+    fn list_permission_groups(
+        &self,
+    ) -> Result<Vec<CollectionDocument<PermissionGroup>>, bonsaidb_core::Error> {
+        let groups = self.send_api_request(&ListPermissionGroups)?;
+        groups.iter().map(CollectionDocument::try_from).collect()
+    }
+
+    fn set_permission_group_statements<
+        'group,
+        G: bonsaidb_core::schema::Nameable<'group, u64> + Send + Sync,
+    >(
+        &self,
+        permission_group: G,
+        statements: Vec<Statement>,
+    ) -> Result<(), bonsaidb_core::Error> {
+        Ok(self.send_api_request(&SetPermissionGroupStatements {
+            group: permission_group.name()?.into_owned(),
+            statements,
+        })?)
+    }
+
+    fn update_permission_group_statements<
+        'group,
+        G: bonsaidb_core::schema::Nameable<'group, u64> + Send + Sync,
+    >(
+        &self,
+        permission_group: G,
+        expected_version: u64,
+        statements: Vec<Statement>,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        Ok(self.send_api_request(&UpdatePermissionGroupStatements {
+            group: permission_group.name()?.into_owned(),
+            expected_version,
+            statements,
+        })?)
+    }
+
+    fn delete_permission_group<
+        'group,
+        G: bonsaidb_core::schema::Nameable<'group, u64> + Send + Sync,
+    >(
+        &self,
+        permission_group: G,
+    ) -> Result<(), bonsaidb_core::Error> {
+        Ok(self.send_api_request(&DeletePermissionGroup {
+            group: permission_group.name()?.into_owned(),
+        })?)
+    }
+
+    fn create_role(&self, name: &str) -> Result<u64, bonsaidb_core::Error> {
+        Ok(self.send_api_request(&CreateRole {
+            name: name.to_string(),
+        })?)
+    }
+
+    fn list_roles(&self) -> Result<Vec<CollectionDocument<Role>>, bonsaidb_core::Error> {
+        let roles = self.send_api_request(&ListRoles)?;
+        roles.iter().map(CollectionDocument::try_from).collect()
+    }
+
+    fn add_permission_group_to_role<
+        'role,
+        'group,
+        R: bonsaidb_core::schema::Nameable<'role, u64> + Send + Sync,
+        G: bonsaidb_core::schema::Nameable<'group, u64> + Send + Sync,
+    >(
+        &self,
+        role: R,
+        permission_group: G,
+    ) -> Result<(), bonsaidb_core::Error> {
+        self.send_api_request(&AlterRolePermissionGroupMembership {
+            role: role.name()?.into_owned(),
+            group: permission_group.name()?.into_owned(),
+            should_be_member: true,
+        })?;
+        Ok(())
+    }
+
+    fn remove_permission_group_from_role<
+        'role,
+        'group,
+        R: bonsaidb_core::schema::Nameable<'role, u64> + Send + Sync,
+        G: bonsaidb_core::schema::Nameable<'group, u64> + Send + Sync,
+    >(
+        &self,
+        role: R,
+        permission_group: G,
+    ) -> Result<(), bonsaidb_core::Error> {
+        self.send_api_request(&AlterRolePermissionGroupMembership {
+            role: role.name()?.into_owned(),
+            group: permission_group.name()?.into_owned(),
+            should_be_member: false,
+        })?;
+        Ok(())
+    }
+
+    fn update_role_permission_groups<
+        'role,
+        R: bonsaidb_core::schema::Nameable<'role, u64> + Send + Sync,
+    >(
+        &self,
+        role: R,
+        expected_version: u64,
+        permission_groups: Vec<u64>,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        Ok(self.send_api_request(&UpdateRolePermissionGroups {
+            role: role.name()?.into_owned(),
+            expected_version,
+            groups: permission_groups,
+        })?)
+    }
+
+    fn delete_role<'role, R: bonsaidb_core::schema::Nameable<'role, u64> + Send + Sync>(
+        &self,
+        role: R,
+    ) -> Result<(), bonsaidb_core::Error> {
+        Ok(self.send_api_request(&DeleteRole {
+            role: role.name()?.into_owned(),
+        })?)
+    }
+
+    fn list_sessions<'user, U: bonsaidb_core::schema::Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<Vec<SessionInfo>, bonsaidb_core::Error> {
+        Ok(self.send_api_request(&ListSessions {
+            user: user.name()?.into_owned(),
+        })?)
+    }
+
+    fn revoke_session(&self, session_id: SessionId) -> Result<(), bonsaidb_core::Error> {
+        Ok(self.send_api_request(&RevokeSession(session_id))?)
+    }
 }
 
 impl HasSession for BlockingClient {
@@ -304,6 +578,57 @@ impl HasSession for BlockingClient {
 #[derive(Debug, Clone)]
 pub struct BlockingRemoteDatabase(AsyncRemoteDatabase);
 
+impl BlockingRemoteDatabase {
+    /// Returns a reference to an async-compatible version of this database.
+    #[must_use]
+    pub fn as_async(&self) -> &AsyncRemoteDatabase {
+        &self.0
+    }
+
+    /// Executes the [`NamedQuery`] `Q` that has been registered with the
+    /// server, using `parameters`.
+    ///
+    /// # Errors
+    ///
+    /// - [`bonsaidb_core::Error::NamedQueryNotFound`]: The server has no
+    ///   query registered with the name of `Q`.
+    /// - [`bonsaidb_core::Error::PermissionDenied`]: The client is not allowed
+    ///   to execute `Q` in this database.
+    pub fn execute_named_query<Q: NamedQuery>(
+        &self,
+        parameters: &Q::Parameters,
+    ) -> Result<Q::Output, bonsaidb_core::Error> {
+        let output = self
+            .0
+            .client
+            .send_blocking_api_request(&ExecuteNamedQuery {
+                database: self.0.name.to_string(),
+                name: Q::name(),
+                parameters: Bytes::from(pot::to_vec(parameters)?),
+            })?;
+        Ok(pot::from_slice(&output)?)
+    }
+
+    /// Sends `operations` to the server in chunks, and applies them atomically
+    /// as a single transaction once every chunk has been sent. See
+    /// [`AsyncRemoteDatabase::apply_streamed_transaction()`].
+    ///
+    /// # Errors
+    ///
+    /// - [`bonsaidb_core::Error::ApiNotFound`]: The server does not support
+    ///   [`Capabilities::STREAMED_TRANSACTIONS`].
+    /// - [`bonsaidb_core::Error::TransactionTooLarge`]: The combined size of
+    ///   the documents in `operations` exceeds the payload limits.
+    pub fn apply_streamed_transaction(
+        &self,
+        operations: impl IntoIterator<Item = Operation>,
+    ) -> Result<Vec<OperationResult>, bonsaidb_core::Error> {
+        self.0
+            .client
+            .apply_streamed_transaction_blocking(&self.0.name, operations)
+    }
+}
+
 impl Connection for BlockingRemoteDatabase {
     type Storage = BlockingClient;
 
@@ -355,10 +680,9 @@ impl LowLevelConnection for BlockingRemoteDatabase {
         &self,
         transaction: bonsaidb_core::transaction::Transaction,
     ) -> Result<Vec<bonsaidb_core::transaction::OperationResult>, bonsaidb_core::Error> {
-        Ok(self.0.client.send_blocking_api_request(&ApplyTransaction {
-            database: self.0.name.to_string(),
-            transaction,
-        })?)
+        self.0
+            .client
+            .apply_transaction_blocking(&self.0.name, transaction)
     }
 
     fn get_from_collection(
@@ -366,11 +690,9 @@ impl LowLevelConnection for BlockingRemoteDatabase {
         id: bonsaidb_core::document::DocumentId,
         collection: &CollectionName,
     ) -> Result<Option<OwnedDocument>, bonsaidb_core::Error> {
-        Ok(self.0.client.send_blocking_api_request(&Get {
-            database: self.0.name.to_string(),
-            collection: collection.clone(),
-            id,
-        })?)
+        self.0
+            .client
+            .get_document_blocking(&self.0.name, collection, id)
     }
 
     fn get_multiple_from_collection(
@@ -417,6 +739,26 @@ impl LowLevelConnection for BlockingRemoteDatabase {
         }))?)
     }
 
+    fn list_projected_from_collection(
+        &self,
+        ids: Range<DocumentId>,
+        order: Sort,
+        limit: Option<u32>,
+        collection: &CollectionName,
+        projection: &Name,
+    ) -> Result<Vec<OwnedDocument>, bonsaidb_core::Error> {
+        Ok(self.0.client.send_blocking_api_request(&ListProjected {
+            list: List {
+                database: self.0.name.to_string(),
+                collection: collection.clone(),
+                ids,
+                order,
+                limit,
+            },
+            projection: projection.clone(),
+        })?)
+    }
+
     fn count_from_collection(
         &self,
         ids: Range<bonsaidb_core::document::DocumentId>,
@@ -447,6 +789,7 @@ impl LowLevelConnection for BlockingRemoteDatabase {
         order: Sort,
         limit: Option<u32>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<Vec<map::Serialized>, bonsaidb_core::Error> {
         Ok(self.0.client.send_blocking_api_request(&Query {
             database: self.0.name.to_string(),
@@ -455,6 +798,7 @@ impl LowLevelConnection for BlockingRemoteDatabase {
             order,
             limit,
             access_policy,
+            read_options,
         })?)
     }
 
@@ -465,6 +809,7 @@ impl LowLevelConnection for BlockingRemoteDatabase {
         order: Sort,
         limit: Option<u32>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<bonsaidb_core::schema::view::map::MappedSerializedDocuments, bonsaidb_core::Error>
     {
         Ok(self
@@ -477,14 +822,44 @@ impl LowLevelConnection for BlockingRemoteDatabase {
                 order,
                 limit,
                 access_policy,
+                read_options,
             }))?)
     }
 
+    fn query_by_name_with_projected_docs(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+        projection: &Name,
+    ) -> Result<bonsaidb_core::schema::view::map::MappedSerializedDocuments, bonsaidb_core::Error>
+    {
+        Ok(self
+            .0
+            .client
+            .send_blocking_api_request(&QueryWithProjectedDocs {
+                query: Query {
+                    database: self.0.name.to_string(),
+                    view: view.clone(),
+                    key,
+                    order,
+                    limit,
+                    access_policy,
+                    read_options,
+                },
+                projection: projection.clone(),
+            })?)
+    }
+
     fn reduce_by_name(
         &self,
         view: &bonsaidb_core::schema::ViewName,
         key: Option<SerializedQueryKey>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<Vec<u8>, bonsaidb_core::Error> {
         Ok(self
             .0
@@ -494,6 +869,7 @@ impl LowLevelConnection for BlockingRemoteDatabase {
                 view: view.clone(),
                 key,
                 access_policy,
+                read_options,
             })?
             .into_vec())
     }
@@ -503,6 +879,7 @@ impl LowLevelConnection for BlockingRemoteDatabase {
         view: &bonsaidb_core::schema::ViewName,
         key: Option<SerializedQueryKey>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<Vec<bonsaidb_core::schema::view::map::MappedSerializedValue>, bonsaidb_core::Error>
     {
         Ok(self
@@ -513,9 +890,80 @@ impl LowLevelConnection for BlockingRemoteDatabase {
                 view: view.clone(),
                 key,
                 access_policy,
+                read_options,
             }))?)
     }
 
+    fn aggregate_by_name(
+        &self,
+        view: &bonsaidb_core::schema::ViewName,
+        key: Option<SerializedQueryKey>,
+        aggregations: &[Aggregation],
+        grouping: AggregationGrouping,
+        access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+    ) -> Result<Vec<SerializedAggregatedGroup>, bonsaidb_core::Error> {
+        Ok(self.0.client.send_blocking_api_request(&Aggregate {
+            database: self.0.name.to_string(),
+            view: view.clone(),
+            key,
+            aggregations: aggregations.to_vec(),
+            grouping,
+            access_policy,
+            read_options,
+        })?)
+    }
+
+    fn count_by_name(
+        &self,
+        view: &bonsaidb_core::schema::ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        Ok(self.0.client.send_blocking_api_request(&CountView {
+            database: self.0.name.to_string(),
+            view: view.clone(),
+            key,
+            access_policy,
+            read_options,
+        })?)
+    }
+
+    fn document_mappings_by_name(
+        &self,
+        view: &bonsaidb_core::schema::ViewName,
+        document_id: DocumentId,
+        access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+    ) -> Result<Vec<map::Serialized>, bonsaidb_core::Error> {
+        Ok(self.0.client.send_blocking_api_request(&DocumentMappings {
+            database: self.0.name.to_string(),
+            view: view.clone(),
+            document_id,
+            access_policy,
+            read_options,
+        })?)
+    }
+
+    fn query_join_by_name(
+        &self,
+        join: &bonsaidb_core::schema::ViewName,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+    ) -> Result<Vec<SerializedJoinedEntry>, bonsaidb_core::Error> {
+        Ok(self.0.client.send_blocking_api_request(&QueryJoin {
+            database: self.0.name.to_string(),
+            join: join.clone(),
+            key,
+            order,
+            limit,
+            access_policy,
+        })?)
+    }
+
     fn delete_docs_by_name(
         &self,
         view: &bonsaidb_core::schema::ViewName,
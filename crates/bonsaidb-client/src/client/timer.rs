@@ -0,0 +1,48 @@
+use std::future::Future;
+use std::time::Duration;
+
+/// Waits for `duration` to elapse.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await;
+}
+
+/// Waits for `duration` to elapse.
+///
+/// Browsers and web workers do not provide a tokio runtime, so the delay is
+/// scheduled using the global `setTimeout()` function.
+#[cfg(target_arch = "wasm32")]
+pub async fn sleep(duration: Duration) {
+    use js_sys::{Function, Promise, Reflect};
+    use wasm_bindgen::{JsCast, JsValue};
+
+    // setTimeout() treats delays that don't fit in an i32 as 0.
+    let millis = i32::try_from(duration.as_millis()).unwrap_or(i32::MAX);
+    let promise = Promise::new(&mut |resolve, _reject| {
+        let global = js_sys::global();
+        let set_timeout = Reflect::get(&global, &JsValue::from_str("setTimeout"))
+            .ok()
+            .and_then(|set_timeout| set_timeout.dyn_into::<Function>().ok());
+        let scheduled = set_timeout.map_or(false, |set_timeout| {
+            set_timeout
+                .call2(&global, &resolve, &JsValue::from(millis))
+                .is_ok()
+        });
+        if !scheduled {
+            drop(resolve.call0(&JsValue::NULL));
+        }
+    });
+    drop(wasm_bindgen_futures::JsFuture::from(promise).await);
+}
+
+/// Waits for `future` to complete, returning `None` if `duration` elapses
+/// first.
+pub async fn timeout<F: Future>(duration: Duration, future: F) -> Option<F::Output> {
+    futures::pin_mut!(future);
+    let delay = sleep(duration);
+    futures::pin_mut!(delay);
+    match futures::future::select(future, delay).await {
+        futures::future::Either::Left((output, _)) => Some(output),
+        futures::future::Either::Right(((), _)) => None,
+    }
+}
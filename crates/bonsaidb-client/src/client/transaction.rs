@@ -0,0 +1,186 @@
+use bonsaidb_core::networking::{
+    ApplyTransaction, BeginTransaction, Capabilities, CommitTransaction, PayloadLimits,
+    PushTransactionOperations,
+};
+use bonsaidb_core::transaction::{Operation, OperationResult, Transaction};
+
+use crate::AsyncClient;
+
+impl AsyncClient {
+    /// Applies `transaction` to `database`. Transactions whose documents are
+    /// larger than [`PayloadLimits::transaction_chunk_size`] are streamed to
+    /// servers that support [`Capabilities::STREAMED_TRANSACTIONS`].
+    pub(crate) async fn apply_transaction(
+        &self,
+        database: &str,
+        transaction: Transaction,
+    ) -> Result<Vec<OperationResult>, bonsaidb_core::Error> {
+        let limits = self.payload_limits();
+        let size = limits.check_operations(&transaction.operations, 0)?;
+        if size > limits.transaction_chunk_size
+            && self
+                .server_capabilities()
+                .await?
+                .contains(Capabilities::STREAMED_TRANSACTIONS)
+        {
+            return self
+                .apply_streamed_transaction(database, transaction.operations)
+                .await;
+        }
+
+        let results = self
+            .send_api_request(&ApplyTransaction {
+                database: database.to_string(),
+                transaction,
+            })
+            .await?;
+        self.invalidate_cached_documents(database, &results);
+        Ok(results)
+    }
+
+    /// Sends `operations` to `database` in chunks, and applies them atomically
+    /// once every chunk has been sent.
+    pub(crate) async fn apply_streamed_transaction<Operations>(
+        &self,
+        database: &str,
+        operations: Operations,
+    ) -> Result<Vec<OperationResult>, bonsaidb_core::Error>
+    where
+        Operations: IntoIterator<Item = Operation> + Send,
+        Operations::IntoIter: Send,
+    {
+        let transaction_id = self
+            .send_api_request(&BeginTransaction {
+                database: database.to_string(),
+            })
+            .await?;
+        let mut chunks = OperationChunks::new(self.payload_limits());
+        for operation in operations {
+            if let Some(operations) = chunks.push(operation)? {
+                self.send_api_request(&PushTransactionOperations {
+                    transaction_id,
+                    operations,
+                })
+                .await?;
+            }
+        }
+        if let Some(operations) = chunks.finish() {
+            self.send_api_request(&PushTransactionOperations {
+                transaction_id,
+                operations,
+            })
+            .await?;
+        }
+        let results = self
+            .send_api_request(&CommitTransaction { transaction_id })
+            .await?;
+        self.invalidate_cached_documents(database, &results);
+        Ok(results)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn apply_transaction_blocking(
+        &self,
+        database: &str,
+        transaction: Transaction,
+    ) -> Result<Vec<OperationResult>, bonsaidb_core::Error> {
+        let limits = self.payload_limits();
+        let size = limits.check_operations(&transaction.operations, 0)?;
+        if size > limits.transaction_chunk_size
+            && self
+                .server_capabilities_blocking()?
+                .contains(Capabilities::STREAMED_TRANSACTIONS)
+        {
+            return self.apply_streamed_transaction_blocking(database, transaction.operations);
+        }
+
+        let results = self.send_blocking_api_request(&ApplyTransaction {
+            database: database.to_string(),
+            transaction,
+        })?;
+        self.invalidate_cached_documents(database, &results);
+        Ok(results)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn apply_streamed_transaction_blocking(
+        &self,
+        database: &str,
+        operations: impl IntoIterator<Item = Operation>,
+    ) -> Result<Vec<OperationResult>, bonsaidb_core::Error> {
+        let transaction_id = self.send_blocking_api_request(&BeginTransaction {
+            database: database.to_string(),
+        })?;
+        let mut chunks = OperationChunks::new(self.payload_limits());
+        for operation in operations {
+            if let Some(operations) = chunks.push(operation)? {
+                self.send_blocking_api_request(&PushTransactionOperations {
+                    transaction_id,
+                    operations,
+                })?;
+            }
+        }
+        if let Some(operations) = chunks.finish() {
+            self.send_blocking_api_request(&PushTransactionOperations {
+                transaction_id,
+                operations,
+            })?;
+        }
+        let results = self.send_blocking_api_request(&CommitTransaction { transaction_id })?;
+        self.invalidate_cached_documents(database, &results);
+        Ok(results)
+    }
+}
+
+/// Groups the operations of a streamed transaction into chunks no larger than
+/// [`PayloadLimits::transaction_chunk_size`], checking the transaction against
+/// the other limits as each operation is added.
+struct OperationChunks<'a> {
+    limits: &'a PayloadLimits,
+    chunk: Vec<Operation>,
+    chunk_size: usize,
+    transaction_size: usize,
+}
+
+impl<'a> OperationChunks<'a> {
+    fn new(limits: &'a PayloadLimits) -> Self {
+        Self {
+            limits,
+            chunk: Vec::new(),
+            chunk_size: 0,
+            transaction_size: 0,
+        }
+    }
+
+    /// Adds `operation` to the current chunk. If the current chunk is full, it
+    /// is returned and `operation` begins the next chunk.
+    fn push(
+        &mut self,
+        operation: Operation,
+    ) -> Result<Option<Vec<Operation>>, bonsaidb_core::Error> {
+        self.transaction_size = self
+            .limits
+            .check_operations(std::slice::from_ref(&operation), self.transaction_size)?;
+        let size = PayloadLimits::operation_size(&operation);
+        let full_chunk = if !self.chunk.is_empty()
+            && self.chunk_size.saturating_add(size) > self.limits.transaction_chunk_size
+        {
+            self.chunk_size = 0;
+            Some(std::mem::take(&mut self.chunk))
+        } else {
+            None
+        };
+        self.chunk_size += size;
+        self.chunk.push(operation);
+        Ok(full_chunk)
+    }
+
+    /// Returns the last chunk, if it contains any operations.
+    fn finish(self) -> Option<Vec<Operation>> {
+        if self.chunk.is_empty() {
+            None
+        } else {
+            Some(self.chunk)
+        }
+    }
+}
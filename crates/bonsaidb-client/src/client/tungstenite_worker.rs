@@ -3,30 +3,34 @@ use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 
 use bonsaidb_core::api::ApiName;
-use bonsaidb_core::networking::Payload;
+use bonsaidb_core::networking::{Payload, PayloadLimits};
 use bonsaidb_utils::fast_async_lock;
 use flume::Receiver;
 use futures::stream::{SplitSink, SplitStream};
 use futures::{SinkExt, StreamExt};
-use tokio::net::TcpStream;
-use tokio_tungstenite::tungstenite::handshake::client::generate_key;
+use parking_lot::Mutex;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::handshake::client::{generate_key, Request};
 use tokio_tungstenite::tungstenite::Message;
-use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
-use url::Url;
+use tokio_tungstenite::WebSocketStream;
 
 use super::PendingRequest;
 use crate::client::{
-    disconnect_pending_requests, AnyApiCallback, OutstandingRequestMapHandle, SubscriberMap,
+    disconnect_pending_requests, AnyApiCallback, Destination, HandshakeState,
+    OutstandingRequestMapHandle, SubscriberMap, WebSocketKeepalive,
 };
 use crate::Error;
 
 pub async fn reconnecting_client_loop(
-    url: Url,
+    destination: Destination,
     protocol_version: &str,
     request_receiver: Receiver<PendingRequest>,
     custom_apis: Arc<HashMap<ApiName, Option<Arc<dyn AnyApiCallback>>>>,
     subscribers: SubscriberMap,
     connection_counter: Arc<AtomicU32>,
+    handshake: Arc<HandshakeState>,
+    keepalive: Option<WebSocketKeepalive>,
 ) -> Result<(), Error> {
     let mut pending_error = None;
     while let Ok(request) = {
@@ -39,92 +43,228 @@ pub async fn reconnecting_client_loop(
         }
 
         connection_counter.fetch_add(1, Ordering::SeqCst);
-        let (stream, _) = match tokio_tungstenite::connect_async(
-            tokio_tungstenite::tungstenite::handshake::client::Request::get(url.as_str())
-                .header("Sec-WebSocket-Protocol", protocol_version)
-                .header("Sec-WebSocket-Version", "13")
-                .header("Sec-WebSocket-Key", generate_key())
-                .header("Host", url.host_str().expect("no host"))
-                .header("Connection", "Upgrade")
-                .header("Upgrade", "websocket")
-                .body(())
-                .unwrap(),
-        )
-        .await
-        {
-            Ok(result) => result,
-            Err(err) => {
-                drop(request.responder.send(Err(Error::from(err))));
-                continue;
+        pending_error = match &destination {
+            Destination::Url(url) => {
+                let handshake = handshake_request(
+                    url.as_str(),
+                    url.host_str().expect("no host"),
+                    protocol_version,
+                );
+                match tokio_tungstenite::connect_async(handshake).await {
+                    Ok((stream, _)) => {
+                        process_connection(
+                            stream,
+                            request,
+                            &request_receiver,
+                            &custom_apis,
+                            &handshake,
+                            keepalive.as_ref(),
+                        )
+                        .await?
+                    }
+                    Err(err) => {
+                        drop(request.responder.send(Err(Error::from(err))));
+                        continue;
+                    }
+                }
+            }
+            #[cfg(unix)]
+            Destination::UnixSocket(path) => {
+                // The URL is only used for the HTTP upgrade request, as the
+                // socket's path determines what is connected to.
+                let handshake = handshake_request("ws://localhost/", "localhost", protocol_version);
+                let stream = match tokio::net::UnixStream::connect(path).await {
+                    Ok(stream) => tokio_tungstenite::client_async(handshake, stream).await,
+                    Err(err) => Err(tokio_tungstenite::tungstenite::Error::from(err)),
+                };
+                match stream {
+                    Ok((stream, _)) => {
+                        process_connection(
+                            stream,
+                            request,
+                            &request_receiver,
+                            &custom_apis,
+                            &handshake,
+                            keepalive.as_ref(),
+                        )
+                        .await?
+                    }
+                    Err(err) => {
+                        drop(request.responder.send(Err(Error::from(err))));
+                        continue;
+                    }
+                }
             }
         };
+    }
+
+    Ok(())
+}
+
+fn handshake_request(url: &str, host: &str, protocol_version: &str) -> Request {
+    Request::get(url)
+        .header("Sec-WebSocket-Protocol", protocol_version)
+        .header("Sec-WebSocket-Version", "13")
+        .header("Sec-WebSocket-Key", generate_key())
+        .header("Host", host)
+        .header("Connection", "Upgrade")
+        .header("Upgrade", "websocket")
+        .body(())
+        .unwrap()
+}
 
-        let (mut sender, receiver) = stream.split();
+/// Sends `request` and all subsequent requests over `stream` until the
+/// connection is lost. The error that caused the disconnection is returned, if
+/// any.
+async fn process_connection<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: WebSocketStream<S>,
+    mut request: PendingRequest,
+    request_receiver: &Receiver<PendingRequest>,
+    custom_apis: &HashMap<ApiName, Option<Arc<dyn AnyApiCallback>>>,
+    handshake: &HandshakeState,
+    keepalive: Option<&WebSocketKeepalive>,
+) -> Result<Option<Error>, Error> {
+    let (mut sender, receiver) = stream.split();
+    let last_received = Mutex::new(Instant::now());
 
-        let outstanding_requests = OutstandingRequestMapHandle::default();
+    let outstanding_requests = OutstandingRequestMapHandle::default();
+    {
+        let mut outstanding_requests = fast_async_lock!(outstanding_requests);
+        if let Err(err) = sender
+            .send(Message::Binary(bincode::serialize(
+                &handshake.connected()?,
+            )?))
+            .await
         {
-            let mut outstanding_requests = fast_async_lock!(outstanding_requests);
-            if let Err(err) = sender
-                .send(Message::Binary(bincode::serialize(&request.request)?))
-                .await
-            {
-                drop(request.responder.send(Err(Error::from(err))));
-                continue;
-            }
-            outstanding_requests.insert(
-                request.request.id.expect("all requests must have ids"),
-                request,
-            );
+            drop(request.responder.send(Err(Error::from(err))));
+            return Ok(None);
         }
-
-        if let Err(err) = tokio::try_join!(
-            request_sender(&request_receiver, sender, outstanding_requests.clone()),
-            response_processor(receiver, outstanding_requests.clone(), &custom_apis,)
-        ) {
-            // Our socket was disconnected, clear the outstanding requests before returning.
-            log::error!("Error on socket {:?}", err);
-            pending_error = Some(err);
-            disconnect_pending_requests(&outstanding_requests, &mut pending_error).await;
+        handshake.compress(&mut request.request);
+        if let Err(err) = sender
+            .send(Message::Binary(bincode::serialize(&request.request)?))
+            .await
+        {
+            drop(request.responder.send(Err(Error::from(err))));
+            return Ok(None);
         }
+        outstanding_requests.insert(
+            request.request.id.expect("all requests must have ids"),
+            request,
+        );
     }
 
-    Ok(())
+    let mut pending_error = None;
+    if let Err(err) = tokio::try_join!(
+        request_sender(
+            request_receiver,
+            sender,
+            outstanding_requests.clone(),
+            handshake,
+            keepalive,
+            &last_received,
+        ),
+        response_processor(
+            receiver,
+            outstanding_requests.clone(),
+            custom_apis,
+            handshake.limits(),
+            &last_received,
+        )
+    ) {
+        // Our socket was disconnected, clear the outstanding requests before returning.
+        log::error!("Error on socket {:?}", err);
+        pending_error = Some(err);
+        disconnect_pending_requests(&outstanding_requests, &mut pending_error).await;
+    }
+
+    Ok(pending_error)
 }
 
-async fn request_sender(
+async fn request_sender<S: AsyncRead + AsyncWrite + Unpin>(
     request_receiver: &Receiver<PendingRequest>,
-    mut sender: SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>,
+    mut sender: SplitSink<WebSocketStream<S>, Message>,
     outstanding_requests: OutstandingRequestMapHandle,
+    handshake: &HandshakeState,
+    keepalive: Option<&WebSocketKeepalive>,
+    last_received: &Mutex<Instant>,
 ) -> Result<(), Error> {
-    while let Ok(pending) = request_receiver.recv_async().await {
-        let mut outstanding_requests = fast_async_lock!(outstanding_requests);
-        sender
-            .send(Message::Binary(bincode::serialize(&pending.request)?))
-            .await?;
+    let mut next_ping = keepalive.map(|keepalive| Instant::now() + keepalive.ping_interval);
+    let mut ping_sent_at = None;
+    loop {
+        let keepalive_deadline = match (keepalive, ping_sent_at) {
+            (Some(keepalive), Some(sent_at)) => Some(sent_at + keepalive.pong_timeout),
+            _ => next_ping,
+        };
+        tokio::select! {
+            pending = request_receiver.recv_async() => {
+                let Ok(mut pending) = pending else { break };
+                handshake.compress(&mut pending.request);
+                let mut outstanding_requests = fast_async_lock!(outstanding_requests);
+                sender
+                    .send(Message::Binary(bincode::serialize(&pending.request)?))
+                    .await?;
 
-        outstanding_requests.insert(
-            pending.request.id.expect("all requests must have ids"),
-            pending,
-        );
+                outstanding_requests.insert(
+                    pending.request.id.expect("all requests must have ids"),
+                    pending,
+                );
+            }
+            () = sleep_until(keepalive_deadline) => {
+                let keepalive = keepalive.expect("deadlines require keepalive");
+                if let Some(sent_at) = ping_sent_at.take() {
+                    if *last_received.lock() < sent_at {
+                        log::warn!("websocket connection is unresponsive, disconnecting");
+                        if let Some(on_unhealthy) = &keepalive.on_unhealthy {
+                            on_unhealthy();
+                        }
+                        drop(sender.send(Message::Close(None)).await);
+                        break;
+                    }
+                    next_ping = Some(sent_at + keepalive.ping_interval);
+                } else {
+                    sender.send(Message::Ping(Vec::new())).await?;
+                    ping_sent_at = Some(Instant::now());
+                }
+            }
+        }
     }
 
     Err(Error::Disconnected)
 }
 
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => futures::future::pending().await,
+    }
+}
+
 #[allow(clippy::collapsible_else_if)] // not possible due to cfg statement
-async fn response_processor(
-    mut receiver: SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>,
+async fn response_processor<S: AsyncRead + AsyncWrite + Unpin>(
+    mut receiver: SplitStream<WebSocketStream<S>>,
     outstanding_requests: OutstandingRequestMapHandle,
     custom_apis: &HashMap<ApiName, Option<Arc<dyn AnyApiCallback>>>,
+    limits: &PayloadLimits,
+    last_received: &Mutex<Instant>,
 ) -> Result<(), Error> {
     while let Some(message) = receiver.next().await {
         let message = message?;
+        *last_received.lock() = Instant::now();
         match message {
             Message::Binary(response) => {
                 let payload = bincode::deserialize::<Payload>(&response)?;
 
-                super::process_response_payload(payload, &outstanding_requests, custom_apis).await;
+                super::process_response_payload(
+                    payload,
+                    &outstanding_requests,
+                    custom_apis,
+                    limits,
+                )
+                .await;
             }
+            // Pings are answered by tungstenite, and pongs are only tracked
+            // through `last_received`.
+            Message::Ping(_) | Message::Pong(_) => {}
             other => {
                 log::error!("Unexpected websocket message: {:?}", other);
             }
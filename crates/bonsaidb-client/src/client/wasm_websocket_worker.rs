@@ -3,7 +3,7 @@ use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::{Arc, Mutex};
 
 use bonsaidb_core::api::ApiName;
-use bonsaidb_core::networking::Payload;
+use bonsaidb_core::networking::{Payload, PayloadLimits};
 use bonsaidb_utils::fast_async_lock;
 use flume::Receiver;
 use url::Url;
@@ -12,11 +12,12 @@ use wasm_bindgen::{JsCast, JsValue};
 use web_sys::{CloseEvent, ErrorEvent, MessageEvent, WebSocket};
 
 use crate::client::{
-    disconnect_pending_requests, AnyApiCallback, OutstandingRequestMapHandle, PendingRequest,
-    SubscriberMap,
+    disconnect_pending_requests, AnyApiCallback, HandshakeState, OutstandingRequestMapHandle,
+    PendingRequest, SubscriberMap,
 };
 use crate::Error;
 
+#[allow(clippy::too_many_arguments)]
 pub fn spawn_client(
     url: Arc<Url>,
     protocol_version: &'static str,
@@ -24,6 +25,7 @@ pub fn spawn_client(
     custom_apis: Arc<HashMap<ApiName, Option<Arc<dyn AnyApiCallback>>>>,
     subscribers: SubscriberMap,
     connection_counter: Arc<AtomicU32>,
+    handshake: Arc<HandshakeState>,
     pending_error: Option<Error>,
 ) {
     wasm_bindgen_futures::spawn_local(create_websocket(
@@ -33,10 +35,12 @@ pub fn spawn_client(
         custom_apis,
         subscribers,
         connection_counter,
+        handshake,
         pending_error,
     ));
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn create_websocket(
     url: Arc<Url>,
     protocol_version: &'static str,
@@ -44,6 +48,7 @@ async fn create_websocket(
     custom_apis: Arc<HashMap<ApiName, Option<Arc<dyn AnyApiCallback>>>>,
     subscribers: SubscriberMap,
     connection_counter: Arc<AtomicU32>,
+    handshake: Arc<HandshakeState>,
     pending_error: Option<Error>,
 ) {
     subscribers.clear();
@@ -75,6 +80,7 @@ async fn create_websocket(
                 custom_apis.clone(),
                 subscribers,
                 connection_counter,
+                handshake,
                 None,
             );
             return;
@@ -98,10 +104,15 @@ async fn create_websocket(
         initial_request.clone(),
         outstanding_requests.clone(),
         ws.clone(),
+        handshake.clone(),
     );
     ws.set_onopen(Some(onopen_callback.as_ref().unchecked_ref()));
 
-    let onmessage_callback = on_message_callback(outstanding_requests.clone(), custom_apis.clone());
+    let onmessage_callback = on_message_callback(
+        outstanding_requests.clone(),
+        custom_apis.clone(),
+        *handshake.limits(),
+    );
     ws.set_onmessage(Some(onmessage_callback.as_ref().unchecked_ref()));
 
     let onerror_callback =
@@ -119,6 +130,7 @@ async fn create_websocket(
         custom_apis.clone(),
         subscribers.clone(),
         connection_counter.clone(),
+        handshake,
     );
     ws.set_onclose(Some(onclose_callback.as_ref().unchecked_ref()));
 }
@@ -153,13 +165,21 @@ fn on_open_callback(
     initial_request: Arc<Mutex<Option<PendingRequest>>>,
     requests: OutstandingRequestMapHandle,
     ws: WebSocket,
+    handshake: Arc<HandshakeState>,
 ) -> JsValue {
     Closure::once_into_js(move || {
         wasm_bindgen_futures::spawn_local(async move {
             if let Some(initial_request) = take_initial_request(&initial_request) {
-                if send_request(&ws, initial_request, &requests).await {
+                match handshake
+                    .connected()
+                    .and_then(|handshake| Ok(bincode::serialize(&handshake)?))
+                {
+                    Ok(bytes) => drop(ws.send_with_u8_array(&bytes)),
+                    Err(err) => log::error!("error serializing request: {err:?}"),
+                }
+                if send_request(&ws, initial_request, &requests, &handshake).await {
                     while let Ok(pending) = request_receiver.recv_async().await {
-                        if !send_request(&ws, pending, &requests).await {
+                        if !send_request(&ws, pending, &requests, &handshake).await {
                             break;
                         }
                     }
@@ -175,9 +195,11 @@ fn on_open_callback(
 #[allow(clippy::future_not_send)]
 async fn send_request(
     ws: &WebSocket,
-    pending: PendingRequest,
+    mut pending: PendingRequest,
     requests: &OutstandingRequestMapHandle,
+    handshake: &HandshakeState,
 ) -> bool {
+    handshake.compress(&mut pending.request);
     let mut outstanding_requests = fast_async_lock!(requests);
     let bytes = match bincode::serialize(&pending.request) {
         Ok(bytes) => bytes,
@@ -210,6 +232,7 @@ async fn send_request(
 fn on_message_callback(
     outstanding_requests: OutstandingRequestMapHandle,
     custom_apis: Arc<HashMap<ApiName, Option<Arc<dyn AnyApiCallback>>>>,
+    limits: PayloadLimits,
 ) -> JsValue {
     Closure::wrap(Box::new(move |e: MessageEvent| {
         // Handle difference Text/Binary,...
@@ -226,7 +249,13 @@ fn on_message_callback(
             let outstanding_requests = outstanding_requests.clone();
             let custom_apis = custom_apis.clone();
             wasm_bindgen_futures::spawn_local(async move {
-                super::process_response_payload(payload, &outstanding_requests, &custom_apis).await;
+                super::process_response_payload(
+                    payload,
+                    &outstanding_requests,
+                    &custom_apis,
+                    &limits,
+                )
+                .await;
             });
         } else {
             log::warn!("Unexpected WebSocket message received: {:?}", e.data());
@@ -279,6 +308,7 @@ fn on_close_callback(
     custom_apis: Arc<HashMap<ApiName, Option<Arc<dyn AnyApiCallback>>>>,
     subscribers: SubscriberMap,
     connection_counter: Arc<AtomicU32>,
+    handshake: Arc<HandshakeState>,
 ) -> JsValue {
     Closure::once_into_js(move |c: CloseEvent| {
         let _ = shutdown.send(());
@@ -308,6 +338,7 @@ fn on_close_callback(
                 custom_apis.clone(),
                 subscribers,
                 connection_counter,
+                handshake,
                 pending_error,
             );
         });
@@ -1,5 +1,6 @@
 use bonsaidb_core::arc_bytes::serde::Bytes;
 use bonsaidb_core::schema::Name;
+use bonsaidb_core::ErrorCode;
 
 /// Errors related to working with [`Client`](crate::Client)
 #[derive(thiserror::Error, Debug)]
@@ -21,6 +22,16 @@ pub enum Error {
     #[error("unexpected disconnection")]
     Disconnected,
 
+    /// The server did not respond to a request before the client's request
+    /// timeout elapsed.
+    #[error("request timed out")]
+    RequestTimeout,
+
+    /// A write could not be queued because the client's offline queue is
+    /// full.
+    #[error("offline write queue is full")]
+    OfflineQueueFull,
+
     /// The connection was interrupted.
     #[error("unexpected disconnection")]
     Core(#[from] bonsaidb_core::Error),
@@ -35,11 +46,55 @@ pub enum Error {
         error: Bytes,
     },
 
+    #[cfg(feature = "client-encryption")]
+    /// A sealed document could not be encrypted or decrypted.
+    #[error("client-side encryption error")]
+    Encryption,
+
     /// The server is incompatible with this version of the client.
     #[error("server incompatible with client protocol version")]
     ProtocolVersionMismatch,
 }
 
+impl Error {
+    /// Returns true if this error was caused by being unable to communicate
+    /// with the server, rather than by the server rejecting the request.
+    ///
+    /// [`Error::RequestTimeout`] is not considered a connection error, as the
+    /// server may have processed the request.
+    #[must_use]
+    pub fn is_connection_error(&self) -> bool {
+        match self {
+            Self::Disconnected | Self::Network(bonsaidb_core::networking::Error::Disconnected) => {
+                true
+            }
+            #[cfg(feature = "websockets")]
+            Self::WebSocket(_) => true,
+            Self::Core(bonsaidb_core::Error::Other { origin, .. }) => origin == "quic",
+            _ => false,
+        }
+    }
+
+    /// Returns the machine-readable category of this error. Errors returned
+    /// by the server retain the [`ErrorCode`] they were created with.
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        if self.is_connection_error() {
+            return ErrorCode::Unavailable;
+        }
+
+        match self {
+            Self::Core(err) => err.code(),
+            Self::Api { .. } => ErrorCode::Api,
+            Self::InvalidUrl(_) => ErrorCode::InvalidArgument,
+            Self::OfflineQueueFull => ErrorCode::QuotaExceeded,
+            #[cfg(feature = "client-encryption")]
+            Self::Encryption => ErrorCode::Internal,
+            _ => ErrorCode::Unavailable,
+        }
+    }
+}
+
 impl<T> From<flume::SendError<T>> for Error {
     fn from(_: flume::SendError<T>) -> Self {
         Self::Disconnected
@@ -25,7 +25,18 @@ mod error;
 pub use fabruic;
 
 pub use self::builder::Builder;
-pub use self::client::{ApiCallback, AsyncClient, AsyncRemoteDatabase, AsyncRemoteSubscriber};
+pub use self::client::{
+    ApiCallback, AsyncClient, AsyncRemoteDatabase, AsyncRemoteSubscriber, Batch, BatchResults,
+    Batched, BatchedView, DocumentCache, RetryPolicy, ViewResponse,
+};
 #[cfg(not(target_arch = "wasm32"))]
-pub use self::client::{BlockingClient, BlockingRemoteDatabase, BlockingRemoteSubscriber};
+pub use self::client::{
+    BlockingBatch, BlockingClient, BlockingRemoteDatabase, BlockingRemoteSubscriber,
+    KeyValueQueueStorage, OfflineQueue, OfflineQueueStorage, QueuedWrite, ReplayedWrite,
+    WriteOutcome,
+};
+#[cfg(feature = "client-encryption")]
+pub use self::client::ClientEncryptionKey;
+#[cfg(all(feature = "websockets", not(target_arch = "wasm32")))]
+pub use self::client::WebSocketKeepalive;
 pub use self::error::{ApiError, Error};
@@ -12,6 +12,12 @@ pub struct Database {
     pub name: String,
     /// The schema defining the database.
     pub schema: SchemaName,
+    /// The maximum size of the database's files, in bytes. Once the
+    /// database's files have reached this size, transactions that write to
+    /// it are rejected with
+    /// [`Error::DatabaseQuotaExceeded`](crate::Error::DatabaseQuotaExceeded).
+    #[serde(default)]
+    pub quota: Option<u64>,
 }
 
 define_basic_unique_mapped_view!(
@@ -14,6 +14,10 @@ pub struct PermissionGroup {
     pub name: String,
     /// The permission statements.
     pub statements: Vec<Statement>,
+    /// The number of times this group's statements have been changed. See
+    /// [`StorageConnection::update_permission_group_statements()`](crate::connection::StorageConnection::update_permission_group_statements).
+    #[serde(default)]
+    pub version: u64,
 }
 
 impl PermissionGroup {
@@ -22,6 +26,7 @@ impl PermissionGroup {
         Self {
             name: name.into(),
             statements: Vec::new(),
+            version: 0,
         }
     }
 
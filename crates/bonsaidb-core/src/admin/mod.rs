@@ -8,18 +8,25 @@ pub mod database;
 pub mod group;
 #[doc(hidden)]
 pub mod role;
+mod security;
 #[doc(hidden)]
 pub mod user;
+#[doc(hidden)]
+pub mod webhook;
 
 pub use self::authentication_token::AuthenticationToken;
 pub use self::database::Database;
 pub use self::group::PermissionGroup;
 pub use self::role::Role;
+pub use self::security::{
+    RoleConfiguration, SecurityChanges, SecurityConfiguration, SecurityRecord, UserConfiguration,
+};
 pub use self::user::User;
+pub use self::webhook::{Webhook, WebhookChange, WebhookEvent, WebhookViewFilter};
 
 /// The BonsaiDb administration schema.
 #[derive(Debug, Schema)]
-#[schema(name = "bonsaidb-admin", authority = "khonsulabs", collections = [Database, PermissionGroup, Role, User, AuthenticationToken], core = crate)]
+#[schema(name = "bonsaidb-admin", authority = "khonsulabs", collections = [Database, PermissionGroup, Role, User, AuthenticationToken, Webhook], core = crate)]
 pub struct Admin;
 
 /// The name of the admin database.
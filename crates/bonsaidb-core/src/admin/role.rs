@@ -16,6 +16,10 @@ pub struct Role {
     pub name: String,
     /// The IDs of the permission groups this role belongs to.
     pub groups: Vec<u64>,
+    /// The number of times this role's groups have been changed. See
+    /// [`StorageConnection::update_role_permission_groups()`](crate::connection::StorageConnection::update_role_permission_groups).
+    #[serde(default)]
+    pub version: u64,
 }
 
 impl Role {
@@ -24,6 +28,7 @@ impl Role {
         Self {
             name: name.into(),
             groups: Vec::new(),
+            version: 0,
         }
     }
 
@@ -0,0 +1,448 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::admin::{PermissionGroup, Role, User};
+use crate::connection::{AsyncStorageConnection, StorageConnection};
+use crate::document::CollectionDocument;
+use crate::permissions::Statement;
+
+/// A declarative description of the permission groups, roles, and users of a
+/// BonsaiDb instance.
+///
+/// Records reference each other by name rather than by id, which allows a
+/// configuration exported from one instance to be applied to another. A
+/// configuration is exported using
+/// [`StorageConnection::export_security_configuration()`] and applied using
+/// [`StorageConnection::apply_security_configuration()`].
+///
+/// Users' passwords and authentication tokens are not part of the
+/// configuration.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SecurityConfiguration {
+    /// The statements of each permission group, keyed by the group's name.
+    #[serde(default)]
+    pub permission_groups: BTreeMap<String, Vec<Statement>>,
+    /// The roles, keyed by name.
+    #[serde(default)]
+    pub roles: BTreeMap<String, RoleConfiguration>,
+    /// The users, keyed by username.
+    #[serde(default)]
+    pub users: BTreeMap<String, UserConfiguration>,
+}
+
+/// The configuration of a [`Role`]. See [`SecurityConfiguration`].
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RoleConfiguration {
+    /// The names of the permission groups the role belongs to.
+    #[serde(default)]
+    pub permission_groups: BTreeSet<String>,
+}
+
+/// The configuration of a [`User`]. See [`SecurityConfiguration`].
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct UserConfiguration {
+    /// The names of the permission groups the user belongs to.
+    #[serde(default)]
+    pub permission_groups: BTreeSet<String>,
+    /// The names of the roles the user has been assigned.
+    #[serde(default)]
+    pub roles: BTreeSet<String>,
+    /// If true, the user is disabled.
+    #[serde(default)]
+    pub disabled: bool,
+}
+
+/// The changes made by applying a [`SecurityConfiguration`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SecurityChanges {
+    /// The records that were created.
+    pub created: Vec<SecurityRecord>,
+    /// The records that were updated.
+    pub updated: Vec<SecurityRecord>,
+    /// The records that were deleted.
+    pub deleted: Vec<SecurityRecord>,
+}
+
+impl SecurityChanges {
+    /// Returns true if no changes were made.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.created.is_empty() && self.updated.is_empty() && self.deleted.is_empty()
+    }
+}
+
+/// A record described by a [`SecurityConfiguration`].
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum SecurityRecord {
+    /// The permission group with the contained name.
+    PermissionGroup(String),
+    /// The role with the contained name.
+    Role(String),
+    /// The user with the contained username.
+    User(String),
+}
+
+impl SecurityConfiguration {
+    pub(crate) fn from_records(
+        permission_groups: Vec<CollectionDocument<PermissionGroup>>,
+        roles: Vec<CollectionDocument<Role>>,
+        users: Vec<CollectionDocument<User>>,
+    ) -> Self {
+        let group_names = permission_groups
+            .iter()
+            .map(|group| (group.header.id, group.contents.name.clone()))
+            .collect::<HashMap<_, _>>();
+        let role_names = roles
+            .iter()
+            .map(|role| (role.header.id, role.contents.name.clone()))
+            .collect::<HashMap<_, _>>();
+        // Ids of records that have since been deleted are ignored.
+        let names = |ids: &[u64], names: &HashMap<u64, String>| {
+            ids.iter()
+                .filter_map(|id| names.get(id).cloned())
+                .collect::<BTreeSet<_>>()
+        };
+
+        Self {
+            roles: roles
+                .iter()
+                .map(|role| {
+                    (
+                        role.contents.name.clone(),
+                        RoleConfiguration {
+                            permission_groups: names(&role.contents.groups, &group_names),
+                        },
+                    )
+                })
+                .collect(),
+            users: users
+                .into_iter()
+                .map(|user| {
+                    (
+                        user.contents.username,
+                        UserConfiguration {
+                            permission_groups: names(&user.contents.groups, &group_names),
+                            roles: names(&user.contents.roles, &role_names),
+                            disabled: user.contents.disabled,
+                        },
+                    )
+                })
+                .collect(),
+            permission_groups: permission_groups
+                .into_iter()
+                .map(|group| (group.contents.name, group.contents.statements))
+                .collect(),
+        }
+    }
+
+    /// Returns the steps that change the existing records to match this
+    /// configuration.
+    pub(crate) fn plan(
+        &self,
+        permission_groups: Vec<CollectionDocument<PermissionGroup>>,
+        roles: Vec<CollectionDocument<Role>>,
+        users: Vec<CollectionDocument<User>>,
+    ) -> Result<SecurityPlan, crate::Error> {
+        self.validate()?;
+        let existing = Self::from_records(permission_groups, roles, users);
+        let mut plan = SecurityPlan::default();
+
+        for (name, statements) in &self.permission_groups {
+            match existing.permission_groups.get(name) {
+                None => plan.create(
+                    SecurityRecord::PermissionGroup(name.clone()),
+                    SecurityStep::CreatePermissionGroup(name.clone(), statements.clone()),
+                ),
+                Some(existing) if !same_statements(existing, statements)? => {
+                    plan.update(
+                        SecurityRecord::PermissionGroup(name.clone()),
+                        vec![SecurityStep::SetPermissionGroupStatements(
+                            name.clone(),
+                            statements.clone(),
+                        )],
+                    );
+                }
+                Some(_) => {}
+            }
+        }
+
+        let no_role = RoleConfiguration::default();
+        for (name, role) in &self.roles {
+            let existing_role = existing.roles.get(name);
+            if existing_role.is_none() {
+                plan.steps.push(SecurityStep::CreateRole(name.clone()));
+            }
+            let existing_groups = &existing_role.unwrap_or(&no_role).permission_groups;
+            let mut steps = Vec::new();
+            for group in role.permission_groups.difference(existing_groups) {
+                steps.push(SecurityStep::AddPermissionGroupToRole(
+                    name.clone(),
+                    group.clone(),
+                ));
+            }
+            for group in existing_groups.difference(&role.permission_groups) {
+                steps.push(SecurityStep::RemovePermissionGroupFromRole(
+                    name.clone(),
+                    group.clone(),
+                ));
+            }
+            let record = SecurityRecord::Role(name.clone());
+            if existing_role.is_none() {
+                plan.steps.extend(steps);
+                plan.changes.created.push(record);
+            } else {
+                plan.update(record, steps);
+            }
+        }
+
+        let no_user = UserConfiguration::default();
+        for (name, user) in &self.users {
+            let existing_user = existing.users.get(name);
+            if existing_user.is_none() {
+                plan.steps.push(SecurityStep::CreateUser(name.clone()));
+            }
+            let existing_user_or_default = existing_user.unwrap_or(&no_user);
+            let mut steps = Vec::new();
+            for group in user
+                .permission_groups
+                .difference(&existing_user_or_default.permission_groups)
+            {
+                steps.push(SecurityStep::AddPermissionGroupToUser(
+                    name.clone(),
+                    group.clone(),
+                ));
+            }
+            for group in existing_user_or_default
+                .permission_groups
+                .difference(&user.permission_groups)
+            {
+                steps.push(SecurityStep::RemovePermissionGroupFromUser(
+                    name.clone(),
+                    group.clone(),
+                ));
+            }
+            for role in user.roles.difference(&existing_user_or_default.roles) {
+                steps.push(SecurityStep::AddRoleToUser(name.clone(), role.clone()));
+            }
+            for role in existing_user_or_default.roles.difference(&user.roles) {
+                steps.push(SecurityStep::RemoveRoleFromUser(name.clone(), role.clone()));
+            }
+            if user.disabled != existing_user_or_default.disabled {
+                steps.push(if user.disabled {
+                    SecurityStep::DisableUser(name.clone())
+                } else {
+                    SecurityStep::EnableUser(name.clone())
+                });
+            }
+            let record = SecurityRecord::User(name.clone());
+            if existing_user.is_none() {
+                plan.steps.extend(steps);
+                plan.changes.created.push(record);
+            } else {
+                plan.update(record, steps);
+            }
+        }
+
+        // Records are deleted once nothing being kept refers to them.
+        for name in existing.users.keys() {
+            if !self.users.contains_key(name) {
+                plan.delete(
+                    SecurityRecord::User(name.clone()),
+                    SecurityStep::DeleteUser(name.clone()),
+                );
+            }
+        }
+        for name in existing.roles.keys() {
+            if !self.roles.contains_key(name) {
+                plan.delete(
+                    SecurityRecord::Role(name.clone()),
+                    SecurityStep::DeleteRole(name.clone()),
+                );
+            }
+        }
+        for name in existing.permission_groups.keys() {
+            if !self.permission_groups.contains_key(name) {
+                plan.delete(
+                    SecurityRecord::PermissionGroup(name.clone()),
+                    SecurityStep::DeletePermissionGroup(name.clone()),
+                );
+            }
+        }
+
+        Ok(plan)
+    }
+
+    /// Checks that every permission group and role referenced by this
+    /// configuration is part of it.
+    fn validate(&self) -> Result<(), crate::Error> {
+        let unknown = |kind: &str, name: &str, referenced_by: &str| {
+            Err(crate::Error::other(
+                "security configuration",
+                format!("{referenced_by} references {kind} {name:?}, which is not configured"),
+            ))
+        };
+        for (role_name, role) in &self.roles {
+            if let Some(group) = role
+                .permission_groups
+                .iter()
+                .find(|group| !self.permission_groups.contains_key(*group))
+            {
+                return unknown("permission group", group, &format!("role {role_name:?}"));
+            }
+        }
+        for (username, user) in &self.users {
+            if let Some(group) = user
+                .permission_groups
+                .iter()
+                .find(|group| !self.permission_groups.contains_key(*group))
+            {
+                return unknown("permission group", group, &format!("user {username:?}"));
+            }
+            if let Some(role) = user
+                .roles
+                .iter()
+                .find(|role| !self.roles.contains_key(*role))
+            {
+                return unknown("role", role, &format!("user {username:?}"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compares statements using their serialized representation, which is
+/// identical for equal statements.
+fn same_statements(a: &[Statement], b: &[Statement]) -> Result<bool, crate::Error> {
+    Ok(pot::to_vec(a)? == pot::to_vec(b)?)
+}
+
+#[derive(Default)]
+pub(crate) struct SecurityPlan {
+    pub steps: Vec<SecurityStep>,
+    pub changes: SecurityChanges,
+}
+
+impl SecurityPlan {
+    fn create(&mut self, record: SecurityRecord, step: SecurityStep) {
+        self.steps.push(step);
+        self.changes.created.push(record);
+    }
+
+    fn update(&mut self, record: SecurityRecord, steps: Vec<SecurityStep>) {
+        if !steps.is_empty() {
+            self.steps.extend(steps);
+            self.changes.updated.push(record);
+        }
+    }
+
+    fn delete(&mut self, record: SecurityRecord, step: SecurityStep) {
+        self.steps.push(step);
+        self.changes.deleted.push(record);
+    }
+}
+
+pub(crate) enum SecurityStep {
+    CreatePermissionGroup(String, Vec<Statement>),
+    SetPermissionGroupStatements(String, Vec<Statement>),
+    DeletePermissionGroup(String),
+    CreateRole(String),
+    AddPermissionGroupToRole(String, String),
+    RemovePermissionGroupFromRole(String, String),
+    DeleteRole(String),
+    CreateUser(String),
+    AddPermissionGroupToUser(String, String),
+    RemovePermissionGroupFromUser(String, String),
+    AddRoleToUser(String, String),
+    RemoveRoleFromUser(String, String),
+    DisableUser(String),
+    EnableUser(String),
+    DeleteUser(String),
+}
+
+impl SecurityStep {
+    pub(crate) fn apply<S: StorageConnection>(self, storage: &S) -> Result<(), crate::Error> {
+        match self {
+            Self::CreatePermissionGroup(name, statements) => {
+                storage.create_permission_group(&name, statements)?;
+            }
+            Self::SetPermissionGroupStatements(name, statements) => {
+                storage.set_permission_group_statements(&name, statements)?;
+            }
+            Self::DeletePermissionGroup(name) => storage.delete_permission_group(&name)?,
+            Self::CreateRole(name) => {
+                storage.create_role(&name)?;
+            }
+            Self::AddPermissionGroupToRole(role, group) => {
+                storage.add_permission_group_to_role(&role, &group)?;
+            }
+            Self::RemovePermissionGroupFromRole(role, group) => {
+                storage.remove_permission_group_from_role(&role, &group)?;
+            }
+            Self::DeleteRole(name) => storage.delete_role(&name)?,
+            Self::CreateUser(name) => {
+                storage.create_user(&name)?;
+            }
+            Self::AddPermissionGroupToUser(user, group) => {
+                storage.add_permission_group_to_user(&user, &group)?;
+            }
+            Self::RemovePermissionGroupFromUser(user, group) => {
+                storage.remove_permission_group_from_user(&user, &group)?;
+            }
+            Self::AddRoleToUser(user, role) => storage.add_role_to_user(&user, &role)?,
+            Self::RemoveRoleFromUser(user, role) => storage.remove_role_from_user(&user, &role)?,
+            Self::DisableUser(name) => storage.disable_user(&name)?,
+            Self::EnableUser(name) => storage.enable_user(&name)?,
+            Self::DeleteUser(name) => storage.delete_user(&name)?,
+        }
+        Ok(())
+    }
+
+    pub(crate) async fn apply_async<S: AsyncStorageConnection>(
+        self,
+        storage: &S,
+    ) -> Result<(), crate::Error> {
+        match self {
+            Self::CreatePermissionGroup(name, statements) => {
+                storage.create_permission_group(&name, statements).await?;
+            }
+            Self::SetPermissionGroupStatements(name, statements) => {
+                storage
+                    .set_permission_group_statements(&name, statements)
+                    .await?;
+            }
+            Self::DeletePermissionGroup(name) => storage.delete_permission_group(&name).await?,
+            Self::CreateRole(name) => {
+                storage.create_role(&name).await?;
+            }
+            Self::AddPermissionGroupToRole(role, group) => {
+                storage.add_permission_group_to_role(&role, &group).await?;
+            }
+            Self::RemovePermissionGroupFromRole(role, group) => {
+                storage
+                    .remove_permission_group_from_role(&role, &group)
+                    .await?;
+            }
+            Self::DeleteRole(name) => storage.delete_role(&name).await?,
+            Self::CreateUser(name) => {
+                storage.create_user(&name).await?;
+            }
+            Self::AddPermissionGroupToUser(user, group) => {
+                storage.add_permission_group_to_user(&user, &group).await?;
+            }
+            Self::RemovePermissionGroupFromUser(user, group) => {
+                storage
+                    .remove_permission_group_from_user(&user, &group)
+                    .await?;
+            }
+            Self::AddRoleToUser(user, role) => storage.add_role_to_user(&user, &role).await?,
+            Self::RemoveRoleFromUser(user, role) => {
+                storage.remove_role_from_user(&user, &role).await?;
+            }
+            Self::DisableUser(name) => storage.disable_user(&name).await?,
+            Self::EnableUser(name) => storage.enable_user(&name).await?,
+            Self::DeleteUser(name) => storage.delete_user(&name).await?,
+        }
+        Ok(())
+    }
+}
@@ -7,6 +7,7 @@ use crate::connection::{
 };
 use crate::define_basic_unique_mapped_view;
 use crate::document::{CollectionDocument, Emit, KeyId};
+use crate::keyvalue::Timestamp;
 use crate::permissions::Permissions;
 use crate::schema::{Collection, Nameable, NamedCollection, SerializedCollection};
 
@@ -29,6 +30,25 @@ pub struct User {
     /// records are updated in the meantime.
     #[serde(default)]
     pub argon_hash: Option<SensitiveString>,
+
+    /// When the user's password was last set. Used to enforce a maximum
+    /// password age.
+    #[serde(default)]
+    pub password_set_at: Option<Timestamp>,
+
+    /// The number of consecutive failed attempts to authenticate using a
+    /// password.
+    #[serde(default)]
+    pub failed_authentications: u32,
+
+    /// If set, authenticating using a password will fail until this time.
+    #[serde(default)]
+    pub locked_until: Option<Timestamp>,
+
+    /// If true, this user will be unable to authenticate or have its identity
+    /// assumed.
+    #[serde(default)]
+    pub disabled: bool,
 }
 
 impl User {
@@ -67,7 +87,7 @@ impl User {
         let role_groups = if self.roles.is_empty() {
             Vec::default()
         } else {
-            let roles = role::Role::get_multiple(self.groups.iter(), admin)?;
+            let roles = role::Role::get_multiple(self.roles.iter(), admin)?;
             roles
                 .into_iter()
                 .flat_map(|doc| doc.contents.groups)
@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+
+use crate::arc_bytes::serde::Bytes;
+use crate::connection::Range;
+use crate::key::KeyEncoding;
+use crate::schema::{Collection, CollectionName, View, ViewName};
+use crate::Error;
+
+/// An HTTP endpoint that is notified when documents in a database change.
+///
+/// Webhooks are registered by storing them in the admin database. When the
+/// server is built with webhook support, each transaction that changes
+/// documents matching the webhook's filters is POSTed to
+/// [`url`](Self::url) as a JSON-encoded [`WebhookEvent`].
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize, Collection)]
+#[collection(authority = "bonsaidb", name = "webhooks", core = crate)]
+#[must_use]
+pub struct Webhook {
+    /// The URL that events are POSTed to.
+    pub url: String,
+    /// The name of the database whose changes are delivered.
+    pub database: String,
+    /// If set, only changes to documents in this collection are delivered.
+    pub collection: Option<CollectionName>,
+    /// If set, only changes to documents that emit a key within a range of a
+    /// view are delivered.
+    pub view: Option<WebhookViewFilter>,
+}
+
+impl Webhook {
+    /// Returns a webhook that delivers every document change in `database` to
+    /// `url`.
+    pub fn new(url: impl Into<String>, database: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            database: database.into(),
+            collection: None,
+            view: None,
+        }
+    }
+
+    /// Builder-style method. Returns self after limiting the delivered changes
+    /// to documents in the collection `C`.
+    pub fn for_collection<C: Collection>(mut self) -> Self {
+        self.collection = Some(C::collection_name());
+        self
+    }
+
+    /// Builder-style method. Returns self after limiting the delivered changes
+    /// to documents in `view`'s collection that emit a key contained in
+    /// `keys`.
+    ///
+    /// Deleted documents can no longer be mapped, so deletions of any document
+    /// in the view's collection are always delivered.
+    pub fn for_view_keys<V: View>(
+        mut self,
+        view: &V,
+        keys: impl Into<Range<V::Key>>,
+    ) -> Result<Self, Error> {
+        let keys = keys.into().map_result(|key| {
+            key.as_ord_bytes()
+                .map(|bytes| Bytes::from(bytes.to_vec()))
+                .map_err(|err| Error::other("key serialization", err))
+        })?;
+        self.collection = Some(V::Collection::collection_name());
+        self.view = Some(WebhookViewFilter {
+            view: view.view_name(),
+            keys,
+        });
+        Ok(self)
+    }
+}
+
+/// Limits a [`Webhook`] to documents that emit keys within a range of a view.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct WebhookViewFilter {
+    /// The name of the view.
+    pub view: ViewName,
+    /// The range of serialized keys to match.
+    pub keys: Range<Bytes>,
+}
+
+/// The body of the request sent to a [`Webhook`] for each transaction.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct WebhookEvent {
+    /// The id of the webhook being notified.
+    pub webhook_id: u64,
+    /// The name of the database that was changed.
+    pub database: String,
+    /// The id of the transaction that changed the documents.
+    pub transaction_id: u64,
+    /// The changed documents that matched the webhook's filters.
+    pub changes: Vec<WebhookChange>,
+}
+
+/// A document change delivered in a [`WebhookEvent`].
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct WebhookChange {
+    /// The name of the document's collection.
+    pub collection: String,
+    /// The id of the document, formatted using
+    /// [`DocumentId`](crate::document::DocumentId)'s `Display`
+    /// implementation, which can be parsed using its `FromStr`
+    /// implementation.
+    pub id: String,
+    /// True if the document was deleted.
+    pub deleted: bool,
+}
@@ -24,6 +24,7 @@ use crate::schema::{Authority, Name, Qualified, QualifiedName};
 /// - `error = ErrorType`: Configures the [`Api::Error`] associated type. This
 ///   is the type that the handler will return upon error. If not specified,
 ///   [`Infallible`] is used.
+/// - `idempotent`: Marks the Api as [idempotent](Api::idempotent).
 ///
 /// ```rust
 /// use bonsaidb_core::api::Api;
@@ -49,6 +50,16 @@ pub trait Api: Serialize + for<'de> Deserialize<'de> + Send + Sync + Debug + 'st
 
     /// Returns the unique name of this api.
     fn name() -> ApiName;
+
+    /// Returns true if sending this request more than once has the same
+    /// effect as sending it once. Clients may automatically retry idempotent
+    /// requests that time out or are interrupted by a disconnection.
+    ///
+    /// The default implementation returns false.
+    #[must_use]
+    fn idempotent() -> bool {
+        false
+    }
 }
 /// An Error type that can be used in within an [`Api`] definition.
 ///
@@ -98,3 +109,66 @@ impl Deref for ApiName {
         &self.0
     }
 }
+
+/// A query that is registered with a server by name and executed on behalf of
+/// clients.
+///
+/// Named queries let a server decide which queries clients can execute. The
+/// server's handler chooses which views to query, which access policy to use,
+/// and how to process the results. Clients can only provide the query's
+/// [`Parameters`](Self::Parameters). Permission to execute each named query
+/// can be granted individually using
+/// [`named_query_resource_name()`](crate::permissions::bonsai::named_query_resource_name),
+/// without granting access to the underlying views.
+///
+/// ```rust
+/// use bonsaidb_core::api::{NamedQuery, QueryName};
+/// use bonsaidb_core::schema::Qualified;
+///
+/// #[derive(Debug)]
+/// struct TopScores;
+///
+/// impl NamedQuery for TopScores {
+///     // The number of scores to return.
+///     type Parameters = u8;
+///     type Output = Vec<f32>;
+///
+///     fn name() -> QueryName {
+///         QueryName::private("top-scores")
+///     }
+/// }
+/// ```
+pub trait NamedQuery: Send + Sync + 'static {
+    /// The parameters that clients provide when executing this query.
+    type Parameters: Serialize + for<'de> Deserialize<'de> + Send + Sync + Debug;
+    /// The result of executing this query.
+    type Output: Serialize + for<'de> Deserialize<'de> + Send + Sync + Debug;
+
+    /// Returns the unique name of this query.
+    fn name() -> QueryName;
+}
+
+/// The qualified name of a [`NamedQuery`].
+#[derive(Hash, PartialEq, Eq, Deserialize, Serialize, Debug, Clone, Ord, PartialOrd)]
+#[serde(transparent)]
+pub struct QueryName(QualifiedName);
+
+impl Qualified for QueryName {
+    fn new<A: Into<Authority>, N: Into<Name>>(authority: A, name: N) -> Self {
+        Self(QualifiedName::new(authority, name))
+    }
+}
+
+impl Display for QueryName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl Deref for QueryName {
+    type Target = QualifiedName;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
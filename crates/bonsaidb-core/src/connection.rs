@@ -1,9 +1,11 @@
 use std::borrow::Borrow;
+use std::collections::{BTreeMap, BTreeSet};
 use std::convert::Infallible;
 use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut};
 use std::string::FromUtf8Error;
 use std::sync::Arc;
+use std::time::Duration;
 
 use actionable::{Action, Identifier};
 use arc_bytes::serde::Bytes;
@@ -13,22 +15,33 @@ use futures::{Future, FutureExt};
 use serde::{Deserialize, Serialize};
 use zeroize::Zeroize;
 
-use crate::admin::{Role, User};
+use crate::admin::{PermissionGroup, Role, SecurityChanges, SecurityConfiguration, User};
 use crate::document::{
-    CollectionDocument, CollectionHeader, Document, HasHeader, Header, OwnedDocument,
+    CollectionDocument, CollectionHeader, Document, DocumentId, HasHeader, Header, OwnedDocument,
 };
+use crate::key::geo::{GeoBounds, GeoPoint};
 use crate::key::{ByteCow, IntoPrefixRange, Key, KeyEncoding};
-use crate::permissions::Permissions;
+use crate::keyvalue::{AsyncKeyValue, KeyValue, Timestamp, Value};
+use crate::permissions::{Permissions, Statement};
+use crate::schema::view::aggregation::{
+    AggregatedGroup, Aggregation, AggregationGrouping, Aggregator, SerializedAggregatedGroup,
+};
 use crate::schema::view::map::MappedDocuments;
+use crate::schema::view::{self as view_schema, ViewMaterialization, MATERIALIZED_VIEW_NAMESPACE};
 use crate::schema::{
-    self, Map, MappedValue, Nameable, NamedReference, Schema, SchemaName, SerializedCollection,
+    self, Map, MappedValue, Nameable, NamedReference, ProjectedDocument, Projection, Schema,
+    SchemaName, SerializedCollection,
 };
 use crate::{transaction, Error};
 
 mod has_session;
+mod import;
+mod join;
 mod lowlevel;
 
 pub use self::has_session::HasSession;
+pub use self::import::{AsyncImport, ImportError, ImportProgress};
+pub use self::join::{AsyncJoinedViewQuery, JoinedViewQuery};
 pub use self::lowlevel::{AsyncLowLevelConnection, HasSchema, LowLevelConnection};
 
 /// A connection to a database's [`Schema`](schema::Schema), giving access to
@@ -53,6 +66,12 @@ pub trait Connection: LowLevelConnection + Sized + Send + Sync {
         View::new(self)
     }
 
+    /// Accesses a [`JoinedView`](view_schema::join::JoinedView) from this
+    /// connection.
+    fn joined_view<J: view_schema::join::JoinedView>(&'_ self) -> JoinedViewQuery<'_, Self, J> {
+        JoinedViewQuery::new(self)
+    }
+
     /// Lists [executed transactions](transaction::Executed) from this
     /// [`Schema`](schema::Schema). By default, a maximum of 1000 entries will
     /// be returned, but that limit can be overridden by setting `result_limit`.
@@ -573,6 +592,46 @@ where
             .list_headers::<Cl, _, PrimaryKey>(range, sort, limit)
     }
 
+    /// Retrieves the matching documents, with their contents projected by
+    /// `P`. Only the projected contents are transferred from the database.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::Connection;
+    /// # use bonsaidb_core::schema::Projection;
+    /// # #[derive(Debug)]
+    /// # struct Ranks;
+    /// # impl Projection for Ranks {
+    /// #     type Collection = MyCollection;
+    /// #     type Output = u32;
+    /// #     fn name(&self) -> Name {
+    /// #         Name::new("ranks")
+    /// #     }
+    /// #     fn project(&self, contents: MyCollection) -> u32 {
+    /// #         contents.rank
+    /// #     }
+    /// # }
+    /// # fn test_fn<C: Connection>(db: &C) -> Result<(), Error> {
+    /// for doc in db.collection::<MyCollection>().all().projected::<Ranks>()? {
+    ///     println!("#{} has rank {}", doc.header.id, doc.contents);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn projected<P: Projection<Collection = Cl>>(
+        self,
+    ) -> Result<Vec<ProjectedDocument<P>>, Error> {
+        let Self {
+            collection,
+            range,
+            sort,
+            limit,
+        } = self;
+        collection
+            .connection
+            .list_projected::<P, _, PrimaryKey>(range, sort, limit)
+    }
+
     /// Retrieves the matching documents.
     ///
     /// ```rust
@@ -658,6 +717,10 @@ where
     /// The view's data access policy. The default value is [`AccessPolicy::UpdateBefore`].
     pub access_policy: AccessPolicy,
 
+    /// Where the query is executed when the database is replicated by a
+    /// cluster.
+    pub read_options: ViewReadOptions,
+
     /// The sort order of the query.
     pub sort: Sort,
 
@@ -679,6 +742,7 @@ where
             connection,
             key: None,
             access_policy: AccessPolicy::UpdateBefore,
+            read_options: ViewReadOptions::default(),
             sort: Sort::Ascending,
             limit: None,
             _view: PhantomData,
@@ -709,6 +773,7 @@ where
             connection: self.connection,
             key: Some(QueryKey::Matches(MaybeOwned::Borrowed(key))),
             access_policy: self.access_policy,
+            read_options: self.read_options,
             sort: self.sort,
             limit: self.limit,
             _view: PhantomData,
@@ -742,6 +807,7 @@ where
                 keys.into_iter().map(MaybeOwned::Borrowed).collect(),
             )),
             access_policy: self.access_policy,
+            read_options: self.read_options,
             sort: self.sort,
             limit: self.limit,
             _view: PhantomData,
@@ -772,6 +838,7 @@ where
             connection: self.connection,
             key: Some(QueryKey::Range(range.into())),
             access_policy: self.access_policy,
+            read_options: self.read_options,
             sort: self.sort,
             limit: self.limit,
             _view: PhantomData,
@@ -806,6 +873,7 @@ where
             connection: self.connection,
             key: Some(QueryKey::Range(prefix.to_prefix_range())),
             access_policy: self.access_policy,
+            read_options: self.read_options,
             sort: self.sort,
             limit: self.limit,
             _view: PhantomData,
@@ -833,6 +901,27 @@ where
         self
     }
 
+    /// Executes the query on the database's primary node when the database is
+    /// replicated by a cluster. By default, queries are executed by the node
+    /// the client is connected to, which may not have replicated the most
+    /// recent changes yet.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::Connection;
+    /// # fn test_fn<C: Connection>(db: C) -> Result<(), Error> {
+    /// let score = ScoresByRank::entries(&db)
+    ///     .read_from_primary_only()
+    ///     .reduce()?;
+    /// println!("Average score: {:3}", score);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub const fn read_from_primary_only(mut self) -> Self {
+        self.read_options.read_from_primary_only = true;
+        self
+    }
+
     /// Returns the matching mappings in ascending key order. This is the
     /// default sorting behavior.
     ///
@@ -911,8 +1000,13 @@ where
     /// # }
     /// ```
     pub fn query(self) -> Result<ViewMappings<V>, Error> {
-        self.connection
-            .query::<V, Key>(self.key, self.sort, self.limit, self.access_policy)
+        self.connection.query::<V, Key>(
+            self.key,
+            self.sort,
+            self.limit,
+            self.access_policy,
+            self.read_options,
+        )
     }
 
     /// Executes the query and retrieves the results with the associated [`Document`s](crate::document::OwnedDocument).
@@ -939,6 +1033,7 @@ where
             self.sort,
             self.limit,
             self.access_policy,
+            self.read_options,
         )
     }
 
@@ -972,6 +1067,52 @@ where
             self.sort,
             self.limit,
             self.access_policy,
+            self.read_options,
+        )
+    }
+
+    /// Executes the query and retrieves the results with the associated
+    /// documents, with the documents' contents projected by `P`. Only the
+    /// projected contents are transferred from the database.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::Connection;
+    /// # use bonsaidb_core::schema::Projection;
+    /// # #[derive(Debug)]
+    /// # struct Ranks;
+    /// # impl Projection for Ranks {
+    /// #     type Collection = MyCollection;
+    /// #     type Output = u32;
+    /// #     fn name(&self) -> Name {
+    /// #         Name::new("ranks")
+    /// #     }
+    /// #     fn project(&self, contents: MyCollection) -> u32 {
+    /// #         contents.rank
+    /// #     }
+    /// # }
+    /// # fn test_fn<C: Connection>(db: C) -> Result<(), Error> {
+    /// for mapping in &ScoresByRank::entries(&db)
+    ///     .with_key_range(42..=44)
+    ///     .query_with_projected_docs::<Ranks>()?
+    /// {
+    ///     println!(
+    ///         "Mapping from #{} with score: {}. Projected rank: {}",
+    ///         mapping.document.header.id, mapping.value, mapping.document.contents
+    ///     );
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn query_with_projected_docs<P: Projection<Collection = V::Collection>>(
+        self,
+    ) -> Result<MappedDocuments<ProjectedDocument<P>, V>, Error> {
+        self.connection.query_with_projected_docs::<V, P, Key>(
+            self.key,
+            self.sort,
+            self.limit,
+            self.access_policy,
+            self.read_options,
         )
     }
 
@@ -989,7 +1130,7 @@ where
     /// ```
     pub fn reduce(self) -> Result<V::Value, Error> {
         self.connection
-            .reduce::<V, Key>(self.key, self.access_policy)
+            .reduce::<V, Key>(self.key, self.access_policy, self.read_options)
     }
 
     /// Executes a reduce over the results of the query, grouping by key.
@@ -1010,7 +1151,73 @@ where
     /// ```
     pub fn reduce_grouped(self) -> Result<GroupedReductions<V>, Error> {
         self.connection
-            .reduce_grouped::<V, Key>(self.key, self.access_policy)
+            .reduce_grouped::<V, Key>(self.key, self.access_policy, self.read_options)
+    }
+
+    /// Executes a reduce over the entries with any of `keys`. This is
+    /// equivalent to `self.with_keys(keys).reduce()`.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::Connection;
+    /// # fn test_fn<C: Connection>(db: C) -> Result<(), Error> {
+    /// // score is an f32 in this example
+    /// let score = ScoresByRank::entries(&db).reduce_for_keys(&[42, 43])?;
+    /// println!("Average score of ranks 42 and 43: {:3}", score);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn reduce_for_keys<K, IntoIter: IntoIterator<Item = &'a K>>(
+        self,
+        keys: IntoIter,
+    ) -> Result<V::Value, Error>
+    where
+        V::Key: Borrow<K> + PartialEq<K>,
+        K: for<'k> KeyEncoding<'k, V::Key> + PartialEq + ?Sized,
+    {
+        self.with_keys(keys).reduce()
+    }
+
+    /// Executes a reduce over the entries emitted by the documents with `ids`
+    /// that match this query's key filter.
+    ///
+    /// The matching entries are retrieved from the database and reduced using
+    /// this connection's copy of the view's schema. The query's limit is
+    /// ignored.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::Connection;
+    /// # fn test_fn<C: Connection>(db: C) -> Result<(), Error> {
+    /// // score is an f32 in this example
+    /// let score = ScoresByRank::entries(&db).reduce_for_documents(&[1_u64, 2, 3])?;
+    /// println!("Average score of documents 1, 2, and 3: {:3}", score);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn reduce_for_documents<'id, DocumentIds, PrimaryKey>(
+        mut self,
+        ids: DocumentIds,
+    ) -> Result<V::Value, Error>
+    where
+        DocumentIds: IntoIterator<Item = &'id PrimaryKey>,
+        PrimaryKey: for<'k> KeyEncoding<'k, <V::Collection as schema::Collection>::PrimaryKey>
+            + 'id
+            + ?Sized,
+    {
+        let ids = ids
+            .into_iter()
+            .map(DocumentId::new)
+            .collect::<Result<BTreeSet<_>, _>>()?;
+        let connection = self.connection;
+        self.limit = None;
+        let mappings = self.query()?;
+        reduce_mappings::<V>(
+            connection.schematic(),
+            mappings
+                .into_iter()
+                .filter(|mapping| ids.contains(&mapping.source.id)),
+        )
     }
 
     /// Deletes all of the associated documents that match this view query.
@@ -1027,99 +1234,661 @@ where
         self.connection
             .delete_docs::<V, Key>(self.key, self.access_policy)
     }
-}
-
-/// This type is the result of `query()`. It is a list of mappings, which
-/// contains:
-///
-/// - The key emitted during the map function.
-/// - The value emitted during the map function.
-/// - The source document header that the mappings originated from.
-pub type ViewMappings<V> = Vec<Map<<V as schema::View>::Key, <V as schema::View>::Value>>;
-/// This type is the result of `reduce_grouped()`. It is a list of all matching
-/// keys and the reduced value of all mapped entries for that key.
-pub type GroupedReductions<V> =
-    Vec<MappedValue<<V as schema::View>::Key, <V as schema::View>::Value>>;
-
-/// A connection to a database's [`Schema`](schema::Schema), giving access to
-/// [`Collection`s](crate::schema::Collection) and
-/// [`Views`s](crate::schema::View). All functions on this trait are safe to use
-/// in an asynchronous context.
-#[async_trait]
-pub trait AsyncConnection: AsyncLowLevelConnection + Sized + Send + Sync {
-    /// The [`AsyncStorageConnection`] type that is paired with this type.
-    type Storage: AsyncStorageConnection<Database = Self>;
-
-    /// Returns the [`StorageConnection`] implementor that this database belongs
-    /// to.
-    fn storage(&self) -> Self::Storage;
 
-    /// Accesses a collection for the connected [`Schema`](schema::Schema).
-    fn collection<C: schema::Collection>(&self) -> AsyncCollection<'_, Self, C> {
-        AsyncCollection::new(self)
+    /// Returns the number of entries that match this query. The entries are
+    /// counted by the database, without transferring any mappings. The sort
+    /// order and limit of this query are ignored.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::Connection;
+    /// # fn test_fn<C: Connection>(db: C) -> Result<(), Error> {
+    /// let top_ten = ScoresByRank::entries(&db).with_key_range(1..=10).count()?;
+    /// println!("{top_ten} entries have a rank between 1 and 10");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn count(self) -> Result<u64, Error> {
+        let view = self.connection.schematic().view::<V>()?;
+        self.connection.count_by_name(
+            &view.view_name(),
+            self.key.map(|key| key.serialized()).transpose()?,
+            self.access_policy,
+            self.read_options,
+        )
     }
 
-    /// Accesses a [`schema::View`] from this connection.
-    fn view<V: schema::SerializedView>(&'_ self) -> AsyncView<'_, Self, V, V::Key> {
-        AsyncView::new(self)
+    /// Returns the entries that the document with `id` emitted into this view.
+    /// The key filter, sort order, and limit of this query are ignored.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::Connection;
+    /// # fn test_fn<C: Connection>(db: C) -> Result<(), Error> {
+    /// for mapping in ScoresByRank::entries(&db).for_document(&42_u64)? {
+    ///     println!("Document 42 has rank {} with score {:3}", mapping.key, mapping.value);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn for_document<PrimaryKey>(self, id: &PrimaryKey) -> Result<ViewMappings<V>, Error>
+    where
+        PrimaryKey:
+            for<'k> KeyEncoding<'k, <V::Collection as schema::Collection>::PrimaryKey> + ?Sized,
+    {
+        let view = self.connection.schematic().view::<V>()?;
+        let mappings = self.connection.document_mappings_by_name(
+            &view.view_name(),
+            DocumentId::new(id)?,
+            self.access_policy,
+            self.read_options,
+        )?;
+        deserialize_mappings::<V>(mappings)
     }
 
-    /// Lists [executed transactions](transaction::Executed) from this [`Schema`](schema::Schema). By default, a maximum of
-    /// 1000 entries will be returned, but that limit can be overridden by
-    /// setting `result_limit`. A hard limit of 100,000 results will be
-    /// returned. To begin listing after another known `transaction_id`, pass
-    /// `transaction_id + 1` into `starting_id`.
-    async fn list_executed_transactions(
-        &self,
-        starting_id: Option<u64>,
-        result_limit: Option<u32>,
-    ) -> Result<Vec<transaction::Executed>, Error>;
-
-    /// Fetches the last transaction id that has been committed, if any.
-    async fn last_transaction_id(&self) -> Result<Option<u64>, Error>;
-
-    /// Compacts the entire database to reclaim unused disk space.
-    ///
-    /// This process is done by writing data to a new file and swapping the file
-    /// once the process completes. This ensures that if a hardware failure,
-    /// power outage, or crash occurs that the original collection data is left
-    /// untouched.
-    ///
-    /// ## Errors
+    /// Computes `aggregations` over the values of all entries matching this
+    /// query. The results are returned in the same order as `aggregations`.
+    /// The sort order and limit of this query are ignored.
     ///
-    /// * [`Error::Other`]: an error occurred while compacting the database.
-    async fn compact(&self) -> Result<(), crate::Error>;
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::Connection;
+    /// # use bonsaidb_core::schema::view::aggregation::Aggregation;
+    /// # fn test_fn<C: Connection>(db: C) -> Result<(), Error> {
+    /// // score is an f32 in this example
+    /// let stats = ScoresByRank::entries(&db)
+    ///     .aggregate(&[Aggregation::Average, Aggregation::Percentile(90.)])?;
+    /// println!("Average score: {:?}, 90th percentile: {:?}", stats[0], stats[1]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn aggregate(self, aggregations: &[Aggregation]) -> Result<Vec<Option<f64>>, Error> {
+        let groups = self.aggregate_by_grouping(aggregations, AggregationGrouping::None)?;
+        Ok(total_aggregation(groups, aggregations))
+    }
 
-    /// Compacts the collection to reclaim unused disk space.
+    /// Computes `aggregations` over the values of entries matching this query,
+    /// grouping the entries by key. The sort order and limit of this query are
+    /// ignored.
     ///
-    /// This process is done by writing data to a new file and swapping the file
-    /// once the process completes. This ensures that if a hardware failure,
-    /// power outage, or crash occurs that the original collection data is left
-    /// untouched.
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::Connection;
+    /// # use bonsaidb_core::schema::view::aggregation::Aggregation;
+    /// # fn test_fn<C: Connection>(db: C) -> Result<(), Error> {
+    /// // score is an f32 in this example
+    /// for group in ScoresByRank::entries(&db).aggregate_grouped(&[Aggregation::Max])? {
+    ///     println!("Rank {} has a high score of {:?}", group.key, group.values[0]);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn aggregate_grouped(
+        self,
+        aggregations: &[Aggregation],
+    ) -> Result<Vec<AggregatedGroup<V::Key>>, Error> {
+        let groups = self.aggregate_by_grouping(aggregations, AggregationGrouping::Key)?;
+        deserialize_aggregated_groups(groups)
+    }
+
+    /// Computes `aggregations` over the values of entries matching this query,
+    /// grouping the entries by the leading `Prefix` of each key. `Prefix` must
+    /// be a [`Key`] with a fixed [length](KeyEncoding::LENGTH), such as the
+    /// first field of a tuple key. The sort order and limit of this query are
+    /// ignored.
     ///
-    /// ## Errors
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::Connection;
+    /// # use bonsaidb_core::schema::view::aggregation::Aggregation;
+    /// # fn test_fn<C: Connection>(db: C) -> Result<(), Error> {
+    /// #[derive(View, Debug, Clone)]
+    /// #[view(name = "by-year-and-day", key = (u16, u16), value = f32, collection = MyCollection)]
+    /// # #[view(core = bonsaidb_core)]
+    /// struct ScoresByYearAndDay;
     ///
-    /// * [`Error::CollectionNotFound`]: database `name` does not exist.
-    /// * [`Error::Other`]: an error occurred while compacting the database.
-    async fn compact_collection<C: schema::Collection>(&self) -> Result<(), crate::Error> {
-        self.compact_collection_by_name(C::collection_name()).await
+    /// for group in ScoresByYearAndDay::entries(&db)
+    ///     .aggregate_grouped_by_prefix::<u16>(&[Aggregation::Sum])?
+    /// {
+    ///     println!("Total score in {}: {:?}", group.key, group.values[0]);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn aggregate_grouped_by_prefix<Prefix>(
+        self,
+        aggregations: &[Aggregation],
+    ) -> Result<Vec<AggregatedGroup<Prefix>>, Error>
+    where
+        Prefix: for<'k> Key<'k>,
+    {
+        let groups = self.aggregate_by_grouping(aggregations, prefix_grouping::<Prefix>()?)?;
+        deserialize_aggregated_groups(groups)
     }
 
-    /// Compacts the key value store to reclaim unused disk space.
-    ///
-    /// This process is done by writing data to a new file and swapping the file
-    /// once the process completes. This ensures that if a hardware failure,
-    /// power outage, or crash occurs that the original collection data is left
-    /// untouched.
+    fn aggregate_by_grouping(
+        self,
+        aggregations: &[Aggregation],
+        grouping: AggregationGrouping,
+    ) -> Result<Vec<SerializedAggregatedGroup>, Error> {
+        let view = self.connection.schematic().view::<V>()?;
+        self.connection.aggregate_by_name(
+            &view.view_name(),
+            self.key.map(|key| key.serialized()).transpose()?,
+            aggregations,
+            grouping,
+            self.access_policy,
+            self.read_options,
+        )
+    }
+}
+
+impl<'a, Cn, V, Key> View<'a, Cn, V, Key>
+where
+    V::Key: Borrow<Key> + PartialEq<Key>,
+    V: schema::SerializedView,
+    Cn: Connection + KeyValue,
+    Key: for<'k> KeyEncoding<'k, V::Key> + PartialEq + ?Sized,
+{
+    /// Returns the reduced value that was materialized into the key-value
+    /// store the last time the view was updated. Returns `None` if no value
+    /// has been materialized.
     ///
-    /// ## Errors
+    /// Views using [`ViewMaterialization::Total`] must be read without a key
+    /// filter. Views using [`ViewMaterialization::Grouped`] must be read with
+    /// a filter matching a single key.
     ///
-    /// * [`Error::Other`]: an error occurred while compacting the database.
-    async fn compact_key_value_store(&self) -> Result<(), crate::Error>;
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::Connection;
+    /// # use bonsaidb_core::keyvalue::KeyValue;
+    /// # fn test_fn<C: Connection + KeyValue>(db: C) -> Result<(), Error> {
+    /// // ScoresByRank is materialized with ViewMaterialization::Grouped
+    /// if let Some(score) = ScoresByRank::entries(&db).with_key(&42).materialized_reduce()? {
+    ///     println!("Rank 42 has an average score of {:3}", score);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn materialized_reduce(self) -> Result<Option<V::Value>, Error> {
+        let key = materialized_key::<V, Key>(self.connection.schematic(), self.key)?;
+        let value = self
+            .connection
+            .with_key_namespace(MATERIALIZED_VIEW_NAMESPACE)
+            .get_key(key)
+            .query()?;
+        deserialize_materialized_value::<V>(value)
+    }
 }
 
-/// Interacts with a collection over a `Connection`.
-///
+impl<'a, Cn, V, Key> View<'a, Cn, V, Key>
+where
+    V: schema::SerializedView<Key = GeoPoint>,
+    Cn: Connection,
+    Key: for<'k> KeyEncoding<'k, GeoPoint> + PartialEq + ?Sized,
+    V::Key: Borrow<Key> + PartialEq<Key>,
+{
+    /// Filters for entries in the view with keys inside of `bounds`.
+    ///
+    /// The view is scanned using [`GeoBounds::key_range()`], and entries
+    /// outside of `bounds` are filtered out of the results. If a
+    /// [`limit`](Self::limit) was set, it is applied after filtering.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::Connection;
+    /// # use bonsaidb_core::key::geo::{GeoBounds, GeoPoint};
+    /// # fn test_fn<C: Connection>(db: C) -> Result<(), Error> {
+    /// #[derive(View, Debug, Clone)]
+    /// #[view(name = "by-location", key = GeoPoint, collection = MyCollection)]
+    /// # #[view(core = bonsaidb_core)]
+    /// struct ByLocation;
+    ///
+    /// let oregon = GeoBounds::new(41.99, -124.57, 46.29, -116.46).unwrap();
+    /// for mapping in ByLocation::entries(&db).with_key_bounds(oregon).query()? {
+    ///     assert!(oregon.contains(mapping.key));
+    ///     println!("{:?} in document {:?}", mapping.key, mapping.source);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_key_bounds(self, bounds: GeoBounds) -> BoundedView<'a, Cn, V> {
+        BoundedView {
+            view: View {
+                connection: self.connection,
+                key: Some(QueryKey::Range(RangeRef::from(bounds.key_range()))),
+                access_policy: self.access_policy,
+                read_options: self.read_options,
+                sort: self.sort,
+                limit: None,
+                _view: PhantomData,
+            },
+            bounds,
+            limit: self.limit,
+        }
+    }
+}
+
+/// A [`View`] query that is filtered to keys within a [`GeoBounds`]. Created
+/// by [`View::with_key_bounds()`].
+#[must_use]
+pub struct BoundedView<'a, Cn, V>
+where
+    V: schema::SerializedView<Key = GeoPoint>,
+{
+    view: View<'a, Cn, V, GeoPoint>,
+    bounds: GeoBounds,
+    limit: Option<u32>,
+}
+
+impl<'a, Cn, V> BoundedView<'a, Cn, V>
+where
+    V: schema::SerializedView<Key = GeoPoint>,
+    Cn: Connection,
+{
+    /// Sets the access policy for queries.
+    pub const fn with_access_policy(mut self, policy: AccessPolicy) -> Self {
+        self.view.access_policy = policy;
+        self
+    }
+
+    /// Executes the query on the database's primary node when the database is
+    /// replicated by a cluster.
+    pub const fn read_from_primary_only(mut self) -> Self {
+        self.view.read_options.read_from_primary_only = true;
+        self
+    }
+
+    /// Returns the matching mappings in ascending key order. This is the
+    /// default sorting behavior.
+    pub const fn ascending(mut self) -> Self {
+        self.view.sort = Sort::Ascending;
+        self
+    }
+
+    /// Returns the matching mappings in descending key order.
+    pub const fn descending(mut self) -> Self {
+        self.view.sort = Sort::Descending;
+        self
+    }
+
+    /// Sets the maximum number of results to return. The limit is applied
+    /// after the results have been filtered by the bounds.
+    pub const fn limit(mut self, maximum_results: u32) -> Self {
+        self.limit = Some(maximum_results);
+        self
+    }
+
+    /// Executes the query and retrieves the results.
+    pub fn query(self) -> Result<ViewMappings<V>, Error> {
+        let mappings = self.view.query()?;
+        Ok(filter_mappings(mappings, self.bounds, self.limit))
+    }
+
+    /// Executes the query and retrieves the results with the associated [`Document`s](crate::document::OwnedDocument).
+    pub fn query_with_docs(self) -> Result<MappedDocuments<OwnedDocument, V>, Error> {
+        let mapped = self.view.query_with_docs()?;
+        Ok(filter_mapped_documents(mapped, self.bounds, self.limit))
+    }
+
+    /// Executes the query and retrieves the results with the associated [`CollectionDocument`s](crate::document::CollectionDocument).
+    pub fn query_with_collection_docs(
+        self,
+    ) -> Result<MappedDocuments<CollectionDocument<V::Collection>, V>, Error>
+    where
+        V::Collection: SerializedCollection,
+        <V::Collection as SerializedCollection>::Contents: std::fmt::Debug,
+    {
+        let mapped = self.view.query_with_collection_docs()?;
+        Ok(filter_mapped_documents(mapped, self.bounds, self.limit))
+    }
+
+    /// Executes the query and retrieves the results with the associated
+    /// documents, with the documents' contents projected by `P`.
+    pub fn query_with_projected_docs<P: Projection<Collection = V::Collection>>(
+        self,
+    ) -> Result<MappedDocuments<ProjectedDocument<P>, V>, Error> {
+        let mapped = self.view.query_with_projected_docs::<P>()?;
+        Ok(filter_mapped_documents(mapped, self.bounds, self.limit))
+    }
+
+    /// Executes a reduce over the results of the query.
+    pub fn reduce(self) -> Result<V::Value, Error> {
+        let connection = self.view.connection;
+        let grouped = self.reduce_grouped()?;
+        rereduce_grouped::<V>(connection.schematic(), grouped)
+    }
+
+    /// Executes a reduce over the results of the query, grouping by key.
+    pub fn reduce_grouped(self) -> Result<GroupedReductions<V>, Error> {
+        let mut grouped = self.view.reduce_grouped()?;
+        grouped.retain(|mapping| self.bounds.contains(mapping.key));
+        Ok(grouped)
+    }
+
+    /// Deletes all of the associated documents that match this view query.
+    pub fn delete_docs(mut self) -> Result<u64, Error> {
+        self.limit = None;
+        let connection = self.view.connection;
+        match bounded_deletion::<V>(self.query()?) {
+            Some((transaction, deleted)) => {
+                connection.apply_transaction(transaction)?;
+                Ok(deleted)
+            }
+            None => Ok(0),
+        }
+    }
+}
+
+fn filter_mappings<V: schema::SerializedView<Key = GeoPoint>>(
+    mut mappings: ViewMappings<V>,
+    bounds: GeoBounds,
+    limit: Option<u32>,
+) -> ViewMappings<V> {
+    mappings.retain(|mapping| bounds.contains(mapping.key));
+    if let Some(limit) = limit {
+        mappings.truncate(usize::try_from(limit).unwrap_or(usize::MAX));
+    }
+    mappings
+}
+
+fn filter_mapped_documents<D, V: schema::SerializedView<Key = GeoPoint>>(
+    mapped: MappedDocuments<D, V>,
+    bounds: GeoBounds,
+    limit: Option<u32>,
+) -> MappedDocuments<D, V> {
+    let mappings = filter_mappings::<V>(mapped.mappings, bounds, limit);
+    let mut documents = mapped.documents;
+    let sources = mappings
+        .iter()
+        .map(|mapping| mapping.source.id)
+        .collect::<BTreeSet<_>>();
+    documents.retain(|id, _| sources.contains(id));
+    MappedDocuments {
+        mappings,
+        documents,
+    }
+}
+
+/// Reduces `mappings` using `schematic`'s copy of the view `V`.
+fn reduce_mappings<V: schema::SerializedView>(
+    schematic: &schema::Schematic,
+    mappings: impl Iterator<Item = Map<V::Key, V::Value>>,
+) -> Result<V::Value, Error> {
+    let serialized = mappings
+        .map(|mapping| {
+            Ok((
+                mapping
+                    .key
+                    .as_ord_bytes()
+                    .map_err(|err| Error::other("key serialization", err))?
+                    .to_vec(),
+                V::serialize(&mapping.value)?,
+            ))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    let reduced = schematic.view::<V>()?.reduce(
+        &serialized
+            .iter()
+            .map(|(key, value)| (key.as_slice(), value.as_slice()))
+            .collect::<Vec<_>>(),
+        false,
+    )?;
+    V::deserialize(&reduced)
+}
+
+fn rereduce_grouped<V: schema::SerializedView<Key = GeoPoint>>(
+    schematic: &schema::Schematic,
+    mut grouped: GroupedReductions<V>,
+) -> Result<V::Value, Error> {
+    if grouped.len() == 1 {
+        return Ok(grouped.pop().unwrap().value);
+    }
+
+    let serialized = grouped
+        .iter()
+        .map(|mapping| {
+            Ok((
+                mapping.key.z_order().to_be_bytes(),
+                V::serialize(&mapping.value)?,
+            ))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    let reduced = schematic.view::<V>()?.reduce(
+        &serialized
+            .iter()
+            .map(|(key, value)| (&key[..], value.as_slice()))
+            .collect::<Vec<_>>(),
+        true,
+    )?;
+    V::deserialize(&reduced)
+}
+
+fn bounded_deletion<V: schema::SerializedView<Key = GeoPoint>>(
+    mappings: ViewMappings<V>,
+) -> Option<(transaction::Transaction, u64)> {
+    let headers = mappings
+        .into_iter()
+        .map(|mapping| (mapping.source.id, mapping.source))
+        .collect::<BTreeMap<_, _>>();
+    if headers.is_empty() {
+        return None;
+    }
+
+    let deleted = u64::try_from(headers.len()).unwrap();
+    let collection = <V::Collection as schema::Collection>::collection_name();
+    let mut transaction = transaction::Transaction::new();
+    for header in headers.into_values() {
+        transaction.push(transaction::Operation::delete(collection.clone(), header));
+    }
+    Some((transaction, deleted))
+}
+
+fn total_aggregation(
+    mut groups: Vec<SerializedAggregatedGroup>,
+    aggregations: &[Aggregation],
+) -> Vec<Option<f64>> {
+    groups.pop().map_or_else(
+        || Aggregator::default().finish(aggregations),
+        |group| group.values,
+    )
+}
+
+fn deserialize_mappings<V: schema::SerializedView>(
+    mappings: Vec<view_schema::map::Serialized>,
+) -> Result<ViewMappings<V>, Error> {
+    mappings
+        .into_iter()
+        .map(|mapping| {
+            Ok(Map {
+                key: <V::Key as Key>::from_ord_bytes(ByteCow::Borrowed(&mapping.key))
+                    .map_err(view_schema::Error::key_serialization)?,
+                value: V::deserialize(&mapping.value)?,
+                source: mapping.source,
+            })
+        })
+        .collect()
+}
+
+fn deserialize_aggregated_groups<K: for<'k> Key<'k>>(
+    groups: Vec<SerializedAggregatedGroup>,
+) -> Result<Vec<AggregatedGroup<K>>, Error> {
+    groups
+        .into_iter()
+        .map(|group| {
+            let key = group
+                .key
+                .ok_or_else(|| Error::other("aggregation", "aggregated group has no key"))?;
+            Ok(AggregatedGroup {
+                key: K::from_ord_bytes(ByteCow::Borrowed(&key))
+                    .map_err(view_schema::Error::key_serialization)?,
+                values: group.values,
+            })
+        })
+        .collect()
+}
+
+fn prefix_grouping<Prefix: for<'k> Key<'k>>() -> Result<AggregationGrouping, Error> {
+    <Prefix as KeyEncoding<'static, Prefix>>::LENGTH
+        .and_then(|length| u32::try_from(length).ok())
+        .map(AggregationGrouping::Prefix)
+        .ok_or_else(|| Error::other("aggregation", "prefix keys must have a fixed length"))
+}
+
+fn materialized_key<V, Key>(
+    schematic: &schema::Schematic,
+    key: Option<QueryKey<'_, V::Key, Key>>,
+) -> Result<String, Error>
+where
+    V: schema::SerializedView,
+    V::Key: Borrow<Key> + PartialEq<Key>,
+    Key: for<'k> KeyEncoding<'k, V::Key> + PartialEq + ?Sized,
+{
+    let view = schematic.view::<V>()?;
+    let key = key.map(|key| key.serialized()).transpose()?;
+    match (view.materialization(), key) {
+        (ViewMaterialization::Total, None) => {
+            Ok(view_schema::materialized_view_key(&view.view_name(), None))
+        }
+        (ViewMaterialization::Grouped, Some(SerializedQueryKey::Matches(key))) => Ok(
+            view_schema::materialized_view_key(&view.view_name(), Some(&key)),
+        ),
+        (ViewMaterialization::Total, Some(_)) => Err(Error::other(
+            "materialized view",
+            "totals can not be filtered by key",
+        )),
+        (ViewMaterialization::Grouped, _) => Err(Error::other(
+            "materialized view",
+            "grouped values can only be read for a single key",
+        )),
+        (ViewMaterialization::None, _) => Err(Error::other(
+            "materialized view",
+            "view is not materialized",
+        )),
+    }
+}
+
+fn deserialize_materialized_value<V: schema::SerializedView>(
+    value: Option<Value>,
+) -> Result<Option<V::Value>, Error> {
+    match value {
+        Some(Value::Bytes(bytes)) => V::deserialize(&bytes).map(Some),
+        Some(Value::Numeric(_)) => Err(Error::other(
+            "materialized view",
+            "materialized value is not a byte array",
+        )),
+        None => Ok(None),
+    }
+}
+
+/// This type is the result of `query()`. It is a list of mappings, which
+/// contains:
+///
+/// - The key emitted during the map function.
+/// - The value emitted during the map function.
+/// - The source document header that the mappings originated from.
+pub type ViewMappings<V> = Vec<Map<<V as schema::View>::Key, <V as schema::View>::Value>>;
+/// This type is the result of `reduce_grouped()`. It is a list of all matching
+/// keys and the reduced value of all mapped entries for that key.
+pub type GroupedReductions<V> =
+    Vec<MappedValue<<V as schema::View>::Key, <V as schema::View>::Value>>;
+
+/// A connection to a database's [`Schema`](schema::Schema), giving access to
+/// [`Collection`s](crate::schema::Collection) and
+/// [`Views`s](crate::schema::View). All functions on this trait are safe to use
+/// in an asynchronous context.
+#[async_trait]
+pub trait AsyncConnection: AsyncLowLevelConnection + Sized + Send + Sync {
+    /// The [`AsyncStorageConnection`] type that is paired with this type.
+    type Storage: AsyncStorageConnection<Database = Self>;
+
+    /// Returns the [`StorageConnection`] implementor that this database belongs
+    /// to.
+    fn storage(&self) -> Self::Storage;
+
+    /// Accesses a collection for the connected [`Schema`](schema::Schema).
+    fn collection<C: schema::Collection>(&self) -> AsyncCollection<'_, Self, C> {
+        AsyncCollection::new(self)
+    }
+
+    /// Accesses a [`schema::View`] from this connection.
+    fn view<V: schema::SerializedView>(&'_ self) -> AsyncView<'_, Self, V, V::Key> {
+        AsyncView::new(self)
+    }
+
+    /// Accesses a [`JoinedView`](view_schema::join::JoinedView) from this
+    /// connection.
+    fn joined_view<J: view_schema::join::JoinedView>(
+        &'_ self,
+    ) -> AsyncJoinedViewQuery<'_, Self, J> {
+        AsyncJoinedViewQuery::new(self)
+    }
+
+    /// Imports the operations produced by `operations`, applying them in
+    /// batched transactions. See [`AsyncImport`] for more information.
+    fn import<Operations>(&self, operations: Operations) -> AsyncImport<'_, Self, Operations>
+    where
+        Operations: futures::Stream<Item = Result<transaction::Operation, Error>> + Send,
+    {
+        AsyncImport::new(self, operations)
+    }
+
+    /// Lists [executed transactions](transaction::Executed) from this [`Schema`](schema::Schema). By default, a maximum of
+    /// 1000 entries will be returned, but that limit can be overridden by
+    /// setting `result_limit`. A hard limit of 100,000 results will be
+    /// returned. To begin listing after another known `transaction_id`, pass
+    /// `transaction_id + 1` into `starting_id`.
+    async fn list_executed_transactions(
+        &self,
+        starting_id: Option<u64>,
+        result_limit: Option<u32>,
+    ) -> Result<Vec<transaction::Executed>, Error>;
+
+    /// Fetches the last transaction id that has been committed, if any.
+    async fn last_transaction_id(&self) -> Result<Option<u64>, Error>;
+
+    /// Compacts the entire database to reclaim unused disk space.
+    ///
+    /// This process is done by writing data to a new file and swapping the file
+    /// once the process completes. This ensures that if a hardware failure,
+    /// power outage, or crash occurs that the original collection data is left
+    /// untouched.
+    ///
+    /// ## Errors
+    ///
+    /// * [`Error::Other`]: an error occurred while compacting the database.
+    async fn compact(&self) -> Result<(), crate::Error>;
+
+    /// Compacts the collection to reclaim unused disk space.
+    ///
+    /// This process is done by writing data to a new file and swapping the file
+    /// once the process completes. This ensures that if a hardware failure,
+    /// power outage, or crash occurs that the original collection data is left
+    /// untouched.
+    ///
+    /// ## Errors
+    ///
+    /// * [`Error::CollectionNotFound`]: database `name` does not exist.
+    /// * [`Error::Other`]: an error occurred while compacting the database.
+    async fn compact_collection<C: schema::Collection>(&self) -> Result<(), crate::Error> {
+        self.compact_collection_by_name(C::collection_name()).await
+    }
+
+    /// Compacts the key value store to reclaim unused disk space.
+    ///
+    /// This process is done by writing data to a new file and swapping the file
+    /// once the process completes. This ensures that if a hardware failure,
+    /// power outage, or crash occurs that the original collection data is left
+    /// untouched.
+    ///
+    /// ## Errors
+    ///
+    /// * [`Error::Other`]: an error occurred while compacting the database.
+    async fn compact_key_value_store(&self) -> Result<(), crate::Error>;
+}
+
+/// Interacts with a collection over a `Connection`.
+///
 /// These examples in this type use this basic collection definition:
 ///
 /// ```rust
@@ -1710,35 +2479,87 @@ where
         }
     }
 
-    /// Returns the number of documents contained within the range.
-    ///
-    /// Order and limit are ignored if they were set.
+    /// Retrieves the matching documents, with their contents projected by
+    /// `P`. Only the projected contents are transferred from the database.
     ///
     /// ```rust
     /// # bonsaidb_core::__doctest_prelude!();
     /// # use bonsaidb_core::connection::AsyncConnection;
+    /// # use bonsaidb_core::schema::Projection;
+    /// # #[derive(Debug)]
+    /// # struct Ranks;
+    /// # impl Projection for Ranks {
+    /// #     type Collection = MyCollection;
+    /// #     type Output = u32;
+    /// #     fn name(&self) -> Name {
+    /// #         Name::new("ranks")
+    /// #     }
+    /// #     fn project(&self, contents: MyCollection) -> u32 {
+    /// #         contents.rank
+    /// #     }
+    /// # }
     /// # fn test_fn<C: AsyncConnection>(db: &C) -> Result<(), Error> {
     /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
-    /// println!(
-    ///     "Number of documents with id 42 or larger: {}",
-    ///     db.collection::<MyCollection>().list(42..).count().await?
-    /// );
-    /// println!(
-    ///     "Number of documents in MyCollection: {}",
-    ///     db.collection::<MyCollection>().all().count().await?
-    /// );
+    /// for doc in db
+    ///     .collection::<MyCollection>()
+    ///     .all()
+    ///     .projected::<Ranks>()
+    ///     .await?
+    /// {
+    ///     println!("#{} has rank {}", doc.header.id, doc.contents);
+    /// }
     /// # Ok(())
     /// # })
     /// # }
     /// ```
-    pub async fn count(self) -> Result<u64, Error> {
+    pub async fn projected<P: Projection<Collection = Cl>>(
+        self,
+    ) -> Result<Vec<ProjectedDocument<P>>, Error> {
         match self.state {
             ListState::Pending(Some(AsyncListBuilder {
-                collection, range, ..
-            })) => collection.connection.count::<Cl, _, _>(range).await,
-            _ => unreachable!("Attempted to use after retrieving the result"),
-        }
-    }
+                collection,
+                range,
+                sort,
+                limit,
+            })) => {
+                collection
+                    .connection
+                    .list_projected::<P, _, _>(range, sort, limit)
+                    .await
+            }
+            _ => unreachable!("Attempted to use after retrieving the result"),
+        }
+    }
+
+    /// Returns the number of documents contained within the range.
+    ///
+    /// Order and limit are ignored if they were set.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::AsyncConnection;
+    /// # fn test_fn<C: AsyncConnection>(db: &C) -> Result<(), Error> {
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// println!(
+    ///     "Number of documents with id 42 or larger: {}",
+    ///     db.collection::<MyCollection>().list(42..).count().await?
+    /// );
+    /// println!(
+    ///     "Number of documents in MyCollection: {}",
+    ///     db.collection::<MyCollection>().all().count().await?
+    /// );
+    /// # Ok(())
+    /// # })
+    /// # }
+    /// ```
+    pub async fn count(self) -> Result<u64, Error> {
+        match self.state {
+            ListState::Pending(Some(AsyncListBuilder {
+                collection, range, ..
+            })) => collection.connection.count::<Cl, _, _>(range).await,
+            _ => unreachable!("Attempted to use after retrieving the result"),
+        }
+    }
 }
 
 #[allow(clippy::type_repetition_in_bounds)]
@@ -1840,6 +2661,10 @@ where
     /// The view's data access policy. The default value is [`AccessPolicy::UpdateBefore`].
     pub access_policy: AccessPolicy,
 
+    /// Where the query is executed when the database is replicated by a
+    /// cluster.
+    pub read_options: ViewReadOptions,
+
     /// The sort order of the query.
     pub sort: Sort,
 
@@ -1861,6 +2686,7 @@ where
             connection,
             key: None,
             access_policy: AccessPolicy::UpdateBefore,
+            read_options: ViewReadOptions::default(),
             sort: Sort::Ascending,
             limit: None,
             _view: PhantomData,
@@ -1897,6 +2723,7 @@ where
             connection: self.connection,
             key: Some(QueryKey::Matches(MaybeOwned::Borrowed(key))),
             access_policy: self.access_policy,
+            read_options: self.read_options,
             sort: self.sort,
             limit: self.limit,
             _view: PhantomData,
@@ -1936,6 +2763,7 @@ where
                 keys.into_iter().map(MaybeOwned::Borrowed).collect(),
             )),
             access_policy: self.access_policy,
+            read_options: self.read_options,
             sort: self.sort,
             limit: self.limit,
             _view: PhantomData,
@@ -1974,6 +2802,7 @@ where
             connection: self.connection,
             key: Some(QueryKey::Range(range.into())),
             access_policy: self.access_policy,
+            read_options: self.read_options,
             sort: self.sort,
             limit: self.limit,
             _view: PhantomData,
@@ -2014,6 +2843,7 @@ where
             connection: self.connection,
             key: Some(QueryKey::Range(prefix.to_prefix_range())),
             access_policy: self.access_policy,
+            read_options: self.read_options,
             sort: self.sort,
             limit: self.limit,
             _view: PhantomData,
@@ -2044,6 +2874,28 @@ where
         self
     }
 
+    /// Executes the query on the database's primary node when the database is
+    /// replicated by a cluster. By default, queries are executed by the node
+    /// the client is connected to, which may not have replicated the most
+    /// recent changes yet.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::AsyncConnection;
+    /// # fn test_fn<C: AsyncConnection>(db: C) -> Result<(), Error> {
+    /// let score = ScoresByRank::entries(&db)
+    ///     .read_from_primary_only()
+    ///     .reduce()
+    ///     .await?;
+    /// println!("Average score: {:3}", score);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub const fn read_from_primary_only(mut self) -> Self {
+        self.read_options.read_from_primary_only = true;
+        self
+    }
+
     /// Returns the matching mappings in ascending key order. This is the
     /// default sorting behavior.
     ///
@@ -2135,7 +2987,13 @@ where
     /// ```
     pub async fn query(self) -> Result<Vec<Map<V::Key, V::Value>>, Error> {
         self.connection
-            .query::<V, Key>(self.key, self.sort, self.limit, self.access_policy)
+            .query::<V, Key>(
+                self.key,
+                self.sort,
+                self.limit,
+                self.access_policy,
+                self.read_options,
+            )
             .await
     }
 
@@ -2162,7 +3020,13 @@ where
     /// ```
     pub async fn query_with_docs(self) -> Result<MappedDocuments<OwnedDocument, V>, Error> {
         self.connection
-            .query_with_docs::<V, _>(self.key, self.sort, self.limit, self.access_policy)
+            .query_with_docs::<V, _>(
+                self.key,
+                self.sort,
+                self.limit,
+                self.access_policy,
+                self.read_options,
+            )
             .await
     }
 
@@ -2195,7 +3059,63 @@ where
         <V::Collection as SerializedCollection>::Contents: std::fmt::Debug,
     {
         self.connection
-            .query_with_collection_docs::<V, _>(self.key, self.sort, self.limit, self.access_policy)
+            .query_with_collection_docs::<V, _>(
+                self.key,
+                self.sort,
+                self.limit,
+                self.access_policy,
+                self.read_options,
+            )
+            .await
+    }
+
+    /// Executes the query and retrieves the results with the associated
+    /// documents, with the documents' contents projected by `P`. Only the
+    /// projected contents are transferred from the database.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::AsyncConnection;
+    /// # use bonsaidb_core::schema::Projection;
+    /// # #[derive(Debug)]
+    /// # struct Ranks;
+    /// # impl Projection for Ranks {
+    /// #     type Collection = MyCollection;
+    /// #     type Output = u32;
+    /// #     fn name(&self) -> Name {
+    /// #         Name::new("ranks")
+    /// #     }
+    /// #     fn project(&self, contents: MyCollection) -> u32 {
+    /// #         contents.rank
+    /// #     }
+    /// # }
+    /// # fn test_fn<C: AsyncConnection>(db: C) -> Result<(), Error> {
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// for mapping in &ScoresByRank::entries_async(&db)
+    ///     .with_key_range(42..=44)
+    ///     .query_with_projected_docs::<Ranks>()
+    ///     .await?
+    /// {
+    ///     println!(
+    ///         "Mapping from #{} with score: {}. Projected rank: {}",
+    ///         mapping.document.header.id, mapping.value, mapping.document.contents
+    ///     );
+    /// }
+    /// # Ok(())
+    /// # })
+    /// # }
+    /// ```
+    pub async fn query_with_projected_docs<P: Projection<Collection = V::Collection>>(
+        self,
+    ) -> Result<MappedDocuments<ProjectedDocument<P>, V>, Error> {
+        self.connection
+            .query_with_projected_docs::<V, P, _>(
+                self.key,
+                self.sort,
+                self.limit,
+                self.access_policy,
+                self.read_options,
+            )
             .await
     }
 
@@ -2215,7 +3135,7 @@ where
     /// ```
     pub async fn reduce(self) -> Result<V::Value, Error> {
         self.connection
-            .reduce::<V, _>(self.key, self.access_policy)
+            .reduce::<V, _>(self.key, self.access_policy, self.read_options)
             .await
     }
 
@@ -2239,26 +3159,466 @@ where
     /// ```
     pub async fn reduce_grouped(self) -> Result<Vec<MappedValue<V::Key, V::Value>>, Error> {
         self.connection
-            .reduce_grouped::<V, _>(self.key, self.access_policy)
+            .reduce_grouped::<V, _>(self.key, self.access_policy, self.read_options)
+            .await
+    }
+
+    /// Executes a reduce over the entries with any of `keys`. This is
+    /// equivalent to `self.with_keys(keys).reduce().await`.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::AsyncConnection;
+    /// # fn test_fn<C: AsyncConnection>(db: C) -> Result<(), Error> {
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// // score is an f32 in this example
+    /// let score = ScoresByRank::entries_async(&db)
+    ///     .reduce_for_keys(&[42, 43])
+    ///     .await?;
+    /// println!("Average score of ranks 42 and 43: {:3}", score);
+    /// # Ok(())
+    /// # })
+    /// # }
+    /// ```
+    pub async fn reduce_for_keys<K, IntoIter: IntoIterator<Item = &'a K>>(
+        self,
+        keys: IntoIter,
+    ) -> Result<V::Value, Error>
+    where
+        V::Key: Borrow<K> + PartialEq<K>,
+        K: for<'k> KeyEncoding<'k, V::Key> + PartialEq + ?Sized,
+    {
+        self.with_keys(keys).reduce().await
+    }
+
+    /// Executes a reduce over the entries emitted by the documents with `ids`
+    /// that match this query's key filter. See
+    /// [`View::reduce_for_documents()`] for more information.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::AsyncConnection;
+    /// # fn test_fn<C: AsyncConnection>(db: C) -> Result<(), Error> {
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// // score is an f32 in this example
+    /// let score = ScoresByRank::entries_async(&db)
+    ///     .reduce_for_documents(&[1_u64, 2, 3])
+    ///     .await?;
+    /// println!("Average score of documents 1, 2, and 3: {:3}", score);
+    /// # Ok(())
+    /// # })
+    /// # }
+    /// ```
+    pub async fn reduce_for_documents<'id, DocumentIds, PrimaryKey>(
+        mut self,
+        ids: DocumentIds,
+    ) -> Result<V::Value, Error>
+    where
+        DocumentIds: IntoIterator<Item = &'id PrimaryKey> + Send + Sync,
+        PrimaryKey: for<'k> KeyEncoding<'k, <V::Collection as schema::Collection>::PrimaryKey>
+            + 'id
+            + ?Sized,
+    {
+        let ids = ids
+            .into_iter()
+            .map(DocumentId::new)
+            .collect::<Result<BTreeSet<_>, _>>()?;
+        let connection = self.connection;
+        self.limit = None;
+        let mappings = self.query().await?;
+        reduce_mappings::<V>(
+            connection.schematic(),
+            mappings
+                .into_iter()
+                .filter(|mapping| ids.contains(&mapping.source.id)),
+        )
+    }
+
+    /// Deletes all of the associated documents that match this view query.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::AsyncConnection;
+    /// # fn test_fn<C: AsyncConnection>(db: C) -> Result<(), Error> {
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// ScoresByRank::entries_async(&db).delete_docs().await?;
+    /// # Ok(())
+    /// # })
+    /// # }
+    /// ```
+    pub async fn delete_docs(self) -> Result<u64, Error> {
+        self.connection
+            .delete_docs::<V, _>(self.key, self.access_policy)
+            .await
+    }
+
+    /// Returns the number of entries that match this query. The entries are
+    /// counted by the database, without transferring any mappings. The sort
+    /// order and limit of this query are ignored.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::AsyncConnection;
+    /// # fn test_fn<C: AsyncConnection>(db: C) -> Result<(), Error> {
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let top_ten = ScoresByRank::entries_async(&db)
+    ///     .with_key_range(1..=10)
+    ///     .count()
+    ///     .await?;
+    /// println!("{top_ten} entries have a rank between 1 and 10");
+    /// # Ok(())
+    /// # })
+    /// # }
+    /// ```
+    pub async fn count(self) -> Result<u64, Error> {
+        let view = self.connection.schematic().view::<V>()?;
+        self.connection
+            .count_by_name(
+                &view.view_name(),
+                self.key.map(|key| key.serialized()).transpose()?,
+                self.access_policy,
+                self.read_options,
+            )
+            .await
+    }
+
+    /// Returns the entries that the document with `id` emitted into this view.
+    /// The key filter, sort order, and limit of this query are ignored.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::AsyncConnection;
+    /// # fn test_fn<C: AsyncConnection>(db: C) -> Result<(), Error> {
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// for mapping in ScoresByRank::entries_async(&db)
+    ///     .for_document(&42_u64)
+    ///     .await?
+    /// {
+    ///     println!("Document 42 has rank {} with score {:3}", mapping.key, mapping.value);
+    /// }
+    /// # Ok(())
+    /// # })
+    /// # }
+    /// ```
+    pub async fn for_document<PrimaryKey>(self, id: &PrimaryKey) -> Result<ViewMappings<V>, Error>
+    where
+        PrimaryKey: for<'k> KeyEncoding<'k, <V::Collection as schema::Collection>::PrimaryKey>
+            + ?Sized
+            + Sync,
+    {
+        let view = self.connection.schematic().view::<V>()?;
+        let mappings = self
+            .connection
+            .document_mappings_by_name(
+                &view.view_name(),
+                DocumentId::new(id)?,
+                self.access_policy,
+                self.read_options,
+            )
+            .await?;
+        deserialize_mappings::<V>(mappings)
+    }
+
+    /// Computes `aggregations` over the values of all entries matching this
+    /// query. The results are returned in the same order as `aggregations`.
+    /// The sort order and limit of this query are ignored.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::AsyncConnection;
+    /// # use bonsaidb_core::schema::view::aggregation::Aggregation;
+    /// # fn test_fn<C: AsyncConnection>(db: C) -> Result<(), Error> {
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// // score is an f32 in this example
+    /// let stats = ScoresByRank::entries_async(&db)
+    ///     .aggregate(&[Aggregation::Average, Aggregation::Percentile(90.)])
+    ///     .await?;
+    /// println!("Average score: {:?}, 90th percentile: {:?}", stats[0], stats[1]);
+    /// # Ok(())
+    /// # })
+    /// # }
+    /// ```
+    pub async fn aggregate(self, aggregations: &[Aggregation]) -> Result<Vec<Option<f64>>, Error> {
+        let groups = self
+            .aggregate_by_grouping(aggregations, AggregationGrouping::None)
+            .await?;
+        Ok(total_aggregation(groups, aggregations))
+    }
+
+    /// Computes `aggregations` over the values of entries matching this query,
+    /// grouping the entries by key. The sort order and limit of this query are
+    /// ignored.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::AsyncConnection;
+    /// # use bonsaidb_core::schema::view::aggregation::Aggregation;
+    /// # fn test_fn<C: AsyncConnection>(db: C) -> Result<(), Error> {
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// // score is an f32 in this example
+    /// for group in ScoresByRank::entries_async(&db)
+    ///     .aggregate_grouped(&[Aggregation::Max])
+    ///     .await?
+    /// {
+    ///     println!("Rank {} has a high score of {:?}", group.key, group.values[0]);
+    /// }
+    /// # Ok(())
+    /// # })
+    /// # }
+    /// ```
+    pub async fn aggregate_grouped(
+        self,
+        aggregations: &[Aggregation],
+    ) -> Result<Vec<AggregatedGroup<V::Key>>, Error> {
+        let groups = self
+            .aggregate_by_grouping(aggregations, AggregationGrouping::Key)
+            .await?;
+        deserialize_aggregated_groups(groups)
+    }
+
+    /// Computes `aggregations` over the values of entries matching this query,
+    /// grouping the entries by the leading `Prefix` of each key. `Prefix` must
+    /// be a [`Key`] with a fixed [length](KeyEncoding::LENGTH), such as the
+    /// first field of a tuple key. The sort order and limit of this query are
+    /// ignored.
+    pub async fn aggregate_grouped_by_prefix<Prefix>(
+        self,
+        aggregations: &[Aggregation],
+    ) -> Result<Vec<AggregatedGroup<Prefix>>, Error>
+    where
+        Prefix: for<'k> Key<'k>,
+    {
+        let groups = self
+            .aggregate_by_grouping(aggregations, prefix_grouping::<Prefix>()?)
+            .await?;
+        deserialize_aggregated_groups(groups)
+    }
+
+    async fn aggregate_by_grouping(
+        self,
+        aggregations: &[Aggregation],
+        grouping: AggregationGrouping,
+    ) -> Result<Vec<SerializedAggregatedGroup>, Error> {
+        let view = self.connection.schematic().view::<V>()?;
+        self.connection
+            .aggregate_by_name(
+                &view.view_name(),
+                self.key.map(|key| key.serialized()).transpose()?,
+                aggregations,
+                grouping,
+                self.access_policy,
+                self.read_options,
+            )
             .await
     }
+}
+
+impl<'a, Cn, V, Key> AsyncView<'a, Cn, V, Key>
+where
+    V::Key: Borrow<Key> + PartialEq<Key>,
+    V: schema::SerializedView,
+    Cn: AsyncConnection + AsyncKeyValue,
+    Key: for<'k> KeyEncoding<'k, V::Key> + PartialEq + ?Sized,
+{
+    /// Returns the reduced value that was materialized into the key-value
+    /// store the last time the view was updated. Returns `None` if no value
+    /// has been materialized.
+    ///
+    /// Views using [`ViewMaterialization::Total`] must be read without a key
+    /// filter. Views using [`ViewMaterialization::Grouped`] must be read with
+    /// a filter matching a single key.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::AsyncConnection;
+    /// # use bonsaidb_core::keyvalue::AsyncKeyValue;
+    /// # fn test_fn<C: AsyncConnection + AsyncKeyValue>(db: C) -> Result<(), Error> {
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// // ScoresByRank is materialized with ViewMaterialization::Grouped
+    /// if let Some(score) = ScoresByRank::entries_async(&db)
+    ///     .with_key(&42)
+    ///     .materialized_reduce()
+    ///     .await?
+    /// {
+    ///     println!("Rank 42 has an average score of {:3}", score);
+    /// }
+    /// # Ok(())
+    /// # })
+    /// # }
+    /// ```
+    pub async fn materialized_reduce(self) -> Result<Option<V::Value>, Error> {
+        let key = materialized_key::<V, Key>(self.connection.schematic(), self.key)?;
+        let value = self
+            .connection
+            .with_key_namespace(MATERIALIZED_VIEW_NAMESPACE)
+            .get_key(key)
+            .await?;
+        deserialize_materialized_value::<V>(value)
+    }
+}
+
+impl<'a, Cn, V, Key> AsyncView<'a, Cn, V, Key>
+where
+    V: schema::SerializedView<Key = GeoPoint>,
+    Cn: AsyncConnection,
+    Key: for<'k> KeyEncoding<'k, GeoPoint> + PartialEq + ?Sized,
+    V::Key: Borrow<Key> + PartialEq<Key>,
+{
+    /// Filters for entries in the view with keys inside of `bounds`.
+    ///
+    /// The view is scanned using [`GeoBounds::key_range()`], and entries
+    /// outside of `bounds` are filtered out of the results. If a
+    /// [`limit`](Self::limit) was set, it is applied after filtering.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::AsyncConnection;
+    /// # use bonsaidb_core::key::geo::{GeoBounds, GeoPoint};
+    /// # fn test_fn<C: AsyncConnection>(db: C) -> Result<(), Error> {
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// #[derive(View, Debug, Clone)]
+    /// #[view(name = "by-location", key = GeoPoint, collection = MyCollection)]
+    /// # #[view(core = bonsaidb_core)]
+    /// struct ByLocation;
+    ///
+    /// let oregon = GeoBounds::new(41.99, -124.57, 46.29, -116.46).unwrap();
+    /// for mapping in ByLocation::entries_async(&db)
+    ///     .with_key_bounds(oregon)
+    ///     .query()
+    ///     .await?
+    /// {
+    ///     assert!(oregon.contains(mapping.key));
+    ///     println!("{:?} in document {:?}", mapping.key, mapping.source);
+    /// }
+    /// # Ok(())
+    /// # })
+    /// # }
+    /// ```
+    pub fn with_key_bounds(self, bounds: GeoBounds) -> AsyncBoundedView<'a, Cn, V> {
+        AsyncBoundedView {
+            view: AsyncView {
+                connection: self.connection,
+                key: Some(QueryKey::Range(RangeRef::from(bounds.key_range()))),
+                access_policy: self.access_policy,
+                read_options: self.read_options,
+                sort: self.sort,
+                limit: None,
+                _view: PhantomData,
+            },
+            bounds,
+            limit: self.limit,
+        }
+    }
+}
+
+/// An [`AsyncView`] query that is filtered to keys within a [`GeoBounds`]. Created
+/// by [`AsyncView::with_key_bounds()`].
+#[must_use]
+pub struct AsyncBoundedView<'a, Cn, V>
+where
+    V: schema::SerializedView<Key = GeoPoint>,
+{
+    view: AsyncView<'a, Cn, V, GeoPoint>,
+    bounds: GeoBounds,
+    limit: Option<u32>,
+}
+
+impl<'a, Cn, V> AsyncBoundedView<'a, Cn, V>
+where
+    V: schema::SerializedView<Key = GeoPoint>,
+    Cn: AsyncConnection,
+{
+    /// Sets the access policy for queries.
+    pub const fn with_access_policy(mut self, policy: AccessPolicy) -> Self {
+        self.view.access_policy = policy;
+        self
+    }
+
+    /// Executes the query on the database's primary node when the database is
+    /// replicated by a cluster.
+    pub const fn read_from_primary_only(mut self) -> Self {
+        self.view.read_options.read_from_primary_only = true;
+        self
+    }
+
+    /// Returns the matching mappings in ascending key order. This is the
+    /// default sorting behavior.
+    pub const fn ascending(mut self) -> Self {
+        self.view.sort = Sort::Ascending;
+        self
+    }
+
+    /// Returns the matching mappings in descending key order.
+    pub const fn descending(mut self) -> Self {
+        self.view.sort = Sort::Descending;
+        self
+    }
+
+    /// Sets the maximum number of results to return. The limit is applied
+    /// after the results have been filtered by the bounds.
+    pub const fn limit(mut self, maximum_results: u32) -> Self {
+        self.limit = Some(maximum_results);
+        self
+    }
+
+    /// Executes the query and retrieves the results.
+    pub async fn query(self) -> Result<ViewMappings<V>, Error> {
+        let mappings = self.view.query().await?;
+        Ok(filter_mappings(mappings, self.bounds, self.limit))
+    }
+
+    /// Executes the query and retrieves the results with the associated [`Document`s](crate::document::OwnedDocument).
+    pub async fn query_with_docs(self) -> Result<MappedDocuments<OwnedDocument, V>, Error> {
+        let mapped = self.view.query_with_docs().await?;
+        Ok(filter_mapped_documents(mapped, self.bounds, self.limit))
+    }
+
+    /// Executes the query and retrieves the results with the associated [`CollectionDocument`s](crate::document::CollectionDocument).
+    pub async fn query_with_collection_docs(
+        self,
+    ) -> Result<MappedDocuments<CollectionDocument<V::Collection>, V>, Error>
+    where
+        V::Collection: SerializedCollection,
+        <V::Collection as SerializedCollection>::Contents: std::fmt::Debug,
+    {
+        let mapped = self.view.query_with_collection_docs().await?;
+        Ok(filter_mapped_documents(mapped, self.bounds, self.limit))
+    }
+
+    /// Executes the query and retrieves the results with the associated
+    /// documents, with the documents' contents projected by `P`.
+    pub async fn query_with_projected_docs<P: Projection<Collection = V::Collection>>(
+        self,
+    ) -> Result<MappedDocuments<ProjectedDocument<P>, V>, Error> {
+        let mapped = self.view.query_with_projected_docs::<P>().await?;
+        Ok(filter_mapped_documents(mapped, self.bounds, self.limit))
+    }
+
+    /// Executes a reduce over the results of the query.
+    pub async fn reduce(self) -> Result<V::Value, Error> {
+        let connection = self.view.connection;
+        let grouped = self.reduce_grouped().await?;
+        rereduce_grouped::<V>(connection.schematic(), grouped)
+    }
+
+    /// Executes a reduce over the results of the query, grouping by key.
+    pub async fn reduce_grouped(self) -> Result<GroupedReductions<V>, Error> {
+        let mut grouped = self.view.reduce_grouped().await?;
+        grouped.retain(|mapping| self.bounds.contains(mapping.key));
+        Ok(grouped)
+    }
 
     /// Deletes all of the associated documents that match this view query.
-    ///
-    /// ```rust
-    /// # bonsaidb_core::__doctest_prelude!();
-    /// # use bonsaidb_core::connection::AsyncConnection;
-    /// # fn test_fn<C: AsyncConnection>(db: C) -> Result<(), Error> {
-    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
-    /// ScoresByRank::entries_async(&db).delete_docs().await?;
-    /// # Ok(())
-    /// # })
-    /// # }
-    /// ```
-    pub async fn delete_docs(self) -> Result<u64, Error> {
-        self.connection
-            .delete_docs::<V, _>(self.key, self.access_policy)
-            .await
+    pub async fn delete_docs(mut self) -> Result<u64, Error> {
+        self.limit = None;
+        let connection = self.view.connection;
+        match bounded_deletion::<V>(self.query().await?) {
+            Some((transaction, deleted)) => {
+                connection.apply_transaction(transaction).await?;
+                Ok(deleted)
+            }
+            None => Ok(0),
+        }
     }
 }
 
@@ -2965,6 +4325,48 @@ pub enum AccessPolicy {
     /// shouldn't have much overhead, this option removes all overhead related
     /// to view updating from the query.
     NoUpdate,
+
+    /// Return the results without updating the view first if the view has
+    /// been up-to-date at some point within the specified duration, and start
+    /// an update job in the background. Otherwise, update any changed documents
+    /// before returning a response. This balances response times and freshness
+    /// by bounding how out-of-date the results can be.
+    MaxStaleness(Duration),
+
+    /// Return the results without updating the view first if the view has
+    /// been updated to include the transaction with the specified id, and
+    /// start an update job in the background. Otherwise, update any changed
+    /// documents before returning a response. This can be used to ensure the
+    /// results reflect a transaction that was previously executed, such as
+    /// one returned by
+    /// [`Connection::last_transaction_id()`](Connection::last_transaction_id).
+    UpToTransaction(u64),
+}
+
+/// Options controlling which node of a cluster executes a view request.
+///
+/// These options are ignored when the database isn't replicated by a cluster.
+#[derive(Copy, Clone, Serialize, Deserialize, Default, Debug, Eq, PartialEq)]
+#[must_use]
+#[non_exhaustive]
+pub struct ViewReadOptions {
+    /// If true, the request is executed by the database's primary node, and
+    /// the results reflect every transaction committed before the request.
+    /// Otherwise, the request is executed by the node that received it, whose
+    /// results may not reflect the most recent changes. The default value is
+    /// `false`.
+    #[serde(default)]
+    pub read_from_primary_only: bool,
+}
+
+impl ViewReadOptions {
+    /// Returns options that execute the request on the database's primary
+    /// node.
+    pub const fn primary_only() -> Self {
+        Self {
+            read_from_primary_only: true,
+        }
+    }
 }
 
 /// Functions for interacting with a multi-database BonsaiDb instance.
@@ -3026,7 +4428,13 @@ pub trait StorageConnection: HasSession + Sized + Send + Sync {
     fn delete_database(&self, name: &str) -> Result<(), crate::Error>;
 
     /// Lists the databases in this storage.
-    fn list_databases(&self) -> Result<Vec<Database>, crate::Error>;
+    fn list_databases(&self) -> Result<Vec<Database>, crate::Error> {
+        self.list_databases_matching(DatabaseQuery::default())
+    }
+
+    /// Lists the databases in this storage that match `query`, ordered by
+    /// name.
+    fn list_databases_matching(&self, query: DatabaseQuery) -> Result<Vec<Database>, crate::Error>;
 
     /// Lists the [`SchemaName`]s registered with this storage.
     fn list_available_schemas(&self) -> Result<Vec<SchemaName>, crate::Error>;
@@ -3040,6 +4448,73 @@ pub trait StorageConnection: HasSession + Sized + Send + Sync {
         user: U,
     ) -> Result<(), crate::Error>;
 
+    /// Lists all users. The returned documents will not contain the users'
+    /// password hashes.
+    fn list_users(&self) -> Result<Vec<CollectionDocument<User>>, crate::Error>;
+
+    /// Returns the user named `username`, if one exists. The returned document
+    /// will not contain the user's password hash.
+    fn user_by_name(
+        &self,
+        username: &str,
+    ) -> Result<Option<CollectionDocument<User>>, crate::Error>;
+
+    /// Disables a user. A disabled user is unable to authenticate or have its
+    /// identity assumed, and all of its active sessions are revoked.
+    fn disable_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<(), crate::Error>;
+
+    /// Re-enables a user previously disabled with
+    /// [`disable_user()`](Self::disable_user).
+    fn enable_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<(), crate::Error>;
+
+    /// Removes any lockout preventing a user from authenticating with a
+    /// password, and clears the user's count of failed authentication
+    /// attempts.
+    fn unlock_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<(), crate::Error>;
+
+    /// Returns the effective permissions of `user`. This is the merged set of
+    /// permissions from the [`PermissionGroup`]s the user belongs to, directly
+    /// or through its [`Role`]s, combined with the permissions granted to all
+    /// authenticated sessions. A session authenticated as `user` is granted
+    /// these permissions.
+    fn effective_permissions<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<Permissions, crate::Error>;
+
+    /// Checks whether `user` is permitted to perform `action` against
+    /// `resource_name` without performing the action.
+    ///
+    /// ## Errors
+    ///
+    /// * [`Error::PermissionDenied`]: `user` is not permitted to perform
+    ///   `action`.
+    fn check_user_permission<
+        'user,
+        'a,
+        U: Nameable<'user, u64> + Send + Sync,
+        R: AsRef<[Identifier<'a>]>,
+        P: Action,
+    >(
+        &self,
+        user: U,
+        resource_name: R,
+        action: &P,
+    ) -> Result<(), crate::Error> {
+        self.effective_permissions(user)?
+            .check(resource_name, action)
+            .map_err(crate::Error::from)
+    }
+
     /// Sets a user's password.
     #[cfg(feature = "password-hashing")]
     fn set_user_password<'user, U: Nameable<'user, u64> + Send + Sync>(
@@ -3064,6 +4539,24 @@ pub trait StorageConnection: HasSession + Sized + Send + Sync {
         identity: IdentityReference<'_>,
     ) -> Result<Self::Authenticated, crate::Error>;
 
+    /// Returns an instance that executes operations using the permissions of
+    /// `user`, without requiring the user's credentials. This is intended for
+    /// trusted application servers that have authenticated an end-user by
+    /// other means.
+    ///
+    /// Like [`assume_identity()`](Self::assume_identity), this requires
+    /// permission to perform
+    /// [`ServerAction::AssumeIdentity`](crate::permissions::bonsai::ServerAction::AssumeIdentity)
+    /// on the user. Implementations may avoid registering a session for the
+    /// returned instance, in which case the user's permissions only apply until
+    /// the instance is dropped.
+    fn as_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<Self::Authenticated, crate::Error> {
+        self.assume_identity(IdentityReference::user(user)?)
+    }
+
     /// Authenticates using an
     /// [`AuthenticationToken`](crate::admin::AuthenticationToken). If
     ///  successful, the returned instance will have the permissions from
@@ -3157,6 +4650,161 @@ pub trait StorageConnection: HasSession + Sized + Send + Sync {
         user: U,
         role: R,
     ) -> Result<(), crate::Error>;
+
+    /// Creates a [`PermissionGroup`] named `name` containing `statements`,
+    /// returning the new group's id.
+    fn create_permission_group(
+        &self,
+        name: &str,
+        statements: Vec<Statement>,
+    ) -> Result<u64, crate::Error>;
+
+    /// Lists all [`PermissionGroup`]s.
+    fn list_permission_groups(
+        &self,
+    ) -> Result<Vec<CollectionDocument<PermissionGroup>>, crate::Error>;
+
+    /// Replaces the statements of a [`PermissionGroup`] with `statements`.
+    /// The statements are checked using
+    /// [`validate_statements()`](crate::permissions::bonsai::validate_statements).
+    fn set_permission_group_statements<'group, G: Nameable<'group, u64> + Send + Sync>(
+        &self,
+        permission_group: G,
+        statements: Vec<Statement>,
+    ) -> Result<(), crate::Error>;
+
+    /// Replaces the statements of a [`PermissionGroup`] with `statements` if
+    /// the group's [`version`](PermissionGroup::version) is
+    /// `expected_version`, returning the group's new version.
+    ///
+    /// If the group has been changed since `expected_version` was read,
+    /// [`Error::VersionConflict`](crate::Error::VersionConflict) is returned
+    /// and no changes are made. The statements are checked using
+    /// [`validate_statements()`](crate::permissions::bonsai::validate_statements).
+    fn update_permission_group_statements<'group, G: Nameable<'group, u64> + Send + Sync>(
+        &self,
+        permission_group: G,
+        expected_version: u64,
+        statements: Vec<Statement>,
+    ) -> Result<u64, crate::Error>;
+
+    /// Deletes a [`PermissionGroup`].
+    fn delete_permission_group<'group, G: Nameable<'group, u64> + Send + Sync>(
+        &self,
+        permission_group: G,
+    ) -> Result<(), crate::Error>;
+
+    /// Creates a [`Role`] named `name`, returning the new role's id.
+    fn create_role(&self, name: &str) -> Result<u64, crate::Error>;
+
+    /// Lists all [`Role`]s.
+    fn list_roles(&self) -> Result<Vec<CollectionDocument<Role>>, crate::Error>;
+
+    /// Adds a permission group to a role.
+    fn add_permission_group_to_role<
+        'role,
+        'group,
+        R: Nameable<'role, u64> + Send + Sync,
+        G: Nameable<'group, u64> + Send + Sync,
+    >(
+        &self,
+        role: R,
+        permission_group: G,
+    ) -> Result<(), crate::Error>;
+
+    /// Removes a permission group from a role.
+    fn remove_permission_group_from_role<
+        'role,
+        'group,
+        R: Nameable<'role, u64> + Send + Sync,
+        G: Nameable<'group, u64> + Send + Sync,
+    >(
+        &self,
+        role: R,
+        permission_group: G,
+    ) -> Result<(), crate::Error>;
+
+    /// Replaces the permission groups of a [`Role`] with `permission_groups`
+    /// if the role's [`version`](Role::version) is `expected_version`,
+    /// returning the role's new version.
+    ///
+    /// If the role has been changed since `expected_version` was read,
+    /// [`Error::VersionConflict`](crate::Error::VersionConflict) is returned
+    /// and no changes are made.
+    fn update_role_permission_groups<'role, R: Nameable<'role, u64> + Send + Sync>(
+        &self,
+        role: R,
+        expected_version: u64,
+        permission_groups: Vec<u64>,
+    ) -> Result<u64, crate::Error>;
+
+    /// Deletes a [`Role`].
+    fn delete_role<'role, R: Nameable<'role, u64> + Send + Sync>(
+        &self,
+        role: R,
+    ) -> Result<(), crate::Error>;
+
+    /// Lists the active sessions authenticated as `user`.
+    fn list_sessions<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<Vec<SessionInfo>, crate::Error>;
+
+    /// Revokes the session with id `session_id`. Once revoked, any further
+    /// requests made using the session will fail with
+    /// [`Error::SessionExpired`].
+    fn revoke_session(&self, session_id: SessionId) -> Result<(), crate::Error>;
+
+    /// Revokes the currently authenticated session, if any.
+    fn logout(&self) -> Result<(), crate::Error> {
+        match self.session().and_then(|session| session.id) {
+            Some(session_id) => self.revoke_session(session_id),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the permission groups, roles, and users of this instance as a
+    /// [`SecurityConfiguration`].
+    fn export_security_configuration(&self) -> Result<SecurityConfiguration, crate::Error> {
+        Ok(SecurityConfiguration::from_records(
+            self.list_permission_groups()?,
+            self.list_roles()?,
+            self.list_users()?,
+        ))
+    }
+
+    /// Creates, updates, and deletes permission groups, roles, and users until
+    /// they match `configuration`, returning the changes that were made.
+    /// Applying the same configuration again makes no changes.
+    ///
+    /// Records that aren't part of `configuration` are deleted, including any
+    /// users that aren't listed. Users created by this function have no
+    /// password, and existing users' passwords are unchanged.
+    ///
+    /// The changes are made one at a time. If a change fails, the changes made
+    /// before it are kept, and applying the configuration again completes the
+    /// remaining changes.
+    ///
+    /// ## Errors
+    ///
+    /// - [`Error::Other`]: `configuration` references a permission group or
+    ///   role that it doesn't contain.
+    /// - [`Error::PermissionDenied`]: the session is not allowed to make one of
+    ///   the changes.
+    fn apply_security_configuration(
+        &self,
+        configuration: &SecurityConfiguration,
+    ) -> Result<SecurityChanges, crate::Error> {
+        let plan = configuration.plan(
+            self.list_permission_groups()?,
+            self.list_roles()?,
+            self.list_users()?,
+        )?;
+        for step in plan.steps {
+            step.apply(self)?;
+        }
+        Ok(plan.changes)
+    }
 }
 
 /// Functions for interacting with a multi-database BonsaiDb instance.
@@ -3218,7 +4866,16 @@ pub trait AsyncStorageConnection: HasSession + Sized + Send + Sync {
     async fn delete_database(&self, name: &str) -> Result<(), crate::Error>;
 
     /// Lists the databases in this storage.
-    async fn list_databases(&self) -> Result<Vec<Database>, crate::Error>;
+    async fn list_databases(&self) -> Result<Vec<Database>, crate::Error> {
+        self.list_databases_matching(DatabaseQuery::default()).await
+    }
+
+    /// Lists the databases in this storage that match `query`, ordered by
+    /// name.
+    async fn list_databases_matching(
+        &self,
+        query: DatabaseQuery,
+    ) -> Result<Vec<Database>, crate::Error>;
 
     /// Lists the [`SchemaName`]s registered with this storage.
     async fn list_available_schemas(&self) -> Result<Vec<SchemaName>, crate::Error>;
@@ -3232,6 +4889,74 @@ pub trait AsyncStorageConnection: HasSession + Sized + Send + Sync {
         user: U,
     ) -> Result<(), crate::Error>;
 
+    /// Lists all users. The returned documents will not contain the users'
+    /// password hashes.
+    async fn list_users(&self) -> Result<Vec<CollectionDocument<User>>, crate::Error>;
+
+    /// Returns the user named `username`, if one exists. The returned document
+    /// will not contain the user's password hash.
+    async fn user_by_name(
+        &self,
+        username: &str,
+    ) -> Result<Option<CollectionDocument<User>>, crate::Error>;
+
+    /// Disables a user. A disabled user is unable to authenticate or have its
+    /// identity assumed, and all of its active sessions are revoked.
+    async fn disable_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<(), crate::Error>;
+
+    /// Re-enables a user previously disabled with
+    /// [`disable_user()`](Self::disable_user).
+    async fn enable_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<(), crate::Error>;
+
+    /// Removes any lockout preventing a user from authenticating with a
+    /// password, and clears the user's count of failed authentication
+    /// attempts.
+    async fn unlock_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<(), crate::Error>;
+
+    /// Returns the effective permissions of `user`. This is the merged set of
+    /// permissions from the [`PermissionGroup`]s the user belongs to, directly
+    /// or through its [`Role`]s, combined with the permissions granted to all
+    /// authenticated sessions. A session authenticated as `user` is granted
+    /// these permissions.
+    async fn effective_permissions<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<Permissions, crate::Error>;
+
+    /// Checks whether `user` is permitted to perform `action` against
+    /// `resource_name` without performing the action.
+    ///
+    /// ## Errors
+    ///
+    /// * [`Error::PermissionDenied`]: `user` is not permitted to perform
+    ///   `action`.
+    async fn check_user_permission<
+        'user,
+        'a,
+        U: Nameable<'user, u64> + Send + Sync,
+        R: AsRef<[Identifier<'a>]> + Send + Sync,
+        P: Action + Send + Sync,
+    >(
+        &self,
+        user: U,
+        resource_name: R,
+        action: &P,
+    ) -> Result<(), crate::Error> {
+        self.effective_permissions(user)
+            .await?
+            .check(resource_name, action)
+            .map_err(crate::Error::from)
+    }
+
     /// Sets a user's password.
     #[cfg(feature = "password-hashing")]
     async fn set_user_password<'user, U: Nameable<'user, u64> + Send + Sync>(
@@ -3307,6 +5032,24 @@ pub trait AsyncStorageConnection: HasSession + Sized + Send + Sync {
         identity: IdentityReference<'_>,
     ) -> Result<Self::Authenticated, crate::Error>;
 
+    /// Returns an instance that executes operations using the permissions of
+    /// `user`, without requiring the user's credentials. This is intended for
+    /// trusted application servers that have authenticated an end-user by
+    /// other means.
+    ///
+    /// Like [`assume_identity()`](Self::assume_identity), this requires
+    /// permission to perform
+    /// [`ServerAction::AssumeIdentity`](crate::permissions::bonsai::ServerAction::AssumeIdentity)
+    /// on the user. Implementations may avoid registering a session for the
+    /// returned instance, in which case the user's permissions only apply until
+    /// the instance is dropped.
+    async fn as_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<Self::Authenticated, crate::Error> {
+        self.assume_identity(IdentityReference::user(user)?).await
+    }
+
     /// Adds a user to a permission group.
     async fn add_permission_group_to_user<
         'user,
@@ -3354,6 +5097,147 @@ pub trait AsyncStorageConnection: HasSession + Sized + Send + Sync {
         user: U,
         role: R,
     ) -> Result<(), crate::Error>;
+
+    /// Creates a [`PermissionGroup`] named `name` containing `statements`,
+    /// returning the new group's id.
+    async fn create_permission_group(
+        &self,
+        name: &str,
+        statements: Vec<Statement>,
+    ) -> Result<u64, crate::Error>;
+
+    /// Lists all [`PermissionGroup`]s.
+    async fn list_permission_groups(
+        &self,
+    ) -> Result<Vec<CollectionDocument<PermissionGroup>>, crate::Error>;
+
+    /// Replaces the statements of a [`PermissionGroup`] with `statements`.
+    /// The statements are checked using
+    /// [`validate_statements()`](crate::permissions::bonsai::validate_statements).
+    async fn set_permission_group_statements<'group, G: Nameable<'group, u64> + Send + Sync>(
+        &self,
+        permission_group: G,
+        statements: Vec<Statement>,
+    ) -> Result<(), crate::Error>;
+
+    /// Replaces the statements of a [`PermissionGroup`] with `statements` if
+    /// the group's [`version`](PermissionGroup::version) is
+    /// `expected_version`, returning the group's new version.
+    ///
+    /// If the group has been changed since `expected_version` was read,
+    /// [`Error::VersionConflict`](crate::Error::VersionConflict) is returned
+    /// and no changes are made. The statements are checked using
+    /// [`validate_statements()`](crate::permissions::bonsai::validate_statements).
+    async fn update_permission_group_statements<'group, G: Nameable<'group, u64> + Send + Sync>(
+        &self,
+        permission_group: G,
+        expected_version: u64,
+        statements: Vec<Statement>,
+    ) -> Result<u64, crate::Error>;
+
+    /// Deletes a [`PermissionGroup`].
+    async fn delete_permission_group<'group, G: Nameable<'group, u64> + Send + Sync>(
+        &self,
+        permission_group: G,
+    ) -> Result<(), crate::Error>;
+
+    /// Creates a [`Role`] named `name`, returning the new role's id.
+    async fn create_role(&self, name: &str) -> Result<u64, crate::Error>;
+
+    /// Lists all [`Role`]s.
+    async fn list_roles(&self) -> Result<Vec<CollectionDocument<Role>>, crate::Error>;
+
+    /// Adds a permission group to a role.
+    async fn add_permission_group_to_role<
+        'role,
+        'group,
+        R: Nameable<'role, u64> + Send + Sync,
+        G: Nameable<'group, u64> + Send + Sync,
+    >(
+        &self,
+        role: R,
+        permission_group: G,
+    ) -> Result<(), crate::Error>;
+
+    /// Removes a permission group from a role.
+    async fn remove_permission_group_from_role<
+        'role,
+        'group,
+        R: Nameable<'role, u64> + Send + Sync,
+        G: Nameable<'group, u64> + Send + Sync,
+    >(
+        &self,
+        role: R,
+        permission_group: G,
+    ) -> Result<(), crate::Error>;
+
+    /// Replaces the permission groups of a [`Role`] with `permission_groups`
+    /// if the role's [`version`](Role::version) is `expected_version`,
+    /// returning the role's new version.
+    ///
+    /// If the role has been changed since `expected_version` was read,
+    /// [`Error::VersionConflict`](crate::Error::VersionConflict) is returned
+    /// and no changes are made.
+    async fn update_role_permission_groups<'role, R: Nameable<'role, u64> + Send + Sync>(
+        &self,
+        role: R,
+        expected_version: u64,
+        permission_groups: Vec<u64>,
+    ) -> Result<u64, crate::Error>;
+
+    /// Deletes a [`Role`].
+    async fn delete_role<'role, R: Nameable<'role, u64> + Send + Sync>(
+        &self,
+        role: R,
+    ) -> Result<(), crate::Error>;
+
+    /// Lists the active sessions authenticated as `user`.
+    async fn list_sessions<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<Vec<SessionInfo>, crate::Error>;
+
+    /// Revokes the session with id `session_id`. Once revoked, any further
+    /// requests made using the session will fail with
+    /// [`Error::SessionExpired`].
+    async fn revoke_session(&self, session_id: SessionId) -> Result<(), crate::Error>;
+
+    /// Revokes the currently authenticated session, if any.
+    async fn logout(&self) -> Result<(), crate::Error> {
+        match self.session().and_then(|session| session.id) {
+            Some(session_id) => self.revoke_session(session_id).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the permission groups, roles, and users of this instance as a
+    /// [`SecurityConfiguration`].
+    async fn export_security_configuration(&self) -> Result<SecurityConfiguration, crate::Error> {
+        Ok(SecurityConfiguration::from_records(
+            self.list_permission_groups().await?,
+            self.list_roles().await?,
+            self.list_users().await?,
+        ))
+    }
+
+    /// Creates, updates, and deletes permission groups, roles, and users until
+    /// they match `configuration`, returning the changes that were made. See
+    /// [`StorageConnection::apply_security_configuration()`] for more
+    /// information.
+    async fn apply_security_configuration(
+        &self,
+        configuration: &SecurityConfiguration,
+    ) -> Result<SecurityChanges, crate::Error> {
+        let plan = configuration.plan(
+            self.list_permission_groups().await?,
+            self.list_roles().await?,
+            self.list_users().await?,
+        )?;
+        for step in plan.steps {
+            step.apply_async(self).await?;
+        }
+        Ok(plan.changes)
+    }
 }
 
 /// A database stored in BonsaiDb.
@@ -3365,6 +5249,52 @@ pub struct Database {
     pub schema: SchemaName,
 }
 
+/// Criteria used to list databases with
+/// [`StorageConnection::list_databases_matching`].
+///
+/// Results are always ordered by name. To list databases a page at a time,
+/// use [`Self::limit()`], and request each following page using
+/// [`Self::after()`] with the name of the last database returned.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[must_use]
+pub struct DatabaseQuery {
+    /// If set, only databases whose names begin with this prefix are listed.
+    pub name_prefix: Option<String>,
+    /// If set, only databases using this schema are listed.
+    pub schema: Option<SchemaName>,
+    /// If set, only databases whose names are ordered after this name are
+    /// listed.
+    pub after: Option<String>,
+    /// The maximum number of databases to list.
+    pub limit: Option<u32>,
+}
+
+impl DatabaseQuery {
+    /// Lists only databases whose names begin with `prefix`.
+    pub fn with_name_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.name_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Lists only databases using `schema`.
+    pub fn with_schema(mut self, schema: SchemaName) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    /// Lists only databases whose names are ordered after `name`.
+    pub fn after(mut self, name: impl Into<String>) -> Self {
+        self.after = Some(name.into());
+        self
+    }
+
+    /// Lists at most `limit` databases.
+    pub const fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
 /// A string containing sensitive (private) data. This struct automatically
 /// overwrites its contents with zeroes when dropped.
 #[derive(Clone, Default, Serialize, Deserialize, Zeroize, Eq, PartialEq)]
@@ -3710,6 +5640,19 @@ pub enum AuthenticationMethod {
 #[serde(transparent)]
 pub struct SessionId(pub u64);
 
+/// Information about an active, authenticated [`Session`].
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct SessionInfo {
+    /// The session's unique ID.
+    pub id: SessionId,
+    /// The identity the session is authenticated as.
+    pub identity: Arc<Identity>,
+    /// The time the session was created.
+    pub created_at: Timestamp,
+    /// The last time the session was used to make a request.
+    pub last_used_at: Timestamp,
+}
+
 impl Session {
     /// Checks if `action` is permitted against `resource_name`.
     pub fn allowed_to<'a, R: AsRef<[Identifier<'a>]>, P: Action>(
@@ -0,0 +1,217 @@
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use super::{AsyncConnection, AsyncLowLevelConnection};
+use crate::networking::PayloadLimits;
+use crate::transaction::{Operation, Transaction};
+use crate::Error;
+
+/// Imports a stream of operations into a database.
+///
+/// The operations are applied in transactions of up to
+/// [`max_operations`](Self::max_operations) operations whose documents total
+/// up to [`max_transaction_size`](Self::max_transaction_size) bytes. The
+/// stream is only polled while a transaction is being gathered: no more
+/// operations are read until the previous transaction has been applied, which
+/// keeps the memory used by an import bounded regardless of how quickly the
+/// stream produces operations.
+///
+/// After each transaction is applied, an [`ImportProgress`] is reported to the
+/// function passed to [`on_progress()`](Self::on_progress). If an import fails,
+/// the returned [`ImportError`] contains the progress made before the failure.
+/// Passing that progress to [`resume_from()`](Self::resume_from) on a new
+/// import of the same stream skips the operations that have already been
+/// applied.
+///
+/// This type is returned from [`AsyncConnection::import()`].
+#[must_use]
+pub struct AsyncImport<'a, Cn, Operations> {
+    connection: &'a Cn,
+    operations: Operations,
+    max_operations: usize,
+    max_transaction_size: usize,
+    progress: ImportProgress,
+    on_progress: Option<Box<dyn FnMut(&ImportProgress) + Send + 'a>>,
+}
+
+impl<'a, Cn, Operations> AsyncImport<'a, Cn, Operations>
+where
+    Cn: AsyncConnection,
+    Operations: Stream<Item = Result<Operation, Error>> + Send,
+{
+    pub(crate) fn new(connection: &'a Cn, operations: Operations) -> Self {
+        Self {
+            connection,
+            operations,
+            max_operations: 1_000,
+            max_transaction_size: 4 * 1024 * 1024,
+            progress: ImportProgress::default(),
+            on_progress: None,
+        }
+    }
+
+    /// Sets the maximum number of operations applied in each transaction. The
+    /// default value is 1,000.
+    pub const fn max_operations(mut self, operations: usize) -> Self {
+        self.max_operations = operations;
+        self
+    }
+
+    /// Sets the maximum combined size of the documents applied in each
+    /// transaction, in bytes. A single operation larger than this size is
+    /// applied in its own transaction. The default value is 4MB.
+    pub const fn max_transaction_size(mut self, bytes: usize) -> Self {
+        self.max_transaction_size = bytes;
+        self
+    }
+
+    /// Resumes a previous import of the same stream. The operations that
+    /// `progress` reports as applied are read from the stream and skipped.
+    pub const fn resume_from(mut self, progress: ImportProgress) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Invokes `on_progress` after each transaction has been applied.
+    pub fn on_progress<F: FnMut(&ImportProgress) + Send + 'a>(mut self, on_progress: F) -> Self {
+        self.on_progress = Some(Box::new(on_progress));
+        self
+    }
+
+    /// Imports the operations, returning the final progress of the import
+    /// once the stream is exhausted.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::AsyncConnection;
+    /// # use bonsaidb_core::transaction::Operation;
+    /// # use futures::StreamExt;
+    /// # fn test_fn<C: AsyncConnection>(db: C) -> Result<(), Error> {
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let operations = futures::stream::iter(0..1_000_000).map(|rank| {
+    ///     Operation::push_serialized::<MyCollection>(&MyCollection::new("player", rank, 0.))
+    /// });
+    /// let progress = db
+    ///     .import(operations)
+    ///     .on_progress(|progress| println!("Imported {} operations", progress.operations))
+    ///     .execute()
+    ///     .await?;
+    /// println!("Applied {} transactions", progress.transactions);
+    /// # Ok(())
+    /// # })
+    /// # }
+    /// ```
+    pub async fn execute(self) -> Result<ImportProgress, ImportError> {
+        let Self {
+            connection,
+            operations,
+            max_operations,
+            max_transaction_size,
+            progress,
+            mut on_progress,
+        } = self;
+        let mut import = ImportState {
+            connection,
+            transaction: Transaction::new(),
+            transaction_size: 0,
+            progress,
+        };
+
+        futures::pin_mut!(operations);
+        let mut to_skip = import.progress.operations;
+        while let Some(operation) = operations.next().await {
+            let operation = match operation {
+                Ok(operation) => operation,
+                Err(error) => return Err(import.error(error)),
+            };
+            if to_skip > 0 {
+                to_skip -= 1;
+                continue;
+            }
+
+            let size = PayloadLimits::operation_size(&operation);
+            if !import.transaction.operations.is_empty()
+                && (import.transaction.operations.len() >= max_operations
+                    || import.transaction_size.saturating_add(size) > max_transaction_size)
+            {
+                import.apply(&mut on_progress).await?;
+            }
+            import.transaction_size += size;
+            import.transaction.push(operation);
+        }
+
+        if !import.transaction.operations.is_empty() {
+            import.apply(&mut on_progress).await?;
+        }
+
+        Ok(import.progress)
+    }
+}
+
+struct ImportState<'a, Cn> {
+    connection: &'a Cn,
+    transaction: Transaction,
+    transaction_size: usize,
+    progress: ImportProgress,
+}
+
+impl<'a, Cn> ImportState<'a, Cn>
+where
+    Cn: AsyncLowLevelConnection,
+{
+    async fn apply(
+        &mut self,
+        on_progress: &mut Option<Box<dyn FnMut(&ImportProgress) + Send + 'a>>,
+    ) -> Result<(), ImportError> {
+        let transaction = std::mem::take(&mut self.transaction);
+        let operations = transaction.operations.len() as u64;
+        let size = std::mem::take(&mut self.transaction_size) as u64;
+        if let Err(error) = self.connection.apply_transaction(transaction).await {
+            return Err(self.error(error));
+        }
+
+        self.progress.operations += operations;
+        self.progress.transactions += 1;
+        self.progress.bytes += size;
+        if let Some(on_progress) = on_progress {
+            on_progress(&self.progress);
+        }
+        Ok(())
+    }
+
+    fn error(&self, error: Error) -> ImportError {
+        ImportError {
+            error,
+            progress: self.progress,
+        }
+    }
+}
+
+/// The progress of an [`AsyncImport`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ImportProgress {
+    /// The number of operations from the stream that have been applied,
+    /// including those applied before the import was resumed.
+    pub operations: u64,
+    /// The number of transactions that have been applied.
+    pub transactions: u64,
+    /// The combined size of the documents that have been applied, in bytes.
+    pub bytes: u64,
+}
+
+/// An error that stopped an [`AsyncImport`].
+#[derive(thiserror::Error, Debug)]
+#[error("import failed after {} operations: {error}", .progress.operations)]
+pub struct ImportError {
+    /// The error that occurred.
+    pub error: Error,
+    /// The progress of the import before the error occurred. None of the
+    /// operations after those reported by this progress have been applied.
+    pub progress: ImportProgress,
+}
+
+impl From<ImportError> for Error {
+    fn from(err: ImportError) -> Self {
+        err.error
+    }
+}
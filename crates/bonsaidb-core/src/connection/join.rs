@@ -0,0 +1,283 @@
+use std::marker::PhantomData;
+
+use super::{AccessPolicy, AsyncConnection, Connection, MaybeOwned, QueryKey, RangeRef, Sort};
+use crate::schema::view::join::{JoinedEntries, JoinedView, SerializedJoinedEntry};
+use crate::schema::View;
+use crate::Error;
+
+type JoinKey<J> = <<J as JoinedView>::Left as View>::Key;
+
+/// Parameters to query a [`JoinedView`].
+///
+/// This type is returned from [`Connection::joined_view()`].
+#[must_use]
+pub struct JoinedViewQuery<'a, Cn, J: JoinedView> {
+    connection: &'a Cn,
+
+    /// Key filtering criteria.
+    pub key: Option<QueryKey<'a, JoinKey<J>>>,
+
+    /// The joined view's data access policy. The default value is
+    /// [`AccessPolicy::UpdateBefore`].
+    pub access_policy: AccessPolicy,
+
+    /// The sort order of the query.
+    pub sort: Sort,
+
+    /// The maximum number of results to return.
+    pub limit: Option<u32>,
+
+    _join: PhantomData<J>,
+}
+
+impl<'a, Cn, J> JoinedViewQuery<'a, Cn, J>
+where
+    Cn: Connection,
+    J: JoinedView,
+{
+    pub(crate) const fn new(connection: &'a Cn) -> Self {
+        Self {
+            connection,
+            key: None,
+            access_policy: AccessPolicy::UpdateBefore,
+            sort: Sort::Ascending,
+            limit: None,
+            _join: PhantomData,
+        }
+    }
+
+    /// Filters for entries in the joined view with `key`.
+    pub fn with_key(mut self, key: &'a JoinKey<J>) -> Self {
+        self.key = Some(QueryKey::Matches(MaybeOwned::Borrowed(key)));
+        self
+    }
+
+    /// Filters for entries in the joined view with `keys`.
+    pub fn with_keys<IntoIter: IntoIterator<Item = &'a JoinKey<J>>>(
+        mut self,
+        keys: IntoIter,
+    ) -> Self {
+        self.key = Some(QueryKey::Multiple(
+            keys.into_iter().map(MaybeOwned::Borrowed).collect(),
+        ));
+        self
+    }
+
+    /// Filters for entries in the joined view with the range `keys`.
+    pub fn with_key_range<R: Into<RangeRef<'a, JoinKey<J>>>>(mut self, range: R) -> Self {
+        self.key = Some(QueryKey::Range(range.into()));
+        self
+    }
+
+    /// Sets the access policy for queries.
+    pub const fn with_access_policy(mut self, policy: AccessPolicy) -> Self {
+        self.access_policy = policy;
+        self
+    }
+
+    /// Returns the matching entries in ascending key order. This is the
+    /// default sorting behavior.
+    pub const fn ascending(mut self) -> Self {
+        self.sort = Sort::Ascending;
+        self
+    }
+
+    /// Returns the matching entries in descending key order.
+    pub const fn descending(mut self) -> Self {
+        self.sort = Sort::Descending;
+        self
+    }
+
+    /// Sets the maximum number of results to return.
+    pub const fn limit(mut self, maximum_results: u32) -> Self {
+        self.limit = Some(maximum_results);
+        self
+    }
+
+    /// Executes the query and retrieves the joined entries. Only keys that
+    /// both views contain are returned.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::Connection;
+    /// # use bonsaidb_core::schema::view::join::JoinedView;
+    /// # #[derive(Debug)]
+    /// # struct ScoresJoin;
+    /// # impl JoinedView for ScoresJoin {
+    /// #     type Left = ScoresByRank;
+    /// #     type Right = ScoresByRank;
+    /// #     fn name(&self) -> bonsaidb_core::schema::Name {
+    /// #         bonsaidb_core::schema::Name::new("scores-join")
+    /// #     }
+    /// # }
+    /// # fn test_fn<C: Connection>(db: C) -> Result<(), Error> {
+    /// for entry in db.joined_view::<ScoresJoin>().with_key(&42).query()? {
+    ///     println!(
+    ///         "Rank {} has {} left and {} right entries",
+    ///         entry.key,
+    ///         entry.left.len(),
+    ///         entry.right.len()
+    ///     );
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn query(self) -> Result<JoinedEntries<J>, Error> {
+        let join = self.connection.schematic().join::<J>()?;
+        let entries = self.connection.query_join_by_name(
+            &join.name,
+            self.key.map(|key| key.serialized()).transpose()?,
+            self.sort,
+            self.limit,
+            self.access_policy,
+        )?;
+        deserialize_entries::<J>(&entries)
+    }
+}
+
+/// Parameters to query a [`JoinedView`].
+///
+/// This type is returned from [`AsyncConnection::joined_view()`].
+#[must_use]
+pub struct AsyncJoinedViewQuery<'a, Cn, J: JoinedView> {
+    connection: &'a Cn,
+
+    /// Key filtering criteria.
+    pub key: Option<QueryKey<'a, JoinKey<J>>>,
+
+    /// The joined view's data access policy. The default value is
+    /// [`AccessPolicy::UpdateBefore`].
+    pub access_policy: AccessPolicy,
+
+    /// The sort order of the query.
+    pub sort: Sort,
+
+    /// The maximum number of results to return.
+    pub limit: Option<u32>,
+
+    _join: PhantomData<J>,
+}
+
+impl<'a, Cn, J> AsyncJoinedViewQuery<'a, Cn, J>
+where
+    Cn: AsyncConnection,
+    J: JoinedView,
+{
+    pub(crate) const fn new(connection: &'a Cn) -> Self {
+        Self {
+            connection,
+            key: None,
+            access_policy: AccessPolicy::UpdateBefore,
+            sort: Sort::Ascending,
+            limit: None,
+            _join: PhantomData,
+        }
+    }
+
+    /// Filters for entries in the joined view with `key`.
+    pub fn with_key(mut self, key: &'a JoinKey<J>) -> Self {
+        self.key = Some(QueryKey::Matches(MaybeOwned::Borrowed(key)));
+        self
+    }
+
+    /// Filters for entries in the joined view with `keys`.
+    pub fn with_keys<IntoIter: IntoIterator<Item = &'a JoinKey<J>>>(
+        mut self,
+        keys: IntoIter,
+    ) -> Self {
+        self.key = Some(QueryKey::Multiple(
+            keys.into_iter().map(MaybeOwned::Borrowed).collect(),
+        ));
+        self
+    }
+
+    /// Filters for entries in the joined view with the range `keys`.
+    pub fn with_key_range<R: Into<RangeRef<'a, JoinKey<J>>>>(mut self, range: R) -> Self {
+        self.key = Some(QueryKey::Range(range.into()));
+        self
+    }
+
+    /// Sets the access policy for queries.
+    pub const fn with_access_policy(mut self, policy: AccessPolicy) -> Self {
+        self.access_policy = policy;
+        self
+    }
+
+    /// Returns the matching entries in ascending key order. This is the
+    /// default sorting behavior.
+    pub const fn ascending(mut self) -> Self {
+        self.sort = Sort::Ascending;
+        self
+    }
+
+    /// Returns the matching entries in descending key order.
+    pub const fn descending(mut self) -> Self {
+        self.sort = Sort::Descending;
+        self
+    }
+
+    /// Sets the maximum number of results to return.
+    pub const fn limit(mut self, maximum_results: u32) -> Self {
+        self.limit = Some(maximum_results);
+        self
+    }
+
+    /// Executes the query and retrieves the joined entries. Only keys that
+    /// both views contain are returned.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::AsyncConnection;
+    /// # use bonsaidb_core::schema::view::join::JoinedView;
+    /// # #[derive(Debug)]
+    /// # struct ScoresJoin;
+    /// # impl JoinedView for ScoresJoin {
+    /// #     type Left = ScoresByRank;
+    /// #     type Right = ScoresByRank;
+    /// #     fn name(&self) -> bonsaidb_core::schema::Name {
+    /// #         bonsaidb_core::schema::Name::new("scores-join")
+    /// #     }
+    /// # }
+    /// # fn test_fn<C: AsyncConnection>(db: C) -> Result<(), Error> {
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// for entry in db
+    ///     .joined_view::<ScoresJoin>()
+    ///     .with_key(&42)
+    ///     .query()
+    ///     .await?
+    /// {
+    ///     println!(
+    ///         "Rank {} has {} left and {} right entries",
+    ///         entry.key,
+    ///         entry.left.len(),
+    ///         entry.right.len()
+    ///     );
+    /// }
+    /// # Ok(())
+    /// # })
+    /// # }
+    /// ```
+    pub async fn query(self) -> Result<JoinedEntries<J>, Error> {
+        let join = self.connection.schematic().join::<J>()?;
+        let entries = self
+            .connection
+            .query_join_by_name(
+                &join.name,
+                self.key.map(|key| key.serialized()).transpose()?,
+                self.sort,
+                self.limit,
+                self.access_policy,
+            )
+            .await?;
+        deserialize_entries::<J>(&entries)
+    }
+}
+
+fn deserialize_entries<J: JoinedView>(
+    entries: &[SerializedJoinedEntry],
+) -> Result<JoinedEntries<J>, Error> {
+    entries
+        .iter()
+        .map(SerializedJoinedEntry::deserialize::<J>)
+        .collect()
+}
@@ -7,15 +7,21 @@ use async_trait::async_trait;
 use super::GroupedReductions;
 use crate::connection::{
     AccessPolicy, HasSession, QueryKey, Range, RangeRef, SerializedQueryKey, Sort, ViewMappings,
+    ViewReadOptions,
 };
 use crate::document::{
     CollectionDocument, CollectionHeader, Document, DocumentId, HasHeader, Header, OwnedDocument,
 };
 use crate::key::{self, ByteCow, Key, KeyEncoding};
+use crate::schema::view::aggregation::{
+    Aggregation, AggregationGrouping, SerializedAggregatedGroup,
+};
+use crate::schema::view::join::SerializedJoinedEntry;
 use crate::schema::view::map::{MappedDocuments, MappedSerializedValue};
 use crate::schema::view::{self};
 use crate::schema::{
-    self, CollectionName, Map, MappedValue, Schematic, SerializedCollection, ViewName,
+    self, CollectionName, Map, MappedValue, Name, ProjectedDocument, Projection, Schematic,
+    SerializedCollection, ViewName,
 };
 use crate::transaction::{OperationResult, Transaction};
 use crate::Error;
@@ -215,6 +221,44 @@ pub trait LowLevelConnection: HasSchema + HasSession {
         self.list_headers_from_collection(ids, order, limit, &C::collection_name())
     }
 
+    /// Retrieves all documents within the range of `ids`, with their contents
+    /// projected by `P`. To retrieve all documents, pass in `..` for `ids`.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider using one of:
+    ///
+    /// - [`SerializedCollection::all().projected()`](schema::List::projected)
+    /// - [`self.collection::<Collection>().all().projected()`](super::List::projected)
+    fn list_projected<'id, P, R, PrimaryKey>(
+        &self,
+        ids: R,
+        order: Sort,
+        limit: Option<u32>,
+    ) -> Result<Vec<ProjectedDocument<P>>, Error>
+    where
+        P: Projection,
+        R: Into<RangeRef<'id, <P::Collection as schema::Collection>::PrimaryKey, PrimaryKey>>
+            + Send,
+        PrimaryKey: for<'k> KeyEncoding<'k, <P::Collection as schema::Collection>::PrimaryKey>
+            + PartialEq
+            + 'id
+            + ?Sized,
+        <P::Collection as schema::Collection>::PrimaryKey:
+            Borrow<PrimaryKey> + PartialEq<PrimaryKey>,
+    {
+        let ids = ids.into().map_result(|id| DocumentId::new(id))?;
+        let projection = self.schematic().projection::<P>()?.clone();
+        self.list_projected_from_collection(
+            ids,
+            order,
+            limit,
+            &P::Collection::collection_name(),
+            &projection,
+        )?
+        .iter()
+        .map(ProjectedDocument::try_from)
+        .collect()
+    }
+
     /// Counts the number of documents within the range of `ids`.
     ///
     /// This is a lower-level API. For better ergonomics, consider using one of:
@@ -272,6 +316,7 @@ pub trait LowLevelConnection: HasSchema + HasSession {
         order: Sort,
         limit: Option<u32>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<ViewMappings<V>, Error>
     where
         Key: for<'k> KeyEncoding<'k, V::Key> + PartialEq + ?Sized,
@@ -284,6 +329,7 @@ pub trait LowLevelConnection: HasSchema + HasSession {
             order,
             limit,
             access_policy,
+            read_options,
         )?;
         mappings
             .into_iter()
@@ -316,13 +362,31 @@ pub trait LowLevelConnection: HasSchema + HasSession {
         order: Sort,
         limit: Option<u32>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<MappedDocuments<OwnedDocument, V>, Error>
     where
         Key: for<'k> KeyEncoding<'k, V::Key> + PartialEq + ?Sized,
         V::Key: Borrow<Key> + PartialEq<Key>,
     {
+        if read_options.read_from_primary_only {
+            // The documents must be read from the primary as well, otherwise
+            // they may not reflect the returned mappings.
+            let view = self.schematic().view::<V>()?;
+            return self
+                .query_by_name_with_docs(
+                    &view.view_name(),
+                    key.map(|key| key.serialized()).transpose()?,
+                    order,
+                    limit,
+                    access_policy,
+                    read_options,
+                )?
+                .deserialized::<V>();
+        }
+
         // Query permission is checked by the query call
-        let results = self.query::<V, Key>(key, order, limit, access_policy)?;
+        let results =
+            self.query::<V, Key>(key, order, limit, access_policy, ViewReadOptions::default())?;
 
         // Verify that there is permission to fetch each document
         let documents = self
@@ -354,6 +418,7 @@ pub trait LowLevelConnection: HasSchema + HasSession {
         order: Sort,
         limit: Option<u32>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<MappedDocuments<CollectionDocument<V::Collection>, V>, Error>
     where
         Key: for<'k> KeyEncoding<'k, V::Key> + PartialEq + ?Sized,
@@ -362,7 +427,8 @@ pub trait LowLevelConnection: HasSchema + HasSession {
         V::Collection: SerializedCollection,
         <V::Collection as SerializedCollection>::Contents: std::fmt::Debug,
     {
-        let mapped_docs = self.query_with_docs::<V, Key>(key, order, limit, access_policy)?;
+        let mapped_docs =
+            self.query_with_docs::<V, Key>(key, order, limit, access_policy, read_options)?;
         let mut collection_docs = BTreeMap::new();
         for (id, doc) in mapped_docs.documents {
             collection_docs.insert(id, CollectionDocument::<V::Collection>::try_from(&doc)?);
@@ -373,6 +439,51 @@ pub trait LowLevelConnection: HasSchema + HasSession {
         })
     }
 
+    /// Queries for view entries matching [`View`](schema::View) with their
+    /// source documents, with the documents' contents projected by `P`.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider querying the
+    /// view using
+    /// [`View::entries(self).query_with_projected_docs()`](super::View::query_with_projected_docs)
+    /// instead. The parameters for the query can be customized on the builder
+    /// returned from [`Connection::view()`](super::Connection::view).
+    fn query_with_projected_docs<V, P, Key>(
+        &self,
+        key: Option<QueryKey<'_, V::Key, Key>>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+    ) -> Result<MappedDocuments<ProjectedDocument<P>, V>, Error>
+    where
+        Key: for<'k> KeyEncoding<'k, V::Key> + PartialEq + ?Sized,
+        V::Key: Borrow<Key> + PartialEq<Key>,
+        V: schema::SerializedView,
+        P: Projection<Collection = V::Collection>,
+    {
+        let view = self.schematic().view::<V>()?.view_name();
+        let projection = self.schematic().projection::<P>()?.clone();
+        let mapped_docs = self
+            .query_by_name_with_projected_docs(
+                &view,
+                key.map(|key| key.serialized()).transpose()?,
+                order,
+                limit,
+                access_policy,
+                read_options,
+                &projection,
+            )?
+            .deserialized::<V>()?;
+        let mut projected_docs = BTreeMap::new();
+        for (id, doc) in mapped_docs.documents {
+            projected_docs.insert(id, ProjectedDocument::<P>::try_from(&doc)?);
+        }
+        Ok(MappedDocuments {
+            mappings: mapped_docs.mappings,
+            documents: projected_docs,
+        })
+    }
+
     /// Reduces the view entries matching [`View`](schema::View).
     ///
     /// This is a lower-level API. For better ergonomics, consider reducing the
@@ -386,6 +497,7 @@ pub trait LowLevelConnection: HasSchema + HasSession {
         &self,
         key: Option<QueryKey<'_, V::Key, Key>>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<V::Value, Error>
     where
         Key: for<'k> KeyEncoding<'k, V::Key> + PartialEq + ?Sized,
@@ -396,6 +508,7 @@ pub trait LowLevelConnection: HasSchema + HasSession {
             &view.view_name(),
             key.map(|key| key.serialized()).transpose()?,
             access_policy,
+            read_options,
         )
         .and_then(|value| V::deserialize(&value))
     }
@@ -415,6 +528,7 @@ pub trait LowLevelConnection: HasSchema + HasSession {
         &self,
         key: Option<QueryKey<'_, V::Key, Key>>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<GroupedReductions<V>, Error>
     where
         Key: for<'k> KeyEncoding<'k, V::Key> + PartialEq + ?Sized,
@@ -425,6 +539,7 @@ pub trait LowLevelConnection: HasSchema + HasSession {
             &view.view_name(),
             key.map(|key| key.serialized()).transpose()?,
             access_policy,
+            read_options,
         )?
         .into_iter()
         .map(|map| {
@@ -530,6 +645,24 @@ pub trait LowLevelConnection: HasSchema + HasSession {
         collection: &CollectionName,
     ) -> Result<Vec<Header>, Error>;
 
+    /// Retrieves all documents within the range of `ids` from the named
+    /// `collection`, replacing each document's contents with the contents
+    /// projected by the projection named `projection`. To retrieve all
+    /// documents, pass in `..` for `ids`.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider using one of:
+    ///
+    /// - [`SerializedCollection::all().projected()`](schema::List::projected)
+    /// - [`self.collection::<Collection>().all().projected()`](super::List::projected)
+    fn list_projected_from_collection(
+        &self,
+        ids: Range<DocumentId>,
+        order: Sort,
+        limit: Option<u32>,
+        collection: &CollectionName,
+        projection: &Name,
+    ) -> Result<Vec<OwnedDocument>, Error>;
+
     /// Counts the number of documents within the range of `ids` from the named
     /// `collection`.
     ///
@@ -571,6 +704,7 @@ pub trait LowLevelConnection: HasSchema + HasSession {
         order: Sort,
         limit: Option<u32>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<Vec<schema::view::map::Serialized>, Error>;
 
     /// Queries for view entries from the named `view` with their source
@@ -588,6 +722,28 @@ pub trait LowLevelConnection: HasSchema + HasSession {
         order: Sort,
         limit: Option<u32>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+    ) -> Result<schema::view::map::MappedSerializedDocuments, Error>;
+
+    /// Queries for view entries from the named `view` with their source
+    /// documents, replacing each document's contents with the contents
+    /// projected by the projection named `projection`.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider querying the
+    /// view using
+    /// [`View::entries(self).query_with_projected_docs()`](super::View::query_with_projected_docs)
+    /// instead. The parameters for the query can be customized on the builder
+    /// returned from [`Connection::view()`](super::Connection::view).
+    #[allow(clippy::too_many_arguments)]
+    fn query_by_name_with_projected_docs(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+        projection: &Name,
     ) -> Result<schema::view::map::MappedSerializedDocuments, Error>;
 
     /// Reduces the view entries from the named `view`.
@@ -601,6 +757,7 @@ pub trait LowLevelConnection: HasSchema + HasSession {
         view: &ViewName,
         key: Option<SerializedQueryKey>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<Vec<u8>, Error>;
 
     /// Reduces the view entries from the named `view`, reducing the values by each
@@ -616,8 +773,72 @@ pub trait LowLevelConnection: HasSchema + HasSession {
         view: &ViewName,
         key: Option<SerializedQueryKey>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<Vec<MappedSerializedValue>, Error>;
 
+    /// Computes `aggregations` over the view entries from the named `view`,
+    /// grouping the entries using `grouping`.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider aggregating
+    /// the view using [`View::entries(self).aggregate()`](super::View::aggregate)
+    /// instead. The parameters for the query can be customized on the builder
+    /// returned from [`Connection::view()`](super::Connection::view).
+    fn aggregate_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        aggregations: &[Aggregation],
+        grouping: AggregationGrouping,
+        access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+    ) -> Result<Vec<SerializedAggregatedGroup>, Error>;
+
+    /// Counts the view entries that match within the named `view`, without
+    /// returning the entries themselves.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider counting the
+    /// view's entries using [`View::entries(self).count()`](super::View::count)
+    /// instead. The parameters for the query can be customized on the builder
+    /// returned from [`Connection::view()`](super::Connection::view).
+    fn count_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+    ) -> Result<u64, Error>;
+
+    /// Returns the view entries that the document with `document_id` emitted
+    /// into the named `view`.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider looking up
+    /// the document's entries using
+    /// [`View::entries(self).for_document()`](super::View::for_document)
+    /// instead. The parameters for the query can be customized on the builder
+    /// returned from [`Connection::view()`](super::Connection::view).
+    fn document_mappings_by_name(
+        &self,
+        view: &ViewName,
+        document_id: DocumentId,
+        access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+    ) -> Result<Vec<schema::view::map::Serialized>, Error>;
+
+    /// Queries for the entries of the named joined view `join`.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider querying the
+    /// join using
+    /// [`Connection::joined_view()`](super::Connection::joined_view)
+    /// instead.
+    fn query_join_by_name(
+        &self,
+        join: &ViewName,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+    ) -> Result<Vec<SerializedJoinedEntry>, Error>;
+
     /// Deletes all source documents for entries that match within the named
     /// `view`.
     ///
@@ -840,6 +1061,45 @@ pub trait AsyncLowLevelConnection: HasSchema + HasSession + Send + Sync {
             .await
     }
 
+    /// Retrieves all documents within the range of `ids`, with their contents
+    /// projected by `P`. To retrieve all documents, pass in `..` for `ids`.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider using one of:
+    ///
+    /// - [`SerializedCollection::all().projected()`](schema::AsyncList::projected)
+    /// - [`self.collection::<Collection>().all().projected()`](super::AsyncList::projected)
+    async fn list_projected<'id, P, R, PrimaryKey>(
+        &self,
+        ids: R,
+        order: Sort,
+        limit: Option<u32>,
+    ) -> Result<Vec<ProjectedDocument<P>>, Error>
+    where
+        P: Projection,
+        R: Into<RangeRef<'id, <P::Collection as schema::Collection>::PrimaryKey, PrimaryKey>>
+            + Send,
+        PrimaryKey: for<'k> KeyEncoding<'k, <P::Collection as schema::Collection>::PrimaryKey>
+            + PartialEq
+            + 'id
+            + ?Sized,
+        <P::Collection as schema::Collection>::PrimaryKey:
+            Borrow<PrimaryKey> + PartialEq<PrimaryKey>,
+    {
+        let ids = ids.into().map_result(|id| DocumentId::new(id))?;
+        let projection = self.schematic().projection::<P>()?.clone();
+        self.list_projected_from_collection(
+            ids,
+            order,
+            limit,
+            &P::Collection::collection_name(),
+            &projection,
+        )
+        .await?
+        .iter()
+        .map(ProjectedDocument::try_from)
+        .collect()
+    }
+
     /// Counts the number of documents within the range of `ids`.
     ///
     /// This is the lower-level API. For better ergonomics, consider using
@@ -897,6 +1157,7 @@ pub trait AsyncLowLevelConnection: HasSchema + HasSession + Send + Sync {
         order: Sort,
         limit: Option<u32>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<ViewMappings<V>, Error>
     where
         Key: for<'k> KeyEncoding<'k, V::Key> + PartialEq + ?Sized,
@@ -910,6 +1171,7 @@ pub trait AsyncLowLevelConnection: HasSchema + HasSession + Send + Sync {
                 order,
                 limit,
                 access_policy,
+                read_options,
             )
             .await?;
         mappings
@@ -939,14 +1201,32 @@ pub trait AsyncLowLevelConnection: HasSchema + HasSession + Send + Sync {
         order: Sort,
         limit: Option<u32>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<MappedDocuments<OwnedDocument, V>, Error>
     where
         Key: for<'k> KeyEncoding<'k, V::Key> + PartialEq + ?Sized,
         V::Key: Borrow<Key> + PartialEq<Key>,
     {
+        if read_options.read_from_primary_only {
+            // The documents must be read from the primary as well, otherwise
+            // they may not reflect the returned mappings.
+            let view = self.schematic().view::<V>()?;
+            return self
+                .query_by_name_with_docs(
+                    &view.view_name(),
+                    key.map(|key| key.serialized()).transpose()?,
+                    order,
+                    limit,
+                    access_policy,
+                    read_options,
+                )
+                .await?
+                .deserialized::<V>();
+        }
+
         // Query permission is checked by the query call
         let results = self
-            .query::<V, Key>(key, order, limit, access_policy)
+            .query::<V, Key>(key, order, limit, access_policy, ViewReadOptions::default())
             .await?;
 
         // Verify that there is permission to fetch each document
@@ -978,6 +1258,7 @@ pub trait AsyncLowLevelConnection: HasSchema + HasSession + Send + Sync {
         order: Sort,
         limit: Option<u32>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<MappedDocuments<CollectionDocument<V::Collection>, V>, Error>
     where
         Key: for<'k> KeyEncoding<'k, V::Key> + PartialEq + ?Sized,
@@ -987,7 +1268,7 @@ pub trait AsyncLowLevelConnection: HasSchema + HasSession + Send + Sync {
         <V::Collection as SerializedCollection>::Contents: std::fmt::Debug,
     {
         let mapped_docs = self
-            .query_with_docs::<V, Key>(key, order, limit, access_policy)
+            .query_with_docs::<V, Key>(key, order, limit, access_policy, read_options)
             .await?;
         let mut collection_docs = BTreeMap::new();
         for (id, doc) in mapped_docs.documents {
@@ -999,6 +1280,52 @@ pub trait AsyncLowLevelConnection: HasSchema + HasSession + Send + Sync {
         })
     }
 
+    /// Queries for view entries matching [`View`](schema::View) with their
+    /// source documents, with the documents' contents projected by `P`.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider querying the
+    /// view using
+    /// [`View::entries(self).query_with_projected_docs()`](super::AsyncView::query_with_projected_docs)
+    /// instead. The parameters for the query can be customized on the builder
+    /// returned from [`AsyncConnection::view()`](super::AsyncConnection::view).
+    async fn query_with_projected_docs<V, P, Key>(
+        &self,
+        key: Option<QueryKey<'_, V::Key, Key>>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+    ) -> Result<MappedDocuments<ProjectedDocument<P>, V>, Error>
+    where
+        Key: for<'k> KeyEncoding<'k, V::Key> + PartialEq + ?Sized,
+        V::Key: Borrow<Key> + PartialEq<Key>,
+        V: schema::SerializedView,
+        P: Projection<Collection = V::Collection>,
+    {
+        let view = self.schematic().view::<V>()?.view_name();
+        let projection = self.schematic().projection::<P>()?.clone();
+        let mapped_docs = self
+            .query_by_name_with_projected_docs(
+                &view,
+                key.map(|key| key.serialized()).transpose()?,
+                order,
+                limit,
+                access_policy,
+                read_options,
+                &projection,
+            )
+            .await?
+            .deserialized::<V>()?;
+        let mut projected_docs = BTreeMap::new();
+        for (id, doc) in mapped_docs.documents {
+            projected_docs.insert(id, ProjectedDocument::<P>::try_from(&doc)?);
+        }
+        Ok(MappedDocuments {
+            mappings: mapped_docs.mappings,
+            documents: projected_docs,
+        })
+    }
+
     /// Reduces the view entries matching [`View`](schema::View).
     ///
     /// This is the lower-level API. For better ergonomics, consider querying
@@ -1011,6 +1338,7 @@ pub trait AsyncLowLevelConnection: HasSchema + HasSession + Send + Sync {
         &self,
         key: Option<QueryKey<'_, V::Key, Key>>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<V::Value, Error>
     where
         Key: for<'k> KeyEncoding<'k, V::Key> + PartialEq + ?Sized,
@@ -1021,6 +1349,7 @@ pub trait AsyncLowLevelConnection: HasSchema + HasSession + Send + Sync {
             &view.view_name(),
             key.map(|key| key.serialized()).transpose()?,
             access_policy,
+            read_options,
         )
         .await
         .and_then(|value| V::deserialize(&value))
@@ -1039,6 +1368,7 @@ pub trait AsyncLowLevelConnection: HasSchema + HasSession + Send + Sync {
         &self,
         key: Option<QueryKey<'_, V::Key, Key>>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<GroupedReductions<V>, Error>
     where
         Key: for<'k> KeyEncoding<'k, V::Key> + PartialEq + ?Sized,
@@ -1049,6 +1379,7 @@ pub trait AsyncLowLevelConnection: HasSchema + HasSession + Send + Sync {
             &view.view_name(),
             key.map(|key| key.serialized()).transpose()?,
             access_policy,
+            read_options,
         )
         .await?
         .into_iter()
@@ -1156,6 +1487,24 @@ pub trait AsyncLowLevelConnection: HasSchema + HasSession + Send + Sync {
         collection: &CollectionName,
     ) -> Result<Vec<Header>, Error>;
 
+    /// Retrieves all documents within the range of `ids` from the named
+    /// `collection`, replacing each document's contents with the contents
+    /// projected by the projection named `projection`. To retrieve all
+    /// documents, pass in `..` for `ids`.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider using one of:
+    ///
+    /// - [`SerializedCollection::all().projected()`](schema::AsyncList::projected)
+    /// - [`self.collection::<Collection>().all().projected()`](super::AsyncList::projected)
+    async fn list_projected_from_collection(
+        &self,
+        ids: Range<DocumentId>,
+        order: Sort,
+        limit: Option<u32>,
+        collection: &CollectionName,
+        projection: &Name,
+    ) -> Result<Vec<OwnedDocument>, Error>;
+
     /// Counts the number of documents within the range of `ids` from the named
     /// `collection`.
     ///
@@ -1197,6 +1546,7 @@ pub trait AsyncLowLevelConnection: HasSchema + HasSession + Send + Sync {
         order: Sort,
         limit: Option<u32>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<Vec<schema::view::map::Serialized>, Error>;
 
     /// Queries for view entries from the named `view` with their source
@@ -1213,6 +1563,28 @@ pub trait AsyncLowLevelConnection: HasSchema + HasSession + Send + Sync {
         order: Sort,
         limit: Option<u32>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+    ) -> Result<schema::view::map::MappedSerializedDocuments, Error>;
+
+    /// Queries for view entries from the named `view` with their source
+    /// documents, replacing each document's contents with the contents
+    /// projected by the projection named `projection`.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider querying the
+    /// view using
+    /// [`View::entries(self).query_with_projected_docs()`](super::AsyncView::query_with_projected_docs)
+    /// instead. The parameters for the query can be customized on the builder
+    /// returned from [`AsyncConnection::view()`](super::AsyncConnection::view).
+    #[allow(clippy::too_many_arguments)]
+    async fn query_by_name_with_projected_docs(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+        projection: &Name,
     ) -> Result<schema::view::map::MappedSerializedDocuments, Error>;
 
     /// Reduces the view entries from the named `view`.
@@ -1227,6 +1599,7 @@ pub trait AsyncLowLevelConnection: HasSchema + HasSession + Send + Sync {
         view: &ViewName,
         key: Option<SerializedQueryKey>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<Vec<u8>, Error>;
 
     /// Reduces the view entries from the named `view`, reducing the values by each
@@ -1242,8 +1615,74 @@ pub trait AsyncLowLevelConnection: HasSchema + HasSession + Send + Sync {
         view: &ViewName,
         key: Option<SerializedQueryKey>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<Vec<MappedSerializedValue>, Error>;
 
+    /// Computes `aggregations` over the view entries from the named `view`,
+    /// grouping the entries using `grouping`.
+    ///
+    /// This is the lower-level API. For better ergonomics, consider querying
+    /// the view using
+    /// [`View::entries(self).aggregate()`](super::AsyncView::aggregate)
+    /// instead. The parameters for the query can be customized on the builder
+    /// returned from [`AsyncConnection::view()`](super::AsyncConnection::view).
+    async fn aggregate_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        aggregations: &[Aggregation],
+        grouping: AggregationGrouping,
+        access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+    ) -> Result<Vec<SerializedAggregatedGroup>, Error>;
+
+    /// Counts the view entries that match within the named `view`, without
+    /// returning the entries themselves.
+    ///
+    /// This is the lower-level API. For better ergonomics, consider counting
+    /// the view's entries using
+    /// [`View::entries(self).count()`](super::AsyncView::count)
+    /// instead. The parameters for the query can be customized on the builder
+    /// returned from [`AsyncConnection::view()`](super::AsyncConnection::view).
+    async fn count_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+    ) -> Result<u64, Error>;
+
+    /// Returns the view entries that the document with `document_id` emitted
+    /// into the named `view`.
+    ///
+    /// This is the lower-level API. For better ergonomics, consider looking up
+    /// the document's entries using
+    /// [`View::entries(self).for_document()`](super::AsyncView::for_document)
+    /// instead. The parameters for the query can be customized on the builder
+    /// returned from [`AsyncConnection::view()`](super::AsyncConnection::view).
+    async fn document_mappings_by_name(
+        &self,
+        view: &ViewName,
+        document_id: DocumentId,
+        access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+    ) -> Result<Vec<schema::view::map::Serialized>, Error>;
+
+    /// Queries for the entries of the named joined view `join`.
+    ///
+    /// This is the lower-level API. For better ergonomics, consider querying
+    /// the join using
+    /// [`AsyncConnection::joined_view()`](super::AsyncConnection::joined_view)
+    /// instead.
+    async fn query_join_by_name(
+        &self,
+        join: &ViewName,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+    ) -> Result<Vec<SerializedJoinedEntry>, Error>;
+
     /// Deletes all source documents for entries that match within the named
     /// `view`.
     ///
@@ -0,0 +1,540 @@
+//! Conflict-free replicated data types (CRDTs).
+//!
+//! A CRDT is a value that can be modified independently by multiple writers
+//! and later combined without conflicts. Every type in this module implements
+//! [`Crdt`], whose [`merge()`](Crdt::merge) function is commutative,
+//! associative, and idempotent: no matter what order changes are merged in,
+//! every copy converges to the same value.
+//!
+//! Collections can opt into storing CRDT contents using
+//! [`Schematic::define_crdt()`](crate::schema::Schematic::define_crdt) or the
+//! `crdt` parameter of the `Collection` derive macro. When a document in one of
+//! these collections is updated with a stale revision, or is replicated from
+//! another node, the stored contents and the incoming contents are merged
+//! instead of returning a [`DocumentConflict`](crate::Error::DocumentConflict).
+//!
+//! Each writer identifies itself using a [`ReplicaId`]. Two writers that modify
+//! the same value concurrently must use different ids, or some of their changes
+//! may be lost when merged. [`ReplicaId::random()`] is a convenient way to
+//! create an id for a client or session.
+
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub use bonsaidb_macros::Crdt;
+use serde::{Deserialize, Serialize};
+
+/// A state-based conflict-free replicated data type.
+///
+/// Implementors must ensure that [`merge()`](Self::merge) is commutative,
+/// associative, and idempotent.
+///
+/// This trait can be derived for structures whose fields all implement
+/// [`Crdt`]. Each field is merged independently:
+///
+/// ```rust
+/// use bonsaidb_core::crdt::{Counter, Crdt, LwwRegister};
+///
+/// #[derive(Crdt, Default)]
+/// # #[crdt(core = bonsaidb_core)]
+/// struct Article {
+///     title: LwwRegister<String>,
+///     views: Counter,
+/// }
+/// ```
+pub trait Crdt {
+    /// Merges the state of `other` into `self`.
+    fn merge(&mut self, other: Self);
+}
+
+/// A unique identifier of a writer that modifies [`Crdt`] values.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub struct ReplicaId(pub u64);
+
+impl ReplicaId {
+    /// Returns a randomly generated replica id.
+    #[must_use]
+    pub fn random() -> Self {
+        Self(rand::random())
+    }
+}
+
+/// A hybrid timestamp used to order changes made by different replicas.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+struct Stamp {
+    micros: u64,
+    replica: ReplicaId,
+}
+
+impl Stamp {
+    /// Returns a stamp for `replica` that is ordered after `after`, even if the
+    /// system clock is behind it.
+    fn next(replica: ReplicaId, after: Option<Stamp>) -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| {
+                u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX)
+            });
+        let micros = match after {
+            Some(after) if after.micros >= now => after.micros.saturating_add(1),
+            _ => now,
+        };
+        Self { micros, replica }
+    }
+}
+
+/// A counter that can be incremented and decremented concurrently.
+///
+/// Each replica tracks the total it has added and subtracted. Merging keeps the
+/// largest totals seen for each replica.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Counter {
+    increments: BTreeMap<ReplicaId, u64>,
+    decrements: BTreeMap<ReplicaId, u64>,
+}
+
+impl Counter {
+    /// Adds `amount` to the counter on behalf of `replica`.
+    pub fn increment(&mut self, replica: ReplicaId, amount: u64) {
+        let total = self.increments.entry(replica).or_default();
+        *total = total.saturating_add(amount);
+    }
+
+    /// Subtracts `amount` from the counter on behalf of `replica`.
+    pub fn decrement(&mut self, replica: ReplicaId, amount: u64) {
+        let total = self.decrements.entry(replica).or_default();
+        *total = total.saturating_add(amount);
+    }
+
+    /// Returns the current value of the counter.
+    #[must_use]
+    pub fn value(&self) -> i64 {
+        let increments = self
+            .increments
+            .values()
+            .map(|&v| i128::from(v))
+            .sum::<i128>();
+        let decrements = self
+            .decrements
+            .values()
+            .map(|&v| i128::from(v))
+            .sum::<i128>();
+        let value = increments - decrements;
+        i64::try_from(value).unwrap_or(if value < 0 { i64::MIN } else { i64::MAX })
+    }
+}
+
+impl Crdt for Counter {
+    fn merge(&mut self, other: Self) {
+        merge_totals(&mut self.increments, other.increments);
+        merge_totals(&mut self.decrements, other.decrements);
+    }
+}
+
+fn merge_totals(totals: &mut BTreeMap<ReplicaId, u64>, other: BTreeMap<ReplicaId, u64>) {
+    for (replica, other_total) in other {
+        let total = totals.entry(replica).or_default();
+        *total = (*total).max(other_total);
+    }
+}
+
+/// A single value where the most recent write wins.
+///
+/// When two replicas set the value concurrently, the write with the later
+/// timestamp is kept. Ties are broken by comparing the [`ReplicaId`]s.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct LwwRegister<T> {
+    value: T,
+    stamp: Option<Stamp>,
+}
+
+impl<T> LwwRegister<T> {
+    /// Returns a register containing `value`. Any value written with
+    /// [`set()`](Self::set) will replace this initial value when merged.
+    pub const fn new(value: T) -> Self {
+        Self { value, stamp: None }
+    }
+
+    /// Replaces the value on behalf of `replica`.
+    pub fn set(&mut self, replica: ReplicaId, value: T) {
+        self.stamp = Some(Stamp::next(replica, self.stamp));
+        self.value = value;
+    }
+
+    /// Returns the current value.
+    pub const fn get(&self) -> &T {
+        &self.value
+    }
+
+    /// Returns the current value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> Crdt for LwwRegister<T> {
+    fn merge(&mut self, other: Self) {
+        if other.stamp > self.stamp {
+            *self = other;
+        }
+    }
+}
+
+/// A map of keys to [`Crdt`] values.
+///
+/// Concurrent changes to the same key are combined by merging the values. When
+/// a key is updated concurrently with its removal, the update wins. Removed
+/// entries are retained so that removals can be merged: if a key is added
+/// again after being removed, its new value is merged with its previous value.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CrdtMap<K: Ord, V> {
+    entries: BTreeMap<K, MapEntry<V>>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+struct MapEntry<V> {
+    value: V,
+    updated: Stamp,
+    removed: Option<Stamp>,
+}
+
+impl<V> MapEntry<V> {
+    fn is_present(&self) -> bool {
+        self.removed.map_or(true, |removed| self.updated > removed)
+    }
+
+    fn latest(&self) -> Stamp {
+        self.removed
+            .map_or(self.updated, |removed| self.updated.max(removed))
+    }
+}
+
+impl<K: Ord, V> Default for CrdtMap<K, V> {
+    fn default() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+        }
+    }
+}
+
+impl<K: Ord, V: Crdt> CrdtMap<K, V> {
+    /// Modifies the value stored at `key` on behalf of `replica`. If `key` is
+    /// not present, `V::default()` is passed to `update`.
+    pub fn update<F: FnOnce(&mut V)>(&mut self, replica: ReplicaId, key: K, update: F)
+    where
+        V: Default,
+    {
+        let entry = match self.entries.entry(key) {
+            Entry::Occupied(entry) => {
+                let entry = entry.into_mut();
+                entry.updated = Stamp::next(replica, Some(entry.latest()));
+                entry
+            }
+            Entry::Vacant(entry) => entry.insert(MapEntry {
+                value: V::default(),
+                updated: Stamp::next(replica, None),
+                removed: None,
+            }),
+        };
+        update(&mut entry.value);
+    }
+
+    /// Merges `value` into the value stored at `key` on behalf of `replica`.
+    pub fn insert(&mut self, replica: ReplicaId, key: K, value: V) {
+        match self.entries.entry(key) {
+            Entry::Occupied(entry) => {
+                let entry = entry.into_mut();
+                entry.updated = Stamp::next(replica, Some(entry.latest()));
+                entry.value.merge(value);
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(MapEntry {
+                    value,
+                    updated: Stamp::next(replica, None),
+                    removed: None,
+                });
+            }
+        }
+    }
+
+    /// Removes `key` on behalf of `replica`. Returns true if the key was
+    /// present.
+    pub fn remove(&mut self, replica: ReplicaId, key: &K) -> bool {
+        match self.entries.get_mut(key) {
+            Some(entry) if entry.is_present() => {
+                entry.removed = Some(Stamp::next(replica, Some(entry.latest())));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the value stored at `key`, if present.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.entries
+            .get(key)
+            .filter(|entry| entry.is_present())
+            .map(|entry| &entry.value)
+    }
+
+    /// Returns true if `key` is present in this map.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Returns an iterator over the present keys and values, ordered by key.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries
+            .iter()
+            .filter(|(_, entry)| entry.is_present())
+            .map(|(key, entry)| (key, &entry.value))
+    }
+
+    /// Returns the number of present keys.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Returns true if no keys are present.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+}
+
+impl<K: Ord, V: Crdt> Crdt for CrdtMap<K, V> {
+    fn merge(&mut self, other: Self) {
+        for (key, other) in other.entries {
+            match self.entries.entry(key) {
+                Entry::Occupied(entry) => {
+                    let entry = entry.into_mut();
+                    entry.value.merge(other.value);
+                    entry.updated = entry.updated.max(other.updated);
+                    entry.removed = entry.removed.max(other.removed);
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(other);
+                }
+            }
+        }
+    }
+}
+
+/// An ordered list that supports concurrent insertions and removals.
+///
+/// This is a replicated growable array: each element remembers which element
+/// it was inserted after, which allows insertions made by different replicas to
+/// be interleaved consistently. Removed elements are kept as tombstones so that
+/// later insertions can still be positioned relative to them.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Sequence<T> {
+    elements: BTreeMap<Stamp, SequenceElement<T>>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+struct SequenceElement<T> {
+    after: Option<Stamp>,
+    value: Option<T>,
+}
+
+impl<T> Default for Sequence<T> {
+    fn default() -> Self {
+        Self {
+            elements: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T> Sequence<T> {
+    /// Appends `value` to the end of the sequence on behalf of `replica`.
+    pub fn push(&mut self, replica: ReplicaId, value: T) {
+        let after = self.visible_ids().last().copied();
+        self.insert_after(replica, after, value);
+    }
+
+    /// Inserts `value` at `index` on behalf of `replica`, shifting all
+    /// elements after it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than [`len()`](Self::len).
+    pub fn insert(&mut self, replica: ReplicaId, index: usize, value: T) {
+        let visible = self.visible_ids();
+        assert!(index <= visible.len(), "index out of bounds");
+        let after = index.checked_sub(1).map(|previous| visible[previous]);
+        self.insert_after(replica, after, value);
+    }
+
+    /// Removes and returns the element at `index`, if present.
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        let id = self.visible_ids().get(index).copied()?;
+        self.elements
+            .get_mut(&id)
+            .and_then(|element| element.value.take())
+    }
+
+    /// Returns the element at `index`, if present.
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.iter().nth(index)
+    }
+
+    /// Returns an iterator over the elements in order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.ordered_ids()
+            .into_iter()
+            .filter_map(move |id| self.elements[&id].value.as_ref())
+    }
+
+    /// Returns the number of elements in the sequence.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.elements
+            .values()
+            .filter(|element| element.value.is_some())
+            .count()
+    }
+
+    /// Returns true if the sequence contains no elements.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn insert_after(&mut self, replica: ReplicaId, after: Option<Stamp>, value: T) {
+        // The new id must be greater than every existing id, which guarantees
+        // it is ordered immediately after `after`.
+        let id = Stamp::next(replica, self.elements.keys().next_back().copied());
+        self.elements.insert(
+            id,
+            SequenceElement {
+                after,
+                value: Some(value),
+            },
+        );
+    }
+
+    fn visible_ids(&self) -> Vec<Stamp> {
+        self.ordered_ids()
+            .into_iter()
+            .filter(|id| self.elements[id].value.is_some())
+            .collect()
+    }
+
+    /// Returns all ids, including tombstones, in sequence order. Each element
+    /// is followed by the elements inserted after it, newest first.
+    fn ordered_ids(&self) -> Vec<Stamp> {
+        let mut children = BTreeMap::<Option<Stamp>, Vec<Stamp>>::new();
+        // Iterating in reverse produces each list of children in descending
+        // order.
+        for (id, element) in self.elements.iter().rev() {
+            children.entry(element.after).or_default().push(*id);
+        }
+
+        let mut ordered = Vec::with_capacity(self.elements.len());
+        let mut stack = children.remove(&None).unwrap_or_default();
+        stack.reverse();
+        while let Some(id) = stack.pop() {
+            ordered.push(id);
+            if let Some(mut inserted_after) = children.remove(&Some(id)) {
+                inserted_after.reverse();
+                stack.extend(inserted_after);
+            }
+        }
+        ordered
+    }
+}
+
+impl<T> Crdt for Sequence<T> {
+    fn merge(&mut self, other: Self) {
+        for (id, other) in other.elements {
+            match self.elements.entry(id) {
+                Entry::Occupied(mut entry) => {
+                    if other.value.is_none() {
+                        entry.get_mut().value = None;
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(other);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A: ReplicaId = ReplicaId(1);
+    const B: ReplicaId = ReplicaId(2);
+
+    #[test]
+    fn counter() {
+        let mut a = Counter::default();
+        a.increment(A, 5);
+        let mut b = a.clone();
+        a.decrement(A, 2);
+        b.increment(B, 10);
+
+        let mut merged = a.clone();
+        merged.merge(b.clone());
+        assert_eq!(merged.value(), 13);
+        b.merge(a);
+        assert_eq!(b, merged);
+        merged.merge(b);
+        assert_eq!(merged.value(), 13);
+    }
+
+    #[test]
+    fn register() {
+        let mut a = LwwRegister::new(0);
+        let mut b = a.clone();
+        a.set(A, 1);
+        b.set(B, 2);
+        b.set(B, 3);
+
+        let mut merged = a.clone();
+        merged.merge(b.clone());
+        b.merge(a);
+        assert_eq!(merged, b);
+    }
+
+    #[test]
+    fn map() {
+        let mut a = CrdtMap::<String, Counter>::default();
+        a.update(A, String::from("a"), |counter| counter.increment(A, 1));
+        a.update(A, String::from("b"), |counter| counter.increment(A, 1));
+        let mut b = a.clone();
+        a.remove(A, &String::from("a"));
+        b.update(B, String::from("b"), |counter| counter.increment(B, 1));
+        b.update(B, String::from("c"), |counter| counter.increment(B, 1));
+
+        let mut merged = a.clone();
+        merged.merge(b.clone());
+        b.merge(a);
+        assert_eq!(merged, b);
+        assert!(!merged.contains_key(&String::from("a")));
+        assert_eq!(merged.get(&String::from("b")).unwrap().value(), 2);
+        assert_eq!(merged.len(), 2);
+    }
+
+    #[test]
+    fn sequence() {
+        let mut a = Sequence::default();
+        a.push(A, 'a');
+        a.push(A, 'c');
+        let mut b = a.clone();
+        a.insert(A, 1, 'b');
+        b.push(B, 'd');
+        b.remove(0);
+
+        let mut merged = a.clone();
+        merged.merge(b.clone());
+        b.merge(a);
+        assert_eq!(merged, b);
+        assert_eq!(merged.iter().copied().collect::<String>(), "bcd");
+    }
+}
@@ -32,10 +32,12 @@ use crate::key::KeyEncoding;
 use crate::schema::{Collection, SerializedCollection};
 
 mod collection;
+mod encrypted;
 mod header;
 mod id;
 mod revision;
 pub use self::collection::{CollectionDocument, OwnedDocuments};
+pub use self::encrypted::{Encrypted, FieldEncryption};
 pub use self::header::{AnyHeader, CollectionHeader, Emit, HasHeader, Header};
 pub use self::id::{DocumentId, InvalidHexadecimal};
 pub use self::revision::Revision;
@@ -0,0 +1,126 @@
+use std::marker::PhantomData;
+
+use arc_bytes::serde::Bytes;
+use derive_where::derive_where;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::document::KeyId;
+use crate::Error;
+
+/// A value that is stored encrypted, even when the document containing it is
+/// not.
+///
+/// This type is useful for protecting individual fields, such as tokens or
+/// personal information, within an otherwise plaintext document. The value is
+/// serialized using [`Pot`](https://github.com/khonsulabs/pot) and encrypted by
+/// the vault using the [`KeyId`] provided to [`Encrypted::encrypt()`]. Only the
+/// encrypted payload is serialized, which means views and other readers of the
+/// document will not be able to see the value.
+///
+/// The value can only be retrieved using [`Encrypted::decrypt()`] with a
+/// [`FieldEncryption`] implementor whose vault has the key loaded and whose
+/// session is permitted to use the key.
+///
+/// The vault currently only contains its master keys, so values can only be
+/// encrypted using [`KeyId::Master`]. Named keys are not supported.
+#[derive_where(Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+#[serde(transparent, bound = "")]
+pub struct Encrypted<T> {
+    payload: Bytes,
+    #[serde(skip)]
+    _value: PhantomData<fn() -> T>,
+}
+
+impl<T> Encrypted<T> {
+    /// Serializes `value` and encrypts it using `key`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnsupportedEncryptionKey`] if `key` is not
+    /// [`KeyId::Master`].
+    pub fn encrypt<E: FieldEncryption + ?Sized>(
+        value: &T,
+        key: &KeyId,
+        encryption: &E,
+    ) -> Result<Self, Error>
+    where
+        T: Serialize,
+    {
+        if key != &KeyId::Master {
+            return Err(Error::UnsupportedEncryptionKey(key.clone()));
+        }
+        let plaintext = pot::to_vec(value)?;
+        Ok(Self::from_encrypted_bytes(
+            encryption.encrypt_field_bytes(key, &plaintext)?,
+        ))
+    }
+
+    /// Decrypts and deserializes the contained value.
+    pub fn decrypt<E: FieldEncryption + ?Sized>(&self, encryption: &E) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+    {
+        let plaintext = encryption.decrypt_field_bytes(&self.payload)?;
+        Ok(pot::from_slice(&plaintext)?)
+    }
+
+    /// Returns an instance wrapping a payload previously returned from
+    /// [`Encrypted::as_encrypted_bytes()`].
+    #[must_use]
+    pub fn from_encrypted_bytes(payload: impl Into<Bytes>) -> Self {
+        Self {
+            payload: payload.into(),
+            _value: PhantomData,
+        }
+    }
+
+    /// Returns the encrypted payload.
+    #[must_use]
+    pub fn as_encrypted_bytes(&self) -> &[u8] {
+        &self.payload
+    }
+}
+
+/// Encrypts and decrypts individual values using a vault.
+///
+/// This trait is used by [`Encrypted<T>`] to perform its encryption.
+pub trait FieldEncryption {
+    /// Encrypts `plaintext` using `key`, returning the encrypted payload.
+    fn encrypt_field_bytes(&self, key: &KeyId, plaintext: &[u8]) -> Result<Vec<u8>, Error>;
+
+    /// Decrypts a payload previously returned from
+    /// [`encrypt_field_bytes()`](Self::encrypt_field_bytes).
+    fn decrypt_field_bytes(&self, payload: &[u8]) -> Result<Vec<u8>, Error>;
+}
+
+#[test]
+fn encrypted_serialization_is_opaque() {
+    struct Reversed;
+
+    impl FieldEncryption for Reversed {
+        fn encrypt_field_bytes(&self, _key: &KeyId, plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+            Ok(plaintext.iter().rev().copied().collect())
+        }
+
+        fn decrypt_field_bytes(&self, payload: &[u8]) -> Result<Vec<u8>, Error> {
+            Ok(payload.iter().rev().copied().collect())
+        }
+    }
+
+    let encrypted = Encrypted::encrypt(&String::from("secret"), &KeyId::Master, &Reversed).unwrap();
+    let serialized = pot::to_vec(&encrypted).unwrap();
+    let deserialized = pot::from_slice::<Encrypted<String>>(&serialized).unwrap();
+    assert_eq!(deserialized, encrypted);
+    assert_eq!(deserialized.decrypt(&Reversed).unwrap(), "secret");
+
+    assert!(matches!(
+        Encrypted::encrypt(
+            &String::from("secret"),
+            &KeyId::Id(std::borrow::Cow::Borrowed("named")),
+            &Reversed
+        ),
+        Err(Error::UnsupportedEncryptionKey(KeyId::Id(_)))
+    ));
+}
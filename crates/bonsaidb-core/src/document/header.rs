@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::document::{BorrowedDocument, CollectionDocument, DocumentId, OwnedDocument, Revision};
 use crate::key::Key;
-use crate::schema::view::map::Mappings;
+use crate::schema::view::map::{Emitter, Mappings};
 use crate::schema::{Map, SerializedCollection};
 
 /// The header of a `Document`.
@@ -54,6 +54,11 @@ pub trait Emit {
         key: K,
         value: Value,
     ) -> Result<Mappings<K, Value>, crate::Error>;
+
+    /// Returns an [`Emitter`] that can emit any number of entries.
+    fn emitter<K: for<'a> Key<'a> + PartialEq, Value>(
+        &self,
+    ) -> Result<Emitter<K, Value>, crate::Error>;
 }
 
 impl Emit for Header {
@@ -64,6 +69,12 @@ impl Emit for Header {
     ) -> Result<Mappings<K, Value>, crate::Error> {
         Ok(Mappings::Simple(Some(Map::new(self.clone(), key, value))))
     }
+
+    fn emitter<K: for<'a> Key<'a> + PartialEq, Value>(
+        &self,
+    ) -> Result<Emitter<K, Value>, crate::Error> {
+        Ok(Emitter::new(self.clone()))
+    }
 }
 
 impl Display for Header {
@@ -95,6 +106,12 @@ where
         let header = Header::try_from(self.clone())?;
         Ok(Mappings::Simple(Some(Map::new(header, key, value))))
     }
+
+    fn emitter<K: for<'a> Key<'a> + PartialEq, Value>(
+        &self,
+    ) -> Result<Emitter<K, Value>, crate::Error> {
+        Ok(Emitter::new(Header::try_from(self.clone())?))
+    }
 }
 
 impl<PrimaryKey> HasHeader for CollectionHeader<PrimaryKey>
@@ -225,6 +242,31 @@ fn chained_mappings_test() -> Result<(), crate::Error> {
     Ok(())
 }
 
+#[test]
+fn emitter_test() -> Result<(), crate::Error> {
+    use crate::schema::Map;
+    use crate::test_util::Basic;
+
+    let doc = BorrowedDocument::with_contents::<Basic, _>(&1, &Basic::default())?;
+
+    let mut emitter = doc.header.emitter()?;
+    assert!(emitter.is_empty());
+    emitter.emit(2, "a").emit(1, "b").emit(2, "c");
+    assert_eq!(emitter.len(), 2);
+    assert_eq!(
+        emitter.into_mappings(),
+        Mappings::List(vec![
+            Map::new(doc.header.clone(), 2, "c"),
+            Map::new(doc.header.clone(), 1, "b")
+        ])
+    );
+
+    let emitter = doc.header.emitter::<u64, ()>()?;
+    assert_eq!(Mappings::from(emitter), Mappings::none());
+
+    Ok(())
+}
+
 #[test]
 fn header_display_test() {
     let original_contents = b"one";
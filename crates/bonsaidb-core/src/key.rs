@@ -1,3 +1,5 @@
+/// [`Key`] implementations for geographic coordinates.
+pub mod geo;
 /// [`Key`] implementations for time types.
 pub mod time;
 
@@ -0,0 +1,286 @@
+use std::borrow::Cow;
+use std::fmt::Debug;
+
+use serde::{Deserialize, Serialize};
+
+use crate::key::{ByteCow, IncorrectByteLength, Key, KeyEncoding, NextValueError};
+
+/// A latitude and longitude pair that can be used as a [`Key`].
+///
+/// Both coordinates are quantized to 32 bits, and their bits are interleaved
+/// into a single 64-bit [Z-order
+/// curve](https://en.wikipedia.org/wiki/Z-order_curve) value. Points that are
+/// close together geographically tend to be close together in the encoded
+/// key's order, which allows a view to be queried for all points within a
+/// [`GeoBounds`] by scanning a single key range.
+///
+/// The quantization error is less than a centimeter at any location.
+///
+/// ```rust
+/// use bonsaidb_core::key::geo::{GeoBounds, GeoPoint};
+///
+/// let portland = GeoPoint::new(45.5152, -122.6784).unwrap();
+/// let oregon = GeoBounds::new(41.99, -124.57, 46.29, -116.46).unwrap();
+/// assert!(oregon.contains(portland));
+/// ```
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub struct GeoPoint(u64);
+
+impl GeoPoint {
+    /// The smallest point: the south-west corner of the map.
+    pub const MIN: Self = Self(0);
+    /// The largest point: the north-east corner of the map.
+    pub const MAX: Self = Self(u64::MAX);
+
+    /// Returns a point at `latitude` and `longitude`, in degrees.
+    ///
+    /// # Errors
+    ///
+    /// - [`GeoError::InvalidLatitude`]: `latitude` is not within `-90.0..=90.0`.
+    /// - [`GeoError::InvalidLongitude`]: `longitude` is not within
+    ///   `-180.0..=180.0`.
+    pub fn new(latitude: f64, longitude: f64) -> Result<Self, GeoError> {
+        Ok(Self::from_quantized(
+            quantize(latitude, 90.).ok_or(GeoError::InvalidLatitude)?,
+            quantize(longitude, 180.).ok_or(GeoError::InvalidLongitude)?,
+        ))
+    }
+
+    const fn from_quantized(latitude: u32, longitude: u32) -> Self {
+        Self(spread(latitude) << 1 | spread(longitude))
+    }
+
+    /// Returns the latitude of this point, in degrees.
+    #[must_use]
+    pub fn latitude(self) -> f64 {
+        dequantize(self.quantized_latitude(), 90.)
+    }
+
+    /// Returns the longitude of this point, in degrees.
+    #[must_use]
+    pub fn longitude(self) -> f64 {
+        dequantize(self.quantized_longitude(), 180.)
+    }
+
+    const fn quantized_latitude(self) -> u32 {
+        compact(self.0 >> 1)
+    }
+
+    const fn quantized_longitude(self) -> u32 {
+        compact(self.0)
+    }
+
+    /// Returns the underlying Z-order encoded value.
+    #[must_use]
+    pub const fn z_order(self) -> u64 {
+        self.0
+    }
+
+    /// Returns the point represented by the Z-order encoded `value`.
+    #[must_use]
+    pub const fn from_z_order(value: u64) -> Self {
+        Self(value)
+    }
+}
+
+impl Debug for GeoPoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GeoPoint")
+            .field("latitude", &self.latitude())
+            .field("longitude", &self.longitude())
+            .finish()
+    }
+}
+
+impl<'a> Key<'a> for GeoPoint {
+    const CAN_OWN_BYTES: bool = false;
+
+    fn from_ord_bytes<'b>(bytes: ByteCow<'a, 'b>) -> Result<Self, Self::Error> {
+        u64::from_ord_bytes(bytes).map(Self)
+    }
+
+    fn first_value() -> Result<Self, NextValueError> {
+        Ok(Self::MIN)
+    }
+
+    fn next_value(&self) -> Result<Self, NextValueError> {
+        self.0.next_value().map(Self)
+    }
+}
+
+impl<'a> KeyEncoding<'a, Self> for GeoPoint {
+    type Error = IncorrectByteLength;
+
+    const LENGTH: Option<usize> = Some(8);
+
+    fn as_ord_bytes(&'a self) -> Result<Cow<'a, [u8]>, Self::Error> {
+        Ok(Cow::Owned(self.0.to_be_bytes().to_vec()))
+    }
+}
+
+/// A rectangular region bounded by two lines of latitude and two lines of
+/// longitude.
+///
+/// Bounds crossing the antimeridian are not supported. Instead, split the
+/// region into two bounds and query each separately.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct GeoBounds {
+    south_west: GeoPoint,
+    north_east: GeoPoint,
+}
+
+impl GeoBounds {
+    /// Returns the region between `south` and `north` latitudes and between
+    /// `west` and `east` longitudes, in degrees. The edges are inclusive.
+    ///
+    /// # Errors
+    ///
+    /// - [`GeoError::InvalidLatitude`]: `south` or `north` is not within
+    ///   `-90.0..=90.0`.
+    /// - [`GeoError::InvalidLongitude`]: `west` or `east` is not within
+    ///   `-180.0..=180.0`.
+    /// - [`GeoError::InvalidBounds`]: `south` is greater than `north`, or
+    ///   `west` is greater than `east`.
+    pub fn new(south: f64, west: f64, north: f64, east: f64) -> Result<Self, GeoError> {
+        Self::from_corners(GeoPoint::new(south, west)?, GeoPoint::new(north, east)?)
+    }
+
+    /// Returns the region between the `south_west` and `north_east` corners.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GeoError::InvalidBounds`] if `south_west` is north or east of
+    /// `north_east`.
+    pub fn from_corners(south_west: GeoPoint, north_east: GeoPoint) -> Result<Self, GeoError> {
+        if south_west.quantized_latitude() <= north_east.quantized_latitude()
+            && south_west.quantized_longitude() <= north_east.quantized_longitude()
+        {
+            Ok(Self {
+                south_west,
+                north_east,
+            })
+        } else {
+            Err(GeoError::InvalidBounds)
+        }
+    }
+
+    /// Returns the south-west corner of this region.
+    #[must_use]
+    pub const fn south_west(&self) -> GeoPoint {
+        self.south_west
+    }
+
+    /// Returns the north-east corner of this region.
+    #[must_use]
+    pub const fn north_east(&self) -> GeoPoint {
+        self.north_east
+    }
+
+    /// Returns true if `point` is within this region.
+    #[must_use]
+    pub const fn contains(&self, point: GeoPoint) -> bool {
+        let latitude = point.quantized_latitude();
+        let longitude = point.quantized_longitude();
+        latitude >= self.south_west.quantized_latitude()
+            && latitude <= self.north_east.quantized_latitude()
+            && longitude >= self.south_west.quantized_longitude()
+            && longitude <= self.north_east.quantized_longitude()
+    }
+
+    /// Returns the range of keys that contains every point within this region.
+    ///
+    /// Because the Z-order curve leaves and re-enters the region, the range
+    /// can also contain points outside of this region. Results should be
+    /// filtered using [`Self::contains()`].
+    #[must_use]
+    pub const fn key_range(&self) -> std::ops::RangeInclusive<GeoPoint> {
+        self.south_west..=self.north_east
+    }
+}
+
+/// Errors that can arise from creating a [`GeoPoint`] or [`GeoBounds`].
+#[derive(thiserror::Error, Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum GeoError {
+    /// The latitude was not a finite value within `-90.0..=90.0`.
+    #[error("latitude must be between -90 and 90 degrees")]
+    InvalidLatitude,
+    /// The longitude was not a finite value within `-180.0..=180.0`.
+    #[error("longitude must be between -180 and 180 degrees")]
+    InvalidLongitude,
+    /// The south-west corner was north or east of the north-east corner.
+    #[error("the south-west corner must not be north or east of the north-east corner")]
+    InvalidBounds,
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn quantize(degrees: f64, limit: f64) -> Option<u32> {
+    if (-limit..=limit).contains(&degrees) {
+        // The value is within 0.0..=u32::MAX after scaling, so the cast can
+        // not truncate.
+        Some(((degrees + limit) / (limit * 2.) * f64::from(u32::MAX)).round() as u32)
+    } else {
+        None
+    }
+}
+
+fn dequantize(value: u32, limit: f64) -> f64 {
+    f64::from(value) / f64::from(u32::MAX) * limit * 2. - limit
+}
+
+/// Spreads the bits of `value` so that there is an empty bit between each.
+#[allow(clippy::cast_lossless)] // u64::from isn't const
+const fn spread(value: u32) -> u64 {
+    let mut value = value as u64;
+    value = (value | (value << 16)) & 0x0000_FFFF_0000_FFFF;
+    value = (value | (value << 8)) & 0x00FF_00FF_00FF_00FF;
+    value = (value | (value << 4)) & 0x0F0F_0F0F_0F0F_0F0F;
+    value = (value | (value << 2)) & 0x3333_3333_3333_3333;
+    (value | (value << 1)) & 0x5555_5555_5555_5555
+}
+
+/// The inverse of [`spread()`]: collects every other bit of `value`.
+#[allow(clippy::cast_possible_truncation)]
+const fn compact(value: u64) -> u32 {
+    let mut value = value & 0x5555_5555_5555_5555;
+    value = (value | (value >> 1)) & 0x3333_3333_3333_3333;
+    value = (value | (value >> 2)) & 0x0F0F_0F0F_0F0F_0F0F;
+    value = (value | (value >> 4)) & 0x00FF_00FF_00FF_00FF;
+    value = (value | (value >> 8)) & 0x0000_FFFF_0000_FFFF;
+    // The upper 32 bits are cleared by the previous step.
+    (value | (value >> 16)) as u32
+}
+
+#[test]
+fn geo_point_key_tests() {
+    for (latitude, longitude) in [(0., 0.), (-90., -180.), (90., 180.), (45.5152, -122.6784)] {
+        let point = GeoPoint::new(latitude, longitude).unwrap();
+        let decoded =
+            GeoPoint::from_ord_bytes(ByteCow::Borrowed(&point.as_ord_bytes().unwrap())).unwrap();
+        assert_eq!(point, decoded);
+        assert!((decoded.latitude() - latitude).abs() < 1e-7);
+        assert!((decoded.longitude() - longitude).abs() < 1e-7);
+    }
+    assert_eq!(GeoPoint::new(-90., -180.).unwrap(), GeoPoint::MIN);
+    assert_eq!(GeoPoint::new(90., 180.).unwrap(), GeoPoint::MAX);
+    assert_eq!(GeoPoint::new(90.1, 0.), Err(GeoError::InvalidLatitude));
+    assert_eq!(GeoPoint::new(0., f64::NAN), Err(GeoError::InvalidLongitude));
+}
+
+#[test]
+fn geo_bounds_tests() {
+    let bounds = GeoBounds::new(40., -125., 50., -115.).unwrap();
+    let range = bounds.key_range();
+    for latitude in (0..=20).map(|step| 40. + f64::from(step) / 2.) {
+        for longitude in (0..=20).map(|step| -125. + f64::from(step) / 2.) {
+            let point = GeoPoint::new(latitude, longitude).unwrap();
+            assert!(bounds.contains(point));
+            assert!(range.contains(&point));
+        }
+    }
+    assert!(!bounds.contains(GeoPoint::new(39.9, -120.).unwrap()));
+    assert!(!bounds.contains(GeoPoint::new(45., -114.9).unwrap()));
+    assert_eq!(
+        GeoBounds::new(50., -125., 40., -115.),
+        Err(GeoError::InvalidBounds)
+    );
+}
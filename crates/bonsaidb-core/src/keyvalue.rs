@@ -160,7 +160,105 @@ mod implementation {
                 command: Command::Delete,
             })? {
                 Output::Status(status) => Ok(status),
-                Output::Value(_) => unreachable!("invalid output from delete operation"),
+                _ => unreachable!("invalid output from delete operation"),
+            }
+        }
+
+        /// Gets the values stored at each of `keys` in a single operation. The
+        /// returned values are in the same order as `keys`, and `None` is
+        /// returned for each key that has no value.
+        fn get_keys<K: Into<String>, I: IntoIterator<Item = K>>(
+            &self,
+            keys: I,
+        ) -> Result<Vec<Option<Value>>, Error> {
+            match self.execute_key_operation(KeyOperation {
+                namespace: self.key_namespace().map(ToOwned::to_owned),
+                key: String::new(),
+                command: Command::GetMultiple {
+                    keys: keys.into_iter().map(Into::into).collect(),
+                },
+            })? {
+                Output::Values(values) => Ok(values),
+                _ => unreachable!("invalid output from get multiple operation"),
+            }
+        }
+
+        /// Sets each key in `entries` to its value in a single operation. Either
+        /// every key is updated or, if an error occurs, none of them are. Any
+        /// existing expirations of the keys are cleared.
+        ///
+        /// The returned statuses are in the same order as `entries`.
+        fn set_keys<K: Into<String>, V: Serialize, I: IntoIterator<Item = (K, V)>>(
+            &self,
+            entries: I,
+        ) -> Result<Vec<KeyStatus>, Error> {
+            match self.execute_key_operation(KeyOperation {
+                namespace: self.key_namespace().map(ToOwned::to_owned),
+                key: String::new(),
+                command: Command::SetMultiple {
+                    entries: serialize_entries(entries)?,
+                },
+            })? {
+                Output::Statuses(statuses) => Ok(statuses),
+                _ => unreachable!("invalid output from set multiple operation"),
+            }
+        }
+
+        /// Returns the names of every namespace that contains at least one
+        /// key, in ascending order. Keys stored without a namespace are not
+        /// included.
+        fn list_key_namespaces(&self) -> Result<Vec<String>, Error> {
+            match self.execute_key_operation(KeyOperation {
+                namespace: None,
+                key: String::new(),
+                command: Command::ListNamespaces,
+            })? {
+                Output::Namespaces(namespaces) => Ok(namespaces),
+                _ => unreachable!("invalid output from list namespaces operation"),
+            }
+        }
+
+        /// Returns the number of keys stored in the current namespace.
+        fn count_keys(&self) -> Result<u64, Error> {
+            match self.execute_key_operation(KeyOperation {
+                namespace: self.key_namespace().map(ToOwned::to_owned),
+                key: String::new(),
+                command: Command::CountKeys,
+            })? {
+                Output::Count(count) => Ok(count),
+                _ => unreachable!("invalid output from count keys operation"),
+            }
+        }
+
+        /// Deletes every key stored in the current namespace, returning the
+        /// number of keys deleted. All of the keys are removed in a single
+        /// operation: no other key-value operation can observe the namespace
+        /// partially deleted.
+        fn delete_key_namespace(&self) -> Result<u64, Error> {
+            match self.execute_key_operation(KeyOperation {
+                namespace: self.key_namespace().map(ToOwned::to_owned),
+                key: String::new(),
+                command: Command::DeleteNamespace,
+            })? {
+                Output::Count(count) => Ok(count),
+                _ => unreachable!("invalid output from delete namespace operation"),
+            }
+        }
+
+        /// Persists all changes to the key-value store that haven't been
+        /// persisted yet, returning after they have been written to disk.
+        /// Returns true if there were any changes to persist.
+        ///
+        /// This is useful when the key-value store is configured to persist
+        /// changes lazily, but specific changes must be made durable.
+        fn flush_key_value_store(&self) -> Result<bool, Error> {
+            match self.execute_key_operation(KeyOperation {
+                namespace: None,
+                key: String::new(),
+                command: Command::Flush,
+            })? {
+                Output::Status(status) => Ok(status == KeyStatus::Updated),
+                _ => unreachable!("invalid output from flush operation"),
             }
         }
 
@@ -314,7 +412,125 @@ mod implementation {
                 .await?
             {
                 Output::Status(status) => Ok(status),
-                Output::Value(_) => unreachable!("invalid output from delete operation"),
+                _ => unreachable!("invalid output from delete operation"),
+            }
+        }
+
+        /// Gets the values stored at each of `keys` in a single operation. The
+        /// returned values are in the same order as `keys`, and `None` is
+        /// returned for each key that has no value.
+        async fn get_keys<K: Into<String> + Send, I: IntoIterator<Item = K> + Send>(
+            &self,
+            keys: I,
+        ) -> Result<Vec<Option<Value>>, Error> {
+            let keys = keys.into_iter().map(Into::into).collect();
+            match self
+                .execute_key_operation(KeyOperation {
+                    namespace: self.key_namespace().map(ToOwned::to_owned),
+                    key: String::new(),
+                    command: Command::GetMultiple { keys },
+                })
+                .await?
+            {
+                Output::Values(values) => Ok(values),
+                _ => unreachable!("invalid output from get multiple operation"),
+            }
+        }
+
+        /// Sets each key in `entries` to its value in a single operation. Either
+        /// every key is updated or, if an error occurs, none of them are. Any
+        /// existing expirations of the keys are cleared.
+        ///
+        /// The returned statuses are in the same order as `entries`.
+        async fn set_keys<
+            K: Into<String> + Send,
+            V: Serialize + Send,
+            I: IntoIterator<Item = (K, V)> + Send,
+        >(
+            &self,
+            entries: I,
+        ) -> Result<Vec<KeyStatus>, Error> {
+            let entries = serialize_entries(entries)?;
+            match self
+                .execute_key_operation(KeyOperation {
+                    namespace: self.key_namespace().map(ToOwned::to_owned),
+                    key: String::new(),
+                    command: Command::SetMultiple { entries },
+                })
+                .await?
+            {
+                Output::Statuses(statuses) => Ok(statuses),
+                _ => unreachable!("invalid output from set multiple operation"),
+            }
+        }
+
+        /// Returns the names of every namespace that contains at least one
+        /// key, in ascending order. Keys stored without a namespace are not
+        /// included.
+        async fn list_key_namespaces(&self) -> Result<Vec<String>, Error> {
+            match self
+                .execute_key_operation(KeyOperation {
+                    namespace: None,
+                    key: String::new(),
+                    command: Command::ListNamespaces,
+                })
+                .await?
+            {
+                Output::Namespaces(namespaces) => Ok(namespaces),
+                _ => unreachable!("invalid output from list namespaces operation"),
+            }
+        }
+
+        /// Returns the number of keys stored in the current namespace.
+        async fn count_keys(&self) -> Result<u64, Error> {
+            match self
+                .execute_key_operation(KeyOperation {
+                    namespace: self.key_namespace().map(ToOwned::to_owned),
+                    key: String::new(),
+                    command: Command::CountKeys,
+                })
+                .await?
+            {
+                Output::Count(count) => Ok(count),
+                _ => unreachable!("invalid output from count keys operation"),
+            }
+        }
+
+        /// Deletes every key stored in the current namespace, returning the
+        /// number of keys deleted. All of the keys are removed in a single
+        /// operation: no other key-value operation can observe the namespace
+        /// partially deleted.
+        async fn delete_key_namespace(&self) -> Result<u64, Error> {
+            match self
+                .execute_key_operation(KeyOperation {
+                    namespace: self.key_namespace().map(ToOwned::to_owned),
+                    key: String::new(),
+                    command: Command::DeleteNamespace,
+                })
+                .await?
+            {
+                Output::Count(count) => Ok(count),
+                _ => unreachable!("invalid output from delete namespace operation"),
+            }
+        }
+
+        /// Persists all changes to the key-value store that haven't been
+        /// persisted yet, returning after they have been written to disk.
+        /// Returns true if there were any changes to persist.
+        ///
+        /// This is useful when the key-value store is configured to persist
+        /// changes lazily, but specific changes must be made durable.
+        async fn flush_key_value_store(&self) -> Result<bool, Error> {
+            match self
+                .execute_key_operation(KeyOperation {
+                    namespace: None,
+                    key: String::new(),
+                    command: Command::Flush,
+                })
+                .await?
+            {
+                Output::Status(status) => Ok(status == KeyStatus::Updated),
+                _ => unreachable!("invalid output from flush operation"),
             }
         }
 
@@ -331,6 +547,15 @@ mod implementation {
         }
     }
 
+    fn serialize_entries<K: Into<String>, V: Serialize, I: IntoIterator<Item = (K, V)>>(
+        entries: I,
+    ) -> Result<Vec<(String, Value)>, Error> {
+        entries
+            .into_iter()
+            .map(|(key, value)| Ok((key.into(), Value::Bytes(Bytes::from(pot::to_vec(&value)?)))))
+            .collect()
+    }
+
     enum BuilderState<'a, T, V> {
         Pending(Option<T>),
         Executing(BoxFuture<'a, V>),
@@ -413,6 +638,33 @@ pub enum Command {
     },
     /// Delete a key.
     Delete,
+    /// Get the values of multiple keys. The operation's key is ignored.
+    GetMultiple {
+        /// The keys to retrieve.
+        keys: Vec<String>,
+    },
+    /// Set multiple key/value pairs in a single operation. Either every key is
+    /// set or none of them are. Existing expirations of the keys are cleared.
+    /// The operation's key is ignored.
+    SetMultiple {
+        /// The keys and the values to store.
+        entries: Vec<(String, Value)>,
+    },
+    /// List the namespaces that contain at least one key. The operation's
+    /// namespace and key are ignored.
+    ListNamespaces,
+    /// Count the keys stored in the operation's namespace. The operation's key
+    /// is ignored.
+    CountKeys,
+    /// Delete every key stored in the operation's namespace in a single
+    /// operation. The operation's key is ignored.
+    DeleteNamespace,
+    /// Persist every change that hasn't been persisted yet, completing once
+    /// the changes have been written to disk. The output is
+    /// [`KeyStatus::Updated`] if any changes were persisted and
+    /// [`KeyStatus::NotChanged`] otherwise. The operation's namespace and key
+    /// are ignored.
+    Flush,
 }
 
 /// Set a key/value pair.
@@ -727,6 +979,14 @@ pub enum Output {
     Status(KeyStatus),
     /// A value was returned.
     Value(Option<Value>),
+    /// A list of namespaces was returned.
+    Namespaces(Vec<String>),
+    /// A number of keys was returned.
+    Count(u64),
+    /// Values for multiple keys were returned.
+    Values(Vec<Option<Value>>),
+    /// Statuses for multiple keys were returned.
+    Statuses(Vec<KeyStatus>),
 }
 /// The status of an operation on a Key.
 #[derive(Copy, Clone, Serialize, Deserialize, Debug, Eq, PartialEq)]
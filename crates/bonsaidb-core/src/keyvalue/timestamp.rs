@@ -31,6 +31,51 @@ impl Timestamp {
     pub fn now() -> Self {
         Self::from(SystemTime::now())
     }
+
+    /// Returns this timestamp rounded down to a multiple of `interval` since
+    /// [`UNIX_EPOCH`]. If `interval` is zero, the timestamp is returned
+    /// unchanged.
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use bonsaidb_core::keyvalue::Timestamp;
+    ///
+    /// let timestamp = Timestamp {
+    ///     seconds: 125,
+    ///     nanos: 5,
+    /// };
+    /// assert_eq!(
+    ///     timestamp.truncate(Duration::from_secs(60)),
+    ///     Timestamp {
+    ///         seconds: 120,
+    ///         nanos: 0,
+    ///     }
+    /// );
+    /// ```
+    #[must_use]
+    pub fn truncate(self, interval: Duration) -> Self {
+        let interval = interval.as_nanos();
+        if interval == 0 {
+            return self;
+        }
+        let nanos = Duration::from(self).as_nanos();
+        let truncated = nanos - nanos % interval;
+        Self {
+            seconds: u64::try_from(truncated / 1_000_000_000)
+                .expect("truncating can't increase the number of seconds"),
+            nanos: u32::try_from(truncated % 1_000_000_000).expect("remainder is less than 1e9"),
+        }
+    }
+}
+
+impl From<Duration> for Timestamp {
+    fn from(duration_since_epoch: Duration) -> Self {
+        Self {
+            seconds: duration_since_epoch.as_secs(),
+            nanos: duration_since_epoch.subsec_nanos(),
+        }
+    }
 }
 
 impl From<SystemTime> for Timestamp {
@@ -65,7 +110,7 @@ impl std::ops::Add<Duration> for Timestamp {
     fn add(self, rhs: Duration) -> Self::Output {
         let mut nanos = self.nanos + rhs.subsec_nanos();
         let mut seconds = self.seconds.saturating_add(rhs.as_secs());
-        while nanos > 1_000_000_000 {
+        while nanos >= 1_000_000_000 {
             nanos -= 1_000_000_000;
             seconds = seconds.saturating_add(1);
         }
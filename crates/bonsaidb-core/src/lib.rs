@@ -23,6 +23,7 @@ pub mod permissions;
 pub mod admin;
 /// Types for interacting with BonsaiDb.
 pub mod connection;
+pub mod crdt;
 pub mod document;
 pub mod limits;
 /// Types for defining database schema.
@@ -45,19 +46,25 @@ pub mod networking;
 /// Types for Publish/Subscribe (`PubSub`) messaging.
 pub mod pubsub;
 
+pub mod sealed;
+
+#[cfg(feature = "sql")]
+pub mod sql;
+
 use std::fmt::Display;
 use std::string::FromUtf8Error;
+use std::time::Duration;
 
-use schema::{view, CollectionName, SchemaName, ViewName};
+use schema::{view, CollectionName, Name, SchemaName, ViewName};
 use serde::{Deserialize, Serialize};
 pub use {
     actionable, arc_bytes, async_trait, circulate, num_traits, ordered_varint, transmog,
     transmog_pot,
 };
 
-use crate::api::ApiName;
+use crate::api::{ApiName, QueryName};
 use crate::connection::HasSchema;
-use crate::document::{DocumentId, Header, InvalidHexadecimal};
+use crate::document::{DocumentId, Header, InvalidHexadecimal, KeyId};
 use crate::key::time::TimeError;
 use crate::key::NextValueError;
 use crate::schema::InsertError;
@@ -111,10 +118,18 @@ pub enum Error {
     #[error("collection was not found")]
     CollectionNotFound,
 
+    /// The projection was not found.
+    #[error("projection was not found")]
+    ProjectionNotFound,
+
     /// The api invoked was not found.
     #[error("api '{0}' was not found")]
     ApiNotFound(ApiName),
 
+    /// The named query executed was not found.
+    #[error("named query '{0}' was not found")]
+    NamedQueryNotFound(QueryName),
+
     /// The database name already exists.
     #[error("a database with name '{0}' already exists")]
     DatabaseNameAlreadyTaken(String),
@@ -127,6 +142,10 @@ pub enum Error {
     #[error("attempted to define a collection that already has been defined")]
     CollectionAlreadyDefined,
 
+    /// A projection named `0` has already been defined for its collection.
+    #[error("projection '{0}' was already defined")]
+    ProjectionAlreadyDefined(Name),
+
     /// An attempt to update a document that doesn't exist.
     #[error("the requested document id {1} from collection {0} was not found")]
     DocumentNotFound(CollectionName, Box<DocumentId>),
@@ -178,18 +197,149 @@ pub enum Error {
     #[error("user not found")]
     UserNotFound,
 
+    /// A [`PermissionGroup`](crate::admin::PermissionGroup) was not found.
+    #[error("permission group not found")]
+    PermissionGroupNotFound,
+
+    /// A [`Role`](crate::admin::Role) was not found.
+    #[error("role not found")]
+    RoleNotFound,
+
+    /// A permission statement was rejected by
+    /// [`validate_statements()`](crate::permissions::bonsai::validate_statements).
+    #[error("permission statement {index} is invalid: {reason}")]
+    InvalidPermissionStatement {
+        /// The index of the invalid statement.
+        index: usize,
+        /// The reason the statement is invalid.
+        reason: String,
+    },
+
+    /// A [`PermissionGroup`](crate::admin::PermissionGroup) or
+    /// [`Role`](crate::admin::Role) was modified after the version being
+    /// updated was read.
+    #[error("version conflict: expected version {expected}, but the current version is {current}")]
+    VersionConflict {
+        /// The version the update expected.
+        expected: u64,
+        /// The current version.
+        current: u64,
+    },
+
     /// An error occurred converting from bytes to Utf-8.
     #[error("invalid string: {0}")]
     InvalidUnicode(String),
 
+    /// The encryption key can't be used to encrypt values. Only
+    /// [`KeyId::Master`] is currently supported.
+    #[error("encryption key {0:?} is not supported: only the master key can be used")]
+    UnsupportedEncryptionKey(KeyId),
+
     /// The credentials specified are not valid.
     #[error("invalid credentials")]
     InvalidCredentials,
 
+    /// A password does not satisfy the minimum length of the password policy.
+    #[error("password must be at least {minimum_length} characters")]
+    PasswordTooShort {
+        /// The minimum number of characters a password must contain.
+        minimum_length: usize,
+    },
+
+    /// The user's password has expired. A new password must be set before the
+    /// user can authenticate using a password.
+    #[error("password expired")]
+    PasswordExpired,
+
+    /// Password authentication is locked for the user or client after too many
+    /// failed attempts.
+    #[error("account locked")]
+    AccountLocked {
+        /// The amount of time until the lock is removed. When `None`, the lock
+        /// remains until it is removed by an administrator.
+        retry_after: Option<Duration>,
+    },
+
+    /// The session used for the request has expired or has been revoked.
+    #[error("session expired")]
+    SessionExpired,
+
+    /// The server has reached the maximum number of connections it allows,
+    /// either in total or for the user being authenticated.
+    #[error("connection limit reached")]
+    ConnectionLimitReached,
+
+    /// The request was rejected because a rate limit was exceeded.
+    #[error("rate limit exceeded")]
+    RateLimited {
+        /// The amount of time to wait before retrying the request, if known.
+        /// When `None`, the request can be retried once another request that
+        /// is in progress completes.
+        retry_after: Option<Duration>,
+    },
+
+    /// The request's deadline passed before the operation completed. The
+    /// operation may still complete in the background.
+    #[error("deadline exceeded")]
+    DeadlineExceeded,
+
+    /// A document's contents are larger than the maximum document size.
+    #[error("document is {size} bytes, which exceeds the limit of {limit} bytes")]
+    DocumentTooLarge {
+        /// The size of the document's contents, in bytes.
+        size: usize,
+        /// The maximum size allowed, in bytes.
+        limit: usize,
+    },
+
+    /// The combined size of the documents in a transaction is larger than the
+    /// maximum transaction size, or the combined size of a client's open
+    /// chunked transactions is larger than the maximum size of its open
+    /// transactions.
+    #[error("transaction is {size} bytes, which exceeds the limit of {limit} bytes")]
+    TransactionTooLarge {
+        /// The combined size of the transaction's documents, in bytes.
+        size: usize,
+        /// The maximum size allowed, in bytes.
+        limit: usize,
+    },
+
+    /// A response is larger than the maximum response size.
+    #[error("response is {size} bytes, which exceeds the limit of {limit} bytes")]
+    ResponseTooLarge {
+        /// The size of the response, in bytes.
+        size: usize,
+        /// The maximum size allowed, in bytes.
+        limit: usize,
+    },
+
+    /// A session already has the maximum number of chunked transactions open.
+    #[error("the limit of {limit} open transactions has been reached")]
+    TooManyOpenTransactions {
+        /// The maximum number of open transactions allowed.
+        limit: usize,
+    },
+
+    /// A database's files are larger than the database's quota, preventing
+    /// more data from being written to it.
+    #[error("database '{database}' is {size} bytes, which exceeds its quota of {limit} bytes")]
+    DatabaseQuotaExceeded {
+        /// The name of the database.
+        database: String,
+        /// The size of the database's files, in bytes.
+        size: u64,
+        /// The database's quota, in bytes.
+        limit: u64,
+    },
+
     /// Returned when the a view's reduce() function is unimplemented.
     #[error("reduce is unimplemented")]
     ReduceUnimplemented,
 
+    /// Returned when the a view's aggregate_value() function is unimplemented.
+    #[error("aggregate_value is unimplemented")]
+    AggregationUnimplemented,
+
     /// A floating point operation yielded Not a Number.
     #[error("floating point operation yielded NaN")]
     NotANumber,
@@ -217,6 +367,62 @@ impl Error {
         }
     }
 
+    /// Returns the machine-readable category of this error. The code is
+    /// preserved when errors are sent between servers and clients, allowing
+    /// callers to handle errors without inspecting their messages.
+    #[must_use]
+    pub const fn code(&self) -> ErrorCode {
+        match self {
+            Self::DatabaseNotFound(_)
+            | Self::SchemaNotRegistered(_)
+            | Self::ViewNotFound
+            | Self::CollectionNotFound
+            | Self::ProjectionNotFound
+            | Self::ApiNotFound(_)
+            | Self::NamedQueryNotFound(_)
+            | Self::DocumentNotFound(..)
+            | Self::UserNotFound
+            | Self::PermissionGroupNotFound
+            | Self::RoleNotFound => ErrorCode::NotFound,
+            Self::SchemaAlreadyRegistered(_)
+            | Self::ViewAlreadyRegistered(_)
+            | Self::DatabaseNameAlreadyTaken(_)
+            | Self::CollectionAlreadyDefined
+            | Self::ProjectionAlreadyDefined(_) => ErrorCode::AlreadyExists,
+            Self::DocumentConflict(..) | Self::VersionConflict { .. } => {
+                ErrorCode::DocumentConflict
+            }
+            Self::UniqueKeyViolation { .. } => ErrorCode::UniqueViolation,
+            Self::PermissionDenied(_) => ErrorCode::PermissionDenied,
+            Self::InvalidCredentials
+            | Self::PasswordExpired
+            | Self::AccountLocked { .. }
+            | Self::SessionExpired => ErrorCode::Unauthenticated,
+            Self::SchemaMismatch { .. }
+            | Self::InvalidDatabaseName(_)
+            | Self::DocumentIdTooLong
+            | Self::InvalidName(_)
+            | Self::InvalidPermissionStatement { .. }
+            | Self::PasswordTooShort { .. }
+            | Self::InvalidUnicode(_)
+            | Self::UnsupportedEncryptionKey(_)
+            | Self::Time(_) => ErrorCode::InvalidArgument,
+            Self::ConnectionLimitReached
+            | Self::DocumentTooLarge { .. }
+            | Self::TransactionTooLarge { .. }
+            | Self::ResponseTooLarge { .. }
+            | Self::TooManyOpenTransactions { .. }
+            | Self::DatabaseQuotaExceeded { .. } => ErrorCode::QuotaExceeded,
+            Self::RateLimited { .. } => ErrorCode::RateLimited,
+            Self::DeadlineExceeded => ErrorCode::DeadlineExceeded,
+            Self::Networking(_) => ErrorCode::Unavailable,
+            Self::ReduceUnimplemented | Self::AggregationUnimplemented => ErrorCode::Unimplemented,
+            Self::DocumentPush(..) | Self::Password(_) | Self::NotANumber | Self::Other { .. } => {
+                ErrorCode::Internal
+            }
+        }
+    }
+
     /// Returns true if this error is a [`Error::UniqueKeyViolation`] from
     /// `View`.
     pub fn is_unique_key_error<View: schema::View, C: HasSchema>(&self, connection: &C) -> bool {
@@ -244,6 +450,74 @@ impl Error {
     }
 }
 
+/// A machine-readable category of an [`Error`], returned from
+/// [`Error::code()`].
+///
+/// Codes are stable across versions and can be compared against or stored
+/// using [`ErrorCode::as_str()`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum ErrorCode {
+    /// The requested database, collection, document, user, or other entity
+    /// does not exist.
+    NotFound,
+    /// An entity with the same name has already been defined or created.
+    AlreadyExists,
+    /// A document or versioned entity was modified since it was read.
+    DocumentConflict,
+    /// A unique view already contains the key being emitted.
+    UniqueViolation,
+    /// The session is not permitted to perform the requested action.
+    PermissionDenied,
+    /// The request requires authentication, or authentication failed.
+    Unauthenticated,
+    /// A value provided in the request is invalid.
+    InvalidArgument,
+    /// A size or usage limit was exceeded.
+    QuotaExceeded,
+    /// A rate limit was exceeded.
+    RateLimited,
+    /// The request's deadline passed before the operation completed.
+    DeadlineExceeded,
+    /// The server could not be reached or stopped responding.
+    Unavailable,
+    /// The requested functionality is not implemented.
+    Unimplemented,
+    /// A custom [`Api`](api::Api) returned its own error type.
+    Api,
+    /// An unexpected error occurred.
+    Internal,
+}
+
+impl ErrorCode {
+    /// Returns the code as a `snake_case` string.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::NotFound => "not_found",
+            Self::AlreadyExists => "already_exists",
+            Self::DocumentConflict => "document_conflict",
+            Self::UniqueViolation => "unique_violation",
+            Self::PermissionDenied => "permission_denied",
+            Self::Unauthenticated => "unauthenticated",
+            Self::InvalidArgument => "invalid_argument",
+            Self::QuotaExceeded => "quota_exceeded",
+            Self::RateLimited => "rate_limited",
+            Self::DeadlineExceeded => "deadline_exceeded",
+            Self::Unavailable => "unavailable",
+            Self::Unimplemented => "unimplemented",
+            Self::Api => "api",
+            Self::Internal => "internal",
+        }
+    }
+}
+
+impl Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 impl From<pot::Error> for Error {
     fn from(err: pot::Error) -> Self {
         Self::other("pot", err)
@@ -258,7 +532,10 @@ impl<T> From<InsertError<T>> for Error {
 
 impl From<view::Error> for Error {
     fn from(err: view::Error) -> Self {
-        Self::other("view", err)
+        match err {
+            view::Error::Core(err) => err,
+            other => Self::other("view", other),
+        }
     }
 }
 
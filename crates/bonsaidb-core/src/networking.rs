@@ -1,19 +1,37 @@
+use std::time::Duration;
+
 use arc_bytes::serde::Bytes;
 use schema::SchemaName;
 use serde::{Deserialize, Serialize};
 
-use crate::api::{Api, ApiName};
+use crate::api::{Api, ApiName, QueryName};
 use crate::connection::{
-    AccessPolicy, Database, IdentityReference, Range, SerializedQueryKey, Session, SessionId, Sort,
+    AccessPolicy, Database, DatabaseQuery, IdentityReference, Range, SerializedQueryKey, Session,
+    SessionId, SessionInfo, Sort, ViewReadOptions,
 };
 use crate::document::{DocumentId, Header, OwnedDocument};
 use crate::keyvalue::{KeyOperation, Output};
+use crate::permissions::{Permissions, Statement};
+use crate::schema::view::aggregation::{
+    Aggregation, AggregationGrouping, SerializedAggregatedGroup,
+};
+use crate::schema::view::join::SerializedJoinedEntry;
 use crate::schema::view::map::{self, MappedSerializedDocuments};
-use crate::schema::{self, CollectionName, NamedReference, Qualified, ViewName};
-use crate::transaction::{Executed, OperationResult, Transaction};
+use crate::schema::{self, CollectionName, Name, NamedReference, Qualified, ViewName};
+use crate::transaction::{DocumentChanges, Executed, Operation, OperationResult, Transaction};
+
+mod compression;
+mod events;
+mod handshake;
+mod limits;
+
+pub use self::compression::{Compression, CompressionConfig};
+pub use self::events::ServerEvent;
+pub use self::handshake::{Capabilities, Handshake, HandshakeResponse, CURRENT_PROTOCOL_REVISION};
+pub use self::limits::PayloadLimits;
 
 /// The current protocol version.
-pub const CURRENT_PROTOCOL_VERSION: &str = "bonsai/pre/0";
+pub const CURRENT_PROTOCOL_VERSION: &str = "bonsai/pre/2";
 
 /// A payload with an associated id.
 #[derive(Clone, Deserialize, Serialize, Debug)]
@@ -26,6 +44,48 @@ pub struct Payload {
     pub name: ApiName,
     /// The payload
     pub value: Result<Bytes, crate::Error>,
+    /// The algorithm `value` was compressed with, if any. Only successful
+    /// values are compressed.
+    pub compression: Option<Compression>,
+    /// The amount of time the sender is willing to wait for a response, if
+    /// limited. Once this much time has elapsed since the request was
+    /// received, the server stops waiting on long-running operations for this
+    /// request and returns
+    /// [`Error::DeadlineExceeded`](crate::Error::DeadlineExceeded). A duration
+    /// is sent rather than a timestamp so that clock differences between the
+    /// client and server do not affect the deadline.
+    pub deadline: Option<Duration>,
+}
+
+/// Executes several requests in a single round trip. The server executes the
+/// requests concurrently and responds with the result of each request, in the
+/// same order as `requests`.
+///
+/// Each result mirrors [`Payload::value`]: on success, it contains the
+/// serialized `Result<Api::Response, Api::Error>` of the request's
+/// [`Api`]. Batches cannot contain other batches.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct ExecuteBatch {
+    /// The requests to execute.
+    pub requests: Vec<BatchedRequest>,
+}
+
+impl Api for ExecuteBatch {
+    type Error = crate::Error;
+    type Response = Vec<Result<Bytes, crate::Error>>;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "ExecuteBatch")
+    }
+}
+
+/// A request executed as part of an [`ExecuteBatch`] request.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct BatchedRequest {
+    /// The unique name of the api.
+    pub name: ApiName,
+    /// The serialized request.
+    pub value: Bytes,
 }
 
 /// Creates a database.
@@ -62,9 +122,12 @@ impl Api for DeleteDatabase {
     }
 }
 
-/// Lists all databases.
+/// Lists the databases matching `query`.
 #[derive(Clone, Deserialize, Serialize, Debug)]
-pub struct ListDatabases;
+pub struct ListDatabases {
+    /// The criteria databases must match to be listed.
+    pub query: DatabaseQuery,
+}
 
 impl Api for ListDatabases {
     type Error = crate::Error;
@@ -73,6 +136,10 @@ impl Api for ListDatabases {
     fn name() -> ApiName {
         ApiName::new("bonsaidb", "ListDatabases")
     }
+
+    fn idempotent() -> bool {
+        true
+    }
 }
 
 /// Lists available schemas.
@@ -86,6 +153,10 @@ impl Api for ListAvailableSchemas {
     fn name() -> ApiName {
         ApiName::new("bonsaidb", "ListAvailableSchemas")
     }
+
+    fn idempotent() -> bool {
+        true
+    }
 }
 
 /// Creates a user.
@@ -120,6 +191,97 @@ impl Api for DeleteUser {
     }
 }
 
+/// Lists all users.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct ListUsers;
+
+impl Api for ListUsers {
+    type Error = crate::Error;
+    type Response = Vec<OwnedDocument>;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "ListUsers")
+    }
+
+    fn idempotent() -> bool {
+        true
+    }
+}
+
+/// Looks up a user by its username.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct UserByName {
+    /// The username of the user.
+    pub username: String,
+}
+
+impl Api for UserByName {
+    type Error = crate::Error;
+    type Response = Option<OwnedDocument>;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "UserByName")
+    }
+
+    fn idempotent() -> bool {
+        true
+    }
+}
+
+/// Enables or disables a user.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct SetUserDisabled {
+    /// The username or id of the user.
+    pub user: NamedReference<'static, u64>,
+    /// Whether the user should be disabled.
+    pub disabled: bool,
+}
+
+impl Api for SetUserDisabled {
+    type Error = crate::Error;
+    type Response = ();
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "SetUserDisabled")
+    }
+}
+
+/// Removes any lockout preventing a user from authenticating with a password.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct UnlockUser {
+    /// The username or id of the user.
+    pub user: NamedReference<'static, u64>,
+}
+
+impl Api for UnlockUser {
+    type Error = crate::Error;
+    type Response = ();
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "UnlockUser")
+    }
+}
+
+/// Returns the effective permissions of a user.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct EffectivePermissions {
+    /// The username or id of the user.
+    pub user: NamedReference<'static, u64>,
+}
+
+impl Api for EffectivePermissions {
+    type Error = crate::Error;
+    type Response = Permissions;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "EffectivePermissions")
+    }
+
+    fn idempotent() -> bool {
+        true
+    }
+}
+
 /// Set's a user's password.
 #[cfg(feature = "password-hashing")]
 #[derive(Clone, Deserialize, Serialize, Debug)]
@@ -184,6 +346,219 @@ impl Api for LogOutSession {
     }
 }
 
+/// Creates a permission group.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct CreatePermissionGroup {
+    /// The name of the permission group.
+    pub name: String,
+    /// The permission statements of the group.
+    pub statements: Vec<Statement>,
+}
+
+impl Api for CreatePermissionGroup {
+    type Error = crate::Error;
+    type Response = u64;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "CreatePermissionGroup")
+    }
+}
+
+/// Lists all permission groups.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct ListPermissionGroups;
+
+impl Api for ListPermissionGroups {
+    type Error = crate::Error;
+    type Response = Vec<OwnedDocument>;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "ListPermissionGroups")
+    }
+
+    fn idempotent() -> bool {
+        true
+    }
+}
+
+/// Replaces the statements of a permission group.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct SetPermissionGroupStatements {
+    /// The name or id of the group.
+    pub group: NamedReference<'static, u64>,
+    /// The new permission statements of the group.
+    pub statements: Vec<Statement>,
+}
+
+impl Api for SetPermissionGroupStatements {
+    type Error = crate::Error;
+    type Response = ();
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "SetPermissionGroupStatements")
+    }
+}
+
+/// Replaces the statements of a permission group if its version matches.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct UpdatePermissionGroupStatements {
+    /// The name or id of the group.
+    pub group: NamedReference<'static, u64>,
+    /// The version of the group the statements are replacing.
+    pub expected_version: u64,
+    /// The new permission statements of the group.
+    pub statements: Vec<Statement>,
+}
+
+impl Api for UpdatePermissionGroupStatements {
+    type Error = crate::Error;
+    type Response = u64;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "UpdatePermissionGroupStatements")
+    }
+}
+
+/// Deletes a permission group.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct DeletePermissionGroup {
+    /// The name or id of the group.
+    pub group: NamedReference<'static, u64>,
+}
+
+impl Api for DeletePermissionGroup {
+    type Error = crate::Error;
+    type Response = ();
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "DeletePermissionGroup")
+    }
+}
+
+/// Creates a role.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct CreateRole {
+    /// The name of the role.
+    pub name: String,
+}
+
+impl Api for CreateRole {
+    type Error = crate::Error;
+    type Response = u64;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "CreateRole")
+    }
+}
+
+/// Lists all roles.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct ListRoles;
+
+impl Api for ListRoles {
+    type Error = crate::Error;
+    type Response = Vec<OwnedDocument>;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "ListRoles")
+    }
+
+    fn idempotent() -> bool {
+        true
+    }
+}
+
+/// Alter's a role's membership in a permission group.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct AlterRolePermissionGroupMembership {
+    /// The name or id of the role.
+    pub role: NamedReference<'static, u64>,
+
+    /// The name or id of the group.
+    pub group: NamedReference<'static, u64>,
+
+    /// Whether the role should be in the group.
+    pub should_be_member: bool,
+}
+
+impl Api for AlterRolePermissionGroupMembership {
+    type Error = crate::Error;
+    type Response = ();
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "AlterRolePermissionGroupMembership")
+    }
+}
+
+/// Replaces the permission groups of a role if its version matches.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct UpdateRolePermissionGroups {
+    /// The name or id of the role.
+    pub role: NamedReference<'static, u64>,
+    /// The version of the role the groups are replacing.
+    pub expected_version: u64,
+    /// The ids of the role's new permission groups.
+    pub groups: Vec<u64>,
+}
+
+impl Api for UpdateRolePermissionGroups {
+    type Error = crate::Error;
+    type Response = u64;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "UpdateRolePermissionGroups")
+    }
+}
+
+/// Deletes a role.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct DeleteRole {
+    /// The name or id of the role.
+    pub role: NamedReference<'static, u64>,
+}
+
+impl Api for DeleteRole {
+    type Error = crate::Error;
+    type Response = ();
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "DeleteRole")
+    }
+}
+
+/// Lists the active sessions of a user.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct ListSessions {
+    /// The username or id of the user.
+    pub user: NamedReference<'static, u64>,
+}
+
+impl Api for ListSessions {
+    type Error = crate::Error;
+    type Response = Vec<SessionInfo>;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "ListSessions")
+    }
+
+    fn idempotent() -> bool {
+        true
+    }
+}
+
+/// Revokes an active session.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct RevokeSession(pub SessionId);
+
+impl Api for RevokeSession {
+    type Error = crate::Error;
+    type Response = ();
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "RevokeSession")
+    }
+}
+
 /// Alter's a user's membership in a permission group.
 #[derive(Clone, Deserialize, Serialize, Debug)]
 pub struct AlterUserPermissionGroupMembership {
@@ -246,6 +621,10 @@ impl Api for Get {
     fn name() -> ApiName {
         ApiName::new("bonsaidb", "Get")
     }
+
+    fn idempotent() -> bool {
+        true
+    }
 }
 
 /// Retrieve multiple documents.
@@ -266,6 +645,10 @@ impl Api for GetMultiple {
     fn name() -> ApiName {
         ApiName::new("bonsaidb", "GetMultiple")
     }
+
+    fn idempotent() -> bool {
+        true
+    }
 }
 
 /// Retrieve multiple documents.
@@ -290,6 +673,10 @@ impl Api for List {
     fn name() -> ApiName {
         ApiName::new("bonsaidb", "List")
     }
+
+    fn idempotent() -> bool {
+        true
+    }
 }
 
 /// Retrieve multiple document headers.
@@ -303,6 +690,33 @@ impl Api for ListHeaders {
     fn name() -> ApiName {
         ApiName::new("bonsaidb", "ListHeaders")
     }
+
+    fn idempotent() -> bool {
+        true
+    }
+}
+
+/// Retrieve multiple documents, with their contents projected by a
+/// [`Projection`](crate::schema::Projection).
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct ListProjected {
+    /// The documents to list.
+    pub list: List,
+    /// The name of the projection.
+    pub projection: Name,
+}
+
+impl Api for ListProjected {
+    type Error = crate::Error;
+    type Response = Vec<OwnedDocument>;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "ListProjected")
+    }
+
+    fn idempotent() -> bool {
+        true
+    }
 }
 
 /// Counts the number of documents in the specified range.
@@ -323,6 +737,10 @@ impl Api for Count {
     fn name() -> ApiName {
         ApiName::new("bonsaidb", "Count")
     }
+
+    fn idempotent() -> bool {
+        true
+    }
 }
 
 /// Queries a view.
@@ -340,6 +758,9 @@ pub struct Query {
     pub limit: Option<u32>,
     /// The access policy for the query.
     pub access_policy: AccessPolicy,
+    /// Where the query is executed when the server is a member of a cluster.
+    #[serde(default)]
+    pub read_options: ViewReadOptions,
 }
 
 impl Api for Query {
@@ -349,6 +770,10 @@ impl Api for Query {
     fn name() -> ApiName {
         ApiName::new("bonsaidb", "Query")
     }
+
+    fn idempotent() -> bool {
+        true
+    }
 }
 
 /// Queries a view with the associated documents.
@@ -362,6 +787,33 @@ impl Api for QueryWithDocs {
     fn name() -> ApiName {
         ApiName::new("bonsaidb", "QueryWithDocs")
     }
+
+    fn idempotent() -> bool {
+        true
+    }
+}
+
+/// Queries a view with the associated documents, with the documents' contents
+/// projected by a [`Projection`](crate::schema::Projection).
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct QueryWithProjectedDocs {
+    /// The view query.
+    pub query: Query,
+    /// The name of the projection.
+    pub projection: Name,
+}
+
+impl Api for QueryWithProjectedDocs {
+    type Error = crate::Error;
+    type Response = MappedSerializedDocuments;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "QueryWithProjectedDocs")
+    }
+
+    fn idempotent() -> bool {
+        true
+    }
 }
 
 /// Reduces a view.
@@ -375,6 +827,9 @@ pub struct Reduce {
     pub key: Option<SerializedQueryKey>,
     /// The access policy for the query.
     pub access_policy: AccessPolicy,
+    /// Where the query is executed when the server is a member of a cluster.
+    #[serde(default)]
+    pub read_options: ViewReadOptions,
 }
 
 impl Api for Reduce {
@@ -384,6 +839,10 @@ impl Api for Reduce {
     fn name() -> ApiName {
         ApiName::new("bonsaidb", "Reduce")
     }
+
+    fn idempotent() -> bool {
+        true
+    }
 }
 
 /// Reduces a view, grouping the reduced values by key.
@@ -397,6 +856,152 @@ impl Api for ReduceGrouped {
     fn name() -> ApiName {
         ApiName::new("bonsaidb", "ReduceGrouped")
     }
+
+    fn idempotent() -> bool {
+        true
+    }
+}
+
+/// Computes aggregations over the values of a view.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct Aggregate {
+    /// The name of the database.
+    pub database: String,
+    /// The name of the view.
+    pub view: ViewName,
+    /// The filter for the view.
+    pub key: Option<SerializedQueryKey>,
+    /// The aggregations to compute.
+    pub aggregations: Vec<Aggregation>,
+    /// How the view entries are grouped.
+    pub grouping: AggregationGrouping,
+    /// The access policy for the query.
+    pub access_policy: AccessPolicy,
+    /// Where the query is executed when the server is a member of a cluster.
+    #[serde(default)]
+    pub read_options: ViewReadOptions,
+}
+
+impl Api for Aggregate {
+    type Error = crate::Error;
+    type Response = Vec<SerializedAggregatedGroup>;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "Aggregate")
+    }
+
+    fn idempotent() -> bool {
+        true
+    }
+}
+
+/// Counts the entries matching a view query.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct CountView {
+    /// The name of the database.
+    pub database: String,
+    /// The name of the view.
+    pub view: ViewName,
+    /// The filter for the view.
+    pub key: Option<SerializedQueryKey>,
+    /// The access policy for the query.
+    pub access_policy: AccessPolicy,
+    /// Where the query is executed when the server is a member of a cluster.
+    #[serde(default)]
+    pub read_options: ViewReadOptions,
+}
+
+impl Api for CountView {
+    type Error = crate::Error;
+    type Response = u64;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "CountView")
+    }
+
+    fn idempotent() -> bool {
+        true
+    }
+}
+
+/// Returns the view entries emitted by a single document.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct DocumentMappings {
+    /// The name of the database.
+    pub database: String,
+    /// The name of the view.
+    pub view: ViewName,
+    /// The id of the document whose entries are returned.
+    pub document_id: DocumentId,
+    /// The access policy for the query.
+    pub access_policy: AccessPolicy,
+    /// Where the query is executed when the server is a member of a cluster.
+    #[serde(default)]
+    pub read_options: ViewReadOptions,
+}
+
+impl Api for DocumentMappings {
+    type Error = crate::Error;
+    type Response = Vec<map::Serialized>;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "DocumentMappings")
+    }
+
+    fn idempotent() -> bool {
+        true
+    }
+}
+
+/// Queries a joined view.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct QueryJoin {
+    /// The name of the database.
+    pub database: String,
+    /// The name of the joined view.
+    pub join: ViewName,
+    /// The filter for the joined view.
+    pub key: Option<SerializedQueryKey>,
+    /// The order for the query into the joined view.
+    pub order: Sort,
+    /// The maximum number of results to return.
+    pub limit: Option<u32>,
+    /// The access policy for the query.
+    pub access_policy: AccessPolicy,
+}
+
+impl Api for QueryJoin {
+    type Error = crate::Error;
+    type Response = Vec<SerializedJoinedEntry>;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "QueryJoin")
+    }
+
+    fn idempotent() -> bool {
+        true
+    }
+}
+
+/// Executes a [`NamedQuery`](crate::api::NamedQuery) registered with the
+/// server.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct ExecuteNamedQuery {
+    /// The name of the database.
+    pub database: String,
+    /// The name of the query.
+    pub name: QueryName,
+    /// The serialized parameters of the query.
+    pub parameters: Bytes,
+}
+
+impl Api for ExecuteNamedQuery {
+    type Error = crate::Error;
+    type Response = Bytes;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "ExecuteNamedQuery")
+    }
 }
 
 /// Deletes the associated documents resulting from the view query.
@@ -439,6 +1044,62 @@ impl Api for ApplyTransaction {
     }
 }
 
+/// Begins a transaction whose operations are sent in chunks using
+/// [`PushTransactionOperations`]. No operations are applied until
+/// [`CommitTransaction`] is sent. Returns the id of the transaction.
+///
+/// Only supported by servers with
+/// [`Capabilities::STREAMED_TRANSACTIONS`].
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct BeginTransaction {
+    /// The name of the database.
+    pub database: String,
+}
+
+impl Api for BeginTransaction {
+    type Error = crate::Error;
+    type Response = u64;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "BeginTransaction")
+    }
+}
+
+/// Adds operations to a transaction begun with [`BeginTransaction`].
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct PushTransactionOperations {
+    /// The id of the transaction.
+    pub transaction_id: u64,
+    /// The operations to add to the transaction.
+    pub operations: Vec<Operation>,
+}
+
+impl Api for PushTransactionOperations {
+    type Error = crate::Error;
+    type Response = ();
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "PushTransactionOperations")
+    }
+}
+
+/// Atomically applies every operation pushed to a transaction begun with
+/// [`BeginTransaction`].
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct CommitTransaction {
+    /// The id of the transaction.
+    pub transaction_id: u64,
+}
+
+impl Api for CommitTransaction {
+    type Error = crate::Error;
+    type Response = Vec<OperationResult>;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "CommitTransaction")
+    }
+}
+
 /// Lists executed transactions.
 #[derive(Clone, Deserialize, Serialize, Debug)]
 pub struct ListExecutedTransactions {
@@ -457,6 +1118,10 @@ impl Api for ListExecutedTransactions {
     fn name() -> ApiName {
         ApiName::new("bonsaidb", "ListExecutedTransactions")
     }
+
+    fn idempotent() -> bool {
+        true
+    }
 }
 
 /// Queries the last transaction id.
@@ -473,6 +1138,32 @@ impl Api for LastTransactionId {
     fn name() -> ApiName {
         ApiName::new("bonsaidb", "LastTransactionId")
     }
+
+    fn idempotent() -> bool {
+        true
+    }
+}
+
+/// Begins notifying the client of documents changed in `database` using the
+/// [`DocumentsChanged`] API. Watching a database requires permission to list
+/// its executed transactions.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct WatchDocumentChanges {
+    /// The name of the database.
+    pub database: String,
+}
+
+impl Api for WatchDocumentChanges {
+    type Error = crate::Error;
+    type Response = ();
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "WatchDocumentChanges")
+    }
+
+    fn idempotent() -> bool {
+        true
+    }
 }
 
 /// Creates a `PubSub` [`Subscriber`](crate::pubsub::Subscriber)
@@ -571,6 +1262,46 @@ impl Api for MessageReceived {
     }
 }
 
+/// Documents were changed in a database being watched by the client. This
+/// notification is sent after the transaction making the changes has been
+/// committed.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct DocumentsChanged {
+    /// The name of the database.
+    pub database: String,
+    /// The documents that were changed.
+    pub changes: DocumentChanges,
+}
+
+impl Api for DocumentsChanged {
+    type Error = crate::Error;
+    type Response = Self;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "DocumentsChanged")
+    }
+}
+
+/// The server has begun shutting down. This notification is sent to every
+/// connected client when a graceful shutdown begins. Requests that have
+/// already been received will still be answered, but the connection will be
+/// closed once they have completed or the grace period has elapsed.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct ShuttingDown {
+    /// The maximum amount of time the server will wait for outstanding work
+    /// to complete before closing all connections.
+    pub grace_period: Duration,
+}
+
+impl Api for ShuttingDown {
+    type Error = crate::Error;
+    type Response = Self;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "ShuttingDown")
+    }
+}
+
 /// Unsubscribes `subscriber_id` from messages for `topic`.
 #[derive(Clone, Deserialize, Serialize, Debug)]
 pub struct UnsubscribeFrom {
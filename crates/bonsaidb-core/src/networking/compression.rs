@@ -0,0 +1,234 @@
+use arc_bytes::serde::Bytes;
+use serde::{Deserialize, Serialize};
+
+use crate::networking::Payload;
+
+/// An algorithm used to compress [`Payload`] values.
+///
+/// Every algorithm can be named by both peers, but compressing or
+/// decompressing requires the algorithm's feature to be enabled:
+/// `network-compression-zstd` or `network-compression-deflate`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Compression {
+    /// Compress using [Zstandard](https://facebook.github.io/zstd/).
+    Zstd,
+    /// Compress using DEFLATE.
+    Deflate,
+}
+
+impl Compression {
+    /// Returns the algorithms supported by this build, in order of
+    /// preference.
+    #[must_use]
+    pub fn supported() -> Vec<Self> {
+        [Self::Zstd, Self::Deflate]
+            .into_iter()
+            .filter(|compression| compression.is_supported())
+            .collect()
+    }
+
+    /// Returns true if this build is able to compress and decompress using
+    /// this algorithm.
+    #[must_use]
+    pub const fn is_supported(self) -> bool {
+        match self {
+            Self::Zstd => cfg!(feature = "network-compression-zstd"),
+            Self::Deflate => cfg!(feature = "network-compression-deflate"),
+        }
+    }
+
+    /// Compresses `data` using this algorithm.
+    #[cfg_attr(
+        not(any(
+            feature = "network-compression-zstd",
+            feature = "network-compression-deflate"
+        )),
+        allow(unused_variables)
+    )]
+    pub fn compress(self, data: &[u8]) -> Result<Vec<u8>, crate::Error> {
+        match self {
+            #[cfg(feature = "network-compression-zstd")]
+            Self::Zstd => zstd::bulk::compress(data, 0).map_err(compression_error),
+            #[cfg(feature = "network-compression-deflate")]
+            Self::Deflate => {
+                use std::io::Write;
+                let mut encoder = flate2::write::DeflateEncoder::new(
+                    Vec::with_capacity(data.len() / 2),
+                    flate2::Compression::default(),
+                );
+                encoder.write_all(data).map_err(compression_error)?;
+                encoder.finish().map_err(compression_error)
+            }
+            #[allow(unreachable_patterns)]
+            unsupported => Err(unsupported.unsupported_error()),
+        }
+    }
+
+    /// Decompresses `data` using this algorithm. Returns an error if the
+    /// decompressed data would be larger than `max_size` bytes.
+    #[cfg_attr(
+        not(any(
+            feature = "network-compression-zstd",
+            feature = "network-compression-deflate"
+        )),
+        allow(unused_variables, unused_mut, unreachable_code)
+    )]
+    pub fn decompress(self, data: &[u8], max_size: usize) -> Result<Vec<u8>, crate::Error> {
+        let mut decompressed = Vec::new();
+        let limit = u64::try_from(max_size)
+            .unwrap_or(u64::MAX)
+            .saturating_add(1);
+        match self {
+            #[cfg(feature = "network-compression-zstd")]
+            Self::Zstd => {
+                use std::io::Read;
+                zstd::stream::read::Decoder::new(data)
+                    .map_err(compression_error)?
+                    .take(limit)
+                    .read_to_end(&mut decompressed)
+                    .map_err(compression_error)?;
+            }
+            #[cfg(feature = "network-compression-deflate")]
+            Self::Deflate => {
+                use std::io::Read;
+                flate2::read::DeflateDecoder::new(data)
+                    .take(limit)
+                    .read_to_end(&mut decompressed)
+                    .map_err(compression_error)?;
+            }
+            #[allow(unreachable_patterns)]
+            unsupported => return Err(unsupported.unsupported_error()),
+        }
+
+        if decompressed.len() > max_size {
+            return Err(crate::Error::other(
+                "compression",
+                format!("decompressed payload exceeds {max_size} bytes"),
+            ));
+        }
+
+        Ok(decompressed)
+    }
+
+    fn unsupported_error(self) -> crate::Error {
+        crate::Error::other(
+            "compression",
+            format!("{self:?} compression is not enabled in this build"),
+        )
+    }
+}
+
+#[cfg(any(
+    feature = "network-compression-zstd",
+    feature = "network-compression-deflate"
+))]
+fn compression_error(err: std::io::Error) -> crate::Error {
+    crate::Error::other("compression", err)
+}
+
+/// Controls how the [`Payload`]s sent over a connection are compressed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CompressionConfig {
+    /// The algorithms that may be used, in order of preference.
+    pub algorithms: Vec<Compression>,
+    /// Payload values smaller than this number of bytes are sent
+    /// uncompressed, as compressing small messages rarely reduces their size.
+    pub threshold: usize,
+}
+
+impl Default for CompressionConfig {
+    /// Returns a configuration that allows every algorithm supported by this
+    /// build, compressing values that are at least 1KB.
+    fn default() -> Self {
+        Self {
+            algorithms: Compression::supported(),
+            threshold: 1024,
+        }
+    }
+}
+
+impl CompressionConfig {
+    /// Returns a configuration that never compresses payloads.
+    #[must_use]
+    pub const fn disabled() -> Self {
+        Self {
+            algorithms: Vec::new(),
+            threshold: usize::MAX,
+        }
+    }
+
+    /// Returns a configuration that allows `algorithms`, in order of
+    /// preference, compressing values that are at least 1KB.
+    #[must_use]
+    pub fn new(algorithms: impl IntoIterator<Item = Compression>) -> Self {
+        Self {
+            algorithms: algorithms.into_iter().collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Sets the minimum size of a value to compress, in bytes, and returns
+    /// self.
+    #[must_use]
+    pub const fn with_threshold(mut self, threshold: usize) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Returns true if no algorithms are allowed.
+    #[must_use]
+    pub fn is_disabled(&self) -> bool {
+        self.algorithms.is_empty()
+    }
+
+    /// Returns the first algorithm in `offered` that is allowed by this
+    /// configuration and supported by this build. Algorithms are chosen using
+    /// the order of preference of `offered`.
+    #[must_use]
+    pub fn negotiate(&self, offered: &[Compression]) -> Option<Compression> {
+        offered
+            .iter()
+            .copied()
+            .find(|compression| compression.is_supported() && self.algorithms.contains(compression))
+    }
+}
+
+impl Payload {
+    /// Compresses this payload's value using `compression` if the value is
+    /// at least `threshold` bytes long and the payload is not already
+    /// compressed. If compressing does not reduce the size of the value, the
+    /// payload is left unchanged.
+    pub fn compress(
+        &mut self,
+        compression: Compression,
+        threshold: usize,
+    ) -> Result<(), crate::Error> {
+        if self.compression.is_some() {
+            return Ok(());
+        }
+
+        if let Ok(value) = &self.value {
+            if value.len() >= threshold {
+                let compressed = compression.compress(value)?;
+                if compressed.len() < value.len() {
+                    self.value = Ok(Bytes::from(compressed));
+                    self.compression = Some(compression);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Decompresses this payload's value if it was compressed. Returns an
+    /// error if the decompressed value would be larger than `max_size` bytes.
+    pub fn decompress(&mut self, max_size: usize) -> Result<(), crate::Error> {
+        if let Some(compression) = self.compression.take() {
+            if let Ok(value) = &self.value {
+                self.value = Ok(Bytes::from(compression.decompress(value, max_size)?));
+            }
+        }
+
+        Ok(())
+    }
+}
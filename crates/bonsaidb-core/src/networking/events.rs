@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::{Api, ApiName};
+use crate::schema::SchemaName;
+
+/// A notification about the state of the server, sent to connected clients
+/// that support [`Capabilities::SERVER_EVENTS`].
+///
+/// Unlike `PubSub` messages, server events are not scoped to a database and
+/// do not require a subscription on the server. Events describing databases
+/// or schemas are only delivered to clients with a session allowed to list
+/// them.
+///
+/// [`Capabilities::SERVER_EVENTS`]: crate::networking::Capabilities::SERVER_EVENTS
+#[derive(Clone, Deserialize, Serialize, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ServerEvent {
+    /// A database was created.
+    DatabaseCreated {
+        /// The name of the database.
+        name: String,
+        /// The schema of the database.
+        schema: SchemaName,
+    },
+    /// A database was deleted.
+    DatabaseDeleted {
+        /// The name of the database.
+        name: String,
+    },
+    /// A schema was registered, allowing databases using it to be created.
+    SchemaRegistered {
+        /// The name of the schema.
+        schema: SchemaName,
+    },
+    /// The server has begun shutting down. The connection will be closed once
+    /// outstanding requests have completed or the grace period has elapsed.
+    ShuttingDown {
+        /// The maximum amount of time the server will wait for outstanding
+        /// work to complete before closing all connections.
+        grace_period: Duration,
+    },
+    /// Usage of a resource is approaching or has exceeded its quota.
+    QuotaWarning {
+        /// The resource the quota applies to.
+        resource: String,
+        /// The current usage of the resource.
+        usage: u64,
+        /// The quota of the resource.
+        limit: u64,
+    },
+}
+
+impl Api for ServerEvent {
+    type Error = crate::Error;
+    type Response = Self;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "ServerEvent")
+    }
+}
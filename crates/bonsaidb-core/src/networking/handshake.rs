@@ -0,0 +1,181 @@
+use std::ops::{BitAnd, BitOr, BitOrAssign};
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::{Api, ApiName};
+use crate::networking::Compression;
+
+/// The revision of the BonsaiDb network APIs implemented by this build.
+///
+/// [`CURRENT_PROTOCOL_VERSION`](crate::networking::CURRENT_PROTOCOL_VERSION)
+/// identifies the format of the messages exchanged over a connection, and
+/// peers using different versions cannot communicate. Peers that share a
+/// protocol version but implement different revisions can communicate, and
+/// use [`Capabilities`] to discover which APIs the other peer supports.
+pub const CURRENT_PROTOCOL_REVISION: u32 = 1;
+
+/// A set of optional protocol features supported by a client or server.
+///
+/// Capabilities not known to this build are preserved when received from a
+/// peer, allowing newer peers to be detected.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Capabilities(u64);
+
+impl Capabilities {
+    /// Several requests can be executed in a single round trip using
+    /// [`ExecuteBatch`](crate::networking::ExecuteBatch).
+    pub const BATCHES: Self = Self(1 << 0);
+    /// The documents changed in a database can be watched using
+    /// [`WatchDocumentChanges`](crate::networking::WatchDocumentChanges).
+    pub const DOCUMENT_CHANGES: Self = Self(1 << 1);
+    /// Payloads can be compressed using the algorithm negotiated by
+    /// [`Handshake`].
+    pub const COMPRESSION: Self = Self(1 << 2);
+    /// Notifications about the state of the server are delivered using
+    /// [`ServerEvent`](crate::networking::ServerEvent).
+    pub const SERVER_EVENTS: Self = Self(1 << 3);
+    /// Transactions can be sent in chunks using
+    /// [`BeginTransaction`](crate::networking::BeginTransaction).
+    pub const STREAMED_TRANSACTIONS: Self = Self(1 << 4);
+
+    /// Returns an empty set of capabilities.
+    #[must_use]
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Returns the capabilities implemented by this build.
+    #[must_use]
+    pub const fn current() -> Self {
+        Self::BATCHES
+            .union(Self::DOCUMENT_CHANGES)
+            .union(Self::COMPRESSION)
+            .union(Self::SERVER_EVENTS)
+            .union(Self::STREAMED_TRANSACTIONS)
+    }
+
+    /// Returns the capabilities represented by `bits`.
+    #[must_use]
+    pub const fn from_bits(bits: u64) -> Self {
+        Self(bits)
+    }
+
+    /// Returns the raw bits of this set.
+    #[must_use]
+    pub const fn bits(self) -> u64 {
+        self.0
+    }
+
+    /// Returns true if no capabilities are in this set.
+    #[must_use]
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns true if every capability in `other` is in this set.
+    #[must_use]
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns the capabilities in either this set or `other`.
+    #[must_use]
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    /// Returns the capabilities in both this set and `other`.
+    #[must_use]
+    pub const fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+}
+
+impl BitOr for Capabilities {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        self.union(rhs)
+    }
+}
+
+impl BitOrAssign for Capabilities {
+    fn bitor_assign(&mut self, rhs: Self) {
+        *self = self.union(rhs);
+    }
+}
+
+impl BitAnd for Capabilities {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        self.intersection(rhs)
+    }
+}
+
+/// Exchanges protocol information with the server. Clients send this request
+/// when they connect, before any other request. The server responds with its
+/// own revision and capabilities, and the compression algorithm it will use
+/// for the remainder of the connection.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct Handshake {
+    /// The client's [`CURRENT_PROTOCOL_REVISION`].
+    pub revision: u32,
+    /// The capabilities supported by the client.
+    pub capabilities: Capabilities,
+    /// The compression algorithms the client supports, in order of
+    /// preference.
+    pub compression: Vec<Compression>,
+}
+
+impl Handshake {
+    /// Returns a handshake describing this build, offering `compression`.
+    #[must_use]
+    pub fn new(compression: Vec<Compression>) -> Self {
+        Self {
+            revision: CURRENT_PROTOCOL_REVISION,
+            capabilities: Capabilities::current(),
+            compression,
+        }
+    }
+}
+
+impl Api for Handshake {
+    type Error = crate::Error;
+    type Response = HandshakeResponse;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "Handshake")
+    }
+
+    fn idempotent() -> bool {
+        true
+    }
+}
+
+/// The server's response to a [`Handshake`].
+#[derive(Clone, Deserialize, Serialize, Debug, Eq, PartialEq)]
+pub struct HandshakeResponse {
+    /// The server's [`CURRENT_PROTOCOL_REVISION`].
+    pub revision: u32,
+    /// The capabilities supported by the server.
+    pub capabilities: Capabilities,
+    /// The algorithm the server will use to compress its responses. Clients
+    /// may use the same algorithm to compress their requests. If `None`,
+    /// payloads are not compressed.
+    pub compression: Option<Compression>,
+}
+
+impl HandshakeResponse {
+    /// Returns the response describing a server that does not support
+    /// [`Handshake`]. These servers predate capability negotiation.
+    #[must_use]
+    pub const fn legacy() -> Self {
+        Self {
+            revision: 0,
+            capabilities: Capabilities::empty(),
+            compression: None,
+        }
+    }
+}
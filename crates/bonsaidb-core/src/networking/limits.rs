@@ -0,0 +1,175 @@
+use std::time::Duration;
+
+use crate::transaction::{Command, Operation, Transaction};
+
+/// Limits on the size of the data exchanged between clients and servers.
+///
+/// Exceeding a limit results in [`Error::DocumentTooLarge`],
+/// [`Error::TransactionTooLarge`], or [`Error::ResponseTooLarge`] rather than
+/// a transport failure.
+///
+/// [`Error::DocumentTooLarge`]: crate::Error::DocumentTooLarge
+/// [`Error::TransactionTooLarge`]: crate::Error::TransactionTooLarge
+/// [`Error::ResponseTooLarge`]: crate::Error::ResponseTooLarge
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[must_use]
+pub struct PayloadLimits {
+    /// The maximum size of a single document's contents, in bytes. Default
+    /// value is 16MB.
+    pub max_document_size: usize,
+    /// The maximum combined size of the documents in a transaction, in bytes.
+    /// Default value is 64MB.
+    pub max_transaction_size: usize,
+    /// The maximum size of a response, in bytes. Default value is 64MB.
+    pub max_response_size: usize,
+    /// The combined size of the documents in a transaction, in bytes, above
+    /// which a client sends the transaction to the server in chunks of at most
+    /// this size. The chunks are applied atomically once the last chunk has
+    /// been received. Default value is 4MB.
+    pub transaction_chunk_size: usize,
+    /// The maximum number of chunked transactions each session of a client may
+    /// have open at once. Beginning another transaction returns
+    /// [`Error::TooManyOpenTransactions`](crate::Error::TooManyOpenTransactions).
+    /// Only enforced by servers. Default value is 4.
+    pub max_open_transactions: usize,
+    /// The maximum combined size of the documents a client has sent in chunked
+    /// transactions that haven't been committed, across all of its sessions,
+    /// in bytes. Only enforced by servers. Default value is 128MB.
+    pub max_open_transactions_size: usize,
+    /// How long a chunked transaction may go without receiving operations
+    /// before it is discarded. Only enforced by servers. Default value is 5
+    /// minutes.
+    pub open_transaction_timeout: Duration,
+}
+
+impl Default for PayloadLimits {
+    fn default() -> Self {
+        Self {
+            max_document_size: 16 * 1024 * 1024,
+            max_transaction_size: 64 * 1024 * 1024,
+            max_response_size: 64 * 1024 * 1024,
+            transaction_chunk_size: 4 * 1024 * 1024,
+            max_open_transactions: 4,
+            max_open_transactions_size: 128 * 1024 * 1024,
+            open_transaction_timeout: Duration::from_secs(5 * 60),
+        }
+    }
+}
+
+impl PayloadLimits {
+    /// Returns limits that allow payloads of any size.
+    pub const fn unlimited() -> Self {
+        Self {
+            max_document_size: usize::MAX,
+            max_transaction_size: usize::MAX,
+            max_response_size: usize::MAX,
+            transaction_chunk_size: usize::MAX,
+            max_open_transactions: usize::MAX,
+            max_open_transactions_size: usize::MAX,
+            open_transaction_timeout: Duration::MAX,
+        }
+    }
+
+    /// Sets [`Self::max_document_size`](Self#structfield.max_document_size) to `bytes` and returns self.
+    pub const fn max_document_size(mut self, bytes: usize) -> Self {
+        self.max_document_size = bytes;
+        self
+    }
+
+    /// Sets [`Self::max_transaction_size`](Self#structfield.max_transaction_size) to `bytes` and returns self.
+    pub const fn max_transaction_size(mut self, bytes: usize) -> Self {
+        self.max_transaction_size = bytes;
+        self
+    }
+
+    /// Sets [`Self::max_response_size`](Self#structfield.max_response_size) to `bytes` and returns self.
+    pub const fn max_response_size(mut self, bytes: usize) -> Self {
+        self.max_response_size = bytes;
+        self
+    }
+
+    /// Sets [`Self::transaction_chunk_size`](Self#structfield.transaction_chunk_size) to `bytes` and returns self.
+    pub const fn transaction_chunk_size(mut self, bytes: usize) -> Self {
+        self.transaction_chunk_size = bytes;
+        self
+    }
+
+    /// Sets [`Self::max_open_transactions`](Self#structfield.max_open_transactions) to `transactions` and returns self.
+    pub const fn max_open_transactions(mut self, transactions: usize) -> Self {
+        self.max_open_transactions = transactions;
+        self
+    }
+
+    /// Sets [`Self::max_open_transactions_size`](Self#structfield.max_open_transactions_size) to `bytes` and returns self.
+    pub const fn max_open_transactions_size(mut self, bytes: usize) -> Self {
+        self.max_open_transactions_size = bytes;
+        self
+    }
+
+    /// Sets [`Self::open_transaction_timeout`](Self#structfield.open_transaction_timeout) to `timeout` and returns self.
+    pub const fn open_transaction_timeout(mut self, timeout: Duration) -> Self {
+        self.open_transaction_timeout = timeout;
+        self
+    }
+
+    /// Returns the size of the document contents in `operation`, which is the
+    /// size counted against these limits.
+    #[must_use]
+    pub fn operation_size(operation: &Operation) -> usize {
+        match &operation.command {
+            Command::Insert { contents, .. }
+            | Command::Update { contents, .. }
+            | Command::Overwrite { contents, .. } => contents.len(),
+            Command::Delete { .. } | Command::Check { .. } => 0,
+        }
+    }
+
+    /// Checks the size of each document in `transaction`, and the combined
+    /// size of all of its documents.
+    pub fn check_transaction(&self, transaction: &Transaction) -> Result<(), crate::Error> {
+        self.check_operations(&transaction.operations, 0)
+            .map(|_| ())
+    }
+
+    /// Checks the size of each document in `operations`, and the combined size
+    /// of all of their documents added to `existing_size`, the size of the
+    /// documents already in the same transaction. Returns the combined size.
+    pub fn check_operations(
+        &self,
+        operations: &[Operation],
+        existing_size: usize,
+    ) -> Result<usize, crate::Error> {
+        let mut total = existing_size;
+        for operation in operations {
+            let size = Self::operation_size(operation);
+            if size > self.max_document_size {
+                return Err(crate::Error::DocumentTooLarge {
+                    size,
+                    limit: self.max_document_size,
+                });
+            }
+            total = total.saturating_add(size);
+        }
+
+        if total > self.max_transaction_size {
+            return Err(crate::Error::TransactionTooLarge {
+                size: total,
+                limit: self.max_transaction_size,
+            });
+        }
+
+        Ok(total)
+    }
+
+    /// Checks that a response of `size` bytes is allowed.
+    pub const fn check_response(&self, size: usize) -> Result<(), crate::Error> {
+        if size > self.max_response_size {
+            Err(crate::Error::ResponseTooLarge {
+                size,
+                limit: self.max_response_size,
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
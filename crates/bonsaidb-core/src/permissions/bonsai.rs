@@ -1,6 +1,9 @@
-use actionable::{Action, Identifier, ResourceName};
+use std::marker::PhantomData;
+
+use actionable::{Action, ActionNameList, Identifier, ResourceName, Statement};
 use serde::{Deserialize, Serialize};
 
+use crate::api::QueryName;
 use crate::connection::AuthenticationMethod;
 use crate::document::{DocumentId, KeyId};
 use crate::schema::{CollectionName, ViewName};
@@ -48,6 +51,14 @@ pub fn view_resource_name<'a>(database: &'a str, view: &'a ViewName) -> Resource
         .and(view.name.as_ref())
 }
 
+/// Creates a resource name for the named query `query` within `database`.
+#[must_use]
+pub fn named_query_resource_name<'a>(database: &'a str, query: &QueryName) -> ResourceName<'a> {
+    database_resource_name(database)
+        .and("query")
+        .and(query.to_string())
+}
+
 /// Creates a resource name for `PubSub` `topic` within `database`.
 #[must_use]
 pub fn pubsub_topic_resource_name<'a>(database: &'a str, topic: &'a [u8]) -> ResourceName<'a> {
@@ -60,6 +71,18 @@ pub fn kv_resource_name(database: &str) -> ResourceName<'_> {
     database_resource_name(database).and("keyvalue")
 }
 
+/// Creates a resource name for `namespace` within the key-value store of
+/// `database`. Keys stored without a namespace use an empty namespace name.
+///
+/// Permissions granted for this resource apply to every key in the namespace.
+#[must_use]
+pub fn keyvalue_namespace_resource_name<'a>(
+    database: &'a str,
+    namespace: Option<&'a str>,
+) -> ResourceName<'a> {
+    kv_resource_name(database).and(namespace.unwrap_or(""))
+}
+
 /// Creates a resource name for `key` within `namespace` within the key-value store of `database`.
 #[must_use]
 pub fn keyvalue_key_resource_name<'a>(
@@ -67,9 +90,25 @@ pub fn keyvalue_key_resource_name<'a>(
     namespace: Option<&'a str>,
     key: &'a str,
 ) -> ResourceName<'a> {
-    kv_resource_name(database)
-        .and(namespace.unwrap_or(""))
-        .and(key)
+    keyvalue_namespace_resource_name(database, namespace).and(key)
+}
+
+/// Creates a resource name for every key beginning with `prefix` within
+/// `namespace` within the key-value store of `database`.
+///
+/// The final segment of the resource name is `prefix` followed by `*`. When an
+/// operation on a key is checked, permissions granted for any of the key's
+/// prefixes are considered in addition to those granted for
+/// [`keyvalue_key_resource_name()`]. Because of this, a key ending in `*` can't
+/// be granted access to individually without also granting access to the keys
+/// it is a prefix of.
+#[must_use]
+pub fn keyvalue_key_prefix_resource_name<'a>(
+    database: &'a str,
+    namespace: Option<&'a str>,
+    prefix: &str,
+) -> ResourceName<'a> {
+    keyvalue_namespace_resource_name(database, namespace).and(format!("{prefix}*"))
 }
 
 /// Creates a resource name for encryption key `key_id`.
@@ -91,6 +130,14 @@ pub fn user_resource_name<'a>(user_id: u64) -> ResourceName<'a> {
     bonsaidb_resource_name().and("user").and(user_id)
 }
 
+/// Creates a resource name for `permission_group_id`.
+#[must_use]
+pub fn permission_group_resource_name<'a>(permission_group_id: u64) -> ResourceName<'a> {
+    bonsaidb_resource_name()
+        .and("permission-group")
+        .and(permission_group_id)
+}
+
 /// Creates a resource name for `role_id`.
 #[must_use]
 pub fn role_resource_name<'a>(role_id: u64) -> ResourceName<'a> {
@@ -134,6 +181,19 @@ pub enum ServerAction {
     CreateUser,
     /// Permits [`StorageConnection::delete_user`](crate::connection::StorageConnection::delete_user).
     DeleteUser,
+    /// Permits [`StorageConnection::list_users`](crate::connection::StorageConnection::list_users).
+    ListUsers,
+    /// Permits [`StorageConnection::user_by_name`](crate::connection::StorageConnection::user_by_name).
+    ViewUser,
+    /// Permits [`StorageConnection::disable_user`](crate::connection::StorageConnection::disable_user).
+    DisableUser,
+    /// Permits [`StorageConnection::enable_user`](crate::connection::StorageConnection::enable_user).
+    EnableUser,
+    /// Permits [`StorageConnection::unlock_user`](crate::connection::StorageConnection::unlock_user).
+    UnlockUser,
+    /// Permits [`StorageConnection::effective_permissions`](crate::connection::StorageConnection::effective_permissions)
+    /// and [`StorageConnection::check_user_permission`](crate::connection::StorageConnection::check_user_permission).
+    ViewEffectivePermissions,
     /// Permits [`StorageConnection::set_user_password`](crate::connection::StorageConnection::set_user_password).
     SetPassword,
     /// Permits the ability to log in with a password.
@@ -146,6 +206,31 @@ pub enum ServerAction {
     /// Permits .
     /// Permits [`StorageConnection::add_role_to_user`](crate::connection::StorageConnection::add_role_to_user) and [`StorageConnection::remove_role_from_user`](crate::connection::StorageConnection::remove_role_from_user).
     ModifyUserRoles,
+    /// Permits [`StorageConnection::create_permission_group`](crate::connection::StorageConnection::create_permission_group).
+    CreatePermissionGroup,
+    /// Permits [`StorageConnection::list_permission_groups`](crate::connection::StorageConnection::list_permission_groups).
+    ListPermissionGroups,
+    /// Permits [`StorageConnection::set_permission_group_statements`](crate::connection::StorageConnection::set_permission_group_statements) and [`StorageConnection::update_permission_group_statements`](crate::connection::StorageConnection::update_permission_group_statements).
+    ModifyPermissionGroup,
+    /// Permits [`StorageConnection::delete_permission_group`](crate::connection::StorageConnection::delete_permission_group).
+    DeletePermissionGroup,
+    /// Permits [`StorageConnection::create_role`](crate::connection::StorageConnection::create_role).
+    CreateRole,
+    /// Permits [`StorageConnection::list_roles`](crate::connection::StorageConnection::list_roles).
+    ListRoles,
+    /// Permits [`StorageConnection::add_permission_group_to_role`](crate::connection::StorageConnection::add_permission_group_to_role), [`StorageConnection::remove_permission_group_from_role`](crate::connection::StorageConnection::remove_permission_group_from_role), and [`StorageConnection::update_role_permission_groups`](crate::connection::StorageConnection::update_role_permission_groups).
+    ModifyRolePermissionGroups,
+    /// Permits [`StorageConnection::delete_role`](crate::connection::StorageConnection::delete_role).
+    DeleteRole,
+    /// Permits [`StorageConnection::list_sessions`](crate::connection::StorageConnection::list_sessions).
+    ListSessions,
+    /// Permits [`StorageConnection::revoke_session`](crate::connection::StorageConnection::revoke_session)
+    /// for sessions other than the current session.
+    RevokeSession,
+    /// Permits viewing the server's background tasks and the resources used
+    /// by each database and user, such as through the server's admin
+    /// dashboard.
+    ViewServerStatus,
 }
 
 /// Actions that operate on a specific database.
@@ -163,6 +248,8 @@ pub enum DatabaseAction {
     PubSub(PubSubAction),
     /// Actions that operate on the key-value store.
     KeyValue(KeyValueAction),
+    /// Actions that operate on named queries.
+    NamedQuery(NamedQueryAction),
 }
 
 /// Actions that operate on a document.
@@ -273,10 +360,23 @@ pub enum PubSubAction {
 pub enum KeyValueAction {
     /// Allows executing a key-value store operation with
     /// [`KeyValue::execute_key_operation()`](crate::keyvalue::KeyValue::execute_key_operation).
-    /// See [`keyvalue_key_resource_name()`] for the format of key resource names.
+    /// See [`keyvalue_key_resource_name()`] and
+    /// [`keyvalue_key_prefix_resource_name()`] for the format of key resource
+    /// names. Operations on an entire namespace are checked against
+    /// [`keyvalue_namespace_resource_name()`], and listing namespaces is
+    /// checked against [`kv_resource_name()`].
     ExecuteOperation,
 }
 
+/// Actions that operate on named queries.
+#[derive(Action, Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum NamedQueryAction {
+    /// Allows executing a [`NamedQuery`](crate::api::NamedQuery) registered
+    /// with the server. See [`named_query_resource_name()`] for the format of
+    /// named query resource names.
+    Execute,
+}
+
 /// Actions that use encryption keys.
 #[derive(Action, Serialize, Deserialize, Clone, Copy, Debug)]
 pub enum EncryptionKeyAction {
@@ -285,3 +385,507 @@ pub enum EncryptionKeyAction {
     /// Uses a key to decrypt data.
     Decrypt,
 }
+
+macro_rules! impl_into_bonsai_action {
+    ($($action:ident => $variant:ident),+ $(,)?) => {
+        $(
+            impl From<$action> for DatabaseAction {
+                fn from(action: $action) -> Self {
+                    Self::$variant(action)
+                }
+            }
+
+            impl From<$action> for BonsaiAction {
+                fn from(action: $action) -> Self {
+                    Self::Database(DatabaseAction::$variant(action))
+                }
+            }
+        )+
+    };
+}
+
+impl_into_bonsai_action!(
+    DocumentAction => Document,
+    ViewAction => View,
+    TransactionAction => Transaction,
+    PubSubAction => PubSub,
+    KeyValueAction => KeyValue,
+    NamedQueryAction => NamedQuery,
+);
+
+impl From<ServerAction> for BonsaiAction {
+    fn from(action: ServerAction) -> Self {
+        Self::Server(action)
+    }
+}
+
+impl From<DatabaseAction> for BonsaiAction {
+    fn from(action: DatabaseAction) -> Self {
+        Self::Database(action)
+    }
+}
+
+/// A BonsaiDb resource that [`Statement`]s can be built for.
+///
+/// Each resource type only accepts the actions that are checked against it or
+/// against the resources it contains. This allows building statements without
+/// assembling resource names or action names by hand:
+///
+/// ```rust
+/// use bonsaidb_core::permissions::bonsai::{
+///     BonsaiResource, DatabaseResource, DocumentAction, KeyValueAction, ViewAction,
+/// };
+/// use bonsaidb_core::permissions::Statement;
+/// use bonsaidb_core::schema::CollectionName;
+///
+/// let app = DatabaseResource::named("app");
+/// let posts = CollectionName::new("example", "posts");
+/// let statements: Vec<Statement> = vec![
+///     app.collection(&posts)
+///         .allowing(DocumentAction::Get)
+///         .allowing(ViewAction::Query)
+///         .into(),
+///     app.key_value_namespace(Some("cache"))
+///         .allowing(KeyValueAction::ExecuteOperation)
+///         .into(),
+/// ];
+/// ```
+pub trait BonsaiResource: Sized {
+    /// Returns the resource name permissions are granted against.
+    fn resource_name(&self) -> ResourceName<'static>;
+
+    /// Returns a statement builder for this resource allowing `action`.
+    fn allowing<A: ActionFor<Self>>(self, action: A) -> StatementBuilder<Self> {
+        StatementBuilder::new(self).allowing(action)
+    }
+
+    /// Returns a statement builder for this resource allowing all actions.
+    fn allowing_all(self) -> StatementBuilder<Self> {
+        StatementBuilder::new(self).allowing_all()
+    }
+}
+
+/// An action that can be granted for resources of type `R`.
+pub trait ActionFor<R: BonsaiResource>: Into<BonsaiAction> {}
+
+/// Builds a [`Statement`] for one or more resources of type `R`.
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct StatementBuilder<R> {
+    resources: Vec<ResourceName<'static>>,
+    actions: Option<Vec<BonsaiAction>>,
+    _resource: PhantomData<R>,
+}
+
+impl<R: BonsaiResource> StatementBuilder<R> {
+    /// Returns a builder for `resource` that allows no actions.
+    pub fn new(resource: R) -> Self {
+        Self {
+            resources: vec![resource.resource_name()],
+            actions: Some(Vec::new()),
+            _resource: PhantomData,
+        }
+    }
+
+    /// Adds `resource` to the resources this statement applies to.
+    pub fn and(mut self, resource: R) -> Self {
+        self.resources.push(resource.resource_name());
+        self
+    }
+
+    /// Allows `action`.
+    pub fn allowing<A: ActionFor<R>>(mut self, action: A) -> Self {
+        if let Some(actions) = &mut self.actions {
+            actions.push(action.into());
+        }
+        self
+    }
+
+    /// Allows all actions.
+    pub fn allowing_all(mut self) -> Self {
+        self.actions = None;
+        self
+    }
+
+    /// Returns the built statement.
+    pub fn build(self) -> Statement {
+        let mut statement = Statement::for_resources(self.resources);
+        match self.actions {
+            Some(actions) => {
+                for action in &actions {
+                    statement = statement.allowing(action);
+                }
+            }
+            None => statement = statement.allowing_all(),
+        }
+        statement
+    }
+}
+
+impl<R: BonsaiResource> From<StatementBuilder<R>> for Statement {
+    fn from(builder: StatementBuilder<R>) -> Self {
+        builder.build()
+    }
+}
+
+/// The root BonsaiDb resource, which contains every other resource. See
+/// [`bonsaidb_resource_name()`].
+#[derive(Debug, Clone, Copy)]
+pub struct ServerResource;
+
+impl BonsaiResource for ServerResource {
+    fn resource_name(&self) -> ResourceName<'static> {
+        bonsaidb_resource_name()
+    }
+}
+
+impl ActionFor<ServerResource> for BonsaiAction {}
+impl ActionFor<ServerResource> for ServerAction {}
+impl ActionFor<ServerResource> for DatabaseAction {}
+impl ActionFor<ServerResource> for DocumentAction {}
+impl ActionFor<ServerResource> for ViewAction {}
+impl ActionFor<ServerResource> for TransactionAction {}
+impl ActionFor<ServerResource> for PubSubAction {}
+impl ActionFor<ServerResource> for KeyValueAction {}
+impl ActionFor<ServerResource> for NamedQueryAction {}
+
+/// A database. See [`database_resource_name()`].
+#[derive(Debug, Clone)]
+pub struct DatabaseResource {
+    name: String,
+}
+
+impl DatabaseResource {
+    /// Returns the database named `name`.
+    pub fn named(name: impl Into<String>) -> Self {
+        Self { name: name.into() }
+    }
+
+    /// Returns `collection` within this database. See
+    /// [`collection_resource_name()`].
+    #[must_use]
+    pub fn collection(&self, collection: &CollectionName) -> CollectionResource {
+        CollectionResource(collection_resource_name(self.name.clone(), collection))
+    }
+
+    /// Returns `view` within this database. See [`view_resource_name()`].
+    #[must_use]
+    pub fn view(&self, view: &ViewName) -> ViewResource {
+        ViewResource(
+            self.resource_name()
+                .and(view.collection.to_string())
+                .and("view")
+                .and(view.name.to_string()),
+        )
+    }
+
+    /// Returns the named query `query` within this database. See
+    /// [`named_query_resource_name()`].
+    #[must_use]
+    pub fn named_query(&self, query: &QueryName) -> NamedQueryResource {
+        NamedQueryResource(self.resource_name().and("query").and(query.to_string()))
+    }
+
+    /// Returns the `PubSub` topic `topic` within this database. See
+    /// [`pubsub_topic_resource_name()`].
+    #[must_use]
+    pub fn pubsub_topic(&self, topic: &[u8]) -> PubSubTopicResource {
+        PubSubTopicResource(self.resource_name().and("pubsub").and(topic.to_vec()))
+    }
+
+    /// Returns the entire key-value store of this database. See
+    /// [`kv_resource_name()`].
+    #[must_use]
+    pub fn key_value(&self) -> KeyValueResource {
+        KeyValueResource(self.resource_name().and("keyvalue"))
+    }
+
+    /// Returns `namespace` within the key-value store of this database. See
+    /// [`keyvalue_namespace_resource_name()`].
+    #[must_use]
+    pub fn key_value_namespace(&self, namespace: Option<&str>) -> KeyValueResource {
+        self.key_value()
+            .and_segment(namespace.unwrap_or("").to_string())
+    }
+
+    /// Returns `key` within `namespace` of the key-value store of this
+    /// database. See [`keyvalue_key_resource_name()`].
+    #[must_use]
+    pub fn key_value_key(&self, namespace: Option<&str>, key: &str) -> KeyValueResource {
+        self.key_value_namespace(namespace)
+            .and_segment(key.to_string())
+    }
+
+    /// Returns every key beginning with `prefix` within `namespace` of the
+    /// key-value store of this database. See
+    /// [`keyvalue_key_prefix_resource_name()`].
+    #[must_use]
+    pub fn key_value_prefix(&self, namespace: Option<&str>, prefix: &str) -> KeyValueResource {
+        self.key_value_namespace(namespace)
+            .and_segment(format!("{prefix}*"))
+    }
+}
+
+impl BonsaiResource for DatabaseResource {
+    fn resource_name(&self) -> ResourceName<'static> {
+        database_resource_name(self.name.clone())
+    }
+}
+
+impl ActionFor<DatabaseResource> for DatabaseAction {}
+impl ActionFor<DatabaseResource> for DocumentAction {}
+impl ActionFor<DatabaseResource> for ViewAction {}
+impl ActionFor<DatabaseResource> for TransactionAction {}
+impl ActionFor<DatabaseResource> for PubSubAction {}
+impl ActionFor<DatabaseResource> for KeyValueAction {}
+impl ActionFor<DatabaseResource> for NamedQueryAction {}
+
+/// A collection within a database. Permissions granted for a collection also
+/// apply to its documents and views. Returned from
+/// [`DatabaseResource::collection()`].
+#[derive(Debug, Clone)]
+pub struct CollectionResource(ResourceName<'static>);
+
+impl BonsaiResource for CollectionResource {
+    fn resource_name(&self) -> ResourceName<'static> {
+        self.0.clone()
+    }
+}
+
+impl ActionFor<CollectionResource> for DocumentAction {}
+impl ActionFor<CollectionResource> for ViewAction {}
+
+/// A view within a database. Returned from [`DatabaseResource::view()`].
+#[derive(Debug, Clone)]
+pub struct ViewResource(ResourceName<'static>);
+
+impl BonsaiResource for ViewResource {
+    fn resource_name(&self) -> ResourceName<'static> {
+        self.0.clone()
+    }
+}
+
+impl ActionFor<ViewResource> for ViewAction {}
+
+/// A named query within a database. Returned from
+/// [`DatabaseResource::named_query()`].
+#[derive(Debug, Clone)]
+pub struct NamedQueryResource(ResourceName<'static>);
+
+impl BonsaiResource for NamedQueryResource {
+    fn resource_name(&self) -> ResourceName<'static> {
+        self.0.clone()
+    }
+}
+
+impl ActionFor<NamedQueryResource> for NamedQueryAction {}
+
+/// A `PubSub` topic within a database. Returned from
+/// [`DatabaseResource::pubsub_topic()`].
+#[derive(Debug, Clone)]
+pub struct PubSubTopicResource(ResourceName<'static>);
+
+impl BonsaiResource for PubSubTopicResource {
+    fn resource_name(&self) -> ResourceName<'static> {
+        self.0.clone()
+    }
+}
+
+impl ActionFor<PubSubTopicResource> for PubSubAction {}
+
+/// A portion of the key-value store within a database: the entire store, a
+/// namespace, a key, or a key prefix. Returned from
+/// [`DatabaseResource::key_value()`] and related functions.
+#[derive(Debug, Clone)]
+pub struct KeyValueResource(ResourceName<'static>);
+
+impl BonsaiResource for KeyValueResource {
+    fn resource_name(&self) -> ResourceName<'static> {
+        self.0.clone()
+    }
+}
+
+impl KeyValueResource {
+    fn and_segment(self, segment: String) -> Self {
+        Self(self.0.and(segment))
+    }
+}
+
+impl ActionFor<KeyValueResource> for KeyValueAction {}
+
+/// Checks that each of `statements` is well-formed.
+///
+/// A statement is invalid if it has no resources, if any of its resource names
+/// are empty, if it allows an empty list of actions, or if it allows an action
+/// beginning with `Server` or `Database` that isn't a [`BonsaiAction`].
+/// Actions defined outside of BonsaiDb are not checked.
+pub fn validate_statements(statements: &[Statement]) -> Result<(), crate::Error> {
+    for (index, statement) in statements.iter().enumerate() {
+        validate_statement(statement)
+            .map_err(|reason| crate::Error::InvalidPermissionStatement { index, reason })?;
+    }
+    Ok(())
+}
+
+fn validate_statement(statement: &Statement) -> Result<(), String> {
+    if statement.resources.is_empty() {
+        return Err(String::from("no resources specified"));
+    }
+    if statement
+        .resources
+        .iter()
+        .any(|resource| resource.as_ref().is_empty())
+    {
+        return Err(String::from("empty resource name"));
+    }
+
+    match &statement.actions {
+        Some(ActionNameList::All) => Ok(()),
+        Some(ActionNameList::List(actions)) if !actions.is_empty() => {
+            for action in actions {
+                let segments = action.0.iter().map(AsRef::as_ref).collect::<Vec<&str>>();
+                if !is_known_action(&segments) {
+                    return Err(format!("unknown action {}", segments.join(".")));
+                }
+            }
+            Ok(())
+        }
+        _ => Err(String::from("no actions specified")),
+    }
+}
+
+fn is_known_action(segments: &[&str]) -> bool {
+    const SERVER_ACTIONS: &[&str] = &[
+        "Connect",
+        "ListAvailableSchemas",
+        "ListDatabases",
+        "CreateDatabase",
+        "DeleteDatabase",
+        "CreateUser",
+        "DeleteUser",
+        "ListUsers",
+        "ViewUser",
+        "DisableUser",
+        "EnableUser",
+        "UnlockUser",
+        "ViewEffectivePermissions",
+        "SetPassword",
+        "Authenticate",
+        "AssumeIdentity",
+        "ModifyUserPermissionGroups",
+        "ModifyUserRoles",
+        "CreatePermissionGroup",
+        "ListPermissionGroups",
+        "ModifyPermissionGroup",
+        "DeletePermissionGroup",
+        "CreateRole",
+        "ListRoles",
+        "ModifyRolePermissionGroups",
+        "DeleteRole",
+        "ListSessions",
+        "RevokeSession",
+        "ViewServerStatus",
+    ];
+    const DOCUMENT_ACTIONS: &[&str] = &[
+        "Get",
+        "List",
+        "ListHeaders",
+        "Count",
+        "Insert",
+        "Update",
+        "Overwrite",
+        "Delete",
+    ];
+
+    match segments {
+        ["Server" | "Database"] => true,
+        ["Server", "Authenticate", "Token" | "PasswordHash"] => true,
+        ["Server", action] => SERVER_ACTIONS.contains(action),
+        ["Database", "Compact" | "Document" | "View" | "Transaction" | "PubSub" | "KeyValue" | "NamedQuery"] => {
+            true
+        }
+        ["Database", "Document", action] => DOCUMENT_ACTIONS.contains(action),
+        ["Database", "View", "Query" | "Reduce" | "DeleteDocs"]
+        | ["Database", "Transaction", "ListExecuted" | "GetLastId"]
+        | ["Database", "PubSub", "CreateSuscriber" | "Publish" | "SubscribeTo" | "UnsubscribeFrom"]
+        | ["Database", "KeyValue", "ExecuteOperation"]
+        | ["Database", "NamedQuery", "Execute"] => true,
+        ["Server" | "Database", ..] => false,
+        // Actions defined outside of BonsaiDb.
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::permissions::ActionName;
+
+    #[test]
+    fn builder_resource_names() {
+        let database = DatabaseResource::named("db");
+        let collection = CollectionName::new("khonsulabs", "collection");
+        let view = ViewName {
+            collection: collection.clone(),
+            name: crate::schema::Name::new("view"),
+        };
+        assert_eq!(database.resource_name(), database_resource_name("db"));
+        assert_eq!(
+            database.collection(&collection).resource_name(),
+            collection_resource_name("db", &collection)
+        );
+        assert_eq!(
+            database.view(&view).resource_name(),
+            view_resource_name("db", &view)
+        );
+        assert_eq!(
+            database.pubsub_topic(b"topic").resource_name(),
+            pubsub_topic_resource_name("db", b"topic")
+        );
+        assert_eq!(database.key_value().resource_name(), kv_resource_name("db"));
+        assert_eq!(
+            database.key_value_namespace(None).resource_name(),
+            keyvalue_namespace_resource_name("db", None)
+        );
+        assert_eq!(
+            database.key_value_key(Some("ns"), "key").resource_name(),
+            keyvalue_key_resource_name("db", Some("ns"), "key")
+        );
+        assert_eq!(
+            database.key_value_prefix(Some("ns"), "ke").resource_name(),
+            keyvalue_key_prefix_resource_name("db", Some("ns"), "ke")
+        );
+    }
+
+    #[test]
+    fn statement_validation() {
+        let database = DatabaseResource::named("db");
+        validate_statements(&[
+            database.clone().allowing(DocumentAction::Get).build(),
+            ServerResource.allowing_all().build(),
+            Statement::for_any().allowing(&BonsaiAction::Server(ServerAction::Authenticate(
+                AuthenticationMethod::Token,
+            ))),
+        ])
+        .unwrap();
+
+        // Allowing nothing is most likely a mistake.
+        assert!(matches!(
+            validate_statements(&[
+                database.clone().allowing_all().build(),
+                StatementBuilder::new(database).build()
+            ]),
+            Err(crate::Error::InvalidPermissionStatement { index: 1, .. })
+        ));
+
+        let mut misspelled = Statement::for_any().allowing(&ServerAction::Connect);
+        misspelled.actions = Some(ActionNameList::List(vec![ActionName(vec![
+            "Server".into(),
+            "Conect".into(),
+        ])]));
+        assert!(matches!(
+            validate_statements(&[misspelled]),
+            Err(crate::Error::InvalidPermissionStatement { index: 0, .. })
+        ));
+    }
+}
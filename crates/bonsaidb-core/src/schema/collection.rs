@@ -1,4 +1,5 @@
 use std::borrow::{Borrow, Cow};
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::marker::PhantomData;
 use std::task::Poll;
@@ -17,7 +18,7 @@ use crate::document::{
     OwnedDocument, OwnedDocuments, Revision,
 };
 use crate::key::{IntoPrefixRange, Key, KeyEncoding};
-use crate::schema::{CollectionName, Schematic};
+use crate::schema::{CollectionName, ProjectedDocument, Projection, Schematic, TimeSeries};
 use crate::transaction::{Operation, OperationResult, Transaction};
 use crate::Error;
 
@@ -130,6 +131,65 @@ use crate::Error;
 ///
 /// [natural-key]: https://en.wikipedia.org/wiki/Natural_key
 ///
+/// ### Assigning ids automatically
+///
+/// When a document is pushed without an id, an id is assigned according to
+/// [`Self::id_assignment()`]. By default, ids are assigned sequentially using
+/// [`Key::next_value()`]. Collections that need ids that can't be guessed can
+/// assign random ids instead, which works best with a large primary key type
+/// like `u128`:
+///
+/// ```rust
+/// use bonsaidb_core::schema::{Collection, IdAssignment};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, Serialize, Deserialize, Default, Collection)]
+/// #[collection(name = "MyCollection", primary_key = u128, id_assignment = IdAssignment::Random)]
+/// # #[collection(core = bonsaidb_core)]
+/// pub struct MyCollection;
+/// ```
+///
+/// ### Skipping revision history
+///
+/// By default, documents are stored in a versioned tree, which allows conflicts
+/// between updates to be detected using each document's revision. Collections
+/// whose documents are only ever overwritten can store their documents in an
+/// unversioned tree instead, which reduces the amount of data written when a
+/// document changes:
+///
+/// ```rust
+/// use bonsaidb_core::schema::Collection;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, Serialize, Deserialize, Default, Collection)]
+/// #[collection(name = "MyCollection", versioned = false)]
+/// # #[collection(core = bonsaidb_core)]
+/// pub struct MyCollection;
+/// ```
+///
+/// ### Merging concurrent updates
+///
+/// Collections whose contents implement [`Crdt`](crate::crdt::Crdt) can merge
+/// concurrent changes instead of returning a conflict. When a document is
+/// updated using an outdated revision, or replicated from another node, the
+/// stored contents are merged with the new contents:
+///
+/// ```rust
+/// use bonsaidb_core::crdt::{Counter, Crdt, CrdtMap};
+/// use bonsaidb_core::schema::Collection;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, Serialize, Deserialize, Default, Collection, Crdt)]
+/// #[collection(name = "MyCollection", crdt)]
+/// # #[collection(core = bonsaidb_core)]
+/// # #[crdt(core = bonsaidb_core)]
+/// pub struct MyCollection {
+///     votes: CrdtMap<String, Counter>,
+/// }
+/// ```
+///
+/// Because the stored contents may differ from the contents that were saved,
+/// documents should be retrieved again to observe the merged result.
 ///
 /// ### Specifying a Collection Encryption Key
 ///
@@ -231,6 +291,95 @@ pub trait Collection: Debug + Send + Sync {
     fn encryption_key() -> Option<KeyId> {
         None
     }
+
+    /// If options are returned, this collection is a time-series collection.
+    /// See [`TimeSeries`] for more information.
+    #[must_use]
+    fn time_series() -> Option<TimeSeries> {
+        None
+    }
+
+    /// Returns how ids are assigned to documents that are pushed without an
+    /// id. Time-series collections ignore this setting and always use
+    /// timestamps as their ids.
+    #[must_use]
+    fn id_assignment() -> IdAssignment {
+        IdAssignment::Sequential
+    }
+
+    /// Returns true if this collection's documents are stored in a versioned
+    /// tree. The default is true.
+    ///
+    /// When false, updating or deleting a document whose revision doesn't
+    /// match the stored revision overwrites or deletes the stored document
+    /// rather than returning [`Error::DocumentConflict`]. Documents still have
+    /// revisions, but they can't be used to detect conflicting changes.
+    ///
+    /// Changing this setting for a collection that already has stored
+    /// documents is not supported.
+    #[must_use]
+    fn versioned() -> bool {
+        true
+    }
+
+    /// Returns true if this collection's documents share the storage of
+    /// identical contents. The default is false.
+    ///
+    /// When true, the contents of each document are stored once per unique
+    /// SHA256 digest along with a count of the documents referencing them.
+    /// Many documents with identical contents only store the contents once,
+    /// which is useful for collections such as file metadata or blob chunks.
+    /// Contents are removed once the last document referencing them is
+    /// deleted, and compacting the collection recounts the references to
+    /// every stored contents.
+    ///
+    /// Changing this setting for a collection that already has stored
+    /// documents is not supported.
+    #[must_use]
+    fn deduplicated() -> bool {
+        false
+    }
+
+    /// Returns the names this collection was previously stored under. The
+    /// default is no names.
+    ///
+    /// When a database is opened, documents stored under any of these names
+    /// are moved into this collection by a background task, which completes
+    /// before the database is returned. Views of the previous collections are
+    /// removed, and this collection's views are rebuilt. Legacy collections
+    /// must have the same encryption key and versioning as this collection,
+    /// and can't be [deduplicated](Self::deduplicated).
+    #[must_use]
+    fn legacy_names() -> Vec<CollectionName> {
+        Vec::new()
+    }
+}
+
+/// How ids are automatically assigned to documents pushed into a
+/// [`Collection`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum IdAssignment {
+    /// Each id is the [next value](Key::next_value) after the largest id
+    /// stored in the collection, or the [first value](Key::first_value) if the
+    /// collection is empty.
+    ///
+    /// Because only the largest stored id is considered, deleting the document
+    /// with the largest id allows its id to be assigned again. Ids of other
+    /// deleted documents are never reassigned. Pushing fails with
+    /// [`NextValueError::WouldWrap`](crate::key::NextValueError::WouldWrap)
+    /// once the primary key type runs out of values.
+    #[default]
+    Sequential,
+    /// Each id is chosen randomly, which prevents ids from being guessed or
+    /// revealing how many documents have been stored. Only primary key types
+    /// with a fixed encoded length are supported, and time series collections
+    /// can't use random ids. Defining an unsupported collection fails with
+    /// [`NextValueError::Unsupported`](crate::key::NextValueError::Unsupported).
+    ///
+    /// If a randomly chosen id is already in use, pushing fails with
+    /// [`Error::DocumentConflict`]. To make this unlikely, use a primary key
+    /// type with at least 128 bits, such as `u128`.
+    Random,
 }
 
 /// A collection that knows how to serialize and deserialize documents to an associated type.
@@ -1419,6 +1568,71 @@ pub trait NamedCollection: Collection + Unpin {
         }
     }
 
+    /// Loads the document named `name`, inserting the contents returned from
+    /// `insert` if no document is found.
+    ///
+    /// If another client inserts a document with the same name before the new
+    /// document is saved, the unique key violation from [`Self::ByNameView`] is
+    /// handled by loading the other client's document instead. If the
+    /// conflicting document can't be loaded, such as when the session isn't
+    /// allowed to read it, the unique key violation is returned. `insert` is
+    /// invoked at most once. If [`Self::ByNameView`] isn't a unique view,
+    /// concurrent calls may insert multiple documents with the same name.
+    fn load_or_insert_with<C: Connection, F: FnOnce() -> Self::Contents + Send>(
+        name: &str,
+        connection: &C,
+        insert: F,
+    ) -> Result<CollectionDocument<Self>, Error>
+    where
+        Self: SerializedCollection + Sized + 'static,
+    {
+        if let Some(existing) = Self::load(name, connection)? {
+            return Ok(existing);
+        }
+
+        match Self::push(insert(), connection) {
+            Ok(document) => Ok(document),
+            Err(err)
+                if err
+                    .error
+                    .is_unique_key_error::<Self::ByNameView, _>(connection) =>
+            {
+                // Another client inserted a document with this name.
+                Self::load(name, connection)?.ok_or(err.error)
+            }
+            Err(err) => Err(err.error),
+        }
+    }
+
+    /// Loads the document named `name`, inserting the contents returned from
+    /// `insert` if no document is found. See [`Self::load_or_insert_with()`]
+    /// for more information.
+    async fn load_or_insert_with_async<C: AsyncConnection, F: FnOnce() -> Self::Contents + Send>(
+        name: &str,
+        connection: &C,
+        insert: F,
+    ) -> Result<CollectionDocument<Self>, Error>
+    where
+        Self: SerializedCollection + Sized + 'static,
+    {
+        if let Some(existing) = Self::load_async(name, connection).await? {
+            return Ok(existing);
+        }
+
+        match Self::push_async(insert(), connection).await {
+            Ok(document) => Ok(document),
+            Err(err)
+                if err
+                    .error
+                    .is_unique_key_error::<Self::ByNameView, _>(connection) =>
+            {
+                // Another client inserted a document with this name.
+                Self::load_async(name, connection).await?.ok_or(err.error)
+            }
+            Err(err) => Err(err.error),
+        }
+    }
+
     /// Deletes a document by its name. Returns true if a document was deleted.
     fn delete_by_name<C: Connection>(name: &str, connection: &C) -> Result<bool, Error>
     where
@@ -1628,6 +1842,73 @@ where
             Self::Key(id) => Ok(Some(id.clone())),
         }
     }
+
+    /// Returns the ids of `references`, in the same order. All names are
+    /// resolved using a single query of [`NamedCollection::ByNameView`]. `None`
+    /// is returned for each name that isn't found.
+    pub fn ids<Col: NamedCollection<PrimaryKey = Id>, Cn: Connection>(
+        references: &[Self],
+        connection: &Cn,
+    ) -> Result<Vec<Option<Col::PrimaryKey>>, Error> {
+        let names = Self::names(references);
+        let mut ids_by_name = HashMap::new();
+        if !names.is_empty() {
+            for mapping in connection
+                .view::<Col::ByNameView>()
+                .with_keys(names)
+                .query()?
+            {
+                ids_by_name.insert(mapping.key, mapping.source.id.deserialize()?);
+            }
+        }
+        Self::resolve_ids(references, &ids_by_name)
+    }
+
+    /// Returns the ids of `references`, in the same order. All names are
+    /// resolved using a single query of [`NamedCollection::ByNameView`]. `None`
+    /// is returned for each name that isn't found.
+    pub async fn ids_async<Col: NamedCollection<PrimaryKey = Id>, Cn: AsyncConnection>(
+        references: &[Self],
+        connection: &Cn,
+    ) -> Result<Vec<Option<Col::PrimaryKey>>, Error> {
+        let names = Self::names(references);
+        let mut ids_by_name = HashMap::new();
+        if !names.is_empty() {
+            for mapping in connection
+                .view::<Col::ByNameView>()
+                .with_keys(names)
+                .query()
+                .await?
+            {
+                ids_by_name.insert(mapping.key, mapping.source.id.deserialize()?);
+            }
+        }
+        Self::resolve_ids(references, &ids_by_name)
+    }
+
+    fn names<'r>(references: &'r [Self]) -> Vec<&'r str> {
+        references
+            .iter()
+            .filter_map(|reference| match reference {
+                Self::Name(name) => Some(name.as_ref()),
+                Self::Id(_) | Self::Key(_) => None,
+            })
+            .collect()
+    }
+
+    fn resolve_ids(
+        references: &[Self],
+        ids_by_name: &HashMap<String, Id>,
+    ) -> Result<Vec<Option<Id>>, Error> {
+        references
+            .iter()
+            .map(|reference| match reference {
+                Self::Name(name) => Ok(ids_by_name.get(name.as_ref()).cloned()),
+                Self::Id(id) => Ok(Some(id.deserialize()?)),
+                Self::Key(id) => Ok(Some(id.clone())),
+            })
+            .collect()
+    }
 }
 
 /// A future that resolves to an entry in a [`NamedCollection`].
@@ -2078,6 +2359,14 @@ where
     pub fn query(self) -> Result<Vec<CollectionDocument<Cl>>, Error> {
         self.0.query().and_then(|docs| docs.collection_documents())
     }
+
+    /// Retrieves the list of documents, with their contents projected by `P`.
+    /// Only the projected contents are transferred from the database.
+    pub fn projected<P: Projection<Collection = Cl>>(
+        self,
+    ) -> Result<Vec<ProjectedDocument<P>>, Error> {
+        self.0.projected::<P>()
+    }
 }
 
 /// Retrieves a list of documents from a collection, when awaited. This
@@ -2161,6 +2450,14 @@ where
     pub async fn headers(self) -> Result<Vec<Header>, Error> {
         self.0.headers().await
     }
+
+    /// Retrieves the list of documents, with their contents projected by `P`.
+    /// Only the projected contents are transferred from the database.
+    pub async fn projected<P: Projection<Collection = Cl>>(
+        self,
+    ) -> Result<Vec<ProjectedDocument<P>>, Error> {
+        self.0.projected::<P>().await
+    }
 }
 
 #[allow(clippy::type_repetition_in_bounds)]
@@ -1,6 +1,8 @@
 mod collection;
 mod names;
+mod projection;
 mod schematic;
+mod time_series;
 /// Types for defining map/reduce-powered `View`s.
 pub mod view;
 use std::fmt::Debug;
@@ -8,19 +10,22 @@ use std::fmt::Debug;
 pub use bonsaidb_macros::{Collection, Schema, View};
 
 pub use self::collection::{
-    AsyncEntry, AsyncList, Collection, DefaultSerialization, InsertError, List, Nameable,
-    NamedCollection, NamedReference, SerializedCollection,
+    AsyncEntry, AsyncList, Collection, DefaultSerialization, IdAssignment, InsertError, List,
+    Nameable, NamedCollection, NamedReference, SerializedCollection,
 };
 pub use self::names::{
     Authority, CollectionName, InvalidNameError, Name, Qualified, QualifiedName, SchemaName,
     ViewName,
 };
+pub use self::projection::{ProjectedDocument, Projection, SerializedProjection};
 pub use self::schematic::Schematic;
+pub use self::time_series::TimeSeries;
 pub use self::view::map::{Map, MappedValue, ViewMappedValue};
 pub use self::view::{
-    CollectionViewSchema, DefaultViewSerialization, ReduceResult, SerializedView, View,
-    ViewMapResult, ViewSchema,
+    CollectionViewSchema, DefaultViewSerialization, ReduceResult, SerializedView, ValueStorage,
+    View, ViewMapResult, ViewMaterialization, ViewSchema,
 };
+use crate::document::KeyId;
 use crate::Error;
 
 /// Defines a group of collections that are stored into a single database.
@@ -75,6 +80,19 @@ pub trait Schema: Send + Sync + Debug + 'static {
     /// Defines the `Collection`s into `schema`.
     fn define_collections(schema: &mut Schematic) -> Result<(), Error>;
 
+    /// If a [`KeyId`] is returned, the key-value store for databases using
+    /// this schema will be encrypted with this key. If `None` is returned,
+    /// the storage's default encryption key will be used, if one is
+    /// configured.
+    ///
+    /// Entries written before an encryption key was configured remain stored
+    /// in plaintext and are returned as-is when read. Local databases can
+    /// encrypt them using `Database::reencrypt()`.
+    #[must_use]
+    fn key_value_encryption_key() -> Option<KeyId> {
+        None
+    }
+
     /// Retrieves the [`Schematic`] for this schema.
     fn schematic() -> Result<Schematic, Error> {
         Schematic::from_schema::<Self>()
@@ -0,0 +1,126 @@
+use std::fmt::Debug;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::document::{CollectionHeader, OwnedDocument};
+use crate::schema::{Collection, Name, SerializedCollection};
+use crate::Error;
+
+/// A subset of the contents of a collection's documents.
+///
+/// When documents are listed or queried with a projection, the database
+/// returns the projected contents of each document instead of the document's
+/// full contents. This allows listing and querying large documents without
+/// transferring the fields that aren't needed.
+///
+/// Projections are added to a schema using
+/// [`Schematic::define_projection()`](crate::schema::Schematic::define_projection)
+/// or the `projections` parameter of the `Collection` derive macro.
+///
+/// ```rust
+/// # bonsaidb_core::__doctest_prelude!();
+/// use bonsaidb_core::schema::Projection;
+///
+/// #[derive(Debug)]
+/// struct Ranks;
+///
+/// impl Projection for Ranks {
+///     type Collection = MyCollection;
+///     type Output = u32;
+///
+///     fn name(&self) -> Name {
+///         Name::new("ranks")
+///     }
+///
+///     fn project(&self, contents: MyCollection) -> Self::Output {
+///         contents.rank
+///     }
+/// }
+/// ```
+pub trait Projection: Send + Sync + Debug + 'static {
+    /// The collection whose documents are projected.
+    type Collection: SerializedCollection;
+    /// The projected contents of a document.
+    type Output: Serialize + DeserializeOwned + Send + Sync;
+
+    /// The name of this projection. The name must be unique within
+    /// [`Self::Collection`].
+    fn name(&self) -> Name;
+
+    /// Returns the projection of a document's `contents`.
+    fn project(
+        &self,
+        contents: <Self::Collection as SerializedCollection>::Contents,
+    ) -> Self::Output;
+}
+
+/// A document whose contents were projected by the [`Projection`] `P`.
+pub struct ProjectedDocument<P: Projection> {
+    /// The header of the document, which contains the id and `Revision`.
+    pub header: CollectionHeader<<P::Collection as Collection>::PrimaryKey>,
+
+    /// The projected contents of the document.
+    pub contents: P::Output,
+}
+
+impl<P> Clone for ProjectedDocument<P>
+where
+    P: Projection,
+    P::Output: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            header: self.header.clone(),
+            contents: self.contents.clone(),
+        }
+    }
+}
+
+impl<P> Debug for ProjectedDocument<P>
+where
+    P: Projection,
+    P::Output: Debug,
+    <P::Collection as Collection>::PrimaryKey: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProjectedDocument")
+            .field("header", &self.header)
+            .field("contents", &self.contents)
+            .finish()
+    }
+}
+
+impl<'a, P> TryFrom<&'a OwnedDocument> for ProjectedDocument<P>
+where
+    P: Projection,
+{
+    type Error = Error;
+
+    fn try_from(value: &'a OwnedDocument) -> Result<Self, Self::Error> {
+        Ok(Self {
+            header: CollectionHeader::try_from(value.header.clone())?,
+            contents: pot::from_slice(&value.contents)?,
+        })
+    }
+}
+
+/// A [`Projection`] that operates on serialized documents.
+pub trait SerializedProjection: Debug + Send + Sync {
+    /// Projects the serialized `contents` of a document, returning the
+    /// serialized projection.
+    fn project(&self, contents: &[u8]) -> Result<Vec<u8>, Error>;
+}
+
+#[derive(Debug)]
+pub(crate) struct ProjectionInstance<P>(pub P);
+
+impl<P> SerializedProjection for ProjectionInstance<P>
+where
+    P: Projection,
+{
+    fn project(&self, contents: &[u8]) -> Result<Vec<u8>, Error> {
+        let contents = P::Collection::deserialize(contents)?;
+        Ok(pot::to_vec(&self.0.project(contents))?)
+    }
+}
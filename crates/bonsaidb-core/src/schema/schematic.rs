@@ -2,15 +2,25 @@ use std::any::TypeId;
 use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::marker::PhantomData;
+use std::time::Duration;
 
 use derive_where::derive_where;
+use sha2::{Digest, Sha256};
 
+use crate::crdt::Crdt;
 use crate::document::{BorrowedDocument, DocumentId, KeyId};
-use crate::key::{ByteCow, Key};
-use crate::schema::collection::Collection;
+use crate::key::{ByteCow, Key, KeyEncoding, NextValueError};
+use crate::keyvalue::Timestamp;
+use crate::schema::collection::{Collection, SerializedCollection};
+use crate::schema::projection::{Projection, ProjectionInstance, SerializedProjection};
+use crate::schema::view::join::{JoinDefinition, JoinedView};
 use crate::schema::view::map::{self, MappedValue};
-use crate::schema::view::{self, Serialized, SerializedView, ViewSchema};
-use crate::schema::{CollectionName, Schema, SchemaName, View, ViewName};
+use crate::schema::view::{
+    self, Serialized, SerializedView, ValueStorage, ViewMaterialization, ViewSchema,
+};
+use crate::schema::{
+    CollectionName, IdAssignment, Name, Schema, SchemaName, TimeSeries, View, ViewName,
+};
 use crate::Error;
 
 /// A collection of defined collections and views.
@@ -21,11 +31,21 @@ pub struct Schematic {
     contained_collections: HashSet<CollectionName>,
     collections_by_type_id: HashMap<TypeId, CollectionName>,
     collection_encryption_keys: HashMap<CollectionName, KeyId>,
+    collection_time_series: HashMap<CollectionName, TimeSeries>,
+    unversioned_collections: HashSet<CollectionName>,
+    deduplicated_collections: HashSet<CollectionName>,
+    collection_legacy_names: HashMap<CollectionName, Vec<CollectionName>>,
+    key_value_encryption_key: Option<KeyId>,
     collection_id_generators: HashMap<CollectionName, Box<dyn IdGenerator>>,
+    collection_mergers: HashMap<CollectionName, Box<dyn ContentsMerger>>,
     views: HashMap<TypeId, Box<dyn view::Serialized>>,
     views_by_name: HashMap<ViewName, TypeId>,
     views_by_collection: HashMap<CollectionName, Vec<TypeId>>,
     eager_views_by_collection: HashMap<CollectionName, Vec<TypeId>>,
+    joins: HashMap<ViewName, (TypeId, TypeId)>,
+    joins_by_type_id: HashMap<TypeId, ViewName>,
+    projections: HashMap<(CollectionName, Name), Box<dyn SerializedProjection>>,
+    projections_by_type_id: HashMap<TypeId, Name>,
 }
 
 impl Schematic {
@@ -36,11 +56,21 @@ impl Schematic {
             contained_collections: HashSet::new(),
             collections_by_type_id: HashMap::new(),
             collection_encryption_keys: HashMap::new(),
+            collection_time_series: HashMap::new(),
+            unversioned_collections: HashSet::new(),
+            deduplicated_collections: HashSet::new(),
+            collection_legacy_names: HashMap::new(),
+            key_value_encryption_key: S::key_value_encryption_key(),
             collection_id_generators: HashMap::new(),
+            collection_mergers: HashMap::new(),
             views: HashMap::new(),
             views_by_name: HashMap::new(),
             views_by_collection: HashMap::new(),
             eager_views_by_collection: HashMap::new(),
+            joins: HashMap::new(),
+            joins_by_type_id: HashMap::new(),
+            projections: HashMap::new(),
+            projections_by_type_id: HashMap::new(),
         };
         S::define_collections(&mut schematic)?;
         Ok(schematic)
@@ -57,13 +87,62 @@ impl Schematic {
             if let Some(key) = C::encryption_key() {
                 self.collection_encryption_keys.insert(name.clone(), key);
             }
-            self.collection_id_generators
-                .insert(name.clone(), Box::<KeyIdGenerator<C>>::default());
+            if C::id_assignment() == IdAssignment::Random
+                && (C::time_series().is_some()
+                    || <C::PrimaryKey as KeyEncoding<'_, C::PrimaryKey>>::LENGTH.is_none())
+            {
+                return Err(Error::DocumentPush(name, NextValueError::Unsupported));
+            }
+            if let Some(time_series) = C::time_series() {
+                self.collection_time_series
+                    .insert(name.clone(), time_series);
+                self.collection_id_generators
+                    .insert(name.clone(), Box::new(TimestampIdGenerator));
+            } else {
+                let generator: Box<dyn IdGenerator> = match C::id_assignment() {
+                    IdAssignment::Sequential => Box::<KeyIdGenerator<C>>::default(),
+                    IdAssignment::Random => Box::<RandomIdGenerator<C>>::default(),
+                };
+                self.collection_id_generators
+                    .insert(name.clone(), generator);
+            }
+            if !C::versioned() {
+                self.unversioned_collections.insert(name.clone());
+            }
+            if C::deduplicated() {
+                self.deduplicated_collections.insert(name.clone());
+            }
+            let legacy_names = C::legacy_names();
+            if !legacy_names.is_empty() {
+                self.collection_legacy_names
+                    .insert(name.clone(), legacy_names);
+            }
             self.contained_collections.insert(name);
             C::define_views(self)
         }
     }
 
+    /// Marks the previously defined collection `C` as storing [`Crdt`]
+    /// contents. Instead of returning a conflict, updates with a stale revision
+    /// and replicated changes are merged with the stored contents.
+    ///
+    /// This is typically called from [`Collection::define_views()`], which is
+    /// what the `crdt` parameter of the `Collection` derive macro does.
+    pub fn define_crdt<C>(&mut self) -> Result<(), Error>
+    where
+        C: SerializedCollection + 'static,
+        C::Contents: Crdt,
+    {
+        let name = C::collection_name();
+        if self.contained_collections.contains(&name) {
+            self.collection_mergers
+                .insert(name, Box::<CrdtMerger<C>>::default());
+            Ok(())
+        } else {
+            Err(Error::CollectionNotFound)
+        }
+    }
+
     /// Adds the view `V`.
     pub fn define_view<V: ViewSchema<View = V> + SerializedView + Clone + 'static>(
         &mut self,
@@ -108,6 +187,38 @@ impl Schematic {
         Ok(())
     }
 
+    /// Adds the joined view `J`. The views joined by `J` don't need to be
+    /// defined yet, but must be defined before the join is queried.
+    pub fn define_joined_view<J: JoinedView>(&mut self, join: J) -> Result<(), Error> {
+        let name = ViewName {
+            collection: <J::Left as View>::Collection::collection_name(),
+            name: join.name(),
+        };
+        if self.joins.contains_key(&name) || self.views_by_name.contains_key(&name) {
+            return Err(Error::ViewAlreadyRegistered(name));
+        }
+
+        self.joins_by_type_id
+            .insert(TypeId::of::<J>(), name.clone());
+        self.joins
+            .insert(name, (TypeId::of::<J::Left>(), TypeId::of::<J::Right>()));
+        Ok(())
+    }
+
+    /// Adds the projection `P`.
+    pub fn define_projection<P: Projection>(&mut self, projection: P) -> Result<(), Error> {
+        let name = projection.name();
+        let key = (P::Collection::collection_name(), name.clone());
+        if self.projections.contains_key(&key) {
+            return Err(Error::ProjectionAlreadyDefined(name));
+        }
+
+        self.projections_by_type_id.insert(TypeId::of::<P>(), name);
+        self.projections
+            .insert(key, Box::new(ProjectionInstance(projection)));
+        Ok(())
+    }
+
     /// Returns `true` if this schema contains the collection `C`.
     #[must_use]
     pub fn contains_collection<C: Collection + 'static>(&self) -> bool {
@@ -134,6 +245,21 @@ impl Schematic {
         generator.next_id(id)
     }
 
+    /// Returns true if the ids assigned to new documents in `collection` only
+    /// depend on the ids already stored. Applying the same changes to copies
+    /// of the collection assigns the same ids to each copy. Random and
+    /// time-based ids are not deterministic.
+    pub fn collection_assigns_deterministic_ids(
+        &self,
+        collection: &CollectionName,
+    ) -> Result<bool, Error> {
+        let generator = self
+            .collection_id_generators
+            .get(collection)
+            .ok_or(Error::CollectionNotFound)?;
+        Ok(generator.is_deterministic())
+    }
+
     /// Looks up a [`view::Serialized`] by name.
     pub fn view_by_name(&self, name: &ViewName) -> Result<&'_ dyn view::Serialized, Error> {
         self.views_by_name
@@ -151,6 +277,71 @@ impl Schematic {
             .ok_or(Error::ViewNotFound)
     }
 
+    /// Looks up a [`JoinDefinition`] by name.
+    pub fn join_by_name(&self, name: &ViewName) -> Result<JoinDefinition, Error> {
+        let (left, right) = self.joins.get(name).ok_or(Error::ViewNotFound)?;
+        self.join_definition(name, *left, *right)
+    }
+
+    /// Looks up the [`JoinDefinition`] through the type `J`.
+    pub fn join<J: JoinedView>(&self) -> Result<JoinDefinition, Error> {
+        self.join_by_name(
+            self.joins_by_type_id
+                .get(&TypeId::of::<J>())
+                .ok_or(Error::ViewNotFound)?,
+        )
+    }
+
+    /// Returns the definitions of all joins that `view` is part of.
+    pub fn joins_of_view(&self, view: &ViewName) -> Result<Vec<JoinDefinition>, Error> {
+        let mut joins = Vec::new();
+        for (name, (left, right)) in &self.joins {
+            let join = self.join_definition(name, *left, *right)?;
+            if join.joins(view) {
+                joins.push(join);
+            }
+        }
+        Ok(joins)
+    }
+
+    fn join_definition(
+        &self,
+        name: &ViewName,
+        left: TypeId,
+        right: TypeId,
+    ) -> Result<JoinDefinition, Error> {
+        let view_name = |type_id: TypeId| {
+            self.views
+                .get(&type_id)
+                .map(|view| view.view_name())
+                .ok_or(Error::ViewNotFound)
+        };
+        Ok(JoinDefinition {
+            name: name.clone(),
+            left: view_name(left)?,
+            right: view_name(right)?,
+        })
+    }
+
+    /// Looks up the name of the [`Projection`] `P`.
+    pub fn projection<P: Projection>(&self) -> Result<&Name, Error> {
+        self.projections_by_type_id
+            .get(&TypeId::of::<P>())
+            .ok_or(Error::ProjectionNotFound)
+    }
+
+    /// Looks up a [`SerializedProjection`] of `collection` by name.
+    pub fn projection_by_name(
+        &self,
+        collection: &CollectionName,
+        name: &Name,
+    ) -> Result<&'_ dyn SerializedProjection, Error> {
+        self.projections
+            .get(&(collection.clone(), name.clone()))
+            .map(AsRef::as_ref)
+            .ok_or(Error::ProjectionNotFound)
+    }
+
     /// Iterates over all registered views.
     pub fn views(&self) -> impl Iterator<Item = &'_ dyn view::Serialized> {
         self.views.values().map(AsRef::as_ref)
@@ -193,6 +384,72 @@ impl Schematic {
         self.collection_encryption_keys.get(collection)
     }
 
+    /// Returns the time-series options of a collection, if it is a
+    /// time-series collection.
+    #[must_use]
+    pub fn time_series_for_collection(&self, collection: &CollectionName) -> Option<&TimeSeries> {
+        self.collection_time_series.get(collection)
+    }
+
+    /// Returns the names of all time-series collections contained in this
+    /// schematic.
+    #[must_use]
+    pub fn time_series_collections(&self) -> Vec<CollectionName> {
+        self.collection_time_series.keys().cloned().collect()
+    }
+
+    /// Returns true if the documents of `collection` are stored in a versioned
+    /// tree. See [`Collection::versioned()`] for more information.
+    #[must_use]
+    pub fn collection_is_versioned(&self, collection: &CollectionName) -> bool {
+        !self.unversioned_collections.contains(collection)
+    }
+
+    /// Returns true if documents of `collection` with identical contents share
+    /// storage. See [`Collection::deduplicated()`] for more information.
+    #[must_use]
+    pub fn collection_is_deduplicated(&self, collection: &CollectionName) -> bool {
+        self.deduplicated_collections.contains(collection)
+    }
+
+    /// Returns the names `collection` was previously stored under. See
+    /// [`Collection::legacy_names()`] for more information.
+    #[must_use]
+    pub fn legacy_names_for_collection(&self, collection: &CollectionName) -> &[CollectionName] {
+        self.collection_legacy_names
+            .get(collection)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns true if the documents of `collection` contain [`Crdt`] values.
+    /// See [`Schematic::define_crdt()`] for more information.
+    #[must_use]
+    pub fn collection_is_crdt(&self, collection: &CollectionName) -> bool {
+        self.collection_mergers.contains_key(collection)
+    }
+
+    /// Merges the serialized contents `incoming` into `existing` if
+    /// `collection` contains [`Crdt`] values. Returns `None` if `collection`
+    /// is not a CRDT collection.
+    pub fn merge_collection_contents(
+        &self,
+        collection: &CollectionName,
+        existing: &[u8],
+        incoming: &[u8],
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.collection_mergers
+            .get(collection)
+            .map(|merger| merger.merge(existing, incoming))
+            .transpose()
+    }
+
+    /// Returns the key-value store's default encryption key, if one was
+    /// defined.
+    #[must_use]
+    pub fn encryption_key_for_key_value_store(&self) -> Option<&KeyId> {
+        self.key_value_encryption_key.as_ref()
+    }
+
     /// Returns a list of all collections contained in this schematic.
     #[must_use]
     pub fn collections(&self) -> Vec<CollectionName> {
@@ -224,14 +481,48 @@ where
         self.schema.lazy()
     }
 
+    fn materialization(&self) -> ViewMaterialization {
+        self.schema.materialization()
+    }
+
+    fn value_storage(&self) -> ValueStorage {
+        self.schema.value_storage()
+    }
+
     fn version(&self) -> u64 {
         self.schema.version()
     }
 
+    fn definition_checksum(&self) -> u64 {
+        let mut hasher = Sha256::new();
+        for part in [
+            self.view_name().to_string().as_str(),
+            std::any::type_name::<V::Key>(),
+            std::any::type_name::<V::Value>(),
+        ] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        hasher.update(self.version().to_be_bytes());
+        // Only included when not using the default, which keeps the checksums
+        // of existing views unchanged.
+        if self.value_storage() == ValueStorage::Reference {
+            hasher.update(b"value-storage:reference");
+        }
+        let digest = hasher.finalize();
+        let mut checksum = [0; 8];
+        checksum.copy_from_slice(&digest[..8]);
+        u64::from_be_bytes(checksum)
+    }
+
     fn view_name(&self) -> ViewName {
         self.view.view_name()
     }
 
+    fn filter(&self, document: &BorrowedDocument<'_>) -> Result<bool, view::Error> {
+        Ok(self.schema.filter(document)?)
+    }
+
     fn map(&self, document: &BorrowedDocument<'_>) -> Result<Vec<map::Serialized>, view::Error> {
         let map = self.schema.map(document)?;
 
@@ -241,6 +532,10 @@ where
     }
 
     fn reduce(&self, mappings: &[(&[u8], &[u8])], rereduce: bool) -> Result<Vec<u8>, view::Error> {
+        if self.value_storage() == ValueStorage::Reference {
+            return Err(view::Error::Core(crate::Error::ReduceUnimplemented));
+        }
+
         let mappings = mappings
             .iter()
             .map(
@@ -258,10 +553,21 @@ where
 
         V::serialize(&reduced_value).map_err(view::Error::from)
     }
+
+    fn aggregate_value(&self, value: &[u8]) -> Result<f64, view::Error> {
+        let value = V::deserialize(value)?;
+        self.schema
+            .aggregate_value(&value)
+            .map_err(view::Error::from)
+    }
 }
 
 pub trait IdGenerator: Debug + Send + Sync {
     fn next_id(&self, id: Option<DocumentId>) -> Result<DocumentId, Error>;
+
+    /// Returns true if [`Self::next_id()`] always returns the same id for the
+    /// same input.
+    fn is_deterministic(&self) -> bool;
 }
 
 #[derive(Debug)]
@@ -285,6 +591,78 @@ where
             .map_err(|err| Error::DocumentPush(C::collection_name(), err))?;
         DocumentId::new(&next_value)
     }
+
+    fn is_deterministic(&self) -> bool {
+        true
+    }
+}
+
+/// Assigns random ids by decoding randomly generated bytes as the collection's
+/// primary key.
+#[derive(Debug)]
+#[derive_where(Default)]
+pub struct RandomIdGenerator<C: Collection>(PhantomData<C>);
+
+impl<C> IdGenerator for RandomIdGenerator<C>
+where
+    C: Collection,
+{
+    fn next_id(&self, _id: Option<DocumentId>) -> Result<DocumentId, Error> {
+        let length =
+            <C::PrimaryKey as KeyEncoding<'_, C::PrimaryKey>>::LENGTH.ok_or_else(|| {
+                Error::DocumentPush(C::collection_name(), NextValueError::Unsupported)
+            })?;
+        let mut bytes = vec![0; length];
+        rand::Rng::fill(&mut rand::thread_rng(), bytes.as_mut_slice());
+        let key = <C::PrimaryKey as Key<'_>>::from_ord_bytes(ByteCow::Owned(bytes))
+            .map_err(|_| Error::DocumentPush(C::collection_name(), NextValueError::Unsupported))?;
+        DocumentId::new(&key)
+    }
+
+    fn is_deterministic(&self) -> bool {
+        false
+    }
+}
+
+pub trait ContentsMerger: Debug + Send + Sync {
+    fn merge(&self, existing: &[u8], incoming: &[u8]) -> Result<Vec<u8>, Error>;
+}
+
+/// Merges the contents of documents whose contents implement [`Crdt`].
+#[derive(Debug)]
+#[derive_where(Default)]
+pub struct CrdtMerger<C: SerializedCollection>(PhantomData<C>);
+
+impl<C> ContentsMerger for CrdtMerger<C>
+where
+    C: SerializedCollection,
+    C::Contents: Crdt,
+{
+    fn merge(&self, existing: &[u8], incoming: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut contents = C::deserialize(existing)?;
+        contents.merge(C::deserialize(incoming)?);
+        C::serialize(&contents)
+    }
+}
+
+/// Assigns ids to time-series collections using the current time. Ids are
+/// always greater than the most recent id, even if the clock has not advanced.
+#[derive(Debug)]
+pub struct TimestampIdGenerator;
+
+impl IdGenerator for TimestampIdGenerator {
+    fn next_id(&self, id: Option<DocumentId>) -> Result<DocumentId, Error> {
+        let now = Timestamp::now();
+        let next = match id.map(|id| id.deserialize::<Timestamp>()).transpose()? {
+            Some(newest) if newest >= now => newest + Duration::from_nanos(1),
+            _ => now,
+        };
+        DocumentId::new(&next)
+    }
+
+    fn is_deterministic(&self) -> bool {
+        false
+    }
 }
 
 #[test]
@@ -297,7 +675,7 @@ fn schema_tests() -> anyhow::Result<()> {
         schema.collections_by_type_id[&TypeId::of::<Basic>()],
         Basic::collection_name()
     );
-    assert_eq!(schema.views.len(), 5);
+    assert_eq!(schema.views.len(), 6);
     assert_eq!(
         schema.views[&TypeId::of::<BasicCount>()].view_name(),
         View::view_name(&BasicCount)
@@ -305,3 +683,32 @@ fn schema_tests() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn unsupported_random_ids() {
+    use serde::{Deserialize, Serialize};
+
+    use crate::schema::Collection;
+
+    #[derive(Serialize, Deserialize, Debug, Collection)]
+    #[collection(name = "random-strings", primary_key = String, core = crate)]
+    #[collection(id_assignment = IdAssignment::Random)]
+    struct RandomString;
+
+    #[derive(Serialize, Deserialize, Debug, Collection)]
+    #[collection(name = "random-series", core = crate)]
+    #[collection(time_series = TimeSeries::partitioned_by(Duration::from_secs(60)))]
+    #[collection(id_assignment = IdAssignment::Random)]
+    struct RandomSeries;
+
+    // Collections that can't be assigned random ids are rejected when they are
+    // defined rather than when the first document is pushed.
+    assert!(matches!(
+        Schematic::from_schema::<RandomString>(),
+        Err(Error::DocumentPush(_, NextValueError::Unsupported))
+    ));
+    assert!(matches!(
+        Schematic::from_schema::<RandomSeries>(),
+        Err(Error::DocumentPush(_, NextValueError::Unsupported))
+    ));
+}
@@ -0,0 +1,89 @@
+use std::time::Duration;
+
+use crate::keyvalue::Timestamp;
+
+/// Options for a collection of append-only, time-ordered documents.
+///
+/// Time-series collections must use [`Timestamp`] as their primary key. When a
+/// document is pushed without an id, it is assigned the current time, or one
+/// nanosecond after the newest document if the clock has not advanced.
+///
+/// Documents are grouped into partitions covering a fixed [`Duration`],
+/// aligned to [`UNIX_EPOCH`](std::time::UNIX_EPOCH). When a retention period
+/// is configured, each partition whose documents are all older than the
+/// retention period is removed in a single operation once it expires.
+///
+/// Downsampling views can be created by emitting keys rounded down using
+/// [`Timestamp::truncate()`] and reducing the values within each interval.
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use bonsaidb_core::keyvalue::Timestamp;
+/// use bonsaidb_core::schema::{Collection, TimeSeries};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, Serialize, Deserialize, Collection)]
+/// #[collection(name = "measurements")]
+/// #[collection(time_series = TimeSeries::partitioned_by(Duration::from_secs(60 * 60)).retain_for(Duration::from_secs(7 * 24 * 60 * 60)))]
+/// # #[collection(core = bonsaidb_core)]
+/// struct Measurement {
+///     pub value: f64,
+/// }
+///
+/// assert_eq!(
+///     <Measurement as Collection>::time_series()
+///         .unwrap()
+///         .partition_of(Timestamp {
+///             seconds: 5_000,
+///             nanos: 0
+///         }),
+///     Timestamp {
+///         seconds: 3_600,
+///         nanos: 0
+///     }
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[must_use]
+pub struct TimeSeries {
+    /// The amount of time covered by each partition.
+    pub partition: Duration,
+    /// The minimum amount of time documents are kept. If `None`, documents are
+    /// kept until they are deleted.
+    pub retention: Option<Duration>,
+}
+
+impl TimeSeries {
+    /// Returns options that group documents into partitions covering
+    /// `partition`. Documents are kept until they are deleted.
+    pub const fn partitioned_by(partition: Duration) -> Self {
+        Self {
+            partition,
+            retention: None,
+        }
+    }
+
+    /// Removes each partition once all of its documents are older than
+    /// `retention`.
+    pub const fn retain_for(mut self, retention: Duration) -> Self {
+        self.retention = Some(retention);
+        self
+    }
+
+    /// Returns the start of the partition containing `timestamp`.
+    pub fn partition_of(&self, timestamp: Timestamp) -> Timestamp {
+        timestamp.truncate(self.partition)
+    }
+
+    /// Returns the start of the oldest partition that is retained at `now`.
+    /// All documents with an id before the returned timestamp have expired.
+    /// Returns `None` if no retention period is configured or no partitions
+    /// could have expired yet.
+    #[must_use]
+    pub fn retention_cutoff(&self, now: Timestamp) -> Option<Timestamp> {
+        let retention = self.retention?;
+        let oldest_retained = Duration::from(now).checked_sub(retention)?;
+        Some(self.partition_of(Timestamp::from(oldest_retained)))
+    }
+}
@@ -1,4 +1,4 @@
-use std::fmt::Debug;
+use std::fmt::{Debug, Write};
 
 use serde::de::DeserializeOwned;
 use serde::Serialize;
@@ -15,6 +15,15 @@ use crate::AnyError;
 /// Types for defining a `Map` within a `View`.
 pub mod map;
 
+/// Types for aggregating the values within a `View`.
+pub mod aggregation;
+
+/// Reusable value types for implementing `View` reduce functions.
+pub mod reduce;
+
+/// Types for maintaining persistent joins of two `View`s.
+pub mod join;
+
 /// Errors that arise when interacting with views.
 #[derive(thiserror::Error, Debug)]
 // TODO add which view name and collection
@@ -47,6 +56,67 @@ pub type ViewMapResult<V> = Result<Mappings<<V as View>::Key, <V as View>::Value
 /// A type alias for the result of `ViewSchema::reduce()`.
 pub type ReduceResult<V> = Result<<V as View>::Value, crate::Error>;
 
+/// The key-value namespace that materialized view reductions are stored in.
+pub const MATERIALIZED_VIEW_NAMESPACE: &str = "_bonsaidb.materialized-views";
+
+/// Controls whether a view's reduced values are copied into the key-value
+/// store each time the view is updated.
+///
+/// Materialized values are stored in [`MATERIALIZED_VIEW_NAMESPACE`], and can
+/// be read using `materialized_reduce()` on a view query. Materializing a view
+/// causes it to be updated eagerly.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum ViewMaterialization {
+    /// Reduced values are not stored in the key-value store.
+    #[default]
+    None,
+    /// The reduced value of all entries in the view is stored in a single key.
+    Total,
+    /// The reduced value of each unique key in the view is stored in its own
+    /// key.
+    Grouped,
+}
+
+/// Controls how a view stores the values emitted by its map function.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum ValueStorage {
+    /// Each emitted value is serialized and stored in the view's entries.
+    #[default]
+    Inline,
+    /// Only the emitting document's header is stored in the view's entries.
+    /// When the view is queried, each value is produced again by mapping the
+    /// document it was emitted by.
+    ///
+    /// This is useful for views whose values are copies of large portions of
+    /// their documents: the view's entries no longer contain a copy of the
+    /// documents, at the cost of reading and mapping each document a query
+    /// returns. Because each value is produced from the document's current
+    /// contents, a query using an [`AccessPolicy`](crate::connection::AccessPolicy)
+    /// that doesn't update the view returns no value for a document that no
+    /// longer emits the queried key.
+    ///
+    /// Views storing values by reference can't be reduced, because reducing
+    /// the view requires every value to be available as entries are updated.
+    Reference,
+}
+
+/// Returns the key-value key that the materialized reduction of `view` is
+/// stored in. When `key` is `None`, the key for the
+/// [`Total`](ViewMaterialization::Total) reduction is returned. Otherwise, the
+/// key for the [`Grouped`](ViewMaterialization::Grouped) reduction of the
+/// serialized view key is returned.
+#[must_use]
+pub fn materialized_view_key(view: &ViewName, key: Option<&[u8]>) -> String {
+    let mut kv_key = view.to_string();
+    if let Some(key) = key {
+        kv_key.push('/');
+        for byte in key {
+            write!(kv_key, "{byte:02x}").expect("writing to a string can't fail");
+        }
+    }
+    kv_key
+}
+
 /// An lazy index of mapped and/or reduced data from a [`Collection`].
 ///
 /// A view provides an efficient way to query data within a collection. BonsaiDb
@@ -112,6 +182,31 @@ pub trait ViewSchema: Send + Sync + Debug + 'static {
         0
     }
 
+    /// Returns whether this view's reduced values should be stored in the
+    /// key-value store each time the view is updated. See
+    /// [`ViewMaterialization`] for more information.
+    fn materialization(&self) -> ViewMaterialization {
+        ViewMaterialization::None
+    }
+
+    /// Returns how this view stores the values emitted by [`Self::map()`].
+    /// See [`ValueStorage`] for more information. Changing this value causes
+    /// the view to be rebuilt.
+    fn value_storage(&self) -> ValueStorage {
+        ValueStorage::Inline
+    }
+
+    /// Returns whether `document` belongs in this view. Documents that don't
+    /// belong in the view are never passed to [`Self::map()`], and the view
+    /// stores no record of them. This function is called each time a document
+    /// is mapped, so it should be inexpensive to evaluate. Like
+    /// [`Self::map()`], changing the behavior of this function requires
+    /// changing the [version](Self::version) of the view.
+    #[allow(unused_variables)]
+    fn filter(&self, document: &BorrowedDocument<'_>) -> Result<bool, crate::Error> {
+        Ok(true)
+    }
+
     /// The map function for this view. This function is responsible for
     /// emitting entries for any documents that should be contained in this
     /// View. If None is returned, the View will not include the document. See [the user guide's chapter on
@@ -134,6 +229,16 @@ pub trait ViewSchema: Send + Sync + Debug + 'static {
     ) -> Result<<Self::View as View>::Value, crate::Error> {
         Err(crate::Error::ReduceUnimplemented)
     }
+
+    /// Returns the numeric representation of `value` used when computing
+    /// [`Aggregation`s](aggregation::Aggregation). If an error of
+    /// [`AggregationUnimplemented`](crate::Error::AggregationUnimplemented) is
+    /// returned, queries that ask for an aggregation other than
+    /// [`Count`](aggregation::Aggregation::Count) will return an error.
+    #[allow(unused_variables)]
+    fn aggregate_value(&self, value: &<Self::View as View>::Value) -> Result<f64, crate::Error> {
+        Err(crate::Error::AggregationUnimplemented)
+    }
 }
 
 /// A [`View`] with additional tyes and logic to handle serializing view values.
@@ -223,6 +328,32 @@ where
         0
     }
 
+    /// Returns whether this view's reduced values should be stored in the
+    /// key-value store each time the view is updated. See
+    /// [`ViewMaterialization`] for more information.
+    fn materialization(&self) -> ViewMaterialization {
+        ViewMaterialization::None
+    }
+
+    /// Returns how this view stores the values emitted by [`Self::map()`].
+    /// See [`ValueStorage`] for more information. Changing this value causes
+    /// the view to be rebuilt.
+    fn value_storage(&self) -> ValueStorage {
+        ValueStorage::Inline
+    }
+
+    /// Returns whether `document` belongs in this view. Documents that don't
+    /// belong in the view are never passed to [`Self::map()`], and the view
+    /// stores no record of them. This function receives the document before
+    /// its contents are deserialized, which keeps checks against the
+    /// document's [header](BorrowedDocument::header) inexpensive. Changing the
+    /// behavior of this function requires changing the
+    /// [version](Self::version) of the view.
+    #[allow(unused_variables)]
+    fn filter(&self, document: &BorrowedDocument<'_>) -> Result<bool, crate::Error> {
+        Ok(true)
+    }
+
     /// The map function for this view. This function is responsible for
     /// emitting entries for any documents that should be contained in this
     /// View. If None is returned, the View will not include the document.
@@ -244,6 +375,16 @@ where
     ) -> ReduceResult<Self::View> {
         Err(crate::Error::ReduceUnimplemented)
     }
+
+    /// Returns the numeric representation of `value` used when computing
+    /// [`Aggregation`s](aggregation::Aggregation). If
+    /// `Err(Error::AggregationUnimplemented)` is returned, queries that ask for
+    /// an aggregation other than [`Count`](aggregation::Aggregation::Count)
+    /// will return an error.
+    #[allow(unused_variables)]
+    fn aggregate_value(&self, value: &<Self::View as View>::Value) -> Result<f64, crate::Error> {
+        Err(crate::Error::AggregationUnimplemented)
+    }
 }
 
 impl<T> ViewSchema for T
@@ -258,6 +399,10 @@ where
         T::version(self)
     }
 
+    fn filter(&self, document: &BorrowedDocument<'_>) -> Result<bool, crate::Error> {
+        T::filter(self, document)
+    }
+
     fn map(&self, document: &BorrowedDocument<'_>) -> ViewMapResult<Self::View> {
         T::map(self, CollectionDocument::try_from(document)?)
     }
@@ -270,6 +415,10 @@ where
         T::reduce(self, mappings, rereduce)
     }
 
+    fn aggregate_value(&self, value: &<Self::View as View>::Value) -> Result<f64, crate::Error> {
+        T::aggregate_value(self, value)
+    }
+
     fn unique(&self) -> bool {
         T::unique(self)
     }
@@ -277,6 +426,14 @@ where
     fn lazy(&self) -> bool {
         T::lazy(self)
     }
+
+    fn materialization(&self) -> ViewMaterialization {
+        T::materialization(self)
+    }
+
+    fn value_storage(&self) -> ValueStorage {
+        T::value_storage(self)
+    }
 }
 
 /// Wraps a [`View`] with serialization to erase the associated types
@@ -288,20 +445,36 @@ pub trait Serialized: Send + Sync + Debug {
     /// Wraps [`ViewSchema::lazy`]
     fn lazy(&self) -> bool;
 
+    /// Wraps [`ViewSchema::materialization`]
+    fn materialization(&self) -> ViewMaterialization;
+
+    /// Wraps [`ViewSchema::value_storage`]
+    fn value_storage(&self) -> ValueStorage;
+
     /// Returns true if this view should be eagerly updated during document
     /// updates.
     fn eager(&self) -> bool {
-        self.unique() || !self.lazy()
+        self.unique() || !self.lazy() || self.materialization() != ViewMaterialization::None
     }
 
     /// Wraps [`ViewSchema::version`]
     fn version(&self) -> u64;
+    /// Returns a checksum of the information identifying this view's
+    /// definition: its name, [version](ViewSchema::version), and the names of
+    /// its key and value types. Storage implementations compare this against
+    /// the stored checksum to detect views whose definition changed without
+    /// their version being updated.
+    fn definition_checksum(&self) -> u64;
     /// Wraps [`View::view_name`]
     fn view_name(&self) -> ViewName;
+    /// Wraps [`ViewSchema::filter`]
+    fn filter(&self, document: &BorrowedDocument<'_>) -> Result<bool, Error>;
     /// Wraps [`ViewSchema::map`]
     fn map(&self, document: &BorrowedDocument<'_>) -> Result<Vec<map::Serialized>, Error>;
     /// Wraps [`ViewSchema::reduce`]
     fn reduce(&self, mappings: &[(&[u8], &[u8])], rereduce: bool) -> Result<Vec<u8>, Error>;
+    /// Wraps [`ViewSchema::aggregate_value`]
+    fn aggregate_value(&self, value: &[u8]) -> Result<f64, Error>;
 }
 
 /// Defines an unique view named `$view_name` for `$collection` with the
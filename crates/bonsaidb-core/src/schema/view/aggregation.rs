@@ -0,0 +1,158 @@
+use arc_bytes::serde::Bytes;
+use serde::{Deserialize, Serialize};
+
+/// A statistic computed over the values of a view's entries.
+///
+/// Aggregations other than [`Count`](Self::Count) require the view to
+/// implement [`ViewSchema::aggregate_value()`](super::ViewSchema::aggregate_value)
+/// or [`CollectionViewSchema::aggregate_value()`](super::CollectionViewSchema::aggregate_value).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Aggregation {
+    /// The number of mapped entries.
+    Count,
+    /// The sum of all values.
+    Sum,
+    /// The smallest value.
+    Min,
+    /// The largest value.
+    Max,
+    /// The arithmetic mean of all values.
+    Average,
+    /// The value below which the given percentage of values fall. The
+    /// percentage must be within `0.0..=100.0`. Values between ranks are
+    /// linearly interpolated.
+    Percentile(f64),
+}
+
+impl Aggregation {
+    /// Returns true if this aggregation needs the numeric value of each entry.
+    #[must_use]
+    pub const fn requires_values(&self) -> bool {
+        !matches!(self, Self::Count)
+    }
+
+    /// Computes this aggregation over `count` mapped entries with `sorted`
+    /// values. `sorted` must be in ascending order. Returns `None` if the
+    /// aggregation is undefined for an empty set of values.
+    #[must_use]
+    pub fn compute(&self, count: u64, sorted: &[f64]) -> Option<f64> {
+        match self {
+            #[allow(clippy::cast_precision_loss)]
+            Self::Count => Some(count as f64),
+            Self::Sum => Some(sorted.iter().sum()),
+            Self::Min => sorted.first().copied(),
+            Self::Max => sorted.last().copied(),
+            #[allow(clippy::cast_precision_loss)]
+            Self::Average => {
+                (!sorted.is_empty()).then(|| sorted.iter().sum::<f64>() / sorted.len() as f64)
+            }
+            Self::Percentile(percent) => percentile(sorted, *percent),
+        }
+    }
+}
+
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn percentile(sorted: &[f64], percent: f64) -> Option<f64> {
+    if sorted.is_empty() || !(0. ..=100.).contains(&percent) {
+        return None;
+    }
+
+    // rank is within 0..=len - 1, so the casts can not truncate.
+    let rank = percent / 100. * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    Some(sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - rank.floor()))
+}
+
+/// Controls how a view's entries are grouped when aggregating.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum AggregationGrouping {
+    /// All matching entries are aggregated together.
+    None,
+    /// Entries are aggregated for each unique key.
+    Key,
+    /// Entries are aggregated by the first `n` bytes of their serialized key.
+    /// Keys shorter than `n` bytes are grouped by their entire key.
+    Prefix(u32),
+}
+
+/// The aggregated values for a group of view entries.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AggregatedGroup<K> {
+    /// The key that the entries were grouped by.
+    pub key: K,
+    /// The result of each requested [`Aggregation`], in the order they were
+    /// requested.
+    pub values: Vec<Option<f64>>,
+}
+
+/// An [`AggregatedGroup`] with its key still serialized. The key is `None`
+/// when the aggregation used [`AggregationGrouping::None`].
+pub type SerializedAggregatedGroup = AggregatedGroup<Option<Bytes>>;
+
+/// Accumulates the values of a single group of entries.
+#[derive(Default, Debug)]
+pub struct Aggregator {
+    count: u64,
+    values: Vec<f64>,
+}
+
+impl Aggregator {
+    /// Adds a mapped entry to this group. `value` should be `Some` if any
+    /// requested aggregations [require values](Aggregation::requires_values).
+    pub fn push(&mut self, value: Option<f64>) {
+        self.count += 1;
+        if let Some(value) = value {
+            self.values.push(value);
+        }
+    }
+
+    /// Computes `aggregations` over the values in this group.
+    #[must_use]
+    pub fn finish(mut self, aggregations: &[Aggregation]) -> Vec<Option<f64>> {
+        self.values.sort_unstable_by(f64::total_cmp);
+        aggregations
+            .iter()
+            .map(|aggregation| aggregation.compute(self.count, &self.values))
+            .collect()
+    }
+}
+
+#[test]
+fn aggregation_tests() {
+    let mut aggregator = Aggregator::default();
+    for value in [4., 1., 3., 2.] {
+        aggregator.push(Some(value));
+    }
+    assert_eq!(
+        aggregator.finish(&[
+            Aggregation::Count,
+            Aggregation::Sum,
+            Aggregation::Min,
+            Aggregation::Max,
+            Aggregation::Average,
+            Aggregation::Percentile(50.),
+            Aggregation::Percentile(100.),
+            Aggregation::Percentile(101.),
+        ]),
+        vec![
+            Some(4.),
+            Some(10.),
+            Some(1.),
+            Some(4.),
+            Some(2.5),
+            Some(2.5),
+            Some(4.),
+            None
+        ]
+    );
+
+    assert_eq!(
+        Aggregator::default().finish(&[Aggregation::Count, Aggregation::Sum, Aggregation::Min]),
+        vec![Some(0.), Some(0.), None]
+    );
+}
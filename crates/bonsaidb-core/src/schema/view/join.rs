@@ -0,0 +1,140 @@
+use std::fmt::Debug;
+
+use arc_bytes::serde::Bytes;
+use serde::{Deserialize, Serialize};
+
+use crate::document::Header;
+use crate::key::{ByteCow, Key};
+use crate::schema::view::{self, SerializedView};
+use crate::schema::{Name, View, ViewName};
+use crate::Error;
+
+/// A persistent join of two views on their shared key.
+///
+/// The database stores the entries of both views for every key that both
+/// views contain. When either view's entries for a key change, the joined
+/// entry for that key is updated, which allows the joined entries to be
+/// queried without merging the results of two view queries.
+///
+/// Joins are added to a schema using
+/// [`Schematic::define_joined_view()`](crate::schema::Schematic::define_joined_view)
+/// or the `joins` parameter of the `Collection` derive macro.
+///
+/// ```rust
+/// # bonsaidb_core::__doctest_prelude!();
+/// use bonsaidb_core::schema::view::join::JoinedView;
+/// use bonsaidb_core::schema::Name;
+///
+/// #[derive(Debug)]
+/// struct ScoresAndNames;
+///
+/// impl JoinedView for ScoresAndNames {
+///     type Left = ScoresByRank;
+///     type Right = ScoresByRank;
+///
+///     fn name(&self) -> Name {
+///         Name::new("scores-and-names")
+///     }
+/// }
+/// ```
+pub trait JoinedView: Send + Sync + Debug + 'static {
+    /// The view whose entries make up the left side of the join.
+    type Left: SerializedView;
+    /// The view whose entries make up the right side of the join. Its key
+    /// must be the same type as [`Self::Left`]'s key.
+    type Right: SerializedView<Key = <Self::Left as View>::Key>;
+
+    /// The name of this join. The join is named within the collection of
+    /// [`Self::Left`].
+    fn name(&self) -> Name;
+}
+
+/// The names of a [`JoinedView`] and the views it joins.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct JoinDefinition {
+    /// The name of the join.
+    pub name: ViewName,
+    /// The name of the view whose entries make up the left side of the join.
+    pub left: ViewName,
+    /// The name of the view whose entries make up the right side of the join.
+    pub right: ViewName,
+}
+
+impl JoinDefinition {
+    /// Returns true if `view` is either side of this join.
+    #[must_use]
+    pub fn joins(&self, view: &ViewName) -> bool {
+        &self.left == view || &self.right == view
+    }
+}
+
+/// A value emitted into one side of a joined view.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct JoinedMapping<Value> {
+    /// The header of the document that emitted the value.
+    pub source: Header,
+    /// The value emitted.
+    pub value: Value,
+}
+
+/// The entries of both views of a [`JoinedView`] that share a key.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JoinedEntry<K, LeftValue, RightValue> {
+    /// The key shared by both sides of the join.
+    pub key: K,
+    /// The values emitted with this key into the left view.
+    pub left: Vec<JoinedMapping<LeftValue>>,
+    /// The values emitted with this key into the right view.
+    pub right: Vec<JoinedMapping<RightValue>>,
+}
+
+/// The entries returned when querying the [`JoinedView`] `J`.
+pub type JoinedEntries<J> = Vec<
+    JoinedEntry<
+        <<J as JoinedView>::Left as View>::Key,
+        <<J as JoinedView>::Left as View>::Value,
+        <<J as JoinedView>::Right as View>::Value,
+    >,
+>;
+
+/// A serialized [`JoinedEntry`].
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct SerializedJoinedEntry {
+    /// The serialized key shared by both sides of the join.
+    pub key: Bytes,
+    /// The serialized values emitted with this key into the left view.
+    pub left: Vec<JoinedMapping<Bytes>>,
+    /// The serialized values emitted with this key into the right view.
+    pub right: Vec<JoinedMapping<Bytes>>,
+}
+
+impl SerializedJoinedEntry {
+    /// Deserializes this entry using the views of `J`.
+    pub fn deserialize<J: JoinedView>(
+        &self,
+    ) -> Result<
+        JoinedEntry<<J::Left as View>::Key, <J::Left as View>::Value, <J::Right as View>::Value>,
+        Error,
+    > {
+        Ok(JoinedEntry {
+            key: <<J::Left as View>::Key as Key>::from_ord_bytes(ByteCow::Borrowed(&self.key))
+                .map_err(view::Error::key_serialization)?,
+            left: deserialize_mappings::<J::Left>(&self.left)?,
+            right: deserialize_mappings::<J::Right>(&self.right)?,
+        })
+    }
+}
+
+fn deserialize_mappings<V: SerializedView>(
+    mappings: &[JoinedMapping<Bytes>],
+) -> Result<Vec<JoinedMapping<V::Value>>, Error> {
+    mappings
+        .iter()
+        .map(|mapping| {
+            Ok(JoinedMapping {
+                source: mapping.source.clone(),
+                value: V::deserialize(&mapping.value)?,
+            })
+        })
+        .collect()
+}
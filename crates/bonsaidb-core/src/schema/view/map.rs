@@ -131,6 +131,91 @@ impl<K: for<'a> Key<'a>, V> IntoIterator for Mappings<K, V> {
     }
 }
 
+/// Collects any number of entries emitted by a single document. Returned from
+/// [`Emit::emitter()`](crate::document::Emit::emitter).
+///
+/// ```rust
+/// # bonsaidb_core::__doctest_prelude!();
+/// # use bonsaidb_core::document::BorrowedDocument;
+/// # use bonsaidb_core::schema::ViewSchema;
+/// # #[derive(Debug, Clone, View)]
+/// # #[view(collection = MyCollection, key = u32, value = f32, name = "scores-by-rank")]
+/// # #[view(core = bonsaidb_core)]
+/// # pub struct ScoresByRankView;
+/// # impl ViewSchema for ScoresByRankView {
+/// #     type View = Self;
+/// fn map(&self, document: &BorrowedDocument<'_>) -> ViewMapResult<Self::View> {
+///     let mut emitter = document.header.emitter()?;
+///     for rank in 1..=3 {
+///         emitter.emit(rank, 1.0 / rank as f32);
+///     }
+///     Ok(emitter.into_mappings())
+/// }
+/// # }
+/// ```
+///
+/// ## Ordering and duplicate keys
+///
+/// Entries are kept in the order they were first emitted. Emitting a key that
+/// has already been emitted replaces the previously emitted value, because a
+/// view stores at most one value per document for each key.
+///
+/// Once stored, a view's entries are ordered by key, regardless of the order
+/// they were emitted in. The values stored for a key are ordered by when each
+/// document first emitted the key.
+#[derive(Debug)]
+#[must_use]
+pub struct Emitter<K: for<'a> Key<'a> = (), V = ()> {
+    source: Header,
+    mappings: Vec<Map<K, V>>,
+}
+
+impl<K: for<'a> Key<'a> + PartialEq, V> Emitter<K, V> {
+    /// Returns a new emitter for entries emitted by the document `source`.
+    pub const fn new(source: Header) -> Self {
+        Self {
+            source,
+            mappings: Vec::new(),
+        }
+    }
+
+    /// Emits an entry with `key` and `value`. If `key` has already been
+    /// emitted, its value is replaced with `value`.
+    pub fn emit(&mut self, key: K, value: V) -> &mut Self {
+        if let Some(existing) = self.mappings.iter_mut().find(|map| map.key == key) {
+            existing.value = value;
+        } else {
+            self.mappings
+                .push(Map::new(self.source.clone(), key, value));
+        }
+        self
+    }
+
+    /// Returns the number of distinct keys emitted.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.mappings.len()
+    }
+
+    /// Returns true if no entries have been emitted.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.mappings.is_empty()
+    }
+
+    /// Returns the emitted entries, in the order their keys were first
+    /// emitted.
+    pub fn into_mappings(self) -> Mappings<K, V> {
+        Mappings::from_iter(self.mappings)
+    }
+}
+
+impl<K: for<'a> Key<'a> + PartialEq, V> From<Emitter<K, V>> for Mappings<K, V> {
+    fn from(emitter: Emitter<K, V>) -> Self {
+        emitter.into_mappings()
+    }
+}
+
 /// An iterator over [`Mappings`].
 pub enum MappingsIter<K: for<'a> Key<'a> = (), V = ()> {
     /// An iterator over a [`Mappings::Simple`] value.
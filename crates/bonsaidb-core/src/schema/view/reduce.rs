@@ -0,0 +1,361 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::key::Key;
+use crate::schema::view::map::MappedValue;
+
+/// A view value that can be combined with other values of the same type.
+///
+/// Values implementing this trait can be reduced without any custom rereduce
+/// logic:
+///
+/// ```rust
+/// # bonsaidb_core::__doctest_prelude!();
+/// use bonsaidb_core::schema::view::reduce::{Reducible, TopN};
+///
+/// #[derive(Debug, Clone, View)]
+/// #[view(collection = MyCollection, key = (), value = TopN<u32, 10>, name = "top-ranks")]
+/// # #[view(core = bonsaidb_core)]
+/// struct TopRanks;
+///
+/// impl CollectionViewSchema for TopRanks {
+///     type View = Self;
+///
+///     fn map(&self, document: CollectionDocument<MyCollection>) -> ViewMapResult<Self::View> {
+///         document
+///             .header
+///             .emit_key_and_value((), TopN::new(document.contents.rank))
+///     }
+///
+///     fn reduce(
+///         &self,
+///         mappings: &[ViewMappedValue<Self::View>],
+///         _rereduce: bool,
+///     ) -> ReduceResult<Self::View> {
+///         Ok(TopN::reduce_mappings(mappings))
+///     }
+/// }
+/// ```
+pub trait Reducible: Default + Sized {
+    /// Combines `other` into `self`.
+    fn merge(&mut self, other: &Self);
+
+    /// Returns the result of merging all of the values in `mappings`.
+    fn reduce_mappings<K>(mappings: &[MappedValue<K, Self>]) -> Self
+    where
+        K: for<'k> Key<'k>,
+    {
+        let mut reduced = Self::default();
+        for mapping in mappings {
+            reduced.merge(&mapping.value);
+        }
+        reduced
+    }
+}
+
+/// The `N` largest values, in descending order.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TopN<T, const N: usize> {
+    values: Vec<T>,
+}
+
+impl<T, const N: usize> TopN<T, N>
+where
+    T: Ord + Clone,
+{
+    /// Returns a new instance containing `value`.
+    #[must_use]
+    pub fn new(value: T) -> Self {
+        let mut top = Self::default();
+        top.push(value);
+        top
+    }
+
+    /// Adds `value`, removing the smallest value if more than `N` values are
+    /// contained.
+    pub fn push(&mut self, value: T) {
+        insert_ranked(&mut self.values, value, N, |a, b| b.cmp(a));
+    }
+
+    /// Returns the contained values, largest first.
+    #[must_use]
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// Returns the contained values, largest first.
+    #[must_use]
+    pub fn into_values(self) -> Vec<T> {
+        self.values
+    }
+}
+
+impl<T, const N: usize> Default for TopN<T, N> {
+    fn default() -> Self {
+        Self { values: Vec::new() }
+    }
+}
+
+impl<T, const N: usize> Reducible for TopN<T, N>
+where
+    T: Ord + Clone,
+{
+    fn merge(&mut self, other: &Self) {
+        for value in &other.values {
+            self.push(value.clone());
+        }
+    }
+}
+
+/// The `N` smallest values, in ascending order.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BottomN<T, const N: usize> {
+    values: Vec<T>,
+}
+
+impl<T, const N: usize> BottomN<T, N>
+where
+    T: Ord + Clone,
+{
+    /// Returns a new instance containing `value`.
+    #[must_use]
+    pub fn new(value: T) -> Self {
+        let mut bottom = Self::default();
+        bottom.push(value);
+        bottom
+    }
+
+    /// Adds `value`, removing the largest value if more than `N` values are
+    /// contained.
+    pub fn push(&mut self, value: T) {
+        insert_ranked(&mut self.values, value, N, Ord::cmp);
+    }
+
+    /// Returns the contained values, smallest first.
+    #[must_use]
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// Returns the contained values, smallest first.
+    #[must_use]
+    pub fn into_values(self) -> Vec<T> {
+        self.values
+    }
+}
+
+impl<T, const N: usize> Default for BottomN<T, N> {
+    fn default() -> Self {
+        Self { values: Vec::new() }
+    }
+}
+
+impl<T, const N: usize> Reducible for BottomN<T, N>
+where
+    T: Ord + Clone,
+{
+    fn merge(&mut self, other: &Self) {
+        for value in &other.values {
+            self.push(value.clone());
+        }
+    }
+}
+
+fn insert_ranked<T, F: Fn(&T, &T) -> std::cmp::Ordering>(
+    values: &mut Vec<T>,
+    value: T,
+    limit: usize,
+    compare: F,
+) {
+    let index = values
+        .binary_search_by(|existing| compare(existing, &value))
+        .unwrap_or_else(|index| index);
+    if index < limit {
+        values.insert(index, value);
+        values.truncate(limit);
+    }
+}
+
+/// The smallest and largest values.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MinMax<T> {
+    /// The smallest value, or `None` if no values have been reduced.
+    pub min: Option<T>,
+    /// The largest value, or `None` if no values have been reduced.
+    pub max: Option<T>,
+}
+
+impl<T> MinMax<T>
+where
+    T: PartialOrd + Clone,
+{
+    /// Returns a new instance whose minimum and maximum are `value`.
+    #[must_use]
+    pub fn new(value: T) -> Self {
+        Self {
+            min: Some(value.clone()),
+            max: Some(value),
+        }
+    }
+}
+
+impl<T> Default for MinMax<T> {
+    fn default() -> Self {
+        Self {
+            min: None,
+            max: None,
+        }
+    }
+}
+
+impl<T> Reducible for MinMax<T>
+where
+    T: PartialOrd + Clone,
+{
+    fn merge(&mut self, other: &Self) {
+        if let Some(other_min) = &other.min {
+            if self.min.as_ref().map_or(true, |min| other_min < min) {
+                self.min = Some(other_min.clone());
+            }
+        }
+        if let Some(other_max) = &other.max {
+            if self.max.as_ref().map_or(true, |max| other_max > max) {
+                self.max = Some(other_max.clone());
+            }
+        }
+    }
+}
+
+/// Counts of values grouped into fixed-width buckets.
+///
+/// Each bucket is identified by its smallest value, which is always a multiple
+/// of the bucket width.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Histogram {
+    bucket_width: u32,
+    buckets: BTreeMap<i64, u64>,
+}
+
+impl Histogram {
+    /// Returns a histogram with buckets `bucket_width` wide containing
+    /// `value`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bucket_width` is 0.
+    #[must_use]
+    pub fn new(bucket_width: u32, value: i64) -> Self {
+        assert!(bucket_width > 0, "bucket_width must be greater than 0");
+        let mut histogram = Self {
+            bucket_width,
+            buckets: BTreeMap::new(),
+        };
+        histogram.record(value, 1);
+        histogram
+    }
+
+    /// Adds `count` occurrences of `value` to its bucket.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this histogram has no bucket width, which is only the case
+    /// for [`Histogram::default()`] before any other histogram has been merged
+    /// into it.
+    pub fn record(&mut self, value: i64, count: u64) {
+        let width = i64::from(self.bucket_width);
+        *self
+            .buckets
+            .entry(value.div_euclid(width) * width)
+            .or_default() += count;
+    }
+
+    /// Returns the width of each bucket. Returns 0 if no values have been
+    /// recorded.
+    #[must_use]
+    pub const fn bucket_width(&self) -> u32 {
+        self.bucket_width
+    }
+
+    /// Returns the number of values in each non-empty bucket, keyed by the
+    /// smallest value of the bucket.
+    #[must_use]
+    pub const fn buckets(&self) -> &BTreeMap<i64, u64> {
+        &self.buckets
+    }
+
+    /// Returns the total number of recorded values.
+    #[must_use]
+    pub fn count(&self) -> u64 {
+        self.buckets.values().sum()
+    }
+}
+
+impl Reducible for Histogram {
+    /// Adds the counts from `other`. If the histograms have different bucket
+    /// widths, the counts from `other` are placed into the buckets of `self`
+    /// that contain each of `other`'s bucket starting values.
+    fn merge(&mut self, other: &Self) {
+        if self.bucket_width == 0 {
+            self.bucket_width = other.bucket_width;
+        }
+        for (&start, &count) in &other.buckets {
+            self.record(start, count);
+        }
+    }
+}
+
+#[test]
+fn ranked_tests() {
+    let mut top = TopN::<u32, 3>::default();
+    let mut bottom = BottomN::<u32, 3>::default();
+    for value in [5, 1, 4, 2, 3, 5] {
+        top.merge(&TopN::new(value));
+        bottom.merge(&BottomN::new(value));
+    }
+    assert_eq!(top.values(), &[5, 5, 4]);
+    assert_eq!(bottom.values(), &[1, 2, 3]);
+
+    let mappings = [
+        MappedValue::new((), TopN::<u32, 3>::new(1)),
+        MappedValue::new((), top),
+    ];
+    assert_eq!(
+        TopN::reduce_mappings(&mappings).into_values(),
+        vec![5, 5, 4]
+    );
+}
+
+#[test]
+fn min_max_tests() {
+    let mut min_max = MinMax::default();
+    assert_eq!(min_max.min, None);
+    for value in [3, -2, 7] {
+        min_max.merge(&MinMax::new(value));
+    }
+    assert_eq!(
+        min_max,
+        MinMax {
+            min: Some(-2),
+            max: Some(7)
+        }
+    );
+}
+
+#[test]
+fn histogram_tests() {
+    let mut histogram = Histogram::default();
+    for value in [0, 5, 9, 10, -1, -10, -11] {
+        histogram.merge(&Histogram::new(10, value));
+    }
+    assert_eq!(histogram.bucket_width(), 10);
+    assert_eq!(histogram.count(), 7);
+    assert_eq!(
+        histogram
+            .buckets()
+            .iter()
+            .map(|(k, v)| (*k, *v))
+            .collect::<Vec<_>>(),
+        vec![(-20, 1), (-10, 2), (0, 3), (10, 1)]
+    );
+}
@@ -0,0 +1,131 @@
+//! Types for collections whose documents are encrypted by clients.
+//!
+//! A [`SealedCollection`] stores its documents as [`Sealed`] values. The
+//! contents are encrypted by the client before they are sent to the server,
+//! using keys the server never has access to. Because the server cannot read
+//! the contents, it cannot evaluate views over them. Instead, the client
+//! computes a keyed hash of each value the document should be found by, called
+//! a blind key, and sends the blind keys alongside the encrypted contents. The
+//! [`BlindIndex`] view indexes these blind keys, allowing a client that knows
+//! the key to find documents by value without revealing the value to the
+//! server.
+//!
+//! Encrypting and decrypting sealed documents is implemented by
+//! `bonsaidb-client` when the `client-encryption` feature is enabled.
+
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use arc_bytes::serde::Bytes;
+use derive_where::derive_where;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::document::{BorrowedDocument, Emit};
+use crate::schema::view::DefaultViewSerialization;
+use crate::schema::{Name, SerializedCollection, View, ViewMapResult, ViewSchema};
+
+/// The stored contents of a document in a [`SealedCollection`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Sealed {
+    /// The encrypted contents of the document.
+    pub ciphertext: Bytes,
+    /// Keyed hashes of the values this document can be found by.
+    pub blind_keys: Vec<Bytes>,
+}
+
+/// A collection whose contents are encrypted by clients before being stored.
+///
+/// The implementing type is the plaintext contents of each document. Its
+/// stored contents are [`Sealed`] values. This trait can be implemented for
+/// types that derive `Collection` with the `sealed` parameter:
+///
+/// ```rust
+/// use bonsaidb_core::schema::Collection;
+/// use bonsaidb_core::sealed::{BlindValue, SealedCollection};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, Serialize, Deserialize, Collection)]
+/// #[collection(name = "notes", sealed)]
+/// # #[collection(core = bonsaidb_core)]
+/// pub struct Note {
+///     pub tag: String,
+///     pub body: String,
+/// }
+///
+/// impl SealedCollection for Note {
+///     fn blind_index(&self) -> Vec<BlindValue> {
+///         vec![BlindValue::new("tag", self.tag.as_bytes())]
+///     }
+/// }
+/// ```
+pub trait SealedCollection:
+    SerializedCollection<Contents = Sealed> + Serialize + DeserializeOwned
+{
+    /// Returns the values this document should be found by using
+    /// [`BlindIndex`]. By default, no values are indexed.
+    fn blind_index(&self) -> Vec<BlindValue> {
+        Vec::new()
+    }
+}
+
+/// A plaintext value that a sealed document is indexed by.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BlindValue {
+    /// The name of the index this value belongs to. The same value in two
+    /// different indexes produces different blind keys.
+    pub index: &'static str,
+    /// The value to index.
+    pub value: Vec<u8>,
+}
+
+impl BlindValue {
+    /// Returns a new value for `index`.
+    pub fn new(index: &'static str, value: impl Into<Vec<u8>>) -> Self {
+        Self {
+            index,
+            value: value.into(),
+        }
+    }
+}
+
+/// A view of a [`SealedCollection`]'s documents by each of their blind keys.
+///
+/// Collections deriving `Collection` with the `sealed` parameter define this
+/// view automatically.
+#[derive_where(Debug, Clone, Default)]
+pub struct BlindIndex<C>(PhantomData<C>);
+
+impl<C> View for BlindIndex<C>
+where
+    C: SerializedCollection<Contents = Sealed> + 'static,
+{
+    type Collection = C;
+    type Key = Bytes;
+    type Value = ();
+
+    fn name(&self) -> Name {
+        Name::new("blind-index")
+    }
+}
+
+impl<C> ViewSchema for BlindIndex<C>
+where
+    C: SerializedCollection<Contents = Sealed> + 'static,
+{
+    type View = Self;
+
+    fn map(&self, document: &BorrowedDocument<'_>) -> ViewMapResult<Self::View> {
+        let sealed = C::document_contents(document)?;
+        sealed
+            .blind_keys
+            .into_iter()
+            .map(|key| document.header.emit_key(key))
+            .collect()
+    }
+}
+
+impl<C> DefaultViewSerialization for BlindIndex<C> where
+    C: SerializedCollection<Contents = Sealed> + 'static
+{
+}
@@ -0,0 +1,1096 @@
+//! A small, read-only SQL dialect for exploring views.
+//!
+//! Views are exposed as tables by registering them in a [`SqlCatalog`]. Each
+//! table has three columns: `key`, `value`, and `source`, which is the id of
+//! the document that emitted the entry. Only the following form of `SELECT`
+//! is supported:
+//!
+//! ```sql
+//! SELECT key, value, source FROM table
+//!     WHERE key >= 1 AND key < 10
+//!     ORDER BY key DESC
+//!     LIMIT 5
+//! ```
+//!
+//! - The column list can be `*` or any combination of `key`, `value`, and
+//!   `source`.
+//! - `WHERE` conditions can only filter on `key`. The operators `=`, `<`,
+//!   `<=`, `>`, `>=`, `BETWEEN ... AND ...`, and `IN (...)` are supported, and
+//!   multiple conditions can be combined with `AND`.
+//! - `ORDER BY` can only sort by `key`.
+//!
+//! Literals can be integers, `'strings'`, `X'hex'` bytes, `TRUE`, `FALSE`, and
+//! `NULL`. Literals are converted to the view's key type using [`SqlKey`], and
+//! the conditions are planned into a single key query that is executed by the
+//! database. Values are decoded as [`Pot`](pot), the default format of views.
+
+use std::fmt::{Display, Write};
+use std::str::FromStr;
+
+use arc_bytes::serde::Bytes;
+use serde::Serialize;
+
+use crate::connection::{
+    AccessPolicy, AsyncLowLevelConnection, Bound, LowLevelConnection, Range, SerializedQueryKey,
+    Sort, ViewReadOptions,
+};
+use crate::key::{ByteCow, Key, KeyEncoding};
+use crate::schema::view::map;
+use crate::schema::{View, ViewName};
+
+/// A set of views that can be queried using SQL.
+///
+/// ```rust
+/// # use bonsaidb_core::test_util::{BasicByCategory, BasicByParentId};
+/// use bonsaidb_core::sql::SqlCatalog;
+///
+/// let catalog = SqlCatalog::default()
+///     .with_view("basic_by_category", &BasicByCategory)
+///     .with_view("basic_by_parent_id", &BasicByParentId);
+/// ```
+#[derive(Clone, Debug, Default)]
+#[must_use]
+pub struct SqlCatalog {
+    tables: Vec<SqlTable>,
+}
+
+#[derive(Clone, Debug)]
+struct SqlTable {
+    name: String,
+    view: ViewName,
+    encode_key: fn(&SqlValue) -> Result<Option<Bytes>, crate::Error>,
+    decode_key: fn(&[u8]) -> Result<SqlValue, crate::Error>,
+}
+
+impl SqlCatalog {
+    /// Registers `view` as the table named `table`, and returns self. Table
+    /// names are matched case-insensitively. If a table with the same name is
+    /// already registered, it is replaced.
+    pub fn with_view<V>(mut self, table: impl Into<String>, view: &V) -> Self
+    where
+        V: View,
+        V::Key: SqlKey,
+    {
+        let table = SqlTable {
+            name: table.into(),
+            view: view.view_name(),
+            encode_key: encode_key::<V::Key>,
+            decode_key: decode_key::<V::Key>,
+        };
+        self.tables
+            .retain(|existing| !existing.name.eq_ignore_ascii_case(&table.name));
+        self.tables.push(table);
+        self
+    }
+
+    /// Returns the names of the registered tables.
+    pub fn tables(&self) -> impl Iterator<Item = &str> {
+        self.tables.iter().map(|table| table.name.as_str())
+    }
+
+    /// Parses and executes `sql` on `connection`. The views queried are
+    /// updated before the results are returned.
+    pub fn execute<C: LowLevelConnection>(
+        &self,
+        connection: &C,
+        sql: &str,
+    ) -> Result<SqlResults, SqlError> {
+        let plan = self.plan(&sql.parse()?)?;
+        let mappings = match &plan.key {
+            PlannedKey::Nothing => Vec::new(),
+            PlannedKey::Unfiltered => connection.query_by_name(
+                &plan.table.view,
+                None,
+                plan.sort,
+                plan.limit,
+                AccessPolicy::UpdateBefore,
+                ViewReadOptions::default(),
+            )?,
+            PlannedKey::Filtered(key) => connection.query_by_name(
+                &plan.table.view,
+                Some(key.clone()),
+                plan.sort,
+                plan.limit,
+                AccessPolicy::UpdateBefore,
+                ViewReadOptions::default(),
+            )?,
+        };
+        plan.results(&mappings)
+    }
+
+    /// Parses and executes `sql` on `connection`. The views queried are
+    /// updated before the results are returned.
+    pub async fn execute_async<C: AsyncLowLevelConnection>(
+        &self,
+        connection: &C,
+        sql: &str,
+    ) -> Result<SqlResults, SqlError> {
+        let plan = self.plan(&sql.parse()?)?;
+        let mappings = match &plan.key {
+            PlannedKey::Nothing => Vec::new(),
+            PlannedKey::Unfiltered => {
+                connection
+                    .query_by_name(
+                        &plan.table.view,
+                        None,
+                        plan.sort,
+                        plan.limit,
+                        AccessPolicy::UpdateBefore,
+                        ViewReadOptions::default(),
+                    )
+                    .await?
+            }
+            PlannedKey::Filtered(key) => {
+                connection
+                    .query_by_name(
+                        &plan.table.view,
+                        Some(key.clone()),
+                        plan.sort,
+                        plan.limit,
+                        AccessPolicy::UpdateBefore,
+                        ViewReadOptions::default(),
+                    )
+                    .await?
+            }
+        };
+        plan.results(&mappings)
+    }
+
+    fn plan<'a>(&'a self, query: &SqlQuery) -> Result<QueryPlan<'a>, SqlError> {
+        let table = self
+            .tables
+            .iter()
+            .find(|table| table.name.eq_ignore_ascii_case(&query.table))
+            .ok_or_else(|| SqlError::UnknownTable(query.table.clone()))?;
+        let encode = |value: &SqlValue| -> Result<Bytes, SqlError> {
+            (table.encode_key)(value)?.ok_or_else(|| SqlError::InvalidKey {
+                table: table.name.clone(),
+                value: value.to_string(),
+            })
+        };
+
+        let mut bounds = KeyBounds::default();
+        let mut matches = None::<Vec<Bytes>>;
+        for condition in &query.conditions {
+            match condition {
+                Condition::Compare(Comparison::Equal, value) => {
+                    intersect(&mut matches, vec![encode(value)?]);
+                }
+                Condition::Compare(Comparison::Less, value) => {
+                    bounds.restrict_end(Bound::Excluded(encode(value)?));
+                }
+                Condition::Compare(Comparison::LessOrEqual, value) => {
+                    bounds.restrict_end(Bound::Included(encode(value)?));
+                }
+                Condition::Compare(Comparison::Greater, value) => {
+                    bounds.restrict_start(Bound::Excluded(encode(value)?));
+                }
+                Condition::Compare(Comparison::GreaterOrEqual, value) => {
+                    bounds.restrict_start(Bound::Included(encode(value)?));
+                }
+                Condition::Between(start, end) => {
+                    bounds.restrict_start(Bound::Included(encode(start)?));
+                    bounds.restrict_end(Bound::Included(encode(end)?));
+                }
+                Condition::In(values) => {
+                    let keys = values.iter().map(encode).collect::<Result<_, _>>()?;
+                    intersect(&mut matches, keys);
+                }
+            }
+        }
+
+        let sort = query.sort.unwrap_or(Sort::Ascending);
+        let key = match matches {
+            Some(mut keys) => {
+                keys.retain(|key| bounds.contains(key));
+                keys.sort_by(|a, b| a.as_slice().cmp(b.as_slice()));
+                keys.dedup();
+                if matches!(sort, Sort::Descending) {
+                    keys.reverse();
+                }
+                match keys.len() {
+                    0 => PlannedKey::Nothing,
+                    1 => PlannedKey::Filtered(SerializedQueryKey::Matches(keys.remove(0))),
+                    _ => PlannedKey::Filtered(SerializedQueryKey::Multiple(keys)),
+                }
+            }
+            None if bounds.is_empty() => PlannedKey::Nothing,
+            None if bounds.is_unbounded() => PlannedKey::Unfiltered,
+            None => PlannedKey::Filtered(SerializedQueryKey::Range(Range {
+                start: bounds.start,
+                end: bounds.end,
+            })),
+        };
+
+        Ok(QueryPlan {
+            table,
+            columns: query.columns.clone(),
+            key,
+            sort,
+            limit: query.limit,
+        })
+    }
+}
+
+fn encode_key<K: SqlKey>(value: &SqlValue) -> Result<Option<Bytes>, crate::Error> {
+    let Some(key) = K::from_sql(value) else {
+        return Ok(None);
+    };
+    let bytes = key
+        .as_ord_bytes()
+        .map_err(|err| crate::Error::other("key serialization", err))?;
+    Ok(Some(Bytes::from(bytes.to_vec())))
+}
+
+fn decode_key<K: SqlKey>(bytes: &[u8]) -> Result<SqlValue, crate::Error> {
+    K::from_ord_bytes(ByteCow::Borrowed(bytes))
+        .map(|key| key.to_sql())
+        .map_err(|err| crate::Error::other("key serialization", err))
+}
+
+/// Keeps only the keys in `matches` that are also in `keys`.
+fn intersect(matches: &mut Option<Vec<Bytes>>, keys: Vec<Bytes>) {
+    match matches {
+        Some(matches) => matches.retain(|key| keys.contains(key)),
+        None => *matches = Some(keys),
+    }
+}
+
+/// The range of encoded keys that satisfies a query's conditions. Because
+/// keys are encoded such that their bytes sort in the same order as the keys
+/// themselves, bounds can be compared using their encoded bytes.
+struct KeyBounds {
+    start: Bound<Bytes>,
+    end: Bound<Bytes>,
+}
+
+impl Default for KeyBounds {
+    fn default() -> Self {
+        Self {
+            start: Bound::Unbounded,
+            end: Bound::Unbounded,
+        }
+    }
+}
+
+impl KeyBounds {
+    fn restrict_start(&mut self, bound: Bound<Bytes>) {
+        let replace = match (&self.start, &bound) {
+            (Bound::Unbounded, _) => true,
+            (_, Bound::Unbounded) => false,
+            (Bound::Included(current) | Bound::Excluded(current), Bound::Included(new)) => {
+                new.as_slice() > current.as_slice()
+            }
+            (Bound::Included(current) | Bound::Excluded(current), Bound::Excluded(new)) => {
+                new.as_slice() >= current.as_slice()
+            }
+        };
+        if replace {
+            self.start = bound;
+        }
+    }
+
+    fn restrict_end(&mut self, bound: Bound<Bytes>) {
+        let replace = match (&self.end, &bound) {
+            (Bound::Unbounded, _) => true,
+            (_, Bound::Unbounded) => false,
+            (Bound::Included(current) | Bound::Excluded(current), Bound::Included(new)) => {
+                new.as_slice() < current.as_slice()
+            }
+            (Bound::Included(current) | Bound::Excluded(current), Bound::Excluded(new)) => {
+                new.as_slice() <= current.as_slice()
+            }
+        };
+        if replace {
+            self.end = bound;
+        }
+    }
+
+    fn contains(&self, key: &[u8]) -> bool {
+        let after_start = match &self.start {
+            Bound::Unbounded => true,
+            Bound::Included(start) => key >= start.as_slice(),
+            Bound::Excluded(start) => key > start.as_slice(),
+        };
+        let before_end = match &self.end {
+            Bound::Unbounded => true,
+            Bound::Included(end) => key <= end.as_slice(),
+            Bound::Excluded(end) => key < end.as_slice(),
+        };
+        after_start && before_end
+    }
+
+    fn is_unbounded(&self) -> bool {
+        matches!(
+            (&self.start, &self.end),
+            (Bound::Unbounded, Bound::Unbounded)
+        )
+    }
+
+    /// Returns true if no key can satisfy these bounds.
+    fn is_empty(&self) -> bool {
+        match (&self.start, &self.end) {
+            (Bound::Included(start), Bound::Included(end)) => start.as_slice() > end.as_slice(),
+            (
+                Bound::Included(start) | Bound::Excluded(start),
+                Bound::Included(end) | Bound::Excluded(end),
+            ) => start.as_slice() >= end.as_slice(),
+            _ => false,
+        }
+    }
+}
+
+struct QueryPlan<'a> {
+    table: &'a SqlTable,
+    columns: Vec<Column>,
+    key: PlannedKey,
+    sort: Sort,
+    limit: Option<u32>,
+}
+
+enum PlannedKey {
+    /// The conditions can't match any key.
+    Nothing,
+    /// Every entry in the view matches.
+    Unfiltered,
+    /// Only entries matching this key match.
+    Filtered(SerializedQueryKey),
+}
+
+impl<'a> QueryPlan<'a> {
+    fn results(&self, mappings: &[map::Serialized]) -> Result<SqlResults, SqlError> {
+        let rows = mappings
+            .iter()
+            .map(|mapping| {
+                self.columns
+                    .iter()
+                    .map(|column| match column {
+                        Column::Key => (self.table.decode_key)(&mapping.key),
+                        Column::Value => Ok(SqlValue::from_pot(&mapping.value)),
+                        Column::Source => Ok(SqlValue::Text(mapping.source.id.to_string())),
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(SqlResults {
+            columns: self
+                .columns
+                .iter()
+                .map(|column| column.name().to_string())
+                .collect(),
+            rows,
+        })
+    }
+}
+
+/// The results of a SQL query.
+#[derive(Serialize, Clone, Debug)]
+pub struct SqlResults {
+    /// The names of the columns in each row.
+    pub columns: Vec<String>,
+    /// The rows returned, each containing one value per column.
+    pub rows: Vec<Vec<SqlValue>>,
+}
+
+/// A value used in a SQL query or its results.
+#[derive(Serialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum SqlValue {
+    /// A missing value.
+    Null,
+    /// A boolean.
+    Boolean(bool),
+    /// A signed integer.
+    Integer(i64),
+    /// An unsigned integer that is too large to be represented as an `i64`.
+    UnsignedInteger(u64),
+    /// A string.
+    Text(String),
+    /// A sequence of bytes.
+    Bytes(Vec<u8>),
+    /// A value that has no SQL equivalent, such as a sequence or map.
+    Structured(pot::Value<'static>),
+}
+
+impl SqlValue {
+    /// Decodes `bytes` serialized using [`Pot`](pot). If `bytes` can't be
+    /// decoded, they are returned as [`SqlValue::Bytes`].
+    #[must_use]
+    pub fn from_pot(bytes: &[u8]) -> Self {
+        match pot::from_slice::<pot::Value<'_>>(bytes) {
+            Ok(pot::Value::None | pot::Value::Unit) => Self::Null,
+            Ok(pot::Value::Bool(value)) => Self::Boolean(value),
+            Ok(pot::Value::Integer(value)) => value.as_i64().map_or_else(
+                |_| {
+                    value.as_u64().map_or_else(
+                        |_| Self::Structured(pot::Value::Integer(value)),
+                        Self::UnsignedInteger,
+                    )
+                },
+                Self::Integer,
+            ),
+            Ok(pot::Value::String(value)) => Self::Text(value.into_owned()),
+            Ok(pot::Value::Bytes(value)) => Self::Bytes(value.into_owned()),
+            Ok(value) => Self::Structured(value.into_static()),
+            Err(_) => Self::Bytes(bytes.to_vec()),
+        }
+    }
+}
+
+impl Display for SqlValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Null => f.write_str("NULL"),
+            Self::Boolean(value) => Display::fmt(value, f),
+            Self::Integer(value) => Display::fmt(value, f),
+            Self::UnsignedInteger(value) => Display::fmt(value, f),
+            Self::Text(value) => f.write_str(value),
+            Self::Bytes(value) => {
+                f.write_str("X'")?;
+                for byte in value {
+                    write!(f, "{byte:02x}")?;
+                }
+                f.write_char('\'')
+            }
+            Self::Structured(value) => write!(f, "{value:?}"),
+        }
+    }
+}
+
+/// A [`Key`] that can be converted to and from a [`SqlValue`]. The keys of a
+/// view must implement this trait for the view to be registered in a
+/// [`SqlCatalog`].
+pub trait SqlKey: for<'k> Key<'k> {
+    /// Converts a literal from a query into a key, returning None if `value`
+    /// can't be represented by this type.
+    fn from_sql(value: &SqlValue) -> Option<Self>;
+
+    /// Converts this key into a value that can be returned in query results.
+    fn to_sql(&self) -> SqlValue;
+}
+
+macro_rules! impl_sql_key_for_integer {
+    ($type:ident, $variant:ident) => {
+        // Conversions to and from `i64` and `u64` are not useless for the
+        // other integer types.
+        #[allow(clippy::useless_conversion)]
+        impl SqlKey for $type {
+            fn from_sql(value: &SqlValue) -> Option<Self> {
+                match value {
+                    SqlValue::Integer(value) => Self::try_from(*value).ok(),
+                    SqlValue::UnsignedInteger(value) => Self::try_from(*value).ok(),
+                    _ => None,
+                }
+            }
+
+            fn to_sql(&self) -> SqlValue {
+                SqlValue::$variant((*self).into())
+            }
+        }
+    };
+}
+
+impl_sql_key_for_integer!(i8, Integer);
+impl_sql_key_for_integer!(i16, Integer);
+impl_sql_key_for_integer!(i32, Integer);
+impl_sql_key_for_integer!(i64, Integer);
+impl_sql_key_for_integer!(u8, Integer);
+impl_sql_key_for_integer!(u16, Integer);
+impl_sql_key_for_integer!(u32, Integer);
+
+impl SqlKey for u64 {
+    fn from_sql(value: &SqlValue) -> Option<Self> {
+        match value {
+            SqlValue::Integer(value) => Self::try_from(*value).ok(),
+            SqlValue::UnsignedInteger(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    fn to_sql(&self) -> SqlValue {
+        i64::try_from(*self).map_or(SqlValue::UnsignedInteger(*self), SqlValue::Integer)
+    }
+}
+
+impl SqlKey for bool {
+    fn from_sql(value: &SqlValue) -> Option<Self> {
+        match value {
+            SqlValue::Boolean(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    fn to_sql(&self) -> SqlValue {
+        SqlValue::Boolean(*self)
+    }
+}
+
+impl SqlKey for String {
+    fn from_sql(value: &SqlValue) -> Option<Self> {
+        match value {
+            SqlValue::Text(value) => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    fn to_sql(&self) -> SqlValue {
+        SqlValue::Text(self.clone())
+    }
+}
+
+impl SqlKey for Vec<u8> {
+    fn from_sql(value: &SqlValue) -> Option<Self> {
+        match value {
+            SqlValue::Bytes(value) => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    fn to_sql(&self) -> SqlValue {
+        SqlValue::Bytes(self.clone())
+    }
+}
+
+impl<T> SqlKey for Option<T>
+where
+    T: SqlKey,
+    Self: for<'k> Key<'k>,
+{
+    fn from_sql(value: &SqlValue) -> Option<Self> {
+        match value {
+            SqlValue::Null => Some(None),
+            value => T::from_sql(value).map(Some),
+        }
+    }
+
+    fn to_sql(&self) -> SqlValue {
+        self.as_ref().map_or(SqlValue::Null, T::to_sql)
+    }
+}
+
+/// An error from parsing or executing a SQL query.
+#[derive(thiserror::Error, Debug)]
+pub enum SqlError {
+    /// The query could not be parsed.
+    #[error("syntax error at offset {offset}: {message}")]
+    Syntax {
+        /// The byte offset in the query where the error was found.
+        offset: usize,
+        /// A description of the error.
+        message: String,
+    },
+    /// The query referenced a table that is not in the catalog.
+    #[error("unknown table: {0}")]
+    UnknownTable(String),
+    /// The query referenced a column that does not exist or can't be used in
+    /// this position.
+    #[error("unknown column: {0}")]
+    UnknownColumn(String),
+    /// A literal could not be converted to the table's key type.
+    #[error("{value} is not a valid key for table {table}")]
+    InvalidKey {
+        /// The name of the table being queried.
+        table: String,
+        /// The literal that could not be converted.
+        value: String,
+    },
+    /// An error occurred executing the query.
+    #[error(transparent)]
+    Core(#[from] crate::Error),
+}
+
+/// A parsed SQL query.
+#[derive(Clone, Debug)]
+pub struct SqlQuery {
+    columns: Vec<Column>,
+    table: String,
+    conditions: Vec<Condition>,
+    sort: Option<Sort>,
+    limit: Option<u32>,
+}
+
+impl SqlQuery {
+    /// Returns the name of the table being queried.
+    #[must_use]
+    pub fn table(&self) -> &str {
+        &self.table
+    }
+}
+
+impl FromStr for SqlQuery {
+    type Err = SqlError;
+
+    fn from_str(sql: &str) -> Result<Self, Self::Err> {
+        Parser {
+            tokens: tokenize(sql)?,
+            position: 0,
+            end: sql.len(),
+        }
+        .query()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Column {
+    Key,
+    Value,
+    Source,
+}
+
+impl Column {
+    const ALL: [Self; 3] = [Self::Key, Self::Value, Self::Source];
+
+    fn parse(name: &str) -> Result<Self, SqlError> {
+        Self::ALL
+            .into_iter()
+            .find(|column| column.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| SqlError::UnknownColumn(name.to_string()))
+    }
+
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Key => "key",
+            Self::Value => "value",
+            Self::Source => "source",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Condition {
+    Compare(Comparison, SqlValue),
+    Between(SqlValue, SqlValue),
+    In(Vec<SqlValue>),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Comparison {
+    Equal,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Word(String),
+    QuotedIdentifier(String),
+    Literal(Literal),
+    Symbol(&'static str),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Literal {
+    Integer(String),
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+const SYMBOLS: [&str; 10] = ["<=", ">=", "*", ",", "(", ")", "=", "<", ">", ";"];
+
+fn tokenize(sql: &str) -> Result<Vec<(usize, Token)>, SqlError> {
+    let mut tokens = Vec::new();
+    let mut chars = sql.char_indices().peekable();
+    while let Some(&(offset, ch)) = chars.peek() {
+        let rest = &sql[offset..];
+        if ch.is_whitespace() {
+            chars.next();
+        } else if ch == '\'' || ((ch == 'x' || ch == 'X') && rest[1..].starts_with('\'')) {
+            let is_bytes = ch != '\'';
+            if is_bytes {
+                chars.next();
+            }
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, '\'')) if matches!(chars.peek(), Some((_, '\''))) => {
+                        chars.next();
+                        text.push('\'');
+                    }
+                    Some((_, '\'')) => break,
+                    Some((_, ch)) => text.push(ch),
+                    None => return Err(syntax_error(offset, "unterminated string")),
+                }
+            }
+            let literal = if is_bytes {
+                Literal::Bytes(decode_hex(&text).ok_or_else(|| {
+                    syntax_error(offset, "byte literals must contain pairs of hex digits")
+                })?)
+            } else {
+                Literal::Text(text)
+            };
+            tokens.push((offset, Token::Literal(literal)));
+        } else if ch == '"' {
+            chars.next();
+            let mut identifier = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, '"')) => break,
+                    Some((_, ch)) => identifier.push(ch),
+                    None => return Err(syntax_error(offset, "unterminated identifier")),
+                }
+            }
+            tokens.push((offset, Token::QuotedIdentifier(identifier)));
+        } else if ch.is_ascii_digit()
+            || (ch == '-' && rest[1..].starts_with(|ch: char| ch.is_ascii_digit()))
+        {
+            let mut number = String::from(ch);
+            chars.next();
+            while let Some(&(_, ch)) = chars.peek() {
+                if !ch.is_ascii_digit() {
+                    break;
+                }
+                number.push(ch);
+                chars.next();
+            }
+            tokens.push((offset, Token::Literal(Literal::Integer(number))));
+        } else if ch.is_alphabetic() || ch == '_' {
+            let mut word = String::new();
+            while let Some(&(_, ch)) = chars.peek() {
+                if !(ch.is_alphanumeric() || ch == '_' || ch == '.' || ch == '-') {
+                    break;
+                }
+                word.push(ch);
+                chars.next();
+            }
+            tokens.push((offset, Token::Word(word)));
+        } else if let Some(symbol) = SYMBOLS.into_iter().find(|symbol| rest.starts_with(symbol)) {
+            for _ in 0..symbol.len() {
+                chars.next();
+            }
+            tokens.push((offset, Token::Symbol(symbol)));
+        } else {
+            return Err(syntax_error(offset, format!("unexpected character `{ch}`")));
+        }
+    }
+    Ok(tokens)
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&hex[index..index + 2], 16).ok())
+        .collect()
+}
+
+fn syntax_error(offset: usize, message: impl Into<String>) -> SqlError {
+    SqlError::Syntax {
+        offset,
+        message: message.into(),
+    }
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn query(mut self) -> Result<SqlQuery, SqlError> {
+        self.expect_keyword("SELECT")?;
+        let columns = if self.next_if_symbol("*") {
+            Column::ALL.to_vec()
+        } else {
+            let mut columns = vec![Column::parse(&self.identifier()?)?];
+            while self.next_if_symbol(",") {
+                columns.push(Column::parse(&self.identifier()?)?);
+            }
+            columns
+        };
+
+        self.expect_keyword("FROM")?;
+        let table = self.identifier()?;
+
+        let mut conditions = Vec::new();
+        if self.next_if_keyword("WHERE") {
+            conditions.push(self.condition()?);
+            while self.next_if_keyword("AND") {
+                conditions.push(self.condition()?);
+            }
+        }
+
+        let mut sort = None;
+        if self.next_if_keyword("ORDER") {
+            self.expect_keyword("BY")?;
+            self.key_column()?;
+            sort = Some(if self.next_if_keyword("DESC") {
+                Sort::Descending
+            } else {
+                self.next_if_keyword("ASC");
+                Sort::Ascending
+            });
+        }
+
+        let mut limit = None;
+        if self.next_if_keyword("LIMIT") {
+            let offset = self.offset();
+            match self.next() {
+                Some(Token::Literal(Literal::Integer(value))) => {
+                    limit = Some(value.parse().map_err(|_| {
+                        syntax_error(offset, "LIMIT must be a non-negative 32-bit integer")
+                    })?);
+                }
+                _ => return Err(syntax_error(offset, "expected a number after LIMIT")),
+            }
+        }
+
+        self.next_if_symbol(";");
+        if self.position < self.tokens.len() {
+            return Err(syntax_error(self.offset(), "unexpected input"));
+        }
+
+        Ok(SqlQuery {
+            columns,
+            table,
+            conditions,
+            sort,
+            limit,
+        })
+    }
+
+    fn condition(&mut self) -> Result<Condition, SqlError> {
+        self.key_column()?;
+        if self.next_if_keyword("BETWEEN") {
+            let start = self.literal()?;
+            self.expect_keyword("AND")?;
+            let end = self.literal()?;
+            return Ok(Condition::Between(start, end));
+        } else if self.next_if_keyword("IN") {
+            self.expect_symbol("(")?;
+            let mut values = vec![self.literal()?];
+            while self.next_if_symbol(",") {
+                values.push(self.literal()?);
+            }
+            self.expect_symbol(")")?;
+            return Ok(Condition::In(values));
+        }
+
+        let offset = self.offset();
+        let comparison = match self.next() {
+            Some(Token::Symbol("=")) => Comparison::Equal,
+            Some(Token::Symbol("<")) => Comparison::Less,
+            Some(Token::Symbol("<=")) => Comparison::LessOrEqual,
+            Some(Token::Symbol(">")) => Comparison::Greater,
+            Some(Token::Symbol(">=")) => Comparison::GreaterOrEqual,
+            _ => {
+                return Err(syntax_error(
+                    offset,
+                    "expected =, <, <=, >, >=, BETWEEN, or IN",
+                ))
+            }
+        };
+        Ok(Condition::Compare(comparison, self.literal()?))
+    }
+
+    /// Parses a column name that must be `key`.
+    fn key_column(&mut self) -> Result<(), SqlError> {
+        let column = self.identifier()?;
+        if Column::parse(&column)? == Column::Key {
+            Ok(())
+        } else {
+            Err(SqlError::UnknownColumn(column))
+        }
+    }
+
+    fn literal(&mut self) -> Result<SqlValue, SqlError> {
+        let offset = self.offset();
+        match self.next() {
+            Some(Token::Literal(Literal::Integer(value))) => value
+                .parse::<i64>()
+                .map(SqlValue::Integer)
+                .or_else(|_| value.parse::<u64>().map(SqlValue::UnsignedInteger))
+                .map_err(|_| syntax_error(offset, "integer out of range")),
+            Some(Token::Literal(Literal::Text(value))) => Ok(SqlValue::Text(value)),
+            Some(Token::Literal(Literal::Bytes(value))) => Ok(SqlValue::Bytes(value)),
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case("NULL") => Ok(SqlValue::Null),
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case("TRUE") => {
+                Ok(SqlValue::Boolean(true))
+            }
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case("FALSE") => {
+                Ok(SqlValue::Boolean(false))
+            }
+            _ => Err(syntax_error(offset, "expected a literal")),
+        }
+    }
+
+    fn identifier(&mut self) -> Result<String, SqlError> {
+        let offset = self.offset();
+        match self.next() {
+            Some(Token::Word(word) | Token::QuotedIdentifier(word)) => Ok(word),
+            _ => Err(syntax_error(offset, "expected an identifier")),
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), SqlError> {
+        if self.next_if_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(syntax_error(self.offset(), format!("expected {keyword}")))
+        }
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<(), SqlError> {
+        if self.next_if_symbol(symbol) {
+            Ok(())
+        } else {
+            Err(syntax_error(self.offset(), format!("expected `{symbol}`")))
+        }
+    }
+
+    fn next_if_keyword(&mut self, keyword: &str) -> bool {
+        let matched = matches!(
+            self.tokens.get(self.position),
+            Some((_, Token::Word(word))) if word.eq_ignore_ascii_case(keyword)
+        );
+        if matched {
+            self.position += 1;
+        }
+        matched
+    }
+
+    fn next_if_symbol(&mut self, symbol: &str) -> bool {
+        let matched = matches!(
+            self.tokens.get(self.position),
+            Some((_, Token::Symbol(next))) if *next == symbol
+        );
+        if matched {
+            self.position += 1;
+        }
+        matched
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let (_, token) = self.tokens.get(self.position)?;
+        self.position += 1;
+        Some(token.clone())
+    }
+
+    /// Returns the offset of the next token, or the end of the query.
+    fn offset(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .map_or(self.end, |(offset, _)| *offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{BasicByCategory, BasicByParentId};
+
+    fn catalog() -> SqlCatalog {
+        SqlCatalog::default()
+            .with_view("by_category", &BasicByCategory)
+            .with_view("by_parent_id", &BasicByParentId)
+    }
+
+    fn plan_key(sql: &str) -> Result<PlannedKey, SqlError> {
+        let catalog = catalog();
+        let plan = catalog.plan(&sql.parse()?)?;
+        Ok(plan.key)
+    }
+
+    fn encoded(key: Option<u64>) -> Bytes {
+        encode_key::<Option<u64>>(&key.map_or(SqlValue::Null, |key| key.to_sql()))
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn parsing() {
+        let query = "select key, SOURCE from By_Category where key >= 'a' and key < 'b' order by key desc limit 5;"
+            .parse::<SqlQuery>()
+            .unwrap();
+        assert_eq!(query.table(), "By_Category");
+        assert_eq!(query.columns, vec![Column::Key, Column::Source]);
+        assert_eq!(query.conditions.len(), 2);
+        assert!(matches!(query.sort, Some(Sort::Descending)));
+        assert_eq!(query.limit, Some(5));
+
+        let query = "SELECT * FROM \"by category\" WHERE key IN (1, -2, X'00ff', NULL)"
+            .parse::<SqlQuery>()
+            .unwrap();
+        assert_eq!(query.table(), "by category");
+        assert_eq!(query.columns, Column::ALL.to_vec());
+        assert!(matches!(
+            &query.conditions[0],
+            Condition::In(values) if matches!(
+                values.as_slice(),
+                [
+                    SqlValue::Integer(1),
+                    SqlValue::Integer(-2),
+                    SqlValue::Bytes(bytes),
+                    SqlValue::Null,
+                ] if bytes == &[0, 255]
+            )
+        ));
+
+        assert!(matches!(
+            "SELECT * FROM by_category WHERE value = 1".parse::<SqlQuery>(),
+            Err(SqlError::UnknownColumn(column)) if column == "value"
+        ));
+        assert!(matches!(
+            "SELECT * FROM by_category LIMIT".parse::<SqlQuery>(),
+            Err(SqlError::Syntax { offset: 31, .. })
+        ));
+        assert!(matches!(
+            "SELECT * FROM by_category WHERE key = 'a".parse::<SqlQuery>(),
+            Err(SqlError::Syntax { offset: 38, .. })
+        ));
+        assert!(matches!(
+            "DELETE FROM by_category".parse::<SqlQuery>(),
+            Err(SqlError::Syntax { offset: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn planning() {
+        assert!(matches!(
+            plan_key("SELECT * FROM by_parent_id").unwrap(),
+            PlannedKey::Unfiltered
+        ));
+        assert!(matches!(
+            plan_key("SELECT * FROM BY_PARENT_ID WHERE key = 1").unwrap(),
+            PlannedKey::Filtered(SerializedQueryKey::Matches(key)) if key == encoded(Some(1))
+        ));
+        assert!(matches!(
+            plan_key("SELECT * FROM by_parent_id WHERE key IN (3, 1, 2) AND key > 1 ORDER BY key DESC").unwrap(),
+            PlannedKey::Filtered(SerializedQueryKey::Multiple(keys))
+                if keys == vec![encoded(Some(3)), encoded(Some(2))]
+        ));
+        assert!(matches!(
+            plan_key("SELECT * FROM by_parent_id WHERE key BETWEEN 1 AND 5 AND key < 4 AND key >= 0").unwrap(),
+            PlannedKey::Filtered(SerializedQueryKey::Range(Range {
+                start: Bound::Included(start),
+                end: Bound::Excluded(end),
+            })) if start == encoded(Some(1)) && end == encoded(Some(4))
+        ));
+        assert!(matches!(
+            plan_key("SELECT * FROM by_parent_id WHERE key = 1 AND key = 2").unwrap(),
+            PlannedKey::Nothing
+        ));
+        assert!(matches!(
+            plan_key("SELECT * FROM by_parent_id WHERE key > 2 AND key <= 2").unwrap(),
+            PlannedKey::Nothing
+        ));
+
+        assert!(matches!(
+            plan_key("SELECT * FROM missing"),
+            Err(SqlError::UnknownTable(table)) if table == "missing"
+        ));
+        assert!(matches!(
+            plan_key("SELECT * FROM by_category WHERE key = 1"),
+            Err(SqlError::InvalidKey { .. })
+        ));
+        assert!(matches!(
+            plan_key("SELECT * FROM by_parent_id WHERE key = -1"),
+            Err(SqlError::InvalidKey { .. })
+        ));
+    }
+}
@@ -14,31 +14,39 @@ use transmog_pot::Pot;
 
 use crate::admin::{PermissionGroup, Role, User};
 use crate::connection::{
-    AccessPolicy, AsyncConnection, AsyncStorageConnection, Connection, StorageConnection,
+    AccessPolicy, AsyncConnection, AsyncStorageConnection, Connection, DatabaseQuery,
+    StorageConnection,
 };
+use crate::crdt::{Counter, Crdt, CrdtMap, Sequence};
 use crate::document::{
     BorrowedDocument, CollectionDocument, CollectionHeader, DocumentId, Emit, Header, KeyId,
 };
-use crate::keyvalue::{AsyncKeyValue, KeyValue};
+use crate::key::geo::GeoPoint;
+use crate::keyvalue::{AsyncKeyValue, KeyValue, Timestamp};
 use crate::limits::{LIST_TRANSACTIONS_DEFAULT_RESULT_COUNT, LIST_TRANSACTIONS_MAX_RESULTS};
+use crate::permissions::bonsai::{bonsaidb_resource_name, BonsaiAction, ServerAction};
+use crate::permissions::Statement;
+use crate::schema::view::aggregation::{AggregatedGroup, Aggregation};
+use crate::schema::view::join::JoinedView;
 use crate::schema::view::map::{Mappings, ViewMappedValue};
-use crate::schema::view::{ReduceResult, ViewSchema};
+use crate::schema::view::{ReduceResult, ViewMaterialization, ViewSchema};
 use crate::schema::{
-    Collection, CollectionName, MappedValue, NamedCollection, Qualified, Schema, SchemaName,
-    Schematic, SerializedCollection, View, ViewMapResult,
+    Collection, CollectionName, CollectionViewSchema, IdAssignment, MappedValue, Name,
+    NamedCollection, NamedReference, Projection, Qualified, Schema, SchemaName, Schematic,
+    SerializedCollection, TimeSeries, View, ViewMapResult,
 };
 use crate::transaction::{Operation, Transaction};
-use crate::Error;
 #[cfg(feature = "token-authentication")]
 use crate::{
     admin::AuthenticationToken,
     connection::{HasSession, Identity, IdentityReference, Session},
 };
+use crate::{Error, ErrorCode};
 
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Default, Clone, Collection)]
 // This collection purposely uses names with characters that need
 // escaping, since it's used in backup/restore.
-#[collection(name = "_basic", authority = "khonsulabs_", views = [BasicCount, BasicByParentId, BasicByParentIdEager, BasicByTag, BasicByCategory], core = crate)]
+#[collection(name = "_basic", authority = "khonsulabs_", views = [BasicCount, BasicByParentId, BasicByParentIdEager, BasicByTag, BasicByCategory, BasicTagPositions], joins = [BasicTagsAndCategories], projections = [BasicValues], core = crate)]
 #[must_use]
 pub struct Basic {
     pub value: String,
@@ -116,6 +124,11 @@ impl ViewSchema for BasicByParentId {
     ) -> ReduceResult<Self::View> {
         Ok(mappings.iter().map(|map| map.value).sum())
     }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn aggregate_value(&self, value: &usize) -> Result<f64, Error> {
+        Ok(*value as f64)
+    }
 }
 
 #[derive(Debug, Clone, View)]
@@ -199,6 +212,56 @@ impl ViewSchema for BasicByTag {
     }
 }
 
+/// Emits each distinct tag of a document once, with the position the tag last
+/// appears at in the document's tags.
+#[derive(Debug, Clone, View)]
+#[view(collection = Basic, key = String, value = usize, name = "tag-positions", core = crate)]
+pub struct BasicTagPositions;
+
+impl ViewSchema for BasicTagPositions {
+    type View = Self;
+
+    fn map(&self, document: &BorrowedDocument<'_>) -> ViewMapResult<Self::View> {
+        let contents = Basic::document_contents(document)?;
+        let mut emitter = document.header.emitter()?;
+        for (position, tag) in contents.tags.into_iter().enumerate() {
+            emitter.emit(tag, position);
+        }
+        Ok(emitter.into_mappings())
+    }
+}
+
+/// Joins the documents tagged with a name to the documents whose category has
+/// the same name.
+#[derive(Debug, Clone)]
+pub struct BasicTagsAndCategories;
+
+impl JoinedView for BasicTagsAndCategories {
+    type Left = BasicByTag;
+    type Right = BasicByCategory;
+
+    fn name(&self) -> Name {
+        Name::new("tags-and-categories")
+    }
+}
+
+/// Projects the documents of [`Basic`] to their values.
+#[derive(Debug, Clone)]
+pub struct BasicValues;
+
+impl Projection for BasicValues {
+    type Collection = Basic;
+    type Output = String;
+
+    fn name(&self) -> Name {
+        Name::new("values")
+    }
+
+    fn project(&self, contents: Basic) -> Self::Output {
+        contents.value
+    }
+}
+
 #[derive(Debug, Clone, View)]
 #[view(collection = Basic, key = (), value = (), name = "by-parent-id", core = crate)]
 pub struct BasicByBrokenParentId;
@@ -432,6 +495,163 @@ impl Collection for BasicCollectionWithOnlyBrokenParentId {
     }
 }
 
+#[derive(Debug)]
+pub struct BasicCollectionWithMaterializedViews;
+
+impl Collection for BasicCollectionWithMaterializedViews {
+    type PrimaryKey = u64;
+
+    fn collection_name() -> CollectionName {
+        Basic::collection_name()
+    }
+
+    fn define_views(schema: &mut Schematic) -> Result<(), Error> {
+        schema.define_view(MaterializedBasicCount)?;
+        schema.define_view(MaterializedBasicByParentId)
+    }
+}
+
+impl SerializedCollection for BasicCollectionWithMaterializedViews {
+    type Contents = Basic;
+    type Format = Pot;
+
+    fn format() -> Self::Format {
+        Pot::default()
+    }
+}
+
+#[derive(Debug, Clone, View)]
+#[view(collection = BasicCollectionWithMaterializedViews, key = (), value = usize, name = "materialized-count", core = crate)]
+pub struct MaterializedBasicCount;
+
+impl ViewSchema for MaterializedBasicCount {
+    type View = Self;
+
+    fn materialization(&self) -> ViewMaterialization {
+        ViewMaterialization::Total
+    }
+
+    fn map(&self, document: &BorrowedDocument<'_>) -> ViewMapResult<Self::View> {
+        document.header.emit_key_and_value((), 1)
+    }
+
+    fn reduce(
+        &self,
+        mappings: &[ViewMappedValue<Self::View>],
+        _rereduce: bool,
+    ) -> ReduceResult<Self::View> {
+        Ok(mappings.iter().map(|map| map.value).sum())
+    }
+}
+
+#[derive(Debug, Clone, View)]
+#[view(collection = BasicCollectionWithMaterializedViews, key = Option<u64>, value = usize, name = "materialized-by-parent-id", core = crate)]
+pub struct MaterializedBasicByParentId;
+
+impl ViewSchema for MaterializedBasicByParentId {
+    type View = Self;
+
+    fn materialization(&self) -> ViewMaterialization {
+        ViewMaterialization::Grouped
+    }
+
+    fn map(&self, document: &BorrowedDocument<'_>) -> ViewMapResult<Self::View> {
+        let contents = Basic::document_contents(document)?;
+        document.header.emit_key_and_value(contents.parent_id, 1)
+    }
+
+    fn reduce(
+        &self,
+        mappings: &[ViewMappedValue<Self::View>],
+        _rereduce: bool,
+    ) -> ReduceResult<Self::View> {
+        Ok(mappings.iter().map(|map| map.value).sum())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Collection)]
+#[collection(name = "measurements", authority = "khonsulabs", views = [MeasurementsByMinute], core = crate)]
+#[collection(time_series = TimeSeries::partitioned_by(Duration::from_secs(60)).retain_for(Duration::from_secs(60 * 60)))]
+pub struct Measurement {
+    pub value: u64,
+}
+
+#[derive(Debug, Clone, View)]
+#[view(collection = Measurement, key = Timestamp, value = u64, name = "by-minute", core = crate)]
+pub struct MeasurementsByMinute;
+
+impl CollectionViewSchema for MeasurementsByMinute {
+    type View = Self;
+
+    fn map(&self, document: CollectionDocument<Measurement>) -> ViewMapResult<Self::View> {
+        document.header.emit_key_and_value(
+            document.header.id.truncate(Duration::from_secs(60)),
+            document.contents.value,
+        )
+    }
+
+    fn reduce(
+        &self,
+        mappings: &[ViewMappedValue<Self::View>],
+        _rereduce: bool,
+    ) -> ReduceResult<Self::View> {
+        Ok(mappings.iter().map(|map| map.value).sum())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Collection)]
+#[collection(name = "landmarks", authority = "khonsulabs", views = [LandmarksByLocation], core = crate)]
+pub struct Landmark {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub visitors: u64,
+}
+
+#[derive(Debug, Clone, View)]
+#[view(collection = Landmark, key = GeoPoint, value = u64, name = "by-location", core = crate)]
+pub struct LandmarksByLocation;
+
+impl CollectionViewSchema for LandmarksByLocation {
+    type View = Self;
+
+    fn map(&self, document: CollectionDocument<Landmark>) -> ViewMapResult<Self::View> {
+        let location = GeoPoint::new(document.contents.latitude, document.contents.longitude)
+            .map_err(|err| Error::other("geo", err))?;
+        document
+            .header
+            .emit_key_and_value(location, document.contents.visitors)
+    }
+
+    fn reduce(
+        &self,
+        mappings: &[ViewMappedValue<Self::View>],
+        _rereduce: bool,
+    ) -> ReduceResult<Self::View> {
+        Ok(mappings.iter().map(|map| map.value).sum())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Collection)]
+#[collection(name = "tickets", authority = "khonsulabs", primary_key = u128, core = crate)]
+#[collection(id_assignment = IdAssignment::Random)]
+pub struct Ticket {
+    pub holder: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Collection)]
+#[collection(name = "settings", authority = "khonsulabs", versioned = false, core = crate)]
+pub struct Setting {
+    pub value: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Collection, Crdt)]
+#[collection(name = "scoreboards", authority = "khonsulabs", crdt, core = crate)]
+#[crdt(core = crate)]
+pub struct Scoreboard {
+    pub scores: CrdtMap<String, Counter>,
+    pub log: Sequence<String>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, Collection)]
 #[collection(name = "unassociated", authority = "khonsulabs", core = crate)]
 pub struct UnassociatedCollection;
@@ -474,6 +694,8 @@ pub enum HarnessTest {
     KvExpiration,
     KvDeleteExpire,
     KvTransactions,
+    KvNamespaces,
+    KvMultiple,
 }
 
 impl HarnessTest {
@@ -1286,9 +1508,61 @@ pub async fn conflict_tests<C: AsyncConnection>(db: &C) -> anyhow::Result<()> {
     let doc = Basic::get_async(&doc.header.id, db).await?.unwrap();
     assert_eq!(doc.contents.value, "modify worked");
 
+    // Modify two documents in one transaction based on their contents.
+    let first = collection.push(&Basic::new("first")).await?;
+    let second = collection.push(&Basic::new("second")).await?;
+    let documents = [
+        (Basic::collection_name(), DocumentId::new(&first.id)?),
+        (Basic::collection_name(), DocumentId::new(&second.id)?),
+    ];
+    let results = Transaction::modify_documents_async(db, &documents, swap_basic_values).await?;
+    assert_eq!(results.len(), 2);
+    assert_eq!(
+        Basic::get_async(&first.id, db)
+            .await?
+            .unwrap()
+            .contents
+            .value,
+        "second"
+    );
+    assert_eq!(
+        Basic::get_async(&second.id, db)
+            .await?
+            .unwrap()
+            .contents
+            .value,
+        "first"
+    );
+
     Ok(())
 }
 
+/// Swaps the values of two [`Basic`] documents.
+fn swap_basic_values(
+    documents: &[Option<crate::document::OwnedDocument>],
+) -> Result<Transaction, Error> {
+    let [Some(first), Some(second)] = documents else {
+        unreachable!("both documents exist")
+    };
+    let first_value = Basic::document_contents(first)?;
+    let second_value = Basic::document_contents(second)?;
+    let mut first = first.clone();
+    let mut second = second.clone();
+    Basic::set_document_contents(&mut first, second_value)?;
+    Basic::set_document_contents(&mut second, first_value)?;
+    Ok(Transaction::new()
+        .with(Operation::update(
+            Basic::collection_name(),
+            first.header,
+            first.contents,
+        ))
+        .with(Operation::update(
+            Basic::collection_name(),
+            second.header,
+            second.contents,
+        )))
+}
+
 pub fn blocking_conflict_tests<C: Connection>(db: &C) -> anyhow::Result<()> {
     let original_value = Basic::new("initial_value");
     let collection = db.collection::<Basic>();
@@ -1321,6 +1595,32 @@ pub fn blocking_conflict_tests<C: Connection>(db: &C) -> anyhow::Result<()> {
     doc.modify(db, |doc| {
         doc.contents.value = String::from("modify worked");
     })?;
+
+    // Modify two documents in one transaction based on their contents,
+    // updating one of them while the modifier is executing to force a retry.
+    let first = collection.push(&Basic::new("first"))?;
+    let second = collection.push(&Basic::new("second"))?;
+    let documents = [
+        (Basic::collection_name(), DocumentId::new(&first.id)?),
+        (Basic::collection_name(), DocumentId::new(&second.id)?),
+    ];
+    let mut attempts = 0;
+    let results = Transaction::modify_documents(db, &documents, |documents| {
+        attempts += 1;
+        if attempts == 1 {
+            let mut first = Basic::get(&first.id, db)?.unwrap();
+            first.contents.value = String::from("changed");
+            first.update(db)?;
+        }
+        swap_basic_values(documents)
+    })?;
+    assert_eq!(attempts, 2);
+    assert_eq!(results.len(), 2);
+    assert_eq!(Basic::get(&first.id, db)?.unwrap().contents.value, "second");
+    assert_eq!(
+        Basic::get(&second.id, db)?.unwrap().contents.value,
+        "changed"
+    );
     assert_eq!(doc.contents.value, "modify worked");
     let doc = Basic::get(&doc.header.id, db)?.unwrap();
     assert_eq!(doc.contents.value, "modify worked");
@@ -1466,6 +1766,14 @@ pub async fn list_tests<C: AsyncConnection>(db: &C) -> anyhow::Result<()> {
 
     assert_eq!(both_headers.len(), 2);
 
+    let both_values = Basic::list_async(doc1.id..=doc2.id, db)
+        .projected::<BasicValues>()
+        .await?;
+    assert_eq!(both_values.len(), 2);
+    assert_eq!(both_values[0].header, doc1);
+    assert_eq!(both_values[0].contents, doc1_value.value);
+    assert_eq!(both_values[1].contents, doc2_value.value);
+
     let one_doc = Basic::list_async(doc1.id..doc2.id, db).await?;
     assert_eq!(one_doc.len(), 1);
 
@@ -1502,6 +1810,12 @@ pub fn blocking_list_tests<C: Connection>(db: &C) -> anyhow::Result<()> {
 
     assert_eq!(both_headers.len(), 2);
 
+    let both_values = Basic::list(doc1.id..=doc2.id, db).projected::<BasicValues>()?;
+    assert_eq!(both_values.len(), 2);
+    assert_eq!(both_values[0].header, doc1);
+    assert_eq!(both_values[0].contents, doc1_value.value);
+    assert_eq!(both_values[1].contents, doc2_value.value);
+
     let one_doc = Basic::list(doc1.id..doc2.id, db).query()?;
     assert_eq!(one_doc.len(), 1);
 
@@ -1659,6 +1973,7 @@ pub async fn transaction_check_tests<C: AsyncConnection + 'static>(db: &C) -> an
     tx.push(Operation::check_document_exists::<Basic>(&42)?);
     let result = tx.apply_async(db).await.unwrap_err();
     assert!(matches!(result, Error::DocumentConflict(_, _)));
+    assert_eq!(result.code(), ErrorCode::DocumentConflict);
 
     let mut tx = Transaction::new();
     tx.push(Operation::check_document_exists::<Basic>(&42)?);
@@ -1740,6 +2055,16 @@ pub async fn view_query_tests<C: AsyncConnection>(db: &C) -> anyhow::Result<()>
     assert_eq!(a_children.len(), 1);
     assert_eq!(a_children.get(0).unwrap().document.header, a_child);
 
+    let a_children = db
+        .view::<BasicByParentId>()
+        .with_key(&Some(a.id))
+        .query_with_projected_docs::<BasicValues>()
+        .await?;
+    assert_eq!(a_children.len(), 1);
+    let a_child_value = a_children.get(0).unwrap().document;
+    assert_eq!(a_child_value.header, a_child);
+    assert_eq!(a_child_value.contents, "A.1");
+
     let b_children = db
         .view::<BasicByParentId>()
         .with_key(&Some(b.id))
@@ -1787,6 +2112,60 @@ pub async fn view_query_tests<C: AsyncConnection>(db: &C) -> anyhow::Result<()>
     let items_with_categories = db.view::<BasicByCategory>().query().await?;
     assert_eq!(items_with_categories.len(), 3);
 
+    // Test counting
+    assert_eq!(db.view::<BasicByParentId>().count().await?, 5);
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .with_key_range(Some(0)..=Some(u64::MAX))
+            .count()
+            .await?,
+        has_parent.len() as u64
+    );
+
+    // Test aggregations
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .aggregate(&[Aggregation::Count, Aggregation::Sum, Aggregation::Max])
+            .await?,
+        vec![Some(5.), Some(5.), Some(1.)]
+    );
+    let children_by_parent = db
+        .view::<BasicByParentId>()
+        .with_key_range(Some(0)..=Some(u64::MAX))
+        .aggregate_grouped(&[Aggregation::Count])
+        .await?;
+    assert_eq!(
+        children_by_parent,
+        vec![
+            AggregatedGroup {
+                key: Some(a.id),
+                values: vec![Some(1.)]
+            },
+            AggregatedGroup {
+                key: Some(b.id),
+                values: vec![Some(2.)]
+            }
+        ]
+    );
+    // Option keys are prefixed with a byte indicating whether they are Some.
+    let by_has_parent = db
+        .view::<BasicByParentId>()
+        .aggregate_grouped_by_prefix::<u8>(&[Aggregation::Count])
+        .await?;
+    assert_eq!(
+        by_has_parent
+            .iter()
+            .map(|group| (group.key, group.values[0]))
+            .collect::<Vec<_>>(),
+        vec![(0, Some(2.)), (1, Some(3.))]
+    );
+    assert!(matches!(
+        db.view::<BasicByCategory>()
+            .aggregate(&[Aggregation::Sum])
+            .await,
+        Err(Error::AggregationUnimplemented)
+    ));
+
     // Test deleting
     let deleted_count = db
         .view::<BasicByParentId>()
@@ -1828,6 +2207,15 @@ pub fn blocking_view_query_tests<C: Connection>(db: &C) -> anyhow::Result<()> {
     assert_eq!(a_children.len(), 1);
     assert_eq!(a_children.get(0).unwrap().document.header, a_child);
 
+    let a_children = db
+        .view::<BasicByParentId>()
+        .with_key(&Some(a.id))
+        .query_with_projected_docs::<BasicValues>()?;
+    assert_eq!(a_children.len(), 1);
+    let a_child_value = a_children.get(0).unwrap().document;
+    assert_eq!(a_child_value.header, a_child);
+    assert_eq!(a_child_value.contents, "A.1");
+
     let b_children = db.view::<BasicByParentId>().with_key(&Some(b.id)).query()?;
     assert_eq!(b_children.len(), 2);
 
@@ -1867,6 +2255,57 @@ pub fn blocking_view_query_tests<C: Connection>(db: &C) -> anyhow::Result<()> {
     let items_with_categories = db.view::<BasicByCategory>().query()?;
     assert_eq!(items_with_categories.len(), 3);
 
+    // Test counting
+    assert_eq!(db.view::<BasicByParentId>().count()?, 5);
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .with_key_range(Some(0)..=Some(u64::MAX))
+            .count()?,
+        has_parent.len() as u64
+    );
+
+    // Test aggregations
+    assert_eq!(
+        db.view::<BasicByParentId>().aggregate(&[
+            Aggregation::Count,
+            Aggregation::Sum,
+            Aggregation::Max
+        ])?,
+        vec![Some(5.), Some(5.), Some(1.)]
+    );
+    let children_by_parent = db
+        .view::<BasicByParentId>()
+        .with_key_range(Some(0)..=Some(u64::MAX))
+        .aggregate_grouped(&[Aggregation::Count])?;
+    assert_eq!(
+        children_by_parent,
+        vec![
+            AggregatedGroup {
+                key: Some(a.id),
+                values: vec![Some(1.)]
+            },
+            AggregatedGroup {
+                key: Some(b.id),
+                values: vec![Some(2.)]
+            }
+        ]
+    );
+    // Option keys are prefixed with a byte indicating whether they are Some.
+    let by_has_parent = db
+        .view::<BasicByParentId>()
+        .aggregate_grouped_by_prefix::<u8>(&[Aggregation::Count])?;
+    assert_eq!(
+        by_has_parent
+            .iter()
+            .map(|group| (group.key, group.values[0]))
+            .collect::<Vec<_>>(),
+        vec![(0, Some(2.)), (1, Some(3.))]
+    );
+    assert!(matches!(
+        db.view::<BasicByCategory>().aggregate(&[Aggregation::Sum]),
+        Err(Error::AggregationUnimplemented)
+    ));
+
     // Test deleting
     let deleted_count = db
         .view::<BasicByParentId>()
@@ -2013,6 +2452,37 @@ pub async fn view_update_tests<C: AsyncConnection>(db: &C) -> anyhow::Result<()>
         0
     );
     assert_eq!(db.view::<BasicByParentId>().reduce().await?, 3);
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .reduce_for_keys(&[Some(a.id), Some(b.id)])
+            .await?,
+        1
+    );
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .reduce_for_documents(&[a.id, b.id])
+            .await?,
+        2
+    );
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .with_key(&None::<u64>)
+            .reduce_for_documents(&[a.id, a_child.id])
+            .await?,
+        1
+    );
+    let a_child_mappings = db
+        .view::<BasicByParentId>()
+        .for_document(&a_child.id)
+        .await?;
+    assert_eq!(a_child_mappings.len(), 1);
+    assert_eq!(a_child_mappings[0].key, Some(b.id));
+    assert_eq!(a_child_mappings[0].source, doc.header);
+    assert!(db
+        .view::<BasicByParentId>()
+        .for_document(&u64::MAX)
+        .await?
+        .is_empty());
 
     // Update the record, but don't change its mapping. Ensure the source's
     // header is updated.
@@ -2121,6 +2591,30 @@ pub fn blocking_view_update_tests<C: Connection>(db: &C) -> anyhow::Result<()> {
         0
     );
     assert_eq!(db.view::<BasicByParentId>().reduce()?, 3);
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .reduce_for_keys(&[Some(a.id), Some(b.id)])?,
+        1
+    );
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .reduce_for_documents(&[a.id, b.id])?,
+        2
+    );
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .with_key(&None::<u64>)
+            .reduce_for_documents(&[a.id, a_child.id])?,
+        1
+    );
+    let a_child_mappings = db.view::<BasicByParentId>().for_document(&a_child.id)?;
+    assert_eq!(a_child_mappings.len(), 1);
+    assert_eq!(a_child_mappings[0].key, Some(b.id));
+    assert_eq!(a_child_mappings[0].source, doc.header);
+    assert!(db
+        .view::<BasicByParentId>()
+        .for_document(&u64::MAX)?
+        .is_empty());
 
     // Update the record, but don't change its mapping. Ensure the source's
     // header is updated.
@@ -2245,6 +2739,49 @@ pub async fn view_multi_emit_tests<C: AsyncConnection>(db: &C) -> anyhow::Result
         1
     );
 
+    // Join the tags to a document with a matching category.
+    let c = Basic::new("C")
+        .with_category("Blue")
+        .push_into_async(db)
+        .await?;
+    let joined = db.joined_view::<BasicTagsAndCategories>().query().await?;
+    assert_eq!(joined.len(), 1);
+    assert_eq!(joined[0].key, "blue");
+    assert_eq!(joined[0].left.len(), 1);
+    assert_eq!(
+        joined[0].left[0].source.id.deserialize::<u64>()?,
+        a.header.id
+    );
+    assert_eq!(joined[0].right.len(), 1);
+    assert_eq!(
+        joined[0].right[0].source.id.deserialize::<u64>()?,
+        c.header.id
+    );
+
+    // Removing the tag removes the key from the join.
+    a.contents.tags.clear();
+    a.update_async(db).await?;
+    assert!(db
+        .joined_view::<BasicTagsAndCategories>()
+        .query()
+        .await?
+        .is_empty());
+
+    // An emitter emits a repeated tag once, keeping its last position.
+    let d = Basic::new("D")
+        .with_tag("red")
+        .with_tag("blue")
+        .with_tag("red")
+        .push_into_async(db)
+        .await?;
+    let positions = db.view::<BasicTagPositions>().query().await?;
+    assert_eq!(positions.len(), 2);
+    assert_eq!(positions[0].key, "blue");
+    assert_eq!(positions[0].value, 1);
+    assert_eq!(positions[1].key, "red");
+    assert_eq!(positions[1].value, 2);
+    assert_eq!(positions[1].source.id.deserialize::<u64>()?, d.header.id);
+
     Ok(())
 }
 
@@ -2284,6 +2821,44 @@ pub fn blocking_view_multi_emit_tests<C: Connection>(db: &C) -> anyhow::Result<(
 
     assert_eq!(db.view::<BasicByTag>().with_key("blue").query()?.len(), 1);
 
+    // Join the tags to a document with a matching category.
+    let c = Basic::new("C").with_category("Blue").push_into(db)?;
+    let joined = db.joined_view::<BasicTagsAndCategories>().query()?;
+    assert_eq!(joined.len(), 1);
+    assert_eq!(joined[0].key, "blue");
+    assert_eq!(joined[0].left.len(), 1);
+    assert_eq!(
+        joined[0].left[0].source.id.deserialize::<u64>()?,
+        a.header.id
+    );
+    assert_eq!(joined[0].right.len(), 1);
+    assert_eq!(
+        joined[0].right[0].source.id.deserialize::<u64>()?,
+        c.header.id
+    );
+
+    // Removing the tag removes the key from the join.
+    a.contents.tags.clear();
+    a.update(db)?;
+    assert!(db
+        .joined_view::<BasicTagsAndCategories>()
+        .query()?
+        .is_empty());
+
+    // An emitter emits a repeated tag once, keeping its last position.
+    let d = Basic::new("D")
+        .with_tag("red")
+        .with_tag("blue")
+        .with_tag("red")
+        .push_into(db)?;
+    let positions = db.view::<BasicTagPositions>().query()?;
+    assert_eq!(positions.len(), 2);
+    assert_eq!(positions[0].key, "blue");
+    assert_eq!(positions[0].value, 1);
+    assert_eq!(positions[1].key, "red");
+    assert_eq!(positions[1].value, 2);
+    assert_eq!(positions[1].source.id.deserialize::<u64>()?, d.header.id);
+
     Ok(())
 }
 
@@ -2512,6 +3087,33 @@ pub async fn named_collection_tests<C: AsyncConnection>(db: &C) -> anyhow::Resul
         })
         .await;
     assert!(matches!(conflict, Err(Error::UniqueKeyViolation { .. })));
+    assert_eq!(
+        conflict.err().as_ref().map(Error::code),
+        Some(ErrorCode::UniqueViolation)
+    );
+
+    let existing = Unique::load_or_insert_with_async("2", db, || unreachable!()).await?;
+    assert_eq!(existing.header.id, updated.header.id);
+    let inserted = Unique::load_or_insert_with_async("3", db, || Unique::new("3")).await?;
+    assert_eq!(
+        Unique::load_async("3", db).await?.unwrap().header.id,
+        inserted.header.id
+    );
+    assert_eq!(
+        NamedReference::ids_async::<Unique, _>(
+            &[
+                NamedReference::from("2"),
+                NamedReference::from("missing"),
+                NamedReference::Key(inserted.header.id),
+            ],
+            db
+        )
+        .await?,
+        vec![Some(updated.header.id), None, Some(inserted.header.id)]
+    );
+    assert!(Unique::delete_by_name_async("3", db).await?);
+    assert!(!Unique::delete_by_name_async("3", db).await?);
+    assert!(Unique::load_async("3", db).await?.is_none());
 
     Ok(())
 }
@@ -2544,6 +3146,28 @@ pub fn blocking_named_collection_tests<C: Connection>(db: &C) -> anyhow::Result<
         .execute();
     assert!(matches!(conflict, Err(Error::UniqueKeyViolation { .. })));
 
+    let existing = Unique::load_or_insert_with("2", db, || unreachable!())?;
+    assert_eq!(existing.header.id, updated.header.id);
+    let inserted = Unique::load_or_insert_with("3", db, || Unique::new("3"))?;
+    assert_eq!(
+        Unique::load("3", db)?.unwrap().header.id,
+        inserted.header.id
+    );
+    assert_eq!(
+        NamedReference::ids::<Unique, _>(
+            &[
+                NamedReference::from("2"),
+                NamedReference::from("missing"),
+                NamedReference::Key(inserted.header.id),
+            ],
+            db
+        )?,
+        vec![Some(updated.header.id), None, Some(inserted.header.id)]
+    );
+    assert!(Unique::delete_by_name("3", db)?);
+    assert!(!Unique::delete_by_name("3", db)?);
+    assert!(Unique::load("3", db)?.is_none());
+
     Ok(())
 }
 
@@ -2663,12 +3287,140 @@ pub async fn user_management_tests<C: AsyncConnection, S: AsyncStorageConnection
         .await?;
     server.remove_role_from_user(user_id, &role).await?;
 
+    // Look up the user.
+    let user = server
+        .user_by_name(&username)
+        .await?
+        .expect("user not found");
+    assert_eq!(user.header.id, user_id);
+    assert!(user.contents.argon_hash.is_none());
+    assert!(server
+        .user_by_name(&format!("{username}-missing"))
+        .await?
+        .is_none());
+    assert!(server
+        .list_users()
+        .await?
+        .iter()
+        .any(|user| user.header.id == user_id && user.contents.username == username));
+
+    // Disable and re-enable the user.
+    server.disable_user(user_id).await?;
+    let user = User::get_async(&user_id, admin)
+        .await?
+        .expect("user not found");
+    assert!(user.contents.disabled);
+    server.enable_user(&username).await?;
+    let user = User::get_async(&user_id, admin)
+        .await?
+        .expect("user not found");
+    assert!(!user.contents.disabled);
+
     // Remove the user
     server.delete_user(user_id).await?;
     // Test if user is removed.
 
     assert!(User::get_async(&user_id, admin).await.unwrap().is_none());
 
+    // Manage permission groups and roles without editing the admin database.
+    let group_name = format!("managed-group-{server_name}");
+    let group_id = server
+        .create_permission_group(&group_name, Vec::new())
+        .await?;
+    assert!(server
+        .list_permission_groups()
+        .await?
+        .iter()
+        .any(|group| group.header.id == group_id && group.contents.name == group_name));
+    server
+        .set_permission_group_statements(
+            &group_name,
+            vec![Statement::for_any().allowing(&BonsaiAction::Server(ServerAction::ListDatabases))],
+        )
+        .await?;
+    let group = PermissionGroup::get_async(&group_id, admin)
+        .await?
+        .expect("group not found");
+    assert_eq!(group.contents.statements.len(), 1);
+
+    let role_name = format!("managed-role-{server_name}");
+    let role_id = server.create_role(&role_name).await?;
+    assert!(server
+        .list_roles()
+        .await?
+        .iter()
+        .any(|role| role.header.id == role_id && role.contents.name == role_name));
+    server
+        .add_permission_group_to_role(role_id, &group_name)
+        .await?;
+    // Adding again, using names, should not do anything.
+    server
+        .add_permission_group_to_role(&role_name, group_id)
+        .await?;
+    let role = Role::get_async(&role_id, admin)
+        .await?
+        .expect("role not found");
+    assert_eq!(role.contents.groups, vec![group_id]);
+
+    // A user assigned the role should inherit the group's permissions.
+    let member_name = format!("managed-role-member-{server_name}");
+    let member_id = server.create_user(&member_name).await?;
+    server.add_role_to_user(member_id, role_id).await?;
+    let list_databases = BonsaiAction::Server(ServerAction::ListDatabases);
+    assert!(server
+        .effective_permissions(member_id)
+        .await?
+        .allowed_to(bonsaidb_resource_name(), &list_databases));
+    server
+        .check_user_permission(&member_name, bonsaidb_resource_name(), &list_databases)
+        .await?;
+    server.delete_user(member_id).await?;
+
+    server
+        .remove_permission_group_from_role(role_id, group_id)
+        .await?;
+    let role = Role::get_async(&role_id, admin)
+        .await?
+        .expect("role not found");
+    assert!(role.contents.groups.is_empty());
+
+    // Deleting a role or group removes it from the users and roles it was
+    // assigned to.
+    let member_id = server
+        .create_user(&format!("managed-member-{server_name}"))
+        .await?;
+    server
+        .add_permission_group_to_user(member_id, group_id)
+        .await?;
+    server.add_role_to_user(member_id, role_id).await?;
+    let other_role_id = server
+        .create_role(&format!("managed-other-role-{server_name}"))
+        .await?;
+    server
+        .add_permission_group_to_role(other_role_id, group_id)
+        .await?;
+
+    server.delete_role(role_id).await?;
+    assert!(Role::get_async(&role_id, admin).await?.is_none());
+    let member = User::get_async(&member_id, admin)
+        .await?
+        .expect("user not found");
+    assert!(member.contents.roles.is_empty());
+    assert_eq!(member.contents.groups, vec![group_id]);
+
+    server.delete_permission_group(group_id).await?;
+    assert!(PermissionGroup::get_async(&group_id, admin)
+        .await?
+        .is_none());
+    let member = User::get_async(&member_id, admin)
+        .await?
+        .expect("user not found");
+    assert!(member.contents.groups.is_empty());
+    let other_role = Role::get_async(&other_role_id, admin)
+        .await?
+        .expect("role not found");
+    assert!(other_role.contents.groups.is_empty());
+
     Ok(())
 }
 
@@ -2735,12 +3487,94 @@ pub fn blocking_user_management_tests<C: Connection, S: StorageConnection>(
     server.remove_permission_group_from_user(user_id, &group)?;
     server.remove_role_from_user(user_id, &role)?;
 
+    // Look up the user.
+    let user = server.user_by_name(&username)?.expect("user not found");
+    assert_eq!(user.header.id, user_id);
+    assert!(user.contents.argon_hash.is_none());
+    assert!(server
+        .user_by_name(&format!("{username}-missing"))?
+        .is_none());
+    assert!(server
+        .list_users()?
+        .iter()
+        .any(|user| user.header.id == user_id && user.contents.username == username));
+
+    // Disable and re-enable the user.
+    server.disable_user(user_id)?;
+    let user = User::get(&user_id, admin)?.expect("user not found");
+    assert!(user.contents.disabled);
+    server.enable_user(&username)?;
+    let user = User::get(&user_id, admin)?.expect("user not found");
+    assert!(!user.contents.disabled);
+
     // Remove the user
     server.delete_user(user_id)?;
     // Test if user is removed.
 
     assert!(User::get(&user_id, admin).unwrap().is_none());
 
+    // Manage permission groups and roles without editing the admin database.
+    let group_name = format!("managed-group-{server_name}");
+    let group_id = server.create_permission_group(&group_name, Vec::new())?;
+    assert!(server
+        .list_permission_groups()?
+        .iter()
+        .any(|group| group.header.id == group_id && group.contents.name == group_name));
+    server.set_permission_group_statements(
+        &group_name,
+        vec![Statement::for_any().allowing(&BonsaiAction::Server(ServerAction::ListDatabases))],
+    )?;
+    let group = PermissionGroup::get(&group_id, admin)?.expect("group not found");
+    assert_eq!(group.contents.statements.len(), 1);
+
+    let role_name = format!("managed-role-{server_name}");
+    let role_id = server.create_role(&role_name)?;
+    assert!(server
+        .list_roles()?
+        .iter()
+        .any(|role| role.header.id == role_id && role.contents.name == role_name));
+    server.add_permission_group_to_role(role_id, &group_name)?;
+    // Adding again, using names, should not do anything.
+    server.add_permission_group_to_role(&role_name, group_id)?;
+    let role = Role::get(&role_id, admin)?.expect("role not found");
+    assert_eq!(role.contents.groups, vec![group_id]);
+
+    // A user assigned the role should inherit the group's permissions.
+    let member_name = format!("managed-role-member-{server_name}");
+    let member_id = server.create_user(&member_name)?;
+    server.add_role_to_user(member_id, role_id)?;
+    let list_databases = BonsaiAction::Server(ServerAction::ListDatabases);
+    assert!(server
+        .effective_permissions(member_id)?
+        .allowed_to(bonsaidb_resource_name(), &list_databases));
+    server.check_user_permission(&member_name, bonsaidb_resource_name(), &list_databases)?;
+    server.delete_user(member_id)?;
+
+    server.remove_permission_group_from_role(role_id, group_id)?;
+    let role = Role::get(&role_id, admin)?.expect("role not found");
+    assert!(role.contents.groups.is_empty());
+
+    // Deleting a role or group removes it from the users and roles it was
+    // assigned to.
+    let member_id = server.create_user(&format!("managed-member-{server_name}"))?;
+    server.add_permission_group_to_user(member_id, group_id)?;
+    server.add_role_to_user(member_id, role_id)?;
+    let other_role_id = server.create_role(&format!("managed-other-role-{server_name}"))?;
+    server.add_permission_group_to_role(other_role_id, group_id)?;
+
+    server.delete_role(role_id)?;
+    assert!(Role::get(&role_id, admin)?.is_none());
+    let member = User::get(&member_id, admin)?.expect("user not found");
+    assert!(member.contents.roles.is_empty());
+    assert_eq!(member.contents.groups, vec![group_id]);
+
+    server.delete_permission_group(group_id)?;
+    assert!(PermissionGroup::get(&group_id, admin)?.is_none());
+    let member = User::get(&member_id, admin)?.expect("user not found");
+    assert!(member.contents.groups.is_empty());
+    let other_role = Role::get(&other_role_id, admin)?.expect("role not found");
+    assert!(other_role.contents.groups.is_empty());
+
     Ok(())
 }
 
@@ -3289,6 +4123,88 @@ macro_rules! define_async_kv_test_suite {
 
                 Ok(())
             }
+
+            #[tokio::test]
+            async fn kv_namespace_tests() -> anyhow::Result<()> {
+                use $crate::keyvalue::AsyncKeyValue;
+                let harness = $harness::new($crate::test_util::HarnessTest::KvNamespaces).await?;
+                let db = harness.connect().await?;
+
+                db.set_key("default", &0_u32).await?;
+                let a = db.with_key_namespace("a");
+                a.set_key("one", &1_u32).await?;
+                a.set_key("two", &2_u32).await?;
+                let b = db.with_key_namespace("b");
+                b.set_key("one", &1_u32).await?;
+
+                assert_eq!(db.list_key_namespaces().await?, vec!["a", "b"]);
+                assert_eq!(db.count_keys().await?, 1);
+                assert_eq!(a.count_keys().await?, 2);
+                assert_eq!(b.count_keys().await?, 1);
+
+                assert_eq!(a.delete_key_namespace().await?, 2);
+                assert_eq!(a.count_keys().await?, 0);
+                assert_eq!(a.get_key("one").await?, None);
+                assert_eq!(db.list_key_namespaces().await?, vec!["b"]);
+                assert_eq!(b.get_key("one").into::<u32>().await?, Some(1));
+                assert_eq!(db.get_key("default").into::<u32>().await?, Some(0));
+
+                harness.shutdown().await?;
+
+                Ok(())
+            }
+
+            #[tokio::test]
+            async fn kv_multiple_tests() -> anyhow::Result<()> {
+                use $crate::keyvalue::{AsyncKeyValue, KeyStatus, Numeric, Value};
+                let harness = $harness::new($crate::test_util::HarnessTest::KvMultiple).await?;
+                let db = harness.connect().await?;
+
+                db.set_key("b", &0_u32).await?;
+                assert_eq!(
+                    db.set_keys([("a", 1_u32), ("b", 2_u32)]).await?,
+                    vec![KeyStatus::Inserted, KeyStatus::Updated]
+                );
+                let values = db.get_keys(["a", "missing", "b"]).await?;
+                assert_eq!(values.len(), 3);
+                assert_eq!(values[0].as_ref().unwrap().deserialize::<u32>()?, 1);
+                assert!(values[1].is_none());
+                assert_eq!(values[2].as_ref().unwrap().deserialize::<u32>()?, 2);
+
+                // Keys are namespaced.
+                assert_eq!(
+                    db.with_key_namespace("other").get_keys(["a"]).await?,
+                    vec![None]
+                );
+
+                // A numeric key is unaffected by a set that fails validation.
+                db.set_numeric_key("n", 1_u64).await?;
+                assert!(db
+                    .execute_key_operation($crate::keyvalue::KeyOperation {
+                        namespace: None,
+                        key: String::new(),
+                        command: $crate::keyvalue::Command::SetMultiple {
+                            entries: vec![
+                                (
+                                    String::from("n"),
+                                    Value::Numeric(Numeric::UnsignedInteger(2))
+                                ),
+                                (
+                                    String::from("nan"),
+                                    Value::Numeric(Numeric::Float(f64::NAN))
+                                ),
+                            ],
+                        },
+                    })
+                    .await
+                    .is_err());
+                assert_eq!(db.get_key("n").into_u64().await?, Some(1));
+                assert_eq!(db.get_key("nan").await?, None);
+
+                harness.shutdown().await?;
+
+                Ok(())
+            }
         }
     };
 }
@@ -3752,6 +4668,84 @@ macro_rules! define_blocking_kv_test_suite {
 
                 Ok(())
             }
+
+            #[test]
+            fn kv_namespace_tests() -> anyhow::Result<()> {
+                use $crate::keyvalue::KeyValue;
+                let harness = $harness::new($crate::test_util::HarnessTest::KvNamespaces)?;
+                let db = harness.connect()?;
+
+                db.set_key("default", &0_u32).execute()?;
+                let a = db.with_key_namespace("a");
+                a.set_key("one", &1_u32).execute()?;
+                a.set_key("two", &2_u32).execute()?;
+                let b = db.with_key_namespace("b");
+                b.set_key("one", &1_u32).execute()?;
+
+                assert_eq!(db.list_key_namespaces()?, vec!["a", "b"]);
+                assert_eq!(db.count_keys()?, 1);
+                assert_eq!(a.count_keys()?, 2);
+                assert_eq!(b.count_keys()?, 1);
+
+                assert_eq!(a.delete_key_namespace()?, 2);
+                assert_eq!(a.count_keys()?, 0);
+                assert_eq!(a.get_key("one").query()?, None);
+                assert_eq!(db.list_key_namespaces()?, vec!["b"]);
+                assert_eq!(b.get_key("one").into::<u32>()?, Some(1));
+                assert_eq!(db.get_key("default").into::<u32>()?, Some(0));
+
+                harness.shutdown()?;
+
+                Ok(())
+            }
+
+            #[test]
+            fn kv_multiple_tests() -> anyhow::Result<()> {
+                use $crate::keyvalue::{KeyStatus, KeyValue, Numeric, Value};
+                let harness = $harness::new($crate::test_util::HarnessTest::KvMultiple)?;
+                let db = harness.connect()?;
+
+                db.set_key("b", &0_u32).execute()?;
+                assert_eq!(
+                    db.set_keys([("a", 1_u32), ("b", 2_u32)])?,
+                    vec![KeyStatus::Inserted, KeyStatus::Updated]
+                );
+                let values = db.get_keys(["a", "missing", "b"])?;
+                assert_eq!(values.len(), 3);
+                assert_eq!(values[0].as_ref().unwrap().deserialize::<u32>()?, 1);
+                assert!(values[1].is_none());
+                assert_eq!(values[2].as_ref().unwrap().deserialize::<u32>()?, 2);
+
+                // Keys are namespaced.
+                assert_eq!(db.with_key_namespace("other").get_keys(["a"])?, vec![None]);
+
+                // A numeric key is unaffected by a set that fails validation.
+                db.set_numeric_key("n", 1_u64).execute()?;
+                assert!(db
+                    .execute_key_operation($crate::keyvalue::KeyOperation {
+                        namespace: None,
+                        key: String::new(),
+                        command: $crate::keyvalue::Command::SetMultiple {
+                            entries: vec![
+                                (
+                                    String::from("n"),
+                                    Value::Numeric(Numeric::UnsignedInteger(2))
+                                ),
+                                (
+                                    String::from("nan"),
+                                    Value::Numeric(Numeric::Float(f64::NAN))
+                                ),
+                            ],
+                        },
+                    })
+                    .is_err());
+                assert_eq!(db.get_key("n").into_u64()?, Some(1));
+                assert_eq!(db.get_key("nan").query()?, None);
+
+                harness.shutdown()?;
+
+                Ok(())
+            }
         }
     };
 }
@@ -3819,6 +4813,27 @@ pub async fn basic_server_connection_tests<C: AsyncStorageConnection>(
     server
         .create_database::<BasicSchema>(newdb_name, false)
         .await?;
+    let matching = server
+        .list_databases_matching(DatabaseQuery::default().with_name_prefix(newdb_name))
+        .await?;
+    assert!(matching.iter().any(|db| db.name == newdb_name));
+    assert!(matching.iter().all(|db| db.name.starts_with(newdb_name)));
+    let matching = server
+        .list_databases_matching(DatabaseQuery::default().with_schema(BasicSchema::schema_name()))
+        .await?;
+    assert!(matching.iter().any(|db| db.name == "tests"));
+    assert!(matching
+        .iter()
+        .all(|db| db.schema == BasicSchema::schema_name()));
+    let first_page = server
+        .list_databases_matching(DatabaseQuery::default().limit(1))
+        .await?;
+    assert_eq!(first_page.len(), 1);
+    let second_page = server
+        .list_databases_matching(DatabaseQuery::default().after(&first_page[0].name).limit(1))
+        .await?;
+    assert_eq!(second_page.len(), 1);
+    assert!(second_page[0].name > first_page[0].name);
     server.delete_database(newdb_name).await?;
 
     assert!(matches!(
@@ -3866,6 +4881,23 @@ pub fn blocking_basic_server_connection_tests<C: StorageConnection>(
     assert!(databases.iter().any(|db| db.name == "tests"));
 
     server.create_database::<BasicSchema>(newdb_name, false)?;
+    let matching =
+        server.list_databases_matching(DatabaseQuery::default().with_name_prefix(newdb_name))?;
+    assert!(matching.iter().any(|db| db.name == newdb_name));
+    assert!(matching.iter().all(|db| db.name.starts_with(newdb_name)));
+    let matching = server.list_databases_matching(
+        DatabaseQuery::default().with_schema(BasicSchema::schema_name()),
+    )?;
+    assert!(matching.iter().any(|db| db.name == "tests"));
+    assert!(matching
+        .iter()
+        .all(|db| db.schema == BasicSchema::schema_name()));
+    let first_page = server.list_databases_matching(DatabaseQuery::default().limit(1))?;
+    assert_eq!(first_page.len(), 1);
+    let second_page = server
+        .list_databases_matching(DatabaseQuery::default().after(&first_page[0].name).limit(1))?;
+    assert_eq!(second_page.len(), 1);
+    assert!(second_page[0].name > first_page[0].name);
     server.delete_database(newdb_name)?;
 
     assert!(matches!(
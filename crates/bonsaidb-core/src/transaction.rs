@@ -2,7 +2,7 @@ use arc_bytes::serde::Bytes;
 use serde::{Deserialize, Serialize};
 
 use crate::connection::{AsyncLowLevelConnection, LowLevelConnection};
-use crate::document::{CollectionHeader, DocumentId, HasHeader, Header, Revision};
+use crate::document::{CollectionHeader, DocumentId, HasHeader, Header, OwnedDocument, Revision};
 use crate::schema::{Collection, CollectionName, SerializedCollection};
 use crate::Error;
 
@@ -65,6 +65,21 @@ use crate::Error;
 pub struct Transaction {
     /// The operations in this transaction.
     pub operations: Vec<Operation>,
+    /// The number of cluster nodes that must have stored this transaction
+    /// before it is considered applied. This setting is ignored when the
+    /// database isn't replicated by a cluster.
+    #[serde(default)]
+    pub write_quorum: WriteQuorum,
+    /// A key identifying this transaction. When a server receives a
+    /// transaction with the same key as one it recently applied to the same
+    /// database, the transaction is not applied again. Instead, the results
+    /// of the original transaction are returned. This allows a transaction to
+    /// be safely retried when the result of a previous attempt is unknown,
+    /// such as after a request times out.
+    ///
+    /// This setting is ignored when the database is accessed locally.
+    #[serde(default)]
+    pub idempotency_key: Option<IdempotencyKey>,
 }
 
 impl Transaction {
@@ -84,6 +99,20 @@ impl Transaction {
         self
     }
 
+    /// Sets [`Self::write_quorum`](Self#structfield.write_quorum) to
+    /// `write_quorum` and returns self.
+    pub const fn with_write_quorum(mut self, write_quorum: WriteQuorum) -> Self {
+        self.write_quorum = write_quorum;
+        self
+    }
+
+    /// Sets [`Self::idempotency_key`](Self#structfield.idempotency_key) to
+    /// `key` and returns self.
+    pub const fn with_idempotency_key(mut self, key: IdempotencyKey) -> Self {
+        self.idempotency_key = Some(key);
+        self
+    }
+
     /// Applies the transaction to the `database`, returning the results of the
     /// operations. All operations will succeed or none will be performed and an
     /// error will be returned.
@@ -103,16 +132,203 @@ impl Transaction {
     ) -> Result<Vec<OperationResult>, Error> {
         db.apply_transaction(self).await
     }
+
+    /// Loads `documents`, invokes `modifier` with the loaded documents to
+    /// produce a transaction, and applies it. If any of the loaded documents
+    /// are changed or deleted before the transaction is applied, the process is
+    /// retried with the current documents.
+    ///
+    /// The documents are passed to `modifier` in the same order as
+    /// `documents`, with `None` for each document that doesn't exist. The
+    /// returned results only include the results of the operations returned
+    /// from `modifier`.
+    ///
+    /// This ensures that changes that depend on the contents of multiple
+    /// documents are only applied if none of the documents were modified in the
+    /// meantime. Documents that don't exist when loaded are not checked, which
+    /// means that a document inserted while `modifier` is executing will not
+    /// cause a retry.
+    ///
+    /// `modifier` may be invoked multiple times, and should not have side
+    /// effects beyond producing the transaction. Returning an error from
+    /// `modifier` aborts the process.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::Connection;
+    /// # fn test_fn<C: Connection>(db: &C) -> Result<(), Error> {
+    /// use bonsaidb_core::document::DocumentId;
+    /// use bonsaidb_core::transaction::{Operation, Transaction};
+    /// let documents = [
+    ///     (MyCollection::collection_name(), DocumentId::new(&1_u64)?),
+    ///     (MyCollection::collection_name(), DocumentId::new(&2_u64)?),
+    /// ];
+    /// Transaction::modify_documents(db, &documents, |documents| {
+    ///     // Move the contents of the first document into the second.
+    ///     let mut tx = Transaction::new();
+    ///     if let [Some(first), Some(second)] = documents {
+    ///         tx.push(Operation::update(
+    ///             MyCollection::collection_name(),
+    ///             second.header.clone(),
+    ///             first.contents.clone(),
+    ///         ));
+    ///         tx.push(Operation::delete(
+    ///             MyCollection::collection_name(),
+    ///             first.header.clone(),
+    ///         ));
+    ///     }
+    ///     Ok(tx)
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn modify_documents<Connection, Modifier>(
+        db: &Connection,
+        documents: &[(CollectionName, DocumentId)],
+        mut modifier: Modifier,
+    ) -> Result<Vec<OperationResult>, Error>
+    where
+        Connection: LowLevelConnection,
+        Modifier: FnMut(&[Option<OwnedDocument>]) -> Result<Self, Error>,
+    {
+        loop {
+            let mut loaded = Vec::with_capacity(documents.len());
+            for (collection, id) in documents {
+                loaded.push(db.get_from_collection(id.clone(), collection)?);
+            }
+            let (transaction, checks) = modifier(&loaded)?.checking_documents(documents, &loaded);
+            match db.apply_transaction(transaction) {
+                Ok(mut results) => {
+                    results.drain(..checks);
+                    return Ok(results);
+                }
+                Err(err) if is_modified_document_error(&err, documents, &loaded) => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Loads `documents`, invokes `modifier` with the loaded documents to
+    /// produce a transaction, and applies it. If any of the loaded documents
+    /// are changed or deleted before the transaction is applied, the process is
+    /// retried with the current documents. See
+    /// [`Self::modify_documents()`] for more information.
+    pub async fn modify_documents_async<Connection, Modifier>(
+        db: &Connection,
+        documents: &[(CollectionName, DocumentId)],
+        mut modifier: Modifier,
+    ) -> Result<Vec<OperationResult>, Error>
+    where
+        Connection: AsyncLowLevelConnection,
+        Modifier: FnMut(&[Option<OwnedDocument>]) -> Result<Self, Error> + Send,
+    {
+        loop {
+            let mut loaded = Vec::with_capacity(documents.len());
+            for (collection, id) in documents {
+                loaded.push(db.get_from_collection(id.clone(), collection).await?);
+            }
+            let (transaction, checks) = modifier(&loaded)?.checking_documents(documents, &loaded);
+            match db.apply_transaction(transaction).await {
+                Ok(mut results) => {
+                    results.drain(..checks);
+                    return Ok(results);
+                }
+                Err(err) if is_modified_document_error(&err, documents, &loaded) => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Inserts operations at the start of this transaction that check that
+    /// each loaded document is still current. Returns the transaction and the
+    /// number of operations inserted.
+    fn checking_documents(
+        mut self,
+        documents: &[(CollectionName, DocumentId)],
+        loaded: &[Option<OwnedDocument>],
+    ) -> (Self, usize) {
+        let checks = documents
+            .iter()
+            .zip(loaded)
+            .filter_map(|((collection, _), document)| {
+                document.as_ref().map(|document| Operation {
+                    collection: collection.clone(),
+                    command: Command::Check {
+                        id: document.header.id.clone(),
+                        revision: Some(document.header.revision),
+                    },
+                })
+            })
+            .collect::<Vec<_>>();
+        let check_count = checks.len();
+        self.operations.splice(0..0, checks);
+        (self, check_count)
+    }
+}
+
+/// Returns true if `err` was caused by one of the `loaded` documents being
+/// modified or deleted.
+fn is_modified_document_error(
+    err: &Error,
+    documents: &[(CollectionName, DocumentId)],
+    loaded: &[Option<OwnedDocument>],
+) -> bool {
+    let (error_collection, error_id) = match err {
+        Error::DocumentConflict(collection, header) => (collection, &header.id),
+        Error::DocumentNotFound(collection, id) => (collection, &**id),
+        _ => return false,
+    };
+    documents
+        .iter()
+        .zip(loaded)
+        .any(|((collection, id), document)| {
+            document.is_some() && collection == error_collection && id == error_id
+        })
 }
 
 impl From<Operation> for Transaction {
     fn from(operation: Operation) -> Self {
         Self {
             operations: vec![operation],
+            write_quorum: WriteQuorum::default(),
+            idempotency_key: None,
         }
     }
 }
 
+/// A unique key identifying a [`Transaction`], allowing it to be retried
+/// without being applied more than once. See
+/// [`Transaction::idempotency_key`](Transaction#structfield.idempotency_key).
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Eq, PartialEq, Hash)]
+pub struct IdempotencyKey(pub u128);
+
+impl IdempotencyKey {
+    /// Returns a new, randomly generated key.
+    #[must_use]
+    pub fn random() -> Self {
+        Self(rand::random())
+    }
+}
+
+/// The number of cluster nodes that must store a [`Transaction`] before
+/// applying it returns successfully.
+///
+/// A transaction is only committed once a majority of the cluster's nodes have
+/// stored it. Requiring every node increases the durability of the transaction
+/// at the cost of latency. If the quorum isn't reached within the cluster's
+/// failure timeout, an error is returned. If the transaction was committed, it
+/// will continue to be replicated to the other nodes.
+#[derive(Clone, Copy, Serialize, Deserialize, Default, Debug, Eq, PartialEq)]
+pub enum WriteQuorum {
+    /// The transaction is considered applied once a majority of the cluster's
+    /// nodes have stored it and it has been committed.
+    #[default]
+    Majority,
+    /// The transaction is considered applied once every node in the cluster
+    /// has stored it.
+    All,
+}
+
 impl Transaction {
     /// Inserts a new document with `contents` into `collection`.  If `id` is
     /// `None` a unique id will be generated. If an id is provided and a
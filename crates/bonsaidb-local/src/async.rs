@@ -1,25 +1,40 @@
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
+use bonsaidb_core::admin::{PermissionGroup, Role, User};
 use bonsaidb_core::connection::{
     self, AccessPolicy, AsyncConnection, AsyncLowLevelConnection, AsyncStorageConnection,
-    Connection, HasSchema, HasSession, IdentityReference, LowLevelConnection, Range,
-    SerializedQueryKey, Session, Sort, StorageConnection,
+    Connection, DatabaseQuery, HasSchema, HasSession, IdentityReference, LowLevelConnection,
+    QueryKey, Range, SerializedQueryKey, Session, SessionId, SessionInfo, Sort, StorageConnection,
+    ViewReadOptions,
 };
-use bonsaidb_core::document::{DocumentId, Header, OwnedDocument};
+use bonsaidb_core::document::{CollectionDocument, DocumentId, Header, OwnedDocument};
+#[cfg(feature = "encryption")]
+use bonsaidb_core::document::{FieldEncryption, KeyId};
 use bonsaidb_core::keyvalue::{AsyncKeyValue, KeyOperation, KeyValue, Output};
-use bonsaidb_core::permissions::Permissions;
+use bonsaidb_core::permissions::{Permissions, Statement};
 use bonsaidb_core::pubsub::{self, AsyncPubSub, AsyncSubscriber, PubSub, Receiver};
-use bonsaidb_core::schema::view::map::MappedSerializedValue;
+use bonsaidb_core::schema::view::aggregation::{
+    Aggregation, AggregationGrouping, SerializedAggregatedGroup,
+};
+use bonsaidb_core::schema::view::join::SerializedJoinedEntry;
+use bonsaidb_core::schema::view::map::{Map, MappedSerializedValue};
 use bonsaidb_core::schema::{
-    self, CollectionName, Nameable, Schema, SchemaName, Schematic, ViewName,
+    self, CollectionName, Name, Nameable, Schema, SchemaName, Schematic, SerializedView, ViewName,
 };
-use bonsaidb_core::transaction::{self, OperationResult, Transaction};
+use bonsaidb_core::transaction::{self, DocumentChanges, OperationResult, Transaction};
 
 use crate::config::StorageConfiguration;
+use crate::database::lock::LOCK_RETRY_INTERVAL;
 use crate::database::DatabaseNonBlocking;
 use crate::storage::{AnyBackupLocation, StorageNonBlocking};
-use crate::{Database, Error, Storage, Subscriber};
+use crate::{
+    BackgroundTasks, BackupOptions, BackupVerificationReport, CrossDatabaseTransaction, Database,
+    DatabaseMapping, Error, IntegrityReport, KeyValueLock, RecoveryReport, RestoreOptions,
+    SchemaStatus, ScrubReport, Storage, Subscriber, UsageReport, ViewBacklog,
+};
 
 /// A file-based, multi-database, multi-user database engine. This type is
 /// designed for use with [Tokio](https://tokio.rs). For blocking
@@ -143,6 +158,19 @@ impl AsyncStorage {
             .await?
     }
 
+    /// Restores the data selected by `options` from a previously stored backup
+    /// `location`. See [`Storage::restore_with()`] for more information.
+    pub async fn restore_with<L: AnyBackupLocation + 'static>(
+        &self,
+        location: L,
+        options: RestoreOptions,
+    ) -> Result<(), Error> {
+        let task_self = self.clone();
+        self.runtime
+            .spawn_blocking(move || task_self.storage.restore_with(&location, options))
+            .await?
+    }
+
     /// Stores a copy of all data in this instance to `location`.
     pub async fn backup<L: AnyBackupLocation + 'static>(&self, location: L) -> Result<(), Error> {
         let task_self = self.clone();
@@ -151,6 +179,223 @@ impl AsyncStorage {
             .await?
     }
 
+    /// Stores a copy of the data selected by `options` to `location`.
+    pub async fn backup_with<L: AnyBackupLocation + 'static>(
+        &self,
+        location: L,
+        options: BackupOptions,
+    ) -> Result<(), Error> {
+        let task_self = self.clone();
+        self.runtime
+            .spawn_blocking(move || task_self.storage.backup_with(&location, options))
+            .await?
+    }
+
+    /// Verifies the completeness and integrity of the backup stored at
+    /// `location` without restoring it. See [`Storage::verify_backup()`] for
+    /// more information.
+    pub async fn verify_backup<L: AnyBackupLocation + 'static>(
+        &self,
+        location: L,
+    ) -> Result<BackupVerificationReport, Error> {
+        let task_self = self.clone();
+        self.runtime
+            .spawn_blocking(move || task_self.storage.verify_backup(&location))
+            .await?
+    }
+
+    /// Reads the contents of every collection and view tree in every database,
+    /// returning a report of the trees that could not be read. See
+    /// [`Storage::scrub()`] for more information.
+    pub async fn scrub(&self) -> Result<ScrubReport, Error> {
+        let task_self = self.clone();
+        self.runtime
+            .spawn_blocking(move || task_self.storage.scrub())
+            .await?
+    }
+
+    /// Copies every readable document from the database named `name` into a
+    /// new database named `recovered_name`. See
+    /// [`Storage::recover_database()`] for more information.
+    pub async fn recover_database(
+        &self,
+        name: &str,
+        recovered_name: &str,
+    ) -> Result<RecoveryReport, Error> {
+        let task_self = self.clone();
+        let name = name.to_string();
+        let recovered_name = recovered_name.to_string();
+        self.runtime
+            .spawn_blocking(move || task_self.storage.recover_database(&name, &recovered_name))
+            .await?
+    }
+
+    /// Applies `transaction` to each of its databases. See
+    /// [`Storage::apply_cross_database_transaction()`] for more information.
+    pub async fn apply_cross_database_transaction(
+        &self,
+        transaction: CrossDatabaseTransaction,
+    ) -> Result<BTreeMap<String, Vec<OperationResult>>, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        self.runtime
+            .spawn_blocking(move || {
+                task_self
+                    .storage
+                    .apply_cross_database_transaction(transaction)
+            })
+            .await
+            .map_err(Error::from)?
+    }
+
+    /// Queries the view named `view` in every database whose schema contains
+    /// it. See [`Storage::query_view_in_databases_by_name()`] for more
+    /// information.
+    pub async fn query_view_in_databases_by_name(
+        &self,
+        view: &ViewName,
+        schema: Option<&SchemaName>,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+    ) -> Result<Vec<DatabaseMapping<schema::view::map::Serialized>>, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let view = view.clone();
+        let schema = schema.cloned();
+        self.runtime
+            .spawn_blocking(move || {
+                task_self.storage.query_view_in_databases_by_name(
+                    &view,
+                    schema.as_ref(),
+                    key,
+                    order,
+                    limit,
+                    access_policy,
+                )
+            })
+            .await
+            .map_err(Error::from)?
+    }
+
+    /// Queries the view `V` in every database whose schema contains it. See
+    /// [`Storage::query_view_in_databases_by_name()`] for more information.
+    pub async fn query_view_in_databases<V: SerializedView>(
+        &self,
+        schema: Option<&SchemaName>,
+        key: Option<QueryKey<'_, V::Key>>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+    ) -> Result<Vec<DatabaseMapping<Map<V::Key, V::Value>>>, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let key = key.map(|key| key.serialized()).transpose()?;
+        let schema = schema.cloned();
+        let mappings = self
+            .runtime
+            .spawn_blocking(move || {
+                task_self.storage.query_view_in_databases_with(
+                    |schematic| schematic.view::<V>().ok().map(|view| view.view_name()),
+                    schema.as_ref(),
+                    key,
+                    order,
+                    limit,
+                    access_policy,
+                )
+            })
+            .await
+            .map_err(Error::from)??;
+        mappings
+            .iter()
+            .map(|mapping| {
+                mapping
+                    .deserialized::<V>()
+                    .map_err(bonsaidb_core::Error::from)
+            })
+            .collect()
+    }
+
+    /// Returns the report of the most recently completed scrub, if any.
+    #[must_use]
+    pub fn last_scrub_report(&self) -> Option<ScrubReport> {
+        self.storage.last_scrub_report()
+    }
+
+    /// Waits for all background tasks, such as view indexing and compaction,
+    /// to complete. If `timeout` elapses before the tasks complete, `false` is
+    /// returned.
+    pub async fn wait_for_background_tasks(
+        &self,
+        timeout: Option<Duration>,
+    ) -> Result<bool, Error> {
+        let task_self = self.clone();
+        Ok(self
+            .runtime
+            .spawn_blocking(move || task_self.storage.wait_for_background_tasks(timeout))
+            .await?)
+    }
+
+    /// Returns a summary of the background tasks, such as view indexing and
+    /// compaction, that are queued or running.
+    #[must_use]
+    pub fn background_tasks(&self) -> BackgroundTasks {
+        self.storage.background_tasks()
+    }
+
+    /// Returns the resources consumed by each database and user since this
+    /// storage was opened.
+    #[must_use]
+    pub fn usage(&self) -> UsageReport {
+        self.storage.usage()
+    }
+
+    /// Sets the quota of the database named `name`. See
+    /// [`Storage::set_database_quota()`] for more information.
+    pub async fn set_database_quota(
+        &self,
+        name: &str,
+        quota: Option<u64>,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let name = name.to_string();
+        self.runtime
+            .spawn_blocking(move || task_self.storage.set_database_quota(&name, quota))
+            .await
+            .map_err(Error::from)?
+    }
+
+    /// Returns the quota of the database named `name`, if one has been set.
+    #[must_use]
+    pub fn database_quota(&self, name: &str) -> Option<u64> {
+        self.storage.database_quota(name)
+    }
+
+    /// Returns the combined size of the files of the database named `name`.
+    /// See [`Storage::database_size()`] for more information.
+    pub async fn database_size(&self, name: &str) -> Result<u64, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let name = name.to_string();
+        self.runtime
+            .spawn_blocking(move || task_self.storage.database_size(&name))
+            .await
+            .map_err(Error::from)?
+    }
+
+    /// Registers a schema for use within the server. See
+    /// [`Storage::register_schema()`] for more information.
+    pub fn register_schema<DB: Schema>(&self) -> Result<(), Error> {
+        self.storage.register_schema::<DB>()
+    }
+
+    /// Persists any pending key-value store changes for all open databases.
+    /// See [`Storage::flush()`] for more information.
+    pub async fn flush(&self) -> Result<(), Error> {
+        let task_self = self.clone();
+        self.runtime
+            .spawn_blocking(move || task_self.storage.flush())
+            .await?;
+        Ok(())
+    }
+
     /// Restricts an unauthenticated instance to having `effective_permissions`.
     /// Returns `None` if a session has already been established.
     #[must_use]
@@ -163,6 +408,17 @@ impl AsyncStorage {
             })
     }
 
+    /// Returns a clone of this instance whose operations fail with
+    /// [`bonsaidb_core::Error::DeadlineExceeded`] if they are still waiting on
+    /// view updates, reductions, or compactions once `deadline` has passed.
+    #[must_use]
+    pub fn with_deadline(&self, deadline: Instant) -> Self {
+        Self {
+            storage: self.storage.with_deadline(deadline),
+            runtime: self.runtime.clone(),
+        }
+    }
+
     #[cfg(feature = "internal-apis")]
     #[doc(hidden)]
     pub async fn database_without_schema(&self, name: &str) -> Result<AsyncDatabase, Error> {
@@ -220,6 +476,10 @@ impl StorageNonBlocking for AsyncStorage {
             runtime: self.runtime.clone(),
         })
     }
+
+    fn check_session(&self, session_id: SessionId) -> Result<(), bonsaidb_core::Error> {
+        self.storage.check_session(session_id)
+    }
 }
 
 /// A database stored in BonsaiDb. This type is designed for use with
@@ -301,6 +561,144 @@ impl AsyncDatabase {
             })
     }
 
+    /// Returns a clone of this instance whose operations fail with
+    /// [`bonsaidb_core::Error::DeadlineExceeded`] if they are still waiting on
+    /// view updates, reductions, or compactions once `deadline` has passed.
+    #[must_use]
+    pub fn with_deadline(&self, deadline: Instant) -> Self {
+        Self {
+            database: self.database.with_deadline(deadline),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    #[cfg(feature = "internal-apis")]
+    #[doc(hidden)]
+    pub fn check_transaction_permissions(
+        &self,
+        transaction: &bonsaidb_core::transaction::Transaction,
+    ) -> Result<(), bonsaidb_core::Error> {
+        self.database.check_transaction_permissions(transaction)
+    }
+
+    #[cfg(feature = "internal-apis")]
+    #[doc(hidden)]
+    pub async fn apply_sequenced_transaction(
+        &self,
+        transaction: Transaction,
+        source: String,
+        sequence: u64,
+    ) -> Result<Option<Vec<OperationResult>>, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        self.runtime
+            .spawn_blocking(move || {
+                task_self
+                    .database
+                    .apply_sequenced_transaction(transaction, &source, sequence)
+            })
+            .await
+            .map_err(Error::from)?
+    }
+
+    /// Verifies the integrity of this database, returning a report of any
+    /// problems found. See [`Database::verify_integrity()`] for more
+    /// information.
+    pub async fn verify_integrity(&self) -> Result<IntegrityReport, Error> {
+        let task_self = self.clone();
+        self.runtime
+            .spawn_blocking(move || task_self.database.verify_integrity())
+            .await?
+    }
+
+    /// Verifies the integrity of this database and rebuilds each view that has
+    /// inconsistent mappings. See [`Database::verify_and_repair_integrity()`]
+    /// for more information.
+    pub async fn verify_and_repair_integrity(&self) -> Result<IntegrityReport, Error> {
+        let task_self = self.clone();
+        self.runtime
+            .spawn_blocking(move || task_self.database.verify_and_repair_integrity())
+            .await?
+    }
+
+    /// Rewrites every entry stored in this database using its current
+    /// encryption, and then compacts the database. See
+    /// [`Database::reencrypt()`] for more information.
+    pub async fn reencrypt(&self) -> Result<(), Error> {
+        let task_self = self.clone();
+        self.runtime
+            .spawn_blocking(move || task_self.database.reencrypt())
+            .await?
+    }
+
+    /// Returns whether each view in this database's schema has been built
+    /// using the view's current definition. See [`Database::schema_status()`]
+    /// for more information.
+    pub async fn schema_status(&self) -> Result<SchemaStatus, Error> {
+        let task_self = self.clone();
+        self.runtime
+            .spawn_blocking(move || task_self.database.schema_status())
+            .await?
+    }
+
+    /// Returns the number of documents waiting to be mapped into each view in
+    /// this database's schema. See [`Database::view_backlog()`] for more
+    /// information.
+    pub async fn view_backlog(&self) -> Result<HashMap<ViewName, ViewBacklog>, Error> {
+        let task_self = self.clone();
+        self.runtime
+            .spawn_blocking(move || task_self.database.view_backlog())
+            .await?
+    }
+
+    /// Acquires the lock named `name`, waiting until it is available. See
+    /// [`Database::lock()`] for more information.
+    ///
+    /// Releasing the returned lock, including when it is dropped, blocks the
+    /// current thread briefly.
+    pub async fn lock(&self, name: &str, ttl: Duration) -> Result<KeyValueLock, Error> {
+        loop {
+            if let Some(lock) = self.try_lock(name, ttl).await? {
+                return Ok(lock);
+            }
+            tokio::time::sleep(LOCK_RETRY_INTERVAL).await;
+        }
+    }
+
+    /// Acquires the lock named `name` if it is available. Returns None if the
+    /// lock is held elsewhere. See [`Database::try_lock()`] for more
+    /// information.
+    pub async fn try_lock(&self, name: &str, ttl: Duration) -> Result<Option<KeyValueLock>, Error> {
+        let task_self = self.clone();
+        let name = name.to_string();
+        self.runtime
+            .spawn_blocking(move || task_self.database.try_lock(&name, ttl))
+            .await?
+    }
+
+    /// Returns a receiver of the documents changed by each transaction
+    /// committed to this database after this function is called. See
+    /// [`Database::watch_document_changes()`] for more information.
+    pub fn watch_document_changes(
+        &self,
+    ) -> Result<flume::Receiver<Arc<DocumentChanges>>, bonsaidb_core::Error> {
+        self.database.watch_document_changes()
+    }
+
+    /// Executes the read-only SQL `query` against the views registered in
+    /// [`StorageConfiguration::sql`](crate::config::StorageConfiguration#structfield.sql).
+    /// See [`Database::query_sql()`] for more information.
+    #[cfg(feature = "sql")]
+    pub async fn query_sql(
+        &self,
+        query: &str,
+    ) -> Result<bonsaidb_core::sql::SqlResults, bonsaidb_core::sql::SqlError> {
+        self.database
+            .storage
+            .sql_catalog()
+            .execute_async(self, query)
+            .await
+    }
+
     /// Converts this instance into its blocking version, which is able to be
     /// used without async.
     #[must_use]
@@ -347,6 +745,21 @@ impl HasSession for AsyncStorage {
     }
 }
 
+#[cfg(feature = "encryption")]
+impl FieldEncryption for AsyncStorage {
+    fn encrypt_field_bytes(
+        &self,
+        key: &KeyId,
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, bonsaidb_core::Error> {
+        self.storage.encrypt_field_bytes(key, plaintext)
+    }
+
+    fn decrypt_field_bytes(&self, payload: &[u8]) -> Result<Vec<u8>, bonsaidb_core::Error> {
+        self.storage.decrypt_field_bytes(payload)
+    }
+}
+
 #[async_trait]
 impl AsyncStorageConnection for AsyncStorage {
     type Authenticated = Self;
@@ -409,10 +822,13 @@ impl AsyncStorageConnection for AsyncStorage {
             .map_err(Error::from)?
     }
 
-    async fn list_databases(&self) -> Result<Vec<connection::Database>, bonsaidb_core::Error> {
+    async fn list_databases_matching(
+        &self,
+        query: DatabaseQuery,
+    ) -> Result<Vec<connection::Database>, bonsaidb_core::Error> {
         let task_self = self.clone();
         self.runtime
-            .spawn_blocking(move || task_self.storage.list_databases())
+            .spawn_blocking(move || task_self.storage.list_databases_matching(query))
             .await
             .map_err(Error::from)?
     }
@@ -446,6 +862,74 @@ impl AsyncStorageConnection for AsyncStorage {
             .map_err(Error::from)?
     }
 
+    async fn list_users(&self) -> Result<Vec<CollectionDocument<User>>, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        self.runtime
+            .spawn_blocking(move || task_self.storage.list_users())
+            .await
+            .map_err(Error::from)?
+    }
+
+    async fn user_by_name(
+        &self,
+        username: &str,
+    ) -> Result<Option<CollectionDocument<User>>, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let username = username.to_owned();
+        self.runtime
+            .spawn_blocking(move || task_self.storage.user_by_name(&username))
+            .await
+            .map_err(Error::from)?
+    }
+
+    async fn disable_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let user = user.name()?.into_owned();
+        self.runtime
+            .spawn_blocking(move || task_self.storage.disable_user(user))
+            .await
+            .map_err(Error::from)?
+    }
+
+    async fn enable_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let user = user.name()?.into_owned();
+        self.runtime
+            .spawn_blocking(move || task_self.storage.enable_user(user))
+            .await
+            .map_err(Error::from)?
+    }
+
+    async fn unlock_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let user = user.name()?.into_owned();
+        self.runtime
+            .spawn_blocking(move || task_self.storage.unlock_user(user))
+            .await
+            .map_err(Error::from)?
+    }
+
+    async fn effective_permissions<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<Permissions, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let user = user.name()?.into_owned();
+        self.runtime
+            .spawn_blocking(move || task_self.storage.effective_permissions(user))
+            .await
+            .map_err(Error::from)?
+    }
+
     #[cfg(feature = "password-hashing")]
     async fn set_user_password<'user, U: Nameable<'user, u64> + Send + Sync>(
         &self,
@@ -494,6 +978,18 @@ impl AsyncStorageConnection for AsyncStorage {
             .map_err(Error::from)?
     }
 
+    async fn as_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<Self::Authenticated, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let user = user.name()?.into_owned();
+        self.runtime
+            .spawn_blocking(move || task_self.storage.as_user(user).map(Storage::into_async))
+            .await
+            .map_err(Error::from)?
+    }
+
     async fn add_permission_group_to_user<
         'user,
         'group,
@@ -573,6 +1069,189 @@ impl AsyncStorageConnection for AsyncStorage {
             .await
             .map_err(Error::from)?
     }
+
+    async fn create_permission_group(
+        &self,
+        name: &str,
+        statements: Vec<Statement>,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let name = name.to_owned();
+        self.runtime
+            .spawn_blocking(move || task_self.storage.create_permission_group(&name, statements))
+            .await
+            .map_err(Error::from)?
+    }
+
+    async fn list_permission_groups(
+        &self,
+    ) -> Result<Vec<CollectionDocument<PermissionGroup>>, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        self.runtime
+            .spawn_blocking(move || task_self.storage.list_permission_groups())
+            .await
+            .map_err(Error::from)?
+    }
+
+    async fn set_permission_group_statements<'group, G: Nameable<'group, u64> + Send + Sync>(
+        &self,
+        permission_group: G,
+        statements: Vec<Statement>,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let permission_group = permission_group.name()?.into_owned();
+        self.runtime
+            .spawn_blocking(move || {
+                task_self
+                    .storage
+                    .set_permission_group_statements(permission_group, statements)
+            })
+            .await
+            .map_err(Error::from)?
+    }
+
+    async fn update_permission_group_statements<'group, G: Nameable<'group, u64> + Send + Sync>(
+        &self,
+        permission_group: G,
+        expected_version: u64,
+        statements: Vec<Statement>,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let permission_group = permission_group.name()?.into_owned();
+        self.runtime
+            .spawn_blocking(move || {
+                task_self.storage.update_permission_group_statements(
+                    permission_group,
+                    expected_version,
+                    statements,
+                )
+            })
+            .await
+            .map_err(Error::from)?
+    }
+
+    async fn delete_permission_group<'group, G: Nameable<'group, u64> + Send + Sync>(
+        &self,
+        permission_group: G,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let permission_group = permission_group.name()?.into_owned();
+        self.runtime
+            .spawn_blocking(move || task_self.storage.delete_permission_group(permission_group))
+            .await
+            .map_err(Error::from)?
+    }
+
+    async fn create_role(&self, name: &str) -> Result<u64, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let name = name.to_owned();
+        self.runtime
+            .spawn_blocking(move || task_self.storage.create_role(&name))
+            .await
+            .map_err(Error::from)?
+    }
+
+    async fn list_roles(&self) -> Result<Vec<CollectionDocument<Role>>, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        self.runtime
+            .spawn_blocking(move || task_self.storage.list_roles())
+            .await
+            .map_err(Error::from)?
+    }
+
+    async fn add_permission_group_to_role<
+        'role,
+        'group,
+        R: Nameable<'role, u64> + Send + Sync,
+        G: Nameable<'group, u64> + Send + Sync,
+    >(
+        &self,
+        role: R,
+        permission_group: G,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let role = role.name()?.into_owned();
+        let permission_group = permission_group.name()?.into_owned();
+        self.runtime
+            .spawn_blocking(move || {
+                task_self
+                    .storage
+                    .add_permission_group_to_role(role, permission_group)
+            })
+            .await
+            .map_err(Error::from)?
+    }
+
+    async fn remove_permission_group_from_role<
+        'role,
+        'group,
+        R: Nameable<'role, u64> + Send + Sync,
+        G: Nameable<'group, u64> + Send + Sync,
+    >(
+        &self,
+        role: R,
+        permission_group: G,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let role = role.name()?.into_owned();
+        let permission_group = permission_group.name()?.into_owned();
+        self.runtime
+            .spawn_blocking(move || {
+                task_self
+                    .storage
+                    .remove_permission_group_from_role(role, permission_group)
+            })
+            .await
+            .map_err(Error::from)?
+    }
+
+    async fn update_role_permission_groups<'role, R: Nameable<'role, u64> + Send + Sync>(
+        &self,
+        role: R,
+        expected_version: u64,
+        permission_groups: Vec<u64>,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let role = role.name()?.into_owned();
+        self.runtime
+            .spawn_blocking(move || {
+                task_self.storage.update_role_permission_groups(
+                    role,
+                    expected_version,
+                    permission_groups,
+                )
+            })
+            .await
+            .map_err(Error::from)?
+    }
+
+    async fn delete_role<'role, R: Nameable<'role, u64> + Send + Sync>(
+        &self,
+        role: R,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let role = role.name()?.into_owned();
+        self.runtime
+            .spawn_blocking(move || task_self.storage.delete_role(role))
+            .await
+            .map_err(Error::from)?
+    }
+
+    async fn list_sessions<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<Vec<SessionInfo>, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let user = user.name()?.into_owned();
+        self.runtime
+            .spawn_blocking(move || task_self.storage.list_sessions(user))
+            .await
+            .map_err(Error::from)?
+    }
+
+    async fn revoke_session(&self, session_id: SessionId) -> Result<(), bonsaidb_core::Error> {
+        self.storage.revoke_session(session_id)
+    }
 }
 
 impl HasSession for AsyncDatabase {
@@ -581,6 +1260,21 @@ impl HasSession for AsyncDatabase {
     }
 }
 
+#[cfg(feature = "encryption")]
+impl FieldEncryption for AsyncDatabase {
+    fn encrypt_field_bytes(
+        &self,
+        key: &KeyId,
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, bonsaidb_core::Error> {
+        self.database.encrypt_field_bytes(key, plaintext)
+    }
+
+    fn decrypt_field_bytes(&self, payload: &[u8]) -> Result<Vec<u8>, bonsaidb_core::Error> {
+        self.database.decrypt_field_bytes(payload)
+    }
+}
+
 #[async_trait]
 impl AsyncConnection for AsyncDatabase {
     type Storage = AsyncStorage;
@@ -765,6 +1459,31 @@ impl AsyncLowLevelConnection for AsyncDatabase {
             .map_err(Error::from)?
     }
 
+    async fn list_projected_from_collection(
+        &self,
+        ids: Range<DocumentId>,
+        order: Sort,
+        limit: Option<u32>,
+        collection: &CollectionName,
+        projection: &Name,
+    ) -> Result<Vec<OwnedDocument>, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let collection = collection.clone();
+        let projection = projection.clone();
+        self.runtime
+            .spawn_blocking(move || {
+                task_self.database.list_projected_from_collection(
+                    ids,
+                    order,
+                    limit,
+                    &collection,
+                    &projection,
+                )
+            })
+            .await
+            .map_err(Error::from)?
+    }
+
     async fn count_from_collection(
         &self,
         ids: Range<DocumentId>,
@@ -815,14 +1534,20 @@ impl AsyncLowLevelConnection for AsyncDatabase {
         order: Sort,
         limit: Option<u32>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<Vec<schema::view::map::Serialized>, bonsaidb_core::Error> {
         let task_self = self.clone();
         let view = view.clone();
         self.runtime
             .spawn_blocking(move || {
-                task_self
-                    .database
-                    .query_by_name(&view, key, order, limit, access_policy)
+                task_self.database.query_by_name(
+                    &view,
+                    key,
+                    order,
+                    limit,
+                    access_policy,
+                    read_options,
+                )
             })
             .await
             .map_err(Error::from)?
@@ -835,14 +1560,49 @@ impl AsyncLowLevelConnection for AsyncDatabase {
         order: Sort,
         limit: Option<u32>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<schema::view::map::MappedSerializedDocuments, bonsaidb_core::Error> {
         let task_self = self.clone();
         let view = view.clone();
         self.runtime
             .spawn_blocking(move || {
-                task_self
-                    .database
-                    .query_by_name_with_docs(&view, key, order, limit, access_policy)
+                task_self.database.query_by_name_with_docs(
+                    &view,
+                    key,
+                    order,
+                    limit,
+                    access_policy,
+                    read_options,
+                )
+            })
+            .await
+            .map_err(Error::from)?
+    }
+
+    async fn query_by_name_with_projected_docs(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+        projection: &Name,
+    ) -> Result<schema::view::map::MappedSerializedDocuments, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let view = view.clone();
+        let projection = projection.clone();
+        self.runtime
+            .spawn_blocking(move || {
+                task_self.database.query_by_name_with_projected_docs(
+                    &view,
+                    key,
+                    order,
+                    limit,
+                    access_policy,
+                    read_options,
+                    &projection,
+                )
             })
             .await
             .map_err(Error::from)?
@@ -853,11 +1613,16 @@ impl AsyncLowLevelConnection for AsyncDatabase {
         view: &ViewName,
         key: Option<SerializedQueryKey>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<Vec<u8>, bonsaidb_core::Error> {
         let task_self = self.clone();
         let view = view.clone();
         self.runtime
-            .spawn_blocking(move || task_self.database.reduce_by_name(&view, key, access_policy))
+            .spawn_blocking(move || {
+                task_self
+                    .database
+                    .reduce_by_name(&view, key, access_policy, read_options)
+            })
             .await
             .map_err(Error::from)?
     }
@@ -867,6 +1632,7 @@ impl AsyncLowLevelConnection for AsyncDatabase {
         view: &ViewName,
         key: Option<SerializedQueryKey>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<Vec<MappedSerializedValue>, bonsaidb_core::Error> {
         let task_self = self.clone();
         let view = view.clone();
@@ -874,7 +1640,95 @@ impl AsyncLowLevelConnection for AsyncDatabase {
             .spawn_blocking(move || {
                 task_self
                     .database
-                    .reduce_grouped_by_name(&view, key, access_policy)
+                    .reduce_grouped_by_name(&view, key, access_policy, read_options)
+            })
+            .await
+            .map_err(Error::from)?
+    }
+
+    async fn aggregate_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        aggregations: &[Aggregation],
+        grouping: AggregationGrouping,
+        access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+    ) -> Result<Vec<SerializedAggregatedGroup>, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let view = view.clone();
+        let aggregations = aggregations.to_vec();
+        self.runtime
+            .spawn_blocking(move || {
+                task_self.database.aggregate_by_name(
+                    &view,
+                    key,
+                    &aggregations,
+                    grouping,
+                    access_policy,
+                    read_options,
+                )
+            })
+            .await
+            .map_err(Error::from)?
+    }
+
+    async fn count_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let view = view.clone();
+        self.runtime
+            .spawn_blocking(move || {
+                task_self
+                    .database
+                    .count_by_name(&view, key, access_policy, read_options)
+            })
+            .await
+            .map_err(Error::from)?
+    }
+
+    async fn document_mappings_by_name(
+        &self,
+        view: &ViewName,
+        document_id: DocumentId,
+        access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+    ) -> Result<Vec<schema::view::map::Serialized>, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let view = view.clone();
+        self.runtime
+            .spawn_blocking(move || {
+                task_self.database.document_mappings_by_name(
+                    &view,
+                    document_id,
+                    access_policy,
+                    read_options,
+                )
+            })
+            .await
+            .map_err(Error::from)?
+    }
+
+    async fn query_join_by_name(
+        &self,
+        join: &ViewName,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+    ) -> Result<Vec<SerializedJoinedEntry>, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let join = join.clone();
+        self.runtime
+            .spawn_blocking(move || {
+                task_self
+                    .database
+                    .query_join_by_name(&join, key, order, limit, access_policy)
             })
             .await
             .map_err(Error::from)?
@@ -20,6 +20,15 @@ pub enum StorageCommand {
     /// Executes an admin command.
     #[clap(subcommand)]
     Admin(admin::Command),
+    /// Executes a read-only SQL query against the views registered in the
+    /// storage configuration.
+    #[cfg(feature = "sql")]
+    Query {
+        /// The name of the database to query.
+        database: String,
+        /// The query to execute, such as `SELECT * FROM my_view LIMIT 10`.
+        query: String,
+    },
 }
 
 /// A backup location.
@@ -45,6 +54,14 @@ impl StorageCommand {
             StorageCommand::Backup(location) => location.backup(storage),
             StorageCommand::Restore(location) => location.restore(storage),
             StorageCommand::Admin(admin) => admin.execute(storage),
+            #[cfg(feature = "sql")]
+            StorageCommand::Query { database, query } => {
+                let results = storage
+                    .database_without_schema(&database)?
+                    .query_sql(&query)?;
+                print_results(&results);
+                Ok(())
+            }
         }
     }
 
@@ -55,10 +72,35 @@ impl StorageCommand {
             StorageCommand::Backup(location) => location.backup_async(storage).await,
             StorageCommand::Restore(location) => location.restore_async(storage).await,
             StorageCommand::Admin(admin) => admin.execute_async(storage).await,
+            #[cfg(feature = "sql")]
+            StorageCommand::Query { database, query } => {
+                let results = storage
+                    .database_without_schema(&database)
+                    .await?
+                    .query_sql(&query)
+                    .await?;
+                print_results(&results);
+                Ok(())
+            }
         }
     }
 }
 
+/// Prints `results` to stdout with one row per line and tabs between columns.
+#[cfg(feature = "sql")]
+fn print_results(results: &bonsaidb_core::sql::SqlResults) {
+    println!("{}", results.columns.join("\t"));
+    for row in &results.rows {
+        println!(
+            "{}",
+            row.iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\t")
+        );
+    }
+}
+
 impl Location {
     /// Backs-up `storage` to `self`.
     pub fn backup(&self, storage: &Storage) -> Result<(), Error> {
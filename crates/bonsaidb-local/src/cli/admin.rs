@@ -22,6 +22,24 @@ pub enum UserCommand {
         #[clap(long)]
         password: bool,
     },
+    /// Lists all users.
+    List,
+    /// Deletes a user.
+    Delete {
+        /// The username of the user to delete.
+        username: String,
+    },
+    /// Disables a user, preventing it from authenticating and revoking all of
+    /// its active sessions.
+    Disable {
+        /// The username of the user to disable.
+        username: String,
+    },
+    /// Enables a previously disabled user.
+    Enable {
+        /// The username of the user to enable.
+        username: String,
+    },
     /// Sets an existing user's password. The password will be prompted for over
     /// stdin.
     #[cfg(feature = "password-hashing")]
@@ -81,6 +99,35 @@ impl Command {
                     println!("User #{user_id} {username} created");
                     Ok(())
                 }
+                UserCommand::List => {
+                    for user in storage.list_users()? {
+                        let disabled = if user.contents.disabled {
+                            " (disabled)"
+                        } else {
+                            ""
+                        };
+                        println!(
+                            "User #{} {}{disabled}",
+                            user.header.id, user.contents.username
+                        );
+                    }
+                    Ok(())
+                }
+                UserCommand::Delete { username } => {
+                    storage.delete_user(&username)?;
+                    println!("User {username} deleted");
+                    Ok(())
+                }
+                UserCommand::Disable { username } => {
+                    storage.disable_user(&username)?;
+                    println!("User {username} disabled");
+                    Ok(())
+                }
+                UserCommand::Enable { username } => {
+                    storage.enable_user(&username)?;
+                    println!("User {username} enabled");
+                    Ok(())
+                }
                 #[cfg(feature = "password-hashing")]
                 UserCommand::SetPassword { username } => {
                     let password = super::read_password_from_stdin(true)?;
@@ -136,6 +183,35 @@ impl Command {
                     println!("User #{user_id} {username} created");
                     Ok(())
                 }
+                UserCommand::List => {
+                    for user in storage.list_users().await? {
+                        let disabled = if user.contents.disabled {
+                            " (disabled)"
+                        } else {
+                            ""
+                        };
+                        println!(
+                            "User #{} {}{disabled}",
+                            user.header.id, user.contents.username
+                        );
+                    }
+                    Ok(())
+                }
+                UserCommand::Delete { username } => {
+                    storage.delete_user(&username).await?;
+                    println!("User {username} deleted");
+                    Ok(())
+                }
+                UserCommand::Disable { username } => {
+                    storage.disable_user(&username).await?;
+                    println!("User {username} disabled");
+                    Ok(())
+                }
+                UserCommand::Enable { username } => {
+                    storage.enable_user(&username).await?;
+                    println!("User {username} enabled");
+                    Ok(())
+                }
                 #[cfg(feature = "password-hashing")]
                 UserCommand::SetPassword { username } => {
                     let password = super::read_password_from_stdin(true)?;
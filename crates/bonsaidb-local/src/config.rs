@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
@@ -6,7 +6,9 @@ use std::time::Duration;
 #[cfg(feature = "encryption")]
 use bonsaidb_core::document::KeyId;
 use bonsaidb_core::permissions::Permissions;
-use bonsaidb_core::schema::{Schema, SchemaName};
+use bonsaidb_core::schema::{Collection, CollectionName, Schema, SchemaName, View, ViewName};
+#[cfg(feature = "sql")]
+use bonsaidb_core::sql::{SqlCatalog, SqlKey};
 use sysinfo::{CpuRefreshKind, RefreshKind, System, SystemExt};
 
 use crate::storage::{DatabaseOpener, StorageSchemaOpener};
@@ -18,6 +20,13 @@ use crate::Error;
 mod argon;
 #[cfg(feature = "password-hashing")]
 pub use argon::*;
+#[cfg(feature = "password-hashing")]
+mod password_policy;
+#[cfg(feature = "password-hashing")]
+pub use password_policy::*;
+mod sharding;
+pub(crate) use sharding::shard_database_name;
+pub use sharding::{Shard, Sharding, ShardingStrategy};
 
 /// Configuration options for [`Storage`](crate::storage::Storage).
 #[derive(Debug, Clone)]
@@ -65,6 +74,26 @@ pub struct StorageConfiguration {
     /// Controls how the key-value store persists keys, on a per-database basis.
     pub key_value_persistence: KeyValuePersistence,
 
+    /// Overrides [`Self::key_value_persistence`] for the databases with the
+    /// given names.
+    pub database_key_value_persistence: HashMap<String, KeyValuePersistence>,
+
+    /// The maximum number of expensive operations each database executes
+    /// concurrently. View queries, reductions, and aggregations, as well as
+    /// listing documents without a limit, count towards this limit. Once it is
+    /// reached, further operations wait for one to finish. This prevents
+    /// expensive queries against one database from occupying all of the
+    /// storage's threads. Default value is `None`, which does not limit
+    /// operations.
+    pub concurrent_query_limit: Option<usize>,
+
+    /// Overrides [`Self::concurrent_query_limit`] for the databases with the
+    /// given names.
+    pub database_concurrent_query_limit: HashMap<String, usize>,
+
+    /// Configuration options related to authentication sessions.
+    pub sessions: Sessions,
+
     /// Sets the default compression algorithm.
     #[cfg(feature = "compression")]
     pub default_compression: Option<Compression>,
@@ -72,10 +101,29 @@ pub struct StorageConfiguration {
     /// The permissions granted to authenticated connections to this server.
     pub authenticated_permissions: Permissions,
 
+    /// If set, all trees will be read on a background thread each time this
+    /// duration elapses, verifying that their data is intact. See
+    /// [`Storage::scrub()`](crate::Storage::scrub) for more information.
+    /// Default value is `None`.
+    pub scrub_interval: Option<Duration>,
+
     /// Password hashing configuration.
     #[cfg(feature = "password-hashing")]
     pub argon: ArgonConfiguration,
 
+    /// The rules for user passwords.
+    #[cfg(feature = "password-hashing")]
+    pub password_policy: PasswordPolicy,
+
+    /// The views that can be queried using
+    /// [`Database::query_sql()`](crate::Database::query_sql).
+    #[cfg(feature = "sql")]
+    pub sql: SqlCatalog,
+
+    /// The collections whose documents are split across multiple shards,
+    /// keyed by database name and then by collection name.
+    pub sharding: HashMap<String, HashMap<CollectionName, Sharding>>,
+
     pub(crate) initial_schemas: HashMap<SchemaName, Arc<dyn DatabaseOpener>>,
 }
 
@@ -99,9 +147,19 @@ impl Default for StorageConfiguration {
             workers: Tasks::default_for(&system),
             views: Views::default(),
             key_value_persistence: KeyValuePersistence::default(),
+            database_key_value_persistence: HashMap::default(),
+            concurrent_query_limit: None,
+            database_concurrent_query_limit: HashMap::default(),
+            sessions: Sessions::default(),
             authenticated_permissions: Permissions::default(),
+            scrub_interval: None,
             #[cfg(feature = "password-hashing")]
             argon: ArgonConfiguration::default_for(&system),
+            #[cfg(feature = "password-hashing")]
+            password_policy: PasswordPolicy::default(),
+            #[cfg(feature = "sql")]
+            sql: SqlCatalog::default(),
+            sharding: HashMap::default(),
             initial_schemas: HashMap::default(),
         }
     }
@@ -154,6 +212,68 @@ pub struct Views {
     /// be checked. However, for faster startup time, you may wish to delay the
     /// integrity scan. Default value is `false`.
     pub check_integrity_on_open: bool,
+
+    /// The views whose query results should be cached. Repeated queries with
+    /// the same key filter, order, and limit will return the cached results
+    /// until a document in the view's collection is changed. This is most
+    /// useful for views over data that is queried frequently but rarely
+    /// changes. Default value is empty.
+    pub cached_queries: HashSet<ViewName>,
+
+    /// If set, the number of times each view is queried is recorded, and when
+    /// a database is opened, this many of its most queried views are updated
+    /// in the background. This allows the first queries after a restart to
+    /// avoid waiting for views to be updated with
+    /// [`AccessPolicy::UpdateBefore`](bonsaidb_core::connection::AccessPolicy::UpdateBefore).
+    /// Default value is `None`.
+    pub warm_on_open: Option<usize>,
+
+    /// The names of databases whose views continue to serve their existing
+    /// data while being rebuilt after their definitions change.
+    ///
+    /// Normally, a view's stored data is discarded when its definition
+    /// changes, and queries using
+    /// [`AccessPolicy::UpdateBefore`](bonsaidb_core::connection::AccessPolicy::UpdateBefore)
+    /// wait until the view has been rebuilt. For databases in this set, queries
+    /// return immediately while the view is rebuilt:
+    ///
+    /// - Lazy views are rebuilt side-by-side. The new entries are built
+    ///   separately while queries continue to return the existing entries, and
+    ///   the existing entries are replaced in a single transaction once every
+    ///   document has been mapped.
+    /// - Eager views are updated by each transaction, so they are rebuilt in
+    ///   place. Each document is mapped again while the existing entries are
+    ///   kept, and queries return a mix of stale and rebuilt entries until the
+    ///   rebuild completes. This should only be used for eager views whose key
+    ///   and value types are unchanged by the new definition.
+    ///
+    /// Default value is empty.
+    pub stale_while_rebuilding: HashSet<String>,
+
+    /// If true, view queries omit the entries whose source documents the
+    /// querying session is not allowed to
+    /// [`Get`](bonsaidb_core::permissions::bonsai::DocumentAction::Get).
+    /// Counts, reductions, and aggregations also exclude these entries, which
+    /// requires reducing the readable entries of each key rather than using
+    /// the cached reduced values.
+    ///
+    /// Queries that include documents always omit the documents the session
+    /// can't read, along with their entries. When this option is disabled,
+    /// queries that don't include documents return every entry, which can
+    /// reveal the keys and values that unreadable documents emit. Default
+    /// value is `false`.
+    pub redact_unreadable_entries: bool,
+}
+
+/// Configuration options for authentication sessions.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Sessions {
+    /// If specified, sessions that have not been used for this duration will
+    /// expire. Default value is `None`.
+    pub idle_timeout: Option<Duration>,
+    /// If specified, sessions will expire once this duration has elapsed since
+    /// they were created, regardless of activity. Default value is `None`.
+    pub absolute_timeout: Option<Duration>,
 }
 
 /// Rules for persisting key-value changes. Default persistence is to
@@ -352,6 +472,18 @@ pub trait Builder: Sized {
     /// Sets [`Views::check_integrity_on_open`] to `check` and returns self.
     #[must_use]
     fn check_view_integrity_on_open(self, check: bool) -> Self;
+    /// Adds `view` to [`Views::cached_queries`] and returns self.
+    #[must_use]
+    fn cache_view_queries<V: View>(self, view: &V) -> Self;
+    /// Sets [`Views::warm_on_open`] to `count` and returns self.
+    #[must_use]
+    fn warm_views_on_open(self, count: usize) -> Self;
+    /// Adds `database` to [`Views::stale_while_rebuilding`] and returns self.
+    #[must_use]
+    fn serve_stale_views_while_rebuilding<N: Into<String>>(self, database: N) -> Self;
+    /// Sets [`Views::redact_unreadable_entries`] to `redact` and returns self.
+    #[must_use]
+    fn redact_unreadable_view_entries(self, redact: bool) -> Self;
     /// Sets [`StorageConfiguration::default_compression`](StorageConfiguration#structfield.default_compression) to `path` and returns self.
     #[cfg(feature = "compression")]
     #[must_use]
@@ -359,13 +491,64 @@ pub trait Builder: Sized {
     /// Sets [`StorageConfiguration::key_value_persistence`](StorageConfiguration#structfield.key_value_persistence) to `persistence` and returns self.
     #[must_use]
     fn key_value_persistence(self, persistence: KeyValuePersistence) -> Self;
+    /// Sets the key-value persistence of the database named `database` to
+    /// `persistence` in
+    /// [`StorageConfiguration::database_key_value_persistence`](StorageConfiguration#structfield.database_key_value_persistence)
+    /// and returns self.
+    #[must_use]
+    fn database_key_value_persistence<N: Into<String>>(
+        self,
+        database: N,
+        persistence: KeyValuePersistence,
+    ) -> Self;
+    /// Sets [`StorageConfiguration::concurrent_query_limit`](StorageConfiguration#structfield.concurrent_query_limit) to `limit` and returns self.
+    #[must_use]
+    fn concurrent_query_limit(self, limit: usize) -> Self;
+    /// Sets the concurrent query limit of the database named `database` to
+    /// `limit` in
+    /// [`StorageConfiguration::database_concurrent_query_limit`](StorageConfiguration#structfield.database_concurrent_query_limit)
+    /// and returns self.
+    #[must_use]
+    fn database_concurrent_query_limit<N: Into<String>>(self, database: N, limit: usize) -> Self;
+    /// Sets [`Sessions::idle_timeout`] to `timeout` and returns self.
+    #[must_use]
+    fn session_idle_timeout(self, timeout: Duration) -> Self;
+    /// Sets [`Sessions::absolute_timeout`] to `timeout` and returns self.
+    #[must_use]
+    fn session_absolute_timeout(self, timeout: Duration) -> Self;
     /// Sets [`Self::authenticated_permissions`](Self#structfield.authenticated_permissions) to `authenticated_permissions` and returns self.
     #[must_use]
     fn authenticated_permissions<P: Into<Permissions>>(self, authenticated_permissions: P) -> Self;
+    /// Sets [`StorageConfiguration::scrub_interval`](StorageConfiguration#structfield.scrub_interval) to `interval` and returns self.
+    #[must_use]
+    fn scrub_interval(self, interval: Duration) -> Self;
     /// Sets [`StorageConfiguration::argon`](StorageConfiguration#structfield.argon) to `argon` and returns self.
     #[cfg(feature = "password-hashing")]
     #[must_use]
     fn argon(self, argon: ArgonConfiguration) -> Self;
+    /// Sets [`StorageConfiguration::password_policy`](StorageConfiguration#structfield.password_policy) to `policy` and returns self.
+    #[cfg(feature = "password-hashing")]
+    #[must_use]
+    fn password_policy(self, policy: PasswordPolicy) -> Self;
+    /// Registers `view` as the table named `table` in
+    /// [`StorageConfiguration::sql`](StorageConfiguration#structfield.sql) and
+    /// returns self.
+    #[cfg(feature = "sql")]
+    #[must_use]
+    fn sql_view<V>(self, table: impl Into<String>, view: &V) -> Self
+    where
+        V: View,
+        V::Key: SqlKey;
+    /// Splits the documents of collection `C` in the database named `database`
+    /// across the shards in `sharding` by adding it to
+    /// [`StorageConfiguration::sharding`](StorageConfiguration#structfield.sharding)
+    /// and returns self.
+    #[must_use]
+    fn shard_collection<C: Collection>(
+        self,
+        database: impl Into<String>,
+        sharding: Sharding,
+    ) -> Self;
 }
 
 impl Builder for StorageConfiguration {
@@ -425,11 +608,66 @@ impl Builder for StorageConfiguration {
         self
     }
 
+    fn cache_view_queries<V: View>(mut self, view: &V) -> Self {
+        self.views.cached_queries.insert(view.view_name());
+        self
+    }
+
+    fn warm_views_on_open(mut self, count: usize) -> Self {
+        self.views.warm_on_open = Some(count);
+        self
+    }
+
+    fn serve_stale_views_while_rebuilding<N: Into<String>>(mut self, database: N) -> Self {
+        self.views.stale_while_rebuilding.insert(database.into());
+        self
+    }
+
+    fn redact_unreadable_view_entries(mut self, redact: bool) -> Self {
+        self.views.redact_unreadable_entries = redact;
+        self
+    }
+
     fn key_value_persistence(mut self, persistence: KeyValuePersistence) -> Self {
         self.key_value_persistence = persistence;
         self
     }
 
+    fn database_key_value_persistence<N: Into<String>>(
+        mut self,
+        database: N,
+        persistence: KeyValuePersistence,
+    ) -> Self {
+        self.database_key_value_persistence
+            .insert(database.into(), persistence);
+        self
+    }
+
+    fn concurrent_query_limit(mut self, limit: usize) -> Self {
+        self.concurrent_query_limit = Some(limit);
+        self
+    }
+
+    fn database_concurrent_query_limit<N: Into<String>>(
+        mut self,
+        database: N,
+        limit: usize,
+    ) -> Self {
+        self.database_concurrent_query_limit
+            .insert(database.into(), limit);
+        self
+    }
+
+    fn session_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.sessions.idle_timeout = Some(timeout);
+        self
+    }
+
+    fn session_absolute_timeout(mut self, timeout: Duration) -> Self {
+        self.sessions.absolute_timeout = Some(timeout);
+        self
+    }
+
     fn authenticated_permissions<P: Into<Permissions>>(
         mut self,
         authenticated_permissions: P,
@@ -438,11 +676,44 @@ impl Builder for StorageConfiguration {
         self
     }
 
+    fn scrub_interval(mut self, interval: Duration) -> Self {
+        self.scrub_interval = Some(interval);
+        self
+    }
+
     #[cfg(feature = "password-hashing")]
     fn argon(mut self, argon: ArgonConfiguration) -> Self {
         self.argon = argon;
         self
     }
+
+    #[cfg(feature = "password-hashing")]
+    fn password_policy(mut self, policy: PasswordPolicy) -> Self {
+        self.password_policy = policy;
+        self
+    }
+
+    #[cfg(feature = "sql")]
+    fn sql_view<V>(mut self, table: impl Into<String>, view: &V) -> Self
+    where
+        V: View,
+        V::Key: SqlKey,
+    {
+        self.sql = self.sql.with_view(table, view);
+        self
+    }
+
+    fn shard_collection<C: Collection>(
+        mut self,
+        database: impl Into<String>,
+        sharding: Sharding,
+    ) -> Self {
+        self.sharding
+            .entry(database.into())
+            .or_default()
+            .insert(C::collection_name(), sharding);
+        self
+    }
 }
 
 pub(crate) trait SystemDefault: Sized {
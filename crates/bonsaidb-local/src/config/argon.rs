@@ -1,6 +1,6 @@
 use std::time::Duration;
 
-use argon2::Algorithm;
+use argon2::{Algorithm, ParamsBuilder};
 use sysinfo::{System, SystemExt};
 
 use crate::config::SystemDefault;
@@ -65,6 +65,24 @@ impl ArgonParams {
     pub fn default_for(system: &System, hashers: u32) -> Self {
         ArgonParams::Timed(TimedArgonParams::default_for(system, hashers))
     }
+
+    /// Returns fixed parameters where each hashing operation uses
+    /// `ram_per_hasher` bytes of RAM (`m`), `iterations` passes over the
+    /// memory (`t`), and `lanes` degrees of parallelism (`p`).
+    ///
+    /// Unlike [`TimedArgonParams`], these parameters will not be adjusted based
+    /// on how long hashing takes on the current machine. Returns an error if
+    /// any parameter is outside of the range Argon2 allows.
+    pub fn fixed(ram_per_hasher: u32, iterations: u32, lanes: u32) -> Result<Self, argon2::Error> {
+        let mut builder = ParamsBuilder::new();
+        builder
+            .m_cost(ram_per_hasher / 1_024)?
+            .t_cost(iterations)?
+            .p_cost(lanes)?;
+        // Validate the combination of parameters.
+        builder.clone().params()?;
+        Ok(ArgonParams::Params(builder))
+    }
 }
 
 /// Automatic configuration based on execution time. This is measured during the
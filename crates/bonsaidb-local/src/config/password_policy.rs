@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+/// Rules for the passwords users authenticate with.
+///
+/// The state used to enforce these rules is stored on each
+/// [`User`](bonsaidb_core::admin::User) in the admin database. The default
+/// policy does not restrict passwords.
+#[derive(Debug, Clone, Copy, Default)]
+#[must_use]
+pub struct PasswordPolicy {
+    /// The minimum number of characters a password must contain.
+    /// [`StorageConnection::set_user_password()`](bonsaidb_core::connection::StorageConnection::set_user_password)
+    /// returns
+    /// [`Error::PasswordTooShort`](bonsaidb_core::Error::PasswordTooShort) for
+    /// shorter passwords. Default value is `0`.
+    pub minimum_length: usize,
+    /// If specified, passwords expire once this duration has elapsed since they
+    /// were set. Authenticating with an expired password returns
+    /// [`Error::PasswordExpired`](bonsaidb_core::Error::PasswordExpired), and a
+    /// new password must be set before the user can authenticate with a
+    /// password again. Passwords set before this policy was enabled begin
+    /// aging when the user next authenticates. Default value is `None`.
+    pub maximum_age: Option<Duration>,
+    /// If specified, users are locked out after repeatedly failing to
+    /// authenticate. Default value is `None`.
+    pub lockout: Option<Lockout>,
+}
+
+impl PasswordPolicy {
+    /// Sets [`Self::minimum_length`] to `length` and returns self.
+    pub const fn with_minimum_length(mut self, length: usize) -> Self {
+        self.minimum_length = length;
+        self
+    }
+
+    /// Sets [`Self::maximum_age`] to `age` and returns self.
+    pub const fn with_maximum_age(mut self, age: Duration) -> Self {
+        self.maximum_age = Some(age);
+        self
+    }
+
+    /// Sets [`Self::lockout`] to `lockout` and returns self.
+    pub const fn with_lockout(mut self, lockout: Lockout) -> Self {
+        self.lockout = Some(lockout);
+        self
+    }
+}
+
+/// Locks a user out of password authentication after too many consecutive
+/// failed attempts.
+///
+/// While locked, authenticating returns
+/// [`Error::AccountLocked`](bonsaidb_core::Error::AccountLocked) without
+/// checking the password. Setting the user's password removes the lock.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[must_use]
+pub struct Lockout {
+    /// The number of consecutive failed attempts that locks the user.
+    pub failures: u32,
+    /// How long the user is locked out for.
+    pub duration: Duration,
+}
+
+impl Lockout {
+    /// Returns a lockout that lasts for `duration` after `failures`
+    /// consecutive failed attempts.
+    pub const fn after_failures(failures: u32, duration: Duration) -> Self {
+        Self { failures, duration }
+    }
+}
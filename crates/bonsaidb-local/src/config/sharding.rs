@@ -0,0 +1,164 @@
+use std::path::{Path, PathBuf};
+
+use bonsaidb_core::document::DocumentId;
+use bonsaidb_core::key::{Key, KeyEncoding};
+
+/// Configuration for splitting a collection's documents across multiple
+/// shards.
+///
+/// Each shard is stored as its own set of files, which can be placed on a
+/// separate disk using [`Shard::path()`]. Documents are assigned to a shard
+/// based on their id using the configured [`ShardingStrategy`]. Retrieving a
+/// document by its id only reads from the shard that the document belongs to,
+/// while listing documents and querying views reads from every shard and
+/// merges the results. Sharding is transparent to users of
+/// [`Connection`](bonsaidb_core::connection::Connection).
+///
+/// A transaction can only modify a single shard. Transactions that modify
+/// documents in multiple shards, or that modify a sharded collection and
+/// another collection at the same time, will return an error.
+///
+/// Changes to sharded collections are not included in the database's
+/// transaction log, and sharded collections are not included in backups.
+#[derive(Debug, Clone)]
+#[must_use]
+#[non_exhaustive]
+pub struct Sharding {
+    /// How documents are assigned to shards.
+    pub strategy: ShardingStrategy,
+    /// The shards that the collection's documents are stored in.
+    pub shards: Vec<Shard>,
+}
+
+impl Sharding {
+    /// Returns a configuration that assigns documents to shards using a hash
+    /// of their id.
+    pub const fn hashed() -> Self {
+        Self {
+            strategy: ShardingStrategy::Hash,
+            shards: Vec::new(),
+        }
+    }
+
+    /// Returns a configuration that assigns documents to shards by comparing
+    /// their ids to `boundaries`. See [`ShardingStrategy::Range`] for more
+    /// information.
+    pub fn ranged<PrimaryKey, Boundaries>(
+        boundaries: Boundaries,
+    ) -> Result<Self, bonsaidb_core::Error>
+    where
+        PrimaryKey: for<'k> Key<'k>,
+        Boundaries: IntoIterator,
+        Boundaries::Item: for<'k> KeyEncoding<'k, PrimaryKey>,
+    {
+        Ok(Self {
+            strategy: ShardingStrategy::Range(
+                boundaries
+                    .into_iter()
+                    .map(|boundary| DocumentId::new::<PrimaryKey, _>(&boundary))
+                    .collect::<Result<_, _>>()?,
+            ),
+            shards: Vec::new(),
+        })
+    }
+
+    /// Adds `shard` to [`Self::shards`](Self#structfield.shards) and returns self.
+    pub fn with_shard(mut self, shard: Shard) -> Self {
+        self.shards.push(shard);
+        self
+    }
+
+    /// Returns the index of the shard that stores the document with `id`.
+    #[must_use]
+    pub fn shard_for(&self, id: &DocumentId) -> usize {
+        match &self.strategy {
+            ShardingStrategy::Hash => {
+                // FNV-1a is used because it is stable across platforms and
+                // releases, unlike `std`'s default hasher.
+                let hash = id
+                    .as_ref()
+                    .iter()
+                    .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+                        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+                    });
+                usize::try_from(hash % self.shards.len() as u64).expect("shard count is a usize")
+            }
+            ShardingStrategy::Range(boundaries) => boundaries
+                .iter()
+                .position(|boundary| id < boundary)
+                .unwrap_or(boundaries.len()),
+        }
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if self.shards.is_empty() {
+            return Err(String::from("at least one shard must be configured"));
+        }
+        if let ShardingStrategy::Range(boundaries) = &self.strategy {
+            if boundaries.len() + 1 != self.shards.len() {
+                return Err(format!(
+                    "{} shards require {} range boundaries, but {} were provided",
+                    self.shards.len(),
+                    self.shards.len() - 1,
+                    boundaries.len()
+                ));
+            }
+            if boundaries.windows(2).any(|pair| pair[0] >= pair[1]) {
+                return Err(String::from("range boundaries must be sorted and unique"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Determines which shard a document is stored in.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ShardingStrategy {
+    /// Documents are assigned to shards using a hash of their id. This
+    /// distributes documents evenly, but every shard is read when listing
+    /// documents or querying views.
+    Hash,
+    /// Documents are assigned to shards by comparing their ids to a list of
+    /// sorted boundaries. A document whose id is less than the first boundary
+    /// is stored in the first shard, a document whose id is less than the
+    /// second boundary is stored in the second shard, and so on. Documents
+    /// whose ids are greater than or equal to the last boundary are stored in
+    /// the last shard. One fewer boundary than the number of shards must be
+    /// provided.
+    Range(Vec<DocumentId>),
+}
+
+/// A shard of a [`Sharding`] configuration.
+#[derive(Debug, Clone)]
+#[must_use]
+#[non_exhaustive]
+pub struct Shard {
+    /// The name of the shard. Must be unique within the database, and follow
+    /// the same rules as database names.
+    pub name: String,
+    /// The location to store the shard's data. If not specified, the shard is
+    /// stored within the storage's path.
+    pub path: Option<PathBuf>,
+}
+
+impl Shard {
+    /// Returns a shard named `name` that is stored within the storage's path.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            path: None,
+        }
+    }
+
+    /// Sets [`Self::path`](Self#structfield.path) to `path` and returns self.
+    pub fn path<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.path = Some(path.as_ref().to_path_buf());
+        self
+    }
+}
+
+/// Returns the name of the database that stores `shard` of `database`.
+pub(crate) fn shard_database_name(database: &str, shard: &str) -> String {
+    format!("_shard.{database}.{shard}")
+}
@@ -2,19 +2,23 @@ use std::borrow::{Borrow, Cow};
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::convert::Infallible;
 use std::ops::{self, Deref};
+use std::slice;
 use std::sync::Arc;
+use std::time::Instant;
 use std::u8;
 
-use bonsaidb_core::arc_bytes::serde::CowBytes;
-use bonsaidb_core::arc_bytes::ArcBytes;
+use bonsaidb_core::arc_bytes::serde::{Bytes, CowBytes};
+use bonsaidb_core::arc_bytes::{ArcBytes, OwnedBytes};
 use bonsaidb_core::connection::{
     self, AccessPolicy, Connection, HasSchema, HasSession, LowLevelConnection, Range,
-    SerializedQueryKey, Session, Sort, StorageConnection,
+    SerializedQueryKey, Session, Sort, StorageConnection, ViewReadOptions,
 };
+#[cfg(feature = "encryption")]
+use bonsaidb_core::document::FieldEncryption;
 #[cfg(any(feature = "encryption", feature = "compression"))]
 use bonsaidb_core::document::KeyId;
 use bonsaidb_core::document::{BorrowedDocument, DocumentId, Header, OwnedDocument, Revision};
-use bonsaidb_core::keyvalue::{KeyOperation, Output, Timestamp};
+use bonsaidb_core::keyvalue::{KeyOperation, KeyStatus, Output, Timestamp, Value};
 use bonsaidb_core::limits::{
     LIST_TRANSACTIONS_DEFAULT_RESULT_COUNT, LIST_TRANSACTIONS_MAX_RESULTS,
 };
@@ -24,9 +28,13 @@ use bonsaidb_core::permissions::bonsai::{
     ViewAction,
 };
 use bonsaidb_core::permissions::Permissions;
+use bonsaidb_core::schema::view::aggregation::{
+    Aggregation, AggregationGrouping, Aggregator, SerializedAggregatedGroup,
+};
+use bonsaidb_core::schema::view::join::{JoinedMapping, SerializedJoinedEntry};
 use bonsaidb_core::schema::view::map::MappedSerializedValue;
-use bonsaidb_core::schema::view::{self};
-use bonsaidb_core::schema::{self, CollectionName, Schema, Schematic, ViewName};
+use bonsaidb_core::schema::view::{self, ValueStorage};
+use bonsaidb_core::schema::{self, CollectionName, Name, Schema, Schematic, ViewName};
 use bonsaidb_core::transaction::{
     self, ChangedDocument, Changes, Command, DocumentChanges, Operation, OperationResult,
     Transaction,
@@ -35,7 +43,7 @@ use itertools::Itertools;
 use nebari::io::any::AnyFile;
 use nebari::tree::{
     AnyTreeRoot, BorrowByteRange, BorrowedRange, CompareSwap, Root, ScanEvaluation, TreeRoot,
-    Unversioned, Versioned,
+    Unversioned,
 };
 use nebari::{AbortError, ExecutingTransaction, Roots, Tree};
 use parking_lot::Mutex;
@@ -43,22 +51,66 @@ use serde::{Deserialize, Serialize};
 use watchable::Watchable;
 
 use crate::config::{Builder, KeyValuePersistence, StorageConfiguration};
+use crate::database::deduplication::document_contents_tree_name;
+use crate::database::integrity::IntegrityReport;
 use crate::database::keyvalue::BackgroundWorkerProcessTarget;
+use crate::database::query_limit::{QueryLimiter, QueryPermit};
+use crate::database::schema_status::SchemaStatus;
+use crate::database::sharding::merge_shard_results;
+use crate::database::view_backlog::{MappingRates, ViewBacklog};
 use crate::error::Error;
 use crate::open_trees::OpenTrees;
 use crate::storage::StorageLock;
-#[cfg(feature = "encryption")]
-use crate::storage::TreeVault;
+use crate::usage::{Activity, Usage, UsageTimer};
+use crate::views::cache::{
+    query_cache_key, range_contains, segment_containing, QueryCache, ReduceCache, ReducedSegment,
+    REDUCE_SEGMENT_LENGTH,
+};
+use crate::views::materialize::PendingMaterializations;
+use crate::views::warming::ViewQueryCounts;
 use crate::views::{
-    mapper, view_document_map_tree_name, view_entries_tree_name, view_invalidated_docs_tree_name,
-    ViewEntry,
+    join, mapper, view_document_map_tree_name, view_entries_tree_name,
+    view_invalidated_docs_tree_name, EntryMapping, ViewEntry,
 };
 use crate::Storage;
 
+/// Evaluates `$body` with `$root` defined as the [`Root`] of the document tree
+/// of `$collection`. Document trees are versioned unless the collection opted
+/// out using
+/// [`Collection::versioned()`](bonsaidb_core::schema::Collection::versioned).
+macro_rules! with_document_root {
+    ($schema:expr, $collection:expr, |$root:ident| $body:expr) => {
+        if $schema.collection_is_versioned($collection) {
+            type $root = nebari::tree::Versioned;
+            $body
+        } else {
+            type $root = nebari::tree::Unversioned;
+            $body
+        }
+    };
+}
+pub(crate) use with_document_root;
+
+/// The tree storing the last sequence applied from each source by
+/// [`Database::apply_sequenced_transaction()`].
+const SEQUENCES_TREE: &str = "sequences";
+
+pub mod integrity;
 pub mod keyvalue;
+pub mod lock;
+pub(crate) mod migration;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+mod query_limit;
+mod raw;
+pub mod schema_status;
+pub mod view_backlog;
 
 pub(crate) mod compat;
+pub(crate) mod deduplication;
 pub mod pubsub;
+mod sharding;
+pub(crate) mod time_series;
 
 /// A database stored in BonsaiDb. This type blocks the current thread when
 /// used. See [`AsyncDatabase`](crate::AsyncDatabase) for this type's async counterpart.
@@ -125,13 +177,13 @@ pub struct Data {
 
 impl Database {
     /// Opens a local file as a bonsaidb.
-    pub(crate) fn new<DB: Schema, S: Into<Cow<'static, str>> + Send>(
+    pub(crate) fn new<S: Into<Cow<'static, str>> + Send>(
         name: S,
+        schema: Arc<Schematic>,
         context: Context,
         storage: &Storage,
     ) -> Result<Self, Error> {
         let name = name.into();
-        let schema = Arc::new(DB::schematic()?);
         let db = Self {
             storage: storage.clone(),
             data: Arc::new(Data {
@@ -141,6 +193,22 @@ impl Database {
             }),
         };
 
+        // Documents stored under legacy collection names must be moved before
+        // the views that index them are checked.
+        for collection in db.data.schema.collections() {
+            if !db
+                .data
+                .schema
+                .legacy_names_for_collection(&collection)
+                .is_empty()
+            {
+                storage
+                    .instance
+                    .tasks()
+                    .migrate_collection(&db, collection)?;
+            }
+        }
+
         if storage.instance.check_view_integrity_on_database_open() {
             for view in db.data.schema.views() {
                 storage.instance.tasks().spawn_integrity_check(view, &db);
@@ -152,9 +220,54 @@ impl Database {
             .tasks()
             .spawn_key_value_expiration_loader(&db);
 
+        for collection in db.data.schema.time_series_collections() {
+            db.enforce_time_series_retention(&collection);
+        }
+
+        if let Some(count) = storage.instance.warm_views_on_open() {
+            db.warm_views(count)?;
+        }
+
         Ok(db)
     }
 
+    /// Updates up to `count` of this database's most queried views in the
+    /// background, if they have not already been warmed since the database's
+    /// files were opened.
+    fn warm_views(&self, count: usize) -> Result<(), Error> {
+        let views = self.data.context.view_query_counts.views_to_warm(
+            &self.data.context.roots,
+            self.data.schema.views(),
+            count,
+        )?;
+        if views.is_empty() {
+            return Ok(());
+        }
+
+        let db = self.clone();
+        std::thread::Builder::new()
+            .name(String::from("view-warmer"))
+            .spawn(move || {
+                for view_name in views {
+                    let view = db
+                        .data
+                        .schema
+                        .view_by_name(&view_name)
+                        .expect("view names are gathered from the schema");
+                    if let Err(err) = db
+                        .storage
+                        .instance
+                        .tasks()
+                        .update_view_if_needed(view, &db, false)
+                    {
+                        log::error!("Error warming view {view_name}: {err}");
+                    }
+                }
+            })
+            .map_err(|err| Error::other("view-warmer", err))?;
+        Ok(())
+    }
+
     /// Restricts an unauthenticated instance to having `effective_permissions`.
     /// Returns `None` if a session has already been established.
     #[must_use]
@@ -167,6 +280,17 @@ impl Database {
             })
     }
 
+    /// Returns a clone of this instance whose operations fail with
+    /// [`bonsaidb_core::Error::DeadlineExceeded`] if they are still waiting on
+    /// view updates, reductions, or compactions once `deadline` has passed.
+    #[must_use]
+    pub fn with_deadline(&self, deadline: Instant) -> Self {
+        Self {
+            storage: self.storage.with_deadline(deadline),
+            data: self.data.clone(),
+        }
+    }
+
     /// Creates a `Storage` with a single-database named "default" with its data
     /// stored at `path`. This requires exclusive access to the storage location
     /// configured. Attempting to open the same path multiple times concurrently
@@ -197,6 +321,320 @@ impl Database {
         &self.data.context.roots
     }
 
+    pub(crate) fn query_cache(&self) -> &'_ QueryCache {
+        &self.data.context.query_cache
+    }
+
+    pub(crate) fn mapping_rates(&self) -> &'_ MappingRates {
+        &self.data.context.mapping_rates
+    }
+
+    pub(crate) fn reduce_cache(&self) -> &'_ ReduceCache {
+        &self.data.context.reduce_cache
+    }
+
+    /// Waits until this database's
+    /// [`concurrent_query_limit`](StorageConfiguration#structfield.concurrent_query_limit)
+    /// allows another expensive operation to execute. The returned permit
+    /// must be held while the operation executes.
+    pub(crate) fn limit_query(&self) -> Result<Option<QueryPermit<'_>>, bonsaidb_core::Error> {
+        self.data
+            .context
+            .query_limiter
+            .as_ref()
+            .map(|limiter| limiter.acquire(self.storage.deadline()))
+            .transpose()
+    }
+
+    /// Attributes the time spent on `activity` to this database and its
+    /// session's user until the returned timer is dropped.
+    pub(crate) fn track_usage(&self, activity: Activity) -> UsageTimer<'_> {
+        UsageTimer::new(
+            self.storage.instance.usage(),
+            self.name(),
+            self.storage.user_id(),
+            activity,
+        )
+    }
+
+    /// Attributes `usage` to this database and its session's user.
+    pub(crate) fn record_usage(&self, usage: Usage) {
+        self.storage
+            .instance
+            .usage()
+            .record(self.name(), self.storage.user_id(), usage);
+    }
+
+    fn record_bytes_read(&self, bytes: usize) {
+        self.record_usage(Usage {
+            bytes_read: u64::try_from(bytes).unwrap_or(u64::MAX),
+            ..Usage::default()
+        });
+    }
+
+    /// Verifies the integrity of this database, returning a report of any
+    /// problems found.
+    ///
+    /// Every document is read, which verifies the checksums of the stored data
+    /// and that encrypted documents can be decrypted. Each view's stored
+    /// mappings are compared against the collection's documents. Normally,
+    /// view integrity is only checked when a view's version changes. See
+    /// [`Views::check_integrity_on_open`](crate::config::Views::check_integrity_on_open).
+    pub fn verify_integrity(&self) -> Result<IntegrityReport, Error> {
+        self.check_integrity(false)
+    }
+
+    /// Verifies the integrity of this database like
+    /// [`Self::verify_integrity()`], and rebuilds each view that has
+    /// inconsistent mappings. Documents that can't be read can't be repaired.
+    ///
+    /// Views are rebuilt by discarding all of their stored data and mapping
+    /// every document again, which can take a long time for large collections.
+    pub fn verify_and_repair_integrity(&self) -> Result<IntegrityReport, Error> {
+        self.check_integrity(true)
+    }
+
+    /// Rewrites every document, view entry, and key-value entry stored in this
+    /// database, and then compacts the database.
+    ///
+    /// Entries are written using the encryption configured when they are
+    /// written. Entries stored before an encryption key was configured remain
+    /// stored in plaintext until this function is invoked. Compacting removes
+    /// the previously stored plaintext values from disk.
+    ///
+    /// Requires [`DatabaseAction::Compact`] permission.
+    pub fn reencrypt(&self) -> Result<(), Error> {
+        self.check_permission(
+            database_resource_name(self.name()),
+            &BonsaiAction::Database(DatabaseAction::Compact),
+        )?;
+        self.storage()
+            .instance
+            .tasks()
+            .rewrite_database(self.clone())
+    }
+
+    fn check_integrity(&self, repair: bool) -> Result<IntegrityReport, Error> {
+        let mut report = IntegrityReport::default();
+        for collection in self.data.schema.collections() {
+            report
+                .collections
+                .push(integrity::verify_collection(self, &collection, repair)?);
+        }
+        Ok(report)
+    }
+
+    /// Returns whether each view in this database's schema has been built
+    /// using the view's current definition.
+    ///
+    /// When a view's definition changes, the view is rebuilt the next time it
+    /// is accessed, or when the database is opened if
+    /// [`Views::check_integrity_on_open`](crate::config::Views::check_integrity_on_open)
+    /// is enabled. This can be used to determine when rebuilding has finished
+    /// after upgrading to a new schema.
+    pub fn schema_status(&self) -> Result<SchemaStatus, Error> {
+        schema_status::schema_status(self)
+    }
+
+    /// Returns the number of documents waiting to be mapped into each view in
+    /// this database's schema, along with an estimate of how long mapping them
+    /// will take.
+    ///
+    /// This can be used to decide whether querying a view using
+    /// [`AccessPolicy::UpdateBefore`] is worth waiting for, or whether
+    /// [`AccessPolicy::NoUpdate`] returns results that are current enough.
+    pub fn view_backlog(&self) -> Result<HashMap<ViewName, ViewBacklog>, Error> {
+        view_backlog::view_backlogs(self)
+    }
+
+    /// Returns a receiver of the documents changed by each transaction
+    /// committed to this database after this function is called. Changes are
+    /// sent after the transaction has been committed.
+    ///
+    /// Watching a database requires permission to list its executed
+    /// transactions. The watcher is removed once the receiver is dropped.
+    pub fn watch_document_changes(
+        &self,
+    ) -> Result<flume::Receiver<Arc<DocumentChanges>>, bonsaidb_core::Error> {
+        self.check_permission(
+            database_resource_name(self.name()),
+            &BonsaiAction::Database(DatabaseAction::Transaction(TransactionAction::ListExecuted)),
+        )?;
+        let (sender, receiver) = flume::unbounded();
+        let mut watchers = self.data.context.document_watchers.lock();
+        watchers.push(sender);
+        Ok(receiver)
+    }
+
+    /// Executes the read-only SQL `query` against the views registered in
+    /// [`StorageConfiguration::sql`](StorageConfiguration#structfield.sql).
+    /// See [`bonsaidb_core::sql`] for the supported syntax.
+    ///
+    /// Querying requires the same permissions as querying the views directly.
+    #[cfg(feature = "sql")]
+    pub fn query_sql(
+        &self,
+        query: &str,
+    ) -> Result<bonsaidb_core::sql::SqlResults, bonsaidb_core::sql::SqlError> {
+        self.storage.sql_catalog().execute(self, query)
+    }
+
+    /// Checks that this instance's session is allowed to perform every
+    /// operation in `transaction`.
+    pub(crate) fn check_transaction_permissions(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<(), bonsaidb_core::Error> {
+        for op in &transaction.operations {
+            let (resource, action) = match &op.command {
+                Command::Insert { .. } => (
+                    collection_resource_name(self.name(), &op.collection),
+                    BonsaiAction::Database(DatabaseAction::Document(DocumentAction::Insert)),
+                ),
+                Command::Update { header, .. } => (
+                    document_resource_name(self.name(), &op.collection, &header.id),
+                    BonsaiAction::Database(DatabaseAction::Document(DocumentAction::Update)),
+                ),
+                Command::Overwrite { id, .. } => (
+                    document_resource_name(self.name(), &op.collection, id),
+                    BonsaiAction::Database(DatabaseAction::Document(DocumentAction::Overwrite)),
+                ),
+                Command::Delete { header } => (
+                    document_resource_name(self.name(), &op.collection, &header.id),
+                    BonsaiAction::Database(DatabaseAction::Document(DocumentAction::Delete)),
+                ),
+                Command::Check { id, .. } => (
+                    document_resource_name(self.name(), &op.collection, id),
+                    BonsaiAction::Database(DatabaseAction::Document(DocumentAction::Get)),
+                ),
+            };
+            self.check_permission(resource, &action)?;
+        }
+        Ok(())
+    }
+
+    /// Checks that `transaction` can be applied without exceeding this
+    /// database's quota. Transactions that only delete or check documents are
+    /// always allowed, ensuring a database that has reached its quota can
+    /// still have space reclaimed.
+    fn check_quota(&self, transaction: &Transaction) -> Result<(), bonsaidb_core::Error> {
+        let Some(limit) = self.storage.instance.database_quota(self.name()) else {
+            return Ok(());
+        };
+        if transaction
+            .operations
+            .iter()
+            .all(|op| matches!(op.command, Command::Delete { .. } | Command::Check { .. }))
+        {
+            return Ok(());
+        }
+
+        let size = self.storage.instance.database_size(self.name())?;
+        if size >= limit {
+            Err(bonsaidb_core::Error::DatabaseQuotaExceeded {
+                database: self.name().to_string(),
+                size,
+                limit,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns true if this instance's session is allowed to get the document
+    /// `id` from `collection`.
+    fn can_read_document(
+        &self,
+        collection: &CollectionName,
+        id: &DocumentId,
+    ) -> Result<bool, bonsaidb_core::Error> {
+        match self.check_permission(
+            document_resource_name(self.name(), collection, id),
+            &BonsaiAction::Database(DatabaseAction::Document(DocumentAction::Get)),
+        ) {
+            Ok(()) => Ok(true),
+            Err(bonsaidb_core::Error::PermissionDenied(_)) => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Removes the mappings whose source documents this instance's session is
+    /// not allowed to read, returning the ids of the remaining mappings'
+    /// source documents.
+    fn retain_readable_mappings(
+        &self,
+        collection: &CollectionName,
+        mappings: &mut Vec<schema::view::map::Serialized>,
+    ) -> Result<Vec<DocumentId>, bonsaidb_core::Error> {
+        let mut readable = HashMap::new();
+        for mapping in mappings.iter() {
+            self.can_read_document_cached(collection, &mapping.source.id, &mut readable)?;
+        }
+        mappings.retain(|mapping| readable[&mapping.source.id]);
+        Ok(readable
+            .into_iter()
+            .filter_map(|(id, readable)| readable.then_some(id))
+            .collect())
+    }
+
+    /// Returns true if this instance's session is allowed to get the document
+    /// `id` from `collection`, remembering the result in `readable`.
+    fn can_read_document_cached(
+        &self,
+        collection: &CollectionName,
+        id: &DocumentId,
+        readable: &mut HashMap<DocumentId, bool>,
+    ) -> Result<bool, bonsaidb_core::Error> {
+        if let Some(readable) = readable.get(id) {
+            return Ok(*readable);
+        }
+        let can_read = self.can_read_document(collection, id)?;
+        readable.insert(id.clone(), can_read);
+        Ok(can_read)
+    }
+
+    /// Removes the mappings of a view entry whose source documents this
+    /// instance's session is not allowed to read, returning true if any
+    /// mappings were removed. Results are remembered in `readable`.
+    fn retain_readable_entry_mappings(
+        &self,
+        collection: &CollectionName,
+        mappings: &mut Vec<EntryMapping>,
+        readable: &mut HashMap<DocumentId, bool>,
+    ) -> Result<bool, bonsaidb_core::Error> {
+        let original_len = mappings.len();
+        let mut result = Ok(());
+        mappings.retain(|mapping| {
+            if result.is_err() {
+                return false;
+            }
+            match self.can_read_document_cached(collection, &mapping.source.id, readable) {
+                Ok(can_read) => can_read,
+                Err(err) => {
+                    result = Err(err);
+                    false
+                }
+            }
+        });
+        result?;
+        Ok(mappings.len() != original_len)
+    }
+
+    /// Removes the mappings whose source documents this instance's session is
+    /// not allowed to read, if
+    /// [`Views::redact_unreadable_entries`](crate::config::Views::redact_unreadable_entries)
+    /// is enabled.
+    fn redact_unreadable_mappings(
+        &self,
+        collection: &CollectionName,
+        mut mappings: Vec<schema::view::map::Serialized>,
+    ) -> Result<Vec<schema::view::map::Serialized>, bonsaidb_core::Error> {
+        if self.storage.instance.redacts_unreadable_view_entries() {
+            self.retain_readable_mappings(collection, &mut mappings)?;
+        }
+        Ok(mappings)
+    }
+
     fn for_each_in_view<F: FnMut(ViewEntry) -> Result<(), bonsaidb_core::Error> + Send + Sync>(
         &self,
         view: &dyn view::Serialized,
@@ -206,38 +644,167 @@ impl Database {
         access_policy: AccessPolicy,
         mut callback: F,
     ) -> Result<(), bonsaidb_core::Error> {
-        if matches!(access_policy, AccessPolicy::UpdateBefore) {
-            self.storage
-                .instance
-                .tasks()
-                .update_view_if_needed(view, self, true)?;
-        } else if let Some(integrity_check) = self
-            .storage
-            .instance
-            .tasks()
-            .spawn_integrity_check(view, self)
-        {
+        if let Some(sharding) = self.sharding(&view.collection()) {
+            return self.for_each_in_sharded_view(
+                sharding,
+                view,
+                key,
+                order,
+                limit,
+                access_policy,
+                callback,
+            );
+        }
+
+        self.prepare_view_for_access(view, access_policy)?;
+
+        let view_entries = self.view_entries(view)?;
+        for entry in Self::create_view_iterator(&view_entries, key, order, limit)? {
+            callback(entry)?;
+        }
+
+        self.finish_view_access(view, access_policy)
+    }
+
+    /// Ensures `view` is ready to be read using `access_policy`.
+    fn prepare_view_for_access(
+        &self,
+        view: &dyn view::Serialized,
+        access_policy: AccessPolicy,
+    ) -> Result<(), bonsaidb_core::Error> {
+        if self.storage.instance.warm_views_on_open().is_some() {
+            self.data
+                .context
+                .view_query_counts
+                .record(&view.view_name());
+        }
+        let tasks = self.storage.instance.tasks();
+        let update_before = match access_policy {
+            AccessPolicy::UpdateBefore => true,
+            AccessPolicy::UpdateAfter | AccessPolicy::NoUpdate => false,
+            AccessPolicy::MaxStaleness(max_staleness) => {
+                !tasks.view_updated_within(view, self, max_staleness)?
+            }
+            AccessPolicy::UpToTransaction(transaction_id) => {
+                !tasks.view_updated_through(view, self, transaction_id)
+            }
+        };
+        if update_before {
+            tasks.update_view_if_needed(view, self, true)?;
+        } else if let Some(integrity_check) = tasks.spawn_integrity_check(view, self) {
             integrity_check
                 .receive()
                 .map_err(Error::from)?
                 .map_err(Error::from)?;
         }
+        Ok(())
+    }
 
-        let view_entries = self
+    fn view_entries(
+        &self,
+        view: &dyn view::Serialized,
+    ) -> Result<Tree<Unversioned, AnyFile>, bonsaidb_core::Error> {
+        Ok(self
             .roots()
             .tree(self.collection_tree(
                 &view.collection(),
                 view_entries_tree_name(&view.view_name()),
             )?)
-            .map_err(Error::from)?;
+            .map_err(Error::from)?)
+    }
 
-        {
-            for entry in Self::create_view_iterator(&view_entries, key, order, limit)? {
-                callback(entry)?;
+    /// Produces the values of `mappings` again by mapping the current contents
+    /// of each mapping's document, if `view` stores its values by reference.
+    /// Mappings whose documents no longer emit `key` are removed.
+    fn resolve_referenced_values(
+        &self,
+        view: &dyn view::Serialized,
+        key: &[u8],
+        mappings: &mut Vec<EntryMapping>,
+    ) -> Result<(), bonsaidb_core::Error> {
+        if view.value_storage() != ValueStorage::Reference || mappings.is_empty() {
+            return Ok(());
+        }
+
+        let ids = mappings
+            .iter()
+            .map(|mapping| mapping.source.id.clone())
+            .collect::<Vec<_>>();
+        let documents = self
+            .read_multiple_from_collection(&ids, &view.collection())?
+            .into_iter()
+            .map(|document| (document.header.id.clone(), document))
+            .collect::<HashMap<_, _>>();
+        let mut resolved = Vec::with_capacity(mappings.len());
+        for mut mapping in mappings.drain(..) {
+            let Some(document) = documents.get(&mapping.source.id) else {
+                continue;
+            };
+            let document = BorrowedDocument {
+                header: document.header.clone(),
+                contents: CowBytes::from(&document.contents[..]),
+            };
+            if !view.filter(&document).map_err(Error::from)? {
+                continue;
+            }
+            // When a key is emitted more than once, the view stores the last
+            // value emitted.
+            let emitted = view
+                .map(&document)
+                .map_err(Error::from)?
+                .into_iter()
+                .rev()
+                .find(|map| map.key[..] == key[..]);
+            if let Some(emitted) = emitted {
+                mapping.source = document.header;
+                mapping.value = emitted.value;
+                resolved.push(mapping);
             }
         }
+        *mappings = resolved;
+        Ok(())
+    }
+
+    /// Resolves the values of one side of a joined entry. See
+    /// [`Self::resolve_referenced_values()`].
+    fn resolve_joined_values(
+        &self,
+        view: &dyn view::Serialized,
+        key: &[u8],
+        mappings: &mut Vec<JoinedMapping<Bytes>>,
+    ) -> Result<(), bonsaidb_core::Error> {
+        if view.value_storage() != ValueStorage::Reference {
+            return Ok(());
+        }
+
+        let mut entry_mappings = mappings
+            .drain(..)
+            .map(|mapping| EntryMapping {
+                source: mapping.source,
+                value: mapping.value,
+            })
+            .collect();
+        self.resolve_referenced_values(view, key, &mut entry_mappings)?;
+        mappings.extend(entry_mappings.into_iter().map(|mapping| JoinedMapping {
+            source: mapping.source,
+            value: mapping.value,
+        }));
+        Ok(())
+    }
 
-        if matches!(access_policy, AccessPolicy::UpdateAfter) {
+    /// Starts updating `view` in the background if `access_policy` requests
+    /// it.
+    fn finish_view_access(
+        &self,
+        view: &dyn view::Serialized,
+        access_policy: AccessPolicy,
+    ) -> Result<(), bonsaidb_core::Error> {
+        if matches!(
+            access_policy,
+            AccessPolicy::UpdateAfter
+                | AccessPolicy::MaxStaleness(_)
+                | AccessPolicy::UpToTransaction(_)
+        ) {
             let db = self.clone();
             let view_name = view.view_name();
             let view = db
@@ -254,10 +821,140 @@ impl Database {
         Ok(())
     }
 
-    fn apply_transaction_to_roots(
+    /// Checks that `transaction` is allowed and waits for the eager views it
+    /// will update to be up to date. Returns the database the transaction must
+    /// be applied to, which is a shard of this database if every operation
+    /// targets documents in the same shard.
+    pub(crate) fn transaction_target(
+        &self,
+        transaction: &mut Transaction,
+    ) -> Result<Self, bonsaidb_core::Error> {
+        self.check_transaction_permissions(transaction)?;
+        self.check_quota(transaction)?;
+        if let Some(shard) = self.route_transaction(transaction)? {
+            return shard.transaction_target(transaction);
+        }
+
+        let mut eager_view_tasks = Vec::new();
+        for collection_name in transaction
+            .operations
+            .iter()
+            .map(|op| &op.collection)
+            .collect::<HashSet<_>>()
+        {
+            if let Some(views) = self.data.schema.views_in_collection(collection_name) {
+                for view in views {
+                    if view.eager() {
+                        if let Some(task) = self
+                            .storage
+                            .instance
+                            .tasks()
+                            .spawn_integrity_check(view, self)
+                        {
+                            eager_view_tasks.push(task);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut eager_view_mapping_tasks = Vec::new();
+        for task in eager_view_tasks {
+            if let Some(spawned_task) = task.receive().map_err(Error::from)?.map_err(Error::from)? {
+                eager_view_mapping_tasks.push(spawned_task);
+            }
+        }
+
+        for task in eager_view_mapping_tasks {
+            let mut task = task.lock();
+            if let Some(task) = task.take() {
+                task.receive().map_err(Error::from)?.map_err(Error::from)?;
+            }
+        }
+
+        Ok(self.clone())
+    }
+
+    /// Executes `transaction` without committing it. The trees modified by
+    /// the transaction remain locked until the returned transaction is
+    /// committed or dropped.
+    pub(crate) fn prepare_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<PreparedTransaction, Error> {
+        Ok(self
+            .prepare_transaction_with(transaction, PrepareOptions::default())?
+            .expect("transactions without a sequence are never skipped"))
+    }
+
+    /// Applies `transaction` unless a transaction with a sequence of at least
+    /// `sequence` has already been applied from `source`, returning None if
+    /// the transaction was skipped.
+    ///
+    /// The sequence is stored in the same commit as the transaction. This
+    /// allows a caller that replays a log of transactions after a crash, such
+    /// as a cluster member, to apply each transaction exactly once.
+    #[cfg(feature = "internal-apis")]
+    #[doc(hidden)]
+    pub fn apply_sequenced_transaction(
+        &self,
+        mut transaction: Transaction,
+        source: &str,
+        sequence: u64,
+    ) -> Result<Option<Vec<OperationResult>>, bonsaidb_core::Error> {
+        let target = self.transaction_target(&mut transaction)?;
+        let options = PrepareOptions {
+            sequence: Some((source, sequence)),
+            ..PrepareOptions::default()
+        };
+        target
+            .prepare_transaction_with(&transaction, options)
+            .and_then(|prepared| prepared.map(PreparedTransaction::commit).transpose())
+            .map_err(bonsaidb_core::Error::from)
+    }
+
+    /// Inserts `documents` into `collection`, keeping the revisions they were
+    /// stored with instead of assigning each document its first revision.
+    ///
+    /// This allows copying documents from another database, such as a
+    /// cluster member's snapshot, without changing the revisions that later
+    /// updates are checked against.
+    #[cfg(feature = "internal-apis")]
+    #[doc(hidden)]
+    pub fn restore_documents(
+        &self,
+        collection: &CollectionName,
+        documents: Vec<OwnedDocument>,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let mut transaction = Transaction::new();
+        let mut revisions = Vec::with_capacity(documents.len());
+        for document in documents {
+            revisions.push(document.header.revision);
+            transaction.push(Operation::insert(
+                collection.clone(),
+                Some(document.header.id),
+                document.contents,
+            ));
+        }
+        let target = self.transaction_target(&mut transaction)?;
+        let options = PrepareOptions {
+            revisions: Some(&revisions),
+            ..PrepareOptions::default()
+        };
+        target
+            .prepare_transaction_with(&transaction, options)?
+            .expect("transactions without a sequence are never skipped")
+            .commit()?;
+        Ok(())
+    }
+
+    /// Executes `transaction` like [`Self::prepare_transaction()`], returning
+    /// None if `options` caused the transaction to be skipped.
+    fn prepare_transaction_with(
         &self,
         transaction: &Transaction,
-    ) -> Result<Vec<OperationResult>, Error> {
+        options: PrepareOptions<'_>,
+    ) -> Result<Option<PreparedTransaction>, Error> {
         let mut open_trees = OpenTrees::default();
         for op in &transaction.operations {
             if !self.data.schema.contains_collection_name(&op.collection) {
@@ -265,30 +962,10 @@ impl Database {
             }
 
             #[cfg(any(feature = "encryption", feature = "compression"))]
-            let vault = if let Some(encryption_key) =
-                self.collection_encryption_key(&op.collection).cloned()
-            {
-                #[cfg(feature = "encryption")]
-                if let Some(mut vault) = self.storage().tree_vault().cloned() {
-                    vault.key = Some(encryption_key);
-                    Some(vault)
-                } else {
-                    TreeVault::new_if_needed(
-                        Some(encryption_key),
-                        self.storage().vault(),
-                        #[cfg(feature = "compression")]
-                        None,
-                    )
-                }
-
-                #[cfg(not(feature = "encryption"))]
-                {
-                    drop(encryption_key);
-                    return Err(Error::EncryptionDisabled);
-                }
-            } else {
-                self.storage().tree_vault().cloned()
-            };
+            let vault = self
+                .storage()
+                .instance
+                .tree_vault_for_key(self.collection_encryption_key(&op.collection))?;
 
             open_trees.open_trees_for_document_change(
                 &op.collection,
@@ -298,22 +975,58 @@ impl Database {
             );
         }
 
+        if options.sequence.is_some() {
+            open_trees.open_tree::<Unversioned>(
+                SEQUENCES_TREE,
+                #[cfg(any(feature = "encryption", feature = "compression"))]
+                None,
+            );
+        }
+
         let mut roots_transaction = self
             .data
             .context
             .roots
             .transaction::<_, dyn AnyTreeRoot<AnyFile>>(&open_trees.trees)?;
 
+        if let Some((source, sequence)) = options.sequence {
+            let mut sequences = roots_transaction
+                .tree::<Unversioned>(open_trees.trees_index_by_name[SEQUENCES_TREE])
+                .unwrap();
+            let applied = sequences
+                .get(source.as_bytes())?
+                .and_then(|applied| <[u8; 8]>::try_from(&applied[..]).ok())
+                .map_or(0, u64::from_be_bytes);
+            if applied >= sequence {
+                return Ok(None);
+            }
+            sequences.set(source.as_bytes().to_vec(), sequence.to_be_bytes().to_vec())?;
+        }
+
         let mut results = Vec::new();
         let mut changed_documents = Vec::new();
         let mut collection_indexes = HashMap::new();
         let mut collections = Vec::new();
-        for op in &transaction.operations {
-            let result = self.execute_operation(
+        let mut materializations = PendingMaterializations::default();
+        for (index, op) in transaction.operations.iter().enumerate() {
+            let mut result = self.execute_operation(
                 op,
                 &mut roots_transaction,
                 &open_trees.trees_index_by_name,
+                &mut materializations,
             )?;
+            if let (Some(revisions), OperationResult::DocumentUpdated { collection, header }) =
+                (options.revisions, &mut result)
+            {
+                header.revision = revisions[index];
+                self.restore_revision(
+                    collection,
+                    header,
+                    &mut roots_transaction,
+                    &open_trees.trees_index_by_name,
+                    &mut materializations,
+                )?;
+            }
 
             if let Some((collection, id, deleted)) = match &result {
                 OperationResult::DocumentUpdated { header, collection } => {
@@ -352,18 +1065,69 @@ impl Database {
             &changed_documents,
         )?;
 
+        let changes = DocumentChanges {
+            collections,
+            documents: changed_documents,
+        };
         roots_transaction
             .entry_mut()
             .set_data(compat::serialize_executed_transaction_changes(
-                &Changes::Documents(DocumentChanges {
-                    collections,
-                    documents: changed_documents,
-                }),
+                &Changes::Documents(changes.clone()),
             )?)?;
 
-        roots_transaction.commit()?;
+        let bytes_written = transaction
+            .operations
+            .iter()
+            .map(|operation| match &operation.command {
+                Command::Insert { contents, .. }
+                | Command::Update { contents, .. }
+                | Command::Overwrite { contents, .. } => contents.len(),
+                Command::Delete { .. } | Command::Check { .. } => 0,
+            })
+            .fold(0_u64, |total, size| {
+                total.saturating_add(u64::try_from(size).unwrap_or(u64::MAX))
+            });
 
-        Ok(results)
+        Ok(Some(PreparedTransaction {
+            database: self.clone(),
+            roots_transaction,
+            results,
+            changes,
+            materializations,
+            bytes_written,
+        }))
+    }
+
+    /// Replaces the revision of the document stored by `header.id` with
+    /// `header.revision`, and updates the eager views that were given the
+    /// document's previous revision.
+    fn restore_revision(
+        &self,
+        collection: &CollectionName,
+        header: &Header,
+        transaction: &mut ExecutingTransaction<AnyFile>,
+        tree_index_map: &HashMap<String, usize>,
+        materializations: &mut PendingMaterializations,
+    ) -> Result<(), Error> {
+        with_document_root!(self.data.schema, collection, |R| {
+            let mut documents = transaction
+                .tree::<R>(tree_index_map[&document_tree_name(collection)])
+                .unwrap();
+            let document_id = ArcBytes::from(header.id.to_vec());
+            if let Some(stored) = documents.get(header.id.as_ref())? {
+                let mut document = deserialize_document(&stored)?;
+                document.header.revision = header.revision;
+                documents.set(document_id.clone(), serialize_document(&document)?)?;
+            }
+            drop(documents);
+            self.update_eager_views_for_documents(
+                collection,
+                vec![document_id],
+                transaction,
+                tree_index_map,
+                materializations,
+            )
+        })
     }
 
     #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
@@ -395,45 +1159,80 @@ impl Database {
         Ok(())
     }
 
+    /// Removes the cached query results of all views in `collections`. This
+    /// must be called after the transaction is committed, otherwise a query
+    /// could cache results from before the transaction.
+    fn invalidate_cached_queries(&self, collections: &[CollectionName]) {
+        for collection in collections {
+            if let Some(views) = self.data.schema.views_in_collection(collection) {
+                for view in views {
+                    let view_name = view.view_name();
+                    if self.storage.instance.caches_view_queries(&view_name) {
+                        self.query_cache().invalidate(&view_name);
+                    }
+                }
+            }
+        }
+    }
+
     fn execute_operation(
         &self,
         operation: &Operation,
         transaction: &mut ExecutingTransaction<AnyFile>,
         tree_index_map: &HashMap<String, usize>,
+        materializations: &mut PendingMaterializations,
     ) -> Result<OperationResult, Error> {
-        match &operation.command {
-            Command::Insert { id, contents } => {
-                self.execute_insert(operation, transaction, tree_index_map, id.clone(), contents)
-            }
-            Command::Update { header, contents } => self.execute_update(
-                operation,
-                transaction,
-                tree_index_map,
-                &header.id,
-                Some(&header.revision),
-                contents,
-            ),
-            Command::Overwrite { id, contents } => {
-                self.execute_update(operation, transaction, tree_index_map, id, None, contents)
-            }
-            Command::Delete { header } => {
-                self.execute_delete(operation, transaction, tree_index_map, header)
+        with_document_root!(self.data.schema, &operation.collection, |R| {
+            match &operation.command {
+                Command::Insert { id, contents } => self.execute_insert::<R>(
+                    operation,
+                    transaction,
+                    tree_index_map,
+                    materializations,
+                    id.clone(),
+                    contents,
+                ),
+                Command::Update { header, contents } => self.execute_update::<R>(
+                    operation,
+                    transaction,
+                    tree_index_map,
+                    materializations,
+                    &header.id,
+                    Some(&header.revision),
+                    contents,
+                ),
+                Command::Overwrite { id, contents } => self.execute_update::<R>(
+                    operation,
+                    transaction,
+                    tree_index_map,
+                    materializations,
+                    id,
+                    None,
+                    contents,
+                ),
+                Command::Delete { header } => self.execute_delete::<R>(
+                    operation,
+                    transaction,
+                    tree_index_map,
+                    materializations,
+                    header,
+                ),
+                Command::Check { id, revision } => Self::execute_check::<R>(
+                    operation,
+                    transaction,
+                    tree_index_map,
+                    id.clone(),
+                    *revision,
+                ),
             }
-            Command::Check { id, revision } => Self::execute_check(
-                operation,
-                transaction,
-                tree_index_map,
-                id.clone(),
-                *revision,
-            ),
-        }
+        })
     }
 
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument(
             level = "trace",
-            skip(self, operation, transaction, tree_index_map, contents),
+            skip(self, operation, transaction, tree_index_map, materializations, contents),
             fields(
                 database = self.name(),
                 collection.name = operation.collection.name.as_ref(),
@@ -441,21 +1240,50 @@ impl Database {
             )
         )
     )]
-    fn execute_update(
+    fn execute_update<R: Root>(
         &self,
         operation: &Operation,
         transaction: &mut ExecutingTransaction<AnyFile>,
         tree_index_map: &HashMap<String, usize>,
+        materializations: &mut PendingMaterializations,
         id: &DocumentId,
         check_revision: Option<&Revision>,
         contents: &[u8],
     ) -> Result<OperationResult, crate::Error> {
         let mut documents = transaction
-            .tree::<Versioned>(tree_index_map[&document_tree_name(&operation.collection)])
+            .tree::<R>(tree_index_map[&document_tree_name(&operation.collection)])
             .unwrap();
+        // Unversioned collections overwrite documents regardless of revision.
+        let versioned = self
+            .data
+            .schema
+            .collection_is_versioned(&operation.collection);
+        let deduplicated = self
+            .data
+            .schema
+            .collection_is_deduplicated(&operation.collection);
+        // Merging CRDT contents requires the stored contents, which
+        // deduplicated collections store outside of the documents tree.
+        let stored_contents =
+            if deduplicated && self.data.schema.collection_is_crdt(&operation.collection) {
+                match documents.get(id.as_ref())? {
+                    Some(document) => deduplication::stored_contents(
+                        &mut transaction
+                            .tree::<Unversioned>(
+                                tree_index_map[&document_contents_tree_name(&operation.collection)],
+                            )
+                            .unwrap(),
+                        &deserialize_document(&document)?.header.revision.sha256,
+                    )?,
+                    None => None,
+                }
+            } else {
+                None
+            };
         let document_id = ArcBytes::from(id.to_vec());
         let mut result = None;
         let mut updated = false;
+        let mut referenced_contents = None;
         documents.modify(
             vec![document_id.clone()],
             nebari::tree::Operation::CompareSwap(CompareSwap::new(&mut |_key,
@@ -470,23 +1298,51 @@ impl Database {
                             return nebari::tree::KeyOperation::Skip;
                         }
                     };
-                    if check_revision.is_none() || Some(&doc.header.revision) == check_revision {
+                    // CRDT collections merge the stored contents with the new
+                    // contents instead of checking the revision.
+                    let merged = match self.data.schema.merge_collection_contents(
+                        &operation.collection,
+                        stored_contents.as_deref().unwrap_or(&doc.contents),
+                        contents,
+                    ) {
+                        Ok(merged) => merged,
+                        Err(err) => {
+                            result = Some(Err(Error::Core(err)));
+                            return nebari::tree::KeyOperation::Skip;
+                        }
+                    };
+                    if merged.is_some()
+                        || check_revision.is_none()
+                        || !versioned
+                        || Some(&doc.header.revision) == check_revision
+                    {
+                        let contents = merged.as_deref().unwrap_or(contents);
                         if let Some(updated_revision) = doc.header.revision.next_revision(contents)
                         {
                             let updated_header = Header {
                                 id: id.clone(),
                                 revision: updated_revision,
                             };
-                            let serialized_doc = match serialize_document(&BorrowedDocument {
-                                header: updated_header.clone(),
-                                contents: CowBytes::from(contents),
-                            }) {
+                            let serialized_doc = match serialize_stored_document(
+                                &BorrowedDocument {
+                                    header: updated_header.clone(),
+                                    contents: CowBytes::from(contents),
+                                },
+                                deduplicated,
+                            ) {
                                 Ok(bytes) => bytes,
                                 Err(err) => {
                                     result = Some(Err(Error::from(err)));
                                     return nebari::tree::KeyOperation::Skip;
                                 }
                             };
+                            if deduplicated {
+                                referenced_contents = Some((
+                                    Some(doc.header.revision.sha256),
+                                    updated_revision.sha256,
+                                    contents.to_vec(),
+                                ));
+                            }
                             result = Some(Ok(OperationResult::DocumentUpdated {
                                 collection: operation.collection.clone(),
                                 header: updated_header,
@@ -511,8 +1367,12 @@ impl Database {
                     }
                 } else if check_revision.is_none() {
                     let doc = BorrowedDocument::new(id.clone(), contents);
-                    match serialize_document(&doc).map(|bytes| (doc, bytes)) {
+                    match serialize_stored_document(&doc, deduplicated).map(|bytes| (doc, bytes)) {
                         Ok((doc, serialized)) => {
+                            if deduplicated {
+                                referenced_contents =
+                                    Some((None, doc.header.revision.sha256, contents.to_vec()));
+                            }
                             result = Some(Ok(OperationResult::DocumentUpdated {
                                 collection: operation.collection.clone(),
                                 header: doc.header,
@@ -536,7 +1396,22 @@ impl Database {
         drop(documents);
 
         if updated {
-            self.update_eager_views(&document_id, operation, transaction, tree_index_map)?;
+            if let Some((previous, sha256, contents)) = referenced_contents {
+                let mut stored = transaction
+                    .tree::<Unversioned>(
+                        tree_index_map[&document_contents_tree_name(&operation.collection)],
+                    )
+                    .unwrap();
+                deduplication::add_reference(&mut stored, &sha256, &contents)?;
+                deduplication::remove_references(&mut stored, previous)?;
+            }
+            self.update_eager_views(
+                &document_id,
+                operation,
+                transaction,
+                tree_index_map,
+                materializations,
+            )?;
         }
 
         result.expect("nebari should invoke the callback even when the key isn't found")
@@ -546,7 +1421,7 @@ impl Database {
         feature = "tracing",
         tracing::instrument(
             level = "trace",
-            skip(self, operation, transaction, tree_index_map, contents),
+            skip(self, operation, transaction, tree_index_map, materializations, contents),
             fields(
                 database = self.name(),
                 collection.name = operation.collection.name.as_ref(),
@@ -554,16 +1429,33 @@ impl Database {
             )
         )
     )]
-    fn execute_insert(
+    fn execute_insert<R: Root>(
         &self,
         operation: &Operation,
         transaction: &mut ExecutingTransaction<AnyFile>,
         tree_index_map: &HashMap<String, usize>,
+        materializations: &mut PendingMaterializations,
         id: Option<DocumentId>,
         contents: &[u8],
     ) -> Result<OperationResult, Error> {
+        // Inserting into a CRDT collection with an existing id merges the
+        // contents rather than conflicting.
+        if let Some(id) = &id {
+            if self.data.schema.collection_is_crdt(&operation.collection) {
+                return self.execute_update::<R>(
+                    operation,
+                    transaction,
+                    tree_index_map,
+                    materializations,
+                    id,
+                    None,
+                    contents,
+                );
+            }
+        }
+
         let mut documents = transaction
-            .tree::<Versioned>(tree_index_map[&document_tree_name(&operation.collection)])
+            .tree::<R>(tree_index_map[&document_tree_name(&operation.collection)])
             .unwrap();
         let id = if let Some(id) = id {
             id
@@ -578,8 +1470,12 @@ impl Database {
                 .next_id_for_collection(&operation.collection, None)?
         };
 
+        let deduplicated = self
+            .data
+            .schema
+            .collection_is_deduplicated(&operation.collection);
         let doc = BorrowedDocument::new(id, contents);
-        let serialized: Vec<u8> = serialize_document(&doc)?;
+        let serialized: Vec<u8> = serialize_stored_document(&doc, deduplicated)?;
         let document_id = ArcBytes::from(doc.header.id.as_ref().to_vec());
         if let Some(document) = documents.replace(document_id.clone(), serialized)? {
             let doc = deserialize_document(&document)?;
@@ -589,7 +1485,24 @@ impl Database {
             )))
         } else {
             drop(documents);
-            self.update_eager_views(&document_id, operation, transaction, tree_index_map)?;
+            if deduplicated {
+                deduplication::add_reference(
+                    &mut transaction
+                        .tree::<Unversioned>(
+                            tree_index_map[&document_contents_tree_name(&operation.collection)],
+                        )
+                        .unwrap(),
+                    &doc.header.revision.sha256,
+                    contents,
+                )?;
+            }
+            self.update_eager_views(
+                &document_id,
+                operation,
+                transaction,
+                tree_index_map,
+                materializations,
+            )?;
 
             Ok(OperationResult::DocumentUpdated {
                 collection: operation.collection.clone(),
@@ -600,32 +1513,54 @@ impl Database {
 
     #[cfg_attr(feature = "tracing", tracing::instrument(
         level = "trace",
-        skip(self, operation, transaction, tree_index_map),
+        skip(self, operation, transaction, tree_index_map, materializations),
         fields(
             database = self.name(),
             collection.name = operation.collection.name.as_ref(),
             collection.authority = operation.collection.authority.as_ref()
         )
     ))]
-    fn execute_delete(
+    fn execute_delete<R: Root>(
         &self,
         operation: &Operation,
         transaction: &mut ExecutingTransaction<AnyFile>,
         tree_index_map: &HashMap<String, usize>,
+        materializations: &mut PendingMaterializations,
         header: &Header,
     ) -> Result<OperationResult, Error> {
         let mut documents = transaction
-            .tree::<Versioned>(tree_index_map[&document_tree_name(&operation.collection)])
+            .tree::<R>(tree_index_map[&document_tree_name(&operation.collection)])
             .unwrap();
         if let Some(vec) = documents.remove(header.id.as_ref())? {
             drop(documents);
             let doc = deserialize_document(&vec)?;
-            if &doc.header == header {
+            // Unversioned collections delete documents regardless of revision.
+            if &doc.header == header
+                || !self
+                    .data
+                    .schema
+                    .collection_is_versioned(&operation.collection)
+            {
+                if self
+                    .data
+                    .schema
+                    .collection_is_deduplicated(&operation.collection)
+                {
+                    deduplication::remove_references(
+                        &mut transaction
+                            .tree::<Unversioned>(
+                                tree_index_map[&document_contents_tree_name(&operation.collection)],
+                            )
+                            .unwrap(),
+                        Some(doc.header.revision.sha256),
+                    )?;
+                }
                 self.update_eager_views(
                     &ArcBytes::from(doc.header.id.to_vec()),
                     operation,
                     transaction,
                     tree_index_map,
+                    materializations,
                 )?;
 
                 Ok(OperationResult::DocumentDeleted {
@@ -648,7 +1583,7 @@ impl Database {
 
     #[cfg_attr(feature = "tracing", tracing::instrument(
         level = "trace",
-        skip(self, operation, transaction, tree_index_map),
+        skip(self, operation, transaction, tree_index_map, materializations),
         fields(
             database = self.name(),
             collection.name = operation.collection.name.as_ref(),
@@ -661,15 +1596,32 @@ impl Database {
         operation: &Operation,
         transaction: &mut ExecutingTransaction<AnyFile>,
         tree_index_map: &HashMap<String, usize>,
+        materializations: &mut PendingMaterializations,
     ) -> Result<(), Error> {
-        if let Some(eager_views) = self
-            .data
-            .schema
-            .eager_views_in_collection(&operation.collection)
-        {
+        self.update_eager_views_for_documents(
+            &operation.collection,
+            vec![document_id.clone()],
+            transaction,
+            tree_index_map,
+            materializations,
+        )
+    }
+
+    fn update_eager_views_for_documents(
+        &self,
+        collection: &CollectionName,
+        document_ids: Vec<ArcBytes<'static>>,
+        transaction: &mut ExecutingTransaction<AnyFile>,
+        tree_index_map: &HashMap<String, usize>,
+        materializations: &mut PendingMaterializations,
+    ) -> Result<(), Error> {
+        if let Some(eager_views) = self.data.schema.eager_views_in_collection(collection) {
             let documents = transaction
-                .unlocked_tree(tree_index_map[&document_tree_name(&operation.collection)])
+                .unlocked_tree(tree_index_map[&document_tree_name(collection)])
                 .unwrap();
+            let document_contents = tree_index_map
+                .get(&document_contents_tree_name(collection))
+                .map(|index| transaction.unlocked_tree(*index).unwrap());
             for view in eager_views {
                 let name = view.view_name();
                 let document_map = transaction
@@ -678,20 +1630,22 @@ impl Database {
                 let view_entries = transaction
                     .unlocked_tree(tree_index_map[&view_entries_tree_name(&name)])
                     .unwrap();
-                mapper::DocumentRequest {
+                let modified_keys = mapper::DocumentRequest {
                     database: self,
-                    document_ids: vec![document_id.clone()],
+                    document_ids: document_ids.clone(),
                     map_request: &mapper::Map {
                         database: self.data.name.clone(),
-                        collection: operation.collection.clone(),
+                        collection: collection.clone(),
                         view_name: name.clone(),
                     },
                     document_map,
                     documents,
+                    document_contents,
                     view_entries,
                     view,
                 }
                 .map()?;
+                materializations.record(collection, view, modified_keys);
             }
         }
 
@@ -706,7 +1660,7 @@ impl Database {
             collection.authority = operation.collection.authority.as_ref(),
         ),
     ))]
-    fn execute_check(
+    fn execute_check<R: Root>(
         operation: &Operation,
         transaction: &mut ExecutingTransaction<AnyFile>,
         tree_index_map: &HashMap<String, usize>,
@@ -714,7 +1668,7 @@ impl Database {
         revision: Option<Revision>,
     ) -> Result<OperationResult, Error> {
         let mut documents = transaction
-            .tree::<Versioned>(tree_index_map[&document_tree_name(&operation.collection)])
+            .tree::<R>(tree_index_map[&document_tree_name(&operation.collection)])
             .unwrap();
         if let Some(vec) = documents.get(id.as_ref())? {
             drop(documents);
@@ -744,6 +1698,19 @@ impl Database {
         order: Sort,
         limit: Option<u32>,
     ) -> Result<Vec<ViewEntry>, Error> {
+        Self::read_keys(view_entries, key, order, limit)?
+            .into_iter()
+            .map(|value| bincode::deserialize(&value).map_err(Error::from))
+            .collect::<Result<Vec<_>, Error>>()
+    }
+
+    /// Returns the values stored in `tree` for the keys matching `key`.
+    fn read_keys(
+        tree: &Tree<Unversioned, AnyFile>,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+    ) -> Result<Vec<ArcBytes<'static>>, Error> {
         let mut values = Vec::new();
         let forwards = match order {
             Sort::Ascending => true,
@@ -753,7 +1720,7 @@ impl Database {
         if let Some(key) = key {
             match key {
                 SerializedQueryKey::Range(range) => {
-                    view_entries.scan::<Infallible, _, _, _, _>(
+                    tree.scan::<Infallible, _, _, _, _>(
                         &range.map_ref(|bytes| &bytes[..]),
                         forwards,
                         |_, _, _| ScanEvaluation::ReadData,
@@ -773,21 +1740,20 @@ impl Database {
                     )?;
                 }
                 SerializedQueryKey::Matches(key) => {
-                    values.extend(view_entries.get(&key)?);
+                    values.extend(tree.get(&key)?);
                 }
                 SerializedQueryKey::Multiple(mut list) => {
                     list.sort();
 
                     values.extend(
-                        view_entries
-                            .get_multiple(list.iter().map(|bytes| bytes.as_slice()))?
+                        tree.get_multiple(list.iter().map(|bytes| bytes.as_slice()))?
                             .into_iter()
                             .map(|(_, value)| value),
                     );
                 }
             }
         } else {
-            view_entries.scan::<Infallible, _, _, _, _>(
+            tree.scan::<Infallible, _, _, _, _>(
                 &(..),
                 forwards,
                 |_, _, _| ScanEvaluation::ReadData,
@@ -807,10 +1773,195 @@ impl Database {
             )?;
         }
 
-        values
-            .into_iter()
-            .map(|value| bincode::deserialize(&value).map_err(Error::from))
-            .collect::<Result<Vec<_>, Error>>()
+        Ok(values)
+    }
+
+    /// Returns the reduced value of each key matching `key`.
+    fn reduce_grouped(
+        &self,
+        view_name: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+    ) -> Result<Vec<MappedSerializedValue>, bonsaidb_core::Error> {
+        let view = self.data.schema.view_by_name(view_name)?;
+        self.check_permission(
+            view_resource_name(self.name(), &view.view_name()),
+            &BonsaiAction::Database(DatabaseAction::View(ViewAction::Reduce)),
+        )?;
+        if view.value_storage() == ValueStorage::Reference {
+            return Err(bonsaidb_core::Error::ReduceUnimplemented);
+        }
+        let collection = view.collection();
+        let redact = self.storage.instance.redacts_unreadable_view_entries();
+        let mut readable = HashMap::new();
+        let mut mappings = Vec::new();
+        self.for_each_in_view(
+            view,
+            key,
+            Sort::Ascending,
+            None,
+            access_policy,
+            |mut entry| {
+                // The stored reduced value includes every document's mapping, so
+                // it is recomputed from the mappings the session can read.
+                if redact
+                    && self.retain_readable_entry_mappings(
+                        &collection,
+                        &mut entry.mappings,
+                        &mut readable,
+                    )?
+                {
+                    if entry.mappings.is_empty() {
+                        return Ok(());
+                    }
+                    entry.reduced_value = Bytes::from(
+                        view.reduce(
+                            &entry
+                                .mappings
+                                .iter()
+                                .map(|mapping| (&entry.key[..], &mapping.value[..]))
+                                .collect::<Vec<_>>(),
+                            false,
+                        )
+                        .map_err(Error::from)?,
+                    );
+                }
+                mappings.push(MappedSerializedValue {
+                    key: entry.key,
+                    value: entry.reduced_value,
+                });
+                Ok(())
+            },
+        )?;
+
+        Ok(mappings)
+    }
+
+    /// Returns the reduced values needed to reduce all entries in `range`.
+    ///
+    /// Runs of [`REDUCE_SEGMENT_LENGTH`] consecutive entries are rereduced
+    /// into a single value and cached. Cached segments that are entirely
+    /// contained within `range` are used instead of reading their entries,
+    /// which means repeated reductions of large ranges only need to read the
+    /// entries that have been modified since the previous reduction.
+    fn reduce_range_segmented(
+        &self,
+        view_name: &ViewName,
+        range: Range<Bytes>,
+        access_policy: AccessPolicy,
+    ) -> Result<Vec<MappedSerializedValue>, bonsaidb_core::Error> {
+        enum Item {
+            Segment(MappedSerializedValue),
+            Entry { in_segment: bool },
+        }
+
+        let view = self.data.schema.view_by_name(view_name)?;
+        self.check_permission(
+            view_resource_name(self.name(), &view.view_name()),
+            &BonsaiAction::Database(DatabaseAction::View(ViewAction::Reduce)),
+        )?;
+        self.prepare_view_for_access(view, access_policy)?;
+        self.storage.check_deadline()?;
+
+        let (generation, segments) = self.reduce_cache().segments(view_name);
+        let view_entries = self.view_entries(view)?;
+        let mut items = Vec::new();
+        let mut entries = Vec::new();
+        view_entries
+            .scan::<Infallible, _, _, _, _>(
+                &range.map_ref(|bytes| &bytes[..]),
+                true,
+                |_, _, _| ScanEvaluation::ReadData,
+                |key, _| match segment_containing(&segments, key.as_slice()) {
+                    Some((first_key, segment))
+                        if range_contains(&range, first_key)
+                            && range_contains(&range, &segment.last_key) =>
+                    {
+                        if first_key.as_slice() == key.as_slice() {
+                            items.push(Item::Segment(MappedSerializedValue {
+                                key: Bytes::from(first_key.clone()),
+                                value: Bytes::from(segment.value.clone()),
+                            }));
+                        }
+                        ScanEvaluation::Skip
+                    }
+                    segment => {
+                        items.push(Item::Entry {
+                            in_segment: segment.is_some(),
+                        });
+                        ScanEvaluation::ReadData
+                    }
+                },
+                |_, _, value| {
+                    entries.push(value);
+                    Ok(())
+                },
+            )
+            .map_err(Error::from)?;
+
+        let mut entries = entries.into_iter();
+        let mut mappings = Vec::with_capacity(items.len());
+        let mut new_segments = Vec::new();
+        let mut run = Vec::with_capacity(REDUCE_SEGMENT_LENGTH);
+        for item in items {
+            match item {
+                Item::Segment(segment) => {
+                    mappings.append(&mut run);
+                    mappings.push(segment);
+                }
+                Item::Entry { in_segment } => {
+                    let entry = bincode::deserialize::<ViewEntry>(
+                        &entries.next().expect("an entry is read for each key"),
+                    )
+                    .map_err(Error::from)?;
+                    let mapping = MappedSerializedValue {
+                        key: entry.key,
+                        value: entry.reduced_value,
+                    };
+                    if in_segment {
+                        // This entry is part of a segment that extends beyond
+                        // the range, so it can't be part of a new segment.
+                        mappings.append(&mut run);
+                        mappings.push(mapping);
+                        continue;
+                    }
+
+                    run.push(mapping);
+                    if run.len() == REDUCE_SEGMENT_LENGTH {
+                        self.storage.check_deadline()?;
+                        let value = view
+                            .reduce(
+                                &run.iter()
+                                    .map(|map| (map.key.as_ref(), map.value.as_ref()))
+                                    .collect::<Vec<_>>(),
+                                true,
+                            )
+                            .map_err(Error::from)?;
+                        let first_key = run.first().expect("run is not empty").key.clone();
+                        let last_key = run.last().expect("run is not empty").key.to_vec();
+                        new_segments.push((
+                            first_key.to_vec(),
+                            ReducedSegment {
+                                last_key,
+                                value: value.clone(),
+                            },
+                        ));
+                        mappings.push(MappedSerializedValue {
+                            key: first_key,
+                            value: Bytes::from(value),
+                        });
+                        run.clear();
+                    }
+                }
+            }
+        }
+        mappings.append(&mut run);
+        self.reduce_cache()
+            .insert(view_name, generation, new_segments);
+
+        self.finish_view_access(view, access_policy)?;
+
+        Ok(mappings)
     }
 
     #[cfg(any(feature = "encryption", feature = "compression"))]
@@ -821,15 +1972,15 @@ impl Database {
     }
 
     #[cfg_attr(
-        not(feature = "encryption"),
+        not(any(feature = "encryption", feature = "compression")),
         allow(
             unused_mut,
             unused_variables,
             clippy::unused_self,
-            clippy::let_and_return
+            clippy::let_and_return,
+            clippy::unnecessary_wraps
         )
     )]
-    #[allow(clippy::unnecessary_wraps)]
     pub(crate) fn collection_tree<R: Root, S: Into<Cow<'static, str>>>(
         &self,
         collection: &CollectionName,
@@ -838,46 +1989,23 @@ impl Database {
         let mut tree = R::tree(name);
 
         #[cfg(any(feature = "encryption", feature = "compression"))]
-        match (
-            self.collection_encryption_key(collection),
-            self.storage().tree_vault().cloned(),
-        ) {
-            (Some(override_key), Some(mut vault)) => {
-                #[cfg(feature = "encryption")]
-                {
-                    vault.key = Some(override_key.clone());
-                    tree = tree.with_vault(vault);
-                }
-
-                #[cfg(not(feature = "encryption"))]
-                {
-                    return Err(Error::EncryptionDisabled);
-                }
-            }
-            (None, Some(vault)) => {
-                tree = tree.with_vault(vault);
-            }
-            (key, None) => {
-                #[cfg(feature = "encryption")]
-                if let Some(vault) = TreeVault::new_if_needed(
-                    key.cloned(),
-                    self.storage().vault(),
-                    #[cfg(feature = "compression")]
-                    None,
-                ) {
-                    tree = tree.with_vault(vault);
-                }
-
-                #[cfg(not(feature = "encryption"))]
-                if key.is_some() {
-                    return Err(Error::EncryptionDisabled);
-                }
-            }
+        if let Some(vault) = self
+            .storage()
+            .instance
+            .tree_vault_for_key(self.collection_encryption_key(collection))?
+        {
+            tree = tree.with_vault(vault);
         }
 
         Ok(tree)
     }
 
+    /// Returns the tree root for the key-value store, using the vault
+    /// configured for the key-value store of this database's schema.
+    pub(crate) fn key_value_tree(&self) -> TreeRoot<Unversioned, AnyFile> {
+        self.data.context.key_tree().root()
+    }
+
     pub(crate) fn update_key_expiration<'key>(
         &self,
         tree_key: impl Into<Cow<'key, str>>,
@@ -935,6 +2063,102 @@ impl Database {
         self.clone().into_async_with_runtime(runtime)
     }
 }
+
+/// Changes to how [`Database::prepare_transaction_with()`] executes a
+/// transaction.
+#[derive(Clone, Copy, Default)]
+struct PrepareOptions<'a> {
+    /// The source and sequence of the transaction. The transaction is skipped
+    /// if a sequence at least this high has been applied from the source.
+    sequence: Option<(&'a str, u64)>,
+    /// The revisions of the documents inserted by the transaction, indexed by
+    /// operation.
+    revisions: Option<&'a [Revision]>,
+}
+
+/// A transaction that has been executed but not committed. See
+/// [`Database::prepare_transaction()`].
+pub(crate) struct PreparedTransaction {
+    database: Database,
+    roots_transaction: ExecutingTransaction<AnyFile>,
+    results: Vec<OperationResult>,
+    changes: DocumentChanges,
+    materializations: PendingMaterializations,
+    bytes_written: u64,
+}
+
+impl PreparedTransaction {
+    /// Returns the database this transaction was executed in.
+    pub(crate) const fn database(&self) -> &Database {
+        &self.database
+    }
+
+    /// Commits the transaction, returning the results of its operations.
+    pub(crate) fn commit(self) -> Result<Vec<OperationResult>, Error> {
+        self.write()?.finish()
+    }
+
+    /// Writes the transaction's changes. Once this returns successfully, the
+    /// changes are stored, even if [`WrittenTransaction::finish()`] fails.
+    pub(crate) fn write(self) -> Result<WrittenTransaction, Error> {
+        let Self {
+            database,
+            roots_transaction,
+            results,
+            changes,
+            materializations,
+            bytes_written,
+        } = self;
+        roots_transaction.commit()?;
+        Ok(WrittenTransaction {
+            database,
+            results,
+            changes,
+            materializations,
+            bytes_written,
+        })
+    }
+}
+
+/// A transaction whose changes have been stored, but whose materialized views,
+/// caches, and watchers have not been updated. See
+/// [`PreparedTransaction::write()`].
+pub(crate) struct WrittenTransaction {
+    database: Database,
+    results: Vec<OperationResult>,
+    changes: DocumentChanges,
+    materializations: PendingMaterializations,
+    bytes_written: u64,
+}
+
+impl WrittenTransaction {
+    /// Finishes committing the transaction, returning the results of its
+    /// operations.
+    pub(crate) fn finish(self) -> Result<Vec<OperationResult>, Error> {
+        let Self {
+            database,
+            results,
+            changes,
+            materializations,
+            bytes_written,
+        } = self;
+        database.invalidate_cached_queries(&changes.collections);
+        materializations.apply(&database)?;
+
+        for collection in &changes.collections {
+            database.enforce_time_series_retention(collection);
+        }
+
+        database.data.context.notify_document_watchers(changes);
+        database.record_usage(Usage {
+            bytes_written,
+            ..Usage::default()
+        });
+
+        Ok(results)
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 struct LegacyHeader {
     id: u64,
@@ -963,18 +2187,52 @@ pub(crate) fn deserialize_document(bytes: &[u8]) -> Result<BorrowedDocument<'_>,
     }
 }
 
-fn serialize_document(document: &BorrowedDocument<'_>) -> Result<Vec<u8>, bonsaidb_core::Error> {
+pub(crate) fn serialize_document(
+    document: &BorrowedDocument<'_>,
+) -> Result<Vec<u8>, bonsaidb_core::Error> {
     pot::to_vec(document)
         .map_err(Error::from)
         .map_err(bonsaidb_core::Error::from)
 }
 
+/// Serializes `document` to be stored in its collection's documents tree. The
+/// contents of documents in deduplicated collections are stored separately,
+/// so only the header is stored.
+fn serialize_stored_document(
+    document: &BorrowedDocument<'_>,
+    deduplicated: bool,
+) -> Result<Vec<u8>, bonsaidb_core::Error> {
+    if deduplicated {
+        serialize_document(&BorrowedDocument {
+            header: document.header.clone(),
+            contents: CowBytes::from(&[][..]),
+        })
+    } else {
+        serialize_document(document)
+    }
+}
+
 impl HasSession for Database {
     fn session(&self) -> Option<&Session> {
         self.storage.session()
     }
 }
 
+#[cfg(feature = "encryption")]
+impl FieldEncryption for Database {
+    fn encrypt_field_bytes(
+        &self,
+        key: &KeyId,
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, bonsaidb_core::Error> {
+        self.storage.encrypt_field_bytes(key, plaintext)
+    }
+
+    fn decrypt_field_bytes(&self, payload: &[u8]) -> Result<Vec<u8>, bonsaidb_core::Error> {
+        self.storage.decrypt_field_bytes(payload)
+    }
+}
+
 impl Connection for Database {
     type Storage = Storage;
 
@@ -1109,72 +2367,12 @@ impl LowLevelConnection for Database {
     ))]
     fn apply_transaction(
         &self,
-        transaction: Transaction,
+        mut transaction: Transaction,
     ) -> Result<Vec<OperationResult>, bonsaidb_core::Error> {
-        for op in &transaction.operations {
-            let (resource, action) = match &op.command {
-                Command::Insert { .. } => (
-                    collection_resource_name(self.name(), &op.collection),
-                    BonsaiAction::Database(DatabaseAction::Document(DocumentAction::Insert)),
-                ),
-                Command::Update { header, .. } => (
-                    document_resource_name(self.name(), &op.collection, &header.id),
-                    BonsaiAction::Database(DatabaseAction::Document(DocumentAction::Update)),
-                ),
-                Command::Overwrite { id, .. } => (
-                    document_resource_name(self.name(), &op.collection, id),
-                    BonsaiAction::Database(DatabaseAction::Document(DocumentAction::Overwrite)),
-                ),
-                Command::Delete { header } => (
-                    document_resource_name(self.name(), &op.collection, &header.id),
-                    BonsaiAction::Database(DatabaseAction::Document(DocumentAction::Delete)),
-                ),
-                Command::Check { id, .. } => (
-                    document_resource_name(self.name(), &op.collection, id),
-                    BonsaiAction::Database(DatabaseAction::Document(DocumentAction::Get)),
-                ),
-            };
-            self.check_permission(resource, &action)?;
-        }
-
-        let mut eager_view_tasks = Vec::new();
-        for collection_name in transaction
-            .operations
-            .iter()
-            .map(|op| &op.collection)
-            .collect::<HashSet<_>>()
-        {
-            if let Some(views) = self.data.schema.views_in_collection(collection_name) {
-                for view in views {
-                    if view.eager() {
-                        if let Some(task) = self
-                            .storage
-                            .instance
-                            .tasks()
-                            .spawn_integrity_check(view, self)
-                        {
-                            eager_view_tasks.push(task);
-                        }
-                    }
-                }
-            }
-        }
-
-        let mut eager_view_mapping_tasks = Vec::new();
-        for task in eager_view_tasks {
-            if let Some(spawned_task) = task.receive().map_err(Error::from)?.map_err(Error::from)? {
-                eager_view_mapping_tasks.push(spawned_task);
-            }
-        }
-
-        for task in eager_view_mapping_tasks {
-            let mut task = task.lock();
-            if let Some(task) = task.take() {
-                task.receive().map_err(Error::from)?.map_err(Error::from)?;
-            }
-        }
-
-        self.apply_transaction_to_roots(&transaction)
+        let target = self.transaction_target(&mut transaction)?;
+        target
+            .prepare_transaction(&transaction)
+            .and_then(PreparedTransaction::commit)
             .map_err(bonsaidb_core::Error::from)
     }
 
@@ -1196,14 +2394,25 @@ impl LowLevelConnection for Database {
             document_resource_name(self.name(), collection, &id),
             &BonsaiAction::Database(DatabaseAction::Document(DocumentAction::Get)),
         )?;
-        let tree = self
-            .data
-            .context
-            .roots
-            .tree(self.collection_tree::<Versioned, _>(collection, document_tree_name(collection))?)
-            .map_err(Error::from)?;
-        if let Some(vec) = tree.get(id.as_ref()).map_err(Error::from)? {
-            Ok(Some(deserialize_document(&vec)?.into_owned()))
+        if let Some(sharding) = self.sharding(collection) {
+            return self
+                .shard_for(sharding, &id)?
+                .get_from_collection(id, collection);
+        }
+        let document = with_document_root!(self.data.schema, collection, |R| {
+            let tree = self
+                .data
+                .context
+                .roots
+                .tree(self.collection_tree::<R, _>(collection, document_tree_name(collection))?)
+                .map_err(Error::from)?;
+            tree.get(id.as_ref()).map_err(Error::from)?
+        });
+        if let Some(vec) = document {
+            self.record_bytes_read(vec.len());
+            let mut document = deserialize_document(&vec)?.into_owned();
+            deduplication::resolve_contents(self, collection, slice::from_mut(&mut document))?;
+            Ok(Some(document))
         } else {
             Ok(None)
         }
@@ -1229,49 +2438,101 @@ impl LowLevelConnection for Database {
             collection_resource_name(self.name(), collection),
             &BonsaiAction::Database(DatabaseAction::Document(DocumentAction::List)),
         )?;
-        let tree = self
-            .data
-            .context
-            .roots
-            .tree(self.collection_tree::<Versioned, _>(collection, document_tree_name(collection))?)
-            .map_err(Error::from)?;
+        if let Some(sharding) = self.sharding(collection) {
+            let mut found_docs = Vec::new();
+            for shard in self.shards(sharding)? {
+                found_docs.extend(shard.list_from_collection(
+                    ids.clone(),
+                    sort,
+                    limit,
+                    collection,
+                )?);
+            }
+            merge_shard_results(&mut found_docs, sort, limit, |doc| &doc.header.id);
+            return Ok(found_docs);
+        }
+        let _permit = if limit.is_none() {
+            self.limit_query()?
+        } else {
+            None
+        };
         let mut found_docs = Vec::new();
         let mut keys_read = 0;
+        let mut bytes_read = 0;
         let ids = DocumentIdRange(ids);
-        tree.scan(
-            &ids.borrow_as_bytes(),
-            match sort {
-                Sort::Ascending => true,
-                Sort::Descending => false,
-            },
-            |_, _, _| ScanEvaluation::ReadData,
-            |_, _| {
-                if let Some(limit) = limit {
-                    if keys_read >= limit {
-                        return ScanEvaluation::Stop;
-                    }
+        with_document_root!(self.data.schema, collection, |R| {
+            let tree = self
+                .data
+                .context
+                .roots
+                .tree(self.collection_tree::<R, _>(collection, document_tree_name(collection))?)
+                .map_err(Error::from)?;
+            tree.scan(
+                &ids.borrow_as_bytes(),
+                match sort {
+                    Sort::Ascending => true,
+                    Sort::Descending => false,
+                },
+                |_, _, _| ScanEvaluation::ReadData,
+                |_, _| {
+                    if let Some(limit) = limit {
+                        if keys_read >= limit {
+                            return ScanEvaluation::Stop;
+                        }
 
-                    keys_read += 1;
-                }
-                ScanEvaluation::ReadData
-            },
-            |_, _, doc| {
-                found_docs.push(
-                    deserialize_document(&doc)
-                        .map(BorrowedDocument::into_owned)
-                        .map_err(AbortError::Other)?,
-                );
-                Ok(())
-            },
-        )
-        .map_err(|err| match err {
-            AbortError::Other(err) => err,
-            AbortError::Nebari(err) => crate::Error::from(err),
-        })?;
+                        keys_read += 1;
+                    }
+                    ScanEvaluation::ReadData
+                },
+                |_, _, doc| {
+                    bytes_read += doc.len();
+                    found_docs.push(
+                        deserialize_document(&doc)
+                            .map(BorrowedDocument::into_owned)
+                            .map_err(AbortError::Other)?,
+                    );
+                    Ok(())
+                },
+            )
+            .map_err(|err| match err {
+                AbortError::Other(err) => err,
+                AbortError::Nebari(err) => crate::Error::from(err),
+            })?;
+        });
+        self.record_bytes_read(bytes_read);
+        deduplication::resolve_contents(self, collection, &mut found_docs)?;
 
         Ok(found_docs)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        level = "trace",
+        skip(self, collection, projection),
+        fields(
+            database = self.name(),
+            collection.name = collection.name.as_ref(),
+            collection.authority = collection.authority.as_ref(),
+        )
+    ))]
+    fn list_projected_from_collection(
+        &self,
+        ids: Range<DocumentId>,
+        order: Sort,
+        limit: Option<u32>,
+        collection: &CollectionName,
+        projection: &Name,
+    ) -> Result<Vec<OwnedDocument>, bonsaidb_core::Error> {
+        let projection = self
+            .data
+            .schema
+            .projection_by_name(collection, projection)?;
+        let mut documents = self.list_from_collection(ids, order, limit, collection)?;
+        for document in &mut documents {
+            document.contents = Bytes::from(projection.project(&document.contents)?);
+        }
+        Ok(documents)
+    }
+
     #[cfg_attr(feature = "tracing", tracing::instrument(
         level = "trace",
         skip(self, collection),
@@ -1291,46 +2552,66 @@ impl LowLevelConnection for Database {
         self.check_permission(
             collection_resource_name(self.name(), collection),
             &BonsaiAction::Database(DatabaseAction::Document(DocumentAction::ListHeaders)),
-        )?;
-        let tree = self
-            .data
-            .context
-            .roots
-            .tree(self.collection_tree::<Versioned, _>(collection, document_tree_name(collection))?)
-            .map_err(Error::from)?;
+        )?;
+        if let Some(sharding) = self.sharding(collection) {
+            let mut found_headers = Vec::new();
+            for shard in self.shards(sharding)? {
+                found_headers.extend(shard.list_headers_from_collection(
+                    ids.clone(),
+                    sort,
+                    limit,
+                    collection,
+                )?);
+            }
+            merge_shard_results(&mut found_headers, sort, limit, |header| &header.id);
+            return Ok(found_headers);
+        }
+        let _permit = if limit.is_none() {
+            self.limit_query()?
+        } else {
+            None
+        };
         let mut found_headers = Vec::new();
         let mut keys_read = 0;
         let ids = DocumentIdRange(ids);
-        tree.scan(
-            &ids.borrow_as_bytes(),
-            match sort {
-                Sort::Ascending => true,
-                Sort::Descending => false,
-            },
-            |_, _, _| ScanEvaluation::ReadData,
-            |_, _| {
-                if let Some(limit) = limit {
-                    if keys_read >= limit {
-                        return ScanEvaluation::Stop;
-                    }
+        with_document_root!(self.data.schema, collection, |R| {
+            let tree = self
+                .data
+                .context
+                .roots
+                .tree(self.collection_tree::<R, _>(collection, document_tree_name(collection))?)
+                .map_err(Error::from)?;
+            tree.scan(
+                &ids.borrow_as_bytes(),
+                match sort {
+                    Sort::Ascending => true,
+                    Sort::Descending => false,
+                },
+                |_, _, _| ScanEvaluation::ReadData,
+                |_, _| {
+                    if let Some(limit) = limit {
+                        if keys_read >= limit {
+                            return ScanEvaluation::Stop;
+                        }
 
-                    keys_read += 1;
-                }
-                ScanEvaluation::ReadData
-            },
-            |_, _, doc| {
-                found_headers.push(
-                    deserialize_document(&doc)
-                        .map(|doc| doc.header)
-                        .map_err(AbortError::Other)?,
-                );
-                Ok(())
-            },
-        )
-        .map_err(|err| match err {
-            AbortError::Other(err) => err,
-            AbortError::Nebari(err) => crate::Error::from(err),
-        })?;
+                        keys_read += 1;
+                    }
+                    ScanEvaluation::ReadData
+                },
+                |_, _, doc| {
+                    found_headers.push(
+                        deserialize_document(&doc)
+                            .map(|doc| doc.header)
+                            .map_err(AbortError::Other)?,
+                    );
+                    Ok(())
+                },
+            )
+            .map_err(|err| match err {
+                AbortError::Other(err) => err,
+                AbortError::Nebari(err) => crate::Error::from(err),
+            })?;
+        });
 
         Ok(found_headers)
     }
@@ -1353,16 +2634,27 @@ impl LowLevelConnection for Database {
             collection_resource_name(self.name(), collection),
             &BonsaiAction::Database(DatabaseAction::Document(DocumentAction::Count)),
         )?;
-        let tree = self
-            .data
-            .context
-            .roots
-            .tree(self.collection_tree::<Versioned, _>(collection, document_tree_name(collection))?)
-            .map_err(Error::from)?;
+        if let Some(sharding) = self.sharding(collection) {
+            let mut count = 0;
+            for shard in self.shards(sharding)? {
+                count += shard.count_from_collection(ids.clone(), collection)?;
+            }
+            return Ok(count);
+        }
         let ids = DocumentIdRange(ids);
-        let stats = tree.reduce(&ids.borrow_as_bytes()).map_err(Error::from)?;
+        let alive_keys = with_document_root!(self.data.schema, collection, |R| {
+            let tree = self
+                .data
+                .context
+                .roots
+                .tree(self.collection_tree::<R, _>(collection, document_tree_name(collection))?)
+                .map_err(Error::from)?;
+            tree.reduce(&ids.borrow_as_bytes())
+                .map_err(Error::from)?
+                .alive_keys
+        });
 
-        Ok(stats.alive_keys)
+        Ok(alive_keys)
     }
 
     #[cfg_attr(feature = "tracing", tracing::instrument(
@@ -1385,26 +2677,52 @@ impl LowLevelConnection for Database {
                 &BonsaiAction::Database(DatabaseAction::Document(DocumentAction::Get)),
             )?;
         }
+        self.read_multiple_from_collection(ids, collection)
+    }
+
+    /// Reads the documents with `ids` from `collection` without checking
+    /// permissions.
+    fn read_multiple_from_collection(
+        &self,
+        ids: &[DocumentId],
+        collection: &CollectionName,
+    ) -> Result<Vec<OwnedDocument>, bonsaidb_core::Error> {
+        if let Some(sharding) = self.sharding(collection) {
+            let mut ids_by_shard = BTreeMap::<usize, Vec<DocumentId>>::new();
+            for id in ids {
+                ids_by_shard
+                    .entry(sharding.shard_for(id))
+                    .or_default()
+                    .push(id.clone());
+            }
+            let shards = self.shards(sharding)?;
+            let mut documents = Vec::with_capacity(ids.len());
+            for (shard, ids) in ids_by_shard {
+                documents.extend(shards[shard].read_multiple_from_collection(&ids, collection)?);
+            }
+            return Ok(documents);
+        }
         let mut ids = ids.to_vec();
         let collection = collection.clone();
-        let tree = self
-            .data
-            .context
-            .roots
-            .tree(
-                self.collection_tree::<Versioned, _>(&collection, document_tree_name(&collection))?,
-            )
-            .map_err(Error::from)?;
         ids.sort();
-        let keys_and_values = tree
-            .get_multiple(ids.iter().map(|id| id.as_ref()))
-            .map_err(Error::from)?;
+        let keys_and_values = with_document_root!(self.data.schema, &collection, |R| {
+            let tree = self
+                .data
+                .context
+                .roots
+                .tree(self.collection_tree::<R, _>(&collection, document_tree_name(&collection))?)
+                .map_err(Error::from)?;
+            tree.get_multiple(ids.iter().map(|id| id.as_ref()))
+                .map_err(Error::from)?
+        });
 
-        keys_and_values
+        self.record_bytes_read(keys_and_values.iter().map(|(_, value)| value.len()).sum());
+        let mut documents = keys_and_values
             .into_iter()
             .map(|(_, value)| deserialize_document(&value).map(BorrowedDocument::into_owned))
-            .collect::<Result<Vec<_>, Error>>()
-            .map_err(bonsaidb_core::Error::from)
+            .collect::<Result<Vec<_>, Error>>()?;
+        deduplication::resolve_contents(self, &collection, &mut documents)?;
+        Ok(documents)
     }
 
     #[cfg_attr(feature = "tracing", tracing::instrument(
@@ -1424,6 +2742,12 @@ impl LowLevelConnection for Database {
             collection_resource_name(self.name(), &collection),
             &BonsaiAction::Database(DatabaseAction::Compact),
         )?;
+        if let Some(sharding) = self.sharding(&collection) {
+            for shard in self.shards(sharding)? {
+                shard.compact_collection_by_name(collection.clone())?;
+            }
+            return Ok(());
+        }
         self.storage()
             .instance
             .tasks()
@@ -1448,15 +2772,38 @@ impl LowLevelConnection for Database {
         order: Sort,
         limit: Option<u32>,
         access_policy: AccessPolicy,
+        _read_options: ViewReadOptions,
     ) -> Result<Vec<schema::view::map::Serialized>, bonsaidb_core::Error> {
         let view = self.schematic().view_by_name(view)?;
         self.check_permission(
             view_resource_name(self.name(), &view.view_name()),
             &BonsaiAction::Database(DatabaseAction::View(ViewAction::Query)),
         )?;
+
+        let view_name = view.view_name();
+        // Sharded views are cached by each shard, because writes to a shard
+        // don't invalidate this database's cache.
+        let cache = if self.storage.instance.caches_view_queries(&view_name)
+            && self.sharding(&view.collection()).is_none()
+        {
+            let query = query_cache_key(key.as_ref(), order, limit)?;
+            if let Some(results) = self.query_cache().get(&view_name, &query) {
+                return self.redact_unreadable_mappings(&view.collection(), results);
+            }
+            Some((query, self.query_cache().generation(&view_name)))
+        } else {
+            None
+        };
+
+        let _permit = self.limit_query()?;
+        let _usage = self.track_usage(Activity::Querying);
         let mut results = Vec::new();
-        self.for_each_in_view(view, key, order, limit, access_policy, |entry| {
+        let mut bytes_read = 0;
+        self.for_each_in_view(view, key, order, limit, access_policy, |mut entry| {
+            self.resolve_referenced_values(view, &entry.key, &mut entry.mappings)?;
+            bytes_read += entry.key.len();
             for mapping in entry.mappings {
+                bytes_read += mapping.value.len();
                 results.push(bonsaidb_core::schema::view::map::Serialized {
                     source: mapping.source,
                     key: entry.key.clone(),
@@ -1465,8 +2812,15 @@ impl LowLevelConnection for Database {
             }
             Ok(())
         })?;
+        self.record_bytes_read(bytes_read);
 
-        Ok(results)
+        // Only results that are known to be up-to-date are cached.
+        if let (Some((query, generation)), AccessPolicy::UpdateBefore) = (cache, access_policy) {
+            self.query_cache()
+                .insert(&view_name, generation, query, results.clone());
+        }
+
+        self.redact_unreadable_mappings(&view.collection(), results)
     }
 
     #[cfg_attr(feature = "tracing", tracing::instrument(
@@ -1486,18 +2840,24 @@ impl LowLevelConnection for Database {
         order: Sort,
         limit: Option<u32>,
         access_policy: AccessPolicy,
+        _read_options: ViewReadOptions,
     ) -> Result<schema::view::map::MappedSerializedDocuments, bonsaidb_core::Error> {
-        let results = self.query_by_name(view, key, order, limit, access_policy)?;
+        let mut results = self.query_by_name(
+            view,
+            key,
+            order,
+            limit,
+            access_policy,
+            ViewReadOptions::default(),
+        )?;
         let view = self.schematic().view_by_name(view).unwrap(); // query() will fail if it's not present
+        let collection = view.collection();
 
+        // Documents the session isn't allowed to read are omitted, along with
+        // the mappings they emitted.
+        let readable = self.retain_readable_mappings(&collection, &mut results)?;
         let documents = self
-            .get_multiple_from_collection(
-                &results
-                    .iter()
-                    .map(|m| m.source.id.clone())
-                    .collect::<Vec<_>>(),
-                &view.collection(),
-            )?
+            .read_multiple_from_collection(&readable, &collection)?
             .into_iter()
             .map(|doc| (doc.header.id.clone(), doc))
             .collect::<BTreeMap<_, _>>();
@@ -1510,6 +2870,28 @@ impl LowLevelConnection for Database {
         )
     }
 
+    fn query_by_name_with_projected_docs(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+        projection: &Name,
+    ) -> Result<schema::view::map::MappedSerializedDocuments, bonsaidb_core::Error> {
+        let projection = self
+            .data
+            .schema
+            .projection_by_name(&view.collection, projection)?;
+        let mut results =
+            self.query_by_name_with_docs(view, key, order, limit, access_policy, read_options)?;
+        for document in results.documents.values_mut() {
+            document.contents = Bytes::from(projection.project(&document.contents)?);
+        }
+        Ok(results)
+    }
+
     #[cfg_attr(feature = "tracing", tracing::instrument(
         level = "trace",
         skip(self, view_name),
@@ -1525,13 +2907,41 @@ impl LowLevelConnection for Database {
         view_name: &ViewName,
         key: Option<SerializedQueryKey>,
         access_policy: AccessPolicy,
+        _read_options: ViewReadOptions,
     ) -> Result<Vec<u8>, bonsaidb_core::Error> {
-        let mut mappings = self.reduce_grouped_by_name(view_name, key, access_policy)?;
+        let view = self.data.schema.view_by_name(view_name)?;
+        // Entries storing values by reference have no reduced values.
+        if view.value_storage() == ValueStorage::Reference {
+            return Err(bonsaidb_core::Error::ReduceUnimplemented);
+        }
+        let sharded = self.sharding(&view.collection()).is_some();
+        let redact = self.storage.instance.redacts_unreadable_view_entries();
+        let _permit = self.limit_query()?;
+        let _usage = self.track_usage(Activity::Querying);
+        let mut mappings = match key {
+            // The cached reduce segments only cover this database's entries,
+            // so sharded views are reduced from each key's reduced value.
+            // Segments also include every document's mappings, so redacted
+            // reductions only use the values readable by the session.
+            key if sharded || redact => self.reduce_grouped(view_name, key, access_policy)?,
+            None => self.reduce_range_segmented(
+                view_name,
+                Range {
+                    start: connection::Bound::Unbounded,
+                    end: connection::Bound::Unbounded,
+                },
+                access_policy,
+            )?,
+            Some(SerializedQueryKey::Range(range)) => {
+                self.reduce_range_segmented(view_name, range, access_policy)?
+            }
+            key => self.reduce_grouped(view_name, key, access_policy)?,
+        };
+        self.storage.check_deadline()?;
 
         let result = if mappings.len() == 1 {
             mappings.pop().unwrap().value.into_vec()
         } else {
-            let view = self.data.schema.view_by_name(view_name)?;
             view.reduce(
                 &mappings
                     .iter()
@@ -1560,22 +2970,303 @@ impl LowLevelConnection for Database {
         view_name: &ViewName,
         key: Option<SerializedQueryKey>,
         access_policy: AccessPolicy,
+        _read_options: ViewReadOptions,
     ) -> Result<Vec<MappedSerializedValue>, bonsaidb_core::Error> {
+        let _permit = self.limit_query()?;
+        let _usage = self.track_usage(Activity::Querying);
+        self.reduce_grouped(view_name, key, access_policy)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        level = "trace",
+        skip(self, view_name, aggregations),
+        fields(
+            database = self.name(),
+            view.collection.name = view_name.collection.name.as_ref(),
+            view.collection.authority = view_name.collection.authority.as_ref(),
+            view.name = view_name.name.as_ref(),
+        )
+    ))]
+    fn aggregate_by_name(
+        &self,
+        view_name: &ViewName,
+        key: Option<SerializedQueryKey>,
+        aggregations: &[Aggregation],
+        grouping: AggregationGrouping,
+        access_policy: AccessPolicy,
+        _read_options: ViewReadOptions,
+    ) -> Result<Vec<SerializedAggregatedGroup>, bonsaidb_core::Error> {
         let view = self.data.schema.view_by_name(view_name)?;
         self.check_permission(
             view_resource_name(self.name(), &view.view_name()),
             &BonsaiAction::Database(DatabaseAction::View(ViewAction::Reduce)),
         )?;
-        let mut mappings = Vec::new();
-        self.for_each_in_view(view, key, Sort::Ascending, None, access_policy, |entry| {
-            mappings.push(MappedSerializedValue {
-                key: entry.key,
-                value: entry.reduced_value,
-            });
-            Ok(())
-        })?;
+        let _permit = self.limit_query()?;
+        let _usage = self.track_usage(Activity::Querying);
+        let requires_values = aggregations.iter().any(Aggregation::requires_values);
+        let collection = view.collection();
+        let redact = self.storage.instance.redacts_unreadable_view_entries();
+        let mut readable = HashMap::new();
+        let mut groups = Vec::<(Option<Bytes>, Aggregator)>::new();
+        if grouping == AggregationGrouping::None {
+            groups.push((None, Aggregator::default()));
+        }
+        self.for_each_in_view(
+            view,
+            key,
+            Sort::Ascending,
+            None,
+            access_policy,
+            |mut entry| {
+                if redact {
+                    self.retain_readable_entry_mappings(
+                        &collection,
+                        &mut entry.mappings,
+                        &mut readable,
+                    )?;
+                    if entry.mappings.is_empty() {
+                        return Ok(());
+                    }
+                }
+                if requires_values {
+                    self.resolve_referenced_values(view, &entry.key, &mut entry.mappings)?;
+                }
+                let group_key = match grouping {
+                    AggregationGrouping::None => None,
+                    AggregationGrouping::Key => Some(&entry.key[..]),
+                    AggregationGrouping::Prefix(length) => {
+                        let length = usize::try_from(length)
+                            .unwrap_or(usize::MAX)
+                            .min(entry.key.len());
+                        Some(&entry.key[..length])
+                    }
+                };
+                // Entries are visited in key order, which keeps each group's
+                // entries next to each other.
+                if groups.last().map_or(true, |(key, _)| {
+                    key.as_ref().map(|key| &key[..]) != group_key
+                }) {
+                    groups.push((
+                        group_key.map(|key| Bytes::from(key.to_vec())),
+                        Aggregator::default(),
+                    ));
+                }
+                let (_, aggregator) = groups.last_mut().expect("group pushed above");
+                for mapping in &entry.mappings {
+                    let value = if requires_values {
+                        Some(view.aggregate_value(&mapping.value).map_err(Error::from)?)
+                    } else {
+                        None
+                    };
+                    aggregator.push(value);
+                }
+                Ok(())
+            },
+        )?;
 
-        Ok(mappings)
+        Ok(groups
+            .into_iter()
+            .map(|(key, aggregator)| SerializedAggregatedGroup {
+                key,
+                values: aggregator.finish(aggregations),
+            })
+            .collect())
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        level = "trace",
+        skip(self, view_name),
+        fields(
+            database = self.name(),
+            view.collection.name = view_name.collection.name.as_ref(),
+            view.collection.authority = view_name.collection.authority.as_ref(),
+            view.name = view_name.name.as_ref(),
+        )
+    ))]
+    fn count_by_name(
+        &self,
+        view_name: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+        _read_options: ViewReadOptions,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        let view = self.data.schema.view_by_name(view_name)?;
+        self.check_permission(
+            view_resource_name(self.name(), &view.view_name()),
+            &BonsaiAction::Database(DatabaseAction::View(ViewAction::Query)),
+        )?;
+        // Each view entry stores all of the mappings for a single key, so the
+        // tree's key statistics can't be used to count the mappings.
+        let collection = view.collection();
+        let redact = self.storage.instance.redacts_unreadable_view_entries();
+        let mut readable = HashMap::new();
+        let mut count = 0;
+        self.for_each_in_view(
+            view,
+            key,
+            Sort::Ascending,
+            None,
+            access_policy,
+            |mut entry| {
+                if redact {
+                    self.retain_readable_entry_mappings(
+                        &collection,
+                        &mut entry.mappings,
+                        &mut readable,
+                    )?;
+                }
+                count += entry.mappings.len() as u64;
+                Ok(())
+            },
+        )?;
+
+        Ok(count)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        level = "trace",
+        skip(self, view_name),
+        fields(
+            database = self.name(),
+            view.collection.name = view_name.collection.name.as_ref(),
+            view.collection.authority = view_name.collection.authority.as_ref(),
+            view.name = view_name.name.as_ref(),
+        )
+    ))]
+    fn document_mappings_by_name(
+        &self,
+        view_name: &ViewName,
+        document_id: DocumentId,
+        access_policy: AccessPolicy,
+        _read_options: ViewReadOptions,
+    ) -> Result<Vec<schema::view::map::Serialized>, bonsaidb_core::Error> {
+        let view = self.data.schema.view_by_name(view_name)?;
+        self.check_permission(
+            view_resource_name(self.name(), &view.view_name()),
+            &BonsaiAction::Database(DatabaseAction::View(ViewAction::Query)),
+        )?;
+        // A document's entries are stored in the shard that stores the
+        // document.
+        if let Some(sharding) = self.sharding(&view.collection()) {
+            return self
+                .shard_for(sharding, &document_id)?
+                .document_mappings_by_name(
+                    view_name,
+                    document_id,
+                    access_policy,
+                    ViewReadOptions::default(),
+                );
+        }
+
+        let _permit = self.limit_query()?;
+        let _usage = self.track_usage(Activity::Querying);
+        self.prepare_view_for_access(view, access_policy)?;
+
+        // The document map stores the keys each document emitted, which
+        // avoids scanning the view's entries.
+        let document_map = self
+            .roots()
+            .tree(self.collection_tree::<Unversioned, _>(
+                &view.collection(),
+                view_document_map_tree_name(&view.view_name()),
+            )?)
+            .map_err(Error::from)?;
+        let mut keys = match document_map.get(&document_id).map_err(Error::from)? {
+            Some(keys) => bincode::deserialize::<HashSet<OwnedBytes>>(&keys)
+                .map_err(Error::from)?
+                .into_iter()
+                .map(|key| key.0)
+                .collect::<Vec<_>>(),
+            None => Vec::new(),
+        };
+        keys.sort();
+
+        let mut results = Vec::new();
+        let mut bytes_read = 0;
+        for (_, entry) in self
+            .view_entries(view)?
+            .get_multiple(keys.iter().map(|key| key.as_slice()))
+            .map_err(Error::from)?
+        {
+            let mut entry = bincode::deserialize::<ViewEntry>(&entry).map_err(Error::from)?;
+            entry
+                .mappings
+                .retain(|mapping| mapping.source.id == document_id);
+            self.resolve_referenced_values(view, &entry.key, &mut entry.mappings)?;
+            for mapping in entry.mappings {
+                bytes_read += entry.key.len() + mapping.value.len();
+                results.push(bonsaidb_core::schema::view::map::Serialized {
+                    source: mapping.source,
+                    key: entry.key.clone(),
+                    value: mapping.value,
+                });
+            }
+        }
+        self.record_bytes_read(bytes_read);
+
+        self.finish_view_access(view, access_policy)?;
+        Ok(results)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        level = "trace",
+        skip(self, join_name),
+        fields(
+            database = self.name(),
+            join.collection.name = join_name.collection.name.as_ref(),
+            join.collection.authority = join_name.collection.authority.as_ref(),
+            join.name = join_name.name.as_ref(),
+        )
+    ))]
+    fn query_join_by_name(
+        &self,
+        join_name: &ViewName,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+    ) -> Result<Vec<SerializedJoinedEntry>, bonsaidb_core::Error> {
+        let join = self.data.schema.join_by_name(join_name)?;
+        let left = self.data.schema.view_by_name(&join.left)?;
+        let right = self.data.schema.view_by_name(&join.right)?;
+        for view in [left, right] {
+            self.check_permission(
+                view_resource_name(self.name(), &view.view_name()),
+                &BonsaiAction::Database(DatabaseAction::View(ViewAction::Query)),
+            )?;
+            // Each shard only contains a portion of the view's entries, so the
+            // entries sharing a key may be stored in different shards.
+            if self.sharding(&view.collection()).is_some() {
+                return Err(bonsaidb_core::Error::other(
+                    "bonsaidb-local",
+                    "joined views can't join views of sharded collections",
+                ));
+            }
+        }
+
+        let _permit = self.limit_query()?;
+        let _usage = self.track_usage(Activity::Querying);
+        // Updating the views updates the joined entries of the keys the
+        // updates modified.
+        self.prepare_view_for_access(left, access_policy)?;
+        self.prepare_view_for_access(right, access_policy)?;
+        join::ensure_join_built(self, &join)?;
+
+        let entries = Self::read_keys(&join::join_tree(self, &join)?, key, order, limit)?;
+        self.record_bytes_read(entries.iter().map(|entry| entry.len()).sum());
+        let mut entries = entries
+            .into_iter()
+            .map(|entry| bincode::deserialize(&entry).map_err(Error::from))
+            .collect::<Result<Vec<SerializedJoinedEntry>, Error>>()?;
+        for entry in &mut entries {
+            for (view, mappings) in [(left, &mut entry.left), (right, &mut entry.right)] {
+                self.resolve_joined_values(view, &entry.key, mappings)?;
+            }
+        }
+
+        self.finish_view_access(left, access_policy)?;
+        self.finish_view_access(right, access_policy)?;
+        Ok(entries)
     }
 
     #[cfg_attr(feature = "tracing", tracing::instrument(
@@ -1605,9 +3296,24 @@ impl LowLevelConnection for Database {
             Ok(())
         })?;
 
-        let results = LowLevelConnection::apply_transaction(self, transaction)?;
+        // Each shard's documents are deleted in a separate transaction.
+        let sharding = self.sharding(&collection);
+        let mut transactions = BTreeMap::<usize, Transaction>::new();
+        for op in transaction.operations {
+            let shard = match (sharding, &op.command) {
+                (Some(sharding), Command::Delete { header }) => sharding.shard_for(&header.id),
+                _ => 0,
+            };
+            transactions.entry(shard).or_default().push(op);
+        }
+
+        let mut deleted = 0;
+        for transaction in transactions.into_values() {
+            let results = LowLevelConnection::apply_transaction(self, transaction)?;
+            deleted += results.len() as u64;
+        }
 
-        Ok(results.len() as u64)
+        Ok(deleted)
     }
 }
 
@@ -1651,7 +3357,15 @@ impl Deref for Context {
 #[derive(Debug)]
 pub(crate) struct ContextData {
     pub(crate) roots: Roots<AnyFile>,
+    key_tree: keyvalue::KeyTree,
     key_value_state: Arc<Mutex<keyvalue::KeyValueState>>,
+    document_watchers: Mutex<Vec<flume::Sender<Arc<DocumentChanges>>>>,
+    query_cache: QueryCache,
+    reduce_cache: ReduceCache,
+    query_limiter: Option<QueryLimiter>,
+    view_query_counts: ViewQueryCounts,
+    mapping_rates: MappingRates,
+    pub(crate) join_lock: Mutex<()>,
 }
 
 impl Borrow<Roots<AnyFile>> for Context {
@@ -1663,7 +3377,9 @@ impl Borrow<Roots<AnyFile>> for Context {
 impl Context {
     pub(crate) fn new(
         roots: Roots<AnyFile>,
+        key_tree: keyvalue::KeyTree,
         key_value_persistence: KeyValuePersistence,
+        concurrent_query_limit: Option<usize>,
         storage_lock: Option<StorageLock>,
     ) -> Self {
         let background_worker_target = Watchable::new(BackgroundWorkerProcessTarget::Never);
@@ -1671,13 +3387,22 @@ impl Context {
         let key_value_state = Arc::new(Mutex::new(keyvalue::KeyValueState::new(
             key_value_persistence,
             roots.clone(),
+            key_tree.clone(),
             background_worker_target,
         )));
         let background_worker_state = Arc::downgrade(&key_value_state);
         let context = Self {
             data: Arc::new(ContextData {
                 roots,
+                key_tree,
                 key_value_state,
+                document_watchers: Mutex::default(),
+                query_cache: QueryCache::default(),
+                reduce_cache: ReduceCache::default(),
+                query_limiter: concurrent_query_limit.map(QueryLimiter::new),
+                view_query_counts: ViewQueryCounts::default(),
+                mapping_rates: MappingRates::default(),
+                join_lock: Mutex::default(),
             }),
         };
         std::thread::Builder::new()
@@ -1693,14 +3418,44 @@ impl Context {
         context
     }
 
+    pub(crate) fn key_tree(&self) -> &keyvalue::KeyTree {
+        &self.data.key_tree
+    }
+
     pub(crate) fn perform_kv_operation(
         &self,
         op: KeyOperation,
     ) -> Result<Output, bonsaidb_core::Error> {
+        if matches!(op.command, bonsaidb_core::keyvalue::Command::Flush) {
+            let flushed = keyvalue::KeyValueState::flush(&self.data.key_value_state);
+            return Ok(Output::Status(if flushed {
+                KeyStatus::Updated
+            } else {
+                KeyStatus::NotChanged
+            }));
+        }
+
         let mut state = self.data.key_value_state.lock();
         state.perform_kv_operation(op, &self.data.key_value_state)
     }
 
+    pub(crate) fn compare_and_swap_key(
+        &self,
+        namespace: Option<&str>,
+        key: &str,
+        expected: &Value,
+        new_entry: Option<(Value, Option<Timestamp>)>,
+    ) -> Result<bool, Error> {
+        let mut state = self.data.key_value_state.lock();
+        state.compare_and_swap(
+            namespace,
+            key,
+            expected,
+            new_entry,
+            &self.data.key_value_state,
+        )
+    }
+
     pub(crate) fn update_key_expiration<'key>(
         &self,
         tree_key: impl Into<Cow<'key, str>>,
@@ -1710,6 +3465,16 @@ impl Context {
         state.update_key_expiration(tree_key, expiration);
     }
 
+    fn notify_document_watchers(&self, changes: DocumentChanges) {
+        let mut watchers = self.data.document_watchers.lock();
+        if watchers.is_empty() || changes.is_empty() {
+            return;
+        }
+
+        let changes = Arc::new(changes);
+        watchers.retain(|watcher| watcher.send(changes.clone()).is_ok());
+    }
+
     #[cfg(test)]
     pub(crate) fn kv_persistence_watcher(&self) -> watchable::Watcher<Timestamp> {
         let state = self.data.key_value_state.lock();
@@ -1717,8 +3482,10 @@ impl Context {
     }
 }
 
-impl Drop for ContextData {
-    fn drop(&mut self) {
+impl ContextData {
+    /// Writes any dirty key-value entries to disk, blocking until they have
+    /// been persisted.
+    pub(crate) fn persist_key_value_store(&self) {
         if let Some(shutdown) = {
             let mut state = self.key_value_state.lock();
             state.shutdown(&self.key_value_state)
@@ -1726,6 +3493,20 @@ impl Drop for ContextData {
             let _ = shutdown.recv();
         }
     }
+
+    /// Writes the number of times each view has been queried to disk.
+    pub(crate) fn persist_view_query_counts(&self) {
+        if let Err(err) = self.view_query_counts.persist(&self.roots) {
+            log::error!("Error persisting view query counts: {err}");
+        }
+    }
+}
+
+impl Drop for ContextData {
+    fn drop(&mut self) {
+        self.persist_key_value_store();
+        self.persist_view_query_counts();
+    }
 }
 
 pub fn document_tree_name(collection: &CollectionName) -> String {
@@ -0,0 +1,281 @@
+use std::collections::{BTreeMap, HashMap};
+use std::convert::Infallible;
+
+use bonsaidb_core::arc_bytes::serde::{Bytes, CowBytes};
+use bonsaidb_core::arc_bytes::ArcBytes;
+use bonsaidb_core::document::{BorrowedDocument, OwnedDocument};
+use bonsaidb_core::schema::CollectionName;
+use nebari::io::any::AnyFile;
+use nebari::tree::{
+    AnyTreeRoot, CompareSwap, KeyOperation, Operation, Root, ScanEvaluation, Unversioned,
+};
+use nebari::{AbortError, LockedTransactionTree, UnlockedTransactionTree};
+
+use crate::database::{
+    deserialize_document, document_tree_name, serialize_document, with_document_root, Database,
+};
+use crate::Error;
+
+/// Used to store the contents of a deduplicated collection's documents.
+///
+/// Each entry is keyed by the SHA256 digest of the contents, which is the
+/// digest stored in the revision of each document referencing them. The value
+/// is the number of references as a big-endian `u64` followed by the contents.
+pub fn document_contents_tree_name(collection: &CollectionName) -> String {
+    format!("contents.{collection:#}")
+}
+
+fn encode_entry(references: u64, contents: &[u8]) -> ArcBytes<'static> {
+    let mut entry = Vec::with_capacity(8 + contents.len());
+    entry.extend_from_slice(&references.to_be_bytes());
+    entry.extend_from_slice(contents);
+    ArcBytes::from(entry)
+}
+
+fn decode_entry(entry: &[u8]) -> Result<(u64, &[u8]), Error> {
+    if entry.len() < 8 {
+        return Err(Error::other(
+            "bonsaidb-local",
+            "invalid deduplicated contents entry",
+        ));
+    }
+    let (references, contents) = entry.split_at(8);
+    let references = u64::from_be_bytes(references.try_into().expect("checked length"));
+    Ok((references, contents))
+}
+
+/// Adds a reference to `contents`, whose digest is `sha256`. The contents are
+/// stored if no other documents reference them.
+pub fn add_reference(
+    tree: &mut LockedTransactionTree<'_, Unversioned, AnyFile>,
+    sha256: &[u8; 32],
+    contents: &[u8],
+) -> Result<(), Error> {
+    let mut result = Ok(());
+    tree.modify(
+        vec![ArcBytes::from(sha256.to_vec())],
+        Operation::CompareSwap(CompareSwap::new(&mut |_, entry| match entry {
+            Some(entry) => match decode_entry(&entry) {
+                Ok((references, stored)) => {
+                    KeyOperation::Set(encode_entry(references.saturating_add(1), stored))
+                }
+                Err(err) => {
+                    result = Err(err);
+                    KeyOperation::Skip
+                }
+            },
+            None => KeyOperation::Set(encode_entry(1, contents)),
+        })),
+    )?;
+    result
+}
+
+/// Returns the stored contents whose digest is `sha256`.
+pub fn stored_contents(
+    tree: &mut LockedTransactionTree<'_, Unversioned, AnyFile>,
+    sha256: &[u8; 32],
+) -> Result<Option<Vec<u8>>, Error> {
+    tree.get(sha256)?
+        .map(|entry| decode_entry(&entry).map(|(_, contents)| contents.to_vec()))
+        .transpose()
+}
+
+/// Removes one reference for each digest in `digests`. Contents that are no
+/// longer referenced are removed.
+pub fn remove_references(
+    tree: &mut LockedTransactionTree<'_, Unversioned, AnyFile>,
+    digests: impl IntoIterator<Item = [u8; 32]>,
+) -> Result<(), Error> {
+    let mut removed = BTreeMap::<ArcBytes<'static>, u64>::new();
+    for digest in digests {
+        *removed.entry(ArcBytes::from(digest.to_vec())).or_default() += 1;
+    }
+    if removed.is_empty() {
+        return Ok(());
+    }
+
+    let mut result = Ok(());
+    tree.modify(
+        removed.keys().cloned().collect(),
+        Operation::CompareSwap(CompareSwap::new(&mut |key, entry| {
+            let Some(entry) = entry else {
+                return KeyOperation::Skip;
+            };
+            match decode_entry(&entry) {
+                Ok((references, stored)) => {
+                    let references = references.saturating_sub(removed[key]);
+                    if references == 0 {
+                        KeyOperation::Remove
+                    } else {
+                        KeyOperation::Set(encode_entry(references, stored))
+                    }
+                }
+                Err(err) => {
+                    result = Err(err);
+                    KeyOperation::Skip
+                }
+            }
+        })),
+    )?;
+    result
+}
+
+/// Replaces the contents of `documents`, which were read from the documents
+/// tree of `collection`, with their stored contents.
+pub fn resolve_contents(
+    database: &Database,
+    collection: &CollectionName,
+    documents: &mut [OwnedDocument],
+) -> Result<(), Error> {
+    if documents.is_empty() || !database.data.schema.collection_is_deduplicated(collection) {
+        return Ok(());
+    }
+
+    let tree = database
+        .roots()
+        .tree(database.collection_tree::<Unversioned, _>(
+            collection,
+            document_contents_tree_name(collection),
+        )?)?;
+    let mut digests = documents
+        .iter()
+        .map(|document| document.header.revision.sha256)
+        .collect::<Vec<_>>();
+    digests.sort_unstable();
+    digests.dedup();
+    let entries = tree
+        .get_multiple(digests.iter().map(|digest| &digest[..]))?
+        .into_iter()
+        .map(|(digest, entry)| (digest.to_vec(), entry))
+        .collect::<HashMap<_, _>>();
+    for document in documents {
+        let entry = entries
+            .get(&document.header.revision.sha256[..])
+            .ok_or_else(|| Error::other("bonsaidb-local", "deduplicated contents are missing"))?;
+        let (_, contents) = decode_entry(entry)?;
+        document.contents = Bytes::from(contents.to_vec());
+    }
+    Ok(())
+}
+
+/// Replaces the contents of the serialized `documents` with their stored
+/// contents from the `contents` tree.
+pub fn resolve_serialized_contents(
+    contents: &UnlockedTransactionTree<AnyFile>,
+    documents: &mut [(ArcBytes<'static>, ArcBytes<'static>)],
+) -> Result<(), Error> {
+    let headers = documents
+        .iter()
+        .map(|(_, document)| deserialize_document(document).map(|document| document.header))
+        .collect::<Result<Vec<_>, Error>>()?;
+    let mut digests = headers
+        .iter()
+        .map(|header| header.revision.sha256)
+        .collect::<Vec<_>>();
+    digests.sort_unstable();
+    digests.dedup();
+    let entries = contents
+        .lock::<Unversioned>()
+        .get_multiple(digests.iter().map(|digest| &digest[..]))?
+        .into_iter()
+        .map(|(digest, entry)| (digest.to_vec(), entry))
+        .collect::<HashMap<_, _>>();
+    for ((_, serialized), header) in documents.iter_mut().zip(headers) {
+        let entry = entries
+            .get(&header.revision.sha256[..])
+            .ok_or_else(|| Error::other("bonsaidb-local", "deduplicated contents are missing"))?;
+        let (_, stored) = decode_entry(entry)?;
+        *serialized = ArcBytes::from(serialize_document(&BorrowedDocument {
+            header,
+            contents: CowBytes::from(stored),
+        })?);
+    }
+    Ok(())
+}
+
+/// Recounts the references to the stored contents of `collection`, removing
+/// any contents that are no longer referenced by a document.
+pub fn recount_references(database: &Database, collection: &CollectionName) -> Result<(), Error> {
+    with_document_root!(database.data.schema, collection, |R| {
+        recount_references_in::<R>(database, collection)
+    })
+}
+
+fn recount_references_in<R: Root>(
+    database: &Database,
+    collection: &CollectionName,
+) -> Result<(), Error> {
+    let transaction = database
+        .roots()
+        .transaction::<_, dyn AnyTreeRoot<AnyFile>>(&[
+            Box::new(database.collection_tree::<R, _>(collection, document_tree_name(collection))?)
+                as Box<dyn AnyTreeRoot<AnyFile>>,
+            Box::new(database.collection_tree::<Unversioned, _>(
+                collection,
+                document_contents_tree_name(collection),
+            )?),
+        ])?;
+
+    let mut references = HashMap::<Vec<u8>, u64>::new();
+    transaction
+        .tree::<R>(0)
+        .unwrap()
+        .scan(
+            &(..),
+            true,
+            |_, _, _| ScanEvaluation::ReadData,
+            |_, _| ScanEvaluation::ReadData,
+            |_, _, document| {
+                let document = deserialize_document(&document).map_err(AbortError::Other)?;
+                *references
+                    .entry(document.header.revision.sha256.to_vec())
+                    .or_default() += 1;
+                Ok(())
+            },
+        )
+        .map_err(|err| match err {
+            AbortError::Other(err) => err,
+            AbortError::Nebari(err) => Error::from(err),
+        })?;
+
+    let mut contents = transaction.tree::<Unversioned>(1).unwrap();
+    let mut digests = Vec::new();
+    contents.scan::<Infallible, _, _, _, _>(
+        &(..),
+        true,
+        |_, _, _| ScanEvaluation::ReadData,
+        |digest, _| {
+            digests.push(digest.clone());
+            ScanEvaluation::Skip
+        },
+        |_, _, _| unreachable!("contents are never read"),
+    )?;
+    let mut result = Ok(());
+    contents.modify(
+        digests,
+        Operation::CompareSwap(CompareSwap::new(&mut |digest, entry| {
+            let Some(entry) = entry else {
+                return KeyOperation::Skip;
+            };
+            match (decode_entry(&entry), references.get(&digest[..])) {
+                (Ok((stored_references, stored)), Some(&references)) => {
+                    if stored_references == references {
+                        KeyOperation::Skip
+                    } else {
+                        KeyOperation::Set(encode_entry(references, stored))
+                    }
+                }
+                (Ok(_), None) => KeyOperation::Remove,
+                (Err(err), _) => {
+                    result = Err(err);
+                    KeyOperation::Skip
+                }
+            }
+        })),
+    )?;
+    drop(contents);
+    result?;
+
+    transaction.commit()?;
+    Ok(())
+}
@@ -0,0 +1,316 @@
+use std::collections::{HashMap, HashSet};
+use std::slice;
+
+use bonsaidb_core::arc_bytes::serde::CowBytes;
+use bonsaidb_core::arc_bytes::OwnedBytes;
+use bonsaidb_core::document::{BorrowedDocument, DocumentId};
+use bonsaidb_core::schema::view::Serialized;
+use bonsaidb_core::schema::{CollectionName, ViewName};
+use nebari::tree::Unversioned;
+
+use crate::database::deduplication::resolve_contents;
+use crate::database::{deserialize_document, document_tree_name, with_document_root, Database};
+use crate::views::integrity_scanner::{reset_view, tree_keys};
+use crate::views::mapper::{Map, Mapper};
+use crate::views::{
+    view_document_map_tree_name, view_entries_tree_name, view_invalidated_docs_tree_name, ViewEntry,
+};
+use crate::Error;
+
+/// The result of verifying the integrity of a [`Database`].
+///
+/// Documents that are modified while the database is being verified may be
+/// reported as inconsistent.
+#[derive(Clone, Debug, Default)]
+pub struct IntegrityReport {
+    /// The results for each collection in the database's schema.
+    pub collections: Vec<CollectionIntegrity>,
+}
+
+impl IntegrityReport {
+    /// Returns true if no problems were found.
+    #[must_use]
+    pub fn is_consistent(&self) -> bool {
+        self.collections
+            .iter()
+            .all(CollectionIntegrity::is_consistent)
+    }
+}
+
+/// The integrity of a collection and its views.
+#[derive(Clone, Debug)]
+pub struct CollectionIntegrity {
+    /// The name of the collection.
+    pub collection: CollectionName,
+    /// The number of documents that were verified.
+    pub documents: usize,
+    /// The documents that could not be read, along with the error that
+    /// occurred. Reading a document verifies the checksums of its stored data
+    /// and, if the collection is encrypted, that it can be decrypted.
+    pub unreadable_documents: Vec<(DocumentId, String)>,
+    /// The results for each view of this collection.
+    pub views: Vec<ViewIntegrity>,
+}
+
+impl CollectionIntegrity {
+    /// Returns true if no problems were found.
+    #[must_use]
+    pub fn is_consistent(&self) -> bool {
+        self.unreadable_documents.is_empty() && self.views.iter().all(ViewIntegrity::is_consistent)
+    }
+}
+
+/// The integrity of a view's stored mappings.
+///
+/// Documents that are waiting to be mapped into a lazily updated view are not
+/// considered inconsistent.
+#[derive(Clone, Debug)]
+pub struct ViewIntegrity {
+    /// The name of the view.
+    pub view: ViewName,
+    /// Documents that exist but have never been mapped into the view.
+    pub unmapped_documents: Vec<DocumentId>,
+    /// Documents that no longer exist but still have mappings in the view.
+    pub orphaned_documents: Vec<DocumentId>,
+    /// Documents whose view entries don't match the keys recorded for the
+    /// document when it was mapped.
+    pub mismatched_documents: Vec<DocumentId>,
+    /// True if the view was rebuilt to repair the problems found.
+    pub repaired: bool,
+}
+
+impl ViewIntegrity {
+    /// Returns true if no problems were found.
+    #[must_use]
+    pub fn is_consistent(&self) -> bool {
+        self.unmapped_documents.is_empty()
+            && self.orphaned_documents.is_empty()
+            && self.mismatched_documents.is_empty()
+    }
+}
+
+pub(crate) fn verify_collection(
+    database: &Database,
+    collection: &CollectionName,
+    repair: bool,
+) -> Result<CollectionIntegrity, Error> {
+    let (document_ids, mut unreadable_documents) =
+        with_document_root!(database.data.schema, collection, |R| {
+            let documents = database.roots().tree(
+                database.collection_tree::<R, _>(collection, document_tree_name(collection))?,
+            )?;
+            let document_ids = tree_keys(&documents)?;
+
+            let mut unreadable_documents = Vec::new();
+            for id in &document_ids {
+                // Reading the document verifies its checksums and decrypts it.
+                let result = documents
+                    .get(id.as_ref())
+                    .map_err(Error::from)
+                    .and_then(|document| {
+                        document.map_or(Ok(()), |document| {
+                            deserialize_document(&document).map(|_| ())
+                        })
+                    });
+                if let Err(err) = result {
+                    unreadable_documents.push((id.clone(), err.to_string()));
+                }
+            }
+            (document_ids, unreadable_documents)
+        });
+    unreadable_documents.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut views = Vec::new();
+    for view in database
+        .data
+        .schema
+        .views_in_collection(collection)
+        .unwrap_or_default()
+    {
+        let mut integrity = verify_view(database, collection, view, &document_ids)?;
+        if repair && !integrity.is_consistent() {
+            repair_view(database, collection, view)?;
+            integrity.repaired = true;
+        }
+        views.push(integrity);
+    }
+
+    Ok(CollectionIntegrity {
+        collection: collection.clone(),
+        documents: document_ids.len(),
+        unreadable_documents,
+        views,
+    })
+}
+
+/// Removes the documents that the view's filter excludes from `document_ids`.
+/// Excluded documents are never recorded in the view's document map.
+fn retain_documents_in_view(
+    database: &Database,
+    collection: &CollectionName,
+    view: &dyn Serialized,
+    document_ids: &mut Vec<DocumentId>,
+) -> Result<(), Error> {
+    if document_ids.is_empty() {
+        return Ok(());
+    }
+
+    with_document_root!(database.data.schema, collection, |R| {
+        let documents = database
+            .roots()
+            .tree(database.collection_tree::<R, _>(collection, document_tree_name(collection))?)?;
+        let mut retained = Vec::with_capacity(document_ids.len());
+        for id in document_ids.drain(..) {
+            let in_view = match documents.get(id.as_ref())? {
+                // Unreadable documents are reported separately, and are kept
+                // so that repairing the view attempts to map them.
+                Some(document) => match deserialize_document(&document) {
+                    Ok(document) => {
+                        let mut document = document.into_owned();
+                        resolve_contents(database, collection, slice::from_mut(&mut document))?;
+                        view.filter(&BorrowedDocument {
+                            header: document.header,
+                            contents: CowBytes::from(&document.contents[..]),
+                        })
+                        .map_err(bonsaidb_core::Error::from)?
+                    }
+                    Err(_) => true,
+                },
+                None => false,
+            };
+            if in_view {
+                retained.push(id);
+            }
+        }
+        *document_ids = retained;
+    });
+    Ok(())
+}
+
+fn verify_view(
+    database: &Database,
+    collection: &CollectionName,
+    view: &dyn Serialized,
+    document_ids: &HashSet<DocumentId>,
+) -> Result<ViewIntegrity, Error> {
+    let view_name = view.view_name();
+    if let Some(job) = database
+        .storage
+        .instance
+        .tasks()
+        .spawn_integrity_check(view, database)
+    {
+        job.receive()??;
+    }
+
+    let invalidated = tree_keys(&database.roots().tree(
+        database.collection_tree::<Unversioned, _>(
+            collection,
+            view_invalidated_docs_tree_name(&view_name),
+        )?,
+    )?)?;
+
+    let mut document_map = HashMap::new();
+    for (id, keys) in database
+        .roots()
+        .tree(database.collection_tree::<Unversioned, _>(
+            collection,
+            view_document_map_tree_name(&view_name),
+        )?)?
+        .get_range(&(..))?
+    {
+        document_map.insert(
+            DocumentId::try_from(id.as_slice())?,
+            bincode::deserialize::<HashSet<OwnedBytes>>(&keys)?,
+        );
+    }
+
+    let mut entry_keys = HashMap::<_, HashSet<OwnedBytes>>::new();
+    for (key, entry) in
+        database
+            .roots()
+            .tree(database.collection_tree::<Unversioned, _>(
+                collection,
+                view_entries_tree_name(&view_name),
+            )?)?
+            .get_range(&(..))?
+    {
+        let entry = bincode::deserialize::<ViewEntry>(&entry)?;
+        for mapping in entry.mappings {
+            entry_keys
+                .entry(mapping.source.id)
+                .or_default()
+                .insert(OwnedBytes::from(key.as_slice()));
+        }
+    }
+
+    let mut unmapped_documents = document_ids
+        .iter()
+        .filter(|id| !invalidated.contains(*id) && !document_map.contains_key(*id))
+        .cloned()
+        .collect::<Vec<_>>();
+    retain_documents_in_view(database, collection, view, &mut unmapped_documents)?;
+    unmapped_documents.sort();
+
+    // Mapping a deleted document records that it produced no keys, so only
+    // documents with keys are orphaned.
+    let mut orphaned_documents = document_map
+        .iter()
+        .filter(|(id, keys)| {
+            !keys.is_empty() && !document_ids.contains(*id) && !invalidated.contains(*id)
+        })
+        .map(|(id, _)| id.clone())
+        .collect::<Vec<_>>();
+    orphaned_documents.sort();
+
+    let no_keys = HashSet::new();
+    let mut mismatched_documents = document_map
+        .keys()
+        .chain(entry_keys.keys())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .filter(|id| {
+            !invalidated.contains(*id)
+                && document_map.get(*id).unwrap_or(&no_keys)
+                    != entry_keys.get(*id).unwrap_or(&no_keys)
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    mismatched_documents.sort();
+
+    Ok(ViewIntegrity {
+        view: view_name,
+        unmapped_documents,
+        orphaned_documents,
+        mismatched_documents,
+        repaired: false,
+    })
+}
+
+/// Discards all stored data for `view` and maps every document again.
+fn repair_view(
+    database: &Database,
+    collection: &CollectionName,
+    view: &dyn Serialized,
+) -> Result<(), Error> {
+    let view_name = view.view_name();
+    reset_view(database, collection, &view_name, view.version())?;
+    let tasks = database.storage.instance.tasks();
+    tasks.mark_view_rebuilding(
+        database.data.name.clone(),
+        collection.clone(),
+        view_name.clone(),
+    );
+    tasks
+        .jobs
+        .lookup_or_enqueue(Mapper {
+            database: database.clone(),
+            map: Map {
+                database: database.data.name.clone(),
+                collection: collection.clone(),
+                view_name,
+            },
+        })
+        .receive()??;
+    Ok(())
+}
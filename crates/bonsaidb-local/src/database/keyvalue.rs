@@ -1,5 +1,6 @@
 use std::borrow::Cow;
-use std::collections::{btree_map, BTreeMap, VecDeque};
+use std::collections::{btree_map, BTreeMap, BTreeSet, VecDeque};
+use std::convert::Infallible;
 use std::sync::{Arc, Weak};
 use std::time::Duration;
 
@@ -9,11 +10,13 @@ use bonsaidb_core::keyvalue::{
     Value,
 };
 use bonsaidb_core::permissions::bonsai::{
-    keyvalue_key_resource_name, BonsaiAction, DatabaseAction, KeyValueAction,
+    keyvalue_key_prefix_resource_name, keyvalue_key_resource_name,
+    keyvalue_namespace_resource_name, kv_resource_name, BonsaiAction, DatabaseAction,
+    KeyValueAction,
 };
 use bonsaidb_core::transaction::{ChangedKey, Changes};
 use nebari::io::any::AnyFile;
-use nebari::tree::{CompareSwap, Operation, Root, ScanEvaluation, Unversioned};
+use nebari::tree::{CompareSwap, Operation, Root, ScanEvaluation, TreeRoot, Unversioned};
 use nebari::{AbortError, ArcBytes, Roots};
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
@@ -22,6 +25,8 @@ use watchable::{Watchable, Watcher};
 use crate::config::KeyValuePersistence;
 use crate::database::compat;
 use crate::storage::StorageLock;
+#[cfg(any(feature = "encryption", feature = "compression"))]
+use crate::storage::TreeVault;
 use crate::tasks::{Job, Keyed, Task};
 use crate::{Database, DatabaseNonBlocking, Error};
 
@@ -57,15 +62,56 @@ impl Entry {
 
 impl KeyValue for Database {
     fn execute_key_operation(&self, op: KeyOperation) -> Result<Output, bonsaidb_core::Error> {
-        self.check_permission(
-            keyvalue_key_resource_name(self.name(), op.namespace.as_deref(), &op.key),
-            &BonsaiAction::Database(DatabaseAction::KeyValue(KeyValueAction::ExecuteOperation)),
-        )?;
+        let action =
+            BonsaiAction::Database(DatabaseAction::KeyValue(KeyValueAction::ExecuteOperation));
+        match &op.command {
+            Command::GetMultiple { keys } => keys.iter().try_for_each(|key| {
+                self.check_key_permission(op.namespace.as_deref(), key, &action)
+            }),
+            Command::SetMultiple { entries } => entries.iter().try_for_each(|(key, _)| {
+                self.check_key_permission(op.namespace.as_deref(), key, &action)
+            }),
+            Command::ListNamespaces | Command::Flush => {
+                self.check_permission(kv_resource_name(self.name()), &action)
+            }
+            Command::CountKeys | Command::DeleteNamespace => self.check_permission(
+                keyvalue_namespace_resource_name(self.name(), op.namespace.as_deref()),
+                &action,
+            ),
+            _ => self.check_key_permission(op.namespace.as_deref(), &op.key, &action),
+        }?;
         self.data.context.perform_kv_operation(op)
     }
 }
 
 impl Database {
+    /// Checks that `action` is allowed on `key`, either directly or through
+    /// any of the key's prefixes.
+    fn check_key_permission(
+        &self,
+        namespace: Option<&str>,
+        key: &str,
+        action: &BonsaiAction,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let resource_name = keyvalue_key_resource_name(self.name(), namespace, key);
+        let allowed_by_prefix = || {
+            key.char_indices()
+                .map(|(index, _)| index)
+                .chain(std::iter::once(key.len()))
+                .any(|end| {
+                    self.allowed_to(
+                        keyvalue_key_prefix_resource_name(self.name(), namespace, &key[..end]),
+                        action,
+                    )
+                })
+        };
+        if self.allowed_to(&resource_name, action) || allowed_by_prefix() {
+            Ok(())
+        } else {
+            self.check_permission(resource_name, action)
+        }
+    }
+
     pub(crate) fn all_key_value_entries(
         &self,
     ) -> Result<BTreeMap<(Option<String>, String), Entry>, Error> {
@@ -76,7 +122,7 @@ impl Database {
         let mut all_entries = BTreeMap::new();
         database
             .roots()
-            .tree(Unversioned::tree(KEY_TREE))?
+            .tree(database.key_value_tree())?
             .scan::<Error, _, _, _, _>(
                 &(..),
                 true,
@@ -124,6 +170,40 @@ impl Database {
 
 pub(crate) const KEY_TREE: &str = "kv";
 
+/// Describes how to open the key-value store's tree, including which vault
+/// encrypts and compresses its contents.
+#[derive(Debug, Clone, Default)]
+pub struct KeyTree {
+    #[cfg(any(feature = "encryption", feature = "compression"))]
+    vault: Option<TreeVault>,
+}
+
+impl KeyTree {
+    pub fn new(
+        #[cfg(any(feature = "encryption", feature = "compression"))] vault: Option<TreeVault>,
+    ) -> Self {
+        Self {
+            #[cfg(any(feature = "encryption", feature = "compression"))]
+            vault,
+        }
+    }
+
+    #[cfg_attr(
+        not(any(feature = "encryption", feature = "compression")),
+        allow(unused_mut, clippy::unused_self)
+    )]
+    pub fn root(&self) -> TreeRoot<Unversioned, AnyFile> {
+        let mut tree = Unversioned::tree(KEY_TREE);
+
+        #[cfg(any(feature = "encryption", feature = "compression"))]
+        if let Some(vault) = self.vault.clone() {
+            tree = tree.with_vault(vault);
+        }
+
+        tree
+    }
+}
+
 fn full_key(namespace: Option<&str>, key: &str) -> String {
     let full_length = namespace.map_or_else(|| 0, str::len) + key.len() + 1;
     let mut full_key = String::with_capacity(full_length);
@@ -207,6 +287,7 @@ fn decrement(existing: &Numeric, amount: &Numeric, saturating: bool) -> Numeric
 #[derive(Debug)]
 pub struct KeyValueState {
     roots: Roots<AnyFile>,
+    key_tree: KeyTree,
     persistence: KeyValuePersistence,
     last_commit: Timestamp,
     background_worker_target: Watchable<BackgroundWorkerProcessTarget>,
@@ -222,10 +303,12 @@ impl KeyValueState {
     pub fn new(
         persistence: KeyValuePersistence,
         roots: Roots<AnyFile>,
+        key_tree: KeyTree,
         background_worker_target: Watchable<BackgroundWorkerProcessTarget>,
     ) -> Self {
         Self {
             roots,
+            key_tree,
             persistence,
             last_commit: Timestamp::now(),
             expiring_keys: BTreeMap::new(),
@@ -278,6 +361,18 @@ impl KeyValueState {
                 saturating,
                 now,
             ),
+            Command::GetMultiple { keys } => {
+                self.execute_get_multiple_operation(op.namespace.as_deref(), &keys)
+            }
+            Command::SetMultiple { entries } => {
+                self.execute_set_multiple_operation(op.namespace.as_deref(), entries, now)
+            }
+            Command::ListNamespaces => self.execute_list_namespaces_operation(),
+            Command::CountKeys => self.execute_count_keys_operation(op.namespace.as_deref()),
+            Command::DeleteNamespace => {
+                self.execute_delete_namespace_operation(op.namespace.as_deref())
+            }
+            Command::Flush => unreachable!("flush operations are handled by KeyValueState::flush"),
         };
         if result.is_ok() {
             if self.needs_commit(now) {
@@ -481,6 +576,143 @@ impl KeyValueState {
         self.execute_numeric_operation(namespace, key, amount, saturating, now, decrement)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(self)))]
+    fn execute_get_multiple_operation(
+        &self,
+        namespace: Option<&str>,
+        keys: &[String],
+    ) -> Result<Output, bonsaidb_core::Error> {
+        let values = keys
+            .iter()
+            .map(|key| {
+                self.get(&full_key(namespace, key))
+                    .map(|entry| entry.map(|entry| entry.value))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Error::from)?;
+        Ok(Output::Values(values))
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip(self, entries, now))
+    )]
+    fn execute_set_multiple_operation(
+        &mut self,
+        namespace: Option<&str>,
+        entries: Vec<(String, Value)>,
+        now: Timestamp,
+    ) -> Result<Output, bonsaidb_core::Error> {
+        // Validate every value and look up every existing entry before making
+        // any changes, ensuring that either all or none of the keys are set.
+        let mut pending = Vec::with_capacity(entries.len());
+        let mut statuses = Vec::with_capacity(entries.len());
+        let mut seen_keys = BTreeSet::new();
+        for (key, value) in entries {
+            let full_key = full_key(namespace, &key);
+            let status = if seen_keys.contains(&full_key)
+                || self.get(&full_key).map_err(Error::from)?.is_some()
+            {
+                KeyStatus::Updated
+            } else {
+                KeyStatus::Inserted
+            };
+            seen_keys.insert(full_key.clone());
+            pending.push((full_key, value.validate()?));
+            statuses.push(status);
+        }
+
+        for (full_key, value) in pending {
+            self.update_key_expiration(&full_key, None);
+            self.set(
+                full_key,
+                Entry {
+                    value,
+                    expiration: None,
+                    last_updated: now,
+                },
+            );
+        }
+        Ok(Output::Statuses(statuses))
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(self)))]
+    fn execute_list_namespaces_operation(&self) -> Result<Output, bonsaidb_core::Error> {
+        let namespaces = self
+            .keys_with_prefix("")?
+            .into_iter()
+            .filter_map(|full_key| split_key(&full_key).and_then(|(namespace, _)| namespace))
+            .collect::<BTreeSet<_>>();
+        Ok(Output::Namespaces(namespaces.into_iter().collect()))
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(self)))]
+    fn execute_count_keys_operation(
+        &self,
+        namespace: Option<&str>,
+    ) -> Result<Output, bonsaidb_core::Error> {
+        let keys = self.keys_with_prefix(&full_key(namespace, ""))?;
+        Ok(Output::Count(keys.len() as u64))
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(self)))]
+    fn execute_delete_namespace_operation(
+        &mut self,
+        namespace: Option<&str>,
+    ) -> Result<Output, bonsaidb_core::Error> {
+        let keys = self.keys_with_prefix(&full_key(namespace, ""))?;
+        let deleted = keys.len() as u64;
+        // Every removal is staged as a dirty key, which are all persisted
+        // within the same transaction.
+        for key in keys {
+            self.update_key_expiration(&key, None);
+            self.dirty_keys.insert(key, None);
+        }
+        Ok(Output::Count(deleted))
+    }
+
+    /// Returns every stored key whose full key begins with `prefix`, including
+    /// keys that have not been persisted yet.
+    fn keys_with_prefix(&self, prefix: &str) -> Result<BTreeSet<String>, Error> {
+        let mut keys = BTreeSet::new();
+        let prefix_bytes = prefix.as_bytes();
+        self.roots
+            .tree(self.key_tree.root())?
+            .scan::<Infallible, _, _, _, _>(
+                &(prefix_bytes..),
+                true,
+                |_, _, _| ScanEvaluation::ReadData,
+                |key, _| {
+                    if !key.starts_with(prefix_bytes) {
+                        return ScanEvaluation::Stop;
+                    }
+                    if let Ok(key) = std::str::from_utf8(key) {
+                        keys.insert(key.to_string());
+                    }
+                    ScanEvaluation::Skip
+                },
+                |_, _, _| unreachable!("key-value entries are never read"),
+            )?;
+
+        // Apply the pending writes, followed by the dirty keys.
+        for (key, entry) in self
+            .keys_being_persisted
+            .iter()
+            .flat_map(|keys| keys.iter())
+            .chain(&self.dirty_keys)
+        {
+            if key.starts_with(prefix) {
+                if entry.is_some() {
+                    keys.insert(key.clone());
+                } else {
+                    keys.remove(key);
+                }
+            }
+        }
+
+        Ok(keys)
+    }
+
     fn execute_numeric_operation<F: Fn(&Numeric, &Numeric, bool) -> Numeric>(
         &mut self,
         namespace: Option<&str>,
@@ -528,7 +760,7 @@ impl KeyValueState {
             Ok(persisting_entry.clone())
         } else {
             // There might be a value on-disk we need to remove.
-            let previous_value = Self::retrieve_key_from_disk(&self.roots, &key)?;
+            let previous_value = Self::retrieve_key_from_disk(&self.roots, &self.key_tree, &key)?;
             self.dirty_keys.insert(key, None);
             Ok(previous_value)
         }
@@ -545,7 +777,7 @@ impl KeyValueState {
         {
             Ok(persisting_entry.clone())
         } else {
-            Self::retrieve_key_from_disk(&self.roots, key)
+            Self::retrieve_key_from_disk(&self.roots, &self.key_tree, key)
         }
     }
 
@@ -566,7 +798,7 @@ impl KeyValueState {
             {
                 persisting_entry.clone()
             } else {
-                Self::retrieve_key_from_disk(&self.roots, map_entry.key())?
+                Self::retrieve_key_from_disk(&self.roots, &self.key_tree, map_entry.key())?
             };
             map_entry.or_insert(value);
             Ok(stored_value)
@@ -580,13 +812,17 @@ impl KeyValueState {
         }
     }
 
-    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(roots)))]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip(roots, key_tree))
+    )]
     fn retrieve_key_from_disk(
         roots: &Roots<AnyFile>,
+        key_tree: &KeyTree,
         key: &str,
     ) -> Result<Option<Entry>, nebari::Error> {
         roots
-            .tree(Unversioned::tree(KEY_TREE))?
+            .tree(key_tree.root())?
             .get(key.as_bytes())
             .map(|current| current.and_then(|current| bincode::deserialize::<Entry>(&current).ok()))
     }
@@ -663,10 +899,11 @@ impl KeyValueState {
     pub fn commit_dirty_keys(&mut self, state: &Arc<Mutex<KeyValueState>>) -> bool {
         if let Some(keys) = self.stage_dirty_keys() {
             let roots = self.roots.clone();
+            let key_tree = self.key_tree.clone();
             let state = state.clone();
             std::thread::Builder::new()
                 .name(String::from("keyvalue-persist"))
-                .spawn(move || Self::persist_keys(&state, &roots, &keys))
+                .spawn(move || Self::persist_keys(&state, &roots, &key_tree, &keys))
                 .unwrap();
             self.last_commit = Timestamp::now();
             true
@@ -675,6 +912,70 @@ impl KeyValueState {
         }
     }
 
+    /// Replaces the entry stored in `key` with `new_entry` if its current value
+    /// is `expected`. If `new_entry` is None, the key is removed instead.
+    /// Returns true if the entry was replaced.
+    pub fn compare_and_swap(
+        &mut self,
+        namespace: Option<&str>,
+        key: &str,
+        expected: &Value,
+        new_entry: Option<(Value, Option<Timestamp>)>,
+        state: &Arc<Mutex<KeyValueState>>,
+    ) -> Result<bool, Error> {
+        let now = Timestamp::now();
+        self.remove_expired_keys(now);
+        let full_key = full_key(namespace, key);
+        let matches = self
+            .get(&full_key)?
+            .map_or(false, |entry| &entry.value == expected);
+        if matches {
+            if let Some((value, expiration)) = new_entry {
+                self.update_key_expiration(&full_key, expiration);
+                self.set(
+                    full_key,
+                    Entry {
+                        value,
+                        expiration,
+                        last_updated: now,
+                    },
+                );
+            } else {
+                self.remove(full_key)?;
+            }
+            if self.needs_commit(now) {
+                self.commit_dirty_keys(state);
+            }
+            self.update_background_worker_target();
+        }
+        Ok(matches)
+    }
+
+    /// Persists all dirty keys, returning once they have been written to disk.
+    /// Returns true if there were any keys to persist.
+    pub fn flush(state: &Arc<Mutex<KeyValueState>>) -> bool {
+        let mut flushed = false;
+        loop {
+            let mut persistence_watcher = {
+                let mut locked = state.lock();
+                if locked.dirty_keys.is_empty() && locked.keys_being_persisted.is_none() {
+                    return flushed;
+                }
+                flushed = true;
+                // The watcher must be created while the lock is held to ensure
+                // that the persistence can't complete before we begin waiting.
+                let watcher = locked.last_persistence.watch();
+                // If keys are already being persisted, the dirty keys will be
+                // committed on a later iteration.
+                locked.commit_dirty_keys(state);
+                watcher
+            };
+            if persistence_watcher.watch().is_err() {
+                return flushed;
+            }
+        }
+    }
+
     #[cfg(test)]
     pub fn persistence_watcher(&self) -> Watcher<Timestamp> {
         self.last_persistence.watch()
@@ -684,11 +985,10 @@ impl KeyValueState {
     fn persist_keys(
         key_value_state: &Arc<Mutex<KeyValueState>>,
         roots: &Roots<AnyFile>,
+        key_tree: &KeyTree,
         keys: &BTreeMap<String, Option<Entry>>,
     ) -> Result<(), bonsaidb_core::Error> {
-        let mut transaction = roots
-            .transaction(&[Unversioned::tree(KEY_TREE)])
-            .map_err(Error::from)?;
+        let mut transaction = roots.transaction(&[key_tree.root()]).map_err(Error::from)?;
         let all_keys = keys
             .keys()
             .map(|key| ArcBytes::from(key.as_bytes().to_vec()))
@@ -758,7 +1058,7 @@ impl KeyValueState {
             }
         };
         if let Some(final_keys) = final_keys {
-            Self::persist_keys(key_value_state, roots, &final_keys)?;
+            Self::persist_keys(key_value_state, roots, key_tree, &final_keys)?;
         }
         Ok(())
     }
@@ -891,7 +1191,7 @@ mod tests {
             .file_manager(AnyFileManager::std())
             .open()?;
 
-        let context = Context::new(sled.clone(), persistence, None);
+        let context = Context::new(sled.clone(), KeyTree::default(), persistence, None, None);
 
         test_contents(context, sled)?;
 
@@ -1171,6 +1471,44 @@ mod tests {
         )
     }
 
+    #[test]
+    fn flush() -> anyhow::Result<()> {
+        run_test_with_persistence(
+            "kv-flush",
+            KeyValuePersistence::lazy([PersistenceThreshold::after_changes(100)]),
+            &|sender, sled| {
+                let tree = sled.tree(Unversioned::tree(KEY_TREE))?;
+                let flush = || {
+                    sender.perform_kv_operation(KeyOperation {
+                        namespace: None,
+                        key: String::new(),
+                        command: Command::Flush,
+                    })
+                };
+                assert!(matches!(flush()?, Output::Status(KeyStatus::NotChanged)));
+
+                sender.perform_kv_operation(KeyOperation {
+                    namespace: None,
+                    key: String::from("key1"),
+                    command: Command::Set(SetCommand {
+                        value: Value::Bytes(Bytes::default()),
+                        expiration: None,
+                        keep_existing_expiration: false,
+                        check: None,
+                        return_previous_value: false,
+                    }),
+                })?;
+                assert!(tree.get(b"\0key1")?.is_none());
+
+                assert!(matches!(flush()?, Output::Status(KeyStatus::Updated)));
+                assert!(tree.get(b"\0key1")?.is_some());
+                assert!(matches!(flush()?, Output::Status(KeyStatus::NotChanged)));
+
+                Ok(())
+            },
+        )
+    }
+
     #[test]
     fn saves_on_drop() -> anyhow::Result<()> {
         let dir = TestDirectory::new("saves-on-drop.bonsaidb");
@@ -1181,8 +1519,10 @@ mod tests {
 
         let context = Context::new(
             sled,
+            KeyTree::default(),
             KeyValuePersistence::lazy([PersistenceThreshold::after_changes(2)]),
             None,
+            None,
         );
         context
             .perform_kv_operation(KeyOperation {
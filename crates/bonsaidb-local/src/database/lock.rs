@@ -0,0 +1,176 @@
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use bonsaidb_core::arc_bytes::serde::Bytes;
+use bonsaidb_core::keyvalue::{
+    Command, KeyCheck, KeyOperation, KeyStatus, KeyValue, Output, SetCommand, Timestamp, Value,
+};
+
+use crate::{Database, Error};
+
+/// The key-value namespace that locks are stored in.
+pub const LOCK_NAMESPACE: &str = "_bonsaidb.locks";
+
+/// How often [`Database::lock()`] retries acquiring a lock that is held
+/// elsewhere.
+pub(crate) const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(25);
+
+impl Database {
+    /// Acquires the lock named `name`, waiting until it is available.
+    ///
+    /// Locks are stored as keys in the [`LOCK_NAMESPACE`] key-value namespace
+    /// that expire after `ttl`. While the returned guard exists, the lock is
+    /// renewed in the background well before it expires. If the process holding
+    /// the lock stops without releasing it, the lock becomes available once
+    /// `ttl` has elapsed.
+    ///
+    /// Acquiring a lock requires the permission to execute key-value operations
+    /// on the lock's key.
+    pub fn lock(&self, name: &str, ttl: Duration) -> Result<KeyValueLock, Error> {
+        loop {
+            if let Some(lock) = self.try_lock(name, ttl)? {
+                return Ok(lock);
+            }
+            std::thread::sleep(LOCK_RETRY_INTERVAL);
+        }
+    }
+
+    /// Acquires the lock named `name` if it is available. Returns None if the
+    /// lock is held elsewhere. See [`Self::lock()`] for more information.
+    pub fn try_lock(&self, name: &str, ttl: Duration) -> Result<Option<KeyValueLock>, Error> {
+        let token = Value::Bytes(Bytes::from(rand::random::<[u8; 16]>().to_vec()));
+        let output = self.execute_key_operation(KeyOperation {
+            namespace: Some(LOCK_NAMESPACE.to_string()),
+            key: name.to_string(),
+            command: Command::Set(SetCommand {
+                value: token.clone(),
+                expiration: Some(Timestamp::now() + ttl),
+                keep_existing_expiration: false,
+                check: Some(KeyCheck::OnlyIfVacant),
+                return_previous_value: false,
+            }),
+        })?;
+        match output {
+            Output::Status(KeyStatus::Inserted) => Ok(Some(KeyValueLock::new(
+                self.clone(),
+                name.to_string(),
+                token,
+                ttl,
+            ))),
+            Output::Status(KeyStatus::NotChanged) => Ok(None),
+            _ => unreachable!("Unexpected output from Set"),
+        }
+    }
+}
+
+/// A lock acquired with [`Database::lock()`] or [`Database::try_lock()`].
+///
+/// The lock is renewed in the background until it is released, either by
+/// calling [`Self::release()`] or by dropping this guard.
+#[derive(Debug)]
+#[must_use = "the lock is released when dropped"]
+pub struct KeyValueLock {
+    database: Database,
+    name: String,
+    token: Value,
+    renewal: Option<(flume::Sender<()>, JoinHandle<()>)>,
+    released: bool,
+}
+
+impl KeyValueLock {
+    fn new(database: Database, name: String, token: Value, ttl: Duration) -> Self {
+        let (stop_sender, stop_receiver) = flume::bounded(1);
+        let renewal = {
+            let database = database.clone();
+            let name = name.clone();
+            let token = token.clone();
+            std::thread::Builder::new()
+                .name(String::from("bonsaidb-lock"))
+                .spawn(move || renew_lock(&database, &name, &token, ttl, &stop_receiver))
+                .unwrap()
+        };
+        Self {
+            database,
+            name,
+            token,
+            renewal: Some((stop_sender, renewal)),
+            released: false,
+        }
+    }
+
+    /// Returns the name of this lock.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns true if this lock is still held. A lock can be lost if it
+    /// couldn't be renewed before it expired.
+    pub fn is_held(&self) -> Result<bool, Error> {
+        let output = self.database.execute_key_operation(KeyOperation {
+            namespace: Some(LOCK_NAMESPACE.to_string()),
+            key: self.name.clone(),
+            command: Command::Get { delete: false },
+        })?;
+        match output {
+            Output::Value(value) => Ok(value.as_ref() == Some(&self.token)),
+            _ => unreachable!("Unexpected output from Get"),
+        }
+    }
+
+    /// Releases this lock. Returns false if the lock was no longer held.
+    pub fn release(mut self) -> Result<bool, Error> {
+        self.unlock()
+    }
+
+    fn unlock(&mut self) -> Result<bool, Error> {
+        if let Some((stop_sender, renewal)) = self.renewal.take() {
+            drop(stop_sender);
+            let _ = renewal.join();
+        }
+        if self.released {
+            return Ok(false);
+        }
+        self.released = true;
+        self.database.data.context.compare_and_swap_key(
+            Some(LOCK_NAMESPACE),
+            &self.name,
+            &self.token,
+            None,
+        )
+    }
+}
+
+impl Drop for KeyValueLock {
+    fn drop(&mut self) {
+        if let Err(err) = self.unlock() {
+            log::error!("Error releasing lock {}: {err}", self.name);
+        }
+    }
+}
+
+/// Renews the lock every third of `ttl` until `stop` is disconnected or the
+/// lock is lost.
+fn renew_lock(
+    database: &Database,
+    name: &str,
+    token: &Value,
+    ttl: Duration,
+    stop: &flume::Receiver<()>,
+) {
+    while let Err(flume::RecvTimeoutError::Timeout) = stop.recv_timeout(ttl / 3) {
+        match database.data.context.compare_and_swap_key(
+            Some(LOCK_NAMESPACE),
+            name,
+            token,
+            Some((token.clone(), Some(Timestamp::now() + ttl))),
+        ) {
+            Ok(true) => {}
+            Ok(false) => {
+                log::warn!("Lock {name} expired before it could be renewed");
+                break;
+            }
+            Err(err) => log::error!("Error renewing lock {name}: {err}"),
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+
+use bonsaidb_core::arc_bytes::ArcBytes;
+use bonsaidb_core::schema::{CollectionName, ViewName};
+use nebari::tree::{CompareSwap, KeyOperation, Operation, Root};
+
+use crate::database::{document_tree_name, with_document_root, Database};
+use crate::tasks::{Job, Keyed, Task};
+use crate::views::{
+    view_document_map_tree_name, view_entries_tree_name, view_invalidated_docs_tree_name,
+    view_versions_tree_name,
+};
+use crate::Error;
+
+impl Database {
+    /// Moves the documents stored under each of `collection`'s legacy names
+    /// into `collection`'s document tree, and removes the trees stored under
+    /// the legacy names. Documents already stored in `collection` are kept.
+    /// Returns the number of documents moved.
+    pub(crate) fn migrate_legacy_collections(
+        &self,
+        collection: &CollectionName,
+    ) -> Result<u64, Error> {
+        let mut moved = 0;
+        for legacy_name in self.data.schema.legacy_names_for_collection(collection) {
+            with_document_root!(self.data.schema, collection, |R| {
+                moved += self.move_documents::<R>(legacy_name, collection)?;
+            });
+
+            // The legacy collection's views are no longer reachable. The
+            // views of `collection` are rebuilt, because their versions are
+            // stored under the new collection name.
+            let roots = self.roots();
+            for view in self
+                .data
+                .schema
+                .views_in_collection(collection)
+                .unwrap_or_default()
+            {
+                let legacy_view = ViewName {
+                    collection: legacy_name.clone(),
+                    name: view.view_name().name,
+                };
+                roots.delete_tree(view_entries_tree_name(&legacy_view))?;
+                roots.delete_tree(view_document_map_tree_name(&legacy_view))?;
+                roots.delete_tree(view_invalidated_docs_tree_name(&legacy_view))?;
+            }
+            roots.delete_tree(view_versions_tree_name(legacy_name))?;
+        }
+        Ok(moved)
+    }
+
+    fn move_documents<R: Root>(
+        &self,
+        legacy_name: &CollectionName,
+        collection: &CollectionName,
+    ) -> Result<u64, Error> {
+        let roots = self.roots();
+        // Legacy trees are opened using the vault of the collection they were
+        // renamed to.
+        let mut documents = roots
+            .tree(self.collection_tree::<R, _>(collection, document_tree_name(legacy_name))?)?
+            .get_range(&(..))?
+            .into_iter()
+            .map(|(id, contents)| (id.to_vec(), contents))
+            .collect::<HashMap<_, _>>();
+        let moved = documents.len() as u64;
+        if !documents.is_empty() {
+            let mut ids = documents
+                .keys()
+                .map(|id| ArcBytes::from(id.clone()))
+                .collect::<Vec<_>>();
+            ids.sort();
+
+            let transaction = roots.transaction(&[
+                self.collection_tree::<R, _>(collection, document_tree_name(collection))?
+            ])?;
+            transaction.tree::<R>(0).unwrap().modify(
+                ids,
+                Operation::CompareSwap(CompareSwap::new(&mut |id, existing| {
+                    let contents = documents.remove(&id[..]);
+                    match contents {
+                        Some(contents) if existing.is_none() => KeyOperation::Set(contents),
+                        _ => KeyOperation::Skip,
+                    }
+                })),
+            )?;
+            transaction.commit()?;
+        }
+
+        roots.delete_tree(document_tree_name(legacy_name))?;
+        Ok(moved)
+    }
+}
+
+/// Moves the documents stored under a collection's legacy names into the
+/// collection.
+#[derive(Debug)]
+pub struct CollectionMigrator {
+    pub database: Database,
+    pub collection: CollectionName,
+}
+
+impl Keyed<Task> for CollectionMigrator {
+    fn key(&self) -> Task {
+        Task::CollectionMigration(self.database.data.name.clone(), self.collection.clone())
+    }
+}
+
+impl Job for CollectionMigrator {
+    type Error = Error;
+    type Output = ();
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    fn execute(&mut self) -> Result<Self::Output, Self::Error> {
+        let moved = self.database.migrate_legacy_collections(&self.collection)?;
+        if moved > 0 {
+            log::info!(
+                "Moved {moved} documents into {} in database {}",
+                self.collection,
+                self.database.data.name
+            );
+        }
+        self.database
+            .storage
+            .instance
+            .tasks()
+            .mark_collection_migrated(self.database.data.name.clone(), self.collection.clone());
+        Ok(())
+    }
+}
@@ -0,0 +1,276 @@
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow::array::{
+    ArrayRef, BinaryArray, BooleanArray, Float64Array, Int64Array, StringArray, UInt64Array,
+};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use bonsaidb_core::connection::{Connection, LowLevelConnection, Range, Sort, ViewMappings};
+use bonsaidb_core::document::CollectionDocument;
+use bonsaidb_core::schema::{Map, SerializedCollection, SerializedView};
+use parquet::arrow::ArrowWriter;
+
+use crate::{Database, Error};
+
+/// The number of rows written to each row group of an exported file.
+const EXPORT_BATCH_SIZE: u32 = 10_000;
+
+/// The columns written when exporting documents or view entries of type `T`
+/// to Parquet.
+///
+/// Each column is computed from a row using a function that returns None to
+/// write a null value.
+///
+/// ```rust
+/// # use bonsaidb_core::document::CollectionDocument;
+/// # use bonsaidb_core::test_util::Basic;
+/// use bonsaidb_local::ParquetMapping;
+///
+/// let mapping = ParquetMapping::<CollectionDocument<Basic>>::new()
+///     .with_u64("id", |doc| Some(doc.header.id))
+///     .with_string("value", |doc| Some(doc.contents.value.clone()))
+///     .with_string("category", |doc| doc.contents.category.clone());
+/// ```
+#[must_use]
+pub struct ParquetMapping<T> {
+    columns: Vec<Column<T>>,
+}
+
+struct Column<T> {
+    name: String,
+    values: ColumnValues<T>,
+}
+
+type ColumnFn<T, V> = Box<dyn Fn(&T) -> Option<V> + Send + Sync>;
+
+enum ColumnValues<T> {
+    Boolean(ColumnFn<T, bool>),
+    Int64(ColumnFn<T, i64>),
+    UInt64(ColumnFn<T, u64>),
+    Float64(ColumnFn<T, f64>),
+    Utf8(ColumnFn<T, String>),
+    Binary(ColumnFn<T, Vec<u8>>),
+}
+
+impl<T> Default for ParquetMapping<T> {
+    fn default() -> Self {
+        Self {
+            columns: Vec::new(),
+        }
+    }
+}
+
+impl<T> ParquetMapping<T> {
+    /// Returns a mapping with no columns.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a boolean column named `name` and returns self.
+    pub fn with_bool<F>(self, name: impl Into<String>, column: F) -> Self
+    where
+        F: Fn(&T) -> Option<bool> + Send + Sync + 'static,
+    {
+        self.with_column(name, ColumnValues::Boolean(Box::new(column)))
+    }
+
+    /// Adds a signed 64-bit integer column named `name` and returns self.
+    pub fn with_i64<F>(self, name: impl Into<String>, column: F) -> Self
+    where
+        F: Fn(&T) -> Option<i64> + Send + Sync + 'static,
+    {
+        self.with_column(name, ColumnValues::Int64(Box::new(column)))
+    }
+
+    /// Adds an unsigned 64-bit integer column named `name` and returns self.
+    pub fn with_u64<F>(self, name: impl Into<String>, column: F) -> Self
+    where
+        F: Fn(&T) -> Option<u64> + Send + Sync + 'static,
+    {
+        self.with_column(name, ColumnValues::UInt64(Box::new(column)))
+    }
+
+    /// Adds a 64-bit floating point column named `name` and returns self.
+    pub fn with_f64<F>(self, name: impl Into<String>, column: F) -> Self
+    where
+        F: Fn(&T) -> Option<f64> + Send + Sync + 'static,
+    {
+        self.with_column(name, ColumnValues::Float64(Box::new(column)))
+    }
+
+    /// Adds a UTF-8 string column named `name` and returns self.
+    pub fn with_string<F>(self, name: impl Into<String>, column: F) -> Self
+    where
+        F: Fn(&T) -> Option<String> + Send + Sync + 'static,
+    {
+        self.with_column(name, ColumnValues::Utf8(Box::new(column)))
+    }
+
+    /// Adds a binary column named `name` and returns self.
+    pub fn with_bytes<F>(self, name: impl Into<String>, column: F) -> Self
+    where
+        F: Fn(&T) -> Option<Vec<u8>> + Send + Sync + 'static,
+    {
+        self.with_column(name, ColumnValues::Binary(Box::new(column)))
+    }
+
+    fn with_column(mut self, name: impl Into<String>, values: ColumnValues<T>) -> Self {
+        self.columns.push(Column {
+            name: name.into(),
+            values,
+        });
+        self
+    }
+
+    /// Returns the Arrow schema of the columns in this mapping. Every column
+    /// is nullable.
+    #[must_use]
+    pub fn schema(&self) -> Schema {
+        Schema::new(
+            self.columns
+                .iter()
+                .map(|column| {
+                    let data_type = match &column.values {
+                        ColumnValues::Boolean(_) => DataType::Boolean,
+                        ColumnValues::Int64(_) => DataType::Int64,
+                        ColumnValues::UInt64(_) => DataType::UInt64,
+                        ColumnValues::Float64(_) => DataType::Float64,
+                        ColumnValues::Utf8(_) => DataType::Utf8,
+                        ColumnValues::Binary(_) => DataType::Binary,
+                    };
+                    Field::new(&column.name, data_type, true)
+                })
+                .collect(),
+        )
+    }
+
+    /// Converts `rows` into an Arrow [`RecordBatch`], which can be used
+    /// directly with Arrow-based tools.
+    pub fn record_batch(&self, rows: &[T]) -> Result<RecordBatch, Error> {
+        let arrays = self
+            .columns
+            .iter()
+            .map(|column| -> ArrayRef {
+                match &column.values {
+                    ColumnValues::Boolean(values) => {
+                        Arc::new(rows.iter().map(values.as_ref()).collect::<BooleanArray>())
+                    }
+                    ColumnValues::Int64(values) => {
+                        Arc::new(rows.iter().map(values.as_ref()).collect::<Int64Array>())
+                    }
+                    ColumnValues::UInt64(values) => {
+                        Arc::new(rows.iter().map(values.as_ref()).collect::<UInt64Array>())
+                    }
+                    ColumnValues::Float64(values) => {
+                        Arc::new(rows.iter().map(values.as_ref()).collect::<Float64Array>())
+                    }
+                    ColumnValues::Utf8(values) => {
+                        Arc::new(rows.iter().map(values.as_ref()).collect::<StringArray>())
+                    }
+                    ColumnValues::Binary(values) => {
+                        Arc::new(rows.iter().map(values.as_ref()).collect::<BinaryArray>())
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+        RecordBatch::try_new(Arc::new(self.schema()), arrays)
+            .map_err(|err| Error::other("parquet", err))
+    }
+}
+
+impl Database {
+    /// Writes the documents in `C` to a Parquet file at `path`, using
+    /// `mapping` to compute each row's columns. Returns the number of rows
+    /// written.
+    ///
+    /// Documents are written in ascending order of their ids. If `path`
+    /// already exists, it is replaced.
+    pub fn export_collection_parquet<C: SerializedCollection>(
+        &self,
+        path: impl AsRef<Path>,
+        mapping: &ParquetMapping<CollectionDocument<C>>,
+    ) -> Result<u64, Error> {
+        let mut writer = ParquetExport::create(path.as_ref(), mapping)?;
+        let collection = C::collection_name();
+        let mut ids = Range::from(..);
+        loop {
+            let documents = self.list_from_collection(
+                ids.clone(),
+                Sort::Ascending,
+                Some(EXPORT_BATCH_SIZE),
+                &collection,
+            )?;
+            let Some(last) = documents.last() else {
+                break;
+            };
+            ids = Range::from(..).after(last.header.id.clone());
+
+            let documents = documents
+                .iter()
+                .map(CollectionDocument::<C>::try_from)
+                .collect::<Result<Vec<_>, _>>()?;
+            writer.write(&documents)?;
+            if documents.len() < EXPORT_BATCH_SIZE as usize {
+                break;
+            }
+        }
+
+        writer.finish()
+    }
+
+    /// Writes the entries of the view `V` to a Parquet file at `path`, using
+    /// `mapping` to compute each row's columns. Returns the number of rows
+    /// written.
+    ///
+    /// Entries are written in ascending order of their keys. The view is
+    /// updated before it is exported. If `path` already exists, it is
+    /// replaced.
+    pub fn export_view_parquet<V: SerializedView>(
+        &self,
+        path: impl AsRef<Path>,
+        mapping: &ParquetMapping<Map<V::Key, V::Value>>,
+    ) -> Result<u64, Error> {
+        let mut writer = ParquetExport::create(path.as_ref(), mapping)?;
+        let entries: ViewMappings<V> = self.view::<V>().query()?;
+        for entries in entries.chunks(EXPORT_BATCH_SIZE as usize) {
+            writer.write(entries)?;
+        }
+        writer.finish()
+    }
+}
+
+struct ParquetExport<'a, T> {
+    writer: ArrowWriter<File>,
+    mapping: &'a ParquetMapping<T>,
+    rows: u64,
+}
+
+impl<'a, T> ParquetExport<'a, T> {
+    fn create(path: &Path, mapping: &'a ParquetMapping<T>) -> Result<Self, Error> {
+        let file = File::create(path)?;
+        let writer = ArrowWriter::try_new(file, Arc::new(mapping.schema()), None)
+            .map_err(|err| Error::other("parquet", err))?;
+        Ok(Self {
+            writer,
+            mapping,
+            rows: 0,
+        })
+    }
+
+    fn write(&mut self, rows: &[T]) -> Result<(), Error> {
+        self.writer
+            .write(&self.mapping.record_batch(rows)?)
+            .map_err(|err| Error::other("parquet", err))?;
+        self.rows += rows.len() as u64;
+        Ok(())
+    }
+
+    fn finish(self) -> Result<u64, Error> {
+        self.writer
+            .close()
+            .map_err(|err| Error::other("parquet", err))?;
+        Ok(self.rows)
+    }
+}
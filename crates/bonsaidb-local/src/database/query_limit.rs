@@ -0,0 +1,62 @@
+use std::time::Instant;
+
+use parking_lot::{Condvar, Mutex};
+
+/// Limits the number of expensive operations that execute concurrently on a
+/// database.
+#[derive(Debug)]
+pub(crate) struct QueryLimiter {
+    limit: usize,
+    active: Mutex<usize>,
+    released: Condvar,
+}
+
+impl QueryLimiter {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit: limit.max(1),
+            active: Mutex::new(0),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Waits until fewer than `limit` operations are executing and returns a
+    /// permit for one more operation, which is released when dropped.
+    ///
+    /// If `deadline` passes while waiting,
+    /// [`bonsaidb_core::Error::DeadlineExceeded`] is returned.
+    pub fn acquire(
+        &self,
+        deadline: Option<Instant>,
+    ) -> Result<QueryPermit<'_>, bonsaidb_core::Error> {
+        let mut active = self.active.lock();
+        while *active >= self.limit {
+            if let Some(deadline) = deadline {
+                if self.released.wait_until(&mut active, deadline).timed_out()
+                    && *active >= self.limit
+                {
+                    return Err(bonsaidb_core::Error::DeadlineExceeded);
+                }
+            } else {
+                self.released.wait(&mut active);
+            }
+        }
+        *active += 1;
+        Ok(QueryPermit { limiter: self })
+    }
+}
+
+/// Allows an expensive operation to execute. The next waiting operation is
+/// allowed to execute when this is dropped.
+#[derive(Debug)]
+#[must_use]
+pub(crate) struct QueryPermit<'a> {
+    limiter: &'a QueryLimiter,
+}
+
+impl Drop for QueryPermit<'_> {
+    fn drop(&mut self) {
+        *self.limiter.active.lock() -= 1;
+        self.limiter.released.notify_one();
+    }
+}
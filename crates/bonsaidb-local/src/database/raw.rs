@@ -0,0 +1,69 @@
+use nebari::io::any::AnyFile;
+use nebari::tree::{TreeRoot, Unversioned};
+use nebari::{ExecutingTransaction, Tree};
+
+use crate::{Database, Error};
+
+impl Database {
+    /// Opens the application-defined tree `name`, creating it if it doesn't
+    /// exist.
+    ///
+    /// Raw trees allow storing custom ordered data in the same files as this
+    /// database's documents, without the overhead of documents and views. Each
+    /// name is stored separately from the trees BonsaiDb uses internally, and
+    /// the tree is encrypted using the storage's default encryption key, if
+    /// one is configured.
+    ///
+    /// Raw trees are not subject to permissions, are not included in backups,
+    /// and are not synchronized to other storages. They should only be used by
+    /// trusted code.
+    pub fn raw_tree(&self, name: &str) -> Result<Tree<Unversioned, AnyFile>, Error> {
+        Ok(self.roots().tree(self.raw_tree_root(name)?)?)
+    }
+
+    /// Returns the root of the application-defined tree `name`, which can be
+    /// used with [`Self::raw_transaction()`]. See [`Self::raw_tree()`] for more
+    /// information.
+    pub fn raw_tree_root(&self, name: &str) -> Result<TreeRoot<Unversioned, AnyFile>, Error> {
+        if name.is_empty() {
+            return Err(Error::other(
+                "bonsaidb-local",
+                "raw tree names can't be empty",
+            ));
+        }
+
+        #[cfg_attr(
+            not(any(feature = "encryption", feature = "compression")),
+            allow(unused_mut)
+        )]
+        let mut tree = Unversioned::tree(raw_tree_name(name));
+
+        #[cfg(any(feature = "encryption", feature = "compression"))]
+        if let Some(vault) = self.storage.instance.tree_vault_for_key(None)? {
+            tree = tree.with_vault(vault);
+        }
+
+        Ok(tree)
+    }
+
+    /// Begins a transaction that atomically modifies one or more
+    /// application-defined trees. The trees are accessed by their index in
+    /// `trees`, and the changes are only persisted once the transaction is
+    /// committed.
+    pub fn raw_transaction(
+        &self,
+        trees: &[TreeRoot<Unversioned, AnyFile>],
+    ) -> Result<ExecutingTransaction<AnyFile>, Error> {
+        Ok(self.roots().transaction(trees)?)
+    }
+
+    /// Deletes the application-defined tree `name`. Returns true if the tree
+    /// existed.
+    pub fn delete_raw_tree(&self, name: &str) -> Result<bool, Error> {
+        Ok(self.roots().delete_tree(raw_tree_name(name))?)
+    }
+}
+
+fn raw_tree_name(name: &str) -> String {
+    format!("raw.{name}")
+}
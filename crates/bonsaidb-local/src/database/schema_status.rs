@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+
+use bonsaidb_core::schema::ViewName;
+use nebari::tree::Unversioned;
+
+use crate::database::Database;
+use crate::views::integrity_scanner::ViewVersion;
+use crate::views::view_versions_tree_name;
+use crate::Error;
+
+/// Whether each view of a [`Database`] has been built using the view's
+/// current definition.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SchemaStatus {
+    /// The status of each view in the database's schema.
+    pub views: HashMap<ViewName, ViewStatus>,
+}
+
+impl SchemaStatus {
+    /// Returns true if every view has been built using its current
+    /// definition.
+    #[must_use]
+    pub fn is_current(&self) -> bool {
+        self.views
+            .values()
+            .all(|status| matches!(status, ViewStatus::Current))
+    }
+}
+
+/// Whether a view's stored data was built using the view's current
+/// definition.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ViewStatus {
+    /// Every document has been mapped using the view's current definition.
+    /// Documents changed since the view was last updated may still be waiting
+    /// to be mapped.
+    Current,
+    /// The view's definition changed, and its documents are being mapped
+    /// again.
+    Rebuilding,
+    /// The view's stored data was built using a previous definition, or the
+    /// view hasn't been checked since it was added to the schema. The view is
+    /// rebuilt the next time it is accessed.
+    Outdated,
+}
+
+pub(crate) fn schema_status(database: &Database) -> Result<SchemaStatus, Error> {
+    let mut views = HashMap::new();
+    for collection in database.data.schema.collections() {
+        let view_versions = database
+            .roots()
+            .tree(database.collection_tree::<Unversioned, _>(
+                &collection,
+                view_versions_tree_name(&collection),
+            )?)?;
+        for view in database
+            .data
+            .schema
+            .views_in_collection(&collection)
+            .unwrap_or_default()
+        {
+            let view_name = view.view_name();
+            let version = view_versions
+                .get(view_name.to_string().as_bytes())?
+                .map(|version| ViewVersion::from_bytes(&version))
+                .transpose()?;
+            let status = match version {
+                Some(version) if version.is_current(view.version(), view.definition_checksum()) => {
+                    if version.rebuilding {
+                        ViewStatus::Rebuilding
+                    } else {
+                        ViewStatus::Current
+                    }
+                }
+                _ => ViewStatus::Outdated,
+            };
+            views.insert(view_name, status);
+        }
+    }
+
+    Ok(SchemaStatus { views })
+}
@@ -0,0 +1,222 @@
+use std::borrow::Cow;
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use bonsaidb_core::arc_bytes::serde::Bytes;
+use bonsaidb_core::connection::{
+    self, AccessPolicy, LowLevelConnection, Range, SerializedQueryKey, Sort,
+};
+use bonsaidb_core::document::DocumentId;
+use bonsaidb_core::schema::view::{self};
+use bonsaidb_core::schema::CollectionName;
+use bonsaidb_core::transaction::{Command, Transaction};
+
+use crate::config::{shard_database_name, Sharding};
+use crate::database::{Data, Database, DatabaseNonBlocking};
+use crate::views::ViewEntry;
+use crate::{Error, Storage};
+
+impl Database {
+    /// Returns the sharding configuration of `collection`, if its documents
+    /// are split across shards.
+    pub(crate) fn sharding(&self, collection: &CollectionName) -> Option<&Sharding> {
+        self.storage.instance.sharding(self.name(), collection)
+    }
+
+    /// Opens the shard at `index` of `sharding`.
+    ///
+    /// Permissions are checked against this database before an operation is
+    /// routed to a shard, so the shard is opened without a session.
+    fn open_shard(&self, sharding: &Sharding, index: usize) -> Result<Self, Error> {
+        let name = shard_database_name(self.name(), &sharding.shards[index].name);
+        let storage = Storage::from(self.storage.instance.clone());
+        let context = storage.instance.open_roots(&name, &self.data.schema)?;
+        Ok(Self {
+            storage,
+            data: Arc::new(Data {
+                name: Arc::new(Cow::Owned(name)),
+                context,
+                schema: self.data.schema.clone(),
+            }),
+        })
+    }
+
+    /// Returns the shard that stores the document with `id`.
+    pub(crate) fn shard_for(&self, sharding: &Sharding, id: &DocumentId) -> Result<Self, Error> {
+        self.open_shard(sharding, sharding.shard_for(id))
+    }
+
+    /// Returns every shard of `sharding`.
+    pub(crate) fn shards(&self, sharding: &Sharding) -> Result<Vec<Self>, Error> {
+        (0..sharding.shards.len())
+            .map(|index| self.open_shard(sharding, index))
+            .collect()
+    }
+
+    /// Returns the shard that `transaction` modifies, or None if it doesn't
+    /// modify any sharded collections. Inserts into sharded collections that
+    /// don't specify an id are assigned one before routing.
+    ///
+    /// Returns an error if `transaction` modifies more than one shard, or a
+    /// shard and this database.
+    pub(crate) fn route_transaction(
+        &self,
+        transaction: &mut Transaction,
+    ) -> Result<Option<Self>, bonsaidb_core::Error> {
+        let mut target = None::<(String, &Sharding, DocumentId)>;
+        let mut modifies_database = false;
+        let mut assigned_ids = HashMap::<CollectionName, DocumentId>::new();
+        for op in &mut transaction.operations {
+            let Some(sharding) = self.sharding(&op.collection) else {
+                modifies_database = true;
+                continue;
+            };
+            let id = match &mut op.command {
+                Command::Insert { id, .. } => {
+                    if id.is_none() {
+                        let next_id = self.next_sharded_id(
+                            &op.collection,
+                            sharding,
+                            assigned_ids.get(&op.collection).cloned(),
+                        )?;
+                        assigned_ids.insert(op.collection.clone(), next_id.clone());
+                        *id = Some(next_id);
+                    }
+                    id.clone().expect("id assigned above")
+                }
+                Command::Update { header, .. } | Command::Delete { header } => header.id.clone(),
+                Command::Overwrite { id, .. } | Command::Check { id, .. } => id.clone(),
+            };
+            let shard_name =
+                shard_database_name(self.name(), &sharding.shards[sharding.shard_for(&id)].name);
+            match &target {
+                Some((target_name, ..)) if target_name != &shard_name => {
+                    return Err(spans_multiple_shards())
+                }
+                Some(_) => {}
+                None => target = Some((shard_name, sharding, id)),
+            }
+        }
+
+        match target {
+            Some(_) if modifies_database => Err(spans_multiple_shards()),
+            Some((_, sharding, id)) => Ok(Some(self.shard_for(sharding, &id)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the next id for a document inserted into the sharded
+    /// `collection`. The next id follows the largest id stored in any shard,
+    /// or `previous` if an id was already assigned in the same transaction.
+    fn next_sharded_id(
+        &self,
+        collection: &CollectionName,
+        sharding: &Sharding,
+        previous: Option<DocumentId>,
+    ) -> Result<DocumentId, bonsaidb_core::Error> {
+        let mut last_id = previous;
+        for shard in self.shards(sharding)? {
+            if let Some(header) = shard
+                .list_headers_from_collection(
+                    Range {
+                        start: connection::Bound::Unbounded,
+                        end: connection::Bound::Unbounded,
+                    },
+                    Sort::Descending,
+                    Some(1),
+                    collection,
+                )?
+                .pop()
+            {
+                if last_id
+                    .as_ref()
+                    .map_or(true, |last_id| &header.id > last_id)
+                {
+                    last_id = Some(header.id);
+                }
+            }
+        }
+
+        self.data.schema.next_id_for_collection(collection, last_id)
+    }
+
+    /// Queries `view` in every shard of `sharding`, invoking `callback` with
+    /// the merged entries in key order.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn for_each_in_sharded_view<
+        F: FnMut(ViewEntry) -> Result<(), bonsaidb_core::Error> + Send + Sync,
+    >(
+        &self,
+        sharding: &Sharding,
+        view: &dyn view::Serialized,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+        callback: F,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let mut entries = BTreeMap::<Vec<u8>, ViewEntry>::new();
+        for shard in self.shards(sharding)? {
+            shard.for_each_in_view(view, key.clone(), order, limit, access_policy, |entry| {
+                match entries.entry(entry.key.to_vec()) {
+                    Entry::Vacant(vacant) => {
+                        vacant.insert(entry);
+                    }
+                    Entry::Occupied(mut occupied) => {
+                        let existing = occupied.get_mut();
+                        match view.reduce(
+                            &[
+                                (&existing.key[..], &existing.reduced_value[..]),
+                                (&entry.key[..], &entry.reduced_value[..]),
+                            ],
+                            true,
+                        ) {
+                            Ok(reduced) => existing.reduced_value = Bytes::from(reduced),
+                            Err(view::Error::Core(bonsaidb_core::Error::ReduceUnimplemented)) => {}
+                            Err(other) => {
+                                return Err(bonsaidb_core::Error::from(Error::from(other)))
+                            }
+                        }
+                        existing.mappings.extend(entry.mappings);
+                    }
+                }
+                Ok(())
+            })?;
+        }
+
+        let limit = limit.map_or(usize::MAX, |limit| {
+            usize::try_from(limit).unwrap_or(usize::MAX)
+        });
+        let entries = entries.into_values();
+        if matches!(order, Sort::Ascending) {
+            entries.take(limit).try_for_each(callback)
+        } else {
+            entries.rev().take(limit).try_for_each(callback)
+        }
+    }
+}
+
+fn spans_multiple_shards() -> bonsaidb_core::Error {
+    bonsaidb_core::Error::other(
+        "bonsaidb-local sharding",
+        "a transaction can only modify a single shard",
+    )
+}
+
+/// Sorts `items` gathered from multiple shards by their ids and truncates them
+/// to `limit`.
+pub(crate) fn merge_shard_results<T>(
+    items: &mut Vec<T>,
+    sort: Sort,
+    limit: Option<u32>,
+    id: impl Fn(&T) -> &DocumentId,
+) {
+    items.sort_by(|a, b| id(a).cmp(id(b)));
+    if matches!(sort, Sort::Descending) {
+        items.reverse();
+    }
+    if let Some(limit) = limit {
+        items.truncate(usize::try_from(limit).unwrap_or(usize::MAX));
+    }
+}
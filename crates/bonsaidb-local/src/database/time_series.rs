@@ -0,0 +1,220 @@
+use std::convert::Infallible;
+
+use bonsaidb_core::arc_bytes::ArcBytes;
+use bonsaidb_core::document::DocumentId;
+use bonsaidb_core::key::{ByteCow, Key};
+use bonsaidb_core::keyvalue::Timestamp;
+use bonsaidb_core::schema::CollectionName;
+use bonsaidb_core::transaction::{ChangedDocument, Changes, DocumentChanges};
+use itertools::Itertools;
+use nebari::io::any::AnyFile;
+use nebari::tree::{
+    AnyTreeRoot, CompareSwap, KeyOperation, Operation, ScanEvaluation, Unversioned,
+};
+
+use crate::database::deduplication::{self, document_contents_tree_name};
+use crate::database::{
+    compat, deserialize_document, document_tree_name, with_document_root, Database,
+};
+use crate::open_trees::OpenTrees;
+use crate::tasks::{Job, Keyed, Task};
+use crate::views::materialize::PendingMaterializations;
+use crate::Error;
+
+impl Database {
+    /// Queues removal of the expired partitions of `collection` if it is a
+    /// time-series collection with a retention period.
+    pub(crate) fn enforce_time_series_retention(&self, collection: &CollectionName) {
+        if let Some(time_series) = self.data.schema.time_series_for_collection(collection) {
+            if let Some(cutoff) = time_series.retention_cutoff(Timestamp::now()) {
+                self.storage.instance.tasks().spawn_retention_enforcer(
+                    self,
+                    collection.clone(),
+                    cutoff,
+                );
+            }
+        }
+    }
+
+    /// Removes every document in the time-series `collection` whose id is
+    /// before `cutoff`. Each partition is removed in its own transaction,
+    /// without reading the documents being removed unless the collection is
+    /// deduplicated. Returns the number of documents removed.
+    pub(crate) fn remove_expired_partitions(
+        &self,
+        collection: &CollectionName,
+        cutoff: Timestamp,
+    ) -> Result<u64, Error> {
+        let time_series = match self.data.schema.time_series_for_collection(collection) {
+            Some(time_series) => *time_series,
+            None => return Ok(0),
+        };
+        let cutoff = DocumentId::new(&cutoff)?;
+        let mut expired = Vec::new();
+        with_document_root!(self.data.schema, collection, |R| {
+            let documents = self
+                .roots()
+                .tree(self.collection_tree::<R, _>(collection, document_tree_name(collection))?)?;
+            documents.scan::<Infallible, _, _, _, _>(
+                &(..cutoff.as_ref()),
+                true,
+                |_, _, _| ScanEvaluation::ReadData,
+                |key, _| {
+                    expired.push(key.clone());
+                    ScanEvaluation::Skip
+                },
+                |_, _, _| unreachable!("document contents are never read"),
+            )?;
+        });
+
+        let mut removed = 0;
+        for (_, partition) in &expired.into_iter().group_by(|id| {
+            Timestamp::from_ord_bytes(ByteCow::Borrowed(id.as_slice()))
+                .ok()
+                .map(|timestamp| time_series.partition_of(timestamp))
+        }) {
+            let document_ids = partition.collect::<Vec<_>>();
+            removed += document_ids.len() as u64;
+            self.remove_partition(collection, document_ids)?;
+        }
+
+        Ok(removed)
+    }
+
+    fn remove_partition(
+        &self,
+        collection: &CollectionName,
+        document_ids: Vec<ArcBytes<'static>>,
+    ) -> Result<(), Error> {
+        #[cfg(any(feature = "encryption", feature = "compression"))]
+        let vault = self
+            .storage()
+            .instance
+            .tree_vault_for_key(self.collection_encryption_key(collection))?;
+        let mut open_trees = OpenTrees::default();
+        open_trees.open_trees_for_document_change(
+            collection,
+            &self.data.schema,
+            #[cfg(any(feature = "encryption", feature = "compression"))]
+            vault,
+        );
+
+        let mut transaction = self
+            .data
+            .context
+            .roots
+            .transaction::<_, dyn AnyTreeRoot<AnyFile>>(&open_trees.trees)?;
+        let mut removed_documents = Vec::new();
+        let deduplicated = self.data.schema.collection_is_deduplicated(collection);
+        with_document_root!(self.data.schema, collection, |R| {
+            let mut documents = transaction
+                .tree::<R>(open_trees.trees_index_by_name[&document_tree_name(collection)])
+                .unwrap();
+            if deduplicated {
+                documents.modify(
+                    document_ids.clone(),
+                    Operation::CompareSwap(CompareSwap::new(&mut |_, document| {
+                        removed_documents.extend(document);
+                        KeyOperation::Remove
+                    })),
+                )?;
+            } else {
+                documents.modify(document_ids.clone(), Operation::Remove)?;
+            }
+        });
+        if deduplicated {
+            let digests = removed_documents
+                .iter()
+                .map(|document| {
+                    deserialize_document(document).map(|document| document.header.revision.sha256)
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            deduplication::remove_references(
+                &mut transaction
+                    .tree::<Unversioned>(
+                        open_trees.trees_index_by_name[&document_contents_tree_name(collection)],
+                    )
+                    .unwrap(),
+                digests,
+            )?;
+        }
+
+        let mut materializations = PendingMaterializations::default();
+        let changed_documents = document_ids
+            .iter()
+            .map(|id| {
+                Ok(ChangedDocument {
+                    collection: 0,
+                    id: DocumentId::try_from(id.as_slice())?,
+                    deleted: true,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        self.update_eager_views_for_documents(
+            collection,
+            document_ids,
+            &mut transaction,
+            &open_trees.trees_index_by_name,
+            &mut materializations,
+        )?;
+        let collections = vec![collection.clone()];
+        self.invalidate_changed_documents(
+            &mut transaction,
+            &open_trees,
+            &collections,
+            &changed_documents,
+        )?;
+
+        let changes = DocumentChanges {
+            collections,
+            documents: changed_documents,
+        };
+        transaction
+            .entry_mut()
+            .set_data(compat::serialize_executed_transaction_changes(
+                &Changes::Documents(changes.clone()),
+            )?)?;
+
+        transaction.commit()?;
+        materializations.apply(self)?;
+
+        self.data.context.notify_document_watchers(changes);
+
+        Ok(())
+    }
+}
+
+/// Removes the expired partitions of a time-series collection.
+#[derive(Debug)]
+pub struct RetentionEnforcer {
+    pub database: Database,
+    pub collection: CollectionName,
+    pub cutoff: Timestamp,
+}
+
+impl Keyed<Task> for RetentionEnforcer {
+    fn key(&self) -> Task {
+        Task::RetentionEnforcer(self.database.data.name.clone(), self.collection.clone())
+    }
+}
+
+impl Job for RetentionEnforcer {
+    type Error = Error;
+    type Output = u64;
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    fn execute(&mut self) -> Result<Self::Output, Self::Error> {
+        let removed = self
+            .database
+            .remove_expired_partitions(&self.collection, self.cutoff)?;
+        if removed > 0 {
+            // Reclaim the space used by the removed documents.
+            self.database
+                .storage()
+                .instance
+                .tasks()
+                .compact_documents(self.database.clone(), self.collection.clone());
+        }
+        Ok(removed)
+    }
+}
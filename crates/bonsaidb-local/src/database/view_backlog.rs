@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::time::Duration;
+
+use bonsaidb_core::schema::ViewName;
+use nebari::io::any::AnyFile;
+use nebari::tree::{ScanEvaluation, Unversioned};
+use nebari::Tree;
+use parking_lot::Mutex;
+
+use crate::database::Database;
+use crate::views::{view_invalidated_docs_tree_name, view_rebuild_tree_name};
+use crate::Error;
+
+/// The documents waiting to be mapped into a view.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ViewBacklog {
+    /// The number of documents changed since they were last mapped into the
+    /// view.
+    pub pending_documents: u64,
+    /// An estimate of how long mapping the pending documents will take, based
+    /// on how quickly documents have been mapped into the view since the
+    /// database was opened. `None` if no documents have been mapped into the
+    /// view since the database was opened.
+    pub estimated_catch_up: Option<Duration>,
+}
+
+/// The number of documents mapped into each view, and the time spent mapping
+/// them, since the database was opened.
+#[derive(Debug, Default)]
+pub(crate) struct MappingRates {
+    views: Mutex<HashMap<ViewName, (u64, Duration)>>,
+}
+
+impl MappingRates {
+    pub fn record(&self, view: &ViewName, documents: u64, elapsed: Duration) {
+        let mut views = self.views.lock();
+        if let Some((mapped, spent)) = views.get_mut(view) {
+            *mapped = mapped.saturating_add(documents);
+            *spent += elapsed;
+        } else {
+            views.insert(view.clone(), (documents, elapsed));
+        }
+    }
+
+    /// Estimates how long mapping `pending` documents into `view` will take.
+    pub fn estimate(&self, view: &ViewName, pending: u64) -> Option<Duration> {
+        let views = self.views.lock();
+        let (mapped, spent) = views.get(view)?;
+        if *mapped == 0 {
+            return None;
+        }
+        let nanos = spent.as_nanos() * u128::from(pending) / u128::from(*mapped);
+        Some(Duration::from_nanos(
+            u64::try_from(nanos).unwrap_or(u64::MAX),
+        ))
+    }
+}
+
+pub(crate) fn view_backlogs(database: &Database) -> Result<HashMap<ViewName, ViewBacklog>, Error> {
+    let tasks = database.storage.instance.tasks();
+    let mut backlogs = HashMap::new();
+    for view in database.data.schema.views() {
+        let collection = view.collection();
+        let view_name = view.view_name();
+        let open_tree = |name: String| {
+            database
+                .collection_tree::<Unversioned, _>(&collection, name)
+                .and_then(|root| Ok(database.roots().tree(root)?))
+        };
+        let invalidated_tree_name = view_invalidated_docs_tree_name(&view_name);
+        let mut pending_documents = count_keys(&open_tree(invalidated_tree_name.clone())?)?;
+        if tasks.view_rebuilding_side_by_side(
+            database.data.name.clone(),
+            collection.clone(),
+            view_name.clone(),
+        ) {
+            pending_documents +=
+                count_keys(&open_tree(view_rebuild_tree_name(&invalidated_tree_name))?)?;
+        }
+
+        let estimated_catch_up = database
+            .mapping_rates()
+            .estimate(&view_name, pending_documents);
+        backlogs.insert(
+            view_name,
+            ViewBacklog {
+                pending_documents,
+                estimated_catch_up,
+            },
+        );
+    }
+
+    Ok(backlogs)
+}
+
+fn count_keys(tree: &Tree<Unversioned, AnyFile>) -> Result<u64, Error> {
+    let mut count = 0;
+    tree.scan::<Infallible, _, _, _, _>(
+        &(..),
+        true,
+        |_, _, _| ScanEvaluation::ReadData,
+        |_, _| {
+            count += 1;
+            ScanEvaluation::Skip
+        },
+        |_, _, _| unreachable!("keys are never read"),
+    )?;
+    Ok(count)
+}
@@ -107,6 +107,16 @@ impl From<Disconnected> for Error {
     }
 }
 
+#[cfg(feature = "sql")]
+impl From<bonsaidb_core::sql::SqlError> for Error {
+    fn from(err: bonsaidb_core::sql::SqlError) -> Self {
+        match err {
+            bonsaidb_core::sql::SqlError::Core(err) => Self::Core(err),
+            other => Self::other("sql", other),
+        }
+    }
+}
+
 impl From<bincode::Error> for Error {
     fn from(err: bincode::Error) -> Self {
         Self::other("bincode", err)
@@ -151,6 +161,13 @@ impl From<Error> for bonsaidb_core::Error {
     fn from(err: Error) -> Self {
         match err {
             Error::View(view::Error::Core(core)) | Error::Core(core) => core,
+            Error::Job(job) => match Arc::try_unwrap(job) {
+                Ok(err) => Self::from(err),
+                Err(job) => match job.as_ref() {
+                    Error::View(view::Error::Core(core)) | Error::Core(core) => core.clone(),
+                    other => Self::other("bonsaidb-local", other),
+                },
+            },
             other => Self::other("bonsaidb-local", other),
         }
     }
@@ -218,3 +235,22 @@ fn test_converting_error() {
         _ => unreachable!(),
     }
 }
+
+#[test]
+fn test_converting_job_error() {
+    use bonsaidb_core::document::{DocumentId, Header, Revision};
+    use bonsaidb_core::schema::{CollectionName, Qualified};
+
+    let conflict = bonsaidb_core::Error::DocumentConflict(
+        CollectionName::private("collection"),
+        Box::new(Header {
+            id: DocumentId::from_u64(1),
+            revision: Revision::new(b"contents"),
+        }),
+    );
+    let job = Arc::new(Error::Core(conflict));
+    let _still_referenced = job.clone();
+    let err: bonsaidb_core::Error = Error::Job(job).into();
+    assert!(matches!(err, bonsaidb_core::Error::DocumentConflict(..)));
+    assert_eq!(err.code(), bonsaidb_core::ErrorCode::DocumentConflict);
+}
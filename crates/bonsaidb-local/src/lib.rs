@@ -25,6 +25,7 @@ mod error;
 mod open_trees;
 mod storage;
 mod tasks;
+mod usage;
 #[cfg(feature = "encryption")]
 pub mod vault;
 mod views;
@@ -33,11 +34,26 @@ mod views;
 pub use argon2;
 #[cfg(not(feature = "included-from-omnibus"))]
 pub use bonsaidb_core as core;
+pub use nebari;
+#[cfg(feature = "parquet")]
+pub use {arrow, parquet};
 
+pub use self::database::integrity::{CollectionIntegrity, IntegrityReport, ViewIntegrity};
+pub use self::database::lock::{KeyValueLock, LOCK_NAMESPACE};
+#[cfg(feature = "parquet")]
+pub use self::database::parquet::ParquetMapping;
 pub use self::database::pubsub::Subscriber;
+pub use self::database::schema_status::{SchemaStatus, ViewStatus};
+pub use self::database::view_backlog::ViewBacklog;
 pub use self::database::{Database, DatabaseNonBlocking};
 pub use self::error::Error;
-pub use self::storage::{BackupLocation, Storage, StorageId, StorageNonBlocking};
+pub use self::storage::{
+    BackupIssue, BackupLocation, BackupOptions, BackupProblem, BackupVerificationReport,
+    CorruptedTree, CrossDatabaseTransaction, DatabaseMapping, RecoveryReport, RestoreOptions,
+    ScrubReport, Storage, StorageId, StorageNonBlocking, Tenants,
+};
+pub use self::tasks::BackgroundTasks;
+pub use self::usage::{Usage, UsageReport};
 
 #[cfg(feature = "async")]
 mod r#async;
@@ -2,9 +2,10 @@ use std::collections::HashMap;
 
 use bonsaidb_core::schema::{CollectionName, Schematic};
 use nebari::io::any::AnyFile;
-use nebari::tree::{AnyTreeRoot, Root, Unversioned, Versioned};
+use nebari::tree::{AnyTreeRoot, Root, Unversioned};
 
-use crate::database::document_tree_name;
+use crate::database::deduplication::document_contents_tree_name;
+use crate::database::{document_tree_name, with_document_root};
 #[cfg(any(feature = "encryption", feature = "compression"))]
 use crate::storage::TreeVault;
 use crate::views::{
@@ -46,11 +47,19 @@ impl OpenTrees {
         schema: &Schematic,
         #[cfg(any(feature = "encryption", feature = "compression"))] vault: Option<TreeVault>,
     ) {
-        self.open_tree::<Versioned>(
+        with_document_root!(schema, collection, |R| self.open_tree::<R>(
             &document_tree_name(collection),
             #[cfg(any(feature = "encryption", feature = "compression"))]
             vault.clone(),
-        );
+        ));
+
+        if schema.collection_is_deduplicated(collection) {
+            self.open_tree::<Unversioned>(
+                &document_contents_tree_name(collection),
+                #[cfg(any(feature = "encryption", feature = "compression"))]
+                vault.clone(),
+            );
+        }
 
         if let Some(views) = schema.views_in_collection(collection) {
             for view in views {
@@ -1,11 +1,14 @@
 use std::borrow::Cow;
-use std::collections::{HashMap, HashSet};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::{Debug, Display};
 use std::fs::{self, File};
 use std::io::{Read, Write};
 use std::marker::PhantomData;
+use std::ops::Bound;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
 
 use bonsaidb_core::admin::database::{self, ByName, Database as DatabaseRecord};
 use bonsaidb_core::admin::user::User;
@@ -13,18 +16,24 @@ use bonsaidb_core::admin::{self, Admin, PermissionGroup, Role, ADMIN_DATABASE_NA
 use bonsaidb_core::circulate;
 pub use bonsaidb_core::circulate::Relay;
 use bonsaidb_core::connection::{
-    self, Connection, HasSession, Identity, IdentityReference, LowLevelConnection, Session,
-    SessionAuthentication, SessionId, StorageConnection,
+    self, Connection, DatabaseQuery, HasSession, Identity, IdentityReference, LowLevelConnection,
+    Session, SessionAuthentication, SessionId, SessionInfo, StorageConnection,
 };
 use bonsaidb_core::document::CollectionDocument;
+#[cfg(feature = "encryption")]
+use bonsaidb_core::document::FieldEncryption;
 #[cfg(any(feature = "encryption", feature = "compression"))]
 use bonsaidb_core::document::KeyId;
+use bonsaidb_core::keyvalue::Timestamp;
 use bonsaidb_core::permissions::bonsai::{
-    bonsaidb_resource_name, database_resource_name, role_resource_name, user_resource_name,
-    BonsaiAction, ServerAction,
+    bonsaidb_resource_name, database_resource_name, permission_group_resource_name,
+    role_resource_name, user_resource_name, validate_statements, BonsaiAction, ServerAction,
+};
+use bonsaidb_core::permissions::{Permissions, Statement};
+use bonsaidb_core::schema::{
+    CollectionName, Nameable, NamedCollection, Schema, SchemaName, Schematic, SerializedCollection,
+    ViewName,
 };
-use bonsaidb_core::permissions::Permissions;
-use bonsaidb_core::schema::{Nameable, NamedCollection, Schema, SchemaName, Schematic};
 use fs2::FileExt;
 use itertools::Itertools;
 use nebari::io::any::{AnyFile, AnyFileManager};
@@ -35,10 +44,16 @@ use rand::{thread_rng, Rng};
 
 #[cfg(feature = "compression")]
 use crate::config::Compression;
-use crate::config::{KeyValuePersistence, StorageConfiguration};
-use crate::database::Context;
+#[cfg(feature = "password-hashing")]
+use crate::config::PasswordPolicy;
+use crate::config::{
+    shard_database_name, KeyValuePersistence, Sessions, Sharding, StorageConfiguration,
+};
+use crate::database::keyvalue::KeyTree;
+use crate::database::{Context, Data as DatabaseData};
 use crate::tasks::manager::Manager;
-use crate::tasks::TaskManager;
+use crate::tasks::{BackgroundTasks, TaskManager};
+use crate::usage::UsageTracker;
 #[cfg(feature = "encryption")]
 use crate::vault::{self, LocalVaultKeyStorage, Vault};
 use crate::{Database, Error};
@@ -49,8 +64,19 @@ mod argon;
 mod token_authentication;
 
 mod backup;
+mod backup_verification;
+mod cross_database;
 mod pubsub;
-pub use backup::{AnyBackupLocation, BackupLocation};
+mod quota;
+mod recover;
+mod scrub;
+mod tenants;
+pub use backup::{AnyBackupLocation, BackupLocation, BackupOptions, RestoreOptions};
+pub use backup_verification::{BackupIssue, BackupProblem, BackupVerificationReport};
+pub use cross_database::{CrossDatabaseTransaction, DatabaseMapping};
+pub use recover::RecoveryReport;
+pub use scrub::{CorruptedTree, ScrubReport};
+pub use tenants::Tenants;
 
 /// A file-based, multi-database, multi-user database engine. This type blocks
 /// the current thread when used. See [`AsyncStorage`](crate::AsyncStorage) for
@@ -150,6 +176,7 @@ pub struct Storage {
     pub(crate) instance: StorageInstance,
     pub(crate) authentication: Option<Arc<AuthenticatedSession>>,
     effective_session: Option<Arc<Session>>,
+    deadline: Option<Instant>,
 }
 
 #[derive(Debug)]
@@ -157,6 +184,42 @@ pub struct AuthenticatedSession {
     // TODO: client_data,
     storage: Weak<Data>,
     pub session: Mutex<Session>,
+    created_at: Timestamp,
+    last_used_at: Mutex<Timestamp>,
+}
+
+impl AuthenticatedSession {
+    fn new(storage: &Arc<Data>, session: Session) -> Arc<Self> {
+        let now = Timestamp::now();
+        Arc::new(Self {
+            storage: Arc::downgrade(storage),
+            session: Mutex::new(session),
+            created_at: now,
+            last_used_at: Mutex::new(now),
+        })
+    }
+
+    fn is_expired(&self, timeouts: &Sessions, now: Timestamp) -> bool {
+        timeouts
+            .absolute_timeout
+            .map_or(false, |timeout| self.created_at + timeout <= now)
+            || timeouts
+                .idle_timeout
+                .map_or(false, |timeout| *self.last_used_at.lock() + timeout <= now)
+    }
+
+    fn info(&self) -> Option<SessionInfo> {
+        let session = self.session.lock();
+        match (session.id, &session.authentication) {
+            (Some(id), SessionAuthentication::Identity(identity)) => Some(SessionInfo {
+                id,
+                identity: identity.clone(),
+                created_at: self.created_at,
+                last_used_at: *self.last_used_at.lock(),
+            }),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Default)]
@@ -192,19 +255,7 @@ impl Drop for AuthenticatedSession {
         if let Some(id) = session.id.take() {
             if let Some(storage) = self.storage.upgrade() {
                 // Deregister the session id once dropped.
-                let mut sessions = storage.sessions.write();
-                sessions.sessions.remove(&id);
-
-                // Remove all subscribers.
-                let mut sessions = storage.subscribers.write();
-                for id in sessions
-                    .subscribers_by_session
-                    .remove(&id)
-                    .into_iter()
-                    .flatten()
-                {
-                    sessions.subscribers.remove(&id);
-                }
+                drop(storage.remove_session(id));
             }
         }
     }
@@ -227,6 +278,7 @@ impl From<StorageInstance> for Storage {
             instance,
             authentication: None,
             effective_session: None,
+            deadline: None,
         }
     }
 }
@@ -240,14 +292,18 @@ struct Data {
     file_manager: AnyFileManager,
     pub(crate) tasks: TaskManager,
     schemas: RwLock<HashMap<SchemaName, Arc<dyn DatabaseOpener>>>,
-    available_databases: RwLock<HashMap<String, SchemaName>>,
+    available_databases: RwLock<BTreeMap<String, SchemaName>>,
+    database_quotas: RwLock<HashMap<String, u64>>,
     open_roots: Mutex<HashMap<String, Context>>,
+    open_databases: Mutex<HashMap<String, Arc<DatabaseData>>>,
     // cfg check matches `Connection::authenticate`
     authenticated_permissions: Permissions,
     sessions: RwLock<AuthenticatedSessions>,
     pub(crate) subscribers: Arc<RwLock<SessionSubscribers>>,
     #[cfg(feature = "password-hashing")]
     argon: argon::Hasher,
+    #[cfg(feature = "password-hashing")]
+    password_policy: PasswordPolicy,
     #[cfg(feature = "encryption")]
     pub(crate) vault: Arc<Vault>,
     #[cfg(feature = "encryption")]
@@ -255,9 +311,104 @@ struct Data {
     #[cfg(any(feature = "compression", feature = "encryption"))]
     tree_vault: Option<TreeVault>,
     pub(crate) key_value_persistence: KeyValuePersistence,
+    database_key_value_persistence: HashMap<String, KeyValuePersistence>,
+    concurrent_query_limit: Option<usize>,
+    database_concurrent_query_limit: HashMap<String, usize>,
+    usage: UsageTracker,
     chunk_cache: ChunkCache,
     pub(crate) check_view_integrity_on_database_open: bool,
+    cached_view_queries: HashSet<ViewName>,
+    warm_views_on_open: Option<usize>,
+    stale_views_while_rebuilding: HashSet<String>,
+    redact_unreadable_view_entries: bool,
+    last_scrub_report: Mutex<Option<ScrubReport>>,
+    session_timeouts: Sessions,
     relay: Relay,
+    #[cfg(feature = "sql")]
+    sql: bonsaidb_core::sql::SqlCatalog,
+    sharding: HashMap<String, HashMap<CollectionName, Sharding>>,
+    shard_paths: HashMap<String, PathBuf>,
+}
+
+impl Data {
+    /// Returns the authenticated session `session_id` and records that it was
+    /// used. If the session has expired, it is removed and
+    /// [`SessionExpired`](bonsaidb_core::Error::SessionExpired) is returned.
+    fn active_session(
+        &self,
+        session_id: SessionId,
+    ) -> Result<Arc<AuthenticatedSession>, bonsaidb_core::Error> {
+        let authentication = self
+            .sessions
+            .read()
+            .sessions
+            .get(&session_id)
+            .cloned()
+            .ok_or(bonsaidb_core::Error::SessionExpired)?;
+
+        let now = Timestamp::now();
+        if authentication.is_expired(&self.session_timeouts, now) {
+            drop(self.remove_session(session_id));
+            return Err(bonsaidb_core::Error::SessionExpired);
+        }
+        *authentication.last_used_at.lock() = now;
+
+        Ok(authentication)
+    }
+
+    /// Removes the session from the list of active sessions and removes all of
+    /// its subscribers. The removed session is returned so that it can be
+    /// dropped after the locks have been released.
+    fn remove_session(&self, session_id: SessionId) -> Option<Arc<AuthenticatedSession>> {
+        let removed = self.sessions.write().sessions.remove(&session_id);
+
+        let mut subscribers = self.subscribers.write();
+        for id in subscribers
+            .subscribers_by_session
+            .remove(&session_id)
+            .into_iter()
+            .flatten()
+        {
+            subscribers.subscribers.remove(&id);
+        }
+
+        removed
+    }
+
+    fn remove_user_sessions(&self, user_id: u64) {
+        let sessions = {
+            let sessions = self.sessions.read();
+            sessions
+                .sessions
+                .iter()
+                .filter(|(_, session)| {
+                    matches!(
+                        session.session.lock().identity(),
+                        Some(Identity::User { id, .. }) if *id == user_id
+                    )
+                })
+                .map(|(id, _)| *id)
+                .collect::<Vec<_>>()
+        };
+        for session_id in sessions {
+            drop(self.remove_session(session_id));
+        }
+    }
+
+    fn remove_expired_sessions(&self, now: Timestamp) {
+        let expired = {
+            let sessions = self.sessions.read();
+            sessions
+                .sessions
+                .iter()
+                .filter(|(_, session)| session.is_expired(&self.session_timeouts, now))
+                .map(|(id, _)| *id)
+                .collect::<Vec<_>>()
+        };
+        for session_id in expired {
+            drop(self.remove_session(session_id));
+        }
+    }
 }
 
 impl Storage {
@@ -302,9 +453,19 @@ impl Storage {
 
         let parallelization = configuration.workers.parallelization;
         let check_view_integrity_on_database_open = configuration.views.check_integrity_on_open;
+        let cached_view_queries = configuration.views.cached_queries;
+        let warm_views_on_open = configuration.views.warm_on_open;
+        let stale_views_while_rebuilding = configuration.views.stale_while_rebuilding;
+        let redact_unreadable_view_entries = configuration.views.redact_unreadable_entries;
+        let scrub_interval = configuration.scrub_interval;
         let key_value_persistence = configuration.key_value_persistence;
+        let database_key_value_persistence = configuration.database_key_value_persistence;
+        let concurrent_query_limit = configuration.concurrent_query_limit;
+        let database_concurrent_query_limit = configuration.database_concurrent_query_limit;
         #[cfg(feature = "password-hashing")]
         let argon = argon::Hasher::new(configuration.argon);
+        #[cfg(feature = "password-hashing")]
+        let password_policy = configuration.password_policy;
         #[cfg(feature = "encryption")]
         let default_encryption_key = configuration.default_encryption_key;
         #[cfg(all(feature = "compression", feature = "encryption"))]
@@ -319,6 +480,28 @@ impl Storage {
         let tree_vault = TreeVault::new_if_needed(configuration.default_compression);
 
         let authenticated_permissions = configuration.authenticated_permissions;
+        let session_timeouts = configuration.sessions;
+        #[cfg(feature = "sql")]
+        let sql = configuration.sql;
+        let sharding = configuration.sharding;
+        let mut shard_paths = HashMap::new();
+        for (database, collections) in &sharding {
+            for (collection, config) in collections {
+                config.validate().map_err(|err| {
+                    Error::Core(bonsaidb_core::Error::other(
+                        "bonsaidb-local sharding",
+                        format!("invalid sharding of {collection} in {database}: {err}"),
+                    ))
+                })?;
+                for shard in &config.shards {
+                    Self::validate_name(&shard.name)?;
+                    if let Some(path) = &shard.path {
+                        shard_paths
+                            .insert(shard_database_name(database, &shard.name), path.clone());
+                    }
+                }
+            }
+        }
 
         let storage = Self {
             instance: StorageInstance {
@@ -331,6 +514,8 @@ impl Storage {
                     sessions: RwLock::default(),
                     #[cfg(feature = "password-hashing")]
                     argon,
+                    #[cfg(feature = "password-hashing")]
+                    password_policy,
                     #[cfg(feature = "encryption")]
                     vault,
                     #[cfg(feature = "encryption")]
@@ -343,20 +528,41 @@ impl Storage {
                     threadpool: ThreadPool::new(parallelization),
                     schemas: RwLock::new(configuration.initial_schemas),
                     available_databases: RwLock::default(),
+                    database_quotas: RwLock::default(),
                     open_roots: Mutex::default(),
+                    open_databases: Mutex::default(),
                     key_value_persistence,
+                    database_key_value_persistence,
+                    concurrent_query_limit,
+                    database_concurrent_query_limit,
+                    usage: UsageTracker::default(),
                     check_view_integrity_on_database_open,
+                    cached_view_queries,
+                    warm_views_on_open,
+                    stale_views_while_rebuilding,
+                    redact_unreadable_view_entries,
+                    last_scrub_report: Mutex::default(),
+                    session_timeouts,
                     relay: Relay::default(),
+                    #[cfg(feature = "sql")]
+                    sql,
+                    sharding,
+                    shard_paths,
                 }),
             },
             authentication: None,
             effective_session: None,
+            deadline: None,
         };
 
         storage.cache_available_databases()?;
 
         storage.create_admin_database_if_needed()?;
 
+        if let Some(interval) = scrub_interval {
+            scrub::spawn_scrubber(&storage.instance.data, interval);
+        }
+
         Ok(storage)
     }
 
@@ -435,6 +641,19 @@ impl Storage {
             .collect();
         let mut storage_databases = self.instance.data.available_databases.write();
         *storage_databases = available_databases;
+        drop(storage_databases);
+
+        let database_quotas = DatabaseRecord::all(&self.admin())
+            .query()?
+            .into_iter()
+            .filter_map(|record| {
+                record
+                    .contents
+                    .quota
+                    .map(|quota| (record.contents.name, quota))
+            })
+            .collect();
+        *self.instance.data.database_quotas.write() = database_quotas;
         Ok(())
     }
 
@@ -463,6 +682,14 @@ impl Storage {
         self.instance.data.lock.id()
     }
 
+    /// Returns the views that can be queried using
+    /// [`Database::query_sql()`](crate::Database::query_sql).
+    #[must_use]
+    #[cfg(feature = "sql")]
+    pub fn sql_catalog(&self) -> &bonsaidb_core::sql::SqlCatalog {
+        &self.instance.data.sql
+    }
+
     #[must_use]
     pub(crate) fn parallelization(&self) -> usize {
         self.instance.data.parallelization
@@ -494,20 +721,25 @@ impl Storage {
     }
 
     /// Registers a schema for use within the server.
+    ///
+    /// Schemas can be registered at any time, including while the storage is
+    /// in use. Once registered, databases using the schema can be created, and
+    /// existing databases using the schema can be opened. If the schema is
+    /// already registered, the existing registration is kept and
+    /// [`bonsaidb_core::Error::SchemaAlreadyRegistered`] is returned.
     pub fn register_schema<DB: Schema>(&self) -> Result<(), Error> {
+        // Building the schematic can be expensive, so it is done before
+        // acquiring the lock that opening any database requires.
+        let opener = Arc::new(StorageSchemaOpener::<DB>::new()?);
         let mut schemas = self.instance.data.schemas.write();
-        if schemas
-            .insert(
-                DB::schema_name(),
-                Arc::new(StorageSchemaOpener::<DB>::new()?),
-            )
-            .is_none()
-        {
-            Ok(())
-        } else {
-            Err(Error::Core(bonsaidb_core::Error::SchemaAlreadyRegistered(
+        match schemas.entry(DB::schema_name()) {
+            Entry::Vacant(entry) => {
+                entry.insert(opener);
+                Ok(())
+            }
+            Entry::Occupied(_) => Err(Error::Core(bonsaidb_core::Error::SchemaAlreadyRegistered(
                 DB::schema_name(),
-            )))
+            ))),
         }
     }
 
@@ -525,6 +757,98 @@ impl Storage {
         }
     }
 
+    /// Waits for all background tasks, such as view indexing and compaction,
+    /// to complete. If `timeout` elapses before the tasks complete, `false` is
+    /// returned.
+    #[must_use]
+    pub fn wait_for_background_tasks(&self, timeout: Option<Duration>) -> bool {
+        match self.instance.tasks().jobs.idle_notifier() {
+            Some(idle) => match timeout {
+                Some(timeout) => idle.recv_timeout(timeout).is_ok(),
+                None => idle.recv().is_ok(),
+            },
+            None => true,
+        }
+    }
+
+    /// Returns a summary of the background tasks, such as view indexing and
+    /// compaction, that are queued or running.
+    #[must_use]
+    pub fn background_tasks(&self) -> BackgroundTasks {
+        self.instance.tasks().summary()
+    }
+
+    /// Returns the resources consumed by each database and user since this
+    /// storage was opened.
+    #[must_use]
+    pub fn usage(&self) -> UsageReport {
+        self.instance.usage().report()
+    }
+
+    /// Returns the id of the user this instance's session belongs to, if any.
+    pub(crate) fn user_id(&self) -> Option<u64> {
+        match self.effective_session.as_ref()?.identity()? {
+            Identity::User { id, .. } => Some(*id),
+            _ => None,
+        }
+    }
+
+    /// Persists any pending key-value store changes for all open databases.
+    ///
+    /// Transactions are written to disk before they are reported as
+    /// committed, but key-value operations may be buffered in memory according
+    /// to the configured
+    /// [`KeyValuePersistence`](crate::config::KeyValuePersistence). This
+    /// function writes out the buffered changes, and is called automatically
+    /// when the last reference to a database is dropped. View query counts
+    /// recorded for [`Views::warm_on_open`](crate::config::Views::warm_on_open)
+    /// are also written.
+    pub fn flush(&self) {
+        let open_roots = self
+            .instance
+            .data
+            .open_roots
+            .lock()
+            .values()
+            .cloned()
+            .collect::<Vec<_>>();
+        for context in open_roots {
+            context.persist_key_value_store();
+            context.persist_view_query_counts();
+        }
+    }
+
+    /// Returns a clone of this instance whose operations fail with
+    /// [`bonsaidb_core::Error::DeadlineExceeded`] if they are still waiting on
+    /// view updates, reductions, or compactions once `deadline` has passed.
+    #[must_use]
+    pub fn with_deadline(&self, deadline: Instant) -> Self {
+        Self {
+            instance: self.instance.clone(),
+            authentication: self.authentication.clone(),
+            effective_session: self.effective_session.clone(),
+            deadline: Some(deadline),
+        }
+    }
+
+    /// Returns the deadline operations on this instance must complete by, if
+    /// one has been set with [`Self::with_deadline`].
+    #[must_use]
+    pub const fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Returns [`bonsaidb_core::Error::DeadlineExceeded`] if this instance's
+    /// deadline has passed.
+    pub(crate) fn check_deadline(&self) -> Result<(), bonsaidb_core::Error> {
+        match self.deadline {
+            Some(deadline) if Instant::now() >= deadline => {
+                Err(bonsaidb_core::Error::DeadlineExceeded)
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Restricts an unauthenticated instance to having `effective_permissions`.
     /// Returns `None` if a session has already been established.
     #[must_use]
@@ -540,6 +864,7 @@ impl Storage {
                     authentication: SessionAuthentication::None,
                     permissions: effective_permissions,
                 })),
+                deadline: self.deadline,
             })
         }
     }
@@ -593,14 +918,24 @@ impl StorageInstance {
         not(any(feature = "encryption", feature = "compression")),
         allow(unused_mut)
     )]
-    pub(crate) fn open_roots(&self, name: &str) -> Result<Context, Error> {
+    #[cfg_attr(
+        not(any(feature = "encryption", feature = "compression")),
+        allow(unused_variables)
+    )]
+    pub(crate) fn open_roots(&self, name: &str, schematic: &Schematic) -> Result<Context, Error> {
         let mut open_roots = self.data.open_roots.lock();
         if let Some(roots) = open_roots.get(name) {
             Ok(roots.clone())
         } else {
             let task_name = name.to_string();
 
-            let mut config = nebari::Config::new(self.data.path.join(task_name))
+            let path = self
+                .data
+                .shard_paths
+                .get(name)
+                .cloned()
+                .unwrap_or_else(|| self.data.path.join(task_name));
+            let mut config = nebari::Config::new(path)
                 .file_manager(self.data.file_manager.clone())
                 .cache(self.data.chunk_cache.clone())
                 .shared_thread_pool(&self.data.threadpool);
@@ -613,7 +948,20 @@ impl StorageInstance {
             let roots = config.open().map_err(Error::from)?;
             let context = Context::new(
                 roots,
-                self.data.key_value_persistence.clone(),
+                KeyTree::new(
+                    #[cfg(any(feature = "encryption", feature = "compression"))]
+                    self.tree_vault_for_key(schematic.encryption_key_for_key_value_store())?,
+                ),
+                self.data
+                    .database_key_value_persistence
+                    .get(name)
+                    .unwrap_or(&self.data.key_value_persistence)
+                    .clone(),
+                self.data
+                    .database_concurrent_query_limit
+                    .get(name)
+                    .copied()
+                    .or(self.data.concurrent_query_limit),
                 Some(self.data.lock.clone()),
             );
 
@@ -623,18 +971,119 @@ impl StorageInstance {
         }
     }
 
+    /// Returns the vault to use for a tree whose contents should be encrypted
+    /// using `encryption_key`. If `encryption_key` is `None`, the storage's
+    /// default vault is returned.
+    #[cfg(any(feature = "encryption", feature = "compression"))]
+    #[cfg_attr(not(feature = "encryption"), allow(unused_mut, unused_variables))]
+    pub(crate) fn tree_vault_for_key(
+        &self,
+        encryption_key: Option<&KeyId>,
+    ) -> Result<Option<TreeVault>, Error> {
+        match (encryption_key, self.data.tree_vault.clone()) {
+            (Some(override_key), Some(mut vault)) => {
+                #[cfg(feature = "encryption")]
+                {
+                    vault.key = Some(override_key.clone());
+                    Ok(Some(vault))
+                }
+
+                #[cfg(not(feature = "encryption"))]
+                {
+                    Err(Error::EncryptionDisabled)
+                }
+            }
+            (None, Some(vault)) => Ok(Some(vault)),
+            (key, None) => {
+                #[cfg(feature = "encryption")]
+                {
+                    Ok(TreeVault::new_if_needed(
+                        key.cloned(),
+                        &self.data.vault,
+                        #[cfg(feature = "compression")]
+                        None,
+                    ))
+                }
+
+                #[cfg(not(feature = "encryption"))]
+                {
+                    if key.is_some() {
+                        Err(Error::EncryptionDisabled)
+                    } else {
+                        Ok(None)
+                    }
+                }
+            }
+        }
+    }
+
     pub(crate) fn tasks(&self) -> &'_ TaskManager {
         &self.data.tasks
     }
 
+    pub(crate) fn usage(&self) -> &'_ UsageTracker {
+        &self.data.usage
+    }
+
     pub(crate) fn check_view_integrity_on_database_open(&self) -> bool {
         self.data.check_view_integrity_on_database_open
     }
 
+    pub(crate) fn caches_view_queries(&self, view: &ViewName) -> bool {
+        self.data.cached_view_queries.contains(view)
+    }
+
+    pub(crate) fn warm_views_on_open(&self) -> Option<usize> {
+        self.data.warm_views_on_open
+    }
+
+    pub(crate) fn serves_stale_views_while_rebuilding(&self, database: &str) -> bool {
+        self.data.stale_views_while_rebuilding.contains(database)
+    }
+
+    pub(crate) fn redacts_unreadable_view_entries(&self) -> bool {
+        self.data.redact_unreadable_view_entries
+    }
+
+    /// Returns the sharding configuration of `collection` in the database named
+    /// `database`, if it is sharded.
+    pub(crate) fn sharding(
+        &self,
+        database: &str,
+        collection: &CollectionName,
+    ) -> Option<&Sharding> {
+        self.data
+            .sharding
+            .get(database)
+            .and_then(|collections| collections.get(collection))
+    }
+
     pub(crate) fn relay(&self) -> &'_ Relay {
         &self.data.relay
     }
 
+    /// Returns a handle to the database named `name` if it has already been
+    /// opened, reusing its schematic and context.
+    fn cached_database(&self, name: &str, storage: &Storage) -> Option<Database> {
+        let data = self.data.open_databases.lock().get(name).cloned()?;
+        Some(Database {
+            data,
+            storage: storage.clone(),
+        })
+    }
+
+    /// Stores the state of `db` so that future requests to open it can reuse
+    /// it. If another thread opened the same database first, its state is
+    /// returned instead.
+    fn cache_database(&self, mut db: Database) -> Database {
+        let mut open_databases = self.data.open_databases.lock();
+        db.data = open_databases
+            .entry(db.data.name.to_string())
+            .or_insert_with(|| db.data.clone())
+            .clone();
+        db
+    }
+
     /// Opens a database through a generic-free trait.
     pub(crate) fn database_without_schema(
         &self,
@@ -663,12 +1112,16 @@ impl StorageInstance {
             }
         }
 
-        let mut schemas = self.data.schemas.write();
         let storage =
             storage.map_or_else(|| Cow::Owned(Storage::from(self.clone())), Cow::Borrowed);
-        if let Some(schema) = schemas.get_mut(&stored_schema) {
+        if let Some(db) = self.cached_database(name, &storage) {
+            return Ok(db);
+        }
+
+        let schema = self.data.schemas.read().get(&stored_schema).cloned();
+        if let Some(schema) = schema {
             let db = schema.open(name.to_string(), storage.as_ref())?;
-            Ok(db)
+            Ok(self.cache_database(db))
         } else {
             // The schema was stored, the user is requesting the same schema,
             // but it isn't registerd with the storage currently.
@@ -707,6 +1160,31 @@ impl StorageInstance {
         }
     }
 
+    fn update_role_with_named_group<
+        'role,
+        'group,
+        R: Nameable<'role, u64> + Send + Sync,
+        G: Nameable<'group, u64> + Send + Sync,
+        F: FnOnce(&mut CollectionDocument<Role>, u64) -> Result<bool, bonsaidb_core::Error>,
+    >(
+        &self,
+        role: R,
+        permission_group: G,
+        callback: F,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let admin = self.admin();
+        let mut role = Role::load(role, &admin)?.ok_or(bonsaidb_core::Error::RoleNotFound)?;
+        let permission_group_id = permission_group
+            .name()?
+            .id::<PermissionGroup, _>(&admin)?
+            .ok_or(bonsaidb_core::Error::PermissionGroupNotFound)?;
+        if callback(&mut role, permission_group_id)? {
+            role.contents.version += 1;
+            role.update(&admin)?;
+        }
+        Ok(())
+    }
+
     #[cfg(any(feature = "token-authentication", feature = "password-hashing"))]
     #[cfg_attr(
         any(
@@ -715,7 +1193,7 @@ impl StorageInstance {
         ),
         allow(unused_variables, clippy::needless_pass_by_value)
     )]
-    fn authenticate_inner(
+    pub(crate) fn authenticate_inner(
         &self,
         authentication: bonsaidb_core::connection::Authentication,
         loaded_user: Option<CollectionDocument<User>>,
@@ -751,19 +1229,125 @@ impl StorageInstance {
                     .clone()
                     .ok_or(bonsaidb_core::Error::InvalidCredentials)?;
 
-                self.data
-                    .argon
-                    .verify(user.header.id, password, saved_hash)?;
+                let now = Timestamp::now();
+                if let Some(locked_until) = user.contents.locked_until {
+                    if locked_until > now {
+                        return Err(bonsaidb_core::Error::AccountLocked {
+                            retry_after: Some((locked_until - now).unwrap_or_default()),
+                        });
+                    }
+                }
+
+                if let Err(err) = self.data.argon.verify(user.header.id, password, saved_hash) {
+                    self.record_failed_authentication(user, now, admin)?;
+                    return Err(err.into());
+                }
+
+                let user = self.record_successful_authentication(user, now, admin)?;
                 self.assume_user(user, admin)
             }
         }
     }
 
+    /// Increments the user's consecutive authentication failures, locking the
+    /// user if the password policy's lockout threshold is reached.
+    #[cfg(feature = "password-hashing")]
+    fn record_failed_authentication(
+        &self,
+        mut user: CollectionDocument<User>,
+        now: Timestamp,
+        admin: &Database,
+    ) -> Result<(), bonsaidb_core::Error> {
+        loop {
+            user.contents.failed_authentications += 1;
+            if let Some(lockout) = self.data.password_policy.lockout {
+                if user.contents.failed_authentications >= lockout.failures.max(1) {
+                    user.contents.failed_authentications = 0;
+                    user.contents.locked_until = Some(now + lockout.duration);
+                }
+            }
+            match user.update(admin) {
+                Ok(()) => return Ok(()),
+                // Another attempt was recorded concurrently.
+                Err(bonsaidb_core::Error::DocumentConflict(..)) => {
+                    user = User::get(&user.header.id, admin)?
+                        .ok_or(bonsaidb_core::Error::InvalidCredentials)?;
+                }
+                Err(other) => return Err(other),
+            }
+        }
+    }
+
+    /// Clears the user's authentication failures and checks that the user's
+    /// password hasn't expired.
+    ///
+    /// If the user was updated while the password was being verified, the
+    /// user is reloaded and checked again, ensuring a lock recorded by a
+    /// concurrent failed attempt or a concurrent password change isn't
+    /// discarded.
+    #[cfg(feature = "password-hashing")]
+    fn record_successful_authentication(
+        &self,
+        mut user: CollectionDocument<User>,
+        now: Timestamp,
+        admin: &Database,
+    ) -> Result<CollectionDocument<User>, bonsaidb_core::Error> {
+        let verified_hash = user.contents.argon_hash.clone();
+        loop {
+            if let (Some(maximum_age), Some(password_set_at)) = (
+                self.data.password_policy.maximum_age,
+                user.contents.password_set_at,
+            ) {
+                if password_set_at + maximum_age <= now {
+                    return Err(bonsaidb_core::Error::PasswordExpired);
+                }
+            }
+
+            let start_aging = self.data.password_policy.maximum_age.is_some()
+                && user.contents.password_set_at.is_none();
+            if user.contents.failed_authentications == 0
+                && user.contents.locked_until.is_none()
+                && !start_aging
+            {
+                return Ok(user);
+            }
+
+            user.contents.failed_authentications = 0;
+            user.contents.locked_until = None;
+            if start_aging {
+                user.contents.password_set_at = Some(now);
+            }
+            match user.update(admin) {
+                Ok(()) => return Ok(user),
+                // Another attempt was recorded concurrently.
+                Err(bonsaidb_core::Error::DocumentConflict(..)) => {
+                    user = User::get(&user.header.id, admin)?
+                        .ok_or(bonsaidb_core::Error::InvalidCredentials)?;
+                    if user.contents.argon_hash != verified_hash {
+                        return Err(bonsaidb_core::Error::InvalidCredentials);
+                    }
+                    if let Some(locked_until) = user.contents.locked_until {
+                        if locked_until > now {
+                            return Err(bonsaidb_core::Error::AccountLocked {
+                                retry_after: Some((locked_until - now).unwrap_or_default()),
+                            });
+                        }
+                    }
+                }
+                Err(other) => return Err(other),
+            }
+        }
+    }
+
     fn assume_user(
         &self,
         user: CollectionDocument<User>,
         admin: &Database,
     ) -> Result<Storage, bonsaidb_core::Error> {
+        if user.contents.disabled {
+            return Err(bonsaidb_core::Error::InvalidCredentials);
+        }
+
         let permissions = user.contents.effective_permissions(
             admin,
             &admin.storage().instance.data.authenticated_permissions,
@@ -780,16 +1364,45 @@ impl StorageInstance {
             })),
             permissions,
         };
-        let authentication = Arc::new(AuthenticatedSession {
-            storage: Arc::downgrade(&self.data),
-            session: Mutex::new(session.clone()),
-        });
+        let authentication = AuthenticatedSession::new(&self.data, session.clone());
         sessions.sessions.insert(session_id, authentication.clone());
 
         Ok(Storage {
             instance: self.clone(),
             authentication: Some(authentication),
             effective_session: Some(Arc::new(session)),
+            deadline: None,
+        })
+    }
+
+    /// Returns an instance with `user`'s permissions whose session is not
+    /// registered, which means it ends when the instance is dropped.
+    fn impersonate_user(
+        &self,
+        user: CollectionDocument<User>,
+        admin: &Database,
+    ) -> Result<Storage, bonsaidb_core::Error> {
+        if user.contents.disabled {
+            return Err(bonsaidb_core::Error::InvalidCredentials);
+        }
+
+        let permissions = user.contents.effective_permissions(
+            admin,
+            &admin.storage().instance.data.authenticated_permissions,
+        )?;
+
+        Ok(Storage {
+            instance: self.clone(),
+            authentication: None,
+            effective_session: Some(Arc::new(Session {
+                id: None,
+                authentication: SessionAuthentication::Identity(Arc::new(Identity::User {
+                    id: user.header.id,
+                    username: user.contents.username,
+                })),
+                permissions,
+            })),
+            deadline: None,
         })
     }
 
@@ -814,19 +1427,39 @@ impl StorageInstance {
             })),
             permissions,
         };
-        let authentication = Arc::new(AuthenticatedSession {
-            storage: Arc::downgrade(&self.data),
-            session: Mutex::new(session.clone()),
-        });
+        let authentication = AuthenticatedSession::new(&self.data, session.clone());
         sessions.sessions.insert(session_id, authentication.clone());
 
         Ok(Storage {
             instance: self.clone(),
             authentication: Some(authentication),
             effective_session: Some(Arc::new(session)),
+            deadline: None,
         })
     }
 
+    fn set_user_disabled<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+        disabled: bool,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let admin = self.admin();
+        let mut user = User::load(user, &admin)?.ok_or(bonsaidb_core::Error::UserNotFound)?;
+        if user.contents.disabled != disabled {
+            user.contents.disabled = disabled;
+            user.update(&admin)?;
+        }
+        if disabled {
+            self.data.remove_user_sessions(user.header.id);
+        }
+        Ok(())
+    }
+
+    fn without_password_hash(mut user: CollectionDocument<User>) -> CollectionDocument<User> {
+        user.contents.argon_hash = None;
+        user
+    }
+
     fn add_permission_group_to_user_inner(
         user: &mut CollectionDocument<User>,
         permission_group_id: u64,
@@ -848,6 +1481,27 @@ impl StorageInstance {
         old_len != user.contents.groups.len()
     }
 
+    fn add_permission_group_to_role_inner(
+        role: &mut CollectionDocument<Role>,
+        permission_group_id: u64,
+    ) -> bool {
+        if role.contents.groups.contains(&permission_group_id) {
+            false
+        } else {
+            role.contents.groups.push(permission_group_id);
+            true
+        }
+    }
+
+    fn remove_permission_group_from_role_inner(
+        role: &mut CollectionDocument<Role>,
+        permission_group_id: u64,
+    ) -> bool {
+        let old_len = role.contents.groups.len();
+        role.contents.groups.retain(|id| id != &permission_group_id);
+        old_len != role.contents.groups.len()
+    }
+
     fn add_role_to_user_inner(user: &mut CollectionDocument<User>, role_id: u64) -> bool {
         if user.contents.roles.contains(&role_id) {
             false
@@ -871,7 +1525,7 @@ pub trait DatabaseOpener: Send + Sync + Debug {
 
 #[derive(Debug)]
 pub struct StorageSchemaOpener<DB: Schema> {
-    schematic: Schematic,
+    schematic: Arc<Schematic>,
     _phantom: PhantomData<DB>,
 }
 
@@ -880,7 +1534,7 @@ where
     DB: Schema,
 {
     pub fn new() -> Result<Self, Error> {
-        let schematic = DB::schematic()?;
+        let schematic = Arc::new(DB::schematic()?);
         Ok(Self {
             schematic,
             _phantom: PhantomData::default(),
@@ -897,8 +1551,8 @@ where
     }
 
     fn open(&self, name: String, storage: &Storage) -> Result<Database, Error> {
-        let roots = storage.instance.open_roots(&name)?;
-        let db = Database::new::<DB, _>(name, roots, storage)?;
+        let roots = storage.instance.open_roots(&name, &self.schematic)?;
+        let db = Database::new(name, self.schematic.clone(), roots, storage)?;
         Ok(db)
     }
 }
@@ -914,12 +1568,20 @@ impl StorageConnection for StorageInstance {
     type Database = Database;
 
     fn admin(&self) -> Self::Database {
-        Database::new::<Admin, _>(
+        let storage = Storage::from(self.clone());
+        if let Some(admin) = self.cached_database(ADMIN_DATABASE_NAME, &storage) {
+            return admin;
+        }
+
+        let schematic = Arc::new(Admin::schematic().unwrap());
+        let admin = Database::new(
             ADMIN_DATABASE_NAME,
-            self.open_roots(ADMIN_DATABASE_NAME).unwrap(),
-            &Storage::from(self.clone()),
+            schematic.clone(),
+            self.open_roots(ADMIN_DATABASE_NAME, &schematic).unwrap(),
+            &storage,
         )
-        .unwrap()
+        .unwrap();
+        self.cache_database(admin)
     }
 
     #[cfg_attr(feature = "tracing", tracing::instrument(
@@ -953,6 +1615,7 @@ impl StorageConnection for StorageInstance {
                 .push(&admin::Database {
                     name: name.to_string(),
                     schema: schema.clone(),
+                    quota: None,
                 })?;
             available_databases.insert(name.to_string(), schema);
         } else if !only_if_needed {
@@ -974,7 +1637,9 @@ impl StorageConnection for StorageInstance {
         let admin = self.admin();
         let mut available_databases = self.data.available_databases.write();
         available_databases.remove(name);
+        self.data.database_quotas.write().remove(name);
 
+        self.data.open_databases.lock().remove(name);
         let mut open_roots = self.data.open_roots.lock();
         open_roots.remove(name);
 
@@ -986,6 +1651,30 @@ impl StorageConnection for StorageInstance {
                 .map_err(Error::Nebari)?;
         }
 
+        // Shards are stored as separate sets of files, which are removed
+        // along with the database.
+        for shard in self
+            .data
+            .sharding
+            .get(name)
+            .into_iter()
+            .flat_map(HashMap::values)
+            .flat_map(|sharding| &sharding.shards)
+        {
+            let shard_name = shard_database_name(name, &shard.name);
+            open_roots.remove(&shard_name);
+            let shard_folder = shard
+                .path
+                .clone()
+                .unwrap_or_else(|| self.data.path.join(&shard_name));
+            if shard_folder.exists() {
+                self.data
+                    .file_manager
+                    .delete_directory(&shard_folder)
+                    .map_err(Error::Nebari)?;
+            }
+        }
+
         if let Some(entry) = admin
             .view::<database::ByName>()
             .with_key(&name.to_ascii_lowercase())
@@ -1001,10 +1690,32 @@ impl StorageConnection for StorageInstance {
     }
 
     #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
-    fn list_databases(&self) -> Result<Vec<connection::Database>, bonsaidb_core::Error> {
+    fn list_databases_matching(
+        &self,
+        query: DatabaseQuery,
+    ) -> Result<Vec<connection::Database>, bonsaidb_core::Error> {
+        // Databases are ordered by name, so iteration can begin at the first
+        // name that could match and stop once the prefix no longer matches.
+        let start = match (query.after.as_deref(), query.name_prefix.as_deref()) {
+            (Some(after), Some(prefix)) if after < prefix => Bound::Included(prefix),
+            (Some(after), _) => Bound::Excluded(after),
+            (None, Some(prefix)) => Bound::Included(prefix),
+            (None, None) => Bound::Unbounded,
+        };
+        let limit = query.limit.map_or(usize::MAX, |limit| {
+            usize::try_from(limit).unwrap_or(usize::MAX)
+        });
         let available_databases = self.data.available_databases.read();
         Ok(available_databases
-            .iter()
+            .range::<str, _>((start, Bound::Unbounded))
+            .take_while(|(name, _)| {
+                query
+                    .name_prefix
+                    .as_ref()
+                    .map_or(true, |prefix| name.starts_with(prefix.as_str()))
+            })
+            .filter(|(_, schema)| query.schema.as_ref().map_or(true, |s| s == *schema))
+            .take(limit)
             .map(|(name, schema)| connection::Database {
                 name: name.to_string(),
                 schema: schema.clone(),
@@ -1039,6 +1750,65 @@ impl StorageConnection for StorageInstance {
         Ok(())
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    fn list_users(&self) -> Result<Vec<CollectionDocument<User>>, bonsaidb_core::Error> {
+        let admin = self.admin();
+        let users = User::all(&admin).query()?;
+        Ok(users.into_iter().map(Self::without_password_hash).collect())
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    fn user_by_name(
+        &self,
+        username: &str,
+    ) -> Result<Option<CollectionDocument<User>>, bonsaidb_core::Error> {
+        let admin = self.admin();
+        let user = User::load(username, &admin)?;
+        Ok(user.map(Self::without_password_hash))
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    fn disable_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<(), bonsaidb_core::Error> {
+        self.set_user_disabled(user, true)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    fn enable_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<(), bonsaidb_core::Error> {
+        self.set_user_disabled(user, false)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    fn unlock_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let admin = self.admin();
+        let mut user = User::load(user, &admin)?.ok_or(bonsaidb_core::Error::UserNotFound)?;
+        if user.contents.failed_authentications > 0 || user.contents.locked_until.is_some() {
+            user.contents.failed_authentications = 0;
+            user.contents.locked_until = None;
+            user.update(&admin)?;
+        }
+        Ok(())
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    fn effective_permissions<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<Permissions, bonsaidb_core::Error> {
+        let admin = self.admin();
+        let user = User::load(user, &admin)?.ok_or(bonsaidb_core::Error::UserNotFound)?;
+        user.contents
+            .effective_permissions(&admin, &self.data.authenticated_permissions)
+    }
+
     #[cfg(feature = "password-hashing")]
     #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
     fn set_user_password<'user, U: Nameable<'user, u64> + Send + Sync>(
@@ -1046,9 +1816,16 @@ impl StorageConnection for StorageInstance {
         user: U,
         password: bonsaidb_core::connection::SensitiveString,
     ) -> Result<(), bonsaidb_core::Error> {
+        let minimum_length = self.data.password_policy.minimum_length;
+        if password.chars().count() < minimum_length {
+            return Err(bonsaidb_core::Error::PasswordTooShort { minimum_length });
+        }
         let admin = self.admin();
         let mut user = User::load(user, &admin)?.ok_or(bonsaidb_core::Error::UserNotFound)?;
         user.contents.argon_hash = Some(self.data.argon.hash(user.header.id, password)?);
+        user.contents.password_set_at = Some(Timestamp::now());
+        user.contents.failed_authentications = 0;
+        user.contents.locked_until = None;
         user.update(&admin)
     }
 
@@ -1084,6 +1861,16 @@ impl StorageConnection for StorageInstance {
         }
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    fn as_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<Self::Authenticated, bonsaidb_core::Error> {
+        let admin = self.admin();
+        let user = User::load(user, &admin)?.ok_or(bonsaidb_core::Error::InvalidCredentials)?;
+        self.impersonate_user(user, &admin)
+    }
+
     #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
     fn add_permission_group_to_user<
         'user,
@@ -1161,6 +1948,231 @@ impl StorageConnection for StorageInstance {
             Ok(Self::remove_role_from_user_inner(user, role_id))
         })
     }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    fn create_permission_group(
+        &self,
+        name: &str,
+        statements: Vec<Statement>,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        validate_statements(&statements)?;
+        let result = self
+            .admin()
+            .collection::<PermissionGroup>()
+            .push(&PermissionGroup {
+                name: name.to_string(),
+                statements,
+                version: 0,
+            })?;
+        Ok(result.id)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    fn list_permission_groups(
+        &self,
+    ) -> Result<Vec<CollectionDocument<PermissionGroup>>, bonsaidb_core::Error> {
+        let admin = self.admin();
+        let groups = PermissionGroup::all(&admin).query()?;
+        Ok(groups)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    fn set_permission_group_statements<'group, G: Nameable<'group, u64> + Send + Sync>(
+        &self,
+        permission_group: G,
+        statements: Vec<Statement>,
+    ) -> Result<(), bonsaidb_core::Error> {
+        validate_statements(&statements)?;
+        let admin = self.admin();
+        let mut permission_group = PermissionGroup::load(permission_group, &admin)?
+            .ok_or(bonsaidb_core::Error::PermissionGroupNotFound)?;
+        permission_group.contents.statements = statements;
+        permission_group.contents.version += 1;
+        permission_group.update(&admin)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    fn update_permission_group_statements<'group, G: Nameable<'group, u64> + Send + Sync>(
+        &self,
+        permission_group: G,
+        expected_version: u64,
+        statements: Vec<Statement>,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        validate_statements(&statements)?;
+        let admin = self.admin();
+        let mut permission_group = PermissionGroup::load(permission_group, &admin)?
+            .ok_or(bonsaidb_core::Error::PermissionGroupNotFound)?;
+        if permission_group.contents.version != expected_version {
+            return Err(bonsaidb_core::Error::VersionConflict {
+                expected: expected_version,
+                current: permission_group.contents.version,
+            });
+        }
+        permission_group.contents.statements = statements;
+        permission_group.contents.version += 1;
+        permission_group.update(&admin)?;
+        Ok(permission_group.contents.version)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    fn delete_permission_group<'group, G: Nameable<'group, u64> + Send + Sync>(
+        &self,
+        permission_group: G,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let admin = self.admin();
+        let permission_group = PermissionGroup::load(permission_group, &admin)?
+            .ok_or(bonsaidb_core::Error::PermissionGroupNotFound)?;
+
+        // Remove the group from the users and roles it was assigned to, which
+        // prevents them from referring to a group that no longer exists.
+        for mut user in User::all(&admin).query()? {
+            if Self::remove_permission_group_from_user_inner(&mut user, permission_group.header.id)
+            {
+                user.update(&admin)?;
+            }
+        }
+        for mut role in Role::all(&admin).query()? {
+            if Self::remove_permission_group_from_role_inner(&mut role, permission_group.header.id)
+            {
+                role.update(&admin)?;
+            }
+        }
+        permission_group.delete(&admin)?;
+
+        Ok(())
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    fn create_role(&self, name: &str) -> Result<u64, bonsaidb_core::Error> {
+        let result = self.admin().collection::<Role>().push(&Role::named(name))?;
+        Ok(result.id)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    fn list_roles(&self) -> Result<Vec<CollectionDocument<Role>>, bonsaidb_core::Error> {
+        let admin = self.admin();
+        let roles = Role::all(&admin).query()?;
+        Ok(roles)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    fn add_permission_group_to_role<
+        'role,
+        'group,
+        R: Nameable<'role, u64> + Send + Sync,
+        G: Nameable<'group, u64> + Send + Sync,
+    >(
+        &self,
+        role: R,
+        permission_group: G,
+    ) -> Result<(), bonsaidb_core::Error> {
+        self.update_role_with_named_group(role, permission_group, |role, permission_group_id| {
+            Ok(Self::add_permission_group_to_role_inner(
+                role,
+                permission_group_id,
+            ))
+        })
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    fn remove_permission_group_from_role<
+        'role,
+        'group,
+        R: Nameable<'role, u64> + Send + Sync,
+        G: Nameable<'group, u64> + Send + Sync,
+    >(
+        &self,
+        role: R,
+        permission_group: G,
+    ) -> Result<(), bonsaidb_core::Error> {
+        self.update_role_with_named_group(role, permission_group, |role, permission_group_id| {
+            Ok(Self::remove_permission_group_from_role_inner(
+                role,
+                permission_group_id,
+            ))
+        })
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    fn update_role_permission_groups<'role, R: Nameable<'role, u64> + Send + Sync>(
+        &self,
+        role: R,
+        expected_version: u64,
+        mut permission_groups: Vec<u64>,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        let admin = self.admin();
+        let mut role = Role::load(role, &admin)?.ok_or(bonsaidb_core::Error::RoleNotFound)?;
+        if role.contents.version != expected_version {
+            return Err(bonsaidb_core::Error::VersionConflict {
+                expected: expected_version,
+                current: role.contents.version,
+            });
+        }
+        permission_groups.sort_unstable();
+        permission_groups.dedup();
+        if PermissionGroup::get_multiple(&permission_groups, &admin)?.len()
+            != permission_groups.len()
+        {
+            return Err(bonsaidb_core::Error::PermissionGroupNotFound);
+        }
+        role.contents.groups = permission_groups;
+        role.contents.version += 1;
+        role.update(&admin)?;
+        Ok(role.contents.version)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    fn delete_role<'role, R: Nameable<'role, u64> + Send + Sync>(
+        &self,
+        role: R,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let admin = self.admin();
+        let role = Role::load(role, &admin)?.ok_or(bonsaidb_core::Error::RoleNotFound)?;
+
+        // Remove the role from the users it was assigned to.
+        for mut user in User::all(&admin).query()? {
+            if Self::remove_role_from_user_inner(&mut user, role.header.id) {
+                user.update(&admin)?;
+            }
+        }
+        role.delete(&admin)?;
+
+        Ok(())
+    }
+
+    fn list_sessions<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<Vec<SessionInfo>, bonsaidb_core::Error> {
+        let admin = self.admin();
+        let user_id = user
+            .name()?
+            .id::<User, _>(&admin)?
+            .ok_or(bonsaidb_core::Error::UserNotFound)?;
+
+        self.data.remove_expired_sessions(Timestamp::now());
+
+        let mut sessions = self
+            .data
+            .sessions
+            .read()
+            .sessions
+            .values()
+            .filter_map(|session| session.info())
+            .filter(|info| {
+                matches!(info.identity.as_ref(), Identity::User { id, .. } if *id == user_id)
+            })
+            .collect::<Vec<_>>();
+        sessions.sort_by_key(|info| info.id.0);
+        Ok(sessions)
+    }
+
+    fn revoke_session(&self, session_id: SessionId) -> Result<(), bonsaidb_core::Error> {
+        self.data
+            .remove_session(session_id)
+            .map(drop)
+            .ok_or(bonsaidb_core::Error::SessionExpired)
+    }
 }
 
 impl HasSession for Storage {
@@ -1169,6 +2181,33 @@ impl HasSession for Storage {
     }
 }
 
+#[cfg(feature = "encryption")]
+impl FieldEncryption for Storage {
+    fn encrypt_field_bytes(
+        &self,
+        key: &KeyId,
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, bonsaidb_core::Error> {
+        self.instance
+            .data
+            .vault
+            .encrypt_payload(
+                key,
+                plaintext,
+                self.session().map(|session| &session.permissions),
+            )
+            .map_err(bonsaidb_core::Error::from)
+    }
+
+    fn decrypt_field_bytes(&self, payload: &[u8]) -> Result<Vec<u8>, bonsaidb_core::Error> {
+        self.instance
+            .data
+            .vault
+            .decrypt_encrypted_payload(payload, self.session().map(|session| &session.permissions))
+            .map_err(bonsaidb_core::Error::from)
+    }
+}
+
 impl StorageConnection for Storage {
     type Authenticated = Self;
     type Database = Database;
@@ -1203,12 +2242,15 @@ impl StorageConnection for Storage {
         self.instance.delete_database(name)
     }
 
-    fn list_databases(&self) -> Result<Vec<connection::Database>, bonsaidb_core::Error> {
+    fn list_databases_matching(
+        &self,
+        query: DatabaseQuery,
+    ) -> Result<Vec<connection::Database>, bonsaidb_core::Error> {
         self.check_permission(
             bonsaidb_resource_name(),
             &BonsaiAction::Server(ServerAction::ListDatabases),
         )?;
-        self.instance.list_databases()
+        self.instance.list_databases_matching(query)
     }
 
     fn list_available_schemas(&self) -> Result<Vec<SchemaName>, bonsaidb_core::Error> {
@@ -1243,6 +2285,92 @@ impl StorageConnection for Storage {
         self.instance.delete_user(user)
     }
 
+    fn list_users(&self) -> Result<Vec<CollectionDocument<User>>, bonsaidb_core::Error> {
+        self.check_permission(
+            bonsaidb_resource_name(),
+            &BonsaiAction::Server(ServerAction::ListUsers),
+        )?;
+        self.instance.list_users()
+    }
+
+    fn user_by_name(
+        &self,
+        username: &str,
+    ) -> Result<Option<CollectionDocument<User>>, bonsaidb_core::Error> {
+        let user = self.instance.user_by_name(username)?;
+        if let Some(user) = &user {
+            self.check_permission(
+                user_resource_name(user.header.id),
+                &BonsaiAction::Server(ServerAction::ViewUser),
+            )?;
+        }
+        Ok(user)
+    }
+
+    fn disable_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let admin = self.admin();
+        let user_id = user
+            .name()?
+            .id::<User, _>(&admin)?
+            .ok_or(bonsaidb_core::Error::UserNotFound)?;
+        self.check_permission(
+            user_resource_name(user_id),
+            &BonsaiAction::Server(ServerAction::DisableUser),
+        )?;
+        self.instance.disable_user(user_id)
+    }
+
+    fn enable_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let admin = self.admin();
+        let user_id = user
+            .name()?
+            .id::<User, _>(&admin)?
+            .ok_or(bonsaidb_core::Error::UserNotFound)?;
+        self.check_permission(
+            user_resource_name(user_id),
+            &BonsaiAction::Server(ServerAction::EnableUser),
+        )?;
+        self.instance.enable_user(user_id)
+    }
+
+    fn unlock_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let admin = self.admin();
+        let user_id = user
+            .name()?
+            .id::<User, _>(&admin)?
+            .ok_or(bonsaidb_core::Error::UserNotFound)?;
+        self.check_permission(
+            user_resource_name(user_id),
+            &BonsaiAction::Server(ServerAction::UnlockUser),
+        )?;
+        self.instance.unlock_user(user_id)
+    }
+
+    fn effective_permissions<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<Permissions, bonsaidb_core::Error> {
+        let admin = self.admin();
+        let user_id = user
+            .name()?
+            .id::<User, _>(&admin)?
+            .ok_or(bonsaidb_core::Error::UserNotFound)?;
+        self.check_permission(
+            user_resource_name(user_id),
+            &BonsaiAction::Server(ServerAction::ViewEffectivePermissions),
+        )?;
+        self.instance.effective_permissions(user_id)
+    }
+
     #[cfg(feature = "password-hashing")]
     fn set_user_password<'user, U: Nameable<'user, u64> + Send + Sync>(
         &self,
@@ -1335,6 +2463,19 @@ impl StorageConnection for Storage {
         }
     }
 
+    fn as_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<Self::Authenticated, bonsaidb_core::Error> {
+        let admin = self.admin();
+        let user = User::load(user, &admin)?.ok_or(bonsaidb_core::Error::InvalidCredentials)?;
+        self.check_permission(
+            user_resource_name(user.header.id),
+            &BonsaiAction::Server(ServerAction::AssumeIdentity),
+        )?;
+        self.instance.impersonate_user(user, &admin)
+    }
+
     fn add_permission_group_to_user<
         'user,
         'group,
@@ -1428,6 +2569,235 @@ impl StorageConnection for Storage {
                 Ok(StorageInstance::remove_role_from_user_inner(user, role_id))
             })
     }
+
+    fn create_permission_group(
+        &self,
+        name: &str,
+        statements: Vec<Statement>,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        self.check_permission(
+            bonsaidb_resource_name(),
+            &BonsaiAction::Server(ServerAction::CreatePermissionGroup),
+        )?;
+        self.instance.create_permission_group(name, statements)
+    }
+
+    fn list_permission_groups(
+        &self,
+    ) -> Result<Vec<CollectionDocument<PermissionGroup>>, bonsaidb_core::Error> {
+        self.check_permission(
+            bonsaidb_resource_name(),
+            &BonsaiAction::Server(ServerAction::ListPermissionGroups),
+        )?;
+        self.instance.list_permission_groups()
+    }
+
+    fn set_permission_group_statements<'group, G: Nameable<'group, u64> + Send + Sync>(
+        &self,
+        permission_group: G,
+        statements: Vec<Statement>,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let admin = self.admin();
+        let permission_group_id = permission_group
+            .name()?
+            .id::<PermissionGroup, _>(&admin)?
+            .ok_or(bonsaidb_core::Error::PermissionGroupNotFound)?;
+        self.check_permission(
+            permission_group_resource_name(permission_group_id),
+            &BonsaiAction::Server(ServerAction::ModifyPermissionGroup),
+        )?;
+        self.instance
+            .set_permission_group_statements(permission_group_id, statements)
+    }
+
+    fn update_permission_group_statements<'group, G: Nameable<'group, u64> + Send + Sync>(
+        &self,
+        permission_group: G,
+        expected_version: u64,
+        statements: Vec<Statement>,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        let admin = self.admin();
+        let permission_group_id = permission_group
+            .name()?
+            .id::<PermissionGroup, _>(&admin)?
+            .ok_or(bonsaidb_core::Error::PermissionGroupNotFound)?;
+        self.check_permission(
+            permission_group_resource_name(permission_group_id),
+            &BonsaiAction::Server(ServerAction::ModifyPermissionGroup),
+        )?;
+        self.instance.update_permission_group_statements(
+            permission_group_id,
+            expected_version,
+            statements,
+        )
+    }
+
+    fn delete_permission_group<'group, G: Nameable<'group, u64> + Send + Sync>(
+        &self,
+        permission_group: G,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let admin = self.admin();
+        let permission_group_id = permission_group
+            .name()?
+            .id::<PermissionGroup, _>(&admin)?
+            .ok_or(bonsaidb_core::Error::PermissionGroupNotFound)?;
+        self.check_permission(
+            permission_group_resource_name(permission_group_id),
+            &BonsaiAction::Server(ServerAction::DeletePermissionGroup),
+        )?;
+        self.instance.delete_permission_group(permission_group_id)
+    }
+
+    fn create_role(&self, name: &str) -> Result<u64, bonsaidb_core::Error> {
+        self.check_permission(
+            bonsaidb_resource_name(),
+            &BonsaiAction::Server(ServerAction::CreateRole),
+        )?;
+        self.instance.create_role(name)
+    }
+
+    fn list_roles(&self) -> Result<Vec<CollectionDocument<Role>>, bonsaidb_core::Error> {
+        self.check_permission(
+            bonsaidb_resource_name(),
+            &BonsaiAction::Server(ServerAction::ListRoles),
+        )?;
+        self.instance.list_roles()
+    }
+
+    fn add_permission_group_to_role<
+        'role,
+        'group,
+        R: Nameable<'role, u64> + Send + Sync,
+        G: Nameable<'group, u64> + Send + Sync,
+    >(
+        &self,
+        role: R,
+        permission_group: G,
+    ) -> Result<(), bonsaidb_core::Error> {
+        self.instance.update_role_with_named_group(
+            role,
+            permission_group,
+            |role, permission_group_id| {
+                self.check_permission(
+                    role_resource_name(role.header.id),
+                    &BonsaiAction::Server(ServerAction::ModifyRolePermissionGroups),
+                )?;
+                Ok(StorageInstance::add_permission_group_to_role_inner(
+                    role,
+                    permission_group_id,
+                ))
+            },
+        )
+    }
+
+    fn remove_permission_group_from_role<
+        'role,
+        'group,
+        R: Nameable<'role, u64> + Send + Sync,
+        G: Nameable<'group, u64> + Send + Sync,
+    >(
+        &self,
+        role: R,
+        permission_group: G,
+    ) -> Result<(), bonsaidb_core::Error> {
+        self.instance.update_role_with_named_group(
+            role,
+            permission_group,
+            |role, permission_group_id| {
+                self.check_permission(
+                    role_resource_name(role.header.id),
+                    &BonsaiAction::Server(ServerAction::ModifyRolePermissionGroups),
+                )?;
+                Ok(StorageInstance::remove_permission_group_from_role_inner(
+                    role,
+                    permission_group_id,
+                ))
+            },
+        )
+    }
+
+    fn update_role_permission_groups<'role, R: Nameable<'role, u64> + Send + Sync>(
+        &self,
+        role: R,
+        expected_version: u64,
+        permission_groups: Vec<u64>,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        let admin = self.admin();
+        let role_id = role
+            .name()?
+            .id::<Role, _>(&admin)?
+            .ok_or(bonsaidb_core::Error::RoleNotFound)?;
+        self.check_permission(
+            role_resource_name(role_id),
+            &BonsaiAction::Server(ServerAction::ModifyRolePermissionGroups),
+        )?;
+        self.instance
+            .update_role_permission_groups(role_id, expected_version, permission_groups)
+    }
+
+    fn delete_role<'role, R: Nameable<'role, u64> + Send + Sync>(
+        &self,
+        role: R,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let admin = self.admin();
+        let role_id = role
+            .name()?
+            .id::<Role, _>(&admin)?
+            .ok_or(bonsaidb_core::Error::RoleNotFound)?;
+        self.check_permission(
+            role_resource_name(role_id),
+            &BonsaiAction::Server(ServerAction::DeleteRole),
+        )?;
+        self.instance.delete_role(role_id)
+    }
+
+    fn list_sessions<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<Vec<SessionInfo>, bonsaidb_core::Error> {
+        let admin = self.admin();
+        let user = user.name()?;
+        let user_id = user
+            .id::<User, _>(&admin)?
+            .ok_or(bonsaidb_core::Error::UserNotFound)?;
+        self.check_permission(
+            user_resource_name(user_id),
+            &BonsaiAction::Server(ServerAction::ListSessions),
+        )?;
+        self.instance.list_sessions(user)
+    }
+
+    fn revoke_session(&self, session_id: SessionId) -> Result<(), bonsaidb_core::Error> {
+        // Any session is allowed to revoke itself.
+        let is_current_session = self
+            .effective_session
+            .as_ref()
+            .map_or(false, |session| session.id == Some(session_id));
+        if !is_current_session {
+            let identity = self
+                .instance
+                .data
+                .sessions
+                .read()
+                .sessions
+                .get(&session_id)
+                .ok_or(bonsaidb_core::Error::SessionExpired)?
+                .session
+                .lock()
+                .identity()
+                .cloned();
+            let resource_name = match identity {
+                Some(Identity::User { id, .. }) => user_resource_name(id),
+                Some(Identity::Role { id, .. }) => role_resource_name(id),
+                _ => bonsaidb_resource_name(),
+            };
+            self.check_permission(
+                resource_name,
+                &BonsaiAction::Server(ServerAction::RevokeSession),
+            )?;
+        }
+        self.instance.revoke_session(session_id)
+    }
 }
 
 #[test]
@@ -1564,6 +2934,10 @@ impl nebari::Vault for TreeVault {
                 None => decrypted.into_owned(),
             });
         }
+        // Payloads without a header were either encrypted before headers were
+        // introduced or written before encryption was enabled. Plaintext
+        // payloads are returned unchanged until `Database::reencrypt()`
+        // rewrites them.
         self.vault.decrypt_payload(payload, None)
     }
 }
@@ -1579,6 +2953,11 @@ pub trait StorageNonBlocking: Sized {
     /// authentication session. This call will only succeed if there is no
     /// current session.
     fn assume_session(&self, session: Session) -> Result<Self, bonsaidb_core::Error>;
+
+    /// Returns an error if the authenticated session `session_id` has expired,
+    /// been logged out, or been revoked. Otherwise, the session is recorded as
+    /// having been used.
+    fn check_session(&self, session_id: SessionId) -> Result<(), bonsaidb_core::Error>;
 }
 
 impl StorageNonBlocking for Storage {
@@ -1592,22 +2971,16 @@ impl StorageNonBlocking for Storage {
             return Err(bonsaidb_core::Error::InvalidCredentials);
         }
 
-        let Some(session_id) = session.id
-            else {
-                return Ok(Self {
-                    instance: self.instance.clone(),
-                    authentication: None,
-                    effective_session: Some(Arc::new(session)),
-                })
-            };
-
-        let session_data = self.instance.data.sessions.read();
-        // TODO better error
-        let authentication = session_data
-            .sessions
-            .get(&session_id)
-            .ok_or(bonsaidb_core::Error::InvalidCredentials)?;
+        let Some(session_id) = session.id else {
+            return Ok(Self {
+                instance: self.instance.clone(),
+                authentication: None,
+                effective_session: Some(Arc::new(session)),
+                deadline: self.deadline,
+            });
+        };
 
+        let authentication = self.instance.data.active_session(session_id)?;
         let authentication_session = authentication.session.lock();
         let effective_permissions =
             Permissions::merged([&session.permissions, &authentication_session.permissions]);
@@ -1621,8 +2994,13 @@ impl StorageNonBlocking for Storage {
             instance: self.instance.clone(),
             authentication: Some(authentication.clone()),
             effective_session: Some(Arc::new(effective_session)),
+            deadline: self.deadline,
         })
     }
+
+    fn check_session(&self, session_id: SessionId) -> Result<(), bonsaidb_core::Error> {
+        self.instance.data.active_session(session_id).map(drop)
+    }
 }
 
 #[cfg(all(feature = "compression", not(feature = "encryption")))]
@@ -1,12 +1,15 @@
+use std::collections::BTreeMap;
 use std::fs::DirEntry;
 use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
 
 use bonsaidb_core::connection::{LowLevelConnection, Range, Sort, StorageConnection};
-use bonsaidb_core::document::DocumentId;
+use bonsaidb_core::document::{DocumentId, Revision};
+use bonsaidb_core::keyvalue::KeyValue;
 use bonsaidb_core::schema::{Collection, Qualified, SchemaName};
 use bonsaidb_core::transaction::{Operation, Transaction};
 use bonsaidb_core::{admin, AnyError};
+use serde::{Deserialize, Serialize};
 
 use crate::database::keyvalue::Entry;
 use crate::database::DatabaseNonBlocking;
@@ -51,9 +54,102 @@ pub trait BackupLocation: Send + Sync {
     ) -> Result<Vec<u8>, Self::Error>;
 }
 
+/// Options controlling what [`Storage::backup_with()`] stores.
+#[derive(Clone, Copy, Debug)]
+#[must_use]
+pub struct BackupOptions {
+    /// If true, each database's key-value store is included in the backup.
+    /// Default value is `true`.
+    pub key_value_store: bool,
+    /// If true, each database's list of executed transactions is included in
+    /// the backup. The transaction log is stored for auditing purposes: it is
+    /// not restored by [`Storage::restore()`], and the restored databases
+    /// record their own transactions. Default value is `false`.
+    pub transaction_log: bool,
+}
+
+impl Default for BackupOptions {
+    fn default() -> Self {
+        Self {
+            key_value_store: true,
+            transaction_log: false,
+        }
+    }
+}
+
+impl BackupOptions {
+    /// Sets [`Self::key_value_store`] to `include` and returns self.
+    pub const fn include_key_value_store(mut self, include: bool) -> Self {
+        self.key_value_store = include;
+        self
+    }
+
+    /// Sets [`Self::transaction_log`] to `include` and returns self.
+    pub const fn include_transaction_log(mut self, include: bool) -> Self {
+        self.transaction_log = include;
+        self
+    }
+}
+
+/// Options controlling how [`Storage::restore_with()`] restores a backup.
+#[derive(Clone, Copy, Debug)]
+#[must_use]
+pub struct RestoreOptions {
+    /// If true, each database's key-value store is restored. Default value is
+    /// `true`.
+    pub key_value_store: bool,
+    /// The maximum number of documents restored in each transaction, and the
+    /// number of key-value entries restored between each checkpoint. Default
+    /// value is 1,000.
+    pub batch_size: usize,
+    /// If true, a restore that was interrupted continues from its last
+    /// checkpoint. If false, any previous progress is discarded and the
+    /// restore starts over. Default value is `true`.
+    pub resume: bool,
+}
+
+impl Default for RestoreOptions {
+    fn default() -> Self {
+        Self {
+            key_value_store: true,
+            batch_size: 1_000,
+            resume: true,
+        }
+    }
+}
+
+impl RestoreOptions {
+    /// Sets [`Self::key_value_store`] to `include` and returns self.
+    pub const fn include_key_value_store(mut self, include: bool) -> Self {
+        self.key_value_store = include;
+        self
+    }
+
+    /// Sets [`Self::batch_size`] to `batch_size` and returns self.
+    pub const fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Sets [`Self::resume`] to `resume` and returns self.
+    pub const fn resume(mut self, resume: bool) -> Self {
+        self.resume = resume;
+        self
+    }
+}
+
 impl Storage {
     /// Stores a copy of all data in this instance to `location`.
     pub fn backup<L: AnyBackupLocation>(&self, location: &L) -> Result<(), Error> {
+        self.backup_with(location, BackupOptions::default())
+    }
+
+    /// Stores a copy of the data selected by `options` to `location`.
+    pub fn backup_with<L: AnyBackupLocation>(
+        &self,
+        location: &L,
+        options: BackupOptions,
+    ) -> Result<(), Error> {
         let databases = {
             self.instance
                 .data
@@ -68,7 +164,7 @@ impl Storage {
             let database = self
                 .instance
                 .database_without_schema(&name, Some(self), None)?;
-            Self::backup_database(&database, location)?;
+            Self::backup_database(&database, location, options)?;
         }
 
         Ok(())
@@ -76,32 +172,63 @@ impl Storage {
 
     /// Restores all data from a previously stored backup `location`.
     pub fn restore<L: AnyBackupLocation>(&self, location: &L) -> Result<(), Error> {
-        for schema in location
+        self.restore_with(location, RestoreOptions::default())
+    }
+
+    /// Restores the data selected by `options` from a previously stored backup
+    /// `location`.
+    ///
+    /// Documents are restored in transactions of up to
+    /// [`RestoreOptions::batch_size`] documents. After each batch, the
+    /// progress of the restore is recorded in the admin database. If the
+    /// restore is interrupted, calling this function again with the same
+    /// `location` continues from the last recorded batch rather than starting
+    /// over. The progress is removed once the restore completes.
+    pub fn restore_with<L: AnyBackupLocation>(
+        &self,
+        location: &L,
+        options: RestoreOptions,
+    ) -> Result<(), Error> {
+        let mut checkpoint = RestoreCheckpoint::load(self.admin(), options.resume)?;
+        let mut schemas = location
             .list_schemas()
-            .map_err(|err| Error::Backup(Box::new(err)))?
-        {
-            for database in location
+            .map_err(|err| Error::Backup(Box::new(err)))?;
+        schemas.sort();
+        for schema in schemas {
+            let mut databases = location
                 .list_databases(&schema)
-                .map_err(|err| Error::Backup(Box::new(err)))?
-            {
+                .map_err(|err| Error::Backup(Box::new(err)))?;
+            databases.sort();
+            for database in databases {
+                if checkpoint.database_restored(&database) {
+                    continue;
+                }
+
                 // The admin database is already going to be created by the process of creating a database.
                 self.create_database_with_schema(&database, schema.clone(), true)?;
 
                 let database =
                     self.instance
                         .database_without_schema(&database, Some(self), None)?;
-                Self::restore_database(&database, location)?;
+                Self::restore_database(&database, location, options, &mut checkpoint)?;
             }
         }
 
-        Ok(())
+        checkpoint.finish()
     }
 
     pub(crate) fn backup_database(
         database: &Database,
         location: &dyn AnyBackupLocation,
+        options: BackupOptions,
     ) -> Result<(), Error> {
         let schema = database.schematic().name.clone();
+        let mut manifest = BackupManifest::default();
+        let mut store = |container: &str, name: &str, object: &[u8]| -> Result<(), Error> {
+            location.store(&schema, database.name(), container, name, object)?;
+            manifest.record(container, name, object);
+            Ok(())
+        };
         for collection in database.schematic().collections() {
             let documents = database.list_from_collection(
                 Range::from(..),
@@ -112,34 +239,59 @@ impl Storage {
             let collection_name = collection.encoded();
             // TODO consider how to best parallelize -- perhaps a location can opt into parallelization?
             for document in documents {
-                location.store(
-                    &schema,
-                    database.name(),
+                store(
                     &collection_name,
                     &document.header.id.to_string(),
                     &document.contents,
                 )?;
             }
+        }
+
+        if options.key_value_store {
             for ((namespace, key), entry) in database.all_key_value_entries()? {
                 let full_name = format!("{}._key._{key}", namespace.as_deref().unwrap_or(""));
-                location.store(
-                    &schema,
-                    database.name(),
-                    "_kv",
-                    &full_name,
-                    &pot::to_vec(&entry)?,
-                )?;
+                store("_kv", &full_name, &pot::to_vec(&entry)?)?;
+            }
+        }
+
+        if options.transaction_log {
+            let mut starting_id = None;
+            loop {
+                let transactions = database.list_executed_transactions(starting_id, None)?;
+                let Some(last) = transactions.last() else {
+                    break;
+                };
+                starting_id = Some(last.id + 1);
+                for transaction in &transactions {
+                    store(
+                        "_transactions",
+                        // Zero-padding keeps the names in transaction order.
+                        &format!("{:020}", transaction.id),
+                        &pot::to_vec(transaction)?,
+                    )?;
+                }
             }
         }
+
+        // The manifest is stored last, which means a backup that was
+        // interrupted has no manifest.
+        location.store(
+            &schema,
+            database.name(),
+            MANIFEST_CONTAINER,
+            MANIFEST_NAME,
+            &pot::to_vec(&manifest)?,
+        )?;
         Ok(())
     }
 
     pub(crate) fn restore_database(
         database: &Database,
         location: &dyn AnyBackupLocation,
+        options: RestoreOptions,
+        checkpoint: &mut RestoreCheckpoint,
     ) -> Result<(), Error> {
         let schema = database.schematic().name.clone();
-        let mut transaction = Transaction::new();
         // Restore all the collections. However, there's one collection we don't
         // want to restore: the Databases list. This will be recreated during
         // the process of restoring the backup, so we skip it.
@@ -151,36 +303,193 @@ impl Storage {
             .filter(|c| c != &database_collection)
         {
             let collection_name = collection.encoded();
-            for (id, id_string) in location
-                .list_stored(&schema, database.name(), &collection_name)?
-                .into_iter()
-                .filter_map(|id_string| {
-                    id_string
-                        .parse::<DocumentId>()
-                        .ok()
-                        .map(|id| (id, id_string))
-                })
-            {
-                let contents =
-                    location.load(&schema, database.name(), &collection_name, &id_string)?;
-                transaction.push(Operation::insert(collection.clone(), Some(id), contents));
-            }
+            let stored = location.list_stored(&schema, database.name(), &collection_name)?;
+            checkpoint.restore_container(
+                database.name(),
+                &collection_name,
+                stored,
+                options.batch_size,
+                |id_strings, retrying| {
+                    let mut transaction = Transaction::new();
+                    for (id, id_string) in id_strings.iter().filter_map(|id_string| {
+                        id_string
+                            .parse::<DocumentId>()
+                            .ok()
+                            .map(|id| (id, id_string))
+                    }) {
+                        let contents =
+                            location.load(&schema, database.name(), &collection_name, id_string)?;
+                        // A batch being retried may have been applied before
+                        // its checkpoint was recorded.
+                        transaction.push(if retrying {
+                            Operation::overwrite(collection.clone(), id, contents)
+                        } else {
+                            Operation::insert(collection.clone(), Some(id), contents)
+                        });
+                    }
+                    if !transaction.operations.is_empty() {
+                        database.apply_transaction(transaction)?;
+                    }
+                    Ok(())
+                },
+            )?;
         }
-        database.apply_transaction(transaction)?;
-
-        for full_key in location.list_stored(&schema, database.name(), "_kv")? {
-            if let Some((namespace, key)) = full_key.split_once("._key._") {
-                let entry = location.load(&schema, database.name(), "_kv", &full_key)?;
-                let entry = pot::from_slice::<Entry>(&entry)?;
-                let namespace = if namespace.is_empty() {
-                    None
-                } else {
-                    Some(namespace.to_string())
-                };
-                entry.restore(namespace, key.to_string(), database)?;
+
+        if options.key_value_store {
+            let stored = location.list_stored(&schema, database.name(), "_kv")?;
+            checkpoint.restore_container(
+                database.name(),
+                "_kv",
+                stored,
+                options.batch_size,
+                |full_keys, _| {
+                    for full_key in full_keys {
+                        if let Some((namespace, key)) = full_key.split_once("._key._") {
+                            let entry = location.load(&schema, database.name(), "_kv", full_key)?;
+                            let entry = pot::from_slice::<Entry>(&entry)?;
+                            let namespace = if namespace.is_empty() {
+                                None
+                            } else {
+                                Some(namespace.to_string())
+                            };
+                            entry.restore(namespace, key.to_string(), database)?;
+                        }
+                    }
+                    Ok(())
+                },
+            )?;
+        }
+
+        checkpoint.database_finished(database.name())
+    }
+}
+
+/// The container that each database's [`BackupManifest`] is stored in.
+pub(crate) const MANIFEST_CONTAINER: &str = "_manifest";
+/// The name of each database's [`BackupManifest`].
+pub(crate) const MANIFEST_NAME: &str = "manifest";
+
+/// The objects stored in a database's backup, used to verify the backup's
+/// completeness and integrity.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct BackupManifest {
+    /// The SHA-256 digest of each stored object, keyed by container and name.
+    pub containers: BTreeMap<String, BTreeMap<String, [u8; 32]>>,
+}
+
+impl BackupManifest {
+    fn record(&mut self, container: &str, name: &str, object: &[u8]) {
+        self.containers
+            .entry(container.to_string())
+            .or_default()
+            .insert(name.to_string(), Revision::new(object).sha256);
+    }
+}
+
+/// The key in the admin database's key-value store that the progress of a
+/// restore is recorded in.
+const RESTORE_PROGRESS_KEY: &str = "_restore_progress";
+
+/// The progress of a restore, recorded after each batch so that an
+/// interrupted restore can resume.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RestoreProgress {
+    databases: BTreeMap<String, DatabaseRestoreProgress>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct DatabaseRestoreProgress {
+    /// The name of the last object restored from each container. Objects are
+    /// restored in ascending order of their names.
+    containers: BTreeMap<String, String>,
+    restored: bool,
+}
+
+pub(crate) struct RestoreCheckpoint {
+    admin: Database,
+    progress: RestoreProgress,
+}
+
+impl RestoreCheckpoint {
+    fn load(admin: Database, resume: bool) -> Result<Self, Error> {
+        let progress = if resume {
+            admin
+                .get_key(RESTORE_PROGRESS_KEY)
+                .into::<RestoreProgress>()?
+                .unwrap_or_default()
+        } else {
+            RestoreProgress::default()
+        };
+        Ok(Self { admin, progress })
+    }
+
+    fn database_restored(&self, database: &str) -> bool {
+        self.progress
+            .databases
+            .get(database)
+            .map_or(false, |progress| progress.restored)
+    }
+
+    /// Restores the objects named `names` from `container` in batches of up to
+    /// `batch_size` using `restore_batch`, skipping the objects that a previous
+    /// attempt has already restored. The second parameter of `restore_batch`
+    /// is true if the batch may have already been restored.
+    fn restore_container<F: FnMut(&[String], bool) -> Result<(), Error>>(
+        &mut self,
+        database: &str,
+        container: &str,
+        mut names: Vec<String>,
+        batch_size: usize,
+        mut restore_batch: F,
+    ) -> Result<(), Error> {
+        names.sort();
+        let last_restored = self
+            .progress
+            .databases
+            .get(database)
+            .and_then(|progress| progress.containers.get(container));
+        let mut retrying = false;
+        if let Some(last_restored) = last_restored {
+            names.retain(|name| name > last_restored);
+            retrying = true;
+        }
+
+        for batch in names.chunks(batch_size.max(1)) {
+            restore_batch(batch, retrying)?;
+            retrying = false;
+            if let Some(last) = batch.last() {
+                self.progress
+                    .databases
+                    .entry(database.to_string())
+                    .or_default()
+                    .containers
+                    .insert(container.to_string(), last.clone());
+                self.save()?;
             }
         }
+        Ok(())
+    }
+
+    fn database_finished(&mut self, database: &str) -> Result<(), Error> {
+        let progress = self
+            .progress
+            .databases
+            .entry(database.to_string())
+            .or_default();
+        progress.restored = true;
+        progress.containers.clear();
+        self.save()
+    }
 
+    fn save(&self) -> Result<(), Error> {
+        self.admin
+            .set_key(RESTORE_PROGRESS_KEY, &self.progress)
+            .execute()?;
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), Error> {
+        self.admin.delete_key(RESTORE_PROGRESS_KEY)?;
         Ok(())
     }
 }
@@ -441,13 +750,17 @@ fn container_folder(
 
 #[cfg(test)]
 mod tests {
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
     use bonsaidb_core::connection::{Connection as _, StorageConnection as _};
     use bonsaidb_core::keyvalue::KeyValue;
-    use bonsaidb_core::schema::SerializedCollection;
+    use bonsaidb_core::schema::{Collection, Qualified, Schema, SchemaName, SerializedCollection};
     use bonsaidb_core::test_util::{Basic, TestDirectory};
 
+    use super::BackupLocation;
     use crate::config::{Builder, KeyValuePersistence, PersistenceThreshold, StorageConfiguration};
-    use crate::Storage;
+    use crate::{BackupOptions, BackupProblem, RestoreOptions, Storage};
 
     #[test]
     fn backup_restore() -> anyhow::Result<()> {
@@ -496,4 +809,206 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn backup_options() -> anyhow::Result<()> {
+        let backup_destination = TestDirectory::new("backup-options.bonsaidb.backup");
+
+        {
+            let database_directory = TestDirectory::new("backup-options.bonsaidb");
+            let storage = Storage::open(
+                StorageConfiguration::new(&database_directory).with_schema::<Basic>()?,
+            )?;
+
+            let db = storage.create_database::<Basic>("basic", false)?;
+            db.collection::<Basic>().push(&Basic::new("somevalue"))?;
+            db.set_numeric_key("key1", 1_u64).execute()?;
+
+            storage.backup_with(
+                &backup_destination.0,
+                BackupOptions::default()
+                    .include_key_value_store(false)
+                    .include_transaction_log(true),
+            )?;
+        }
+
+        let schema = Basic::schema_name();
+        assert!(backup_destination
+            .0
+            .list_stored(&schema, "basic", "_kv")?
+            .is_empty());
+        assert!(!backup_destination
+            .0
+            .list_stored(&schema, "basic", "_transactions")?
+            .is_empty());
+
+        let database_directory = TestDirectory::new("backup-options.bonsaidb");
+        let restored_storage =
+            Storage::open(StorageConfiguration::new(&database_directory).with_schema::<Basic>()?)?;
+        restored_storage.restore(&backup_destination.0)?;
+
+        let db = restored_storage.database::<Basic>("basic")?;
+        assert_eq!(Basic::all(&db).count()?, 1);
+        assert_eq!(db.get_key("key1").into_u64()?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn verify_backup() -> anyhow::Result<()> {
+        let backup_destination = TestDirectory::new("verify-backup.bonsaidb.backup");
+        let database_directory = TestDirectory::new("verify-backup.bonsaidb");
+        let storage =
+            Storage::open(StorageConfiguration::new(&database_directory).with_schema::<Basic>()?)?;
+
+        let db = storage.create_database::<Basic>("basic", false)?;
+        let doc = db.collection::<Basic>().push(&Basic::new("somevalue"))?;
+        db.set_numeric_key("key1", 1_u64).execute()?;
+        storage.backup(&backup_destination.0)?;
+
+        let report = storage.verify_backup(&backup_destination.0)?;
+        assert!(report.is_valid(), "{report:?}");
+        assert_eq!(report.databases_verified, 2);
+
+        // Corrupt the document and remove the key-value entries.
+        let schema = Basic::schema_name();
+        let collection = Basic::collection_name().encoded();
+        backup_destination.0.store(
+            &schema,
+            "basic",
+            &collection,
+            &doc.id.to_string(),
+            b"corrupted",
+        )?;
+        std::fs::remove_dir_all(
+            backup_destination
+                .0
+                .join(schema.encoded())
+                .join("basic")
+                .join("_kv"),
+        )?;
+
+        let report = storage.verify_backup(&backup_destination.0)?;
+        let mut problems = report
+            .issues
+            .iter()
+            .map(|issue| (issue.container.as_deref(), &issue.problem))
+            .collect::<Vec<_>>();
+        problems.sort_by_key(|(container, _)| *container);
+        assert_eq!(
+            problems,
+            [
+                (Some("_kv"), &BackupProblem::MissingObject),
+                (Some(collection.as_str()), &BackupProblem::ChecksumMismatch),
+            ]
+        );
+
+        Ok(())
+    }
+
+    /// A backup location that fails after loading a limited number of objects.
+    struct InterruptedLocation {
+        path: PathBuf,
+        loads_remaining: AtomicUsize,
+    }
+
+    impl BackupLocation for InterruptedLocation {
+        type Error = std::io::Error;
+
+        fn store(
+            &self,
+            schema: &SchemaName,
+            database_name: &str,
+            container: &str,
+            name: &str,
+            object: &[u8],
+        ) -> Result<(), Self::Error> {
+            self.path
+                .store(schema, database_name, container, name, object)
+        }
+
+        fn list_schemas(&self) -> Result<Vec<SchemaName>, Self::Error> {
+            self.path.list_schemas()
+        }
+
+        fn list_databases(&self, schema: &SchemaName) -> Result<Vec<String>, Self::Error> {
+            self.path.list_databases(schema)
+        }
+
+        fn list_stored(
+            &self,
+            schema: &SchemaName,
+            database_name: &str,
+            container: &str,
+        ) -> Result<Vec<String>, Self::Error> {
+            self.path.list_stored(schema, database_name, container)
+        }
+
+        fn load(
+            &self,
+            schema: &SchemaName,
+            database_name: &str,
+            container: &str,
+            name: &str,
+        ) -> Result<Vec<u8>, Self::Error> {
+            if self
+                .loads_remaining
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |remaining| {
+                    remaining.checked_sub(1)
+                })
+                .is_err()
+            {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Other,
+                    "backup location interrupted",
+                ));
+            }
+            self.path.load(schema, database_name, container, name)
+        }
+    }
+
+    #[test]
+    fn resumed_restore() -> anyhow::Result<()> {
+        let backup_destination = TestDirectory::new("resumed-restore.bonsaidb.backup");
+
+        {
+            let database_directory = TestDirectory::new("resumed-restore.bonsaidb");
+            let storage = Storage::open(
+                StorageConfiguration::new(&database_directory).with_schema::<Basic>()?,
+            )?;
+
+            let db = storage.create_database::<Basic>("basic", false)?;
+            for i in 0..10 {
+                db.collection::<Basic>()
+                    .push(&Basic::new(format!("value{i}")))?;
+            }
+            db.set_numeric_key("key1", 1_u64).execute()?;
+
+            storage.backup(&backup_destination.0)?;
+        }
+
+        let database_directory = TestDirectory::new("resumed-restore.bonsaidb");
+        let restored_storage =
+            Storage::open(StorageConfiguration::new(&database_directory).with_schema::<Basic>()?)?;
+        let options = RestoreOptions::default().batch_size(3);
+        // Interrupt the restore partway through the documents.
+        assert!(restored_storage
+            .restore_with(
+                &InterruptedLocation {
+                    path: backup_destination.0.clone(),
+                    loads_remaining: AtomicUsize::new(5),
+                },
+                options,
+            )
+            .is_err());
+        let db = restored_storage.database::<Basic>("basic")?;
+        assert_eq!(Basic::all(&db).count()?, 3);
+
+        // Restoring again continues where the interrupted restore stopped.
+        restored_storage.restore_with(&backup_destination.0, options)?;
+        assert_eq!(Basic::all(&db).count()?, 10);
+        assert_eq!(db.get_key("key1").into_u64()?, Some(1));
+
+        Ok(())
+    }
 }
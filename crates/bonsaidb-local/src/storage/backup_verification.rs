@@ -0,0 +1,331 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use bonsaidb_core::admin::{self, Admin};
+use bonsaidb_core::document::{DocumentId, Revision};
+use bonsaidb_core::schema::{
+    Collection, CollectionName, Qualified, Schema, SchemaName, SerializedCollection,
+};
+use bonsaidb_core::transaction::Executed;
+
+use super::backup::{BackupManifest, MANIFEST_CONTAINER, MANIFEST_NAME};
+use crate::database::keyvalue::Entry;
+use crate::storage::AnyBackupLocation;
+use crate::{Error, Storage};
+
+/// The result of verifying a backup using [`Storage::verify_backup()`].
+#[derive(Clone, Debug, Default)]
+pub struct BackupVerificationReport {
+    /// The number of databases found in the backup.
+    pub databases_verified: usize,
+    /// The number of stored objects that were read.
+    pub objects_verified: usize,
+    /// The problems found in the backup.
+    pub issues: Vec<BackupIssue>,
+}
+
+impl BackupVerificationReport {
+    /// Returns true if no problems were found.
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// A problem found while verifying a backup.
+#[derive(Clone, Debug)]
+pub struct BackupIssue {
+    /// The schema of the database with the problem.
+    pub schema: SchemaName,
+    /// The name of the database with the problem.
+    pub database: String,
+    /// The container of the stored object with the problem, if the problem
+    /// concerns a single object.
+    pub container: Option<String>,
+    /// The name of the stored object with the problem, if the problem
+    /// concerns a single object.
+    pub name: Option<String>,
+    /// The problem.
+    pub problem: BackupProblem,
+}
+
+/// A problem with a backup. See [`BackupIssue`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BackupProblem {
+    /// The database's schema is not registered with the storage verifying the
+    /// backup, which means the database can't be restored into it.
+    SchemaNotRegistered,
+    /// The database is recorded in the backup of the admin database, but it
+    /// isn't in the backup.
+    MissingDatabase,
+    /// The backup has no manifest for the database, which means the
+    /// completeness of the database's backup and the checksums of its objects
+    /// can't be verified. Backups that were interrupted, or that were made by
+    /// versions of BonsaiDb that didn't store manifests, have no manifests.
+    MissingManifest,
+    /// The object is listed in the database's manifest, but it isn't in the
+    /// backup.
+    MissingObject,
+    /// The object is in the backup, but it isn't listed in the database's
+    /// manifest.
+    UnexpectedObject,
+    /// The object's contents don't match the checksum recorded in the
+    /// database's manifest.
+    ChecksumMismatch,
+    /// The object could not be loaded or decoded.
+    Unreadable(String),
+}
+
+impl Storage {
+    /// Verifies the completeness and integrity of the backup stored at
+    /// `location` without restoring it, returning a report of the problems
+    /// found.
+    ///
+    /// For each database in the backup, this function:
+    ///
+    /// - checks that the database's schema is registered with this storage.
+    /// - checks that every object listed in the manifest stored by
+    ///   [`Self::backup()`] is present, and that its SHA-256 digest matches
+    ///   the manifest.
+    /// - loads every stored object, and checks that document ids,
+    ///   key-value entries, and transaction log entries can be decoded.
+    /// - decodes every record of the admin database, and checks that every
+    ///   database it lists is present in the backup.
+    ///
+    /// Loading the backup's objects is the only work performed, which makes
+    /// this function suitable for regularly verifying backups.
+    ///
+    /// ## Errors
+    ///
+    /// Errors are only returned when the backup location can't be listed.
+    /// Problems with the backup's contents are returned in the report.
+    pub fn verify_backup<L: AnyBackupLocation>(
+        &self,
+        location: &L,
+    ) -> Result<BackupVerificationReport, Error> {
+        let mut report = BackupVerificationReport::default();
+        let mut backed_up = BTreeSet::new();
+        let mut recorded = Vec::new();
+
+        let mut schemas = location
+            .list_schemas()
+            .map_err(|err| Error::Backup(Box::new(err)))?;
+        schemas.sort();
+        for schema in schemas {
+            let mut databases = location
+                .list_databases(&schema)
+                .map_err(|err| Error::Backup(Box::new(err)))?;
+            databases.sort();
+            for database in databases {
+                let collections = self
+                    .instance
+                    .data
+                    .schemas
+                    .read()
+                    .get(&schema)
+                    .map(|opener| opener.schematic().collections());
+                let mut verifier = DatabaseVerifier {
+                    location,
+                    schema: &schema,
+                    database: &database,
+                    report: &mut report,
+                    recorded: &mut recorded,
+                };
+                verifier.verify(collections)?;
+                backed_up.insert((schema.clone(), database));
+                report.databases_verified += 1;
+            }
+        }
+
+        for record in recorded {
+            if !backed_up.contains(&(record.schema.clone(), record.name.clone())) {
+                report.issues.push(BackupIssue {
+                    schema: record.schema,
+                    database: record.name,
+                    container: None,
+                    name: None,
+                    problem: BackupProblem::MissingDatabase,
+                });
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+struct DatabaseVerifier<'a> {
+    location: &'a dyn AnyBackupLocation,
+    schema: &'a SchemaName,
+    database: &'a str,
+    report: &'a mut BackupVerificationReport,
+    recorded: &'a mut Vec<admin::Database>,
+}
+
+impl<'a> DatabaseVerifier<'a> {
+    fn verify(&mut self, collections: Option<Vec<CollectionName>>) -> Result<(), Error> {
+        let Some(collections) = collections else {
+            self.issue(None, BackupProblem::SchemaNotRegistered);
+            return Ok(());
+        };
+
+        let manifest = self.load_manifest()?;
+        let mut containers = collections
+            .into_iter()
+            .map(|collection| (collection.encoded(), Some(collection)))
+            .collect::<BTreeMap<_, _>>();
+        for container in ["_kv", "_transactions"]
+            .into_iter()
+            .map(String::from)
+            .chain(manifest.iter().flat_map(|m| m.containers.keys().cloned()))
+        {
+            containers.entry(container).or_insert(None);
+        }
+
+        for (container, collection) in containers {
+            let stored = self
+                .location
+                .list_stored(self.schema, self.database, &container)?
+                .into_iter()
+                .collect::<BTreeSet<_>>();
+            let checksums = manifest
+                .as_ref()
+                .map(|manifest| manifest.containers.get(&container));
+            if let Some(Some(checksums)) = checksums {
+                for name in checksums.keys().filter(|name| !stored.contains(*name)) {
+                    self.issue(Some((&container, name)), BackupProblem::MissingObject);
+                }
+            }
+
+            for name in &stored {
+                let checksum = match checksums {
+                    Some(checksums) => {
+                        let checksum = checksums.and_then(|checksums| checksums.get(name));
+                        if checksum.is_none() {
+                            self.issue(Some((&container, name)), BackupProblem::UnexpectedObject);
+                        }
+                        checksum
+                    }
+                    None => None,
+                };
+                self.verify_object(&container, collection.as_ref(), name, checksum);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn load_manifest(&mut self) -> Result<Option<BackupManifest>, Error> {
+        if !self
+            .location
+            .list_stored(self.schema, self.database, MANIFEST_CONTAINER)?
+            .iter()
+            .any(|name| name == MANIFEST_NAME)
+        {
+            self.issue(None, BackupProblem::MissingManifest);
+            return Ok(None);
+        }
+
+        match self
+            .location
+            .load(
+                self.schema,
+                self.database,
+                MANIFEST_CONTAINER,
+                MANIFEST_NAME,
+            )
+            .and_then(|manifest| Ok(pot::from_slice::<BackupManifest>(&manifest)?))
+        {
+            Ok(manifest) => Ok(Some(manifest)),
+            Err(err) => {
+                self.issue(
+                    Some((MANIFEST_CONTAINER, MANIFEST_NAME)),
+                    BackupProblem::Unreadable(err.to_string()),
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    fn verify_object(
+        &mut self,
+        container: &str,
+        collection: Option<&CollectionName>,
+        name: &str,
+        checksum: Option<&[u8; 32]>,
+    ) {
+        let object = match self
+            .location
+            .load(self.schema, self.database, container, name)
+        {
+            Ok(object) => object,
+            Err(err) => {
+                self.issue(
+                    Some((container, name)),
+                    BackupProblem::Unreadable(err.to_string()),
+                );
+                return;
+            }
+        };
+        self.report.objects_verified += 1;
+
+        if checksum.map_or(false, |checksum| &Revision::new(&object).sha256 != checksum) {
+            self.issue(Some((container, name)), BackupProblem::ChecksumMismatch);
+        } else if let Err(err) = self.decode_object(container, collection, name, &object) {
+            self.issue(Some((container, name)), BackupProblem::Unreadable(err));
+        }
+    }
+
+    fn decode_object(
+        &mut self,
+        container: &str,
+        collection: Option<&CollectionName>,
+        name: &str,
+        object: &[u8],
+    ) -> Result<(), String> {
+        if let Some(collection) = collection {
+            name.parse::<DocumentId>().map_err(|err| err.to_string())?;
+            if self.schema == &Admin::schema_name() {
+                self.decode_admin_record(collection, object)
+                    .map_err(|err| err.to_string())?;
+            }
+        } else if container == "_kv" {
+            if !name.contains("._key._") {
+                return Err(format!("invalid key-value entry name: {name}"));
+            }
+            pot::from_slice::<Entry>(object).map_err(|err| err.to_string())?;
+        } else if container == "_transactions" {
+            pot::from_slice::<Executed>(object).map_err(|err| err.to_string())?;
+        }
+        Ok(())
+    }
+
+    fn decode_admin_record(
+        &mut self,
+        collection: &CollectionName,
+        object: &[u8],
+    ) -> Result<(), bonsaidb_core::Error> {
+        if collection == &admin::Database::collection_name() {
+            self.recorded.push(admin::Database::deserialize(object)?);
+        } else if collection == &admin::PermissionGroup::collection_name() {
+            admin::PermissionGroup::deserialize(object)?;
+        } else if collection == &admin::Role::collection_name() {
+            admin::Role::deserialize(object)?;
+        } else if collection == &admin::User::collection_name() {
+            admin::User::deserialize(object)?;
+        } else if collection == &admin::AuthenticationToken::collection_name() {
+            admin::AuthenticationToken::deserialize(object)?;
+        } else if collection == &admin::Webhook::collection_name() {
+            admin::Webhook::deserialize(object)?;
+        }
+        Ok(())
+    }
+
+    fn issue(&mut self, object: Option<(&str, &str)>, problem: BackupProblem) {
+        self.report.issues.push(BackupIssue {
+            schema: self.schema.clone(),
+            database: self.database.to_string(),
+            container: object.map(|(container, _)| container.to_string()),
+            name: object.map(|(_, name)| name.to_string()),
+            problem,
+        });
+    }
+}
@@ -0,0 +1,293 @@
+use std::collections::BTreeMap;
+
+use bonsaidb_core::connection::{
+    AccessPolicy, DatabaseQuery, HasSchema, LowLevelConnection, QueryKey, SerializedQueryKey, Sort,
+    StorageConnection, ViewReadOptions,
+};
+use bonsaidb_core::schema::view;
+use bonsaidb_core::schema::view::map::{self, Map};
+use bonsaidb_core::schema::{SchemaName, Schematic, SerializedView, ViewName};
+use bonsaidb_core::transaction::{Operation, OperationResult, Transaction};
+
+use crate::{DatabaseNonBlocking, Storage};
+
+/// A transaction whose operations are applied to several databases of the same
+/// [`Storage`]. See [`Storage::apply_cross_database_transaction()`].
+#[derive(Clone, Debug, Default)]
+#[must_use]
+pub struct CrossDatabaseTransaction {
+    /// The transaction to apply to each database, keyed by the database's
+    /// name.
+    pub transactions: BTreeMap<String, Transaction>,
+}
+
+impl CrossDatabaseTransaction {
+    /// Returns a new, empty transaction.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an operation to apply to the database named `database`.
+    pub fn push(&mut self, database: impl Into<String>, operation: Operation) {
+        self.transactions
+            .entry(database.into())
+            .or_default()
+            .push(operation);
+    }
+
+    /// Adds an operation to apply to the database named `database` and returns
+    /// self.
+    pub fn with(mut self, database: impl Into<String>, operation: Operation) -> Self {
+        self.push(database, operation);
+        self
+    }
+}
+
+impl Storage {
+    /// Applies `transaction` to each of its databases, returning the results
+    /// of each database's operations keyed by the database's name.
+    ///
+    /// Every database's operations are executed before any of them are
+    /// committed, and the documents they modify stay locked until all
+    /// databases have been committed. If any operation fails, such as due to
+    /// a conflict, no changes are made to any database.
+    ///
+    /// **Committing is best-effort. It is not atomic, and there is no
+    /// two-phase commit.** Each database is stored independently, and the
+    /// databases are committed one at a time, in order of their names. Once a
+    /// database has been committed, its changes are kept regardless of what
+    /// happens to the databases after it:
+    ///
+    /// - If writing a database's commit fails, such as when the disk is full,
+    ///   it and the databases after it are not committed.
+    /// - If a database's commit is written but updating its views afterward
+    ///   fails, the databases after it are still committed.
+    ///
+    /// In either case, unless no database was committed, a
+    /// [`bonsaidb_core::Error::Other`] is returned that lists the databases
+    /// that were and weren't committed. If the process exits while
+    /// committing, the databases whose commits were written keep their
+    /// changes, and nothing records which databases were not committed.
+    ///
+    /// ## Errors
+    ///
+    /// - [`bonsaidb_core::Error::DatabaseNotFound`]: one of the databases
+    ///   does not exist.
+    /// - [`bonsaidb_core::Error::PermissionDenied`]: the session is not
+    ///   allowed to perform one of the operations.
+    pub fn apply_cross_database_transaction(
+        &self,
+        transaction: CrossDatabaseTransaction,
+    ) -> Result<BTreeMap<String, Vec<OperationResult>>, bonsaidb_core::Error> {
+        // Sharded databases may route a transaction to one of their shards.
+        // The transactions are prepared in order of the database they are
+        // applied to, ensuring that two cross-database transactions can't
+        // each wait on the locks held by the other.
+        let mut targets = BTreeMap::new();
+        for (name, mut transaction) in transaction.transactions {
+            let database = self
+                .instance
+                .database_without_schema(&name, Some(self), None)?;
+            let target = database.transaction_target(&mut transaction)?;
+            if targets
+                .insert(target.name().to_string(), (name, target, transaction))
+                .is_some()
+            {
+                return Err(bonsaidb_core::Error::other(
+                    "bonsaidb-local",
+                    "a cross-database transaction can only apply one transaction to each database",
+                ));
+            }
+        }
+
+        let mut prepared = Vec::with_capacity(targets.len());
+        for (name, target, transaction) in targets.into_values() {
+            prepared.push((name, target.prepare_transaction(&transaction)?));
+        }
+
+        // Once a database's changes have been written, they can't be undone.
+        // If a database fails to finish committing after its changes were
+        // written, the remaining databases are still committed. If writing a
+        // database's changes fails, the remaining databases are not committed.
+        let mut results = BTreeMap::new();
+        let mut committed = Vec::new();
+        let mut errors = Vec::new();
+        let mut prepared = prepared.into_iter();
+        while let Some((name, transaction)) = prepared.next() {
+            let target = transaction.database().name().to_string();
+            match transaction.write() {
+                Ok(written) => {
+                    committed.push(name.clone());
+                    match written.finish() {
+                        Ok(database_results) => {
+                            results.insert(name, database_results);
+                        }
+                        Err(err) => errors.push(format!("{target}: {err}")),
+                    }
+                }
+                Err(err) if committed.is_empty() => return Err(bonsaidb_core::Error::from(err)),
+                Err(err) => {
+                    errors.push(format!("{target}: {err}"));
+                    let not_committed = std::iter::once(name)
+                        .chain(prepared.map(|(name, _)| name))
+                        .collect::<Vec<_>>();
+                    return Err(partially_committed(&committed, &not_committed, &errors));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(results)
+        } else {
+            Err(partially_committed(&committed, &[], &errors))
+        }
+    }
+
+    /// Queries the view named `view` in every database whose schema contains
+    /// it, returning the entries of every database merged into a single list
+    /// ordered by key. If `schema` is provided, only databases using `schema`
+    /// are queried.
+    ///
+    /// `order` and `limit` apply to the merged list. Entries with the same key
+    /// are ordered by the name of their database.
+    ///
+    /// ## Errors
+    ///
+    /// - [`bonsaidb_core::Error::PermissionDenied`]: the session is not
+    ///   allowed to list databases or to query the view in one of the
+    ///   databases.
+    pub fn query_view_in_databases_by_name(
+        &self,
+        view: &ViewName,
+        schema: Option<&SchemaName>,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+    ) -> Result<Vec<DatabaseMapping<map::Serialized>>, bonsaidb_core::Error> {
+        self.query_view_in_databases_with(
+            |schematic| schematic.view_by_name(view).ok().map(|_| view.clone()),
+            schema,
+            key,
+            order,
+            limit,
+            access_policy,
+        )
+    }
+
+    /// Queries the view `V` in every database whose schema contains it. See
+    /// [`Self::query_view_in_databases_by_name()`] for more information.
+    pub fn query_view_in_databases<V: SerializedView>(
+        &self,
+        schema: Option<&SchemaName>,
+        key: Option<QueryKey<'_, V::Key>>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+    ) -> Result<Vec<DatabaseMapping<Map<V::Key, V::Value>>>, bonsaidb_core::Error> {
+        let mappings = self.query_view_in_databases_with(
+            |schematic| schematic.view::<V>().ok().map(|view| view.view_name()),
+            schema,
+            key.map(|key| key.serialized()).transpose()?,
+            order,
+            limit,
+            access_policy,
+        )?;
+        mappings
+            .iter()
+            .map(|mapping| {
+                mapping
+                    .deserialized::<V>()
+                    .map_err(bonsaidb_core::Error::from)
+            })
+            .collect()
+    }
+
+    pub(crate) fn query_view_in_databases_with(
+        &self,
+        view_in_schema: impl Fn(&Schematic) -> Option<ViewName>,
+        schema: Option<&SchemaName>,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+    ) -> Result<Vec<DatabaseMapping<map::Serialized>>, bonsaidb_core::Error> {
+        let mut query = DatabaseQuery::default();
+        query.schema = schema.cloned();
+        let mut mappings = Vec::new();
+        for database in self.list_databases_matching(query)? {
+            let database =
+                self.instance
+                    .database_without_schema(&database.name, Some(self), None)?;
+            let Some(view) = view_in_schema(database.schematic()) else {
+                continue;
+            };
+            for mapping in database.query_by_name(
+                &view,
+                key.clone(),
+                order,
+                limit,
+                access_policy,
+                ViewReadOptions::default(),
+            )? {
+                mappings.push(DatabaseMapping {
+                    database: database.name().to_string(),
+                    mapping,
+                });
+            }
+        }
+
+        mappings.sort_by(|a, b| {
+            let key_order = a.mapping.key.cmp(&b.mapping.key);
+            match order {
+                Sort::Ascending => key_order,
+                Sort::Descending => key_order.reverse(),
+            }
+            .then_with(|| a.database.cmp(&b.database))
+        });
+        if let Some(limit) = limit {
+            mappings.truncate(usize::try_from(limit).unwrap_or(usize::MAX));
+        }
+        Ok(mappings)
+    }
+}
+
+/// Returns the error reported when a cross-database transaction was committed
+/// to some, but not all, of its databases, or when a database's commit was
+/// written but could not be finished.
+fn partially_committed(
+    committed: &[String],
+    not_committed: &[String],
+    errors: &[String],
+) -> bonsaidb_core::Error {
+    bonsaidb_core::Error::other(
+        "bonsaidb-local",
+        format!(
+            "cross-database transaction partially committed. These databases were committed: \
+             {committed:?}. These databases were not committed: {not_committed:?}. Errors: {}",
+            errors.join("; ")
+        ),
+    )
+}
+
+/// A view entry returned from querying several databases of the same
+/// [`Storage`]. See [`Storage::query_view_in_databases()`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DatabaseMapping<Mapping> {
+    /// The name of the database containing the entry.
+    pub database: String,
+    /// The view entry.
+    pub mapping: Mapping,
+}
+
+impl DatabaseMapping<map::Serialized> {
+    /// Deserializes this entry's mapping using the view `V`.
+    pub fn deserialized<V: SerializedView>(
+        &self,
+    ) -> Result<DatabaseMapping<Map<V::Key, V::Value>>, view::Error> {
+        Ok(DatabaseMapping {
+            database: self.database.clone(),
+            mapping: self.mapping.deserialized::<V>()?,
+        })
+    }
+}
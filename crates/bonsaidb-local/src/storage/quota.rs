@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use bonsaidb_core::admin::database::ByName;
+use bonsaidb_core::connection::{Connection, HasSession, StorageConnection};
+use bonsaidb_core::permissions::bonsai::{database_resource_name, BonsaiAction, ServerAction};
+
+use crate::config::shard_database_name;
+use crate::storage::StorageInstance;
+use crate::{Error, Storage};
+
+impl Storage {
+    /// Sets the quota of the database named `name` to `quota` bytes. If
+    /// `quota` is `None`, the database's quota is removed.
+    ///
+    /// A database's quota is compared against the size of its files, as
+    /// returned by [`Self::database_size()`]. Once the files have reached the
+    /// quota, transactions that write to the database are rejected with
+    /// [`bonsaidb_core::Error::DatabaseQuotaExceeded`]. Transactions that only
+    /// delete or check documents are still allowed, but the space used by
+    /// deleted documents is only reclaimed once the database is compacted.
+    ///
+    /// The size of memory-only storage is not tracked, which means quotas are
+    /// never exceeded when
+    /// [`memory_only`](crate::config::StorageConfiguration#structfield.memory_only)
+    /// is enabled.
+    ///
+    /// ## Errors
+    ///
+    /// - [`bonsaidb_core::Error::DatabaseNotFound`]: the database does not
+    ///   exist.
+    /// - [`bonsaidb_core::Error::PermissionDenied`]: the session is not
+    ///   allowed to create the database, which is required to change its
+    ///   quota.
+    pub fn set_database_quota(
+        &self,
+        name: &str,
+        quota: Option<u64>,
+    ) -> Result<(), bonsaidb_core::Error> {
+        self.check_permission(
+            database_resource_name(name),
+            &BonsaiAction::Server(ServerAction::CreateDatabase),
+        )?;
+        self.instance.set_database_quota(name, quota)
+    }
+
+    /// Returns the quota of the database named `name`, if one has been set
+    /// using [`Self::set_database_quota()`].
+    #[must_use]
+    pub fn database_quota(&self, name: &str) -> Option<u64> {
+        self.instance.database_quota(name)
+    }
+
+    /// Returns the combined size of the files of the database named `name`,
+    /// including the files of its shards, in bytes.
+    pub fn database_size(&self, name: &str) -> Result<u64, bonsaidb_core::Error> {
+        self.instance
+            .database_size(name)
+            .map_err(bonsaidb_core::Error::from)
+    }
+}
+
+impl StorageInstance {
+    pub(crate) fn set_database_quota(
+        &self,
+        name: &str,
+        quota: Option<u64>,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let admin = self.admin();
+        let Some((_, mut record)) = admin
+            .view::<ByName>()
+            .with_key(&name.to_ascii_lowercase())
+            .query_with_collection_docs()?
+            .documents
+            .into_iter()
+            .next()
+        else {
+            return Err(bonsaidb_core::Error::DatabaseNotFound(name.to_string()));
+        };
+
+        if record.contents.quota != quota {
+            record.contents.quota = quota;
+            record.update(&admin)?;
+        }
+        // The quotas aren't locked while the record is updated, because
+        // applying the update checks the admin database's quota.
+        let mut quotas = self.data.database_quotas.write();
+        match quota {
+            Some(quota) => quotas.insert(record.contents.name, quota),
+            None => quotas.remove(&record.contents.name),
+        };
+        Ok(())
+    }
+
+    pub(crate) fn database_quota(&self, name: &str) -> Option<u64> {
+        self.data.database_quotas.read().get(name).copied()
+    }
+
+    pub(crate) fn database_size(&self, name: &str) -> Result<u64, Error> {
+        let mut size = directory_size(&self.data.path.join(name))?;
+        for shard in self
+            .data
+            .sharding
+            .get(name)
+            .into_iter()
+            .flat_map(HashMap::values)
+            .flat_map(|sharding| &sharding.shards)
+        {
+            let shard_folder = shard
+                .path
+                .clone()
+                .unwrap_or_else(|| self.data.path.join(shard_database_name(name, &shard.name)));
+            size += directory_size(&shard_folder)?;
+        }
+        Ok(size)
+    }
+}
+
+fn directory_size(path: &Path) -> Result<u64, Error> {
+    if !path.is_dir() {
+        return Ok(0);
+    }
+
+    let mut size = 0;
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if metadata.is_dir() {
+            size += directory_size(&entry.path())?;
+        } else {
+            size += metadata.len();
+        }
+    }
+    Ok(size)
+}
@@ -0,0 +1,158 @@
+use std::convert::Infallible;
+use std::slice;
+
+use bonsaidb_core::connection::{LowLevelConnection, StorageConnection};
+use bonsaidb_core::document::DocumentId;
+use bonsaidb_core::schema::CollectionName;
+use bonsaidb_core::transaction::{Operation, Transaction};
+use nebari::tree::{Root, ScanEvaluation};
+
+use crate::database::deduplication::resolve_contents;
+use crate::database::{deserialize_document, document_tree_name, with_document_root};
+use crate::{Database, Error, Storage};
+
+/// The number of recovered documents written in each transaction.
+const RECOVERY_BATCH_SIZE: usize = 1_000;
+
+/// The result of [`Storage::recover_database()`].
+#[derive(Clone, Debug, Default)]
+pub struct RecoveryReport {
+    /// The number of documents copied into the recovered database.
+    pub recovered_documents: usize,
+    /// The documents that could not be read, along with the error that
+    /// occurred.
+    pub lost_documents: Vec<(CollectionName, DocumentId, String)>,
+    /// The collections whose list of documents could not be fully read, along
+    /// with the error that occurred. Documents that couldn't be listed are not
+    /// included in [`Self::lost_documents`].
+    pub incomplete_collections: Vec<(CollectionName, String)>,
+}
+
+impl RecoveryReport {
+    /// Returns true if every document was recovered.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.lost_documents.is_empty() && self.incomplete_collections.is_empty()
+    }
+}
+
+impl Storage {
+    /// Copies every readable document from the database named `name` into a
+    /// new database named `recovered_name`, which is created with the same
+    /// schema.
+    ///
+    /// Documents that can't be read, such as those stored in corrupted chunks,
+    /// are skipped and listed in the returned report. Views are rebuilt from
+    /// the recovered documents, and the key-value store is not copied.
+    ///
+    /// ## Errors
+    ///
+    /// - [`bonsaidb_core::Error::DatabaseNotFound`]: `name` does not exist.
+    /// - [`bonsaidb_core::Error::DatabaseNameAlreadyTaken`]: `recovered_name`
+    ///   already exists.
+    pub fn recover_database(
+        &self,
+        name: &str,
+        recovered_name: &str,
+    ) -> Result<RecoveryReport, Error> {
+        let source = self
+            .instance
+            .database_without_schema(name, Some(self), None)?;
+        let schema = source.schematic().name.clone();
+        self.create_database_with_schema(recovered_name, schema.clone(), false)?;
+        let destination =
+            self.instance
+                .database_without_schema(recovered_name, Some(self), Some(schema))?;
+
+        let mut report = RecoveryReport::default();
+        for collection in source.schematic().collections() {
+            with_document_root!(source.data.schema, &collection, |R| {
+                recover_collection::<R>(&source, &destination, &collection, &mut report)?;
+            });
+        }
+        Ok(report)
+    }
+}
+
+fn recover_collection<R: Root>(
+    source: &Database,
+    destination: &Database,
+    collection: &CollectionName,
+    report: &mut RecoveryReport,
+) -> Result<(), Error> {
+    let documents = match source
+        .collection_tree::<R, _>(collection, document_tree_name(collection))
+        .and_then(|tree| Ok(source.roots().tree(tree)?))
+    {
+        Ok(documents) => documents,
+        Err(err) => {
+            report
+                .incomplete_collections
+                .push((collection.clone(), err.to_string()));
+            return Ok(());
+        }
+    };
+
+    // If the tree's index is damaged, the scan stops at the first unreadable
+    // node. The ids listed before the error can still be recovered.
+    let mut ids = Vec::new();
+    if let Err(err) = documents.scan::<Infallible, _, _, _, _>(
+        &(..),
+        true,
+        |_, _, _| ScanEvaluation::ReadData,
+        |key, _| {
+            ids.push(key.clone());
+            ScanEvaluation::Skip
+        },
+        |_, _, _| Ok(()),
+    ) {
+        report
+            .incomplete_collections
+            .push((collection.clone(), err.to_string()));
+    }
+
+    let mut transaction = Transaction::new();
+    for id in ids {
+        let document = documents
+            .get(&id)
+            .map_err(Error::from)
+            .and_then(|document| {
+                document
+                    .map(|document| -> Result<_, Error> {
+                        let mut document = deserialize_document(&document)?.into_owned();
+                        resolve_contents(source, collection, slice::from_mut(&mut document))?;
+                        Ok(document.contents.into_vec())
+                    })
+                    .transpose()
+            });
+        match document {
+            Ok(Some(contents)) => {
+                transaction.push(Operation::overwrite(
+                    collection.clone(),
+                    DocumentId::try_from(id.as_slice())?,
+                    contents,
+                ));
+                if transaction.operations.len() == RECOVERY_BATCH_SIZE {
+                    report.recovered_documents += transaction.operations.len();
+                    destination.apply_transaction(std::mem::take(&mut transaction))?;
+                }
+            }
+            // The document was deleted while recovering.
+            Ok(None) => {}
+            Err(err) => {
+                report.lost_documents.push((
+                    collection.clone(),
+                    DocumentId::try_from(id.as_slice())?,
+                    err.to_string(),
+                ));
+            }
+        }
+    }
+
+    if !transaction.operations.is_empty() {
+        report.recovered_documents += transaction.operations.len();
+        destination.apply_transaction(transaction)?;
+    }
+
+    Ok(())
+}
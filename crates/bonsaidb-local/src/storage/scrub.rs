@@ -0,0 +1,190 @@
+use std::convert::Infallible;
+use std::sync::{Arc, Weak};
+use std::time::{Duration, SystemTime};
+
+use bonsaidb_core::schema::CollectionName;
+use nebari::tree::{Root, ScanEvaluation, Unversioned};
+
+use super::{Data, StorageInstance};
+use crate::database::{document_tree_name, with_document_root};
+use crate::views::{
+    view_document_map_tree_name, view_entries_tree_name, view_invalidated_docs_tree_name,
+    view_versions_tree_name,
+};
+use crate::{Database, DatabaseNonBlocking, Error, Storage};
+
+/// The result of reading every tree stored in a [`Storage`] instance.
+#[derive(Clone, Debug)]
+pub struct ScrubReport {
+    /// When the scrub began.
+    pub started_at: SystemTime,
+    /// When the scrub finished.
+    pub finished_at: SystemTime,
+    /// The number of trees that were read.
+    pub trees_scrubbed: usize,
+    /// The trees that could not be read.
+    pub corrupted_trees: Vec<CorruptedTree>,
+}
+
+impl ScrubReport {
+    /// Returns true if no corruption was found.
+    #[must_use]
+    pub fn is_clean(&self) -> bool {
+        self.corrupted_trees.is_empty()
+    }
+}
+
+/// A tree that could not be read while scrubbing.
+#[derive(Clone, Debug)]
+pub struct CorruptedTree {
+    /// The name of the database containing the tree.
+    pub database: String,
+    /// The name of the tree.
+    pub tree: String,
+    /// The error that occurred while reading the tree.
+    pub error: String,
+}
+
+impl Storage {
+    /// Reads the contents of every collection and view tree in every database,
+    /// returning a report of the trees that could not be read.
+    ///
+    /// Reading a tree verifies the checksums of its stored data and, for
+    /// encrypted trees, that the data can be decrypted. This allows corruption
+    /// to be detected before the data is needed. To scrub periodically in the
+    /// background, see
+    /// [`StorageConfiguration::scrub_interval`](crate::config::StorageConfiguration#structfield.scrub_interval).
+    ///
+    /// The most recent report is also available from
+    /// [`Self::last_scrub_report()`].
+    pub fn scrub(&self) -> Result<ScrubReport, Error> {
+        let mut report = ScrubReport {
+            started_at: SystemTime::now(),
+            finished_at: SystemTime::now(),
+            trees_scrubbed: 0,
+            corrupted_trees: Vec::new(),
+        };
+
+        let mut names = self
+            .instance
+            .data
+            .available_databases
+            .read()
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        names.sort();
+        for name in names {
+            let database = match self
+                .instance
+                .database_without_schema(&name, Some(self), None)
+            {
+                Ok(database) => database,
+                // The database was deleted after the names were listed, or its
+                // schema isn't registered, which means its trees can't be
+                // opened.
+                Err(Error::Core(
+                    bonsaidb_core::Error::DatabaseNotFound(_)
+                    | bonsaidb_core::Error::SchemaNotRegistered(_),
+                )) => continue,
+                Err(err) => return Err(err),
+            };
+            scrub_database(&database, &mut report);
+        }
+
+        report.finished_at = SystemTime::now();
+        *self.instance.data.last_scrub_report.lock() = Some(report.clone());
+        Ok(report)
+    }
+
+    /// Returns the report of the most recently completed scrub, if any.
+    #[must_use]
+    pub fn last_scrub_report(&self) -> Option<ScrubReport> {
+        self.instance.data.last_scrub_report.lock().clone()
+    }
+}
+
+fn scrub_database(database: &Database, report: &mut ScrubReport) {
+    let schema = database.data.schema.clone();
+    for collection in schema.collections() {
+        with_document_root!(schema, &collection, |R| scrub_tree::<R>(
+            database,
+            &collection,
+            document_tree_name(&collection),
+            report,
+        ));
+        scrub_tree::<Unversioned>(
+            database,
+            &collection,
+            view_versions_tree_name(&collection),
+            report,
+        );
+        for view in schema.views_in_collection(&collection).unwrap_or_default() {
+            let view_name = view.view_name();
+            for tree in [
+                view_entries_tree_name(&view_name),
+                view_document_map_tree_name(&view_name),
+                view_invalidated_docs_tree_name(&view_name),
+            ] {
+                scrub_tree::<Unversioned>(database, &collection, tree, report);
+            }
+        }
+    }
+}
+
+fn scrub_tree<R: Root>(
+    database: &Database,
+    collection: &CollectionName,
+    tree_name: String,
+    report: &mut ScrubReport,
+) {
+    let result = database
+        .collection_tree::<R, _>(collection, tree_name.clone())
+        .and_then(|tree| Ok(database.roots().tree(tree)?))
+        .and_then(|tree| {
+            tree.scan::<Infallible, _, _, _, _>(
+                &(..),
+                true,
+                |_, _, _| ScanEvaluation::ReadData,
+                |_, _| ScanEvaluation::ReadData,
+                |_, _, _| Ok(()),
+            )
+            .map_err(Error::from)
+        });
+
+    report.trees_scrubbed += 1;
+    if let Err(err) = result {
+        log::error!(
+            "Scrubbing found corruption in tree {tree_name} of database {}: {err}",
+            database.name()
+        );
+        report.corrupted_trees.push(CorruptedTree {
+            database: database.name().to_string(),
+            tree: tree_name,
+            error: err.to_string(),
+        });
+    }
+}
+
+/// Spawns a thread that scrubs the storage every `interval` until the storage
+/// is dropped.
+pub(super) fn spawn_scrubber(data: &Arc<Data>, interval: Duration) {
+    let data = Arc::downgrade(data);
+    std::thread::Builder::new()
+        .name(String::from("bonsaidb-scrubber"))
+        .spawn(move || scrubber_loop(&data, interval))
+        .unwrap();
+}
+
+fn scrubber_loop(data: &Weak<Data>, interval: Duration) {
+    loop {
+        std::thread::sleep(interval);
+        let Some(data) = data.upgrade() else {
+            break;
+        };
+        let storage = Storage::from(StorageInstance { data });
+        if let Err(err) = storage.scrub() {
+            log::error!("Error scrubbing storage: {err}");
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use std::marker::PhantomData;
+
+use bonsaidb_core::connection::{DatabaseQuery, StorageConnection};
+use bonsaidb_core::permissions::bonsai::{BonsaiResource, DatabaseResource};
+use bonsaidb_core::schema::{Nameable, Schema};
+
+use crate::{Database, Storage};
+
+impl Storage {
+    /// Returns a [`Tenants`] that manages databases using `DB` whose names
+    /// begin with `prefix`.
+    pub fn tenants<DB: Schema>(&self, prefix: impl Into<String>) -> Tenants<DB> {
+        Tenants {
+            storage: self.clone(),
+            prefix: prefix.into(),
+            _schema: PhantomData,
+        }
+    }
+}
+
+/// Manages the databases of a multi-tenant application, where each tenant's
+/// data is stored in its own database using the schema `DB`.
+///
+/// Each tenant is identified by an id, which is combined with this type's
+/// prefix to name the tenant's database. For example, the database of the
+/// tenant `acme` managed by `storage.tenants::<MySchema>("customers")` is
+/// named `customers.acme`. Tenant ids must be valid database names.
+///
+/// When a tenant is created, a [`PermissionGroup`] with the same name as the
+/// tenant's database is created alongside it, allowing all actions on the
+/// tenant's database. Users are given access to a tenant by being added to
+/// this group using [`Self::add_user()`].
+///
+/// This type is returned from [`Storage::tenants()`].
+///
+/// [`PermissionGroup`]: bonsaidb_core::admin::PermissionGroup
+#[derive(Debug)]
+#[must_use]
+pub struct Tenants<DB> {
+    storage: Storage,
+    prefix: String,
+    _schema: PhantomData<DB>,
+}
+
+impl<DB> Clone for Tenants<DB> {
+    fn clone(&self) -> Self {
+        Self {
+            storage: self.storage.clone(),
+            prefix: self.prefix.clone(),
+            _schema: PhantomData,
+        }
+    }
+}
+
+impl<DB: Schema> Tenants<DB> {
+    /// Returns the prefix of the names of the tenants' databases.
+    #[must_use]
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Returns the name of the database of `tenant`. This is also the name of
+    /// the tenant's permission group.
+    #[must_use]
+    pub fn database_name(&self, tenant: &str) -> String {
+        format!("{}.{tenant}", self.prefix)
+    }
+
+    /// Creates the database and permission group of `tenant`, returning the
+    /// tenant's database.
+    ///
+    /// ## Errors
+    ///
+    /// - [`bonsaidb_core::Error::InvalidDatabaseName`]: the tenant's database
+    ///   name is not a valid database name.
+    /// - [`bonsaidb_core::Error::DatabaseNameAlreadyTaken`]: the tenant
+    ///   already exists.
+    pub fn create(&self, tenant: &str) -> Result<Database, bonsaidb_core::Error> {
+        let name = self.database_name(tenant);
+        let database = self.storage.create_database::<DB>(&name, false)?;
+        if let Err(err) = self.storage.create_permission_group(
+            &name,
+            vec![DatabaseResource::named(&name).allowing_all().into()],
+        ) {
+            drop(self.storage.delete_database(&name));
+            return Err(err);
+        }
+        Ok(database)
+    }
+
+    /// Creates the database and permission group of `tenant` with a quota of
+    /// `quota` bytes, returning the tenant's database. See
+    /// [`Storage::set_database_quota()`] for how quotas are enforced.
+    ///
+    /// ## Errors
+    ///
+    /// - [`bonsaidb_core::Error::InvalidDatabaseName`]: the tenant's database
+    ///   name is not a valid database name.
+    /// - [`bonsaidb_core::Error::DatabaseNameAlreadyTaken`]: the tenant
+    ///   already exists.
+    pub fn create_with_quota(
+        &self,
+        tenant: &str,
+        quota: u64,
+    ) -> Result<Database, bonsaidb_core::Error> {
+        let database = self.create(tenant)?;
+        self.set_quota(tenant, Some(quota))?;
+        Ok(database)
+    }
+
+    /// Returns the database of `tenant`.
+    pub fn database(&self, tenant: &str) -> Result<Database, bonsaidb_core::Error> {
+        self.storage.database::<DB>(&self.database_name(tenant))
+    }
+
+    /// Deletes the database and permission group of `tenant`.
+    pub fn delete(&self, tenant: &str) -> Result<(), bonsaidb_core::Error> {
+        let name = self.database_name(tenant);
+        self.storage.delete_database(&name)?;
+        match self.storage.delete_permission_group(&name) {
+            Ok(()) | Err(bonsaidb_core::Error::PermissionGroupNotFound) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Returns the ids of all tenants, in ascending order.
+    pub fn list(&self) -> Result<Vec<String>, bonsaidb_core::Error> {
+        let prefix = format!("{}.", self.prefix);
+        Ok(self
+            .storage
+            .list_databases_matching(
+                DatabaseQuery::default()
+                    .with_name_prefix(&prefix)
+                    .with_schema(DB::schema_name()),
+            )?
+            .into_iter()
+            .filter_map(|database| database.name.strip_prefix(&prefix).map(ToString::to_string))
+            .collect())
+    }
+
+    /// Gives `user` access to `tenant` by adding the user to the tenant's
+    /// permission group.
+    pub fn add_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        tenant: &str,
+        user: U,
+    ) -> Result<(), bonsaidb_core::Error> {
+        self.storage
+            .add_permission_group_to_user(user, self.database_name(tenant))
+    }
+
+    /// Revokes the access `user` was given to `tenant` by removing the user
+    /// from the tenant's permission group.
+    pub fn remove_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        tenant: &str,
+        user: U,
+    ) -> Result<(), bonsaidb_core::Error> {
+        self.storage
+            .remove_permission_group_from_user(user, self.database_name(tenant))
+    }
+
+    /// Creates a user named `username` with access to `tenant`, returning the
+    /// new user's id.
+    pub fn create_user(&self, tenant: &str, username: &str) -> Result<u64, bonsaidb_core::Error> {
+        let user_id = self.storage.create_user(username)?;
+        self.add_user(tenant, user_id)?;
+        Ok(user_id)
+    }
+
+    /// Sets the quota of `tenant`'s database. See
+    /// [`Storage::set_database_quota()`].
+    pub fn set_quota(&self, tenant: &str, quota: Option<u64>) -> Result<(), bonsaidb_core::Error> {
+        self.storage
+            .set_database_quota(&self.database_name(tenant), quota)
+    }
+
+    /// Returns the size of `tenant`'s database. See
+    /// [`Storage::database_size()`].
+    pub fn size(&self, tenant: &str) -> Result<u64, bonsaidb_core::Error> {
+        self.storage.database_size(&self.database_name(tenant))
+    }
+}
@@ -7,7 +7,6 @@ use bonsaidb_core::connection::{
 use bonsaidb_core::key::time::TimestampAsNanoseconds;
 use bonsaidb_core::permissions::Permissions;
 use bonsaidb_core::schema::SerializedCollection;
-use parking_lot::Mutex;
 use rand::{thread_rng, Rng};
 
 use crate::storage::AuthenticatedSession;
@@ -57,16 +56,14 @@ impl super::StorageInstance {
             },
             permissions: Permissions::default(), /* This session will have no permissions until it finishes token authentication */
         };
-        let authentication = Arc::new(AuthenticatedSession {
-            storage: Arc::downgrade(&self.data),
-            session: Mutex::new(session.clone()),
-        });
+        let authentication = AuthenticatedSession::new(&self.data, session.clone());
         sessions.sessions.insert(session_id, authentication.clone());
 
         Ok(Storage {
             instance: self.clone(),
             authentication: Some(authentication),
             effective_session: Some(Arc::new(session)),
+            deadline: None,
         })
     }
 
@@ -1,6 +1,7 @@
 use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use bonsaidb_core::connection::Connection;
 use bonsaidb_core::keyvalue::Timestamp;
@@ -8,6 +9,8 @@ use bonsaidb_core::schema::{view, CollectionName, ViewName};
 use parking_lot::RwLock;
 
 use crate::database::keyvalue::ExpirationLoader;
+use crate::database::migration::CollectionMigrator;
+use crate::database::time_series::RetentionEnforcer;
 use crate::database::Database;
 use crate::tasks::compactor::Compactor;
 use crate::tasks::handle::Handle;
@@ -29,6 +32,39 @@ mod task;
 
 pub use task::Task;
 
+/// A summary of the background tasks queued or running in a
+/// [`Storage`](crate::Storage).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct BackgroundTasks {
+    /// The number of views being updated.
+    pub view_updates: usize,
+    /// The number of view integrity checks.
+    pub integrity_checks: usize,
+    /// The number of compaction operations.
+    pub compactions: usize,
+    /// The number of databases loading key-value entry expirations.
+    pub key_value_expiration_loads: usize,
+    /// The number of time-series collections having expired partitions
+    /// removed.
+    pub retention_enforcements: usize,
+    /// The number of collections having documents moved from their legacy
+    /// names.
+    pub collection_migrations: usize,
+}
+
+impl BackgroundTasks {
+    /// Returns the total number of background tasks.
+    #[must_use]
+    pub const fn total(&self) -> usize {
+        self.view_updates
+            + self.integrity_checks
+            + self.compactions
+            + self.key_value_expiration_loads
+            + self.retention_enforcements
+            + self.collection_migrations
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TaskManager {
     pub jobs: Manager<Task>,
@@ -41,7 +77,11 @@ type ViewKey = (Arc<Cow<'static, str>>, CollectionName, ViewName);
 pub struct Statuses {
     completed_integrity_checks: HashSet<ViewKey>,
     key_value_expiration_loads: HashSet<Arc<Cow<'static, str>>>,
-    view_update_last_status: HashMap<ViewKey, u64>,
+    view_update_last_status: HashMap<ViewKey, (u64, Instant)>,
+    rebuilding_views: HashSet<ViewKey>,
+    side_by_side_rebuilds: HashSet<ViewKey>,
+    retention_cutoffs: HashMap<(Arc<Cow<'static, str>>, CollectionName), Timestamp>,
+    migrated_collections: HashSet<(Arc<Cow<'static, str>>, CollectionName)>,
 }
 
 impl TaskManager {
@@ -52,6 +92,21 @@ impl TaskManager {
         }
     }
 
+    pub fn summary(&self) -> BackgroundTasks {
+        let mut summary = BackgroundTasks::default();
+        for task in self.jobs.keyed_jobs() {
+            match task {
+                Task::IntegrityScan(_) => summary.integrity_checks += 1,
+                Task::ViewMap(_) => summary.view_updates += 1,
+                Task::Compaction(_) => summary.compactions += 1,
+                Task::ExpirationLoader(_) => summary.key_value_expiration_loads += 1,
+                Task::RetentionEnforcer(..) => summary.retention_enforcements += 1,
+                Task::CollectionMigration(..) => summary.collection_migrations += 1,
+            }
+        }
+        summary
+    }
+
     pub fn update_view_if_needed(
         &self,
         view: &dyn view::Serialized,
@@ -59,10 +114,24 @@ impl TaskManager {
         block_until_updated: bool,
     ) -> Result<(), crate::Error> {
         let view_name = view.view_name();
+        let deadline = database.storage.deadline();
         if let Some(job) = self.spawn_integrity_check(view, database) {
-            job.receive()??;
+            job.receive_before(deadline)??;
         }
 
+        // Views being rebuilt in databases that serve stale views are updated
+        // in the background rather than making the caller wait.
+        let block_until_updated = block_until_updated
+            && !(database
+                .storage
+                .instance
+                .serves_stale_views_while_rebuilding(&database.data.name)
+                && self.view_rebuilding(
+                    database.data.name.clone(),
+                    view.collection(),
+                    view_name.clone(),
+                ));
+
         // If there is no transaction id, there is no data, so the view is "up-to-date"
         if let Some(current_transaction_id) = database.last_transaction_id()? {
             let needs_reindex = {
@@ -70,12 +139,14 @@ impl TaskManager {
                 // they mapped. If that value is current, we don't need to go
                 // through the jobs system at all.
                 let statuses = self.statuses.read();
-                if let Some(last_transaction_indexed) = statuses.view_update_last_status.get(&(
-                    database.data.name.clone(),
-                    view.collection(),
-                    view.view_name(),
-                )) {
-                    last_transaction_indexed < &current_transaction_id
+                if let Some((last_transaction_indexed, _)) =
+                    statuses.view_update_last_status.get(&(
+                        database.data.name.clone(),
+                        view.collection(),
+                        view.view_name(),
+                    ))
+                {
+                    *last_transaction_indexed < current_transaction_id
                 } else {
                     true
                 }
@@ -97,7 +168,7 @@ impl TaskManager {
                         break;
                     }
 
-                    let id = job.receive()??;
+                    let id = job.receive_before(deadline)??;
                     if wait_for_transaction <= id {
                         break;
                     }
@@ -108,6 +179,53 @@ impl TaskManager {
         Ok(())
     }
 
+    /// Returns true if `view` has been updated to include every transaction up
+    /// to and including `transaction_id`.
+    pub fn view_updated_through(
+        &self,
+        view: &dyn view::Serialized,
+        database: &Database,
+        transaction_id: u64,
+    ) -> bool {
+        let statuses = self.statuses.read();
+        statuses
+            .view_update_last_status
+            .get(&(
+                database.data.name.clone(),
+                view.collection(),
+                view.view_name(),
+            ))
+            .map_or(false, |(last_transaction_indexed, _)| {
+                *last_transaction_indexed >= transaction_id
+            })
+    }
+
+    /// Returns true if `view` has been up-to-date at some point within
+    /// `max_staleness`.
+    pub fn view_updated_within(
+        &self,
+        view: &dyn view::Serialized,
+        database: &Database,
+        max_staleness: Duration,
+    ) -> Result<bool, Error> {
+        let Some(current_transaction_id) = database.last_transaction_id()? else {
+            // Without any transactions, there is no data to map.
+            return Ok(true);
+        };
+        let statuses = self.statuses.read();
+        Ok(statuses
+            .view_update_last_status
+            .get(&(
+                database.data.name.clone(),
+                view.collection(),
+                view.view_name(),
+            ))
+            .map_or(false, |(last_transaction_indexed, as_of)| {
+                *last_transaction_indexed >= current_transaction_id
+                    || as_of.elapsed() <= max_staleness
+            }))
+    }
+
     pub fn key_value_expiration_loaded(&self, database: &Arc<Cow<'static, str>>) -> bool {
         let statuses = self.statuses.read();
         statuses.key_value_expiration_loads.contains(database)
@@ -143,6 +261,7 @@ impl TaskManager {
                 scan: IntegrityScan {
                     database: database.data.name.clone(),
                     view_version: view.version(),
+                    view_checksum: view.definition_checksum(),
                     collection: view.collection(),
                     view_name,
                 },
@@ -163,6 +282,82 @@ impl TaskManager {
             .insert((database, collection, view_name));
     }
 
+    pub fn view_rebuilding(
+        &self,
+        database: Arc<Cow<'static, str>>,
+        collection: CollectionName,
+        view_name: ViewName,
+    ) -> bool {
+        let statuses = self.statuses.read();
+        statuses
+            .rebuilding_views
+            .contains(&(database, collection, view_name))
+    }
+
+    pub fn mark_view_rebuilding(
+        &self,
+        database: Arc<Cow<'static, str>>,
+        collection: CollectionName,
+        view_name: ViewName,
+    ) {
+        let mut statuses = self.statuses.write();
+        statuses
+            .rebuilding_views
+            .insert((database, collection, view_name));
+    }
+
+    /// Records that the view has finished being rebuilt, returning true if it
+    /// was being rebuilt.
+    pub fn mark_view_rebuilt(
+        &self,
+        database: Arc<Cow<'static, str>>,
+        collection: CollectionName,
+        view_name: ViewName,
+    ) -> bool {
+        let mut statuses = self.statuses.write();
+        statuses
+            .rebuilding_views
+            .remove(&(database, collection, view_name))
+    }
+
+    pub fn view_rebuilding_side_by_side(
+        &self,
+        database: Arc<Cow<'static, str>>,
+        collection: CollectionName,
+        view_name: ViewName,
+    ) -> bool {
+        let statuses = self.statuses.read();
+        statuses
+            .side_by_side_rebuilds
+            .contains(&(database, collection, view_name))
+    }
+
+    /// Records that the view's replacement entries must be built before its
+    /// existing entries are updated.
+    pub fn mark_view_rebuilding_side_by_side(
+        &self,
+        database: Arc<Cow<'static, str>>,
+        collection: CollectionName,
+        view_name: ViewName,
+    ) {
+        let mut statuses = self.statuses.write();
+        statuses
+            .side_by_side_rebuilds
+            .insert((database, collection, view_name));
+    }
+
+    pub fn mark_side_by_side_rebuild_swapped(
+        &self,
+        database: Arc<Cow<'static, str>>,
+        collection: CollectionName,
+        view_name: ViewName,
+    ) {
+        let mut statuses = self.statuses.write();
+        statuses
+            .side_by_side_rebuilds
+            .remove(&(database, collection, view_name));
+    }
+
     pub fn mark_key_value_expiration_loaded(&self, database: Arc<Cow<'static, str>>) {
         let mut statuses = self.statuses.write();
         statuses.key_value_expiration_loads.insert(database);
@@ -174,11 +369,12 @@ impl TaskManager {
         collection: CollectionName,
         view_name: ViewName,
         transaction_id: u64,
+        as_of: Instant,
     ) {
         let mut statuses = self.statuses.write();
         statuses
             .view_update_last_status
-            .insert((database, collection, view_name), transaction_id);
+            .insert((database, collection, view_name), (transaction_id, as_of));
     }
 
     pub fn spawn_key_value_expiration_loader(
@@ -195,6 +391,78 @@ impl TaskManager {
         }
     }
 
+    /// Removes the partitions of `collection` before `cutoff`, unless they
+    /// have already been removed.
+    pub fn spawn_retention_enforcer(
+        &self,
+        database: &Database,
+        collection: CollectionName,
+        cutoff: Timestamp,
+    ) -> Option<Handle<u64, Error>> {
+        {
+            let mut statuses = self.statuses.write();
+            let enforced = statuses
+                .retention_cutoffs
+                .entry((database.data.name.clone(), collection.clone()))
+                .or_insert(Timestamp::MIN);
+            if *enforced >= cutoff {
+                return None;
+            }
+            *enforced = cutoff;
+        }
+
+        Some(self.jobs.lookup_or_enqueue(RetentionEnforcer {
+            database: database.clone(),
+            collection,
+            cutoff,
+        }))
+    }
+
+    /// Moves the documents stored under `collection`'s legacy names into
+    /// `collection`, unless they have already been moved since the storage
+    /// was opened. Waits for the migration to complete.
+    pub fn migrate_collection(
+        &self,
+        database: &Database,
+        collection: CollectionName,
+    ) -> Result<(), Error> {
+        if self
+            .statuses
+            .read()
+            .migrated_collections
+            .contains(&(database.data.name.clone(), collection.clone()))
+        {
+            return Ok(());
+        }
+
+        let deadline = database.storage.deadline();
+        Ok(self
+            .jobs
+            .lookup_or_enqueue(CollectionMigrator {
+                database: database.clone(),
+                collection,
+            })
+            .receive_before(deadline)??)
+    }
+
+    pub fn mark_collection_migrated(
+        &self,
+        database: Arc<Cow<'static, str>>,
+        collection: CollectionName,
+    ) {
+        let mut statuses = self.statuses.write();
+        statuses.migrated_collections.insert((database, collection));
+    }
+
+    pub fn compact_documents(
+        &self,
+        database: Database,
+        collection: CollectionName,
+    ) -> Handle<(), Error> {
+        let target = compactor::Target::documents(&database, collection);
+        self.spawn_compact_target(database, target)
+    }
+
     pub fn spawn_compact_target(
         &self,
         database: Database,
@@ -204,28 +472,48 @@ impl TaskManager {
             .lookup_or_enqueue(Compactor::target(database, target))
     }
 
+    pub fn spawn_rewrite_target(
+        &self,
+        database: Database,
+        target: compactor::Target,
+    ) -> Handle<(), Error> {
+        self.jobs
+            .lookup_or_enqueue(Compactor::rewrite(database, target))
+    }
+
     pub fn compact_collection(
         &self,
         database: Database,
         collection_name: CollectionName,
     ) -> Result<(), Error> {
+        let deadline = database.storage.deadline();
         Ok(self
             .jobs
             .lookup_or_enqueue(Compactor::collection(database, collection_name))
-            .receive()??)
+            .receive_before(deadline)??)
     }
 
     pub fn compact_key_value_store(&self, database: Database) -> Result<(), Error> {
+        let deadline = database.storage.deadline();
         Ok(self
             .jobs
             .lookup_or_enqueue(Compactor::keyvalue(database))
-            .receive()??)
+            .receive_before(deadline)??)
     }
 
     pub fn compact_database(&self, database: Database) -> Result<(), Error> {
+        let deadline = database.storage.deadline();
         Ok(self
             .jobs
             .lookup_or_enqueue(Compactor::database(database))
-            .receive()??)
+            .receive_before(deadline)??)
+    }
+
+    pub fn rewrite_database(&self, database: Database) -> Result<(), Error> {
+        let deadline = database.storage.deadline();
+        Ok(self
+            .jobs
+            .lookup_or_enqueue(Compactor::rewrite(database, compactor::Target::Database))
+            .receive_before(deadline)??)
     }
 }
@@ -1,12 +1,16 @@
-use std::borrow::Cow;
+use std::convert::Infallible;
 
 use bonsaidb_core::connection::Connection;
 use bonsaidb_core::schema::CollectionName;
-use nebari::tree::{Root, Unversioned, Versioned};
+use nebari::io::any::AnyFile;
+use nebari::tree::{
+    CompareSwap, KeyOperation, Operation, Root, ScanEvaluation, TreeRoot, Unversioned, Versioned,
+};
 
-use crate::database::keyvalue::KEY_TREE;
+use crate::database::deduplication::{document_contents_tree_name, recount_references};
 use crate::database::{document_tree_name, DatabaseNonBlocking};
 use crate::tasks::{Job, Keyed, Task};
+use crate::usage::Activity;
 use crate::views::{
     view_document_map_tree_name, view_entries_tree_name, view_invalidated_docs_tree_name,
     view_versions_tree_name,
@@ -19,12 +23,31 @@ pub struct Compactor {
     pub compaction: Compaction,
 }
 
+/// The number of entries rewritten in each transaction by
+/// [`Compactor::rewrite()`].
+const REWRITE_BATCH_SIZE: usize = 1_000;
+
 impl Compactor {
     pub fn target(database: Database, target: Target) -> Self {
         Self {
             compaction: Compaction {
                 database_name: database.name().to_string(),
                 target,
+                rewrite: false,
+            },
+            database,
+        }
+    }
+
+    /// Returns a compactor that rewrites every entry of `target` through its
+    /// tree's vault before compacting it. This stores entries written before
+    /// the tree's encryption policy was configured using the current policy.
+    pub fn rewrite(database: Database, target: Target) -> Self {
+        Self {
+            compaction: Compaction {
+                database_name: database.name().to_string(),
+                target,
+                rewrite: true,
             },
             database,
         }
@@ -47,35 +70,66 @@ impl Compactor {
 pub struct Compaction {
     database_name: String,
     target: Target,
+    rewrite: bool,
 }
 
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
 pub enum Target {
-    VersionedTree(String),
-    UnversionedTree(String),
+    VersionedTree(CollectionName, String),
+    UnversionedTree(CollectionName, String),
     Collection(CollectionName),
+    DocumentContents(CollectionName),
     KeyValue,
     Database,
 }
 
 impl Target {
-    fn compact(self, database: &Database) -> Result<(), Error> {
+    /// Returns the target for the document tree of `collection`.
+    pub fn documents(database: &Database, collection: CollectionName) -> Self {
+        let tree = document_tree_name(&collection);
+        if database.data.schema.collection_is_versioned(&collection) {
+            Target::VersionedTree(collection, tree)
+        } else {
+            Target::UnversionedTree(collection, tree)
+        }
+    }
+
+    fn compact(self, database: &Database, rewrite: bool) -> Result<(), Error> {
         match self {
-            Target::UnversionedTree(name) => compact_tree::<Unversioned, _>(database, name),
-            Target::VersionedTree(name) => compact_tree::<Versioned, _>(database, name),
+            Target::UnversionedTree(collection, name) => compact_tree(
+                database,
+                database.collection_tree::<Unversioned, _>(&collection, name)?,
+                rewrite,
+            ),
+            Target::VersionedTree(collection, name) => compact_tree(
+                database,
+                database.collection_tree::<Versioned, _>(&collection, name)?,
+                rewrite,
+            ),
+            Target::DocumentContents(collection) => {
+                recount_references(database, &collection)?;
+                compact_tree(
+                    database,
+                    database.collection_tree::<Unversioned, _>(
+                        &collection,
+                        document_contents_tree_name(&collection),
+                    )?,
+                    rewrite,
+                )
+            }
             Target::Collection(collection) => {
                 let mut trees = Vec::new();
                 gather_collection_trees(database, &collection, &mut trees);
-                compact_trees(database, trees)
+                compact_trees(database, trees, rewrite)
             }
-            Target::KeyValue => compact_tree::<Unversioned, _>(database, KEY_TREE),
+            Target::KeyValue => compact_tree(database, database.key_value_tree(), rewrite),
             Target::Database => {
                 let mut trees = Vec::new();
                 for collection in database.schematic().collections() {
                     gather_collection_trees(database, &collection, &mut trees);
                 }
                 trees.push(Target::KeyValue);
-                compact_trees(database, trees)
+                compact_trees(database, trees, rewrite)
             }
         }
     }
@@ -87,7 +141,10 @@ impl Job for Compactor {
 
     #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
     fn execute(&mut self) -> Result<Self::Output, Error> {
-        self.compaction.target.clone().compact(&self.database)
+        self.compaction
+            .target
+            .clone()
+            .compact(&self.database, self.compaction.rewrite)
     }
 }
 
@@ -102,31 +159,45 @@ fn gather_collection_trees(
     collection: &CollectionName,
     trees: &mut Vec<Target>,
 ) {
-    trees.push(Target::VersionedTree(document_tree_name(collection)));
-    trees.push(Target::UnversionedTree(view_versions_tree_name(collection)));
+    trees.push(Target::documents(database, collection.clone()));
+    if database.data.schema.collection_is_deduplicated(collection) {
+        trees.push(Target::DocumentContents(collection.clone()));
+    }
+    trees.push(Target::UnversionedTree(
+        collection.clone(),
+        view_versions_tree_name(collection),
+    ));
 
     if let Some(views) = database.data.schema.views_in_collection(collection) {
         for view in views {
             let name = view.view_name();
-            trees.push(Target::UnversionedTree(view_entries_tree_name(&name)));
-            trees.push(Target::UnversionedTree(view_document_map_tree_name(&name)));
-            trees.push(Target::UnversionedTree(view_invalidated_docs_tree_name(
-                &name,
-            )));
+            trees.push(Target::UnversionedTree(
+                collection.clone(),
+                view_entries_tree_name(&name),
+            ));
+            trees.push(Target::UnversionedTree(
+                collection.clone(),
+                view_document_map_tree_name(&name),
+            ));
+            trees.push(Target::UnversionedTree(
+                collection.clone(),
+                view_invalidated_docs_tree_name(&name),
+            ));
         }
     }
 }
 
-fn compact_trees(database: &Database, targets: Vec<Target>) -> Result<(), Error> {
+fn compact_trees(database: &Database, targets: Vec<Target>, rewrite: bool) -> Result<(), Error> {
     // Enqueue all the jobs
     let handles = targets
         .into_iter()
         .map(|target| {
-            database
-                .storage()
-                .instance
-                .tasks()
-                .spawn_compact_target(database.clone(), target)
+            let tasks = database.storage().instance.tasks();
+            if rewrite {
+                tasks.spawn_rewrite_target(database.clone(), target)
+            } else {
+                tasks.spawn_compact_target(database.clone(), target)
+            }
         })
         .collect::<Vec<_>>();
     // Wait for them to finish.
@@ -136,11 +207,54 @@ fn compact_trees(database: &Database, targets: Vec<Target>) -> Result<(), Error>
     Ok(())
 }
 
-fn compact_tree<R: Root, S: Into<Cow<'static, str>>>(
+/// Compacts the tree described by `root`, opening it with the tree's vault.
+/// If `rewrite` is true, every entry is rewritten through the vault first.
+fn compact_tree<R: Root>(
     database: &Database,
-    name: S,
+    root: TreeRoot<R, AnyFile>,
+    rewrite: bool,
 ) -> Result<(), Error> {
-    let documents = database.roots().tree(R::tree(name))?;
+    let _usage = database.track_usage(Activity::Compacting);
+    if rewrite {
+        rewrite_tree(database, &root)?;
+    }
+    let documents = database.roots().tree(root)?;
     documents.compact()?;
     Ok(())
 }
+
+/// Writes the current value of every entry in the tree described by `root`
+/// back to the tree. Values are encoded by the tree's vault when written, so
+/// entries that were stored before the vault encrypted the tree are stored
+/// encrypted. The previously stored values remain in the tree's file until it
+/// is compacted.
+fn rewrite_tree<R: Root>(database: &Database, root: &TreeRoot<R, AnyFile>) -> Result<(), Error> {
+    let mut keys = Vec::new();
+    database
+        .roots()
+        .tree(root.clone())?
+        .scan::<Infallible, _, _, _, _>(
+            &(..),
+            true,
+            |_, _, _| ScanEvaluation::ReadData,
+            |key, _| {
+                keys.push(key.clone());
+                ScanEvaluation::Skip
+            },
+            |_, _, _| unreachable!("values are never read"),
+        )?;
+
+    for batch in keys.chunks(REWRITE_BATCH_SIZE) {
+        let transaction = database.roots().transaction(&[root.clone()])?;
+        // Entries modified or removed since the keys were scanned are written
+        // with their current value.
+        transaction.tree::<R>(0).unwrap().modify(
+            batch.to_vec(),
+            Operation::CompareSwap(CompareSwap::new(&mut |_, value| {
+                value.map_or(KeyOperation::Skip, KeyOperation::Set)
+            })),
+        )?;
+        transaction.commit()?;
+    }
+    Ok(())
+}
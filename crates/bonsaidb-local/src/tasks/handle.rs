@@ -1,5 +1,6 @@
 use std::fmt::Debug;
 use std::sync::Arc;
+use std::time::Instant;
 
 /// he `Id` of an executing task.
 #[derive(Debug, Hash, Eq, PartialEq, Clone, Copy)]
@@ -27,4 +28,29 @@ where
     pub fn receive(self) -> Result<Result<T, Arc<E>>, flume::RecvError> {
         self.receiver.recv()
     }
+
+    /// Waits for the job to complete and returns the result. If `deadline`
+    /// passes first, [`bonsaidb_core::Error::DeadlineExceeded`] is returned
+    /// and the job continues running in the background.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the job is cancelled or `deadline` passes.
+    pub(crate) fn receive_before(
+        self,
+        deadline: Option<Instant>,
+    ) -> Result<Result<T, Arc<E>>, crate::Error> {
+        match deadline {
+            Some(deadline) => self
+                .receiver
+                .recv_deadline(deadline)
+                .map_err(|err| match err {
+                    flume::RecvTimeoutError::Timeout => {
+                        crate::Error::Core(bonsaidb_core::Error::DeadlineExceeded)
+                    }
+                    flume::RecvTimeoutError::Disconnected => crate::Error::InternalCommunication,
+                }),
+            None => Ok(self.receiver.recv()?),
+        }
+    }
 }
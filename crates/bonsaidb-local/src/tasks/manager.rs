@@ -47,6 +47,19 @@ where
         jobs.lookup_or_enqueue(job, self.clone())
     }
 
+    /// Returns a receiver that is notified once every queued and running job
+    /// has completed, or `None` if there are no outstanding jobs.
+    pub fn idle_notifier(&self) -> Option<flume::Receiver<()>> {
+        let mut jobs = self.jobs.write();
+        jobs.idle_notifier()
+    }
+
+    /// Returns the keys of the keyed jobs that are queued or running.
+    pub fn keyed_jobs(&self) -> Vec<Key> {
+        let jobs = self.jobs.read();
+        jobs.keyed_jobs().cloned().collect()
+    }
+
     fn job_completed<T: Clone + Send + Sync + 'static, E: Send + Sync + 'static>(
         &self,
         id: Id,
@@ -14,6 +14,7 @@ pub struct Jobs<Key> {
     last_task_id: u64,
     result_senders: HashMap<Id, Vec<Box<dyn AnySender>>>,
     keyed_jobs: HashMap<Key, Id>,
+    idle_notifiers: Vec<Sender<()>>,
     queuer: Sender<Box<dyn Executable>>,
     queue: Receiver<Box<dyn Executable>>,
 }
@@ -27,6 +28,7 @@ where
             .field("last_task_id", &self.last_task_id)
             .field("result_senders", &self.result_senders.len())
             .field("keyed_jobs", &self.keyed_jobs)
+            .field("idle_notifiers", &self.idle_notifiers.len())
             .field("queuer", &self.queuer)
             .field("queue", &self.queue)
             .finish()
@@ -41,6 +43,7 @@ impl<Key> Default for Jobs<Key> {
             last_task_id: 0,
             result_senders: HashMap::new(),
             keyed_jobs: HashMap::new(),
+            idle_notifiers: Vec::new(),
             queuer,
             queue,
         }
@@ -55,6 +58,10 @@ where
         self.queue.clone()
     }
 
+    pub fn keyed_jobs(&self) -> impl Iterator<Item = &Key> {
+        self.keyed_jobs.keys()
+    }
+
     pub fn enqueue<J: Job + 'static>(
         &mut self,
         job: J,
@@ -101,6 +108,18 @@ where
         }
     }
 
+    /// Returns a receiver that is notified once no jobs are queued or
+    /// running, or `None` if there are no outstanding jobs.
+    pub fn idle_notifier(&mut self) -> Option<Receiver<()>> {
+        if self.result_senders.is_empty() {
+            None
+        } else {
+            let (sender, receiver) = flume::bounded(1);
+            self.idle_notifiers.push(sender);
+            Some(receiver)
+        }
+    }
+
     pub fn job_completed<T: Clone + Send + Sync + 'static, E: Send + Sync + 'static>(
         &mut self,
         id: Id,
@@ -121,6 +140,12 @@ where
                 drop(sender.send(result.clone()));
             }
         }
+
+        if self.result_senders.is_empty() {
+            for notifier in self.idle_notifiers.drain(..) {
+                drop(notifier.send(()));
+            }
+        }
     }
 }
 
@@ -65,3 +65,18 @@ fn keyed_simple() {
         assert_eq!(result.unwrap(), 1);
     }
 }
+
+#[test]
+fn idle_notifier() {
+    let manager = Manager::<usize>::default();
+    assert!(manager.idle_notifier().is_none());
+
+    let handle = manager.lookup_or_enqueue(Echo(1));
+    let idle = manager.idle_notifier().expect("a job is outstanding");
+    assert!(idle.try_recv().is_err());
+
+    manager.spawn_worker();
+    idle.recv().unwrap();
+    assert_eq!(handle.receive().unwrap().unwrap(), 1);
+    assert!(manager.idle_notifier().is_none());
+}
@@ -1,6 +1,8 @@
 use std::borrow::Cow;
 use std::sync::Arc;
 
+use bonsaidb_core::schema::CollectionName;
+
 use crate::tasks::compactor::Compaction;
 use crate::views::integrity_scanner::IntegrityScan;
 use crate::views::mapper::Map;
@@ -11,4 +13,6 @@ pub enum Task {
     ViewMap(Map),
     Compaction(Compaction),
     ExpirationLoader(Arc<Cow<'static, str>>),
+    RetentionEnforcer(Arc<Cow<'static, str>>, CollectionName),
+    CollectionMigration(Arc<Cow<'static, str>>, CollectionName),
 }
@@ -7,8 +7,10 @@ use bonsaidb_core::permissions::{Permissions, Statement};
 #[cfg(feature = "encryption")]
 use bonsaidb_core::test_util::EncryptedBasic;
 use bonsaidb_core::test_util::{
-    Basic, BasicByBrokenParentId, BasicByParentId, BasicCollectionWithNoViews,
-    BasicCollectionWithOnlyBrokenParentId, BasicSchema, HarnessTest, TestDirectory,
+    Basic, BasicByBrokenParentId, BasicByParentId, BasicCollectionWithMaterializedViews,
+    BasicCollectionWithNoViews, BasicCollectionWithOnlyBrokenParentId, BasicSchema, HarnessTest,
+    Landmark, LandmarksByLocation, MaterializedBasicByParentId, MaterializedBasicCount,
+    Measurement, MeasurementsByMinute, Scoreboard, Setting, TestDirectory, Ticket,
 };
 
 use crate::config::{Builder, StorageConfiguration};
@@ -224,45 +226,2164 @@ fn integrity_checks() -> anyhow::Result<()> {
     unreachable!("Integrity checker didn't run in the allocated time")
 }
 
+#[test]
+fn view_definition_checksums() -> anyhow::Result<()> {
+    use bonsaidb_core::schema::Collection;
+    use nebari::io::any::AnyFile;
+    use nebari::tree::Unversioned;
+
+    use crate::views::integrity_scanner::ViewVersion;
+    use crate::views::view_versions_tree_name;
+
+    fn view_versions(db: &Database) -> anyhow::Result<nebari::Tree<Unversioned, AnyFile>> {
+        let collection = Basic::collection_name();
+        Ok(db.roots().tree(db.collection_tree::<Unversioned, _>(
+            &collection,
+            view_versions_tree_name(&collection),
+        )?)?)
+    }
+
+    let path = TestDirectory::new("view-definition-checksums");
+    let config = StorageConfiguration::new(&path);
+    let view_name = {
+        let db = Database::open::<Basic>(config.clone())?;
+        db.collection::<Basic>()
+            .push(&Basic::default().with_parent_id(1))?;
+        assert_eq!(
+            db.view::<BasicByParentId>()
+                .with_key(&Some(1))
+                .query()?
+                .len(),
+            1
+        );
+
+        let view = db.schematic().view::<BasicByParentId>()?;
+        let view_name = view.view_name().to_string();
+        let versions = view_versions(&db)?;
+        let mut version = ViewVersion::from_bytes(&versions.get(view_name.as_bytes())?.unwrap())?;
+        assert_eq!(
+            version.definition_checksum,
+            Some(view.definition_checksum())
+        );
+
+        // Simulate the view's definition changing without its version being
+        // updated.
+        version.definition_checksum = Some(0);
+        versions.set(view_name.as_bytes().to_vec(), version.to_vec()?)?;
+        view_name
+    };
+
+    // Reopening detects the mismatch and rebuilds the view.
+    let db = Database::open::<Basic>(config)?;
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .with_key(&Some(1))
+            .query()?
+            .len(),
+        1
+    );
+    let version =
+        ViewVersion::from_bytes(&view_versions(&db)?.get(view_name.as_bytes())?.unwrap())?;
+    assert_eq!(
+        version.definition_checksum,
+        Some(
+            db.schematic()
+                .view::<BasicByParentId>()?
+                .definition_checksum()
+        )
+    );
+
+    Ok(())
+}
+
+#[test]
+fn schema_status() -> anyhow::Result<()> {
+    use bonsaidb_core::schema::{Collection, SerializedCollection, View};
+    use nebari::tree::Unversioned;
+
+    use crate::views::integrity_scanner::ViewVersion;
+    use crate::views::view_versions_tree_name;
+    use crate::ViewStatus;
+
+    // Simulates the definition of `BasicByParentId` changing.
+    fn change_definition(db: &Database) -> anyhow::Result<()> {
+        let collection = Basic::collection_name();
+        let versions = db.roots().tree(db.collection_tree::<Unversioned, _>(
+            &collection,
+            view_versions_tree_name(&collection),
+        )?)?;
+        let view_name = BasicByParentId.view_name().to_string();
+        let mut version = ViewVersion::from_bytes(&versions.get(view_name.as_bytes())?.unwrap())?;
+        version.definition_checksum = Some(0);
+        versions.set(view_name.as_bytes().to_vec(), version.to_vec()?)?;
+        Ok(())
+    }
+
+    let path = TestDirectory::new("schema-status");
+    let config = StorageConfiguration::new(&path);
+    {
+        let db = Database::open::<Basic>(config.clone())?;
+        Basic::new("a").with_parent_id(1).push_into(&db)?;
+        assert_eq!(db.view::<BasicByParentId>().query()?.len(), 1);
+        assert_eq!(
+            db.schema_status()?.views[&BasicByParentId.view_name()],
+            ViewStatus::Current
+        );
+        change_definition(&db)?;
+    }
+
+    {
+        let db = Database::open::<Basic>(config.clone())?;
+        assert_eq!(
+            db.schema_status()?.views[&BasicByParentId.view_name()],
+            ViewStatus::Outdated
+        );
+        // Querying rebuilds the view before returning.
+        assert_eq!(db.view::<BasicByParentId>().query()?.len(), 1);
+        assert!(db.schema_status()?.is_current());
+        change_definition(&db)?;
+    }
+
+    // Databases serving stale views don't wait for the rebuild, and keep the
+    // existing entries while it runs.
+    let db = Database::open::<Basic>(config.serve_stale_views_while_rebuilding("default"))?;
+    assert_eq!(db.view::<BasicByParentId>().query()?.len(), 1);
+    assert!(db
+        .storage
+        .wait_for_background_tasks(Some(Duration::from_secs(10))));
+    assert_eq!(db.view::<BasicByParentId>().query()?.len(), 1);
+    assert!(db.schema_status()?.is_current());
+
+    Ok(())
+}
+
+#[test]
+fn bounded_staleness() -> anyhow::Result<()> {
+    use bonsaidb_core::schema::SerializedCollection;
+
+    let path = TestDirectory::new("bounded-staleness");
+    let db = Database::open::<Basic>(StorageConfiguration::new(&path))?;
+    Basic::new("a").with_parent_id(1).push_into(&db)?;
+    assert_eq!(db.view::<BasicByParentId>().query()?.len(), 1);
+
+    // The view was up-to-date moments ago, so stale results are returned.
+    Basic::new("b").with_parent_id(2).push_into(&db)?;
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .with_access_policy(AccessPolicy::MaxStaleness(Duration::from_secs(3600)))
+            .query()?
+            .len(),
+        1
+    );
+
+    // The view must include the latest transaction.
+    let transaction_id = db.last_transaction_id()?.unwrap();
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .with_access_policy(AccessPolicy::UpToTransaction(transaction_id))
+            .query()?
+            .len(),
+        2
+    );
+
+    // No staleness is acceptable.
+    Basic::new("c").with_parent_id(3).push_into(&db)?;
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .with_access_policy(AccessPolicy::MaxStaleness(Duration::ZERO))
+            .query()?
+            .len(),
+        3
+    );
+
+    Ok(())
+}
+
+#[test]
+fn view_backlog() -> anyhow::Result<()> {
+    use bonsaidb_core::schema::{SerializedCollection, View};
+
+    let path = TestDirectory::new("view-backlog");
+    let db = Database::open::<Basic>(StorageConfiguration::new(&path))?;
+    Basic::new("a").with_parent_id(1).push_into(&db)?;
+    Basic::new("b").with_parent_id(2).push_into(&db)?;
+
+    let view_name = BasicByParentId.view_name();
+    let backlog = db.view_backlog()?[&view_name];
+    assert_eq!(backlog.pending_documents, 2);
+    // No documents have been mapped yet, so there is nothing to estimate from.
+    assert_eq!(backlog.estimated_catch_up, None);
+
+    assert_eq!(db.view::<BasicByParentId>().query()?.len(), 2);
+    let backlog = db.view_backlog()?[&view_name];
+    assert_eq!(backlog.pending_documents, 0);
+    assert_eq!(backlog.estimated_catch_up, Some(Duration::ZERO));
+
+    Basic::new("c").with_parent_id(3).push_into(&db)?;
+    let backlog = db.view_backlog()?[&view_name];
+    assert_eq!(backlog.pending_documents, 1);
+    assert!(backlog.estimated_catch_up.is_some());
+
+    Ok(())
+}
+
+#[test]
+fn side_by_side_view_rebuild() -> anyhow::Result<()> {
+    use bonsaidb_core::schema::{Collection, SerializedCollection, View};
+    use nebari::tree::Unversioned;
+
+    use crate::views::integrity_scanner::ViewVersion;
+    use crate::views::{
+        view_document_map_tree_name, view_entries_tree_name, view_rebuild_tree_name,
+        view_versions_tree_name,
+    };
+
+    let path = TestDirectory::new("side-by-side-view-rebuild");
+    let config = StorageConfiguration::new(&path).serve_stale_views_while_rebuilding("default");
+    let view_name = BasicByParentId.view_name();
+    {
+        let db = Database::open::<Basic>(config.clone())?;
+        Basic::new("a").with_parent_id(1).push_into(&db)?;
+        Basic::new("b").with_parent_id(2).push_into(&db)?;
+        assert_eq!(db.view::<BasicByParentId>().query()?.len(), 2);
+
+        // Simulate the definition of `BasicByParentId` changing.
+        let collection = Basic::collection_name();
+        let versions = db.roots().tree(db.collection_tree::<Unversioned, _>(
+            &collection,
+            view_versions_tree_name(&collection),
+        )?)?;
+        let key = view_name.to_string();
+        let mut version = ViewVersion::from_bytes(&versions.get(key.as_bytes())?.unwrap())?;
+        version.definition_checksum = Some(0);
+        versions.set(key.as_bytes().to_vec(), version.to_vec()?)?;
+    }
+
+    let db = Database::open::<Basic>(config)?;
+    // Documents changed while the view is rebuilt are mapped after the
+    // rebuilt entries replace the existing entries.
+    Basic::new("c").with_parent_id(3).push_into(&db)?;
+    assert!(db
+        .storage
+        .wait_for_background_tasks(Some(Duration::from_secs(10))));
+    assert!(db.schema_status()?.is_current());
+    assert_eq!(db.view::<BasicByParentId>().query()?.len(), 3);
+
+    // The trees the view was rebuilt in have been removed.
+    for tree_name in [
+        view_entries_tree_name(&view_name),
+        view_document_map_tree_name(&view_name),
+    ] {
+        assert!(!db.roots().delete_tree(view_rebuild_tree_name(&tree_name))?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn materialized_views() -> anyhow::Result<()> {
+    let path = TestDirectory::new("materialized-views");
+    let db =
+        Database::open::<BasicCollectionWithMaterializedViews>(StorageConfiguration::new(&path))?;
+    let collection = db.collection::<BasicCollectionWithMaterializedViews>();
+    assert_eq!(
+        db.view::<MaterializedBasicCount>().materialized_reduce()?,
+        None
+    );
+
+    collection.push(&Basic::new("a").with_parent_id(1))?;
+    collection.push(&Basic::new("b").with_parent_id(1))?;
+    let orphan = collection.push(&Basic::new("c").with_parent_id(2))?;
+    assert_eq!(
+        db.view::<MaterializedBasicCount>().materialized_reduce()?,
+        Some(3)
+    );
+    assert_eq!(
+        db.view::<MaterializedBasicByParentId>()
+            .with_key(&Some(1))
+            .materialized_reduce()?,
+        Some(2)
+    );
+    assert_eq!(
+        db.view::<MaterializedBasicByParentId>()
+            .with_key(&Some(2))
+            .materialized_reduce()?,
+        Some(1)
+    );
+
+    // Removing the only document with a key removes its materialized value.
+    collection.delete(&orphan)?;
+    assert_eq!(
+        db.view::<MaterializedBasicCount>().materialized_reduce()?,
+        Some(2)
+    );
+    assert_eq!(
+        db.view::<MaterializedBasicByParentId>()
+            .with_key(&Some(2))
+            .materialized_reduce()?,
+        None
+    );
+
+    // Grouped values can only be read one key at a time.
+    assert!(db
+        .view::<MaterializedBasicByParentId>()
+        .materialized_reduce()
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn cached_queries() -> anyhow::Result<()> {
+    use bonsaidb_core::connection::Sort;
+    use bonsaidb_core::schema::View;
+
+    use crate::views::cache::query_cache_key;
+
+    let path = TestDirectory::new("cached-queries");
+    let db = Database::open::<Basic>(
+        StorageConfiguration::new(&path).cache_view_queries(&BasicByParentId),
+    )?;
+    let collection = db.collection::<Basic>();
+    collection.push(&Basic::new("a").with_parent_id(1))?;
+    collection.push(&Basic::new("b").with_parent_id(2))?;
+
+    let view_name = BasicByParentId.view_name();
+    let query = query_cache_key(None, Sort::Ascending, None)?;
+    assert!(db.query_cache().get(&view_name, &query).is_none());
+    assert_eq!(db.view::<BasicByParentId>().query()?.len(), 2);
+    assert_eq!(
+        db.query_cache()
+            .get(&view_name, &query)
+            .map(|results| results.len()),
+        Some(2)
+    );
+    // The cached results are returned regardless of the access policy.
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .with_access_policy(AccessPolicy::NoUpdate)
+            .query()?
+            .len(),
+        2
+    );
+
+    // Changing a document in the view's collection invalidates the cache.
+    collection.push(&Basic::new("c").with_parent_id(1))?;
+    assert!(db.query_cache().get(&view_name, &query).is_none());
+    assert_eq!(db.view::<BasicByParentId>().query()?.len(), 3);
+
+    Ok(())
+}
+
+#[test]
+fn reduce_segments() -> anyhow::Result<()> {
+    use bonsaidb_core::schema::{SerializedCollection, View};
+
+    let path = TestDirectory::new("reduce-segments");
+    let db = Database::open::<Basic>(StorageConfiguration::new(&path))?;
+    let documents = Basic::push_all(
+        (0..200).map(|parent_id| Basic::new("a").with_parent_id(parent_id)),
+        &db,
+    )?;
+
+    let view_name = BasicByParentId.view_name();
+    assert_eq!(db.view::<BasicByParentId>().reduce()?, 200);
+    // 200 entries fill three segments of 64 entries.
+    assert_eq!(db.reduce_cache().segments(&view_name).1.len(), 3);
+    assert_eq!(db.view::<BasicByParentId>().reduce()?, 200);
+    // Segments that extend beyond the range are read entry-by-entry.
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .with_key_range(Some(10)..Some(150))
+            .reduce()?,
+        140
+    );
+
+    // Modifying an entry only invalidates the segment that contains it.
+    documents[0].delete(&db)?;
+    assert_eq!(db.view::<BasicByParentId>().reduce()?, 199);
+    assert_eq!(db.reduce_cache().segments(&view_name).1.len(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn deadlines() -> anyhow::Result<()> {
+    use std::time::Instant;
+
+    use bonsaidb_core::schema::SerializedCollection;
+
+    let path = TestDirectory::new("deadlines");
+    let db = Database::open::<Basic>(StorageConfiguration::new(&path))?;
+    Basic::push_all(
+        (0..10).map(|parent_id| Basic::new("a").with_parent_id(parent_id)),
+        &db,
+    )?;
+
+    let expired = db.with_deadline(Instant::now());
+    assert!(matches!(
+        expired.view::<BasicByParentId>().reduce(),
+        Err(bonsaidb_core::Error::DeadlineExceeded)
+    ));
+
+    let db = db.with_deadline(Instant::now() + Duration::from_secs(60));
+    assert_eq!(db.view::<BasicByParentId>().reduce()?, 10);
+
+    Ok(())
+}
+
+#[test]
+fn concurrent_query_limit() -> anyhow::Result<()> {
+    use std::time::Instant;
+
+    use bonsaidb_core::schema::SerializedCollection;
+
+    let path = TestDirectory::new("concurrent-query-limit");
+    let db = Database::open::<Basic>(StorageConfiguration::new(&path).concurrent_query_limit(1))?;
+    Basic::push_all(
+        (0..10).map(|parent_id| Basic::new("a").with_parent_id(parent_id)),
+        &db,
+    )?;
+
+    // While the only permit is held, expensive operations wait for it.
+    let permit = db.limit_query()?;
+    let waiting = db.with_deadline(Instant::now() + Duration::from_millis(100));
+    assert!(matches!(
+        waiting.view::<BasicByParentId>().reduce(),
+        Err(bonsaidb_core::Error::DeadlineExceeded)
+    ));
+    assert!(matches!(
+        Basic::all(&waiting).query(),
+        Err(bonsaidb_core::Error::DeadlineExceeded)
+    ));
+    // Listing with a limit is not counted.
+    assert_eq!(Basic::all(&waiting).limit(5).query()?.len(), 5);
+
+    let waiting = db.clone();
+    let reduce = std::thread::spawn(move || waiting.view::<BasicByParentId>().reduce());
+    std::thread::sleep(Duration::from_millis(50));
+    assert!(!reduce.is_finished());
+    drop(permit);
+    assert_eq!(reduce.join().unwrap()?, 10);
+
+    Ok(())
+}
+
+#[test]
+fn usage_accounting() -> anyhow::Result<()> {
+    use bonsaidb_core::connection::{IdentityReference, StorageConnection};
+    use bonsaidb_core::schema::SerializedCollection;
+
+    let path = TestDirectory::new("usage-accounting");
+    let storage = Storage::open(
+        StorageConfiguration::new(&path)
+            .with_schema::<Basic>()?
+            .authenticated_permissions(Permissions::allow_all()),
+    )?;
+    storage.create_database::<Basic>("tests", false)?;
+    let user_id = storage.create_user("ecton")?;
+    let authenticated = storage.assume_identity(IdentityReference::user(user_id)?)?;
+    let db = authenticated
+        .instance
+        .database_without_schema("tests", Some(&authenticated), None)?;
+
+    Basic::push_all(
+        (0..10).map(|parent_id| Basic::new("a").with_parent_id(parent_id)),
+        &db,
+    )?;
+    assert_eq!(db.view::<BasicByParentId>().reduce()?, 10);
+    assert_eq!(Basic::all(&db).query()?.len(), 10);
+    db.compact()?;
+
+    let usage = storage.usage();
+    let database = usage.databases["tests"];
+    assert!(database.bytes_written > 0);
+    assert!(database.bytes_read > 0);
+    assert!(database.mapping > Duration::ZERO);
+    assert!(database.querying > Duration::ZERO);
+    assert!(database.compacting > Duration::ZERO);
+    // Everything done to the database was done by the user.
+    assert_eq!(usage.users[&user_id], database);
+
+    Ok(())
+}
+
+#[test]
+fn warm_views_on_open() -> anyhow::Result<()> {
+    use bonsaidb_core::schema::SerializedCollection;
+
+    let path = TestDirectory::new("warm-views-on-open");
+    let config = StorageConfiguration::new(&path).warm_views_on_open(1);
+    {
+        let db = Database::open::<Basic>(config.clone())?;
+        Basic::new("a").with_parent_id(1).push_into(&db)?;
+        assert_eq!(db.view::<BasicByParentId>().query()?.len(), 1);
+        // Add a document that the view hasn't mapped yet.
+        Basic::new("b").with_parent_id(1).push_into(&db)?;
+    }
+
+    // Reopening should update the view in the background without a query
+    // requesting it.
+    let db = Database::open::<Basic>(config)?;
+    for _ in 0_u8..100 {
+        if db
+            .view::<BasicByParentId>()
+            .with_access_policy(AccessPolicy::NoUpdate)
+            .query()?
+            .len()
+            == 2
+        {
+            return Ok(());
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    unreachable!("view was not warmed")
+}
+
+#[test]
+fn database_handles_are_reused() -> anyhow::Result<()> {
+    use std::sync::Arc;
+
+    use bonsaidb_core::connection::StorageConnection;
+
+    let path = TestDirectory::new("database-handles-are-reused");
+    let storage = Storage::open(StorageConfiguration::new(&path).with_schema::<Basic>()?)?;
+    let first = storage.create_database::<Basic>("tests", false)?;
+    let second = storage.database::<Basic>("tests")?;
+    assert!(Arc::ptr_eq(&first.data, &second.data));
+    assert!(Arc::ptr_eq(&storage.admin().data, &storage.admin().data));
+
+    // Deleting the database discards its cached state.
+    storage.delete_database("tests")?;
+    let recreated = storage.create_database::<Basic>("tests", false)?;
+    assert!(!Arc::ptr_eq(&first.data, &recreated.data));
+
+    Ok(())
+}
+
+#[test]
+fn register_schema_at_runtime() -> anyhow::Result<()> {
+    use bonsaidb_core::connection::StorageConnection;
+    use bonsaidb_core::schema::SerializedCollection;
+
+    let path = TestDirectory::new("register-schema-at-runtime");
+    {
+        let storage = Storage::open(StorageConfiguration::new(&path).with_schema::<Basic>()?)?;
+        let db = storage.create_database::<Basic>("tests", false)?;
+        Basic::new("a").push_into(&db)?;
+    }
+
+    let storage = Storage::open(StorageConfiguration::new(&path))?;
+    assert!(matches!(
+        storage.database::<Basic>("tests"),
+        Err(bonsaidb_core::Error::SchemaNotRegistered(_))
+    ));
+
+    storage.register_schema::<Basic>()?;
+    let db = storage.database::<Basic>("tests")?;
+    assert_eq!(Basic::all(&db).count()?, 1);
+    assert!(matches!(
+        storage.register_schema::<Basic>(),
+        Err(crate::Error::Core(
+            bonsaidb_core::Error::SchemaAlreadyRegistered(_)
+        ))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn legacy_collection_names() -> anyhow::Result<()> {
+    use bonsaidb_core::schema::{Collection, SerializedCollection};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Serialize, Deserialize, Collection)]
+    #[collection(name = "renamed", legacy_names = [Basic::collection_name()], core = bonsaidb_core)]
+    struct Renamed {
+        value: String,
+        category: Option<String>,
+        parent_id: Option<u64>,
+        tags: Vec<String>,
+    }
+
+    let path = TestDirectory::new("legacy-collection-names");
+    {
+        let db = Database::open::<Basic>(StorageConfiguration::new(&path))?;
+        Basic::new("a").push_into(&db)?;
+        Basic::new("b").push_into(&db)?;
+    }
+
+    let db = Database::open::<Renamed>(StorageConfiguration::new(&path))?;
+    let documents = Renamed::all(&db).query()?;
+    assert_eq!(documents.len(), 2);
+    assert_eq!(documents[0].contents.value, "a");
+    assert_eq!(documents[1].contents.value, "b");
+
+    // The legacy tree was removed, so migrating again doesn't move anything.
+    assert_eq!(
+        db.migrate_legacy_collections(&Renamed::collection_name())?,
+        0
+    );
+
+    Ok(())
+}
+
+#[test]
+fn verify_integrity() -> anyhow::Result<()> {
+    use bonsaidb_core::schema::View;
+
+    use crate::views::view_document_map_tree_name;
+
+    let path = TestDirectory::new("verify-integrity");
+    let db = Database::open::<Basic>(StorageConfiguration::new(&path))?;
+    let collection = db.collection::<Basic>();
+    collection.push(&Basic::new("a").with_parent_id(1))?;
+    collection.push(&Basic::new("b").with_parent_id(2))?;
+    assert_eq!(db.view::<BasicByParentId>().query()?.len(), 2);
+
+    let report = db.verify_integrity()?;
+    assert!(report.is_consistent());
+    assert_eq!(report.collections[0].documents, 2);
+
+    // Losing the view's document map leaves the documents unmapped.
+    let view_name = BasicByParentId.view_name();
+    db.roots()
+        .delete_tree(view_document_map_tree_name(&view_name))?;
+    let report = db.verify_and_repair_integrity()?;
+    let view = report.collections[0]
+        .views
+        .iter()
+        .find(|view| view.view == view_name)
+        .unwrap();
+    assert_eq!(view.unmapped_documents.len(), 2);
+    assert!(view.repaired);
+
+    assert!(db.verify_integrity()?.is_consistent());
+    assert_eq!(db.view::<BasicByParentId>().query()?.len(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn filtered_views() -> anyhow::Result<()> {
+    use bonsaidb_core::document::{BorrowedDocument, Emit};
+    use bonsaidb_core::schema::{
+        Collection, SerializedCollection, View, ViewMapResult, ViewSchema,
+    };
+    use nebari::tree::Unversioned;
+    use serde::{Deserialize, Serialize};
+
+    use crate::views::view_document_map_tree_name;
+
+    #[derive(Debug, Serialize, Deserialize, Collection)]
+    #[collection(name = "tasks", views = [ActiveTasksByName], core = bonsaidb_core)]
+    struct Task {
+        name: String,
+        active: bool,
+    }
+
+    #[derive(Debug, Clone, View)]
+    #[view(collection = Task, key = String, value = (), name = "active-by-name", core = bonsaidb_core)]
+    struct ActiveTasksByName;
+
+    impl ViewSchema for ActiveTasksByName {
+        type View = Self;
+
+        fn filter(&self, document: &BorrowedDocument<'_>) -> Result<bool, bonsaidb_core::Error> {
+            Ok(Task::document_contents(document)?.active)
+        }
+
+        fn map(&self, document: &BorrowedDocument<'_>) -> ViewMapResult<Self::View> {
+            let task = Task::document_contents(document)?;
+            document.header.emit_key(task.name)
+        }
+    }
+
+    let path = TestDirectory::new("filtered-views");
+    let db = Database::open::<Task>(StorageConfiguration::new(&path))?;
+    let mut active = Task {
+        name: String::from("a"),
+        active: true,
+    }
+    .push_into(&db)?;
+    Task {
+        name: String::from("b"),
+        active: false,
+    }
+    .push_into(&db)?;
+
+    let entries = db.view::<ActiveTasksByName>().query()?;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].key, "a");
+
+    // Only the document that passed the filter is recorded by the view.
+    let document_map = db
+        .roots()
+        .tree(Unversioned::tree(view_document_map_tree_name(
+            &ActiveTasksByName.view_name(),
+        )))?;
+    assert_eq!(document_map.get_range(&(..))?.len(), 1);
+    assert!(db.verify_integrity()?.is_consistent());
+
+    // Documents that stop passing the filter are removed from the view.
+    active.contents.active = false;
+    active.update(&db)?;
+    assert!(db.view::<ActiveTasksByName>().query()?.is_empty());
+    assert!(document_map.get_range(&(..))?.is_empty());
+    assert!(db.verify_integrity()?.is_consistent());
+
+    Ok(())
+}
+
+#[test]
+fn values_stored_by_reference() -> anyhow::Result<()> {
+    use bonsaidb_core::document::{BorrowedDocument, Emit};
+    use bonsaidb_core::schema::{
+        Collection, SerializedCollection, ValueStorage, View, ViewMapResult, ViewSchema,
+    };
+    use nebari::tree::Unversioned;
+    use serde::{Deserialize, Serialize};
+
+    use crate::views::{view_entries_tree_name, ViewEntry};
+
+    #[derive(Debug, Serialize, Deserialize, Collection)]
+    #[collection(name = "articles", views = [ArticlesByAuthor], core = bonsaidb_core)]
+    struct Article {
+        author: String,
+        body: String,
+    }
+
+    #[derive(Debug, Clone, View)]
+    #[view(collection = Article, key = String, value = String, name = "by-author", core = bonsaidb_core)]
+    struct ArticlesByAuthor;
+
+    impl ViewSchema for ArticlesByAuthor {
+        type View = Self;
+
+        fn value_storage(&self) -> ValueStorage {
+            ValueStorage::Reference
+        }
+
+        fn map(&self, document: &BorrowedDocument<'_>) -> ViewMapResult<Self::View> {
+            let article = Article::document_contents(document)?;
+            document
+                .header
+                .emit_key_and_value(article.author, article.body)
+        }
+    }
+
+    let path = TestDirectory::new("values-stored-by-reference");
+    let db = Database::open::<Article>(StorageConfiguration::new(&path))?;
+    let mut article = Article {
+        author: String::from("ecton"),
+        body: String::from("a long article"),
+    }
+    .push_into(&db)?;
+
+    let entries = db.view::<ArticlesByAuthor>().query()?;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].value, "a long article");
+
+    // The view's entries don't contain the value.
+    let stored = db
+        .roots()
+        .tree(Unversioned::tree(view_entries_tree_name(
+            &ArticlesByAuthor.view_name(),
+        )))?
+        .get_range(&(..))?;
+    assert_eq!(stored.len(), 1);
+    let entry = bincode::deserialize::<ViewEntry>(&stored[0].1)?;
+    assert!(entry.mappings[0].value.is_empty());
+
+    // Values are produced from the document's current contents.
+    article.contents.body = String::from("an edited article");
+    article.update(&db)?;
+    let mappings = db
+        .view::<ArticlesByAuthor>()
+        .for_document(&article.header.id)?;
+    assert_eq!(mappings[0].value, "an edited article");
+
+    assert!(matches!(
+        db.view::<ArticlesByAuthor>().reduce(),
+        Err(bonsaidb_core::Error::ReduceUnimplemented)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn scrub() -> anyhow::Result<()> {
+    let path = TestDirectory::new("scrub");
+    let db = Database::open::<Basic>(StorageConfiguration::new(&path))?;
+    db.collection::<Basic>()
+        .push(&Basic::new("a").with_parent_id(1))?;
+
+    let storage = db.storage();
+    assert!(storage.last_scrub_report().is_none());
+    let report = storage.scrub()?;
+    assert!(report.is_clean());
+    assert!(report.trees_scrubbed > 0);
+    assert_eq!(
+        storage
+            .last_scrub_report()
+            .map(|report| report.trees_scrubbed),
+        Some(report.trees_scrubbed)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn recover_database() -> anyhow::Result<()> {
+    use bonsaidb_core::connection::StorageConnection;
+    use bonsaidb_core::schema::SerializedCollection;
+
+    let path = TestDirectory::new("recover-database");
+    let db = Database::open::<Basic>(StorageConfiguration::new(&path))?;
+    let first = Basic::new("a").with_parent_id(1).push_into(&db)?;
+    Basic::new("b").with_parent_id(1).push_into(&db)?;
+
+    let storage = db.storage();
+    let report = storage.recover_database("default", "recovered")?;
+    assert!(report.is_complete());
+    assert_eq!(report.recovered_documents, 2);
+
+    let recovered = storage.database::<Basic>("recovered")?;
+    assert_eq!(
+        Basic::get(&first.header.id, &recovered)?.map(|doc| doc.contents),
+        Some(first.contents)
+    );
+    assert_eq!(
+        recovered
+            .view::<BasicByParentId>()
+            .with_key(&Some(1))
+            .query()?
+            .len(),
+        2
+    );
+
+    // Recovering into an existing database fails rather than mixing data.
+    assert!(storage.recover_database("default", "recovered").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn time_series() -> anyhow::Result<()> {
+    use bonsaidb_core::keyvalue::Timestamp;
+    use bonsaidb_core::schema::SerializedCollection;
+
+    let path = TestDirectory::new("time-series");
+    let expired = Timestamp {
+        seconds: 90,
+        nanos: 0,
+    };
+    {
+        let db = Database::open::<Measurement>(StorageConfiguration::new(&path))?;
+        let first = Measurement { value: 1 }.push_into(&db)?;
+        let second = Measurement { value: 2 }.push_into(&db)?;
+        assert!(first.header.id < second.header.id);
+
+        Measurement { value: 3 }.insert_into(&expired, &db)?;
+        Measurement { value: 4 }.insert_into(
+            &Timestamp {
+                seconds: 100,
+                nanos: 0,
+            },
+            &db,
+        )?;
+        // Both expired measurements were taken during the same minute.
+        assert_eq!(
+            db.view::<MeasurementsByMinute>()
+                .with_key(&Timestamp {
+                    seconds: 60,
+                    nanos: 0,
+                })
+                .reduce()?,
+            7
+        );
+    }
+
+    // Expired partitions are removed when the database is opened.
+    let db = Database::open::<Measurement>(StorageConfiguration::new(&path))?;
+    for _ in 0_u8..100 {
+        if Measurement::get(&expired, &db)?.is_none() {
+            assert_eq!(Measurement::all(&db).count()?, 2);
+            assert_eq!(db.view::<MeasurementsByMinute>().reduce()?, 3);
+            return Ok(());
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    unreachable!("expired partitions weren't removed in the allocated time")
+}
+
+#[test]
+fn random_ids() -> anyhow::Result<()> {
+    use bonsaidb_core::schema::SerializedCollection;
+
+    let path = TestDirectory::new("random-ids");
+    let db = Database::open::<Ticket>(StorageConfiguration::new(&path))?;
+    let mut ids = Vec::new();
+    for holder in ["a", "b", "c", "d"] {
+        let ticket = Ticket {
+            holder: holder.to_string(),
+        }
+        .push_into(&db)?;
+        ids.push(ticket.header.id);
+    }
+
+    // Random ids are neither sequential nor limited to the range of a u64.
+    assert!(ids.iter().any(|id| *id > u128::from(u64::MAX)));
+    let mut sorted = ids.clone();
+    sorted.sort_unstable();
+    sorted.dedup();
+    assert_eq!(sorted.len(), ids.len());
+    for id in ids {
+        assert!(Ticket::get(&id, &db)?.is_some());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn load_or_insert_with_unreadable_conflict() -> anyhow::Result<()> {
+    use bonsaidb_core::permissions::bonsai::{
+        BonsaiResource, DatabaseResource, DocumentAction, ViewAction,
+    };
+    use bonsaidb_core::schema::{Collection, NamedCollection, SerializedCollection};
+    use bonsaidb_core::test_util::Unique;
+
+    let path = TestDirectory::new("load-or-insert-with-unreadable-conflict");
+    let db = Database::open::<BasicSchema>(StorageConfiguration::new(&path))?;
+    Unique::new("a").push_into(&db)?;
+
+    // This session can insert documents and query the by-name view, but can't
+    // read the existing document.
+    let restricted = db
+        .with_effective_permissions(Permissions::from(vec![Statement::from(
+            DatabaseResource::named("default")
+                .collection(&Unique::collection_name())
+                .allowing(DocumentAction::Insert)
+                .allowing(ViewAction::Query),
+        )]))
+        .unwrap();
+    assert!(Unique::load("a", &restricted)?.is_none());
+
+    // The conflicting document can't be loaded, so the unique key violation
+    // is returned rather than retrying forever.
+    let err = Unique::load_or_insert_with("a", &restricted, || Unique::new("a")).unwrap_err();
+    assert!(err.is_unique_key_error::<<Unique as NamedCollection>::ByNameView, _>(&db));
+
+    Ok(())
+}
+
+#[test]
+fn unversioned_collections() -> anyhow::Result<()> {
+    use bonsaidb_core::schema::SerializedCollection;
+
+    let path = TestDirectory::new("unversioned-collections");
+    {
+        let db = Database::open::<Setting>(StorageConfiguration::new(&path))?;
+        let mut setting = Setting {
+            value: String::from("a"),
+        }
+        .push_into(&db)?;
+        let mut stale = setting.clone();
+
+        // Updating with an outdated revision overwrites the stored document.
+        setting.contents.value = String::from("b");
+        setting.update(&db)?;
+        stale.contents.value = String::from("c");
+        stale.update(&db)?;
+        assert_eq!(
+            Setting::get(&setting.header.id, &db)?
+                .unwrap()
+                .contents
+                .value,
+            "c"
+        );
+
+        Setting {
+            value: String::from("d"),
+        }
+        .push_into(&db)?;
+        assert_eq!(Setting::all(&db).count()?, 2);
+        db.compact_collection::<Setting>()?;
+
+        // Deleting with an outdated revision deletes the stored document.
+        setting.delete(&db)?;
+        assert!(Setting::get(&setting.header.id, &db)?.is_none());
+    }
+
+    let db = Database::open::<Setting>(StorageConfiguration::new(&path))?;
+    let settings = Setting::all(&db).query()?;
+    assert_eq!(settings.len(), 1);
+    assert_eq!(settings[0].contents.value, "d");
+
+    Ok(())
+}
+
+#[test]
+fn deduplicated_collections() -> anyhow::Result<()> {
+    use bonsaidb_core::document::{BorrowedDocument, Emit};
+    use bonsaidb_core::schema::{
+        Collection, SerializedCollection, View, ViewMapResult, ViewSchema,
+    };
+    use nebari::tree::Unversioned;
+    use serde::{Deserialize, Serialize};
+
+    use crate::database::deduplication::document_contents_tree_name;
+
+    #[derive(Debug, Clone, Serialize, Deserialize, Collection)]
+    #[collection(name = "chunks", views = [ChunksByData], deduplicated, core = bonsaidb_core)]
+    struct Chunk {
+        data: Vec<u8>,
+    }
+
+    #[derive(Debug, Clone, View)]
+    #[view(collection = Chunk, key = Vec<u8>, value = (), name = "by-data", core = bonsaidb_core)]
+    struct ChunksByData;
+
+    impl ViewSchema for ChunksByData {
+        type View = Self;
+
+        fn map(&self, document: &BorrowedDocument<'_>) -> ViewMapResult<Self::View> {
+            document
+                .header
+                .emit_key(Chunk::document_contents(document)?.data)
+        }
+    }
+
+    let path = TestDirectory::new("deduplicated-collections");
+    let db = Database::open::<Chunk>(StorageConfiguration::new(&path))?;
+    // Returns the sorted reference counts of the stored contents.
+    let stored_contents = || -> anyhow::Result<Vec<u64>> {
+        let mut references = db
+            .roots()
+            .tree(Unversioned::tree(document_contents_tree_name(
+                &Chunk::collection_name(),
+            )))?
+            .get_range(&(..))?
+            .into_iter()
+            .map(|(_, entry)| u64::from_be_bytes(entry[..8].try_into().unwrap()))
+            .collect::<Vec<_>>();
+        references.sort_unstable();
+        Ok(references)
+    };
+
+    let mut first = Chunk { data: vec![1; 64] }.push_into(&db)?;
+    let second = Chunk { data: vec![1; 64] }.push_into(&db)?;
+    let third = Chunk { data: vec![2; 64] }.push_into(&db)?;
+    // Identical contents are stored once, and are referenced by both
+    // documents.
+    assert_eq!(stored_contents()?, vec![1, 2]);
+    assert_eq!(
+        Chunk::get(&second.header.id, &db)?.unwrap().contents.data,
+        vec![1; 64]
+    );
+    assert_eq!(Chunk::all(&db).query()?.len(), 3);
+    assert_eq!(
+        db.view::<ChunksByData>()
+            .with_key(&vec![1; 64])
+            .query()?
+            .len(),
+        2
+    );
+
+    // Updating a document moves its reference to the new contents.
+    first.contents.data = vec![2; 64];
+    first.update(&db)?;
+    assert_eq!(stored_contents()?, vec![1, 2]);
+
+    // Contents are removed once no documents reference them.
+    second.delete(&db)?;
+    assert_eq!(stored_contents()?, vec![2]);
+    third.delete(&db)?;
+    db.compact_collection::<Chunk>()?;
+    assert_eq!(stored_contents()?, vec![1]);
+    assert_eq!(
+        Chunk::get(&first.header.id, &db)?.unwrap().contents.data,
+        vec![2; 64]
+    );
+    assert!(db.verify_integrity()?.is_consistent());
+
+    Ok(())
+}
+
+#[test]
+fn crdt_collections() -> anyhow::Result<()> {
+    use bonsaidb_core::crdt::ReplicaId;
+    use bonsaidb_core::schema::SerializedCollection;
+
+    let path = TestDirectory::new("crdt-collections");
+    let db = Database::open::<Scoreboard>(StorageConfiguration::new(&path))?;
+    let alice = ReplicaId(1);
+    let bob = ReplicaId(2);
+
+    let mut scoreboard = Scoreboard::default();
+    scoreboard
+        .scores
+        .update(alice, String::from("alice"), |score| {
+            score.increment(alice, 1)
+        });
+    let mut alices = scoreboard.push_into(&db)?;
+    let mut bobs = alices.clone();
+
+    // Both updates use the same revision. Instead of the second update
+    // conflicting, the contents are merged.
+    alices
+        .contents
+        .scores
+        .update(alice, String::from("alice"), |score| {
+            score.increment(alice, 2)
+        });
+    alices
+        .contents
+        .log
+        .push(alice, String::from("alice scored"));
+    alices.update(&db)?;
+    bobs.contents
+        .scores
+        .update(bob, String::from("bob"), |score| score.increment(bob, 5));
+    bobs.contents.log.push(bob, String::from("bob scored"));
+    bobs.update(&db)?;
+
+    let merged = Scoreboard::get(&alices.header.id, &db)?.unwrap();
+    assert_eq!(
+        merged
+            .contents
+            .scores
+            .get(&String::from("alice"))
+            .unwrap()
+            .value(),
+        3
+    );
+    assert_eq!(
+        merged
+            .contents
+            .scores
+            .get(&String::from("bob"))
+            .unwrap()
+            .value(),
+        5
+    );
+    assert_eq!(merged.contents.log.len(), 2);
+
+    // Inserting with an existing id also merges.
+    let mut late = Scoreboard::default();
+    late.scores
+        .update(bob, String::from("bob"), |score| score.increment(bob, 6));
+    late.insert_into(&merged.header.id, &db)?;
+    let merged = Scoreboard::get(&merged.header.id, &db)?.unwrap();
+    assert_eq!(
+        merged
+            .contents
+            .scores
+            .get(&String::from("bob"))
+            .unwrap()
+            .value(),
+        6
+    );
+    assert_eq!(merged.contents.log.len(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn geo_bounds() -> anyhow::Result<()> {
+    use bonsaidb_core::key::geo::GeoBounds;
+    use bonsaidb_core::schema::SerializedCollection;
+
+    let path = TestDirectory::new("geo-bounds");
+    let db = Database::open::<Landmark>(StorageConfiguration::new(&path))?;
+    for (latitude, longitude, visitors) in [
+        (45.5152, -122.6784, 1),
+        (44.0521, -123.0868, 2),
+        (47.6062, -122.3321, 4),
+        (37.7749, -122.4194, 8),
+    ] {
+        Landmark {
+            latitude,
+            longitude,
+            visitors,
+        }
+        .push_into(&db)?;
+    }
+
+    let oregon = GeoBounds::new(41.99, -124.57, 46.29, -116.46)?;
+    let mappings = db
+        .view::<LandmarksByLocation>()
+        .with_key_bounds(oregon)
+        .query()?;
+    assert_eq!(mappings.len(), 2);
+    assert!(mappings.iter().all(|mapping| oregon.contains(mapping.key)));
+    assert_eq!(
+        db.view::<LandmarksByLocation>()
+            .with_key_bounds(oregon)
+            .reduce()?,
+        3
+    );
+    assert_eq!(
+        db.view::<LandmarksByLocation>()
+            .with_key_bounds(oregon)
+            .limit(1)
+            .query_with_docs()?
+            .documents
+            .len(),
+        1
+    );
+
+    assert_eq!(
+        db.view::<LandmarksByLocation>()
+            .with_key_bounds(oregon)
+            .delete_docs()?,
+        2
+    );
+    assert_eq!(Landmark::all(&db).count()?, 2);
+    assert_eq!(db.view::<LandmarksByLocation>().reduce()?, 12);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "encryption")]
+fn encryption() -> anyhow::Result<()> {
+    use bonsaidb_core::schema::SerializedCollection;
+    let path = TestDirectory::new("encryption");
+    let document_header = {
+        let db = Database::open::<BasicSchema>(StorageConfiguration::new(&path))?;
+
+        let document_header = db
+            .collection::<EncryptedBasic>()
+            .push(&EncryptedBasic::new("hello"))?;
+
+        // Retrieve the document, showing that it was stored successfully.
+        let doc = db
+            .collection::<EncryptedBasic>()
+            .get(&document_header.id)?
+            .expect("doc not found");
+        assert_eq!(&EncryptedBasic::document_contents(&doc)?.value, "hello");
+
+        document_header
+    };
+
+    // By resetting the encryption key, we should be able to force an error in
+    // decryption, which proves that the document was encrypted. To ensure the
+    // server starts up and generates a new key, we must delete the sealing key.
+
+    std::fs::remove_file(path.join("master-keys"))?;
+
+    let db = Database::open::<BasicSchema>(StorageConfiguration::new(&path))?;
+
+    // Try retrieving the document, but expect an error decrypting.
+    if let Err(bonsaidb_core::Error::Other { error, .. }) =
+        db.collection::<EncryptedBasic>().get(&document_header.id)
+    {
+        assert!(error.contains("vault"));
+    } else {
+        panic!("successfully retrieved encrypted document without keys");
+    }
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "encryption")]
+fn key_value_encryption() -> anyhow::Result<()> {
+    use bonsaidb_core::document::KeyId;
+    use bonsaidb_core::keyvalue::KeyValue;
+    use bonsaidb_core::schema::Schema;
+
+    #[derive(Debug, Schema)]
+    #[schema(name = "encrypted-kv", key_value_encryption_key = Some(KeyId::Master), core = bonsaidb_core)]
+    struct EncryptedKeyValue;
+
+    let path = TestDirectory::new("key-value-encryption");
+    {
+        let db = Database::open::<EncryptedKeyValue>(StorageConfiguration::new(&path))?;
+        db.set_key("secret", &String::from("hello")).execute()?;
+        assert_eq!(db.get_key("secret").into()?, Some(String::from("hello")));
+    }
+
+    // Resetting the master key ensures the persisted entry can no longer be
+    // decrypted, proving that it was encrypted at rest.
+    std::fs::remove_file(path.join("master-keys"))?;
+
+    let db = Database::open::<EncryptedKeyValue>(StorageConfiguration::new(&path))?;
+    if let Err(bonsaidb_core::Error::Other { error, .. }) = db.get_key("secret").into::<String>() {
+        assert!(error.contains("vault"));
+    } else {
+        panic!("successfully retrieved encrypted key without keys");
+    }
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "encryption")]
+fn key_value_plaintext_passthrough() -> anyhow::Result<()> {
+    use bonsaidb_core::document::KeyId;
+    use bonsaidb_core::keyvalue::KeyValue;
+    use bonsaidb_core::schema::Schema;
+
+    #[derive(Debug, Schema)]
+    #[schema(name = "plaintext-kv", core = bonsaidb_core)]
+    struct PlaintextKeyValue;
+
+    #[derive(Debug, Schema)]
+    #[schema(name = "plaintext-kv", key_value_encryption_key = Some(KeyId::Master), core = bonsaidb_core)]
+    struct EncryptedKeyValue;
+
+    let path = TestDirectory::new("key-value-plaintext-passthrough");
+    {
+        let db = Database::open::<PlaintextKeyValue>(StorageConfiguration::new(&path))?;
+        let plaintext = String::from("plaintext");
+        db.set_key("existing", &plaintext).execute()?;
+    }
+
+    {
+        // The existing entry is read as-is after a key is configured, and new
+        // entries are encrypted.
+        let db = Database::open::<EncryptedKeyValue>(StorageConfiguration::new(&path))?;
+        assert_eq!(
+            db.get_key("existing").into()?,
+            Some(String::from("plaintext"))
+        );
+        db.set_key("new", &String::from("encrypted")).execute()?;
+    }
+
+    // Without the master key, only the entry that was never migrated remains
+    // readable.
+    std::fs::remove_file(path.join("master-keys"))?;
+    let db = Database::open::<EncryptedKeyValue>(StorageConfiguration::new(&path))?;
+    assert_eq!(
+        db.get_key("existing").into()?,
+        Some(String::from("plaintext"))
+    );
+    assert!(db.get_key("new").into::<String>().is_err());
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "encryption")]
+fn key_value_reencryption() -> anyhow::Result<()> {
+    use std::path::Path;
+
+    use bonsaidb_core::document::KeyId;
+    use bonsaidb_core::keyvalue::KeyValue;
+    use bonsaidb_core::schema::Schema;
+
+    #[derive(Debug, Schema)]
+    #[schema(name = "reencrypted-kv", core = bonsaidb_core)]
+    struct PlaintextKeyValue;
+
+    #[derive(Debug, Schema)]
+    #[schema(name = "reencrypted-kv", key_value_encryption_key = Some(KeyId::Master), core = bonsaidb_core)]
+    struct EncryptedKeyValue;
+
+    const SECRET: &str = "plaintext-secret-value";
+
+    fn stored_in_plaintext(path: &Path) -> anyhow::Result<bool> {
+        for entry in std::fs::read_dir(path)? {
+            let entry = entry?;
+            let found = if entry.file_type()?.is_dir() {
+                stored_in_plaintext(&entry.path())?
+            } else {
+                std::fs::read(entry.path())?
+                    .windows(SECRET.len())
+                    .any(|window| window == SECRET.as_bytes())
+            };
+            if found {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    let path = TestDirectory::new("key-value-reencryption");
+    {
+        let db = Database::open::<PlaintextKeyValue>(StorageConfiguration::new(&path))?;
+        db.set_key("existing", &String::from(SECRET)).execute()?;
+    }
+    assert!(stored_in_plaintext(&path)?);
+
+    {
+        let db = Database::open::<EncryptedKeyValue>(StorageConfiguration::new(&path))?;
+        db.reencrypt()?;
+        assert_eq!(db.get_key("existing").into()?, Some(String::from(SECRET)));
+    }
+    assert!(!stored_in_plaintext(&path)?);
+
+    // Without the master key, the migrated entry can no longer be read.
+    std::fs::remove_file(path.join("master-keys"))?;
+    let db = Database::open::<EncryptedKeyValue>(StorageConfiguration::new(&path))?;
+    assert!(db.get_key("existing").into::<String>().is_err());
+
+    Ok(())
+}
+
 #[test]
 #[cfg(feature = "encryption")]
-fn encryption() -> anyhow::Result<()> {
+fn field_encryption() -> anyhow::Result<()> {
+    use bonsaidb_core::document::{Encrypted, KeyId};
+
+    let path = TestDirectory::new("field-encryption");
+    let db = Database::open::<()>(StorageConfiguration::new(&path))?;
+
+    let encrypted = Encrypted::encrypt(&String::from("secret"), &KeyId::Master, &db)?;
+    assert!(!encrypted
+        .as_encrypted_bytes()
+        .windows(6)
+        .any(|window| window == b"secret"));
+    assert_eq!(encrypted.decrypt(&db)?, "secret");
+
+    // Without permission to use the key, neither operation should succeed.
+    let restricted = db
+        .with_effective_permissions(Permissions::default())
+        .unwrap();
+    assert!(matches!(
+        Encrypted::encrypt(&String::from("secret"), &KeyId::Master, &restricted),
+        Err(bonsaidb_core::Error::PermissionDenied(_))
+    ));
+    assert!(matches!(
+        encrypted.decrypt(&restricted),
+        Err(bonsaidb_core::Error::PermissionDenied(_))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn session_expiration() -> anyhow::Result<()> {
+    use bonsaidb_core::connection::{HasSession, IdentityReference, StorageConnection};
+
+    use crate::StorageNonBlocking;
+
+    let path = TestDirectory::new("session-expiration");
+    let storage = Storage::open(
+        StorageConfiguration::new(&path).session_idle_timeout(Duration::from_millis(500)),
+    )?;
+    let user_id = storage.create_user("ecton")?;
+    let authenticated = storage.assume_identity(IdentityReference::user(user_id)?)?;
+    let session = authenticated.session().cloned().unwrap();
+
+    let sessions = storage.list_sessions(user_id)?;
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(Some(sessions[0].id), session.id);
+
+    // The session can be assumed until it has been idle for too long.
+    drop(storage.assume_session(session.clone())?);
+    std::thread::sleep(Duration::from_secs(1));
+    assert!(matches!(
+        storage.assume_session(session),
+        Err(bonsaidb_core::Error::SessionExpired)
+    ));
+    assert!(storage.list_sessions(user_id)?.is_empty());
+
+    // Revoked sessions can no longer be assumed.
+    let authenticated = storage.assume_identity(IdentityReference::user(user_id)?)?;
+    let session = authenticated.session().cloned().unwrap();
+    authenticated.logout()?;
+    assert!(matches!(
+        storage.assume_session(session),
+        Err(bonsaidb_core::Error::SessionExpired)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn disabled_users() -> anyhow::Result<()> {
+    use bonsaidb_core::connection::{HasSession, IdentityReference, StorageConnection};
+
+    use crate::StorageNonBlocking;
+
+    let path = TestDirectory::new("disabled-users");
+    let storage = Storage::open(StorageConfiguration::new(&path))?;
+    let user_id = storage.create_user("ecton")?;
+    let authenticated = storage.assume_identity(IdentityReference::user(user_id)?)?;
+    let session = authenticated.session().cloned().unwrap();
+
+    // Disabling a user revokes its sessions and prevents new ones.
+    storage.disable_user("ecton")?;
+    assert!(matches!(
+        storage.assume_session(session),
+        Err(bonsaidb_core::Error::SessionExpired)
+    ));
+    assert!(storage.list_sessions(user_id)?.is_empty());
+    assert!(matches!(
+        storage.assume_identity(IdentityReference::user(user_id)?),
+        Err(bonsaidb_core::Error::InvalidCredentials)
+    ));
+
+    storage.enable_user(user_id)?;
+    drop(storage.assume_identity(IdentityReference::user(user_id)?)?);
+
+    Ok(())
+}
+
+#[test]
+fn impersonation() -> anyhow::Result<()> {
+    use bonsaidb_core::connection::{HasSession, Identity, StorageConnection};
+    use bonsaidb_core::permissions::bonsai::{
+        database_resource_name, BonsaiAction, DatabaseAction, DocumentAction,
+    };
+
+    use crate::StorageNonBlocking;
+
+    let path = TestDirectory::new("impersonation");
+    let storage = Storage::open(StorageConfiguration::new(&path))?;
+    let user_id = storage.create_user("ecton")?;
+    storage.create_permission_group(
+        "readers",
+        vec![
+            Statement::for_resource(database_resource_name("tests")).allowing(
+                &BonsaiAction::Database(DatabaseAction::Document(DocumentAction::Get)),
+            ),
+        ],
+    )?;
+    storage.add_permission_group_to_user(user_id, "readers")?;
+
+    // The returned instance only has the user's permissions, and its session
+    // is not registered.
+    let as_user = storage.as_user("ecton")?;
+    assert!(matches!(
+        as_user.session().unwrap().identity(),
+        Some(Identity::User { id, .. }) if *id == user_id
+    ));
+    as_user.check_permission(
+        database_resource_name("tests"),
+        &BonsaiAction::Database(DatabaseAction::Document(DocumentAction::Get)),
+    )?;
+    assert!(matches!(
+        as_user.check_permission(
+            database_resource_name("tests"),
+            &BonsaiAction::Database(DatabaseAction::Document(DocumentAction::Insert)),
+        ),
+        Err(bonsaidb_core::Error::PermissionDenied(_))
+    ));
+    assert!(storage.list_sessions(user_id)?.is_empty());
+
+    // Impersonating requires permission to assume the user's identity.
+    let restricted = storage
+        .with_effective_permissions(Permissions::default())
+        .unwrap();
+    assert!(matches!(
+        restricted.as_user("ecton"),
+        Err(bonsaidb_core::Error::PermissionDenied(_))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn versioned_permission_updates() -> anyhow::Result<()> {
+    use bonsaidb_core::admin::{PermissionGroup, Role};
+    use bonsaidb_core::connection::StorageConnection;
+    use bonsaidb_core::permissions::bonsai::{
+        BonsaiResource, DatabaseResource, DocumentAction, KeyValueAction, ViewAction,
+    };
+    use bonsaidb_core::schema::{Collection, SerializedCollection};
+
+    let path = TestDirectory::new("versioned-permission-updates");
+    let storage = Storage::open(StorageConfiguration::new(&path))?;
+    let admin = storage.admin();
+    let tests = DatabaseResource::named("tests");
+    let group_id = storage.create_permission_group(
+        "editors",
+        vec![tests
+            .collection(&Basic::collection_name())
+            .allowing(DocumentAction::Get)
+            .allowing(ViewAction::Query)
+            .into()],
+    )?;
+    let group = PermissionGroup::get(&group_id, &admin)?.unwrap();
+    assert_eq!(group.contents.version, 0);
+
+    let version = storage.update_permission_group_statements(
+        "editors",
+        0,
+        vec![tests
+            .key_value_namespace(Some("drafts"))
+            .allowing(KeyValueAction::ExecuteOperation)
+            .into()],
+    )?;
+    assert_eq!(version, 1);
+
+    // Updating from a stale version fails without making changes.
+    assert!(matches!(
+        storage.update_permission_group_statements("editors", 0, Vec::new()),
+        Err(bonsaidb_core::Error::VersionConflict {
+            expected: 0,
+            current: 1
+        })
+    ));
+    // Statements that allow nothing are rejected.
+    assert!(matches!(
+        storage.set_permission_group_statements(
+            "editors",
+            vec![Statement::for_resource(tests.resource_name())]
+        ),
+        Err(bonsaidb_core::Error::InvalidPermissionStatement { index: 0, .. })
+    ));
+    let group = PermissionGroup::get(&group_id, &admin)?.unwrap();
+    assert_eq!(group.contents.version, 1);
+    assert_eq!(group.contents.statements.len(), 1);
+
+    let role_id = storage.create_role("editor")?;
+    storage.add_permission_group_to_role(role_id, group_id)?;
+    let role = Role::get(&role_id, &admin)?.unwrap();
+    assert_eq!(role.contents.version, 1);
+    assert!(matches!(
+        storage.update_role_permission_groups(role_id, 1, vec![group_id + 1]),
+        Err(bonsaidb_core::Error::PermissionGroupNotFound)
+    ));
+    assert_eq!(
+        storage.update_role_permission_groups(role_id, 1, Vec::new())?,
+        2
+    );
+    assert!(matches!(
+        storage.update_role_permission_groups(role_id, 1, vec![group_id]),
+        Err(bonsaidb_core::Error::VersionConflict { .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "password-hashing")]
+fn password_policy() -> anyhow::Result<()> {
+    use bonsaidb_core::admin::User;
+    use bonsaidb_core::connection::{Authentication, SensitiveString, StorageConnection};
     use bonsaidb_core::schema::SerializedCollection;
-    let path = TestDirectory::new("encryption");
-    let document_header = {
-        let db = Database::open::<BasicSchema>(StorageConfiguration::new(&path))?;
 
-        let document_header = db
-            .collection::<EncryptedBasic>()
-            .push(&EncryptedBasic::new("hello"))?;
+    use crate::config::{ArgonConfiguration, ArgonParams, Lockout, PasswordPolicy, SystemDefault};
 
-        // Retrieve the document, showing that it was stored successfully.
-        let doc = db
-            .collection::<EncryptedBasic>()
-            .get(&document_header.id)?
-            .expect("doc not found");
-        assert_eq!(&EncryptedBasic::document_contents(&doc)?.value, "hello");
+    let path = TestDirectory::new("password-policy");
+    let mut argon = ArgonConfiguration::default();
+    argon.params = ArgonParams::fixed(32 * 1024, 1, 1).unwrap();
+    let storage = Storage::open(
+        StorageConfiguration::new(&path)
+            .argon(argon)
+            .password_policy(
+                PasswordPolicy::default()
+                    .with_minimum_length(8)
+                    .with_lockout(Lockout::after_failures(2, Duration::from_secs(60))),
+            ),
+    )?;
+    let user_id = storage.create_user("ecton")?;
+    assert!(matches!(
+        storage.set_user_password(user_id, SensitiveString::from("hunter2")),
+        Err(bonsaidb_core::Error::PasswordTooShort { minimum_length: 8 })
+    ));
+    storage.set_user_password(user_id, SensitiveString::from("correct horse"))?;
 
-        document_header
+    let authenticate = |password: &str| {
+        storage.authenticate(Authentication::password(
+            "ecton",
+            SensitiveString::from(password),
+        )?)
     };
+    authenticate("correct horse")?;
 
-    // By resetting the encryption key, we should be able to force an error in
-    // decryption, which proves that the document was encrypted. To ensure the
-    // server starts up and generates a new key, we must delete the sealing key.
+    // A single failure is recorded, and is cleared by authenticating.
+    assert!(authenticate("battery staple").is_err());
+    let user = User::get(&user_id, &storage.admin())?.unwrap();
+    assert_eq!(user.contents.failed_authentications, 1);
+    authenticate("correct horse")?;
+    let user = User::get(&user_id, &storage.admin())?.unwrap();
+    assert_eq!(user.contents.failed_authentications, 0);
 
-    std::fs::remove_file(path.join("master-keys"))?;
+    // Two consecutive failures lock the user out, even when the correct
+    // password is used.
+    assert!(authenticate("battery staple").is_err());
+    assert!(authenticate("battery staple").is_err());
+    assert!(matches!(
+        authenticate("correct horse"),
+        Err(bonsaidb_core::Error::AccountLocked { .. })
+    ));
+    let user = User::get(&user_id, &storage.admin())?.unwrap();
+    assert!(user.contents.locked_until.is_some());
+
+    // Setting the password removes the lock.
+    storage.set_user_password(user_id, SensitiveString::from("correct horse"))?;
+    authenticate("correct horse")?;
+
+    // An administrator can also remove the lock.
+    assert!(authenticate("battery staple").is_err());
+    assert!(authenticate("battery staple").is_err());
+    storage.unlock_user(user_id)?;
+    let user = User::get(&user_id, &storage.admin())?.unwrap();
+    assert_eq!(user.contents.failed_authentications, 0);
+    assert!(user.contents.locked_until.is_none());
+    authenticate("correct horse")?;
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "password-hashing")]
+fn password_authentication_races() -> anyhow::Result<()> {
+    use bonsaidb_core::admin::User;
+    use bonsaidb_core::connection::{Authentication, SensitiveString, StorageConnection};
+    use bonsaidb_core::document::CollectionDocument;
+    use bonsaidb_core::schema::SerializedCollection;
+
+    use crate::config::{ArgonConfiguration, ArgonParams, Lockout, PasswordPolicy, SystemDefault};
+
+    let path = TestDirectory::new("password-authentication-races");
+    let mut argon = ArgonConfiguration::default();
+    argon.params = ArgonParams::fixed(32 * 1024, 1, 1).unwrap();
+    let storage = Storage::open(
+        StorageConfiguration::new(&path)
+            .argon(argon)
+            .password_policy(
+                PasswordPolicy::default()
+                    .with_lockout(Lockout::after_failures(3, Duration::from_secs(60))),
+            ),
+    )?;
+    let user_id = storage.create_user("ecton")?;
+    storage.set_user_password(user_id, SensitiveString::from("correct horse"))?;
+    let admin = storage.admin();
+    let authenticate = |password: &str| {
+        storage.authenticate(Authentication::password(
+            "ecton",
+            SensitiveString::from(password),
+        )?)
+    };
+    // Authenticates using a copy of the user loaded before other attempts
+    // were recorded, as happens when attempts race each other.
+    let authenticate_with_stale = |user: CollectionDocument<User>| {
+        storage.instance.authenticate_inner(
+            Authentication::password("ecton", SensitiveString::from("correct horse"))?,
+            Some(user),
+            None,
+            &admin,
+        )
+    };
+
+    // A failure recorded concurrently doesn't cause the successful attempt
+    // to fail, and is still cleared.
+    assert!(authenticate("battery staple").is_err());
+    let stale = User::get(&user_id, &admin)?.unwrap();
+    assert!(authenticate("battery staple").is_err());
+    authenticate_with_stale(stale)?;
+    let user = User::get(&user_id, &admin)?.unwrap();
+    assert_eq!(user.contents.failed_authentications, 0);
+
+    // A lock recorded concurrently isn't removed by the successful attempt.
+    assert!(authenticate("battery staple").is_err());
+    let stale = User::get(&user_id, &admin)?.unwrap();
+    assert!(authenticate("battery staple").is_err());
+    assert!(authenticate("battery staple").is_err());
+    assert!(matches!(
+        authenticate_with_stale(stale),
+        Err(bonsaidb_core::Error::AccountLocked { .. })
+    ));
+    let user = User::get(&user_id, &admin)?.unwrap();
+    assert!(user.contents.locked_until.is_some());
+
+    // A password changed concurrently rejects the old password.
+    storage.unlock_user(user_id)?;
+    assert!(authenticate("battery staple").is_err());
+    let stale = User::get(&user_id, &admin)?.unwrap();
+    storage.set_user_password(user_id, SensitiveString::from("battery staple"))?;
+    assert!(matches!(
+        authenticate_with_stale(stale),
+        Err(bonsaidb_core::Error::InvalidCredentials)
+    ));
+    authenticate("battery staple")?;
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "password-hashing")]
+fn password_expiration() -> anyhow::Result<()> {
+    use bonsaidb_core::connection::{Authentication, SensitiveString, StorageConnection};
+
+    use crate::config::{ArgonConfiguration, ArgonParams, PasswordPolicy, SystemDefault};
+
+    let path = TestDirectory::new("password-expiration");
+    let mut argon = ArgonConfiguration::default();
+    argon.params = ArgonParams::fixed(32 * 1024, 1, 1).unwrap();
+    let storage = Storage::open(
+        StorageConfiguration::new(&path)
+            .argon(argon)
+            .password_policy(PasswordPolicy::default().with_maximum_age(Duration::ZERO)),
+    )?;
+    let user_id = storage.create_user("ecton")?;
+    storage.set_user_password(user_id, SensitiveString::from("hunter2"))?;
+    assert!(matches!(
+        storage.authenticate(Authentication::password(
+            "ecton",
+            SensitiveString::from("hunter2")
+        )?),
+        Err(bonsaidb_core::Error::PasswordExpired)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn effective_permissions() -> anyhow::Result<()> {
+    use bonsaidb_core::connection::StorageConnection;
+    use bonsaidb_core::permissions::bonsai::{
+        bonsaidb_resource_name, database_resource_name, BonsaiAction, DatabaseAction,
+        DocumentAction, ServerAction,
+    };
+
+    let path = TestDirectory::new("effective-permissions");
+    let storage = Storage::open(
+        StorageConfiguration::new(&path).authenticated_permissions(vec![
+            Statement::for_any().allowing(&BonsaiAction::Server(ServerAction::ListDatabases))
+        ]),
+    )?;
+    let user_id = storage.create_user("ecton")?;
+    storage.create_permission_group(
+        "readers",
+        vec![
+            Statement::for_resource(database_resource_name("tests")).allowing(
+                &BonsaiAction::Database(DatabaseAction::Document(DocumentAction::Get)),
+            ),
+        ],
+    )?;
+    storage.create_role("reader")?;
+    storage.add_permission_group_to_role("reader", "readers")?;
+
+    // Permissions granted to all authenticated sessions are included.
+    let list_databases = BonsaiAction::Server(ServerAction::ListDatabases);
+    let get_document = BonsaiAction::Database(DatabaseAction::Document(DocumentAction::Get));
+    storage.check_user_permission(user_id, bonsaidb_resource_name(), &list_databases)?;
+    assert!(matches!(
+        storage.check_user_permission(user_id, database_resource_name("tests"), &get_document),
+        Err(bonsaidb_core::Error::PermissionDenied(_))
+    ));
+
+    // Permissions granted through roles are included.
+    storage.add_role_to_user(user_id, "reader")?;
+    assert!(storage
+        .effective_permissions(user_id)?
+        .allowed_to(database_resource_name("tests"), &get_document));
+    storage.check_user_permission("ecton", database_resource_name("tests"), &get_document)?;
+
+    Ok(())
+}
+
+#[test]
+fn keyvalue_permissions() -> anyhow::Result<()> {
+    use bonsaidb_core::keyvalue::KeyValue;
+    use bonsaidb_core::permissions::bonsai::{
+        keyvalue_key_prefix_resource_name, keyvalue_namespace_resource_name, BonsaiAction,
+        DatabaseAction, KeyValueAction,
+    };
+
+    let path = TestDirectory::new("keyvalue-permissions");
+    let db = Database::open::<()>(StorageConfiguration::new(&path))?;
+    let execute =
+        BonsaiAction::Database(DatabaseAction::KeyValue(KeyValueAction::ExecuteOperation));
+    let restricted = db
+        .with_effective_permissions(Permissions::from(vec![
+            Statement::for_resource(keyvalue_namespace_resource_name("default", Some("tenant")))
+                .allowing(&execute),
+            Statement::for_resource(keyvalue_key_prefix_resource_name(
+                "default", None, "public:",
+            ))
+            .allowing(&execute),
+        ]))
+        .unwrap();
+
+    // Namespace grants apply to every key in the namespace.
+    let tenant = restricted.with_key_namespace("tenant");
+    tenant.set_key("a", &1_u32).execute()?;
+    assert_eq!(tenant.count_keys()?, 1);
+    assert_eq!(tenant.delete_key_namespace()?, 1);
+
+    // Prefix grants apply to every key beginning with the prefix.
+    restricted.set_key("public:", &1_u32).execute()?;
+    restricted.set_key("public:a", &1_u32).execute()?;
+    assert!(matches!(
+        restricted.set_key("public", &1_u32).execute(),
+        Err(bonsaidb_core::Error::PermissionDenied(_))
+    ));
+    assert!(matches!(
+        restricted
+            .with_key_namespace("other")
+            .get_key("public:a")
+            .query(),
+        Err(bonsaidb_core::Error::PermissionDenied(_))
+    ));
+
+    // A prefix grant doesn't allow operating on the entire namespace.
+    assert!(matches!(
+        restricted.count_keys(),
+        Err(bonsaidb_core::Error::PermissionDenied(_))
+    ));
+    assert!(matches!(
+        restricted.list_key_namespaces(),
+        Err(bonsaidb_core::Error::PermissionDenied(_))
+    ));
+
+    Ok(())
+}
 
+#[test]
+fn locks() -> anyhow::Result<()> {
+    let path = TestDirectory::new("locks");
+    let db = Database::open::<()>(StorageConfiguration::new(&path))?;
+
+    let lock = db.lock("a", Duration::from_millis(300))?;
+    assert_eq!(lock.name(), "a");
+    assert!(db.try_lock("a", Duration::from_secs(1))?.is_none());
+    // Other locks are independent.
+    let other = db.try_lock("b", Duration::from_secs(1))?.unwrap();
+
+    // The lock is renewed in the background, so it remains held after its
+    // original expiration.
+    std::thread::sleep(Duration::from_millis(600));
+    assert!(lock.is_held()?);
+    assert!(db.try_lock("a", Duration::from_secs(1))?.is_none());
+
+    assert!(lock.release()?);
+    let lock = db.try_lock("a", Duration::from_secs(1))?.unwrap();
+    drop(lock);
+    drop(other);
+    assert!(db.try_lock("a", Duration::from_secs(1))?.is_some());
+    assert!(db.try_lock("b", Duration::from_secs(1))?.is_some());
+
+    Ok(())
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn parquet_export() -> anyhow::Result<()> {
+    use bonsaidb_core::document::CollectionDocument;
+    use bonsaidb_core::schema::{Map, SerializedCollection};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    use crate::ParquetMapping;
+
+    let path = TestDirectory::new("parquet-export");
     let db = Database::open::<BasicSchema>(StorageConfiguration::new(&path))?;
+    Basic::new("a").with_parent_id(1).push_into(&db)?;
+    Basic::new("b").push_into(&db)?;
+    Basic::new("c").with_parent_id(1).push_into(&db)?;
 
-    // Try retrieving the document, but expect an error decrypting.
-    if let Err(bonsaidb_core::Error::Other { error, .. }) =
-        db.collection::<EncryptedBasic>().get(&document_header.id)
+    let documents = path.as_ref().join("basic.parquet");
+    let exported = db.export_collection_parquet::<Basic>(
+        &documents,
+        &ParquetMapping::<CollectionDocument<Basic>>::new()
+            .with_u64("id", |doc| Some(doc.header.id))
+            .with_string("value", |doc| Some(doc.contents.value.clone()))
+            .with_u64("parent_id", |doc| doc.contents.parent_id),
+    )?;
+    assert_eq!(exported, 3);
+    let batches = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(&documents)?)?
+        .build()?
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(
+        batches.iter().map(|batch| batch.num_rows()).sum::<usize>(),
+        3
+    );
+    assert_eq!(batches[0].num_columns(), 3);
+    assert_eq!(batches[0].column(2).null_count(), 1);
+
+    let entries = path.as_ref().join("by-parent-id.parquet");
+    let exported = db.export_view_parquet::<BasicByParentId>(
+        &entries,
+        &ParquetMapping::<Map<Option<u64>, usize>>::new()
+            .with_u64("parent_id", |entry| entry.key)
+            .with_u64("source", |entry| entry.source.id.deserialize().ok()),
+    )?;
+    assert_eq!(exported, 3);
+
+    Ok(())
+}
+
+#[cfg(feature = "sql")]
+#[test]
+fn sql_queries() -> anyhow::Result<()> {
+    use bonsaidb_core::document::DocumentId;
+    use bonsaidb_core::schema::SerializedCollection;
+    use bonsaidb_core::sql::{SqlError, SqlValue};
+
+    let path = TestDirectory::new("sql-queries");
+    let db = Database::open::<BasicSchema>(
+        StorageConfiguration::new(&path).sql_view("basic_by_parent_id", &BasicByParentId),
+    )?;
+    let a = Basic::new("a").with_parent_id(1).push_into(&db)?;
+    Basic::new("b").push_into(&db)?;
+    let c = Basic::new("c").with_parent_id(2).push_into(&db)?;
+
+    let results = db.query_sql("SELECT * FROM basic_by_parent_id")?;
+    assert_eq!(results.columns, vec!["key", "value", "source"]);
+    assert_eq!(results.rows.len(), 3);
+
+    let results = db.query_sql(
+        "SELECT key, source FROM basic_by_parent_id WHERE key >= 1 ORDER BY key DESC LIMIT 5",
+    )?;
+    assert_eq!(results.columns, vec!["key", "source"]);
+    let rows = results
+        .rows
+        .iter()
+        .map(|row| match row.as_slice() {
+            [SqlValue::Integer(key), SqlValue::Text(source)] => (*key, source.clone()),
+            other => unreachable!("unexpected row: {other:?}"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        rows,
+        vec![
+            (2, DocumentId::from_u64(c.header.id).to_string()),
+            (1, DocumentId::from_u64(a.header.id).to_string())
+        ]
+    );
+
+    let results = db.query_sql("SELECT value FROM basic_by_parent_id WHERE key = NULL")?;
+    assert!(matches!(
+        results.rows.as_slice(),
+        [row] if matches!(row.as_slice(), [SqlValue::Integer(1)])
+    ));
+
+    assert!(matches!(
+        db.query_sql("SELECT * FROM basic"),
+        Err(SqlError::UnknownTable(_))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn sharded_collections() -> anyhow::Result<()> {
+    use bonsaidb_core::document::{CollectionDocument, DocumentId, Header};
+    use bonsaidb_core::schema::SerializedCollection;
+    use bonsaidb_core::transaction::{Operation, Transaction};
+
+    use crate::config::{Shard, Sharding};
+
+    let path = TestDirectory::new("sharded-collections");
+    let shard_path = TestDirectory::new("sharded-collections-shard");
+    let sharding = Sharding::hashed()
+        .with_shard(Shard::new("a"))
+        .with_shard(Shard::new("b").path(&shard_path));
+    let db = Database::open::<BasicSchema>(
+        StorageConfiguration::new(&path).shard_collection::<Basic>("default", sharding.clone()),
+    )?;
+
+    let mut ids = Vec::new();
+    for value in 0..10_u64 {
+        let doc = Basic::new(value.to_string())
+            .with_parent_id(value % 2)
+            .push_into(&db)?;
+        ids.push(doc.header.id);
+    }
+    assert_eq!(ids, (1..=10).collect::<Vec<_>>());
+    assert!(std::fs::read_dir(&shard_path)?.next().is_some());
+
+    assert_eq!(Basic::get(&ids[3], &db)?.unwrap().contents.value, "3");
+    let all = Basic::all(&db).query()?;
+    assert_eq!(all.iter().map(|doc| doc.header.id).collect::<Vec<_>>(), ids);
+    let last = Basic::all(&db).descending().limit(3).query()?;
+    assert_eq!(
+        last.iter().map(|doc| doc.header.id).collect::<Vec<_>>(),
+        vec![10, 9, 8]
+    );
+    assert_eq!(Basic::all(&db).count()?, 10);
+
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .with_key(&Some(1))
+            .query()?
+            .len(),
+        5
+    );
+    assert_eq!(db.view::<BasicByParentId>().reduce()?, 10);
+    let grouped = db.view::<BasicByParentId>().reduce_grouped()?;
+    assert_eq!(
+        grouped
+            .iter()
+            .map(|group| (group.key, group.value))
+            .collect::<Vec<_>>(),
+        vec![(Some(0), 5), (Some(1), 5)]
+    );
+
+    // A transaction can't modify documents stored in different shards.
+    let shard_of =
+        |doc: &CollectionDocument<Basic>| sharding.shard_for(&DocumentId::from_u64(doc.header.id));
+    let other = all
+        .iter()
+        .find(|doc| shard_of(doc) != shard_of(&all[0]))
+        .expect("documents are spread across shards");
+    let mut transaction = Transaction::new();
+    transaction.push(Operation::delete(
+        Basic::collection_name(),
+        Header::try_from(all[0].header.clone())?,
+    ));
+    transaction.push(Operation::delete(
+        Basic::collection_name(),
+        Header::try_from(other.header.clone())?,
+    ));
+    assert!(transaction.apply(&db).is_err());
+
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .with_key(&Some(0))
+            .delete_docs()?,
+        5
+    );
+    assert_eq!(Basic::all(&db).count()?, 5);
+
+    Ok(())
+}
+
+#[test]
+fn raw_trees() -> anyhow::Result<()> {
+    use bonsaidb_core::schema::SerializedCollection;
+    use nebari::tree::Unversioned;
+
+    let path = TestDirectory::new("raw-trees");
     {
-        assert!(error.contains("vault"));
-    } else {
-        panic!("successfully retrieved encrypted document without keys");
+        let db = Database::open::<Basic>(StorageConfiguration::new(&path))?;
+        db.collection::<Basic>().push(&Basic::new("test"))?;
+
+        let tree = db.raw_tree("a")?;
+        tree.set(b"key".to_vec(), b"value".to_vec())?;
+        assert_eq!(tree.get(b"key")?.as_deref(), Some(&b"value"[..]));
+
+        // Raw trees are independent of each other.
+        let b = db.raw_tree("b")?;
+        assert!(b.get(b"key")?.is_none());
+
+        let mut transaction =
+            db.raw_transaction(&[db.raw_tree_root("a")?, db.raw_tree_root("b")?])?;
+        transaction
+            .tree::<Unversioned>(0)
+            .unwrap()
+            .set(b"key".to_vec(), b"updated".to_vec())?;
+        transaction
+            .tree::<Unversioned>(1)
+            .unwrap()
+            .set(b"key".to_vec(), b"b".to_vec())?;
+        transaction.commit()?;
+
+        assert!(db.raw_tree_root("").is_err());
     }
 
+    let db = Database::open::<Basic>(StorageConfiguration::new(&path))?;
+    assert_eq!(
+        db.raw_tree("a")?.get(b"key")?.as_deref(),
+        Some(&b"updated"[..])
+    );
+    assert_eq!(db.raw_tree("b")?.get(b"key")?.as_deref(), Some(&b"b"[..]));
+    // Documents are unaffected by raw trees.
+    assert_eq!(Basic::all(&db).count()?, 1);
+
+    assert!(db.delete_raw_tree("b")?);
+    assert!(db.raw_tree("b")?.get(b"key")?.is_none());
+
     Ok(())
 }
 
@@ -318,3 +2439,447 @@ fn expiration_after_close() -> anyhow::Result<()> {
     }
     Ok(())
 }
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn import() -> anyhow::Result<()> {
+    use bonsaidb_core::connection::AsyncConnection;
+    use bonsaidb_core::schema::SerializedCollection;
+    use bonsaidb_core::transaction::Operation;
+    use futures::StreamExt;
+
+    use crate::AsyncDatabase;
+
+    let path = TestDirectory::new("import");
+    let db = AsyncDatabase::open::<Basic>(StorageConfiguration::new(&path)).await?;
+    // Produces 25 documents, failing after the first `fail_at` operations.
+    let operations = |fail_at: Option<usize>| {
+        futures::stream::iter(0..25).map(move |index| {
+            if Some(index) == fail_at {
+                Err(bonsaidb_core::Error::other("test", "source failure"))
+            } else {
+                Operation::push_serialized::<Basic>(&Basic::new(index.to_string()))
+            }
+        })
+    };
+
+    // The operations are applied in batches of 5. The operations gathered
+    // into the batch that was pending when the stream failed are not applied.
+    let mut reported = Vec::new();
+    let err = db
+        .import(operations(Some(17)))
+        .max_operations(5)
+        .on_progress(|progress| reported.push(progress.operations))
+        .execute()
+        .await
+        .unwrap_err();
+    assert_eq!(reported, vec![5, 10, 15]);
+    assert_eq!(err.progress.operations, 15);
+    assert_eq!(err.progress.transactions, 3);
+    assert_eq!(Basic::all_async(&db).count().await?, 15);
+
+    // Resuming skips the operations that have already been applied.
+    let progress = db
+        .import(operations(None))
+        .max_operations(5)
+        .resume_from(err.progress)
+        .execute()
+        .await?;
+    assert_eq!(progress.operations, 25);
+    assert_eq!(progress.transactions, 5);
+    let mut values = Basic::all_async(&db)
+        .await?
+        .into_iter()
+        .map(|doc| doc.contents.value.parse::<usize>())
+        .collect::<Result<Vec<_>, _>>()?;
+    values.sort_unstable();
+    assert_eq!(values, (0..25).collect::<Vec<_>>());
+
+    Ok(())
+}
+
+#[test]
+fn cross_database_transactions() -> anyhow::Result<()> {
+    use bonsaidb_core::connection::StorageConnection;
+    use bonsaidb_core::schema::SerializedCollection;
+    use bonsaidb_core::transaction::Operation;
+
+    use crate::CrossDatabaseTransaction;
+
+    let path = TestDirectory::new("cross-database-transactions");
+    let storage = Storage::open(StorageConfiguration::new(&path).with_schema::<Basic>()?)?;
+    let tenant = storage.create_database::<Basic>("tenant", false)?;
+    let ledger = storage.create_database::<Basic>("ledger", false)?;
+
+    let results = storage.apply_cross_database_transaction(
+        CrossDatabaseTransaction::new()
+            .with(
+                "tenant",
+                Operation::push_serialized::<Basic>(&Basic::new("order"))?,
+            )
+            .with(
+                "ledger",
+                Operation::push_serialized::<Basic>(&Basic::new("entry"))?,
+            ),
+    )?;
+    assert_eq!(results["tenant"].len(), 1);
+    assert_eq!(results["ledger"].len(), 1);
+    let entry = Basic::all(&ledger).query()?.into_iter().next().unwrap();
+    assert_eq!(entry.contents.value, "entry");
+
+    // A conflict in one database prevents every database from changing.
+    let stale_header = entry.header.clone();
+    let mut entry = entry;
+    entry.contents.value = String::from("updated entry");
+    entry.update(&ledger)?;
+    let err = storage
+        .apply_cross_database_transaction(
+            CrossDatabaseTransaction::new()
+                .with(
+                    "tenant",
+                    Operation::push_serialized::<Basic>(&Basic::new("second order"))?,
+                )
+                .with(
+                    "ledger",
+                    Operation::update_serialized::<Basic>(
+                        stale_header,
+                        &Basic::new("conflicting entry"),
+                    )?,
+                ),
+        )
+        .unwrap_err();
+    assert!(matches!(err, bonsaidb_core::Error::DocumentConflict(..)));
+    assert_eq!(Basic::all(&tenant).count()?, 1);
+    assert_eq!(
+        Basic::get(&entry.header.id, &ledger)?
+            .unwrap()
+            .contents
+            .value,
+        "updated entry"
+    );
+
+    // Missing databases are reported before anything is applied.
+    let err = storage
+        .apply_cross_database_transaction(
+            CrossDatabaseTransaction::new()
+                .with(
+                    "tenant",
+                    Operation::push_serialized::<Basic>(&Basic::new("third order"))?,
+                )
+                .with(
+                    "missing",
+                    Operation::push_serialized::<Basic>(&Basic::new("entry"))?,
+                ),
+        )
+        .unwrap_err();
+    assert!(matches!(err, bonsaidb_core::Error::DatabaseNotFound(_)));
+    assert_eq!(Basic::all(&tenant).count()?, 1);
+
+    Ok(())
+}
+
+#[test]
+fn view_queries_across_databases() -> anyhow::Result<()> {
+    use bonsaidb_core::connection::{MaybeOwned, QueryKey, Sort, StorageConnection};
+    use bonsaidb_core::schema::{Schema, SerializedCollection};
+    use bonsaidb_core::test_util::BasicByCategory;
+
+    let path = TestDirectory::new("view-queries-across-databases");
+    let storage = Storage::open(
+        StorageConfiguration::new(&path)
+            .with_schema::<Basic>()?
+            .with_schema::<BasicSchema>()?,
+    )?;
+    let first = storage.create_database::<Basic>("first", false)?;
+    let second = storage.create_database::<Basic>("second", false)?;
+    let other_schema = storage.create_database::<BasicSchema>("other-schema", false)?;
+    Basic::new("a").with_category("red").push_into(&first)?;
+    Basic::new("b").with_category("blue").push_into(&first)?;
+    Basic::new("c").with_category("red").push_into(&second)?;
+    Basic::new("d")
+        .with_category("green")
+        .push_into(&other_schema)?;
+
+    // Every database containing the view is queried, and entries are ordered
+    // by key and then by database.
+    let entries = storage.query_view_in_databases::<BasicByCategory>(
+        None,
+        None,
+        Sort::Ascending,
+        None,
+        AccessPolicy::UpdateBefore,
+    )?;
+    assert_eq!(
+        entries
+            .iter()
+            .map(|entry| (entry.database.as_str(), entry.mapping.key.as_str()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("first", "blue"),
+            ("other-schema", "green"),
+            ("first", "red"),
+            ("second", "red"),
+        ]
+    );
+
+    // Queries can be limited to a single schema, and the order and limit
+    // apply to the merged entries.
+    let entries = storage.query_view_in_databases::<BasicByCategory>(
+        Some(&Basic::schema_name()),
+        Some(QueryKey::Matches(MaybeOwned::Owned(String::from("red")))),
+        Sort::Descending,
+        Some(1),
+        AccessPolicy::UpdateBefore,
+    )?;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].database, "first");
+    assert_eq!(entries[0].mapping.key, "red");
+
+    Ok(())
+}
+
+#[test]
+fn tenants() -> anyhow::Result<()> {
+    use bonsaidb_core::connection::StorageConnection;
+    use bonsaidb_core::permissions::bonsai::{
+        database_resource_name, BonsaiAction, DatabaseAction, DocumentAction,
+    };
+    use bonsaidb_core::schema::SerializedCollection;
+
+    use crate::DatabaseNonBlocking;
+
+    let path = TestDirectory::new("tenants");
+    let size = {
+        let storage = Storage::open(StorageConfiguration::new(&path).with_schema::<Basic>()?)?;
+        let tenants = storage.tenants::<Basic>("customers");
+        let acme = tenants.create("acme")?;
+        tenants.create("globex")?;
+        assert_eq!(acme.name(), "customers.acme");
+        assert_eq!(tenants.list()?, vec!["acme", "globex"]);
+
+        // Each tenant's users are only granted access to the tenant's database.
+        let user_id = tenants.create_user("acme", "wile")?;
+        let insert = BonsaiAction::Database(DatabaseAction::Document(DocumentAction::Insert));
+        storage.check_user_permission(
+            user_id,
+            database_resource_name("customers.acme"),
+            &insert,
+        )?;
+        assert!(storage
+            .check_user_permission(user_id, database_resource_name("customers.globex"), &insert)
+            .is_err());
+        tenants.remove_user("acme", user_id)?;
+        assert!(storage
+            .check_user_permission(user_id, database_resource_name("customers.acme"), &insert)
+            .is_err());
+
+        // Once a tenant's database reaches its quota, writes are rejected.
+        let document = Basic::new("a").push_into(&acme)?;
+        let size = tenants.size("acme")?;
+        assert!(size > 0);
+        tenants.set_quota("acme", Some(size))?;
+        assert!(matches!(
+            Basic::new("b").push_into(&acme),
+            Err(bonsaidb_core::schema::InsertError {
+                error: bonsaidb_core::Error::DatabaseQuotaExceeded { .. },
+                ..
+            })
+        ));
+        // Other tenants aren't affected, and deleting documents is still allowed.
+        Basic::new("b").push_into(&tenants.database("globex")?)?;
+        document.delete(&acme)?;
+
+        size
+    };
+
+    // Quotas are persisted.
+    let storage = Storage::open(StorageConfiguration::new(&path).with_schema::<Basic>()?)?;
+    let tenants = storage.tenants::<Basic>("customers");
+    assert_eq!(storage.database_quota("customers.acme"), Some(size));
+    tenants.set_quota("acme", None)?;
+    Basic::new("b").push_into(&tenants.database("acme")?)?;
+
+    tenants.delete("globex")?;
+    assert_eq!(tenants.list()?, vec!["acme"]);
+    assert!(storage.delete_permission_group("customers.globex").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn view_queries_omit_unreadable_documents() -> anyhow::Result<()> {
+    use bonsaidb_core::connection::StorageConnection;
+    use bonsaidb_core::document::DocumentId;
+    use bonsaidb_core::permissions::bonsai::{
+        document_resource_name, BonsaiAction, DatabaseAction, DocumentAction, ViewAction,
+    };
+    use bonsaidb_core::schema::view::aggregation::Aggregation;
+    use bonsaidb_core::schema::view::map::MappedValue;
+    use bonsaidb_core::schema::{Collection, SerializedCollection};
+    use bonsaidb_core::test_util::BasicByCategory;
+
+    for redact in [false, true] {
+        let path = TestDirectory::new(format!("view-queries-omit-unreadable-{redact}"));
+        let storage = Storage::open(
+            StorageConfiguration::new(&path)
+                .with_schema::<Basic>()?
+                .redact_unreadable_view_entries(redact),
+        )?;
+        let db = storage.create_database::<Basic>("tests", false)?;
+        let readable = Basic::new("readable").with_category("a").push_into(&db)?;
+        Basic::new("hidden").with_category("a").push_into(&db)?;
+        Basic::new("hidden").with_category("b").push_into(&db)?;
+
+        let readable_id = DocumentId::from_u64(readable.header.id);
+        let restricted = storage
+            .with_effective_permissions(Permissions::from(vec![
+                Statement::for_any()
+                    .allowing(&BonsaiAction::Database(DatabaseAction::View(
+                        ViewAction::Query,
+                    )))
+                    .allowing(&BonsaiAction::Database(DatabaseAction::View(
+                        ViewAction::Reduce,
+                    ))),
+                Statement::for_resource(document_resource_name(
+                    "tests",
+                    &Basic::collection_name(),
+                    &readable_id,
+                ))
+                .allowing(&BonsaiAction::Database(DatabaseAction::Document(
+                    DocumentAction::Get,
+                ))),
+            ]))
+            .unwrap()
+            .database::<Basic>("tests")?;
+
+        // Documents that can't be read are omitted along with their mappings,
+        // rather than failing the query.
+        let mappings = restricted
+            .view::<BasicByCategory>()
+            .with_key("a")
+            .query_with_collection_docs()?;
+        assert_eq!(mappings.len(), 1);
+        assert_eq!(mappings.documents.len(), 1);
+        assert_eq!(mappings.get(0).unwrap().document.contents.value, "readable");
+
+        // Mappings without documents are only omitted when redaction is
+        // enabled.
+        let mappings = restricted.view::<BasicByCategory>().with_key("a").query()?;
+        assert_eq!(mappings.len(), if redact { 1 } else { 2 });
+
+        // Counts, reductions, and aggregations only include the mappings of
+        // readable documents when redaction is enabled.
+        let view = || restricted.view::<BasicByCategory>();
+        assert_eq!(view().with_key("a").count()?, if redact { 1 } else { 2 });
+        assert_eq!(view().count()?, if redact { 1 } else { 3 });
+        assert_eq!(view().with_key("a").reduce()?, if redact { 1 } else { 2 });
+        assert_eq!(view().reduce()?, if redact { 1 } else { 3 });
+        let expected_groups = if redact {
+            vec![MappedValue::new(String::from("a"), 1)]
+        } else {
+            vec![
+                MappedValue::new(String::from("a"), 2),
+                MappedValue::new(String::from("b"), 1),
+            ]
+        };
+        assert_eq!(view().reduce_grouped()?, expected_groups);
+        assert_eq!(
+            view().aggregate(&[Aggregation::Count])?,
+            vec![Some(if redact { 1. } else { 3. })]
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn security_configuration() -> anyhow::Result<()> {
+    use bonsaidb_core::admin::{RoleConfiguration, SecurityRecord, UserConfiguration};
+    use bonsaidb_core::connection::StorageConnection;
+    use bonsaidb_core::permissions::bonsai::{BonsaiResource, DatabaseResource};
+
+    let path = TestDirectory::new("security-configuration");
+    let storage = Storage::open(StorageConfiguration::new(&path))?;
+    storage.create_user("stale")?;
+
+    let mut configuration = storage.export_security_configuration()?;
+    assert!(configuration.users.remove("stale").is_some());
+    configuration.permission_groups.insert(
+        String::from("editors"),
+        vec![DatabaseResource::named("tests").allowing_all().into()],
+    );
+    configuration.roles.insert(
+        String::from("editor"),
+        RoleConfiguration {
+            permission_groups: [String::from("editors")].into(),
+        },
+    );
+    configuration.users.insert(
+        String::from("ecton"),
+        UserConfiguration {
+            roles: [String::from("editor")].into(),
+            disabled: true,
+            ..UserConfiguration::default()
+        },
+    );
+
+    let changes = storage.apply_security_configuration(&configuration)?;
+    assert_eq!(
+        changes.created,
+        [
+            SecurityRecord::PermissionGroup(String::from("editors")),
+            SecurityRecord::Role(String::from("editor")),
+            SecurityRecord::User(String::from("ecton")),
+        ]
+    );
+    assert!(changes.updated.is_empty());
+    assert_eq!(
+        changes.deleted,
+        [SecurityRecord::User(String::from("stale"))]
+    );
+    assert!(storage.user_by_name("stale")?.is_none());
+    let user = storage.user_by_name("ecton")?.unwrap();
+    assert!(user.contents.disabled);
+    assert_eq!(user.contents.roles.len(), 1);
+
+    // Applying the same configuration again makes no changes, and exporting
+    // returns the applied configuration.
+    assert!(storage
+        .apply_security_configuration(&configuration)?
+        .is_empty());
+    let exported = storage.export_security_configuration()?;
+    assert_eq!(exported.roles, configuration.roles);
+    assert_eq!(exported.users, configuration.users);
+
+    configuration.users.insert(
+        String::from("ecton"),
+        UserConfiguration {
+            permission_groups: [String::from("editors")].into(),
+            ..UserConfiguration::default()
+        },
+    );
+    let changes = storage.apply_security_configuration(&configuration)?;
+    assert_eq!(
+        changes.updated,
+        [SecurityRecord::User(String::from("ecton"))]
+    );
+    let user = storage.user_by_name("ecton")?.unwrap();
+    assert!(!user.contents.disabled);
+    assert!(user.contents.roles.is_empty());
+    assert_eq!(user.contents.groups.len(), 1);
+
+    // Configurations referencing records they don't contain are rejected
+    // without making changes.
+    configuration
+        .roles
+        .get_mut("editor")
+        .unwrap()
+        .permission_groups
+        .insert(String::from("missing"));
+    assert!(matches!(
+        storage.apply_security_configuration(&configuration),
+        Err(bonsaidb_core::Error::Other { .. })
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::ops::AddAssign;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+/// The resources consumed by operations, as tracked by a
+/// [`Storage`](crate::Storage).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Usage {
+    /// The time spent mapping documents into views.
+    pub mapping: Duration,
+    /// The time spent executing view queries, reductions, and aggregations.
+    pub querying: Duration,
+    /// The time spent compacting trees.
+    pub compacting: Duration,
+    /// The number of bytes of documents and view entries read.
+    pub bytes_read: u64,
+    /// The number of bytes of documents written.
+    pub bytes_written: u64,
+}
+
+impl Usage {
+    /// Returns the total time spent across all activities.
+    #[must_use]
+    pub fn total_time(&self) -> Duration {
+        self.mapping + self.querying + self.compacting
+    }
+}
+
+impl AddAssign for Usage {
+    fn add_assign(&mut self, rhs: Self) {
+        self.mapping += rhs.mapping;
+        self.querying += rhs.querying;
+        self.compacting += rhs.compacting;
+        self.bytes_read = self.bytes_read.saturating_add(rhs.bytes_read);
+        self.bytes_written = self.bytes_written.saturating_add(rhs.bytes_written);
+    }
+}
+
+/// The resources consumed by each database and user since a
+/// [`Storage`](crate::Storage) was opened.
+///
+/// Operations are attributed to the database they were performed on and, if
+/// the operation was performed using an authenticated session, the user that
+/// the session belongs to. View mapping and compaction run in the background,
+/// and are attributed to the user whose request caused them to run.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct UsageReport {
+    /// The resources consumed by each database, keyed by database name.
+    pub databases: HashMap<String, Usage>,
+    /// The resources consumed by each user, keyed by user id.
+    pub users: HashMap<u64, Usage>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Activity {
+    Mapping,
+    Querying,
+    Compacting,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct UsageTracker {
+    report: Mutex<UsageReport>,
+}
+
+impl UsageTracker {
+    pub fn record(&self, database: &str, user: Option<u64>, usage: Usage) {
+        let mut report = self.report.lock();
+        if let Some(database_usage) = report.databases.get_mut(database) {
+            *database_usage += usage;
+        } else {
+            report.databases.insert(database.to_string(), usage);
+        }
+        if let Some(user) = user {
+            *report.users.entry(user).or_default() += usage;
+        }
+    }
+
+    pub fn report(&self) -> UsageReport {
+        self.report.lock().clone()
+    }
+}
+
+/// Records the time elapsed between its creation and being dropped.
+#[must_use]
+pub(crate) struct UsageTimer<'a> {
+    tracker: &'a UsageTracker,
+    database: &'a str,
+    user: Option<u64>,
+    activity: Activity,
+    started_at: Instant,
+}
+
+impl<'a> UsageTimer<'a> {
+    pub fn new(
+        tracker: &'a UsageTracker,
+        database: &'a str,
+        user: Option<u64>,
+        activity: Activity,
+    ) -> Self {
+        Self {
+            tracker,
+            database,
+            user,
+            activity,
+            started_at: Instant::now(),
+        }
+    }
+}
+
+impl Drop for UsageTimer<'_> {
+    fn drop(&mut self) {
+        let elapsed = self.started_at.elapsed();
+        let mut usage = Usage::default();
+        match self.activity {
+            Activity::Mapping => usage.mapping = elapsed,
+            Activity::Querying => usage.querying = elapsed,
+            Activity::Compacting => usage.compacting = elapsed,
+        }
+        self.tracker.record(self.database, self.user, usage);
+    }
+}
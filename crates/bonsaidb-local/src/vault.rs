@@ -157,6 +157,9 @@ pub enum Error {
     /// doesn't contain the key.
     #[error("vault key not found")]
     VaultKeyNotFound,
+    /// The requested encryption key is not loaded in the vault.
+    #[error("encryption key not loaded in vault: {0}")]
+    KeyNotLoaded(String),
 }
 
 impl From<chacha20poly1305::aead::Error> for Error {
@@ -334,8 +337,8 @@ impl Vault {
 
         let (key, version) = match key_id {
             KeyId::Master => (self.current_master_key(), self.current_master_key_id),
-            KeyId::Id(_) => todo!(),
-            KeyId::None => unreachable!(),
+            KeyId::Id(name) => return Err(Error::KeyNotLoaded(name.to_string()).into()),
+            KeyId::None => return Err(no_key_specified()),
         };
         let payload = key.encrypt_payload(key_id.clone(), version, payload);
         Ok(payload.to_vec())
@@ -357,6 +360,19 @@ impl Vault {
         }
     }
 
+    /// Decrypts a payload that was produced by
+    /// [`encrypt_payload()`](Self::encrypt_payload). Unlike
+    /// [`decrypt_payload()`](Self::decrypt_payload), payloads that were not
+    /// encrypted by the vault are rejected.
+    pub fn decrypt_encrypted_payload(
+        &self,
+        payload: &[u8],
+        permissions: Option<&Permissions>,
+    ) -> Result<Vec<u8>, crate::Error> {
+        let payload = VaultPayload::from_slice(payload)?;
+        self.decrypt(&payload, permissions)
+    }
+
     fn decrypt(
         &self,
         payload: &VaultPayload<'_>,
@@ -372,13 +388,20 @@ impl Vault {
         // TODO handle key version
         let key = match &payload.key_id {
             KeyId::Master => self.current_master_key(),
-            KeyId::Id(_) => todo!(),
-            KeyId::None => unreachable!(),
+            KeyId::Id(name) => return Err(Error::KeyNotLoaded(name.to_string()).into()),
+            KeyId::None => return Err(no_key_specified()),
         };
         Ok(key.decrypt_payload(payload)?)
     }
 }
 
+/// Returns the error for an attempt to use [`KeyId::None`] with the vault.
+/// Payloads can be provided by clients through
+/// [`Encrypted`](bonsaidb_core::document::Encrypted), so this must not panic.
+fn no_key_specified() -> crate::Error {
+    Error::Encryption(String::from("an encryption key must be specified")).into()
+}
+
 /// Stores encrypted keys for a vault.
 pub trait VaultKeyStorage: Send + Sync + Debug + 'static {
     /// The error type that the functions return.
@@ -671,6 +694,37 @@ mod tests {
         assert_eq!(decrypted, b"hello");
     }
 
+    #[test]
+    fn vault_strict_decryption_test() {
+        let vault = random_null_vault();
+        let encrypted = vault
+            .encrypt_payload(&KeyId::Master, b"hello", None)
+            .unwrap();
+        let decrypted = vault.decrypt_encrypted_payload(&encrypted, None).unwrap();
+        assert_eq!(decrypted, b"hello");
+
+        assert!(vault.decrypt_encrypted_payload(b"hello", None).is_err());
+        assert!(matches!(
+            vault.encrypt_payload(&KeyId::Id(Cow::Borrowed("missing")), b"hello", None),
+            Err(crate::Error::Vault(Error::KeyNotLoaded(_)))
+        ));
+
+        // KeyId::None is rejected rather than panicking, whether it is
+        // requested directly or found in a payload.
+        assert!(matches!(
+            vault.encrypt_payload(&KeyId::None, b"hello", None),
+            Err(crate::Error::Vault(Error::Encryption(_)))
+        ));
+        let unkeyed = vault
+            .current_master_key()
+            .encrypt_payload(KeyId::None, vault.current_master_key_id, b"hello")
+            .to_vec();
+        assert!(matches!(
+            vault.decrypt_encrypted_payload(&unkeyed, None),
+            Err(crate::Error::Vault(Error::Encryption(_)))
+        ));
+    }
+
     #[test]
     fn vault_permissions_test() {
         let vault = random_null_vault();
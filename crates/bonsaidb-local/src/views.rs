@@ -20,8 +20,12 @@ pub struct EntryMapping {
     pub value: Bytes,
 }
 
+pub mod cache;
 pub mod integrity_scanner;
+pub mod join;
 pub mod mapper;
+pub mod materialize;
+pub mod warming;
 
 pub fn view_entries_tree_name(view_name: &impl Display) -> String {
     format!("view.{view_name:#}")
@@ -36,6 +40,12 @@ pub fn view_invalidated_docs_tree_name(view_name: &impl Display) -> String {
     format!("view.{view_name:#}.invalidated")
 }
 
+/// Used to build the replacement of the tree named `tree_name` while a view is
+/// rebuilt side-by-side with its existing entries.
+pub fn view_rebuild_tree_name(tree_name: &str) -> String {
+    format!("{tree_name}.rebuild")
+}
+
 pub fn view_versions_tree_name(collection: &CollectionName) -> String {
     format!("view-versions.{collection:#}")
 }
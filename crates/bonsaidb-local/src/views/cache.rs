@@ -0,0 +1,199 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::ops::Bound;
+use std::sync::Arc;
+
+use bonsaidb_core::arc_bytes::serde::Bytes;
+use bonsaidb_core::arc_bytes::ArcBytes;
+use bonsaidb_core::connection::{self, SerializedQueryKey, Sort};
+use bonsaidb_core::schema::view::map;
+use bonsaidb_core::schema::ViewName;
+use parking_lot::Mutex;
+
+use crate::Error;
+
+/// Cached query results for views that have opted into query caching through
+/// [`Views::cached_queries`](crate::config::Views::cached_queries).
+///
+/// Each view has a generation that is incremented every time a transaction
+/// changes a document in the view's collection. Results are only stored if the
+/// generation hasn't changed since the query began, which prevents a query
+/// that raced with a transaction from caching outdated results.
+#[derive(Debug, Default)]
+pub struct QueryCache {
+    views: Mutex<HashMap<ViewName, CachedView>>,
+}
+
+#[derive(Debug, Default)]
+struct CachedView {
+    generation: u64,
+    results: HashMap<Vec<u8>, Vec<map::Serialized>>,
+}
+
+impl QueryCache {
+    /// Returns the current generation of `view`.
+    pub fn generation(&self, view: &ViewName) -> u64 {
+        self.views
+            .lock()
+            .get(view)
+            .map_or(0, |cached| cached.generation)
+    }
+
+    /// Returns the cached results for `query`, if present.
+    pub fn get(&self, view: &ViewName, query: &[u8]) -> Option<Vec<map::Serialized>> {
+        self.views
+            .lock()
+            .get(view)
+            .and_then(|cached| cached.results.get(query).cloned())
+    }
+
+    /// Stores `results` for `query`, unless `view` has been invalidated since
+    /// `generation` was retrieved.
+    pub fn insert(
+        &self,
+        view: &ViewName,
+        generation: u64,
+        query: Vec<u8>,
+        results: Vec<map::Serialized>,
+    ) {
+        let mut views = self.views.lock();
+        let cached = views.entry(view.clone()).or_default();
+        if cached.generation == generation {
+            cached.results.insert(query, results);
+        }
+    }
+
+    /// Removes all cached results for `view`.
+    pub fn invalidate(&self, view: &ViewName) {
+        let mut views = self.views.lock();
+        let cached = views.entry(view.clone()).or_default();
+        cached.generation += 1;
+        cached.results.clear();
+    }
+}
+
+/// Returns the key used to cache the results of a query with these
+/// parameters.
+pub fn query_cache_key(
+    key: Option<&SerializedQueryKey>,
+    order: Sort,
+    limit: Option<u32>,
+) -> Result<Vec<u8>, Error> {
+    Ok(bincode::serialize(&(key, order, limit))?)
+}
+
+/// The number of consecutive view entries that are reduced into each cached
+/// [`ReducedSegment`].
+pub const REDUCE_SEGMENT_LENGTH: usize = 64;
+
+/// Reduced values of runs of consecutive view entries, which allow reducing
+/// large key ranges without reading and rereducing every entry each time.
+///
+/// Each segment covers every key from its first key through its last key. When
+/// any key within a segment is modified, only that segment is removed. Like
+/// [`QueryCache`], a generation is used to prevent a reduce operation that
+/// raced with a modification from caching outdated segments.
+#[derive(Debug, Default)]
+pub struct ReduceCache {
+    views: Mutex<HashMap<ViewName, CachedSegments>>,
+}
+
+#[derive(Debug, Default)]
+struct CachedSegments {
+    generation: u64,
+    segments: Arc<ReducedSegments>,
+}
+
+/// Cached segments, keyed by the first key of each segment.
+pub type ReducedSegments = BTreeMap<Vec<u8>, ReducedSegment>;
+
+/// The reduced value of a run of consecutive view entries.
+#[derive(Debug, Clone)]
+pub struct ReducedSegment {
+    /// The last key contained in this segment.
+    pub last_key: Vec<u8>,
+    /// The result of rereducing every entry in this segment.
+    pub value: Vec<u8>,
+}
+
+impl ReduceCache {
+    /// Returns the current generation and a snapshot of the segments of
+    /// `view`.
+    pub fn segments(&self, view: &ViewName) -> (u64, Arc<ReducedSegments>) {
+        self.views.lock().get(view).map_or_else(
+            || (0, Arc::default()),
+            |cached| (cached.generation, cached.segments.clone()),
+        )
+    }
+
+    /// Stores `segments`, unless `view` has been modified since `generation`
+    /// was retrieved.
+    pub fn insert(
+        &self,
+        view: &ViewName,
+        generation: u64,
+        segments: Vec<(Vec<u8>, ReducedSegment)>,
+    ) {
+        if segments.is_empty() {
+            return;
+        }
+
+        let mut views = self.views.lock();
+        let cached = views.entry(view.clone()).or_default();
+        if cached.generation == generation {
+            Arc::make_mut(&mut cached.segments).extend(segments);
+        }
+    }
+
+    /// Removes all segments of `view`.
+    pub fn clear(&self, view: &ViewName) {
+        let mut views = self.views.lock();
+        let cached = views.entry(view.clone()).or_default();
+        cached.generation += 1;
+        cached.segments = Arc::default();
+    }
+
+    /// Removes the segments of `view` that contain any of `keys`.
+    pub fn invalidate(&self, view: &ViewName, keys: &BTreeSet<ArcBytes<'static>>) {
+        let mut views = self.views.lock();
+        let cached = views.entry(view.clone()).or_default();
+        cached.generation += 1;
+        if cached.segments.is_empty() {
+            return;
+        }
+
+        let segments = Arc::make_mut(&mut cached.segments);
+        for key in keys {
+            if let Some(first_key) =
+                segment_containing(segments, key.as_slice()).map(|(first_key, _)| first_key.clone())
+            {
+                segments.remove(&first_key);
+            }
+        }
+    }
+}
+
+/// Returns the first key and segment that contains `key`, if any.
+pub fn segment_containing<'a>(
+    segments: &'a ReducedSegments,
+    key: &[u8],
+) -> Option<(&'a Vec<u8>, &'a ReducedSegment)> {
+    segments
+        .range::<[u8], _>((Bound::Unbounded, Bound::Included(key)))
+        .next_back()
+        .filter(|(_, segment)| segment.last_key.as_slice() >= key)
+}
+
+/// Returns true if `key` is contained within `range`.
+pub fn range_contains(range: &connection::Range<Bytes>, key: &[u8]) -> bool {
+    let after_start = match &range.start {
+        connection::Bound::Unbounded => true,
+        connection::Bound::Included(start) => key >= start.as_slice(),
+        connection::Bound::Excluded(start) => key > start.as_slice(),
+    };
+    let before_end = match &range.end {
+        connection::Bound::Unbounded => true,
+        connection::Bound::Included(end) => key <= end.as_slice(),
+        connection::Bound::Excluded(end) => key < end.as_slice(),
+    };
+    after_start && before_end
+}
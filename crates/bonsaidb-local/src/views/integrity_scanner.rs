@@ -5,18 +5,21 @@ use std::hash::Hash;
 use std::sync::Arc;
 
 use bonsaidb_core::document::DocumentId;
+use bonsaidb_core::schema::view::Serialized;
 use bonsaidb_core::schema::{CollectionName, ViewName};
 use nebari::io::any::AnyFile;
-use nebari::tree::{Operation, ScanEvaluation, Unversioned, Versioned};
+use nebari::tree::{Operation, ScanEvaluation, Unversioned};
 use nebari::{ArcBytes, Roots, Tree};
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 
 use super::mapper::{Map, Mapper};
 use super::{view_invalidated_docs_tree_name, view_versions_tree_name};
-use crate::database::{document_tree_name, Database};
+use crate::database::{document_tree_name, with_document_root, Database};
 use crate::tasks::handle::Handle;
 use crate::tasks::{Job, Keyed, Task};
+use crate::views::join::reset_joins;
+use crate::views::materialize::clear_materialized_values;
 use crate::views::{view_document_map_tree_name, view_entries_tree_name};
 use crate::Error;
 
@@ -29,6 +32,7 @@ pub struct IntegrityScanner {
 #[derive(Debug, Hash, Eq, PartialEq, Clone)]
 pub struct IntegrityScan {
     pub view_version: u64,
+    pub view_checksum: u64,
     pub database: Arc<Cow<'static, str>>,
     pub collection: CollectionName,
     pub view_name: ViewName,
@@ -43,22 +47,19 @@ impl Job for IntegrityScanner {
     #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
     #[allow(clippy::too_many_lines)]
     fn execute(&mut self) -> Result<Self::Output, Self::Error> {
-        let documents =
+        let view_versions =
             self.database
                 .roots()
-                .tree(self.database.collection_tree::<Versioned, _>(
+                .tree(self.database.collection_tree::<Unversioned, _>(
                     &self.scan.collection,
-                    document_tree_name(&self.scan.collection),
+                    view_versions_tree_name(&self.scan.collection),
                 )?)?;
 
-        let view_versions_tree = self.database.collection_tree::<Unversioned, _>(
-            &self.scan.collection,
-            view_versions_tree_name(&self.scan.collection),
-        )?;
-        let view_versions = self.database.roots().tree(view_versions_tree.clone())?;
-
         let view_name = self.scan.view_name.clone();
         let view_version = self.scan.view_version;
+        let view_checksum = self.scan.view_checksum;
+        let view = self.database.data.schema.view_by_name(&view_name)?;
+        let side_by_side = self.rebuilds_side_by_side(view);
         let roots = self.database.roots().clone();
         let version = view_versions
             .get(view_name.to_string().as_bytes())?
@@ -68,56 +69,53 @@ impl Job for IntegrityScanner {
         // Remove any old files that are no longer used.
         version.cleanup(&roots, &view_name)?;
 
-        let task = if version.is_current(view_version) {
-            None
+        let task = if version.is_current(view_version, view_checksum) {
+            if version.definition_checksum.is_none() {
+                // Views created before checksums were recorded are assumed to
+                // be up-to-date.
+                view_versions.set(
+                    view_name.to_string().as_bytes().to_vec(),
+                    ViewVersion::current_for(view_version, view_checksum).to_vec()?,
+                )?;
+            }
+            if version.rebuilding {
+                // The view's rebuild was interrupted before it completed.
+                Some(self.rebuild(side_by_side))
+            } else {
+                None
+            }
         } else {
+            if version.is_current_version(view_version) {
+                log::warn!(
+                    "The definition of view {view_name} changed without its version being \
+                     updated. Rebuilding the view."
+                );
+            }
             // The view isn't the current version, queue up all documents.
-            let missing_entries = tree_keys::<Versioned>(&documents)?;
-            // When a version is updated, we can make no guarantees about
-            // existing keys. The best we can do is delete the existing files so
-            // that the view starts fresh.
-            roots.delete_tree(view_invalidated_docs_tree_name(&self.scan.view_name))?;
-            roots.delete_tree(view_entries_tree_name(&self.scan.view_name))?;
-            roots.delete_tree(view_document_map_tree_name(&self.scan.view_name))?;
-            // Add all missing entries to the invalidated list. The view
-            // mapping job will update them on the next pass.
-            let invalidated_entries_tree = self.database.collection_tree::<Unversioned, _>(
-                &self.scan.collection,
-                view_invalidated_docs_tree_name(&self.scan.view_name),
-            )?;
-
-            let transaction = roots.transaction(&[invalidated_entries_tree, view_versions_tree])?;
-            {
-                let mut view_versions = transaction.tree::<Unversioned>(1).unwrap();
+            if side_by_side {
+                // The existing entries are kept as-is and served until their
+                // replacement has been built.
                 view_versions.set(
                     view_name.to_string().as_bytes().to_vec(),
-                    ViewVersion::current_for(view_version).to_vec()?,
+                    ViewVersion::rebuilding_for(view_version, view_checksum).to_vec()?,
+                )?;
+            } else if self
+                .database
+                .storage
+                .instance
+                .serves_stale_views_while_rebuilding(&self.database.data.name)
+            {
+                invalidate_view(&self.database, &self.scan.collection, view, view_version)?;
+            } else {
+                reset_view(
+                    &self.database,
+                    &self.scan.collection,
+                    &view_name,
+                    view_version,
                 )?;
-                let mut invalidated_entries = transaction.tree::<Unversioned>(0).unwrap();
-                let mut missing_entries = missing_entries
-                    .into_iter()
-                    .map(|id| ArcBytes::from(id.to_vec()))
-                    .collect::<Vec<_>>();
-                missing_entries.sort();
-                invalidated_entries.modify(missing_entries, Operation::Set(ArcBytes::default()))?;
             }
-            transaction.commit()?;
-
-            Some(Arc::new(Mutex::new(Some(
-                self.database
-                    .storage
-                    .instance
-                    .tasks()
-                    .jobs
-                    .lookup_or_enqueue(Mapper {
-                        database: self.database.clone(),
-                        map: Map {
-                            database: self.database.data.name.clone(),
-                            collection: self.scan.collection.clone(),
-                            view_name: self.scan.view_name.clone(),
-                        },
-                    }),
-            ))))
+
+            Some(self.rebuild(side_by_side))
         };
 
         self.database
@@ -134,10 +132,162 @@ impl Job for IntegrityScanner {
     }
 }
 
+impl IntegrityScanner {
+    /// Returns true if `view` is rebuilt alongside its existing entries, which
+    /// are replaced once every document has been mapped. Eager views are
+    /// updated by each transaction, so they are always rebuilt in place.
+    fn rebuilds_side_by_side(&self, view: &dyn Serialized) -> bool {
+        view.lazy()
+            && self
+                .database
+                .storage
+                .instance
+                .serves_stale_views_while_rebuilding(&self.database.data.name)
+    }
+
+    /// Marks the view as being rebuilt, and queues the job that maps its
+    /// invalidated documents.
+    fn rebuild(&self, side_by_side: bool) -> Arc<Mutex<Option<Handle<u64, Error>>>> {
+        let tasks = self.database.storage.instance.tasks();
+        tasks.mark_view_rebuilding(
+            self.database.data.name.clone(),
+            self.scan.collection.clone(),
+            self.scan.view_name.clone(),
+        );
+        if side_by_side {
+            tasks.mark_view_rebuilding_side_by_side(
+                self.database.data.name.clone(),
+                self.scan.collection.clone(),
+                self.scan.view_name.clone(),
+            );
+        }
+        Arc::new(Mutex::new(Some(tasks.jobs.lookup_or_enqueue(Mapper {
+            database: self.database.clone(),
+            map: Map {
+                database: self.database.data.name.clone(),
+                collection: self.scan.collection.clone(),
+                view_name: self.scan.view_name.clone(),
+            },
+        }))))
+    }
+}
+
+/// Removes all of the data stored for `view_name` and invalidates every
+/// document in `collection`, causing the view to be rebuilt from scratch the
+/// next time it is mapped.
+pub fn reset_view(
+    database: &Database,
+    collection: &CollectionName,
+    view_name: &ViewName,
+    view_version: u64,
+) -> Result<(), Error> {
+    let roots = database.roots();
+    // When a version is updated, we can make no guarantees about existing
+    // keys. The best we can do is delete the existing files so that the view
+    // starts fresh.
+    let view = database.data.schema.view_by_name(view_name)?;
+    let view_entries = roots.tree(
+        database
+            .collection_tree::<Unversioned, _>(collection, view_entries_tree_name(view_name))?,
+    )?;
+    clear_materialized_values(database, view, &view_entries)?;
+    drop(view_entries);
+    reset_joins(database, view_name)?;
+    roots.delete_tree(view_invalidated_docs_tree_name(view_name))?;
+    roots.delete_tree(view_entries_tree_name(view_name))?;
+    roots.delete_tree(view_document_map_tree_name(view_name))?;
+    invalidate_view(database, collection, view, view_version)
+}
+
+/// Invalidates every document in `collection` and records that `view` is
+/// being rebuilt. Unlike [`reset_view()`], the view's existing entries are
+/// kept, and are replaced as each document is mapped again.
+pub fn invalidate_view(
+    database: &Database,
+    collection: &CollectionName,
+    view: &dyn Serialized,
+    view_version: u64,
+) -> Result<(), Error> {
+    let roots = database.roots();
+    let view_name = view.view_name();
+    let missing_entries =
+        with_document_root!(database.data.schema, collection, |R| {
+            tree_keys::<R>(&roots.tree(
+                database.collection_tree::<R, _>(collection, document_tree_name(collection))?,
+            )?)?
+        });
+    let view_checksum = view.definition_checksum();
+    // Add all missing entries to the invalidated list. The view mapping job
+    // will update them on the next pass.
+    let invalidated_entries_tree = database.collection_tree::<Unversioned, _>(
+        collection,
+        view_invalidated_docs_tree_name(&view_name),
+    )?;
+    let view_versions_tree = database
+        .collection_tree::<Unversioned, _>(collection, view_versions_tree_name(collection))?;
+
+    let transaction = roots.transaction(&[invalidated_entries_tree, view_versions_tree])?;
+    {
+        let mut view_versions = transaction.tree::<Unversioned>(1).unwrap();
+        view_versions.set(
+            view_name.to_string().as_bytes().to_vec(),
+            ViewVersion::rebuilding_for(view_version, view_checksum).to_vec()?,
+        )?;
+        let mut invalidated_entries = transaction.tree::<Unversioned>(0).unwrap();
+        let mut missing_entries = missing_entries
+            .into_iter()
+            .map(|id| ArcBytes::from(id.to_vec()))
+            .collect::<Vec<_>>();
+        missing_entries.sort();
+        invalidated_entries.modify(missing_entries, Operation::Set(ArcBytes::default()))?;
+    }
+    transaction.commit()?;
+    database.query_cache().invalidate(&view_name);
+    database.reduce_cache().clear(&view_name);
+    Ok(())
+}
+
+/// Records that every document has been mapped into `view` using its current
+/// definition, if it was being rebuilt.
+pub fn complete_rebuild(
+    database: &Database,
+    collection: &CollectionName,
+    view: &dyn Serialized,
+) -> Result<(), Error> {
+    let view_name = view.view_name();
+    if database.storage.instance.tasks().mark_view_rebuilt(
+        database.data.name.clone(),
+        collection.clone(),
+        view_name.clone(),
+    ) {
+        database
+            .roots()
+            .tree(database.collection_tree::<Unversioned, _>(
+                collection,
+                view_versions_tree_name(collection),
+            )?)?
+            .set(
+                view_name.to_string().as_bytes().to_vec(),
+                ViewVersion::current_for(view.version(), view.definition_checksum()).to_vec()?,
+            )?;
+    }
+    Ok(())
+}
+
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub struct ViewVersion {
     internal_version: u8,
     schema_version: u64,
+    /// The [`Serialized::definition_checksum()`] of the view, which is `None`
+    /// for views created before checksums were recorded.
+    ///
+    /// [`Serialized::definition_checksum()`]: bonsaidb_core::schema::view::Serialized::definition_checksum
+    #[serde(default)]
+    pub definition_checksum: Option<u64>,
+    /// True if the view's definition changed and not every document has been
+    /// mapped using the new definition yet.
+    #[serde(default)]
+    pub rebuilding: bool,
 }
 
 impl ViewVersion {
@@ -153,6 +303,8 @@ impl ViewVersion {
                 Ok(Self {
                     internal_version: 0,
                     schema_version,
+                    definition_checksum: None,
+                    rebuilding: false,
                 })
             }
             Err(err) => Err(crate::Error::from(err)),
@@ -163,17 +315,33 @@ impl ViewVersion {
         pot::to_vec(self).map_err(crate::Error::from)
     }
 
-    pub fn current_for(schema_version: u64) -> Self {
+    pub fn current_for(schema_version: u64, definition_checksum: u64) -> Self {
         Self {
             internal_version: Self::CURRENT_VERSION,
             schema_version,
+            definition_checksum: Some(definition_checksum),
+            rebuilding: false,
         }
     }
 
-    pub fn is_current(&self, schema_version: u64) -> bool {
+    pub fn rebuilding_for(schema_version: u64, definition_checksum: u64) -> Self {
+        Self {
+            rebuilding: true,
+            ..Self::current_for(schema_version, definition_checksum)
+        }
+    }
+
+    pub fn is_current_version(&self, schema_version: u64) -> bool {
         self.internal_version == Self::CURRENT_VERSION && self.schema_version == schema_version
     }
 
+    pub fn is_current(&self, schema_version: u64, definition_checksum: u64) -> bool {
+        self.is_current_version(schema_version)
+            && self
+                .definition_checksum
+                .map_or(true, |checksum| checksum == definition_checksum)
+    }
+
     pub fn cleanup(&self, roots: &Roots<AnyFile>, view: &ViewName) -> Result<(), crate::Error> {
         if self.internal_version < 2 {
             // omitted entries was removed
@@ -183,7 +351,7 @@ impl ViewVersion {
     }
 }
 
-fn tree_keys<R: nebari::tree::Root>(
+pub fn tree_keys<R: nebari::tree::Root>(
     tree: &Tree<R, AnyFile>,
 ) -> Result<HashSet<DocumentId>, crate::Error> {
     let mut ids = Vec::new();
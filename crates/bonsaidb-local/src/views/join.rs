@@ -0,0 +1,187 @@
+use std::collections::{BTreeSet, HashMap};
+use std::convert::Infallible;
+use std::fmt::Display;
+
+use bonsaidb_core::arc_bytes::serde::Bytes;
+use bonsaidb_core::arc_bytes::ArcBytes;
+use bonsaidb_core::schema::view::join::{JoinDefinition, JoinedMapping, SerializedJoinedEntry};
+use bonsaidb_core::schema::ViewName;
+use nebari::io::any::AnyFile;
+use nebari::tree::{CompareSwap, KeyOperation, Operation, ScanEvaluation, Unversioned};
+use nebari::Tree;
+
+use crate::database::Database;
+use crate::views::{view_entries_tree_name, EntryMapping, ViewEntry};
+use crate::Error;
+
+/// The tree that records which joins have been built.
+const BUILT_JOINS_TREE: &str = "joins-built";
+
+/// Used to store the joined entries of a joined view.
+pub fn view_join_tree_name(join_name: &impl Display) -> String {
+    format!("join.{join_name:#}")
+}
+
+/// Opens the tree storing the joined entries of `join`.
+pub fn join_tree(
+    database: &Database,
+    join: &JoinDefinition,
+) -> Result<Tree<Unversioned, AnyFile>, Error> {
+    Ok(database
+        .roots()
+        .tree(database.collection_tree::<Unversioned, _>(
+            &join.name.collection,
+            view_join_tree_name(&join.name),
+        )?)?)
+}
+
+/// Updates the joined entries for `keys` of every built join that `view` is
+/// part of. Joins that haven't been built are built when they are queried.
+pub fn update_joins(
+    database: &Database,
+    view: &ViewName,
+    keys: &BTreeSet<ArcBytes<'static>>,
+) -> Result<(), Error> {
+    let joins = database.data.schema.joins_of_view(view)?;
+    if joins.is_empty() {
+        return Ok(());
+    }
+
+    // Each key's joined entry is read from both views while the lock is held,
+    // which ensures an update using older entries can't overwrite an update
+    // using newer entries.
+    let _guard = database.data.context.join_lock.lock();
+    for join in joins {
+        if join_is_built(database, &join)? {
+            write_joined_entries(database, &join, keys.iter().cloned().collect())?;
+        }
+    }
+    Ok(())
+}
+
+/// Builds `join` from the current entries of its views, unless it has already
+/// been built.
+pub fn ensure_join_built(database: &Database, join: &JoinDefinition) -> Result<(), Error> {
+    let _guard = database.data.context.join_lock.lock();
+    if join_is_built(database, join)? {
+        return Ok(());
+    }
+
+    database
+        .roots()
+        .delete_tree(view_join_tree_name(&join.name))?;
+    let left_entries = view_entries(database, &join.left)?;
+    let mut keys = Vec::new();
+    left_entries.scan::<Infallible, _, _, _, _>(
+        &(..),
+        true,
+        |_, _, _| ScanEvaluation::ReadData,
+        |key, _| {
+            keys.push(key.clone());
+            ScanEvaluation::Skip
+        },
+        |_, _, _| unreachable!("entries are never read"),
+    )?;
+    write_joined_entries(database, join, keys)?;
+
+    database
+        .roots()
+        .tree(Unversioned::tree(BUILT_JOINS_TREE))?
+        .set(join_key(join).into_bytes(), Vec::new())?;
+    Ok(())
+}
+
+/// Removes the joined entries of every join that `view` is part of. The joins
+/// are rebuilt the next time they are queried.
+pub fn reset_joins(database: &Database, view: &ViewName) -> Result<(), Error> {
+    let joins = database.data.schema.joins_of_view(view)?;
+    if joins.is_empty() {
+        return Ok(());
+    }
+
+    let _guard = database.data.context.join_lock.lock();
+    let built_joins = database.roots().tree(Unversioned::tree(BUILT_JOINS_TREE))?;
+    for join in joins {
+        built_joins.remove(join_key(&join).as_bytes())?;
+        database
+            .roots()
+            .delete_tree(view_join_tree_name(&join.name))?;
+    }
+    Ok(())
+}
+
+fn join_is_built(database: &Database, join: &JoinDefinition) -> Result<bool, Error> {
+    Ok(database
+        .roots()
+        .tree(Unversioned::tree(BUILT_JOINS_TREE))?
+        .get(join_key(join).as_bytes())?
+        .is_some())
+}
+
+fn join_key(join: &JoinDefinition) -> String {
+    format!("{:#}", join.name)
+}
+
+fn view_entries(database: &Database, view: &ViewName) -> Result<Tree<Unversioned, AnyFile>, Error> {
+    Ok(database.roots().tree(
+        database
+            .collection_tree::<Unversioned, _>(&view.collection, view_entries_tree_name(view))?,
+    )?)
+}
+
+/// Replaces the joined entries of `keys` with the current entries of both of
+/// `join`'s views. Keys missing from either view are removed from the join.
+fn write_joined_entries(
+    database: &Database,
+    join: &JoinDefinition,
+    mut keys: Vec<ArcBytes<'static>>,
+) -> Result<(), Error> {
+    if keys.is_empty() {
+        return Ok(());
+    }
+    keys.sort();
+    keys.dedup();
+
+    let read_entries = |view: &ViewName| -> Result<HashMap<ArcBytes<'static>, ViewEntry>, Error> {
+        view_entries(database, view)?
+            .get_multiple(keys.iter().map(ArcBytes::as_slice))?
+            .into_iter()
+            .map(|(key, entry)| Ok((key, bincode::deserialize::<ViewEntry>(&entry)?)))
+            .collect()
+    };
+    let mut left = read_entries(&join.left)?;
+    let mut right = read_entries(&join.right)?;
+
+    let mut joined = HashMap::new();
+    for key in &keys {
+        if let (Some(left), Some(right)) = (left.remove(key), right.remove(key)) {
+            let entry = SerializedJoinedEntry {
+                key: left.key,
+                left: joined_mappings(left.mappings),
+                right: joined_mappings(right.mappings),
+            };
+            joined.insert(key.to_vec(), ArcBytes::from(bincode::serialize(&entry)?));
+        }
+    }
+
+    join_tree(database, join)?.modify(
+        keys,
+        Operation::CompareSwap(CompareSwap::new(
+            &mut |key, _| match joined.remove(&key[..]) {
+                Some(entry) => KeyOperation::Set(entry),
+                None => KeyOperation::Remove,
+            },
+        )),
+    )?;
+    Ok(())
+}
+
+fn joined_mappings(mappings: Vec<EntryMapping>) -> Vec<JoinedMapping<Bytes>> {
+    mappings
+        .into_iter()
+        .map(|mapping| JoinedMapping {
+            source: mapping.source,
+            value: mapping.value,
+        })
+        .collect()
+}
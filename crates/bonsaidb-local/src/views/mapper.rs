@@ -2,22 +2,28 @@ use std::borrow::Cow;
 use std::collections::hash_map::RandomState;
 use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::sync::Arc;
+use std::time::Instant;
 
 use bonsaidb_core::arc_bytes::serde::Bytes;
 use bonsaidb_core::arc_bytes::{ArcBytes, OwnedBytes};
 use bonsaidb_core::connection::Connection;
-use bonsaidb_core::schema::view::{self, map, Serialized};
+use bonsaidb_core::schema::view::{self, map, Serialized, ValueStorage};
 use bonsaidb_core::schema::{CollectionName, ViewName};
 use easy_parallel::Parallel;
 use nebari::io::any::AnyFile;
-use nebari::tree::{AnyTreeRoot, CompareSwap, KeyOperation, Operation, Unversioned, Versioned};
+use nebari::tree::{AnyTreeRoot, CompareSwap, KeyOperation, Operation, Root, Unversioned};
 use nebari::{LockedTransactionTree, Tree, UnlockedTransactionTree};
 
-use crate::database::{deserialize_document, document_tree_name, Database};
+use crate::database::deduplication::{document_contents_tree_name, resolve_serialized_contents};
+use crate::database::{deserialize_document, document_tree_name, with_document_root, Database};
 use crate::tasks::{Job, Keyed, Task};
+use crate::usage::Activity;
+use crate::views::integrity_scanner::{complete_rebuild, tree_keys};
+use crate::views::join::reset_joins;
+use crate::views::materialize::{clear_materialized_values, PendingMaterializations};
 use crate::views::{
     view_document_map_tree_name, view_entries_tree_name, view_invalidated_docs_tree_name,
-    EntryMapping, ViewEntry,
+    view_rebuild_tree_name, EntryMapping, ViewEntry,
 };
 use crate::Error;
 
@@ -41,13 +47,21 @@ impl Job for Mapper {
     #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
     #[allow(clippy::too_many_lines)]
     fn execute(&mut self) -> Result<Self::Output, Error> {
-        let documents =
-            self.database
-                .roots()
-                .tree(self.database.collection_tree::<Versioned, _>(
-                    &self.map.collection,
-                    document_tree_name(&self.map.collection),
-                )?)?;
+        let database = self.database.clone();
+        let _usage = database.track_usage(Activity::Mapping);
+        let tasks = self.database.storage.instance.tasks();
+        if tasks.view_rebuilding_side_by_side(
+            self.map.database.clone(),
+            self.map.collection.clone(),
+            self.map.view_name.clone(),
+        ) {
+            rebuild_side_by_side(&self.database, &self.map)?;
+            tasks.mark_side_by_side_rebuild_swapped(
+                self.map.database.clone(),
+                self.map.collection.clone(),
+                self.map.view_name.clone(),
+            );
+        }
 
         let view_entries =
             self.database
@@ -73,6 +87,7 @@ impl Job for Mapper {
                     view_invalidated_docs_tree_name(&self.map.view_name),
                 )?)?;
 
+        let updated_as_of = Instant::now();
         let transaction_id = self
             .database
             .last_transaction_id()?
@@ -81,13 +96,33 @@ impl Job for Mapper {
         let storage = self.database.clone();
         let map_request = self.map.clone();
 
-        map_view(
-            &invalidated_entries,
-            &document_map,
-            &documents,
-            &view_entries,
-            &storage,
-            &map_request,
+        with_document_root!(self.database.data.schema, &self.map.collection, |R| {
+            let documents = self
+                .database
+                .roots()
+                .tree(self.database.collection_tree::<R, _>(
+                    &self.map.collection,
+                    document_tree_name(&self.map.collection),
+                )?)?;
+            map_view(
+                &invalidated_entries,
+                &document_map,
+                &documents,
+                document_contents_tree(&self.database, &self.map.collection)?.as_ref(),
+                &view_entries,
+                &storage,
+                &map_request,
+                true,
+            )?;
+        });
+
+        complete_rebuild(
+            &self.database,
+            &self.map.collection,
+            self.database
+                .data
+                .schema
+                .view_by_name(&self.map.view_name)?,
         )?;
 
         self.database.storage.instance.tasks().mark_view_updated(
@@ -95,19 +130,23 @@ impl Job for Mapper {
             self.map.collection.clone(),
             self.map.view_name.clone(),
             transaction_id,
+            updated_as_of,
         );
 
         Ok(transaction_id)
     }
 }
 
-fn map_view(
+#[allow(clippy::too_many_arguments)]
+fn map_view<R: Root>(
     invalidated_entries: &Tree<Unversioned, AnyFile>,
     document_map: &Tree<Unversioned, AnyFile>,
-    documents: &Tree<Versioned, AnyFile>,
+    documents: &Tree<R, AnyFile>,
+    document_contents: Option<&Tree<Unversioned, AnyFile>>,
     view_entries: &Tree<Unversioned, AnyFile>,
     database: &Database,
     map_request: &Map,
+    materialize: bool,
 ) -> Result<(), Error> {
     const CHUNK_SIZE: usize = 100_000;
     // Only do any work if there are invalidated documents to process
@@ -117,14 +156,24 @@ fn map_view(
         .map(|(key, _)| key)
         .collect::<Vec<_>>();
     while !invalidated_ids.is_empty() {
+        let started_at = Instant::now();
+        let document_ids = invalidated_ids
+            .drain(invalidated_ids.len().saturating_sub(CHUNK_SIZE)..)
+            .collect::<Vec<_>>();
+        let mapped = document_ids.len() as u64;
+        let mut materializations = PendingMaterializations::default();
+        let mut trees = vec![
+            Box::new(invalidated_entries.clone()) as Box<dyn AnyTreeRoot<AnyFile>>,
+            Box::new(document_map.clone()),
+            Box::new(documents.clone()),
+            Box::new(view_entries.clone()),
+        ];
+        if let Some(document_contents) = document_contents {
+            trees.push(Box::new(document_contents.clone()));
+        }
         let transaction = database
             .roots()
-            .transaction::<_, dyn AnyTreeRoot<AnyFile>>(&[
-                Box::new(invalidated_entries.clone()) as Box<dyn AnyTreeRoot<AnyFile>>,
-                Box::new(document_map.clone()),
-                Box::new(documents.clone()),
-                Box::new(view_entries.clone()),
-            ])?;
+            .transaction::<_, dyn AnyTreeRoot<AnyFile>>(&trees)?;
         {
             let view = database
                 .data
@@ -132,32 +181,182 @@ fn map_view(
                 .view_by_name(&map_request.view_name)
                 .unwrap();
 
-            let document_ids = invalidated_ids
-                .drain(invalidated_ids.len().saturating_sub(CHUNK_SIZE)..)
-                .collect::<Vec<_>>();
             let document_map = transaction.unlocked_tree(1).unwrap();
             let documents = transaction.unlocked_tree(2).unwrap();
             let view_entries = transaction.unlocked_tree(3).unwrap();
-            DocumentRequest {
+            let document_contents = transaction.unlocked_tree(4);
+            let modified_keys = DocumentRequest {
                 document_ids: document_ids.clone(),
                 map_request,
                 database,
                 document_map,
                 documents,
+                document_contents,
                 view_entries,
                 view,
             }
             .map()?;
+            if materialize {
+                materializations.record(&map_request.collection, view, modified_keys);
+            }
 
             let mut invalidated_entries = transaction.tree::<Unversioned>(0).unwrap();
             invalidated_entries.modify(document_ids, nebari::tree::Operation::Remove)?;
         }
         transaction.commit()?;
+        materializations.apply(database)?;
+        database
+            .mapping_rates()
+            .record(&map_request.view_name, mapped, started_at.elapsed());
     }
 
     Ok(())
 }
 
+/// Opens the tree storing the contents of `collection`'s documents, if the
+/// collection is deduplicated.
+fn document_contents_tree(
+    database: &Database,
+    collection: &CollectionName,
+) -> Result<Option<Tree<Unversioned, AnyFile>>, Error> {
+    if database.data.schema.collection_is_deduplicated(collection) {
+        Ok(Some(database.roots().tree(
+            database.collection_tree::<Unversioned, _>(
+                collection,
+                document_contents_tree_name(collection),
+            )?,
+        )?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Maps every document into trees separate from the view's existing entries,
+/// which continue to be queried while the view is rebuilt. Once every document
+/// has been mapped, the existing entries are replaced in a single transaction.
+///
+/// Documents changed while the view is rebuilt are invalidated in the view's
+/// existing invalidated documents tree, and are mapped again after the entries
+/// are replaced.
+fn rebuild_side_by_side(database: &Database, map_request: &Map) -> Result<(), Error> {
+    let roots = database.roots();
+    let collection = &map_request.collection;
+    let view_name = &map_request.view_name;
+    let view = database.data.schema.view_by_name(view_name)?;
+    let entries_tree_name = view_entries_tree_name(view_name);
+    let document_map_tree_name = view_document_map_tree_name(view_name);
+    let rebuilt_entries_tree_name = view_rebuild_tree_name(&entries_tree_name);
+    let rebuilt_document_map_tree_name = view_rebuild_tree_name(&document_map_tree_name);
+    let rebuild_invalidated_tree_name =
+        view_rebuild_tree_name(&view_invalidated_docs_tree_name(view_name));
+
+    // A previous rebuild may have been interrupted before it completed.
+    roots.delete_tree(rebuilt_entries_tree_name.clone())?;
+    roots.delete_tree(rebuilt_document_map_tree_name.clone())?;
+    roots.delete_tree(rebuild_invalidated_tree_name.clone())?;
+
+    let open_tree = |name: &str| {
+        database
+            .collection_tree::<Unversioned, _>(collection, name.to_string())
+            .and_then(|root| Ok(roots.tree(root)?))
+    };
+    let rebuilt_entries = open_tree(&rebuilt_entries_tree_name)?;
+    let rebuilt_document_map = open_tree(&rebuilt_document_map_tree_name)?;
+    let rebuild_invalidated = open_tree(&rebuild_invalidated_tree_name)?;
+
+    with_document_root!(database.data.schema, collection, |R| {
+        let documents = roots
+            .tree(database.collection_tree::<R, _>(collection, document_tree_name(collection))?)?;
+        let mut document_ids = tree_keys::<R>(&documents)?
+            .into_iter()
+            .map(|id| ArcBytes::from(id.to_vec()))
+            .collect::<Vec<_>>();
+        document_ids.sort();
+        rebuild_invalidated.modify(document_ids, Operation::Set(ArcBytes::default()))?;
+        map_view(
+            &rebuild_invalidated,
+            &rebuilt_document_map,
+            &documents,
+            document_contents_tree(database, collection)?.as_ref(),
+            &rebuilt_entries,
+            database,
+            map_request,
+            false,
+        )?;
+    });
+
+    // Only the view's mapper writes to its entries and document map, so their
+    // contents can't change while they are being replaced.
+    let view_entries = open_tree(&entries_tree_name)?;
+    clear_materialized_values(database, view, &view_entries)?;
+    let (entry_keys, mut entries) = replacement_of(&view_entries, &rebuilt_entries)?;
+    let (document_map_keys, mut document_map) =
+        replacement_of(&open_tree(&document_map_tree_name)?, &rebuilt_document_map)?;
+    let transaction = roots.transaction(&[
+        database.collection_tree::<Unversioned, _>(collection, entries_tree_name)?,
+        database.collection_tree::<Unversioned, _>(collection, document_map_tree_name)?,
+    ])?;
+    transaction.tree::<Unversioned>(0).unwrap().modify(
+        entry_keys,
+        Operation::CompareSwap(CompareSwap::new(&mut |key, _| replace(&mut entries, key))),
+    )?;
+    transaction.tree::<Unversioned>(1).unwrap().modify(
+        document_map_keys,
+        Operation::CompareSwap(CompareSwap::new(&mut |key, _| {
+            replace(&mut document_map, key)
+        })),
+    )?;
+    transaction.commit()?;
+    drop((rebuilt_entries, rebuilt_document_map, rebuild_invalidated));
+    roots.delete_tree(rebuilt_entries_tree_name)?;
+    roots.delete_tree(rebuilt_document_map_tree_name)?;
+    roots.delete_tree(rebuild_invalidated_tree_name)?;
+
+    database.query_cache().invalidate(view_name);
+    database.reduce_cache().clear(view_name);
+    reset_joins(database, view_name)?;
+    let mut materializations = PendingMaterializations::default();
+    materializations.record(
+        collection,
+        view,
+        view_entries
+            .get_range(&(..))?
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect(),
+    );
+    materializations.apply(database)
+}
+
+type TreeContents = BTreeMap<Vec<u8>, ArcBytes<'static>>;
+
+/// Returns the keys that must be modified to replace the contents of
+/// `existing` with the contents of `replacement`, along with the contents of
+/// `replacement`.
+fn replacement_of(
+    existing: &Tree<Unversioned, AnyFile>,
+    replacement: &Tree<Unversioned, AnyFile>,
+) -> Result<(Vec<ArcBytes<'static>>, TreeContents), Error> {
+    let contents = replacement
+        .get_range(&(..))?
+        .into_iter()
+        .map(|(key, value)| (key.to_vec(), value))
+        .collect::<TreeContents>();
+    let keys = existing
+        .get_range(&(..))?
+        .into_iter()
+        .map(|(key, _)| key.to_vec())
+        .chain(contents.keys().cloned())
+        .collect::<BTreeSet<_>>();
+    Ok((keys.into_iter().map(ArcBytes::from).collect(), contents))
+}
+
+fn replace(contents: &mut TreeContents, key: &[u8]) -> KeyOperation<ArcBytes<'static>> {
+    contents
+        .remove(key)
+        .map_or(KeyOperation::Remove, KeyOperation::Set)
+}
+
 pub struct DocumentRequest<'a> {
     pub document_ids: Vec<ArcBytes<'static>>,
     pub map_request: &'a Map,
@@ -165,6 +364,7 @@ pub struct DocumentRequest<'a> {
 
     pub document_map: &'a UnlockedTransactionTree<AnyFile>,
     pub documents: &'a UnlockedTransactionTree<AnyFile>,
+    pub document_contents: Option<&'a UnlockedTransactionTree<AnyFile>>,
     pub view_entries: &'a UnlockedTransactionTree<AnyFile>,
     pub view: &'a dyn Serialized,
 }
@@ -173,19 +373,23 @@ type DocumentIdPayload = (ArcBytes<'static>, Option<ArcBytes<'static>>);
 type BatchPayload = (Vec<ArcBytes<'static>>, flume::Receiver<DocumentIdPayload>);
 
 impl<'a> DocumentRequest<'a> {
-    fn generate_batches(
+    fn generate_batches<R: Root>(
         batch_sender: flume::Sender<BatchPayload>,
         document_ids: &[ArcBytes<'static>],
         documents: &UnlockedTransactionTree<AnyFile>,
+        document_contents: Option<&UnlockedTransactionTree<AnyFile>>,
     ) -> Result<(), Error> {
         // Generate batches
-        let mut documents = documents.lock::<Versioned>();
+        let mut documents = documents.lock::<R>();
         for chunk in document_ids.chunks(1024) {
             let (document_id_sender, document_id_receiver) = flume::bounded(chunk.len());
             batch_sender
                 .send((chunk.to_vec(), document_id_receiver))
                 .unwrap();
             let mut documents = documents.get_multiple(chunk.iter().map(ArcBytes::as_slice))?;
+            if let Some(document_contents) = document_contents {
+                resolve_serialized_contents(document_contents, &mut documents)?;
+            }
             documents.sort_by(|a, b| a.0.cmp(&b.0));
 
             for document_id in chunk.iter().rev() {
@@ -221,20 +425,33 @@ impl<'a> DocumentRequest<'a> {
                 .each(1..=parallelization, |_| -> Result<_, Error> {
                     let mut results = Vec::new();
                     while let Ok((document_id, document)) = document_id_receiver.recv() {
-                        let map_result = if let Some(document) = document {
+                        let (map_result, included) = if let Some(document) = document {
                             let document = deserialize_document(&document)?;
 
-                            // Call the schema map function
-                            view.map(&document).map_err(bonsaidb_core::Error::from)?
+                            if view.filter(&document).map_err(bonsaidb_core::Error::from)? {
+                                // Call the schema map function
+                                let map_result =
+                                    view.map(&document).map_err(bonsaidb_core::Error::from)?;
+                                (map_result, true)
+                            } else {
+                                (Vec::new(), false)
+                            }
                         } else {
                             // Get multiple didn't return this document ID.
-                            Vec::new()
+                            (Vec::new(), true)
                         };
                         let keys: HashSet<OwnedBytes> = map_result
                             .iter()
                             .map(|map| OwnedBytes::from(map.key.as_slice()))
                             .collect();
-                        let new_keys = ArcBytes::from(bincode::serialize(&keys)?);
+                        // Documents excluded by the view's filter are removed
+                        // from the document map rather than recorded as
+                        // emitting no keys.
+                        let new_keys = if included {
+                            Some(ArcBytes::from(bincode::serialize(&keys)?))
+                        } else {
+                            None
+                        };
 
                         results.push((document_id, new_keys, keys, map_result));
                     }
@@ -267,7 +484,7 @@ impl<'a> DocumentRequest<'a> {
     fn update_document_map(
         document_ids: Vec<ArcBytes<'static>>,
         document_map: &mut LockedTransactionTree<'_, Unversioned, AnyFile>,
-        document_maps: &BTreeMap<ArcBytes<'static>, ArcBytes<'static>>,
+        document_maps: &BTreeMap<ArcBytes<'static>, Option<ArcBytes<'static>>>,
         mut document_keys: BTreeMap<ArcBytes<'static>, HashSet<OwnedBytes>>,
         all_keys: &mut BTreeSet<ArcBytes<'static>>,
     ) -> Result<BTreeMap<ArcBytes<'static>, HashSet<ArcBytes<'static>>>, Error> {
@@ -279,8 +496,10 @@ impl<'a> DocumentRequest<'a> {
                 if let Some(existing_map) = value {
                     maps_to_clear.push((key.to_owned(), existing_map));
                 }
-                let new_map = document_maps.get(key).unwrap();
-                KeyOperation::Set(new_map.clone())
+                match document_maps.get(key).unwrap() {
+                    Some(new_map) => KeyOperation::Set(new_map.clone()),
+                    None => KeyOperation::Remove,
+                }
             })),
         )?;
         let mut view_entries_to_clean = BTreeMap::new();
@@ -331,6 +550,7 @@ impl<'a> DocumentRequest<'a> {
         map_request: &Map,
         document_map: &mut LockedTransactionTree<'_, Unversioned, AnyFile>,
         view_entries: &mut LockedTransactionTree<'_, Unversioned, AnyFile>,
+        modified_keys: &mut BTreeSet<ArcBytes<'static>>,
     ) -> Result<(), Error> {
         while let Ok(Batch {
             document_ids,
@@ -347,6 +567,7 @@ impl<'a> DocumentRequest<'a> {
                 document_keys,
                 &mut all_keys,
             )?;
+            modified_keys.extend(all_keys.iter().cloned());
 
             Self::update_view_entries(
                 view,
@@ -360,12 +581,26 @@ impl<'a> DocumentRequest<'a> {
         Ok(())
     }
 
-    pub fn map(&mut self) -> Result<(), Error> {
+    /// Maps the requested documents, returning the view keys that were
+    /// modified.
+    pub fn map(&mut self) -> Result<BTreeSet<ArcBytes<'static>>, Error> {
         let (batch_sender, batch_receiver) = flume::bounded(1);
         let (mapped_sender, mapped_receiver) = flume::bounded(1);
+        let mut modified_keys = BTreeSet::new();
 
         for result in Parallel::new()
-            .add(|| Self::generate_batches(batch_sender, &self.document_ids, self.documents))
+            .add(|| {
+                with_document_root!(
+                    self.database.data.schema,
+                    &self.map_request.collection,
+                    |R| Self::generate_batches::<R>(
+                        batch_sender,
+                        &self.document_ids,
+                        self.documents,
+                        self.document_contents
+                    )
+                )
+            })
             .add(|| {
                 Self::map_batches(
                     &batch_receiver,
@@ -383,6 +618,7 @@ impl<'a> DocumentRequest<'a> {
                     self.map_request,
                     &mut document_map,
                     &mut view_entries,
+                    &mut modified_keys,
                 )
             })
             .run()
@@ -390,14 +626,14 @@ impl<'a> DocumentRequest<'a> {
             result?;
         }
 
-        Ok(())
+        Ok(modified_keys)
     }
 }
 
 #[derive(Default)]
 struct Batch {
     document_ids: Vec<ArcBytes<'static>>,
-    document_maps: BTreeMap<ArcBytes<'static>, ArcBytes<'static>>,
+    document_maps: BTreeMap<ArcBytes<'static>, Option<ArcBytes<'static>>>,
     document_keys: BTreeMap<ArcBytes<'static>, HashSet<OwnedBytes>>,
     new_mappings: BTreeMap<ArcBytes<'static>, Vec<map::Serialized>>,
     all_keys: BTreeSet<ArcBytes<'static>>,
@@ -476,6 +712,12 @@ impl<'a> ViewEntryUpdater<'a> {
                     }));
                     return KeyOperation::Skip;
                 }
+                // Views storing values by reference produce each value again
+                // when they are queried.
+                let value = match self.view.value_storage() {
+                    ValueStorage::Inline => value,
+                    ValueStorage::Reference => Bytes::default(),
+                };
                 let entry_mapping = EntryMapping { source, value };
 
                 // attempt to update an existing
@@ -0,0 +1,182 @@
+use std::collections::{BTreeSet, HashMap};
+
+use bonsaidb_core::arc_bytes::serde::Bytes;
+use bonsaidb_core::arc_bytes::ArcBytes;
+use bonsaidb_core::keyvalue::{Command, KeyOperation, SetCommand, Value};
+use bonsaidb_core::schema::view::{
+    self, materialized_view_key, Serialized, ViewMaterialization, MATERIALIZED_VIEW_NAMESPACE,
+};
+use bonsaidb_core::schema::{CollectionName, ViewName};
+use nebari::io::any::AnyFile;
+use nebari::tree::Unversioned;
+use nebari::Tree;
+
+use crate::database::Database;
+use crate::views::join::update_joins;
+use crate::views::{view_entries_tree_name, ViewEntry};
+use crate::Error;
+
+/// The view keys modified while updating views. Because the key-value store
+/// isn't part of the view's transaction, the materialized values are only
+/// written once the transaction has been committed. The modified keys are also
+/// used to invalidate the view's cached reduce segments.
+#[derive(Debug, Default)]
+pub struct PendingMaterializations {
+    views: HashMap<ViewName, (CollectionName, BTreeSet<ArcBytes<'static>>)>,
+}
+
+impl PendingMaterializations {
+    /// Records that `keys` were modified in `view`.
+    pub fn record(
+        &mut self,
+        collection: &CollectionName,
+        view: &dyn Serialized,
+        keys: BTreeSet<ArcBytes<'static>>,
+    ) {
+        if !keys.is_empty() {
+            self.views
+                .entry(view.view_name())
+                .or_insert_with(|| (collection.clone(), BTreeSet::new()))
+                .1
+                .extend(keys);
+        }
+    }
+
+    /// Invalidates the cached reduce segments containing the recorded keys,
+    /// updates the joined entries of the recorded keys, and writes the current
+    /// reduced values of all recorded materialized views and keys to the
+    /// key-value store.
+    pub fn apply(self, database: &Database) -> Result<(), Error> {
+        for (view_name, (collection, keys)) in self.views {
+            database.reduce_cache().invalidate(&view_name, &keys);
+            update_joins(database, &view_name, &keys)?;
+            let view = database.data.schema.view_by_name(&view_name)?;
+            if view.materialization() == ViewMaterialization::None {
+                continue;
+            }
+
+            let view_entries =
+                database
+                    .roots()
+                    .tree(database.collection_tree::<Unversioned, _>(
+                        &collection,
+                        view_entries_tree_name(&view_name),
+                    )?)?;
+            match view.materialization() {
+                ViewMaterialization::None => {}
+                ViewMaterialization::Total => {
+                    materialize_total(database, view, &view_name, &view_entries)?;
+                }
+                ViewMaterialization::Grouped => {
+                    materialize_grouped(database, &view_name, &view_entries, &keys)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn materialize_total(
+    database: &Database,
+    view: &dyn Serialized,
+    view_name: &ViewName,
+    view_entries: &Tree<Unversioned, AnyFile>,
+) -> Result<(), Error> {
+    let mut entries = view_entries
+        .get_range(&(..))?
+        .into_iter()
+        .map(|(_, entry)| bincode::deserialize::<ViewEntry>(&entry))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let reduced = if entries.len() == 1 {
+        Ok(entries.pop().unwrap().reduced_value.into_vec())
+    } else {
+        view.reduce(
+            &entries
+                .iter()
+                .map(|entry| (entry.key.as_slice(), entry.reduced_value.as_slice()))
+                .collect::<Vec<_>>(),
+            true,
+        )
+    };
+
+    let key = materialized_view_key(view_name, None);
+    match reduced {
+        Ok(reduced) => set_materialized_value(database, key, reduced),
+        Err(view::Error::Core(bonsaidb_core::Error::ReduceUnimplemented)) => {
+            delete_materialized_value(database, key)
+        }
+        Err(other) => Err(Error::from(other)),
+    }
+}
+
+fn materialize_grouped(
+    database: &Database,
+    view_name: &ViewName,
+    view_entries: &Tree<Unversioned, AnyFile>,
+    keys: &BTreeSet<ArcBytes<'static>>,
+) -> Result<(), Error> {
+    let mut entries = view_entries
+        .get_multiple(keys.iter().map(ArcBytes::as_slice))?
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+    for key in keys {
+        let kv_key = materialized_view_key(view_name, Some(key.as_slice()));
+        if let Some(entry) = entries.remove(key) {
+            let entry = bincode::deserialize::<ViewEntry>(&entry)?;
+            set_materialized_value(database, kv_key, entry.reduced_value.into_vec())?;
+        } else {
+            delete_materialized_value(database, kv_key)?;
+        }
+    }
+    Ok(())
+}
+
+/// Removes all materialized values for `view`, using the keys currently
+/// stored in `view_entries`.
+pub fn clear_materialized_values(
+    database: &Database,
+    view: &dyn Serialized,
+    view_entries: &Tree<Unversioned, AnyFile>,
+) -> Result<(), Error> {
+    let view_name = view.view_name();
+    match view.materialization() {
+        ViewMaterialization::None => Ok(()),
+        ViewMaterialization::Total => {
+            delete_materialized_value(database, materialized_view_key(&view_name, None))
+        }
+        ViewMaterialization::Grouped => {
+            for (key, _) in view_entries.get_range(&(..))? {
+                delete_materialized_value(
+                    database,
+                    materialized_view_key(&view_name, Some(key.as_slice())),
+                )?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn set_materialized_value(database: &Database, key: String, value: Vec<u8>) -> Result<(), Error> {
+    database.perform_kv_operation(KeyOperation {
+        namespace: Some(MATERIALIZED_VIEW_NAMESPACE.to_string()),
+        key,
+        command: Command::Set(SetCommand {
+            value: Value::Bytes(Bytes::from(value)),
+            expiration: None,
+            keep_existing_expiration: false,
+            check: None,
+            return_previous_value: false,
+        }),
+    })?;
+    Ok(())
+}
+
+fn delete_materialized_value(database: &Database, key: String) -> Result<(), Error> {
+    database.perform_kv_operation(KeyOperation {
+        namespace: Some(MATERIALIZED_VIEW_NAMESPACE.to_string()),
+        key,
+        command: Command::Delete,
+    })?;
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use bonsaidb_core::schema::view::Serialized;
+use bonsaidb_core::schema::ViewName;
+use nebari::io::any::AnyFile;
+use nebari::tree::Unversioned;
+use nebari::Roots;
+use parking_lot::Mutex;
+
+use crate::Error;
+
+/// The tree the number of times each view has been queried is stored in.
+const VIEW_QUERY_COUNTS_TREE: &str = "view-query-counts";
+
+/// Counts how often each view of a database is queried, so that the views
+/// queried most often can be updated in the background when the database is
+/// opened. See [`Views::warm_on_open`](crate::config::Views::warm_on_open).
+///
+/// Counts are kept in memory and added to the counts stored in the database
+/// when [`Self::persist()`] is called.
+#[derive(Debug, Default)]
+pub struct ViewQueryCounts {
+    pending: Mutex<HashMap<ViewName, u64>>,
+    warmed: AtomicBool,
+}
+
+impl ViewQueryCounts {
+    /// Records that `view` was queried.
+    pub fn record(&self, view: &ViewName) {
+        let mut pending = self.pending.lock();
+        if let Some(count) = pending.get_mut(view) {
+            *count += 1;
+        } else {
+            pending.insert(view.clone(), 1);
+        }
+    }
+
+    /// Adds the counts recorded since the last call to the counts stored in
+    /// `roots`.
+    pub fn persist(&self, roots: &Roots<AnyFile>) -> Result<(), Error> {
+        let mut pending = self.pending.lock();
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        let tree = roots.tree(Unversioned::tree(VIEW_QUERY_COUNTS_TREE))?;
+        for (view, count) in pending.drain() {
+            let key = view_key(&view);
+            let stored = tree
+                .get(key.as_bytes())?
+                .map_or(0, |value| decode_count(&value));
+            tree.set(
+                key.into_bytes(),
+                stored.saturating_add(count).to_be_bytes().to_vec(),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Returns the names of up to `count` of `views` that have been queried,
+    /// ordered from most to least queried. Only the first call returns any
+    /// views, which ensures each database is only warmed once.
+    pub fn views_to_warm<'a>(
+        &self,
+        roots: &Roots<AnyFile>,
+        views: impl Iterator<Item = &'a dyn Serialized>,
+        count: usize,
+    ) -> Result<Vec<ViewName>, Error> {
+        if self.warmed.swap(true, Ordering::SeqCst) {
+            return Ok(Vec::new());
+        }
+
+        let tree = roots.tree(Unversioned::tree(VIEW_QUERY_COUNTS_TREE))?;
+        let mut counts = Vec::new();
+        for view in views {
+            let name = view.view_name();
+            if let Some(value) = tree.get(view_key(&name).as_bytes())? {
+                counts.push((decode_count(&value), name));
+            }
+        }
+        counts.sort_by(|(a, _), (b, _)| b.cmp(a));
+        Ok(counts
+            .into_iter()
+            .take(count)
+            .map(|(_, name)| name)
+            .collect())
+    }
+}
+
+fn view_key(view: &ViewName) -> String {
+    format!("{view:#}")
+}
+
+fn decode_count(value: &[u8]) -> u64 {
+    value.try_into().map_or(0, u64::from_be_bytes)
+}
@@ -60,7 +60,7 @@ fn core_path() -> Path {
 #[derive(Attribute)]
 #[attribute(ident = "collection")]
 #[attribute(
-    invalid_field = r#"Only `authority = "some-authority"`, `name = "some-name"`, `views = [SomeView, AnotherView]`, `primary_key = u64`, `natural_id = |contents: &Self| Some(contents.id)`, serialization = SerializationFormat` and `core = bonsaidb::core` are supported attributes"#
+    invalid_field = r#"Only `authority = "some-authority"`, `name = "some-name"`, `views = [SomeView, AnotherView]`, `joins = [SomeJoin]`, `projections = [SomeProjection]`, `primary_key = u64`, `natural_id = |contents: &Self| Some(contents.id)`, `time_series = TimeSeries::partitioned_by(duration)`, `id_assignment = IdAssignment::Random`, `versioned = false`, `deduplicated`, `legacy_names = [CollectionName::private("old-name")]`, `crdt`, `sealed`, serialization = SerializationFormat` and `core = bonsaidb::core` are supported attributes"#
 )]
 struct CollectionAttribute {
     authority: Option<Expr>,
@@ -71,6 +71,14 @@ struct CollectionAttribute {
     #[attribute(default)]
     #[attribute(expected = r#"Specify the `views` like so: `view = [SomeView, AnotherView]`"#)]
     views: Vec<Type>,
+    #[attribute(default)]
+    #[attribute(expected = r#"Specify the `joins` like so: `joins = [SomeJoin, AnotherJoin]`"#)]
+    joins: Vec<Type>,
+    #[attribute(default)]
+    #[attribute(
+        expected = r#"Specify the `projections` like so: `projections = [SomeProjection, AnotherProjection]`"#
+    )]
+    projections: Vec<Type>,
     #[attribute(
         expected = r#"Specify the `serialization` like so: `serialization = Format` or `serialization = None` to disable deriving it"#
     )]
@@ -86,6 +94,26 @@ struct CollectionAttribute {
         expected = r#"Specify the `natural_id` like so: `natural_id = function_name` or `natural_id = |doc| { .. }`"#
     )]
     natural_id: Option<Expr>,
+    #[attribute(
+        expected = r#"Specify the `time_series` options like so: `time_series = TimeSeries::partitioned_by(duration)`"#
+    )]
+    time_series: Option<Expr>,
+    #[attribute(
+        expected = r#"Specify the `id_assignment` like so: `id_assignment = IdAssignment::Random`"#
+    )]
+    id_assignment: Option<Expr>,
+    #[attribute(
+        expected = r#"Specify whether the collection is versioned like so: `versioned = false`"#
+    )]
+    versioned: Option<Expr>,
+    #[attribute(default)]
+    #[attribute(
+        expected = r#"Specify the `legacy_names` like so: `legacy_names = [CollectionName::private("old-name")]`"#
+    )]
+    legacy_names: Vec<Expr>,
+    deduplicated: bool,
+    crdt: bool,
+    sealed: bool,
     #[attribute(expected = r#"Specify the the path to `core` like so: `core = bosaidb::core`"#)]
     core: Option<Path>,
 }
@@ -106,9 +134,18 @@ pub fn collection_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStr
         authority,
         name,
         views,
+        joins,
+        projections,
         serialization,
         primary_key,
         natural_id,
+        time_series,
+        id_assignment,
+        versioned,
+        legacy_names,
+        deduplicated,
+        crdt,
+        sealed,
         core,
         encryption_key,
         encryption_required,
@@ -123,9 +160,40 @@ pub fn collection_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStr
 
     let core = core.unwrap_or_else(core_path);
 
-    let primary_key = primary_key.unwrap_or_else(|| parse_quote!(u64));
+    let primary_key = primary_key.unwrap_or_else(|| {
+        if time_series.is_some() {
+            parse_quote!(#core::keyvalue::Timestamp)
+        } else {
+            parse_quote!(u64)
+        }
+    });
 
     let serialization = match serialization {
+        _ if sealed => {
+            if let Some(serialization) = serialization {
+                abort!(
+                    serialization,
+                    "`serialization` cannot be specified for `sealed` collections"
+                );
+            }
+            if let Some(natural_id) = natural_id {
+                abort!(
+                    natural_id,
+                    "`natural_id` is not supported for `sealed` collections"
+                );
+            }
+
+            quote! {
+                impl #impl_generics #core::schema::SerializedCollection for #ident #ty_generics #where_clause {
+                    type Contents = #core::sealed::Sealed;
+                    type Format = #core::transmog_pot::Pot;
+
+                    fn format() -> Self::Format {
+                        #core::transmog_pot::Pot::default()
+                    }
+                }
+            }
+        }
         Some(serialization) if serialization.is_ident("None") => {
             if let Some(natural_id) = natural_id {
                 abort!(
@@ -197,6 +265,50 @@ pub fn collection_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStr
         }
     });
 
+    let time_series = time_series.map(|time_series| {
+        quote! {
+            fn time_series() -> Option<#core::schema::TimeSeries> {
+                Some(#time_series)
+            }
+        }
+    });
+
+    let id_assignment = id_assignment.map(|id_assignment| {
+        quote! {
+            fn id_assignment() -> #core::schema::IdAssignment {
+                #id_assignment
+            }
+        }
+    });
+
+    let versioned = versioned.map(|versioned| {
+        quote! {
+            fn versioned() -> bool {
+                #versioned
+            }
+        }
+    });
+
+    let legacy_names = (!legacy_names.is_empty()).then(|| {
+        quote! {
+            fn legacy_names() -> Vec<#core::schema::CollectionName> {
+                vec![#(#legacy_names),*]
+            }
+        }
+    });
+
+    let deduplicated = deduplicated.then(|| {
+        quote! {
+            fn deduplicated() -> bool {
+                true
+            }
+        }
+    });
+
+    let crdt = crdt.then(|| quote!(schema.define_crdt::<Self>()?;));
+    let sealed =
+        sealed.then(|| quote!(schema.define_view(#core::sealed::BlindIndex::<Self>::default())?;));
+
     quote! {
         impl #impl_generics #core::schema::Collection for #ident #ty_generics #where_clause {
             type PrimaryKey = #primary_key;
@@ -205,10 +317,19 @@ pub fn collection_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStr
                 #name
             }
             fn define_views(schema: &mut #core::schema::Schematic) -> Result<(), #core::Error> {
+                #crdt
+                #sealed
                 #( schema.define_view(#views)?; )*
+                #( schema.define_joined_view(#joins)?; )*
+                #( schema.define_projection(#projections)?; )*
                 Ok(())
             }
             #encryption
+            #time_series
+            #id_assignment
+            #versioned
+            #deduplicated
+            #legacy_names
         }
         #serialization
     }
@@ -313,7 +434,7 @@ pub fn view_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
 #[derive(Attribute)]
 #[attribute(ident = "schema")]
 #[attribute(
-    invalid_field = r#"Only `name = "name"`, `authority = "authority"`, `collections = [SomeCollection, AnotherCollection]`, `include = [OtherSchema]`, and `core = bonsaidb::core` are supported attributes"#
+    invalid_field = r#"Only `name = "name"`, `authority = "authority"`, `collections = [SomeCollection, AnotherCollection]`, `include = [OtherSchema]`, `key_value_encryption_key = Some(KeyId::Master)`, and `core = bonsaidb::core` are supported attributes"#
 )]
 struct SchemaAttribute {
     #[attribute(missing = r#"You need to specify the schema name via `#[schema(name = "name")]`"#)]
@@ -329,14 +450,15 @@ struct SchemaAttribute {
         expected = r#"Specify other Schemas as plugins like so: `include = [SomeSchema, AnotherSchema]`"#
     )]
     include: Vec<Type>,
+    key_value_encryption_key: Option<Expr>,
     #[attribute(expected = r#"Specify the the path to `core` like so: `core = bosaidb::core`"#)]
     core: Option<Path>,
 }
 
 /// Derives the `bonsaidb::core::schema::Schema` trait.
 #[proc_macro_error]
-/// `#[schema(name = "Name", authority = "Authority", collections = [A, B, C]), core = bonsaidb::core]`
-/// `authority`, `collections` and `core` are optional
+/// `#[schema(name = "Name", authority = "Authority", collections = [A, B, C], key_value_encryption_key = Some(KeyId::Master), core = bonsaidb::core)]`
+/// `authority`, `collections`, `key_value_encryption_key` and `core` are optional
 #[proc_macro_derive(Schema, attributes(schema))]
 pub fn schema_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let DeriveInput {
@@ -351,6 +473,7 @@ pub fn schema_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream
         authority,
         collections,
         include,
+        key_value_encryption_key,
         core,
     } = SchemaAttribute::from_attributes(&attrs).unwrap_or_abort();
 
@@ -362,6 +485,14 @@ pub fn schema_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream
         |authority| quote!(#core::schema::Qualified::new(#authority, #name)),
     );
 
+    let key_value_encryption = key_value_encryption_key.map(|key_value_encryption_key| {
+        quote! {
+            fn key_value_encryption_key() -> Option<#core::document::KeyId> {
+                #key_value_encryption_key
+            }
+        }
+    });
+
     // For some reason, quote! does not like #include.
     let plugins = include;
     quote! {
@@ -379,6 +510,8 @@ pub fn schema_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream
 
                 Ok(())
             }
+
+            #key_value_encryption
         }
     }
     .into()
@@ -670,7 +803,7 @@ pub fn key_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
 #[derive(Attribute)]
 #[attribute(ident = "api")]
 #[attribute(
-    invalid_field = r#"Only `name = "name"`, `authority = "authority"`, `response = ResponseType`, `error = ErrorType` and `core = bonsaidb::core` are supported attributes"#
+    invalid_field = r#"Only `name = "name"`, `authority = "authority"`, `response = ResponseType`, `error = ErrorType`, `idempotent` and `core = bonsaidb::core` are supported attributes"#
 )]
 struct ApiAttribute {
     #[attribute(missing = r#"You need to specify the api name via `#[api(name = "name")]`"#)]
@@ -680,14 +813,15 @@ struct ApiAttribute {
     response: Option<Type>,
     #[attribute(expected = r#"Specify the error type like so: `error = ErrorType`"#)]
     error: Option<Type>,
+    idempotent: bool,
     #[attribute(expected = r#"Specify the the path to `core` like so: `core = bosaidb::core`"#)]
     core: Option<Path>,
 }
 
 /// Derives the `bonsaidb::core::api::Api` trait.
 #[proc_macro_error]
-/// `#[api(name = "Name", authority = "Authority", response = ResponseType, error = ErrorType, core = bonsaidb::core)]`
-/// `authority`, `response`, `error` and `core` are optional
+/// `#[api(name = "Name", authority = "Authority", response = ResponseType, error = ErrorType, idempotent, core = bonsaidb::core)]`
+/// `authority`, `response`, `error`, `idempotent` and `core` are optional
 #[proc_macro_derive(Api, attributes(api))]
 pub fn api_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let DeriveInput {
@@ -702,6 +836,7 @@ pub fn api_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
         authority,
         response,
         error,
+        idempotent,
         core,
     } = ApiAttribute::from_attributes(&attrs).unwrap_or_abort();
 
@@ -726,6 +861,74 @@ pub fn api_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
             fn name() -> ApiName {
                 #name
             }
+
+            fn idempotent() -> bool {
+                #idempotent
+            }
+        }
+    }
+    .into()
+}
+
+#[derive(Attribute)]
+#[attribute(ident = "crdt")]
+#[attribute(invalid_field = r#"Only `core = bonsaidb::core` is supported"#)]
+struct CrdtAttribute {
+    #[attribute(expected = r#"Specify the the path to `core` like so: `core = bosaidb::core`"#)]
+    core: Option<Path>,
+}
+
+/// Derives the `bonsaidb::core::crdt::Crdt` trait by merging each field.
+#[proc_macro_error]
+/// `#[crdt(core = bonsaidb::core)]`
+/// `core` is optional
+#[proc_macro_derive(Crdt, attributes(crdt))]
+pub fn crdt_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let DeriveInput {
+        attrs,
+        ident,
+        generics,
+        data,
+        ..
+    } = parse_macro_input!(input as DeriveInput);
+
+    let CrdtAttribute { core } = CrdtAttribute::from_attributes(&attrs).unwrap_or_abort();
+
+    let core = core.unwrap_or_else(core_path);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let merges: Vec<TokenStream> = match data {
+        Data::Struct(DataStruct {
+            fields: Fields::Named(FieldsNamed { named, .. }),
+            ..
+        }) => named
+            .into_iter()
+            .map(|Field { ident, .. }| quote!(Crdt::merge(&mut self.#ident, other.#ident);))
+            .collect(),
+        Data::Struct(DataStruct {
+            fields: Fields::Unnamed(FieldsUnnamed { unnamed, .. }),
+            ..
+        }) => (0..unnamed.len())
+            .map(Index::from)
+            .map(|index| quote!(Crdt::merge(&mut self.#index, other.#index);))
+            .collect(),
+        Data::Struct(DataStruct {
+            fields: Fields::Unit,
+            ..
+        }) => Vec::new(),
+        Data::Enum(_) | Data::Union(_) => {
+            abort_call_site!("`Crdt` can only be derived for structs")
+        }
+    };
+
+    quote! {
+        # use #core::crdt::Crdt;
+
+        impl #impl_generics Crdt for #ident #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn merge(&mut self, other: Self) {
+                #(#merges)*
+            }
         }
     }
     .into()
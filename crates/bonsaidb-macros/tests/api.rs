@@ -45,3 +45,16 @@ fn response() {
 
     assert_eq!(<Test as Api>::Response::new(), String::new());
 }
+#[test]
+fn idempotent() {
+    #[derive(Api, Debug, Serialize, Deserialize)]
+    #[api(name = "name", idempotent)]
+    struct Test;
+
+    #[derive(Api, Debug, Serialize, Deserialize)]
+    #[api(name = "name")]
+    struct NotIdempotent;
+
+    assert!(Test::idempotent());
+    assert!(!NotIdempotent::idempotent());
+}
@@ -2,8 +2,9 @@ use core::fmt::Debug;
 
 use bonsaidb::core::document::{CollectionDocument, Emit, KeyId};
 use bonsaidb::core::schema::{
-    Collection, CollectionViewSchema, DefaultSerialization, DefaultViewSerialization, Name,
-    Qualified, Schematic, SerializedCollection, View, ViewMapResult,
+    Collection, CollectionName, CollectionViewSchema, DefaultSerialization,
+    DefaultViewSerialization, Name, Qualified, Schematic, SerializedCollection, View,
+    ViewMapResult,
 };
 use serde::{Deserialize, Serialize};
 
@@ -70,6 +71,92 @@ fn views() {
     impl DefaultViewSerialization for ShapesByNumberOfSides {}
 }
 
+#[test]
+fn joins() {
+    use bonsaidb::core::schema::view::join::JoinedView;
+
+    #[derive(Clone, Collection, Debug, Serialize, Deserialize)]
+    #[collection(name = "Name", views = [ShapesBySides], joins = [ShapesJoin])]
+    struct Shape {
+        pub sides: u32,
+    }
+
+    let schematic = Schematic::from_schema::<Shape>().unwrap();
+    let join = schematic.join::<ShapesJoin>().expect("couldn't find join");
+    assert_eq!(join.left, ShapesBySides.view_name());
+
+    #[derive(Debug, Clone)]
+    struct ShapesBySides;
+
+    impl View for ShapesBySides {
+        type Collection = Shape;
+        type Key = u32;
+        type Value = usize;
+
+        fn name(&self) -> Name {
+            Name::new("by-sides")
+        }
+    }
+
+    impl CollectionViewSchema for ShapesBySides {
+        type View = Self;
+
+        fn map(&self, document: CollectionDocument<Shape>) -> ViewMapResult<Self::View> {
+            document
+                .header
+                .emit_key_and_value(document.contents.sides, 1)
+        }
+    }
+
+    impl DefaultViewSerialization for ShapesBySides {}
+
+    #[derive(Debug)]
+    struct ShapesJoin;
+
+    impl JoinedView for ShapesJoin {
+        type Left = ShapesBySides;
+        type Right = ShapesBySides;
+
+        fn name(&self) -> Name {
+            Name::new("shapes-join")
+        }
+    }
+}
+
+#[test]
+fn projections() {
+    use bonsaidb::core::schema::Projection;
+
+    #[derive(Clone, Collection, Debug, Serialize, Deserialize)]
+    #[collection(name = "Name", projections = [ShapeSides])]
+    struct Shape {
+        pub name: String,
+        pub sides: u32,
+    }
+
+    let schematic = Schematic::from_schema::<Shape>().unwrap();
+    let projection = schematic
+        .projection::<ShapeSides>()
+        .expect("couldn't find projection");
+    assert_eq!(projection, &Name::new("sides"));
+
+    #[derive(Debug)]
+    struct ShapeSides;
+
+    impl Projection for ShapeSides {
+        type Collection = Shape;
+        type Output = u32;
+
+        fn name(&self) -> Name {
+            Name::new("sides")
+        }
+
+        fn project(&self, contents: Shape) -> Self::Output {
+            contents.sides
+        }
+    }
+}
+
 #[test]
 fn serialization() {
     #[derive(Collection, Clone, Debug, Deserialize, Serialize)]
@@ -158,3 +245,108 @@ fn natural_id() {
     #[collection( natural_id = |_:&Self| Some(1_u64))]
     struct Test;
 }
+
+#[test]
+fn time_series() {
+    use std::time::Duration;
+
+    use bonsaidb::core::keyvalue::Timestamp;
+    use bonsaidb::core::schema::TimeSeries;
+
+    #[derive(Collection, Debug, Deserialize, Serialize)]
+    #[collection(name = "Name")]
+    #[collection(time_series = TimeSeries::partitioned_by(Duration::from_secs(60)))]
+    struct Test;
+
+    assert_eq!(
+        Test::time_series(),
+        Some(TimeSeries::partitioned_by(Duration::from_secs(60)))
+    );
+    let _: Option<Timestamp> = <Test as SerializedCollection>::natural_id(&Test);
+}
+
+#[test]
+fn id_assignment() {
+    use bonsaidb::core::schema::IdAssignment;
+
+    #[derive(Collection, Debug, Deserialize, Serialize)]
+    #[collection(name = "Name", primary_key = u128)]
+    #[collection(id_assignment = IdAssignment::Random)]
+    struct Test;
+
+    assert_eq!(Test::id_assignment(), IdAssignment::Random);
+}
+
+#[test]
+fn versioned() {
+    #[derive(Collection, Debug, Deserialize, Serialize)]
+    #[collection(name = "Name", versioned = false)]
+    struct Test;
+
+    assert!(!Test::versioned());
+}
+
+#[test]
+fn deduplicated() {
+    #[derive(Collection, Debug, Deserialize, Serialize)]
+    #[collection(name = "Name", deduplicated)]
+    struct Test;
+
+    assert!(Test::deduplicated());
+}
+
+#[test]
+fn legacy_names() {
+    #[derive(Collection, Debug, Deserialize, Serialize)]
+    #[collection(
+        name = "Name",
+        legacy_names = [CollectionName::private("OldName"), CollectionName::new("Authority", "Name")]
+    )]
+    struct Test;
+
+    assert_eq!(
+        Test::legacy_names(),
+        vec![
+            CollectionName::private("OldName"),
+            CollectionName::new("Authority", "Name")
+        ]
+    );
+}
+
+#[test]
+fn crdt() {
+    use bonsaidb::core::crdt::{Counter, Crdt, ReplicaId};
+
+    #[derive(Collection, Crdt, Debug, Default, Deserialize, Serialize)]
+    #[collection(name = "Name", crdt)]
+    struct Test {
+        counter: Counter,
+    }
+
+    let schematic = Schematic::from_schema::<Test>().unwrap();
+    assert!(schematic.collection_is_crdt(&Test::collection_name()));
+
+    let mut a = Test::default();
+    a.counter.increment(ReplicaId(1), 1);
+    let mut b = Test::default();
+    b.counter.increment(ReplicaId(2), 2);
+    a.merge(b);
+    assert_eq!(a.counter.value(), 3);
+}
+
+#[test]
+fn sealed() {
+    use bonsaidb::core::sealed::{BlindIndex, Sealed, SealedCollection};
+
+    #[derive(Collection, Debug, Deserialize, Serialize)]
+    #[collection(name = "Name", sealed)]
+    struct Test {
+        secret: String,
+    }
+
+    impl SealedCollection for Test {}
+
+    let schematic = Schematic::from_schema::<Test>().unwrap();
+    assert!(schematic.view::<BlindIndex<Test>>().is_ok());
+    let _: Option<Sealed> = None::<<Test as SerializedCollection>::Contents>;
+}
@@ -1,5 +1,6 @@
 use core::fmt::Debug;
 
+use bonsaidb::core::document::KeyId;
 use bonsaidb::core::schema::{Collection, CollectionName, Qualified, Schema, Schematic};
 
 #[test]
@@ -70,3 +71,16 @@ fn plugins() {
     #[collection(name = "name")]
     struct TestCollection;
 }
+
+#[test]
+fn key_value_encryption_key() {
+    #[derive(Schema, Debug)]
+    #[schema(name = "name", key_value_encryption_key = Some(KeyId::Master))]
+    struct TestSchema;
+
+    let schematic = Schematic::from_schema::<TestSchema>().unwrap();
+    assert_eq!(
+        schematic.encryption_key_for_key_value_store(),
+        Some(&KeyId::Master)
+    );
+}
@@ -1,13 +1,19 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::marker::PhantomData;
+use std::sync::Arc;
 
 use async_trait::async_trait;
-use bonsaidb_core::api::{self, Api, ApiError, Infallible};
+use bonsaidb_core::api::{self, Api, ApiError, ApiName, Infallible, NamedQuery, QueryName};
 use bonsaidb_core::arc_bytes::serde::Bytes;
-use bonsaidb_core::permissions::PermissionDenied;
+use bonsaidb_core::connection::HasSession;
+use bonsaidb_core::permissions::{Action, PermissionDenied, ResourceName};
 use bonsaidb_core::schema::{InsertError, InvalidNameError};
+use derive_where::derive_where;
 
-use crate::{Backend, ConnectedClient, CustomServer, Error, NoBackend};
+use crate::dispatch::ServerDispatcher;
+use crate::{Backend, ConnectedClient, CustomServer, Error, NoBackend, ServerDatabase};
 
 /// A trait that can dispatch requests for a [`Api`].
 #[async_trait]
@@ -32,6 +38,241 @@ pub struct HandlerSession<'a, B: Backend = NoBackend> {
     pub client: &'a ConnectedClient<B>,
 }
 
+/// A registry of [`Handler`]s, dispatched by the [`ApiName`] of the
+/// [`Api`] each handles.
+///
+/// Each [`Api`] can only be registered once. Registering an [`Api`] whose
+/// name is already registered returns [`Error::ApiAlreadyRegistered`].
+#[derive_where(Clone, Debug, Default)]
+pub struct ApiRegistry<B: Backend = NoBackend> {
+    handlers: HashMap<ApiName, Arc<dyn AnyHandler<B>>>,
+}
+
+impl<B: Backend> ApiRegistry<B> {
+    /// Registers `Dispatcher` as the handler for `Api`.
+    pub fn register<Dispatcher: Handler<B, Api> + 'static, Api: api::Api>(
+        &mut self,
+    ) -> Result<(), Error> {
+        self.insert(
+            Api::name(),
+            Arc::new(AnyWrapper::<Dispatcher, B, Api>(PhantomData)),
+        )
+    }
+
+    /// Registers `Dispatcher` as the handler for `Api`. Before `Dispatcher` is
+    /// invoked, the connected client must be allowed to perform `action` on
+    /// `resource_name`. If the client is not allowed,
+    /// [`bonsaidb_core::Error::PermissionDenied`] is returned.
+    pub fn register_with_permission<
+        Dispatcher: Handler<B, Api> + 'static,
+        Api: api::Api,
+        A: Action + 'static,
+    >(
+        &mut self,
+        resource_name: ResourceName<'static>,
+        action: A,
+    ) -> Result<(), Error> {
+        self.insert(
+            Api::name(),
+            Arc::new(PermissionedHandler {
+                check: Box::new(move |session: &HandlerSession<'_, B>| {
+                    session.as_client.check_permission(&resource_name, &action)
+                }),
+                handler: Arc::new(AnyWrapper::<Dispatcher, B, Api>(PhantomData)),
+            }),
+        )
+    }
+
+    /// Registers all of the apis provided by `plugin`.
+    pub fn register_plugin<Plugin: ApiPlugin<B>>(&mut self, plugin: &Plugin) -> Result<(), Error> {
+        plugin.register_apis(self)
+    }
+
+    /// Returns true if a handler is registered for `name`.
+    #[must_use]
+    pub fn contains(&self, name: &ApiName) -> bool {
+        self.handlers.contains_key(name)
+    }
+
+    /// Returns an iterator over the names of the registered apis.
+    pub fn names(&self) -> impl Iterator<Item = &ApiName> {
+        self.handlers.keys()
+    }
+
+    pub(crate) fn get(&self, name: &ApiName) -> Option<Arc<dyn AnyHandler<B>>> {
+        self.handlers.get(name).cloned()
+    }
+
+    fn insert(&mut self, name: ApiName, handler: Arc<dyn AnyHandler<B>>) -> Result<(), Error> {
+        match self.handlers.entry(name) {
+            Entry::Occupied(entry) => Err(Error::ApiAlreadyRegistered(entry.key().clone())),
+            Entry::Vacant(entry) => {
+                entry.insert(handler);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// A trait that executes a [`NamedQuery`] on behalf of clients.
+///
+/// Clients execute named queries using `execute_named_query()` on their
+/// remote database types. Before the handler is invoked, the client must be
+/// allowed to perform
+/// [`NamedQueryAction::Execute`](bonsaidb_core::permissions::bonsai::NamedQueryAction::Execute)
+/// on the query's
+/// [`named_query_resource_name()`](bonsaidb_core::permissions::bonsai::named_query_resource_name).
+#[async_trait]
+pub trait NamedQueryHandler<B: Backend, Q: NamedQuery>: Send + Sync {
+    /// Executes the query against `database` using the client-provided
+    /// `parameters`.
+    ///
+    /// `database` is not limited to the permissions of the connected client,
+    /// allowing clients to execute this query without being granted access
+    /// to the views or collections it uses.
+    async fn execute(
+        database: ServerDatabase<B>,
+        parameters: Q::Parameters,
+    ) -> Result<Q::Output, bonsaidb_core::Error>;
+}
+
+/// A registry of [`NamedQueryHandler`]s, dispatched by the [`QueryName`] of
+/// the [`NamedQuery`] each handles.
+///
+/// Each [`NamedQuery`] can only be registered once. Registering a query whose
+/// name is already registered returns [`Error::NamedQueryAlreadyRegistered`].
+#[derive_where(Clone, Debug, Default)]
+pub struct NamedQueryRegistry<B: Backend = NoBackend> {
+    queries: HashMap<QueryName, Arc<dyn AnyNamedQuery<B>>>,
+}
+
+impl<B: Backend> NamedQueryRegistry<B> {
+    /// Registers `Dispatcher` as the handler for `Query`.
+    pub fn register<Dispatcher: NamedQueryHandler<B, Query> + 'static, Query: NamedQuery>(
+        &mut self,
+    ) -> Result<(), Error> {
+        match self.queries.entry(Query::name()) {
+            Entry::Occupied(entry) => Err(Error::NamedQueryAlreadyRegistered(entry.key().clone())),
+            Entry::Vacant(entry) => {
+                entry.insert(Arc::new(NamedQueryWrapper::<Dispatcher, B, Query>(
+                    PhantomData,
+                )));
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns true if a handler is registered for `name`.
+    #[must_use]
+    pub fn contains(&self, name: &QueryName) -> bool {
+        self.queries.contains_key(name)
+    }
+
+    /// Returns an iterator over the names of the registered queries.
+    pub fn names(&self) -> impl Iterator<Item = &QueryName> {
+        self.queries.keys()
+    }
+
+    pub(crate) fn get(&self, name: &QueryName) -> Option<Arc<dyn AnyNamedQuery<B>>> {
+        self.queries.get(name).cloned()
+    }
+}
+
+/// A collection of [`Api`]s that can be registered with a server together.
+/// This allows independent components to provide their own apis without
+/// modifying the server's dispatcher.
+pub trait ApiPlugin<B: Backend>: Send + Sync {
+    /// Registers this plugin's apis with `apis`.
+    fn register_apis(&self, apis: &mut ApiRegistry<B>) -> Result<(), Error>;
+}
+
+/// Processes requests before they are dispatched to their [`Handler`].
+///
+/// Middleware can inspect or modify a request before passing it to the next
+/// middleware in the chain using [`Next::run()`], inspect or modify the
+/// response returned, or return a response without invoking the remaining
+/// middleware or the request's handler. Middleware is registered using
+/// [`ServerConfiguration::with_middleware()`](crate::ServerConfiguration::with_middleware),
+/// and is invoked for every request, including each request in a batch.
+#[async_trait]
+pub trait Middleware<B: Backend = NoBackend>: Debug + Send + Sync + 'static {
+    /// Processes `request`. To continue processing the request, return the
+    /// result of `next.run(request)`.
+    async fn handle(&self, request: ApiRequest<'_, B>, next: Next<'_, B>) -> Result<Bytes, Error>;
+}
+
+/// A request being processed by a [`Middleware`].
+pub struct ApiRequest<'a, B: Backend = NoBackend> {
+    /// The session the request is being handled for.
+    pub session: HandlerSession<'a, B>,
+    /// The name of the requested [`Api`].
+    pub name: ApiName,
+    /// The serialized request.
+    pub request: Bytes,
+}
+
+impl<'a, B: Backend> ApiRequest<'a, B> {
+    /// Returns true if this is a request for `Api`.
+    #[must_use]
+    pub fn is<Api: api::Api>(&self) -> bool {
+        self.name == Api::name()
+    }
+
+    /// Deserializes this request if it is a request for `Api`.
+    pub fn decode<Api: api::Api>(&self) -> Result<Option<Api>, Error> {
+        if self.is::<Api>() {
+            Ok(Some(pot::from_slice(&self.request)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Replaces this request with `request`.
+    pub fn replace<Api: api::Api>(&mut self, request: &Api) -> Result<(), Error> {
+        self.name = Api::name();
+        self.request = Bytes::from(pot::to_vec(request)?);
+        Ok(())
+    }
+}
+
+/// The remaining [`Middleware`] that process a request before it is
+/// dispatched to its [`Handler`].
+pub struct Next<'a, B: Backend = NoBackend> {
+    middleware: &'a [Arc<dyn Middleware<B>>],
+}
+
+impl<'a, B: Backend> Next<'a, B> {
+    pub(crate) const fn new(middleware: &'a [Arc<dyn Middleware<B>>]) -> Self {
+        Self { middleware }
+    }
+
+    /// Passes `request` to the next middleware, or to the request's
+    /// [`Handler`] if no middleware remains, and returns the serialized
+    /// response.
+    pub async fn run(self, request: ApiRequest<'_, B>) -> Result<Bytes, Error> {
+        match self.middleware.split_first() {
+            Some((middleware, remaining)) => middleware.handle(request, Next::new(remaining)).await,
+            None => {
+                ServerDispatcher::dispatch_to_handler(
+                    request.session,
+                    &request.name,
+                    &request.request,
+                )
+                .await
+            }
+        }
+    }
+}
+
+/// Serializes `response` as the response to an `Api` request. This can be
+/// used by a [`Middleware`] to respond to a request without invoking its
+/// [`Handler`].
+pub fn encode_response<Api: api::Api>(
+    response: &Result<Api::Response, Api::Error>,
+) -> Result<Bytes, Error> {
+    Ok(Bytes::from(pot::to_vec(response)?))
+}
+
 #[async_trait]
 pub(crate) trait AnyHandler<B: Backend>: Send + Sync + Debug {
     async fn handle(&self, session: HandlerSession<'_, B>, request: &[u8]) -> Result<Bytes, Error>;
@@ -66,7 +307,75 @@ where
             Err(HandlerError::Api(err)) => Err(err),
             Err(HandlerError::Server(err)) => return Err(err),
         };
-        Ok(Bytes::from(pot::to_vec(&response)?))
+        encode_response::<A>(&response)
+    }
+}
+
+#[async_trait]
+pub(crate) trait AnyNamedQuery<B: Backend>: Send + Sync + Debug {
+    async fn execute(
+        &self,
+        database: ServerDatabase<B>,
+        parameters: &[u8],
+    ) -> Result<Bytes, bonsaidb_core::Error>;
+}
+
+struct NamedQueryWrapper<D: NamedQueryHandler<B, Q>, B: Backend, Q: NamedQuery>(
+    PhantomData<(D, B, Q)>,
+);
+
+impl<D, B, Q> Debug for NamedQueryWrapper<D, B, Q>
+where
+    D: NamedQueryHandler<B, Q>,
+    B: Backend,
+    Q: NamedQuery,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("NamedQueryWrapper")
+            .field(&Q::name())
+            .finish()
+    }
+}
+
+#[async_trait]
+impl<D, B, Q> AnyNamedQuery<B> for NamedQueryWrapper<D, B, Q>
+where
+    D: NamedQueryHandler<B, Q>,
+    B: Backend,
+    Q: NamedQuery,
+{
+    async fn execute(
+        &self,
+        database: ServerDatabase<B>,
+        parameters: &[u8],
+    ) -> Result<Bytes, bonsaidb_core::Error> {
+        let parameters = pot::from_slice(parameters)?;
+        let output = D::execute(database, parameters).await?;
+        Ok(Bytes::from(pot::to_vec(&output)?))
+    }
+}
+
+type PermissionCheck<B> =
+    Box<dyn for<'a> Fn(&HandlerSession<'a, B>) -> Result<(), bonsaidb_core::Error> + Send + Sync>;
+
+struct PermissionedHandler<B: Backend> {
+    check: PermissionCheck<B>,
+    handler: Arc<dyn AnyHandler<B>>,
+}
+
+impl<B: Backend> Debug for PermissionedHandler<B> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PermissionedHandler")
+            .field("handler", &self.handler)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl<B: Backend> AnyHandler<B> for PermissionedHandler<B> {
+    async fn handle(&self, session: HandlerSession<'_, B>, request: &[u8]) -> Result<Bytes, Error> {
+        (self.check)(&session)?;
+        self.handler.handle(session, request).await
     }
 }
 
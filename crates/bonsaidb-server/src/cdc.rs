@@ -0,0 +1,167 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use bonsaidb_core::arc_bytes::serde::Bytes;
+use bonsaidb_core::document::DocumentId;
+use bonsaidb_core::schema::{Collection, CollectionName};
+
+#[cfg(feature = "cdc-kafka")]
+mod kafka;
+#[cfg(feature = "cdc-nats")]
+mod nats;
+#[cfg(feature = "cdc-search")]
+mod search;
+
+#[cfg(feature = "cdc-kafka")]
+pub use self::kafka::KafkaSink;
+#[cfg(feature = "cdc-nats")]
+pub use self::nats::NatsSink;
+#[cfg(feature = "cdc-search")]
+pub use self::search::{SearchEngine, SearchSink};
+
+/// A destination that document changes are exported to by a
+/// [`ChangeDataCapture`] exporter.
+#[async_trait]
+pub trait ChangeSink: Debug + Send + Sync + 'static {
+    /// Publishes the changes made by a single transaction.
+    ///
+    /// This function should only return once every change has been durably
+    /// accepted by the destination. If an error is returned, the same event
+    /// will be published again after a delay. Because an event may be
+    /// published more than once, consumers should be able to handle duplicate
+    /// events.
+    async fn publish(&self, event: &ChangeEvent) -> Result<(), bonsaidb_core::Error>;
+}
+
+/// The document changes made by a single transaction.
+#[derive(Clone, Debug)]
+pub struct ChangeEvent {
+    /// The name of the database that was changed.
+    pub database: String,
+    /// The id of the transaction that made the changes.
+    pub transaction_id: u64,
+    /// The changed documents.
+    pub changes: Vec<DocumentChange>,
+}
+
+/// A single document change within a [`ChangeEvent`].
+#[derive(Clone, Debug)]
+pub struct DocumentChange {
+    /// The collection containing the document.
+    pub collection: CollectionName,
+    /// The id of the document.
+    pub id: DocumentId,
+    /// True if the document was deleted by this transaction.
+    pub deleted: bool,
+    /// The document's serialized contents at the time the change was
+    /// exported. This is None if the document has been deleted, and may be
+    /// newer than the contents written by this transaction.
+    pub contents: Option<Bytes>,
+}
+
+/// Exports the document changes of a database to a [`ChangeSink`].
+///
+/// The exporter follows the database's transaction log, publishing each
+/// transaction that changed matching documents in the order the transactions
+/// were executed. The id of the next transaction to export is stored in the
+/// admin database's key-value store after each transaction is published,
+/// which allows the exporter to resume where it left off after a restart.
+/// Delivery is at-least-once: a transaction whose publication failed, or
+/// whose cursor wasn't persisted before the server stopped, is published
+/// again.
+///
+/// Exporters are registered using
+/// [`ServerConfiguration::change_data_capture()`](crate::ServerConfiguration::change_data_capture).
+#[derive(Clone, Debug)]
+#[must_use]
+#[non_exhaustive]
+pub struct ChangeDataCapture {
+    /// The unique name of this exporter, which is used to store its position
+    /// in the transaction log.
+    pub name: String,
+    /// The name of the database whose changes are exported.
+    pub database: String,
+    /// If set, only changes to documents in these collections are exported.
+    pub collections: Option<Vec<CollectionName>>,
+    /// The destination of the exported changes.
+    pub sink: Arc<dyn ChangeSink>,
+    /// If true, the first time this exporter runs it begins at the start of
+    /// the transaction log. Otherwise, only changes made after the exporter
+    /// first runs are exported. Default value is false.
+    pub from_beginning: bool,
+    /// How often the transaction log is checked for new transactions once the
+    /// exporter has caught up. Default value is 1 second.
+    pub poll_interval: Duration,
+    /// The maximum number of transactions read from the log at once. Default
+    /// value is 100.
+    pub batch_size: u32,
+    /// The longest time to wait between attempts to publish a transaction.
+    /// The delay starts at 1 second and doubles after each failure. Default
+    /// value is 1 minute.
+    pub max_retry_delay: Duration,
+}
+
+impl ChangeDataCapture {
+    /// Returns an exporter named `name` that publishes every document change
+    /// in `database` to `sink`.
+    pub fn new<S: ChangeSink>(
+        name: impl Into<String>,
+        database: impl Into<String>,
+        sink: S,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            database: database.into(),
+            collections: None,
+            sink: Arc::new(sink),
+            from_beginning: false,
+            poll_interval: Duration::from_secs(1),
+            batch_size: 100,
+            max_retry_delay: Duration::from_secs(60),
+        }
+    }
+
+    /// Adds `C` to the collections whose changes are exported, and returns
+    /// self. If no collections are added, changes to all collections are
+    /// exported.
+    pub fn with_collection<C: Collection>(mut self) -> Self {
+        self.collections
+            .get_or_insert_with(Vec::new)
+            .push(C::collection_name());
+        self
+    }
+
+    /// Sets [`Self::from_beginning`](Self#structfield.from_beginning) to `from_beginning` and returns self.
+    pub const fn from_beginning(mut self, from_beginning: bool) -> Self {
+        self.from_beginning = from_beginning;
+        self
+    }
+
+    /// Sets [`Self::poll_interval`](Self#structfield.poll_interval) to `interval` and returns self.
+    pub const fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Sets [`Self::batch_size`](Self#structfield.batch_size) to `batch_size` and returns self.
+    pub const fn batch_size(mut self, batch_size: u32) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Sets [`Self::max_retry_delay`](Self#structfield.max_retry_delay) to `delay` and returns self.
+    pub const fn max_retry_delay(mut self, delay: Duration) -> Self {
+        self.max_retry_delay = delay;
+        self
+    }
+
+    /// Returns true if changes to documents in `collection` are exported.
+    #[must_use]
+    pub fn exports(&self, collection: &CollectionName) -> bool {
+        self.collections
+            .as_ref()
+            .map_or(true, |collections| collections.contains(collection))
+    }
+}
@@ -0,0 +1,100 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use async_trait::async_trait;
+use rskafka::client::partition::{Compression, PartitionClient, UnknownTopicHandling};
+use rskafka::client::ClientBuilder;
+use rskafka::record::Record;
+
+use super::{ChangeEvent, ChangeSink};
+
+/// A [`ChangeSink`] that produces changes to a partition of a Kafka topic.
+///
+/// Each changed document is produced as a separate record, keyed by
+/// `{collection}/{id}`, with the document's contents as the value. Deleted
+/// documents are produced with no value, which allows the topic to use log
+/// compaction. The record headers contain:
+///
+/// - `bonsaidb-database`: The name of the changed database.
+/// - `bonsaidb-collection`: The name of the document's collection.
+/// - `bonsaidb-document-id`: The document's id.
+/// - `bonsaidb-transaction-id`: The id of the transaction that changed the
+///   document.
+///
+/// All changes are produced to a single partition to preserve the order the
+/// transactions were executed in.
+pub struct KafkaSink {
+    topic: String,
+    partition: i32,
+    client: PartitionClient,
+}
+
+impl KafkaSink {
+    /// Connects to the Kafka cluster at `bootstrap_brokers` and returns a
+    /// sink that produces changes to `partition` of `topic`.
+    pub async fn connect(
+        bootstrap_brokers: Vec<String>,
+        topic: impl Into<String>,
+        partition: i32,
+    ) -> Result<Self, bonsaidb_core::Error> {
+        let topic = topic.into();
+        let client = ClientBuilder::new(bootstrap_brokers)
+            .build()
+            .await
+            .map_err(|err| bonsaidb_core::Error::other("cdc-kafka", err))?
+            .partition_client(topic.clone(), partition, UnknownTopicHandling::Retry)
+            .await
+            .map_err(|err| bonsaidb_core::Error::other("cdc-kafka", err))?;
+        Ok(Self {
+            topic,
+            partition,
+            client,
+        })
+    }
+}
+
+impl Debug for KafkaSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KafkaSink")
+            .field("topic", &self.topic)
+            .field("partition", &self.partition)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl ChangeSink for KafkaSink {
+    async fn publish(&self, event: &ChangeEvent) -> Result<(), bonsaidb_core::Error> {
+        let timestamp = chrono::Utc::now();
+        let records = event
+            .changes
+            .iter()
+            .map(|change| {
+                let collection = change.collection.to_string();
+                let id = change.id.to_string();
+                Record {
+                    key: Some(format!("{collection}/{id}").into_bytes()),
+                    value: change.contents.as_ref().map(|contents| contents.to_vec()),
+                    headers: BTreeMap::from([
+                        (
+                            String::from("bonsaidb-database"),
+                            event.database.as_bytes().to_vec(),
+                        ),
+                        (String::from("bonsaidb-collection"), collection.into_bytes()),
+                        (String::from("bonsaidb-document-id"), id.into_bytes()),
+                        (
+                            String::from("bonsaidb-transaction-id"),
+                            event.transaction_id.to_string().into_bytes(),
+                        ),
+                    ]),
+                    timestamp,
+                }
+            })
+            .collect();
+        self.client
+            .produce(records, Compression::NoCompression)
+            .await
+            .map_err(|err| bonsaidb_core::Error::other("cdc-kafka", err))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+use std::fmt::Debug;
+
+use async_nats::jetstream::{self, Context};
+use async_nats::HeaderMap;
+use async_trait::async_trait;
+
+use super::{ChangeEvent, ChangeSink};
+
+/// A [`ChangeSink`] that publishes changes to a NATS JetStream stream.
+///
+/// Each changed document is published as a separate message to the subject
+/// `{subject_prefix}.{database}.{collection}`, with the document's contents
+/// as the payload. Deleted documents are published with an empty payload.
+/// The message headers contain:
+///
+/// - `BonsaiDb-Document-Id`: The document's id.
+/// - `BonsaiDb-Transaction-Id`: The id of the transaction that changed the
+///   document.
+/// - `BonsaiDb-Deleted`: `true` if the document was deleted, otherwise
+///   `false`.
+/// - `Nats-Msg-Id`: A unique id for the change, which allows JetStream to
+///   discard duplicate publications.
+///
+/// A stream capturing the published subjects must exist before changes are
+/// published. Each message is published once JetStream has acknowledged it.
+pub struct NatsSink {
+    context: Context,
+    subject_prefix: String,
+}
+
+impl NatsSink {
+    /// Returns a sink that publishes changes using `client` to subjects
+    /// beginning with `subject_prefix`.
+    pub fn new(client: async_nats::Client, subject_prefix: impl Into<String>) -> Self {
+        Self {
+            context: jetstream::new(client),
+            subject_prefix: subject_prefix.into(),
+        }
+    }
+}
+
+impl Debug for NatsSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NatsSink")
+            .field("subject_prefix", &self.subject_prefix)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl ChangeSink for NatsSink {
+    async fn publish(&self, event: &ChangeEvent) -> Result<(), bonsaidb_core::Error> {
+        let mut acks = Vec::with_capacity(event.changes.len());
+        for change in &event.changes {
+            let id = change.id.to_string();
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                "Nats-Msg-Id",
+                format!(
+                    "{}.{}.{}.{id}",
+                    event.database, event.transaction_id, change.collection
+                )
+                .as_str(),
+            );
+            headers.insert("BonsaiDb-Document-Id", id.as_str());
+            headers.insert(
+                "BonsaiDb-Transaction-Id",
+                event.transaction_id.to_string().as_str(),
+            );
+            headers.insert(
+                "BonsaiDb-Deleted",
+                if change.deleted { "true" } else { "false" },
+            );
+            let payload = change
+                .contents
+                .as_ref()
+                .map(|contents| contents.to_vec())
+                .unwrap_or_default();
+            let ack = self
+                .context
+                .publish_with_headers(
+                    format!(
+                        "{}.{}.{}",
+                        self.subject_prefix, event.database, change.collection
+                    ),
+                    headers,
+                    payload.into(),
+                )
+                .await
+                .map_err(|err| bonsaidb_core::Error::other("cdc-nats", err))?;
+            acks.push(ack);
+        }
+
+        for ack in acks {
+            ack.await
+                .map_err(|err| bonsaidb_core::Error::other("cdc-nats", err))?;
+        }
+
+        Ok(())
+    }
+}
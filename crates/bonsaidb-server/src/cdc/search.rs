@@ -0,0 +1,418 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+use async_trait::async_trait;
+use bonsaidb_core::connection::{AsyncLowLevelConnection, Range, Sort};
+use bonsaidb_core::document::DocumentId;
+use bonsaidb_core::schema::{CollectionName, SerializedCollection};
+use hyper::client::HttpConnector;
+use hyper::{Body, Client, Method, Request, StatusCode};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use super::{ChangeDataCapture, ChangeEvent, ChangeSink};
+
+/// The number of documents indexed in each request during
+/// [`SearchSink::resync()`].
+const RESYNC_BATCH_SIZE: u32 = 1_000;
+
+/// A search engine that a [`SearchSink`] mirrors documents into.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum SearchEngine {
+    /// A [Meilisearch](https://www.meilisearch.com/) instance.
+    Meilisearch {
+        /// The base `http` URL of the instance.
+        url: String,
+        /// The API key sent as a bearer token, if the instance requires one.
+        api_key: Option<String>,
+    },
+    /// An [Elasticsearch](https://www.elastic.co/elasticsearch/) cluster.
+    Elasticsearch {
+        /// The base `http` URL of the cluster.
+        url: String,
+        /// The encoded API key sent in the `Authorization` header, if the
+        /// cluster requires one.
+        api_key: Option<String>,
+    },
+}
+
+/// A [`ChangeSink`] that mirrors the documents of selected collections into a
+/// [`SearchEngine`].
+///
+/// Each collection is mirrored into its own index. When a document is
+/// inserted or updated, its contents are converted to JSON and stored in the
+/// index, replacing any previous version. When a document is deleted, it is
+/// removed from the index. Each indexed document contains a field named
+/// [`id_field`](Self::id_field), which contains the hex-encoded bytes of the
+/// document's id.
+///
+/// To mirror changes as they happen, register the exporter returned by
+/// [`Self::change_data_capture()`] with the server. To rebuild an index from
+/// the documents currently stored, use [`Self::resync()`].
+#[derive(Clone)]
+#[must_use]
+pub struct SearchSink {
+    engine: SearchEngine,
+    client: Client<HttpConnector>,
+    id_field: String,
+    collections: HashMap<CollectionName, SearchCollection>,
+}
+
+#[derive(Clone)]
+struct SearchCollection {
+    index: String,
+    to_json: fn(&[u8]) -> Result<Value, bonsaidb_core::Error>,
+}
+
+enum SearchOperation {
+    Index {
+        index: String,
+        id: String,
+        document: Value,
+    },
+    Delete {
+        index: String,
+        id: String,
+    },
+}
+
+impl SearchSink {
+    /// Returns a sink that mirrors documents into `engine`. Collections are
+    /// added using [`Self::with_collection()`].
+    pub fn new(engine: SearchEngine) -> Self {
+        Self {
+            engine,
+            client: Client::new(),
+            id_field: String::from("bonsaidb_id"),
+            collections: HashMap::new(),
+        }
+    }
+
+    /// Sets the name of the field that stores each document's id and returns
+    /// self. The default field name is `bonsaidb_id`.
+    pub fn id_field(mut self, id_field: impl Into<String>) -> Self {
+        self.id_field = id_field.into();
+        self
+    }
+
+    /// Mirrors the documents in `C` into the index named `index`, and returns
+    /// self. Documents are converted to JSON by deserializing them as
+    /// `C::Contents`.
+    pub fn with_collection<C>(mut self, index: impl Into<String>) -> Self
+    where
+        C: SerializedCollection,
+        C::Contents: Serialize,
+    {
+        self.collections.insert(
+            C::collection_name(),
+            SearchCollection {
+                index: index.into(),
+                to_json: contents_to_json::<C>,
+            },
+        );
+        self
+    }
+
+    /// Returns an exporter named `name` that mirrors the changes to this
+    /// sink's collections in `database`.
+    pub fn change_data_capture(
+        &self,
+        name: impl Into<String>,
+        database: impl Into<String>,
+    ) -> ChangeDataCapture {
+        let mut exporter = ChangeDataCapture::new(name, database, self.clone());
+        exporter.collections = Some(self.collections.keys().cloned().collect());
+        exporter
+    }
+
+    /// Removes every document from this sink's indexes, and then indexes
+    /// every document in this sink's collections in `database`. Returns the
+    /// number of documents indexed.
+    ///
+    /// Searches performed while resyncing may return incomplete results.
+    pub async fn resync<Database: AsyncLowLevelConnection>(
+        &self,
+        database: &Database,
+    ) -> Result<usize, bonsaidb_core::Error> {
+        let mut indexed = 0;
+        for (collection, search) in &self.collections {
+            self.clear_index(&search.index).await?;
+
+            let mut ids = Range::from(..);
+            loop {
+                let documents = database
+                    .list_from_collection(
+                        ids.clone(),
+                        Sort::Ascending,
+                        Some(RESYNC_BATCH_SIZE),
+                        collection,
+                    )
+                    .await?;
+                let Some(last) = documents.last() else {
+                    break;
+                };
+                ids = Range::from(..).after(last.header.id.clone());
+
+                let operations = documents
+                    .iter()
+                    .map(|document| {
+                        Ok(SearchOperation::Index {
+                            index: search.index.clone(),
+                            id: search_id(&document.header.id),
+                            document: self.search_document(
+                                (search.to_json)(&document.contents)?,
+                                &document.header.id,
+                            ),
+                        })
+                    })
+                    .collect::<Result<Vec<_>, bonsaidb_core::Error>>()?;
+                indexed += operations.len();
+                let finished = operations.len() < RESYNC_BATCH_SIZE as usize;
+                self.apply(operations).await?;
+                if finished {
+                    break;
+                }
+            }
+        }
+
+        Ok(indexed)
+    }
+
+    fn search_document(&self, contents: Value, id: &DocumentId) -> Value {
+        let mut document = match contents {
+            Value::Object(object) => object,
+            other => {
+                let mut object = serde_json::Map::new();
+                object.insert(String::from("value"), other);
+                object
+            }
+        };
+        document.insert(self.id_field.clone(), Value::String(search_id(id)));
+        Value::Object(document)
+    }
+
+    async fn clear_index(&self, index: &str) -> Result<(), bonsaidb_core::Error> {
+        match &self.engine {
+            SearchEngine::Meilisearch { .. } => {
+                self.request(Method::DELETE, &format!("indexes/{index}/documents"), None)
+                    .await?;
+            }
+            SearchEngine::Elasticsearch { .. } => {
+                let body = serde_json::to_vec(&json!({ "query": { "match_all": {} } }))
+                    .map_err(search_error)?;
+                match self
+                    .request(
+                        Method::POST,
+                        &format!("{index}/_delete_by_query"),
+                        Some(("application/json", body)),
+                    )
+                    .await
+                {
+                    // The index doesn't exist yet.
+                    Err(RequestError::Status(StatusCode::NOT_FOUND)) => {}
+                    other => {
+                        other?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn apply(&self, operations: Vec<SearchOperation>) -> Result<(), bonsaidb_core::Error> {
+        if operations.is_empty() {
+            return Ok(());
+        }
+
+        match &self.engine {
+            SearchEngine::Meilisearch { .. } => {
+                let mut upserts = HashMap::<String, Vec<Value>>::new();
+                let mut deletes = HashMap::<String, Vec<String>>::new();
+                for operation in operations {
+                    match operation {
+                        SearchOperation::Index {
+                            index, document, ..
+                        } => upserts.entry(index).or_default().push(document),
+                        SearchOperation::Delete { index, id } => {
+                            deletes.entry(index).or_default().push(id);
+                        }
+                    }
+                }
+                for (index, documents) in upserts {
+                    let body = serde_json::to_vec(&documents).map_err(search_error)?;
+                    self.request(
+                        Method::POST,
+                        &format!("indexes/{index}/documents?primaryKey={}", self.id_field),
+                        Some(("application/json", body)),
+                    )
+                    .await?;
+                }
+                for (index, ids) in deletes {
+                    let body = serde_json::to_vec(&ids).map_err(search_error)?;
+                    self.request(
+                        Method::POST,
+                        &format!("indexes/{index}/documents/delete-batch"),
+                        Some(("application/json", body)),
+                    )
+                    .await?;
+                }
+            }
+            SearchEngine::Elasticsearch { .. } => {
+                let mut body = Vec::new();
+                for operation in operations {
+                    let lines = match operation {
+                        SearchOperation::Index {
+                            index,
+                            id,
+                            document,
+                        } => vec![json!({ "index": { "_index": index, "_id": id } }), document],
+                        SearchOperation::Delete { index, id } => {
+                            vec![json!({ "delete": { "_index": index, "_id": id } })]
+                        }
+                    };
+                    for line in lines {
+                        serde_json::to_writer(&mut body, &line).map_err(search_error)?;
+                        body.push(b'\n');
+                    }
+                }
+                let response = self
+                    .request(Method::POST, "_bulk", Some(("application/x-ndjson", body)))
+                    .await?;
+                let response = serde_json::from_slice::<Value>(&response).map_err(search_error)?;
+                if response.get("errors").and_then(Value::as_bool) == Some(true) {
+                    return Err(bonsaidb_core::Error::other(
+                        "cdc-search",
+                        format!("bulk request failed: {response}"),
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn request(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<(&str, Vec<u8>)>,
+    ) -> Result<Vec<u8>, RequestError> {
+        let (url, authorization) = match &self.engine {
+            SearchEngine::Meilisearch { url, api_key } => {
+                (url, api_key.as_ref().map(|key| format!("Bearer {key}")))
+            }
+            SearchEngine::Elasticsearch { url, api_key } => {
+                (url, api_key.as_ref().map(|key| format!("ApiKey {key}")))
+            }
+        };
+        let mut request = Request::builder()
+            .method(method)
+            .uri(format!("{}/{path}", url.trim_end_matches('/')));
+        if let Some(authorization) = authorization {
+            request = request.header("authorization", authorization);
+        }
+        let request = match body {
+            Some((content_type, body)) => request
+                .header("content-type", content_type)
+                .body(Body::from(body)),
+            None => request.body(Body::empty()),
+        }
+        .map_err(search_error)?;
+
+        let response = self.client.request(request).await.map_err(search_error)?;
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body())
+            .await
+            .map_err(search_error)?;
+        if status.is_success() {
+            Ok(body.to_vec())
+        } else {
+            log::warn!(
+                "Search engine request failed with status {status}: {}",
+                String::from_utf8_lossy(&body)
+            );
+            Err(RequestError::Status(status))
+        }
+    }
+}
+
+impl Debug for SearchSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SearchSink")
+            .field("engine", &self.engine)
+            .field("id_field", &self.id_field)
+            .field("collections", &self.collections.keys())
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl ChangeSink for SearchSink {
+    async fn publish(&self, event: &ChangeEvent) -> Result<(), bonsaidb_core::Error> {
+        let mut operations = Vec::with_capacity(event.changes.len());
+        for change in &event.changes {
+            let Some(search) = self.collections.get(&change.collection) else {
+                continue;
+            };
+            let id = search_id(&change.id);
+            // Documents without contents have been deleted, either by this
+            // transaction or a later one.
+            operations.push(match &change.contents {
+                Some(contents) => SearchOperation::Index {
+                    index: search.index.clone(),
+                    id,
+                    document: self.search_document((search.to_json)(contents)?, &change.id),
+                },
+                None => SearchOperation::Delete {
+                    index: search.index.clone(),
+                    id,
+                },
+            });
+        }
+
+        self.apply(operations).await
+    }
+}
+
+enum RequestError {
+    Status(StatusCode),
+    Other(bonsaidb_core::Error),
+}
+
+impl From<RequestError> for bonsaidb_core::Error {
+    fn from(err: RequestError) -> Self {
+        match err {
+            RequestError::Status(status) => Self::other(
+                "cdc-search",
+                format!("search engine responded with status {status}"),
+            ),
+            RequestError::Other(err) => err,
+        }
+    }
+}
+
+impl From<bonsaidb_core::Error> for RequestError {
+    fn from(err: bonsaidb_core::Error) -> Self {
+        Self::Other(err)
+    }
+}
+
+fn search_error(err: impl std::fmt::Display) -> bonsaidb_core::Error {
+    bonsaidb_core::Error::other("cdc-search", err)
+}
+
+fn contents_to_json<C>(contents: &[u8]) -> Result<Value, bonsaidb_core::Error>
+where
+    C: SerializedCollection,
+    C::Contents: Serialize,
+{
+    let contents = C::deserialize(contents)?;
+    serde_json::to_value(&contents).map_err(search_error)
+}
+
+/// Returns the hex-encoded bytes of `id`, which only contains characters that
+/// every supported search engine allows in document ids.
+fn search_id(id: &DocumentId) -> String {
+    id.iter().map(|byte| format!("{byte:02x}")).collect()
+}
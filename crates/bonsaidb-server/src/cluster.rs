@@ -0,0 +1,194 @@
+use std::time::Duration;
+
+use fabruic::Certificate;
+use url::Url;
+
+/// Configuration for running a server as a node in a cluster.
+///
+/// Each node in a cluster is configured with the same members and shared
+/// secret. The nodes elect a leader, which is the primary of every clustered
+/// database. Each election begins a new term, and a node votes for at most one
+/// candidate per term. A candidate must receive votes from a majority of the
+/// cluster, and only nodes whose log contains every committed transaction
+/// can receive a vote.
+///
+/// Transactions applied to a node that isn't the leader are forwarded to the
+/// leader, which appends them to its replicated log. A transaction is
+/// committed once a majority of the cluster has stored it. Only then is it
+/// applied to the database on each node, in the same order on every node. If
+/// the leader stops responding, the other nodes elect a new leader in a later
+/// term. A leader that can't reach a majority of the cluster steps down. A
+/// leader from an earlier term can't commit transactions, because the
+/// majority has moved on to a later term. This prevents both sides of a
+/// network partition from accepting writes.
+///
+/// The leader is the primary of every clustered database, rather than each
+/// database electing its own primary. Every clustered database shares one
+/// replicated log, which orders transactions and database deletions across
+/// databases the same way on every node, and lets a single election and a
+/// single heartbeat per member cover every database. Per-database primaries
+/// would require a separate log, election, and heartbeats for each database.
+///
+/// Document changes and database deletions are clustered. The admin database
+/// and the key-value store are local to each node. The key-value store
+/// persists changes in the background, so its operations can't be applied
+/// exactly once when the log is replayed after a crash.
+///
+/// The replicated log is stored in the admin database. Once
+/// [`snapshot_interval`](Self#structfield.snapshot_interval) entries have been
+/// applied, they are removed from the log. A member that is missing removed
+/// entries is sent a snapshot of the leader's clustered databases instead,
+/// which replaces the member's clustered databases.
+///
+/// Clustering is enabled using
+/// [`ServerConfiguration::cluster()`](crate::ServerConfiguration::cluster).
+#[derive(Clone, Debug)]
+#[must_use]
+#[non_exhaustive]
+pub struct ClusterConfiguration {
+    /// The unique id of this node.
+    pub node_id: u64,
+    /// The other nodes in the cluster.
+    pub members: Vec<ClusterMember>,
+    /// The secret shared by every node in the cluster. Requests between nodes
+    /// that do not present this secret are rejected.
+    pub secret: String,
+    /// How often the leader sends heartbeats to the other members. Heartbeats
+    /// also replicate the leader's log. Default value is 1 second.
+    pub heartbeat_interval: Duration,
+    /// How long a member can go without a successful heartbeat before it is
+    /// considered unreachable. A member that hasn't heard from the leader for
+    /// a random duration between one and two times this timeout starts an
+    /// election. This must be more than twice the heartbeat interval. Default
+    /// value is 5 seconds.
+    pub failure_timeout: Duration,
+    /// The maximum number of log entries or snapshot documents sent to a
+    /// member at once. Default value is 100.
+    pub replication_batch_size: u32,
+    /// The number of applied log entries that accumulate before they are
+    /// removed from the log. Default value is 10,000.
+    pub snapshot_interval: u64,
+    /// The amount of time a connection to another member may be idle before
+    /// it is closed. A connection is idle when neither side has sent anything.
+    /// If `None`, idle connections are kept open. Default value is `None`.
+    ///
+    /// The effective timeout of a connection is the lower of this value and
+    /// the [`idle_timeout`](crate::BonsaiListenConfig::idle_timeout) of the
+    /// member's listener.
+    pub idle_timeout: Option<Duration>,
+}
+
+impl ClusterConfiguration {
+    /// Returns a configuration for the node `node_id` in a cluster whose nodes
+    /// authenticate each other using `secret`.
+    pub fn new(node_id: u64, secret: impl Into<String>) -> Self {
+        Self {
+            node_id,
+            members: Vec::new(),
+            secret: secret.into(),
+            heartbeat_interval: Duration::from_secs(1),
+            failure_timeout: Duration::from_secs(5),
+            replication_batch_size: 100,
+            snapshot_interval: 10_000,
+            idle_timeout: None,
+        }
+    }
+
+    /// Adds `member` to [`Self::members`](Self#structfield.members) and returns self.
+    pub fn with_member(mut self, member: ClusterMember) -> Self {
+        self.members.push(member);
+        self
+    }
+
+    /// Sets [`Self::heartbeat_interval`](Self#structfield.heartbeat_interval) to `interval` and returns self.
+    pub const fn heartbeat_interval(mut self, interval: Duration) -> Self {
+        self.heartbeat_interval = interval;
+        self
+    }
+
+    /// Sets [`Self::failure_timeout`](Self#structfield.failure_timeout) to `timeout` and returns self.
+    pub const fn failure_timeout(mut self, timeout: Duration) -> Self {
+        self.failure_timeout = timeout;
+        self
+    }
+
+    /// Sets [`Self::replication_batch_size`](Self#structfield.replication_batch_size) to `batch_size` and returns self.
+    pub const fn replication_batch_size(mut self, batch_size: u32) -> Self {
+        self.replication_batch_size = batch_size;
+        self
+    }
+
+    /// Sets [`Self::snapshot_interval`](Self#structfield.snapshot_interval) to `interval` and returns self.
+    pub const fn snapshot_interval(mut self, interval: u64) -> Self {
+        self.snapshot_interval = interval;
+        self
+    }
+
+    /// Sets [`Self::idle_timeout`](Self#structfield.idle_timeout) to `timeout` and returns self.
+    pub const fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+}
+
+/// Another node in a cluster.
+#[derive(Clone, Debug)]
+#[must_use]
+#[non_exhaustive]
+pub struct ClusterMember {
+    /// The unique id of the node.
+    pub id: u64,
+    /// The url of the node's BonsaiDb protocol endpoint, such as
+    /// `bonsaidb://node-2.example.com`.
+    pub url: Url,
+    /// The node's pinned certificate. If None, the node's certificate is
+    /// verified using the system's trusted certificate authorities.
+    pub certificate: Option<Certificate>,
+}
+
+impl ClusterMember {
+    /// Returns a member with id `id` that is reachable at `url`.
+    pub const fn new(id: u64, url: Url) -> Self {
+        Self {
+            id,
+            url,
+            certificate: None,
+        }
+    }
+
+    /// Sets [`Self::certificate`](Self#structfield.certificate) to `certificate` and returns self.
+    pub fn with_certificate(mut self, certificate: Certificate) -> Self {
+        self.certificate = Some(certificate);
+        self
+    }
+}
+
+/// The state of a cluster as observed by one of its nodes, as returned from
+/// [`CustomServer::cluster_status()`](crate::CustomServer::cluster_status).
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct ClusterStatus {
+    /// The id of the node that observed this status.
+    pub node_id: u64,
+    /// The latest election term this node has seen.
+    pub term: u64,
+    /// The id of the leader this node recognizes, if any. The leader is the
+    /// primary of every clustered database.
+    pub leader: Option<u64>,
+    /// The status of each of the other members.
+    pub members: Vec<MemberStatus>,
+    /// True if this node recognizes a leader that can reach a majority of the
+    /// cluster. Writes are only accepted while this is true.
+    pub has_quorum: bool,
+}
+
+/// The status of a member of a cluster.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct MemberStatus {
+    /// The id of the member.
+    pub id: u64,
+    /// True if a request was exchanged with this member within the configured
+    /// failure timeout.
+    pub reachable: bool,
+}
@@ -1,22 +1,34 @@
-use std::collections::HashMap;
-use std::marker::PhantomData;
-use std::net::{Ipv6Addr, SocketAddr, SocketAddrV6};
+use std::fmt::Debug;
+use std::net::{IpAddr, Ipv6Addr, SocketAddr, SocketAddrV6};
 use std::path::Path;
+#[cfg(any(all(unix, feature = "websockets"), feature = "request-log"))]
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 use bonsaidb_core::api;
-use bonsaidb_core::api::ApiName;
+use bonsaidb_core::connection::IdentityReference;
 #[cfg(feature = "encryption")]
 use bonsaidb_core::document::KeyId;
-use bonsaidb_core::permissions::{Permissions, Statement};
-use bonsaidb_core::schema::Schema;
+use bonsaidb_core::networking::{CompressionConfig, PayloadLimits};
+use bonsaidb_core::permissions::{Action, Permissions, ResourceName, Statement};
+use bonsaidb_core::schema::{Collection, Schema, View};
 #[cfg(feature = "compression")]
 use bonsaidb_local::config::Compression;
-use bonsaidb_local::config::{Builder, KeyValuePersistence, StorageConfiguration};
+use bonsaidb_local::config::{
+    Builder, KeyValuePersistence, Lockout, Sharding, StorageConfiguration,
+};
 #[cfg(feature = "encryption")]
 use bonsaidb_local::vault::AnyVaultKeyStorage;
+use fabruic::Certificate;
 
-use crate::api::{AnyHandler, AnyWrapper, Handler};
+use crate::api::{
+    ApiPlugin, ApiRegistry, Handler, Middleware, NamedQueryHandler, NamedQueryRegistry,
+};
+#[cfg(feature = "cdc")]
+use crate::cdc::ChangeDataCapture;
+#[cfg(feature = "cluster")]
+use crate::cluster::ClusterConfiguration;
 use crate::{Backend, Error, NoBackend};
 
 /// Configuration options for [`Server`](crate::Server)
@@ -35,15 +47,95 @@ pub struct ServerConfiguration<B: Backend = NoBackend> {
     pub client_simultaneous_request_limit: usize,
     /// Number of simultaneous requests to be processed. Default value is 16.
     pub request_workers: usize,
+    /// The maximum number of clients that can be connected at the same time.
+    /// By default, the number of connections is not limited.
+    pub max_connections: Option<usize>,
+    /// The maximum number of clients that can be authenticated as the same
+    /// user at the same time. By default, the number of connections per user
+    /// is not limited.
+    pub max_connections_per_user: Option<usize>,
+    /// The limits applied to each connected client. By default, clients are
+    /// not rate limited.
+    pub client_rate_limit: RateLimit,
+    /// The limits applied to each user, across all of the clients
+    /// authenticated as the user. By default, users are not rate limited.
+    pub user_rate_limit: RateLimit,
+    /// The backoff applied to failed password authentication attempts. By
+    /// default, [`LoginThrottle::default()`] is used. When `None`, failed
+    /// attempts are not throttled.
+    pub login_throttle: Option<LoginThrottle>,
+    /// How long the results of a transaction applied with an
+    /// [`IdempotencyKey`](bonsaidb_core::transaction::IdempotencyKey) are
+    /// remembered. A transaction with the same key received within this
+    /// window returns the original results rather than being applied again.
+    /// Default value is 10 minutes.
+    pub transaction_idempotency_window: Duration,
+    /// The most transaction results remembered for
+    /// [`transaction_idempotency_window`](Self#structfield.transaction_idempotency_window).
+    /// Once reached, the oldest results are forgotten early. Default value is
+    /// 100,000.
+    pub transaction_idempotency_limit: usize,
+    /// The payload compression algorithms clients may negotiate. By default,
+    /// every algorithm enabled in this build is allowed, and responses smaller
+    /// than 1KB are not compressed.
+    pub network_compression: CompressionConfig,
+    /// The maximum size of a compressed request after it has been
+    /// decompressed. Requests that decompress to a larger size are rejected.
+    /// Default value is 64MB.
+    pub max_decompressed_request_size: usize,
+    /// The maximum sizes of the documents and transactions clients can store,
+    /// and of the responses sent to clients.
+    pub payload_limits: PayloadLimits,
     /// Configuration options for individual databases.
     pub storage: StorageConfiguration,
     /// The permissions granted to all connections to this server.
     pub default_permissions: DefaultPermissions,
+    /// The endpoints started by
+    /// [`CustomServer::listen()`](crate::CustomServer::listen). By default, no
+    /// listeners are configured.
+    pub listeners: Vec<ListenerConfiguration>,
+    /// Settings for authenticating clients connecting over TLS using client
+    /// certificates. By default, client certificates are not requested.
+    pub client_certificate_authentication: Option<ClientCertificateAuthentication>,
+    /// The addresses of the load balancers and reverse proxies that connect to
+    /// this server's TCP listeners on behalf of clients. By default, no proxies
+    /// are trusted.
+    ///
+    /// Connections from these addresses must begin with a [PROXY protocol
+    /// version 2][proxy] header, which is read before the TLS handshake. The
+    /// client address in the header is used in place of the proxy's address
+    /// for rate limiting, login throttling, and logging, and is the address
+    /// given to [`TcpService`](crate::TcpService)s. Connections from other
+    /// addresses are handled without reading a header, which prevents clients
+    /// from choosing the address they are identified by.
+    ///
+    /// [proxy]: https://www.haproxy.org/download/2.8/doc/proxy-protocol.txt
+    pub trusted_proxies: Vec<IpAddr>,
     /// The ACME settings for automatic TLS certificate management.
     #[cfg(feature = "acme")]
     pub acme: AcmeConfiguration,
+    /// The settings for delivering changes to
+    /// [webhooks](bonsaidb_core::admin::Webhook).
+    #[cfg(feature = "webhooks")]
+    pub webhooks: WebhookConfiguration,
+    /// The keepalive and idle settings of WebSocket connections.
+    #[cfg(feature = "websockets")]
+    pub websockets: WebSocketConfiguration,
+    /// The settings for logging the requests processed by this server. By
+    /// default, requests are not logged.
+    #[cfg(feature = "request-log")]
+    pub request_log: Option<RequestLogConfiguration>,
+    /// The exporters that publish document changes to external systems.
+    #[cfg(feature = "cdc")]
+    pub change_data_capture: Vec<ChangeDataCapture>,
+    /// The settings for running this server as a node in a cluster. By
+    /// default, clustering is disabled.
+    #[cfg(feature = "cluster")]
+    pub cluster: Option<ClusterConfiguration>,
 
-    pub(crate) custom_apis: HashMap<ApiName, Arc<dyn AnyHandler<B>>>,
+    pub(crate) custom_apis: ApiRegistry<B>,
+    pub(crate) named_queries: NamedQueryRegistry<B>,
+    pub(crate) middleware: Vec<Arc<dyn Middleware<B>>>,
 }
 
 impl<B: Backend> ServerConfiguration<B> {
@@ -56,11 +148,36 @@ impl<B: Backend> ServerConfiguration<B> {
             // TODO this was arbitrarily picked, it probably should be higher,
             // but it also should probably be based on the cpu's capabilities
             request_workers: 16,
+            max_connections: None,
+            max_connections_per_user: None,
+            client_rate_limit: RateLimit::default(),
+            user_rate_limit: RateLimit::default(),
+            login_throttle: Some(LoginThrottle::default()),
+            transaction_idempotency_window: Duration::from_secs(10 * 60),
+            transaction_idempotency_limit: 100_000,
+            network_compression: CompressionConfig::default(),
+            max_decompressed_request_size: 64 * 1024 * 1024,
+            payload_limits: PayloadLimits::default(),
             storage: bonsaidb_local::config::StorageConfiguration::default(),
             default_permissions: DefaultPermissions::Permissions(Permissions::default()),
-            custom_apis: HashMap::default(),
+            listeners: Vec::new(),
+            client_certificate_authentication: None,
+            trusted_proxies: Vec::new(),
+            custom_apis: ApiRegistry::default(),
+            named_queries: NamedQueryRegistry::default(),
+            middleware: Vec::new(),
             #[cfg(feature = "acme")]
             acme: AcmeConfiguration::default(),
+            #[cfg(feature = "webhooks")]
+            webhooks: WebhookConfiguration::default(),
+            #[cfg(feature = "websockets")]
+            websockets: WebSocketConfiguration::default(),
+            #[cfg(feature = "request-log")]
+            request_log: None,
+            #[cfg(feature = "cdc")]
+            change_data_capture: Vec::new(),
+            #[cfg(feature = "cluster")]
+            cluster: None,
         }
     }
 
@@ -87,6 +204,72 @@ impl<B: Backend> ServerConfiguration<B> {
         self
     }
 
+    /// Sets [`Self::max_connections`](Self#structfield.max_connections) to `connections` and returns self.
+    pub const fn max_connections(mut self, connections: usize) -> Self {
+        self.max_connections = Some(connections);
+        self
+    }
+
+    /// Sets [`Self::max_connections_per_user`](Self#structfield.max_connections_per_user) to `connections` and returns self.
+    pub const fn max_connections_per_user(mut self, connections: usize) -> Self {
+        self.max_connections_per_user = Some(connections);
+        self
+    }
+
+    /// Sets [`Self::client_rate_limit`](Self#structfield.client_rate_limit) to `limit` and returns self.
+    pub const fn client_rate_limit(mut self, limit: RateLimit) -> Self {
+        self.client_rate_limit = limit;
+        self
+    }
+
+    /// Sets [`Self::user_rate_limit`](Self#structfield.user_rate_limit) to `limit` and returns self.
+    pub const fn user_rate_limit(mut self, limit: RateLimit) -> Self {
+        self.user_rate_limit = limit;
+        self
+    }
+
+    /// Sets [`Self::login_throttle`](Self#structfield.login_throttle) to `throttle` and returns self.
+    pub const fn login_throttle(mut self, throttle: LoginThrottle) -> Self {
+        self.login_throttle = Some(throttle);
+        self
+    }
+
+    /// Sets [`Self::login_throttle`](Self#structfield.login_throttle) to `None` and returns self.
+    pub const fn without_login_throttle(mut self) -> Self {
+        self.login_throttle = None;
+        self
+    }
+
+    /// Sets [`Self::transaction_idempotency_window`](Self#structfield.transaction_idempotency_window) to `window` and returns self.
+    pub const fn transaction_idempotency_window(mut self, window: Duration) -> Self {
+        self.transaction_idempotency_window = window;
+        self
+    }
+
+    /// Sets [`Self::transaction_idempotency_limit`](Self#structfield.transaction_idempotency_limit) to `limit` and returns self.
+    pub const fn transaction_idempotency_limit(mut self, limit: usize) -> Self {
+        self.transaction_idempotency_limit = limit;
+        self
+    }
+
+    /// Sets [`Self::network_compression`](Self#structfield.network_compression) to `compression` and returns self.
+    pub fn network_compression(mut self, compression: CompressionConfig) -> Self {
+        self.network_compression = compression;
+        self
+    }
+
+    /// Sets [`Self::max_decompressed_request_size`](Self#structfield.max_decompressed_request_size) to `bytes` and returns self.
+    pub const fn max_decompressed_request_size(mut self, bytes: usize) -> Self {
+        self.max_decompressed_request_size = bytes;
+        self
+    }
+
+    /// Sets [`Self::payload_limits`](Self#structfield.payload_limits) to `limits` and returns self.
+    pub const fn payload_limits(mut self, limits: PayloadLimits) -> Self {
+        self.payload_limits = limits;
+        self
+    }
+
     /// Sets [`Self::default_permissions`](Self#structfield.default_permissions) to `default_permissions` and returns self.
     pub fn default_permissions<P: Into<DefaultPermissions>>(
         mut self,
@@ -96,6 +279,27 @@ impl<B: Backend> ServerConfiguration<B> {
         self
     }
 
+    /// Adds `listener` to [`Self::listeners`](Self#structfield.listeners) and returns self.
+    pub fn listener(mut self, listener: impl Into<ListenerConfiguration>) -> Self {
+        self.listeners.push(listener.into());
+        self
+    }
+
+    /// Sets [`Self::client_certificate_authentication`](Self#structfield.client_certificate_authentication) to `authentication` and returns self.
+    pub fn client_certificate_authentication(
+        mut self,
+        authentication: ClientCertificateAuthentication,
+    ) -> Self {
+        self.client_certificate_authentication = Some(authentication);
+        self
+    }
+
+    /// Adds `address` to [`Self::trusted_proxies`](Self#structfield.trusted_proxies) and returns self.
+    pub fn trusted_proxy(mut self, address: impl Into<IpAddr>) -> Self {
+        self.trusted_proxies.push(address.into());
+        self
+    }
+
     /// Sets [`AcmeConfiguration::contact_email`] to `contact_email` and returns self.
     #[cfg(feature = "acme")]
     pub fn acme_contact_email(mut self, contact_email: impl Into<String>) -> Self {
@@ -110,17 +314,129 @@ impl<B: Backend> ServerConfiguration<B> {
         self
     }
 
+    /// Sets [`AcmeConfiguration::initial_retry_delay`] to `delay` and returns self.
+    #[cfg(feature = "acme")]
+    pub const fn acme_initial_retry_delay(mut self, delay: Duration) -> Self {
+        self.acme.initial_retry_delay = delay;
+        self
+    }
+
+    /// Sets [`AcmeConfiguration::max_retry_delay`] to `delay` and returns self.
+    #[cfg(feature = "acme")]
+    pub const fn acme_max_retry_delay(mut self, delay: Duration) -> Self {
+        self.acme.max_retry_delay = delay;
+        self
+    }
+
+    /// Sets [`Self::webhooks`](Self#structfield.webhooks) to `webhooks` and returns self.
+    #[cfg(feature = "webhooks")]
+    pub const fn webhooks(mut self, webhooks: WebhookConfiguration) -> Self {
+        self.webhooks = webhooks;
+        self
+    }
+
+    /// Sets [`Self::websockets`](Self#structfield.websockets) to `websockets` and returns self.
+    #[cfg(feature = "websockets")]
+    pub const fn websockets(mut self, websockets: WebSocketConfiguration) -> Self {
+        self.websockets = websockets;
+        self
+    }
+
+    /// Sets [`Self::request_log`](Self#structfield.request_log) to `request_log` and returns self.
+    #[cfg(feature = "request-log")]
+    pub fn request_log(mut self, request_log: RequestLogConfiguration) -> Self {
+        self.request_log = Some(request_log);
+        self
+    }
+
+    /// Adds `exporter` to [`Self::change_data_capture`](Self#structfield.change_data_capture) and returns self.
+    #[cfg(feature = "cdc")]
+    pub fn change_data_capture(mut self, exporter: ChangeDataCapture) -> Self {
+        self.change_data_capture.push(exporter);
+        self
+    }
+
+    /// Sets [`Self::cluster`](Self#structfield.cluster) to `cluster` and returns self.
+    #[cfg(feature = "cluster")]
+    pub fn cluster(mut self, cluster: ClusterConfiguration) -> Self {
+        self.cluster = Some(cluster);
+        self
+    }
+
     /// Registers a `handler` for a [`Api`][api::Api]. When an [`Api`][api::Api] is
-    /// received by the server, the handler will be invoked
+    /// received by the server, the handler will be invoked. If an
+    /// [`Api`][api::Api] with the same name is already registered,
+    /// [`Error::ApiAlreadyRegistered`] is returned.
     pub fn register_custom_api<Dispatcher: Handler<B, Api> + 'static, Api: api::Api>(
         &mut self,
     ) -> Result<(), Error> {
-        // TODO this should error on duplicate registration.
-        self.custom_apis.insert(
-            Api::name(),
-            Arc::new(AnyWrapper::<Dispatcher, B, Api>(PhantomData)),
-        );
-        Ok(())
+        self.custom_apis.register::<Dispatcher, Api>()
+    }
+
+    /// Registers a `handler` for a [`Api`][api::Api] that requires the
+    /// connected client to be allowed to perform `action` on `resource_name`.
+    /// See [`ApiRegistry::register_with_permission()`].
+    pub fn register_custom_api_with_permission<
+        Dispatcher: Handler<B, Api> + 'static,
+        Api: api::Api,
+        A: Action + 'static,
+    >(
+        &mut self,
+        resource_name: ResourceName<'static>,
+        action: A,
+    ) -> Result<(), Error> {
+        self.custom_apis
+            .register_with_permission::<Dispatcher, Api, A>(resource_name, action)
+    }
+
+    /// Registers all of the apis provided by `plugin` and returns self.
+    pub fn with_api_plugin<Plugin: ApiPlugin<B>>(mut self, plugin: &Plugin) -> Result<Self, Error> {
+        self.custom_apis.register_plugin(plugin)?;
+        Ok(self)
+    }
+
+    /// Registers `Dispatcher` as the handler for the [`NamedQuery`](api::NamedQuery)
+    /// `Query`. If a query with the same name is already registered,
+    /// [`Error::NamedQueryAlreadyRegistered`] is returned.
+    pub fn register_named_query<
+        Dispatcher: NamedQueryHandler<B, Query> + 'static,
+        Query: api::NamedQuery,
+    >(
+        &mut self,
+    ) -> Result<(), Error> {
+        self.named_queries.register::<Dispatcher, Query>()
+    }
+
+    /// Registers `Dispatcher` as the handler for the [`NamedQuery`](api::NamedQuery)
+    /// `Query` and returns self.
+    pub fn with_named_query<
+        Dispatcher: NamedQueryHandler<B, Query> + 'static,
+        Query: api::NamedQuery,
+    >(
+        mut self,
+    ) -> Result<Self, Error> {
+        self.register_named_query::<Dispatcher, Query>()?;
+        Ok(self)
+    }
+
+    /// Returns the registry of named queries.
+    #[must_use]
+    pub const fn named_queries(&self) -> &NamedQueryRegistry<B> {
+        &self.named_queries
+    }
+
+    /// Adds `middleware` to the chain of [`Middleware`] that process each
+    /// request, and returns self. Middleware is invoked in the order it is
+    /// added, with the first middleware added receiving each request first.
+    pub fn with_middleware<M: Middleware<B>>(mut self, middleware: M) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Returns the registry of custom apis.
+    #[must_use]
+    pub const fn custom_apis(&self) -> &ApiRegistry<B> {
+        &self.custom_apis
     }
 
     /// Registers the custom api dispatcher and returns self.
@@ -143,6 +459,8 @@ where
 
 #[cfg(feature = "acme")]
 mod acme {
+    use std::time::Duration;
+
     /// The Automated Certificate Management Environment (ACME) configuration.
     #[derive(Debug, Clone)]
     pub struct AcmeConfiguration {
@@ -151,6 +469,13 @@ mod acme {
         /// The ACME directory to use for registration. The default is
         /// [`LETS_ENCRYPT_PRODUCTION_DIRECTORY`].
         pub directory: String,
+        /// How long to wait before retrying a failed certificate order for the
+        /// first time. Each consecutive failure doubles the delay. Default
+        /// value is 5 minutes.
+        pub initial_retry_delay: Duration,
+        /// The longest time to wait between retries of a failed certificate
+        /// order. Default value is 1 hour.
+        pub max_retry_delay: Duration,
     }
 
     impl Default for AcmeConfiguration {
@@ -158,6 +483,8 @@ mod acme {
             Self {
                 contact_email: None,
                 directory: LETS_ENCRYPT_PRODUCTION_DIRECTORY.to_string(),
+                initial_retry_delay: Duration::from_secs(5 * 60),
+                max_retry_delay: Duration::from_secs(60 * 60),
             }
         }
     }
@@ -168,6 +495,484 @@ mod acme {
 #[cfg(feature = "acme")]
 pub use acme::*;
 
+/// Settings for delivering changes to
+/// [webhooks](bonsaidb_core::admin::Webhook).
+///
+/// Each transaction is delivered to a webhook as a separate request, in the
+/// order the transactions were executed. A request is considered delivered
+/// once the webhook responds with a success status. Failed requests are
+/// retried, waiting twice as long between each attempt, until
+/// [`max_attempts`](Self#structfield.max_attempts) is reached, at which point
+/// the transaction is skipped.
+#[cfg(feature = "webhooks")]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[must_use]
+#[non_exhaustive]
+pub struct WebhookConfiguration {
+    /// How often databases are checked for new transactions. Default value is
+    /// 1 second.
+    pub poll_interval: Duration,
+    /// How long to wait for a webhook to respond before the attempt fails.
+    /// Default value is 30 seconds.
+    pub request_timeout: Duration,
+    /// The number of times delivering a transaction is attempted before it is
+    /// skipped. Default value is 10.
+    pub max_attempts: u32,
+    /// How long to wait before retrying a failed request for the first time.
+    /// Default value is 1 second.
+    pub initial_retry_delay: Duration,
+    /// The longest time to wait between retries. Default value is 5 minutes.
+    pub max_retry_delay: Duration,
+}
+
+#[cfg(feature = "webhooks")]
+impl Default for WebhookConfiguration {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(1),
+            request_timeout: Duration::from_secs(30),
+            max_attempts: 10,
+            initial_retry_delay: Duration::from_secs(1),
+            max_retry_delay: Duration::from_secs(5 * 60),
+        }
+    }
+}
+
+#[cfg(feature = "webhooks")]
+impl WebhookConfiguration {
+    /// Sets [`Self::poll_interval`](Self#structfield.poll_interval) to `interval` and returns self.
+    pub const fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Sets [`Self::request_timeout`](Self#structfield.request_timeout) to `timeout` and returns self.
+    pub const fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Sets [`Self::max_attempts`](Self#structfield.max_attempts) to `attempts` and returns self.
+    pub const fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts;
+        self
+    }
+
+    /// Sets [`Self::initial_retry_delay`](Self#structfield.initial_retry_delay) to `delay` and returns self.
+    pub const fn initial_retry_delay(mut self, delay: Duration) -> Self {
+        self.initial_retry_delay = delay;
+        self
+    }
+
+    /// Sets [`Self::max_retry_delay`](Self#structfield.max_retry_delay) to `delay` and returns self.
+    pub const fn max_retry_delay(mut self, delay: Duration) -> Self {
+        self.max_retry_delay = delay;
+        self
+    }
+}
+
+/// The keepalive and idle settings of WebSocket connections.
+///
+/// While a connection is open, the server pings the client every
+/// [`ping_interval`](Self#structfield.ping_interval). If nothing is received
+/// from the client within [`pong_timeout`](Self#structfield.pong_timeout) of a
+/// ping, the connection is closed. Connections that send no requests for
+/// [`idle_timeout`](Self#structfield.idle_timeout) are also closed. Pings and
+/// pongs do not count as requests.
+#[cfg(feature = "websockets")]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[must_use]
+#[non_exhaustive]
+pub struct WebSocketConfiguration {
+    /// How often connected clients are pinged. If `None`, clients are not
+    /// pinged. Default value is 30 seconds.
+    pub ping_interval: Option<Duration>,
+    /// How long to wait for a client to respond to a ping before closing its
+    /// connection. Default value is 10 seconds.
+    pub pong_timeout: Duration,
+    /// How long a connection may go without sending a request before it is
+    /// closed. If `None`, idle connections are kept open. Default value is
+    /// `None`.
+    pub idle_timeout: Option<Duration>,
+}
+
+#[cfg(feature = "websockets")]
+impl Default for WebSocketConfiguration {
+    fn default() -> Self {
+        Self {
+            ping_interval: Some(Duration::from_secs(30)),
+            pong_timeout: Duration::from_secs(10),
+            idle_timeout: None,
+        }
+    }
+}
+
+#[cfg(feature = "websockets")]
+impl WebSocketConfiguration {
+    /// Sets [`Self::ping_interval`](Self#structfield.ping_interval) to `interval` and returns self.
+    pub const fn ping_interval(mut self, interval: Duration) -> Self {
+        self.ping_interval = Some(interval);
+        self
+    }
+
+    /// Sets [`Self::ping_interval`](Self#structfield.ping_interval) to `None` and returns self.
+    pub const fn without_pings(mut self) -> Self {
+        self.ping_interval = None;
+        self
+    }
+
+    /// Sets [`Self::pong_timeout`](Self#structfield.pong_timeout) to `timeout` and returns self.
+    pub const fn pong_timeout(mut self, timeout: Duration) -> Self {
+        self.pong_timeout = timeout;
+        self
+    }
+
+    /// Sets [`Self::idle_timeout`](Self#structfield.idle_timeout) to `timeout` and returns self.
+    pub const fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+}
+
+/// The settings for logging the requests processed by a server. Each logged
+/// request is recorded as a [`RequestLogEntry`](crate::RequestLogEntry).
+///
+/// By default, every request is logged. Busy servers can log a sample of
+/// their requests using [`sample_one_in`](Self#structfield.sample_one_in),
+/// while still logging every failed or slow request.
+#[cfg(feature = "request-log")]
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[must_use]
+#[non_exhaustive]
+pub struct RequestLogConfiguration {
+    /// Where logged requests are written.
+    pub destination: RequestLogDestination,
+    /// Logs one out of every `sample_one_in` requests. Values of 0 and 1 log
+    /// every request. Default value is 1.
+    pub sample_one_in: u32,
+    /// If true, every request that returns an error is logged, regardless of
+    /// [`sample_one_in`](Self#structfield.sample_one_in). Default value is
+    /// true.
+    pub always_log_errors: bool,
+    /// If set, every request that takes at least this long is logged,
+    /// regardless of [`sample_one_in`](Self#structfield.sample_one_in).
+    /// Default value is `None`.
+    pub slow_request_threshold: Option<Duration>,
+}
+
+#[cfg(feature = "request-log")]
+impl RequestLogConfiguration {
+    /// Returns a configuration that appends each logged request to the file
+    /// at `path` as a line of JSON.
+    pub fn json_lines(path: impl AsRef<Path>) -> Self {
+        Self::new(RequestLogDestination::JsonLines(
+            path.as_ref().to_path_buf(),
+        ))
+    }
+
+    /// Returns a configuration that emits each logged request as a
+    /// [`tracing`] event.
+    #[cfg(feature = "instrument")]
+    pub const fn tracing() -> Self {
+        Self::new(RequestLogDestination::Tracing)
+    }
+
+    /// Returns a configuration that writes logged requests to `destination`.
+    pub const fn new(destination: RequestLogDestination) -> Self {
+        Self {
+            destination,
+            sample_one_in: 1,
+            always_log_errors: true,
+            slow_request_threshold: None,
+        }
+    }
+
+    /// Sets [`Self::sample_one_in`](Self#structfield.sample_one_in) to `sample_one_in` and returns self.
+    pub const fn sample_one_in(mut self, sample_one_in: u32) -> Self {
+        self.sample_one_in = sample_one_in;
+        self
+    }
+
+    /// Sets [`Self::always_log_errors`](Self#structfield.always_log_errors) to `always_log_errors` and returns self.
+    pub const fn always_log_errors(mut self, always_log_errors: bool) -> Self {
+        self.always_log_errors = always_log_errors;
+        self
+    }
+
+    /// Sets [`Self::slow_request_threshold`](Self#structfield.slow_request_threshold) to `threshold` and returns self.
+    pub const fn slow_request_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_request_threshold = Some(threshold);
+        self
+    }
+}
+
+/// Where a server's logged requests are written. See
+/// [`RequestLogConfiguration`].
+#[cfg(feature = "request-log")]
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum RequestLogDestination {
+    /// Each request is appended to the file at this path as a line of JSON.
+    /// The file is created if it doesn't exist.
+    JsonLines(PathBuf),
+    /// Each request is emitted as a [`tracing`] event at the `INFO` level,
+    /// with the target `bonsaidb_server::requests`.
+    #[cfg(feature = "instrument")]
+    Tracing,
+}
+
+/// Limits on the requests a client or user may make. Each limit is optional,
+/// and by default no limits are applied.
+///
+/// Requests that exceed a limit are rejected with
+/// [`Error::RateLimited`](bonsaidb_core::Error::RateLimited).
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+#[must_use]
+#[non_exhaustive]
+pub struct RateLimit {
+    /// The maximum number of requests per second. Bursts of up to this many
+    /// requests are allowed.
+    pub requests_per_second: Option<u32>,
+    /// The maximum number of requests that can be in flight at the same time.
+    pub concurrent_requests: Option<usize>,
+    /// The maximum number of request payload bytes per second. A single request
+    /// may exceed this limit, but subsequent requests will be rejected until
+    /// the excess has been paid back.
+    pub bytes_per_second: Option<u64>,
+}
+
+impl RateLimit {
+    /// Sets [`Self::requests_per_second`](Self#structfield.requests_per_second) to `requests` and returns self.
+    pub const fn requests_per_second(mut self, requests: u32) -> Self {
+        self.requests_per_second = Some(requests);
+        self
+    }
+
+    /// Sets [`Self::concurrent_requests`](Self#structfield.concurrent_requests) to `requests` and returns self.
+    pub const fn concurrent_requests(mut self, requests: usize) -> Self {
+        self.concurrent_requests = Some(requests);
+        self
+    }
+
+    /// Sets [`Self::bytes_per_second`](Self#structfield.bytes_per_second) to `bytes` and returns self.
+    pub const fn bytes_per_second(mut self, bytes: u64) -> Self {
+        self.bytes_per_second = Some(bytes);
+        self
+    }
+
+    /// Returns true if no limits are set.
+    #[must_use]
+    pub const fn is_unlimited(&self) -> bool {
+        self.requests_per_second.is_none()
+            && self.concurrent_requests.is_none()
+            && self.bytes_per_second.is_none()
+    }
+}
+
+/// Exponential backoff for failed password authentication attempts.
+///
+/// Failed attempts are counted for the username or user id being
+/// authenticated and for the address of the client. Once either count exceeds
+/// [`free_attempts`](Self#structfield.free_attempts), further attempts are
+/// rejected with
+/// [`Error::RateLimited`](bonsaidb_core::Error::RateLimited) until a delay has
+/// elapsed since the last failure. The delay starts at
+/// [`initial_delay`](Self#structfield.initial_delay) and doubles with each
+/// additional failure, up to
+/// [`maximum_delay`](Self#structfield.maximum_delay).
+///
+/// A successful authentication clears the count for the user, but not for the
+/// address. Counts are forgotten once no failures have occurred for
+/// [`reset_after`](Self#structfield.reset_after). At most
+/// [`maximum_addresses`](Self#structfield.maximum_addresses) addresses are
+/// tracked at once.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[must_use]
+#[non_exhaustive]
+pub struct LoginThrottle {
+    /// The number of failed attempts allowed before attempts are delayed.
+    /// Default value is 5.
+    pub free_attempts: u32,
+    /// The delay after the first failure beyond `free_attempts`. Default
+    /// value is 1 second.
+    pub initial_delay: Duration,
+    /// The longest delay between attempts. Default value is 5 minutes.
+    pub maximum_delay: Duration,
+    /// The amount of time without failures after which the count of failures
+    /// is forgotten. Default value is 15 minutes.
+    pub reset_after: Duration,
+    /// If true, failures are counted for each user, whether the user is named
+    /// by username or by id. Default value is true.
+    pub per_username: bool,
+    /// If true, failures are counted for each client address. Default value
+    /// is true.
+    pub per_address: bool,
+    /// The most client addresses whose failures are tracked at once. Once
+    /// reached, the unlocked address whose last failure is the oldest is
+    /// forgotten to make room for a new address. Default value is 100,000.
+    pub maximum_addresses: usize,
+    /// If specified, a user or address that reaches the lockout's number of
+    /// failures is locked for the lockout's duration, or until it is unlocked
+    /// by an administrator using
+    /// [`unlock_user()`](bonsaidb_core::connection::AsyncStorageConnection::unlock_user)
+    /// or [`CustomServer::unlock_address()`](crate::CustomServer::unlock_address).
+    /// While locked, attempts are rejected with
+    /// [`Error::AccountLocked`](bonsaidb_core::Error::AccountLocked). Default
+    /// value is `None`.
+    pub lockout: Option<Lockout>,
+}
+
+impl Default for LoginThrottle {
+    fn default() -> Self {
+        Self {
+            free_attempts: 5,
+            initial_delay: Duration::from_secs(1),
+            maximum_delay: Duration::from_secs(5 * 60),
+            reset_after: Duration::from_secs(15 * 60),
+            per_username: true,
+            per_address: true,
+            maximum_addresses: 100_000,
+            lockout: None,
+        }
+    }
+}
+
+impl LoginThrottle {
+    /// Sets [`Self::free_attempts`](Self#structfield.free_attempts) to `attempts` and returns self.
+    pub const fn free_attempts(mut self, attempts: u32) -> Self {
+        self.free_attempts = attempts;
+        self
+    }
+
+    /// Sets [`Self::initial_delay`](Self#structfield.initial_delay) to `delay` and returns self.
+    pub const fn initial_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay = delay;
+        self
+    }
+
+    /// Sets [`Self::maximum_delay`](Self#structfield.maximum_delay) to `delay` and returns self.
+    pub const fn maximum_delay(mut self, delay: Duration) -> Self {
+        self.maximum_delay = delay;
+        self
+    }
+
+    /// Sets [`Self::reset_after`](Self#structfield.reset_after) to `duration` and returns self.
+    pub const fn reset_after(mut self, duration: Duration) -> Self {
+        self.reset_after = duration;
+        self
+    }
+
+    /// Sets [`Self::per_username`](Self#structfield.per_username) to `enabled` and returns self.
+    pub const fn per_username(mut self, enabled: bool) -> Self {
+        self.per_username = enabled;
+        self
+    }
+
+    /// Sets [`Self::per_address`](Self#structfield.per_address) to `enabled` and returns self.
+    pub const fn per_address(mut self, enabled: bool) -> Self {
+        self.per_address = enabled;
+        self
+    }
+
+    /// Sets [`Self::maximum_addresses`](Self#structfield.maximum_addresses) to `maximum` and returns self.
+    pub const fn maximum_addresses(mut self, maximum: usize) -> Self {
+        self.maximum_addresses = maximum;
+        self
+    }
+
+    /// Sets [`Self::lockout`](Self#structfield.lockout) to `lockout` and returns self.
+    pub const fn lockout(mut self, lockout: Lockout) -> Self {
+        self.lockout = Some(lockout);
+        self
+    }
+}
+
+/// Settings for authenticating clients using TLS client certificates, also
+/// known as mutual TLS.
+///
+/// Client certificates are only requested by
+/// [`CustomServer::listen_for_secure_tcp_on()`](crate::CustomServer::listen_for_secure_tcp_on).
+/// When a client presents a certificate issued by one of the
+/// [`trusted_authorities`](Self#structfield.trusted_authorities), the
+/// [`resolver`](Self#structfield.resolver) is asked which identity the
+/// certificate belongs to. If an identity is returned, the connection's
+/// unauthenticated session is replaced with a session authenticated as that
+/// identity, and no further authentication is needed.
+#[derive(Debug, Clone)]
+#[must_use]
+#[non_exhaustive]
+pub struct ClientCertificateAuthentication {
+    /// The certificate authorities that client certificates must be issued by.
+    pub trusted_authorities: Vec<Certificate>,
+    /// If true, the TLS handshake fails for clients that do not present a
+    /// certificate. If false, clients without a certificate connect
+    /// unauthenticated. The default is false.
+    pub required: bool,
+    /// Resolves the identity a client certificate authenticates as.
+    pub resolver: Arc<dyn ClientCertificateResolver>,
+}
+
+impl ClientCertificateAuthentication {
+    /// Returns a new configuration trusting certificates issued by
+    /// `trusted_authorities`, using `resolver` to map certificates to
+    /// identities.
+    pub fn new<R: ClientCertificateResolver>(
+        trusted_authorities: impl IntoIterator<Item = Certificate>,
+        resolver: R,
+    ) -> Self {
+        Self {
+            trusted_authorities: trusted_authorities.into_iter().collect(),
+            required: false,
+            resolver: Arc::new(resolver),
+        }
+    }
+
+    /// Sets [`Self::required`](Self#structfield.required) to true and returns self.
+    pub const fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+}
+
+/// Resolves the identity that a verified client certificate authenticates as.
+pub trait ClientCertificateResolver: Debug + Send + Sync + 'static {
+    /// Returns the identity that `certificate` authenticates as. If `None` is
+    /// returned, the client remains unauthenticated.
+    ///
+    /// `certificate` has already been verified to be issued by one of the
+    /// [trusted authorities](ClientCertificateAuthentication#structfield.trusted_authorities).
+    fn resolve(&self, certificate: &Certificate) -> Option<IdentityReference<'static>>;
+}
+
+/// A [`ClientCertificateResolver`] that maps individual certificates to
+/// identities.
+#[derive(Debug, Clone, Default)]
+#[must_use]
+pub struct CertificateIdentities {
+    identities: HashMap<Vec<u8>, IdentityReference<'static>>,
+}
+
+impl CertificateIdentities {
+    /// Associates `certificate` with `identity` and returns self.
+    pub fn with(mut self, certificate: &Certificate, identity: IdentityReference<'_>) -> Self {
+        self.insert(certificate, identity);
+        self
+    }
+
+    /// Associates `certificate` with `identity`, replacing any previously
+    /// associated identity.
+    pub fn insert(&mut self, certificate: &Certificate, identity: IdentityReference<'_>) {
+        self.identities
+            .insert(certificate.as_ref().to_vec(), identity.into_owned());
+    }
+}
+
+impl ClientCertificateResolver for CertificateIdentities {
+    fn resolve(&self, certificate: &Certificate) -> Option<IdentityReference<'static>> {
+        self.identities.get(certificate.as_ref()).cloned()
+    }
+}
+
 /// The default permissions to use for all connections to the server.
 #[derive(Debug, Clone)]
 pub enum DefaultPermissions {
@@ -255,6 +1060,31 @@ impl<B: Backend> Builder for ServerConfiguration<B> {
         self
     }
 
+    fn cache_view_queries<V: View>(mut self, view: &V) -> Self {
+        self.storage = self.storage.cache_view_queries(view);
+        self
+    }
+
+    fn warm_views_on_open(mut self, count: usize) -> Self {
+        self.storage.views.warm_on_open = Some(count);
+        self
+    }
+
+    fn serve_stale_views_while_rebuilding<N: Into<String>>(mut self, database: N) -> Self {
+        self.storage = self.storage.serve_stale_views_while_rebuilding(database);
+        self
+    }
+
+    fn redact_unreadable_view_entries(mut self, redact: bool) -> Self {
+        self.storage.views.redact_unreadable_entries = redact;
+        self
+    }
+
+    fn scrub_interval(mut self, interval: Duration) -> Self {
+        self.storage.scrub_interval = Some(interval);
+        self
+    }
+
     #[cfg(feature = "compression")]
     fn default_compression(mut self, compression: Compression) -> Self {
         self.storage.default_compression = Some(compression);
@@ -266,6 +1096,43 @@ impl<B: Backend> Builder for ServerConfiguration<B> {
         self
     }
 
+    fn database_key_value_persistence<N: Into<String>>(
+        mut self,
+        database: N,
+        persistence: KeyValuePersistence,
+    ) -> Self {
+        self.storage = self
+            .storage
+            .database_key_value_persistence(database, persistence);
+        self
+    }
+
+    fn concurrent_query_limit(mut self, limit: usize) -> Self {
+        self.storage.concurrent_query_limit = Some(limit);
+        self
+    }
+
+    fn database_concurrent_query_limit<N: Into<String>>(
+        mut self,
+        database: N,
+        limit: usize,
+    ) -> Self {
+        self.storage = self
+            .storage
+            .database_concurrent_query_limit(database, limit);
+        self
+    }
+
+    fn session_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.storage.sessions.idle_timeout = Some(timeout);
+        self
+    }
+
+    fn session_absolute_timeout(mut self, timeout: Duration) -> Self {
+        self.storage.sessions.absolute_timeout = Some(timeout);
+        self
+    }
+
     fn authenticated_permissions<P: Into<Permissions>>(
         mut self,
         authenticated_permissions: P,
@@ -279,6 +1146,106 @@ impl<B: Backend> Builder for ServerConfiguration<B> {
         self.storage.argon = argon;
         self
     }
+
+    #[cfg(feature = "password-hashing")]
+    fn password_policy(mut self, policy: bonsaidb_local::config::PasswordPolicy) -> Self {
+        self.storage.password_policy = policy;
+        self
+    }
+
+    #[cfg(feature = "sql")]
+    fn sql_view<V>(mut self, table: impl Into<String>, view: &V) -> Self
+    where
+        V: View,
+        V::Key: bonsaidb_core::sql::SqlKey,
+    {
+        self.storage.sql = self.storage.sql.with_view(table, view);
+        self
+    }
+
+    fn shard_collection<C: Collection>(
+        mut self,
+        database: impl Into<String>,
+        sharding: Sharding,
+    ) -> Self {
+        self.storage = self.storage.shard_collection::<C>(database, sharding);
+        self
+    }
+}
+
+/// An endpoint for the server to accept connections on. Listeners are started
+/// by [`CustomServer::listen()`](crate::CustomServer::listen).
+#[derive(Debug, Clone)]
+#[must_use]
+#[non_exhaustive]
+pub struct ListenerConfiguration {
+    /// The protocol and address to accept connections on.
+    pub endpoint: ListenerEndpoint,
+    /// The permissions granted to all connections accepted by this listener.
+    /// If `None`, [`ServerConfiguration::default_permissions`] is used.
+    pub default_permissions: Option<DefaultPermissions>,
+}
+
+impl ListenerConfiguration {
+    /// Returns a listener for the BonsaiDb QUIC-based protocol.
+    pub fn bonsai(config: impl Into<BonsaiListenConfig>) -> Self {
+        Self::from(ListenerEndpoint::Bonsai(config.into()))
+    }
+
+    /// Returns a listener for `WebSocket` connections on `address`. If
+    /// `secure` is true, connections are secured using TLS.
+    #[cfg(feature = "websockets")]
+    pub fn websockets(address: impl Into<SocketAddr>, secure: bool) -> Self {
+        Self::from(ListenerEndpoint::WebSockets {
+            address: address.into(),
+            secure,
+        })
+    }
+
+    /// Returns a listener for `WebSocket` connections on the Unix domain socket
+    /// at `path`.
+    #[cfg(all(unix, feature = "websockets"))]
+    pub fn unix_socket(path: impl AsRef<Path>) -> Self {
+        Self::from(ListenerEndpoint::UnixSocket(path.as_ref().to_path_buf()))
+    }
+
+    /// Sets [`Self::default_permissions`](Self#structfield.default_permissions) to `default_permissions` and returns self.
+    pub fn default_permissions<P: Into<DefaultPermissions>>(
+        mut self,
+        default_permissions: P,
+    ) -> Self {
+        self.default_permissions = Some(default_permissions.into());
+        self
+    }
+}
+
+impl From<ListenerEndpoint> for ListenerConfiguration {
+    fn from(endpoint: ListenerEndpoint) -> Self {
+        Self {
+            endpoint,
+            default_permissions: None,
+        }
+    }
+}
+
+/// The protocol and address of a [`ListenerConfiguration`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum ListenerEndpoint {
+    /// The BonsaiDb QUIC-based protocol. Connections are always secured using
+    /// TLS.
+    Bonsai(BonsaiListenConfig),
+    /// `WebSocket` connections over TCP.
+    #[cfg(feature = "websockets")]
+    WebSockets {
+        /// The address to listen on.
+        address: SocketAddr,
+        /// If true, connections are secured using TLS.
+        secure: bool,
+    },
+    /// `WebSocket` connections over a Unix domain socket.
+    #[cfg(all(unix, feature = "websockets"))]
+    UnixSocket(PathBuf),
 }
 
 /// Configuration for the BonsaiDb network protocol.
@@ -300,6 +1267,14 @@ pub struct BonsaiListenConfig {
     /// itself. In general, this is not needed for users in regular deployments,
     /// and is more useful for specific kinds of testing.
     pub reuse_address: bool,
+
+    /// The amount of time a connection may be idle before it is closed. A
+    /// connection is idle when neither side has sent anything. If `None`, idle
+    /// connections are kept open. Default value is `None`.
+    ///
+    /// The effective timeout of a connection is the lower of this value and
+    /// the timeout requested by the client.
+    pub idle_timeout: Option<Duration>,
 }
 
 impl Default for BonsaiListenConfig {
@@ -307,6 +1282,7 @@ impl Default for BonsaiListenConfig {
         Self {
             address: SocketAddr::V6(SocketAddrV6::new(Ipv6Addr::LOCALHOST, 5465, 0, 0)),
             reuse_address: false,
+            idle_timeout: None,
         }
     }
 }
@@ -325,6 +1301,13 @@ impl BonsaiListenConfig {
         self.reuse_address = reuse_address;
         self
     }
+
+    /// Sets the [`idle_timeout`](Self::idle_timeout) to `timeout`.
+    #[must_use]
+    pub const fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
 }
 
 impl From<u16> for BonsaiListenConfig {
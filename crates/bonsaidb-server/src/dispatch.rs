@@ -1,63 +1,107 @@
-use bonsaidb_core::api::ApiName;
+use bonsaidb_core::api::{Api, ApiName};
 use bonsaidb_core::arc_bytes::serde::Bytes;
 use bonsaidb_core::async_trait::async_trait;
 use bonsaidb_core::connection::{
     AsyncConnection, AsyncLowLevelConnection, AsyncStorageConnection, HasSession,
 };
+use bonsaidb_core::document::CollectionDocument;
 use bonsaidb_core::keyvalue::AsyncKeyValue;
 use bonsaidb_core::networking::{
-    AlterUserPermissionGroupMembership, AlterUserRoleMembership, ApplyTransaction, AssumeIdentity,
-    Compact, CompactCollection, CompactKeyValueStore, Count, CreateDatabase, CreateSubscriber,
-    CreateUser, DeleteDatabase, DeleteDocs, DeleteUser, ExecuteKeyOperation, Get, GetMultiple,
+    Aggregate, AlterRolePermissionGroupMembership, AlterUserPermissionGroupMembership,
+    AlterUserRoleMembership, ApplyTransaction, AssumeIdentity, BeginTransaction, CommitTransaction,
+    Compact, CompactCollection, CompactKeyValueStore, Count, CountView, CreateDatabase,
+    CreatePermissionGroup, CreateRole, CreateSubscriber, CreateUser, DeleteDatabase, DeleteDocs,
+    DeletePermissionGroup, DeleteRole, DeleteUser, DocumentMappings, EffectivePermissions,
+    ExecuteBatch, ExecuteKeyOperation, ExecuteNamedQuery, Get, GetMultiple, Handshake,
     LastTransactionId, List, ListAvailableSchemas, ListDatabases, ListExecutedTransactions,
-    ListHeaders, LogOutSession, Publish, PublishToAll, Query, QueryWithDocs, Reduce, ReduceGrouped,
-    SubscribeTo, UnregisterSubscriber, UnsubscribeFrom,
+    ListHeaders, ListPermissionGroups, ListProjected, ListRoles, ListSessions, ListUsers,
+    LogOutSession, Publish, PublishToAll, PushTransactionOperations, Query, QueryJoin,
+    QueryWithDocs, QueryWithProjectedDocs, Reduce, ReduceGrouped, RevokeSession,
+    SetPermissionGroupStatements, SetUserDisabled, SubscribeTo, UnlockUser, UnregisterSubscriber,
+    UnsubscribeFrom, UpdatePermissionGroupStatements, UpdateRolePermissionGroups, UserByName,
+    WatchDocumentChanges,
 };
 #[cfg(feature = "password-hashing")]
 use bonsaidb_core::networking::{Authenticate, SetUserPassword};
+use bonsaidb_core::permissions::bonsai::{
+    named_query_resource_name, BonsaiAction, DatabaseAction, NamedQueryAction,
+};
 use bonsaidb_core::pubsub::AsyncPubSub;
 
-use crate::api::{Handler, HandlerError, HandlerResult, HandlerSession};
+use crate::api::{ApiRequest, Handler, HandlerError, HandlerResult, HandlerSession, Next};
 use crate::{Backend, Error, ServerConfiguration};
 
-#[cfg_attr(not(feature = "password-hashing"), allow(unused_mut))]
+#[cfg_attr(
+    not(any(feature = "password-hashing", feature = "cluster")),
+    allow(unused_mut)
+)]
 pub fn register_api_handlers<B: Backend>(
     config: ServerConfiguration<B>,
 ) -> Result<ServerConfiguration<B>, Error> {
     let mut config = config
+        .with_api::<ServerDispatcher, Aggregate>()?
+        .with_api::<ServerDispatcher, AlterRolePermissionGroupMembership>()?
         .with_api::<ServerDispatcher, AlterUserPermissionGroupMembership>()?
         .with_api::<ServerDispatcher, AlterUserRoleMembership>()?
         .with_api::<ServerDispatcher, ApplyTransaction>()?
         .with_api::<ServerDispatcher, AssumeIdentity>()?
+        .with_api::<ServerDispatcher, BeginTransaction>()?
+        .with_api::<ServerDispatcher, CommitTransaction>()?
         .with_api::<ServerDispatcher, Compact>()?
         .with_api::<ServerDispatcher, CompactCollection>()?
         .with_api::<ServerDispatcher, CompactKeyValueStore>()?
         .with_api::<ServerDispatcher, Count>()?
+        .with_api::<ServerDispatcher, CountView>()?
         .with_api::<ServerDispatcher, CreateDatabase>()?
+        .with_api::<ServerDispatcher, CreatePermissionGroup>()?
+        .with_api::<ServerDispatcher, CreateRole>()?
         .with_api::<ServerDispatcher, CreateSubscriber>()?
         .with_api::<ServerDispatcher, CreateUser>()?
         .with_api::<ServerDispatcher, DeleteDatabase>()?
         .with_api::<ServerDispatcher, DeleteDocs>()?
+        .with_api::<ServerDispatcher, DeletePermissionGroup>()?
+        .with_api::<ServerDispatcher, DeleteRole>()?
         .with_api::<ServerDispatcher, DeleteUser>()?
+        .with_api::<ServerDispatcher, DocumentMappings>()?
+        .with_api::<ServerDispatcher, EffectivePermissions>()?
+        .with_api::<ServerDispatcher, ExecuteBatch>()?
         .with_api::<ServerDispatcher, ExecuteKeyOperation>()?
+        .with_api::<ServerDispatcher, ExecuteNamedQuery>()?
         .with_api::<ServerDispatcher, Get>()?
         .with_api::<ServerDispatcher, GetMultiple>()?
+        .with_api::<ServerDispatcher, Handshake>()?
         .with_api::<ServerDispatcher, LastTransactionId>()?
         .with_api::<ServerDispatcher, List>()?
         .with_api::<ServerDispatcher, ListHeaders>()?
         .with_api::<ServerDispatcher, ListAvailableSchemas>()?
         .with_api::<ServerDispatcher, ListDatabases>()?
         .with_api::<ServerDispatcher, ListExecutedTransactions>()?
+        .with_api::<ServerDispatcher, ListPermissionGroups>()?
+        .with_api::<ServerDispatcher, ListProjected>()?
+        .with_api::<ServerDispatcher, ListRoles>()?
+        .with_api::<ServerDispatcher, ListSessions>()?
+        .with_api::<ServerDispatcher, ListUsers>()?
         .with_api::<ServerDispatcher, LogOutSession>()?
         .with_api::<ServerDispatcher, Publish>()?
         .with_api::<ServerDispatcher, PublishToAll>()?
+        .with_api::<ServerDispatcher, PushTransactionOperations>()?
         .with_api::<ServerDispatcher, Query>()?
+        .with_api::<ServerDispatcher, QueryJoin>()?
         .with_api::<ServerDispatcher, QueryWithDocs>()?
+        .with_api::<ServerDispatcher, QueryWithProjectedDocs>()?
         .with_api::<ServerDispatcher, Reduce>()?
         .with_api::<ServerDispatcher, ReduceGrouped>()?
+        .with_api::<ServerDispatcher, RevokeSession>()?
+        .with_api::<ServerDispatcher, SetPermissionGroupStatements>()?
+        .with_api::<ServerDispatcher, SetUserDisabled>()?
         .with_api::<ServerDispatcher, SubscribeTo>()?
+        .with_api::<ServerDispatcher, UnlockUser>()?
         .with_api::<ServerDispatcher, UnregisterSubscriber>()?
-        .with_api::<ServerDispatcher, UnsubscribeFrom>()?;
+        .with_api::<ServerDispatcher, UnsubscribeFrom>()?
+        .with_api::<ServerDispatcher, UpdatePermissionGroupStatements>()?
+        .with_api::<ServerDispatcher, UpdateRolePermissionGroups>()?
+        .with_api::<ServerDispatcher, UserByName>()?
+        .with_api::<ServerDispatcher, WatchDocumentChanges>()?;
 
     #[cfg(feature = "password-hashing")]
     {
@@ -66,6 +110,11 @@ pub fn register_api_handlers<B: Backend>(
             .with_api::<ServerDispatcher, SetUserPassword>()?;
     }
 
+    #[cfg(feature = "cluster")]
+    if config.cluster.is_some() {
+        config = crate::server::register_cluster_apis(config)?;
+    }
+
     Ok(config)
 }
 
@@ -76,15 +125,76 @@ impl ServerDispatcher {
         session: HandlerSession<'_, B>,
         name: &ApiName,
         request: Bytes,
+    ) -> Result<Bytes, Error> {
+        let server = session.server;
+        Next::new(server.middleware())
+            .run(ApiRequest {
+                session,
+                name: name.clone(),
+                request,
+            })
+            .await
+    }
+
+    pub(crate) async fn dispatch_to_handler<B: Backend>(
+        session: HandlerSession<'_, B>,
+        name: &ApiName,
+        request: &[u8],
     ) -> Result<Bytes, Error> {
         if let Some(dispatcher) = session.server.custom_api_dispatcher(name) {
-            dispatcher.handle(session, &request).await
+            dispatcher.handle(session, request).await
         } else {
             Err(Error::from(bonsaidb_core::Error::ApiNotFound(name.clone())))
         }
     }
 }
 
+#[async_trait]
+impl<B: Backend> Handler<B, ExecuteBatch> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: ExecuteBatch,
+    ) -> HandlerResult<ExecuteBatch> {
+        let batch_session = session.as_client.session().cloned().unwrap_or_default();
+        let results = futures::future::join_all(command.requests.into_iter().enumerate().map(
+            |(index, request)| {
+                let request_session = HandlerSession {
+                    server: session.server,
+                    as_client: session.as_client.clone(),
+                    client: session.client,
+                };
+                let batch_session = &batch_session;
+                async move {
+                    if request.name == ExecuteBatch::name() {
+                        return Err(bonsaidb_core::Error::other(
+                            "bonsaidb-server",
+                            "batches cannot contain other batches",
+                        ));
+                    }
+                    // The batch was admitted as a single request. Each
+                    // additional request is charged against the rate limits
+                    // to prevent batches from being used to bypass them.
+                    let _permit = if index == 0 {
+                        None
+                    } else {
+                        Some(request_session.server.begin_request(
+                            request_session.client,
+                            batch_session,
+                            0,
+                        )?)
+                    };
+                    Self::dispatch_api_request(request_session, &request.name, request.value)
+                        .await
+                        .map_err(bonsaidb_core::Error::from)
+                }
+            },
+        ))
+        .await;
+
+        Ok(results)
+    }
+}
+
 #[async_trait]
 impl<B: Backend> Handler<B, CreateDatabase> for ServerDispatcher {
     async fn handle(
@@ -118,11 +228,11 @@ impl<B: Backend> Handler<B, DeleteDatabase> for ServerDispatcher {
 impl<B: Backend> Handler<B, ListDatabases> for ServerDispatcher {
     async fn handle(
         session: HandlerSession<'_, B>,
-        _command: ListDatabases,
+        command: ListDatabases,
     ) -> HandlerResult<ListDatabases> {
         session
             .as_client
-            .list_databases()
+            .list_databases_matching(command.query)
             .await
             .map_err(HandlerError::from)
     }
@@ -170,6 +280,78 @@ impl<B: Backend> Handler<B, DeleteUser> for ServerDispatcher {
     }
 }
 
+#[async_trait]
+impl<B: Backend> Handler<B, ListUsers> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        _command: ListUsers,
+    ) -> HandlerResult<ListUsers> {
+        let users = session.as_client.list_users().await?;
+        users
+            .iter()
+            .map(CollectionDocument::to_document)
+            .collect::<Result<_, _>>()
+            .map_err(HandlerError::from)
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<B, UserByName> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: UserByName,
+    ) -> HandlerResult<UserByName> {
+        let user = session.as_client.user_by_name(&command.username).await?;
+        user.as_ref()
+            .map(CollectionDocument::to_document)
+            .transpose()
+            .map_err(HandlerError::from)
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<B, SetUserDisabled> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: SetUserDisabled,
+    ) -> HandlerResult<SetUserDisabled> {
+        if command.disabled {
+            session.as_client.disable_user(command.user).await?;
+        } else {
+            session.as_client.enable_user(command.user).await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<B, UnlockUser> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: UnlockUser,
+    ) -> HandlerResult<UnlockUser> {
+        session
+            .as_client
+            .unlock_user(command.user)
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<B, EffectivePermissions> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: EffectivePermissions,
+    ) -> HandlerResult<EffectivePermissions> {
+        session
+            .as_client
+            .effective_permissions(command.user)
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
 #[cfg(feature = "password-hashing")]
 #[async_trait]
 impl<B: Backend> Handler<B, SetUserPassword> for ServerDispatcher {
@@ -194,11 +376,17 @@ impl<B: Backend> Handler<B, Authenticate> for ServerDispatcher {
     ) -> HandlerResult<Authenticate> {
         let authenticated = session
             .as_client
-            .authenticate(command.authentication)
+            .authenticate_throttled(command.authentication, Some(session.client.address().ip()))
             .await?;
         let new_session = authenticated.session().cloned().unwrap();
 
-        session.client.logged_in_as(new_session.clone());
+        if let Err(err) = session
+            .server
+            .client_logged_in_as(session.client, new_session.clone())
+        {
+            authenticated.logout().await?;
+            return Err(HandlerError::from(err));
+        }
 
         Ok(new_session)
     }
@@ -213,7 +401,13 @@ impl<B: Backend> Handler<B, AssumeIdentity> for ServerDispatcher {
         let authenticated = session.as_client.assume_identity(command.0).await?;
         let new_session = authenticated.session().cloned().unwrap();
 
-        session.client.logged_in_as(new_session.clone());
+        if let Err(err) = session
+            .server
+            .client_logged_in_as(session.client, new_session.clone())
+        {
+            authenticated.logout().await?;
+            return Err(HandlerError::from(err));
+        }
 
         Ok(new_session)
     }
@@ -225,12 +419,55 @@ impl<B: Backend> Handler<B, LogOutSession> for ServerDispatcher {
         session: HandlerSession<'_, B>,
         command: LogOutSession,
     ) -> HandlerResult<LogOutSession> {
-        session.client.log_out(command.0);
+        if session.client.session(Some(command.0)).is_some() {
+            session.client.log_out(command.0);
+            // The session may have already expired, in which case there is
+            // nothing left to revoke.
+            drop(session.server.storage.revoke_session(command.0).await);
+        }
 
         Ok(())
     }
 }
 
+#[async_trait]
+impl<B: Backend> Handler<B, Handshake> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: Handshake,
+    ) -> HandlerResult<Handshake> {
+        Ok(session.server.handshake(session.client, &command))
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<B, ListSessions> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: ListSessions,
+    ) -> HandlerResult<ListSessions> {
+        session
+            .as_client
+            .list_sessions(command.user)
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<B, RevokeSession> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: RevokeSession,
+    ) -> HandlerResult<RevokeSession> {
+        session
+            .as_client
+            .revoke_session(command.0)
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
 #[async_trait]
 impl<B: Backend> Handler<B, AlterUserPermissionGroupMembership> for ServerDispatcher {
     async fn handle(
@@ -275,6 +512,160 @@ impl<B: Backend> Handler<B, AlterUserRoleMembership> for ServerDispatcher {
     }
 }
 
+#[async_trait]
+impl<B: Backend> Handler<B, CreatePermissionGroup> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: CreatePermissionGroup,
+    ) -> HandlerResult<CreatePermissionGroup> {
+        session
+            .as_client
+            .create_permission_group(&command.name, command.statements)
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<B, ListPermissionGroups> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        _command: ListPermissionGroups,
+    ) -> HandlerResult<ListPermissionGroups> {
+        let groups = session.as_client.list_permission_groups().await?;
+        groups
+            .iter()
+            .map(CollectionDocument::to_document)
+            .collect::<Result<_, _>>()
+            .map_err(HandlerError::from)
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<B, SetPermissionGroupStatements> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: SetPermissionGroupStatements,
+    ) -> HandlerResult<SetPermissionGroupStatements> {
+        session
+            .as_client
+            .set_permission_group_statements(command.group, command.statements)
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<B, UpdatePermissionGroupStatements> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: UpdatePermissionGroupStatements,
+    ) -> HandlerResult<UpdatePermissionGroupStatements> {
+        session
+            .as_client
+            .update_permission_group_statements(
+                command.group,
+                command.expected_version,
+                command.statements,
+            )
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<B, DeletePermissionGroup> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: DeletePermissionGroup,
+    ) -> HandlerResult<DeletePermissionGroup> {
+        session
+            .as_client
+            .delete_permission_group(command.group)
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<B, CreateRole> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: CreateRole,
+    ) -> HandlerResult<CreateRole> {
+        session
+            .as_client
+            .create_role(&command.name)
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<B, ListRoles> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        _command: ListRoles,
+    ) -> HandlerResult<ListRoles> {
+        let roles = session.as_client.list_roles().await?;
+        roles
+            .iter()
+            .map(CollectionDocument::to_document)
+            .collect::<Result<_, _>>()
+            .map_err(HandlerError::from)
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<B, AlterRolePermissionGroupMembership> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: AlterRolePermissionGroupMembership,
+    ) -> HandlerResult<AlterRolePermissionGroupMembership> {
+        if command.should_be_member {
+            session
+                .as_client
+                .add_permission_group_to_role(command.role, command.group)
+                .await
+                .map_err(HandlerError::from)
+        } else {
+            session
+                .as_client
+                .remove_permission_group_from_role(command.role, command.group)
+                .await
+                .map_err(HandlerError::from)
+        }
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<B, UpdateRolePermissionGroups> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: UpdateRolePermissionGroups,
+    ) -> HandlerResult<UpdateRolePermissionGroups> {
+        session
+            .as_client
+            .update_role_permission_groups(command.role, command.expected_version, command.groups)
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<B, DeleteRole> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: DeleteRole,
+    ) -> HandlerResult<DeleteRole> {
+        session
+            .as_client
+            .delete_role(command.role)
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
 #[async_trait]
 impl<B: Backend> Handler<B, Get> for ServerDispatcher {
     async fn handle(session: HandlerSession<'_, B>, command: Get) -> HandlerResult<Get> {
@@ -347,6 +738,29 @@ impl<B: Backend> Handler<B, ListHeaders> for ServerDispatcher {
     }
 }
 
+#[async_trait]
+impl<B: Backend> Handler<B, ListProjected> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: ListProjected,
+    ) -> HandlerResult<ListProjected> {
+        let database = session
+            .as_client
+            .database_without_schema(&command.list.database)
+            .await?;
+        database
+            .list_projected_from_collection(
+                command.list.ids,
+                command.list.order,
+                command.list.limit,
+                &command.list.collection,
+                &command.projection,
+            )
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
 #[async_trait]
 impl<B: Backend> Handler<B, Count> for ServerDispatcher {
     async fn handle(session: HandlerSession<'_, B>, command: Count) -> HandlerResult<Count> {
@@ -375,6 +789,7 @@ impl<B: Backend> Handler<B, Query> for ServerDispatcher {
                 command.order,
                 command.limit,
                 command.access_policy,
+                command.read_options,
             )
             .await
             .map_err(HandlerError::from)
@@ -398,6 +813,32 @@ impl<B: Backend> Handler<B, QueryWithDocs> for ServerDispatcher {
                 command.0.order,
                 command.0.limit,
                 command.0.access_policy,
+                command.0.read_options,
+            )
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<B, QueryWithProjectedDocs> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: QueryWithProjectedDocs,
+    ) -> HandlerResult<QueryWithProjectedDocs> {
+        let database = session
+            .as_client
+            .database_without_schema(&command.query.database)
+            .await?;
+        database
+            .query_by_name_with_projected_docs(
+                &command.query.view,
+                command.query.key,
+                command.query.order,
+                command.query.limit,
+                command.query.access_policy,
+                command.query.read_options,
+                &command.projection,
             )
             .await
             .map_err(HandlerError::from)
@@ -412,7 +853,12 @@ impl<B: Backend> Handler<B, Reduce> for ServerDispatcher {
             .database_without_schema(&command.database)
             .await?;
         database
-            .reduce_by_name(&command.view, command.key, command.access_policy)
+            .reduce_by_name(
+                &command.view,
+                command.key,
+                command.access_policy,
+                command.read_options,
+            )
             .await
             .map(Bytes::from)
             .map_err(HandlerError::from)
@@ -430,7 +876,36 @@ impl<B: Backend> Handler<B, ReduceGrouped> for ServerDispatcher {
             .database_without_schema(&command.0.database)
             .await?;
         database
-            .reduce_grouped_by_name(&command.0.view, command.0.key, command.0.access_policy)
+            .reduce_grouped_by_name(
+                &command.0.view,
+                command.0.key,
+                command.0.access_policy,
+                command.0.read_options,
+            )
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<B, Aggregate> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: Aggregate,
+    ) -> HandlerResult<Aggregate> {
+        let database = session
+            .as_client
+            .database_without_schema(&command.database)
+            .await?;
+        database
+            .aggregate_by_name(
+                &command.view,
+                command.key,
+                &command.aggregations,
+                command.grouping,
+                command.access_policy,
+                command.read_options,
+            )
             .await
             .map_err(HandlerError::from)
     }
@@ -442,6 +917,10 @@ impl<B: Backend> Handler<B, ApplyTransaction> for ServerDispatcher {
         session: HandlerSession<'_, B>,
         command: ApplyTransaction,
     ) -> HandlerResult<ApplyTransaction> {
+        session
+            .server
+            .payload_limits()
+            .check_transaction(&command.transaction)?;
         let database = session
             .as_client
             .database_without_schema(&command.database)
@@ -453,6 +932,132 @@ impl<B: Backend> Handler<B, ApplyTransaction> for ServerDispatcher {
     }
 }
 
+#[async_trait]
+impl<B: Backend> Handler<B, BeginTransaction> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: BeginTransaction,
+    ) -> HandlerResult<BeginTransaction> {
+        // Opening the database verifies that it exists before any operations
+        // are sent.
+        session
+            .as_client
+            .database_without_schema(&command.database)
+            .await?;
+        session
+            .client
+            .begin_transaction(
+                command.database,
+                session.as_client.session().and_then(|session| session.id),
+                session.server.payload_limits(),
+            )
+            .map_err(HandlerError::from)
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<B, PushTransactionOperations> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: PushTransactionOperations,
+    ) -> HandlerResult<PushTransactionOperations> {
+        session
+            .client
+            .push_transaction_operations(
+                command.transaction_id,
+                command.operations,
+                session.as_client.session().and_then(|session| session.id),
+                session.server.payload_limits(),
+            )
+            .map_err(HandlerError::from)
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<B, CommitTransaction> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: CommitTransaction,
+    ) -> HandlerResult<CommitTransaction> {
+        let (database, transaction) = session.client.take_transaction(
+            command.transaction_id,
+            session.as_client.session().and_then(|session| session.id),
+        )?;
+        let database = session.as_client.database_without_schema(&database).await?;
+        database
+            .apply_transaction(transaction)
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<B, CountView> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: CountView,
+    ) -> HandlerResult<CountView> {
+        let database = session
+            .as_client
+            .database_without_schema(&command.database)
+            .await?;
+        database
+            .count_by_name(
+                &command.view,
+                command.key,
+                command.access_policy,
+                command.read_options,
+            )
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<B, DocumentMappings> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: DocumentMappings,
+    ) -> HandlerResult<DocumentMappings> {
+        let database = session
+            .as_client
+            .database_without_schema(&command.database)
+            .await?;
+        database
+            .document_mappings_by_name(
+                &command.view,
+                command.document_id,
+                command.access_policy,
+                command.read_options,
+            )
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<B, QueryJoin> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: QueryJoin,
+    ) -> HandlerResult<QueryJoin> {
+        let database = session
+            .as_client
+            .database_without_schema(&command.database)
+            .await?;
+        database
+            .query_join_by_name(
+                &command.join,
+                command.key,
+                command.order,
+                command.limit,
+                command.access_policy,
+            )
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
 #[async_trait]
 impl<B: Backend> Handler<B, DeleteDocs> for ServerDispatcher {
     async fn handle(
@@ -504,6 +1109,29 @@ impl<B: Backend> Handler<B, LastTransactionId> for ServerDispatcher {
     }
 }
 
+#[async_trait]
+impl<B: Backend> Handler<B, WatchDocumentChanges> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: WatchDocumentChanges,
+    ) -> HandlerResult<WatchDocumentChanges> {
+        let database = session
+            .as_client
+            .database_without_schema(&command.database)
+            .await?;
+        let changes = database.watch_document_changes()?;
+
+        session
+            .client
+            .watch_document_changes(
+                command.database,
+                changes,
+                session.as_client.session().and_then(|session| session.id),
+            )
+            .map_err(HandlerError::from)
+    }
+}
+
 #[async_trait]
 impl<B: Backend> Handler<B, CreateSubscriber> for ServerDispatcher {
     async fn handle(
@@ -627,6 +1255,33 @@ impl<B: Backend> Handler<B, ExecuteKeyOperation> for ServerDispatcher {
     }
 }
 
+#[async_trait]
+impl<B: Backend> Handler<B, ExecuteNamedQuery> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: ExecuteNamedQuery,
+    ) -> HandlerResult<ExecuteNamedQuery> {
+        session.as_client.check_permission(
+            named_query_resource_name(&command.database, &command.name),
+            &BonsaiAction::Database(DatabaseAction::NamedQuery(NamedQueryAction::Execute)),
+        )?;
+        let query = session
+            .server
+            .named_query(&command.name)
+            .ok_or(bonsaidb_core::Error::NamedQueryNotFound(command.name))?;
+        // The query is executed with the server's permissions, allowing access
+        // to be granted to the query rather than the data it uses.
+        let database = session
+            .server
+            .database_without_schema(&command.database)
+            .await?;
+        query
+            .execute(database, &command.parameters)
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
 #[async_trait]
 impl<B: Backend> Handler<B, CompactCollection> for ServerDispatcher {
     async fn handle(
@@ -1,6 +1,7 @@
 use std::fmt::Display;
 use std::sync::Arc;
 
+use bonsaidb_core::api::{ApiName, QueryName};
 use bonsaidb_core::permissions::PermissionDenied;
 use bonsaidb_core::schema::InsertError;
 use bonsaidb_core::{schema, AnyError};
@@ -56,6 +57,16 @@ pub enum Error {
     /// An error occurred during tls signing.
     #[error("an error occurred during tls signing")]
     TlsSigningError,
+
+    /// An [`Api`](bonsaidb_core::api::Api) with this name has already been
+    /// registered.
+    #[error("an api named '{0}' is already registered")]
+    ApiAlreadyRegistered(ApiName),
+
+    /// A [`NamedQuery`](bonsaidb_core::api::NamedQuery) with this name has
+    /// already been registered.
+    #[error("a named query '{0}' is already registered")]
+    NamedQueryAlreadyRegistered(QueryName),
 }
 
 impl Error {
@@ -19,6 +19,12 @@
 /// Types for defining API handlers.
 pub mod api;
 mod backend;
+/// Types for exporting document changes to external systems.
+#[cfg(feature = "cdc")]
+pub mod cdc;
+/// Types for running a server as a node in a cluster.
+#[cfg(feature = "cluster")]
+pub mod cluster;
 /// Command-line interface for the server.
 #[cfg(feature = "cli")]
 pub mod cli;
@@ -34,11 +40,26 @@ pub use config::{
 };
 
 pub use self::backend::{Backend, BackendError, ConnectionHandling, NoBackend};
-pub use self::config::{BonsaiListenConfig, DefaultPermissions, ServerConfiguration};
+#[cfg(feature = "request-log")]
+pub use self::config::{RequestLogConfiguration, RequestLogDestination};
+#[cfg(feature = "webhooks")]
+pub use self::config::WebhookConfiguration;
+#[cfg(feature = "websockets")]
+pub use self::config::WebSocketConfiguration;
+pub use self::config::{
+    BonsaiListenConfig, CertificateIdentities, ClientCertificateAuthentication,
+    ClientCertificateResolver, DefaultPermissions, ListenerConfiguration, ListenerEndpoint,
+    LoginThrottle, RateLimit, ServerConfiguration,
+};
 pub use self::error::Error;
+#[cfg(feature = "admin-dashboard")]
+pub use self::server::AdminDashboard;
+#[cfg(feature = "request-log")]
+pub use self::server::RequestLogEntry;
 pub use self::server::{
-    ApplicationProtocols, ConnectedClient, CustomServer, HttpService, LockedClientDataGuard, Peer,
-    Server, ServerDatabase, StandardTcpProtocols, TcpService, Transport,
+    ApplicationProtocols, ConnectedClient, ConnectionUsage, CustomServer, HttpService,
+    LockedClientDataGuard, Peer, Server, ServerDatabase, StandardTcpProtocols, TcpService,
+    Transport,
 };
 
 #[cfg(test)]
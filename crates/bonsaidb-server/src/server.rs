@@ -1,24 +1,28 @@
 use std::collections::{hash_map, HashMap};
 use std::fmt::Debug;
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::ops::Deref;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
-use bonsaidb_core::admin::{Admin, ADMIN_DATABASE_NAME};
+use bonsaidb_core::admin::{Admin, PermissionGroup, Role, User, ADMIN_DATABASE_NAME};
 use bonsaidb_core::api;
-use bonsaidb_core::api::ApiName;
+use bonsaidb_core::api::{ApiName, QueryName};
 use bonsaidb_core::arc_bytes::serde::Bytes;
 use bonsaidb_core::connection::{
-    self, AsyncConnection, AsyncStorageConnection, HasSession, IdentityReference, Session,
-    SessionId,
+    self, AsyncConnection, AsyncStorageConnection, DatabaseQuery, HasSession, Identity,
+    IdentityReference, Session, SessionId, SessionInfo,
+};
+use bonsaidb_core::document::CollectionDocument;
+use bonsaidb_core::networking::{
+    self, Capabilities, CompressionConfig, Handshake, HandshakeResponse, Payload, PayloadLimits,
+    ServerEvent, ShuttingDown, CURRENT_PROTOCOL_REVISION, CURRENT_PROTOCOL_VERSION,
 };
-use bonsaidb_core::networking::{self, Payload, CURRENT_PROTOCOL_VERSION};
 use bonsaidb_core::permissions::bonsai::{bonsaidb_resource_name, BonsaiAction, ServerAction};
-use bonsaidb_core::permissions::Permissions;
+use bonsaidb_core::permissions::{Permissions, Statement};
 use bonsaidb_core::schema::{self, Nameable, NamedCollection, Schema};
 use bonsaidb_local::config::Builder;
 use bonsaidb_local::{AsyncStorage, Storage, StorageNonBlocking};
@@ -26,7 +30,7 @@ use bonsaidb_utils::fast_async_lock;
 use derive_where::derive_where;
 use fabruic::{self, CertificateChain, Endpoint, KeyPair, PrivateKey};
 use flume::Sender;
-use futures::{Future, StreamExt};
+use futures::{Future, FutureExt, StreamExt};
 use parking_lot::{Mutex, RwLock};
 use rustls::sign::CertifiedKey;
 use schema::SchemaName;
@@ -35,33 +39,88 @@ use signal_hook::consts::SIGQUIT;
 use signal_hook::consts::{SIGINT, SIGTERM};
 use tokio::sync::{oneshot, Notify};
 
-use crate::api::{AnyHandler, HandlerSession};
+use crate::api::{
+    AnyHandler, AnyNamedQuery, ApiRegistry, HandlerSession, Middleware, NamedQueryRegistry,
+};
 use crate::backend::ConnectionHandling;
 #[cfg(feature = "acme")]
 use crate::config::AcmeConfiguration;
+#[cfg(feature = "websockets")]
+use crate::config::WebSocketConfiguration;
+#[cfg(feature = "webhooks")]
+use crate::config::WebhookConfiguration;
 use crate::dispatch::{register_api_handlers, ServerDispatcher};
 use crate::error::Error;
 use crate::hosted::{Hosted, SerializablePrivateKey, TlsCertificate, TlsCertificatesByDomain};
 use crate::server::shutdown::{Shutdown, ShutdownState, ShutdownStateWatcher};
-use crate::{Backend, BackendError, BonsaiListenConfig, NoBackend, ServerConfiguration};
+use crate::{
+    Backend, BackendError, BonsaiListenConfig, ClientCertificateAuthentication,
+    ListenerConfiguration, ListenerEndpoint, NoBackend, RateLimit, ServerConfiguration,
+};
 
 #[cfg(feature = "acme")]
 pub mod acme;
+#[cfg(feature = "cdc")]
+mod cdc;
+#[cfg(feature = "cluster")]
+mod cluster;
 mod connected_client;
+#[cfg(feature = "admin-dashboard")]
+mod dashboard;
 mod database;
-
+mod idempotency;
+#[cfg_attr(not(feature = "password-hashing"), allow(dead_code))]
+pub(crate) mod login_throttle;
+pub(crate) mod proxy_protocol;
+pub(crate) mod rate_limit;
+#[cfg(feature = "request-log")]
+pub(crate) mod request_log;
 mod shutdown;
 mod tcp;
+#[cfg(all(unix, feature = "websockets"))]
+mod unix_socket;
+#[cfg(feature = "webhooks")]
+mod webhooks;
 #[cfg(feature = "websockets")]
 mod websockets;
 
+#[cfg(feature = "cluster")]
+pub(crate) use self::cluster::register_cluster_apis;
+#[cfg(feature = "cluster")]
+use self::cluster::Cluster;
 use self::connected_client::OwnedClient;
 pub use self::connected_client::{ConnectedClient, LockedClientDataGuard, Transport};
+#[cfg(feature = "admin-dashboard")]
+pub use self::dashboard::AdminDashboard;
 pub use self::database::ServerDatabase;
+use self::idempotency::AppliedTransactions;
+use self::login_throttle::LoginThrottler;
+use self::rate_limit::{RateLimiter, RequestPermit};
+#[cfg(feature = "request-log")]
+use self::request_log::RequestLog;
+#[cfg(feature = "request-log")]
+pub use self::request_log::RequestLogEntry;
 pub use self::tcp::{ApplicationProtocols, HttpService, Peer, StandardTcpProtocols, TcpService};
 
 static CONNECTED_CLIENT_ID_COUNTER: AtomicU32 = AtomicU32::new(0);
 
+/// The current connection usage of a server, as returned from
+/// [`CustomServer::connection_usage()`].
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct ConnectionUsage {
+    /// The number of connected clients.
+    pub connections: usize,
+    /// The maximum number of connected clients allowed, if limited.
+    pub max_connections: Option<usize>,
+    /// The number of connected clients authenticated as each user, keyed by
+    /// the user's id.
+    pub connections_by_user: HashMap<u64, usize>,
+    /// The maximum number of connected clients allowed to be authenticated as
+    /// the same user, if limited.
+    pub max_connections_per_user: Option<usize>,
+}
+
 /// A BonsaiDb server.
 #[derive(Debug)]
 #[derive_where(Clone)]
@@ -91,14 +150,39 @@ struct Data<B: Backend = NoBackend> {
     clients: RwLock<HashMap<u32, ConnectedClient<B>>>,
     request_processor: flume::Sender<ClientRequest<B>>,
     default_session: Session,
+    listeners: Vec<ListenerConfiguration>,
     client_simultaneous_request_limit: usize,
+    max_connections: Option<usize>,
+    max_connections_per_user: Option<usize>,
+    client_certificate_authentication: Option<ClientCertificateAuthentication>,
+    trusted_proxies: Vec<IpAddr>,
+    client_rate_limit: RateLimit,
+    user_rate_limit: RateLimit,
+    user_rate_limiters: Mutex<HashMap<u64, Arc<RateLimiter>>>,
+    login_throttler: Option<LoginThrottler>,
+    applied_transactions: AppliedTransactions,
+    network_compression: CompressionConfig,
+    max_decompressed_request_size: usize,
+    payload_limits: PayloadLimits,
     primary_tls_key: CachedCertifiedKey,
     primary_domain: String,
-    custom_apis: RwLock<HashMap<ApiName, Arc<dyn AnyHandler<B>>>>,
+    custom_apis: RwLock<ApiRegistry<B>>,
+    named_queries: NamedQueryRegistry<B>,
+    middleware: Vec<Arc<dyn Middleware<B>>>,
     #[cfg(feature = "acme")]
     acme: AcmeConfiguration,
     #[cfg(feature = "acme")]
     alpn_keys: AlpnKeys,
+    #[cfg(feature = "acme")]
+    acme_task_started: std::sync::atomic::AtomicBool,
+    #[cfg(feature = "webhooks")]
+    webhooks: WebhookConfiguration,
+    #[cfg(feature = "websockets")]
+    websockets: WebSocketConfiguration,
+    #[cfg(feature = "request-log")]
+    request_log: Option<RequestLog>,
+    #[cfg(feature = "cluster")]
+    cluster: Option<Cluster>,
     shutdown: Shutdown,
 }
 
@@ -132,29 +216,65 @@ impl<B: Backend> CustomServer<B> {
                 while let Ok(mut client_request) = request_receiver.recv_async().await {
                     let request = client_request.request.take().unwrap();
                     let session = client_request.session.clone();
+                    let deadline = client_request.deadline;
                     // TODO we should be able to upgrade a session-less Storage to one with a Session.
                     // The Session needs to be looked up from the client based on the request's session id.
-                    let result = match client_request.server.storage.assume_session(session) {
-                        Ok(storage) => {
-                            let client = HandlerSession {
-                                server: &client_request.server,
-                                client: &client_request.client,
-                                as_client: Self {
-                                    data: client_request.server.data.clone(),
-                                    storage,
-                                },
-                            };
-                            ServerDispatcher::dispatch_api_request(
-                                client,
-                                &request.name,
-                                request.value.unwrap(),
-                            )
-                            .await
-                            .map_err(bonsaidb_core::Error::from)
+                    let result = if deadline.map_or(false, |deadline| Instant::now() >= deadline) {
+                        // The request waited in the queue until the client
+                        // stopped waiting for it.
+                        Err(bonsaidb_core::Error::DeadlineExceeded)
+                    } else {
+                        client_request.server.storage.assume_session(session)
+                    };
+                    let name = request.name;
+                    let value = request.value;
+                    #[cfg(feature = "request-log")]
+                    let request_bytes = value.as_ref().map_or(0, |bytes| bytes.len());
+                    let response = async {
+                        match result {
+                            Ok(storage) => {
+                                let storage = match deadline {
+                                    Some(deadline) => storage.with_deadline(deadline),
+                                    None => storage,
+                                };
+                                let client = HandlerSession {
+                                    server: &client_request.server,
+                                    client: &client_request.client,
+                                    as_client: Self {
+                                        data: client_request.server.data.clone(),
+                                        storage,
+                                    },
+                                };
+                                ServerDispatcher::dispatch_api_request(
+                                    client,
+                                    &name,
+                                    value.unwrap(),
+                                )
+                                .await
+                                .map_err(bonsaidb_core::Error::from)
+                            }
+                            Err(err) => Err(err),
+                        }
+                    };
+                    #[cfg(feature = "request-log")]
+                    let result = match &client_request.server.data.request_log {
+                        Some(request_log) => {
+                            request_log
+                                .record(
+                                    &client_request.client,
+                                    &client_request.session,
+                                    &name,
+                                    client_request.received_at,
+                                    request_bytes,
+                                    response,
+                                )
+                                .await
                         }
-                        Err(err) => Err(err),
+                        None => response.await,
                     };
-                    drop(client_request.result_sender.send((request.name, result)));
+                    #[cfg(not(feature = "request-log"))]
+                    let result = response.await;
+                    drop(client_request.result_sender.send((name, result)));
                 }
             });
         }
@@ -165,6 +285,12 @@ impl<B: Backend> CustomServer<B> {
 
         let default_permissions = Permissions::from(configuration.default_permissions);
 
+        #[cfg(feature = "cluster")]
+        let cluster = match configuration.cluster {
+            Some(config) => Some(Cluster::open(config, &storage).await?),
+            None => None,
+        };
+
         let server = Self {
             storage,
             data: Arc::new(Data {
@@ -175,19 +301,59 @@ impl<B: Backend> CustomServer<B> {
                     permissions: default_permissions,
                     ..Session::default()
                 },
+                listeners: configuration.listeners,
                 client_simultaneous_request_limit: configuration.client_simultaneous_request_limit,
+                max_connections: configuration.max_connections,
+                max_connections_per_user: configuration.max_connections_per_user,
+                client_certificate_authentication: configuration.client_certificate_authentication,
+                trusted_proxies: configuration.trusted_proxies,
+                client_rate_limit: configuration.client_rate_limit,
+                user_rate_limit: configuration.user_rate_limit,
+                user_rate_limiters: Mutex::default(),
+                login_throttler: configuration.login_throttle.map(LoginThrottler::new),
+                applied_transactions: AppliedTransactions::new(
+                    configuration.transaction_idempotency_window,
+                    configuration.transaction_idempotency_limit,
+                ),
+                network_compression: configuration.network_compression,
+                max_decompressed_request_size: configuration.max_decompressed_request_size,
+                payload_limits: configuration.payload_limits,
                 primary_tls_key: CachedCertifiedKey::default(),
                 primary_domain: configuration.server_name,
                 custom_apis: parking_lot::RwLock::new(configuration.custom_apis),
+                named_queries: configuration.named_queries,
+                middleware: configuration.middleware,
                 #[cfg(feature = "acme")]
                 acme: configuration.acme,
                 #[cfg(feature = "acme")]
                 alpn_keys: AlpnKeys::default(),
+                #[cfg(feature = "acme")]
+                acme_task_started: std::sync::atomic::AtomicBool::new(false),
+                #[cfg(feature = "webhooks")]
+                webhooks: configuration.webhooks,
+                #[cfg(feature = "websockets")]
+                websockets: configuration.websockets,
+                #[cfg(feature = "request-log")]
+                request_log: configuration.request_log.map(RequestLog::new).transpose()?,
+                #[cfg(feature = "cluster")]
+                cluster,
                 shutdown: Shutdown::new(),
             }),
         };
 
         server.data.backend.initialize(&server).await?;
+
+        #[cfg(feature = "webhooks")]
+        server.spawn_webhook_task().await;
+
+        #[cfg(feature = "cluster")]
+        server.spawn_cluster_tasks().await;
+
+        #[cfg(feature = "cdc")]
+        for exporter in configuration.change_data_capture {
+            server.spawn_change_data_capture(exporter).await;
+        }
+
         Ok(server)
     }
 
@@ -220,6 +386,44 @@ impl<B: Backend> CustomServer<B> {
         }
     }
 
+    /// Returns the session assumed by connections that have not
+    /// authenticated. Its permissions are the server's default permissions.
+    #[must_use]
+    pub fn default_session(&self) -> &Session {
+        &self.data.default_session
+    }
+
+    /// Returns a new instance with `session` as the effective authentication
+    /// session. This call will only succeed if there is no current session.
+    pub fn assume_session(&self, session: Session) -> Result<Self, bonsaidb_core::Error> {
+        let storage = self.storage.assume_session(session)?;
+        Ok(Self {
+            data: self.data.clone(),
+            storage,
+        })
+    }
+
+    /// Opens the database named `name` without requiring its schema to be
+    /// known. Collections and views are accessed using their names, and
+    /// requests are validated against the schema the database was created
+    /// with.
+    pub async fn database_without_schema(
+        &self,
+        name: &str,
+    ) -> Result<ServerDatabase<B>, bonsaidb_core::Error> {
+        let db = self
+            .storage
+            .database_without_schema(name)
+            .await
+            .map_err(bonsaidb_core::Error::from)?;
+        #[cfg(feature = "request-log")]
+        request_log::record_database(name);
+        Ok(ServerDatabase {
+            server: self.clone(),
+            db,
+        })
+    }
+
     pub(crate) async fn hosted(&self) -> ServerDatabase<B> {
         let db = self.storage.database::<Hosted>("_hosted").await.unwrap();
         ServerDatabase {
@@ -230,7 +434,15 @@ impl<B: Backend> CustomServer<B> {
 
     pub(crate) fn custom_api_dispatcher(&self, name: &ApiName) -> Option<Arc<dyn AnyHandler<B>>> {
         let dispatchers = self.data.custom_apis.read();
-        dispatchers.get(name).cloned()
+        dispatchers.get(name)
+    }
+
+    pub(crate) fn named_query(&self, name: &QueryName) -> Option<Arc<dyn AnyNamedQuery<B>>> {
+        self.data.named_queries.get(name)
+    }
+
+    pub(crate) fn middleware(&self) -> &[Arc<dyn Middleware<B>>] {
+        &self.data.middleware
     }
 
     /// Installs an X.509 certificate used for general purpose connections.
@@ -365,17 +577,72 @@ impl<B: Backend> CustomServer<B> {
         }
     }
 
+    /// Listens for incoming client connections on all of the
+    /// [listeners](ServerConfiguration::listeners) configured for this server.
+    /// Does not return until the server shuts down or a listener fails. If no
+    /// listeners are configured, this function returns immediately.
+    pub async fn listen(&self) -> Result<(), Error> {
+        let listeners = self
+            .data
+            .listeners
+            .iter()
+            .map(|listener| {
+                let default_session = match &listener.default_permissions {
+                    Some(permissions) => Session {
+                        permissions: Permissions::from(permissions.clone()),
+                        ..Session::default()
+                    },
+                    None => self.data.default_session.clone(),
+                };
+                match &listener.endpoint {
+                    ListenerEndpoint::Bonsai(config) => self
+                        .listen_on_with_session(*config, default_session)
+                        .boxed(),
+                    #[cfg(feature = "websockets")]
+                    ListenerEndpoint::WebSockets {
+                        address,
+                        secure: false,
+                    } => self
+                        .listen_for_tcp_on_with_session(*address, (), default_session)
+                        .boxed(),
+                    #[cfg(feature = "websockets")]
+                    ListenerEndpoint::WebSockets {
+                        address,
+                        secure: true,
+                    } => self
+                        .listen_for_secure_tcp_on_with_session(*address, (), default_session)
+                        .boxed(),
+                    #[cfg(all(unix, feature = "websockets"))]
+                    ListenerEndpoint::UnixSocket(path) => self
+                        .listen_on_unix_socket_with_session(path, default_session)
+                        .boxed(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        futures::future::try_join_all(listeners).await?;
+        Ok(())
+    }
+
     /// Listens for incoming client connections. Does not return until the
     /// server shuts down.
     pub async fn listen_on(&self, config: impl Into<BonsaiListenConfig>) -> Result<(), Error> {
-        let config = config.into();
+        self.listen_on_with_session(config.into(), self.data.default_session.clone())
+            .await
+    }
+
+    async fn listen_on_with_session(
+        &self,
+        config: BonsaiListenConfig,
+        default_session: Session,
+    ) -> Result<(), Error> {
         let certificate = self.tls_certificate().await?;
         let keypair =
             KeyPair::from_parts(certificate.certificate_chain, certificate.private_key.0)?;
         let mut builder = Endpoint::builder();
         builder.set_protocols([CURRENT_PROTOCOL_VERSION.as_bytes().to_vec()]);
         builder.set_address(config.address);
-        builder.set_max_idle_timeout(None)?;
+        builder.set_max_idle_timeout(config.idle_timeout)?;
         builder.set_server_key_pair(Some(keypair));
         builder.set_reuse_address(config.reuse_address);
         let mut server = builder.build()?;
@@ -399,9 +666,13 @@ impl<B: Backend> CustomServer<B> {
         } {
             let connection = result.accept::<()>().await?;
             let task_self = self.clone();
+            let default_session = default_session.clone();
             tokio::spawn(async move {
                 let address = connection.remote_address();
-                if let Err(err) = task_self.handle_bonsai_connection(connection).await {
+                if let Err(err) = task_self
+                    .handle_bonsai_connection(connection, &default_session)
+                    .await
+                {
                     log::error!("[server] closing connection {}: {:?}", address, err);
                 }
             });
@@ -417,6 +688,193 @@ impl<B: Backend> CustomServer<B> {
         clients.values().cloned().collect()
     }
 
+    /// Returns the current number of connections and the configured connection
+    /// limits.
+    #[must_use]
+    pub fn connection_usage(&self) -> ConnectionUsage {
+        let clients = self.data.clients.read();
+        let mut connections_by_user = HashMap::new();
+        for user_id in clients
+            .values()
+            .flat_map(ConnectedClient::authenticated_users)
+        {
+            *connections_by_user.entry(user_id).or_default() += 1;
+        }
+
+        ConnectionUsage {
+            connections: clients.len(),
+            max_connections: self.data.max_connections,
+            connections_by_user,
+            max_connections_per_user: self.data.max_connections_per_user,
+        }
+    }
+
+    /// Associates `session` with `client`. If `session` is authenticated as a
+    /// user, [`ServerConfiguration::max_connections_per_user`] is enforced.
+    pub(crate) fn client_logged_in_as(
+        &self,
+        client: &ConnectedClient<B>,
+        session: Session,
+    ) -> Result<(), bonsaidb_core::Error> {
+        // Holding the write lock prevents other clients from authenticating
+        // while the quota is being checked.
+        let clients = self.data.clients.write();
+        if let (Some(max_connections), Some(Identity::User { id, .. })) =
+            (self.data.max_connections_per_user, session.identity())
+        {
+            if !client.authenticated_users().contains(id) {
+                let connections = clients
+                    .values()
+                    .filter(|client| client.authenticated_users().contains(id))
+                    .count();
+                if connections >= max_connections {
+                    return Err(bonsaidb_core::Error::ConnectionLimitReached);
+                }
+            }
+        }
+
+        client.logged_in_as(session);
+        Ok(())
+    }
+
+    /// Returns the throttler for failed password authentication attempts, if
+    /// [`ServerConfiguration::login_throttle`] is enabled.
+    pub(crate) fn login_throttler(&self) -> Option<&LoginThrottler> {
+        self.data.login_throttler.as_ref()
+    }
+
+    /// Clears the failed authentication attempts counted against `address`,
+    /// including any lockout from
+    /// [`LoginThrottle::lockout`](crate::LoginThrottle#structfield.lockout).
+    ///
+    /// To clear the attempts counted against a user, use
+    /// [`unlock_user()`](AsyncStorageConnection::unlock_user).
+    pub fn unlock_address(&self, address: IpAddr) {
+        if let Some(throttler) = self.login_throttler() {
+            throttler.unlock_address(address);
+        }
+    }
+
+    /// Authenticates using `authentication` on behalf of a client connected
+    /// from `address`. The attempt is subject to the server's
+    /// [login throttle](ServerConfiguration::login_throttle).
+    #[cfg(any(feature = "token-authentication", feature = "password-hashing"))]
+    pub(crate) async fn authenticate_throttled(
+        &self,
+        authentication: bonsaidb_core::connection::Authentication,
+        address: Option<IpAddr>,
+    ) -> Result<Self, bonsaidb_core::Error> {
+        let throttle = match self.login_throttler() {
+            Some(throttler) => {
+                // Users are counted by id, regardless of how the user was
+                // named in the request.
+                let user_id = match &authentication {
+                    #[cfg(feature = "password-hashing")]
+                    bonsaidb_core::connection::Authentication::Password { user, .. } => {
+                        let admin = self.storage.admin().await;
+                        user.id_async::<User, _>(&admin).await?
+                    }
+                    #[allow(unreachable_patterns)]
+                    _ => None,
+                };
+                Some((throttler, throttler.keys(user_id, address)))
+            }
+            None => None,
+        };
+        if let Some((throttler, keys)) = &throttle {
+            throttler.check(keys, Instant::now())?;
+        }
+
+        match self.authenticate(authentication).await {
+            Ok(authenticated) => {
+                if let Some((throttler, keys)) = &throttle {
+                    throttler.record_success(keys);
+                }
+                Ok(authenticated)
+            }
+            Err(err) => {
+                if let (Some((throttler, keys)), bonsaidb_core::Error::InvalidCredentials) =
+                    (&throttle, &err)
+                {
+                    throttler.record_failure(keys, Instant::now());
+                }
+                Err(err)
+            }
+        }
+    }
+
+    /// Authenticates the credentials in `authorization`, the value of an HTTP
+    /// `Authorization` header using the `Basic` scheme, on behalf of a client
+    /// connected from `address`. Requires feature `password-hashing`.
+    ///
+    /// The attempt is subject to the server's
+    /// [login throttle](ServerConfiguration::login_throttle). The returned
+    /// session is not tracked by the storage, and is meant to be used for a
+    /// single request.
+    #[cfg(feature = "password-hashing")]
+    pub async fn authenticate_basic_authorization(
+        &self,
+        authorization: &[u8],
+        address: Option<IpAddr>,
+    ) -> Result<Session, bonsaidb_core::Error> {
+        use base64::Engine;
+        use bonsaidb_core::connection::{Authentication, SensitiveString};
+
+        let (username, password) = std::str::from_utf8(authorization)
+            .ok()
+            .and_then(|authorization| authorization.strip_prefix("Basic "))
+            .and_then(|encoded| {
+                base64::engine::general_purpose::STANDARD
+                    .decode(encoded)
+                    .ok()
+            })
+            .and_then(|decoded| String::from_utf8(decoded).ok())
+            .and_then(|credentials| {
+                let (username, password) = credentials.split_once(':')?;
+                Some((username.to_string(), password.to_string()))
+            })
+            .ok_or(bonsaidb_core::Error::InvalidCredentials)?;
+
+        let authenticated = self
+            .authenticate_throttled(
+                Authentication::password(username, SensitiveString(password))?,
+                address,
+            )
+            .await?;
+        let session = authenticated.session().cloned().unwrap_or_default();
+        // Each request is authenticated independently, so the session does not
+        // need to be tracked by the storage.
+        drop(authenticated.logout().await);
+        Ok(Session {
+            id: None,
+            ..session
+        })
+    }
+
+    /// Returns the session a client authenticated by `client_certificate`
+    /// should use, if the certificate resolves to an identity.
+    ///
+    /// The session is recorded by the storage like sessions authenticated
+    /// using a password or token, which allows it to be listed and revoked.
+    /// It is revoked when the client disconnects.
+    pub(crate) async fn client_certificate_session(
+        &self,
+        client_certificate: Option<&fabruic::Certificate>,
+    ) -> Result<Option<Session>, bonsaidb_core::Error> {
+        let (Some(authentication), Some(certificate)) = (
+            &self.data.client_certificate_authentication,
+            client_certificate,
+        ) else {
+            return Ok(None);
+        };
+        let Some(identity) = authentication.resolver.resolve(certificate) else {
+            return Ok(None);
+        };
+
+        let authenticated = self.assume_identity(identity).await?;
+        Ok(authenticated.session().cloned())
+    }
+
     /// Sends a custom API response to all connected clients.
     pub fn broadcast<Api: api::Api>(&self, response: &Api::Response) {
         let clients = self.data.clients.read();
@@ -426,44 +884,126 @@ impl<B: Backend> CustomServer<B> {
         }
     }
 
-    async fn initialize_client(
+    /// Sends `event` to all connected clients that support server events.
+    /// Events describing databases or schemas are only sent to clients with a
+    /// session allowed to list them.
+    ///
+    /// Applications can use this to deliver their own notifications, such as
+    /// [`ServerEvent::QuotaWarning`].
+    pub fn send_server_event(&self, event: &ServerEvent) {
+        let clients = self.data.clients.read();
+        for client in clients.values() {
+            drop(client.send_server_event(event));
+        }
+    }
+
+    /// Deletes the database `name`. Clustered databases are deleted from every
+    /// node through the replicated log.
+    async fn delete_database_from_storage(&self, name: &str) -> Result<(), bonsaidb_core::Error> {
+        #[cfg(feature = "cluster")]
+        if let Some(cluster) = &self.data.cluster {
+            return cluster.delete_database(&self.storage, name).await;
+        }
+
+        self.storage.delete_database(name).await
+    }
+
+    /// Registers `DB` as a schema that can be used to create databases, and
+    /// notifies connected clients using [`ServerEvent::SchemaRegistered`].
+    ///
+    /// This can be called while the server is running, allowing schemas to be
+    /// added without restarting the server.
+    pub fn register_schema<DB: Schema>(&self) -> Result<(), Error> {
+        self.storage.as_blocking().register_schema::<DB>()?;
+        self.send_server_event(&ServerEvent::SchemaRegistered {
+            schema: DB::schema_name(),
+        });
+        Ok(())
+    }
+
+    /// Registers a newly connected client. If the connection is rejected and
+    /// an error is returned, the error should be sent to the client in
+    /// response to its first request.
+    ///
+    /// The client's unauthenticated session is `default_session`, which
+    /// contains the permissions of the listener the client connected through.
+    /// If `client_certificate` resolves to an identity, the client's
+    /// unauthenticated session is authenticated as that identity instead.
+    pub(crate) async fn initialize_client(
         &self,
         transport: Transport,
         address: SocketAddr,
         sender: Sender<(Option<SessionId>, ApiName, Bytes)>,
-    ) -> Option<OwnedClient<B>> {
-        if !self.data.default_session.allowed_to(
+        client_certificate: Option<&fabruic::Certificate>,
+        default_session: &Session,
+    ) -> Result<OwnedClient<B>, Option<bonsaidb_core::Error>> {
+        if !default_session.allowed_to(
             bonsaidb_resource_name(),
             &BonsaiAction::Server(ServerAction::Connect),
         ) {
-            return None;
+            return Err(None);
         }
 
+        let session = match self
+            .client_certificate_session(client_certificate)
+            .await
+            .map_err(Some)?
+        {
+            Some(session) => session,
+            None => default_session.clone(),
+        };
+
         let client = loop {
             let next_id = CONNECTED_CLIENT_ID_COUNTER.fetch_add(1, Ordering::SeqCst);
             let mut clients = self.data.clients.write();
+            if matches!(self.data.max_connections, Some(max) if clients.len() >= max) {
+                break None;
+            }
+            if let (Some(max_connections), Some(Identity::User { id, .. })) =
+                (self.data.max_connections_per_user, session.identity())
+            {
+                let connections = clients
+                    .values()
+                    .filter(|client| client.authenticated_users().contains(id))
+                    .count();
+                if connections >= max_connections {
+                    break None;
+                }
+            }
             if let hash_map::Entry::Vacant(e) = clients.entry(next_id) {
+                let rate_limiter = if self.data.client_rate_limit.is_unlimited() {
+                    None
+                } else {
+                    Some(RateLimiter::new(self.data.client_rate_limit))
+                };
                 let client = OwnedClient::new(
                     next_id,
                     address,
                     transport,
                     sender,
                     self.clone(),
-                    self.data.default_session.clone(),
+                    session.clone(),
+                    rate_limiter,
                 );
                 e.insert(client.clone());
-                break client;
+                break Some(client);
             }
         };
+        let Some(client) = client else {
+            if let Some(session_id) = session.id {
+                drop(self.storage.revoke_session(session_id).await);
+            }
+            return Err(Some(bonsaidb_core::Error::ConnectionLimitReached));
+        };
 
         match self.data.backend.client_connected(&client, self).await {
-            Ok(ConnectionHandling::Accept) => Some(client),
-            Ok(ConnectionHandling::Reject) => None,
+            Ok(ConnectionHandling::Accept) => Ok(client),
+            Ok(ConnectionHandling::Reject) => Err(None),
             Err(err) => {
                 log::error!(
                     "[server] Rejecting connection due to error in `client_connected`: {err:?}"
                 );
-                None
+                Err(None)
             }
         }
     }
@@ -475,6 +1015,11 @@ impl<B: Backend> CustomServer<B> {
         };
 
         if let Some(client) = removed_client {
+            // A session authenticated by a client certificate only exists for
+            // the lifetime of its connection.
+            if let Some(session_id) = client.session(None).and_then(|session| session.id) {
+                drop(self.storage.revoke_session(session_id).await);
+            }
             if let Err(err) = self.data.backend.client_disconnected(client, self).await {
                 log::error!("[server] Error in `client_disconnected`: {err:?}");
             }
@@ -484,6 +1029,7 @@ impl<B: Backend> CustomServer<B> {
     async fn handle_bonsai_connection(
         &self,
         mut connection: fabruic::Connection<()>,
+        default_session: &Session,
     ) -> Result<(), Error> {
         if let Some(incoming) = connection.next().await {
             let incoming = match incoming {
@@ -498,30 +1044,34 @@ impl<B: Backend> CustomServer<B> {
                 .accept::<networking::Payload, networking::Payload>()
                 .await
             {
-                Ok((sender, receiver)) => {
+                Ok((sender, mut receiver)) => {
                     let (api_response_sender, api_response_receiver) = flume::unbounded();
-                    if let Some(disconnector) = self
+                    let client = self
                         .initialize_client(
                             Transport::Bonsai,
                             connection.remote_address(),
                             api_response_sender,
+                            None,
+                            default_session,
                         )
-                        .await
-                    {
+                        .await;
+                    if let Ok(disconnector) = client {
                         let task_sender = sender.clone();
+                        let task_client = disconnector.clone();
                         tokio::spawn(async move {
                             while let Ok((session_id, name, bytes)) =
                                 api_response_receiver.recv_async().await
                             {
-                                if task_sender
-                                    .send(&Payload {
-                                        id: None,
-                                        session_id,
-                                        name,
-                                        value: Ok(bytes),
-                                    })
-                                    .is_err()
-                                {
+                                let mut payload = Payload {
+                                    id: None,
+                                    session_id,
+                                    name,
+                                    value: Ok(bytes),
+                                    compression: None,
+                                    deadline: None,
+                                };
+                                task_client.compress_payload(&mut payload);
+                                if task_sender.send(&payload).is_err() {
                                     break;
                                 }
                             }
@@ -537,6 +1087,28 @@ impl<B: Backend> CustomServer<B> {
                                 log::error!("[server] Error handling stream: {err:?}");
                             }
                         });
+                    } else if let Err(Some(err)) = client {
+                        // Respond to the client's first request with the reason
+                        // the connection was rejected rather than disconnecting
+                        // without explanation.
+                        if let Some(Ok(payload)) = receiver.next().await {
+                            drop(sender.send(&Payload {
+                                value: Err(err),
+                                compression: None,
+                                deadline: None,
+                                ..payload
+                            }));
+                        }
+                        drop(sender.finish());
+                        // Give the response a chance to be delivered before the
+                        // connection is closed.
+                        drop(
+                            tokio::time::timeout(Duration::from_secs(1), async {
+                                while receiver.next().await.is_some() {}
+                            })
+                            .await,
+                        );
+                        return Ok(());
                     } else {
                         log::error!("[server] Backend rejected connection.");
                         return Ok(());
@@ -584,7 +1156,11 @@ impl<B: Backend> CustomServer<B> {
                             return
                         },
                         state = shutdown.wait_for_shutdown() => {
-                            if matches!(state, ShutdownState::Shutdown | ShutdownState::GracefulShutdown) {
+                            // During a graceful shutdown, requests that have
+                            // already been received are still processed. The
+                            // request receiver is closed once the connection
+                            // stops reading new requests.
+                            if matches!(state, ShutdownState::Shutdown) {
                                 return
                             }
                         }
@@ -593,18 +1169,28 @@ impl<B: Backend> CustomServer<B> {
                 let session_id = payload.session_id;
                 let id = payload.id;
                 let task_sender = response_sender.clone();
+                let task_client = client.clone();
+                let payload_limits = self.data.payload_limits;
 
                 let notify = notify.clone();
                 let requests_in_queue = requests_in_queue.clone();
                 self.handle_request_through_worker(
                     payload,
                     move |name, value| async move {
-                        drop(task_sender.send(Payload {
+                        let value = value.and_then(|value| {
+                            payload_limits.check_response(value.len())?;
+                            Ok(value)
+                        });
+                        let mut response = Payload {
                             session_id,
                             id,
                             name,
                             value,
-                        }));
+                            compression: None,
+                            deadline: None,
+                        };
+                        task_client.compress_payload(&mut response);
+                        drop(task_sender.send(response));
 
                         requests_in_queue.fetch_sub(1, Ordering::SeqCst);
 
@@ -625,14 +1211,38 @@ impl<B: Backend> CustomServer<B> {
         R: Future<Output = Result<(), Error>> + Send,
     >(
         &self,
-        request: Payload,
+        mut request: Payload,
         callback: F,
         client: ConnectedClient<B>,
     ) -> Result<(), Error> {
+        if let Err(err) = request.decompress(self.data.max_decompressed_request_size) {
+            return callback(request.name, Err(err)).await;
+        }
+        let session = match client.session(request.session_id) {
+            Some(session) => session,
+            // The session has been logged out or revoked. Falling back to the
+            // default session would change the permissions the request is
+            // executed with.
+            None if request.session_id.is_some() => {
+                return callback(request.name, Err(bonsaidb_core::Error::SessionExpired)).await;
+            }
+            None => self.data.default_session.clone(),
+        };
+        // Expiration is checked before the request is rate limited or queued,
+        // which ensures no request is executed using an expired session
+        // regardless of which API it calls.
+        if let Some(session_id) = session.id {
+            if let Err(err) = self.storage.check_session(session_id) {
+                client.log_out(session_id);
+                return callback(request.name, Err(err)).await;
+            }
+        }
+        let request_bytes = request.value.as_ref().map_or(0, |bytes| bytes.len());
+        let permit = match self.begin_request(&client, &session, request_bytes) {
+            Ok(permit) => permit,
+            Err(err) => return callback(request.name, Err(err)).await,
+        };
         let (result_sender, result_receiver) = oneshot::channel();
-        let session = client
-            .session(request.session_id)
-            .unwrap_or_else(|| self.data.default_session.clone());
         self.data
             .request_processor
             .send(ClientRequest::<B>::new(
@@ -652,11 +1262,68 @@ impl<B: Backend> CustomServer<B> {
             // response to a request than the original requestor, so this can be
             // safely unwrapped.
             callback(name, result).await?;
+            drop(permit);
             Result::<(), Error>::Ok(())
         });
         Ok(())
     }
 
+    /// Returns the limits on the size of the data exchanged with clients.
+    pub(crate) fn payload_limits(&self) -> &PayloadLimits {
+        &self.data.payload_limits
+    }
+
+    /// Records the protocol information `client` sent in `handshake`, and
+    /// chooses the compression used for the remainder of its connection.
+    pub(crate) fn handshake(
+        &self,
+        client: &ConnectedClient<B>,
+        handshake: &Handshake,
+    ) -> HandshakeResponse {
+        let compression = self
+            .data
+            .network_compression
+            .negotiate(&handshake.compression);
+        client.handshake_completed(
+            handshake.revision,
+            handshake.capabilities,
+            compression,
+            self.data.network_compression.threshold,
+        );
+        HandshakeResponse {
+            revision: CURRENT_PROTOCOL_REVISION,
+            capabilities: Capabilities::current(),
+            compression,
+        }
+    }
+
+    /// Applies the client and user rate limits to a request of `request_bytes`
+    /// made using `session`.
+    pub(crate) fn begin_request(
+        &self,
+        client: &ConnectedClient<B>,
+        session: &Session,
+        request_bytes: usize,
+    ) -> Result<RequestPermit, bonsaidb_core::Error> {
+        let user_rate_limiter = match session.identity() {
+            Some(Identity::User { id, .. }) if !self.data.user_rate_limit.is_unlimited() => {
+                let mut limiters = self.data.user_rate_limiters.lock();
+                Some(
+                    limiters
+                        .entry(*id)
+                        .or_insert_with(|| RateLimiter::new(self.data.user_rate_limit))
+                        .clone(),
+                )
+            }
+            _ => None,
+        };
+
+        RequestPermit::acquire(
+            client.rate_limiter().into_iter().chain(&user_rate_limiter),
+            request_bytes,
+        )
+    }
+
     async fn handle_stream(
         &self,
         client: OwnedClient<B>,
@@ -678,7 +1345,9 @@ impl<B: Backend> CustomServer<B> {
                                 break 'stream
                             }
                             shutdown = shutdown.wait_for_shutdown() => {
-                                if matches!(shutdown, ShutdownState::Shutdown | ShutdownState::GracefulShutdown) {
+                                // Responses to in-flight requests are still
+                                // delivered during a graceful shutdown.
+                                if matches!(shutdown, ShutdownState::Shutdown) {
                                     break 'stream
                                 }
                             }
@@ -693,6 +1362,7 @@ impl<B: Backend> CustomServer<B> {
 
         let (request_sender, request_receiver) =
             flume::bounded::<Payload>(self.data.client_simultaneous_request_limit);
+        let connected_client = client.clone();
         let task_self = self.clone();
         tokio::spawn({
             let shutdown = shutdown.clone();
@@ -720,6 +1390,9 @@ impl<B: Backend> CustomServer<B> {
 
                         return Ok(());
                     }
+                    () = connected_client.disconnect_requested() => {
+                        return Ok(());
+                    }
                     shutdown = shutdown.wait_for_shutdown() => {
                         if matches!(shutdown, ShutdownState::Shutdown | ShutdownState::GracefulShutdown) {
                             return Ok(());
@@ -731,17 +1404,44 @@ impl<B: Backend> CustomServer<B> {
         }
     }
 
-    /// Shuts the server down. If a `timeout` is provided, the server will stop
-    /// accepting new connections and attempt to respond to any outstanding
-    /// requests already being processed. After the `timeout` has elapsed or if
-    /// no `timeout` was provided, the server is forcefully shut down.
+    /// Shuts the server down. If a `timeout` is provided, the server will:
+    ///
+    /// - stop accepting new connections and requests,
+    /// - notify connected clients using the
+    ///   [`ShuttingDown`](bonsaidb_core::networking::ShuttingDown) API and
+    ///   [`ServerEvent::ShuttingDown`],
+    /// - respond to any outstanding requests already being processed, and
+    /// - wait for background tasks, such as view indexing and compaction, to
+    ///   complete.
+    ///
+    /// After the `timeout` has elapsed or if no `timeout` was provided, the
+    /// server is forcefully shut down. In either case, any buffered key-value
+    /// store changes are persisted before this function returns.
     pub async fn shutdown(&self, timeout: Option<Duration>) -> Result<(), Error> {
         if let Some(timeout) = timeout {
+            let started_at = Instant::now();
+            self.broadcast::<ShuttingDown>(&ShuttingDown {
+                grace_period: timeout,
+            });
+            self.send_server_event(&ServerEvent::ShuttingDown {
+                grace_period: timeout,
+            });
             self.data.shutdown.graceful_shutdown(timeout).await;
+
+            let remaining = timeout.saturating_sub(started_at.elapsed());
+            if !self
+                .storage
+                .wait_for_background_tasks(Some(remaining))
+                .await?
+            {
+                log::warn!("[server] background tasks did not complete before shutting down");
+            }
         } else {
             self.data.shutdown.shutdown().await;
         }
 
+        self.storage.flush().await?;
+
         Ok(())
     }
 
@@ -831,6 +1531,9 @@ impl<B: Backend> Deref for CustomServer<B> {
 #[derive(Debug)]
 struct ClientRequest<B: Backend> {
     request: Option<Payload>,
+    #[cfg(feature = "request-log")]
+    received_at: Instant,
+    deadline: Option<Instant>,
     client: ConnectedClient<B>,
     session: Session,
     server: CustomServer<B>,
@@ -845,7 +1548,13 @@ impl<B: Backend> ClientRequest<B> {
         session: Session,
         result_sender: oneshot::Sender<(ApiName, Result<Bytes, bonsaidb_core::Error>)>,
     ) -> Self {
+        let received_at = Instant::now();
         Self {
+            deadline: request
+                .deadline
+                .and_then(|deadline| received_at.checked_add(deadline)),
+            #[cfg(feature = "request-log")]
+            received_at,
             request: Some(request),
             server,
             client,
@@ -876,9 +1585,23 @@ impl<B: Backend> AsyncStorageConnection for CustomServer<B> {
         schema: SchemaName,
         only_if_needed: bool,
     ) -> Result<(), bonsaidb_core::Error> {
-        self.storage
-            .create_database_with_schema(name, schema, only_if_needed)
+        // The database is always created explicitly so that clients are only
+        // notified when a new database exists.
+        match self
+            .storage
+            .create_database_with_schema(name, schema.clone(), false)
             .await
+        {
+            Ok(()) => {
+                self.send_server_event(&ServerEvent::DatabaseCreated {
+                    name: name.to_string(),
+                    schema,
+                });
+                Ok(())
+            }
+            Err(bonsaidb_core::Error::DatabaseNameAlreadyTaken(_)) if only_if_needed => Ok(()),
+            Err(err) => Err(err),
+        }
     }
 
     async fn database<DB: Schema>(
@@ -893,11 +1616,18 @@ impl<B: Backend> AsyncStorageConnection for CustomServer<B> {
     }
 
     async fn delete_database(&self, name: &str) -> Result<(), bonsaidb_core::Error> {
-        self.storage.delete_database(name).await
+        self.delete_database_from_storage(name).await?;
+        self.send_server_event(&ServerEvent::DatabaseDeleted {
+            name: name.to_string(),
+        });
+        Ok(())
     }
 
-    async fn list_databases(&self) -> Result<Vec<connection::Database>, bonsaidb_core::Error> {
-        self.storage.list_databases().await
+    async fn list_databases_matching(
+        &self,
+        query: DatabaseQuery,
+    ) -> Result<Vec<connection::Database>, bonsaidb_core::Error> {
+        self.storage.list_databases_matching(query).await
     }
 
     async fn list_available_schemas(&self) -> Result<Vec<SchemaName>, bonsaidb_core::Error> {
@@ -915,6 +1645,71 @@ impl<B: Backend> AsyncStorageConnection for CustomServer<B> {
         self.storage.delete_user(user).await
     }
 
+    async fn list_users(&self) -> Result<Vec<CollectionDocument<User>>, bonsaidb_core::Error> {
+        self.storage.list_users().await
+    }
+
+    async fn user_by_name(
+        &self,
+        username: &str,
+    ) -> Result<Option<CollectionDocument<User>>, bonsaidb_core::Error> {
+        self.storage.user_by_name(username).await
+    }
+
+    async fn disable_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let admin = self.storage.admin().await;
+        let user_id = user
+            .name()?
+            .id_async::<User, _>(&admin)
+            .await?
+            .ok_or(bonsaidb_core::Error::UserNotFound)?;
+        self.storage.disable_user(user_id).await?;
+
+        // Sessions cached by connected clients must not outlive the user
+        // being disabled. Clients that were authenticated as the user are
+        // disconnected.
+        let clients = self.data.clients.read();
+        for client in clients.values() {
+            if client.log_out_user(user_id) {
+                client.disconnect();
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn enable_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<(), bonsaidb_core::Error> {
+        self.storage.enable_user(user).await
+    }
+
+    async fn unlock_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let user = user.name()?.into_owned();
+        self.storage.unlock_user(user.clone()).await?;
+        if let Some(throttler) = self.login_throttler() {
+            let admin = self.storage.admin().await;
+            if let Some(id) = user.id_async::<User, _>(&admin).await? {
+                throttler.unlock_user(id);
+            }
+        }
+        Ok(())
+    }
+
+    async fn effective_permissions<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<Permissions, bonsaidb_core::Error> {
+        self.storage.effective_permissions(user).await
+    }
+
     #[cfg(feature = "password-hashing")]
     async fn set_user_password<'user, U: Nameable<'user, u64> + Send + Sync>(
         &self,
@@ -947,6 +1742,17 @@ impl<B: Backend> AsyncStorageConnection for CustomServer<B> {
         })
     }
 
+    async fn as_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<Self::Authenticated, bonsaidb_core::Error> {
+        let storage = self.storage.as_user(user).await?;
+        Ok(Self {
+            data: self.data.clone(),
+            storage,
+        })
+    }
+
     async fn add_permission_group_to_user<
         'user,
         'group,
@@ -1002,6 +1808,126 @@ impl<B: Backend> AsyncStorageConnection for CustomServer<B> {
     ) -> Result<(), bonsaidb_core::Error> {
         self.storage.remove_role_from_user(user, role).await
     }
+
+    async fn create_permission_group(
+        &self,
+        name: &str,
+        statements: Vec<Statement>,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        self.storage.create_permission_group(name, statements).await
+    }
+
+    async fn list_permission_groups(
+        &self,
+    ) -> Result<Vec<CollectionDocument<PermissionGroup>>, bonsaidb_core::Error> {
+        self.storage.list_permission_groups().await
+    }
+
+    async fn set_permission_group_statements<'group, G: Nameable<'group, u64> + Send + Sync>(
+        &self,
+        permission_group: G,
+        statements: Vec<Statement>,
+    ) -> Result<(), bonsaidb_core::Error> {
+        self.storage
+            .set_permission_group_statements(permission_group, statements)
+            .await
+    }
+
+    async fn update_permission_group_statements<
+        'group,
+        G: Nameable<'group, u64> + Send + Sync,
+    >(
+        &self,
+        permission_group: G,
+        expected_version: u64,
+        statements: Vec<Statement>,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        self.storage
+            .update_permission_group_statements(permission_group, expected_version, statements)
+            .await
+    }
+
+    async fn delete_permission_group<'group, G: Nameable<'group, u64> + Send + Sync>(
+        &self,
+        permission_group: G,
+    ) -> Result<(), bonsaidb_core::Error> {
+        self.storage.delete_permission_group(permission_group).await
+    }
+
+    async fn create_role(&self, name: &str) -> Result<u64, bonsaidb_core::Error> {
+        self.storage.create_role(name).await
+    }
+
+    async fn list_roles(&self) -> Result<Vec<CollectionDocument<Role>>, bonsaidb_core::Error> {
+        self.storage.list_roles().await
+    }
+
+    async fn add_permission_group_to_role<
+        'role,
+        'group,
+        R: Nameable<'role, u64> + Send + Sync,
+        G: Nameable<'group, u64> + Send + Sync,
+    >(
+        &self,
+        role: R,
+        permission_group: G,
+    ) -> Result<(), bonsaidb_core::Error> {
+        self.storage
+            .add_permission_group_to_role(role, permission_group)
+            .await
+    }
+
+    async fn remove_permission_group_from_role<
+        'role,
+        'group,
+        R: Nameable<'role, u64> + Send + Sync,
+        G: Nameable<'group, u64> + Send + Sync,
+    >(
+        &self,
+        role: R,
+        permission_group: G,
+    ) -> Result<(), bonsaidb_core::Error> {
+        self.storage
+            .remove_permission_group_from_role(role, permission_group)
+            .await
+    }
+
+    async fn update_role_permission_groups<'role, R: Nameable<'role, u64> + Send + Sync>(
+        &self,
+        role: R,
+        expected_version: u64,
+        permission_groups: Vec<u64>,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        self.storage
+            .update_role_permission_groups(role, expected_version, permission_groups)
+            .await
+    }
+
+    async fn delete_role<'role, R: Nameable<'role, u64> + Send + Sync>(
+        &self,
+        role: R,
+    ) -> Result<(), bonsaidb_core::Error> {
+        self.storage.delete_role(role).await
+    }
+
+    async fn list_sessions<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<Vec<SessionInfo>, bonsaidb_core::Error> {
+        self.storage.list_sessions(user).await
+    }
+
+    async fn revoke_session(&self, session_id: SessionId) -> Result<(), bonsaidb_core::Error> {
+        self.storage.revoke_session(session_id).await?;
+
+        // Remove the session from whichever client was using it.
+        let clients = self.data.clients.read();
+        for client in clients.values() {
+            client.log_out(session_id);
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Default)]
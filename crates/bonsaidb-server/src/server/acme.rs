@@ -1,3 +1,4 @@
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -93,40 +94,71 @@ impl<B: Backend> AcmeCache for CustomServer<B> {
 }
 
 impl<B: Backend> CustomServer<B> {
-    pub(crate) async fn update_acme_certificates(&self) -> Result<(), Error> {
-        loop {
-            {
-                let key = self.data.primary_tls_key.lock().clone();
-                while async_acme::rustls_helper::duration_until_renewal_attempt(key.as_deref(), 0)
-                    > Duration::from_secs(24 * 60 * 60 * 14)
-                {
-                    tokio::time::sleep(Duration::from_secs(60 * 60)).await;
+    /// Starts the task that keeps the server's certificate up-to-date, unless
+    /// it is already running. Multiple secure listeners share the same
+    /// certificate, so only one task is needed per server.
+    pub(crate) fn spawn_acme_task(&self) {
+        if self.data.acme_task_started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let task_self = self.clone();
+        tokio::task::spawn(async move {
+            let acme = &task_self.data.acme;
+            let mut retry_delay = acme.initial_retry_delay;
+            loop {
+                match task_self.update_acme_certificates().await {
+                    Ok(()) => retry_delay = acme.initial_retry_delay,
+                    Err(err) => {
+                        // A failed order shouldn't stop renewals for the
+                        // lifetime of the server.
+                        log::error!(
+                            "[server] acme order error, retrying in {retry_delay:?}: {err}"
+                        );
+                        tokio::time::sleep(retry_delay).await;
+                        retry_delay = (retry_delay * 2).min(acme.max_retry_delay);
+                    }
                 }
             }
+        });
+    }
 
-            log::info!(
-                "requesting new tls certificate for {}",
-                self.data.primary_domain
-            );
-            let domains = vec![self.data.primary_domain.clone()];
-            async_acme::rustls_helper::order(
-                |domain, key| {
-                    let mut auth_keys = self.data.alpn_keys.lock();
-                    auth_keys.insert(domain, Arc::new(key));
-                    Ok(())
-                },
-                &self.data.acme.directory,
-                &domains,
-                Some(self),
-                &self
-                    .data
-                    .acme
-                    .contact_email
-                    .iter()
-                    .cloned()
-                    .collect::<Vec<_>>(),
-            )
-            .await?;
+    /// Waits until the current certificate is due for renewal, and then orders
+    /// a new certificate.
+    async fn update_acme_certificates(&self) -> Result<(), Error> {
+        {
+            let key = self.data.primary_tls_key.lock().clone();
+            while async_acme::rustls_helper::duration_until_renewal_attempt(key.as_deref(), 0)
+                > Duration::from_secs(24 * 60 * 60 * 14)
+            {
+                tokio::time::sleep(Duration::from_secs(60 * 60)).await;
+            }
         }
+
+        log::info!(
+            "requesting new tls certificate for {}",
+            self.data.primary_domain
+        );
+        let domains = vec![self.data.primary_domain.clone()];
+        async_acme::rustls_helper::order(
+            |domain, key| {
+                let mut auth_keys = self.data.alpn_keys.lock();
+                auth_keys.insert(domain, Arc::new(key));
+                Ok(())
+            },
+            &self.data.acme.directory,
+            &domains,
+            Some(self),
+            &self
+                .data
+                .acme
+                .contact_email
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>(),
+        )
+        .await?;
+
+        Ok(())
     }
 }
@@ -0,0 +1,143 @@
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use bonsaidb_core::connection::{AsyncConnection, AsyncLowLevelConnection};
+use bonsaidb_core::keyvalue::AsyncKeyValue;
+use bonsaidb_local::AsyncStorage;
+
+use super::{Data, ShutdownStateWatcher};
+use crate::cdc::{ChangeDataCapture, ChangeEvent, DocumentChange};
+use crate::{Backend, CustomServer, Error};
+
+/// The key-value namespace in the admin database that stores the id of the
+/// next transaction to export for each [`ChangeDataCapture`] exporter.
+const CDC_NAMESPACE: &str = "_bonsaidb.cdc";
+
+/// The delay before retrying a transaction that failed to export for the
+/// first time.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+impl<B: Backend> CustomServer<B> {
+    /// Spawns the task that exports the changes for `exporter`. The task exits
+    /// when the server is shut down or dropped.
+    pub(crate) async fn spawn_change_data_capture(&self, exporter: ChangeDataCapture) {
+        let Some(shutdown) = self.data.shutdown.watcher().await else {
+            return;
+        };
+        tokio::task::spawn(run_exporter(
+            Arc::downgrade(&self.data),
+            self.storage.clone(),
+            exporter,
+            shutdown,
+        ));
+    }
+}
+
+async fn run_exporter<B: Backend>(
+    data: Weak<Data<B>>,
+    storage: AsyncStorage,
+    exporter: ChangeDataCapture,
+    mut shutdown: ShutdownStateWatcher,
+) {
+    let mut delay = Duration::ZERO;
+    let mut retry_delay = INITIAL_RETRY_DELAY;
+    loop {
+        tokio::select! {
+            _ = shutdown.wait_for_shutdown() => break,
+            _ = tokio::time::sleep(delay) => {}
+        }
+
+        // Only hold a reference to the server while exporting, which allows
+        // the server to be dropped without being shut down.
+        let Some(data) = data.upgrade() else {
+            break;
+        };
+        let server = CustomServer {
+            data,
+            storage: storage.clone(),
+        };
+        delay = match export_pending(&server, &exporter).await {
+            Ok(exported) => {
+                retry_delay = INITIAL_RETRY_DELAY;
+                if exported < exporter.batch_size {
+                    exporter.poll_interval
+                } else {
+                    // There may be more transactions waiting to be exported.
+                    Duration::ZERO
+                }
+            }
+            Err(err) => {
+                log::error!("Error exporting changes for {}: {err}", exporter.name);
+                let delay = retry_delay;
+                retry_delay = retry_delay.saturating_mul(2).min(exporter.max_retry_delay);
+                delay
+            }
+        };
+    }
+}
+
+/// Publishes the transactions executed since the exporter's cursor, returning
+/// the number of transactions read from the log.
+async fn export_pending<B: Backend>(
+    server: &CustomServer<B>,
+    exporter: &ChangeDataCapture,
+) -> Result<u32, Error> {
+    let admin = server.admin().await;
+    let cursors = admin.with_key_namespace(CDC_NAMESPACE);
+    let database = server.database_without_schema(&exporter.database).await?;
+    let next_transaction_id = if let Some(id) = cursors.get_key(&exporter.name).into_u64().await? {
+        id
+    } else if exporter.from_beginning {
+        0
+    } else {
+        let id = database.last_transaction_id().await?.map_or(0, |id| id + 1);
+        cursors.set_numeric_key(&exporter.name, id).await?;
+        return Ok(0);
+    };
+
+    let transactions = database
+        .list_executed_transactions(Some(next_transaction_id), Some(exporter.batch_size))
+        .await?;
+    let read = u32::try_from(transactions.len()).unwrap_or(u32::MAX);
+    for transaction in transactions {
+        if let Some(documents) = transaction.changes.documents() {
+            let mut changes = Vec::new();
+            for (collection, changed) in documents.iter() {
+                if !exporter.exports(collection) {
+                    continue;
+                }
+                let contents = if changed.deleted {
+                    None
+                } else {
+                    database
+                        .get_from_collection(changed.id.clone(), collection)
+                        .await?
+                        .map(|document| document.contents)
+                };
+                changes.push(DocumentChange {
+                    collection: collection.clone(),
+                    id: changed.id.clone(),
+                    deleted: changed.deleted,
+                    contents,
+                });
+            }
+
+            if !changes.is_empty() {
+                exporter
+                    .sink
+                    .publish(&ChangeEvent {
+                        database: exporter.database.clone(),
+                        transaction_id: transaction.id,
+                        changes,
+                    })
+                    .await?;
+            }
+        }
+
+        cursors
+            .set_numeric_key(&exporter.name, transaction.id + 1)
+            .await?;
+    }
+
+    Ok(read)
+}
@@ -0,0 +1,2302 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use bonsaidb_client::AsyncClient;
+use bonsaidb_core::admin::ADMIN_DATABASE_NAME;
+use bonsaidb_core::api::{Api, ApiName};
+use bonsaidb_core::arc_bytes::serde::Bytes;
+use bonsaidb_core::connection::{
+    AsyncLowLevelConnection, AsyncStorageConnection, HasSchema, HasSession, LowLevelConnection,
+    Range, Sort, StorageConnection, ViewReadOptions,
+};
+use bonsaidb_core::document::OwnedDocument;
+use bonsaidb_core::networking::{
+    Aggregate, CountView, DocumentMappings, Query, QueryWithDocs, QueryWithProjectedDocs, Reduce,
+    ReduceGrouped,
+};
+use bonsaidb_core::permissions::bonsai::{database_resource_name, BonsaiAction, ServerAction};
+use bonsaidb_core::schema::{CollectionName, SchemaName, Schematic};
+use bonsaidb_core::transaction::{Command, OperationResult, Transaction, WriteQuorum};
+use bonsaidb_local::nebari::tree::Unversioned;
+use bonsaidb_local::{AsyncDatabase, AsyncStorage, Database, Storage};
+use parking_lot::Mutex;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+
+use super::idempotency::Requester;
+use super::{Data, ShutdownStateWatcher};
+use crate::api::{Handler, HandlerResult, HandlerSession};
+use crate::cluster::{ClusterConfiguration, ClusterStatus, MemberStatus};
+use crate::{Backend, CustomServer, Error, ServerConfiguration};
+
+/// The name of the raw tree in the admin database that stores this node's
+/// term, vote, and replicated log.
+const CLUSTER_TREE: &str = "cluster";
+
+/// The key storing the current term and the candidate voted for in it.
+const VOTE_KEY: &[u8] = b"vote";
+
+/// The key storing the index of the last log entry applied to the databases.
+/// Each transaction also stores its index in the database it is applied to,
+/// which prevents it from being applied again if this key wasn't updated.
+const APPLIED_KEY: &[u8] = b"applied";
+
+/// The key storing the index and term of the last entry removed from the log
+/// by compacting it.
+const SNAPSHOT_KEY: &[u8] = b"snapshot";
+
+/// The key that is present while a snapshot from the leader is being
+/// installed. The clustered databases are incomplete until the installation
+/// finishes.
+const INSTALLING_KEY: &[u8] = b"installing";
+
+/// The prefix of the keys storing log entries, which are followed by the
+/// entry's big-endian index.
+const ENTRY_PREFIX: &[u8] = b"entry.";
+
+/// The source of the sequences that replicated transactions are applied with.
+/// See [`Database::apply_sequenced_transaction()`].
+const SEQUENCE_SOURCE: &str = "cluster";
+
+/// How often a node checks whether the log entries it is waiting for have been
+/// replicated or applied.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The role a node has in the current term.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Role {
+    Follower,
+    Candidate,
+    Leader,
+}
+
+/// An entry in the replicated log.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+struct LogEntry {
+    /// The term of the leader that appended this entry.
+    term: u64,
+    /// The command to apply, or None for the entry each leader appends when
+    /// it is elected.
+    command: Option<ReplicatedCommand>,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+enum ReplicatedCommand {
+    Transaction(ReplicatedTransaction),
+    DeleteDatabase(String),
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+struct ReplicatedTransaction {
+    database: String,
+    schema: SchemaName,
+    transaction: Transaction,
+}
+
+/// The leader's knowledge of a member's log.
+#[derive(Clone, Copy, Debug)]
+struct FollowerProgress {
+    /// The index of the next entry to send to the member.
+    next_index: u64,
+    /// The highest index known to be stored by the member.
+    match_index: u64,
+    /// When the most recent request the member responded to was sent.
+    acknowledged: Option<Instant>,
+    /// True if the member must install a snapshot before it can apply
+    /// entries, because a snapshot it was installing was interrupted.
+    requires_snapshot: bool,
+    /// The index of the snapshot being sent to the member, and the position
+    /// of the next document to send.
+    snapshot_offset: Option<(u64, u64)>,
+}
+
+/// A copy of the clustered databases after the entry at `index` was applied,
+/// which the leader sends to members that are missing compacted entries.
+#[derive(Debug)]
+struct Snapshot {
+    index: u64,
+    term: u64,
+    databases: Vec<(String, SchemaName)>,
+    documents: Vec<SnapshotDocument>,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+struct SnapshotDocument {
+    database: String,
+    collection: CollectionName,
+    document: OwnedDocument,
+}
+
+#[derive(Debug)]
+struct RaftState {
+    term: u64,
+    voted_for: Option<u64>,
+    role: Role,
+    leader: Option<u64>,
+    /// The index of the last entry removed from the log by compacting it.
+    snapshot_index: u64,
+    /// The term of the entry at `snapshot_index`.
+    snapshot_term: u64,
+    /// The entry at index `i` is stored at `log[i - snapshot_index - 1]`.
+    log: Vec<LogEntry>,
+    commit_index: u64,
+    last_applied: u64,
+    /// True if a snapshot is being installed, or was interrupted. Entries
+    /// aren't applied until a snapshot has been installed.
+    requires_snapshot: bool,
+    /// The index and term of the snapshot being installed, and the position
+    /// of the next document expected from the leader.
+    installing: Option<(u64, u64, u64)>,
+    /// The snapshot most recently sent by this node as the leader.
+    snapshot: Option<Arc<Snapshot>>,
+    election_deadline: Instant,
+    /// When a request was last received from the leader of the current term.
+    leader_contact: Option<Instant>,
+    leader_since: Instant,
+    followers: HashMap<u64, FollowerProgress>,
+    /// The clients waiting for entries appended by this node as the leader to
+    /// be applied, keyed by the entry's index.
+    waiting: HashMap<u64, flume::Sender<Result<Vec<OperationResult>, bonsaidb_core::Error>>>,
+}
+
+impl RaftState {
+    fn last_index(&self) -> u64 {
+        self.snapshot_index + self.log.len() as u64
+    }
+
+    /// Returns the position of the entry at `index` in `log`.
+    fn position(&self, index: u64) -> Option<usize> {
+        usize::try_from(index.checked_sub(self.snapshot_index + 1)?).ok()
+    }
+
+    fn entry(&self, index: u64) -> Option<&LogEntry> {
+        self.log.get(self.position(index)?)
+    }
+
+    fn term_at(&self, index: u64) -> u64 {
+        if index == self.snapshot_index {
+            self.snapshot_term
+        } else {
+            self.entry(index).map_or(0, |entry| entry.term)
+        }
+    }
+
+    fn last_term(&self) -> u64 {
+        self.term_at(self.last_index())
+    }
+
+    /// Returns true if `progress`'s member can only catch up by installing a
+    /// snapshot.
+    fn needs_snapshot(&self, progress: &FollowerProgress) -> bool {
+        progress.requires_snapshot || progress.next_index <= self.snapshot_index
+    }
+}
+
+/// The state that must survive restarts, stored in a raw tree of the admin
+/// database. Each change is persisted before it is acted upon.
+#[derive(Clone, Debug)]
+struct DurableLog {
+    admin: Database,
+}
+
+#[derive(Debug)]
+struct DurableState {
+    term: u64,
+    voted_for: Option<u64>,
+    snapshot: (u64, u64),
+    log: Vec<LogEntry>,
+    applied: u64,
+    installing: bool,
+}
+
+impl DurableLog {
+    fn load(&self) -> Result<DurableState, bonsaidb_local::Error> {
+        let tree = self.admin.raw_tree(CLUSTER_TREE)?;
+        let (term, voted_for) = match tree.get(VOTE_KEY)? {
+            Some(vote) => pot::from_slice(&vote)?,
+            None => (0, None),
+        };
+        let (snapshot_index, snapshot_term) = match tree.get(SNAPSHOT_KEY)? {
+            Some(snapshot) => pot::from_slice(&snapshot)?,
+            None => (0, 0),
+        };
+        // Only applied entries are compacted.
+        let applied = match tree.get(APPLIED_KEY)? {
+            Some(applied) => pot::from_slice::<u64>(&applied)?.max(snapshot_index),
+            None => snapshot_index,
+        };
+        let mut log = Vec::new();
+        while let Some(entry) = tree.get(&entry_key(snapshot_index + log.len() as u64 + 1))? {
+            log.push(pot::from_slice(&entry)?);
+        }
+        Ok(DurableState {
+            term,
+            voted_for,
+            snapshot: (snapshot_index, snapshot_term),
+            log,
+            applied,
+            installing: tree.get(INSTALLING_KEY)?.is_some(),
+        })
+    }
+
+    async fn save_vote(
+        &self,
+        term: u64,
+        voted_for: Option<u64>,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let admin = self.admin.clone();
+        blocking(move || {
+            admin
+                .raw_tree(CLUSTER_TREE)?
+                .set(VOTE_KEY.to_vec(), pot::to_vec(&(term, voted_for))?)?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn save_applied(&self, index: u64) -> Result<(), bonsaidb_core::Error> {
+        let admin = self.admin.clone();
+        blocking(move || {
+            admin
+                .raw_tree(CLUSTER_TREE)?
+                .set(APPLIED_KEY.to_vec(), pot::to_vec(&index)?)?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Stores `entries`, the first of which is at `first_index`.
+    async fn append(
+        &self,
+        first_index: u64,
+        entries: Vec<LogEntry>,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let admin = self.admin.clone();
+        blocking(move || {
+            let tree = admin.raw_tree(CLUSTER_TREE)?;
+            for (index, entry) in (first_index..).zip(&entries) {
+                tree.set(entry_key(index), pot::to_vec(entry)?)?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    /// Records that the log through `index`, whose entry has `term`, has
+    /// been compacted, and removes the compacted entries that are stored from
+    /// `first_index`.
+    async fn compact(
+        &self,
+        first_index: u64,
+        index: u64,
+        term: u64,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let admin = self.admin.clone();
+        blocking(move || {
+            let mut transaction = admin.raw_transaction(&[admin.raw_tree_root(CLUSTER_TREE)?])?;
+            let mut tree = transaction.tree::<Unversioned>(0).unwrap();
+            tree.set(SNAPSHOT_KEY.to_vec(), pot::to_vec(&(index, term))?)?;
+            for compacted in first_index..=index {
+                tree.remove(&entry_key(compacted))?;
+            }
+            drop(tree);
+            transaction.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn begin_install(&self) -> Result<(), bonsaidb_core::Error> {
+        let admin = self.admin.clone();
+        blocking(move || {
+            admin
+                .raw_tree(CLUSTER_TREE)?
+                .set(INSTALLING_KEY.to_vec(), Vec::new())?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Records that a snapshot of the log through `index`, whose entry has
+    /// `term`, has been installed. The entries from `first_index` through
+    /// `last_index` are removed.
+    async fn finish_install(
+        &self,
+        index: u64,
+        term: u64,
+        first_index: u64,
+        last_index: u64,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let admin = self.admin.clone();
+        blocking(move || {
+            let mut transaction = admin.raw_transaction(&[admin.raw_tree_root(CLUSTER_TREE)?])?;
+            let mut tree = transaction.tree::<Unversioned>(0).unwrap();
+            tree.set(SNAPSHOT_KEY.to_vec(), pot::to_vec(&(index, term))?)?;
+            tree.set(APPLIED_KEY.to_vec(), pot::to_vec(&index)?)?;
+            tree.remove(INSTALLING_KEY)?;
+            for removed in first_index..=last_index {
+                tree.remove(&entry_key(removed))?;
+            }
+            drop(tree);
+            transaction.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Removes the entries from `first_index` through `last_index`. Entries
+    /// are removed from the end of the log first, ensuring the stored log
+    /// never has gaps.
+    async fn truncate(
+        &self,
+        first_index: u64,
+        last_index: u64,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let admin = self.admin.clone();
+        blocking(move || {
+            let tree = admin.raw_tree(CLUSTER_TREE)?;
+            for index in (first_index..=last_index).rev() {
+                tree.remove(&entry_key(index))?;
+            }
+            Ok(())
+        })
+        .await
+    }
+}
+
+fn entry_key(index: u64) -> Vec<u8> {
+    let mut key = ENTRY_PREFIX.to_vec();
+    key.extend(index.to_be_bytes());
+    key
+}
+
+async fn blocking<T: Send + 'static>(
+    operation: impl FnOnce() -> Result<T, bonsaidb_local::Error> + Send + 'static,
+) -> Result<T, bonsaidb_core::Error> {
+    Ok(tokio::task::spawn_blocking(operation)
+        .await
+        .map_err(bonsaidb_local::Error::from)??)
+}
+
+/// This node's membership in a cluster.
+#[derive(Debug)]
+pub(crate) struct Cluster {
+    config: ClusterConfiguration,
+    storage: AsyncStorage,
+    log: DurableLog,
+    state: async_lock::Mutex<RaftState>,
+    /// Held while entries are applied or a snapshot is installed. This lock
+    /// is always acquired before `state`.
+    applying: async_lock::Mutex<()>,
+    /// Wakes the cluster task when there are log entries to replicate or
+    /// apply.
+    wake: Arc<Notify>,
+    last_seen: Mutex<HashMap<u64, Instant>>,
+    clients: Mutex<HashMap<u64, AsyncClient>>,
+}
+
+impl Cluster {
+    pub async fn open(config: ClusterConfiguration, storage: &AsyncStorage) -> Result<Self, Error> {
+        let log = DurableLog {
+            admin: storage.as_blocking().admin(),
+        };
+        let durable = {
+            let log = log.clone();
+            blocking(move || log.load()).await?
+        };
+        let now = Instant::now();
+        let cluster = Self {
+            config,
+            storage: storage.clone(),
+            log,
+            state: async_lock::Mutex::new(RaftState {
+                term: durable.term,
+                voted_for: durable.voted_for,
+                role: Role::Follower,
+                leader: None,
+                snapshot_index: durable.snapshot.0,
+                snapshot_term: durable.snapshot.1,
+                log: durable.log,
+                commit_index: durable.applied,
+                last_applied: durable.applied,
+                requires_snapshot: durable.installing,
+                installing: None,
+                snapshot: None,
+                election_deadline: now,
+                leader_contact: None,
+                leader_since: now,
+                followers: HashMap::new(),
+                waiting: HashMap::new(),
+            }),
+            applying: async_lock::Mutex::new(()),
+            wake: Arc::default(),
+            last_seen: Mutex::default(),
+            clients: Mutex::default(),
+        };
+        cluster.state.lock().await.election_deadline = cluster.election_deadline();
+        Ok(cluster)
+    }
+
+    /// Returns when this node should start an election if it hasn't heard
+    /// from a leader. The timeout is randomized to prevent members from
+    /// repeatedly starting elections at the same time. A node without other
+    /// members elects itself immediately.
+    fn election_deadline(&self) -> Instant {
+        if self.config.members.is_empty() {
+            Instant::now()
+        } else {
+            Instant::now()
+                + self.config.failure_timeout
+                + rand::thread_rng().gen_range(Duration::ZERO..=self.config.failure_timeout)
+        }
+    }
+
+    fn is_majority(&self, nodes: usize) -> bool {
+        nodes * 2 > self.config.members.len() + 1
+    }
+
+    fn is_recent(&self, instant: Option<Instant>) -> bool {
+        instant.map_or(false, |instant| {
+            instant.elapsed() < self.config.failure_timeout
+        })
+    }
+
+    /// Returns true if a majority of the cluster has responded to this node's
+    /// requests as the leader within the failure timeout. Members don't vote
+    /// for a new leader until the failure timeout has elapsed since they last
+    /// heard from the leader, which ensures no other leader has been elected.
+    fn has_lease(&self, state: &RaftState) -> bool {
+        let acknowledged = state
+            .followers
+            .values()
+            .filter(|progress| self.is_recent(progress.acknowledged))
+            .count();
+        self.is_majority(acknowledged + 1)
+    }
+
+    /// Returns the leader this node currently recognizes.
+    fn known_leader(&self, state: &RaftState) -> Option<u64> {
+        match state.role {
+            Role::Leader => self.has_lease(state).then_some(self.config.node_id),
+            Role::Follower | Role::Candidate => state
+                .leader
+                .filter(|_| self.is_recent(state.leader_contact)),
+        }
+    }
+
+    pub async fn status(&self) -> ClusterStatus {
+        let state = self.state.lock().await;
+        let leader = self.known_leader(&state);
+        let last_seen = self.last_seen.lock();
+        ClusterStatus {
+            node_id: self.config.node_id,
+            term: state.term,
+            leader,
+            members: self
+                .config
+                .members
+                .iter()
+                .map(|member| MemberStatus {
+                    id: member.id,
+                    reachable: self.is_recent(last_seen.get(&member.id).copied()),
+                })
+                .collect(),
+            has_quorum: leader.is_some(),
+        }
+    }
+
+    pub async fn leader(&self) -> Option<u64> {
+        let state = self.state.lock().await;
+        self.known_leader(&state)
+    }
+
+    /// Performs this node's periodic work. The leader replicates its log to
+    /// the other members, and other nodes start an election if they haven't
+    /// heard from a leader. Afterwards, newly committed entries are applied.
+    async fn tick(&self) {
+        let is_leader = self.state.lock().await.role == Role::Leader;
+        let result = if is_leader {
+            self.replicate().await
+        } else {
+            self.elect_if_needed().await
+        };
+        if let Err(err) = result {
+            log::error!("Error updating cluster state: {err}");
+        }
+
+        self.apply_committed().await;
+        if let Err(err) = self.compact_if_needed().await {
+            log::error!("Error compacting cluster log: {err}");
+        }
+    }
+
+    /// Starts an election if the election deadline has passed.
+    async fn elect_if_needed(&self) -> Result<(), bonsaidb_core::Error> {
+        // A pre-vote determines whether this node could win an election
+        // without changing any member's term. This prevents a node that was
+        // partitioned from the cluster from disrupting the current leader
+        // when it returns.
+        let pre_vote = {
+            let mut state = self.state.lock().await;
+            // A node whose databases are incomplete until it installs a
+            // snapshot can't become the leader.
+            if state.role == Role::Leader
+                || state.requires_snapshot
+                || Instant::now() < state.election_deadline
+            {
+                return Ok(());
+            }
+            state.election_deadline = self.election_deadline();
+            self.vote_request(&state, state.term + 1, true)
+        };
+        if !self.request_votes(&pre_vote).await? {
+            return Ok(());
+        }
+
+        let request = {
+            let mut state = self.state.lock().await;
+            if state.role == Role::Leader
+                || state.term + 1 != pre_vote.term
+                || self.is_recent(state.leader_contact)
+            {
+                return Ok(());
+            }
+            state.role = Role::Candidate;
+            state.leader = None;
+            state.term = pre_vote.term;
+            state.voted_for = Some(self.config.node_id);
+            self.log.save_vote(state.term, state.voted_for).await?;
+            self.vote_request(&state, state.term, false)
+        };
+        if self.request_votes(&request).await? {
+            let mut state = self.state.lock().await;
+            if state.role == Role::Candidate && state.term == request.term {
+                self.become_leader(&mut state).await?;
+            }
+        }
+        Ok(())
+    }
+
+    fn vote_request(&self, state: &RaftState, term: u64, pre_vote: bool) -> RequestVote {
+        RequestVote {
+            secret: self.config.secret.clone(),
+            term,
+            candidate: self.config.node_id,
+            last_log_index: state.last_index(),
+            last_log_term: state.last_term(),
+            pre_vote,
+        }
+    }
+
+    /// Sends `request` to every member, returning true if a majority of the
+    /// cluster, including this node, granted its vote.
+    async fn request_votes(&self, request: &RequestVote) -> Result<bool, bonsaidb_core::Error> {
+        let responses = futures::future::join_all(
+            self.config
+                .members
+                .iter()
+                .map(|member| async move { (member.id, self.send(member.id, request).await) }),
+        )
+        .await;
+
+        let mut votes = 1;
+        for (member, response) in responses {
+            match response {
+                Ok(response) => {
+                    self.record_contact(member);
+                    if response.term > request.term {
+                        let mut state = self.state.lock().await;
+                        if response.term > state.term {
+                            self.become_follower(&mut state, response.term).await?;
+                        }
+                        return Ok(false);
+                    } else if response.granted {
+                        votes += 1;
+                    }
+                }
+                Err(err) => log::debug!("Vote request to cluster member {member} failed: {err}"),
+            }
+        }
+
+        Ok(self.is_majority(votes))
+    }
+
+    async fn become_leader(&self, state: &mut RaftState) -> Result<(), bonsaidb_core::Error> {
+        log::info!(
+            "Node {} is the cluster leader for term {}",
+            self.config.node_id,
+            state.term
+        );
+        state.role = Role::Leader;
+        state.leader = Some(self.config.node_id);
+        state.leader_since = Instant::now();
+        let next_index = state.last_index() + 1;
+        state.followers = self
+            .config
+            .members
+            .iter()
+            .map(|member| {
+                (
+                    member.id,
+                    FollowerProgress {
+                        next_index,
+                        match_index: 0,
+                        acknowledged: None,
+                        requires_snapshot: false,
+                        snapshot_offset: None,
+                    },
+                )
+            })
+            .collect();
+        // Entries from earlier terms can only be committed by committing an
+        // entry from the current term.
+        let term = state.term;
+        self.append(
+            state,
+            LogEntry {
+                term,
+                command: None,
+            },
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Makes this node a follower, adopting `term` if it is later than the
+    /// current term. Clients waiting on entries appended while this node was
+    /// the leader receive an error, because the entries may be replaced by
+    /// the next leader.
+    async fn become_follower(
+        &self,
+        state: &mut RaftState,
+        term: u64,
+    ) -> Result<(), bonsaidb_core::Error> {
+        if term > state.term {
+            state.term = term;
+            state.voted_for = None;
+            self.log.save_vote(term, None).await?;
+        }
+        if state.role == Role::Leader {
+            log::info!(
+                "Node {} is no longer the cluster leader in term {}",
+                self.config.node_id,
+                state.term
+            );
+        }
+        state.role = Role::Follower;
+        state.leader = None;
+        state.followers.clear();
+        state.waiting.clear();
+        state.snapshot = None;
+        state.election_deadline = self.election_deadline();
+        Ok(())
+    }
+
+    /// Appends `entry` to the log of this node, which is the leader,
+    /// returning the entry's index.
+    async fn append(
+        &self,
+        state: &mut RaftState,
+        entry: LogEntry,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        let index = state.last_index() + 1;
+        self.log.append(index, vec![entry.clone()]).await?;
+        state.log.push(entry);
+        self.advance_commit(state);
+        self.wake.notify_one();
+        Ok(index)
+    }
+
+    /// Commits the entries of the current term that a majority of the cluster
+    /// has stored.
+    fn advance_commit(&self, state: &mut RaftState) {
+        for index in (state.commit_index + 1..=state.last_index()).rev() {
+            if state.term_at(index) != state.term {
+                break;
+            }
+            let stored = 1 + state
+                .followers
+                .values()
+                .filter(|progress| progress.match_index >= index)
+                .count();
+            if self.is_majority(stored) {
+                state.commit_index = index;
+                self.wake.notify_one();
+                break;
+            }
+        }
+    }
+
+    /// Sends the entries each member is missing, or a heartbeat if the member
+    /// is up-to-date. Members that are missing entries that have been
+    /// compacted are sent a snapshot instead.
+    async fn replicate(&self) -> Result<(), bonsaidb_core::Error> {
+        if let Err(err) = self.snapshot_if_needed().await {
+            log::error!("Error creating cluster snapshot: {err}");
+        }
+        let (term, requests) = {
+            let state = self.state.lock().await;
+            if state.role != Role::Leader {
+                return Ok(());
+            }
+            let batch_size =
+                usize::try_from(self.config.replication_batch_size).unwrap_or(usize::MAX);
+            let snapshot = state
+                .snapshot
+                .clone()
+                .filter(|snapshot| snapshot.index >= state.snapshot_index);
+            let requests = state
+                .followers
+                .iter()
+                .map(|(member, progress)| match &snapshot {
+                    Some(snapshot) if state.needs_snapshot(progress) => (
+                        *member,
+                        MemberRequest::Snapshot(
+                            self.snapshot_request(&state, snapshot, progress, batch_size),
+                        ),
+                    ),
+                    _ => (
+                        *member,
+                        MemberRequest::Append(self.append_request(&state, progress, batch_size)),
+                    ),
+                })
+                .collect::<Vec<_>>();
+            (state.term, requests)
+        };
+
+        let sent_at = Instant::now();
+        let responses =
+            futures::future::join_all(requests.into_iter().map(|(member, request)| async move {
+                let exchange = match request {
+                    MemberRequest::Append(request) => self
+                        .send(member, &request)
+                        .await
+                        .map(|response| Exchange::Append(request, response)),
+                    MemberRequest::Snapshot(request) => self
+                        .send(member, &request)
+                        .await
+                        .map(|response| Exchange::Snapshot(request, response)),
+                };
+                (member, exchange)
+            }))
+            .await;
+
+        let mut state = self.state.lock().await;
+        if state.role != Role::Leader || state.term != term {
+            return Ok(());
+        }
+        let last_index = state.last_index();
+        let mut behind = false;
+        for (member, exchange) in responses {
+            match exchange {
+                Ok(exchange) if exchange.term() > state.term => {
+                    self.record_contact(member);
+                    return self.become_follower(&mut state, exchange.term()).await;
+                }
+                Ok(exchange) => {
+                    self.record_contact(member);
+                    let Some(progress) = state.followers.get_mut(&member) else {
+                        continue;
+                    };
+                    progress.acknowledged = Some(sent_at);
+                    match exchange {
+                        Exchange::Append(request, response) => {
+                            progress.requires_snapshot = response.requires_snapshot;
+                            if response.success {
+                                progress.match_index = progress
+                                    .match_index
+                                    .max(request.prev_log_index + request.entries.len() as u64);
+                                progress.next_index = progress.match_index + 1;
+                            } else {
+                                progress.next_index = (progress.next_index - 1)
+                                    .min(response.last_index + 1)
+                                    .max(1);
+                            }
+                        }
+                        Exchange::Snapshot(request, response) => {
+                            if response.installed {
+                                progress.match_index = progress.match_index.max(request.index);
+                                progress.next_index = progress.match_index + 1;
+                                progress.requires_snapshot = false;
+                                progress.snapshot_offset = None;
+                            } else {
+                                progress.snapshot_offset = Some((request.index, response.offset));
+                            }
+                        }
+                    }
+                    behind |=
+                        progress.next_index <= last_index || progress.snapshot_offset.is_some();
+                }
+                Err(err) => log::debug!("Replication to cluster member {member} failed: {err}"),
+            }
+        }
+        self.advance_commit(&mut state);
+
+        if !self.has_lease(&state) && state.leader_since.elapsed() >= self.config.failure_timeout {
+            // The rest of the cluster may have elected a new leader, so this
+            // node must stop serving reads and accepting writes.
+            let term = state.term;
+            self.become_follower(&mut state, term).await?;
+        } else if behind {
+            self.wake.notify_one();
+        }
+        Ok(())
+    }
+
+    /// Returns the request that sends `progress`'s member the entries it is
+    /// missing. Compacted entries can't be sent, so a member that is missing
+    /// them is sent the entries following the snapshot, which it rejects.
+    fn append_request(
+        &self,
+        state: &RaftState,
+        progress: &FollowerProgress,
+        batch_size: usize,
+    ) -> AppendEntries {
+        let prev_log_index = (progress.next_index - 1).max(state.snapshot_index);
+        let entries = state
+            .log
+            .iter()
+            .skip(usize::try_from(prev_log_index - state.snapshot_index).unwrap_or(usize::MAX))
+            .take(batch_size)
+            .cloned()
+            .collect();
+        AppendEntries {
+            secret: self.config.secret.clone(),
+            term: state.term,
+            leader: self.config.node_id,
+            prev_log_index,
+            prev_log_term: state.term_at(prev_log_index),
+            entries,
+            leader_commit: state.commit_index,
+        }
+    }
+
+    /// Returns the request that sends `progress`'s member the next documents
+    /// of `snapshot`.
+    fn snapshot_request(
+        &self,
+        state: &RaftState,
+        snapshot: &Snapshot,
+        progress: &FollowerProgress,
+        batch_size: usize,
+    ) -> InstallSnapshot {
+        let offset = match progress.snapshot_offset {
+            Some((index, offset)) if index == snapshot.index => offset,
+            _ => 0,
+        };
+        let start = usize::try_from(offset)
+            .unwrap_or(usize::MAX)
+            .min(snapshot.documents.len());
+        let documents = snapshot.documents[start..]
+            .iter()
+            .take(batch_size)
+            .cloned()
+            .collect::<Vec<_>>();
+        InstallSnapshot {
+            secret: self.config.secret.clone(),
+            term: state.term,
+            leader: self.config.node_id,
+            index: snapshot.index,
+            last_term: snapshot.term,
+            databases: snapshot.databases.clone(),
+            offset: start as u64,
+            done: start + documents.len() == snapshot.documents.len(),
+            documents,
+        }
+    }
+
+    /// Copies the clustered databases if a member needs a snapshot and the
+    /// most recent snapshot is missing entries that have been compacted. No
+    /// entries are applied while the databases are copied.
+    async fn snapshot_if_needed(&self) -> Result<(), bonsaidb_core::Error> {
+        let _applying = self.applying.lock().await;
+        let (current_term, index, term) = {
+            let state = self.state.lock().await;
+            let current = state
+                .snapshot
+                .as_ref()
+                .map_or(false, |snapshot| snapshot.index >= state.snapshot_index);
+            if state.role != Role::Leader
+                || current
+                || !state
+                    .followers
+                    .values()
+                    .any(|progress| state.needs_snapshot(progress))
+            {
+                return Ok(());
+            }
+            (
+                state.term,
+                state.last_applied,
+                state.term_at(state.last_applied),
+            )
+        };
+
+        let storage = self.storage.as_blocking().clone();
+        let snapshot = blocking(move || {
+            let mut databases = Vec::new();
+            let mut documents = Vec::new();
+            for database in storage.list_databases()? {
+                if !is_clustered(&database.name) {
+                    continue;
+                }
+                let db = storage.database_without_schema(&database.name)?;
+                for collection in db.schematic().collections() {
+                    for document in db.list_from_collection(
+                        Range::from(..),
+                        Sort::Ascending,
+                        None,
+                        &collection,
+                    )? {
+                        documents.push(SnapshotDocument {
+                            database: database.name.clone(),
+                            collection: collection.clone(),
+                            document,
+                        });
+                    }
+                }
+                databases.push((database.name, database.schema));
+            }
+            Ok(Snapshot {
+                index,
+                term,
+                databases,
+                documents,
+            })
+        })
+        .await?;
+        let mut state = self.state.lock().await;
+        if state.role == Role::Leader && state.term == current_term {
+            log::info!(
+                "Node {} created a snapshot of the cluster log through {index}",
+                self.config.node_id
+            );
+            state.snapshot = Some(Arc::new(snapshot));
+        }
+        Ok(())
+    }
+
+    /// Removes the applied entries from the log once the configured snapshot
+    /// interval has been reached. Members that are missing the removed entries
+    /// are sent a snapshot instead.
+    async fn compact_if_needed(&self) -> Result<(), bonsaidb_core::Error> {
+        let mut state = self.state.lock().await;
+        if state.last_applied - state.snapshot_index < self.config.snapshot_interval.max(1) {
+            return Ok(());
+        }
+
+        let index = state.last_applied;
+        let term = state.term_at(index);
+        self.log
+            .compact(state.snapshot_index + 1, index, term)
+            .await?;
+        let compacted = usize::try_from(index - state.snapshot_index).unwrap_or(usize::MAX);
+        state.log.drain(..compacted);
+        state.snapshot_index = index;
+        state.snapshot_term = term;
+        Ok(())
+    }
+
+    /// Handles a vote request from a candidate.
+    async fn vote(&self, request: RequestVote) -> Result<VoteResponse, bonsaidb_core::Error> {
+        self.record_contact(request.candidate);
+        let mut state = self.state.lock().await;
+        // While a leader is active, candidates are ignored. This prevents a
+        // member that can't reach the leader from replacing it.
+        let leader_active = if state.role == Role::Leader {
+            self.has_lease(&state)
+        } else {
+            self.is_recent(state.leader_contact)
+        };
+        if request.term < state.term || leader_active {
+            return Ok(VoteResponse {
+                term: state.term,
+                granted: false,
+            });
+        }
+
+        // Only a candidate whose log contains every committed entry can be
+        // elected. A majority stores every committed entry, and a candidate
+        // needs a vote from a majority, so at least one voter would have a
+        // more up-to-date log than a candidate missing a committed entry.
+        let log_up_to_date = (request.last_log_term, request.last_log_index)
+            >= (state.last_term(), state.last_index());
+        if request.pre_vote {
+            return Ok(VoteResponse {
+                term: state.term,
+                granted: log_up_to_date,
+            });
+        }
+
+        if request.term > state.term {
+            self.become_follower(&mut state, request.term).await?;
+        }
+        let granted = log_up_to_date
+            && state
+                .voted_for
+                .map_or(true, |candidate| candidate == request.candidate);
+        if granted {
+            state.voted_for = Some(request.candidate);
+            self.log.save_vote(state.term, state.voted_for).await?;
+            state.election_deadline = self.election_deadline();
+        }
+        Ok(VoteResponse {
+            term: state.term,
+            granted,
+        })
+    }
+
+    /// Handles a request from the leader to append entries to this node's log.
+    async fn append_entries(
+        &self,
+        request: AppendEntries,
+    ) -> Result<AppendResponse, bonsaidb_core::Error> {
+        self.record_contact(request.leader);
+        let mut state = self.state.lock().await;
+        if request.term < state.term {
+            // The request is from a leader of an earlier term, which steps
+            // down after receiving this response.
+            return Ok(AppendResponse {
+                term: state.term,
+                success: false,
+                last_index: state.last_index(),
+                requires_snapshot: state.requires_snapshot,
+            });
+        }
+        if request.term > state.term || state.role != Role::Follower {
+            self.become_follower(&mut state, request.term).await?;
+        }
+        state.leader = Some(request.leader);
+        state.leader_contact = Some(Instant::now());
+        state.election_deadline = self.election_deadline();
+
+        // Compacted entries were committed, so they match the leader's.
+        if request.prev_log_index > state.last_index()
+            || (request.prev_log_index >= state.snapshot_index
+                && state.term_at(request.prev_log_index) != request.prev_log_term)
+        {
+            return Ok(AppendResponse {
+                term: state.term,
+                success: false,
+                last_index: state
+                    .last_index()
+                    .min(request.prev_log_index.saturating_sub(1)),
+                requires_snapshot: state.requires_snapshot,
+            });
+        }
+
+        let mut index = request.prev_log_index;
+        let mut new_entries = Vec::new();
+        for entry in request.entries {
+            index += 1;
+            if index <= state.snapshot_index {
+                continue;
+            } else if new_entries.is_empty() {
+                match state.entry(index) {
+                    Some(existing) if existing.term == entry.term => continue,
+                    Some(_) => {
+                        // This entry and the ones following it were never
+                        // committed, and are replaced by the leader's entries.
+                        self.log.truncate(index, state.last_index()).await?;
+                        let position = state.position(index).unwrap_or_default();
+                        state.log.truncate(position);
+                    }
+                    None => {}
+                }
+            }
+            new_entries.push(entry);
+        }
+        if !new_entries.is_empty() {
+            self.log
+                .append(state.last_index() + 1, new_entries.clone())
+                .await?;
+            state.log.extend(new_entries);
+        }
+
+        let commit_index = request.leader_commit.min(index);
+        if commit_index > state.commit_index {
+            state.commit_index = commit_index;
+            self.wake.notify_one();
+        }
+
+        Ok(AppendResponse {
+            term: state.term,
+            success: true,
+            last_index: index,
+            requires_snapshot: state.requires_snapshot,
+        })
+    }
+
+    /// Handles a request from the leader to install part of its snapshot.
+    /// The clustered databases are replaced when the first part is received,
+    /// and no entries are applied until the last part has been installed.
+    async fn install_snapshot(
+        &self,
+        request: InstallSnapshot,
+    ) -> Result<SnapshotResponse, bonsaidb_core::Error> {
+        self.record_contact(request.leader);
+        let _applying = self.applying.lock().await;
+        let mut state = self.state.lock().await;
+        if request.term < state.term {
+            return Ok(SnapshotResponse {
+                term: state.term,
+                installed: false,
+                offset: 0,
+            });
+        }
+        if request.term > state.term || state.role != Role::Follower {
+            self.become_follower(&mut state, request.term).await?;
+        }
+        state.leader = Some(request.leader);
+        state.leader_contact = Some(Instant::now());
+        state.election_deadline = self.election_deadline();
+
+        if !state.requires_snapshot && request.index <= state.last_applied {
+            return Ok(SnapshotResponse {
+                term: state.term,
+                installed: true,
+                offset: request.offset,
+            });
+        }
+
+        // An interrupted installation is restarted from the beginning.
+        let expected = match state.installing {
+            Some((index, _, offset)) if index == request.index => offset,
+            _ => 0,
+        };
+        if request.offset != expected {
+            return Ok(SnapshotResponse {
+                term: state.term,
+                installed: false,
+                offset: expected,
+            });
+        }
+
+        let storage = self.storage.as_blocking().clone();
+        if request.offset == 0 {
+            self.log.begin_install().await?;
+            state.requires_snapshot = true;
+            let storage = storage.clone();
+            let databases = request.databases;
+            blocking(move || reset_databases(&storage, &databases)).await?;
+        }
+        let offset = request.offset + request.documents.len() as u64;
+        let documents = request.documents;
+        blocking(move || restore_documents(&storage, documents)).await?;
+        if !request.done {
+            state.installing = Some((request.index, request.last_term, offset));
+            return Ok(SnapshotResponse {
+                term: state.term,
+                installed: false,
+                offset,
+            });
+        }
+
+        // The entries following the snapshot are kept if the log contains
+        // the snapshot's last entry.
+        let removed_from = state.snapshot_index + 1;
+        let last_index = state.last_index();
+        let retained =
+            if request.index < last_index && state.term_at(request.index) == request.last_term {
+                let position = state
+                    .position(request.index)
+                    .map_or(0, |position| position + 1);
+                state.log.split_off(position)
+            } else {
+                Vec::new()
+            };
+        self.log
+            .finish_install(
+                request.index,
+                request.last_term,
+                removed_from,
+                last_index - retained.len() as u64,
+            )
+            .await?;
+        log::info!(
+            "Node {} installed a snapshot of the cluster log through {}",
+            self.config.node_id,
+            request.index
+        );
+        state.log = retained;
+        state.snapshot_index = request.index;
+        state.snapshot_term = request.last_term;
+        state.last_applied = request.index;
+        state.commit_index = state.commit_index.max(request.index);
+        state.requires_snapshot = false;
+        state.installing = None;
+        self.wake.notify_one();
+        Ok(SnapshotResponse {
+            term: state.term,
+            installed: true,
+            offset,
+        })
+    }
+
+    /// Applies the committed entries that haven't been applied to this node's
+    /// databases, in order.
+    async fn apply_committed(&self) {
+        let _applying = self.applying.lock().await;
+        loop {
+            let (index, entry) = {
+                let state = self.state.lock().await;
+                if state.requires_snapshot || state.last_applied >= state.commit_index {
+                    return;
+                }
+                let index = state.last_applied + 1;
+                (index, state.entry(index).cloned())
+            };
+
+            let result = match entry.and_then(|entry| entry.command) {
+                Some(command) => self.apply_entry(index, command).await,
+                None => Ok(Vec::new()),
+            };
+            if let Err(err) = &result {
+                log::debug!("Replicated command {index} failed: {err}");
+            }
+
+            let mut state = self.state.lock().await;
+            state.last_applied = index;
+            if let Some(sender) = state.waiting.remove(&index) {
+                drop(sender.send(result));
+            }
+            drop(state);
+            // If the applied index isn't saved, the transactions applied
+            // since the saved index are skipped when they are applied again,
+            // because each database stores the index of the last transaction
+            // applied to it.
+            if let Err(err) = self.log.save_applied(index).await {
+                log::error!("Error saving applied cluster log index: {err}");
+            }
+        }
+    }
+
+    async fn apply_entry(
+        &self,
+        index: u64,
+        command: ReplicatedCommand,
+    ) -> Result<Vec<OperationResult>, bonsaidb_core::Error> {
+        match command {
+            ReplicatedCommand::Transaction(entry) => {
+                self.storage
+                    .create_database_with_schema(&entry.database, entry.schema, true)
+                    .await?;
+                let database = self
+                    .storage
+                    .database_without_schema(&entry.database)
+                    .await?;
+                // The index is stored in the same commit as the transaction's
+                // changes, which prevents it from being applied twice.
+                Ok(database
+                    .apply_sequenced_transaction(
+                        entry.transaction,
+                        SEQUENCE_SOURCE.to_string(),
+                        index,
+                    )
+                    .await?
+                    .unwrap_or_default())
+            }
+            ReplicatedCommand::DeleteDatabase(name) => {
+                self.storage.delete_database(&name).await?;
+                Ok(Vec::new())
+            }
+        }
+    }
+
+    /// Applies `transaction` to `database` through the replicated log. If
+    /// this node isn't the leader, the transaction is forwarded to the
+    /// leader. The transaction's permissions are checked against `database`'s
+    /// session before it is replicated.
+    pub async fn apply_transaction(
+        &self,
+        database: &AsyncDatabase,
+        transaction: Transaction,
+    ) -> Result<Vec<OperationResult>, bonsaidb_core::Error> {
+        if !is_clustered(database.name()) {
+            return database.apply_transaction(transaction).await;
+        }
+
+        database.check_transaction_permissions(&transaction)?;
+        match self.leader().await {
+            Some(leader) if leader == self.config.node_id => {
+                self.apply_as_leader(database.name(), database.schematic(), transaction)
+                    .await
+            }
+            Some(leader) => {
+                let client = self
+                    .client(leader)?
+                    .with_request_timeout(Some(self.config.failure_timeout * 2));
+                Ok(client
+                    .send_api_request(&ForwardTransaction {
+                        secret: self.config.secret.clone(),
+                        database: database.name().to_string(),
+                        schema: database.schematic().name.clone(),
+                        requester: Requester::of(database.session()),
+                        transaction,
+                    })
+                    .await?)
+            }
+            None => Err(no_quorum()),
+        }
+    }
+
+    /// Appends `transaction` to the log of this node, which is the leader, and
+    /// waits for it to be committed and applied. If the transaction's
+    /// [`WriteQuorum`] is [`WriteQuorum::All`], this also waits for every
+    /// member to store it.
+    async fn apply_as_leader(
+        &self,
+        database: &str,
+        schematic: &Schematic,
+        mut transaction: Transaction,
+    ) -> Result<Vec<OperationResult>, bonsaidb_core::Error> {
+        assign_ids(schematic, &mut transaction)?;
+        let write_quorum = transaction.write_quorum;
+        self.append_command(
+            ReplicatedCommand::Transaction(ReplicatedTransaction {
+                database: database.to_string(),
+                schema: schematic.name.clone(),
+                transaction,
+            }),
+            write_quorum,
+        )
+        .await
+    }
+
+    /// Deletes `database` from every node through the replicated log. If this
+    /// node isn't the leader, the deletion is forwarded to the leader. The
+    /// permission to delete the database is checked against `storage`'s
+    /// session before it is replicated.
+    pub async fn delete_database(
+        &self,
+        storage: &AsyncStorage,
+        database: &str,
+    ) -> Result<(), bonsaidb_core::Error> {
+        if !is_clustered(database) {
+            return storage.delete_database(database).await;
+        }
+
+        storage.check_permission(
+            database_resource_name(database),
+            &BonsaiAction::Server(ServerAction::DeleteDatabase),
+        )?;
+        match self.leader().await {
+            Some(leader) if leader == self.config.node_id => {
+                self.append_command(
+                    ReplicatedCommand::DeleteDatabase(database.to_string()),
+                    WriteQuorum::default(),
+                )
+                .await?;
+                Ok(())
+            }
+            Some(leader) => {
+                let client = self
+                    .client(leader)?
+                    .with_request_timeout(Some(self.config.failure_timeout * 2));
+                Ok(client
+                    .send_api_request(&ForwardDeleteDatabase {
+                        secret: self.config.secret.clone(),
+                        database: database.to_string(),
+                    })
+                    .await?)
+            }
+            None => Err(no_quorum()),
+        }
+    }
+
+    /// Appends `command` to the log of this node, which is the leader, and
+    /// waits for it to be committed and applied, returning the result of
+    /// applying it.
+    async fn append_command(
+        &self,
+        command: ReplicatedCommand,
+        write_quorum: WriteQuorum,
+    ) -> Result<Vec<OperationResult>, bonsaidb_core::Error> {
+        let (index, receiver) = {
+            let mut state = self.state.lock().await;
+            if state.role != Role::Leader {
+                return Err(self.not_leader());
+            }
+            let term = state.term;
+            let index = self
+                .append(
+                    &mut state,
+                    LogEntry {
+                        term,
+                        command: Some(command),
+                    },
+                )
+                .await?;
+            let (sender, receiver) = flume::bounded(1);
+            state.waiting.insert(index, sender);
+            (index, receiver)
+        };
+
+        let results =
+            match tokio::time::timeout(self.config.failure_timeout, receiver.recv_async()).await {
+                Ok(Ok(results)) => results?,
+                Ok(Err(_)) => {
+                    return Err(bonsaidb_core::Error::other(
+                        "bonsaidb-server cluster",
+                        format!("leadership changed before transaction {index} was committed"),
+                    ))
+                }
+                Err(_) => {
+                    return Err(bonsaidb_core::Error::other(
+                        "bonsaidb-server cluster",
+                        format!("transaction {index} was not committed within the failure timeout"),
+                    ))
+                }
+            };
+
+        if write_quorum == WriteQuorum::All {
+            self.wait_for_all_members(index).await?;
+        }
+        Ok(results)
+    }
+
+    /// Waits until every member has stored the entry at `index`. Returns an
+    /// error if this doesn't happen within the failure timeout.
+    async fn wait_for_all_members(&self, index: u64) -> Result<(), bonsaidb_core::Error> {
+        let nodes = self.config.members.len() + 1;
+        let deadline = Instant::now() + self.config.failure_timeout;
+        loop {
+            let stored = {
+                let state = self.state.lock().await;
+                if state.role != Role::Leader {
+                    return Err(self.not_leader());
+                }
+                1 + state
+                    .followers
+                    .values()
+                    .filter(|progress| progress.match_index >= index)
+                    .count()
+            };
+            if stored >= nodes {
+                return Ok(());
+            } else if Instant::now() >= deadline {
+                return Err(bonsaidb_core::Error::other(
+                    "bonsaidb-server cluster",
+                    format!("transaction {index} was stored by {stored} of the {nodes} nodes"),
+                ));
+            }
+
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    /// Returns the id of the leader if reads that require the primary must be
+    /// forwarded to another node, or None if they can be executed by this
+    /// node.
+    pub async fn remote_primary(
+        &self,
+        database: &str,
+    ) -> Result<Option<u64>, bonsaidb_core::Error> {
+        if !is_clustered(database) {
+            return Ok(None);
+        }
+
+        match self.leader().await {
+            Some(leader) if leader == self.config.node_id => {
+                self.wait_for_read_index().await?;
+                Ok(None)
+            }
+            Some(leader) => Ok(Some(leader)),
+            None => Err(no_quorum()),
+        }
+    }
+
+    /// Waits until this node, as the leader, has applied every entry that was
+    /// committed when the read began. Returns an error if this node isn't
+    /// the leader or can't confirm that it is still the leader.
+    async fn wait_for_read_index(&self) -> Result<(), bonsaidb_core::Error> {
+        let deadline = Instant::now() + self.config.failure_timeout;
+        let mut read_index = None;
+        loop {
+            {
+                let state = self.state.lock().await;
+                if state.role != Role::Leader {
+                    return Err(self.not_leader());
+                } else if !self.has_lease(&state) {
+                    return Err(no_quorum());
+                }
+                // A new leader may not know which entries from earlier terms
+                // are committed until it commits an entry in its own term.
+                if read_index.is_none() && state.term_at(state.commit_index) == state.term {
+                    read_index = Some(state.commit_index);
+                }
+                if read_index.map_or(false, |index| state.last_applied >= index) {
+                    return Ok(());
+                }
+            }
+            if Instant::now() >= deadline {
+                return Err(bonsaidb_core::Error::other(
+                    "bonsaidb-server cluster",
+                    "the leader did not apply the committed transactions within the failure timeout",
+                ));
+            }
+
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    /// Executes `request` on `primary`. The request's permissions must be
+    /// checked before it is forwarded.
+    pub async fn forward_read<A: ForwardableRead>(
+        &self,
+        primary: u64,
+        request: A,
+    ) -> Result<A::Response, bonsaidb_core::Error> {
+        let client = self
+            .client(primary)?
+            .with_request_timeout(Some(self.config.failure_timeout * 2));
+        Ok(client
+            .send_api_request(&ForwardRead {
+                secret: self.config.secret.clone(),
+                request,
+            })
+            .await?)
+    }
+
+    fn not_leader(&self) -> bonsaidb_core::Error {
+        bonsaidb_core::Error::other(
+            "bonsaidb-server cluster",
+            format!("node {} is not the cluster leader", self.config.node_id),
+        )
+    }
+
+    fn client(&self, member: u64) -> Result<AsyncClient, bonsaidb_core::Error> {
+        let mut clients = self.clients.lock();
+        if let Some(client) = clients.get(&member) {
+            return Ok(client.clone());
+        }
+
+        let member = self
+            .config
+            .members
+            .iter()
+            .find(|candidate| candidate.id == member)
+            .ok_or_else(|| {
+                bonsaidb_core::Error::other(
+                    "bonsaidb-server cluster",
+                    format!("unknown cluster member {member}"),
+                )
+            })?;
+        let mut builder = AsyncClient::build(member.url.clone());
+        if let Some(certificate) = &member.certificate {
+            builder = builder.with_certificate(certificate.clone());
+        }
+        if let Some(idle_timeout) = self.config.idle_timeout {
+            builder = builder.with_quic_idle_timeout(idle_timeout);
+        }
+        let client = builder.build()?;
+        clients.insert(member.id, client.clone());
+        Ok(client)
+    }
+
+    /// Sends `request` to `member`. Requests time out after half of the
+    /// failure timeout, which prevents an unreachable member from delaying
+    /// heartbeats to the other members.
+    async fn send<A: Api<Error = bonsaidb_core::Error>>(
+        &self,
+        member: u64,
+        request: &A,
+    ) -> Result<A::Response, bonsaidb_core::Error> {
+        let client = self
+            .client(member)?
+            .with_request_timeout(Some(self.config.failure_timeout / 2));
+        Ok(client.send_api_request(request).await?)
+    }
+
+    fn verify_secret(&self, secret: &str) -> Result<(), bonsaidb_core::Error> {
+        if constant_time_eq(secret.as_bytes(), self.config.secret.as_bytes()) {
+            Ok(())
+        } else {
+            Err(bonsaidb_core::Error::other(
+                "bonsaidb-server cluster",
+                "invalid cluster secret",
+            ))
+        }
+    }
+
+    fn record_contact(&self, member: u64) {
+        self.last_seen.lock().insert(member, Instant::now());
+    }
+}
+
+/// Returns true if `database` is replicated across the cluster. The admin
+/// and hosted databases contain node-specific state.
+fn is_clustered(database: &str) -> bool {
+    database != ADMIN_DATABASE_NAME && database != "_hosted"
+}
+
+/// Replaces the clustered databases with empty copies of `databases`.
+fn reset_databases(
+    storage: &Storage,
+    databases: &[(String, SchemaName)],
+) -> Result<(), bonsaidb_local::Error> {
+    for database in storage.list_databases()? {
+        if is_clustered(&database.name) {
+            storage.delete_database(&database.name)?;
+        }
+    }
+    for (name, schema) in databases {
+        storage.create_database_with_schema(name, schema.clone(), false)?;
+    }
+    Ok(())
+}
+
+/// Stores the documents received in a snapshot, keeping their revisions.
+fn restore_documents(
+    storage: &Storage,
+    documents: Vec<SnapshotDocument>,
+) -> Result<(), bonsaidb_local::Error> {
+    let mut collections = HashMap::<_, Vec<_>>::new();
+    for document in documents {
+        collections
+            .entry((document.database, document.collection))
+            .or_default()
+            .push(document.document);
+    }
+    for ((database, collection), documents) in collections {
+        storage
+            .database_without_schema(&database)?
+            .restore_documents(&collection, documents)?;
+    }
+    Ok(())
+}
+
+fn no_quorum() -> bonsaidb_core::Error {
+    bonsaidb_core::Error::other(
+        "bonsaidb-server cluster",
+        "no leader that can reach a majority of the cluster is known",
+    )
+}
+
+/// Compares `a` and `b` in an amount of time that only depends on their
+/// lengths, which prevents a secret from being guessed one byte at a time.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b)
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+/// Assigns ids to the documents inserted by `transaction` whose ids would
+/// differ between members, such as random ids. The remaining ids are derived
+/// from the stored documents when the transaction is applied, which assigns
+/// the same ids on every member.
+fn assign_ids(
+    schematic: &Schematic,
+    transaction: &mut Transaction,
+) -> Result<(), bonsaidb_core::Error> {
+    let mut last_ids = HashMap::new();
+    for operation in &mut transaction.operations {
+        if let Command::Insert { id: id @ None, .. } = &mut operation.command {
+            if !schematic.collection_assigns_deterministic_ids(&operation.collection)? {
+                let next = schematic.next_id_for_collection(
+                    &operation.collection,
+                    last_ids.remove(&operation.collection),
+                )?;
+                last_ids.insert(operation.collection.clone(), next.clone());
+                *id = Some(next);
+            }
+        }
+    }
+    Ok(())
+}
+
+impl<B: Backend> CustomServer<B> {
+    /// Spawns the task that exchanges heartbeats with the other members of
+    /// the cluster and applies committed transactions. The task exits when the
+    /// server is shut down or dropped.
+    pub(crate) async fn spawn_cluster_tasks(&self) {
+        let Some(cluster) = &self.data.cluster else {
+            return;
+        };
+        // A cluster without other members elects this node immediately.
+        cluster.tick().await;
+
+        let Some(shutdown) = self.data.shutdown.watcher().await else {
+            return;
+        };
+        tokio::task::spawn(run_cluster_task(
+            Arc::downgrade(&self.data),
+            cluster.wake.clone(),
+            cluster.config.heartbeat_interval,
+            shutdown,
+        ));
+    }
+
+    /// Returns this node's view of the cluster, or None if clustering is not
+    /// configured.
+    pub async fn cluster_status(&self) -> Option<ClusterStatus> {
+        match &self.data.cluster {
+            Some(cluster) => Some(cluster.status().await),
+            None => None,
+        }
+    }
+
+    /// Returns the id of the cluster's leader, which is the primary of every
+    /// clustered database. Returns None if clustering is not configured or if
+    /// this node doesn't recognize a leader that can reach a majority of the
+    /// cluster.
+    pub async fn cluster_primary(&self) -> Option<u64> {
+        match &self.data.cluster {
+            Some(cluster) => cluster.leader().await,
+            None => None,
+        }
+    }
+}
+
+async fn run_cluster_task<B: Backend>(
+    data: Weak<Data<B>>,
+    wake: Arc<Notify>,
+    interval: Duration,
+    mut shutdown: ShutdownStateWatcher,
+) {
+    loop {
+        tokio::select! {
+            _ = shutdown.wait_for_shutdown() => break,
+            _ = tokio::time::sleep(interval) => {}
+            _ = wake.notified() => {}
+        }
+
+        // Only hold a reference to the server while the task runs, which
+        // allows the server to be dropped without being shut down.
+        let Some(data) = data.upgrade() else {
+            break;
+        };
+        let Some(cluster) = &data.cluster else {
+            break;
+        };
+        cluster.tick().await;
+    }
+}
+
+/// Registers the apis used by cluster members to communicate.
+pub(crate) fn register_cluster_apis<B: Backend>(
+    config: ServerConfiguration<B>,
+) -> Result<ServerConfiguration<B>, Error> {
+    config
+        .with_api::<ClusterDispatcher, RequestVote>()?
+        .with_api::<ClusterDispatcher, AppendEntries>()?
+        .with_api::<ClusterDispatcher, InstallSnapshot>()?
+        .with_api::<ClusterDispatcher, ForwardTransaction>()?
+        .with_api::<ClusterDispatcher, ForwardDeleteDatabase>()?
+        .with_api::<ClusterDispatcher, ForwardRead<Query>>()?
+        .with_api::<ClusterDispatcher, ForwardRead<QueryWithDocs>>()?
+        .with_api::<ClusterDispatcher, ForwardRead<QueryWithProjectedDocs>>()?
+        .with_api::<ClusterDispatcher, ForwardRead<Reduce>>()?
+        .with_api::<ClusterDispatcher, ForwardRead<ReduceGrouped>>()?
+        .with_api::<ClusterDispatcher, ForwardRead<Aggregate>>()?
+        .with_api::<ClusterDispatcher, ForwardRead<CountView>>()?
+        .with_api::<ClusterDispatcher, ForwardRead<DocumentMappings>>()
+}
+
+#[derive(Debug)]
+struct ClusterDispatcher;
+
+impl ClusterDispatcher {
+    fn cluster<'a, B: Backend>(
+        session: &HandlerSession<'a, B>,
+        secret: &str,
+    ) -> Result<&'a Cluster, bonsaidb_core::Error> {
+        let cluster = session.server.data.cluster.as_ref().ok_or_else(|| {
+            bonsaidb_core::Error::other("bonsaidb-server cluster", "clustering is not enabled")
+        })?;
+        cluster.verify_secret(secret)?;
+        Ok(cluster)
+    }
+}
+
+/// Requests a vote from a member for `candidate` to become the leader in
+/// `term`. A pre-vote asks whether the member would grant its vote, without
+/// changing the member's state.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+struct RequestVote {
+    secret: String,
+    term: u64,
+    candidate: u64,
+    last_log_index: u64,
+    last_log_term: u64,
+    pre_vote: bool,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+struct VoteResponse {
+    term: u64,
+    granted: bool,
+}
+
+impl Api for RequestVote {
+    type Error = bonsaidb_core::Error;
+    type Response = VoteResponse;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "ClusterRequestVote")
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<B, RequestVote> for ClusterDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        request: RequestVote,
+    ) -> HandlerResult<RequestVote> {
+        let cluster = Self::cluster(&session, &request.secret)?;
+        Ok(cluster.vote(request).await?)
+    }
+}
+
+/// Appends `entries` to a member's log, following the entry at
+/// `prev_log_index`. Requests without entries are heartbeats.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+struct AppendEntries {
+    secret: String,
+    term: u64,
+    leader: u64,
+    prev_log_index: u64,
+    prev_log_term: u64,
+    entries: Vec<LogEntry>,
+    leader_commit: u64,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+struct AppendResponse {
+    term: u64,
+    success: bool,
+    /// If successful, the index of the last entry in the request. Otherwise,
+    /// the highest index the leader should try next.
+    last_index: u64,
+    /// True if the member must install a snapshot before it can apply
+    /// entries.
+    requires_snapshot: bool,
+}
+
+impl Api for AppendEntries {
+    type Error = bonsaidb_core::Error;
+    type Response = AppendResponse;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "ClusterAppendEntries")
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<B, AppendEntries> for ClusterDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        request: AppendEntries,
+    ) -> HandlerResult<AppendEntries> {
+        let cluster = Self::cluster(&session, &request.secret)?;
+        Ok(cluster.append_entries(request).await?)
+    }
+}
+
+/// Sends part of the leader's snapshot, which contains the clustered databases
+/// after the entry at `index` was applied, to a member that is missing entries
+/// the leader has compacted. The snapshot's documents are sent in order,
+/// starting at `offset`.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+struct InstallSnapshot {
+    secret: String,
+    term: u64,
+    leader: u64,
+    index: u64,
+    /// The term of the entry at `index`.
+    last_term: u64,
+    databases: Vec<(String, SchemaName)>,
+    offset: u64,
+    documents: Vec<SnapshotDocument>,
+    /// True if this request contains the snapshot's last documents.
+    done: bool,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+struct SnapshotResponse {
+    term: u64,
+    /// True if the member has installed the snapshot, or had already applied
+    /// the entries it contains.
+    installed: bool,
+    /// The position of the next document the member expects.
+    offset: u64,
+}
+
+impl Api for InstallSnapshot {
+    type Error = bonsaidb_core::Error;
+    type Response = SnapshotResponse;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "ClusterInstallSnapshot")
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<B, InstallSnapshot> for ClusterDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        request: InstallSnapshot,
+    ) -> HandlerResult<InstallSnapshot> {
+        let cluster = Self::cluster(&session, &request.secret)?;
+        Ok(cluster.install_snapshot(request).await?)
+    }
+}
+
+/// A request sent to a member by the leader.
+enum MemberRequest {
+    Append(AppendEntries),
+    Snapshot(InstallSnapshot),
+}
+
+/// A request sent to a member by the leader, and the member's response.
+enum Exchange {
+    Append(AppendEntries, AppendResponse),
+    Snapshot(InstallSnapshot, SnapshotResponse),
+}
+
+impl Exchange {
+    const fn term(&self) -> u64 {
+        match self {
+            Self::Append(_, response) => response.term,
+            Self::Snapshot(_, response) => response.term,
+        }
+    }
+}
+
+/// Applies a transaction on the leader on behalf of another member. The
+/// forwarding member has already checked the transaction's permissions.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+struct ForwardTransaction {
+    secret: String,
+    database: String,
+    schema: SchemaName,
+    /// The requester the forwarding member applied the transaction for, which
+    /// the leader remembers the results for.
+    requester: Requester,
+    transaction: Transaction,
+}
+
+impl Api for ForwardTransaction {
+    type Error = bonsaidb_core::Error;
+    type Response = Vec<OperationResult>;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "ForwardTransaction")
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<B, ForwardTransaction> for ClusterDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        request: ForwardTransaction,
+    ) -> HandlerResult<ForwardTransaction> {
+        let cluster = Self::cluster(&session, &request.secret)?;
+
+        let storage = &session.server.storage;
+        storage
+            .create_database_with_schema(&request.database, request.schema, true)
+            .await?;
+        let database = storage.database_without_schema(&request.database).await?;
+        Ok(session
+            .server
+            .data
+            .applied_transactions
+            .apply(
+                &request.database,
+                request.requester,
+                request.transaction,
+                |transaction| {
+                    cluster.apply_as_leader(&request.database, database.schematic(), transaction)
+                },
+            )
+            .await?)
+    }
+}
+
+/// Deletes a database on the leader on behalf of another member. The
+/// forwarding member has already checked the permission to delete it.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+struct ForwardDeleteDatabase {
+    secret: String,
+    database: String,
+}
+
+impl Api for ForwardDeleteDatabase {
+    type Error = bonsaidb_core::Error;
+    type Response = ();
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "ForwardDeleteDatabase")
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<B, ForwardDeleteDatabase> for ClusterDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        request: ForwardDeleteDatabase,
+    ) -> HandlerResult<ForwardDeleteDatabase> {
+        let cluster = Self::cluster(&session, &request.secret)?;
+        cluster
+            .append_command(
+                ReplicatedCommand::DeleteDatabase(request.database),
+                WriteQuorum::default(),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+/// A view request that the leader can execute on behalf of another member.
+#[async_trait]
+pub(crate) trait ForwardableRead: Api<Error = bonsaidb_core::Error> {
+    /// The name of the api that forwards this request.
+    const FORWARDED_NAME: &'static str;
+
+    /// Returns the name of the database the request reads from.
+    fn database(&self) -> &str;
+
+    /// Executes the request on `database`.
+    async fn execute(
+        self,
+        database: &AsyncDatabase,
+    ) -> Result<Self::Response, bonsaidb_core::Error>;
+}
+
+#[async_trait]
+impl ForwardableRead for Query {
+    const FORWARDED_NAME: &'static str = "ForwardQuery";
+
+    fn database(&self) -> &str {
+        &self.database
+    }
+
+    async fn execute(
+        self,
+        database: &AsyncDatabase,
+    ) -> Result<Self::Response, bonsaidb_core::Error> {
+        database
+            .query_by_name(
+                &self.view,
+                self.key,
+                self.order,
+                self.limit,
+                self.access_policy,
+                ViewReadOptions::default(),
+            )
+            .await
+    }
+}
+
+#[async_trait]
+impl ForwardableRead for QueryWithDocs {
+    const FORWARDED_NAME: &'static str = "ForwardQueryWithDocs";
+
+    fn database(&self) -> &str {
+        &self.0.database
+    }
+
+    async fn execute(
+        self,
+        database: &AsyncDatabase,
+    ) -> Result<Self::Response, bonsaidb_core::Error> {
+        database
+            .query_by_name_with_docs(
+                &self.0.view,
+                self.0.key,
+                self.0.order,
+                self.0.limit,
+                self.0.access_policy,
+                ViewReadOptions::default(),
+            )
+            .await
+    }
+}
+
+#[async_trait]
+impl ForwardableRead for QueryWithProjectedDocs {
+    const FORWARDED_NAME: &'static str = "ForwardQueryWithProjectedDocs";
+
+    fn database(&self) -> &str {
+        &self.query.database
+    }
+
+    async fn execute(
+        self,
+        database: &AsyncDatabase,
+    ) -> Result<Self::Response, bonsaidb_core::Error> {
+        database
+            .query_by_name_with_projected_docs(
+                &self.query.view,
+                self.query.key,
+                self.query.order,
+                self.query.limit,
+                self.query.access_policy,
+                ViewReadOptions::default(),
+                &self.projection,
+            )
+            .await
+    }
+}
+
+#[async_trait]
+impl ForwardableRead for Reduce {
+    const FORWARDED_NAME: &'static str = "ForwardReduce";
+
+    fn database(&self) -> &str {
+        &self.database
+    }
+
+    async fn execute(
+        self,
+        database: &AsyncDatabase,
+    ) -> Result<Self::Response, bonsaidb_core::Error> {
+        database
+            .reduce_by_name(
+                &self.view,
+                self.key,
+                self.access_policy,
+                ViewReadOptions::default(),
+            )
+            .await
+            .map(Bytes::from)
+    }
+}
+
+#[async_trait]
+impl ForwardableRead for ReduceGrouped {
+    const FORWARDED_NAME: &'static str = "ForwardReduceGrouped";
+
+    fn database(&self) -> &str {
+        &self.0.database
+    }
+
+    async fn execute(
+        self,
+        database: &AsyncDatabase,
+    ) -> Result<Self::Response, bonsaidb_core::Error> {
+        database
+            .reduce_grouped_by_name(
+                &self.0.view,
+                self.0.key,
+                self.0.access_policy,
+                ViewReadOptions::default(),
+            )
+            .await
+    }
+}
+
+#[async_trait]
+impl ForwardableRead for Aggregate {
+    const FORWARDED_NAME: &'static str = "ForwardAggregate";
+
+    fn database(&self) -> &str {
+        &self.database
+    }
+
+    async fn execute(
+        self,
+        database: &AsyncDatabase,
+    ) -> Result<Self::Response, bonsaidb_core::Error> {
+        database
+            .aggregate_by_name(
+                &self.view,
+                self.key,
+                &self.aggregations,
+                self.grouping,
+                self.access_policy,
+                ViewReadOptions::default(),
+            )
+            .await
+    }
+}
+
+#[async_trait]
+impl ForwardableRead for CountView {
+    const FORWARDED_NAME: &'static str = "ForwardCountView";
+
+    fn database(&self) -> &str {
+        &self.database
+    }
+
+    async fn execute(
+        self,
+        database: &AsyncDatabase,
+    ) -> Result<Self::Response, bonsaidb_core::Error> {
+        database
+            .count_by_name(
+                &self.view,
+                self.key,
+                self.access_policy,
+                ViewReadOptions::default(),
+            )
+            .await
+    }
+}
+
+#[async_trait]
+impl ForwardableRead for DocumentMappings {
+    const FORWARDED_NAME: &'static str = "ForwardDocumentMappings";
+
+    fn database(&self) -> &str {
+        &self.database
+    }
+
+    async fn execute(
+        self,
+        database: &AsyncDatabase,
+    ) -> Result<Self::Response, bonsaidb_core::Error> {
+        database
+            .document_mappings_by_name(
+                &self.view,
+                self.document_id,
+                self.access_policy,
+                ViewReadOptions::default(),
+            )
+            .await
+    }
+}
+
+/// Executes a view request on the leader on behalf of another member. The
+/// forwarding member has already checked the request's permissions.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+struct ForwardRead<A> {
+    secret: String,
+    request: A,
+}
+
+impl<A: ForwardableRead> Api for ForwardRead<A> {
+    type Error = bonsaidb_core::Error;
+    type Response = A::Response;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", A::FORWARDED_NAME)
+    }
+
+    fn idempotent() -> bool {
+        true
+    }
+}
+
+#[async_trait]
+impl<B: Backend, A: ForwardableRead> Handler<B, ForwardRead<A>> for ClusterDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        request: ForwardRead<A>,
+    ) -> HandlerResult<ForwardRead<A>> {
+        let cluster = Self::cluster(&session, &request.secret)?;
+        cluster.wait_for_read_index().await?;
+        let database = session
+            .server
+            .storage
+            .database_without_schema(request.request.database())
+            .await?;
+        Ok(request.request.execute(&database).await?)
+    }
+}
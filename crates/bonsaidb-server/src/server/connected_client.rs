@@ -1,21 +1,30 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::net::SocketAddr;
 use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 
 use async_lock::{Mutex, MutexGuard};
 use bonsaidb_core::api;
 use bonsaidb_core::api::ApiName;
 use bonsaidb_core::arc_bytes::serde::Bytes;
-use bonsaidb_core::connection::{Session, SessionId};
-use bonsaidb_core::networking::MessageReceived;
+use bonsaidb_core::connection::{Identity, Session, SessionId};
+use bonsaidb_core::networking::{
+    Capabilities, Compression, DocumentsChanged, MessageReceived, Payload, PayloadLimits,
+    ServerEvent,
+};
+use bonsaidb_core::permissions::bonsai::{bonsaidb_resource_name, BonsaiAction, ServerAction};
 use bonsaidb_core::pubsub::{Receiver, Subscriber as _};
+use bonsaidb_core::transaction::{DocumentChanges, Operation, Transaction};
 use bonsaidb_local::Subscriber;
 use bonsaidb_utils::fast_async_lock;
 use derive_where::derive_where;
 use flume::Sender;
 use parking_lot::RwLock;
+use tokio::sync::Notify;
 
+use crate::server::rate_limit::RateLimiter;
 use crate::{Backend, CustomServer, Error, NoBackend};
 
 /// The ways a client can be connected to the server.
@@ -26,6 +35,9 @@ pub enum Transport {
     /// A connection over WebSockets.
     #[cfg(feature = "websockets")]
     WebSocket,
+    /// A connection over WebSockets on a Unix domain socket.
+    #[cfg(all(unix, feature = "websockets"))]
+    UnixSocket,
 }
 
 /// A connected database client.
@@ -43,12 +55,54 @@ struct Data<B: Backend = NoBackend> {
     transport: Transport,
     response_sender: Sender<(Option<SessionId>, ApiName, Bytes)>,
     client_data: Mutex<Option<B::ClientData>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    protocol: parking_lot::Mutex<ClientProtocol>,
+    next_transaction_id: AtomicU64,
+    disconnect: Notify,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct ClientProtocol {
+    revision: u32,
+    capabilities: Capabilities,
+    compression: Option<NegotiatedCompression>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct NegotiatedCompression {
+    compression: Compression,
+    threshold: usize,
 }
 
 #[derive(Debug)]
 struct ClientSession {
     session: Session,
     subscribers: HashMap<u64, Subscriber>,
+    watched_databases: HashSet<String>,
+    transactions: HashMap<u64, PendingTransaction>,
+}
+
+/// A transaction whose operations are being sent in chunks.
+#[derive(Debug)]
+struct PendingTransaction {
+    database: String,
+    transaction: Transaction,
+    size: usize,
+    last_used: Instant,
+}
+
+/// Discards the pending transactions in `sessions` that haven't received
+/// operations within `limits`' timeout.
+fn expire_transactions(
+    sessions: &mut HashMap<Option<SessionId>, ClientSession>,
+    limits: &PayloadLimits,
+    now: Instant,
+) {
+    for client_session in sessions.values_mut() {
+        client_session.transactions.retain(|_, pending| {
+            now.saturating_duration_since(pending.last_used) < limits.open_transaction_timeout
+        });
+    }
 }
 
 impl<B: Backend> ConnectedClient<B> {
@@ -71,13 +125,146 @@ impl<B: Backend> ConnectedClient<B> {
             ClientSession {
                 session,
                 subscribers: HashMap::default(),
+                watched_databases: HashSet::default(),
+                transactions: HashMap::default(),
             },
         );
     }
 
+    /// Returns the ids of the users this client is authenticated as.
+    #[must_use]
+    pub fn authenticated_users(&self) -> HashSet<u64> {
+        let sessions = self.data.sessions.read();
+        sessions
+            .values()
+            .filter_map(|client_session| match client_session.session.identity() {
+                Some(Identity::User { id, .. }) => Some(*id),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub(crate) fn rate_limiter(&self) -> Option<&Arc<RateLimiter>> {
+        self.data.rate_limiter.as_ref()
+    }
+
+    pub(crate) fn handshake_completed(
+        &self,
+        revision: u32,
+        capabilities: Capabilities,
+        compression: Option<Compression>,
+        threshold: usize,
+    ) {
+        *self.data.protocol.lock() = ClientProtocol {
+            revision,
+            capabilities,
+            compression: compression.map(|compression| NegotiatedCompression {
+                compression,
+                threshold,
+            }),
+        };
+    }
+
+    /// Returns the protocol revision reported by this client. Clients that
+    /// have not sent a [`Handshake`](bonsaidb_core::networking::Handshake)
+    /// report revision 0.
+    #[must_use]
+    pub fn protocol_revision(&self) -> u32 {
+        self.data.protocol.lock().revision
+    }
+
+    /// Returns the optional protocol features supported by this client.
+    #[must_use]
+    pub fn capabilities(&self) -> Capabilities {
+        self.data.protocol.lock().capabilities
+    }
+
+    /// Returns the payload compression negotiated by this client, if any.
+    #[must_use]
+    pub fn compression(&self) -> Option<Compression> {
+        self.data
+            .protocol
+            .lock()
+            .compression
+            .map(|negotiated| negotiated.compression)
+    }
+
+    /// Compresses `payload` using the algorithm negotiated by this client.
+    pub(crate) fn compress_payload(&self, payload: &mut Payload) {
+        let negotiated = self.data.protocol.lock().compression;
+        if let Some(negotiated) = negotiated {
+            if let Err(err) = payload.compress(negotiated.compression, negotiated.threshold) {
+                log::warn!("[server] error compressing response: {err}");
+            }
+        }
+    }
+
     pub(crate) fn log_out(&self, session: SessionId) {
         let mut sessions = self.data.sessions.write();
         sessions.remove(&Some(session));
+        // A session authenticated by a client certificate is the connection's
+        // default session. The connection is closed when it is removed.
+        if matches!(sessions.get(&None), Some(client_session) if client_session.session.id == Some(session))
+        {
+            sessions.remove(&None);
+            drop(sessions);
+            self.disconnect();
+        }
+    }
+
+    /// Removes every session authenticated as the user with `user_id`.
+    /// Returns true if any sessions were removed.
+    pub(crate) fn log_out_user(&self, user_id: u64) -> bool {
+        let mut sessions = self.data.sessions.write();
+        let session_count = sessions.len();
+        sessions.retain(|_, client_session| {
+            !matches!(
+                client_session.session.identity(),
+                Some(Identity::User { id, .. }) if *id == user_id
+            )
+        });
+        sessions.len() != session_count
+    }
+
+    /// Closes this client's connection.
+    pub(crate) fn disconnect(&self) {
+        // A permit is stored if the connection isn't currently waiting, which
+        // ensures the request isn't lost.
+        self.data.disconnect.notify_one();
+    }
+
+    /// Waits until [`Self::disconnect()`] is called.
+    pub(crate) async fn disconnect_requested(&self) {
+        self.data.disconnect.notified().await;
+    }
+
+    /// Sends `event` to the client if it supports server events and one of its
+    /// sessions is allowed to observe it.
+    pub(crate) fn send_server_event(&self, event: &ServerEvent) -> Result<(), Error> {
+        if !self.capabilities().contains(Capabilities::SERVER_EVENTS) {
+            return Ok(());
+        }
+
+        let required_action = match event {
+            ServerEvent::DatabaseCreated { .. } | ServerEvent::DatabaseDeleted { .. } => {
+                Some(ServerAction::ListDatabases)
+            }
+            ServerEvent::SchemaRegistered { .. } => Some(ServerAction::ListAvailableSchemas),
+            _ => None,
+        };
+        if let Some(action) = required_action {
+            let action = BonsaiAction::Server(action);
+            let sessions = self.data.sessions.read();
+            if !sessions.values().any(|client_session| {
+                client_session
+                    .session
+                    .allowed_to(bonsaidb_resource_name(), &action)
+            }) {
+                return Ok(());
+            }
+        }
+
+        self.send::<ServerEvent>(None, event)
     }
 
     /// Sends a custom API response to the client.
@@ -135,6 +322,112 @@ impl<B: Backend> ConnectedClient<B> {
         });
     }
 
+    /// Begins a transaction on `database` whose operations are pushed in
+    /// chunks, returning the transaction's id. Transactions that have expired
+    /// according to `limits` are discarded, and an error is returned if the
+    /// session has too many open transactions.
+    pub(crate) fn begin_transaction(
+        &self,
+        database: String,
+        session_id: Option<SessionId>,
+        limits: &PayloadLimits,
+    ) -> Result<u64, crate::Error> {
+        let now = Instant::now();
+        let mut sessions = self.data.sessions.write();
+        expire_transactions(&mut sessions, limits, now);
+        let Some(client_session) = sessions.get_mut(&session_id) else {
+            return Err(Error::other("bonsaidb-server auth", "invalid session id"));
+        };
+        if client_session.transactions.len() >= limits.max_open_transactions {
+            return Err(Error::from(bonsaidb_core::Error::TooManyOpenTransactions {
+                limit: limits.max_open_transactions,
+            }));
+        }
+        let transaction_id = self.data.next_transaction_id.fetch_add(1, Ordering::Relaxed);
+        client_session.transactions.insert(
+            transaction_id,
+            PendingTransaction {
+                database,
+                transaction: Transaction::new(),
+                size: 0,
+                last_used: now,
+            },
+        );
+        Ok(transaction_id)
+    }
+
+    /// Adds `operations` to a transaction begun with
+    /// [`Self::begin_transaction()`]. If the transaction, or the combined size
+    /// of this client's open transactions, exceeds `limits`, the transaction
+    /// is discarded.
+    pub(crate) fn push_transaction_operations(
+        &self,
+        transaction_id: u64,
+        operations: Vec<Operation>,
+        session_id: Option<SessionId>,
+        limits: &PayloadLimits,
+    ) -> Result<(), crate::Error> {
+        let now = Instant::now();
+        let mut sessions = self.data.sessions.write();
+        expire_transactions(&mut sessions, limits, now);
+        let open_size = sessions
+            .values()
+            .flat_map(|client_session| client_session.transactions.values())
+            .fold(0_usize, |total, pending| total.saturating_add(pending.size));
+        let Some(client_session) = sessions.get_mut(&session_id) else {
+            return Err(Error::other("bonsaidb-server auth", "invalid session id"));
+        };
+        let Some(pending) = client_session.transactions.get_mut(&transaction_id) else {
+            return Err(Error::other(
+                "bonsaidb-server transactions",
+                "invalid transaction id",
+            ));
+        };
+        let result = limits
+            .check_operations(&operations, pending.size)
+            .and_then(|size| {
+                let open_size = (open_size - pending.size).saturating_add(size);
+                if open_size > limits.max_open_transactions_size {
+                    Err(bonsaidb_core::Error::TransactionTooLarge {
+                        size: open_size,
+                        limit: limits.max_open_transactions_size,
+                    })
+                } else {
+                    Ok(size)
+                }
+            });
+        match result {
+            Ok(size) => {
+                pending.size = size;
+                pending.last_used = now;
+                pending.transaction.operations.extend(operations);
+                Ok(())
+            }
+            Err(err) => {
+                client_session.transactions.remove(&transaction_id);
+                Err(Error::from(err))
+            }
+        }
+    }
+
+    /// Removes a transaction begun with [`Self::begin_transaction()`],
+    /// returning the name of its database and its operations.
+    pub(crate) fn take_transaction(
+        &self,
+        transaction_id: u64,
+        session_id: Option<SessionId>,
+    ) -> Result<(String, Transaction), crate::Error> {
+        let mut sessions = self.data.sessions.write();
+        let Some(client_session) = sessions.get_mut(&session_id) else {
+            return Err(Error::other("bonsaidb-server auth", "invalid session id"));
+        };
+        client_session
+            .transactions
+            .remove(&transaction_id)
+            .map(|pending| (pending.database, pending.transaction))
+            .ok_or_else(|| Error::other("bonsaidb-server transactions", "invalid transaction id"))
+    }
+
     /// Sets the associated data for this client.
     pub async fn set_client_data(&self, data: B::ClientData) {
         let mut client_data = fast_async_lock!(self.data.client_data);
@@ -165,6 +458,69 @@ impl<B: Backend> ConnectedClient<B> {
         }
     }
 
+    /// Forwards the changes received from `changes` to the client using the
+    /// [`DocumentsChanged`] API. If `session_id` is already watching
+    /// `database`, `changes` is dropped.
+    pub(crate) fn watch_document_changes(
+        &self,
+        database: String,
+        changes: flume::Receiver<Arc<DocumentChanges>>,
+        session_id: Option<SessionId>,
+    ) -> Result<(), crate::Error> {
+        {
+            let mut sessions = self.data.sessions.write();
+            let Some(client_session) = sessions.get_mut(&session_id) else {
+                return Err(Error::other("bonsaidb-server auth", "invalid session id"));
+            };
+            if !client_session.watched_databases.insert(database.clone()) {
+                return Ok(());
+            }
+        }
+
+        let task_self = self.clone();
+        tokio::task::spawn(async move {
+            task_self
+                .forward_document_changes_for(session_id, database, changes)
+                .await;
+        });
+        Ok(())
+    }
+
+    async fn forward_document_changes_for(
+        &self,
+        session_id: Option<SessionId>,
+        database: String,
+        changes: flume::Receiver<Arc<DocumentChanges>>,
+    ) {
+        let session = self.session(session_id);
+        while let Ok(changes) = changes.recv_async().await {
+            // Stop forwarding once the session has been logged out.
+            if !self.data.sessions.read().contains_key(&session_id) {
+                break;
+            }
+
+            if self
+                .send::<DocumentsChanged>(
+                    session.as_ref(),
+                    &DocumentsChanged {
+                        database: database.clone(),
+                        changes: DocumentChanges::clone(&changes),
+                    },
+                )
+                .is_err()
+            {
+                break;
+            }
+        }
+
+        // Allow the database to be watched again, which is necessary if the
+        // database was deleted and recreated.
+        let mut sessions = self.data.sessions.write();
+        if let Some(client_session) = sessions.get_mut(&session_id) {
+            client_session.watched_databases.remove(&database);
+        }
+    }
+
     pub(crate) fn subscribe_by_id(
         &self,
         subscriber_id: u64,
@@ -262,6 +618,7 @@ impl<B: Backend> OwnedClient<B> {
         response_sender: Sender<(Option<SessionId>, ApiName, Bytes)>,
         server: CustomServer<B>,
         default_session: Session,
+        rate_limiter: Option<Arc<RateLimiter>>,
     ) -> Self {
         let mut session = HashMap::new();
         session.insert(
@@ -269,6 +626,8 @@ impl<B: Backend> OwnedClient<B> {
             ClientSession {
                 session: default_session,
                 subscribers: HashMap::default(),
+                watched_databases: HashSet::default(),
+                transactions: HashMap::default(),
             },
         );
         Self {
@@ -280,6 +639,10 @@ impl<B: Backend> OwnedClient<B> {
                     response_sender,
                     sessions: RwLock::new(session),
                     client_data: Mutex::default(),
+                    rate_limiter,
+                    protocol: parking_lot::Mutex::default(),
+                    next_transaction_id: AtomicU64::new(0),
+                    disconnect: Notify::new(),
                 }),
             },
             runtime: Arc::new(tokio::runtime::Handle::current()),
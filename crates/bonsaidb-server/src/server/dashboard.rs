@@ -0,0 +1,628 @@
+use std::borrow::Cow;
+use std::convert::Infallible;
+use std::net::IpAddr;
+
+use async_trait::async_trait;
+use bonsaidb_core::connection::{
+    AccessPolicy, AsyncLowLevelConnection, AsyncStorageConnection, Bound, HasSession, Range,
+    Session, Sort, ViewReadOptions,
+};
+use bonsaidb_core::document::DocumentId;
+use bonsaidb_core::permissions::bonsai::{bonsaidb_resource_name, BonsaiAction, ServerAction};
+use bonsaidb_core::schema::{CollectionName, Name, Qualified, ViewName};
+use bonsaidb_core::ErrorCode;
+use bonsaidb_local::Usage;
+use derive_where::derive_where;
+use hyper::body::HttpBody;
+use hyper::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE, WWW_AUTHENTICATE};
+use hyper::server::conn::Http;
+use hyper::service::service_fn;
+use hyper::{Body, Method, Request, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::{Backend, CustomServer, HttpService, NoBackend, Peer};
+
+const INDEX: &str = include_str!("dashboard/index.html");
+const DEFAULT_LIMIT: u32 = 50;
+const MAX_LIMIT: u32 = 1_000;
+const MAX_BODY_SIZE: usize = 16 * 1024;
+
+/// An [`HttpService`] serving a web dashboard for administering a
+/// [`CustomServer`]. Requires feature `admin-dashboard`.
+///
+/// The dashboard shows the server's databases, collections, documents,
+/// views, users, background tasks, and the resources used by each database
+/// and user. Users can be created, disabled, and enabled, and their passwords,
+/// permission groups, and roles can be changed. Requests are authenticated
+/// using HTTP Basic authentication with a user's password, and are subject to
+/// the same permissions as network clients. Requests without credentials use
+/// the server's default permissions.
+///
+/// Requests that change users must use `POST`, `PUT`, or `DELETE` and have a
+/// `Content-Type` of `application/json`. Browsers don't allow other sites to
+/// send these requests without the server's consent, which prevents other
+/// sites from performing actions using a browser's cached credentials.
+#[derive(Debug)]
+#[derive_where(Clone)]
+pub struct AdminDashboard<B: Backend = NoBackend> {
+    server: CustomServer<B>,
+}
+
+#[async_trait]
+impl<B: Backend> HttpService for AdminDashboard<B> {
+    async fn handle_connection<
+        S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send + 'static,
+    >(
+        &self,
+        connection: S,
+        peer: &Peer,
+    ) -> Result<(), S> {
+        let server = self.server.clone();
+        let address = peer.address.ip();
+        let service = service_fn(move |request| {
+            let server = server.clone();
+            async move {
+                Ok::<_, Infallible>(
+                    server
+                        .handle_admin_dashboard_request(request, Some(address))
+                        .await,
+                )
+            }
+        });
+        if let Err(err) = Http::new().serve_connection(connection, service).await {
+            log::error!("[dashboard] error serving {}: {:?}", peer.address, err);
+        }
+
+        Ok(())
+    }
+}
+
+impl<B: Backend> CustomServer<B> {
+    /// Returns an [`HttpService`] serving the admin dashboard, which can be
+    /// passed to [`Self::listen_for_tcp_on()`]. Requires feature
+    /// `admin-dashboard`.
+    #[must_use]
+    pub fn admin_dashboard(&self) -> AdminDashboard<B> {
+        AdminDashboard {
+            server: self.clone(),
+        }
+    }
+
+    /// Handles an HTTP request for the admin dashboard. This allows the
+    /// dashboard to be served by an existing HTTP server. Requires feature
+    /// `admin-dashboard`.
+    ///
+    /// `address` is the address of the client that made the request, which
+    /// failed authentication attempts are counted against.
+    pub async fn handle_admin_dashboard_request(
+        &self,
+        request: Request<Body>,
+        address: Option<IpAddr>,
+    ) -> Response<Body> {
+        let (parts, body) = request.into_parts();
+        if !matches!(
+            parts.method,
+            Method::GET | Method::POST | Method::PUT | Method::DELETE
+        ) {
+            return empty_response(StatusCode::METHOD_NOT_ALLOWED);
+        }
+
+        let path = parts.uri.path();
+        if parts.method == Method::GET && (path == "/" || path == "/index.html") {
+            let mut response = Response::new(Body::from(INDEX));
+            response.headers_mut().insert(
+                CONTENT_TYPE,
+                HeaderValue::from_static("text/html; charset=utf-8"),
+            );
+            return response;
+        }
+
+        let Some(api_path) = path.strip_prefix("/api/") else {
+            return empty_response(StatusCode::NOT_FOUND);
+        };
+
+        let as_client = match self.dashboard_session(&parts.headers, address).await {
+            Ok(as_client) => as_client,
+            Err(response) => return response,
+        };
+
+        let segments = api_path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect::<Vec<_>>();
+        if parts.method != Method::GET {
+            return match as_client
+                .dashboard_change(&parts.method, &segments, &parts.headers, body)
+                .await
+            {
+                Ok(value) => json_response(StatusCode::OK, &value),
+                Err(response) => response,
+            };
+        }
+
+        let query = parts.uri.query().unwrap_or_default();
+        let result = match segments.as_slice() {
+            ["databases"] => as_client.dashboard_databases().await,
+            ["schemas"] => as_client.dashboard_schemas().await,
+            ["databases", database, "collections"] => {
+                as_client.dashboard_collections(database).await
+            }
+            ["databases", database, "collections", collection, "documents"] => {
+                as_client
+                    .dashboard_documents(database, collection, query)
+                    .await
+            }
+            ["databases", database, "collections", collection, "views", view] => {
+                as_client
+                    .dashboard_view(database, collection, view, query)
+                    .await
+            }
+            ["users"] => as_client.dashboard_users().await,
+            ["tasks"] => as_client.dashboard_tasks(),
+            ["usage"] => as_client.dashboard_usage(),
+            _ => return empty_response(StatusCode::NOT_FOUND),
+        };
+
+        match result {
+            Ok(value) => json_response(StatusCode::OK, &value),
+            Err(err) => error_response(&err),
+        }
+    }
+
+    /// Returns a server limited to the permissions of the credentials
+    /// provided in `headers`.
+    async fn dashboard_session(
+        &self,
+        headers: &HeaderMap,
+        address: Option<IpAddr>,
+    ) -> Result<Self, Response<Body>> {
+        let unauthenticated = self.dashboard_as(self.default_session().clone())?;
+        let session = match headers.get(AUTHORIZATION) {
+            Some(authorization) => {
+                unauthenticated
+                    .dashboard_authenticate(authorization, address)
+                    .await?
+            }
+            None => self.default_session().clone(),
+        };
+        if !session.allowed_to(
+            bonsaidb_resource_name(),
+            &BonsaiAction::Server(ServerAction::Connect),
+        ) {
+            return Err(unauthorized());
+        }
+
+        self.dashboard_as(session)
+    }
+
+    fn dashboard_as(&self, session: Session) -> Result<Self, Response<Body>> {
+        self.assume_session(session)
+            .map_err(|err| error_response(&err))
+    }
+
+    #[cfg(feature = "password-hashing")]
+    async fn dashboard_authenticate(
+        &self,
+        authorization: &HeaderValue,
+        address: Option<IpAddr>,
+    ) -> Result<Session, Response<Body>> {
+        self.authenticate_basic_authorization(authorization.as_bytes(), address)
+            .await
+            .map_err(|err| match err {
+                bonsaidb_core::Error::RateLimited { .. }
+                | bonsaidb_core::Error::AccountLocked { .. } => error_response(&err),
+                _ => unauthorized(),
+            })
+    }
+
+    #[cfg(not(feature = "password-hashing"))]
+    #[allow(clippy::unused_async)]
+    async fn dashboard_authenticate(
+        &self,
+        _authorization: &HeaderValue,
+        _address: Option<IpAddr>,
+    ) -> Result<Session, Response<Body>> {
+        Err(unauthorized())
+    }
+
+    async fn dashboard_databases(&self) -> Result<Value, bonsaidb_core::Error> {
+        let databases = self.list_databases().await?;
+        Ok(Value::Array(
+            databases
+                .into_iter()
+                .map(|database| {
+                    json!({
+                        "name": database.name,
+                        "schema": database.schema.to_string(),
+                    })
+                })
+                .collect(),
+        ))
+    }
+
+    async fn dashboard_schemas(&self) -> Result<Value, bonsaidb_core::Error> {
+        let schemas = self.list_available_schemas().await?;
+        Ok(Value::Array(
+            schemas
+                .into_iter()
+                .map(|schema| Value::String(schema.to_string()))
+                .collect(),
+        ))
+    }
+
+    async fn dashboard_collections(&self, database: &str) -> Result<Value, bonsaidb_core::Error> {
+        let database = self.database_without_schema(database).await?;
+        let schematic = database.as_blocking().schematic();
+        Ok(Value::Array(
+            schematic
+                .collections()
+                .into_iter()
+                .map(|collection| {
+                    let views = schematic
+                        .views_in_collection(&collection)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|view| {
+                            let name = view.view_name();
+                            json!({
+                                "name": name.name.to_string(),
+                                "encoded": name.name.encoded(),
+                                "unique": view.unique(),
+                                "lazy": view.lazy(),
+                            })
+                        })
+                        .collect::<Vec<_>>();
+                    json!({
+                        "name": collection.to_string(),
+                        "encoded": collection.encoded(),
+                        "views": views,
+                    })
+                })
+                .collect(),
+        ))
+    }
+
+    async fn dashboard_documents(
+        &self,
+        database: &str,
+        collection: &str,
+        query: &str,
+    ) -> Result<Value, bonsaidb_core::Error> {
+        let collection = CollectionName::parse_encoded(collection)?;
+        let start = match query_parameter(query, "after") {
+            Some(after) => Bound::Excluded(after.parse::<DocumentId>()?),
+            None => Bound::Unbounded,
+        };
+        let database = self.database_without_schema(database).await?;
+        let documents = database
+            .list_from_collection(
+                Range {
+                    start,
+                    end: Bound::Unbounded,
+                },
+                Sort::Ascending,
+                Some(limit(query)),
+                &collection,
+            )
+            .await?;
+        Ok(Value::Array(
+            documents
+                .into_iter()
+                .map(|document| {
+                    json!({
+                        "id": document.header.id.to_string(),
+                        "revision": document.header.revision.id,
+                        "contents": deserialized_value(&document.contents),
+                    })
+                })
+                .collect(),
+        ))
+    }
+
+    async fn dashboard_view(
+        &self,
+        database: &str,
+        collection: &str,
+        view: &str,
+        query: &str,
+    ) -> Result<Value, bonsaidb_core::Error> {
+        let view = ViewName {
+            collection: CollectionName::parse_encoded(collection)?,
+            name: Name::parse_encoded(view)?,
+        };
+        let database = self.database_without_schema(database).await?;
+        let mappings = database
+            .query_by_name(
+                &view,
+                None,
+                Sort::Ascending,
+                Some(limit(query)),
+                AccessPolicy::UpdateBefore,
+                ViewReadOptions::default(),
+            )
+            .await?;
+        Ok(Value::Array(
+            mappings
+                .into_iter()
+                .map(|mapping| {
+                    json!({
+                        "source": mapping.source.id.to_string(),
+                        "key": key_value(&mapping.key),
+                        "value": deserialized_value(&mapping.value),
+                    })
+                })
+                .collect(),
+        ))
+    }
+
+    async fn dashboard_users(&self) -> Result<Value, bonsaidb_core::Error> {
+        let users = self.list_users().await?;
+        Ok(Value::Array(
+            users
+                .into_iter()
+                .map(|user| {
+                    json!({
+                        "id": user.header.id,
+                        "username": user.contents.username,
+                        "groups": user.contents.groups,
+                        "roles": user.contents.roles,
+                        "disabled": user.contents.disabled,
+                    })
+                })
+                .collect(),
+        ))
+    }
+
+    /// Performs the change requested by a `POST`, `PUT`, or `DELETE` request.
+    /// Each change is checked against the session's permissions by the
+    /// storage APIs used to perform it.
+    async fn dashboard_change(
+        &self,
+        method: &Method,
+        segments: &[&str],
+        headers: &HeaderMap,
+        body: Body,
+    ) -> Result<Value, Response<Body>> {
+        let is_json = headers
+            .get(CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .and_then(|content_type| content_type.split(';').next())
+            .map_or(false, |content_type| {
+                content_type.trim().eq_ignore_ascii_case("application/json")
+            });
+        if !is_json {
+            return Err(empty_response(StatusCode::UNSUPPORTED_MEDIA_TYPE));
+        }
+
+        match (method, segments) {
+            (&Method::POST, ["users"]) => {
+                let CreateUser { username } = json_body(body).await?;
+                let id = self
+                    .create_user(&username)
+                    .await
+                    .map_err(|err| error_response(&err))?;
+                Ok(json!({ "id": id }))
+            }
+            (&Method::POST, ["users", user, "disable"]) => {
+                self.disable_user(user_id(user)?)
+                    .await
+                    .map_err(|err| error_response(&err))?;
+                Ok(Value::Null)
+            }
+            (&Method::POST, ["users", user, "enable"]) => {
+                self.enable_user(user_id(user)?)
+                    .await
+                    .map_err(|err| error_response(&err))?;
+                Ok(Value::Null)
+            }
+            #[cfg(feature = "password-hashing")]
+            (&Method::PUT, ["users", user, "password"]) => {
+                let SetPassword { password } = json_body(body).await?;
+                self.set_user_password(user_id(user)?, password)
+                    .await
+                    .map_err(|err| error_response(&err))?;
+                Ok(Value::Null)
+            }
+            (&Method::PUT, ["users", user, "groups", group]) => {
+                self.add_permission_group_to_user(
+                    user_id(user)?,
+                    path_id(group, bonsaidb_core::Error::PermissionGroupNotFound)?,
+                )
+                .await
+                .map_err(|err| error_response(&err))?;
+                Ok(Value::Null)
+            }
+            (&Method::DELETE, ["users", user, "groups", group]) => {
+                self.remove_permission_group_from_user(
+                    user_id(user)?,
+                    path_id(group, bonsaidb_core::Error::PermissionGroupNotFound)?,
+                )
+                .await
+                .map_err(|err| error_response(&err))?;
+                Ok(Value::Null)
+            }
+            (&Method::PUT, ["users", user, "roles", role]) => {
+                self.add_role_to_user(
+                    user_id(user)?,
+                    path_id(role, bonsaidb_core::Error::RoleNotFound)?,
+                )
+                .await
+                .map_err(|err| error_response(&err))?;
+                Ok(Value::Null)
+            }
+            (&Method::DELETE, ["users", user, "roles", role]) => {
+                self.remove_role_from_user(
+                    user_id(user)?,
+                    path_id(role, bonsaidb_core::Error::RoleNotFound)?,
+                )
+                .await
+                .map_err(|err| error_response(&err))?;
+                Ok(Value::Null)
+            }
+            _ => Err(empty_response(StatusCode::METHOD_NOT_ALLOWED)),
+        }
+    }
+
+    fn dashboard_tasks(&self) -> Result<Value, bonsaidb_core::Error> {
+        self.check_permission(
+            bonsaidb_resource_name(),
+            &BonsaiAction::Server(ServerAction::ViewServerStatus),
+        )?;
+        let tasks = self.storage.background_tasks();
+        Ok(json!({
+            "view_updates": tasks.view_updates,
+            "integrity_checks": tasks.integrity_checks,
+            "compactions": tasks.compactions,
+            "key_value_expiration_loads": tasks.key_value_expiration_loads,
+            "retention_enforcements": tasks.retention_enforcements,
+            "collection_migrations": tasks.collection_migrations,
+        }))
+    }
+
+    fn dashboard_usage(&self) -> Result<Value, bonsaidb_core::Error> {
+        self.check_permission(
+            bonsaidb_resource_name(),
+            &BonsaiAction::Server(ServerAction::ViewServerStatus),
+        )?;
+        let usage = self.storage.usage();
+        Ok(json!({
+            "databases": usage
+                .databases
+                .iter()
+                .map(|(name, usage)| (name.clone(), usage_value(usage)))
+                .collect::<serde_json::Map<_, _>>(),
+            "users": usage
+                .users
+                .iter()
+                .map(|(id, usage)| (id.to_string(), usage_value(usage)))
+                .collect::<serde_json::Map<_, _>>(),
+        }))
+    }
+}
+
+fn usage_value(usage: &Usage) -> Value {
+    json!({
+        "mapping_seconds": usage.mapping.as_secs_f64(),
+        "querying_seconds": usage.querying.as_secs_f64(),
+        "compacting_seconds": usage.compacting.as_secs_f64(),
+        "bytes_read": usage.bytes_read,
+        "bytes_written": usage.bytes_written,
+    })
+}
+
+#[derive(Deserialize)]
+struct CreateUser {
+    username: String,
+}
+
+#[cfg(feature = "password-hashing")]
+#[derive(Deserialize)]
+struct SetPassword {
+    password: bonsaidb_core::connection::SensitiveString,
+}
+
+/// Reads and deserializes a JSON request body of up to [`MAX_BODY_SIZE`]
+/// bytes.
+async fn json_body<T: DeserializeOwned>(mut body: Body) -> Result<T, Response<Body>> {
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|_| empty_response(StatusCode::BAD_REQUEST))?;
+        if bytes.len() + chunk.len() > MAX_BODY_SIZE {
+            return Err(empty_response(StatusCode::PAYLOAD_TOO_LARGE));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    serde_json::from_slice(&bytes).map_err(|err| {
+        json_response(
+            StatusCode::BAD_REQUEST,
+            &json!({ "error": err.to_string() }),
+        )
+    })
+}
+
+fn user_id(segment: &str) -> Result<u64, Response<Body>> {
+    path_id(segment, bonsaidb_core::Error::UserNotFound)
+}
+
+/// Parses an id from a path segment, returning `not_found` if the segment
+/// isn't a valid id.
+fn path_id(segment: &str, not_found: bonsaidb_core::Error) -> Result<u64, Response<Body>> {
+    segment.parse().map_err(|_| error_response(&not_found))
+}
+
+/// Returns the percent-decoded value of the parameter `name` in `query`.
+fn query_parameter<'a>(query: &'a str, name: &str) -> Option<Cow<'a, str>> {
+    form_urlencoded::parse(query.as_bytes()).find_map(|(key, value)| (key == name).then_some(value))
+}
+
+fn limit(query: &str) -> u32 {
+    query_parameter(query, "limit")
+        .and_then(|limit| limit.parse().ok())
+        .unwrap_or(DEFAULT_LIMIT)
+        .min(MAX_LIMIT)
+}
+
+/// Converts `bytes` serialized using `Pot` into JSON. Values that cannot be
+/// deserialized are encoded as hexadecimal.
+fn deserialized_value(bytes: &[u8]) -> Value {
+    pot::from_slice::<pot::Value<'_>>(bytes)
+        .ok()
+        .and_then(|value| serde_json::to_value(value).ok())
+        .unwrap_or_else(|| json!({ "hex": hex(bytes) }))
+}
+
+/// Converts a view's key into JSON. Keys that are valid UTF-8 are shown as
+/// strings, and all other keys are encoded as hexadecimal.
+fn key_value(bytes: &[u8]) -> Value {
+    match std::str::from_utf8(bytes) {
+        Ok(key) => Value::String(key.to_string()),
+        Err(_) => json!({ "hex": hex(bytes) }),
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn empty_response(status: StatusCode) -> Response<Body> {
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = status;
+    response
+}
+
+fn json_response(status: StatusCode, value: &Value) -> Response<Body> {
+    let mut response = Response::new(Body::from(value.to_string()));
+    *response.status_mut() = status;
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response
+}
+
+fn error_response(err: &bonsaidb_core::Error) -> Response<Body> {
+    let status = match err.code() {
+        _ if matches!(err, bonsaidb_core::Error::AccountLocked { .. }) => {
+            StatusCode::TOO_MANY_REQUESTS
+        }
+        ErrorCode::PermissionDenied => StatusCode::FORBIDDEN,
+        ErrorCode::NotFound => StatusCode::NOT_FOUND,
+        ErrorCode::AlreadyExists | ErrorCode::DocumentConflict | ErrorCode::UniqueViolation => {
+            StatusCode::CONFLICT
+        }
+        ErrorCode::InvalidArgument => StatusCode::BAD_REQUEST,
+        ErrorCode::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    json_response(status, &json!({ "error": err.to_string() }))
+}
+
+fn unauthorized() -> Response<Body> {
+    let mut response = empty_response(StatusCode::UNAUTHORIZED);
+    response.headers_mut().insert(
+        WWW_AUTHENTICATE,
+        HeaderValue::from_static("Basic realm=\"BonsaiDb\""),
+    );
+    response
+}
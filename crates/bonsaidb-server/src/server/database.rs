@@ -3,17 +3,35 @@ use std::ops::Deref;
 use async_trait::async_trait;
 use bonsaidb_core::connection::{
     AccessPolicy, AsyncLowLevelConnection, HasSchema, HasSession, Range, SerializedQueryKey, Sort,
+    ViewReadOptions,
 };
 use bonsaidb_core::document::{DocumentId, Header, OwnedDocument};
 use bonsaidb_core::keyvalue::AsyncKeyValue;
+#[cfg(feature = "cluster")]
+use bonsaidb_core::networking::{
+    Aggregate, CountView, DocumentMappings, Query, QueryWithDocs, QueryWithProjectedDocs, Reduce,
+    ReduceGrouped,
+};
+#[cfg(feature = "cluster")]
+use bonsaidb_core::permissions::bonsai::{
+    document_resource_name, view_resource_name, BonsaiAction, DatabaseAction, DocumentAction,
+    ViewAction,
+};
 use bonsaidb_core::permissions::Permissions;
 use bonsaidb_core::pubsub::AsyncPubSub;
+use bonsaidb_core::schema::view::aggregation::{
+    Aggregation, AggregationGrouping, SerializedAggregatedGroup,
+};
+use bonsaidb_core::schema::view::join::SerializedJoinedEntry;
 use bonsaidb_core::schema::view::map::MappedSerializedValue;
-use bonsaidb_core::schema::{self, CollectionName, Schematic, ViewName};
+use bonsaidb_core::schema::{self, CollectionName, Name, Schematic, ViewName};
 use bonsaidb_core::transaction::{OperationResult, Transaction};
-use bonsaidb_local::{AsyncDatabase, Database};
+use bonsaidb_local::{AsyncDatabase, Database, DatabaseNonBlocking};
 use derive_where::derive_where;
 
+use super::idempotency::Requester;
+#[cfg(feature = "cluster")]
+use super::Cluster;
 use crate::{Backend, CustomServer, NoBackend};
 
 /// A database belonging to a [`CustomServer`].
@@ -47,6 +65,50 @@ impl<B: Backend> ServerDatabase<B> {
                 server: self.server.clone(),
             })
     }
+
+    /// Applies `transaction`, ignoring its idempotency key.
+    async fn apply_transaction_once(
+        &self,
+        transaction: Transaction,
+    ) -> Result<Vec<OperationResult>, bonsaidb_core::Error> {
+        #[cfg(feature = "cluster")]
+        if let Some(cluster) = &self.server.data.cluster {
+            return cluster.apply_transaction(&self.db, transaction).await;
+        }
+
+        self.db.apply_transaction(transaction).await
+    }
+
+    /// Returns the cluster member that a view request must be forwarded to,
+    /// if `read_options` requires the primary and another member is the
+    /// cluster's leader.
+    #[cfg(feature = "cluster")]
+    async fn read_primary(
+        &self,
+        read_options: ViewReadOptions,
+    ) -> Result<Option<(&Cluster, u64)>, bonsaidb_core::Error> {
+        match &self.server.data.cluster {
+            Some(cluster) if read_options.read_from_primary_only => Ok(cluster
+                .remote_primary(self.db.name())
+                .await?
+                .map(|primary| (cluster, primary))),
+            _ => Ok(None),
+        }
+    }
+
+    /// Checks that `action` is allowed on `view` before the request is
+    /// forwarded to the primary, which executes it without a session.
+    #[cfg(feature = "cluster")]
+    fn check_view_permission(
+        &self,
+        view: &ViewName,
+        action: ViewAction,
+    ) -> Result<(), bonsaidb_core::Error> {
+        self.db.check_permission(
+            view_resource_name(self.db.name(), view),
+            &BonsaiAction::Database(DatabaseAction::View(action)),
+        )
+    }
 }
 
 impl<B: Backend> Deref for ServerDatabase<B> {
@@ -171,6 +233,19 @@ impl<B: Backend> AsyncLowLevelConnection for ServerDatabase<B> {
             .await
     }
 
+    async fn list_projected_from_collection(
+        &self,
+        ids: Range<DocumentId>,
+        order: Sort,
+        limit: Option<u32>,
+        collection: &CollectionName,
+        projection: &Name,
+    ) -> Result<Vec<OwnedDocument>, bonsaidb_core::Error> {
+        self.db
+            .list_projected_from_collection(ids, order, limit, collection, projection)
+            .await
+    }
+
     async fn count_from_collection(
         &self,
         ids: Range<DocumentId>,
@@ -201,9 +276,29 @@ impl<B: Backend> AsyncLowLevelConnection for ServerDatabase<B> {
         order: Sort,
         limit: Option<u32>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<Vec<schema::view::map::Serialized>, bonsaidb_core::Error> {
+        #[cfg(feature = "cluster")]
+        if let Some((cluster, primary)) = self.read_primary(read_options).await? {
+            self.check_view_permission(view, ViewAction::Query)?;
+            return cluster
+                .forward_read(
+                    primary,
+                    Query {
+                        database: self.db.name().to_string(),
+                        view: view.clone(),
+                        key,
+                        order,
+                        limit,
+                        access_policy,
+                        read_options,
+                    },
+                )
+                .await;
+        }
+
         self.db
-            .query_by_name(view, key, order, limit, access_policy)
+            .query_by_name(view, key, order, limit, access_policy, read_options)
             .await
     }
 
@@ -214,9 +309,88 @@ impl<B: Backend> AsyncLowLevelConnection for ServerDatabase<B> {
         order: Sort,
         limit: Option<u32>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<schema::view::map::MappedSerializedDocuments, bonsaidb_core::Error> {
+        #[cfg(feature = "cluster")]
+        if let Some((cluster, primary)) = self.read_primary(read_options).await? {
+            self.check_view_permission(view, ViewAction::Query)?;
+            let results = cluster
+                .forward_read(
+                    primary,
+                    QueryWithDocs(Query {
+                        database: self.db.name().to_string(),
+                        view: view.clone(),
+                        key,
+                        order,
+                        limit,
+                        access_policy,
+                        read_options,
+                    }),
+                )
+                .await?;
+            for id in results.documents.keys() {
+                self.db.check_permission(
+                    document_resource_name(self.db.name(), &view.collection, id),
+                    &BonsaiAction::Database(DatabaseAction::Document(DocumentAction::Get)),
+                )?;
+            }
+            return Ok(results);
+        }
+
         self.db
-            .query_by_name_with_docs(view, key, order, limit, access_policy)
+            .query_by_name_with_docs(view, key, order, limit, access_policy, read_options)
+            .await
+    }
+
+    async fn query_by_name_with_projected_docs(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+        projection: &Name,
+    ) -> Result<schema::view::map::MappedSerializedDocuments, bonsaidb_core::Error> {
+        #[cfg(feature = "cluster")]
+        if let Some((cluster, primary)) = self.read_primary(read_options).await? {
+            self.check_view_permission(view, ViewAction::Query)?;
+            let results = cluster
+                .forward_read(
+                    primary,
+                    QueryWithProjectedDocs {
+                        query: Query {
+                            database: self.db.name().to_string(),
+                            view: view.clone(),
+                            key,
+                            order,
+                            limit,
+                            access_policy,
+                            read_options,
+                        },
+                        projection: projection.clone(),
+                    },
+                )
+                .await?;
+            for id in results.documents.keys() {
+                self.db.check_permission(
+                    document_resource_name(self.db.name(), &view.collection, id),
+                    &BonsaiAction::Database(DatabaseAction::Document(DocumentAction::Get)),
+                )?;
+            }
+            return Ok(results);
+        }
+
+        self.db
+            .query_by_name_with_projected_docs(
+                view,
+                key,
+                order,
+                limit,
+                access_policy,
+                read_options,
+                projection,
+            )
             .await
     }
 
@@ -225,8 +399,29 @@ impl<B: Backend> AsyncLowLevelConnection for ServerDatabase<B> {
         view: &ViewName,
         key: Option<SerializedQueryKey>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<Vec<u8>, bonsaidb_core::Error> {
-        self.db.reduce_by_name(view, key, access_policy).await
+        #[cfg(feature = "cluster")]
+        if let Some((cluster, primary)) = self.read_primary(read_options).await? {
+            self.check_view_permission(view, ViewAction::Reduce)?;
+            return cluster
+                .forward_read(
+                    primary,
+                    Reduce {
+                        database: self.db.name().to_string(),
+                        view: view.clone(),
+                        key,
+                        access_policy,
+                        read_options,
+                    },
+                )
+                .await
+                .map(|reduced| reduced.into_vec());
+        }
+
+        self.db
+            .reduce_by_name(view, key, access_policy, read_options)
+            .await
     }
 
     async fn reduce_grouped_by_name(
@@ -234,9 +429,138 @@ impl<B: Backend> AsyncLowLevelConnection for ServerDatabase<B> {
         view: &ViewName,
         key: Option<SerializedQueryKey>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<Vec<MappedSerializedValue>, bonsaidb_core::Error> {
+        #[cfg(feature = "cluster")]
+        if let Some((cluster, primary)) = self.read_primary(read_options).await? {
+            self.check_view_permission(view, ViewAction::Reduce)?;
+            return cluster
+                .forward_read(
+                    primary,
+                    ReduceGrouped(Reduce {
+                        database: self.db.name().to_string(),
+                        view: view.clone(),
+                        key,
+                        access_policy,
+                        read_options,
+                    }),
+                )
+                .await;
+        }
+
+        self.db
+            .reduce_grouped_by_name(view, key, access_policy, read_options)
+            .await
+    }
+
+    async fn aggregate_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        aggregations: &[Aggregation],
+        grouping: AggregationGrouping,
+        access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+    ) -> Result<Vec<SerializedAggregatedGroup>, bonsaidb_core::Error> {
+        #[cfg(feature = "cluster")]
+        if let Some((cluster, primary)) = self.read_primary(read_options).await? {
+            self.check_view_permission(view, ViewAction::Reduce)?;
+            return cluster
+                .forward_read(
+                    primary,
+                    Aggregate {
+                        database: self.db.name().to_string(),
+                        view: view.clone(),
+                        key,
+                        aggregations: aggregations.to_vec(),
+                        grouping,
+                        access_policy,
+                        read_options,
+                    },
+                )
+                .await;
+        }
+
+        self.db
+            .aggregate_by_name(
+                view,
+                key,
+                aggregations,
+                grouping,
+                access_policy,
+                read_options,
+            )
+            .await
+    }
+
+    async fn count_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        #[cfg(feature = "cluster")]
+        if let Some((cluster, primary)) = self.read_primary(read_options).await? {
+            self.check_view_permission(view, ViewAction::Query)?;
+            return cluster
+                .forward_read(
+                    primary,
+                    CountView {
+                        database: self.db.name().to_string(),
+                        view: view.clone(),
+                        key,
+                        access_policy,
+                        read_options,
+                    },
+                )
+                .await;
+        }
+
         self.db
-            .reduce_grouped_by_name(view, key, access_policy)
+            .count_by_name(view, key, access_policy, read_options)
+            .await
+    }
+
+    async fn document_mappings_by_name(
+        &self,
+        view: &ViewName,
+        document_id: DocumentId,
+        access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+    ) -> Result<Vec<schema::view::map::Serialized>, bonsaidb_core::Error> {
+        #[cfg(feature = "cluster")]
+        if let Some((cluster, primary)) = self.read_primary(read_options).await? {
+            self.check_view_permission(view, ViewAction::Query)?;
+            return cluster
+                .forward_read(
+                    primary,
+                    DocumentMappings {
+                        database: self.db.name().to_string(),
+                        view: view.clone(),
+                        document_id,
+                        access_policy,
+                        read_options,
+                    },
+                )
+                .await;
+        }
+
+        self.db
+            .document_mappings_by_name(view, document_id, access_policy, read_options)
+            .await
+    }
+
+    async fn query_join_by_name(
+        &self,
+        join: &ViewName,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+    ) -> Result<Vec<SerializedJoinedEntry>, bonsaidb_core::Error> {
+        self.db
+            .query_join_by_name(join, key, order, limit, access_policy)
             .await
     }
 
@@ -253,7 +577,16 @@ impl<B: Backend> AsyncLowLevelConnection for ServerDatabase<B> {
         &self,
         transaction: Transaction,
     ) -> Result<Vec<OperationResult>, bonsaidb_core::Error> {
-        self.db.apply_transaction(transaction).await
+        self.server
+            .data
+            .applied_transactions
+            .apply(
+                self.db.name(),
+                Requester::of(self.session()),
+                transaction,
+                |transaction| self.apply_transaction_once(transaction),
+            )
+            .await
     }
 }
 
@@ -0,0 +1,135 @@
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use bonsaidb_core::connection::{Identity, Session, SessionId};
+use bonsaidb_core::transaction::{IdempotencyKey, OperationResult, Transaction};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+/// The results of a transaction applied with an [`IdempotencyKey`]. The lock
+/// is held while the transaction is being applied, which causes retries that
+/// arrive before the original request completes to wait for its results.
+type AppliedResults = Arc<tokio::sync::Mutex<Option<Vec<OperationResult>>>>;
+
+/// Identifies a transaction by its database, requester, and key.
+type AppliedKey = (String, Requester, IdempotencyKey);
+
+/// Who applied a transaction. The results of a transaction are only returned
+/// to the requester that applied it, which prevents another user who learns
+/// the transaction's [`IdempotencyKey`] from reading its results.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Requester {
+    /// A session authenticated as the user with this id.
+    User(u64),
+    /// A session authenticated as the role with this id.
+    Role(u64),
+    /// An unauthenticated session. Sessions without an id are used by the
+    /// server itself.
+    Session(Option<SessionId>),
+}
+
+impl Requester {
+    /// Returns the requester making requests using `session`. Authenticated
+    /// sessions are identified by their identity, allowing a client that
+    /// reconnects to retry its transactions.
+    pub fn of(session: Option<&Session>) -> Self {
+        match session.and_then(Session::identity) {
+            Some(Identity::User { id, .. }) => Self::User(*id),
+            Some(Identity::Role { id, .. }) => Self::Role(*id),
+            _ => Self::Session(session.and_then(|session| session.id)),
+        }
+    }
+}
+
+/// Remembers the results of recently applied transactions by their
+/// [`IdempotencyKey`].
+#[derive(Debug)]
+pub struct AppliedTransactions {
+    window: Duration,
+    limit: usize,
+    applied: Mutex<Applied>,
+}
+
+#[derive(Debug, Default)]
+struct Applied {
+    results: HashMap<AppliedKey, AppliedResults>,
+    /// The keys in `results`, in the order they were received.
+    received: VecDeque<(Instant, AppliedKey)>,
+}
+
+impl Applied {
+    fn forget_oldest(&mut self) {
+        if let Some((_, key)) = self.received.pop_front() {
+            self.results.remove(&key);
+        }
+    }
+}
+
+impl AppliedTransactions {
+    pub fn new(window: Duration, limit: usize) -> Self {
+        Self {
+            window,
+            limit,
+            applied: Mutex::default(),
+        }
+    }
+
+    /// Applies `transaction` to `database` using `apply`, unless a transaction
+    /// with the same [`IdempotencyKey`] has already been applied to
+    /// `database` by `requester`. If it has, the original results are
+    /// returned instead.
+    pub async fn apply<F, Fut>(
+        &self,
+        database: &str,
+        requester: Requester,
+        transaction: Transaction,
+        apply: F,
+    ) -> Result<Vec<OperationResult>, bonsaidb_core::Error>
+    where
+        F: FnOnce(Transaction) -> Fut + Send,
+        Fut: Future<Output = Result<Vec<OperationResult>, bonsaidb_core::Error>> + Send,
+    {
+        let Some(key) = transaction.idempotency_key else {
+            return apply(transaction).await;
+        };
+
+        let results = self.results((database.to_string(), requester, key));
+        let mut results = results.lock().await;
+        if let Some(results) = &*results {
+            return Ok(results.clone());
+        }
+
+        let applied = apply(transaction).await?;
+        *results = Some(applied.clone());
+        Ok(applied)
+    }
+
+    /// Returns the results for `key`. The results are `None` if the
+    /// transaction has not been successfully applied yet.
+    ///
+    /// Expired results are forgotten, and once `limit` results are
+    /// remembered, the oldest results are forgotten to make room for new
+    /// ones.
+    fn results(&self, key: AppliedKey) -> AppliedResults {
+        let now = Instant::now();
+        let mut applied = self.applied.lock();
+        while applied.received.front().map_or(false, |(received_at, _)| {
+            now.saturating_duration_since(*received_at) >= self.window
+        }) {
+            applied.forget_oldest();
+        }
+        if let Some(results) = applied.results.get(&key) {
+            return results.clone();
+        }
+        while applied.results.len() >= self.limit.max(1) {
+            applied.forget_oldest();
+        }
+
+        let results = AppliedResults::default();
+        applied.results.insert(key.clone(), results.clone());
+        applied.received.push_back((now, key));
+        results
+    }
+}
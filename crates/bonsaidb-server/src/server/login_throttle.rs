@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+use crate::config::LoginThrottle;
+
+/// Tracks failed password authentication attempts and applies a
+/// [`LoginThrottle`] to them.
+#[derive(Debug)]
+pub struct LoginThrottler {
+    throttle: LoginThrottle,
+    failures: Mutex<HashMap<ThrottleKey, Failures>>,
+}
+
+/// What failed attempts are counted against.
+///
+/// Users are always counted by their id, ensuring that attempts made using
+/// different forms of a user's name share the same failures.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum ThrottleKey {
+    UserId(u64),
+    Address(IpAddr),
+}
+
+impl ThrottleKey {
+    const fn is_user(&self) -> bool {
+        matches!(self, Self::UserId(_))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Failures {
+    count: u32,
+    last_failure: Instant,
+    locked_until: Option<Instant>,
+}
+
+impl Failures {
+    fn is_locked(&self, now: Instant) -> bool {
+        self.locked_until.map_or(false, |until| until > now)
+    }
+}
+
+impl LoginThrottler {
+    pub fn new(throttle: LoginThrottle) -> Self {
+        Self {
+            throttle,
+            failures: Mutex::default(),
+        }
+    }
+
+    /// Returns the keys that an attempt to authenticate as the user with
+    /// `user_id` from `address` is counted against. `user_id` should be
+    /// `None` if the user doesn't exist.
+    pub fn keys(&self, user_id: Option<u64>, address: Option<IpAddr>) -> Vec<ThrottleKey> {
+        let mut keys = Vec::with_capacity(2);
+        if self.throttle.per_username {
+            keys.extend(user_id.map(ThrottleKey::UserId));
+        }
+        if self.throttle.per_address {
+            keys.extend(address.map(ThrottleKey::Address));
+        }
+        keys
+    }
+
+    /// Returns an error if an attempt counted against `keys` is not allowed
+    /// at `now`.
+    pub fn check(&self, keys: &[ThrottleKey], now: Instant) -> Result<(), bonsaidb_core::Error> {
+        let failures = self.failures.lock();
+        let mut retry_after = None;
+        for key in keys {
+            if let Some(entry) = failures.get(key) {
+                if let Some(until) = entry.locked_until {
+                    if until > now {
+                        return Err(bonsaidb_core::Error::AccountLocked {
+                            retry_after: Some(until.saturating_duration_since(now)),
+                        });
+                    }
+                    // The failures that caused an expired lock are forgotten.
+                    continue;
+                } else if now.saturating_duration_since(entry.last_failure)
+                    >= self.throttle.reset_after
+                {
+                    continue;
+                }
+                if let Some(delay) = self.delay_after(entry.count) {
+                    let wait = (entry.last_failure + delay).saturating_duration_since(now);
+                    if wait > Duration::ZERO {
+                        retry_after = retry_after.max(Some(wait));
+                    }
+                }
+            }
+        }
+
+        match retry_after {
+            Some(retry_after) => Err(bonsaidb_core::Error::RateLimited {
+                retry_after: Some(retry_after),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Records a failed attempt against `keys` at `now`.
+    pub fn record_failure(&self, keys: &[ThrottleKey], now: Instant) {
+        let mut failures = self.failures.lock();
+        let reset_after = self.throttle.reset_after;
+        failures.retain(|_, entry| {
+            entry.is_locked(now)
+                || (entry.locked_until.is_none()
+                    && now.saturating_duration_since(entry.last_failure) < reset_after)
+        });
+        for key in keys {
+            if let ThrottleKey::Address(_) = key {
+                if !failures.contains_key(key) {
+                    Self::make_room_for_address(
+                        &mut failures,
+                        self.throttle.maximum_addresses,
+                        now,
+                    );
+                }
+            }
+            let entry = failures.entry(*key).or_insert(Failures {
+                count: 0,
+                last_failure: now,
+                locked_until: None,
+            });
+            entry.count = entry.count.saturating_add(1);
+            entry.last_failure = now;
+            if let Some(lockout) = self.throttle.lockout {
+                if entry.locked_until.is_none() && entry.count >= lockout.failures.max(1) {
+                    entry.locked_until = Some(now + lockout.duration);
+                }
+            }
+        }
+    }
+
+    /// Forgets addresses until fewer than `maximum` are tracked, starting
+    /// with the unlocked addresses whose last failure is the oldest. Users
+    /// aren't limited, because only existing users are tracked.
+    fn make_room_for_address(
+        failures: &mut HashMap<ThrottleKey, Failures>,
+        maximum: usize,
+        now: Instant,
+    ) {
+        let tracked = failures.keys().filter(|key| !key.is_user()).count();
+        let excess = (tracked + 1).saturating_sub(maximum.max(1));
+        if excess == 0 {
+            return;
+        }
+        let mut addresses = failures
+            .iter()
+            .filter(|(key, _)| !key.is_user())
+            .map(|(key, entry)| (entry.is_locked(now), entry.last_failure, *key))
+            .collect::<Vec<_>>();
+        addresses.sort_unstable_by_key(|(locked, last_failure, _)| (*locked, *last_failure));
+        for (_, _, key) in addresses.into_iter().take(excess) {
+            failures.remove(&key);
+        }
+    }
+
+    /// Records a successful attempt, clearing the failures counted against
+    /// the user. Failures counted against the address are kept so that an
+    /// attacker cannot reset them by authenticating as an account they
+    /// control.
+    pub fn record_success(&self, keys: &[ThrottleKey]) {
+        let mut failures = self.failures.lock();
+        for key in keys.iter().filter(|key| key.is_user()) {
+            failures.remove(key);
+        }
+    }
+
+    /// Clears the failures counted against the user with `id`.
+    pub fn unlock_user(&self, id: u64) {
+        self.failures.lock().remove(&ThrottleKey::UserId(id));
+    }
+
+    /// Clears the failures counted against `address`.
+    pub fn unlock_address(&self, address: IpAddr) {
+        self.failures.lock().remove(&ThrottleKey::Address(address));
+    }
+
+    /// Returns the delay required after `count` failures, if any.
+    fn delay_after(&self, count: u32) -> Option<Duration> {
+        let delayed = count.checked_sub(self.throttle.free_attempts)?;
+        if delayed == 0 {
+            return None;
+        }
+        let delay = 2_u32
+            .checked_pow(delayed - 1)
+            .and_then(|factor| self.throttle.initial_delay.checked_mul(factor))
+            .unwrap_or(self.throttle.maximum_delay);
+        Some(delay.min(self.throttle.maximum_delay))
+    }
+}
@@ -0,0 +1,73 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::Error;
+
+const SIGNATURE: &[u8; 12] = b"\r\n\r\n\0\r\nQUIT\n";
+
+const COMMAND_LOCAL: u8 = 0;
+const COMMAND_PROXY: u8 = 1;
+
+const FAMILY_INET: u8 = 1;
+const FAMILY_INET6: u8 = 2;
+
+/// Reads a PROXY protocol version 2 header from `stream`, returning the
+/// address of the client the proxy accepted the connection from.
+///
+/// `None` is returned when the proxy made the connection on its own behalf,
+/// such as for health checks, or when the client's address isn't an IP
+/// address. Only the header is read from `stream`, which leaves the stream
+/// positioned at the start of the proxied connection's data.
+pub(crate) async fn read_header<S: AsyncRead + Unpin>(
+    stream: &mut S,
+) -> Result<Option<SocketAddr>, Error> {
+    let mut header = [0; 16];
+    stream.read_exact(&mut header).await?;
+    if &header[..12] != SIGNATURE || header[12] >> 4 != 2 {
+        return Err(Error::other(
+            "proxy protocol",
+            "connection did not begin with a PROXY protocol v2 header",
+        ));
+    }
+
+    // The address block is always read, even when it's ignored, to consume
+    // the entire header.
+    let length = usize::from(u16::from_be_bytes([header[14], header[15]]));
+    let mut addresses = vec![0; length];
+    stream.read_exact(&mut addresses).await?;
+
+    match header[12] & 0xF {
+        COMMAND_LOCAL => return Ok(None),
+        COMMAND_PROXY => {}
+        other => {
+            return Err(Error::other(
+                "proxy protocol",
+                format!("unsupported command {other}"),
+            ))
+        }
+    }
+
+    // The address block begins with the source address, followed by the
+    // destination address, the source port, and the destination port. Any
+    // remaining bytes are type-length-value extensions, which are ignored.
+    match header[13] >> 4 {
+        FAMILY_INET if addresses.len() >= 12 => {
+            let ip = Ipv4Addr::new(addresses[0], addresses[1], addresses[2], addresses[3]);
+            let port = u16::from_be_bytes([addresses[8], addresses[9]]);
+            Ok(Some(SocketAddr::new(IpAddr::V4(ip), port)))
+        }
+        FAMILY_INET6 if addresses.len() >= 36 => {
+            let mut ip = [0; 16];
+            ip.copy_from_slice(&addresses[..16]);
+            let port = u16::from_be_bytes([addresses[32], addresses[33]]);
+            Ok(Some(SocketAddr::new(IpAddr::V6(Ipv6Addr::from(ip)), port)))
+        }
+        FAMILY_INET | FAMILY_INET6 => Err(Error::other(
+            "proxy protocol",
+            "address block is too short for its address family",
+        )),
+        // AF_UNSPEC and AF_UNIX
+        _ => Ok(None),
+    }
+}
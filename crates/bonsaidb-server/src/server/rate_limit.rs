@@ -0,0 +1,148 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+use crate::config::RateLimit;
+
+/// Tracks the requests made against a [`RateLimit`] using token buckets that
+/// refill continuously.
+#[derive(Debug)]
+pub struct RateLimiter {
+    limit: RateLimit,
+    state: Mutex<RateLimiterState>,
+}
+
+#[derive(Debug)]
+struct RateLimiterState {
+    request_tokens: f64,
+    byte_tokens: f64,
+    refilled_at: Instant,
+    in_flight: usize,
+}
+
+impl RateLimiter {
+    #[allow(clippy::cast_precision_loss)]
+    pub fn new(limit: RateLimit) -> Arc<Self> {
+        Arc::new(Self {
+            limit,
+            state: Mutex::new(RateLimiterState {
+                request_tokens: limit.requests_per_second.map_or(0., f64::from),
+                byte_tokens: limit.bytes_per_second.map_or(0., |bytes| bytes as f64),
+                refilled_at: Instant::now(),
+                in_flight: 0,
+            }),
+        })
+    }
+}
+
+impl RateLimiterState {
+    #[allow(clippy::cast_precision_loss)]
+    fn refill(&mut self, limit: &RateLimit, now: Instant) {
+        let elapsed = now
+            .saturating_duration_since(self.refilled_at)
+            .as_secs_f64();
+        self.refilled_at = now;
+        if let Some(rate) = limit.requests_per_second {
+            let rate = f64::from(rate);
+            self.request_tokens = (self.request_tokens + elapsed * rate).min(rate);
+        }
+        if let Some(rate) = limit.bytes_per_second {
+            let rate = rate as f64;
+            self.byte_tokens = (self.byte_tokens + elapsed * rate).min(rate);
+        }
+    }
+
+    /// Returns `Ok` if a request can begin. If not, the error contains how long
+    /// to wait before retrying, if known.
+    #[allow(clippy::cast_precision_loss)]
+    fn check(&self, limit: &RateLimit) -> Result<(), Option<Duration>> {
+        if let Some(concurrent_requests) = limit.concurrent_requests {
+            if self.in_flight >= concurrent_requests {
+                return Err(None);
+            }
+        }
+
+        let mut retry_after = None;
+        if let Some(rate) = limit.requests_per_second {
+            if self.request_tokens < 1. {
+                let wait = (1. - self.request_tokens) / f64::from(rate.max(1));
+                retry_after = Some(Duration::from_secs_f64(wait));
+            }
+        }
+        if let Some(rate) = limit.bytes_per_second {
+            if self.byte_tokens < 0. {
+                let wait = Duration::from_secs_f64(-self.byte_tokens / rate.max(1) as f64);
+                retry_after = retry_after.max(Some(wait));
+            }
+        }
+
+        match retry_after {
+            Some(retry_after) => Err(Some(retry_after)),
+            None => Ok(()),
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn consume(&mut self, limit: &RateLimit, request_bytes: usize) {
+        if limit.requests_per_second.is_some() {
+            self.request_tokens -= 1.;
+        }
+        if limit.bytes_per_second.is_some() {
+            self.byte_tokens -= request_bytes as f64;
+        }
+        self.in_flight += 1;
+    }
+}
+
+/// A request that has been admitted by one or more [`RateLimiter`]s. The
+/// request is considered in flight until this permit is dropped.
+#[derive(Debug)]
+pub struct RequestPermit {
+    limiters: Vec<Arc<RateLimiter>>,
+}
+
+impl RequestPermit {
+    /// Admits a request of `request_bytes` if every limiter in `limiters`
+    /// allows it. No limiter is charged for the request unless all of them
+    /// admit it.
+    pub fn acquire<'a>(
+        limiters: impl IntoIterator<Item = &'a Arc<RateLimiter>>,
+        request_bytes: usize,
+    ) -> Result<Self, bonsaidb_core::Error> {
+        let limiters = limiters.into_iter().cloned().collect::<Vec<_>>();
+        let now = Instant::now();
+        let mut states = limiters
+            .iter()
+            .map(|limiter| limiter.state.lock())
+            .collect::<Vec<_>>();
+
+        let mut rejected = false;
+        let mut retry_after = None;
+        for (limiter, state) in limiters.iter().zip(states.iter_mut()) {
+            state.refill(&limiter.limit, now);
+            if let Err(wait) = state.check(&limiter.limit) {
+                rejected = true;
+                retry_after = retry_after.max(wait);
+            }
+        }
+        if rejected {
+            return Err(bonsaidb_core::Error::RateLimited { retry_after });
+        }
+
+        for (limiter, state) in limiters.iter().zip(states.iter_mut()) {
+            state.consume(&limiter.limit, request_bytes);
+        }
+        drop(states);
+
+        Ok(Self { limiters })
+    }
+}
+
+impl Drop for RequestPermit {
+    fn drop(&mut self) {
+        for limiter in &self.limiters {
+            limiter.state.lock().in_flight -= 1;
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use std::cell::RefCell;
+use std::fs::OpenOptions;
+use std::io::{BufWriter, Write};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use bonsaidb_core::api::ApiName;
+use bonsaidb_core::arc_bytes::serde::Bytes;
+use bonsaidb_core::connection::{Identity, Session};
+use futures::Future;
+use serde::{Deserialize, Serialize};
+
+use crate::{Backend, ConnectedClient, Error, RequestLogConfiguration, RequestLogDestination};
+
+tokio::task_local! {
+    static REQUEST_DATABASE: RefCell<Option<String>>;
+}
+
+/// A request processed by a server, as recorded by its
+/// [request log](crate::ServerConfiguration#structfield.request_log).
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct RequestLogEntry {
+    /// When the request finished processing, in milliseconds since the Unix
+    /// epoch.
+    pub timestamp: u64,
+    /// The address of the client that made the request.
+    pub address: SocketAddr,
+    /// The id of the user the request was made as, if the request was made
+    /// by an authenticated user.
+    pub user_id: Option<u64>,
+    /// The username of the user the request was made as, if the request was
+    /// made by an authenticated user.
+    pub username: Option<String>,
+    /// The name of the first database the request accessed, if any.
+    pub database: Option<String>,
+    /// The name of the [`Api`](bonsaidb_core::api::Api) that was requested.
+    pub operation: String,
+    /// How long the request took to process, from when it was received until
+    /// its response was ready, in microseconds. This includes the time the
+    /// request waited for a worker.
+    pub duration_micros: u64,
+    /// `ok` if the request succeeded, otherwise the
+    /// [code](bonsaidb_core::ErrorCode::as_str) of the error returned.
+    pub result: String,
+    /// The size of the request's payload, in bytes.
+    pub request_bytes: usize,
+    /// The size of the response's payload, in bytes. This is 0 for requests
+    /// that returned an error.
+    pub response_bytes: usize,
+}
+
+/// Records the first database accessed by the request being processed by the
+/// current task.
+pub(crate) fn record_database(name: &str) {
+    drop(REQUEST_DATABASE.try_with(|database| {
+        let mut database = database.borrow_mut();
+        if database.is_none() {
+            *database = Some(name.to_string());
+        }
+    }));
+}
+
+/// The request log of a server, which decides which requests are logged and
+/// writes their entries.
+#[derive(Debug)]
+pub(crate) struct RequestLog {
+    config: RequestLogConfiguration,
+    requests: AtomicU64,
+    writer: Option<flume::Sender<RequestLogEntry>>,
+}
+
+impl RequestLog {
+    pub fn new(config: RequestLogConfiguration) -> Result<Self, Error> {
+        let writer = match &config.destination {
+            RequestLogDestination::JsonLines(path) => {
+                let file = OpenOptions::new().create(true).append(true).open(path)?;
+                let (sender, receiver) = flume::unbounded();
+                // The file is written by its own thread to avoid blocking the
+                // request workers on file IO.
+                std::thread::Builder::new()
+                    .name(String::from("bonsaidb-request-log"))
+                    .spawn(move || write_json_lines(&receiver, BufWriter::new(file)))?;
+                Some(sender)
+            }
+            #[cfg(feature = "instrument")]
+            RequestLogDestination::Tracing => None,
+        };
+
+        Ok(Self {
+            config,
+            requests: AtomicU64::new(0),
+            writer,
+        })
+    }
+
+    /// Awaits `response` while recording the database it accesses, and logs
+    /// the request if it is selected by the log's sampling settings.
+    pub async fn record<B: Backend>(
+        &self,
+        client: &ConnectedClient<B>,
+        session: &Session,
+        operation: &ApiName,
+        received_at: Instant,
+        request_bytes: usize,
+        response: impl Future<Output = Result<Bytes, bonsaidb_core::Error>>,
+    ) -> Result<Bytes, bonsaidb_core::Error> {
+        let (result, database) = REQUEST_DATABASE
+            .scope(RefCell::new(None), async {
+                let result = response.await;
+                (result, REQUEST_DATABASE.with(RefCell::take))
+            })
+            .await;
+
+        let duration = received_at.elapsed();
+        if !self.is_sampled(duration, result.is_err()) {
+            return result;
+        }
+
+        let (user_id, username) = match session.identity() {
+            Some(Identity::User { id, username }) => (Some(*id), Some(username.clone())),
+            _ => (None, None),
+        };
+        let entry = RequestLogEntry {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |timestamp| {
+                    u64::try_from(timestamp.as_millis()).unwrap_or(u64::MAX)
+                }),
+            address: *client.address(),
+            user_id,
+            username,
+            database,
+            operation: operation.to_string(),
+            duration_micros: u64::try_from(duration.as_micros()).unwrap_or(u64::MAX),
+            result: match &result {
+                Ok(_) => String::from("ok"),
+                Err(err) => err.code().as_str().to_string(),
+            },
+            request_bytes,
+            response_bytes: result.as_ref().map_or(0, |response| response.len()),
+        };
+        self.write(entry);
+
+        result
+    }
+
+    /// Returns true if a request that took `duration` should be logged.
+    pub fn is_sampled(&self, duration: Duration, is_error: bool) -> bool {
+        if is_error && self.config.always_log_errors {
+            return true;
+        }
+        if self
+            .config
+            .slow_request_threshold
+            .map_or(false, |threshold| duration >= threshold)
+        {
+            return true;
+        }
+        let sample_one_in = u64::from(self.config.sample_one_in.max(1));
+        self.requests.fetch_add(1, Ordering::Relaxed) % sample_one_in == 0
+    }
+
+    /// Writes `entry` to the log's destination.
+    pub fn write(&self, entry: RequestLogEntry) {
+        match &self.writer {
+            Some(writer) => drop(writer.send(entry)),
+            #[cfg(feature = "instrument")]
+            None => tracing::info!(
+                target: "bonsaidb_server::requests",
+                timestamp = entry.timestamp,
+                address = %entry.address,
+                user_id = entry.user_id,
+                username = entry.username.as_deref(),
+                database = entry.database.as_deref(),
+                operation = %entry.operation,
+                duration_micros = entry.duration_micros,
+                result = %entry.result,
+                request_bytes = entry.request_bytes,
+                response_bytes = entry.response_bytes,
+            ),
+            #[cfg(not(feature = "instrument"))]
+            None => {}
+        }
+    }
+}
+
+fn write_json_lines(receiver: &flume::Receiver<RequestLogEntry>, mut file: impl Write) {
+    while let Ok(entry) = receiver.recv() {
+        let result = serde_json::to_writer(&mut file, &entry)
+            .map_err(std::io::Error::from)
+            .and_then(|_| file.write_all(b"\n"))
+            // Entries are flushed once the queue is empty, which batches
+            // writes while requests are arriving faster than they are written.
+            .and_then(|_| {
+                if receiver.is_empty() {
+                    file.flush()
+                } else {
+                    Ok(())
+                }
+            });
+        if let Err(err) = result {
+            log::error!("[server] error writing request log: {err}");
+        }
+    }
+}
@@ -1,10 +1,18 @@
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use rustls::server::ResolvesServerCert;
+use bonsaidb_core::connection::Session;
+use fabruic::Certificate;
+use rustls::server::{
+    AllowAnyAnonymousOrAuthenticatedClient, AllowAnyAuthenticatedClient, ClientCertVerifier,
+    NoClientAuth, ResolvesServerCert,
+};
+use rustls::RootCertStore;
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::net::TcpListener;
 
+use crate::server::proxy_protocol;
 use crate::{Backend, CustomServer, Error};
 
 impl<B: Backend> CustomServer<B> {
@@ -14,6 +22,19 @@ impl<B: Backend> CustomServer<B> {
         &self,
         addr: T,
         service: S,
+    ) -> Result<(), Error> {
+        self.listen_for_tcp_on_with_session(addr, service, self.data.default_session.clone())
+            .await
+    }
+
+    pub(crate) async fn listen_for_tcp_on_with_session<
+        S: TcpService,
+        T: tokio::net::ToSocketAddrs + Send + Sync,
+    >(
+        &self,
+        addr: T,
+        service: S,
+        default_session: Session,
     ) -> Result<(), Error> {
         let listener = TcpListener::bind(&addr).await?;
         let mut shutdown_watcher = self
@@ -32,18 +53,27 @@ impl<B: Backend> CustomServer<B> {
                     if incoming.is_err() {
                         continue;
                     }
-                    let (connection, remote_addr) = incoming.unwrap();
-
-                    let peer = Peer {
-                        address: remote_addr,
-                        protocol: service.available_protocols()[0].clone(),
-                        secure: false,
-                    };
+                    let (mut connection, remote_addr) = incoming.unwrap();
 
                     let task_self = self.clone();
                     let task_service = service.clone();
+                    let default_session = default_session.clone();
                     tokio::spawn(async move {
-                        if let Err(err) = task_self.handle_tcp_connection(connection, peer, &task_service).await {
+                        let remote_addr = match task_self.client_address(&mut connection, remote_addr).await {
+                            Ok(address) => address,
+                            Err(err) => {
+                                log::error!("[server] error reading proxy header from {}: {:?}", remote_addr, err);
+                                return;
+                            }
+                        };
+                        let peer = Peer {
+                            address: remote_addr,
+                            protocol: task_service.available_protocols()[0].clone(),
+                            secure: false,
+                            client_certificate: None,
+                        };
+
+                        if let Err(err) = task_self.handle_tcp_connection(connection, peer, &task_service, &default_session).await {
                             log::error!("[server] closing connection {}: {:?}", remote_addr, err);
                         }
                     });
@@ -59,32 +89,38 @@ impl<B: Backend> CustomServer<B> {
     /// `acme` is enabled, this connection will automatically manage the
     /// server's private key and certificate, which is also used for the
     /// QUIC-based protocol.
+    pub async fn listen_for_secure_tcp_on<
+        S: TcpService,
+        T: tokio::net::ToSocketAddrs + Send + Sync,
+    >(
+        &self,
+        addr: T,
+        service: S,
+    ) -> Result<(), Error> {
+        self.listen_for_secure_tcp_on_with_session(addr, service, self.data.default_session.clone())
+            .await
+    }
+
     #[cfg_attr(not(feature = "websockets"), allow(unused_variables))]
     #[cfg_attr(not(feature = "acme"), allow(unused_mut))]
-    pub async fn listen_for_secure_tcp_on<
+    pub(crate) async fn listen_for_secure_tcp_on_with_session<
         S: TcpService,
         T: tokio::net::ToSocketAddrs + Send + Sync,
     >(
         &self,
         addr: T,
         service: S,
+        default_session: Session,
     ) -> Result<(), Error> {
         // We may not have a certificate yet, so we ignore any errors.
         drop(self.refresh_certified_key().await);
 
         #[cfg(feature = "acme")]
-        {
-            let task_self = self.clone();
-            tokio::task::spawn(async move {
-                if let Err(err) = task_self.update_acme_certificates().await {
-                    log::error!("[server] acme task error: {0}", err);
-                }
-            });
-        }
+        self.spawn_acme_task();
 
         let mut config = rustls::ServerConfig::builder()
             .with_safe_defaults()
-            .with_no_client_auth()
+            .with_client_cert_verifier(self.client_certificate_verifier()?)
             .with_cert_resolver(Arc::new(self.clone()));
         config.alpn_protocols = service
             .available_protocols()
@@ -94,13 +130,37 @@ impl<B: Backend> CustomServer<B> {
 
         let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(config));
         let listener = TcpListener::bind(&addr).await?;
+        let mut shutdown_watcher = self
+            .data
+            .shutdown
+            .watcher()
+            .await
+            .expect("server already shutdown");
+
         loop {
-            let (stream, peer_addr) = listener.accept().await?;
+            let (mut stream, peer_addr) = tokio::select! {
+                _ = shutdown_watcher.wait_for_shutdown() => {
+                    break;
+                }
+                incoming = listener.accept() => incoming?,
+            };
             let acceptor = acceptor.clone();
 
             let task_self = self.clone();
             let task_service = service.clone();
+            let default_session = default_session.clone();
             tokio::task::spawn(async move {
+                let peer_addr = match task_self.client_address(&mut stream, peer_addr).await {
+                    Ok(address) => address,
+                    Err(err) => {
+                        log::error!(
+                            "[server] error reading proxy header from {}: {:?}",
+                            peer_addr,
+                            err
+                        );
+                        return;
+                    }
+                };
                 let stream = match acceptor.accept(stream).await {
                     Ok(stream) => stream,
                     Err(err) => {
@@ -121,19 +181,72 @@ impl<B: Backend> CustomServer<B> {
                             .cloned()
                     })
                     .unwrap_or_else(|| available_protocols[0].clone());
+                // The verifier has already validated the certificate chain, so
+                // only the end-entity certificate is needed.
+                let client_certificate = stream
+                    .get_ref()
+                    .1
+                    .peer_certificates()
+                    .and_then(|certificates| certificates.first())
+                    .and_then(|certificate| Certificate::from_der(certificate.0.clone()).ok());
                 let peer = Peer {
                     address: peer_addr,
                     secure: true,
                     protocol,
+                    client_certificate,
                 };
                 if let Err(err) = task_self
-                    .handle_tcp_connection(stream, peer, &task_service)
+                    .handle_tcp_connection(stream, peer, &task_service, &default_session)
                     .await
                 {
                     log::error!("[server] error for client {}: {:?}", peer_addr, err);
                 }
             });
         }
+
+        Ok(())
+    }
+
+    /// Returns the address of the client connected through `stream`, which
+    /// was accepted from `peer_addr`. If `peer_addr` is one of the
+    /// [trusted proxies](crate::ServerConfiguration#structfield.trusted_proxies),
+    /// the client's address is read from the PROXY protocol header at the
+    /// start of `stream`.
+    async fn client_address<C: AsyncRead + Unpin>(
+        &self,
+        stream: &mut C,
+        peer_addr: SocketAddr,
+    ) -> Result<SocketAddr, Error> {
+        let ip = match peer_addr.ip() {
+            IpAddr::V6(ip) => ip.to_ipv4_mapped().map_or(IpAddr::V6(ip), IpAddr::V4),
+            ip @ IpAddr::V4(_) => ip,
+        };
+        if !self.data.trusted_proxies.contains(&ip) {
+            return Ok(peer_addr);
+        }
+
+        Ok(proxy_protocol::read_header(stream)
+            .await?
+            .unwrap_or(peer_addr))
+    }
+
+    fn client_certificate_verifier(&self) -> Result<Arc<dyn ClientCertVerifier>, Error> {
+        let Some(authentication) = &self.data.client_certificate_authentication else {
+            return Ok(NoClientAuth::new());
+        };
+
+        let mut roots = RootCertStore::empty();
+        for authority in &authentication.trusted_authorities {
+            roots
+                .add(&rustls::Certificate(authority.as_ref().to_vec()))
+                .map_err(|err| Error::other("client certificate authority", err))?;
+        }
+
+        if authentication.required {
+            Ok(AllowAnyAuthenticatedClient::new(roots))
+        } else {
+            Ok(AllowAnyAnonymousOrAuthenticatedClient::new(roots))
+        }
     }
 
     #[cfg_attr(not(feature = "websockets"), allow(unused_variables))]
@@ -145,6 +258,7 @@ impl<B: Backend> CustomServer<B> {
         connection: C,
         peer: Peer<S::ApplicationProtocols>,
         service: &S,
+        default_session: &Session,
     ) -> Result<(), Error> {
         // For ACME, don't send any traffic over the connection.
         #[cfg(feature = "acme")]
@@ -156,7 +270,13 @@ impl<B: Backend> CustomServer<B> {
         if let Err(connection) = service.handle_connection(connection, &peer).await {
             #[cfg(feature = "websockets")]
             if let Err(err) = self
-                .handle_raw_websocket_connection(connection, peer.address)
+                .handle_raw_websocket_connection(
+                    connection,
+                    crate::Transport::WebSocket,
+                    peer.address,
+                    peer.client_certificate.as_ref(),
+                    default_session,
+                )
                 .await
             {
                 log::error!(
@@ -295,6 +415,11 @@ pub struct Peer<P: ApplicationProtocols = StandardTcpProtocols> {
     pub secure: bool,
     /// The application protocol to use for this connection.
     pub protocol: P,
+    /// The certificate the peer authenticated with during the TLS handshake,
+    /// if
+    /// [client certificate authentication](crate::ClientCertificateAuthentication)
+    /// is enabled and the peer presented one.
+    pub client_certificate: Option<Certificate>,
 }
 
 /// TCP [`ApplicationProtocols`] that BonsaiDb has some knowledge of.
@@ -0,0 +1,83 @@
+use std::net::{Ipv6Addr, SocketAddr};
+use std::os::unix::fs::FileTypeExt;
+use std::path::Path;
+
+use bonsaidb_core::connection::Session;
+use tokio::net::UnixListener;
+
+use crate::{Backend, CustomServer, Error, Transport};
+
+impl<B: Backend> CustomServer<B> {
+    /// Listens for `WebSocket` connections on a Unix domain socket at `path`.
+    ///
+    /// Connections made over the socket are not encrypted, and access to the
+    /// server is controlled by the socket's filesystem permissions. If a socket
+    /// already exists at `path`, such as one left behind by a previous server
+    /// process, it is replaced. The socket is removed when the server shuts
+    /// down.
+    ///
+    /// Because Unix domain sockets do not have a network address, clients
+    /// connected using this listener report the IPv6 loopback address from
+    /// [`ConnectedClient::address()`](crate::ConnectedClient::address).
+    pub async fn listen_on_unix_socket<P: AsRef<Path> + Send + Sync>(
+        &self,
+        path: P,
+    ) -> Result<(), Error> {
+        self.listen_on_unix_socket_with_session(path.as_ref(), self.data.default_session.clone())
+            .await
+    }
+
+    pub(crate) async fn listen_on_unix_socket_with_session(
+        &self,
+        path: &Path,
+        default_session: Session,
+    ) -> Result<(), Error> {
+        match tokio::fs::metadata(path).await {
+            Ok(metadata) if metadata.file_type().is_socket() => {
+                tokio::fs::remove_file(path).await?;
+            }
+            _ => {}
+        }
+
+        let listener = UnixListener::bind(path)?;
+        let mut shutdown_watcher = self
+            .data
+            .shutdown
+            .watcher()
+            .await
+            .expect("server already shutdown");
+
+        loop {
+            tokio::select! {
+                _ = shutdown_watcher.wait_for_shutdown() => {
+                    break;
+                }
+                incoming = listener.accept() => {
+                    let Ok((connection, _)) = incoming else { continue };
+
+                    let task_self = self.clone();
+                    let default_session = default_session.clone();
+                    tokio::spawn(async move {
+                        if let Err(err) = task_self
+                            .handle_raw_websocket_connection(
+                                connection,
+                                Transport::UnixSocket,
+                                SocketAddr::from((Ipv6Addr::LOCALHOST, 0)),
+                                None,
+                                &default_session,
+                            )
+                            .await
+                        {
+                            log::error!("[server] error on unix socket connection: {:?}", err);
+                        }
+                    });
+                }
+            }
+        }
+
+        drop(listener);
+        tokio::fs::remove_file(path).await?;
+
+        Ok(())
+    }
+}
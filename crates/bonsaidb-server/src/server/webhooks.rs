@@ -0,0 +1,305 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+
+use bonsaidb_core::admin::{Webhook, WebhookChange, WebhookEvent};
+use bonsaidb_core::arc_bytes::serde::CowBytes;
+use bonsaidb_core::connection::{AsyncConnection, AsyncLowLevelConnection, HasSchema};
+use bonsaidb_core::document::{BorrowedDocument, CollectionDocument};
+use bonsaidb_core::keyvalue::AsyncKeyValue;
+use bonsaidb_core::schema::SerializedCollection;
+use bonsaidb_core::transaction::Executed;
+use bonsaidb_local::AsyncStorage;
+use hyper::client::HttpConnector;
+use hyper::{Body, Client, Request};
+
+use super::{Data, ShutdownStateWatcher};
+use crate::config::WebhookConfiguration;
+use crate::{Backend, CustomServer, Error, ServerDatabase};
+
+/// The key-value namespace in the admin database that stores the id of the
+/// next transaction to deliver to each webhook.
+const WEBHOOK_NAMESPACE: &str = "_bonsaidb.webhooks";
+
+/// The maximum number of transactions read for each webhook per poll.
+const TRANSACTION_BATCH_SIZE: u32 = 100;
+
+impl<B: Backend> CustomServer<B> {
+    /// Spawns the task that delivers document changes to the webhooks stored
+    /// in the admin database. The task exits when the server is shut down or
+    /// dropped.
+    pub(crate) async fn spawn_webhook_task(&self) {
+        let Some(shutdown) = self.data.shutdown.watcher().await else {
+            return;
+        };
+        let task = WebhookTask {
+            data: Arc::downgrade(&self.data),
+            storage: self.storage.clone(),
+            configuration: self.data.webhooks,
+            client: Client::new(),
+            retries: HashMap::new(),
+        };
+        tokio::task::spawn(task.run(shutdown));
+    }
+}
+
+struct WebhookTask<B: Backend> {
+    data: Weak<Data<B>>,
+    storage: AsyncStorage,
+    configuration: WebhookConfiguration,
+    client: Client<HttpConnector>,
+    retries: HashMap<u64, RetryState>,
+}
+
+struct RetryState {
+    attempts: u32,
+    retry_at: Instant,
+}
+
+enum DeliveryError {
+    /// The event for a transaction was not accepted by the webhook's endpoint.
+    Request { transaction_id: u64, error: String },
+    /// The webhook's pending changes could not be read.
+    Server(Error),
+}
+
+impl From<Error> for DeliveryError {
+    fn from(err: Error) -> Self {
+        Self::Server(err)
+    }
+}
+
+impl From<bonsaidb_core::Error> for DeliveryError {
+    fn from(err: bonsaidb_core::Error) -> Self {
+        Self::Server(Error::from(err))
+    }
+}
+
+impl<B: Backend> WebhookTask<B> {
+    async fn run(mut self, mut shutdown: ShutdownStateWatcher) {
+        loop {
+            tokio::select! {
+                _ = shutdown.wait_for_shutdown() => break,
+                _ = tokio::time::sleep(self.configuration.poll_interval) => {}
+            }
+
+            // Only hold a reference to the server while polling, which allows
+            // the server to be dropped without being shut down.
+            let Some(data) = self.data.upgrade() else {
+                break;
+            };
+            let server = CustomServer {
+                data,
+                storage: self.storage.clone(),
+            };
+            if let Err(err) = self.poll(&server).await {
+                log::error!("Error delivering webhooks: {err}");
+            }
+        }
+    }
+
+    async fn poll(&mut self, server: &CustomServer<B>) -> Result<(), Error> {
+        let admin = server.admin().await;
+        let webhooks = Webhook::all_async(&admin).await?;
+        self.retries
+            .retain(|id, _| webhooks.iter().any(|webhook| webhook.header.id == *id));
+
+        let now = Instant::now();
+        let ready = webhooks
+            .iter()
+            .filter(|webhook| {
+                self.retries
+                    .get(&webhook.header.id)
+                    .map_or(true, |retry| retry.retry_at <= now)
+            })
+            .collect::<Vec<_>>();
+        let results = futures::future::join_all(ready.iter().map(|webhook| {
+            deliver_pending(server, &admin, webhook, &self.client, &self.configuration)
+        }))
+        .await;
+
+        for (webhook, result) in ready.into_iter().zip(results) {
+            let id = webhook.header.id;
+            let error = match result {
+                Ok(()) => {
+                    self.retries.remove(&id);
+                    continue;
+                }
+                Err(error) => error,
+            };
+
+            let retry = self.retries.entry(id).or_insert(RetryState {
+                attempts: 0,
+                retry_at: now,
+            });
+            retry.attempts += 1;
+            match error {
+                DeliveryError::Request {
+                    transaction_id,
+                    error,
+                } if retry.attempts >= self.configuration.max_attempts => {
+                    log::error!(
+                        "Skipping transaction {transaction_id} for webhook {id} after {} failed attempts: {error}",
+                        retry.attempts
+                    );
+                    self.retries.remove(&id);
+                    admin
+                        .with_key_namespace(WEBHOOK_NAMESPACE)
+                        .set_numeric_key(id.to_string(), transaction_id + 1)
+                        .await?;
+                }
+                DeliveryError::Request {
+                    transaction_id,
+                    error,
+                } => {
+                    log::warn!(
+                        "Error delivering transaction {transaction_id} to webhook {id}: {error}"
+                    );
+                    retry.retry_at = now + self.configuration.retry_delay(retry.attempts);
+                }
+                DeliveryError::Server(err) => {
+                    log::error!("Error reading changes for webhook {id}: {err}");
+                    retry.retry_at = now + self.configuration.retry_delay(retry.attempts);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl WebhookConfiguration {
+    /// Returns the delay before retrying a delivery that has failed `attempts`
+    /// times in a row.
+    fn retry_delay(&self, attempts: u32) -> Duration {
+        self.initial_retry_delay
+            .saturating_mul(2_u32.saturating_pow(attempts.saturating_sub(1)))
+            .min(self.max_retry_delay)
+    }
+}
+
+/// Delivers each transaction executed since the last delivery to `webhook`,
+/// stopping at the first transaction that can't be delivered.
+async fn deliver_pending<B: Backend>(
+    server: &CustomServer<B>,
+    admin: &ServerDatabase<B>,
+    webhook: &CollectionDocument<Webhook>,
+    client: &Client<HttpConnector>,
+    configuration: &WebhookConfiguration,
+) -> Result<(), DeliveryError> {
+    let cursors = admin.with_key_namespace(WEBHOOK_NAMESPACE);
+    let cursor_key = webhook.header.id.to_string();
+    let database = server
+        .database_without_schema(&webhook.contents.database)
+        .await?;
+    let next_transaction_id = if let Some(id) = cursors.get_key(&cursor_key).into_u64().await? {
+        id
+    } else {
+        // New webhooks only receive changes made after they are first seen.
+        let id = database.last_transaction_id().await?.map_or(0, |id| id + 1);
+        cursors.set_numeric_key(&cursor_key, id).await?;
+        return Ok(());
+    };
+
+    let transactions = database
+        .list_executed_transactions(Some(next_transaction_id), Some(TRANSACTION_BATCH_SIZE))
+        .await?;
+    for transaction in transactions {
+        let changes = matching_changes(&database, &webhook.contents, &transaction).await?;
+        if !changes.is_empty() {
+            let event = WebhookEvent {
+                webhook_id: webhook.header.id,
+                database: webhook.contents.database.clone(),
+                transaction_id: transaction.id,
+                changes,
+            };
+            post_event(client, &webhook.contents.url, &event, configuration)
+                .await
+                .map_err(|error| DeliveryError::Request {
+                    transaction_id: transaction.id,
+                    error,
+                })?;
+        }
+        cursors
+            .set_numeric_key(&cursor_key, transaction.id + 1)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Returns the document changes in `transaction` that match `webhook`'s
+/// filters.
+async fn matching_changes<B: Backend>(
+    database: &ServerDatabase<B>,
+    webhook: &Webhook,
+    transaction: &Executed,
+) -> Result<Vec<WebhookChange>, Error> {
+    let Some(documents) = transaction.changes.documents() else {
+        return Ok(Vec::new());
+    };
+    let view = match &webhook.view {
+        Some(filter) => Some((
+            database.schematic().view_by_name(&filter.view)?,
+            &filter.keys,
+        )),
+        None => None,
+    };
+
+    let mut changes = Vec::new();
+    for (collection, changed) in documents.iter() {
+        if matches!(&webhook.collection, Some(filtered) if filtered != collection) {
+            continue;
+        }
+
+        if let (Some((view, keys)), false) = (&view, changed.deleted) {
+            // The document's current contents are mapped, which may differ
+            // from the contents written by this transaction.
+            let Some(document) = database
+                .get_from_collection(changed.id.clone(), collection)
+                .await?
+            else {
+                continue;
+            };
+            let mappings = view
+                .map(&BorrowedDocument {
+                    header: document.header.clone(),
+                    contents: CowBytes::from(&document.contents[..]),
+                })
+                .map_err(bonsaidb_core::Error::from)?;
+            if !mappings.iter().any(|mapping| keys.contains(&mapping.key)) {
+                continue;
+            }
+        }
+
+        changes.push(WebhookChange {
+            collection: collection.to_string(),
+            id: changed.id.to_string(),
+            deleted: changed.deleted,
+        });
+    }
+
+    Ok(changes)
+}
+
+async fn post_event(
+    client: &Client<HttpConnector>,
+    url: &str,
+    event: &WebhookEvent,
+    configuration: &WebhookConfiguration,
+) -> Result<(), String> {
+    let body = serde_json::to_vec(event).map_err(|err| err.to_string())?;
+    let request = Request::post(url)
+        .header("content-type", "application/json")
+        .body(Body::from(body))
+        .map_err(|err| err.to_string())?;
+    let response = tokio::time::timeout(configuration.request_timeout, client.request(request))
+        .await
+        .map_err(|_| String::from("request timed out"))?
+        .map_err(|err| err.to_string())?;
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("unexpected response status {}", response.status()))
+    }
+}
@@ -1,6 +1,9 @@
+use bonsaidb_core::connection::Session;
 use bonsaidb_core::networking::{Payload, CURRENT_PROTOCOL_VERSION};
+use fabruic::Certificate;
 use futures::{SinkExt, StreamExt};
 use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::time::Instant;
 use tokio_tungstenite::tungstenite::Message;
 
 use crate::server::connected_client::OwnedClient;
@@ -26,10 +29,20 @@ impl<B: Backend> CustomServer<B> {
     >(
         &self,
         connection: S,
+        transport: Transport,
         peer_address: std::net::SocketAddr,
+        client_certificate: Option<&Certificate>,
+        default_session: &Session,
     ) -> Result<(), Error> {
         let stream = tokio_tungstenite::accept_hdr_async(connection, VersionChecker).await?;
-        self.handle_websocket(stream, peer_address).await;
+        self.handle_websocket_connection(
+            stream,
+            transport,
+            peer_address,
+            client_certificate,
+            default_session,
+        )
+        .await;
         Ok(())
     }
 
@@ -101,6 +114,30 @@ impl<B: Backend> CustomServer<B> {
         &self,
         connection: S,
         peer_address: std::net::SocketAddr,
+    ) {
+        self.handle_websocket_connection(
+            connection,
+            Transport::WebSocket,
+            peer_address,
+            None,
+            &self.data.default_session,
+        )
+        .await;
+    }
+
+    async fn handle_websocket_connection<
+        S: futures::Stream<Item = Result<tokio_tungstenite::tungstenite::Message, E>>
+            + futures::Sink<tokio_tungstenite::tungstenite::Message>
+            + Send
+            + 'static,
+        E: std::fmt::Debug + Send,
+    >(
+        &self,
+        connection: S,
+        transport: Transport,
+        peer_address: std::net::SocketAddr,
+        client_certificate: Option<&Certificate>,
+        default_session: &Session,
     ) {
         let mut shutdown = self
             .data
@@ -114,21 +151,53 @@ impl<B: Backend> CustomServer<B> {
         let (message_sender, message_receiver) = flume::unbounded();
 
         let (api_response_sender, api_response_receiver) = flume::unbounded();
-        let Some(client) = self
-            .initialize_client(Transport::WebSocket, peer_address, api_response_sender)
-            .await else { return };
+        let client = match self
+            .initialize_client(
+                transport,
+                peer_address,
+                api_response_sender,
+                client_certificate,
+                default_session,
+            )
+            .await
+        {
+            Ok(client) => client,
+            Err(Some(err)) => {
+                // Respond to the client's first request with the reason the
+                // connection was rejected rather than disconnecting without
+                // explanation.
+                if let Some(Ok(Message::Binary(binary))) = receiver.next().await {
+                    if let Ok(payload) = bincode::deserialize::<Payload>(&binary) {
+                        let response = Payload {
+                            value: Err(err),
+                            compression: None,
+                            deadline: None,
+                            ..payload
+                        };
+                        if let Ok(response) = bincode::serialize(&response) {
+                            drop(sender.send(Message::Binary(response)).await);
+                        }
+                    }
+                }
+                drop(sender.close().await);
+                return;
+            }
+            Err(None) => return,
+        };
         let task_sender = response_sender.clone();
+        let task_client = client.clone();
         tokio::spawn(async move {
             while let Ok((session_id, name, value)) = api_response_receiver.recv_async().await {
-                if task_sender
-                    .send(Payload {
-                        id: None,
-                        session_id,
-                        name,
-                        value: Ok(value),
-                    })
-                    .is_err()
-                {
+                let mut payload = Payload {
+                    id: None,
+                    session_id,
+                    name,
+                    value: Ok(value),
+                    compression: None,
+                    deadline: None,
+                };
+                task_client.compress_payload(&mut payload);
+                if task_sender.send(payload).is_err() {
                     break;
                 }
             }
@@ -161,15 +230,33 @@ impl<B: Backend> CustomServer<B> {
         let (request_sender, request_receiver) =
             flume::bounded::<Payload>(self.data.client_simultaneous_request_limit);
 
+        let connected_client = client.clone();
         self.spawn_client_request_handler(client, request_receiver, response_sender, &shutdown);
 
+        let settings = self.data.websockets;
+        let mut last_received = Instant::now();
+        let mut last_request = last_received;
+        let mut ping_sent_at = None;
+        let mut next_ping = settings
+            .ping_interval
+            .map(|interval| last_received + interval);
         loop {
+            // While a ping is outstanding, wake up when its pong is due.
+            // Otherwise, wake up when the next ping should be sent.
+            let keepalive_deadline = ping_sent_at
+                .map(|sent_at| sent_at + settings.pong_timeout)
+                .or(next_ping);
+            let idle_deadline = settings.idle_timeout.map(|timeout| last_request + timeout);
             tokio::select! {
                 payload = receiver.next() => {
+                    last_received = Instant::now();
                     if let Some(payload) = payload {
                         match payload {
                             Ok(Message::Binary(binary)) => match bincode::deserialize::<Payload>(&binary) {
-                                Ok(payload) => drop(request_sender.send_async(payload).await),
+                                Ok(payload) => {
+                                    last_request = last_received;
+                                    drop(request_sender.send_async(payload).await);
+                                }
                                 Err(err) => {
                                     log::error!("[server] error decoding message: {:?}", err);
                                     break;
@@ -179,6 +266,7 @@ impl<B: Backend> CustomServer<B> {
                             Ok(Message::Ping(payload)) => {
                                 drop(message_sender.send(Message::Pong(payload)));
                             }
+                            Ok(Message::Pong(_)) => {}
                             other => {
                                 log::error!("[server] unexpected message: {:?}", other);
                                 break;
@@ -188,6 +276,28 @@ impl<B: Backend> CustomServer<B> {
                         return;
                     }
                 },
+                () = sleep_until(keepalive_deadline) => {
+                    if let Some(sent_at) = ping_sent_at.take() {
+                        if last_received < sent_at {
+                            log::info!("[server] closing unresponsive websocket from {peer_address}");
+                            drop(message_sender.send(Message::Close(None)));
+                            break;
+                        }
+                        next_ping = settings.ping_interval.map(|interval| sent_at + interval);
+                    } else {
+                        drop(message_sender.send(Message::Ping(Vec::new())));
+                        ping_sent_at = Some(Instant::now());
+                    }
+                },
+                () = sleep_until(idle_deadline) => {
+                    log::info!("[server] closing idle websocket from {peer_address}");
+                    drop(message_sender.send(Message::Close(None)));
+                    break;
+                },
+                () = connected_client.disconnect_requested() => {
+                    drop(message_sender.send(Message::Close(None)));
+                    break;
+                },
                 shutdown = shutdown.wait_for_shutdown() => {
                     if matches!(shutdown, ShutdownState::Shutdown) {
                         return;
@@ -221,6 +331,14 @@ impl<B: Backend> CustomServer<B> {
     }
 }
 
+/// Waits until `deadline`, or forever if `deadline` is `None`.
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => futures::future::pending().await,
+    }
+}
+
 #[cfg(feature = "hyper")]
 fn compute_websocket_accept_header(key: &[u8]) -> hyper::header::HeaderValue {
     use base64::engine::general_purpose::STANDARD as BASE64;
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use bonsaidb_core::actionable::{Permissions, Statement};
 use bonsaidb_core::connection::AsyncStorageConnection;
 use bonsaidb_core::test_util::{self, BasicSchema, HarnessTest, TestDirectory};
@@ -35,6 +37,1425 @@ async fn install_self_signed_certificate_tests() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn rate_limits() {
+    use crate::server::rate_limit::{RateLimiter, RequestPermit};
+    use crate::RateLimit;
+
+    // Requests per second
+    let limiter = RateLimiter::new(RateLimit::default().requests_per_second(2));
+    let first = RequestPermit::acquire([&limiter], 0).unwrap();
+    let second = RequestPermit::acquire([&limiter], 0).unwrap();
+    drop((first, second));
+    let Err(bonsaidb_core::Error::RateLimited {
+        retry_after: Some(retry_after),
+    }) = RequestPermit::acquire([&limiter], 0)
+    else {
+        unreachable!("request not limited")
+    };
+    assert!(retry_after <= Duration::from_millis(500));
+    std::thread::sleep(retry_after + Duration::from_millis(10));
+    drop(RequestPermit::acquire([&limiter], 0).unwrap());
+
+    // Concurrent requests
+    let limiter = RateLimiter::new(RateLimit::default().concurrent_requests(1));
+    let in_flight = RequestPermit::acquire([&limiter], 0).unwrap();
+    assert!(matches!(
+        RequestPermit::acquire([&limiter], 0),
+        Err(bonsaidb_core::Error::RateLimited { retry_after: None })
+    ));
+    drop(in_flight);
+    drop(RequestPermit::acquire([&limiter], 0).unwrap());
+
+    // Bandwidth
+    let limiter = RateLimiter::new(RateLimit::default().bytes_per_second(1_000));
+    drop(RequestPermit::acquire([&limiter], 1_500).unwrap());
+    assert!(matches!(
+        RequestPermit::acquire([&limiter], 1),
+        Err(bonsaidb_core::Error::RateLimited {
+            retry_after: Some(_)
+        })
+    ));
+
+    // A request rejected by one limiter isn't charged to the others.
+    let client = RateLimiter::new(RateLimit::default().requests_per_second(1));
+    let user = RateLimiter::new(RateLimit::default().concurrent_requests(1));
+    let in_flight = RequestPermit::acquire([&user], 0).unwrap();
+    assert!(RequestPermit::acquire([&client, &user], 0).is_err());
+    drop(in_flight);
+    drop(RequestPermit::acquire([&client, &user], 0).unwrap());
+}
+
+#[test]
+fn login_throttle() {
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::time::Instant;
+
+    use bonsaidb_local::config::Lockout;
+
+    use crate::server::login_throttle::LoginThrottler;
+    use crate::LoginThrottle;
+
+    let address = IpAddr::V4(Ipv4Addr::LOCALHOST);
+    let other_address = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+    let throttler = LoginThrottler::new(
+        LoginThrottle::default()
+            .free_attempts(2)
+            .initial_delay(Duration::from_secs(1))
+            .maximum_delay(Duration::from_secs(4))
+            .reset_after(Duration::from_secs(60)),
+    );
+    let ecton = 1;
+    let keys = throttler.keys(Some(ecton), Some(address));
+    let start = Instant::now();
+
+    // Free attempts aren't delayed.
+    throttler.record_failure(&keys, start);
+    throttler.check(&keys, start).unwrap();
+    throttler.record_failure(&keys, start);
+    throttler.check(&keys, start).unwrap();
+
+    // The delay doubles with each failure, up to the maximum.
+    for expected_delay in [1, 2, 4, 4] {
+        throttler.record_failure(&keys, start);
+        let Err(bonsaidb_core::Error::RateLimited {
+            retry_after: Some(retry_after),
+        }) = throttler.check(&keys, start)
+        else {
+            unreachable!("attempt not throttled")
+        };
+        assert_eq!(retry_after, Duration::from_secs(expected_delay));
+    }
+    throttler
+        .check(&keys, start + Duration::from_secs(4))
+        .unwrap();
+
+    // Failures are counted per user and per address.
+    let other_user = throttler.keys(Some(2), Some(address));
+    assert!(throttler.check(&other_user, start).is_err());
+    let unknown_user = throttler.keys(None, Some(address));
+    assert!(throttler.check(&unknown_user, start).is_err());
+    let other_client = throttler.keys(Some(ecton), Some(other_address));
+    assert!(throttler.check(&other_client, start).is_err());
+
+    // A successful authentication only clears the user's failures.
+    throttler.record_success(&keys);
+    throttler.check(&other_client, start).unwrap();
+    assert!(throttler.check(&other_user, start).is_err());
+
+    // Failures are forgotten after `reset_after`.
+    throttler
+        .check(&other_user, start + Duration::from_secs(60))
+        .unwrap();
+
+    // Lockouts last until they expire or are removed.
+    let throttler = LoginThrottler::new(
+        LoginThrottle::default().lockout(Lockout::after_failures(2, Duration::from_secs(3600))),
+    );
+    let keys = throttler.keys(Some(ecton), Some(address));
+    throttler.record_failure(&keys, start);
+    throttler.record_failure(&keys, start);
+    assert!(matches!(
+        throttler.check(&keys, start + Duration::from_secs(3000)),
+        Err(bonsaidb_core::Error::AccountLocked {
+            retry_after: Some(retry_after)
+        }) if retry_after == Duration::from_secs(600)
+    ));
+    throttler
+        .check(&keys, start + Duration::from_secs(3600))
+        .unwrap();
+    throttler.unlock_user(ecton);
+    assert!(throttler.check(&keys, start).is_err());
+    throttler.unlock_address(address);
+    throttler.check(&keys, start).unwrap();
+
+    // Only a limited number of addresses are tracked, and locked addresses
+    // are kept over the others.
+    let throttler = LoginThrottler::new(
+        LoginThrottle::default()
+            .free_attempts(0)
+            .maximum_addresses(2)
+            .lockout(Lockout::after_failures(2, Duration::from_secs(3600))),
+    );
+    let locked = throttler.keys(None, Some(address));
+    throttler.record_failure(&locked, start);
+    throttler.record_failure(&locked, start);
+    for last_octet in 1..=10 {
+        let sprayed = throttler.keys(None, Some(IpAddr::V4(Ipv4Addr::new(10, 0, 0, last_octet))));
+        throttler.record_failure(&sprayed, start + Duration::from_secs(u64::from(last_octet)));
+    }
+    let now = start + Duration::from_secs(10);
+    assert!(matches!(
+        throttler.check(&locked, now),
+        Err(bonsaidb_core::Error::AccountLocked { .. })
+    ));
+    throttler
+        .check(&throttler.keys(None, Some(other_address)), now)
+        .unwrap();
+    let last_sprayed = throttler.keys(None, Some(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 10))));
+    assert!(throttler.check(&last_sprayed, now).is_err());
+}
+
+#[cfg(feature = "password-hashing")]
+#[tokio::test]
+async fn login_throttle_counts_user_ids() -> anyhow::Result<()> {
+    use bonsaidb_core::connection::{Authentication, SensitiveString};
+    use bonsaidb_core::document::DocumentId;
+    use bonsaidb_core::schema::NamedReference;
+    use bonsaidb_local::config::Lockout;
+
+    use crate::{LoginThrottle, ServerConfiguration};
+
+    async fn authenticate(
+        server: &Server,
+        user: NamedReference<'static, u64>,
+        password: &str,
+    ) -> Result<Server, bonsaidb_core::Error> {
+        server
+            .authenticate_throttled(
+                Authentication::password(user, SensitiveString::from(password))?,
+                None,
+            )
+            .await
+    }
+
+    let test_dir = TestDirectory::new("login-throttle-user-ids");
+    let server = Server::open(
+        ServerConfiguration::new(&test_dir).login_throttle(
+            LoginThrottle::default()
+                .free_attempts(2)
+                .lockout(Lockout::after_failures(3, Duration::from_secs(3600)))
+                .per_address(false),
+        ),
+    )
+    .await?;
+    let user_id = server.create_user("ecton").await?;
+    server
+        .set_user_password(user_id, SensitiveString::from("hunter2"))
+        .await?;
+
+    // Naming the user by its username, its id, or its serialized id counts
+    // against the same user.
+    for user in [
+        NamedReference::from("ecton"),
+        NamedReference::Key(user_id),
+        NamedReference::Id(DocumentId::from_u64(user_id)),
+    ] {
+        assert!(matches!(
+            authenticate(&server, user, "wrong").await,
+            Err(bonsaidb_core::Error::InvalidCredentials)
+        ));
+    }
+    assert!(matches!(
+        authenticate(&server, NamedReference::Key(user_id), "hunter2").await,
+        Err(bonsaidb_core::Error::AccountLocked { .. })
+    ));
+
+    // Unlocking the user by name removes the lock counted by id.
+    server.unlock_user("ecton").await?;
+    authenticate(&server, NamedReference::Key(user_id), "hunter2").await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn proxy_protocol_headers() {
+    use std::net::SocketAddr;
+
+    use crate::server::proxy_protocol::read_header;
+
+    const SIGNATURE: &[u8] = b"\r\n\r\n\0\r\nQUIT\n";
+
+    // PROXY, TCP over IPv4, followed by the proxied connection's data.
+    let mut stream = [SIGNATURE, &[0x21, 0x11, 0, 12]].concat();
+    stream.extend([203, 0, 113, 7, 10, 0, 0, 1, 0x1f, 0x90, 0x15, 0x59]);
+    stream.extend(b"GET /");
+    let mut reader = &stream[..];
+    assert_eq!(
+        read_header(&mut reader).await.unwrap(),
+        Some("203.0.113.7:8080".parse::<SocketAddr>().unwrap())
+    );
+    assert_eq!(reader, b"GET /");
+
+    // PROXY, TCP over IPv6, with a trailing extension that is skipped.
+    let mut stream = [SIGNATURE, &[0x21, 0x21, 0, 40]].concat();
+    stream.extend([0x20, 0x01, 0x0d, 0xb8]);
+    stream.extend([0; 11]);
+    stream.push(1);
+    stream.extend([0; 16]);
+    stream.extend([0x1f, 0x90, 0x15, 0x59]);
+    stream.extend([0x04, 0, 1, 0]);
+    let mut reader = &stream[..];
+    assert_eq!(
+        read_header(&mut reader).await.unwrap(),
+        Some("[2001:db8::1]:8080".parse::<SocketAddr>().unwrap())
+    );
+    assert!(reader.is_empty());
+
+    // LOCAL connections, such as health checks, have no client address.
+    let stream = [SIGNATURE, &[0x20, 0x00, 0, 0]].concat();
+    assert_eq!(read_header(&mut &stream[..]).await.unwrap(), None);
+
+    // Version 1 headers and connections without headers are rejected.
+    assert!(
+        read_header(&mut &b"PROXY TCP4 203.0.113.7 10.0.0.1 8080 5465\r\n"[..])
+            .await
+            .is_err()
+    );
+    // An address block that is too short for its family is rejected.
+    let stream = [SIGNATURE, &[0x21, 0x11, 0, 4], &[203, 0, 113, 7]].concat();
+    assert!(read_header(&mut &stream[..]).await.is_err());
+}
+
+#[tokio::test]
+async fn client_certificate_sessions() -> anyhow::Result<()> {
+    use std::net::SocketAddr;
+
+    use bonsaidb_core::connection::{Identity, IdentityReference, Session};
+    use fabruic::KeyPair;
+
+    use crate::{
+        CertificateIdentities, ClientCertificateAuthentication, ServerConfiguration, Transport,
+    };
+
+    let authority = KeyPair::new_self_signed("authority");
+    let known = KeyPair::new_self_signed("known");
+    let unknown = KeyPair::new_self_signed("unknown");
+
+    let test_dir = TestDirectory::new("client-certificate-sessions");
+    let server = Server::open(
+        ServerConfiguration::new(&test_dir).client_certificate_authentication(
+            ClientCertificateAuthentication::new(
+                [authority.end_entity_certificate().clone()],
+                CertificateIdentities::default().with(
+                    known.end_entity_certificate(),
+                    IdentityReference::user("service")?,
+                ),
+            ),
+        ),
+    )
+    .await?;
+    let user_id = server.create_user("service").await?;
+
+    let session = server
+        .client_certificate_session(Some(known.end_entity_certificate()))
+        .await?
+        .expect("certificate not resolved");
+    assert!(matches!(session.identity(), Some(Identity::User { id, .. }) if *id == user_id));
+    // The session is recorded like any other authenticated session.
+    let session_id = session.id.expect("session not recorded");
+    let sessions = server.list_sessions(user_id).await?;
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].id, session_id);
+    server.revoke_session(session_id).await?;
+
+    // Revoking the session of a connected client authenticated by its
+    // certificate removes the client's session and closes the connection.
+    let (sender, _receiver) = flume::unbounded();
+    let client = server
+        .initialize_client(
+            Transport::Bonsai,
+            SocketAddr::from(([127, 0, 0, 1], 12345)),
+            sender,
+            Some(known.end_entity_certificate()),
+            &Session {
+                permissions: Permissions::allow_all(),
+                ..Session::default()
+            },
+        )
+        .await
+        .map_err(|err| anyhow::anyhow!("client rejected: {err:?}"))?;
+    let session_id = client
+        .session(None)
+        .and_then(|session| session.id)
+        .expect("session not recorded");
+    assert_eq!(server.list_sessions(user_id).await?.len(), 1);
+    server.revoke_session(session_id).await?;
+    assert!(client.session(None).is_none());
+    assert!(server.list_sessions(user_id).await?.is_empty());
+    tokio::time::timeout(Duration::from_secs(1), client.disconnect_requested()).await?;
+
+    // Sessions of clients that have disconnected are revoked.
+    let (sender, _receiver) = flume::unbounded();
+    let client = server
+        .initialize_client(
+            Transport::Bonsai,
+            SocketAddr::from(([127, 0, 0, 1], 12345)),
+            sender,
+            Some(known.end_entity_certificate()),
+            &Session {
+                permissions: Permissions::allow_all(),
+                ..Session::default()
+            },
+        )
+        .await
+        .map_err(|err| anyhow::anyhow!("client rejected: {err:?}"))?;
+    assert_eq!(server.list_sessions(user_id).await?.len(), 1);
+    drop(client);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(server.list_sessions(user_id).await?.is_empty());
+
+    assert!(server
+        .client_certificate_session(Some(unknown.end_entity_certificate()))
+        .await?
+        .is_none());
+    assert!(server.client_certificate_session(None).await?.is_none());
+
+    // Disabled users can't authenticate using a certificate.
+    server.disable_user(user_id).await?;
+    assert!(matches!(
+        server
+            .client_certificate_session(Some(known.end_entity_certificate()))
+            .await,
+        Err(bonsaidb_core::Error::InvalidCredentials)
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn idempotent_transactions() -> anyhow::Result<()> {
+    use bonsaidb_core::connection::IdentityReference;
+    use bonsaidb_core::schema::SerializedCollection;
+    use bonsaidb_core::test_util::Basic;
+    use bonsaidb_core::transaction::{IdempotencyKey, Operation, OperationResult, Transaction};
+
+    let test_dir = TestDirectory::new("idempotent-transactions");
+    let server = initialize_basic_server(test_dir.as_ref()).await?;
+    let db = server.database::<BasicSchema>("tests").await?;
+
+    let key = IdempotencyKey::random();
+    let transaction = Transaction::new()
+        .with(Operation::push_serialized::<Basic>(&Basic::new("once"))?)
+        .with_idempotency_key(key);
+    let first = transaction.clone().apply_async(&db).await?;
+    let retried = transaction.apply_async(&db).await?;
+    let (
+        [OperationResult::DocumentUpdated { header: first, .. }],
+        [OperationResult::DocumentUpdated {
+            header: retried, ..
+        }],
+    ) = (first.as_slice(), retried.as_slice())
+    else {
+        unreachable!("unexpected transaction results")
+    };
+    assert_eq!(first, retried);
+    assert_eq!(Basic::all_async(&db).count().await?, 1);
+
+    // Another identity using the same key doesn't receive the results, and
+    // its transaction is applied.
+    server.create_user("other").await?;
+    let other = server
+        .assume_identity(IdentityReference::user("other")?)
+        .await?
+        .database::<BasicSchema>("tests")
+        .await?;
+    let transaction = Transaction::new()
+        .with(Operation::push_serialized::<Basic>(&Basic::new("other"))?)
+        .with_idempotency_key(key);
+    let other_results = transaction.apply_async(&other).await?;
+    let [OperationResult::DocumentUpdated {
+        header: other_header,
+        ..
+    }] = other_results.as_slice()
+    else {
+        unreachable!("unexpected transaction results")
+    };
+    assert_ne!(first, other_header);
+    assert_eq!(Basic::all_async(&db).count().await?, 2);
+
+    // Transactions without a key are applied each time.
+    let transaction =
+        Transaction::new().with(Operation::push_serialized::<Basic>(&Basic::new("twice"))?);
+    transaction.clone().apply_async(&db).await?;
+    transaction.apply_async(&db).await?;
+    assert_eq!(Basic::all_async(&db).count().await?, 4);
+
+    Ok(())
+}
+
+#[cfg(feature = "request-log")]
+#[test]
+fn request_log() {
+    use std::io::{BufRead, BufReader};
+
+    use crate::server::request_log::RequestLog;
+    use crate::{RequestLogConfiguration, RequestLogEntry};
+
+    let test_dir = TestDirectory::new("request-log");
+    std::fs::create_dir_all(&test_dir).unwrap();
+    let path = test_dir.join("requests.jsonl");
+    let log = RequestLog::new(
+        RequestLogConfiguration::json_lines(&path)
+            .sample_one_in(3)
+            .slow_request_threshold(Duration::from_secs(1)),
+    )
+    .unwrap();
+
+    // One in every three requests is logged.
+    let fast = Duration::from_millis(1);
+    let sampled = (0..6)
+        .map(|_| log.is_sampled(fast, false))
+        .collect::<Vec<_>>();
+    assert_eq!(sampled, [true, false, false, true, false, false]);
+    // Failed and slow requests are always logged.
+    assert!(log.is_sampled(fast, true));
+    assert!(log.is_sampled(Duration::from_secs(1), false));
+    assert!(!log.is_sampled(fast, false));
+
+    let entry = RequestLogEntry {
+        timestamp: 1_700_000_000_000,
+        address: "203.0.113.7:8080".parse().unwrap(),
+        user_id: Some(1),
+        username: Some(String::from("ecton")),
+        database: Some(String::from("tests")),
+        operation: String::from("bonsaidb.Get"),
+        duration_micros: 1_500,
+        result: String::from("ok"),
+        request_bytes: 64,
+        response_bytes: 256,
+    };
+    log.write(entry.clone());
+    drop(log);
+
+    // The file is written by a background thread.
+    for _ in 0..100 {
+        let lines = BufReader::new(std::fs::File::open(&path).unwrap())
+            .lines()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        if let [line] = &lines[..] {
+            assert_eq!(
+                serde_json::from_str::<RequestLogEntry>(line).unwrap(),
+                entry
+            );
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    unreachable!("request log entry not written")
+}
+
+#[cfg(feature = "cdc")]
+#[tokio::test]
+async fn change_data_capture() -> anyhow::Result<()> {
+    use async_trait::async_trait;
+    use bonsaidb_core::document::DocumentId;
+    use bonsaidb_core::schema::{Collection, SerializedCollection};
+    use bonsaidb_core::test_util::Basic;
+
+    use crate::cdc::{ChangeDataCapture, ChangeEvent, ChangeSink};
+    use crate::config::DefaultPermissions;
+    use crate::ServerConfiguration;
+
+    #[derive(Debug)]
+    struct ChannelSink(flume::Sender<ChangeEvent>);
+
+    #[async_trait]
+    impl ChangeSink for ChannelSink {
+        async fn publish(&self, event: &ChangeEvent) -> Result<(), bonsaidb_core::Error> {
+            drop(self.0.send(event.clone()));
+            Ok(())
+        }
+    }
+
+    let (sender, receiver) = flume::unbounded();
+    let test_dir = TestDirectory::new("change-data-capture");
+    let server = Server::open(
+        ServerConfiguration::new(&test_dir)
+            .default_permissions(DefaultPermissions::AllowAll)
+            .with_schema::<BasicSchema>()?
+            .change_data_capture(
+                ChangeDataCapture::new("test", "tests", ChannelSink(sender))
+                    .with_collection::<Basic>()
+                    .from_beginning(true)
+                    .poll_interval(Duration::from_millis(10)),
+            ),
+    )
+    .await?;
+    let db = server
+        .create_database::<BasicSchema>("tests", false)
+        .await?;
+    let doc = Basic::new("exported").push_into_async(&db).await?;
+
+    let event = tokio::time::timeout(Duration::from_secs(10), receiver.recv_async()).await??;
+    assert_eq!(event.database, "tests");
+    assert_eq!(event.changes.len(), 1);
+    let change = &event.changes[0];
+    assert_eq!(change.collection, Basic::collection_name());
+    assert_eq!(change.id, DocumentId::new(&doc.header.id)?);
+    assert!(!change.deleted);
+    assert_eq!(
+        Basic::deserialize(&change.contents.as_ref().unwrap()[..])?,
+        doc.contents
+    );
+
+    doc.delete_async(&db).await?;
+    let event = tokio::time::timeout(Duration::from_secs(10), receiver.recv_async()).await??;
+    assert!(event.changes[0].deleted);
+    assert!(event.changes[0].contents.is_none());
+
+    server.shutdown(None).await?;
+    Ok(())
+}
+
+#[cfg(feature = "cluster")]
+#[tokio::test]
+async fn cluster_quorum() -> anyhow::Result<()> {
+    use bonsaidb_core::schema::{SerializedCollection, SerializedView};
+    use bonsaidb_core::test_util::{Basic, BasicByParentId};
+    use bonsaidb_core::transaction::{Operation, Transaction, WriteQuorum};
+
+    use crate::cluster::{ClusterConfiguration, ClusterMember, MemberStatus};
+    use crate::config::DefaultPermissions;
+    use crate::ServerConfiguration;
+
+    // A cluster with a single node always has a quorum, and that node is the
+    // leader.
+    let test_dir = TestDirectory::new("cluster-single-node");
+    let server = Server::open(
+        ServerConfiguration::new(&test_dir)
+            .default_permissions(DefaultPermissions::AllowAll)
+            .with_schema::<BasicSchema>()?
+            .cluster(ClusterConfiguration::new(1, "secret")),
+    )
+    .await?;
+    let status = server.cluster_status().await.unwrap();
+    assert!(status.has_quorum);
+    assert_eq!(status.leader, Some(1));
+    assert_eq!(status.term, 1);
+    assert!(status.members.is_empty());
+    assert_eq!(server.cluster_primary().await, Some(1));
+    let db = server
+        .create_database::<BasicSchema>("tests", false)
+        .await?;
+    let doc = Basic::new("clustered").push_into_async(&db).await?;
+    assert!(Basic::get_async(&doc.header.id, &db).await?.is_some());
+    // Every quorum is reached by the only node, and it serves primary-only
+    // reads itself.
+    Transaction::new()
+        .with(Operation::push_serialized::<Basic>(&Basic::new("all"))?)
+        .with_write_quorum(WriteQuorum::All)
+        .apply_async(&db)
+        .await?;
+    assert_eq!(
+        BasicByParentId::entries_async(&db)
+            .read_from_primary_only()
+            .count()
+            .await?,
+        2
+    );
+    server.shutdown(None).await?;
+
+    // A node that can't reach the other member of a two-node cluster can't be
+    // elected, and refuses writes.
+    let test_dir = TestDirectory::new("cluster-no-quorum");
+    let server = Server::open(
+        ServerConfiguration::new(&test_dir)
+            .default_permissions(DefaultPermissions::AllowAll)
+            .with_schema::<BasicSchema>()?
+            .cluster(
+                ClusterConfiguration::new(1, "secret")
+                    .with_member(ClusterMember::new(2, "bonsaidb://localhost:1".parse()?))
+                    .heartbeat_interval(Duration::from_millis(10))
+                    .failure_timeout(Duration::from_millis(50)),
+            ),
+    )
+    .await?;
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let status = server.cluster_status().await.unwrap();
+    assert!(!status.has_quorum);
+    assert_eq!(status.leader, None);
+    // Pre-votes prevent an isolated node from starting new terms.
+    assert_eq!(status.term, 0);
+    assert_eq!(
+        status.members,
+        vec![MemberStatus {
+            id: 2,
+            reachable: false
+        }]
+    );
+    assert_eq!(server.cluster_primary().await, None);
+    let db = server
+        .create_database::<BasicSchema>("tests", false)
+        .await?;
+    assert!(Basic::new("refused").push_into_async(&db).await.is_err());
+    // Reads are served locally unless they require the primary.
+    assert_eq!(BasicByParentId::entries_async(&db).count().await?, 0);
+    assert!(BasicByParentId::entries_async(&db)
+        .read_from_primary_only()
+        .count()
+        .await
+        .is_err());
+    server.shutdown(None).await?;
+
+    Ok(())
+}
+
+#[cfg(feature = "cluster")]
+#[tokio::test]
+async fn cluster_replication() -> anyhow::Result<()> {
+    use std::collections::HashMap;
+    use std::future::Future;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::Instant;
+
+    use bonsaidb_core::schema::{SerializedCollection, SerializedView};
+    use bonsaidb_core::test_util::{Basic, BasicByParentId};
+    use bonsaidb_core::transaction::{Operation, Transaction, WriteQuorum};
+    use fabruic::KeyPair;
+    use tokio::net::UdpSocket;
+
+    use crate::cluster::{ClusterConfiguration, ClusterMember};
+    use crate::config::DefaultPermissions;
+    use crate::ServerConfiguration;
+
+    /// Forwards the datagrams sent to `port` to `target`, and the replies back
+    /// to the most recent sender. Datagrams are dropped while `blocked` is set,
+    /// simulating a network partition.
+    async fn relay(port: u16, target: u16, blocked: Arc<AtomicBool>) -> std::io::Result<()> {
+        let listener = UdpSocket::bind(("::1", port)).await?;
+        let upstream = UdpSocket::bind(("::1", 0)).await?;
+        upstream.connect(("::1", target)).await?;
+        let mut sender = None;
+        let mut request = vec![0; 65_536];
+        let mut response = vec![0; 65_536];
+        loop {
+            tokio::select! {
+                received = listener.recv_from(&mut request) => {
+                    let (length, address) = received?;
+                    sender = Some(address);
+                    if !blocked.load(Ordering::SeqCst) {
+                        // The target may not be listening yet.
+                        drop(upstream.send(&request[..length]).await);
+                    }
+                }
+                received = upstream.recv(&mut response) => {
+                    let (Ok(length), Some(address)) = (received, sender) else {
+                        continue;
+                    };
+                    if !blocked.load(Ordering::SeqCst) {
+                        listener.send_to(&response[..length], address).await?;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Waits for `condition` to return true.
+    async fn wait_for<F: Future<Output = anyhow::Result<bool>>>(
+        description: &str,
+        mut condition: impl FnMut() -> F,
+    ) -> anyhow::Result<()> {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !condition().await? {
+            anyhow::ensure!(
+                Instant::now() < deadline,
+                "timed out waiting for {description}"
+            );
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        Ok(())
+    }
+
+    /// Returns the leader every server in `servers` recognizes, if they agree.
+    async fn agreed_leader(servers: &[&Server]) -> Option<u64> {
+        let mut leaders = Vec::new();
+        for server in servers {
+            leaders.push(server.cluster_primary().await);
+        }
+        leaders
+            .iter()
+            .all(|leader| *leader == leaders[0])
+            .then_some(leaders[0])
+            .flatten()
+    }
+
+    async fn contains(server: &Server, value: &str) -> anyhow::Result<bool> {
+        let db = server.database::<BasicSchema>("tests").await?;
+        Ok(Basic::all_async(&db)
+            .await?
+            .iter()
+            .any(|doc| doc.contents.value == value))
+    }
+
+    async fn has_database(server: &Server, name: &str) -> anyhow::Result<bool> {
+        Ok(server
+            .list_databases()
+            .await?
+            .iter()
+            .any(|database| database.name == name))
+    }
+
+    // Each node reaches every other member through its own relay, allowing
+    // a node to be partitioned from the others.
+    const NODES: [u16; 3] = [1, 2, 3];
+    let port = |node: u16| 6021 + node;
+    let mut relays = HashMap::new();
+    let mut relay_port = 6025;
+    for from in NODES {
+        for to in NODES.into_iter().filter(|to| *to != from) {
+            let blocked = Arc::new(AtomicBool::new(false));
+            tokio::spawn(relay(relay_port, port(to), blocked.clone()));
+            relays.insert((from, to), (relay_port, blocked));
+            relay_port += 1;
+        }
+    }
+    let partition = |node: u64, blocked: bool| {
+        for ((from, to), (_, relay)) in &relays {
+            if u64::from(*from) == node || u64::from(*to) == node {
+                relay.store(blocked, Ordering::SeqCst);
+            }
+        }
+    };
+
+    let keypairs = NODES.map(|_| KeyPair::new_self_signed("localhost"));
+    let mut directories = Vec::new();
+    let mut servers = Vec::new();
+    for node in NODES {
+        // Compacting the log after every other entry requires members that
+        // fall behind to install a snapshot.
+        let mut cluster = ClusterConfiguration::new(u64::from(node), "secret")
+            .heartbeat_interval(Duration::from_millis(50))
+            .failure_timeout(Duration::from_millis(500))
+            .snapshot_interval(2);
+        for member in NODES.into_iter().filter(|member| *member != node) {
+            let (relay_port, _) = &relays[&(node, member)];
+            cluster = cluster.with_member(
+                ClusterMember::new(
+                    u64::from(member),
+                    format!("bonsaidb://localhost:{relay_port}").parse()?,
+                )
+                .with_certificate(
+                    keypairs[usize::from(member - 1)]
+                        .end_entity_certificate()
+                        .clone(),
+                ),
+            );
+        }
+
+        let directory = TestDirectory::new(format!("cluster-node-{node}"));
+        let server = Server::open(
+            ServerConfiguration::new(&directory)
+                .default_permissions(DefaultPermissions::AllowAll)
+                .with_schema::<BasicSchema>()?
+                .cluster(cluster),
+        )
+        .await?;
+        let keypair = &keypairs[usize::from(node - 1)];
+        server
+            .install_certificate(keypair.certificate_chain(), keypair.private_key())
+            .await?;
+        server
+            .create_database::<BasicSchema>("tests", false)
+            .await?;
+        let listener = server.clone();
+        tokio::spawn(async move { listener.listen_on(port(node)).await });
+        directories.push(directory);
+        servers.push(server);
+    }
+    let node = |id: u64| &servers[usize::try_from(id - 1).unwrap()];
+    let members_except = |excluded: u64| {
+        servers
+            .iter()
+            .zip(NODES)
+            .filter(|(_, id)| u64::from(*id) != excluded)
+            .map(|(server, _)| server)
+            .collect::<Vec<_>>()
+    };
+    let everyone = servers.iter().collect::<Vec<_>>();
+    let everyone = &everyone;
+
+    wait_for("a leader", || async move {
+        Ok(agreed_leader(everyone).await.is_some())
+    })
+    .await?;
+    let leader = agreed_leader(everyone).await.unwrap();
+    let follower = NODES
+        .into_iter()
+        .map(u64::from)
+        .find(|id| *id != leader)
+        .unwrap();
+
+    // Writes to a follower are forwarded to the leader, and primary-only
+    // reads on a follower observe them immediately.
+    let follower_db = node(follower).database::<BasicSchema>("tests").await?;
+    Basic::new("forwarded")
+        .push_into_async(&follower_db)
+        .await?;
+    Transaction::new()
+        .with(Operation::push_serialized::<Basic>(&Basic::new("all"))?)
+        .with_write_quorum(WriteQuorum::All)
+        .apply_async(&follower_db)
+        .await?;
+    assert_eq!(
+        BasicByParentId::entries_async(&follower_db)
+            .read_from_primary_only()
+            .count()
+            .await?,
+        2
+    );
+    for server in &servers {
+        wait_for("replication", || async move {
+            Ok(contains(server, "forwarded").await? && contains(server, "all").await?)
+        })
+        .await?;
+    }
+
+    // A leader partitioned from the rest of the cluster can't commit writes,
+    // and the remaining members elect a new leader in a later term.
+    let old_term = node(leader).cluster_status().await.unwrap().term;
+    partition(leader, true);
+    let leader_db = node(leader).database::<BasicSchema>("tests").await?;
+    assert!(Basic::new("partitioned")
+        .push_into_async(&leader_db)
+        .await
+        .is_err());
+    let majority = members_except(leader);
+    let majority = &majority;
+    wait_for("a new leader", || async move {
+        Ok(agreed_leader(majority)
+            .await
+            .map_or(false, |new_leader| new_leader != leader))
+    })
+    .await?;
+    let new_leader = agreed_leader(majority).await.unwrap();
+    let status = node(new_leader).cluster_status().await.unwrap();
+    assert!(status.term > old_term);
+    let majority_db = node(new_leader).database::<BasicSchema>("tests").await?;
+    Basic::new("majority").push_into_async(&majority_db).await?;
+    // The old leader steps down once it can no longer reach a majority.
+    wait_for("the old leader to step down", || async move {
+        Ok(node(leader).cluster_primary().await.is_none())
+    })
+    .await?;
+
+    // Once the partition heals, the old leader follows the new leader, and
+    // its uncommitted write is discarded. The entries it missed have been
+    // compacted, so it installs a snapshot from the new leader.
+    partition(leader, false);
+    wait_for("the old leader to rejoin", || async move {
+        Ok(agreed_leader(everyone).await == Some(new_leader))
+    })
+    .await?;
+    Basic::new("healed").push_into_async(&leader_db).await?;
+    for server in &servers {
+        wait_for("replication after healing", || async move {
+            Ok(contains(server, "majority").await? && contains(server, "healed").await?)
+        })
+        .await?;
+        assert!(!contains(server, "partitioned").await?);
+    }
+
+    // Deleting a database on a follower deletes it from every node.
+    node(new_leader)
+        .create_database::<BasicSchema>("deleted", false)
+        .await?;
+    let deleted_db = node(new_leader).database::<BasicSchema>("deleted").await?;
+    Basic::new("deleted").push_into_async(&deleted_db).await?;
+    for server in &servers {
+        wait_for("the database to be replicated", || async move {
+            has_database(server, "deleted").await
+        })
+        .await?;
+    }
+    node(leader).delete_database("deleted").await?;
+    for server in &servers {
+        wait_for("the deletion to be replicated", || async move {
+            Ok(!has_database(server, "deleted").await?)
+        })
+        .await?;
+    }
+
+    // When the leader shuts down, the remaining members fail over to a new
+    // leader.
+    node(new_leader).shutdown(None).await?;
+    let remaining = members_except(new_leader);
+    let remaining = &remaining;
+    wait_for("failover", || async move {
+        Ok(agreed_leader(remaining)
+            .await
+            .map_or(false, |leader| leader != new_leader))
+    })
+    .await?;
+    let remaining_db = remaining[0].database::<BasicSchema>("tests").await?;
+    Basic::new("failover")
+        .push_into_async(&remaining_db)
+        .await?;
+    for server in remaining {
+        wait_for("replication after failover", || async move {
+            contains(server, "failover").await
+        })
+        .await?;
+        server.shutdown(None).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "acme")]
+#[tokio::test]
+async fn acme_orders_retried_by_one_task() -> anyhow::Result<()> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use tokio::net::TcpListener;
+
+    use crate::ServerConfiguration;
+
+    // The directory accepts connections without ever responding, which causes
+    // every certificate order to fail.
+    let directory = TcpListener::bind("127.0.0.1:0").await?;
+    let directory_url = format!("https://{}/directory", directory.local_addr()?);
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let open_connections = Arc::new(AtomicUsize::new(0));
+    let max_open_connections = Arc::new(AtomicUsize::new(0));
+    tokio::spawn({
+        let attempts = attempts.clone();
+        let open_connections = open_connections.clone();
+        let max_open_connections = max_open_connections.clone();
+        async move {
+            while let Ok((stream, _)) = directory.accept().await {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                let open = open_connections.fetch_add(1, Ordering::SeqCst) + 1;
+                max_open_connections.fetch_max(open, Ordering::SeqCst);
+                let open_connections = open_connections.clone();
+                tokio::spawn(async move {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    open_connections.fetch_sub(1, Ordering::SeqCst);
+                    drop(stream);
+                });
+            }
+        }
+    });
+
+    let test_dir = TestDirectory::new("acme-order-retries");
+    let server = Server::open(
+        ServerConfiguration::new(&test_dir)
+            .server_name("bonsaidb.test")
+            .acme_directory(directory_url)
+            .acme_initial_retry_delay(Duration::from_millis(10))
+            .acme_max_retry_delay(Duration::from_millis(20)),
+    )
+    .await?;
+    // Both listeners share the server's certificate, so only one of them
+    // orders certificates.
+    for _ in 0..2 {
+        let server = server.clone();
+        tokio::spawn(async move { server.listen_for_secure_tcp_on("127.0.0.1:0", ()).await });
+    }
+
+    // Failed orders are retried rather than ending the renewal task.
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while attempts.load(Ordering::SeqCst) < 3 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await?;
+    assert_eq!(max_open_connections.load(Ordering::SeqCst), 1);
+    server.shutdown(None).await?;
+
+    Ok(())
+}
+
+#[cfg(all(feature = "admin-dashboard", feature = "password-hashing"))]
+#[tokio::test]
+async fn admin_dashboard() -> anyhow::Result<()> {
+    use std::net::{IpAddr, Ipv4Addr};
+
+    use base64::Engine;
+    use bonsaidb_core::admin::{PermissionGroup, Role, User};
+    use bonsaidb_core::connection::SensitiveString;
+    use bonsaidb_core::permissions::bonsai::{AuthenticationMethod, BonsaiAction, ServerAction};
+    use bonsaidb_core::schema::{Collection, Schema, SerializedCollection};
+    use bonsaidb_core::test_util::Basic;
+    use bonsaidb_local::config::Builder;
+    use hyper::{Body, Method, Request, StatusCode};
+    use serde_json::Value;
+
+    use crate::{DefaultPermissions, ServerConfiguration};
+
+    async fn request(
+        server: &Server,
+        method: Method,
+        path: &str,
+        authorization: Option<&str>,
+    ) -> anyhow::Result<(StatusCode, Value)> {
+        request_from(server, method, path, authorization, None).await
+    }
+
+    async fn request_from(
+        server: &Server,
+        method: Method,
+        path: &str,
+        authorization: Option<&str>,
+        address: Option<IpAddr>,
+    ) -> anyhow::Result<(StatusCode, Value)> {
+        let mut request = Request::builder().method(method).uri(path);
+        if let Some(authorization) = authorization {
+            request = request.header("authorization", authorization);
+        }
+        let response = server
+            .handle_admin_dashboard_request(request.body(Body::empty())?, address)
+            .await;
+        parse_response(response).await
+    }
+
+    async fn change(
+        server: &Server,
+        method: Method,
+        path: &str,
+        authorization: Option<&str>,
+        body: &Value,
+    ) -> anyhow::Result<(StatusCode, Value)> {
+        let mut request = Request::builder()
+            .method(method)
+            .uri(path)
+            .header("content-type", "application/json");
+        if let Some(authorization) = authorization {
+            request = request.header("authorization", authorization);
+        }
+        let response = server
+            .handle_admin_dashboard_request(request.body(Body::from(body.to_string()))?, None)
+            .await;
+        parse_response(response).await
+    }
+
+    async fn parse_response(
+        response: hyper::Response<Body>,
+    ) -> anyhow::Result<(StatusCode, Value)> {
+        let status = response.status();
+        let bytes = hyper::body::to_bytes(response.into_body()).await?;
+        Ok((
+            status,
+            serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+        ))
+    }
+
+    fn basic(credentials: &str) -> String {
+        format!(
+            "Basic {}",
+            base64::engine::general_purpose::STANDARD.encode(credentials)
+        )
+    }
+
+    let test_dir = TestDirectory::new("admin-dashboard");
+    let server = Server::open(
+        ServerConfiguration::new(&test_dir)
+            .default_permissions(Permissions::from(
+                Statement::for_any()
+                    .allowing(&BonsaiAction::Server(ServerAction::Connect))
+                    .allowing(&BonsaiAction::Server(ServerAction::Authenticate(
+                        AuthenticationMethod::PasswordHash,
+                    ))),
+            ))
+            .authenticated_permissions(DefaultPermissions::AllowAll)
+            .with_schema::<BasicSchema>()?,
+    )
+    .await?;
+    server
+        .create_database::<BasicSchema>("tests", false)
+        .await?;
+    let db = server.database::<BasicSchema>("tests").await?;
+    let document = Basic::new("dashboard")
+        .with_parent_id(1)
+        .push_into_async(&db)
+        .await?;
+    Basic::new("second").push_into_async(&db).await?;
+    let user_id = server.create_user("admin").await?;
+    server
+        .set_user_password("admin", SensitiveString::from("hunter2"))
+        .await?;
+    let admin = basic("admin:hunter2");
+    let admin = Some(admin.as_str());
+
+    assert_eq!(
+        request(&server, Method::GET, "/", None).await?.0,
+        StatusCode::OK
+    );
+    assert_eq!(
+        request(&server, Method::POST, "/api/databases", admin)
+            .await?
+            .0,
+        StatusCode::UNSUPPORTED_MEDIA_TYPE
+    );
+    assert_eq!(
+        change(&server, Method::POST, "/api/databases", admin, &Value::Null)
+            .await?
+            .0,
+        StatusCode::METHOD_NOT_ALLOWED
+    );
+    assert_eq!(
+        request(&server, Method::GET, "/api/unknown", admin)
+            .await?
+            .0,
+        StatusCode::NOT_FOUND
+    );
+
+    // Invalid credentials are rejected.
+    for authorization in [
+        basic("admin:wrong"),
+        basic("missing:hunter2"),
+        basic("no-separator"),
+        String::from("Basic not-base64!"),
+        String::from("Bearer token"),
+    ] {
+        assert_eq!(
+            request(&server, Method::GET, "/api/databases", Some(&authorization))
+                .await?
+                .0,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    // Requests without credentials use the default permissions, which don't
+    // allow using any of the routes.
+    for path in [
+        "/api/databases",
+        "/api/schemas",
+        "/api/users",
+        "/api/tasks",
+        "/api/usage",
+    ] {
+        assert_eq!(
+            request(&server, Method::GET, path, None).await?.0,
+            StatusCode::FORBIDDEN,
+            "{path}"
+        );
+    }
+
+    let (status, databases) = request(&server, Method::GET, "/api/databases", admin).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert!(databases
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|database| database["name"] == "tests"));
+
+    let (status, schemas) = request(&server, Method::GET, "/api/schemas", admin).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert!(schemas
+        .as_array()
+        .unwrap()
+        .contains(&Value::String(BasicSchema::schema_name().to_string())));
+
+    let (status, collections) = request(
+        &server,
+        Method::GET,
+        "/api/databases/tests/collections",
+        admin,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK);
+    let basic_collection = collections
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|collection| collection["name"] == Basic::collection_name().to_string())
+        .expect("collection not listed");
+    let collection = basic_collection["encoded"].as_str().unwrap();
+
+    let (status, documents) = request(
+        &server,
+        Method::GET,
+        &format!("/api/databases/tests/collections/{collection}/documents"),
+        admin,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(documents[0]["id"], document.header.id.to_string());
+    assert_eq!(documents.as_array().unwrap().len(), 2);
+
+    // Query parameters are percent-decoded.
+    let (status, documents) = request(
+        &server,
+        Method::GET,
+        &format!("/api/databases/tests/collections/{collection}/documents?limit=%31"),
+        admin,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(documents.as_array().unwrap().len(), 1);
+
+    let view = basic_collection["views"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|view| view["name"] == "by-parent-id")
+        .expect("view not listed")["encoded"]
+        .as_str()
+        .unwrap();
+    let (status, mappings) = request(
+        &server,
+        Method::GET,
+        &format!("/api/databases/tests/collections/{collection}/views/{view}"),
+        admin,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(mappings[0]["source"], document.header.id.to_string());
+
+    let (status, users) = request(&server, Method::GET, "/api/users", admin).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(users[0]["id"], user_id);
+    assert_eq!(users[0]["username"], "admin");
+
+    // Users can be administered through the dashboard.
+    let (status, created) = change(
+        &server,
+        Method::POST,
+        "/api/users",
+        admin,
+        &serde_json::json!({ "username": "dashboard-user" }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK);
+    let created = created["id"].as_u64().unwrap();
+    assert_eq!(
+        change(
+            &server,
+            Method::POST,
+            "/api/users",
+            admin,
+            &serde_json::json!({ "username": "dashboard-user" }),
+        )
+        .await?
+        .0,
+        StatusCode::CONFLICT
+    );
+    assert_eq!(
+        change(&server, Method::POST, "/api/users", admin, &Value::Null)
+            .await?
+            .0,
+        StatusCode::BAD_REQUEST
+    );
+
+    let admin_db = server.admin().await;
+    let group = PermissionGroup::named("dashboard")
+        .push_into_async(&admin_db)
+        .await?;
+    let role = Role::named("dashboard").push_into_async(&admin_db).await?;
+    for (method, path, body) in [
+        (
+            Method::POST,
+            format!("/api/users/{created}/disable"),
+            Value::Null,
+        ),
+        (
+            Method::PUT,
+            format!("/api/users/{created}/password"),
+            serde_json::json!({ "password": "dashboard-password" }),
+        ),
+        (
+            Method::PUT,
+            format!("/api/users/{created}/groups/{}", group.header.id),
+            Value::Null,
+        ),
+        (
+            Method::PUT,
+            format!("/api/users/{created}/roles/{}", role.header.id),
+            Value::Null,
+        ),
+    ] {
+        // Requests without credentials aren't permitted to change users.
+        assert_eq!(
+            change(&server, method.clone(), &path, None, &body).await?.0,
+            StatusCode::FORBIDDEN,
+            "{path}"
+        );
+        assert_eq!(
+            change(&server, method, &path, admin, &body).await?.0,
+            StatusCode::OK,
+            "{path}"
+        );
+    }
+    let user = User::get_async(&created, &admin_db).await?.unwrap();
+    assert!(user.contents.disabled);
+    assert_eq!(user.contents.groups, vec![group.header.id]);
+    assert_eq!(user.contents.roles, vec![role.header.id]);
+
+    for (method, path) in [
+        (Method::POST, format!("/api/users/{created}/enable")),
+        (
+            Method::DELETE,
+            format!("/api/users/{created}/groups/{}", group.header.id),
+        ),
+        (
+            Method::DELETE,
+            format!("/api/users/{created}/roles/{}", role.header.id),
+        ),
+    ] {
+        assert_eq!(
+            change(&server, method, &path, admin, &Value::Null).await?.0,
+            StatusCode::OK,
+            "{path}"
+        );
+    }
+    let user = User::get_async(&created, &admin_db).await?.unwrap();
+    assert!(!user.contents.disabled);
+    assert!(user.contents.groups.is_empty());
+    assert!(user.contents.roles.is_empty());
+    assert_eq!(
+        change(
+            &server,
+            Method::POST,
+            "/api/users/not-an-id/disable",
+            admin,
+            &Value::Null
+        )
+        .await?
+        .0,
+        StatusCode::NOT_FOUND
+    );
+
+    let (status, tasks) = request(&server, Method::GET, "/api/tasks", admin).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert!(tasks["view_updates"].is_number());
+
+    let (status, usage) = request(&server, Method::GET, "/api/usage", admin).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert!(usage["databases"].is_object());
+
+    assert_eq!(
+        request(
+            &server,
+            Method::GET,
+            "/api/databases/missing/collections",
+            admin
+        )
+        .await?
+        .0,
+        StatusCode::NOT_FOUND
+    );
+
+    // Failed attempts are subject to the login throttle.
+    let address = Some(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)));
+    let wrong = basic("admin:wrong");
+    for _ in 0..6 {
+        assert_eq!(
+            request_from(
+                &server,
+                Method::GET,
+                "/api/databases",
+                Some(&wrong),
+                address
+            )
+            .await?
+            .0,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+    assert_eq!(
+        request_from(&server, Method::GET, "/api/databases", admin, address)
+            .await?
+            .0,
+        StatusCode::TOO_MANY_REQUESTS
+    );
+
+    Ok(())
+}
+
 struct TestHarness {
     _directory: TestDirectory,
     server: Server,
@@ -1,13 +1,20 @@
 use bonsaidb_client::{AsyncClient, AsyncRemoteDatabase};
+use bonsaidb_core::admin::{PermissionGroup, Role, User};
 use bonsaidb_core::async_trait::async_trait;
 use bonsaidb_core::connection::{
     self, AccessPolicy, AsyncConnection, AsyncLowLevelConnection, AsyncStorageConnection,
-    HasSchema, HasSession, IdentityReference, Range, SerializedQueryKey, Session, Sort,
+    DatabaseQuery, HasSchema, HasSession, IdentityReference, Range, SerializedQueryKey, Session,
+    SessionId, SessionInfo, Sort, ViewReadOptions,
 };
-use bonsaidb_core::document::{DocumentId, Header, OwnedDocument};
+use bonsaidb_core::document::{CollectionDocument, DocumentId, Header, OwnedDocument};
+use bonsaidb_core::permissions::{Permissions, Statement};
+use bonsaidb_core::schema::view::aggregation::{
+    Aggregation, AggregationGrouping, SerializedAggregatedGroup,
+};
+use bonsaidb_core::schema::view::join::SerializedJoinedEntry;
 use bonsaidb_core::schema::view::map::MappedSerializedValue;
 use bonsaidb_core::schema::{
-    self, Collection, CollectionName, Nameable, Schema, SchemaName, Schematic, ViewName,
+    self, Collection, CollectionName, Name, Nameable, Schema, SchemaName, Schematic, ViewName,
 };
 use bonsaidb_core::transaction::{Executed, OperationResult, Transaction};
 use bonsaidb_server::{Backend, CustomServer, NoBackend, ServerDatabase};
@@ -83,10 +90,13 @@ impl<B: Backend> AsyncStorageConnection for AnyServerConnection<B> {
         }
     }
 
-    async fn list_databases(&self) -> Result<Vec<connection::Database>, bonsaidb_core::Error> {
+    async fn list_databases_matching(
+        &self,
+        query: DatabaseQuery,
+    ) -> Result<Vec<connection::Database>, bonsaidb_core::Error> {
         match self {
-            Self::Local(server) => server.list_databases().await,
-            Self::Networked(client) => client.list_databases().await,
+            Self::Local(server) => server.list_databases_matching(query).await,
+            Self::Networked(client) => client.list_databases_matching(query).await,
         }
     }
 
@@ -114,6 +124,65 @@ impl<B: Backend> AsyncStorageConnection for AnyServerConnection<B> {
         }
     }
 
+    async fn list_users(&self) -> Result<Vec<CollectionDocument<User>>, bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => server.list_users().await,
+            Self::Networked(client) => client.list_users().await,
+        }
+    }
+
+    async fn user_by_name(
+        &self,
+        username: &str,
+    ) -> Result<Option<CollectionDocument<User>>, bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => server.user_by_name(username).await,
+            Self::Networked(client) => client.user_by_name(username).await,
+        }
+    }
+
+    async fn disable_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<(), bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => server.disable_user(user).await,
+            Self::Networked(client) => client.disable_user(user).await,
+        }
+    }
+
+    async fn enable_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<(), bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => server.enable_user(user).await,
+            Self::Networked(client) => client.enable_user(user).await,
+        }
+    }
+
+    async fn unlock_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<(), bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => server.unlock_user(user).await,
+            Self::Networked(client) => client.unlock_user(user).await,
+        }
+    }
+
+    async fn effective_permissions<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<Permissions, bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => server.effective_permissions(user).await,
+            Self::Networked(client) => {
+                AsyncStorageConnection::effective_permissions(client, user).await
+            }
+        }
+    }
+
     #[cfg(feature = "password-hashing")]
     async fn set_user_password<'user, U: Nameable<'user, u64> + Send + Sync>(
         &self,
@@ -150,6 +219,16 @@ impl<B: Backend> AsyncStorageConnection for AnyServerConnection<B> {
         }
     }
 
+    async fn as_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<Self::Authenticated, bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => server.as_user(user).await.map(Self::Local),
+            Self::Networked(client) => client.as_user(user).await.map(Self::Networked),
+        }
+    }
+
     async fn add_permission_group_to_user<
         'user,
         'group,
@@ -229,6 +308,192 @@ impl<B: Backend> AsyncStorageConnection for AnyServerConnection<B> {
             Self::Networked(client) => client.remove_role_from_user(user, role).await,
         }
     }
+
+    async fn create_permission_group(
+        &self,
+        name: &str,
+        statements: Vec<Statement>,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => server.create_permission_group(name, statements).await,
+            Self::Networked(client) => client.create_permission_group(name, statements).await,
+        }
+    }
+
+    async fn list_permission_groups(
+        &self,
+    ) -> Result<Vec<CollectionDocument<PermissionGroup>>, bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => server.list_permission_groups().await,
+            Self::Networked(client) => client.list_permission_groups().await,
+        }
+    }
+
+    async fn set_permission_group_statements<'group, G: Nameable<'group, u64> + Send + Sync>(
+        &self,
+        permission_group: G,
+        statements: Vec<Statement>,
+    ) -> Result<(), bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => {
+                server
+                    .set_permission_group_statements(permission_group, statements)
+                    .await
+            }
+            Self::Networked(client) => {
+                client
+                    .set_permission_group_statements(permission_group, statements)
+                    .await
+            }
+        }
+    }
+
+    async fn update_permission_group_statements<'group, G: Nameable<'group, u64> + Send + Sync>(
+        &self,
+        permission_group: G,
+        expected_version: u64,
+        statements: Vec<Statement>,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => {
+                server
+                    .update_permission_group_statements(
+                        permission_group,
+                        expected_version,
+                        statements,
+                    )
+                    .await
+            }
+            Self::Networked(client) => {
+                client
+                    .update_permission_group_statements(
+                        permission_group,
+                        expected_version,
+                        statements,
+                    )
+                    .await
+            }
+        }
+    }
+
+    async fn delete_permission_group<'group, G: Nameable<'group, u64> + Send + Sync>(
+        &self,
+        permission_group: G,
+    ) -> Result<(), bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => server.delete_permission_group(permission_group).await,
+            Self::Networked(client) => client.delete_permission_group(permission_group).await,
+        }
+    }
+
+    async fn create_role(&self, name: &str) -> Result<u64, bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => server.create_role(name).await,
+            Self::Networked(client) => client.create_role(name).await,
+        }
+    }
+
+    async fn list_roles(&self) -> Result<Vec<CollectionDocument<Role>>, bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => server.list_roles().await,
+            Self::Networked(client) => client.list_roles().await,
+        }
+    }
+
+    async fn add_permission_group_to_role<
+        'role,
+        'group,
+        R: Nameable<'role, u64> + Send + Sync,
+        G: Nameable<'group, u64> + Send + Sync,
+    >(
+        &self,
+        role: R,
+        permission_group: G,
+    ) -> Result<(), bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => {
+                server
+                    .add_permission_group_to_role(role, permission_group)
+                    .await
+            }
+            Self::Networked(client) => {
+                client
+                    .add_permission_group_to_role(role, permission_group)
+                    .await
+            }
+        }
+    }
+
+    async fn remove_permission_group_from_role<
+        'role,
+        'group,
+        R: Nameable<'role, u64> + Send + Sync,
+        G: Nameable<'group, u64> + Send + Sync,
+    >(
+        &self,
+        role: R,
+        permission_group: G,
+    ) -> Result<(), bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => {
+                server
+                    .remove_permission_group_from_role(role, permission_group)
+                    .await
+            }
+            Self::Networked(client) => {
+                client
+                    .remove_permission_group_from_role(role, permission_group)
+                    .await
+            }
+        }
+    }
+
+    async fn update_role_permission_groups<'role, R: Nameable<'role, u64> + Send + Sync>(
+        &self,
+        role: R,
+        expected_version: u64,
+        permission_groups: Vec<u64>,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => {
+                server
+                    .update_role_permission_groups(role, expected_version, permission_groups)
+                    .await
+            }
+            Self::Networked(client) => {
+                client
+                    .update_role_permission_groups(role, expected_version, permission_groups)
+                    .await
+            }
+        }
+    }
+
+    async fn delete_role<'role, R: Nameable<'role, u64> + Send + Sync>(
+        &self,
+        role: R,
+    ) -> Result<(), bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => server.delete_role(role).await,
+            Self::Networked(client) => client.delete_role(role).await,
+        }
+    }
+
+    async fn list_sessions<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<Vec<SessionInfo>, bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => server.list_sessions(user).await,
+            Self::Networked(client) => client.list_sessions(user).await,
+        }
+    }
+
+    async fn revoke_session(&self, session_id: SessionId) -> Result<(), bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => server.revoke_session(session_id).await,
+            Self::Networked(client) => client.revoke_session(session_id).await,
+        }
+    }
 }
 
 /// A database connection that can be either from a local server or a server
@@ -374,6 +639,28 @@ impl<B: Backend> AsyncLowLevelConnection for AnyDatabase<B> {
         }
     }
 
+    async fn list_projected_from_collection(
+        &self,
+        ids: Range<DocumentId>,
+        order: Sort,
+        limit: Option<u32>,
+        collection: &CollectionName,
+        projection: &Name,
+    ) -> Result<Vec<OwnedDocument>, bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => {
+                server
+                    .list_projected_from_collection(ids, order, limit, collection, projection)
+                    .await
+            }
+            Self::Networked(client) => {
+                client
+                    .list_projected_from_collection(ids, order, limit, collection, projection)
+                    .await
+            }
+        }
+    }
+
     async fn count_from_collection(
         &self,
         ids: Range<DocumentId>,
@@ -413,16 +700,17 @@ impl<B: Backend> AsyncLowLevelConnection for AnyDatabase<B> {
         order: Sort,
         limit: Option<u32>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<Vec<schema::view::map::Serialized>, bonsaidb_core::Error> {
         match self {
             Self::Local(server) => {
                 server
-                    .query_by_name(view, key, order, limit, access_policy)
+                    .query_by_name(view, key, order, limit, access_policy, read_options)
                     .await
             }
             Self::Networked(client) => {
                 client
-                    .query_by_name(view, key, order, limit, access_policy)
+                    .query_by_name(view, key, order, limit, access_policy, read_options)
                     .await
             }
         }
@@ -435,16 +723,57 @@ impl<B: Backend> AsyncLowLevelConnection for AnyDatabase<B> {
         order: Sort,
         limit: Option<u32>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+    ) -> Result<schema::view::map::MappedSerializedDocuments, bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => {
+                server
+                    .query_by_name_with_docs(view, key, order, limit, access_policy, read_options)
+                    .await
+            }
+            Self::Networked(client) => {
+                client
+                    .query_by_name_with_docs(view, key, order, limit, access_policy, read_options)
+                    .await
+            }
+        }
+    }
+
+    async fn query_by_name_with_projected_docs(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+        projection: &Name,
     ) -> Result<schema::view::map::MappedSerializedDocuments, bonsaidb_core::Error> {
         match self {
             Self::Local(server) => {
                 server
-                    .query_by_name_with_docs(view, key, order, limit, access_policy)
+                    .query_by_name_with_projected_docs(
+                        view,
+                        key,
+                        order,
+                        limit,
+                        access_policy,
+                        read_options,
+                        projection,
+                    )
                     .await
             }
             Self::Networked(client) => {
                 client
-                    .query_by_name_with_docs(view, key, order, limit, access_policy)
+                    .query_by_name_with_projected_docs(
+                        view,
+                        key,
+                        order,
+                        limit,
+                        access_policy,
+                        read_options,
+                        projection,
+                    )
                     .await
             }
         }
@@ -455,10 +784,19 @@ impl<B: Backend> AsyncLowLevelConnection for AnyDatabase<B> {
         view: &ViewName,
         key: Option<SerializedQueryKey>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<Vec<u8>, bonsaidb_core::Error> {
         match self {
-            Self::Local(server) => server.reduce_by_name(view, key, access_policy).await,
-            Self::Networked(client) => client.reduce_by_name(view, key, access_policy).await,
+            Self::Local(server) => {
+                server
+                    .reduce_by_name(view, key, access_policy, read_options)
+                    .await
+            }
+            Self::Networked(client) => {
+                client
+                    .reduce_by_name(view, key, access_policy, read_options)
+                    .await
+            }
         }
     }
 
@@ -467,16 +805,118 @@ impl<B: Backend> AsyncLowLevelConnection for AnyDatabase<B> {
         view: &ViewName,
         key: Option<SerializedQueryKey>,
         access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
     ) -> Result<Vec<MappedSerializedValue>, bonsaidb_core::Error> {
         match self {
             Self::Local(server) => {
                 server
-                    .reduce_grouped_by_name(view, key, access_policy)
+                    .reduce_grouped_by_name(view, key, access_policy, read_options)
+                    .await
+            }
+            Self::Networked(client) => {
+                client
+                    .reduce_grouped_by_name(view, key, access_policy, read_options)
+                    .await
+            }
+        }
+    }
+
+    async fn aggregate_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        aggregations: &[Aggregation],
+        grouping: AggregationGrouping,
+        access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+    ) -> Result<Vec<SerializedAggregatedGroup>, bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => {
+                server
+                    .aggregate_by_name(
+                        view,
+                        key,
+                        aggregations,
+                        grouping,
+                        access_policy,
+                        read_options,
+                    )
+                    .await
+            }
+            Self::Networked(client) => {
+                client
+                    .aggregate_by_name(
+                        view,
+                        key,
+                        aggregations,
+                        grouping,
+                        access_policy,
+                        read_options,
+                    )
+                    .await
+            }
+        }
+    }
+
+    async fn count_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => {
+                server
+                    .count_by_name(view, key, access_policy, read_options)
+                    .await
+            }
+            Self::Networked(client) => {
+                client
+                    .count_by_name(view, key, access_policy, read_options)
+                    .await
+            }
+        }
+    }
+
+    async fn document_mappings_by_name(
+        &self,
+        view: &ViewName,
+        document_id: DocumentId,
+        access_policy: AccessPolicy,
+        read_options: ViewReadOptions,
+    ) -> Result<Vec<schema::view::map::Serialized>, bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => {
+                server
+                    .document_mappings_by_name(view, document_id, access_policy, read_options)
+                    .await
+            }
+            Self::Networked(client) => {
+                client
+                    .document_mappings_by_name(view, document_id, access_policy, read_options)
+                    .await
+            }
+        }
+    }
+
+    async fn query_join_by_name(
+        &self,
+        join: &ViewName,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+    ) -> Result<Vec<SerializedJoinedEntry>, bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => {
+                server
+                    .query_join_by_name(join, key, order, limit, access_policy)
                     .await
             }
             Self::Networked(client) => {
                 client
-                    .reduce_grouped_by_name(view, key, access_policy)
+                    .query_join_by_name(join, key, order, limit, access_policy)
                     .await
             }
         }
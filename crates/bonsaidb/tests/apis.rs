@@ -1,15 +1,31 @@
 //! Tests invoking an API defined in a custom backend.
 
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
 use bonsaidb::client::url::Url;
-use bonsaidb::client::AsyncClient;
-use bonsaidb::core::api::{Api, Infallible};
+use bonsaidb::client::{ApiError, AsyncClient, RetryPolicy};
+use bonsaidb::core::api::{Api, Infallible, NamedQuery, QueryName};
 use bonsaidb::core::async_trait::async_trait;
-use bonsaidb::core::test_util::{Basic, TestDirectory};
+use bonsaidb::core::test_util::{Basic, BasicByParentId, TestDirectory};
 use bonsaidb::local::config::Builder;
-use bonsaidb::server::api::Handler;
-use bonsaidb::server::{Backend, CustomServer, DefaultPermissions, ServerConfiguration};
+use bonsaidb::server::api::{
+    encode_response, ApiPlugin, ApiRegistry, ApiRequest, Handler, Middleware, NamedQueryHandler,
+    Next,
+};
+use bonsaidb::server::{
+    Backend, CustomServer, DefaultPermissions, ServerConfiguration, ServerDatabase,
+};
 use bonsaidb_core::api::ApiName;
+use bonsaidb_core::arc_bytes::serde::Bytes;
+use bonsaidb_core::connection::{AsyncConnection, AsyncStorageConnection};
+use bonsaidb_core::permissions::bonsai::{
+    bonsaidb_resource_name, named_query_resource_name, BonsaiAction, DatabaseAction,
+    NamedQueryAction, ServerAction,
+};
+use bonsaidb_core::permissions::{Permissions, Statement};
 use bonsaidb_core::schema::Qualified;
+use bonsaidb_core::schema::SerializedCollection;
 use bonsaidb_server::api::{HandlerResult, HandlerSession};
 use serde::{Deserialize, Serialize};
 
@@ -51,6 +67,325 @@ async fn custom_api() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn request_timeouts() -> anyhow::Result<()> {
+    let dir = TestDirectory::new("request_timeouts.bonsaidb");
+    let server = CustomServer::<CustomBackend>::open(
+        ServerConfiguration::new(&dir)
+            .default_permissions(DefaultPermissions::AllowAll)
+            .with_api::<SetValueHandler, _>()?
+            .with_api::<StallHandler, _>()?
+            .with_schema::<Basic>()?,
+    )
+    .await?;
+    server.install_self_signed_certificate(false).await?;
+    let certificate = server
+        .certificate_chain()
+        .await?
+        .into_end_entity_certificate();
+    tokio::spawn(async move { server.listen_on(12347).await });
+
+    let client = AsyncClient::build(Url::parse("bonsaidb://localhost:12347")?)
+        .with_api::<SetValue>()
+        .with_api::<Stall>()
+        .with_certificate(certificate)
+        .with_request_timeout(Duration::from_millis(100))
+        .with_retry_policy(RetryPolicy::exponential(2, Duration::from_millis(10)))
+        .build()?;
+
+    // Stall is idempotent, so it is attempted three times before the timeout
+    // is returned.
+    assert!(matches!(
+        client.send_api_request(&Stall).await,
+        Err(ApiError::Client(bonsaidb::client::Error::RequestTimeout))
+    ));
+    assert_eq!(STALLS.load(Ordering::SeqCst), 3);
+
+    // Overriding the timeout allows the request to complete.
+    client
+        .with_request_timeout(Some(Duration::from_secs(5)))
+        .send_api_request(&Stall)
+        .await?;
+
+    let old_value = client.send_api_request(&SetValue { new_value: 1 }).await?;
+    assert_eq!(old_value, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn api_plugins() -> anyhow::Result<()> {
+    let dir = TestDirectory::new("api_plugins.bonsaidb");
+
+    // Each api can only be registered once.
+    assert!(matches!(
+        ServerConfiguration::<CustomBackend>::new(&dir)
+            .with_api::<SetValueHandler, _>()?
+            .with_api_plugin(&ValuePlugin),
+        Err(bonsaidb::server::Error::ApiAlreadyRegistered(name)) if name == SetValue::name()
+    ));
+
+    let server = CustomServer::<CustomBackend>::open(
+        ServerConfiguration::new(&dir)
+            .default_permissions(Permissions::from(
+                Statement::for_any().allowing(&BonsaiAction::Server(ServerAction::Connect)),
+            ))
+            .with_api_plugin(&ValuePlugin)?
+            .with_schema::<Basic>()?,
+    )
+    .await?;
+    server.install_self_signed_certificate(false).await?;
+    let certificate = server
+        .certificate_chain()
+        .await?
+        .into_end_entity_certificate();
+    tokio::spawn(async move { server.listen_on(12348).await });
+
+    let client = AsyncClient::build(Url::parse("bonsaidb://localhost:12348")?)
+        .with_api::<SetValue>()
+        .with_api::<GetValue>()
+        .with_certificate(certificate)
+        .build()?;
+
+    let old_value = client.send_api_request(&SetValue { new_value: 1 }).await?;
+    assert_eq!(old_value, None);
+
+    // GetValue requires permission to list databases, which the client has
+    // not been granted.
+    assert!(matches!(
+        client.send_api_request(&GetValue).await,
+        Err(ApiError::Client(bonsaidb::client::Error::Core(
+            bonsaidb::core::Error::PermissionDenied(_)
+        )))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn middleware() -> anyhow::Result<()> {
+    let dir = TestDirectory::new("middleware.bonsaidb");
+    let server = CustomServer::<CustomBackend>::open(
+        ServerConfiguration::new(&dir)
+            .default_permissions(DefaultPermissions::AllowAll)
+            .with_middleware(ValueMiddleware)
+            .with_api::<SetValueHandler, _>()?
+            .with_schema::<Basic>()?,
+    )
+    .await?;
+    server.install_self_signed_certificate(false).await?;
+    let certificate = server
+        .certificate_chain()
+        .await?
+        .into_end_entity_certificate();
+    tokio::spawn(async move { server.listen_on(12349).await });
+
+    let client = AsyncClient::build(Url::parse("bonsaidb://localhost:12349")?)
+        .with_api::<SetValue>()
+        .with_api::<GetValue>()
+        .with_certificate(certificate)
+        .build()?;
+
+    // The middleware doubles each value before it reaches the handler.
+    let old_value = client.send_api_request(&SetValue { new_value: 1 }).await?;
+    assert_eq!(old_value, None);
+    let old_value = client.send_api_request(&SetValue { new_value: 2 }).await?;
+    assert_eq!(old_value, Some(2));
+    assert_eq!(VALUES_SET.load(Ordering::SeqCst), 2);
+
+    // GetValue has no handler, but the middleware responds to it.
+    assert_eq!(client.send_api_request(&GetValue).await?, Some(42));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn named_queries() -> anyhow::Result<()> {
+    let dir = TestDirectory::new("named_queries.bonsaidb");
+
+    // Each query can only be registered once.
+    assert!(matches!(
+        ServerConfiguration::<CustomBackend>::new(&dir)
+            .with_named_query::<ChildCountHandler, ChildCount>()?
+            .with_named_query::<ChildCountHandler, ChildCount>(),
+        Err(bonsaidb::server::Error::NamedQueryAlreadyRegistered(name)) if name == ChildCount::name()
+    ));
+
+    // Clients are only allowed to execute the named queries, not to query
+    // the view the queries use.
+    let server = CustomServer::<CustomBackend>::open(
+        ServerConfiguration::new(&dir)
+            .default_permissions(Permissions::from(vec![
+                Statement::for_any().allowing(&BonsaiAction::Server(ServerAction::Connect)),
+                Statement::for_resource(named_query_resource_name("tests", &ChildCount::name()))
+                    .allowing(&BonsaiAction::Database(DatabaseAction::NamedQuery(
+                        NamedQueryAction::Execute,
+                    ))),
+                Statement::for_resource(named_query_resource_name(
+                    "tests",
+                    &QueryName::private("unregistered"),
+                ))
+                .allowing(&BonsaiAction::Database(DatabaseAction::NamedQuery(
+                    NamedQueryAction::Execute,
+                ))),
+            ]))
+            .with_named_query::<ChildCountHandler, ChildCount>()?
+            .with_schema::<Basic>()?,
+    )
+    .await?;
+    server.install_self_signed_certificate(false).await?;
+    let certificate = server
+        .certificate_chain()
+        .await?
+        .into_end_entity_certificate();
+    let server_db = server.create_database::<Basic>("tests", false).await?;
+    let parent = Basic::new("parent").push_into_async(&server_db).await?;
+    for _ in 0..2 {
+        Basic::new("child")
+            .with_parent_id(parent.header.id)
+            .push_into_async(&server_db)
+            .await?;
+    }
+    tokio::spawn(async move { server.listen_on(12350).await });
+
+    let client = AsyncClient::build(Url::parse("bonsaidb://localhost:12350")?)
+        .with_certificate(certificate)
+        .build()?;
+    let db = client.database::<Basic>("tests").await?;
+
+    assert_eq!(
+        db.execute_named_query::<ChildCount>(&parent.header.id)
+            .await?,
+        2
+    );
+    assert!(matches!(
+        db.view::<BasicByParentId>().query().await,
+        Err(bonsaidb_core::Error::PermissionDenied(_))
+    ));
+    assert!(matches!(
+        db.execute_named_query::<UnregisteredQuery>(&()).await,
+        Err(bonsaidb_core::Error::NamedQueryNotFound(name)) if name == UnregisteredQuery::name()
+    ));
+    // Permission is granted per query.
+    let other_db = client.database::<Basic>("other").await?;
+    assert!(matches!(
+        other_db
+            .execute_named_query::<ChildCount>(&parent.header.id)
+            .await,
+        Err(bonsaidb_core::Error::PermissionDenied(_))
+    ));
+
+    Ok(())
+}
+
+#[derive(Debug)]
+struct ChildCount;
+
+impl NamedQuery for ChildCount {
+    // The id of the parent document.
+    type Parameters = u64;
+    type Output = u64;
+
+    fn name() -> QueryName {
+        QueryName::private("child-count")
+    }
+}
+
+#[derive(Debug)]
+struct ChildCountHandler;
+
+#[async_trait]
+impl NamedQueryHandler<CustomBackend, ChildCount> for ChildCountHandler {
+    async fn execute(
+        database: ServerDatabase<CustomBackend>,
+        parent_id: u64,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        database
+            .view::<BasicByParentId>()
+            .with_key(&Some(parent_id))
+            .count()
+            .await
+    }
+}
+
+#[derive(Debug)]
+struct UnregisteredQuery;
+
+impl NamedQuery for UnregisteredQuery {
+    type Parameters = ();
+    type Output = ();
+
+    fn name() -> QueryName {
+        QueryName::private("unregistered")
+    }
+}
+
+static VALUES_SET: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug)]
+struct ValueMiddleware;
+
+#[async_trait]
+impl Middleware<CustomBackend> for ValueMiddleware {
+    async fn handle(
+        &self,
+        mut request: ApiRequest<'_, CustomBackend>,
+        next: Next<'_, CustomBackend>,
+    ) -> Result<Bytes, bonsaidb::server::Error> {
+        if request.is::<GetValue>() {
+            return encode_response::<GetValue>(&Ok(Some(42)));
+        }
+        if let Some(set) = request.decode::<SetValue>()? {
+            VALUES_SET.fetch_add(1, Ordering::SeqCst);
+            request.replace(&SetValue {
+                new_value: set.new_value * 2,
+            })?;
+        }
+        next.run(request).await
+    }
+}
+
+#[derive(Debug)]
+struct ValuePlugin;
+
+impl ApiPlugin<CustomBackend> for ValuePlugin {
+    fn register_apis(
+        &self,
+        apis: &mut ApiRegistry<CustomBackend>,
+    ) -> Result<(), bonsaidb::server::Error> {
+        apis.register::<SetValueHandler, SetValue>()?;
+        apis.register_with_permission::<GetValueHandler, GetValue, _>(
+            bonsaidb_resource_name(),
+            BonsaiAction::Server(ServerAction::ListDatabases),
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct GetValue;
+
+impl Api for GetValue {
+    type Error = Infallible;
+    type Response = Option<u64>;
+
+    fn name() -> ApiName {
+        ApiName::private("get-value")
+    }
+}
+
+#[derive(Debug)]
+struct GetValueHandler;
+
+#[async_trait]
+impl Handler<CustomBackend, GetValue> for GetValueHandler {
+    async fn handle(
+        session: HandlerSession<'_, CustomBackend>,
+        _request: GetValue,
+    ) -> HandlerResult<GetValue> {
+        Ok(*session.client.client_data().await)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct SetValue {
     new_value: u64,
@@ -79,3 +414,36 @@ impl Handler<CustomBackend, SetValue> for SetValueHandler {
         Ok(existing_value)
     }
 }
+
+static STALLS: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Stall;
+
+impl Api for Stall {
+    type Error = Infallible;
+    type Response = ();
+
+    fn name() -> ApiName {
+        ApiName::private("stall")
+    }
+
+    fn idempotent() -> bool {
+        true
+    }
+}
+
+#[derive(Debug)]
+struct StallHandler;
+
+#[async_trait]
+impl Handler<CustomBackend, Stall> for StallHandler {
+    async fn handle(
+        _session: HandlerSession<'_, CustomBackend>,
+        _request: Stall,
+    ) -> HandlerResult<Stall> {
+        STALLS.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        Ok(())
+    }
+}
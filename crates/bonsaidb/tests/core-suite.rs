@@ -193,6 +193,693 @@ mod websockets {
         check_incompatible_client(client).await
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn unix_socket() -> anyhow::Result<()> {
+        use bonsaidb::server::Transport;
+        use bonsaidb_core::connection::AsyncStorageConnection;
+
+        let database_path = TestDirectory::new("unix-socket");
+        let server = initialize_basic_server(database_path.as_ref()).await?;
+        let socket_path = database_path.as_ref().join("server.sock");
+        tokio::spawn({
+            let server = server.clone();
+            let socket_path = socket_path.clone();
+            async move {
+                server.listen_on_unix_socket(socket_path).await?;
+                Result::<(), anyhow::Error>::Ok(())
+            }
+        });
+        // Give the server time to listen
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let client = AsyncClient::build_unix_socket(&socket_path).build()?;
+        client
+            .create_database::<BasicSchema>("unix-socket", false)
+            .await?;
+        let db = client.database::<BasicSchema>("unix-socket").await?;
+        bonsaidb_core::test_util::store_retrieve_update_delete_tests(&db).await?;
+
+        let clients = server.connected_clients();
+        assert_eq!(clients.len(), 1);
+        assert_eq!(clients[0].transport(), &Transport::UnixSocket);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn configured_listeners() -> anyhow::Result<()> {
+        use bonsaidb::server::ListenerConfiguration;
+        use bonsaidb_core::connection::AsyncStorageConnection;
+
+        let database_path = TestDirectory::new("configured-listeners");
+        let server = Server::open(
+            ServerConfiguration::new(&database_path)
+                .default_permissions(DefaultPermissions::AllowAll)
+                .listener(ListenerConfiguration::bonsai(6006))
+                .listener(
+                    ListenerConfiguration::websockets(([127, 0, 0, 1], 6007), false)
+                        .default_permissions(
+                            Statement::for_any()
+                                .allowing(&BonsaiAction::Server(ServerAction::Connect)),
+                        ),
+                )
+                .with_schema::<BasicSchema>()?,
+        )
+        .await?;
+        server.install_self_signed_certificate(false).await?;
+        let certificate = server
+            .certificate_chain()
+            .await?
+            .into_end_entity_certificate();
+        tokio::spawn({
+            let server = server.clone();
+            async move {
+                server.listen().await?;
+                Result::<(), anyhow::Error>::Ok(())
+            }
+        });
+        // Give the server time to listen
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        // The QUIC listener uses the server's default permissions.
+        let quic = AsyncClient::build(Url::parse("bonsaidb://localhost:6006")?)
+            .with_certificate(certificate)
+            .build()?;
+        quic.create_database::<BasicSchema>("listeners", false)
+            .await?;
+
+        // The WebSocket listener only allows connecting.
+        let websocket = AsyncClient::new(Url::parse("ws://127.0.0.1:6007")?)?;
+        assert!(matches!(
+            websocket.list_databases().await,
+            Err(bonsaidb_core::Error::PermissionDenied(_))
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn offline_write_queue() -> anyhow::Result<()> {
+        use bonsaidb::client::{KeyValueQueueStorage, OfflineQueue, WriteOutcome};
+        use bonsaidb::core::test_util::Basic;
+        use bonsaidb::core::transaction::{Operation, Transaction};
+        use bonsaidb::local::config::StorageConfiguration;
+        use bonsaidb::local::AsyncDatabase;
+
+        let database_path = TestDirectory::new("offline-write-queue");
+        let server = initialize_basic_server(database_path.as_ref()).await?;
+        let queue_path = TestDirectory::new("offline-write-queue-client");
+        let queue_storage =
+            AsyncDatabase::open::<()>(StorageConfiguration::new(&queue_path)).await?;
+
+        let (replayed_sender, replayed_receiver) = flume::unbounded();
+        let client = AsyncClient::build(Url::parse("ws://localhost:6009")?)
+            .with_offline_queue(
+                OfflineQueue::new(10)
+                    .persisted_in(KeyValueQueueStorage::new(
+                        queue_storage.clone(),
+                        "offline-writes",
+                    ))
+                    .on_replayed(move |replayed| drop(replayed_sender.send(replayed))),
+            )
+            .build()?;
+        let db = client.database::<BasicSchema>("tests").await?;
+
+        // The server isn't listening yet, so the write is queued.
+        let outcome = db
+            .apply_transaction_or_queue(Transaction::from(Operation::push_serialized::<Basic>(
+                &Basic::new("offline"),
+            )?))
+            .await?;
+        let WriteOutcome::Queued(id) = outcome else {
+            unreachable!("write applied while server was offline")
+        };
+        assert_eq!(client.queued_writes().await?.len(), 1);
+        assert!(queue_storage.get_key("offline-writes").await?.is_some());
+
+        tokio::spawn({
+            let server = server.clone();
+            async move {
+                server
+                    .listen_for_websockets_on("localhost:6009", false)
+                    .await?;
+                Result::<(), anyhow::Error>::Ok(())
+            }
+        });
+
+        let replayed =
+            tokio::time::timeout(Duration::from_secs(30), replayed_receiver.recv_async()).await??;
+        assert_eq!(replayed.id, id);
+        assert_eq!(replayed.database, "tests");
+        assert!(replayed.result.is_ok());
+        assert!(client.queued_writes().await?.is_empty());
+        assert!(queue_storage.get_key("offline-writes").await?.is_none());
+
+        let documents = Basic::all_async(&db).await?;
+        assert_eq!(documents.len(), 1);
+        assert_eq!(documents[0].contents.value, "offline");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn document_cache() -> anyhow::Result<()> {
+        use bonsaidb::client::DocumentCache;
+        use bonsaidb::core::test_util::Basic;
+        use bonsaidb_core::connection::AsyncStorageConnection;
+
+        let database_path = TestDirectory::new("document-cache");
+        let server = initialize_basic_server(database_path.as_ref()).await?;
+        tokio::spawn({
+            let server = server.clone();
+            async move {
+                server
+                    .listen_for_websockets_on("localhost:6010", false)
+                    .await?;
+                Result::<(), anyhow::Error>::Ok(())
+            }
+        });
+        // Give the server time to listen
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let client = AsyncClient::build(Url::parse("ws://localhost:6010")?)
+            .with_document_cache(DocumentCache::new(10))
+            .build()?;
+        let db = client.database::<BasicSchema>("tests").await?;
+        let doc = Basic::new("initial").push_into_async(&db).await?;
+
+        let retrieved = Basic::get_async(&doc.header.id, &db).await?.unwrap();
+        assert_eq!(retrieved.contents.value, "initial");
+        assert_eq!(client.cached_document_count(), 1);
+        let retrieved = Basic::get_async(&doc.header.id, &db).await?.unwrap();
+        assert_eq!(retrieved.contents.value, "initial");
+
+        // Changing the document without using the client invalidates the
+        // cached copy once the server's notification is received.
+        let server_db = server.database::<BasicSchema>("tests").await?;
+        let mut server_doc = Basic::get_async(&doc.header.id, &server_db).await?.unwrap();
+        server_doc.contents.value = String::from("updated");
+        server_doc.update_async(&server_db).await?;
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while client.cached_document_count() > 0 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await?;
+        let retrieved = Basic::get_async(&doc.header.id, &db).await?.unwrap();
+        assert_eq!(retrieved.contents.value, "updated");
+        assert_eq!(client.cached_document_count(), 1);
+
+        // Changes made through the client are invalidated immediately.
+        let mut doc = retrieved;
+        doc.contents.value = String::from("local");
+        doc.update_async(&db).await?;
+        assert_eq!(client.cached_document_count(), 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn batched_requests() -> anyhow::Result<()> {
+        use bonsaidb::core::connection::{AccessPolicy, MaybeOwned, QueryKey, Sort};
+        use bonsaidb::core::keyvalue::Output;
+        use bonsaidb::core::test_util::{Basic, BasicByParentId};
+        use bonsaidb_core::connection::AsyncStorageConnection;
+
+        let database_path = TestDirectory::new("batched-requests");
+        let server = initialize_basic_server(database_path.as_ref()).await?;
+        tokio::spawn({
+            let server = server.clone();
+            async move {
+                server
+                    .listen_for_websockets_on("localhost:6011", false)
+                    .await?;
+                Result::<(), anyhow::Error>::Ok(())
+            }
+        });
+        // Give the server time to listen
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let client = AsyncClient::new(Url::parse("ws://localhost:6011")?)?;
+        let db = client.database::<BasicSchema>("tests").await?;
+        let parent = Basic::new("parent").push_into_async(&db).await?;
+        let child = Basic::new("child")
+            .with_parent_id(parent.header.id)
+            .push_into_async(&db)
+            .await?;
+        db.set_key("greeting", &String::from("hello")).await?;
+        let missing_db = client.database::<BasicSchema>("missing").await?;
+
+        let mut batch = client.batch();
+        let get_parent = batch.get::<Basic, _>(&db, &parent.header.id)?;
+        let get_missing_doc = batch.get::<Basic, _>(&db, &u64::MAX)?;
+        let query = batch.query::<BasicByParentId, _>(
+            &db,
+            Some(QueryKey::Matches(MaybeOwned::Owned(Some(parent.header.id)))),
+            Sort::Ascending,
+            None,
+            AccessPolicy::UpdateBefore,
+        )?;
+        let reduce =
+            batch.reduce::<BasicByParentId, Option<u64>>(&db, None, AccessPolicy::UpdateBefore)?;
+        let reduce_grouped = batch.reduce_grouped::<BasicByParentId, Option<u64>>(
+            &db,
+            None,
+            AccessPolicy::UpdateBefore,
+        )?;
+        let count =
+            batch.count::<BasicByParentId, Option<u64>>(&db, None, AccessPolicy::UpdateBefore)?;
+        let get_key = batch.get_key(&db, "greeting")?;
+        let get_missing_db = batch.get::<Basic, _>(&missing_db, &parent.header.id)?;
+        assert_eq!(batch.len(), 8);
+        let mut results = batch.execute().await?;
+
+        let document = results.take(get_parent)?.expect("parent not found");
+        assert_eq!(document.header.id.deserialize::<u64>()?, parent.header.id);
+        assert!(results.take(get_missing_doc)?.is_none());
+        let mappings = results.take_view(query)?;
+        assert_eq!(mappings.len(), 1);
+        assert_eq!(mappings[0].key, Some(parent.header.id));
+        assert_eq!(mappings[0].source.id.deserialize::<u64>()?, child.header.id);
+        assert_eq!(results.take_view(reduce)?, 2);
+        let grouped = results.take_view(reduce_grouped)?;
+        assert_eq!(
+            grouped
+                .iter()
+                .map(|mapping| (mapping.key, mapping.value))
+                .collect::<Vec<_>>(),
+            vec![(None, 1), (Some(parent.header.id), 1)]
+        );
+        assert_eq!(results.take_view(count)?, 2);
+        let Output::Value(Some(value)) = results.take(get_key)? else {
+            unreachable!("key not found")
+        };
+        assert_eq!(value.deserialize::<String>()?, "hello");
+        // A request failing does not affect the other requests in the batch.
+        assert!(results.take(get_missing_db).is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "network-compression-deflate")]
+    async fn negotiated_compression() -> anyhow::Result<()> {
+        use bonsaidb_core::networking::{Compression, CompressionConfig};
+        use bonsaidb_core::test_util::Basic;
+
+        let database_path = TestDirectory::new("websocket-compression");
+        let server = initialize_basic_server(database_path.as_ref()).await?;
+        tokio::spawn({
+            let server = server.clone();
+            async move {
+                server
+                    .listen_for_websockets_on("localhost:6012", false)
+                    .await?;
+                Result::<(), anyhow::Error>::Ok(())
+            }
+        });
+        // Give the server time to listen
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let client = AsyncClient::build(Url::parse("ws://localhost:6012")?)
+            .with_compression(CompressionConfig::new([Compression::Deflate]).with_threshold(0))
+            .build()?;
+        let db = client.database::<BasicSchema>("tests").await?;
+        let value = "compressible ".repeat(10_000);
+        let document = Basic::new(value.clone()).push_into_async(&db).await?;
+
+        // The negotiation response is processed independently of other
+        // requests, so wait for it to arrive.
+        let mut attempts = 0;
+        while client.negotiated_compression().is_none() {
+            attempts += 1;
+            assert!(attempts < 100, "compression was never negotiated");
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(client.negotiated_compression(), Some(Compression::Deflate));
+        let clients = server.connected_clients();
+        assert_eq!(clients.len(), 1);
+        assert_eq!(clients[0].compression(), Some(Compression::Deflate));
+
+        // Both the request and the response are large enough to be
+        // compressed.
+        Basic::overwrite_async(&document.header.id, Basic::new(value.clone()), &db).await?;
+        let retrieved = Basic::get_async(&document.header.id, &db)
+            .await?
+            .expect("document not found");
+        assert_eq!(retrieved.contents.value, value);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn capability_negotiation() -> anyhow::Result<()> {
+        use bonsaidb_core::networking::{Capabilities, CURRENT_PROTOCOL_REVISION};
+
+        let database_path = TestDirectory::new("websocket-capabilities");
+        let server = initialize_basic_server(database_path.as_ref()).await?;
+        tokio::spawn({
+            let server = server.clone();
+            async move {
+                server
+                    .listen_for_websockets_on("localhost:6013", false)
+                    .await?;
+                Result::<(), anyhow::Error>::Ok(())
+            }
+        });
+        // Give the server time to listen
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let client = AsyncClient::new(Url::parse("ws://localhost:6013")?)?;
+        let capabilities = client.server_capabilities().await?;
+        assert_eq!(capabilities, Capabilities::current());
+        assert!(capabilities.contains(Capabilities::BATCHES | Capabilities::DOCUMENT_CHANGES));
+
+        let clients = server.connected_clients();
+        assert_eq!(clients.len(), 1);
+        assert_eq!(clients[0].protocol_revision(), CURRENT_PROTOCOL_REVISION);
+        assert_eq!(clients[0].capabilities(), Capabilities::current());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn server_events() -> anyhow::Result<()> {
+        use bonsaidb_core::connection::AsyncStorageConnection;
+        use bonsaidb_core::networking::ServerEvent;
+        use bonsaidb_core::schema::Schema;
+
+        let database_path = TestDirectory::new("websocket-server-events");
+        let server = initialize_basic_server(database_path.as_ref()).await?;
+        tokio::spawn({
+            let server = server.clone();
+            async move {
+                server
+                    .listen_for_websockets_on("localhost:6015", false)
+                    .await?;
+                Result::<(), anyhow::Error>::Ok(())
+            }
+        });
+        // Give the server time to listen
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let client = AsyncClient::new(Url::parse("ws://localhost:6015")?)?;
+        let events = client.server_events();
+        // Ensure the connection has been established before generating events.
+        client.server_capabilities().await?;
+
+        client
+            .create_database::<BasicSchema>("events", false)
+            .await?;
+        // Databases that already exist do not generate events.
+        server
+            .create_database::<BasicSchema>("events", true)
+            .await?;
+        server.delete_database("events").await?;
+        server.send_server_event(&ServerEvent::QuotaWarning {
+            resource: String::from("storage"),
+            usage: 90,
+            limit: 100,
+        });
+
+        let mut received = Vec::new();
+        for _ in 0..3 {
+            received
+                .push(tokio::time::timeout(Duration::from_secs(5), events.recv_async()).await??);
+        }
+        assert_eq!(
+            received,
+            vec![
+                ServerEvent::DatabaseCreated {
+                    name: String::from("events"),
+                    schema: BasicSchema::schema_name(),
+                },
+                ServerEvent::DatabaseDeleted {
+                    name: String::from("events"),
+                },
+                ServerEvent::QuotaWarning {
+                    resource: String::from("storage"),
+                    usage: 90,
+                    limit: 100,
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn payload_limits() -> anyhow::Result<()> {
+        use bonsaidb_core::connection::AsyncStorageConnection;
+        use bonsaidb_core::networking::PayloadLimits;
+        use bonsaidb_core::test_util::Basic;
+
+        let database_path = TestDirectory::new("websocket-payload-limits");
+        let server = Server::open(
+            ServerConfiguration::new(&database_path)
+                .default_permissions(DefaultPermissions::AllowAll)
+                .payload_limits(
+                    PayloadLimits::default()
+                        .max_document_size(1024)
+                        .max_response_size(4096),
+                )
+                .with_schema::<BasicSchema>()?,
+        )
+        .await?;
+        server
+            .create_database::<BasicSchema>("tests", false)
+            .await?;
+        tokio::spawn({
+            let server = server.clone();
+            async move {
+                server
+                    .listen_for_websockets_on("localhost:6014", false)
+                    .await?;
+                Result::<(), anyhow::Error>::Ok(())
+            }
+        });
+        // Give the server time to listen
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        // The client rejects documents exceeding its own limits without
+        // contacting the server.
+        let client = AsyncClient::build(Url::parse("ws://localhost:6014")?)
+            .with_payload_limits(PayloadLimits::default().max_document_size(512))
+            .build()?;
+        let db = client.database::<BasicSchema>("tests").await?;
+        let err = Basic::new("a".repeat(600))
+            .push_into_async(&db)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.error,
+            bonsaidb_core::Error::DocumentTooLarge {
+                size: 600..,
+                limit: 512
+            }
+        ));
+
+        // The server enforces its own limits.
+        let client = AsyncClient::build(Url::parse("ws://localhost:6014")?)
+            .with_payload_limits(PayloadLimits::unlimited())
+            .build()?;
+        let db = client.database::<BasicSchema>("tests").await?;
+        let err = Basic::new("a".repeat(2048))
+            .push_into_async(&db)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.error,
+            bonsaidb_core::Error::DocumentTooLarge { limit: 1024, .. }
+        ));
+
+        for _ in 0..8 {
+            Basic::new("a".repeat(900)).push_into_async(&db).await?;
+        }
+        assert!(matches!(
+            Basic::all_async(&db).await,
+            Err(bonsaidb_core::Error::ResponseTooLarge { limit: 4096, .. })
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn streamed_transactions() -> anyhow::Result<()> {
+        use bonsaidb::core::transaction::{Operation, Transaction};
+        use bonsaidb_core::connection::{AsyncLowLevelConnection, AsyncStorageConnection};
+        use bonsaidb_core::networking::{BeginTransaction, PayloadLimits};
+        use bonsaidb_core::test_util::Basic;
+
+        let database_path = TestDirectory::new("websocket-streamed-transactions");
+        let server = Server::open(
+            ServerConfiguration::new(&database_path)
+                .default_permissions(DefaultPermissions::AllowAll)
+                .payload_limits(
+                    PayloadLimits::default()
+                        .max_transaction_size(16 * 1024)
+                        .max_open_transactions(2),
+                )
+                .with_schema::<BasicSchema>()?,
+        )
+        .await?;
+        server
+            .create_database::<BasicSchema>("tests", false)
+            .await?;
+        tokio::spawn({
+            let server = server.clone();
+            async move {
+                server
+                    .listen_for_websockets_on("localhost:6017", false)
+                    .await?;
+                Result::<(), anyhow::Error>::Ok(())
+            }
+        });
+        // Give the server time to listen
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let client = AsyncClient::build(Url::parse("ws://localhost:6017")?)
+            .with_payload_limits(PayloadLimits::unlimited().transaction_chunk_size(1024))
+            .build()?;
+        let db = client.database::<BasicSchema>("tests").await?;
+
+        // Transactions larger than the chunk size are streamed automatically.
+        let mut transaction = Transaction::new();
+        for _ in 0..8 {
+            transaction.push(Operation::push_serialized::<Basic>(&Basic::new(
+                "a".repeat(600),
+            ))?);
+        }
+        let results = db.apply_transaction(transaction).await?;
+        assert_eq!(results.len(), 8);
+        assert_eq!(Basic::all_async(&db).await?.len(), 8);
+
+        let results = db
+            .apply_streamed_transaction((0..4).map(|_| {
+                Operation::push_serialized::<Basic>(&Basic::new("b".repeat(600))).unwrap()
+            }))
+            .await?;
+        assert_eq!(results.len(), 4);
+        assert_eq!(Basic::all_async(&db).await?.len(), 12);
+
+        // The server enforces its transaction size limit across every chunk,
+        // and none of the operations are applied.
+        let err = db
+            .apply_streamed_transaction((0..32).map(|_| {
+                Operation::push_serialized::<Basic>(&Basic::new("c".repeat(600))).unwrap()
+            }))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            bonsaidb_core::Error::TransactionTooLarge { limit: 16384, .. }
+        ));
+        assert_eq!(Basic::all_async(&db).await?.len(), 12);
+
+        // Each session may only have a limited number of transactions open.
+        let begin = BeginTransaction {
+            database: String::from("tests"),
+        };
+        client.send_api_request(&begin).await?;
+        client.send_api_request(&begin).await?;
+        let err = client.send_api_request(&begin).await.unwrap_err();
+        assert!(matches!(
+            bonsaidb_core::Error::from(err),
+            bonsaidb_core::Error::TooManyOpenTransactions { limit: 2 }
+        ));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "client-encryption")]
+    #[tokio::test]
+    async fn client_encryption() -> anyhow::Result<()> {
+        use bonsaidb::client::ClientEncryptionKey;
+        use bonsaidb::core::schema::Collection;
+        use bonsaidb::core::sealed::{BlindIndex, BlindValue, SealedCollection};
+        use bonsaidb_core::connection::{AsyncConnection, AsyncStorageConnection};
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Debug, Serialize, Deserialize, Collection)]
+        #[collection(name = "notes", sealed)]
+        struct Note {
+            tag: String,
+            body: String,
+        }
+
+        impl SealedCollection for Note {
+            fn blind_index(&self) -> Vec<BlindValue> {
+                vec![BlindValue::new("tag", self.tag.as_bytes())]
+            }
+        }
+
+        let database_path = TestDirectory::new("websocket-client-encryption");
+        let server = Server::open(
+            ServerConfiguration::new(&database_path)
+                .default_permissions(DefaultPermissions::AllowAll)
+                .with_schema::<Note>()?,
+        )
+        .await?;
+        server.create_database::<Note>("notes", false).await?;
+        tokio::spawn({
+            let server = server.clone();
+            async move {
+                server
+                    .listen_for_websockets_on("localhost:6016", false)
+                    .await?;
+                Result::<(), anyhow::Error>::Ok(())
+            }
+        });
+        // Give the server time to listen
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let client = AsyncClient::new(Url::parse("ws://localhost:6016")?)?;
+        let db = client.database::<Note>("notes").await?;
+        let key = ClientEncryptionKey::from_secret(&[42; 32]);
+        for (tag, body) in [("work", "a secret plan"), ("home", "groceries")] {
+            let note = Note {
+                tag: String::from(tag),
+                body: String::from(body),
+            };
+            Note::push_async(key.seal(&note)?, &db).await?;
+        }
+
+        // The server only stores ciphertext.
+        let server_db = server.database::<Note>("notes").await?;
+        for doc in Note::all_async(&server_db).await? {
+            assert!(!doc
+                .contents
+                .ciphertext
+                .windows(6)
+                .any(|window| window == b"secret"));
+        }
+
+        let work = db
+            .view::<BlindIndex<Note>>()
+            .with_key(&key.blind_key(&BlindValue::new("tag", "work")))
+            .query_with_collection_docs()
+            .await?;
+        assert_eq!(work.len(), 1);
+        let note = key.open::<Note>(&work.get(0).unwrap().document.contents)?;
+        assert_eq!(note.body, "a secret plan");
+
+        // A different key can neither find nor decrypt the notes.
+        let other_key = ClientEncryptionKey::from_secret(&[7; 32]);
+        let found = db
+            .view::<BlindIndex<Note>>()
+            .with_key(&other_key.blind_key(&BlindValue::new("tag", "work")))
+            .query()
+            .await?;
+        assert!(found.is_empty());
+        assert!(other_key
+            .open::<Note>(&work.get(0).unwrap().document.contents)
+            .is_err());
+
+        Ok(())
+    }
+
     bonsaidb_core::define_blocking_connection_test_suite!(BlockingWebsocketTestHarness);
 
     bonsaidb_core::define_blocking_pubsub_test_suite!(BlockingWebsocketTestHarness);
@@ -334,6 +1021,7 @@ async fn assume_permissions(
             let administrator_group_id = match (PermissionGroup {
                 name: String::from(label),
                 statements,
+                version: 0,
             }
             .push_into_async(&admin)
             .await)
@@ -425,3 +1113,522 @@ async fn authenticated_permissions_test() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn session_revocation_test() -> anyhow::Result<()> {
+    use bonsaidb_core::connection::{AsyncStorageConnection, HasSession};
+    let database_path = TestDirectory::new("session-revocation");
+    let server = Server::open(
+        ServerConfiguration::new(&database_path)
+            .default_permissions(Permissions::from(
+                Statement::for_any()
+                    .allowing(&BonsaiAction::Server(ServerAction::Connect))
+                    .allowing(&BonsaiAction::Server(ServerAction::Authenticate(
+                        AuthenticationMethod::PasswordHash,
+                    ))),
+            ))
+            .authenticated_permissions(DefaultPermissions::AllowAll),
+    )
+    .await?;
+    server.install_self_signed_certificate(false).await?;
+    let certificate = server
+        .certificate_chain()
+        .await?
+        .into_end_entity_certificate();
+
+    server.create_user("ecton").await?;
+    server
+        .set_user_password("ecton", SensitiveString::from("hunter2"))
+        .await?;
+    tokio::spawn({
+        let server = server.clone();
+        async move {
+            server.listen_on(6003).await?;
+            Result::<(), anyhow::Error>::Ok(())
+        }
+    });
+    // Give the server time to listen
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    let url = Url::parse("bonsaidb://localhost:6003")?;
+    let client = AsyncClient::build(url)
+        .with_certificate(certificate)
+        .build()?;
+
+    let authenticated_client = client
+        .authenticate_with_password("ecton", SensitiveString::from("hunter2"))
+        .await?;
+    authenticated_client.list_databases().await?;
+
+    let sessions = server.list_sessions("ecton").await?;
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(
+        Some(sessions[0].id),
+        authenticated_client.session().unwrap().id
+    );
+
+    // Once revoked, requests using the session must be rejected.
+    server.revoke_session(sessions[0].id).await?;
+    assert!(matches!(
+        authenticated_client.list_databases().await,
+        Err(bonsaidb_core::Error::SessionExpired)
+    ));
+    assert!(server.list_sessions("ecton").await?.is_empty());
+
+    // Logging out revokes the current session.
+    let authenticated_client = client
+        .authenticate_with_password("ecton", SensitiveString::from("hunter2"))
+        .await?;
+    assert_eq!(server.list_sessions("ecton").await?.len(), 1);
+    authenticated_client.logout().await?;
+    assert!(server.list_sessions("ecton").await?.is_empty());
+    assert!(matches!(
+        authenticated_client.list_databases().await,
+        Err(bonsaidb_core::Error::SessionExpired)
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn session_expiration_test() -> anyhow::Result<()> {
+    use bonsaidb::core::pubsub::AsyncPubSub;
+    use bonsaidb::core::test_util::Basic;
+    use bonsaidb_core::connection::AsyncStorageConnection;
+
+    let database_path = TestDirectory::new("session-expiration");
+    let server = Server::open(
+        ServerConfiguration::new(&database_path)
+            .default_permissions(Permissions::from(
+                Statement::for_any()
+                    .allowing(&BonsaiAction::Server(ServerAction::Connect))
+                    .allowing(&BonsaiAction::Server(ServerAction::Authenticate(
+                        AuthenticationMethod::PasswordHash,
+                    ))),
+            ))
+            .authenticated_permissions(DefaultPermissions::AllowAll)
+            .session_idle_timeout(Duration::from_millis(500))
+            .with_schema::<BasicSchema>()?,
+    )
+    .await?;
+    server.install_self_signed_certificate(false).await?;
+    let certificate = server
+        .certificate_chain()
+        .await?
+        .into_end_entity_certificate();
+    server
+        .create_database::<BasicSchema>("tests", false)
+        .await?;
+    server.create_user("ecton").await?;
+    server
+        .set_user_password("ecton", SensitiveString::from("hunter2"))
+        .await?;
+    tokio::spawn({
+        let server = server.clone();
+        async move {
+            server.listen_on(6018).await?;
+            Result::<(), anyhow::Error>::Ok(())
+        }
+    });
+    #[cfg(feature = "websockets")]
+    tokio::spawn({
+        let server = server.clone();
+        async move {
+            server
+                .listen_for_websockets_on("localhost:6019", false)
+                .await?;
+            Result::<(), anyhow::Error>::Ok(())
+        }
+    });
+    // Give the server time to listen
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    let mut clients = vec![AsyncClient::build(Url::parse("bonsaidb://localhost:6018")?)
+        .with_certificate(certificate)
+        .build()?];
+    #[cfg(feature = "websockets")]
+    clients.push(AsyncClient::new(Url::parse("ws://localhost:6019")?)?);
+
+    for client in clients {
+        let authenticated_client = client
+            .authenticate_with_password("ecton", SensitiveString::from("hunter2"))
+            .await?;
+        let db = authenticated_client
+            .database::<BasicSchema>("tests")
+            .await?;
+        let document = Basic::new("expiring").push_into_async(&db).await?;
+
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        // Every kind of request made using the expired session is rejected,
+        // including requests that don't access a database.
+        assert!(matches!(
+            authenticated_client.list_databases().await,
+            Err(bonsaidb_core::Error::SessionExpired)
+        ));
+        assert!(matches!(
+            Basic::get_async(&document.header.id, &db).await,
+            Err(bonsaidb_core::Error::SessionExpired)
+        ));
+        assert!(matches!(
+            db.set_key("key", &0_u32).await,
+            Err(bonsaidb_core::Error::SessionExpired)
+        ));
+        assert!(matches!(
+            db.publish(&"topic", &0_u32).await,
+            Err(bonsaidb_core::Error::SessionExpired)
+        ));
+        let mut batch = authenticated_client.batch();
+        let _get = batch.get::<Basic, _>(&db, &document.header.id)?;
+        assert!(matches!(
+            batch.execute().await.map_err(bonsaidb_core::Error::from),
+            Err(bonsaidb_core::Error::SessionExpired)
+        ));
+        assert!(server.list_sessions("ecton").await?.is_empty());
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn quic_idle_timeout_test() -> anyhow::Result<()> {
+    use bonsaidb::server::BonsaiListenConfig;
+    use bonsaidb_core::connection::AsyncStorageConnection;
+
+    async fn wait_for_clients(server: &Server, expected: usize) -> anyhow::Result<()> {
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        while server.connected_clients().len() != expected {
+            anyhow::ensure!(
+                tokio::time::Instant::now() < deadline,
+                "expected {expected} connected clients"
+            );
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        Ok(())
+    }
+
+    let database_path = TestDirectory::new("quic-idle-timeout");
+    let server = Server::open(
+        ServerConfiguration::new(&database_path)
+            .default_permissions(DefaultPermissions::AllowAll)
+            .with_schema::<BasicSchema>()?,
+    )
+    .await?;
+    server.install_self_signed_certificate(false).await?;
+    let certificate = server
+        .certificate_chain()
+        .await?
+        .into_end_entity_certificate();
+    // The timeout can be requested by either side of the connection.
+    tokio::spawn({
+        let server = server.clone();
+        async move {
+            server
+                .listen_on(BonsaiListenConfig::from(6031).idle_timeout(Duration::from_millis(250)))
+                .await?;
+            Result::<(), anyhow::Error>::Ok(())
+        }
+    });
+    tokio::spawn({
+        let server = server.clone();
+        async move {
+            server.listen_on(6032).await?;
+            Result::<(), anyhow::Error>::Ok(())
+        }
+    });
+    // Give the server time to listen
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    let clients = [
+        AsyncClient::build(Url::parse("bonsaidb://localhost:6031")?)
+            .with_certificate(certificate.clone())
+            .build()?,
+        AsyncClient::build(Url::parse("bonsaidb://localhost:6032")?)
+            .with_certificate(certificate)
+            .with_quic_idle_timeout(Duration::from_millis(250))
+            .build()?,
+    ];
+    for client in clients {
+        client.list_databases().await?;
+        wait_for_clients(&server, 1).await?;
+
+        // Once the connection is idle for the timeout, it is closed.
+        wait_for_clients(&server, 0).await?;
+
+        // The client reconnects for its next request.
+        client.list_databases().await?;
+        wait_for_clients(&server, 1).await?;
+        drop(client);
+        wait_for_clients(&server, 0).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn disabled_user_disconnect_test() -> anyhow::Result<()> {
+    use bonsaidb_core::connection::AsyncStorageConnection;
+
+    let database_path = TestDirectory::new("disabled-user-disconnect");
+    let server = Server::open(
+        ServerConfiguration::new(&database_path)
+            .default_permissions(Permissions::from(
+                Statement::for_any()
+                    .allowing(&BonsaiAction::Server(ServerAction::Connect))
+                    .allowing(&BonsaiAction::Server(ServerAction::Authenticate(
+                        AuthenticationMethod::PasswordHash,
+                    ))),
+            ))
+            .authenticated_permissions(DefaultPermissions::AllowAll),
+    )
+    .await?;
+    server.install_self_signed_certificate(false).await?;
+    let certificate = server
+        .certificate_chain()
+        .await?
+        .into_end_entity_certificate();
+    let user_id = server.create_user("ecton").await?;
+    server
+        .set_user_password("ecton", SensitiveString::from("hunter2"))
+        .await?;
+    tokio::spawn({
+        let server = server.clone();
+        async move {
+            server.listen_on(6020).await?;
+            Result::<(), anyhow::Error>::Ok(())
+        }
+    });
+    #[cfg(feature = "websockets")]
+    tokio::spawn({
+        let server = server.clone();
+        async move {
+            server
+                .listen_for_websockets_on("localhost:6021", false)
+                .await?;
+            Result::<(), anyhow::Error>::Ok(())
+        }
+    });
+    // Give the server time to listen
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    let mut clients = vec![AsyncClient::build(Url::parse("bonsaidb://localhost:6020")?)
+        .with_certificate(certificate)
+        .build()?];
+    #[cfg(feature = "websockets")]
+    clients.push(AsyncClient::new(Url::parse("ws://localhost:6021")?)?);
+
+    for client in clients {
+        server.enable_user("ecton").await?;
+        let authenticated_client = client
+            .authenticate_with_password("ecton", SensitiveString::from("hunter2"))
+            .await?;
+        authenticated_client.list_databases().await?;
+        assert!(server
+            .connected_clients()
+            .iter()
+            .any(|client| client.authenticated_users().contains(&user_id)));
+
+        server.disable_user("ecton").await?;
+
+        // The connected client's cached session no longer exists, and the
+        // client was disconnected.
+        assert!(!server
+            .connected_clients()
+            .iter()
+            .any(|client| client.authenticated_users().contains(&user_id)));
+        assert!(authenticated_client.list_databases().await.is_err());
+        assert!(authenticated_client.list_databases().await.is_err());
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn rate_limit_test() -> anyhow::Result<()> {
+    use bonsaidb::server::RateLimit;
+    use bonsaidb_core::connection::AsyncStorageConnection;
+    let database_path = TestDirectory::new("rate-limit");
+    let server = Server::open(
+        ServerConfiguration::new(&database_path)
+            .default_permissions(Permissions::from(
+                Statement::for_any()
+                    .allowing(&BonsaiAction::Server(ServerAction::Connect))
+                    .allowing(&BonsaiAction::Server(ServerAction::Authenticate(
+                        AuthenticationMethod::PasswordHash,
+                    ))),
+            ))
+            .authenticated_permissions(DefaultPermissions::AllowAll)
+            .user_rate_limit(RateLimit::default().requests_per_second(1)),
+    )
+    .await?;
+    server.install_self_signed_certificate(false).await?;
+    let certificate = server
+        .certificate_chain()
+        .await?
+        .into_end_entity_certificate();
+
+    server.create_user("ecton").await?;
+    server
+        .set_user_password("ecton", SensitiveString::from("hunter2"))
+        .await?;
+    tokio::spawn({
+        let server = server.clone();
+        async move {
+            server.listen_on(6004).await?;
+            Result::<(), anyhow::Error>::Ok(())
+        }
+    });
+    // Give the server time to listen
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    let url = Url::parse("bonsaidb://localhost:6004")?;
+    let client = AsyncClient::build(url)
+        .with_certificate(certificate)
+        .build()?;
+
+    let authenticated_client = client
+        .authenticate_with_password("ecton", SensitiveString::from("hunter2"))
+        .await?;
+    authenticated_client.list_databases().await?;
+    let Err(bonsaidb_core::Error::RateLimited {
+        retry_after: Some(retry_after),
+    }) = authenticated_client.list_databases().await
+    else {
+        unreachable!("request not limited")
+    };
+
+    // Once the limit has been replenished, requests are accepted again.
+    tokio::time::sleep(retry_after + Duration::from_millis(10)).await;
+    authenticated_client.list_databases().await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn connection_limit_test() -> anyhow::Result<()> {
+    use bonsaidb_core::connection::AsyncStorageConnection;
+
+    let database_path = TestDirectory::new("connection-limit");
+    let server = Server::open(
+        ServerConfiguration::new(&database_path)
+            .default_permissions(Permissions::from(
+                Statement::for_any()
+                    .allowing(&BonsaiAction::Server(ServerAction::Connect))
+                    .allowing(&BonsaiAction::Server(ServerAction::Authenticate(
+                        AuthenticationMethod::PasswordHash,
+                    ))),
+            ))
+            .authenticated_permissions(DefaultPermissions::AllowAll)
+            .max_connections(2)
+            .max_connections_per_user(1),
+    )
+    .await?;
+    server.install_self_signed_certificate(false).await?;
+    let certificate = server
+        .certificate_chain()
+        .await?
+        .into_end_entity_certificate();
+
+    let user_id = server.create_user("ecton").await?;
+    server
+        .set_user_password("ecton", SensitiveString::from("hunter2"))
+        .await?;
+    tokio::spawn({
+        let server = server.clone();
+        async move {
+            server.listen_on(6005).await?;
+            Result::<(), anyhow::Error>::Ok(())
+        }
+    });
+    // Give the server time to listen
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    let url = Url::parse("bonsaidb://localhost:6005")?;
+    let connect = || {
+        AsyncClient::build(url.clone())
+            .with_certificate(certificate.clone())
+            .build()
+    };
+
+    let first = connect()?
+        .authenticate_with_password("ecton", SensitiveString::from("hunter2"))
+        .await?;
+    first.list_databases().await?;
+
+    // The second connection is allowed, but it can't authenticate as the same
+    // user.
+    let second = connect()?;
+    assert!(matches!(
+        second
+            .authenticate_with_password("ecton", SensitiveString::from("hunter2"))
+            .await,
+        Err(bonsaidb_core::Error::ConnectionLimitReached)
+    ));
+
+    // The third connection exceeds the total connection limit.
+    let third = connect()?;
+    assert!(matches!(
+        third
+            .authenticate_with_password("ecton", SensitiveString::from("hunter2"))
+            .await,
+        Err(bonsaidb_core::Error::ConnectionLimitReached)
+    ));
+
+    let usage = server.connection_usage();
+    assert_eq!(usage.connections, 2);
+    assert_eq!(usage.max_connections, Some(2));
+    assert_eq!(usage.connections_by_user.get(&user_id), Some(&1));
+    assert_eq!(usage.max_connections_per_user, Some(1));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn graceful_shutdown_test() -> anyhow::Result<()> {
+    use bonsaidb::client::ApiCallback;
+    use bonsaidb::core::networking::ShuttingDown;
+    use bonsaidb_core::connection::AsyncStorageConnection;
+
+    let database_path = TestDirectory::new("graceful-shutdown");
+    let server = Server::open(
+        ServerConfiguration::new(&database_path).default_permissions(DefaultPermissions::AllowAll),
+    )
+    .await?;
+    server.install_self_signed_certificate(false).await?;
+    let certificate = server
+        .certificate_chain()
+        .await?
+        .into_end_entity_certificate();
+
+    let listener = tokio::spawn({
+        let server = server.clone();
+        async move { server.listen_on(6008).await }
+    });
+    // Give the server time to listen
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    let (notice_sender, notice_receiver) = flume::bounded(1);
+    let client = AsyncClient::build(Url::parse("bonsaidb://localhost:6008")?)
+        .with_certificate(certificate)
+        .with_api_callback(ApiCallback::<ShuttingDown>::new(
+            move |notice: ShuttingDown| {
+                let notice_sender = notice_sender.clone();
+                async move {
+                    drop(notice_sender.send(notice));
+                }
+            },
+        ))
+        .build()?;
+    client.list_databases().await?;
+
+    server.shutdown(Some(Duration::from_secs(5))).await?;
+
+    let notice =
+        tokio::time::timeout(Duration::from_secs(5), notice_receiver.recv_async()).await??;
+    assert_eq!(notice.grace_period, Duration::from_secs(5));
+
+    // The listener stops accepting connections once shutdown begins.
+    tokio::time::timeout(Duration::from_secs(5), listener).await???;
+
+    Ok(())
+}